@@ -0,0 +1,156 @@
+// =============================================================================
+// ECSのベンチマーク
+// =============================================================================
+// このファイルでは、自作ECSの基本操作の速度を測定します。
+// ECSの内部構造を変更した際に、性能が落ちていないかを確認するために使います。
+//
+// 測定項目：
+// - クエリの反復（1,000 / 10,000エンティティ）
+// - コンポーネントの追加・削除の繰り返し
+// - エンティティの生成・削除の繰り返し
+//
+// 実行方法：cargo bench --bench ecs
+// =============================================================================
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ecs_wasm_solitaire::ecs::{Component, World};
+
+/// ベンチマーク用の位置コンポーネント
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+impl Component for Position {}
+
+/// ベンチマーク用の速度コンポーネント
+#[derive(Debug, Clone, Copy)]
+struct Velocity {
+    dx: f32,
+    dy: f32,
+}
+
+impl Component for Velocity {}
+
+/// 指定数のエンティティ（位置と速度を持つ）を生成したワールドを作成
+fn populated_world(entity_count: usize) -> World {
+    let mut world = World::new();
+    let entities = world.spawn_batch((0..entity_count).map(|i| Position {
+        x: i as f32,
+        y: 0.0,
+    }));
+    world.add_components_batch(
+        entities
+            .into_iter()
+            .map(|entity| (entity, Velocity { dx: 1.0, dy: 0.5 })),
+    );
+    world
+}
+
+/// クエリの反復（読み取り・書き込み）
+fn bench_query(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query");
+
+    for entity_count in [1_000, 10_000] {
+        let mut world = populated_world(entity_count);
+
+        group.bench_with_input(
+            BenchmarkId::new("read", entity_count),
+            &entity_count,
+            |b, _| {
+                b.iter(|| {
+                    let sum: f32 = world
+                        .query::<Position>()
+                        .map(|(_, pos)| pos.x + pos.y)
+                        .sum();
+                    black_box(sum)
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("write", entity_count),
+            &entity_count,
+            |b, _| {
+                b.iter(|| {
+                    for (_, pos) in world.query_mut::<Position>() {
+                        pos.x += 1.0;
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("join", entity_count),
+            &entity_count,
+            |b, _| {
+                b.iter(|| {
+                    // 位置と速度の両方を持つエンティティを処理する（システムでよくある形）
+                    let moved: Vec<_> = world
+                        .query::<Velocity>()
+                        .filter_map(|(entity, vel)| {
+                            world
+                                .get_component::<Position>(entity)
+                                .map(|pos| (entity, pos.x + vel.dx, pos.y + vel.dy))
+                        })
+                        .collect();
+                    black_box(moved)
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// コンポーネントの追加・削除の繰り返し
+fn bench_component_churn(c: &mut Criterion) {
+    let mut world = populated_world(1_000);
+    let entities = world.entities().to_vec();
+
+    c.bench_function("component_churn/add_remove_1000", |b| {
+        b.iter(|| {
+            for &entity in &entities {
+                world.remove_component::<Velocity>(entity);
+            }
+            for &entity in &entities {
+                world.add_component(entity, Velocity { dx: 1.0, dy: 0.5 });
+            }
+        })
+    });
+}
+
+/// エンティティの生成・削除の繰り返し（メッセージエンティティの使い方を想定）
+fn bench_entity_churn(c: &mut Criterion) {
+    let mut world = populated_world(1_000);
+
+    c.bench_function("entity_churn/spawn_remove_100", |b| {
+        b.iter(|| {
+            let spawned: Vec<_> = (0..100)
+                .map(|i| {
+                    let entity = world.create_entity();
+                    world.add_component(
+                        entity,
+                        Position {
+                            x: i as f32,
+                            y: 0.0,
+                        },
+                    );
+                    entity
+                })
+                .collect();
+            for entity in spawned {
+                world.remove_entity(entity);
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_query,
+    bench_component_churn,
+    bench_entity_churn
+);
+criterion_main!(benches);
@@ -0,0 +1,109 @@
+// =============================================================================
+// ゲームルールのベンチマーク
+// =============================================================================
+// このファイルでは、ソリティアのゲームロジックのうち、頻繁に呼ばれる処理の
+// 速度を測定します。
+//
+// 測定項目：
+// - クロンダイクの配布（ゲーム開始）
+// - カード移動の判定
+// - ヒントの計算
+// - クライアント向け状態の作成とJSONへのシリアライズ
+//
+// 実行方法：cargo bench --bench rules
+// =============================================================================
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ecs_wasm_solitaire::client_state::ClientState;
+use ecs_wasm_solitaire::ecs::{Entity, World};
+use ecs_wasm_solitaire::hint::HintEngine;
+use ecs_wasm_solitaire::solitaire::{SolitaireCard, SolitaireManager, SolitaireType};
+
+/// 毎回同じ盤面で測定するためのシード値
+const SEED: u64 = 20250727;
+
+/// 配布済みのクロンダイクのワールドを作成
+fn dealt_world() -> (World, Entity) {
+    let mut world = World::new();
+    let game_entity =
+        SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, SEED);
+    (world, game_entity)
+}
+
+/// クロンダイクの配布
+fn bench_deal(c: &mut Criterion) {
+    c.bench_function("deal/klondike", |b| {
+        b.iter(|| {
+            let mut world = World::new();
+            black_box(SolitaireManager::start_new_game_with_seed(
+                &mut world,
+                SolitaireType::Klondike,
+                black_box(SEED),
+            ))
+        })
+    });
+}
+
+/// カード移動の判定（全カードの組み合わせ）
+fn bench_move_validation(c: &mut Criterion) {
+    let (world, _) = dealt_world();
+    let cards: Vec<SolitaireCard> = world
+        .query::<SolitaireCard>()
+        .map(|(_, card)| card.clone())
+        .collect();
+
+    c.bench_function("move_validation/all_pairs", |b| {
+        b.iter(|| {
+            let mut legal = 0;
+            for moving in &cards {
+                for target in &cards {
+                    if moving.can_place_on_tableau(target) {
+                        legal += 1;
+                    }
+                    if moving.can_place_on_foundation(Some(target)) {
+                        legal += 1;
+                    }
+                }
+            }
+            black_box(legal)
+        })
+    });
+}
+
+/// ヒントの計算
+fn bench_hints(c: &mut Criterion) {
+    let (world, _) = dealt_world();
+
+    c.bench_function("hint/all_moves", |b| {
+        b.iter(|| black_box(HintEngine::all_moves(&world)))
+    });
+
+    c.bench_function("hint/find_hint", |b| {
+        b.iter(|| black_box(HintEngine::find_hint(&world)))
+    });
+}
+
+/// クライアント向け状態の作成とシリアライズ
+fn bench_state_serialization(c: &mut Criterion) {
+    let (world, game_entity) = dealt_world();
+
+    c.bench_function("state/from_world", |b| {
+        b.iter(|| black_box(ClientState::from_world(&world, Some(game_entity))))
+    });
+
+    c.bench_function("state/to_json", |b| {
+        b.iter(|| {
+            let state = ClientState::from_world(&world, Some(game_entity));
+            black_box(serde_json::to_string(&state).unwrap_or_default())
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_deal,
+    bench_move_validation,
+    bench_hints,
+    bench_state_serialization
+);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DailyResult } from "./DailyResult";
+
+/**
+ * 過去の日替わりの配り札とその日のリーダーボード（クライアント送信用）
+ */
+export type ArchivedDailyDeal = { 
+/**
+ * UNIXエポックからの日数（UTC）
+ */
+day: number, 
+/**
+ * 配り札のシード
+ */
+seed: number, 
+/**
+ * その日の結果（順位順）
+ */
+results: Array<DailyResult>, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StackLayout } from "./StackLayout";
+
+/**
+ * 盤面全体のレイアウト（クライアント送信用）
+ */
+export type BoardLayout = { 
+/**
+ * カードの幅
+ */
+card_width: number, 
+/**
+ * カードの高さ
+ */
+card_height: number, 
+/**
+ * 山のレイアウト（デッキ・ウェイスト・組札・場札の順）
+ */
+stacks: Array<StackLayout>, };
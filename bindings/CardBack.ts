@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * カードの裏面のデザイン
+ */
+export type CardBack = "classic" | "crimson" | "checkered" | "ocean";
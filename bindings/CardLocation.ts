@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * カードの配置場所（Windowsソリティア準拠）
+ */
+export type CardLocation = "Deck" | "Waste" | "Tableau" | "Foundation" | "FreeCell" | "Hand";
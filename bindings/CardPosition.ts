@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 山の中の1枚のカードの位置（クライアント送信用）
+ */
+export type CardPosition = { 
+/**
+ * カードのエンティティID
+ */
+card_id: number, 
+/**
+ * X座標
+ */
+x: number, 
+/**
+ * Y座標
+ */
+y: number, };
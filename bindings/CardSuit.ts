@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * カードのスート（絵柄）
+ */
+export type CardSuit = "Hearts" | "Diamonds" | "Clubs" | "Spades";
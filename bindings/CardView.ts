@@ -0,0 +1,66 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardRank } from "./CardRank";
+import type { CardSuit } from "./CardSuit";
+import type { SuitColor } from "./SuitColor";
+import type { SuitPattern } from "./SuitPattern";
+
+/**
+ * 1枚のカードの表示情報
+ */
+export type CardView = { 
+/**
+ * カードのエンティティID（移動操作でカードを指定するために使う）
+ */
+id: number, 
+/**
+ * スート（絵柄）
+ */
+suit: CardSuit, 
+/**
+ * ランク（数値・絵札）
+ */
+rank: CardRank, 
+/**
+ * スートの記号・ランクを描く色（テーマの4色のスートの設定に従う）
+ */
+color: SuitColor, 
+/**
+ * カードの表面を塗る模様（テーマで模様を使わない設定の場合はNone）
+ */
+pattern: SuitPattern | null, 
+/**
+ * ランクとスートの記号を描く大きさの倍率（テーマの大きなランク表示の設定に従う）
+ */
+rank_scale: number, 
+/**
+ * 表向きかどうか
+ */
+face_up: boolean, 
+/**
+ * 表示X座標（アニメーション中は途中の座標）
+ */
+x: number, 
+/**
+ * 表示Y座標（アニメーション中は途中の座標）
+ */
+y: number, 
+/**
+ * アニメーション中かどうか
+ */
+animating: boolean, 
+/**
+ * プレイヤーが選択中かどうか
+ */
+selected: boolean, 
+/**
+ * ヒントなどで強調表示中かどうか
+ */
+highlighted: boolean, 
+/**
+ * 操作がないときのヒントの知らせで点滅表示中かどうか
+ */
+pulsing: boolean, 
+/**
+ * 共有盤面で自分が動かせないカードかどうか（観戦中・他のプレイヤーのカード、灰色で表示する）
+ */
+locked: boolean, };
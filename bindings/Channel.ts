@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * メッセージの連番を数えるチャネル
+ *
+ * チャネルごとに1から始まる連番を付け、受信側は抜け・順序の入れ替わりを検出します。
+ */
+export type Channel = "cursor" | "actions" | "chat" | "sync";
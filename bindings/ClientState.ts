@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GamePhase } from "./GamePhase";
+import type { OptionsView } from "./OptionsView";
+import type { PilesView } from "./PilesView";
+import type { ScoreView } from "./ScoreView";
+import type { Theme } from "./Theme";
+import type { TutorialView } from "./TutorialView";
+import type { Viewport } from "./Viewport";
+
+/**
+ * クライアント向けのゲーム状態全体
+ *
+ * get_solitaire_state()はこの型をJSONにしたものを返します。
+ */
+export type ClientState = { 
+/**
+ * このJSONの形式のバージョン
+ */
+schema_version: number, 
+/**
+ * 進行段階
+ */
+phase: GamePhase, 
+/**
+ * ゲーム設定
+ */
+options: OptionsView, 
+/**
+ * スコアと統計情報
+ */
+score: ScoreView, 
+/**
+ * 盤面
+ */
+piles: PilesView, 
+/**
+ * チュートリアルの進み具合（チュートリアル中でない場合はNone）
+ */
+tutorial: TutorialView | null, 
+/**
+ * カードの裏面・テーブルの描画に使うテーマ
+ */
+theme: Theme, 
+/**
+ * 盤面を描画するときの拡大率と表示位置（ポインターの座標の変換と同じ）
+ */
+viewport: Viewport, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 再現する通信状態の設定
+ */
+export type ConditionerSettings = { 
+/**
+ * 遅延（ミリ秒）
+ */
+latency_ms: number, 
+/**
+ * 遅延の揺らぎ（ミリ秒、遅延の前後にこの幅で均等にばらつく）
+ */
+jitter_ms: number, 
+/**
+ * 次のメッセージと順序を入れ替える割合（0.0〜1.0）
+ */
+reorder_rate: number, 
+/**
+ * メッセージを欠落させる割合（0.0〜1.0）
+ */
+drop_rate: number, 
+/**
+ * 乱数のシード（同じシードなら同じ結果になる）
+ */
+seed: number, };
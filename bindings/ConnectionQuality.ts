@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 接続品質
+ */
+export type ConnectionQuality = "good" | "fair" | "poor" | "unknown" | "offline";
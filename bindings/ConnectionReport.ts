@@ -0,0 +1,36 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConnectionQuality } from "./ConnectionQuality";
+import type { QueueMetrics } from "./QueueMetrics";
+
+/**
+ * 接続状態の報告（get_connection_status()の戻り値）
+ */
+export type ConnectionReport = { 
+/**
+ * 接続しているかどうか
+ */
+connected: boolean, 
+/**
+ * 接続品質
+ */
+quality: ConnectionQuality, 
+/**
+ * 平滑化した往復時間（ミリ秒、測定前はNone）
+ */
+rtt_ms: number | null, 
+/**
+ * 直近のPingのうち応答がなかった割合（0.0〜1.0）
+ */
+packet_loss: number, 
+/**
+ * 送信待ちのキューの溜まり具合（セッションがない場合は空のキューの値）
+ */
+queue: QueueMetrics, 
+/**
+ * カーソル位置を送る間隔（ミリ秒）
+ */
+cursor_interval_ms: number, 
+/**
+ * 状態同期のキーフレーム（変わっていなくても送り直す自分のスコア）の間隔（ミリ秒）
+ */
+keyframe_interval_ms: number, };
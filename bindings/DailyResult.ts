@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 過去の日替わりの配り札の結果1件分（クライアント送信用）
+ */
+export type DailyResult = { 
+/**
+ * 順位（1始まり）
+ */
+rank: number, 
+/**
+ * プレイヤー名
+ */
+player_name: string, 
+/**
+ * 最終スコア
+ */
+score: number, 
+/**
+ * プレイ時間（秒）
+ */
+duration_seconds: number, 
+/**
+ * 勝利したかどうか
+ */
+won: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 配り札の難しさ（ソルバーが勝ち筋を見つけるまでに調べた局面数から決める）
+ */
+export type Difficulty = "easy" | "medium" | "hard";
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * クイックリアクションで送れる絵文字
+ *
+ * 決められた絵文字だけを受け付けるため、任意の文字列は送れません。
+ */
+export type Emote = "thumbs_up" | "surprised" | "snail" | "party";
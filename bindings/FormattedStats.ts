@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 状態の出力に加える、言語に合わせて整形した値
+ */
+export type FormattedStats = { 
+/**
+ * 経過時間（"mm:ss"、1時間以上は"h:mm:ss"）
+ */
+elapsed: string, 
+/**
+ * スコア（言語に合わせて桁を区切った数値）
+ */
+score: string, 
+/**
+ * 整形に使った言語（BCP 47の言語タグ）
+ */
+locale: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PresenceStatus } from "./PresenceStatus";
+
+/**
+ * フレンド1人分の情報（クライアント送信用）
+ */
+export type FriendStatus = { 
+/**
+ * プレイヤー名
+ */
+player_name: string, 
+/**
+ * 接続中のプレイヤーID（オフライン・相手がこちらをフレンドにしていない場合はNone）
+ */
+player_id: string | null, 
+/**
+ * 在席状況（相手がこちらをフレンドにしていない場合は常にOffline）
+ */
+status: PresenceStatus, 
+/**
+ * 参加中のルーム（ルームにいない・パスワード付きのルームにいる場合はNone）
+ */
+room_id: string | null, };
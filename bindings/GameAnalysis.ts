@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MoveAnalysis } from "./MoveAnalysis";
+
+/**
+ * ゲーム全体の振り返り
+ */
+export type GameAnalysis = { 
+/**
+ * 各手の振り返り（古い順）
+ */
+moves: Array<MoveAnalysis>, 
+/**
+ * 最善手の数
+ */
+best: number, 
+/**
+ * 良い手の数
+ */
+good: number, 
+/**
+ * 疑問手の数
+ */
+inaccuracies: number, 
+/**
+ * 悪手の数
+ */
+blunders: number, };
@@ -0,0 +1,174 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationSeverity } from "./NotificationSeverity";
+
+/**
+ * ゲームイベント
+ *
+ * JavaScript側へ通知するイベントの種類を定義します。
+ * JSONでは`{"type": "achievement_unlocked", ...}`の形式になります。
+ */
+export type GameEvent = { "type": "achievement_unlocked", 
+/**
+ * 実績ID
+ */
+id: string, 
+/**
+ * 実績名
+ */
+name: string, 
+/**
+ * 実績の説明
+ */
+description: string, } | { "type": "turn_time_warning", 
+/**
+ * ターン中のプレイヤーのエンティティID
+ */
+player: number, 
+/**
+ * ターン番号
+ */
+turn_number: number, 
+/**
+ * 残り時間（秒、持ち時間を含む）
+ */
+remaining_seconds: number, } | { "type": "turn_timed_out", 
+/**
+ * 時間切れになったプレイヤーのエンティティID
+ */
+player: number, 
+/**
+ * 時間切れになったターンの番号
+ */
+turn_number: number, 
+/**
+ * 代わりに実行した操作（"skip_turn" / "auto_draw" / "pass"）
+ */
+auto_action: string, } | { "type": "action_rejected", 
+/**
+ * アクションを送ったプレイヤーのエンティティID
+ */
+player: number, 
+/**
+ * 拒否されたアクションの種類（"move_card"など）
+ */
+action: string, 
+/**
+ * 拒否の理由
+ */
+reason: string, } | { "type": "puzzle_solved", 
+/**
+ * パズルID
+ */
+id: string, 
+/**
+ * 使った手数
+ */
+moves_used: number, } | { "type": "puzzle_failed", 
+/**
+ * パズルID
+ */
+id: string, 
+/**
+ * 使った手数
+ */
+moves_used: number, } | { "type": "tutorial_completed", 
+/**
+ * チュートリアルID
+ */
+id: string, } | { "type": "score_changed", 
+/**
+ * 新しいスコア
+ */
+score: number, 
+/**
+ * 変わる前のスコア
+ */
+previous: number, } | { "type": "moves_changed", 
+/**
+ * 新しい手数
+ */
+move_count: number, } | { "type": "timer_tick", 
+/**
+ * 経過時間（秒）
+ */
+elapsed_seconds: number, } | { "type": "card_revealed", 
+/**
+ * 表向きになったカードのエンティティID
+ */
+card_id: number, 
+/**
+ * カードのあるタブローの列番号（0から開始）
+ */
+column: number, 
+/**
+ * 獲得したポイント
+ */
+points: number, } | { "type": "deck_recycled", 
+/**
+ * デッキを戻した回数
+ */
+deck_turns: number, } | { "type": "hint_nudge", 
+/**
+ * 動かすカードのエンティティID（デッキから引く手の場合はNone）
+ */
+card_id: number | null, 
+/**
+ * 表示用メッセージ
+ */
+message: string, 
+/**
+ * 最後の操作からの経過時間（秒）
+ */
+idle_seconds: number, } | { "type": "state_reconciled", 
+/**
+ * 正しい盤面のスコア
+ */
+score: number, 
+/**
+ * 合わせ直す前の手元のスコア（差は移動の得点ではない）
+ */
+previous_score: number, 
+/**
+ * 位置を直したカードの枚数
+ */
+cards_moved: number, } | { "type": "clock_jump_detected", 
+/**
+ * 前のフレームからの経過時間（ミリ秒）
+ */
+gap_ms: number, 
+/**
+ * 進めずに捨てた経過時間（ミリ秒）
+ */
+dropped_ms: number, } | { "type": "animation_stalled", 
+/**
+ * 完了させたカードのエンティティID
+ */
+card_id: number, 
+/**
+ * 移動先に近づかなかったフレーム数
+ */
+stalled_frames: number, } | { "type": "search_progress", 
+/**
+ * 探索の種類（"winnability"：勝ち筋の確認）
+ */
+search: string, 
+/**
+ * 進み具合（0.0〜1.0）
+ */
+progress: number, } | { "type": "connection_changed", 
+/**
+ * 新しい接続状態（"connecting" / "connected" / "disconnected" / "reconnecting" / "error" / "closed"）
+ */
+status: string, } | { "type": "notification", 
+/**
+ * 重要度（表示の色やアイコンに使う）
+ */
+severity: NotificationSeverity, 
+/**
+ * 表示する文章
+ */
+message: string, 
+/**
+ * 自動で閉じるまでの時間（ミリ秒、Noneの場合はプレイヤーが閉じるまで表示）
+ */
+auto_dismiss_ms: number | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * ゲームの進行段階
+ */
+export type GamePhase = "not_started" | "playing" | "won" | "lost";
@@ -0,0 +1,70 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormattedStats } from "./FormattedStats";
+import type { GameOutcome } from "./GameOutcome";
+import type { ScoreBreakdown } from "./ScoreBreakdown";
+import type { SolitaireType } from "./SolitaireType";
+
+/**
+ * ゲーム結果レポートコンポーネント
+ *
+ * ゲーム終了時にゲーム状態エンティティへ添付されます。
+ * JavaScriptからは`get_game_result()`でJSONとして取得できます。
+ */
+export type GameResult = { 
+/**
+ * 勝敗
+ */
+outcome: GameOutcome, 
+/**
+ * ゲームの種類
+ */
+game_type: SolitaireType, 
+/**
+ * カード配布に使用したシード値
+ */
+seed: number, 
+/**
+ * スコアの内訳
+ */
+score: ScoreBreakdown, 
+/**
+ * 移動回数
+ */
+move_count: number, 
+/**
+ * デッキをめくった回数
+ */
+deck_turns: number, 
+/**
+ * プレイ時間（秒）
+ */
+duration_seconds: number, 
+/**
+ * ヒントを使用した回数
+ */
+hints_used: number, 
+/**
+ * アンドゥを使用した回数
+ */
+undos_used: number, 
+/**
+ * ソルバーが配られた直後の局面から見つけた勝ち筋の手数
+ * （最短とは限らない、勝ち筋を覚えていない配り札ではNone）
+ */
+solver_optimal_moves: number | null, 
+/**
+ * ソルバーの勝ち筋の手数に対する効率（1.0が最善、勝った場合のみ）
+ */
+efficiency: number | null, 
+/**
+ * 勝ち筋がなくなった手（0は配られた時点、勝ち筋が残っていた場合・分からない場合はNone）
+ */
+lost_at_move: number | null, 
+/**
+ * 結果を作成した時刻（UNIXタイムスタンプ）
+ */
+finished_at: number, 
+/**
+ * 言語に合わせて整形した最終スコア・プレイ時間（get_game_result()で設定が有効な場合のみ）
+ */
+formatted?: FormattedStats | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * ゲーム状態
+ */
+export type GameState = "Waiting" | "Starting" | "Playing" | "Finished";
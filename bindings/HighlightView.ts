@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HintLocation } from "./HintLocation";
+
+/**
+ * チュートリアルで強調表示する対象
+ */
+export type HighlightView = { 
+/**
+ * 動かすカードのエンティティID（デッキから引く手順ではNone）
+ */
+card_id: number | null, 
+/**
+ * 動かす元の山
+ */
+from: HintLocation, 
+/**
+ * 動かす先の山
+ */
+to: HintLocation, };
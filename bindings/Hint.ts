@@ -0,0 +1,41 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HintCard } from "./HintCard";
+import type { HintKind } from "./HintKind";
+import type { HintLocation } from "./HintLocation";
+
+/**
+ * ヒント（次の一手）
+ */
+export type Hint = { 
+/**
+ * ヒントの種類
+ */
+type: HintKind, 
+/**
+ * 移動するカード（タブロー間の移動では一番下のカード）
+ */
+card: HintCard | null, 
+/**
+ * 一緒に移動するカードの枚数
+ */
+card_count: number, 
+/**
+ * 移動元
+ */
+from: HintLocation | null, 
+/**
+ * 移動先
+ */
+to: HintLocation | null, 
+/**
+ * 表示用メッセージ
+ */
+message: string, 
+/**
+ * その手を勧める理由（チュートリアル表示用）
+ */
+reason: string, 
+/**
+ * 優先度（大きいほど良い手、チュートリアルでは手の評価値として表示）
+ */
+priority: number, };
@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardRank } from "./CardRank";
+import type { CardSuit } from "./CardSuit";
+
+/**
+ * 移動するカードの情報（JavaScript向け）
+ */
+export type HintCard = { 
+/**
+ * スート
+ */
+suit: CardSuit, 
+/**
+ * ランク
+ */
+rank: CardRank, };
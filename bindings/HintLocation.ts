@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardLocation } from "./CardLocation";
+
+/**
+ * ヒントが指す場所（JavaScript向け）
+ */
+export type HintLocation = { 
+/**
+ * 場所の種類
+ */
+type: CardLocation, 
+/**
+ * 場所のインデックス（タブローの列番号、ファウンデーション番号など）
+ */
+index: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * ルーム内で行われたアクションの記録（再起動後の盤面の再現用）
+ */
+export type LoggedAction = { player_id: string, action: string, x: number | null, y: number | null, timestamp: number, };
@@ -0,0 +1,43 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardLocation } from "./CardLocation";
+import type { CardRank } from "./CardRank";
+import type { CardSuit } from "./CardSuit";
+import type { MoveQuality } from "./MoveQuality";
+import type { Winnability } from "./Winnability";
+
+/**
+ * 1手分の振り返り
+ */
+export type MoveAnalysis = { 
+/**
+ * 何手目か（移動履歴の順番、1から開始）
+ */
+move_number: number, 
+/**
+ * 動かしたカードのスート
+ */
+suit: CardSuit, 
+/**
+ * 動かしたカードのランク
+ */
+rank: CardRank, 
+/**
+ * 移動元の場所
+ */
+from: CardLocation, 
+/**
+ * 移動先の場所
+ */
+to: CardLocation, 
+/**
+ * 手の評価
+ */
+quality: MoveQuality, 
+/**
+ * 打つ前の局面の勝ち筋の有無
+ */
+before: Winnability, 
+/**
+ * 打った後の局面の勝ち筋の有無
+ */
+after: Winnability, };
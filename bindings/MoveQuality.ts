@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 1手の評価
+ */
+export type MoveQuality = "best" | "good" | "inaccuracy" | "blunder";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 通知の重要度
+ */
+export type NotificationSeverity = "info" | "warning" | "achievement" | "error";
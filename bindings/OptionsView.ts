@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SolitaireType } from "./SolitaireType";
+
+/**
+ * ゲーム設定
+ */
+export type OptionsView = { 
+/**
+ * ゲームの種類
+ */
+game_type: SolitaireType, 
+/**
+ * 1回にデッキから引く枚数
+ */
+draw_count: number, 
+/**
+ * 配り札のシード値（ゲーム開始前はNone）
+ */
+seed: number | null, };
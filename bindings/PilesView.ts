@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardView } from "./CardView";
+import type { HintLocation } from "./HintLocation";
+
+/**
+ * 盤面上のすべての山
+ */
+export type PilesView = { 
+/**
+ * デッキ（山札）の残り枚数（裏向きなので中身は送らない）
+ */
+deck_count: number, 
+/**
+ * ウェイストパイル（捨て札）、下から順（最後が一番上）
+ */
+waste: Array<CardView>, 
+/**
+ * ファウンデーション（組札）4つ、それぞれ下から順
+ */
+foundations: Array<Array<CardView>>, 
+/**
+ * タブロー（場札）7列、それぞれ下から順
+ */
+tableau: Array<Array<CardView>>, 
+/**
+ * 選択中のカードを置ける山（選択していない場合は空）
+ */
+drop_targets: Array<HintLocation>, };
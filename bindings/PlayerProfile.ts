@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * プレイヤーのプロフィール情報（クライアント送信用）
+ */
+export type PlayerProfile = { player_id: string, player_name: string, color_index: number, rating: number, games_rated: number, is_bot: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * パワーアップの種類
+ */
+export type PowerUp = "peek" | "free_shuffle" | "time_penalty";
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 送信待ちのキューの溜まり具合（get_connection_status()のqueue）
+ */
+export type QueueMetrics = { 
+/**
+ * 溜められるメッセージの数の上限
+ */
+capacity: number, 
+/**
+ * いま溜まっているメッセージの数
+ */
+backlog: number, 
+/**
+ * これまでに溜めたメッセージの数
+ */
+queued: number, 
+/**
+ * これまでに捨てたメッセージの数（溜めずに捨てた分と、押し出して捨てた分）
+ */
+dropped: number, 
+/**
+ * 接続し直してから溜まっていた中から送り直したメッセージの数
+ */
+retried: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 他のプレイヤーの接続状態
+ */
+export type RemoteConnection = "connected" | "disconnected";
@@ -0,0 +1,52 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardBack } from "./CardBack";
+import type { RemoteConnection } from "./RemoteConnection";
+
+/**
+ * 同じルームにいる他のプレイヤー
+ */
+export type RemotePlayer = { 
+/**
+ * プレイヤーID
+ */
+player_id: string, 
+/**
+ * 表示名
+ */
+player_name: string, 
+/**
+ * カーソルの色のインデックス
+ */
+color_index: number, 
+/**
+ * 現在のスコア
+ */
+score: number, 
+/**
+ * ファウンデーションに置いたカードの枚数
+ */
+foundation_cards: number, 
+/**
+ * 見せているカードの裏面（見せていない場合はNone）
+ */
+card_back: CardBack | null, 
+/**
+ * 続けてファウンデーションに置いている枚数（途切れている場合は0）
+ */
+combo: number, 
+/**
+ * コンボの倍率
+ */
+multiplier: number, 
+/**
+ * このレースで貯まったコンボのボーナス
+ */
+bonus_score: number, 
+/**
+ * 離席中かどうか
+ */
+afk: boolean, 
+/**
+ * 接続状態
+ */
+connection: RemoteConnection, };
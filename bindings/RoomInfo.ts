@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GameState } from "./GameState";
+import type { PlayerProfile } from "./PlayerProfile";
+
+/**
+ * ルーム情報（クライアント送信用）
+ */
+export type RoomInfo = { id: string, name: string, player_count: number, max_players: number, game_state: GameState, host_id: string | null, has_password: boolean, ready_player_ids: Array<string>, average_rating: number | null, power_ups: boolean, shared_board: boolean, players: Array<PlayerProfile>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * WebRTCの接続交渉の内容
+ */
+export type RtcSignalPayload = { "kind": "offer", sdp: string, } | { "kind": "answer", sdp: string, } | { "kind": "ice_candidate", candidate: string, sdp_mid: string | null, sdp_m_line_index: number | null, };
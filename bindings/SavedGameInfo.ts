@@ -0,0 +1,40 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SavedGameThumbnail } from "./SavedGameThumbnail";
+import type { SolitaireType } from "./SolitaireType";
+
+/**
+ * 中断したゲームの一覧の1件（list_saved_games()の戻り値）
+ */
+export type SavedGameInfo = { 
+/**
+ * 一覧の中でゲームを見分けるID（resume_game()・delete_saved_game()に渡す）
+ */
+id: string, 
+/**
+ * ゲームの種類
+ */
+variant: SolitaireType, 
+/**
+ * 進み具合（ファウンデーションに置いたカードの割合、0〜100）
+ */
+progress_percent: number, 
+/**
+ * 中断した時点までの経過時間（秒）
+ */
+elapsed_seconds: number, 
+/**
+ * 中断した時刻（UNIX時刻のミリ秒）
+ */
+saved_at: number, 
+/**
+ * 中断した時点のスコア
+ */
+score: number, 
+/**
+ * 中断した時点の手数
+ */
+move_count: number, 
+/**
+ * 盤面の縮小表示の状態
+ */
+thumbnail: SavedGameThumbnail, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一覧に表示する盤面の縮小表示の状態（カードは"AH"のような表記）
+ */
+export type SavedGameThumbnail = { 
+/**
+ * タブロー各列の表向きのカード（下から上の順）
+ */
+tableau: Array<Array<string>>, 
+/**
+ * タブロー各列の裏向きのカードの枚数
+ */
+face_down: Array<number>, 
+/**
+ * ファウンデーション各組の一番上のカード（空の組はNone）
+ */
+foundations: Array<string | null>, 
+/**
+ * ウェイストの一番上のカード（空の場合はNone）
+ */
+waste_top: string | null, 
+/**
+ * デッキに残っているカードの枚数
+ */
+deck_count: number, };
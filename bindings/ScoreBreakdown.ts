@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 最終スコアの内訳
+ *
+ * 基本スコアにボーナスとペナルティを適用した結果を保持します。
+ * ゲーム結果レポートでプレイヤーにスコアの理由を説明するために使います。
+ */
+export type ScoreBreakdown = { 
+/**
+ * 移動によって獲得した基本スコア
+ */
+base_score: number, 
+/**
+ * 時間ボーナス（早くクリアするほど高い）
+ */
+time_bonus: number, 
+/**
+ * 移動回数ペナルティ（移動が多いほど高い）
+ */
+move_penalty: number, 
+/**
+ * 最終スコア
+ */
+final_score: number, };
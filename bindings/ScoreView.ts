@@ -0,0 +1,40 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FormattedStats } from "./FormattedStats";
+import type { ScoreBreakdown } from "./ScoreBreakdown";
+
+/**
+ * スコアと統計情報
+ */
+export type ScoreView = { 
+/**
+ * 現在のスコア
+ */
+score: number, 
+/**
+ * 移動回数
+ */
+move_count: number, 
+/**
+ * デッキを一巡した回数
+ */
+deck_turns: number, 
+/**
+ * 経過時間（秒）
+ */
+elapsed_seconds: number, 
+/**
+ * ヒントを使用した回数
+ */
+hints_used: number, 
+/**
+ * アンドゥを使用した回数
+ */
+undos_used: number, 
+/**
+ * 最終スコアの内訳（勝利時のみ）
+ */
+breakdown: ScoreBreakdown | null, 
+/**
+ * 言語に合わせて整形した経過時間・スコア（設定のformat_numbersが有効な場合のみ）
+ */
+formatted?: FormattedStats | null, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardBack } from "./CardBack";
+
+/**
+ * スコアボードに表示する参加者の最新のスコア（クライアント送信用）
+ */
+export type ScoreboardEntry = { 
+/**
+ * プレイヤーID
+ */
+player_id: string, 
+/**
+ * プレイヤー名
+ */
+player_name: string, 
+/**
+ * カーソルの色のインデックス
+ */
+color_index: number, 
+/**
+ * 現在のスコア
+ */
+score: number, 
+/**
+ * ファウンデーションに置いたカードの枚数
+ */
+foundation_cards: number, 
+/**
+ * 見せているカードの裏面（見せていない場合はNone）
+ */
+card_back: CardBack | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 共有盤面のルームでの参加者の立場
+ */
+export type SeatRole = "host" | "player" | "spectator";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionStatus } from "./SessionStatus";
+
+/**
+ * セッション一覧の1件分
+ */
+export type SessionInfo = { 
+/**
+ * セッションID
+ */
+id: string, 
+/**
+ * セッションの状態
+ */
+status: SessionStatus, };
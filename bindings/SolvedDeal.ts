@@ -0,0 +1,28 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Difficulty } from "./Difficulty";
+import type { Winnability } from "./Winnability";
+
+/**
+ * 1つの配り札を解いた結果
+ */
+export type SolvedDeal = { 
+/**
+ * 配り札のシード
+ */
+seed: number, 
+/**
+ * 勝ち筋の有無
+ */
+winnability: Winnability, 
+/**
+ * 難しさ（勝ち筋が見つかった場合のみ）
+ */
+difficulty: Difficulty | null, 
+/**
+ * 見つかった勝ち筋の手数（最短とは限らない、勝ち筋が見つかった場合のみ）
+ */
+line_length: number | null, 
+/**
+ * 調べた局面数の上限
+ */
+search_limit: number, };
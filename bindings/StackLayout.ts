@@ -0,0 +1,40 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardLocation } from "./CardLocation";
+import type { CardPosition } from "./CardPosition";
+
+/**
+ * 1つの山のレイアウト（クライアント送信用）
+ */
+export type StackLayout = { 
+/**
+ * 山のID（"deck" / "waste" / "foundation-0" / "tableau-0"など）
+ */
+id: string, 
+/**
+ * 山の種類
+ */
+stack_type: CardLocation, 
+/**
+ * 山の番号（組札・場札の何番目か、デッキ・ウェイストは0）
+ */
+index: number, 
+/**
+ * 一番下のカード（空の山の枠）を置くX座標
+ */
+anchor_x: number, 
+/**
+ * 一番下のカード（空の山の枠）を置くY座標
+ */
+anchor_y: number, 
+/**
+ * 1枚ごとに下へずらす間隔（重ねて置く山は0）
+ */
+spacing: number, 
+/**
+ * 山のカードの枚数
+ */
+card_count: number, 
+/**
+ * 山のカードの位置（下から順）
+ */
+cards: Array<CardPosition>, };
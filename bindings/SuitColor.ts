@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * スートの記号・ランクを描く色
+ */
+export type SuitColor = "red" | "black" | "blue" | "green";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 色の違いが分かりにくいプレイヤー向けに、スートごとにカードの表面を塗る模様
+ */
+export type SuitPattern = "dots" | "stripes" | "waves" | "crosshatch";
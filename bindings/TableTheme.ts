@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * テーブルの配色
+ */
+export type TableTheme = "green_felt" | "blue_felt" | "wood" | "midnight";
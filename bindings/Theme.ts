@@ -0,0 +1,36 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CardBack } from "./CardBack";
+import type { TableTheme } from "./TableTheme";
+
+/**
+ * 選んでいるテーマ（リソース）
+ */
+export type Theme = { 
+/**
+ * カードの裏面のデザイン
+ */
+card_back: CardBack, 
+/**
+ * テーブルの配色
+ */
+table: TableTheme, 
+/**
+ * マルチプレイで同じルームの他のプレイヤーにカードの裏面を見せるか
+ */
+share_with_room: boolean, 
+/**
+ * 4色のスート（ダイヤを青、クラブを緑にして赤・黒の2色と見分けやすくする）
+ */
+four_color_suits: boolean, 
+/**
+ * ランクとスートの記号を大きく表示する（LARGE_PRINT_SCALE倍）
+ */
+large_print: boolean, 
+/**
+ * スートごとの模様でカードの表面を塗る
+ */
+pattern_fills: boolean, 
+/**
+ * 高コントラストの配色（白地に濃い色の文字・太い枠線）
+ */
+high_contrast: boolean, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Emote } from "./Emote";
+
+/**
+ * 表示中のリアクション
+ */
+export type TimedReaction = { 
+/**
+ * 送信したプレイヤーのID（カーソルの特定に使う）
+ */
+player_id: string, 
+/**
+ * 絵文字の種類
+ */
+emote: Emote, 
+/**
+ * 表示の残り時間（秒）
+ */
+remaining_seconds: number, };
@@ -0,0 +1,34 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * トーナメント参加者の順位情報（クライアント送信用）
+ */
+export type TournamentStanding = { 
+/**
+ * 順位（1始まり）
+ */
+rank: number, 
+/**
+ * プレイヤーID
+ */
+player_id: string, 
+/**
+ * プレイヤー名
+ */
+player_name: string, 
+/**
+ * 累計スコア
+ */
+total_score: number, 
+/**
+ * 累計プレイ時間（秒）
+ */
+total_time_seconds: number, 
+/**
+ * 結果を送信したラウンド数
+ */
+rounds_played: number, 
+/**
+ * 勝利したラウンド数
+ */
+rounds_won: number, };
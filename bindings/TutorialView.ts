@@ -0,0 +1,35 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HighlightView } from "./HighlightView";
+
+/**
+ * チュートリアルの進み具合
+ */
+export type TutorialView = { 
+/**
+ * チュートリアルID
+ */
+id: string, 
+/**
+ * チュートリアル名
+ */
+title: string, 
+/**
+ * 現在の手順の番号（0から開始）
+ */
+step: number, 
+/**
+ * 手順の数
+ */
+step_count: number, 
+/**
+ * 現在の手順の案内文（完了後はNone）
+ */
+message: string | null, 
+/**
+ * 強調表示する対象（完了後はNone）
+ */
+highlight: HighlightView | null, 
+/**
+ * すべての手順を終えたかどうか
+ */
+completed: boolean, };
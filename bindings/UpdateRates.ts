@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 品質に合わせた送信間隔
+ */
+export type UpdateRates = { 
+/**
+ * カーソル位置を送る間隔（ミリ秒）
+ */
+cursor_interval_ms: number, 
+/**
+ * 状態同期のキーフレーム（変わっていなくても送り直す自分のスコア）の間隔（ミリ秒）
+ */
+keyframe_interval_ms: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 表示領域リソース
+ */
+export type Viewport = { 
+/**
+ * 拡大率（1.0で等倍、MIN_SCALE〜MAX_SCALE）
+ */
+scale: number, 
+/**
+ * 盤面の原点を表示する画面のX座標
+ */
+offset_x: number, 
+/**
+ * 盤面の原点を表示する画面のY座標
+ */
+offset_y: number, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ArchivedDailyDeal } from "./ArchivedDailyDeal";
+import type { CardBack } from "./CardBack";
+import type { Channel } from "./Channel";
+import type { Emote } from "./Emote";
+import type { FriendStatus } from "./FriendStatus";
+import type { LoggedAction } from "./LoggedAction";
+import type { PlayerProfile } from "./PlayerProfile";
+import type { RoomInfo } from "./RoomInfo";
+import type { RtcSignalPayload } from "./RtcSignalPayload";
+import type { ScoreboardEntry } from "./ScoreboardEntry";
+import type { SolvedDeal } from "./SolvedDeal";
+import type { TournamentStanding } from "./TournamentStanding";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * WebSocketメッセージタイプ
+ */
+export type WebSocketMessage = { "type": "PlayerJoin", player_id: string, player_name: string, player_index: number, session_token?: string | null, request_id?: string | null, } | { "type": "SessionToken", session_token: string, } | { "type": "Ping", ping_id: number, client_time_ms: number, clock_offset_ms?: number | null, } | { "type": "Pong", ping_id: number, client_time_ms: number, server_time_ms: number, } | { "type": "PlayerLeft", player_id: string, player_name: string, } | { "type": "UpdatePreferences", player_id: string, color_index: number | null, player_name: string | null, } | { "type": "PlayerUpdated", player_id: string, player_name: string, color_index: number, } | { "type": "MousePosition", player_id: string, x: number, y: number, timestamp: number, sequence?: number | null, } | { "type": "Reaction", player_id: string, emote: Emote, } | { "type": "GameAction", player_id: string, player_name: string, action: string, x: number | null, y: number | null, timestamp: number, } | { "type": "GrabCard", room_id: string, player_id: string, card_id: string, timestamp: number, } | { "type": "CardGrabbed", room_id: string, player_id: string, card_id: string, } | { "type": "GrabRejected", room_id: string, card_id: string, owner_id: string, } | { "type": "ReleaseCard", room_id: string, player_id: string, card_id: string, } | { "type": "CardReleased", room_id: string, card_id: string, } | { "type": "SetSpectating", room_id: string, player_id: string, spectating: boolean, } | { "type": "SetCardOwner", room_id: string, player_id: string, card_id: string, owner_id: string | null, } | { "type": "PermissionsChanged", room_id: string, host_id: string | null, spectators: Array<string>, card_owners: { [key in string]: string }, } | { "type": "JoinRoom", room_id: string, player_id: string, password?: string | null, request_id?: string | null, } | { "type": "CreateRoom", player_id: string, name: string, max_players: number | null, password: string | null, turn_time_limit: number | null, combo_window_seconds: number | null, power_ups: boolean | null, shared_board: boolean | null, request_id?: string | null, } | { "type": "LeaveRoom", room_id: string, player_id: string, } | { "type": "RoomList", rooms: Array<RoomInfo>, request_id?: string | null, } | { "type": "GetRoomList", player_id: string, request_id?: string | null, } | { "type": "QuickMatch", player_id: string, } | { "type": "RoomRestored", room_id: string, seed: number | null, actions: Array<LoggedAction>, } | { "type": "HostChanged", room_id: string, host_id: string | null, host_name: string | null, } | { "type": "KickPlayer", room_id: string, player_id: string, target_id: string, } | { "type": "Kicked", room_id: string, player_id: string, banned: boolean, rejoin_after_seconds: number | null, } | { "type": "BanPlayer", room_id: string, player_id: string, target_id: string, } | { "type": "UnbanPlayer", room_id: string, player_id: string, target_name: string, } | { "type": "BanList", room_id: string, banned_names: Array<string>, } | { "type": "UpdateRoomSettings", room_id: string, player_id: string, name: string | null, max_players: number | null, password: string | null, turn_time_limit: number | null, combo_window_seconds: number | null, power_ups: boolean | null, afk_seconds: number | null, pause_on_afk: boolean | null, afk_forfeit_turns: number | null, } | { "type": "RoomSettingsChanged", room_id: string, name: string, max_players: number, has_password: boolean, turn_time_limit: number, combo_window_seconds: number, power_ups: boolean, afk_seconds: number, pause_on_afk: boolean, afk_forfeit_turns: number, } | { "type": "TurnStarted", room_id: string, player_id: string, turn_number: number, time_limit_seconds: number, } | { "type": "TurnTimeWarning", room_id: string, player_id: string, turn_number: number, remaining_seconds: number, } | { "type": "TurnTimedOut", room_id: string, player_id: string, turn_number: number, auto_action: string, } | { "type": "AddBot", room_id: string, player_id: string, count: number | null, moves_per_second: number | null, mistake_probability: number | null, } | { "type": "StartRace", room_id: string, player_id: string, seed: number | null, } | { "type": "RaceStart", room_id: string, seed: number, } | { "type": "SetReady", room_id: string, player_id: string, ready: boolean, } | { "type": "ReadyStatus", room_id: string, ready_player_ids: Array<string>, all_ready: boolean, } | { "type": "StartCountdown", room_id: string, seconds_remaining: number, } | { "type": "PlayerProfile", profile: PlayerProfile, request_id?: string | null, } | { "type": "RatingChanged", player_id: string, player_name: string, old_rating: number, new_rating: number, } | { "type": "ScoreUpdate", room_id: string, player_id: string, score: number, foundation_cards: number, } | { "type": "ComboUpdate", room_id: string, player_id: string, combo: number, multiplier: number, bonus_score: number, expires_at_ms?: number | null, } | { "type": "Scoreboard", room_id: string, players: Array<ScoreboardEntry>, } | { "type": "CardBackChanged", room_id: string, player_id: string, card_back: CardBack | null, } | { "type": "IdleStatus", room_id: string, player_id: string, idle_seconds: number, } | { "type": "PlayerAfk", room_id: string, player_id: string, afk: boolean, idle_seconds: number, paused: boolean, } | { "type": "SeatForfeited", room_id: string, player_id: string, afk_turns: number, } | { "type": "GameResult", player_id: string, result: JsonValue, } | { "type": "AddFriend", player_id: string, friend_id: string, request_id?: string | null, } | { "type": "RemoveFriend", player_id: string, friend_name: string, request_id?: string | null, } | { "type": "GetFriends", player_id: string, request_id?: string | null, } | { "type": "FriendList", friends: Array<FriendStatus>, request_id?: string | null, } | { "type": "FriendPresence", friend: FriendStatus, } | { "type": "InviteToRoom", player_id: string, target_id: string, room_id: string, } | { "type": "RoomInvite", invite_id: string, room_id: string, room_name: string, from_player_id: string, from_player_name: string, join_link: string, } | { "type": "RespondToInvite", player_id: string, invite_id: string, accept: boolean, } | { "type": "InviteAnswered", invite_id: string, player_id: string, player_name: string, accepted: boolean, } | { "type": "UpdateNotificationSettings", player_id: string, endpoint: string | null, turn: boolean, room_full: boolean, request_id?: string | null, } | { "type": "NotificationSettings", enabled: boolean, turn: boolean, room_full: boolean, request_id?: string | null, } | { "type": "GetDailyDeal", player_id: string, request_id?: string | null, } | { "type": "DailyDeal", day: number, seed: number, next_change_ms: number, solved?: SolvedDeal | null, request_id?: string | null, } | { "type": "GetDailyArchive", player_id: string, request_id?: string | null, } | { "type": "DailyArchive", deals: Array<ArchivedDailyDeal>, request_id?: string | null, } | { "type": "SignStats", player_id: string, request_id?: string | null, } | { "type": "StatsSigned", export: string, request_id?: string | null, } | { "type": "VerifyStats", player_id: string, export: string, request_id?: string | null, } | { "type": "StatsVerified", valid: boolean, reason?: string | null, signature?: string | null, request_id?: string | null, } | { "type": "CreateTournament", room_id: string, player_id: string, rounds: number, base_seed: number | null, } | { "type": "StartTournament", room_id: string, player_id: string, } | { "type": "TournamentCreated", tournament_id: string, room_id: string, host_id: string, rounds: number, } | { "type": "TournamentRoundStart", tournament_id: string, round: number, total_rounds: number, seed: number, } | { "type": "TournamentStandings", tournament_id: string, round: number, standings: Array<TournamentStanding>, } | { "type": "TournamentFinished", tournament_id: string, winner_id: string, winner_name: string, standings: Array<TournamentStanding>, } | { "type": "RtcSignal", room_id: string, from_player_id: string, to_player_id: string, signal: RtcSignalPayload, } | { "type": "Reliable", message_id: string, message: WebSocketMessage, sequence?: number | null, } | { "type": "Ack", message_id: string, } | { "type": "ResendRequest", channel: Channel, sequences: Array<number>, } | { "type": "Error", message: string, request_id?: string | null, field?: string | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 勝ち筋の有無
+ */
+export type Winnability = "winnable" | "unwinnable" | "unknown";
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Winnability } from "./Winnability";
+
+/**
+ * 勝ち筋の確認結果
+ */
+export type WinnabilityReport = { 
+/**
+ * 勝ち筋の有無
+ */
+winnability: Winnability, 
+/**
+ * 勝てなくなった手（何手目で勝ち筋がなくなったか、0は配られた時点、勝ち筋がある場合はNone）
+ */
+lost_at_move: number | null, 
+/**
+ * 勝てなくなってから打った手数（勝ち筋がある場合はNone）
+ */
+moves_since_lost: number | null, };
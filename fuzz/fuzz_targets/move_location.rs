@@ -0,0 +1,27 @@
+// =============================================================================
+// ファジング：move_card()の場所指定の解析
+// =============================================================================
+// JavaScriptから渡される場所指定を MoveLocation::parse に渡し、
+// どのような入力でもパニックせず、成功した場合はインデックスが
+// 妥当な範囲に収まっていることを確認します。
+//
+// 実行方法：cargo +nightly fuzz run move_location
+// =============================================================================
+
+#![no_main]
+
+use ecs_wasm_solitaire::protocol::MoveLocation;
+use libfuzzer_sys::fuzz_target;
+
+/// どの場所でもインデックスはこの値未満になる（スパイダーのタブロー10列）
+const MAX_INDEX: u32 = 10;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(location) = MoveLocation::parse(json) {
+        assert!(location.index < MAX_INDEX, "範囲外のインデックス: {:?}", location);
+    }
+});
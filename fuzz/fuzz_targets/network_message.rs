@@ -0,0 +1,29 @@
+// =============================================================================
+// ファジング：ネットワークメッセージの解析
+// =============================================================================
+// クライアントのネットワークレイヤが受信したテキストを NetworkMessage::parse に渡し、
+// 解析結果に対する操作（期限切れ判定、再送信カウント）がパニックしないことを確認します。
+//
+// 実行方法：cargo +nightly fuzz run network_message
+// =============================================================================
+
+#![no_main]
+
+use ecs_wasm_solitaire::network::NetworkMessage;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(mut message) = NetworkMessage::parse(text) {
+        // タイムスタンプや再送信回数が極端な値でもパニックしないこと
+        let _ = message.is_expired(0);
+        let _ = message.is_expired(u64::MAX);
+        message.increment_retry();
+
+        let json = serde_json::to_string(&message).expect("解析済みのメッセージはシリアライズできる");
+        NetworkMessage::parse(&json).expect("シリアライズしたメッセージは再度解析できる");
+    }
+});
@@ -0,0 +1,29 @@
+// =============================================================================
+// ファジング：WebSocketメッセージの解析
+// =============================================================================
+// WebSocketサーバーが受信したテキストを WebSocketMessage::parse に渡し、
+// どのような入力でもパニックしないこと、検証を通ったメッセージは
+// 再シリアライズしても同じ検証を通ることを確認します。
+//
+// 実行方法：cargo +nightly fuzz run websocket_message
+// =============================================================================
+
+#![no_main]
+
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    if let Ok(message) = WebSocketMessage::parse(text) {
+        let json = serde_json::to_string(&message).expect("検証済みのメッセージはシリアライズできる");
+        let reparsed: WebSocketMessage =
+            serde_json::from_str(&json).expect("シリアライズしたメッセージは再度解析できる");
+        reparsed
+            .validate()
+            .expect("検証済みのメッセージは往復後も検証を通る");
+    }
+});
@@ -0,0 +1,89 @@
+// =============================================================================
+// 同期アカウントの実績・通算成績（サーバー用）
+// =============================================================================
+// このファイルでは、同期アカウント（セッショントークンを持つプレイヤー）が
+// サーバーに送ったゲーム結果から、実績と通算成績をサーバー側で記録するAccountStatsStoreを実装します。
+//
+// 仕組み：
+// - 成績の書き出しへの署名（SignStats）は、クライアントの送った成績ではなくここに記録した成績にだけ行う
+// - セッショントークンは秘密の値なので書き出しには載せず、アカウントごとに発行したIDを出どころとして載せる
+// - 移動履歴はサーバーに届かないため、移動の順番で決まる実績（スートの順番）はここでは解除しない
+// =============================================================================
+
+use crate::achievements::AchievementStore;
+use crate::clock::GameClock;
+use crate::result::GameResult;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 同期アカウントの成績の保存キー
+const STORAGE_KEY: &str = "account_stats";
+
+/// 同期アカウント1つ分の成績
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStats {
+    /// アカウントのID（書き出しの出どころとして載せる）
+    pub account_id: String,
+
+    /// 実績と通算成績
+    pub achievements: AchievementStore,
+}
+
+/// 全同期アカウントの成績
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountStatsStore {
+    /// セッショントークン → 成績
+    records: HashMap<String, AccountStats>,
+}
+
+impl AccountStatsStore {
+    /// 保存されている成績を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のAccountStatsStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 成績を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("アカウントの成績のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// 同期アカウントの成績を取得
+    ///
+    /// # 引数
+    /// * `session_token` - セッショントークン
+    ///
+    /// # 戻り値
+    /// 記録された成績（まだゲーム結果が届いていない場合はNone）
+    pub fn get(&self, session_token: &str) -> Option<&AccountStats> {
+        self.records.get(session_token)
+    }
+
+    /// ゲーム結果を同期アカウントの成績に反映する
+    ///
+    /// # 引数
+    /// * `session_token` - セッショントークン
+    /// * `result` - 届いたゲーム結果
+    /// * `clock` - 実績を解除した時刻の取得元となる時計
+    pub fn record(&mut self, session_token: &str, result: &GameResult, clock: &GameClock) {
+        let stats = self
+            .records
+            .entry(session_token.to_string())
+            .or_insert_with(|| AccountStats {
+                account_id: Uuid::new_v4().to_string(),
+                achievements: AchievementStore::default(),
+            });
+        stats.achievements.apply_result(result, None, clock);
+    }
+}
@@ -0,0 +1,501 @@
+// =============================================================================
+// 実績・連勝記録システム
+// =============================================================================
+// このファイルでは、ゲーム結果と移動履歴から実績（アチーブメント）を判定し、
+// 通算成績や連勝記録とともに端末内に保存する仕組みを実装します。
+//
+// 主要な責務：
+// - 実績の定義（初勝利、アンドゥなし勝利、3分以内クリア、5連勝など）
+// - ゲーム終了時の通算成績（勝率・連勝・ベストタイム）の更新
+// - 新しく解除された実績のイベント通知
+// - 実績と成績のローカル保存・読み込み
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Component, Entity, Resource, System, World};
+use crate::events::{EventQueue, GameEvent, NotificationSeverity};
+use crate::practice;
+use crate::result::{GameOutcome, GameResult};
+use crate::solitaire::{CardRank, CardSuit, MoveLog};
+use crate::storage;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 実績データの保存キー
+const STORAGE_KEY: &str = "achievements";
+
+// =============================================================================
+// 実績の定義
+// =============================================================================
+
+/// 実績の種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementId {
+    /// 初めて勝利した
+    FirstWin,
+
+    /// アンドゥを使わずに勝利した
+    WinWithoutUndo,
+
+    /// ヒントを使わずに勝利した
+    WinWithoutHints,
+
+    /// 3分以内に勝利した
+    WinUnderThreeMinutes,
+
+    /// 5連勝した
+    WinStreakFive,
+
+    /// ♥→♦→♣→♠の順番でスートを完成させて勝利した
+    SuitsInOrder,
+}
+
+impl AchievementId {
+    /// 全ての実績を取得
+    ///
+    /// # 戻り値
+    /// 全実績の配列
+    pub fn all() -> [AchievementId; 6] {
+        [
+            AchievementId::FirstWin,
+            AchievementId::WinWithoutUndo,
+            AchievementId::WinWithoutHints,
+            AchievementId::WinUnderThreeMinutes,
+            AchievementId::WinStreakFive,
+            AchievementId::SuitsInOrder,
+        ]
+    }
+
+    /// 実績IDを文字列で取得
+    ///
+    /// # 戻り値
+    /// 実績IDの文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AchievementId::FirstWin => "first_win",
+            AchievementId::WinWithoutUndo => "win_without_undo",
+            AchievementId::WinWithoutHints => "win_without_hints",
+            AchievementId::WinUnderThreeMinutes => "win_under_three_minutes",
+            AchievementId::WinStreakFive => "win_streak_five",
+            AchievementId::SuitsInOrder => "suits_in_order",
+        }
+    }
+
+    /// 実績名を取得
+    ///
+    /// # 戻り値
+    /// 実績名の文字列
+    pub fn name(&self) -> &'static str {
+        match self {
+            AchievementId::FirstWin => "はじめての勝利",
+            AchievementId::WinWithoutUndo => "一発勝負",
+            AchievementId::WinWithoutHints => "自力クリア",
+            AchievementId::WinUnderThreeMinutes => "スピードスター",
+            AchievementId::WinStreakFive => "5連勝",
+            AchievementId::SuitsInOrder => "整理整頓",
+        }
+    }
+
+    /// 実績の説明を取得
+    ///
+    /// # 戻り値
+    /// 実績の説明文字列
+    pub fn description(&self) -> &'static str {
+        match self {
+            AchievementId::FirstWin => "ゲームに初めて勝利する",
+            AchievementId::WinWithoutUndo => "アンドゥを使わずに勝利する",
+            AchievementId::WinWithoutHints => "ヒントを使わずに勝利する",
+            AchievementId::WinUnderThreeMinutes => "3分以内に勝利する",
+            AchievementId::WinStreakFive => "5回連続で勝利する",
+            AchievementId::SuitsInOrder => "♥→♦→♣→♠の順番でスートを完成させて勝利する",
+        }
+    }
+
+    /// 実績の達成条件を判定
+    ///
+    /// # 引数
+    /// * `result` - ゲーム結果
+    /// * `move_log` - ゲームの移動履歴（存在しない場合はNone）
+    /// * `stats` - 結果を反映した後の通算成績
+    ///
+    /// # 戻り値
+    /// 条件を満たしている場合true
+    fn is_satisfied(
+        &self,
+        result: &GameResult,
+        move_log: Option<&MoveLog>,
+        stats: &PlayerStats,
+    ) -> bool {
+        if result.outcome != GameOutcome::Won {
+            return false;
+        }
+
+        match self {
+            AchievementId::FirstWin => true,
+            AchievementId::WinWithoutUndo => result.undos_used == 0,
+            AchievementId::WinWithoutHints => result.hints_used == 0,
+            AchievementId::WinUnderThreeMinutes => result.duration_seconds < 180,
+            AchievementId::WinStreakFive => stats.current_streak >= 5,
+            AchievementId::SuitsInOrder => move_log.is_some_and(suits_completed_in_order),
+        }
+    }
+}
+
+/// スートが♥→♦→♣→♠の順番で完成したかチェック
+///
+/// 各スートのKがファウンデーションに置かれた順番で判定します。
+///
+/// # 引数
+/// * `move_log` - ゲームの移動履歴
+///
+/// # 戻り値
+/// 4スートすべてが指定の順番で完成している場合true
+fn suits_completed_in_order(move_log: &MoveLog) -> bool {
+    let completed: Vec<CardSuit> = move_log
+        .foundation_moves()
+        .filter(|record| record.rank == CardRank::King)
+        .map(|record| record.suit)
+        .collect();
+
+    completed == CardSuit::all()
+}
+
+// =============================================================================
+// 通算成績と実績の保存データ
+// =============================================================================
+
+/// 通算成績
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PlayerStats {
+    /// プレイしたゲーム数
+    pub games_played: u32,
+
+    /// 勝利したゲーム数
+    pub games_won: u32,
+
+    /// 現在の連勝数
+    pub current_streak: u32,
+
+    /// 最長連勝数
+    pub best_streak: u32,
+
+    /// 最短クリア時間（秒、未勝利の場合はNone）
+    pub best_time_seconds: Option<u64>,
+
+    /// 最高スコア
+    pub best_score: u32,
+}
+
+impl PlayerStats {
+    /// ゲーム結果を通算成績に反映
+    ///
+    /// # 引数
+    /// * `result` - 反映するゲーム結果
+    pub fn record(&mut self, result: &GameResult) {
+        self.games_played = self.games_played.saturating_add(1);
+
+        match result.outcome {
+            GameOutcome::Won => {
+                self.games_won = self.games_won.saturating_add(1);
+                self.current_streak = self.current_streak.saturating_add(1);
+                self.best_streak = self.best_streak.max(self.current_streak);
+                self.best_time_seconds = Some(
+                    self.best_time_seconds
+                        .map_or(result.duration_seconds, |best| {
+                            best.min(result.duration_seconds)
+                        }),
+                );
+                self.best_score = self.best_score.max(result.score.final_score);
+            }
+            GameOutcome::Lost => {
+                self.current_streak = 0;
+            }
+        }
+    }
+
+    /// 別の端末の通算成績をまとめる
+    ///
+    /// ゲーム数・勝利数は前に同じ出どころから読み込んだ分を除いて足し合わせ、
+    /// 連勝数・最高スコアは大きい方、最短クリア時間は短い方を残します。
+    ///
+    /// # 引数
+    /// * `other` - まとめる通算成績
+    /// * `already_merged` - 同じ出どころから前にまとめた通算成績（その分は数え直さない）
+    pub fn merge(&mut self, other: &PlayerStats, already_merged: &PlayerStats) {
+        let new_games = other.games_played.saturating_sub(already_merged.games_played);
+        let new_wins = other.games_won.saturating_sub(already_merged.games_won);
+        self.games_played = self.games_played.saturating_add(new_games);
+        self.games_won = self.games_won.saturating_add(new_wins);
+        self.current_streak = self.current_streak.max(other.current_streak);
+        self.best_streak = self.best_streak.max(other.best_streak);
+        self.best_time_seconds = match (self.best_time_seconds, other.best_time_seconds) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.best_score = self.best_score.max(other.best_score);
+    }
+}
+
+/// 解除済みの実績
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UnlockedAchievement {
+    /// 実績ID
+    pub id: AchievementId,
+
+    /// 解除した時刻（UNIXタイムスタンプ）
+    pub unlocked_at: u64,
+}
+
+/// 実績・通算成績の保存データリソース
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AchievementStore {
+    /// 解除済みの実績（解除順）
+    pub unlocked: Vec<UnlockedAchievement>,
+
+    /// 通算成績
+    pub stats: PlayerStats,
+
+    /// 読み込んだ成績の書き出しのID（同じ書き出しを2回まとめないように、新しい順にMAX_IMPORTED_EXPORTS件まで）
+    #[serde(default)]
+    pub imported_exports: Vec<String>,
+
+    /// 出どころ（同期アカウントのID）ごとの、最後にまとめた通算成績
+    /// （同じ出どころの新しい書き出しは、増えた分だけを足す）
+    #[serde(default)]
+    pub imported_sources: BTreeMap<String, PlayerStats>,
+}
+
+/// 読み込んだ成績の書き出しのIDを覚えておく件数
+const MAX_IMPORTED_EXPORTS: usize = 32;
+
+impl Resource for AchievementStore {}
+
+impl AchievementStore {
+    /// 保存されている実績データを読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のAchievementStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 実績データを保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("実績データのシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// 実績が解除済みかチェック
+    ///
+    /// # 引数
+    /// * `id` - チェックする実績ID
+    ///
+    /// # 戻り値
+    /// 解除済みの場合true
+    pub fn is_unlocked(&self, id: AchievementId) -> bool {
+        self.unlocked.iter().any(|achievement| achievement.id == id)
+    }
+
+    /// ゲーム結果を反映し、新しく解除された実績を返す
+    ///
+    /// # 引数
+    /// * `result` - ゲーム結果
+    /// * `move_log` - ゲームの移動履歴（存在しない場合はNone）
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    ///
+    /// # 戻り値
+    /// 今回新たに解除された実績IDのベクター
+    pub fn apply_result(
+        &mut self,
+        result: &GameResult,
+        move_log: Option<&MoveLog>,
+        clock: &GameClock,
+    ) -> Vec<AchievementId> {
+        self.stats.record(result);
+
+        let now = clock.now_secs();
+
+        let newly_unlocked: Vec<AchievementId> = AchievementId::all()
+            .into_iter()
+            .filter(|id| !self.is_unlocked(*id))
+            .filter(|id| id.is_satisfied(result, move_log, &self.stats))
+            .collect();
+
+        for id in &newly_unlocked {
+            self.unlocked.push(UnlockedAchievement {
+                id: *id,
+                unlocked_at: now,
+            });
+        }
+
+        newly_unlocked
+    }
+
+    /// 別の端末の実績・通算成績をまとめる
+    ///
+    /// 実績はどちらかで解除していれば解除済みとし、解除した時刻は早い方を残します。
+    /// 同じ出どころの成績を何度まとめても、ゲーム数・勝利数は増えた分しか足しません。
+    ///
+    /// # 引数
+    /// * `source_id` - 成績の出どころ（同期アカウントのID）
+    /// * `export_id` - まとめる成績の書き出しのID
+    /// * `other` - まとめる実績・通算成績
+    ///
+    /// # 戻り値
+    /// 成功時は新しく解除済みになった実績ID、同じ書き出しを既にまとめていた場合はエラーメッセージ
+    pub fn merge(
+        &mut self,
+        source_id: &str,
+        export_id: &str,
+        other: &AchievementStore,
+    ) -> Result<Vec<AchievementId>, String> {
+        if self.imported_exports.iter().any(|id| id == export_id) {
+            return Err("この成績は既に読み込んでいます".to_string());
+        }
+
+        let mut newly_unlocked = Vec::new();
+        for achievement in &other.unlocked {
+            match self
+                .unlocked
+                .iter_mut()
+                .find(|unlocked| unlocked.id == achievement.id)
+            {
+                Some(unlocked) => {
+                    unlocked.unlocked_at = unlocked.unlocked_at.min(achievement.unlocked_at);
+                }
+                None => {
+                    self.unlocked.push(achievement.clone());
+                    newly_unlocked.push(achievement.id);
+                }
+            }
+        }
+        self.unlocked.sort_by_key(|achievement| achievement.unlocked_at);
+        let already_merged = self
+            .imported_sources
+            .get(source_id)
+            .cloned()
+            .unwrap_or_default();
+        self.stats.merge(&other.stats, &already_merged);
+        // 古い書き出しを後から読み込んでも、次の書き出しで数え直さないように多い方を覚えておく
+        self.imported_sources.insert(
+            source_id.to_string(),
+            PlayerStats {
+                games_played: other.stats.games_played.max(already_merged.games_played),
+                games_won: other.stats.games_won.max(already_merged.games_won),
+                ..other.stats.clone()
+            },
+        );
+
+        self.imported_exports.insert(0, export_id.to_string());
+        self.imported_exports.truncate(MAX_IMPORTED_EXPORTS);
+        Ok(newly_unlocked)
+    }
+
+    /// JavaScript向けの実績一覧を作成
+    ///
+    /// # 戻り値
+    /// 全実績の解除状況と通算成績を含むJSON値
+    pub fn to_summary_json(&self) -> serde_json::Value {
+        let achievements: Vec<serde_json::Value> = AchievementId::all()
+            .iter()
+            .map(|id| {
+                let unlocked_at = self
+                    .unlocked
+                    .iter()
+                    .find(|achievement| achievement.id == *id)
+                    .map(|achievement| achievement.unlocked_at);
+
+                serde_json::json!({
+                    "id": id.as_str(),
+                    "name": id.name(),
+                    "description": id.description(),
+                    "unlocked": unlocked_at.is_some(),
+                    "unlocked_at": unlocked_at,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "achievements": achievements,
+            "stats": self.stats,
+        })
+    }
+}
+
+// =============================================================================
+// 実績判定システム
+// =============================================================================
+
+/// 実績判定済みを示すマーカーコンポーネント
+///
+/// 同じゲーム結果で二重に成績を更新しないよう、
+/// 判定済みのゲーム状態エンティティに添付します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AchievementsEvaluated;
+
+impl Component for AchievementsEvaluated {}
+
+/// 実績判定システム
+///
+/// ゲーム結果が作成されたゲームの成績を更新し、実績の解除判定を行います。
+/// 解除された実績はEventQueueへ通知され、成績は端末内に保存されます。
+pub struct AchievementSystem;
+
+impl System for AchievementSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let clock = GameClock::from_world(world);
+
+        // 結果が作成済みで、まだ判定していないゲームを探す（練習の結果は数えない）
+        let pending: Vec<(Entity, GameResult, Option<MoveLog>)> = world
+            .query::<GameResult>()
+            .filter(|(entity, _)| {
+                !world.has_component::<AchievementsEvaluated>(*entity)
+                    && !practice::is_practice(world, *entity)
+            })
+            .map(|(entity, result)| {
+                (
+                    entity,
+                    result.clone(),
+                    world.get_component::<MoveLog>(entity).cloned(),
+                )
+            })
+            .collect();
+
+        for (entity, result, move_log) in pending {
+            world.add_component(entity, AchievementsEvaluated);
+
+            let Some(store) = world.get_resource_mut::<AchievementStore>() else {
+                continue;
+            };
+
+            let newly_unlocked = store.apply_result(&result, move_log.as_ref(), &clock);
+            if let Err(e) = store.save() {
+                warn!("⚠️ 実績データの保存失敗: {}", e);
+            }
+
+            for id in newly_unlocked {
+                info!("🏅 実績解除: {} - {}", id.name(), id.description());
+
+                if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                    events.push(GameEvent::AchievementUnlocked {
+                        id: id.as_str().to_string(),
+                        name: id.name().to_string(),
+                        description: id.description().to_string(),
+                    });
+                    events.notify(
+                        NotificationSeverity::Achievement,
+                        format!("🏅 実績解除: {}", id.name()),
+                    );
+                }
+            }
+        }
+    }
+}
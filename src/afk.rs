@@ -0,0 +1,189 @@
+// =============================================================================
+// 離席（AFK）の判定
+// =============================================================================
+// このファイルでは、マルチプレイのルームで操作が止まったプレイヤーを離席中として扱う
+// AfkTrackerと、クライアントが操作していない時間を知らせる間隔の決め方を実装します。
+//
+// 仕組み：
+// - クライアントはSolitaireGameState.idle_timeをIDLE_REPORT_STEP_SECONDSごとの段階で
+//   IdleStatusとして送り、操作を再開したら0を送る（段階が変わらない間は送らない）
+// - サーバーはゲームアクション・カードの掴み/離し・IdleStatusをプレイヤーの操作として記録する
+//   （カーソルの移動は操作に数えない）
+// - ルームの設定の時間（afk_seconds）を超えて操作がないプレイヤーを離席中にし、
+//   操作が届いたら戻ったことにする。どちらの場合もルームの全員にPlayerAfkを送る
+// - 協力プレイでは、設定により誰かが離席している間ゲームを一時停止する
+// - ターン制では離席中のプレイヤーのターンを飛ばし、続けて飛ばした回数が
+//   設定の回数（afk_forfeit_turns）に達したら席を没収する（ゲームが終わるまで戻れない）
+// =============================================================================
+
+use std::collections::{BTreeSet, HashMap};
+
+/// IdleStatusを送る間隔（操作していない時間がこの秒数を超えるごとに送る）
+pub const IDLE_REPORT_STEP_SECONDS: u32 = 15;
+
+/// 離席とみなすまでの時間（秒）の上限
+pub const MAX_AFK_SECONDS: u32 = 3600;
+
+/// 操作していない時間をIdleStatusで送る段階に直す
+///
+/// # 引数
+/// * `idle_seconds` - 操作していない時間（秒）
+///
+/// # 戻り値
+/// IDLE_REPORT_STEP_SECONDSごとの段階（0は操作中）
+pub fn idle_report_step(idle_seconds: f64) -> u32 {
+    if idle_seconds.is_finite() && idle_seconds > 0.0 {
+        (idle_seconds / f64::from(IDLE_REPORT_STEP_SECONDS)).min(f64::from(u32::MAX)) as u32
+    } else {
+        0
+    }
+}
+
+/// ルーム1つ分の離席の状態
+#[derive(Debug, Clone, Default)]
+pub struct AfkTracker {
+    /// プレイヤーIDごとの最後に操作した時刻（UNIX時刻、ミリ秒）
+    last_activity_ms: HashMap<String, u64>,
+
+    /// 離席中のプレイヤーID
+    afk: BTreeSet<String>,
+
+    /// プレイヤーIDごとの、離席中に続けて飛ばしたターンの数
+    afk_turns: HashMap<String, u32>,
+
+    /// 席を没収されたプレイヤーID
+    forfeited: BTreeSet<String>,
+}
+
+impl AfkTracker {
+    /// 空のトラッカーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// プレイヤーの操作を記録する
+    ///
+    /// # 引数
+    /// * `player_id` - プレイヤーID
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 離席中だったプレイヤーが戻った場合true
+    pub fn record_activity(&mut self, player_id: &str, now_ms: u64) -> bool {
+        self.last_activity_ms.insert(player_id.to_string(), now_ms);
+        self.afk_turns.remove(player_id);
+        self.afk.remove(player_id)
+    }
+
+    /// クライアントから届いた操作していない時間を記録する
+    ///
+    /// サーバーが記録した最後の操作より前にはさかのぼりません。
+    ///
+    /// # 引数
+    /// * `player_id` - プレイヤーID
+    /// * `idle_seconds` - 操作していない時間（秒、0の場合は操作を再開した）
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 離席中だったプレイヤーが戻った場合true
+    pub fn record_idle(&mut self, player_id: &str, idle_seconds: u32, now_ms: u64) -> bool {
+        if idle_seconds == 0 {
+            return self.record_activity(player_id, now_ms);
+        }
+        let reported = now_ms.saturating_sub(u64::from(idle_seconds) * 1000);
+        let last = self
+            .last_activity_ms
+            .entry(player_id.to_string())
+            .or_insert(reported);
+        *last = (*last).max(reported);
+        false
+    }
+
+    /// 操作のないまま時間を超えたプレイヤーを離席中にする
+    ///
+    /// まだ操作を記録していないプレイヤーは、この時点から数え始めます。
+    ///
+    /// # 引数
+    /// * `player_ids` - ルームの参加者
+    /// * `afk_seconds` - 離席とみなすまでの時間（秒）
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 新しく離席中になったプレイヤー（プレイヤーID, 操作していない秒数）
+    pub fn check(
+        &mut self,
+        player_ids: &[String],
+        afk_seconds: u32,
+        now_ms: u64,
+    ) -> Vec<(String, u32)> {
+        let mut changes = Vec::new();
+        for player_id in player_ids {
+            if self.afk.contains(player_id) || self.forfeited.contains(player_id) {
+                continue;
+            }
+            let last = *self
+                .last_activity_ms
+                .entry(player_id.clone())
+                .or_insert(now_ms);
+            let idle_ms = now_ms.saturating_sub(last);
+            if idle_ms >= u64::from(afk_seconds) * 1000 {
+                self.afk.insert(player_id.clone());
+                changes.push((player_id.clone(), (idle_ms / 1000) as u32));
+            }
+        }
+        changes
+    }
+
+    /// プレイヤーが離席中かどうか
+    pub fn is_afk(&self, player_id: &str) -> bool {
+        self.afk.contains(player_id)
+    }
+
+    /// 離席中のプレイヤーがいるかどうか（席を没収されたプレイヤーは除く）
+    ///
+    /// # 引数
+    /// * `player_ids` - ルームの参加者
+    pub fn any_afk(&self, player_ids: &[String]) -> bool {
+        player_ids
+            .iter()
+            .any(|player_id| self.is_afk(player_id) && !self.is_forfeited(player_id))
+    }
+
+    /// 離席中のプレイヤーのターンを飛ばしたことを記録する
+    ///
+    /// # 戻り値
+    /// 続けて飛ばしたターンの数
+    pub fn record_afk_turn(&mut self, player_id: &str) -> u32 {
+        let turns = self.afk_turns.entry(player_id.to_string()).or_insert(0);
+        *turns += 1;
+        *turns
+    }
+
+    /// 席を没収する
+    pub fn forfeit(&mut self, player_id: &str) {
+        self.afk_turns.remove(player_id);
+        self.forfeited.insert(player_id.to_string());
+    }
+
+    /// 席を没収されたかどうか
+    pub fn is_forfeited(&self, player_id: &str) -> bool {
+        self.forfeited.contains(player_id)
+    }
+
+    /// ゲームが終わったので、最後に操作した時刻・飛ばしたターンの数・没収した席を元に戻す
+    ///
+    /// 離席中のプレイヤーは、操作が届くまで離席中のままにします。
+    pub fn end_game(&mut self) {
+        self.last_activity_ms.clear();
+        self.afk_turns.clear();
+        self.forfeited.clear();
+    }
+
+    /// 退室したプレイヤーの状態を削除
+    pub fn remove_player(&mut self, player_id: &str) {
+        self.last_activity_ms.remove(player_id);
+        self.afk.remove(player_id);
+        self.afk_turns.remove(player_id);
+        self.forfeited.remove(player_id);
+    }
+}
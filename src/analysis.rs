@@ -0,0 +1,280 @@
+// =============================================================================
+// 対局後の振り返り
+// =============================================================================
+// このファイルでは、終わったゲームの移動履歴を1手ずつ再現し、ソルバーの選ぶ手と比べて
+// 各手を「最善・良い・疑問手・悪手」に分類する振り返りを実装します。
+//
+// 仕組み：
+// 1. ゲームのシードから配り札を別のワールドに作り直す
+// 2. 移動履歴の手を順に打ち、打つ前と後の局面をソルバーで調べる
+// 3. 最後に作り直した盤面が実際の盤面と一致することを確かめる
+//    （パズル・シナリオの盤面はシードから再現できないため振り返りできない）
+//
+// 分類：
+// - 最善（best）：ソルバーの勝ち筋の最初の一手と同じ局面になった
+// - 良い（good）：勝ち筋を保った（デッキから引くなど局面が変わらない手も含む）
+// - 疑問手（inaccuracy）：勝ち筋は残ったが、勝つまでの手数が大きく増えたか、勝ち筋が見つからなくなった
+// - 悪手（blunder）：この手で勝てなくなった
+// すでに勝てない局面で打った手は、勝敗に影響しないため「良い」とします。
+// =============================================================================
+
+use crate::ecs::{Entity, World};
+use crate::hint::{BoardView, HintEngine, HintLocation};
+use crate::solitaire::{
+    CardLocation, CardRank, CardSuit, MoveLog, MoveRecord, SolitaireCard, SolitaireGameState,
+    SolitaireManager, SolitaireType,
+};
+use crate::solver::{PositionKey, Solution, Solver, Winnability};
+use log::info;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// 勝つまでの手数がこれより多く増えた場合は疑問手とする
+const INACCURACY_EXTRA_STEPS: u32 = 5;
+
+/// 1手の評価
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveQuality {
+    /// ソルバーと同じ手
+    Best,
+
+    /// 勝ち筋を保った手
+    Good,
+
+    /// 勝ち筋は残ったが遠回りになった手
+    Inaccuracy,
+
+    /// 勝てなくなった手
+    Blunder,
+}
+
+impl MoveQuality {
+    /// 手を打つ前と後の探索結果から評価する
+    ///
+    /// # 引数
+    /// * `record` - 打った手
+    /// * `before` - 打つ前の局面
+    /// * `after` - 打った後の局面
+    /// * `best` - 打つ前の局面の探索結果
+    /// * `result` - 打った後の局面の探索結果
+    fn judge(
+        record: &MoveRecord,
+        before: PositionKey,
+        after: PositionKey,
+        best: &Solution,
+        result: &Solution,
+    ) -> Self {
+        if best.winnability == Winnability::Unwinnable {
+            return MoveQuality::Good;
+        }
+        if result.winnability == Winnability::Unwinnable {
+            return MoveQuality::Blunder;
+        }
+        if best.next == Some(after) {
+            return MoveQuality::Best;
+        }
+        if after == before {
+            // 組札へ置いても困らないカードを置く手は最善、デッキから引く手などは良い手
+            return if record.to == CardLocation::Foundation {
+                MoveQuality::Best
+            } else {
+                MoveQuality::Good
+            };
+        }
+        match (best.line_length, result.line_length) {
+            (Some(_), None) => MoveQuality::Inaccuracy,
+            (Some(best_length), Some(length))
+                if length > best_length.saturating_sub(1) + INACCURACY_EXTRA_STEPS =>
+            {
+                MoveQuality::Inaccuracy
+            }
+            _ => MoveQuality::Good,
+        }
+    }
+}
+
+/// 1手分の振り返り
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct MoveAnalysis {
+    /// 何手目か（移動履歴の順番、1から開始）
+    pub move_number: u32,
+
+    /// 動かしたカードのスート
+    pub suit: CardSuit,
+
+    /// 動かしたカードのランク
+    pub rank: CardRank,
+
+    /// 移動元の場所
+    pub from: CardLocation,
+
+    /// 移動先の場所
+    pub to: CardLocation,
+
+    /// 手の評価
+    pub quality: MoveQuality,
+
+    /// 打つ前の局面の勝ち筋の有無
+    pub before: Winnability,
+
+    /// 打った後の局面の勝ち筋の有無
+    pub after: Winnability,
+}
+
+/// ゲーム全体の振り返り
+#[derive(Debug, Clone, Default, Serialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct GameAnalysis {
+    /// 各手の振り返り（古い順）
+    pub moves: Vec<MoveAnalysis>,
+
+    /// 最善手の数
+    pub best: u32,
+
+    /// 良い手の数
+    pub good: u32,
+
+    /// 疑問手の数
+    pub inaccuracies: u32,
+
+    /// 悪手の数
+    pub blunders: u32,
+}
+
+impl GameAnalysis {
+    /// 各手の振り返りから集計する
+    fn new(moves: Vec<MoveAnalysis>) -> Self {
+        let count = |quality| moves.iter().filter(|m| m.quality == quality).count() as u32;
+        Self {
+            best: count(MoveQuality::Best),
+            good: count(MoveQuality::Good),
+            inaccuracies: count(MoveQuality::Inaccuracy),
+            blunders: count(MoveQuality::Blunder),
+            moves,
+        }
+    }
+
+    /// ゲームを振り返る
+    ///
+    /// 移動履歴の手の数だけソルバーで局面を調べるため、長いゲームでは時間がかかります。
+    ///
+    /// # 引数
+    /// * `world` - ゲームのあるECSワールド
+    /// * `game` - ゲーム状態エンティティ
+    /// * `search_limit` - 1局面あたりにソルバーが調べる局面数の上限
+    ///
+    /// # 戻り値
+    /// 成功時は振り返りの結果、シードから盤面を再現できない場合はエラーメッセージ
+    pub fn analyze(world: &World, game: Entity, search_limit: u32) -> Result<Self, String> {
+        let state = world
+            .get_component::<SolitaireGameState>(game)
+            .ok_or_else(|| "ゲームがありません".to_string())?;
+        if state.game_type != SolitaireType::Klondike {
+            return Err("振り返りはクロンダイクのみ対応しています".to_string());
+        }
+        let records = world
+            .get_component::<MoveLog>(game)
+            .map(|log| log.moves.clone())
+            .unwrap_or_default();
+
+        let mut replay = World::new();
+        SolitaireManager::start_new_game_with_seed(&mut replay, state.game_type, state.seed);
+        let mut key = PositionKey::of(&replay);
+        let mut solution = Solver::solve(&replay, search_limit);
+
+        let mut moves = Vec::with_capacity(records.len());
+        for (number, record) in (1..).zip(&records) {
+            replay_move(&mut replay, record)
+                .map_err(|e| format!("{}手目を再現できません: {}", number, e))?;
+            let after_key = PositionKey::of(&replay);
+            let after = if after_key == key {
+                solution
+            } else {
+                Solver::solve(&replay, search_limit)
+            };
+
+            moves.push(MoveAnalysis {
+                move_number: number,
+                suit: record.suit,
+                rank: record.rank,
+                from: record.from,
+                to: record.to,
+                quality: MoveQuality::judge(record, key, after_key, &solution, &after),
+                before: solution.winnability,
+                after: after.winnability,
+            });
+            key = after_key;
+            solution = after;
+        }
+
+        if key != PositionKey::of(world) {
+            return Err(
+                "配り札から現在の盤面を再現できません（パズル・シナリオの盤面は振り返りできません）"
+                    .to_string(),
+            );
+        }
+
+        let analysis = Self::new(moves);
+        info!(
+            "🔍 振り返り: 最善{} 良い{} 疑問手{} 悪手{}",
+            analysis.best, analysis.good, analysis.inaccuracies, analysis.blunders
+        );
+        Ok(analysis)
+    }
+}
+
+/// 移動履歴の1手を再現する
+///
+/// 振り返りのほか、クラッシュレポートの移動記録の再現にも使います。
+///
+/// # 引数
+/// * `world` - 再現中のワールドへの可変参照
+/// * `record` - 再現する手
+///
+/// # 戻り値
+/// 成功時はOk、打てない場合はエラーメッセージ
+pub fn replay_move(world: &mut World, record: &MoveRecord) -> Result<(), String> {
+    if record.from == CardLocation::Deck {
+        // ウェイストをデッキに戻す操作は移動履歴に残らないため、デッキが空なら先に戻す
+        let deck_is_empty = !world
+            .query::<SolitaireCard>()
+            .any(|(_, card)| card.location_type == CardLocation::Deck);
+        if deck_is_empty {
+            SolitaireManager::draw_card(world)?;
+        }
+        return SolitaireManager::draw_card(world);
+    }
+
+    // タブローからは動かしたカードより上のカードもまとめて動かす
+    let count = match record.from {
+        CardLocation::Tableau => {
+            let view = BoardView::from_world(world);
+            let column = view
+                .tableau
+                .get(record.from_index as usize)
+                .ok_or_else(|| format!("タブロー{}は存在しません", record.from_index + 1))?;
+            let start = column
+                .iter()
+                .position(|(_, card)| card.suit == record.suit && card.rank == record.rank)
+                .ok_or_else(|| "動かしたカードが移動元にありません".to_string())?;
+            column.len() - start
+        }
+        _ => 1,
+    };
+
+    HintEngine::move_cards(
+        world,
+        HintLocation {
+            location: record.from,
+            index: record.from_index,
+        },
+        count,
+        HintLocation {
+            location: record.to,
+            index: record.to_index,
+        },
+    )
+}
@@ -0,0 +1,211 @@
+// =============================================================================
+// カードの移動アニメーションの順番待ち
+// =============================================================================
+// このファイルでは、続けて打った手（自動プレイ・複数枚の移動・まとめて届いた手）の
+// アニメーションを同時に動かさずに、打った順に1手ずつ再生する仕組みを実装します。
+//
+// 仕組み：
+// - 1手分の移動（一緒に動くカードの移動先）を1つのステップとしてenqueue()で登録する
+//   （列の複数枚の移動は同じステップなので、重なり順を保ったまま一緒に動く）
+// - ステップは登録した順に番号が付き、前のステップのカードがすべて止まるまで次のステップは始まらない。
+//   同じカード・同じ山の移動が追い越したり、途中で混ざったりしない
+// - 順番待ちのカードはMoveQueueを持ち、表示座標は動き出すまで元の位置のまま。
+//   目標座標には最後の移動先を入れておくため、並び順・レイアウトは移動後の盤面で求まる
+// - fast_forward()で順番待ちをすべて飛ばし、最後の移動先へすぐに置く
+//   （再接続して遅れを取り戻すとき・自動プレイの前・アニメーションを省略する設定で使う）
+// =============================================================================
+
+use crate::ecs::{Component, Entity, Resource, System, World};
+use crate::game::GameSettings;
+use crate::solitaire::{SolitaireCard, CARD_ANIMATION_SPEED};
+use log::debug;
+use std::collections::VecDeque;
+
+/// 順番待ちの移動1つ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QueuedMove {
+    /// ステップの番号（登録した順）
+    pub step: u64,
+
+    /// 移動先のX座標
+    pub x: f32,
+
+    /// 移動先のY座標
+    pub y: f32,
+}
+
+/// 順番待ちの移動があるカードのコンポーネント
+///
+/// 移動を再生している間もこのコンポーネントが付いており、最後の移動を終えると外れます。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MoveQueue {
+    /// 順番待ちの移動（先頭が次に再生する移動）
+    pub moves: VecDeque<QueuedMove>,
+}
+
+impl Component for MoveQueue {}
+
+/// アニメーションの順番待ちのリソース
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnimationQueue {
+    /// 次に登録するステップの番号
+    next_step: u64,
+
+    /// 再生中のステップの番号
+    playing: u64,
+}
+
+impl Resource for AnimationQueue {}
+
+impl AnimationQueue {
+    /// 順番待ちのステップの数（再生中のステップを含む）
+    pub fn pending_steps(&self) -> u64 {
+        self.next_step - self.playing
+    }
+}
+
+/// 1手分の移動をステップとして順番待ちに登録する
+///
+/// カードの表示座標は動かさず、前のステップがすべて終わってから移動先へ動き出します。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `targets` - 一緒に動くカードの(エンティティ, 移動先のX座標, 移動先のY座標)
+pub fn enqueue(world: &mut World, targets: &[(Entity, f32, f32)]) {
+    if targets.is_empty() {
+        return;
+    }
+    if world.get_resource::<AnimationQueue>().is_none() {
+        world.insert_resource(AnimationQueue::default());
+    }
+    let Some(queue) = world.get_resource_mut::<AnimationQueue>() else {
+        return;
+    };
+    let step = queue.next_step;
+    queue.next_step += 1;
+
+    for &(entity, x, y) in targets {
+        let Some(card) = world.get_component_mut::<SolitaireCard>(entity) else {
+            continue;
+        };
+        card.start_animation(x, y);
+        let mut moves = world
+            .get_component::<MoveQueue>(entity)
+            .cloned()
+            .unwrap_or_default();
+        moves.moves.push_back(QueuedMove { step, x, y });
+        world.add_component(entity, moves);
+    }
+    debug!(
+        "🎞️ アニメーションを順番待ちに登録: ステップ{}（{}枚）",
+        step,
+        targets.len()
+    );
+}
+
+/// 順番待ちの移動をすべて飛ばし、カードを最後の移動先へすぐに置く
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+///
+/// # 戻り値
+/// 飛ばしたステップの数
+pub fn fast_forward(world: &mut World) -> u64 {
+    let queued: Vec<Entity> = world
+        .query::<MoveQueue>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in queued {
+        world.remove_component::<MoveQueue>(entity);
+        if let Some(card) = world.get_component_mut::<SolitaireCard>(entity) {
+            card.finish_animation();
+        }
+    }
+
+    let Some(queue) = world.get_resource_mut::<AnimationQueue>() else {
+        return 0;
+    };
+    let skipped = queue.pending_steps();
+    queue.playing = queue.next_step;
+    if skipped > 0 {
+        debug!(
+            "⏩ 順番待ちのアニメーションを{}ステップ飛ばしました",
+            skipped
+        );
+    }
+    skipped
+}
+
+/// アニメーションの順番待ちシステム
+///
+/// 再生中のステップのカードを移動先へ動かし、すべて止まったら次のステップを始めます。
+/// 移動の速さは通常のカードのアニメーションと同じくGameSettingsの設定に従います。
+pub struct AnimationQueueSystem;
+
+impl System for AnimationQueueSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        if world.get_resource::<AnimationQueue>().is_none() {
+            return;
+        }
+        let settings = world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.animation)
+            .unwrap_or_default();
+        if settings.instant {
+            fast_forward(world);
+            return;
+        }
+        let move_distance = CARD_ANIMATION_SPEED * settings.move_speed() * delta_time as f32;
+
+        let queued: Vec<(Entity, QueuedMove)> = world
+            .query::<MoveQueue>()
+            .filter_map(|(entity, queue)| queue.moves.front().map(|front| (entity, *front)))
+            .collect();
+        // 再生中のステップのカードがなければ、次に待っているステップを始める
+        let Some(current) = queued.iter().map(|(_, front)| front.step).min() else {
+            if let Some(queue) = world.get_resource_mut::<AnimationQueue>() {
+                queue.playing = queue.next_step;
+            }
+            return;
+        };
+
+        for (entity, front) in queued {
+            if front.step > current {
+                continue;
+            }
+            let Some(card) = world.get_component_mut::<SolitaireCard>(entity) else {
+                world.remove_component::<MoveQueue>(entity);
+                continue;
+            };
+            let dx = front.x - card.display_x;
+            let dy = front.y - card.display_y;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            // このフレームで移動先に届く場合は、行き過ぎないようにそのまま置く
+            if distance < 2.0 || move_distance >= distance {
+                card.set_display_position(front.x, front.y);
+                let last = world
+                    .get_component_mut::<MoveQueue>(entity)
+                    .map(|queue| {
+                        queue.moves.pop_front();
+                        queue.moves.is_empty()
+                    })
+                    .unwrap_or(true);
+                if last {
+                    world.remove_component::<MoveQueue>(entity);
+                    if let Some(card) = world.get_component_mut::<SolitaireCard>(entity) {
+                        card.finish_animation();
+                    }
+                }
+            } else {
+                let ratio = move_distance / distance;
+                card.display_x += dx * ratio;
+                card.display_y += dy * ratio;
+            }
+        }
+
+        if let Some(queue) = world.get_resource_mut::<AnimationQueue>() {
+            queue.playing = current;
+        }
+    }
+}
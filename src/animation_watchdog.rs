@@ -0,0 +1,136 @@
+// =============================================================================
+// 止まったアニメーションの見張り
+// =============================================================================
+// このファイルでは、移動先に近づかないまま止まってしまったカードのアニメーションを見つけて
+// 完了させるAnimationWatchdogSystemを実装します。
+// アニメーションの終わりを待つ処理（入力など）が、いつまでも待たされないようにします。
+//
+// 仕組み：
+// - アニメーション中のカードにAnimationWatchを付け、移動先までの距離をフレームごとに記録する
+// - 距離が縮まらなかったフレームを数え、STALL_FRAMESフレーム続いたら止まったとみなす
+//   （経過時間0のフレームと、順番待ちで前のステップを待っているカードは数えない）
+// - 止まったカードは順番待ち・合わせ直しの移動を外して移動先へ置き、
+//   AnimationStalledイベントで知らせる（不具合の調査用）
+// - 動かしていたシステムがなくなった（順番待ちが空のまま残ったなど）カードも同じように完了させる
+// =============================================================================
+
+use crate::animation_queue::MoveQueue;
+use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::reconcile::ReconcileTween;
+use crate::solitaire::SolitaireCard;
+use log::warn;
+
+/// 止まったとみなすまでの、移動先に近づかなかったフレーム数
+pub const STALL_FRAMES: u32 = 60;
+
+/// 近づいたとみなす距離の差（ピクセル）
+const PROGRESS_EPSILON: f32 = 0.01;
+
+/// アニメーション中のカードの進み具合の記録のコンポーネント
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationWatch {
+    /// 前のフレームでの移動先までの距離（ピクセル）
+    pub distance: f32,
+
+    /// 移動先に近づかなかったフレームが続いた数
+    pub stalled_frames: u32,
+}
+
+impl Component for AnimationWatch {}
+
+/// 止まったアニメーションの見張りシステム
+///
+/// アニメーションのシステムの後に実行し、止まったカードを移動先へ置きます。
+pub struct AnimationWatchdogSystem;
+
+impl System for AnimationWatchdogSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        // アニメーションが終わったカードの記録を外す
+        let finished: Vec<Entity> = world
+            .query::<AnimationWatch>()
+            .filter(|(entity, _)| {
+                !world
+                    .get_component::<SolitaireCard>(*entity)
+                    .is_some_and(|card| card.is_animating)
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in finished {
+            world.remove_component::<AnimationWatch>(entity);
+        }
+        if delta_time <= 0.0 {
+            return;
+        }
+
+        // 順番待ちで前のステップを待っているカードは動かないのが正しい
+        let playing_step = world
+            .query::<MoveQueue>()
+            .filter_map(|(_, queue)| queue.moves.front().map(|front| front.step))
+            .min();
+        let animating: Vec<(Entity, f32)> = world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.is_animating)
+            .filter(|(entity, _)| {
+                world
+                    .get_component::<MoveQueue>(*entity)
+                    .and_then(|queue| queue.moves.front())
+                    .is_none_or(|front| Some(front.step) == playing_step)
+            })
+            .map(|(entity, card)| (entity, distance_to_target(card)))
+            .collect();
+
+        let mut stalled = Vec::new();
+        for (entity, distance) in animating {
+            let watch = match world.get_component::<AnimationWatch>(entity) {
+                Some(watch) if watch.distance - distance <= PROGRESS_EPSILON => AnimationWatch {
+                    distance,
+                    stalled_frames: watch.stalled_frames + 1,
+                },
+                _ => AnimationWatch {
+                    distance,
+                    stalled_frames: 0,
+                },
+            };
+            if watch.stalled_frames >= STALL_FRAMES {
+                stalled.push((entity, watch.stalled_frames));
+            } else {
+                world.add_component(entity, watch);
+            }
+        }
+
+        for (entity, stalled_frames) in stalled {
+            force_complete(world, entity, stalled_frames);
+        }
+    }
+}
+
+/// カードの表示座標から移動先までの距離
+fn distance_to_target(card: &SolitaireCard) -> f32 {
+    let dx = card.target_x - card.display_x;
+    let dy = card.target_y - card.display_y;
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// 止まったカードを移動先へ置いて知らせる
+fn force_complete(world: &mut World, entity: Entity, stalled_frames: u32) {
+    world.remove_component::<AnimationWatch>(entity);
+    world.remove_component::<MoveQueue>(entity);
+    world.remove_component::<ReconcileTween>(entity);
+    let Some(card) = world.get_component_mut::<SolitaireCard>(entity) else {
+        return;
+    };
+    card.finish_animation();
+    warn!(
+        "🩺 止まったアニメーションを完了させました: {}{}（{}フレーム動かず）",
+        card.suit.symbol(),
+        card.rank.display(),
+        stalled_frames
+    );
+    if let Some(events) = world.get_resource_mut::<EventQueue>() {
+        events.push(GameEvent::AnimationStalled {
+            card_id: entity.0,
+            stalled_frames,
+        });
+    }
+}
@@ -0,0 +1,397 @@
+// =============================================================================
+// インスタンス間のバックプレーン（サーバー用）
+// =============================================================================
+// このファイルでは、複数のサーバーインスタンスでルームを共有するための
+// Redisのpub/subを使ったバックプレーンを実装します。
+//
+// 仕組み：
+// - 各インスタンスはBACKPLANE_CHANNELを購読し、同じチャンネルに発行する
+// - ルームへの配信は自分の接続に送ったあとバックプレーンにも発行し、
+//   受け取った他のインスタンスはそのルームにいる自分の接続に送る
+// - 各インスタンスはANNOUNCE_INTERVAL_MSごとに自分のルーム一覧を発行し、
+//   他のインスタンスのルームもルーム一覧に載せる（REMOTE_ROOM_TTL_MS届かなければ外す）
+// - 他のインスタンスのルームに参加すると、同じIDのルームを手元に作って参加する
+//   （ホスト・準備完了などの状態は各インスタンスで管理し、配信だけを中継する）
+// - 手元に作ったルームの名前・定員・状態などは、管理しているインスタンスのルーム一覧が届くたびに合わせる
+//   （手元に作ったルームは自分のルーム一覧には載せない）
+// - 1つのイベントの大きさはMAX_PAYLOAD_BYTESまで（超えるものは発行せず、届いても読み捨てる）
+//
+// バックプレーンを設定しない場合は1台のインスタンスでルームを管理するため、
+// ロードバランサーでは同じルームのプレイヤーが同じインスタンスにつながるよう
+// スティッキーセッションを設定してください。
+//
+// Redisとの通信にはRESP（Redisのテキストプロトコル）のうち、
+// PUBLISHとSUBSCRIBEに必要な部分だけを実装しています。
+// =============================================================================
+
+use crate::protocol::RoomInfo;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use uuid::Uuid;
+
+/// インスタンス間のやり取りに使うチャンネル
+const BACKPLANE_CHANNEL: &str = "solitaire:backplane";
+
+/// Redisの既定のポート番号
+const DEFAULT_REDIS_PORT: u16 = 6379;
+
+/// ルーム一覧を発行する間隔（ミリ秒）
+pub const ANNOUNCE_INTERVAL_MS: u64 = 1000;
+
+/// 他のインスタンスのルーム一覧が届かなくなってから一覧から外すまでの時間（ミリ秒）
+pub const REMOTE_ROOM_TTL_MS: u64 = 5000;
+
+/// 発行・受信する1つのイベントの大きさの上限（バイト）
+pub const MAX_PAYLOAD_BYTES: usize = 1024 * 1024;
+
+/// 受信するRESPの配列の要素数の上限（購読のメッセージは3要素）
+const MAX_ARRAY_ITEMS: i64 = 16;
+
+/// インスタンス間でやり取りするイベント
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum BackplaneEvent {
+    /// ルームへの配信（messageはクライアントに送るJSON文字列そのまま）
+    RoomBroadcast {
+        origin: String,
+        room_id: String,
+        message: String,
+        exclude_player: Option<String>,
+    },
+
+    /// 発行したインスタンスが管理しているルームの一覧
+    RoomRegistry {
+        origin: String,
+        rooms: Vec<RoomInfo>,
+    },
+}
+
+impl BackplaneEvent {
+    /// イベントを発行したインスタンスのID
+    pub fn origin(&self) -> &str {
+        match self {
+            BackplaneEvent::RoomBroadcast { origin, .. }
+            | BackplaneEvent::RoomRegistry { origin, .. } => origin,
+        }
+    }
+}
+
+/// 他のインスタンスのルーム一覧
+#[derive(Debug, Default)]
+pub struct RemoteRooms {
+    /// インスタンスID → (一覧を受け取った時刻, ルーム一覧)
+    instances: HashMap<String, (u64, Vec<RoomInfo>)>,
+}
+
+impl RemoteRooms {
+    /// インスタンスから届いたルーム一覧で置き換える
+    ///
+    /// # 引数
+    /// * `origin` - 発行したインスタンスのID
+    /// * `rooms` - そのインスタンスのルーム一覧
+    /// * `now_ms` - 受け取った時刻（UNIX時刻、ミリ秒）
+    pub fn update(&mut self, origin: &str, rooms: Vec<RoomInfo>, now_ms: u64) {
+        self.instances.insert(origin.to_string(), (now_ms, rooms));
+    }
+
+    /// 一覧に載せる他のインスタンスのルーム（同じIDのルームは1つにまとめる）
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    pub fn list(&self, now_ms: u64) -> Vec<RoomInfo> {
+        let mut rooms: Vec<RoomInfo> = Vec::new();
+        let live = self
+            .instances
+            .values()
+            .filter(|(received_at, _)| now_ms.saturating_sub(*received_at) < REMOTE_ROOM_TTL_MS);
+        for (_, instance_rooms) in live {
+            for room in instance_rooms {
+                if !rooms.iter().any(|listed| listed.id == room.id) {
+                    rooms.push(room.clone());
+                }
+            }
+        }
+        rooms
+    }
+
+    /// 他のインスタンスのルームをIDで探す
+    ///
+    /// # 戻り値
+    /// ルームを管理しているインスタンスのIDとルームの情報（見つからない場合はNone）
+    pub fn find(&self, room_id: &str, now_ms: u64) -> Option<(String, RoomInfo)> {
+        self.instances
+            .iter()
+            .filter(|(_, (received_at, _))| {
+                now_ms.saturating_sub(*received_at) < REMOTE_ROOM_TTL_MS
+            })
+            .find_map(|(origin, (_, rooms))| {
+                rooms
+                    .iter()
+                    .find(|room| room.id == room_id)
+                    .map(|room| (origin.clone(), room.clone()))
+            })
+    }
+}
+
+/// バックプレーンへの接続
+#[derive(Debug, Clone)]
+pub struct Backplane {
+    /// このインスタンスのID（自分が発行したイベントを無視するのに使う）
+    instance_id: String,
+
+    /// 発行するイベントの送信先（発行用の接続のタスクが受け取る）
+    outgoing: UnboundedSender<String>,
+}
+
+impl Backplane {
+    /// Redisに接続し、チャンネルを購読する
+    ///
+    /// 発行用と購読用の2本の接続を開き、それぞれを別タスクで処理します。
+    ///
+    /// # 引数
+    /// * `url` - 接続先（redis://ホスト:ポート）
+    ///
+    /// # 戻り値
+    /// 接続とイベントの受信側、接続できない場合はエラーメッセージ
+    pub async fn connect(url: &str) -> Result<(Self, UnboundedReceiver<BackplaneEvent>), String> {
+        let addr = parse_redis_url(url)?;
+        let publisher = TcpStream::connect(&addr)
+            .await
+            .map_err(|e| format!("バックプレーンに接続できません ({}): {}", addr, e))?;
+        let mut subscriber = TcpStream::connect(&addr)
+            .await
+            .map_err(|e| format!("バックプレーンに接続できません ({}): {}", addr, e))?;
+
+        // 購読の確定を待ってから返す（直後に発行されたイベントを取りこぼさないように）
+        subscriber
+            .write_all(&encode_command(&["SUBSCRIBE", BACKPLANE_CHANNEL]))
+            .await
+            .map_err(|e| format!("購読要求の送信失敗: {}", e))?;
+        let mut subscriber = BufReader::new(subscriber);
+        read_value(&mut subscriber).await?;
+
+        let instance_id = Uuid::new_v4().to_string();
+        let (outgoing, outgoing_receiver) = mpsc::unbounded_channel();
+        let (events, event_receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run_publisher(publisher, outgoing_receiver));
+        tokio::spawn(run_subscriber(subscriber, instance_id.clone(), events));
+
+        info!(
+            "🛰️ バックプレーンに接続しました: {} (インスタンス{})",
+            addr, instance_id
+        );
+        Ok((
+            Self {
+                instance_id,
+                outgoing,
+            },
+            event_receiver,
+        ))
+    }
+
+    /// このインスタンスのID
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// イベントを他のインスタンスに発行する
+    pub fn publish(&self, event: &BackplaneEvent) {
+        match serde_json::to_string(event) {
+            Ok(payload) if payload.len() > MAX_PAYLOAD_BYTES => warn!(
+                "⚠️ バックプレーンのイベントが大きすぎるため発行しません ({}バイト)",
+                payload.len()
+            ),
+            Ok(payload) => {
+                if self.outgoing.send(payload).is_err() {
+                    warn!("⚠️ バックプレーンへの接続が切れているため発行できません");
+                }
+            }
+            Err(e) => error!(
+                "❌ バックプレーンのイベントのシリアライゼーションエラー: {}",
+                e
+            ),
+        }
+    }
+}
+
+/// 発行用の接続で、送られてきたイベントを順にPUBLISHする
+async fn run_publisher(stream: TcpStream, mut outgoing: UnboundedReceiver<String>) {
+    let mut stream = BufReader::new(stream);
+    while let Some(payload) = outgoing.recv().await {
+        let command = encode_command(&["PUBLISH", BACKPLANE_CHANNEL, &payload]);
+        if let Err(e) = stream.get_mut().write_all(&command).await {
+            error!("❌ バックプレーンへの発行失敗: {}", e);
+            return;
+        }
+        match read_value(&mut stream).await {
+            Ok(RespValue::Error(message)) => {
+                warn!("⚠️ バックプレーンがエラーを返しました: {}", message)
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("❌ バックプレーンの応答を読めません: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// 購読用の接続で、他のインスタンスが発行したイベントを受け取り続ける
+async fn run_subscriber(
+    mut stream: BufReader<TcpStream>,
+    instance_id: String,
+    events: UnboundedSender<BackplaneEvent>,
+) {
+    loop {
+        let value = match read_value(&mut stream).await {
+            Ok(value) => value,
+            Err(e) => {
+                error!("❌ バックプレーンの購読が切れました: {}", e);
+                return;
+            }
+        };
+        let RespValue::Array(items) = value else {
+            continue;
+        };
+        let payload = match items.as_slice() {
+            [RespValue::Bulk(Some(kind)), _, RespValue::Bulk(Some(payload))]
+                if kind.as_slice() == b"message" =>
+            {
+                payload
+            }
+            _ => continue,
+        };
+
+        match serde_json::from_slice::<BackplaneEvent>(payload) {
+            Ok(event) if event.origin() == instance_id => {}
+            Ok(event) => {
+                if events.send(event).is_err() {
+                    return;
+                }
+            }
+            Err(e) => warn!("⚠️ バックプレーンのイベントの形式が不正です: {}", e),
+        }
+    }
+}
+
+/// 接続先のURL（redis://ホスト[:ポート]）からアドレスを取り出す
+fn parse_redis_url(url: &str) -> Result<String, String> {
+    let host = url
+        .strip_prefix("redis://")
+        .ok_or_else(|| format!("対応していないバックプレーンのURLです: {}", url))?
+        .trim_end_matches('/');
+    if host.is_empty() {
+        return Err("バックプレーンのホストが指定されていません".to_string());
+    }
+    if host.contains(':') {
+        Ok(host.to_string())
+    } else {
+        Ok(format!("{}:{}", host, DEFAULT_REDIS_PORT))
+    }
+}
+
+// =============================================================================
+// RESP（Redisのプロトコル）
+// =============================================================================
+
+/// RESPの値（配列は1段のみ扱う）
+#[derive(Debug, Clone, PartialEq)]
+enum RespValue {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+    Array(Vec<RespValue>),
+}
+
+/// コマンドをRESPの配列に変換
+fn encode_command(args: &[&str]) -> Vec<u8> {
+    let mut command = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        command.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        command.extend_from_slice(arg.as_bytes());
+        command.extend_from_slice(b"\r\n");
+    }
+    command
+}
+
+/// 値を1つ読み込む
+async fn read_value<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<RespValue, String> {
+    let line = read_line(reader).await?;
+    if let Some(count) = line.strip_prefix('*') {
+        let count: i64 = count
+            .parse()
+            .map_err(|_| format!("配列の長さが不正です: {}", line))?;
+        if count > MAX_ARRAY_ITEMS {
+            return Err(format!("配列の要素が多すぎます: {}", line));
+        }
+        let mut items = Vec::new();
+        for _ in 0..count.max(0) {
+            let item_line = read_line(reader).await?;
+            items.push(read_scalar(reader, &item_line).await?);
+        }
+        return Ok(RespValue::Array(items));
+    }
+    read_scalar(reader, &line).await
+}
+
+/// 配列以外の値を読み込む（先頭の行は読み込み済み）
+async fn read_scalar<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &str,
+) -> Result<RespValue, String> {
+    let (kind, rest) = line.split_at(line.len().min(1));
+    match kind {
+        "+" => Ok(RespValue::Simple(rest.to_string())),
+        "-" => Ok(RespValue::Error(rest.to_string())),
+        ":" => rest
+            .parse()
+            .map(RespValue::Integer)
+            .map_err(|_| format!("整数が不正です: {}", line)),
+        "$" => {
+            let length: i64 = rest
+                .parse()
+                .map_err(|_| format!("文字列の長さが不正です: {}", line))?;
+            if length < 0 {
+                return Ok(RespValue::Bulk(None));
+            }
+            // 大きすぎる文字列はメモリに読み込まずに捨てる（次の値から読み続けられるように中身は読み飛ばす）
+            if length as usize > MAX_PAYLOAD_BYTES {
+                let mut rest = reader.take(length as u64 + 2);
+                tokio::io::copy(&mut rest, &mut tokio::io::sink())
+                    .await
+                    .map_err(|e| format!("バックプレーンからの読み込み失敗: {}", e))?;
+                warn!(
+                    "⚠️ バックプレーンのイベントが大きすぎるため捨てました ({}バイト)",
+                    length
+                );
+                return Ok(RespValue::Bulk(None));
+            }
+            let mut data = vec![0; length as usize + 2];
+            reader
+                .read_exact(&mut data)
+                .await
+                .map_err(|e| format!("バックプレーンからの読み込み失敗: {}", e))?;
+            data.truncate(length as usize);
+            Ok(RespValue::Bulk(Some(data)))
+        }
+        _ => Err(format!("対応していない値です: {}", line)),
+    }
+}
+
+/// 1行読み込む（末尾の改行は除く）
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<String, String> {
+    let mut line = String::new();
+    let read = reader
+        .read_line(&mut line)
+        .await
+        .map_err(|e| format!("バックプレーンからの読み込み失敗: {}", e))?;
+    if read == 0 {
+        return Err("バックプレーンとの接続が閉じられました".to_string());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
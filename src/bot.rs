@@ -0,0 +1,240 @@
+// =============================================================================
+// ボット対戦相手（サーバー用）
+// =============================================================================
+// このファイルでは、サーバー上で人間の代わりにソリティアをプレイする
+// ボットプレイヤーを実装します。
+//
+// 仕組み：
+// - 人間のプレイヤーと同じシードで配り札を再現し（ミラーディール）、
+//   ヒントエンジンが選んだ手を一定間隔で打つ
+// - 強さは「ミス確率」で調整する（ミスした時は最善手以外の合法手を選ぶ）
+// - 打った手は人間と同じGameActionとして、終了時はGameResultとして配信される
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Entity, World};
+use crate::hint::{HintEngine, HintKind};
+use crate::rng::Rng;
+use crate::solitaire::{CardLocation, SolitaireCard, SolitaireGameState, SolitaireManager, SolitaireType};
+use serde::{Deserialize, Serialize};
+
+/// クロンダイクでファウンデーションに置く必要があるカード枚数
+const CARDS_TO_WIN: usize = 52;
+
+/// 1ゲームで打つ手の上限（念のための無限ループ防止）
+const MAX_STEPS: u32 = 2000;
+
+/// ボットの設定
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BotConfig {
+    /// 1秒あたりに打つ手の数
+    pub moves_per_second: f64,
+
+    /// 最善手以外を選ぶ確率（0.0 = 常に最善手、1.0 = 常に別の手）
+    pub mistake_probability: f64,
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self {
+            moves_per_second: 1.0,
+            mistake_probability: 0.1,
+        }
+    }
+}
+
+impl BotConfig {
+    /// 値を有効な範囲に収めた設定を作成
+    ///
+    /// # 引数
+    /// * `moves_per_second` - 1秒あたりの手数（Noneの場合は既定値）
+    /// * `mistake_probability` - ミス確率（Noneの場合は既定値）
+    ///
+    /// 有限でない値（NaN、無限大）は指定がない場合と同じく既定値になります。
+    ///
+    /// # 戻り値
+    /// 有効範囲に収めたBotConfig
+    pub fn new(moves_per_second: Option<f64>, mistake_probability: Option<f64>) -> Self {
+        let default = Self::default();
+        Self {
+            // NaNや無限大はclampで範囲に収まらないため、既定値として扱う
+            moves_per_second: moves_per_second
+                .filter(|value| value.is_finite())
+                .unwrap_or(default.moves_per_second)
+                .clamp(0.1, 20.0),
+            mistake_probability: mistake_probability
+                .filter(|value| value.is_finite())
+                .unwrap_or(default.mistake_probability)
+                .clamp(0.0, 1.0),
+        }
+    }
+
+    /// 手と手の間の待ち時間を取得
+    pub fn move_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f64(1.0 / self.moves_per_second)
+    }
+}
+
+/// ボットが1手進めた結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum BotStep {
+    /// 手を打った（アクション名）
+    Moved { action: String },
+
+    /// ゲームが終了した（クライアントと同じ形式のゲーム結果JSON）
+    Finished { won: bool, result: serde_json::Value },
+}
+
+/// ボットプレイヤー
+///
+/// 自分専用のECSワールドを持ち、その中でゲームを進めます。
+pub struct BotPlayer {
+    /// ボットの設定
+    config: BotConfig,
+
+    /// ボット専用のECSワールド
+    world: World,
+
+    /// ゲーム状態エンティティ
+    game_entity: Entity,
+
+    /// ミス判定用の乱数生成器
+    rng: Rng,
+
+    /// 「引く」しか打てない状態が続いた回数（手詰まり判定用）
+    forced_draws: usize,
+
+    /// 打った手の数
+    steps: u32,
+}
+
+impl BotPlayer {
+    /// 指定シードのゲームを開始したボットを作成
+    ///
+    /// # 引数
+    /// * `config` - ボットの設定
+    /// * `seed` - 配り札のシード値（対戦相手と同じ値を使う）
+    ///
+    /// # 戻り値
+    /// ゲーム開始済みのBotPlayerインスタンス
+    pub fn new(config: BotConfig, seed: u64) -> Self {
+        let mut world = World::new();
+        let game_entity =
+            SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+
+        Self {
+            config,
+            world,
+            game_entity,
+            // 盤面のシードと同じ値だと判断が配り札と相関するため、定数と混ぜる
+            rng: Rng::new(seed ^ 0x2545_F491_4F6C_DD1D),
+            forced_draws: 0,
+            steps: 0,
+        }
+    }
+
+    /// 1手進める
+    ///
+    /// # 戻り値
+    /// 打った手、またはゲーム終了の結果
+    pub fn step(&mut self) -> BotStep {
+        let moves = HintEngine::all_moves(&self.world);
+
+        // 打てる手がない、またはデッキを一巡しても「引く」しかない場合は手詰まり
+        if moves.is_empty() || self.steps >= MAX_STEPS {
+            return self.finish(false);
+        }
+        if moves.len() == 1 && moves[0].kind == HintKind::Draw {
+            self.forced_draws += 1;
+            if self.forced_draws > HintEngine::draw_cycle_length(&self.world) {
+                return self.finish(false);
+            }
+        } else {
+            self.forced_draws = 0;
+        }
+
+        // ミス確率に応じて最善手以外の手を選ぶ
+        let index = if moves.len() > 1 && self.rng.next_f64() < self.config.mistake_probability {
+            1 + (self.rng.next_f64() * (moves.len() - 1) as f64) as usize
+        } else {
+            0
+        };
+        let chosen = &moves[index.min(moves.len() - 1)];
+
+        if !HintEngine::apply(&mut self.world, chosen) {
+            return self.finish(false);
+        }
+        self.steps += 1;
+
+        if self.foundation_count() == CARDS_TO_WIN {
+            return self.finish(true);
+        }
+
+        let action = match chosen.kind {
+            HintKind::Draw => "draw".to_string(),
+            HintKind::Move => match chosen.to.map(|to| to.location) {
+                Some(CardLocation::Foundation) => "move_to_foundation".to_string(),
+                _ => "move_to_tableau".to_string(),
+            },
+        };
+        BotStep::Moved { action }
+    }
+
+    /// ファウンデーションに置かれたカード枚数を数える
+    fn foundation_count(&self) -> usize {
+        self.world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.location_type == CardLocation::Foundation)
+            .count()
+    }
+
+    /// ゲームを終了し、結果を作成
+    fn finish(&mut self, won: bool) -> BotStep {
+        let clock = GameClock::from_world(&self.world);
+        let result = match self
+            .world
+            .get_component_mut::<SolitaireGameState>(self.game_entity)
+        {
+            Some(state) => {
+                state.finish_game(won, &clock);
+                game_result_json(state, &clock)
+            }
+            None => serde_json::Value::Null,
+        };
+
+        BotStep::Finished { won, result }
+    }
+}
+
+/// ゲーム状態からクライアントと同じ形式のゲーム結果JSONを作成
+///
+/// クライアントのGameResultと同じフィールド名を使うため、
+/// サーバーはボットと人間の結果を区別せずに扱えます。
+fn game_result_json(state: &SolitaireGameState, clock: &GameClock) -> serde_json::Value {
+    let score = state.score_breakdown.map_or_else(
+        || {
+            serde_json::json!({
+                "base_score": state.score,
+                "time_bonus": 0,
+                "move_penalty": 0,
+                "final_score": state.score,
+            })
+        },
+        |breakdown| serde_json::to_value(breakdown).unwrap_or_default(),
+    );
+
+    serde_json::json!({
+        "outcome": if state.is_won { "Won" } else { "Lost" },
+        "game_type": state.game_type,
+        "seed": state.seed,
+        "score": score,
+        "move_count": state.move_count,
+        "deck_turns": state.deck_turns,
+        "duration_seconds": state.elapsed_seconds(clock),
+        "hints_used": 0,
+        "undos_used": 0,
+        "solver_optimal_moves": null,
+        "efficiency": null,
+        "finished_at": state.end_time,
+    })
+}
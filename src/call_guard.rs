@@ -0,0 +1,72 @@
+// =============================================================================
+// 呼び出し順序のチェック（厳格モード）
+// =============================================================================
+// このファイルでは、JavaScriptからの呼び出しがありえない順序で届いたこと
+// （initialize_game()の前の操作、相手のターン中の操作、アニメーション中の盤面の置き換えなど）を
+// 見つけて、理由を説明するエラーメッセージを作るチェックを実装します。
+//
+// 仕組み：
+// - 呼び出しを種類（CallKind）に分け、種類ごとに呼び出せる状態かを確かめる
+// - 厳格モードでは、チェックに通らない呼び出しはJavaScriptの例外にして何もしない
+//   （フロントエンドの組み込みの不具合が、状態を壊す前にすぐに分かるようにする）
+// - 厳格モードでない場合は警告のログだけを出し、これまでどおり処理を続ける
+// =============================================================================
+
+use crate::runtime::GameRuntime;
+
+/// 呼び出しの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallKind {
+    /// セッションを使う呼び出し（initialize_game()の後に呼ぶ）
+    Session,
+
+    /// 盤面を操作する手（ゲームの開始後、自分のターン中に呼ぶ）
+    Move,
+
+    /// 盤面を置き換える呼び出し（カードのアニメーションが終わってから呼ぶ）
+    ReplaceBoard,
+}
+
+/// 呼び出しが届いた順序をチェック
+///
+/// # 引数
+/// * `runtime` - 呼び出し先のセッションのランタイム（セッションがない場合はNone）
+/// * `call` - 呼び出された関数名（エラーメッセージ用）
+/// * `kind` - 呼び出しの種類
+///
+/// # 戻り値
+/// 呼び出せる状態の場合はOk、ありえない順序の場合は理由を表すエラーメッセージ
+pub fn check(runtime: Option<&GameRuntime>, call: &str, kind: CallKind) -> Result<(), String> {
+    let Some(runtime) = runtime else {
+        return Err(format!(
+            "{}()がinitialize_game()より前に呼ばれました（セッションが作成されていません）",
+            call
+        ));
+    };
+
+    match kind {
+        CallKind::Session => Ok(()),
+        CallKind::Move => {
+            if runtime.game_state().is_none() {
+                return Err(format!("{}()がゲームの開始前に呼ばれました", call));
+            }
+            if runtime.network.is_opponents_turn() {
+                return Err(format!(
+                    "{}()が他のプレイヤー（{}）のターン中に呼ばれました",
+                    call,
+                    runtime.network.turn_player_id().unwrap_or_default()
+                ));
+            }
+            Ok(())
+        }
+        CallKind::ReplaceBoard => {
+            if runtime.is_animating() {
+                return Err(format!(
+                    "{}()がカードのアニメーション中に呼ばれました（終わるのを待つか、skip_animations()を先に呼んでください）",
+                    call
+                ));
+            }
+            Ok(())
+        }
+    }
+}
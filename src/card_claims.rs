@@ -0,0 +1,145 @@
+// =============================================================================
+// カードの取り合いの判定（サーバー用）
+// =============================================================================
+// このファイルでは、協力プレイで複数のプレイヤーが同じカードをほぼ同時に
+// 掴んだ場合に、どちらが掴んだことにするかを判定するCardClaimsを実装します。
+//
+// 仕組み：
+// - 掴んだ時刻はクライアントの時計で送られてくるため、時計のずれを補正して
+//   サーバーの時刻に直した値（遅延補正済みの時刻）で比べる
+// - 補正できるのは受信時刻からLAG_COMPENSATION_MSまで前に限る
+//   （それより前の時刻を名乗っても、その時刻に掴んだことにはならない）
+// - 最初の掴みが届いてからLAG_COMPENSATION_MS以内に、より早い時刻の掴みが届いた場合は
+//   後から届いた方が取り、先に掴んでいたプレイヤーには元の位置に戻すよう知らせる
+// - それより後に届いた掴みは、掴んでいるプレイヤーがいる限り断る
+// =============================================================================
+
+use std::collections::HashMap;
+
+/// 遅延を補正する時間の上限（ミリ秒）
+///
+/// 届いた順ではなく掴んだ時刻で判定するのは、この時間内に届いた掴み同士だけです。
+pub const LAG_COMPENSATION_MS: u64 = 250;
+
+/// カードを掴んでいる状態
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardClaim {
+    /// 掴んでいるプレイヤーのID
+    pub player_id: String,
+
+    /// 掴んだ時刻（遅延補正済み、サーバーの時刻）
+    pub claimed_at_ms: u64,
+
+    /// 掴みがサーバーに届いた時刻
+    pub received_at_ms: u64,
+}
+
+/// 掴みの判定結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimOutcome {
+    /// 誰も掴んでいなかったので掴めた（本人が掴み直した場合も含む）
+    Granted,
+
+    /// 先に届いた掴みより早く掴んでいたので、取り上げて掴めた
+    Overtook { previous_owner: String },
+
+    /// 他のプレイヤーが先に掴んでいたので掴めなかった
+    Rejected { owner: String },
+}
+
+/// ルーム内で掴まれているカードの一覧
+#[derive(Debug, Clone, Default)]
+pub struct CardClaims {
+    /// カードID → 掴んでいる状態
+    claims: HashMap<String, CardClaim>,
+}
+
+impl CardClaims {
+    /// カードを掴む
+    ///
+    /// # 引数
+    /// * `card_id` - カードID
+    /// * `player_id` - 掴んだプレイヤーのID
+    /// * `claimed_at_ms` - 掴んだ時刻（サーバーの時刻に直したもの）
+    /// * `received_at_ms` - 掴みがサーバーに届いた時刻
+    ///
+    /// # 戻り値
+    /// 掴めたかどうかの判定結果
+    pub fn claim(
+        &mut self,
+        card_id: &str,
+        player_id: &str,
+        claimed_at_ms: u64,
+        received_at_ms: u64,
+    ) -> ClaimOutcome {
+        let claimed_at_ms = claimed_at_ms.clamp(
+            received_at_ms.saturating_sub(LAG_COMPENSATION_MS),
+            received_at_ms,
+        );
+        let claim = CardClaim {
+            player_id: player_id.to_string(),
+            claimed_at_ms,
+            received_at_ms,
+        };
+
+        let Some(current) = self.claims.get(card_id) else {
+            self.claims.insert(card_id.to_string(), claim);
+            return ClaimOutcome::Granted;
+        };
+        if current.player_id == player_id {
+            return ClaimOutcome::Granted;
+        }
+
+        let contested =
+            received_at_ms.saturating_sub(current.received_at_ms) <= LAG_COMPENSATION_MS;
+        if contested && claimed_at_ms < current.claimed_at_ms {
+            let previous_owner = current.player_id.clone();
+            self.claims.insert(card_id.to_string(), claim);
+            ClaimOutcome::Overtook { previous_owner }
+        } else {
+            ClaimOutcome::Rejected {
+                owner: current.player_id.clone(),
+            }
+        }
+    }
+
+    /// カードを離す
+    ///
+    /// # 引数
+    /// * `card_id` - カードID
+    /// * `player_id` - 離したプレイヤーのID
+    ///
+    /// # 戻り値
+    /// 本人が掴んでいたカードを離せた場合true
+    pub fn release(&mut self, card_id: &str, player_id: &str) -> bool {
+        if self.owner(card_id) != Some(player_id) {
+            return false;
+        }
+        self.claims.remove(card_id);
+        true
+    }
+
+    /// プレイヤーが掴んでいるカードをすべて離す（退室時）
+    ///
+    /// # 戻り値
+    /// 離したカードのID
+    pub fn release_all(&mut self, player_id: &str) -> Vec<String> {
+        let released: Vec<String> = self
+            .claims
+            .iter()
+            .filter(|(_, claim)| claim.player_id == player_id)
+            .map(|(card_id, _)| card_id.clone())
+            .collect();
+        for card_id in &released {
+            self.claims.remove(card_id);
+        }
+        released
+    }
+
+    /// カードを掴んでいるプレイヤーのID
+    pub fn owner(&self, card_id: &str) -> Option<&str> {
+        self.claims
+            .get(card_id)
+            .map(|claim| claim.player_id.as_str())
+    }
+}
@@ -0,0 +1,403 @@
+// =============================================================================
+// クライアント向けゲーム状態
+// =============================================================================
+// このファイルでは、get_solitaire_state()がフロントエンドへ返す
+// ゲーム状態（JSON）の形を型として定義します。
+//
+// 主要な責務：
+// - 盤面（山札・捨て札・組札・場札）、スコア、進行状況、設定、テーマ、表示領域の型定義
+// - ECSワールドからクライアント向け状態への変換
+// - フロントエンドで検証に使うJSON Schemaの生成
+//
+// JSONの形を変える場合は STATE_SCHEMA_VERSION を上げてください。
+// フロントエンドはschema_versionを見て、想定外の形式を検知できます。
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Entity, World};
+use crate::hint::HintLocation;
+use crate::i18n::{self, FormattedStats};
+use crate::permissions;
+use crate::selection::{DropTarget, Highlighted, Selected};
+use crate::solitaire::{
+    CardLocation, CardRank, CardStack, CardSuit, ScoreBreakdown, SolitaireCard, SolitaireGameState,
+    SolitaireType,
+};
+use crate::theme::{SuitColor, SuitPattern, Theme};
+use crate::tutorial::TutorialProgress;
+use crate::viewport::Viewport;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// クライアント向け状態JSONのスキーマバージョン
+pub const STATE_SCHEMA_VERSION: u32 = 9;
+
+/// タブロー（場札）の列数
+const TABLEAU_COLUMNS: usize = 7;
+
+/// ファウンデーション（組札）の数
+const FOUNDATION_PILES: usize = 4;
+
+/// 1回にデッキから引く枚数（現在は1枚引きのみ対応）
+const DRAW_COUNT: u32 = 1;
+
+/// ゲームの進行段階
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum GamePhase {
+    /// ゲーム開始前
+    NotStarted,
+
+    /// プレイ中
+    Playing,
+
+    /// 勝利して終了
+    Won,
+
+    /// 敗北（ギブアップ）して終了
+    Lost,
+}
+
+/// 1枚のカードの表示情報
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct CardView {
+    /// カードのエンティティID（移動操作でカードを指定するために使う）
+    pub id: u32,
+
+    /// スート（絵柄）
+    pub suit: CardSuit,
+
+    /// ランク（数値・絵札）
+    pub rank: CardRank,
+
+    /// スートの記号・ランクを描く色（テーマの4色のスートの設定に従う）
+    pub color: SuitColor,
+
+    /// カードの表面を塗る模様（テーマで模様を使わない設定の場合はNone）
+    pub pattern: Option<SuitPattern>,
+
+    /// ランクとスートの記号を描く大きさの倍率（テーマの大きなランク表示の設定に従う）
+    pub rank_scale: f32,
+
+    /// 表向きかどうか
+    pub face_up: bool,
+
+    /// 表示X座標（アニメーション中は途中の座標）
+    pub x: f32,
+
+    /// 表示Y座標（アニメーション中は途中の座標）
+    pub y: f32,
+
+    /// アニメーション中かどうか
+    pub animating: bool,
+
+    /// プレイヤーが選択中かどうか
+    pub selected: bool,
+
+    /// ヒントなどで強調表示中かどうか
+    pub highlighted: bool,
+
+    /// 操作がないときのヒントの知らせで点滅表示中かどうか
+    pub pulsing: bool,
+
+    /// 共有盤面で自分が動かせないカードかどうか（観戦中・他のプレイヤーのカード、灰色で表示する）
+    pub locked: bool,
+}
+
+/// 盤面上のすべての山
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct PilesView {
+    /// デッキ（山札）の残り枚数（裏向きなので中身は送らない）
+    pub deck_count: u32,
+
+    /// ウェイストパイル（捨て札）、下から順（最後が一番上）
+    pub waste: Vec<CardView>,
+
+    /// ファウンデーション（組札）4つ、それぞれ下から順
+    pub foundations: Vec<Vec<CardView>>,
+
+    /// タブロー（場札）7列、それぞれ下から順
+    pub tableau: Vec<Vec<CardView>>,
+
+    /// 選択中のカードを置ける山（選択していない場合は空）
+    pub drop_targets: Vec<HintLocation>,
+}
+
+/// スコアと統計情報
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct ScoreView {
+    /// 現在のスコア
+    pub score: u32,
+
+    /// 移動回数
+    pub move_count: u32,
+
+    /// デッキを一巡した回数
+    pub deck_turns: u32,
+
+    /// 経過時間（秒）
+    pub elapsed_seconds: u64,
+
+    /// ヒントを使用した回数
+    pub hints_used: u32,
+
+    /// アンドゥを使用した回数
+    pub undos_used: u32,
+
+    /// 最終スコアの内訳（勝利時のみ）
+    pub breakdown: Option<ScoreBreakdown>,
+
+    /// 言語に合わせて整形した経過時間・スコア（設定のformat_numbersが有効な場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<FormattedStats>,
+}
+
+/// ゲーム設定
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct OptionsView {
+    /// ゲームの種類
+    pub game_type: SolitaireType,
+
+    /// 1回にデッキから引く枚数
+    pub draw_count: u32,
+
+    /// 配り札のシード値（ゲーム開始前はNone）
+    pub seed: Option<u64>,
+}
+
+/// チュートリアルで強調表示する対象
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct HighlightView {
+    /// 動かすカードのエンティティID（デッキから引く手順ではNone）
+    pub card_id: Option<u32>,
+
+    /// 動かす元の山
+    pub from: HintLocation,
+
+    /// 動かす先の山
+    pub to: HintLocation,
+}
+
+/// チュートリアルの進み具合
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct TutorialView {
+    /// チュートリアルID
+    pub id: String,
+
+    /// チュートリアル名
+    pub title: String,
+
+    /// 現在の手順の番号（0から開始）
+    pub step: u32,
+
+    /// 手順の数
+    pub step_count: u32,
+
+    /// 現在の手順の案内文（完了後はNone）
+    pub message: Option<String>,
+
+    /// 強調表示する対象（完了後はNone）
+    pub highlight: Option<HighlightView>,
+
+    /// すべての手順を終えたかどうか
+    pub completed: bool,
+}
+
+/// クライアント向けのゲーム状態全体
+///
+/// get_solitaire_state()はこの型をJSONにしたものを返します。
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+#[ts(export)]
+pub struct ClientState {
+    /// このJSONの形式のバージョン
+    pub schema_version: u32,
+
+    /// 進行段階
+    pub phase: GamePhase,
+
+    /// ゲーム設定
+    pub options: OptionsView,
+
+    /// スコアと統計情報
+    pub score: ScoreView,
+
+    /// 盤面
+    pub piles: PilesView,
+
+    /// チュートリアルの進み具合（チュートリアル中でない場合はNone）
+    pub tutorial: Option<TutorialView>,
+
+    /// カードの裏面・テーブルの描画に使うテーマ
+    pub theme: Theme,
+
+    /// 盤面を描画するときの拡大率と表示位置（ポインターの座標の変換と同じ）
+    pub viewport: Viewport,
+}
+
+impl ClientState {
+    /// ECSワールドからクライアント向け状態を作成
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `game_entity` - ゲーム状態エンティティ（ゲーム開始前はNone）
+    ///
+    /// # 戻り値
+    /// 現在の盤面を反映したClientState
+    pub fn from_world(world: &World, game_entity: Option<Entity>) -> Self {
+        let game_state =
+            game_entity.and_then(|entity| world.get_component::<SolitaireGameState>(entity));
+
+        Self {
+            schema_version: STATE_SCHEMA_VERSION,
+            phase: game_state.map_or(GamePhase::NotStarted, phase_of),
+            options: OptionsView {
+                game_type: game_state.map_or(SolitaireType::Klondike, |state| state.game_type),
+                draw_count: DRAW_COUNT,
+                seed: game_state.map(|state| state.seed),
+            },
+            score: game_state
+                .map(|state| score_of(state, world))
+                .unwrap_or_default(),
+            piles: piles_of(world),
+            tutorial: game_entity
+                .and_then(|entity| world.get_component::<TutorialProgress>(entity))
+                .map(|progress| tutorial_of(progress, world)),
+            theme: world.get_resource::<Theme>().copied().unwrap_or_default(),
+            viewport: world.get_resource::<Viewport>().copied().unwrap_or_default(),
+        }
+    }
+
+    /// JSON Schemaを生成
+    ///
+    /// # 戻り値
+    /// ClientStateのJSON Schema
+    pub fn json_schema() -> schemars::Schema {
+        schemars::schema_for!(ClientState)
+    }
+}
+
+/// ゲーム状態から進行段階を判定
+pub(crate) fn phase_of(state: &SolitaireGameState) -> GamePhase {
+    match (state.is_completed, state.is_won) {
+        (false, _) => GamePhase::Playing,
+        (true, true) => GamePhase::Won,
+        (true, false) => GamePhase::Lost,
+    }
+}
+
+/// ゲーム状態からスコア情報を作成
+fn score_of(state: &SolitaireGameState, world: &World) -> ScoreView {
+    let elapsed_seconds = state.elapsed_seconds(&GameClock::from_world(world));
+    ScoreView {
+        score: state.score,
+        move_count: state.move_count,
+        deck_turns: state.deck_turns,
+        elapsed_seconds,
+        hints_used: state.hints_used,
+        undos_used: state.undos_used,
+        breakdown: state.score_breakdown,
+        formatted: i18n::formatted_stats(world, state.score, elapsed_seconds),
+    }
+}
+
+/// チュートリアルの進み具合から表示情報を作成
+fn tutorial_of(progress: &TutorialProgress, world: &World) -> TutorialView {
+    TutorialView {
+        id: progress.tutorial.id.clone(),
+        title: progress.tutorial.title.clone(),
+        step: progress.current_step as u32,
+        step_count: progress.tutorial.steps.len() as u32,
+        message: progress.step().map(|step| step.message.clone()),
+        highlight: progress.highlight(world).map(|highlight| HighlightView {
+            card_id: highlight.card.map(|card| card.id()),
+            from: highlight.from,
+            to: highlight.to,
+        }),
+        completed: progress.is_completed(),
+    }
+}
+
+/// ワールド内のカードを山ごとに振り分ける
+fn piles_of(world: &World) -> PilesView {
+    // (並び順のキー, エンティティ, カード)を山ごとに集める
+    let mut deck_count = 0;
+    let mut waste = Vec::new();
+    let mut foundations = vec![Vec::new(); FOUNDATION_PILES];
+    let mut tableau = vec![Vec::new(); TABLEAU_COLUMNS];
+
+    for (entity, card) in world.query::<SolitaireCard>() {
+        let index = card.position_in_location as usize;
+        match card.location_type {
+            CardLocation::Deck => deck_count += 1,
+            CardLocation::Waste => waste.push((card.position_in_location as f32, entity, card)),
+            CardLocation::Foundation if index < FOUNDATION_PILES => {
+                foundations[index].push((card.rank as u32 as f32, entity, card))
+            }
+            CardLocation::Tableau if index < TABLEAU_COLUMNS => {
+                tableau[index].push((resting_y(card), entity, card))
+            }
+            _ => {}
+        }
+    }
+
+    let mut drop_targets: Vec<HintLocation> = world
+        .query::<DropTarget>()
+        .filter_map(|(entity, _)| world.get_component::<CardStack>(entity))
+        .map(|stack| HintLocation {
+            location: stack.stack_type,
+            index: stack.stack_index,
+        })
+        .collect();
+    drop_targets.sort_by_key(|target| (target.location as u32, target.index));
+
+    PilesView {
+        deck_count,
+        waste: sorted_views(world, waste),
+        foundations: foundations
+            .into_iter()
+            .map(|pile| sorted_views(world, pile))
+            .collect(),
+        tableau: tableau
+            .into_iter()
+            .map(|column| sorted_views(world, column))
+            .collect(),
+        drop_targets,
+    }
+}
+
+/// タブロー内の並び順に使うY座標（アニメーション中は移動先の座標）
+fn resting_y(card: &SolitaireCard) -> f32 {
+    if card.is_animating {
+        card.target_y
+    } else {
+        card.display_y
+    }
+}
+
+/// 並び順のキーでソートしてカード表示情報に変換
+fn sorted_views(world: &World, mut cards: Vec<(f32, Entity, &SolitaireCard)>) -> Vec<CardView> {
+    let theme = world.get_resource::<Theme>().copied().unwrap_or_default();
+    cards.sort_by(|a, b| a.0.total_cmp(&b.0));
+    cards
+        .into_iter()
+        .map(|(_, entity, card)| CardView {
+            id: entity.id(),
+            suit: card.suit,
+            rank: card.rank,
+            color: theme.suit_color(card.suit),
+            pattern: theme.suit_pattern(card.suit),
+            rank_scale: theme.rank_scale(),
+            face_up: card.is_face_up,
+            x: card.display_x,
+            y: card.display_y,
+            animating: card.is_animating,
+            selected: world.has_component::<Selected>(entity),
+            highlighted: world.has_component::<Highlighted>(entity),
+            pulsing: world
+                .get_component::<Highlighted>(entity)
+                .is_some_and(|highlighted| highlighted.pulse),
+            locked: permissions::check_own_card(world, entity).is_err(),
+        })
+        .collect()
+}
@@ -0,0 +1,252 @@
+// =============================================================================
+// ゲーム時計
+// =============================================================================
+// このファイルでは、ゲーム全体で共有する時刻の取得元（GameClock）を実装します。
+// SystemTime::now()はWebAssembly環境では使えず、ネイティブ環境でも
+// システム時計が巻き戻るとduration_since()が失敗するため、
+// 単調増加する時計（performance.now() / Instant）を基準に時刻を求めます。
+//
+// 主要な用途：
+// - ゲーム開始・終了時刻と経過時間（SolitaireGameState）
+// - ターンの制限時間（TurnManager）
+// - 接続の最終アクティビティとメッセージの有効期限（NetworkConnection / NetworkMessage）
+// - 1フレームの経過時間（アニメーションなど、システムに渡すデルタタイム）
+//
+// 1フレームの経過時間は上限（MAX_FRAME_SECONDS）で切り詰めたうえで、
+// MAX_STEP_SECONDS以下の同じ長さに分け、最大MAX_STEPS_PER_FRAME回だけシステムを実行します。
+// スリープ復帰などで経過時間が大きく飛んでも、アニメーションが一度に進んだり、
+// 追いつくためにシステムを何十回も実行して次のフレームがさらに遅れたりすることはありません。
+// =============================================================================
+
+use crate::ecs::{Resource, World};
+
+/// 1回のシステムの実行で進める経過時間の上限（秒）
+pub const MAX_STEP_SECONDS: f64 = 0.05;
+
+/// 1フレームでシステムを実行する回数の上限
+pub const MAX_STEPS_PER_FRAME: u32 = 5;
+
+/// 1フレームとして扱う経過時間の上限（秒）
+/// （タブが非表示だった後などに、アニメーションが一度に飛ばないようにする）
+pub const MAX_FRAME_SECONDS: f64 = MAX_STEP_SECONDS * MAX_STEPS_PER_FRAME as f64;
+
+/// これ以上の経過時間は時計の飛び（スリープ復帰など）として扱う（秒）
+pub const CLOCK_JUMP_SECONDS: f64 = 1.0;
+
+/// ゲーム時計リソース
+///
+/// 作成時のUNIX時刻を一度だけ取得し、以降は単調増加する時計の経過時間を
+/// 足して現在時刻を求めます。そのため、システム時計が途中で変更されても
+/// 時刻が巻き戻ることはありません。
+#[derive(Debug, Clone, Copy)]
+pub struct GameClock {
+    /// 作成時のUNIX時刻（ミリ秒）
+    epoch_origin_ms: f64,
+
+    /// 作成時の単調増加時計の値（ミリ秒）
+    monotonic_origin_ms: f64,
+
+    /// 手動で進めた時間（ミリ秒、テストやデバッグ用）
+    offset_ms: f64,
+
+    /// 直前のフレームの経過時間（秒）
+    frame_seconds: f64,
+
+    /// フレームの経過時間の合計（秒）
+    game_seconds: f64,
+}
+
+impl Resource for GameClock {}
+
+impl GameClock {
+    /// 現在時刻を基準にした新しい時計を作成
+    ///
+    /// # 戻り値
+    /// 新しいGameClockインスタンス
+    pub fn new() -> Self {
+        Self {
+            epoch_origin_ms: unix_time_ms(),
+            monotonic_origin_ms: monotonic_ms(),
+            offset_ms: 0.0,
+            frame_seconds: 0.0,
+            game_seconds: 0.0,
+        }
+    }
+
+    /// ワールドに登録されている時計を取得
+    ///
+    /// 時計が登録されていないワールド（ボットやベンチマーク用など）では、
+    /// その場で作成した時計を返します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    ///
+    /// # 戻り値
+    /// GameClockのコピー
+    pub fn from_world(world: &World) -> Self {
+        world
+            .get_resource::<GameClock>()
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 時計の作成からの経過時間を取得（ミリ秒）
+    pub fn elapsed_ms(&self) -> f64 {
+        (monotonic_ms() - self.monotonic_origin_ms).max(0.0) + self.offset_ms
+    }
+
+    /// 現在のUNIX時刻を取得（ミリ秒）
+    pub fn now_ms(&self) -> u64 {
+        (self.epoch_origin_ms + self.elapsed_ms()) as u64
+    }
+
+    /// 現在のUNIX時刻を取得（秒）
+    pub fn now_secs(&self) -> u64 {
+        self.now_ms() / 1000
+    }
+
+    /// 時計を手動で進める（テストやデバッグ用）
+    ///
+    /// # 引数
+    /// * `ms` - 進める時間（ミリ秒、負の値は無視される）
+    pub fn advance(&mut self, ms: f64) {
+        self.offset_ms += ms.max(0.0);
+    }
+
+    /// 1フレーム分の経過時間を記録
+    ///
+    /// 負の値・非数は0として、上限を超える値はMAX_FRAME_SECONDSとして扱います。
+    ///
+    /// # 引数
+    /// * `delta_seconds` - 前フレームからの経過時間（秒）
+    ///
+    /// # 戻り値
+    /// システムに渡すデルタタイム（秒）
+    pub fn tick(&mut self, delta_seconds: f64) -> f64 {
+        let delta = if delta_seconds.is_finite() {
+            delta_seconds.clamp(0.0, MAX_FRAME_SECONDS)
+        } else {
+            0.0
+        };
+        self.frame_seconds = delta;
+        self.game_seconds += delta;
+        delta
+    }
+
+    /// 1フレーム分の経過時間を記録し、システムを何回に分けて実行するかを決める
+    ///
+    /// # 引数
+    /// * `delta_seconds` - 前フレームからの経過時間（秒）
+    ///
+    /// # 戻り値
+    /// システムの実行回数と1回あたりのデルタタイム
+    pub fn tick_steps(&mut self, delta_seconds: f64) -> FrameSteps {
+        self.tick(delta_seconds);
+        FrameSteps::split(delta_seconds)
+    }
+
+    /// 直前のフレームの経過時間を取得（秒）
+    pub fn frame_seconds(&self) -> f64 {
+        self.frame_seconds
+    }
+
+    /// フレームの経過時間の合計を取得（秒）
+    ///
+    /// ゲームの更新が止まっている間（タブが非表示など）は進みません。
+    pub fn game_seconds(&self) -> f64 {
+        self.game_seconds
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 1フレーム分の経過時間の分け方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSteps {
+    /// システムを実行する回数（1〜MAX_STEPS_PER_FRAME）
+    pub steps: u32,
+
+    /// 1回の実行で渡すデルタタイム（秒）
+    pub step_seconds: f64,
+
+    /// 渡された経過時間（秒、負の値・非数は0）
+    pub raw_seconds: f64,
+}
+
+impl FrameSteps {
+    /// 経過時間を上限で切り詰め、MAX_STEP_SECONDS以下の同じ長さに分ける
+    ///
+    /// # 引数
+    /// * `delta_seconds` - 前フレームからの経過時間（秒）
+    pub fn split(delta_seconds: f64) -> Self {
+        let raw_seconds = if delta_seconds.is_finite() {
+            delta_seconds.max(0.0)
+        } else {
+            0.0
+        };
+        let delta = raw_seconds.min(MAX_FRAME_SECONDS);
+        let steps = ((delta / MAX_STEP_SECONDS).ceil() as u32).clamp(1, MAX_STEPS_PER_FRAME);
+        Self {
+            steps,
+            step_seconds: delta / f64::from(steps),
+            raw_seconds,
+        }
+    }
+
+    /// システムに渡す経過時間の合計（秒）
+    pub fn simulated_seconds(&self) -> f64 {
+        self.step_seconds * f64::from(self.steps)
+    }
+
+    /// 上限を超えて捨てた経過時間（秒）
+    pub fn dropped_seconds(&self) -> f64 {
+        (self.raw_seconds - self.simulated_seconds()).max(0.0)
+    }
+
+    /// 時計の飛び（CLOCK_JUMP_SECONDS以上の経過時間）かどうか
+    pub fn is_clock_jump(&self) -> bool {
+        self.raw_seconds >= CLOCK_JUMP_SECONDS
+    }
+}
+
+/// 単調増加する時計の現在値を取得（ミリ秒）
+///
+/// WebAssembly環境ではstd::time::Instantが使えないため、
+/// ブラウザのperformance.now()を使います。
+#[cfg(feature = "wasm")]
+pub fn monotonic_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+/// 単調増加する時計の現在値を取得（ミリ秒）
+#[cfg(not(feature = "wasm"))]
+pub fn monotonic_ms() -> f64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START
+        .get_or_init(std::time::Instant::now)
+        .elapsed()
+        .as_secs_f64()
+        * 1000.0
+}
+
+/// 現在のUNIX時刻を取得（ミリ秒、WebAssembly版）
+#[cfg(feature = "wasm")]
+pub fn unix_time_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+/// 現在のUNIX時刻を取得（ミリ秒、ネイティブ版）
+///
+/// システム時計がUNIXエポックより前を指している場合は0を返します。
+#[cfg(not(feature = "wasm"))]
+pub fn unix_time_ms() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
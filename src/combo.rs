@@ -0,0 +1,193 @@
+// =============================================================================
+// レースのコンボ（アーケード風の得点）
+// =============================================================================
+// このファイルでは、マルチプレイのレースで、ファウンデーションに続けてカードを置くと
+// 倍率が上がっていくコンボを数えるComboTrackerを実装します。
+// 相手のコンボはComboUpdateで届き、追い上げられている様子が分かるようにします。
+//
+// 仕組み：
+// - ルームの設定でコンボの受付時間（秒）を決める（0の場合はコンボを数えない）
+// - サーバーはレース中に届いたScoreUpdateでファウンデーションの枚数が増えていれば1手として数え
+//   （1手で何枚置いても1つ）、前に置いてから受付時間内なら続けてコンボを伸ばし、過ぎていれば1から数え直す
+// - COMBO_STEP手ごとに倍率が1つ上がる（上限MAX_MULTIPLIER）。
+//   置いた1手ごとにFOUNDATION_POINTS ×（倍率 - 1）点のボーナスが貯まる
+// - 貯まったボーナスは、サーバーがレースの結果のスコアに足してから順位とレーティングを決める
+// - コンボが伸びるたび・受付時間が切れて途切れたときに、ルームの全員にComboUpdateを送る
+// - 元に戻して置き直してもコンボは伸びない（それまでの最多の枚数を超えた分だけ数える）
+// =============================================================================
+
+use crate::protocol::WebSocketMessage;
+use std::collections::HashMap;
+
+/// コンボの受付時間（秒）の上限
+pub const MAX_COMBO_WINDOW_SECONDS: u32 = 30;
+
+/// 倍率が1つ上がるのに必要な連続の手数
+pub const COMBO_STEP: u32 = 3;
+
+/// 倍率の上限
+pub const MAX_MULTIPLIER: u32 = 4;
+
+/// ファウンデーションに1枚置いたときの得点（ボーナスは倍率で増えた分だけ）
+pub const FOUNDATION_POINTS: u32 = 10;
+
+/// 1人分のコンボの状態
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComboStreak {
+    /// 続けて置いた手数（途切れている場合は0）
+    pub combo: u32,
+
+    /// 貯まったボーナスの合計
+    pub bonus_score: u32,
+
+    /// これまでに報告されたファウンデーションのカードの最多の枚数
+    foundation_cards: u16,
+
+    /// 最後にカードを置いた時刻（UNIX時刻、ミリ秒）
+    last_play_ms: u64,
+}
+
+impl ComboStreak {
+    /// 現在の倍率
+    pub fn multiplier(&self) -> u32 {
+        multiplier(self.combo)
+    }
+}
+
+/// 続けて置いた手数から倍率を決める
+///
+/// # 引数
+/// * `combo` - 続けて置いた手数
+///
+/// # 戻り値
+/// 1〜MAX_MULTIPLIERの倍率
+pub fn multiplier(combo: u32) -> u32 {
+    (1 + combo / COMBO_STEP).min(MAX_MULTIPLIER)
+}
+
+/// ルーム1つ分のコンボを数える
+#[derive(Debug, Clone, Default)]
+pub struct ComboTracker {
+    /// プレイヤーIDごとのコンボの状態
+    streaks: HashMap<String, ComboStreak>,
+}
+
+impl ComboTracker {
+    /// 空のトラッカーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// プレイヤーのコンボの状態を取得
+    pub fn streak(&self, player_id: &str) -> Option<&ComboStreak> {
+        self.streaks.get(player_id)
+    }
+
+    /// プレイヤーのこのレースで貯まったボーナス（結果のスコアに足す点数）
+    pub fn bonus_score(&self, player_id: &str) -> u32 {
+        self.streak(player_id)
+            .map_or(0, |streak| streak.bonus_score)
+    }
+
+    /// 報告されたファウンデーションの枚数を反映する
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `player_id` - プレイヤーID
+    /// * `foundation_cards` - ファウンデーションに置いたカードの枚数
+    /// * `window_seconds` - コンボの受付時間（秒）
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// カードが増えた場合は全員に送るComboUpdate、増えていない場合はNone
+    /// （前の報告から何枚増えても1手として数える）
+    pub fn record(
+        &mut self,
+        room_id: &str,
+        player_id: &str,
+        foundation_cards: u16,
+        window_seconds: u32,
+        now_ms: u64,
+    ) -> Option<WebSocketMessage> {
+        let streak = self.streaks.entry(player_id.to_string()).or_default();
+        if foundation_cards <= streak.foundation_cards {
+            return None;
+        }
+        streak.foundation_cards = foundation_cards;
+
+        if now_ms > streak.last_play_ms + window_seconds as u64 * 1000 {
+            streak.combo = 0;
+        }
+        streak.combo += 1;
+        streak.bonus_score += FOUNDATION_POINTS * (streak.multiplier() - 1);
+        streak.last_play_ms = now_ms;
+        Some(update_message(
+            room_id,
+            player_id,
+            streak,
+            Some(now_ms + window_seconds as u64 * 1000),
+        ))
+    }
+
+    /// 受付時間が切れたコンボを途切れさせる
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `window_seconds` - コンボの受付時間（秒）
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 途切れたプレイヤーごとのComboUpdate（コンボ0）
+    pub fn expire(
+        &mut self,
+        room_id: &str,
+        window_seconds: u32,
+        now_ms: u64,
+    ) -> Vec<WebSocketMessage> {
+        let mut expired: Vec<(&String, &mut ComboStreak)> = self
+            .streaks
+            .iter_mut()
+            .filter(|(_, streak)| {
+                streak.combo > 0 && now_ms > streak.last_play_ms + window_seconds as u64 * 1000
+            })
+            .collect();
+        expired.sort_by(|a, b| a.0.cmp(b.0));
+        expired
+            .into_iter()
+            .map(|(player_id, streak)| {
+                streak.combo = 0;
+                update_message(room_id, player_id, streak, None)
+            })
+            .collect()
+    }
+
+    /// レースの開始時・ルームから出たときに数え直す
+    ///
+    /// # 引数
+    /// * `player_id` - 数え直すプレイヤー（Noneの場合は全員）
+    pub fn reset(&mut self, player_id: Option<&str>) {
+        match player_id {
+            Some(player_id) => {
+                self.streaks.remove(player_id);
+            }
+            None => self.streaks.clear(),
+        }
+    }
+}
+
+/// コンボの状態を伝えるメッセージを作成
+fn update_message(
+    room_id: &str,
+    player_id: &str,
+    streak: &ComboStreak,
+    expires_at_ms: Option<u64>,
+) -> WebSocketMessage {
+    WebSocketMessage::ComboUpdate {
+        room_id: room_id.to_string(),
+        player_id: player_id.to_string(),
+        combo: streak.combo,
+        multiplier: streak.multiplier(),
+        bonus_score: streak.bonus_score,
+        expires_at_ms,
+    }
+}
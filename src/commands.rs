@@ -0,0 +1,300 @@
+// =============================================================================
+// 開発用バイナリのサブコマンド
+// =============================================================================
+// このファイルでは、main.rsのサブコマンド（play以外）の処理を実装します。
+// どのコマンドもブラウザ版と同じゲームのコア（SolitaireManager・HintEngine・Solver）を
+// 直接呼び出すため、スクリプトからの分析やルールの確認に使えます。
+//
+// - solve：配り札に勝ち筋があるかをソルバーで調べる
+//   （--jsonで勝ち筋の手順ごとJSONで出力、--replayでverify-replayで読み込める手順のファイルを書き出す）
+// - simulate：ヒントエンジンの手で多数のゲームを自動プレイして勝率を集計する
+// - bench-deal：配り札の作成にかかる時間を測る
+// - verify-replay：クラッシュレポートの移動記録をシードから再現し、盤面が一致するかを確かめる
+// - serve：WebSocketサーバー（websocket_server）を起動する
+// =============================================================================
+
+use ecs_wasm_solitaire::analysis::replay_move;
+use ecs_wasm_solitaire::crash_report::{CrashContext, CrashReport};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::hint::HintEngine;
+use ecs_wasm_solitaire::scenario::{self, Scenario};
+use ecs_wasm_solitaire::solitaire::{MoveLog, SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::solver::{Solver, Winnability};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+
+/// 自動プレイで1ゲームに打つ手数の上限（ブラウザ版の自動プレイと同じ）
+const MAX_AUTO_PLAY_MOVES: u32 = 1000;
+
+/// solveの結果（--jsonで出力する内容）
+#[derive(Debug, Serialize)]
+struct SolveReport {
+    /// 配り札のシード
+    seed: u64,
+
+    /// 勝ち筋の有無
+    winnability: Winnability,
+
+    /// 勝てる手順が見つかったか
+    winnable: bool,
+
+    /// 勝ち筋の手順（scenario::move_notation()の表記、勝ち筋がない場合は空）
+    moves: Vec<String>,
+
+    /// 調べた局面の数
+    nodes_searched: u32,
+
+    /// 調べる局面数の上限
+    search_limit: u32,
+
+    /// 勝ち筋の探索と手順の作成にかかった時間（ミリ秒）
+    elapsed_ms: f64,
+}
+
+/// 配り札に勝ち筋があるかを調べて表示
+///
+/// 勝ち筋が見つかった場合は、その手順を配り札から実際に打って移動記録を作ります。
+///
+/// # 引数
+/// * `seed` - 配り札のシード
+/// * `search_limit` - 調べる局面数の上限
+/// * `json` - 結果をJSONで出力するか
+/// * `replay` - 勝ち筋の手順を書き出すファイルのパス（クラッシュレポートの1セッション分と同じ形式）
+pub fn solve(
+    seed: u64,
+    search_limit: u32,
+    json: bool,
+    replay: Option<&Path>,
+) -> Result<(), String> {
+    let mut world = World::new();
+    let game =
+        SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+
+    let started = Instant::now();
+    let solution = Solver::play_solution(&mut world, search_limit)?;
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let moves: Vec<String> = world
+        .get_component::<MoveLog>(game)
+        .map(|log| log.moves.iter().map(scenario::move_notation).collect())
+        .unwrap_or_default();
+
+    if let Some(path) = replay {
+        if solution.winnability != Winnability::Winnable {
+            return Err("勝ち筋が見つからないため、手順を書き出せません".to_string());
+        }
+        let context = serde_json::to_string_pretty(&CrashContext::capture(&world, Some(game)))
+            .map_err(|e| format!("手順を書き出せません: {}", e))?;
+        std::fs::write(path, context)
+            .map_err(|e| format!("{}に書き込めません: {}", path.display(), e))?;
+    }
+
+    if json {
+        let report = SolveReport {
+            seed,
+            winnability: solution.winnability,
+            winnable: solution.winnability == Winnability::Winnable,
+            moves,
+            nodes_searched: solution.nodes,
+            search_limit,
+            elapsed_ms,
+        };
+        let report = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("結果を書き出せません: {}", e))?;
+        println!("{}", report);
+        return Ok(());
+    }
+
+    match (solution.winnability, solution.line_length) {
+        (Winnability::Winnable, Some(length)) => {
+            println!("✅ シード{}：勝てます（{}手の勝ち筋）", seed, length)
+        }
+        (Winnability::Winnable, None) => println!("✅ シード{}：勝てます", seed),
+        (Winnability::Unwinnable, _) => println!("❌ シード{}：どう打っても勝てません", seed),
+        (Winnability::Unknown, _) => println!(
+            "❓ シード{}：{}局面までに分かりませんでした（--limitで上限を増やせます）",
+            seed, search_limit
+        ),
+    }
+    if !moves.is_empty() {
+        println!("📜 {}手：{}", moves.len(), moves.join(" "));
+    }
+    if let Some(path) = replay {
+        println!("💾 手順を{}に書き出しました", path.display());
+    }
+    println!("⏱️ {:.1}ms（{}局面）", elapsed_ms, solution.nodes);
+    Ok(())
+}
+
+/// ヒントエンジンの手で多数のゲームを自動プレイし、勝率を集計して表示
+///
+/// # 引数
+/// * `games` - 遊ぶゲーム数
+/// * `first_seed` - 最初のゲームのシード（以降は1ずつ増やす）
+pub fn simulate(games: u32, first_seed: u64) -> Result<(), String> {
+    if games == 0 {
+        return Err("ゲーム数は1以上を指定してください".to_string());
+    }
+
+    let started = Instant::now();
+    let mut wins = 0;
+    let mut total_moves = 0u64;
+    for seed in (first_seed..).take(games as usize) {
+        let mut world = World::new();
+        SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+        total_moves += u64::from(HintEngine::play_until_stuck(
+            &mut world,
+            MAX_AUTO_PLAY_MOVES,
+        ));
+        if SolitaireManager::check_windows_solitaire_win(&world) {
+            wins += 1;
+        }
+    }
+
+    println!(
+        "🤖 {}ゲーム（シード{}〜{}）：{}勝 勝率{:.1}%  平均{:.1}手",
+        games,
+        first_seed,
+        first_seed.saturating_add(u64::from(games) - 1),
+        wins,
+        f64::from(wins) * 100.0 / f64::from(games),
+        total_moves as f64 / f64::from(games)
+    );
+    println!("⏱️ {:.2}秒", started.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// 配り札の作成にかかる時間を測って表示
+///
+/// # 引数
+/// * `count` - 作成する配り札の数
+pub fn bench_deal(count: u32) -> Result<(), String> {
+    if count == 0 {
+        return Err("回数は1以上を指定してください".to_string());
+    }
+
+    let started = Instant::now();
+    for seed in 0..u64::from(count) {
+        let mut world = World::new();
+        SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+    }
+    let elapsed = started.elapsed();
+
+    println!(
+        "🃏 {}回の配布：合計{:.1}ms  1回あたり{:.1}µs",
+        count,
+        elapsed.as_secs_f64() * 1000.0,
+        elapsed.as_secs_f64() * 1_000_000.0 / f64::from(count)
+    );
+    Ok(())
+}
+
+/// クラッシュレポートの移動記録をシードから再現し、盤面が一致するかを確かめる
+///
+/// get_crash_report()の出力（全セッション）と、その中の1セッション分（CrashContext）の
+/// どちらのJSONも読み込めます。
+///
+/// # 引数
+/// * `path` - JSONファイルのパス
+///
+/// # 戻り値
+/// すべてのセッションが一致した場合Ok、一致しない・再現できない場合はエラーメッセージ
+pub fn verify_replay(path: &Path) -> Result<(), String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("{}を読み込めません: {}", path.display(), e))?;
+    let sessions = match serde_json::from_str::<CrashReport>(&json) {
+        Ok(report) => {
+            match &report.location {
+                Some(location) => println!("💥 {}（{}）", report.message, location),
+                None => println!("💥 {}", report.message),
+            }
+            report.sessions
+        }
+        Err(_) => {
+            let context: CrashContext = serde_json::from_str(&json)
+                .map_err(|e| format!("クラッシュレポートとして読み込めません: {}", e))?;
+            BTreeMap::from([("記録".to_string(), context)])
+        }
+    };
+
+    let mut failures = 0;
+    for (session_id, context) in &sessions {
+        match verify_context(context) {
+            Ok(()) => println!(
+                "✅ {}：{}手を再現し、盤面が一致しました",
+                session_id,
+                context.moves.len()
+            ),
+            Err(e) => {
+                println!("❌ {}：{}", session_id, e);
+                failures += 1;
+            }
+        }
+    }
+
+    match failures {
+        0 => Ok(()),
+        _ => Err(format!(
+            "{}セッション中{}セッションを再現できませんでした",
+            sessions.len(),
+            failures
+        )),
+    }
+}
+
+/// 1セッション分の移動記録を再現して盤面を比べる
+fn verify_context(context: &CrashContext) -> Result<(), String> {
+    let game_type = context
+        .game_type
+        .ok_or_else(|| "ゲームが始まっていません".to_string())?;
+    if game_type != SolitaireType::Klondike {
+        return Err("再現はクロンダイクのみ対応しています".to_string());
+    }
+
+    let mut world = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut world, game_type, context.seed);
+    for (number, record) in (1..).zip(&context.moves) {
+        replay_move(&mut world, record)
+            .map_err(|e| format!("{}手目を再現できません: {}", number, e))?;
+    }
+
+    let replayed = Scenario::from_world(&world).to_compact()?;
+    if replayed != context.board {
+        return Err(format!(
+            "盤面が一致しません\n  記録：{}\n  再現：{}",
+            context.board, replayed
+        ));
+    }
+    Ok(())
+}
+
+/// WebSocketサーバーを起動し、終了するまで待つ
+///
+/// サーバーはtokioなどを使う別のバイナリのため、このバイナリと同じディレクトリにある
+/// websocket_serverを子プロセスとして起動します。
+///
+/// # 引数
+/// * `addr` - 待ち受けるアドレス（省略時はサーバーの既定値）
+pub fn serve(addr: Option<&str>) -> Result<(), String> {
+    let server = std::env::current_exe()
+        .map_err(|e| format!("実行ファイルの場所が分かりません: {}", e))?
+        .with_file_name(format!("websocket_server{}", std::env::consts::EXE_SUFFIX));
+    if !server.exists() {
+        return Err(format!(
+            "{}がありません（cargo build --features server --bin websocket_server でビルドしてください）",
+            server.display()
+        ));
+    }
+
+    let status = Command::new(&server)
+        .args(addr)
+        .status()
+        .map_err(|e| format!("サーバーを起動できません: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("サーバーが異常終了しました（{}）", status))
+    }
+}
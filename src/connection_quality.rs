@@ -0,0 +1,268 @@
+// =============================================================================
+// 接続品質の測定
+// =============================================================================
+// このファイルでは、サーバーとの往復時間（RTT）と、応答のなかったPingの割合
+// （パケットロス）から接続品質を判定し、品質に合わせた送信間隔を決めます。
+//
+// 仕組み：
+// - PING_INTERVAL_MSごとにPingを送り、Pongが届いたら往復時間を記録する
+// - PONG_TIMEOUT_MSまでにPongが届かなかったPingは失われたものとして数える
+// - 直近SAMPLE_WINDOW回分の結果から品質（良好・普通・不安定）を判定する
+// - 品質が下がるとカーソル位置の送信間隔と状態同期のキーフレーム間隔を広げる
+//
+// 時刻はミリ秒単位で呼び出し側から渡すため、テストでは任意の時刻で確認できます。
+// =============================================================================
+
+use crate::send_queue::{QueueMetrics, SendQueue};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use ts_rs::TS;
+
+/// Pingを送る間隔（ミリ秒）
+pub const PING_INTERVAL_MS: f64 = 2000.0;
+
+/// Pongが届かなければ失われたとみなすまでの時間（ミリ秒）
+pub const PONG_TIMEOUT_MS: f64 = 5000.0;
+
+/// 品質の判定に使う直近のPingの数
+const SAMPLE_WINDOW: usize = 20;
+
+/// 往復時間の平滑化係数（新しい測定値の重み）
+const RTT_SMOOTHING: f64 = 0.2;
+
+/// 良好・普通とみなす往復時間の上限（ミリ秒）
+const GOOD_RTT_MS: f64 = 150.0;
+const FAIR_RTT_MS: f64 = 400.0;
+
+/// 良好・普通とみなすパケットロスの上限（割合）
+const GOOD_LOSS: f64 = 0.05;
+const FAIR_LOSS: f64 = 0.2;
+
+/// 接続品質
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionQuality {
+    /// 良好
+    Good,
+
+    /// 普通（少し遅い・たまに失われる）
+    Fair,
+
+    /// 不安定（遅い・よく失われる）
+    Poor,
+
+    /// まだ測定していない
+    Unknown,
+
+    /// 未接続
+    Offline,
+}
+
+impl ConnectionQuality {
+    /// 品質に合わせた送信間隔を取得
+    ///
+    /// 測定前は良好とみなし、未接続の場合は最も長い間隔にします。
+    pub fn update_rates(&self) -> UpdateRates {
+        let (cursor_interval_ms, keyframe_interval_ms) = match self {
+            ConnectionQuality::Good | ConnectionQuality::Unknown => (50, 5_000),
+            ConnectionQuality::Fair => (100, 10_000),
+            ConnectionQuality::Poor | ConnectionQuality::Offline => (250, 20_000),
+        };
+        UpdateRates {
+            cursor_interval_ms,
+            keyframe_interval_ms,
+        }
+    }
+}
+
+/// 品質に合わせた送信間隔
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdateRates {
+    /// カーソル位置を送る間隔（ミリ秒）
+    pub cursor_interval_ms: u32,
+
+    /// 状態同期のキーフレーム（変わっていなくても送り直す自分のスコア）の間隔（ミリ秒）
+    pub keyframe_interval_ms: u32,
+}
+
+/// 接続状態の報告（get_connection_status()の戻り値）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ConnectionReport {
+    /// 接続しているかどうか
+    pub connected: bool,
+
+    /// 接続品質
+    pub quality: ConnectionQuality,
+
+    /// 平滑化した往復時間（ミリ秒、測定前はNone）
+    pub rtt_ms: Option<u32>,
+
+    /// 直近のPingのうち応答がなかった割合（0.0〜1.0）
+    pub packet_loss: f64,
+
+    /// 品質に合わせた送信間隔
+    #[serde(flatten)]
+    pub rates: UpdateRates,
+
+    /// 送信待ちのキューの溜まり具合（セッションがない場合は空のキューの値）
+    #[serde(default)]
+    pub queue: QueueMetrics,
+}
+
+/// 接続品質の測定器
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionMonitor {
+    /// 接続しているかどうか
+    connected: bool,
+
+    /// 応答待ちのPing（ID, 送信時刻）
+    pending: VecDeque<(u32, f64)>,
+
+    /// 直近のPingの結果（Some(往復時間) / 応答なしはNone）
+    samples: VecDeque<Option<f64>>,
+
+    /// 平滑化した往復時間（ミリ秒）
+    smoothed_rtt_ms: Option<f64>,
+
+    /// 次に送るPingのID
+    next_ping_id: u32,
+
+    /// 最後にPingを送った時刻
+    last_ping_ms: Option<f64>,
+}
+
+impl ConnectionMonitor {
+    /// 未接続の測定器を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 接続状態を設定する
+    ///
+    /// 接続し直した場合は、前の接続の測定結果を捨てて測り直します。
+    ///
+    /// # 引数
+    /// * `connected` - 接続しているかどうか
+    pub fn set_connected(&mut self, connected: bool) {
+        if connected != self.connected {
+            *self = Self {
+                connected,
+                next_ping_id: self.next_ping_id,
+                ..Self::default()
+            };
+        }
+    }
+
+    /// 時間を進め、応答のないPingを失われたものとして数える
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    ///
+    /// # 戻り値
+    /// Pingを送る時刻になった場合は送るPingのID、それ以外はNone
+    pub fn tick(&mut self, now_ms: f64) -> Option<u32> {
+        if !self.connected {
+            return None;
+        }
+
+        while let Some(&(_, sent_at)) = self.pending.front() {
+            if now_ms - sent_at < PONG_TIMEOUT_MS {
+                break;
+            }
+            self.pending.pop_front();
+            self.push_sample(None);
+        }
+
+        if self
+            .last_ping_ms
+            .is_some_and(|last| now_ms - last < PING_INTERVAL_MS)
+        {
+            return None;
+        }
+
+        let ping_id = self.next_ping_id;
+        self.next_ping_id = self.next_ping_id.wrapping_add(1);
+        self.pending.push_back((ping_id, now_ms));
+        self.last_ping_ms = Some(now_ms);
+        Some(ping_id)
+    }
+
+    /// Pongを受け取り、往復時間を記録する
+    ///
+    /// # 引数
+    /// * `ping_id` - Pongに含まれていたPingのID
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    ///
+    /// # 戻り値
+    /// 往復時間（ミリ秒）、応答待ちのPingでない場合（時間切れ後に届いたなど）はNone
+    pub fn record_pong(&mut self, ping_id: u32, now_ms: f64) -> Option<f64> {
+        let index = self.pending.iter().position(|&(id, _)| id == ping_id)?;
+        let (_, sent_at) = self.pending.remove(index)?;
+        let rtt_ms = (now_ms - sent_at).max(0.0);
+
+        self.smoothed_rtt_ms = Some(match self.smoothed_rtt_ms {
+            Some(smoothed) => smoothed + RTT_SMOOTHING * (rtt_ms - smoothed),
+            None => rtt_ms,
+        });
+        self.push_sample(Some(rtt_ms));
+        Some(rtt_ms)
+    }
+
+    /// 直近のPingのうち応答がなかった割合（0.0〜1.0）
+    pub fn packet_loss(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        let missed = self.samples.iter().filter(|sample| sample.is_none()).count();
+        missed as f64 / self.samples.len() as f64
+    }
+
+    /// 接続品質を判定する（往復時間とパケットロスのうち悪い方）
+    pub fn quality(&self) -> ConnectionQuality {
+        if !self.connected {
+            return ConnectionQuality::Offline;
+        }
+        if self.samples.is_empty() {
+            return ConnectionQuality::Unknown;
+        }
+
+        let by_rtt = match self.smoothed_rtt_ms {
+            Some(rtt) if rtt <= GOOD_RTT_MS => ConnectionQuality::Good,
+            Some(rtt) if rtt <= FAIR_RTT_MS => ConnectionQuality::Fair,
+            _ => ConnectionQuality::Poor,
+        };
+        let loss = self.packet_loss();
+        let by_loss = if loss <= GOOD_LOSS {
+            ConnectionQuality::Good
+        } else if loss <= FAIR_LOSS {
+            ConnectionQuality::Fair
+        } else {
+            ConnectionQuality::Poor
+        };
+        by_rtt.max(by_loss)
+    }
+
+    /// 接続状態の報告を作成
+    pub fn report(&self) -> ConnectionReport {
+        let quality = self.quality();
+        ConnectionReport {
+            connected: self.connected,
+            quality,
+            rtt_ms: self.smoothed_rtt_ms.map(|rtt| rtt.round() as u32),
+            packet_loss: self.packet_loss(),
+            rates: quality.update_rates(),
+            queue: SendQueue::default().metrics(),
+        }
+    }
+
+    /// Pingの結果を記録する（古いものから捨てる）
+    fn push_sample(&mut self, sample: Option<f64>) {
+        if self.samples.len() == SAMPLE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+}
@@ -0,0 +1,232 @@
+// =============================================================================
+// クラッシュレポート
+// =============================================================================
+// このファイルでは、パニックが起きたときに直前のゲームの状態を端末内に保存し、
+// 不具合の報告に再現できる状態を添付できるようにします。
+// console_error_panic_hookが出力するスタックトレースだけでは、どの盤面で
+// 何をしたときに起きたのかが分からないためです。
+//
+// 仕組み：
+// - 毎フレームの更新が終わるたびに、セッションごとの状態（CrashContext）を控えておく
+//   （移動の記録は前のフレームから増えた手だけを書き足すため、長いゲームでも毎フレームの手間は増えない）
+// - パニック時はフック内で控えておいた状態とパニックの内容をJSONにまとめて保存する
+//   （パニックはセッションを借用している最中に起きるため、フック内ではワールドを読まない）
+// - 次回の起動後にget_crash_report()で取り出して報告に添付する
+//
+// 控えておく状態：
+// - 直近のゲームイベント（タイムラインの最新RECENT_EVENT_COUNT件）
+// - 移動の記録（MoveLog）
+// - 盤面の短い表記（Scenario::to_compact()、Scenario::from_compact()で盤面を復元できる）
+// =============================================================================
+
+use crate::clock::unix_time_ms;
+use crate::ecs::{Entity, World};
+use crate::events::GameEvent;
+use crate::scenario::Scenario;
+use crate::solitaire::{MoveLog, MoveRecord, SolitaireGameState, SolitaireType};
+use crate::storage;
+use crate::timeline::{TimelineRecorder, RECENT_EVENT_COUNT};
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::panic::PanicHookInfo;
+
+/// クラッシュレポートの保存キー
+const STORAGE_KEY: &str = "crash_report";
+
+thread_local! {
+    /// セッションIDごとの直前の状態（パニック時にフックから読む）
+    static CONTEXTS: RefCell<BTreeMap<String, CrashContext>> =
+        const { RefCell::new(BTreeMap::new()) };
+}
+
+/// パニック直前のゲームの状態
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CrashContext {
+    /// ゲームの種類（ゲーム開始前はNone）
+    pub game_type: Option<SolitaireType>,
+
+    /// 配り札のシード（ゲーム開始前は0）
+    pub seed: u64,
+
+    /// 手数
+    pub move_count: u32,
+
+    /// 直近のゲームイベント（古い順）
+    pub recent_events: Vec<GameEvent>,
+
+    /// 移動の記録（古い順）
+    pub moves: Vec<MoveRecord>,
+
+    /// 盤面の短い表記（Scenario::to_compact()の形式、書き出せない場合は空文字列）
+    pub board: String,
+}
+
+impl CrashContext {
+    /// ワールドから現在のゲームの状態を集める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `game` - ゲーム状態エンティティ（ゲーム開始前はNone）
+    ///
+    /// # 戻り値
+    /// 集めたCrashContext
+    pub fn capture(world: &World, game: Option<Entity>) -> Self {
+        let mut context = Self::default();
+        context.refresh(world, game);
+        context
+    }
+
+    /// 控えておいた状態を現在のゲームの状態に合わせ直す
+    ///
+    /// 移動の記録は手が追加されるだけのため、控えが現在の記録の先頭部分と一致する場合は
+    /// 増えた手だけを書き足します（新しいゲーム・やり直しで一致しない場合はすべて写し直す）。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `game` - ゲーム状態エンティティ（ゲーム開始前はNone）
+    fn refresh(&mut self, world: &World, game: Option<Entity>) {
+        let state = game.and_then(|game| world.get_component::<SolitaireGameState>(game));
+        let log: &[MoveRecord] = game
+            .and_then(|game| world.get_component::<MoveLog>(game))
+            .map_or(&[], |log| &log.moves);
+        let same_game = self.game_type == state.map(|state| state.game_type)
+            && self.seed == state.map_or(0, |state| state.seed)
+            && self.moves.len() <= log.len()
+            && self.moves.last() == self.moves.len().checked_sub(1).and_then(|i| log.get(i));
+        if same_game {
+            let known = self.moves.len();
+            self.moves.extend_from_slice(&log[known..]);
+        } else {
+            self.moves = log.to_vec();
+        }
+
+        self.recent_events = world
+            .get_resource::<TimelineRecorder>()
+            .map(|timeline| timeline.recent_events(RECENT_EVENT_COUNT))
+            .unwrap_or_default();
+        self.board = Scenario::from_world(world)
+            .to_compact()
+            .unwrap_or_else(|e| {
+                warn!("⚠️ クラッシュレポート用に盤面を書き出せません: {}", e);
+                String::new()
+            });
+        self.game_type = state.map(|state| state.game_type);
+        self.seed = state.map_or(0, |state| state.seed);
+        self.move_count = state.map_or(0, |state| state.move_count);
+    }
+}
+
+/// パニック時に保存するレポート
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CrashReport {
+    /// パニックのメッセージ
+    pub message: String,
+
+    /// パニックが起きたソースコードの位置（"ファイル:行:列"、不明な場合はNone）
+    pub location: Option<String>,
+
+    /// パニックが起きた時刻（UNIX時刻のミリ秒）
+    pub occurred_at: u64,
+
+    /// セッションIDごとの直前のゲームの状態
+    pub sessions: BTreeMap<String, CrashContext>,
+}
+
+impl CrashReport {
+    /// パニックの情報と控えておいた状態からレポートを作成
+    fn from_panic(info: &PanicHookInfo) -> Self {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "（メッセージなし）".to_string());
+        // フック内で再びパニックしないよう、借用できない場合は状態なしで保存する
+        let sessions = CONTEXTS
+            .try_with(|contexts| contexts.try_borrow().map(|c| c.clone()).unwrap_or_default())
+            .unwrap_or_default();
+
+        Self {
+            message,
+            location: info.location().map(|location| location.to_string()),
+            occurred_at: unix_time_ms() as u64,
+            sessions,
+        }
+    }
+}
+
+/// セッションの直前の状態を控える
+///
+/// 毎フレームの更新が終わった後に呼び出します。
+///
+/// # 引数
+/// * `session_id` - セッションID
+/// * `world` - セッションのECSワールド
+/// * `game` - ゲーム状態エンティティ（ゲーム開始前はNone）
+pub fn remember(session_id: &str, world: &World, game: Option<Entity>) {
+    CONTEXTS.with(|contexts| {
+        contexts
+            .borrow_mut()
+            .entry(session_id.to_string())
+            .or_default()
+            .refresh(world, game);
+    });
+}
+
+/// 控えておいたセッションの状態を取得（控えていない場合はNone）
+///
+/// # 引数
+/// * `session_id` - セッションID
+pub fn remembered(session_id: &str) -> Option<CrashContext> {
+    CONTEXTS.with(|contexts| contexts.borrow().get(session_id).cloned())
+}
+
+/// 破棄したセッションの控えを消す
+///
+/// # 引数
+/// * `session_id` - セッションID
+pub fn forget(session_id: &str) {
+    CONTEXTS.with(|contexts| {
+        contexts.borrow_mut().remove(session_id);
+    });
+}
+
+/// パニック時にクラッシュレポートを保存するフックを登録
+///
+/// 登録済みのフック（console_error_panic_hookなど）はそのまま呼び出されます。
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        let report = CrashReport::from_panic(info);
+        match serde_json::to_string(&report) {
+            Ok(json) => {
+                if let Err(e) = storage::save(STORAGE_KEY, &json) {
+                    error!("❌ クラッシュレポートを保存できません: {}", e);
+                }
+            }
+            Err(e) => error!("❌ クラッシュレポートを書き出せません: {}", e),
+        }
+    }));
+}
+
+/// 保存されているクラッシュレポートを読み込む
+///
+/// # 戻り値
+/// 保存されている場合はSome(CrashReport)、ない・読み込めない場合はNone
+pub fn load() -> Option<CrashReport> {
+    let json = storage::load(STORAGE_KEY)?;
+    serde_json::from_str(&json)
+        .map_err(|e| warn!("⚠️ 保存されているクラッシュレポートを読み込めません: {}", e))
+        .ok()
+}
+
+/// 保存されているクラッシュレポートを消す
+///
+/// # 戻り値
+/// 消去成功時Ok(())、失敗時Err
+pub fn clear() -> Result<(), String> {
+    storage::remove(STORAGE_KEY)
+}
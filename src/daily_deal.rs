@@ -0,0 +1,170 @@
+// =============================================================================
+// 日替わりの配り札の公開と過去の配り札の保存（サーバー用）
+// =============================================================================
+// このファイルでは、その日（UTC）の日替わりの配り札（DailyDeal）と、
+// 過去の配り札をその日のリーダーボードと一緒に保存するDailyArchiveを実装します。
+//
+// 仕組み：
+// - 配り札のシードは日付だけから決まる（rng::daily_seed）ため、どのインスタンスでも同じになる
+// - クライアントはGetDailyDealでサーバーに今日の配り札を問い合わせる
+//   （端末の時計がずれていても、全員が同じ配り札を遊べるように）
+// - サーバーは0時（UTC）に前日の配り札をリーダーボードと一緒に保存し、
+//   新しい配り札を接続中の全員に送る
+// - サーバーが止まっていて0時に保存できなかった日は、次の起動時にまとめて保存する
+// - 保存する配り札はMAX_ARCHIVED_DEALS日分、結果は1日あたりARCHIVED_RESULTS_PER_DEAL件まで
+// - 今日と明日の配り札はサーバーが先に解いておき（solve_cache）、DailyDealに結果を付けて送る
+// =============================================================================
+
+use crate::leaderboard::LeaderboardEntry;
+use crate::protocol::{ArchivedDailyDeal, DailyResult, WebSocketMessage};
+use crate::rating::compare_race_results;
+use crate::rng::{daily_seed, DAY_MS};
+use crate::solve_cache::SolvedDeal;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// 過去の配り札の保存キー
+const STORAGE_KEY: &str = "daily_archive";
+
+/// 保存する過去の配り札の日数
+pub const MAX_ARCHIVED_DEALS: usize = 30;
+
+/// 過去の配り札ごとに保存する結果の件数（上位から）
+pub const ARCHIVED_RESULTS_PER_DEAL: usize = 10;
+
+/// ある日の日替わりの配り札
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyDeal {
+    /// UNIXエポックからの日数（UTC）
+    pub day: u64,
+
+    /// 配り札のシード
+    pub seed: u64,
+
+    /// 次の配り札に変わる時刻（UNIX時刻、ミリ秒）
+    pub next_change_ms: u64,
+}
+
+impl DailyDeal {
+    /// 指定した時刻の日替わりの配り札を取得
+    ///
+    /// # 引数
+    /// * `now_ms` - 時刻（UNIX時刻、ミリ秒）
+    pub fn at(now_ms: u64) -> Self {
+        let day = now_ms / DAY_MS;
+        Self {
+            day,
+            seed: daily_seed(day),
+            next_change_ms: (day + 1) * DAY_MS,
+        }
+    }
+
+    /// クライアントに送るメッセージを作成
+    ///
+    /// # 引数
+    /// * `solved` - この配り札を解いた結果（まだ解いていない場合はNone）
+    /// * `request_id` - 応答する要求のID（日付が変わったときの通知ではNone）
+    pub fn to_message(
+        self,
+        solved: Option<SolvedDeal>,
+        request_id: Option<String>,
+    ) -> WebSocketMessage {
+        WebSocketMessage::DailyDeal {
+            day: self.day,
+            seed: self.seed,
+            next_change_ms: self.next_change_ms,
+            solved,
+            request_id,
+        }
+    }
+}
+
+/// 過去の日替わりの配り札
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyArchive {
+    /// 過去の配り札（新しい順）
+    deals: Vec<ArchivedDailyDeal>,
+}
+
+impl DailyArchive {
+    /// 保存されている過去の配り札を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のDailyArchive
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 過去の配り札を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("過去の配り札のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// 終わった日の配り札をリーダーボードの記録と一緒に追加する
+    ///
+    /// 同じ日が既にある場合は置き換えます。
+    ///
+    /// # 引数
+    /// * `deal` - 終わった日の配り札
+    /// * `entries` - その配り札のリーダーボードの記録（記録順）
+    pub fn archive(&mut self, deal: &DailyDeal, entries: &[LeaderboardEntry]) {
+        let seed = deal.seed;
+        let mut ranked = entries.to_vec();
+        ranked.sort_by(|a, b| compare_race_results(&b.to_result(seed), &a.to_result(seed)));
+        let results = ranked
+            .into_iter()
+            .take(ARCHIVED_RESULTS_PER_DEAL)
+            .zip(1..)
+            .map(|(entry, rank)| DailyResult {
+                rank,
+                player_name: entry.player_name,
+                score: entry.score,
+                duration_seconds: entry.duration_seconds,
+                won: entry.won,
+            })
+            .collect();
+
+        self.deals.retain(|archived| archived.day != deal.day);
+        self.deals.push(ArchivedDailyDeal {
+            day: deal.day,
+            seed: deal.seed,
+            results,
+        });
+        self.deals.sort_by_key(|archived| Reverse(archived.day));
+        self.deals.truncate(MAX_ARCHIVED_DEALS);
+    }
+
+    /// 保存できていない終わった日（サーバーが止まっていて切り替えられなかった日）を取得
+    ///
+    /// 最後に保存した日の翌日から昨日までのうち、保存する日数（MAX_ARCHIVED_DEALS）に
+    /// 収まる日を返します。まだ1日も保存していない場合は空です。
+    ///
+    /// # 引数
+    /// * `today` - 今日（UNIXエポックからの日数、UTC）
+    ///
+    /// # 戻り値
+    /// 古い順の日付
+    pub fn missed_days(&self, today: u64) -> Vec<u64> {
+        let Some(latest) = self.deals.first().map(|archived| archived.day) else {
+            return Vec::new();
+        };
+        let first = (latest + 1).max(today.saturating_sub(MAX_ARCHIVED_DEALS as u64));
+        (first..today).collect()
+    }
+
+    /// 過去の配り札を取得
+    ///
+    /// # 戻り値
+    /// 新しい順のスライス
+    pub fn deals(&self) -> &[ArchivedDailyDeal] {
+        &self.deals
+    }
+}
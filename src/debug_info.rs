@@ -0,0 +1,240 @@
+// =============================================================================
+// デバッグ情報
+// =============================================================================
+// このファイルでは、JavaScript側でデバッグ用オーバーレイを表示するための
+// 情報（エンティティ数、システムの実行時間、キューの長さなど）を集めます。
+//
+// 収集コストの高い項目（システムの実行時間、直近のイベント、メモリ情報）は
+// GameSettings.debug_mode が有効な場合のみ収集します。
+// =============================================================================
+
+use crate::ecs::{PoolStats, QueueStats, StorageStats, SystemScheduler, SystemTiming, World};
+use crate::events::GameEvent;
+use crate::game::{ActionQueue, GameActionPool, GameSettings};
+use crate::network::{MessagePriority, NetworkConnection, NetworkMessagePool, NetworkQueues};
+use crate::solitaire::SolitaireCard;
+use crate::timeline::{TimelineRecorder, RECENT_EVENT_COUNT};
+use serde::Serialize;
+
+/// WebAssemblyのメモリページサイズ（バイト）
+#[cfg(target_arch = "wasm32")]
+const WASM_PAGE_SIZE: u64 = 65536;
+
+/// メモリ情報に含めるコンポーネント格納庫の数（使用量の多い順）
+const LARGEST_STORAGE_COUNT: usize = 5;
+
+/// エンティティ数の内訳
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct EntityCounts {
+    /// ワールド内の全エンティティ数
+    pub total: usize,
+
+    /// カードのエンティティ数
+    pub cards: usize,
+
+    /// ネットワーク接続のエンティティ数
+    pub network_connections: usize,
+}
+
+/// ネットワークキューの状況
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct NetworkQueueInfo {
+    /// 処理待ちのメッセージ数
+    pub pending_messages: usize,
+
+    /// そのうち高優先度（High以上）のメッセージ数
+    pub high_priority_messages: usize,
+
+    /// 再送信中のメッセージ数
+    pub retrying_messages: usize,
+
+    /// サーバーへの送信を待っているメッセージ数
+    pub outbound_messages: usize,
+
+    /// キューが上限に達していて捨てたメッセージ数（受信・送信の合計）
+    pub dropped_messages: u64,
+}
+
+/// メモリの使用状況
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MemoryStats {
+    /// WebAssemblyの線形メモリのサイズ（バイト、ネイティブ環境ではNone）
+    pub linear_memory_bytes: Option<u64>,
+
+    /// ワールド内の全エンティティ数
+    pub entity_count: usize,
+
+    /// 再利用を待っているエンティティIDの数
+    pub free_entity_ids: usize,
+
+    /// コンポーネント格納庫の数
+    pub component_storages: usize,
+
+    /// リソースの数
+    pub resources: usize,
+
+    /// 使用量の多いコンポーネント格納庫（多い順）
+    pub largest_storages: Vec<StorageStats>,
+
+    /// ネットワークメッセージプールの使用状況（プール未登録の場合はNone）
+    pub message_pool: Option<PoolStats>,
+
+    /// ゲームアクションプールの使用状況（プール未登録の場合はNone）
+    pub action_pool: Option<PoolStats>,
+
+    /// 受信キューの使用状況（キュー未登録の場合はNone）
+    pub inbound_queue: Option<QueueStats>,
+
+    /// 送信キューの使用状況（キュー未登録の場合はNone）
+    pub outbound_queue: Option<QueueStats>,
+
+    /// アクションキューの使用状況（キュー未登録の場合はNone）
+    pub action_queue: Option<QueueStats>,
+}
+
+impl MemoryStats {
+    /// ワールドからメモリの使用状況を集める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    ///
+    /// # 戻り値
+    /// 現在のMemoryStats
+    pub fn collect(world: &World) -> Self {
+        #[cfg(target_arch = "wasm32")]
+        let linear_memory_bytes =
+            Some(core::arch::wasm32::memory_size(0) as u64 * WASM_PAGE_SIZE);
+        #[cfg(not(target_arch = "wasm32"))]
+        let linear_memory_bytes = None;
+
+        let mut largest_storages = world.storage_stats();
+        largest_storages.truncate(LARGEST_STORAGE_COUNT);
+
+        Self {
+            linear_memory_bytes,
+            entity_count: world.entity_count(),
+            free_entity_ids: world.free_entity_id_count(),
+            component_storages: world.component_storage_count(),
+            resources: world.resource_count(),
+            largest_storages,
+            message_pool: world
+                .get_resource::<NetworkMessagePool>()
+                .map(NetworkMessagePool::stats),
+            action_pool: world
+                .get_resource::<GameActionPool>()
+                .map(GameActionPool::stats),
+            inbound_queue: world
+                .get_resource::<NetworkQueues>()
+                .map(|queues| queues.inbound.stats()),
+            outbound_queue: world
+                .get_resource::<NetworkQueues>()
+                .map(|queues| queues.outbound.stats()),
+            action_queue: world.get_resource::<ActionQueue>().map(ActionQueue::stats),
+        }
+    }
+}
+
+/// デバッグ用オーバーレイに表示する情報
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DebugInfo {
+    /// デバッグモードが有効かどうか（無効の場合、一部の項目は空）
+    pub debug_mode: bool,
+
+    /// エンティティ数の内訳
+    pub entities: EntityCounts,
+
+    /// アニメーション中のカード数
+    pub animating_cards: usize,
+
+    /// ネットワークキューの状況
+    pub network: NetworkQueueInfo,
+
+    /// 現在のスケジュールの名前（"playing"など、切り替える前はNone）
+    pub schedule: Option<&'static str>,
+
+    /// 無効にしているシステムの名前
+    pub disabled_systems: Vec<&'static str>,
+
+    /// システムごとの実行時間（デバッグモード時のみ）
+    pub system_timings: Vec<SystemTiming>,
+
+    /// 直近のイベント（デバッグモード時のみ）
+    pub recent_events: Vec<GameEvent>,
+
+    /// メモリの使用状況（デバッグモード時のみ）
+    pub memory: Option<MemoryStats>,
+}
+
+impl DebugInfo {
+    /// ワールドとスケジューラからデバッグ情報を集める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `scheduler` - システムスケジューラへの参照
+    ///
+    /// # 戻り値
+    /// 現在のDebugInfo
+    pub fn collect(world: &World, scheduler: &SystemScheduler) -> Self {
+        let debug_mode = world
+            .get_resource::<GameSettings>()
+            .is_some_and(|settings| settings.debug_mode);
+
+        let mut info = Self {
+            debug_mode,
+            entities: entity_counts(world),
+            animating_cards: world
+                .query::<SolitaireCard>()
+                .filter(|(_, card)| card.is_animating)
+                .count(),
+            network: network_queue(world),
+            schedule: scheduler.schedule(),
+            disabled_systems: scheduler.disabled_systems(),
+            system_timings: Vec::new(),
+            recent_events: Vec::new(),
+            memory: None,
+        };
+
+        if debug_mode {
+            info.system_timings = scheduler.system_timings().to_vec();
+            info.recent_events = world
+                .get_resource::<TimelineRecorder>()
+                .map(|timeline| timeline.recent_events(RECENT_EVENT_COUNT))
+                .unwrap_or_default();
+            info.memory = Some(MemoryStats::collect(world));
+        }
+
+        info
+    }
+}
+
+/// エンティティ数の内訳を数える
+fn entity_counts(world: &World) -> EntityCounts {
+    EntityCounts {
+        total: world.entity_count(),
+        cards: world.query::<SolitaireCard>().count(),
+        network_connections: world.query::<NetworkConnection>().count(),
+    }
+}
+
+/// ネットワークキューの状況を集める
+fn network_queue(world: &World) -> NetworkQueueInfo {
+    let Some(queues) = world.get_resource::<NetworkQueues>() else {
+        return NetworkQueueInfo::default();
+    };
+
+    let info = NetworkQueueInfo {
+        outbound_messages: queues.outbound.len(),
+        dropped_messages: queues.inbound.stats().dropped + queues.outbound.stats().dropped,
+        ..NetworkQueueInfo::default()
+    };
+    queues.inbound.iter().fold(info, |mut queue, message| {
+        queue.pending_messages += 1;
+        if message.priority >= MessagePriority::High {
+            queue.high_priority_messages += 1;
+        }
+        if message.retry_count > 0 {
+            queue.retrying_messages += 1;
+        }
+        queue
+    })
+}
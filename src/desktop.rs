@@ -0,0 +1,245 @@
+// =============================================================================
+// ネイティブのデスクトップ版フロントエンド
+// =============================================================================
+// このファイルでは、ブラウザを使わずにクロンダイクを遊べるデスクトップ版の
+// 画面を実装します。ゲームの中身（ECSワールド・システム・入力の処理）は
+// WebAssembly版と同じGameRuntimeを使い、このファイルは描画と入力の転送だけを行います。
+// ゲームのコアが描画方法に依存していないことの確認と、ブラウザなしでの開発に使います。
+//
+// 実行方法：cargo run --features native-ui --bin desktop
+//
+// 操作方法：
+// - カードをドラッグして移動、クリックで選択（ブラウザ版と同じInputSystemで処理）
+// - 左上のデッキをクリックしてカードを引く（Dキーでも可）
+// - H：ヒントを表示、N：新しいゲーム、Esc：終了
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::{CardView, ClientState, GamePhase};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::input::{PointerEvent, PointerKind};
+use ecs_wasm_solitaire::layout::{CARD_HEIGHT, CARD_WIDTH, DECK_POSITION};
+use ecs_wasm_solitaire::logging;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::scenario::card_code;
+use ecs_wasm_solitaire::selection::{DropTarget, PilePlaceholder};
+use ecs_wasm_solitaire::settings::Preferences;
+use ecs_wasm_solitaire::solitaire::{SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::theme::SuitColor;
+use log::{info, warn};
+use macroquad::prelude::*;
+
+/// デッキを置く位置（配布時にデッキのカードを置く座標と同じ）
+const DECK_X: f32 = DECK_POSITION.0;
+const DECK_Y: f32 = DECK_POSITION.1;
+
+/// テーブルの色
+const TABLE_COLOR: Color = Color::new(0.05, 0.4, 0.2, 1.0);
+
+/// カードの裏面の色
+const CARD_BACK_COLOR: Color = Color::new(0.15, 0.25, 0.6, 1.0);
+
+/// ウィンドウの設定
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "ECS ソリティア".to_string(),
+        window_width: 1000,
+        window_height: 720,
+        ..Default::default()
+    }
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    logging::init();
+    info!("🖥️ デスクトップ版を起動");
+
+    let mut rt = GameRuntime::new();
+    rt.apply_preferences(Preferences::load());
+    rt.start_game(SolitaireType::Klondike);
+
+    loop {
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        handle_keys(&mut rt);
+        handle_mouse(&mut rt);
+
+        rt.update(get_frame_time() as f64);
+        draw_board(
+            &rt.world,
+            &ClientState::from_world(&rt.world, rt.game_entity),
+        );
+
+        next_frame().await;
+    }
+}
+
+/// キーボードの操作を処理
+fn handle_keys(rt: &mut GameRuntime) {
+    if is_key_pressed(KeyCode::N) {
+        rt.start_game(SolitaireType::Klondike);
+    }
+    if is_key_pressed(KeyCode::H) && rt.show_hint().is_none() {
+        info!("💡 打てる手がありません");
+    }
+    if is_key_pressed(KeyCode::D) {
+        draw_from_deck(rt);
+    }
+}
+
+/// マウスの操作をポインターイベントとしてGameRuntimeへ転送
+///
+/// デッキのクリックはInputSystemでは扱わないため、ここでカードを引きます。
+fn handle_mouse(rt: &mut GameRuntime) {
+    let (x, y) = mouse_position();
+    let kind = if is_mouse_button_pressed(MouseButton::Left) {
+        if on_deck(x, y) {
+            draw_from_deck(rt);
+            return;
+        }
+        PointerKind::Down
+    } else if is_mouse_button_released(MouseButton::Left) {
+        PointerKind::Up
+    } else if is_mouse_button_down(MouseButton::Left) && mouse_delta_position() != Vec2::ZERO {
+        PointerKind::Move
+    } else {
+        return;
+    };
+
+    let pointer = PointerEvent {
+        kind,
+        x,
+        y,
+        pointer_id: 0,
+    };
+    if let Err(e) = rt.push_pointer(pointer) {
+        warn!("⚠️ {}", e);
+    }
+}
+
+/// 座標がデッキの上にあるかどうか
+fn on_deck(x: f32, y: f32) -> bool {
+    (DECK_X..DECK_X + CARD_WIDTH).contains(&x) && (DECK_Y..DECK_Y + CARD_HEIGHT).contains(&y)
+}
+
+/// デッキからカードを引く（デッキが空の場合はウェイストをデッキに戻す）
+fn draw_from_deck(rt: &mut GameRuntime) {
+    if let Err(e) = SolitaireManager::draw_card(&mut rt.world) {
+        warn!("⚠️ カードを引けません: {}", e);
+    }
+}
+
+/// 盤面全体を描画
+fn draw_board(world: &World, state: &ClientState) {
+    clear_background(TABLE_COLOR);
+
+    // 組札・場札の置き場所（選択中のカードを置ける山は強調する）
+    for (entity, placeholder) in world.query::<PilePlaceholder>() {
+        if world.has_component::<DropTarget>(entity) {
+            draw_rectangle_lines(
+                placeholder.x,
+                placeholder.y,
+                CARD_WIDTH,
+                CARD_HEIGHT,
+                4.0,
+                SKYBLUE,
+            );
+        } else {
+            draw_slot(placeholder.x, placeholder.y);
+        }
+    }
+
+    // デッキ（枚数だけを描く）
+    draw_slot(DECK_X, DECK_Y);
+    if state.piles.deck_count > 0 {
+        draw_card_back(DECK_X, DECK_Y);
+    }
+
+    // アニメーション中のカードは他のカードの上に描く
+    let piles = &state.piles;
+    let cards = piles
+        .waste
+        .iter()
+        .chain(piles.foundations.iter().flatten())
+        .chain(piles.tableau.iter().flatten());
+    let (moving, resting): (Vec<&CardView>, Vec<&CardView>) =
+        cards.partition(|card| card.animating);
+    for card in resting.into_iter().chain(moving) {
+        draw_card(card);
+    }
+
+    draw_status(state);
+}
+
+/// カードを置く場所の枠を描画
+fn draw_slot(x: f32, y: f32) {
+    draw_rectangle_lines(
+        x,
+        y,
+        CARD_WIDTH,
+        CARD_HEIGHT,
+        2.0,
+        Color::new(1.0, 1.0, 1.0, 0.3),
+    );
+}
+
+/// 裏向きのカードを描画
+fn draw_card_back(x: f32, y: f32) {
+    draw_rectangle(x, y, CARD_WIDTH, CARD_HEIGHT, CARD_BACK_COLOR);
+    draw_rectangle_lines(x, y, CARD_WIDTH, CARD_HEIGHT, 2.0, WHITE);
+}
+
+/// 1枚のカードを描画
+fn draw_card(card: &CardView) {
+    if !card.face_up {
+        draw_card_back(card.x, card.y);
+        return;
+    }
+
+    draw_rectangle(card.x, card.y, CARD_WIDTH, CARD_HEIGHT, WHITE);
+    let (outline, thickness) = if card.selected {
+        (GOLD, 4.0)
+    } else if card.highlighted || card.pulsing {
+        (SKYBLUE, 4.0)
+    } else {
+        (DARKGRAY, 1.0)
+    };
+    draw_rectangle_lines(card.x, card.y, CARD_WIDTH, CARD_HEIGHT, thickness, outline);
+
+    // 標準のフォントはスートの記号を持たないため、"QH"のような表記で描く
+    let font_size = 24.0 * card.rank_scale;
+    draw_text(
+        card_code(card.suit, card.rank),
+        card.x + 6.0,
+        card.y + font_size,
+        font_size,
+        suit_color(card.color),
+    );
+}
+
+/// スートの色を描画の色に変換
+fn suit_color(color: SuitColor) -> Color {
+    match color {
+        SuitColor::Red => RED,
+        SuitColor::Black => BLACK,
+        SuitColor::Blue => BLUE,
+        SuitColor::Green => DARKGREEN,
+    }
+}
+
+/// 画面下部にスコアと操作方法を描画
+fn draw_status(state: &ClientState) {
+    // 標準のフォントは日本語を持たないため、英語で表示する
+    let score = &state.score;
+    let status = match state.phase {
+        GamePhase::Won => format!(
+            "You won!   Score {}   Moves {}   [N] new game",
+            score.score, score.move_count
+        ),
+        _ => format!(
+            "Score {}   Moves {}   Time {}s   [D] draw  [H] hint  [N] new game  [Esc] quit",
+            score.score, score.move_count, score.elapsed_seconds
+        ),
+    };
+    draw_text(&status, 20.0, screen_height() - 20.0, 24.0, WHITE);
+}
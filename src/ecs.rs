@@ -12,11 +12,13 @@
 // - type safetyを重視し、コンパイル時にエラーを検出
 // - メモリ効率を考慮したデータ構造を採用
 // - WebAssembly環境での動作を最適化
+// - サーバーではSharedWorldを通して複数のタスクから共有できる（書き込みは1つずつ）
 // =============================================================================
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::any::{Any, TypeId};
 use std::marker::PhantomData;
+use crate::clock::monotonic_ms;
 
 // =============================================================================
 // Entity（エンティティ）の定義
@@ -75,6 +77,24 @@ pub trait Component: Any + Send + Sync + 'static {
     }
 }
 
+// =============================================================================
+// Resource（リソース）の定義
+// =============================================================================
+
+/// リソーストレイト
+/// 
+/// 特定のエンティティに属さない、ワールド全体で1つだけ存在するデータ
+/// （イベントキュー、設定、統計情報など）が実装すべきトレイトです。
+/// 型ごとに1つのインスタンスだけをワールドに登録できます。
+/// 
+/// 実装例：
+/// ```rust
+/// #[derive(Debug, Default)]
+/// struct FrameCounter { frames: u64 }
+/// impl Resource for FrameCounter {}
+/// ```
+pub trait Resource: Any + Send + Sync + 'static {}
+
 // =============================================================================
 // ComponentStorage（コンポーネント格納庫）の実装
 // =============================================================================
@@ -119,6 +139,14 @@ impl<T: Component> ComponentStorage<T> {
         self.components.insert(entity, component)
     }
 
+    /// 追加で格納する予定の数だけ容量を確保します
+    /// 
+    /// # 引数
+    /// * `additional` - 追加で格納するコンポーネントの数
+    pub fn reserve(&mut self, additional: usize) {
+        self.components.reserve(additional);
+    }
+
     /// エンティティのコンポーネントを取得します（不変参照）
     /// 
     /// # 引数
@@ -196,6 +224,100 @@ impl<T: Component> ComponentStorage<T> {
     }
 }
 
+/// 型消去されたコンポーネント格納庫の共通操作
+/// 
+/// ワールドは格納庫をコンポーネントの型を知らないまま保持するため、
+/// 型に依存しない操作（エンティティの削除や使用量の集計）はこのトレイト経由で行います。
+trait AnyStorage: Any + Send + Sync {
+    /// エンティティのコンポーネントを（あれば）削除
+    fn remove_entity(&mut self, entity: Entity);
+
+    /// 格納庫の使用状況を取得
+    fn stats(&self) -> StorageStats;
+
+    /// エンティティが持つコンポーネントの型名を取得（持っていない場合はNone）
+    fn component_name(&self, entity: Entity) -> Option<&'static str>;
+
+    /// 具体的な型へダウンキャストするための参照
+    fn as_any(&self) -> &dyn Any;
+
+    /// 具体的な型へダウンキャストするための可変参照
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Component> AnyStorage for ComponentStorage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.components.remove(&entity);
+    }
+
+    fn stats(&self) -> StorageStats {
+        StorageStats {
+            component: short_type_name(std::any::type_name::<T>()),
+            count: self.components.len(),
+            capacity: self.components.capacity(),
+            approx_bytes: self.components.capacity() * std::mem::size_of::<(Entity, T)>(),
+        }
+    }
+
+    fn component_name(&self, entity: Entity) -> Option<&'static str> {
+        self.components
+            .get(&entity)
+            .map(|component| short_type_name(component.type_name()))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// コンポーネント格納庫1つ分の使用状況
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct StorageStats {
+    /// コンポーネントの型名（末尾部分）
+    pub component: &'static str,
+
+    /// 格納されているコンポーネント数
+    pub count: usize,
+
+    /// 確保済みの容量（要素数）
+    pub capacity: usize,
+
+    /// 確保済みの容量から見積もったメモリ量（バイト、ヒープ上の文字列などは含まない）
+    pub approx_bytes: usize,
+}
+
+/// ワールドの中身の一覧（World::debug_dump()の結果）
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WorldDump {
+    /// エンティティ数
+    pub entity_count: usize,
+
+    /// リソース数
+    pub resource_count: usize,
+
+    /// エンティティごとのコンポーネント（ID順）
+    pub entities: Vec<EntityDump>,
+}
+
+/// エンティティ1つ分の中身
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct EntityDump {
+    /// エンティティID
+    pub id: u32,
+
+    /// 持っているコンポーネントの型名（名前順）
+    pub components: Vec<&'static str>,
+}
+
+/// 型名からモジュールのパスを取り除く（"crate::solitaire::SolitaireCard" → "SolitaireCard"）
+fn short_type_name(type_name: &'static str) -> &'static str {
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
 // =============================================================================
 // World（ワールド）の実装
 // =============================================================================
@@ -211,17 +333,28 @@ impl<T: Component> ComponentStorage<T> {
 /// - コンポーネントの登録・取得・削除
 /// - システムの実行管理
 pub struct World {
-    /// 次に生成するエンティティのID
-    /// アトミックな操作で一意性を保証
+    /// 次に発行する新しいエンティティのID
+    /// 削除済みのIDを再利用するため、同時に存在したエンティティの最大数+1を超えません
     next_entity_id: u32,
     
     /// 型IDをキーとして、コンポーネント格納庫を管理
-    /// Box<dyn Any>を使用した型消去により、異なる型の格納庫を統一管理
-    component_storages: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    /// Box<dyn AnyStorage>を使用した型消去により、異なる型の格納庫を統一管理
+    component_storages: HashMap<TypeId, Box<dyn AnyStorage>>,
     
     /// 生成されたエンティティのリスト
     /// エンティティの生存確認や一括操作に使用
     entities: Vec<Entity>,
+    
+    /// エンティティIDを添字として、entities内の位置を引く表（存在しないIDはNone）
+    /// 大量のエンティティがあっても生存確認と削除をO(1)で行うために使用
+    entity_slots: Vec<Option<u32>>,
+    
+    /// 削除されて再利用を待っているエンティティIDのリスト
+    /// 世代番号は持たないため、削除済みのEntityを使い続けてはいけません
+    free_entity_ids: Vec<u32>,
+    
+    /// 型IDをキーとして、リソース（ワールド全体で1つのデータ）を管理
+    resources: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
 }
 
 impl World {
@@ -234,11 +367,17 @@ impl World {
             next_entity_id: 1, // 0は無効なIDとして予約
             component_storages: HashMap::new(),
             entities: Vec::new(),
+            entity_slots: Vec::new(),
+            free_entity_ids: Vec::new(),
+            resources: HashMap::new(),
         }
     }
 
     /// 新しいエンティティを生成します
     /// 
+    /// 削除済みのエンティティIDがあれば、新しいIDを発行せずに再利用します。
+    /// 同時に存在できるエンティティはu32::MAX - 1個までで、それを超えるとパニックします。
+    /// 
     /// # 戻り値
     /// 新しく生成されたEntity
     /// 
@@ -249,12 +388,44 @@ impl World {
     /// let enemy = world.create_entity();
     /// ```
     pub fn create_entity(&mut self) -> Entity {
-        let entity = Entity::new(self.next_entity_id);
-        self.next_entity_id += 1;
+        let id = self.free_entity_ids.pop().unwrap_or_else(|| {
+            let id = self.next_entity_id;
+            self.next_entity_id = id
+                .checked_add(1)
+                .expect("エンティティIDを使い切りました（同時に存在するエンティティが多すぎます）");
+            id
+        });
+        let entity = Entity::new(id);
+        
+        let slot = id as usize;
+        if slot >= self.entity_slots.len() {
+            self.entity_slots.resize(slot + 1, None);
+        }
+        self.entity_slots[slot] = Some(self.entities.len() as u32);
         self.entities.push(entity);
         entity
     }
 
+    /// エンティティが存在するかどうかを確認します
+    /// 
+    /// # 引数
+    /// * `entity` - 確認するエンティティ
+    /// 
+    /// # 戻り値
+    /// 存在する場合true（削除済みのIDが再利用された場合もtrue）
+    pub fn contains_entity(&self, entity: Entity) -> bool {
+        self.entity_slot(entity).is_some()
+    }
+
+    /// エンティティのentities内の位置を取得
+    fn entity_slot(&self, entity: Entity) -> Option<usize> {
+        self.entity_slots
+            .get(entity.id() as usize)
+            .copied()
+            .flatten()
+            .map(|slot| slot as usize)
+    }
+
     /// エンティティとその全コンポーネントを削除します
     /// 
     /// # 引数
@@ -264,12 +435,19 @@ impl World {
     /// エンティティが存在して削除された場合true、存在しなかった場合false
     pub fn remove_entity(&mut self, entity: Entity) -> bool {
         // エンティティリストから削除
-        if let Some(pos) = self.entities.iter().position(|&e| e == entity) {
-            self.entities.remove(pos);
+        if let Some(pos) = self.entity_slot(entity) {
+            // 順序は保持しない（swap_removeで末尾と入れ替えてO(1)で削除）
+            self.entities.swap_remove(pos);
+            self.entity_slots[entity.id() as usize] = None;
+            if let Some(&moved) = self.entities.get(pos) {
+                self.entity_slots[moved.id() as usize] = Some(pos as u32);
+            }
+            self.free_entity_ids.push(entity.id());
             
             // 全コンポーネント格納庫からこのエンティティのコンポーネントを削除
-            // 注意：型安全性を保つため、実際の削除処理は各格納庫で個別に実装
-            // ここでは存在確認のみ行う
+            for storage in self.component_storages.values_mut() {
+                storage.remove_entity(entity);
+            }
             true
         } else {
             false
@@ -288,6 +466,7 @@ impl World {
         let type_id = TypeId::of::<T>();
         self.component_storages
             .get(&type_id)?
+            .as_any()
             .downcast_ref::<ComponentStorage<T>>()
     }
 
@@ -303,6 +482,7 @@ impl World {
         let type_id = TypeId::of::<T>();
         self.component_storages
             .get_mut(&type_id)?
+            .as_any_mut()
             .downcast_mut::<ComponentStorage<T>>()
     }
 
@@ -320,6 +500,7 @@ impl World {
         self.component_storages
             .entry(type_id)
             .or_insert_with(|| Box::new(ComponentStorage::<T>::new()))
+            .as_any_mut()
             .downcast_mut::<ComponentStorage<T>>()
             .expect("型の不整合が発生しました。これはバグです。")
     }
@@ -347,6 +528,60 @@ impl World {
         self.get_or_create_storage_mut::<T>().insert(entity, component)
     }
 
+    /// 複数のエンティティにまとめてコンポーネントを追加します
+    /// 
+    /// 格納庫の検索は1回だけ行い、事前に容量を確保してから一度に挿入します。
+    /// 
+    /// # 引数
+    /// * `components` - (エンティティ, コンポーネント)の組
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `T` - 追加するコンポーネントの型
+    pub fn add_components_batch<T, I>(&mut self, components: I)
+    where
+        T: Component,
+        I: IntoIterator<Item = (Entity, T)>,
+    {
+        let components = components.into_iter();
+        let storage = self.get_or_create_storage_mut::<T>();
+        storage.reserve(components.size_hint().0);
+        for (entity, component) in components {
+            storage.insert(entity, component);
+        }
+    }
+
+    /// コンポーネントごとに新しいエンティティを生成し、まとめて追加します
+    /// 
+    /// カードの配布のように大量のエンティティを一度に生成する場合に使います。
+    /// 
+    /// # 引数
+    /// * `components` - 追加するコンポーネント（1つにつき1エンティティ）
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `T` - 追加するコンポーネントの型
+    /// 
+    /// # 戻り値
+    /// 生成されたエンティティのベクター（componentsと同じ順）
+    /// 
+    /// # 例
+    /// ```rust
+    /// let cards = world.spawn_batch(vec![card_a, card_b, card_c]);
+    /// ```
+    pub fn spawn_batch<T, I>(&mut self, components: I) -> Vec<Entity>
+    where
+        T: Component,
+        I: IntoIterator<Item = T>,
+    {
+        let components: Vec<T> = components.into_iter().collect();
+        self.entities.reserve(components.len());
+        let entities: Vec<Entity> = (0..components.len())
+            .map(|_| self.create_entity())
+            .collect();
+
+        self.add_components_batch(entities.iter().copied().zip(components));
+        entities
+    }
+
     /// エンティティのコンポーネントを取得します（不変参照）
     /// 
     /// # 引数
@@ -463,6 +698,542 @@ impl World {
     pub fn entity_count(&self) -> usize {
         self.entities.len()
     }
+
+    /// 再利用を待っているエンティティIDの数を取得
+    /// 
+    /// # 戻り値
+    /// 削除済みで未再利用のエンティティIDの数
+    pub fn free_entity_id_count(&self) -> usize {
+        self.free_entity_ids.len()
+    }
+
+    /// 登録されているコンポーネント格納庫（コンポーネントの型）の数を取得
+    /// 
+    /// # 戻り値
+    /// コンポーネント格納庫の数
+    pub fn component_storage_count(&self) -> usize {
+        self.component_storages.len()
+    }
+
+    /// コンポーネント格納庫ごとの使用状況を取得
+    /// 
+    /// # 戻り値
+    /// 見積もりメモリ量の多い順のStorageStatsのベクター
+    pub fn storage_stats(&self) -> Vec<StorageStats> {
+        let mut stats: Vec<StorageStats> = self
+            .component_storages
+            .values()
+            .map(|storage| storage.stats())
+            .collect();
+        stats.sort_by_key(|stats| std::cmp::Reverse(stats.approx_bytes));
+        stats
+    }
+
+    /// 全エンティティと、それぞれが持つコンポーネントの型名を一覧にする
+    /// 
+    /// 型名はComponent::type_name()で取得します。
+    /// クライアント間の状態のずれの調査やデバッグ表示に使用します。
+    /// 
+    /// # 戻り値
+    /// ワールドの中身の一覧
+    pub fn debug_dump(&self) -> WorldDump {
+        let mut entities: Vec<EntityDump> = self
+            .entities
+            .iter()
+            .map(|&entity| {
+                let mut components: Vec<&'static str> = self
+                    .component_storages
+                    .values()
+                    .filter_map(|storage| storage.component_name(entity))
+                    .collect();
+                components.sort_unstable();
+                EntityDump {
+                    id: entity.id(),
+                    components,
+                }
+            })
+            .collect();
+        entities.sort_by_key(|dump| dump.id);
+
+        WorldDump {
+            entity_count: self.entities.len(),
+            resource_count: self.resources.len(),
+            entities,
+        }
+    }
+
+    /// 登録されているリソースの数を取得
+    /// 
+    /// # 戻り値
+    /// リソースの数
+    pub fn resource_count(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// リソースを登録します
+    /// 
+    /// # 引数
+    /// * `resource` - 登録するリソース
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `R` - 登録するリソースの型
+    /// 
+    /// # 戻り値
+    /// 既に同じ型のリソースが存在した場合は古いリソース、
+    /// 存在しなかった場合はNone
+    pub fn insert_resource<R: Resource>(&mut self, resource: R) -> Option<R> {
+        self.resources
+            .insert(TypeId::of::<R>(), Box::new(resource))
+            .and_then(|old| old.downcast::<R>().ok())
+            .map(|old| *old)
+    }
+
+    /// リソースを取得します（不変参照）
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `R` - 取得するリソースの型
+    /// 
+    /// # 戻り値
+    /// リソースが存在する場合はSome(&R)、存在しない場合はNone
+    pub fn get_resource<R: Resource>(&self) -> Option<&R> {
+        self.resources.get(&TypeId::of::<R>())?.downcast_ref::<R>()
+    }
+
+    /// リソースを取得します（可変参照）
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `R` - 取得するリソースの型
+    /// 
+    /// # 戻り値
+    /// リソースが存在する場合はSome(&mut R)、存在しない場合はNone
+    pub fn get_resource_mut<R: Resource>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut(&TypeId::of::<R>())?.downcast_mut::<R>()
+    }
+
+    /// リソースを削除します
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `R` - 削除するリソースの型
+    /// 
+    /// # 戻り値
+    /// 削除されたリソース、存在しなかった場合はNone
+    pub fn remove_resource<R: Resource>(&mut self) -> Option<R> {
+        self.resources
+            .remove(&TypeId::of::<R>())
+            .and_then(|old| old.downcast::<R>().ok())
+            .map(|old| *old)
+    }
+
+    /// 指定された型のリソースが登録されているかチェック
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `R` - チェックするリソースの型
+    /// 
+    /// # 戻り値
+    /// 登録されている場合true、されていない場合false
+    pub fn has_resource<R: Resource>(&self) -> bool {
+        self.resources.contains_key(&TypeId::of::<R>())
+    }
+
+    /// par_queryで分割する組の数の上限（同時に動かせるスレッドの数、分からない場合は1）
+    pub fn max_shards() -> usize {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    }
+
+    /// 指定された型のコンポーネントを分割して並列に処理します
+    /// 
+    /// コンポーネントを`shards`個の組に分け、組ごとに`f`を呼び出します。
+    /// ネイティブ環境では組ごとにスレッドを立てて同時に実行し、
+    /// WebAssembly環境ではスレッドを使えないため順番に実行します。
+    /// 組の数は同時に動かせるスレッドの数（max_shards()）までに抑えます。
+    /// ワールドは読み取り専用で借用するため、処理中に他から書き換えられることはありません。
+    /// 
+    /// # 引数
+    /// * `shards` - 分割する組の数（0の場合は1、max_shards()より多い場合はmax_shards()として扱う）
+    /// * `f` - 1組分の(Entity, &T)を受け取って結果を返す関数
+    /// 
+    /// # ジェネリック型パラメータ
+    /// * `T` - 処理するコンポーネントの型
+    /// * `R` - 1組分の処理結果の型
+    /// 
+    /// # 戻り値
+    /// 組ごとの処理結果（コンポーネントがない場合は空）
+    /// 
+    /// # 例
+    /// ```rust
+    /// let total: f32 = world
+    ///     .par_query::<Position, _>(4, |shard| shard.iter().map(|(_, p)| p.x).sum::<f32>())
+    ///     .into_iter()
+    ///     .sum();
+    /// ```
+    pub fn par_query<T, R>(&self, shards: usize, f: impl Fn(&[(Entity, &T)]) -> R + Sync) -> Vec<R>
+    where
+        T: Component,
+        R: Send,
+    {
+        let components: Vec<(Entity, &T)> = self.query::<T>().collect();
+        if components.is_empty() {
+            return Vec::new();
+        }
+        let shard_size = components.len().div_ceil(shards.clamp(1, Self::max_shards()));
+        
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let f = &f;
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = components
+                    .chunks(shard_size)
+                    .map(|shard| scope.spawn(move || f(shard)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("並列クエリの処理中にパニックしました"))
+                    .collect()
+            })
+        }
+        
+        #[cfg(target_arch = "wasm32")]
+        {
+            components.chunks(shard_size).map(f).collect()
+        }
+    }
+}
+
+// WorldはArc<RwLock<World>>に入れて複数のタスクから使うため、Send + Syncである必要がある
+// （コンポーネントとリソースにSend + Syncを要求しているため、ここで崩れないことを確認する）
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<World>();
+    assert_send_sync::<SharedWorld>();
+};
+
+// =============================================================================
+// SharedWorld（共有ワールド）の実装
+// =============================================================================
+
+/// 複数のスレッド・タスクから使うためのワールド
+/// 
+/// Arc<RwLock<World>>を包んだもので、クローンすると同じワールドを指します。
+/// 
+/// 並行性の約束：
+/// - 書き込みは1つずつ（write()の間は他の読み取り・書き込みは待たされる）
+/// - 読み取りは同時に何個でも行える（read()同士は互いを待たない）
+/// - ロックを持ったまま.awaitしないこと（クロージャで受け渡すのはこのため）
+/// - システムの実行中にパニックしてもワールドは使い続ける
+///   （途中までの変更は残るため、呼び出し側で必要に応じて状態を確認する）
+#[derive(Clone, Default)]
+pub struct SharedWorld {
+    inner: std::sync::Arc<std::sync::RwLock<World>>,
+}
+
+impl SharedWorld {
+    /// ワールドを共有できるようにします
+    /// 
+    /// # 引数
+    /// * `world` - 共有するワールド
+    pub fn new(world: World) -> Self {
+        Self {
+            inner: std::sync::Arc::new(std::sync::RwLock::new(world)),
+        }
+    }
+
+    /// ワールドを読み取ります（他の読み取りと同時に実行できます）
+    /// 
+    /// # 引数
+    /// * `f` - ワールドへの不変参照を受け取る関数
+    /// 
+    /// # 戻り値
+    /// fの戻り値
+    pub fn read<R>(&self, f: impl FnOnce(&World) -> R) -> R {
+        let world = self
+            .inner
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&world)
+    }
+
+    /// ワールドを書き換えます（実行中は他の読み取り・書き込みを待たせます）
+    /// 
+    /// # 引数
+    /// * `f` - ワールドへの可変参照を受け取る関数
+    /// 
+    /// # 戻り値
+    /// fの戻り値
+    pub fn write<R>(&self, f: impl FnOnce(&mut World) -> R) -> R {
+        let mut world = self
+            .inner
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&mut world)
+    }
+
+    /// スケジューラーのシステムを1フレーム分実行します
+    /// 
+    /// # 引数
+    /// * `scheduler` - 実行するシステムのスケジューラー
+    /// * `delta_time` - 前フレームからの経過時間（秒）
+    pub fn update(&self, scheduler: &mut SystemScheduler, delta_time: f64) {
+        self.write(|world| scheduler.update(world, delta_time));
+    }
+}
+
+// =============================================================================
+// ComponentPool（コンポーネントプール）の実装
+// =============================================================================
+
+/// プールに保持しておくコンポーネントの既定の最大数
+pub const DEFAULT_POOL_CAPACITY: usize = 64;
+
+/// コンポーネントのプール（リソース）
+/// 
+/// メッセージやアクションのように毎フレーム作成・破棄されるコンポーネントを
+/// 捨てずに一定数まで保持しておき、次の作成時に再利用します。
+/// 文字列などのバッファを使い回すことで、メモリ確保の回数を抑えます。
+#[derive(Debug)]
+pub struct ComponentPool<T: Component> {
+    /// 再利用を待っているコンポーネント
+    free: Vec<T>,
+
+    /// 保持しておく最大数
+    capacity: usize,
+
+    /// プールから再利用した回数
+    reused: u64,
+
+    /// プールが空で新しく作成した回数
+    allocated: u64,
+}
+
+impl<T: Component> Resource for ComponentPool<T> {}
+
+/// コンポーネントプールの使用状況
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PoolStats {
+    /// 再利用を待っているコンポーネント数
+    pub idle: usize,
+
+    /// プールから再利用した回数
+    pub reused: u64,
+
+    /// 新しく作成した回数
+    pub allocated: u64,
+}
+
+impl<T: Component> Default for ComponentPool<T> {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_POOL_CAPACITY)
+    }
+}
+
+impl<T: Component> ComponentPool<T> {
+    /// 既定の最大数を持つ空のプールを作成
+    /// 
+    /// # 戻り値
+    /// 空のComponentPoolインスタンス
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最大数を指定して空のプールを作成
+    /// 
+    /// # 引数
+    /// * `capacity` - 保持しておくコンポーネントの最大数
+    /// 
+    /// # 戻り値
+    /// 空のComponentPoolインスタンス
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            free: Vec::new(),
+            capacity,
+            reused: 0,
+            allocated: 0,
+        }
+    }
+
+    /// コンポーネントを取り出す
+    /// 
+    /// `build`にはプールから取り出したコンポーネント（空の場合はNone）が渡されるので、
+    /// Someなら中身を初期化し直し、Noneなら新しく作成して返してください。
+    /// 
+    /// # 引数
+    /// * `build` - 再利用または新規作成を行う関数
+    /// 
+    /// # 戻り値
+    /// 初期化済みのコンポーネント
+    pub fn acquire(&mut self, build: impl FnOnce(Option<T>) -> T) -> T {
+        let recycled = self.free.pop();
+        if recycled.is_some() {
+            self.reused += 1;
+        } else {
+            self.allocated += 1;
+        }
+        build(recycled)
+    }
+
+    /// 使い終わったコンポーネントをプールに戻す
+    /// 
+    /// プールが上限に達している場合、コンポーネントはそのまま破棄されます。
+    /// 
+    /// # 引数
+    /// * `component` - 使い終わったコンポーネント
+    pub fn release(&mut self, component: T) {
+        if self.free.len() < self.capacity {
+            self.free.push(component);
+        }
+    }
+
+    /// プールの使用状況を取得
+    /// 
+    /// # 戻り値
+    /// PoolStats
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            idle: self.free.len(),
+            reused: self.reused,
+            allocated: self.allocated,
+        }
+    }
+}
+
+// =============================================================================
+// MessageQueue（メッセージキュー）の実装
+// =============================================================================
+
+/// メッセージキューに溜めておける既定の最大数
+pub const DEFAULT_QUEUE_CAPACITY: usize = 256;
+
+/// 上限付きのメッセージキュー（リソース）
+/// 
+/// 受信したメッセージやプレイヤーのアクションのように、システムが次のフレームで
+/// まとめて処理するものを、エンティティを作らずに届いた順に溜めておきます。
+/// 上限に達すると新しく追加しようとしたものを拒否し、その回数を記録します（背圧）。
+#[derive(Debug)]
+pub struct MessageQueue<T> {
+    /// 処理を待っているメッセージ（届いた順）
+    items: VecDeque<T>,
+
+    /// 溜めておける最大数
+    capacity: usize,
+
+    /// 追加できた回数
+    enqueued: u64,
+
+    /// 上限に達していて追加できなかった回数
+    dropped: u64,
+
+    /// これまでに溜まった最大数
+    high_water_mark: usize,
+}
+
+impl<T: Send + Sync + 'static> Resource for MessageQueue<T> {}
+
+/// メッセージキューの使用状況
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct QueueStats {
+    /// 処理を待っているメッセージ数
+    pub pending: usize,
+
+    /// 溜めておける最大数
+    pub capacity: usize,
+
+    /// これまでに溜まった最大数
+    pub high_water_mark: usize,
+
+    /// 追加できた回数
+    pub enqueued: u64,
+
+    /// 上限に達していて追加できなかった回数
+    pub dropped: u64,
+}
+
+impl<T> Default for MessageQueue<T> {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_QUEUE_CAPACITY)
+    }
+}
+
+impl<T> MessageQueue<T> {
+    /// 既定の最大数を持つ空のキューを作成
+    /// 
+    /// # 戻り値
+    /// 空のMessageQueueインスタンス
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最大数を指定して空のキューを作成
+    /// 
+    /// # 引数
+    /// * `capacity` - 溜めておける最大数
+    /// 
+    /// # 戻り値
+    /// 空のMessageQueueインスタンス
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::new(),
+            capacity,
+            enqueued: 0,
+            dropped: 0,
+            high_water_mark: 0,
+        }
+    }
+
+    /// メッセージを末尾に追加
+    /// 
+    /// # 引数
+    /// * `item` - 追加するメッセージ
+    /// 
+    /// # 戻り値
+    /// 追加できた場合Ok(())、上限に達している場合は追加しようとしたメッセージをそのまま返す
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.items.len() >= self.capacity {
+            self.dropped += 1;
+            return Err(item);
+        }
+        self.items.push_back(item);
+        self.enqueued += 1;
+        self.high_water_mark = self.high_water_mark.max(self.items.len());
+        Ok(())
+    }
+
+    /// 溜まっているメッセージをすべて取り出す（届いた順）
+    pub fn drain(&mut self) -> Vec<T> {
+        self.items.drain(..).collect()
+    }
+
+    /// 処理を待っているメッセージを先頭から順に参照
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.items.iter()
+    }
+
+    /// 処理を待っているメッセージ数
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 処理を待っているメッセージがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 上限に達しているかどうか
+    pub fn is_full(&self) -> bool {
+        self.items.len() >= self.capacity
+    }
+
+    /// キューの使用状況を取得
+    /// 
+    /// # 戻り値
+    /// QueueStats
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            pending: self.items.len(),
+            capacity: self.capacity,
+            high_water_mark: self.high_water_mark,
+            enqueued: self.enqueued,
+            dropped: self.dropped,
+        }
+    }
 }
 
 // =============================================================================
@@ -474,6 +1245,7 @@ impl World {
 /// ECSアーキテクチャにおけるシステムの動作を定義するトレイトです。
 /// すべてのシステムはこのトレイトを実装し、update関数内で
 /// ワールドのコンポーネントを操作するロジックを記述します。
+/// サーバーではルームごとのティックタスク（tokioのタスク）で実行するため、Sendである必要があります。
 /// 
 /// 実装例：
 /// ```rust
@@ -484,7 +1256,7 @@ impl World {
 ///     }
 /// }
 /// ```
-pub trait System {
+pub trait System: Send {
     /// システムの処理を実行します
     /// 
     /// # 引数
@@ -517,6 +1289,59 @@ pub struct SystemScheduler {
     /// 実行するシステムのリスト
     /// 登録順序で実行されるため、依存関係を考慮した順序で登録する必要があります
     systems: Vec<Box<dyn System>>,
+
+    /// システムごとの実行時間（systemsと同じ順序）
+    timings: Vec<SystemTiming>,
+
+    /// 実行時間を計測するかどうか（計測自体にもコストがかかるため既定は無効）
+    profiling: bool,
+
+    /// システムごとに実行するかどうか（systemsと同じ順序、既定は有効）
+    enabled: Vec<bool>,
+
+    /// システムごとに実行するスケジュールの名前（systemsと同じ順序、空の場合はすべてのスケジュール）
+    schedules: Vec<&'static [&'static str]>,
+
+    /// 現在のスケジュールの名前（Noneの場合はすべてのシステムを実行）
+    schedule: Option<&'static str>,
+}
+
+/// システム1つ分の実行時間の計測結果
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct SystemTiming {
+    /// システム名（型名の末尾部分）
+    pub name: &'static str,
+
+    /// 直近フレームの実行時間（ミリ秒）
+    pub last_ms: f64,
+
+    /// 実行時間の移動平均（ミリ秒）
+    pub average_ms: f64,
+
+    /// 計測開始からの最大実行時間（ミリ秒）
+    pub max_ms: f64,
+}
+
+impl SystemTiming {
+    /// 移動平均で直近の値が占める割合
+    const SMOOTHING: f64 = 0.1;
+
+    /// 計測前の状態を作成
+    fn new(name: &'static str) -> Self {
+        Self {
+            name: name.rsplit("::").next().unwrap_or(name),
+            last_ms: 0.0,
+            average_ms: 0.0,
+            max_ms: 0.0,
+        }
+    }
+
+    /// 1回分の実行時間を記録
+    fn record(&mut self, elapsed_ms: f64) {
+        self.last_ms = elapsed_ms;
+        self.average_ms += (elapsed_ms - self.average_ms) * Self::SMOOTHING;
+        self.max_ms = self.max_ms.max(elapsed_ms);
+    }
 }
 
 impl SystemScheduler {
@@ -527,6 +1352,11 @@ impl SystemScheduler {
     pub fn new() -> Self {
         Self {
             systems: Vec::new(),
+            timings: Vec::new(),
+            profiling: false,
+            enabled: Vec::new(),
+            schedules: Vec::new(),
+            schedule: None,
         }
     }
 
@@ -545,6 +1375,29 @@ impl SystemScheduler {
     /// scheduler.add_system(RenderSystem);
     /// ```
     pub fn add_system<T: System + 'static>(&mut self, system: T) {
+        self.add_system_to(system, &[]);
+    }
+
+    /// 指定したスケジュールでだけ実行するシステムを追加します
+    /// 
+    /// # 引数
+    /// * `system` - 追加するシステム
+    /// * `schedules` - 実行するスケジュールの名前（空の場合はすべてのスケジュールで実行）
+    /// 
+    /// # 例
+    /// ```rust
+    /// let mut scheduler = SystemScheduler::new();
+    /// scheduler.add_system_to(InputSystem, &["playing"]);
+    /// scheduler.set_schedule("main_menu"); // InputSystemは実行されない
+    /// ```
+    pub fn add_system_to<T: System + 'static>(
+        &mut self,
+        system: T,
+        schedules: &'static [&'static str],
+    ) {
+        self.timings.push(SystemTiming::new(system.name()));
+        self.enabled.push(true);
+        self.schedules.push(schedules);
         self.systems.push(Box::new(system));
     }
 
@@ -556,9 +1409,51 @@ impl SystemScheduler {
     /// 
     /// この関数は毎フレーム呼び出され、登録されたすべてのシステムを
     /// 順次実行します。システムの実行順序は登録順序と同じです。
+    /// 無効にしたシステムと、現在のスケジュールに含まれないシステムは実行しません。
     pub fn update(&mut self, world: &mut World, delta_time: f64) {
-        for system in &mut self.systems {
+        let schedule = self.schedule;
+        let runnable = self.enabled.iter().zip(&self.schedules).map(|(enabled, schedules)| {
+            *enabled && in_schedule(schedules, schedule)
+        });
+        let systems = self.systems.iter_mut().zip(&mut self.timings).zip(runnable);
+        for ((system, timing), _) in systems.filter(|(_, runnable)| *runnable) {
+            if !self.profiling {
+                system.update(world, delta_time);
+                continue;
+            }
+            let start = monotonic_ms();
+            system.update(world, delta_time);
+            timing.record(monotonic_ms() - start);
+        }
+    }
+
+    /// 全システムを実行し、実行したシステムごとの実行時間を知らせます
+    /// 
+    /// 実行するシステムはupdate()と同じです。計測が有効な場合はsystem_timings()も更新します。
+    /// 
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `delta_time` - 前フレームからの経過時間（秒）
+    /// * `record` - システム名と実行時間（ミリ秒）を受け取る関数
+    pub fn update_timed(
+        &mut self,
+        world: &mut World,
+        delta_time: f64,
+        mut record: impl FnMut(&'static str, f64),
+    ) {
+        let schedule = self.schedule;
+        let runnable = self.enabled.iter().zip(&self.schedules).map(|(enabled, schedules)| {
+            *enabled && in_schedule(schedules, schedule)
+        });
+        let systems = self.systems.iter_mut().zip(&mut self.timings).zip(runnable);
+        for ((system, timing), _) in systems.filter(|(_, runnable)| *runnable) {
+            let start = monotonic_ms();
             system.update(world, delta_time);
+            let elapsed_ms = monotonic_ms() - start;
+            if self.profiling {
+                timing.record(elapsed_ms);
+            }
+            record(timing.name, elapsed_ms);
         }
     }
 
@@ -569,6 +1464,112 @@ impl SystemScheduler {
     pub fn system_count(&self) -> usize {
         self.systems.len()
     }
+
+    /// 実行時間の計測を有効/無効にします
+    /// 
+    /// # 引数
+    /// * `enabled` - 計測する場合true
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+    }
+
+    /// システムごとの実行時間を取得します
+    /// 
+    /// # 戻り値
+    /// 登録順の計測結果（計測が無効の間は更新されない）
+    pub fn system_timings(&self) -> &[SystemTiming] {
+        &self.timings
+    }
+
+    /// 名前を指定してシステムを有効/無効にします
+    /// 
+    /// 無効にしたシステムは、有効に戻すまでupdate()で実行されません
+    /// （アニメーションの一時停止・通信の停止などのデバッグや、機能の切り替えに使います）。
+    /// 
+    /// # 引数
+    /// * `name` - システム名（型名の末尾部分、例："CardAnimationSystem"）
+    /// * `enabled` - 実行する場合true
+    /// 
+    /// # 戻り値
+    /// 指定した名前のシステムが登録されていた場合true
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let mut found = false;
+        for (timing, flag) in self.timings.iter().zip(&mut self.enabled) {
+            if timing.name == name {
+                *flag = enabled;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// システムが有効かどうかを取得します
+    /// 
+    /// # 引数
+    /// * `name` - システム名（型名の末尾部分）
+    /// 
+    /// # 戻り値
+    /// 有効な場合Some(true)、無効な場合Some(false)、登録されていない場合None
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.timings
+            .iter()
+            .position(|timing| timing.name == name)
+            .map(|index| self.enabled[index])
+    }
+
+    /// スケジュールを切り替えます
+    /// 
+    /// 以降のupdate()では、このスケジュールに含まれるシステム
+    /// （スケジュールを指定せずに追加したシステムを含む）だけを実行します。
+    /// 
+    /// # 引数
+    /// * `schedule` - スケジュールの名前
+    pub fn set_schedule(&mut self, schedule: &'static str) {
+        self.schedule = Some(schedule);
+    }
+
+    /// 現在のスケジュールの名前を取得します
+    /// 
+    /// # 戻り値
+    /// スケジュールの名前（切り替える前はNone）
+    pub fn schedule(&self) -> Option<&'static str> {
+        self.schedule
+    }
+
+    /// 現在のスケジュールで実行するシステムの名前を取得します
+    /// 
+    /// # 戻り値
+    /// 登録順のシステム名（無効にしているシステムを除く）
+    pub fn scheduled_systems(&self) -> Vec<&'static str> {
+        self.timings
+            .iter()
+            .zip(&self.enabled)
+            .zip(&self.schedules)
+            .filter(|((_, enabled), schedules)| **enabled && in_schedule(schedules, self.schedule))
+            .map(|((timing, _), _)| timing.name)
+            .collect()
+    }
+
+    /// 無効にしているシステムの名前を取得します
+    /// 
+    /// # 戻り値
+    /// 登録順のシステム名
+    pub fn disabled_systems(&self) -> Vec<&'static str> {
+        self.timings
+            .iter()
+            .zip(&self.enabled)
+            .filter(|(_, enabled)| !**enabled)
+            .map(|(timing, _)| timing.name)
+            .collect()
+    }
+}
+
+/// システムが現在のスケジュールで実行されるかどうか
+fn in_schedule(schedules: &[&'static str], schedule: Option<&'static str>) -> bool {
+    match schedule {
+        Some(schedule) => schedules.is_empty() || schedules.contains(&schedule),
+        None => true,
+    }
 }
 
 // =============================================================================
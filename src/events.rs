@@ -0,0 +1,307 @@
+// =============================================================================
+// ゲームイベント
+// =============================================================================
+// このファイルでは、ゲーム内で発生したイベント（実績解除など）を
+// JavaScript側へ通知するためのイベントキューを実装します。
+//
+// 仕組み：
+// - システムはEventQueueリソースにGameEventを追加する
+// - フレームの最後にイベントを取り出し、JavaScriptのコールバックへ渡す
+// - イベントはJSON文字列（"type"フィールドで種類を判別）として配信される
+// - プレイヤーに見せる文章はNotification（重要度・自動で閉じる時間付き）で送る
+// - 配信前のイベントはタイムライン（TimelineRecorder）にも記録される（直近のイベントの表示はそちらを読む）
+// =============================================================================
+
+use crate::ecs::Resource;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// ゲームイベント
+///
+/// JavaScript側へ通知するイベントの種類を定義します。
+/// JSONでは`{"type": "achievement_unlocked", ...}`の形式になります。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// 実績が解除された
+    AchievementUnlocked {
+        /// 実績ID
+        id: String,
+        /// 実績名
+        name: String,
+        /// 実績の説明
+        description: String,
+    },
+
+    /// ターンの残り時間が少なくなった
+    TurnTimeWarning {
+        /// ターン中のプレイヤーのエンティティID
+        player: u32,
+        /// ターン番号
+        turn_number: u32,
+        /// 残り時間（秒、持ち時間を含む）
+        remaining_seconds: u32,
+    },
+
+    /// ターンの制限時間が切れた
+    TurnTimedOut {
+        /// 時間切れになったプレイヤーのエンティティID
+        player: u32,
+        /// 時間切れになったターンの番号
+        turn_number: u32,
+        /// 代わりに実行した操作（"skip_turn" / "auto_draw" / "pass"）
+        auto_action: String,
+    },
+
+    /// プレイヤーのアクションが拒否された
+    ActionRejected {
+        /// アクションを送ったプレイヤーのエンティティID
+        player: u32,
+        /// 拒否されたアクションの種類（"move_card"など）
+        action: String,
+        /// 拒否の理由
+        reason: String,
+    },
+
+    /// パズルの目標を達成した
+    PuzzleSolved {
+        /// パズルID
+        id: String,
+        /// 使った手数
+        moves_used: u32,
+    },
+
+    /// パズルの手数を使い切った
+    PuzzleFailed {
+        /// パズルID
+        id: String,
+        /// 使った手数
+        moves_used: u32,
+    },
+    /// チュートリアルの手順をすべて終えた
+    TutorialCompleted {
+        /// チュートリアルID
+        id: String,
+    },
+
+    /// スコアが変わった
+    ScoreChanged {
+        /// 新しいスコア
+        score: u32,
+        /// 変わる前のスコア
+        previous: u32,
+    },
+
+    /// 手数が変わった
+    MovesChanged {
+        /// 新しい手数
+        move_count: u32,
+    },
+
+    /// ゲーム開始からの経過時間が1秒進んだ（ゲーム中のみ）
+    TimerTick {
+        /// 経過時間（秒）
+        elapsed_seconds: u64,
+    },
+
+    /// 移動元のタブローで裏向きのカードが表向きになった（手数には数えず得点だけ加わる）
+    CardRevealed {
+        /// 表向きになったカードのエンティティID
+        card_id: u32,
+        /// カードのあるタブローの列番号（0から開始）
+        column: u32,
+        /// 獲得したポイント
+        points: u32,
+    },
+
+    /// ウェイストをデッキに戻した
+    DeckRecycled {
+        /// デッキを戻した回数
+        deck_turns: u32,
+    },
+
+    /// しばらく操作がないので次の一手を知らせる（知らせたカードは点滅表示になる）
+    HintNudge {
+        /// 動かすカードのエンティティID（デッキから引く手の場合はNone）
+        card_id: Option<u32>,
+        /// 表示用メッセージ
+        message: String,
+        /// 最後の操作からの経過時間（秒）
+        idle_seconds: u32,
+    },
+
+    /// サーバーの正しい盤面に合わせ直した（位置の違うカードは手元の位置から動かす）
+    StateReconciled {
+        /// 正しい盤面のスコア
+        score: u32,
+        /// 合わせ直す前の手元のスコア（差は移動の得点ではない）
+        previous_score: u32,
+        /// 位置を直したカードの枚数
+        cards_moved: u32,
+    },
+
+    /// 前のフレームからの経過時間が大きく飛んだ（スリープ復帰など、ゲームは上限までしか進めない）
+    ClockJumpDetected {
+        /// 前のフレームからの経過時間（ミリ秒）
+        gap_ms: u64,
+        /// 進めずに捨てた経過時間（ミリ秒）
+        dropped_ms: u64,
+    },
+
+    /// 移動先に近づかないまま止まったカードのアニメーションを完了させた（不具合の調査用）
+    AnimationStalled {
+        /// 完了させたカードのエンティティID
+        card_id: u32,
+        /// 移動先に近づかなかったフレーム数
+        stalled_frames: u32,
+    },
+
+    /// 時間のかかる探索の進み具合（複数のフレームにまたがる探索のみ、終わると1.0）
+    SearchProgress {
+        /// 探索の種類（"winnability"：勝ち筋の確認）
+        search: String,
+        /// 進み具合（0.0〜1.0）
+        progress: f32,
+    },
+
+    /// サーバーとの接続状態が変わった
+    ConnectionChanged {
+        /// 新しい接続状態（"connecting" / "connected" / "disconnected" / "reconnecting" / "error" / "closed"）
+        status: String,
+    },
+
+    /// プレイヤーに知らせるメッセージ（トースト・確認ダイアログ用）
+    Notification {
+        /// 重要度（表示の色やアイコンに使う）
+        severity: NotificationSeverity,
+        /// 表示する文章
+        message: String,
+        /// 自動で閉じるまでの時間（ミリ秒、Noneの場合はプレイヤーが閉じるまで表示）
+        auto_dismiss_ms: Option<u32>,
+    },
+}
+
+impl GameEvent {
+    /// 通知イベントを作成（自動で閉じるまでの時間は重要度の既定値）
+    ///
+    /// # 引数
+    /// * `severity` - 重要度
+    /// * `message` - 表示する文章
+    pub fn notification(severity: NotificationSeverity, message: impl Into<String>) -> Self {
+        GameEvent::Notification {
+            severity,
+            message: message.into(),
+            auto_dismiss_ms: severity.default_auto_dismiss_ms(),
+        }
+    }
+}
+
+/// 通知の重要度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationSeverity {
+    /// お知らせ
+    Info,
+
+    /// 注意（減点・手詰まりなど）
+    Warning,
+
+    /// 実績の解除
+    Achievement,
+
+    /// エラー（接続が切れたなど）
+    Error,
+}
+
+impl NotificationSeverity {
+    /// 自動で閉じるまでの既定の時間を取得
+    ///
+    /// # 戻り値
+    /// ミリ秒（エラーは確認が必要なためNone）
+    pub fn default_auto_dismiss_ms(self) -> Option<u32> {
+        match self {
+            NotificationSeverity::Info => Some(3000),
+            NotificationSeverity::Warning => Some(5000),
+            NotificationSeverity::Achievement => Some(6000),
+            NotificationSeverity::Error => None,
+        }
+    }
+}
+
+/// イベントキューリソース
+///
+/// フレーム中に発生したイベントを溜めておき、
+/// フレームの最後にまとめて取り出します。
+#[derive(Debug, Default)]
+pub struct EventQueue {
+    /// 未配信のイベント
+    events: Vec<GameEvent>,
+
+    /// eventsのうちタイムラインに記録済みの件数
+    recorded: usize,
+}
+
+impl Resource for EventQueue {}
+
+impl EventQueue {
+    /// 新しい空のイベントキューを作成
+    ///
+    /// # 戻り値
+    /// 空のEventQueueインスタンス
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// イベントを追加
+    ///
+    /// # 引数
+    /// * `event` - 追加するイベント
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    /// 通知イベントを追加（自動で閉じるまでの時間は重要度の既定値）
+    ///
+    /// # 引数
+    /// * `severity` - 重要度
+    /// * `message` - 表示する文章
+    pub fn notify(&mut self, severity: NotificationSeverity, message: impl Into<String>) {
+        self.push(GameEvent::notification(severity, message));
+    }
+
+    /// まだタイムラインに記録していないイベントを取得し、記録済みにする
+    ///
+    /// # 戻り値
+    /// 発生順のイベントのコピー（キューには残る）
+    pub fn take_unrecorded(&mut self) -> Vec<GameEvent> {
+        let events = self.events[self.recorded..].to_vec();
+        self.recorded = self.events.len();
+        events
+    }
+
+    /// 溜まっているイベントをすべて取り出す
+    ///
+    /// # 戻り値
+    /// 発生順のイベントのベクター（キューは空になる）
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        self.recorded = 0;
+        std::mem::take(&mut self.events)
+    }
+
+    /// 未配信のイベント数を取得
+    ///
+    /// # 戻り値
+    /// イベント数
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// キューが空かどうかチェック
+    ///
+    /// # 戻り値
+    /// 空の場合true
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
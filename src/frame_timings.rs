@@ -0,0 +1,191 @@
+// =============================================================================
+// フレームごとの処理時間の記録
+// =============================================================================
+// このファイルでは、GameRuntimeの1フレームにかかった時間を、ゲームロジック・
+// アニメーション・状態のシリアライズ・通信の4つに分けて記録するFrameTimingHistoryを実装します。
+// フロントエンドはget_frame_timings()で直近のフレームの値を受け取り、WebAssembly側が
+// 16ms（60FPS）を超え続ける遅い端末では、影やアニメーションを切って描画を軽くできます。
+//
+// 仕組み：
+// - update()の開始時に新しいフレームを始め、各システムの実行時間を種類ごとに足し込む
+//   （どの種類に入れるかはシステム名で決める。通信の取り込み・送信は通信に入れる）
+// - get_solitaire_state()で状態を作ってJSONにした時間は、直近のフレームのシリアライズに足す
+// - 直近FRAME_TIMING_HISTORYフレーム分だけを残し、古いものから捨てる
+// - to_array()は1フレームあたりFIELDS_PER_FRAME個の値を古い順に並べた配列を返す
+//   （[ロジック, アニメーション, シリアライズ, 通信, ...]、単位はミリ秒）
+// =============================================================================
+
+use std::collections::VecDeque;
+
+/// 残しておくフレームの数（60FPSで1秒分）
+pub const FRAME_TIMING_HISTORY: usize = 60;
+
+/// to_array()で1フレームあたりに並べる値の数
+pub const FIELDS_PER_FRAME: usize = 4;
+
+/// 1フレームの時間予算（ミリ秒、60FPS）
+pub const FRAME_BUDGET_MS: f64 = 16.0;
+
+/// アニメーションとして数えるシステム
+const ANIMATION_SYSTEMS: &[&str] = &[
+    "AnimationQueueSystem",
+    "CardAnimationSystem",
+    "ReconcileSystem",
+    "AnimationWatchdogSystem",
+];
+
+/// 通信として数えるシステム
+const NETWORK_SYSTEMS: &[&str] = &[
+    "NetworkConnectionSystem",
+    "NetworkConditionerSystem",
+    "MessageProcessingSystem",
+];
+
+/// 処理時間の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimingCategory {
+    /// ゲームロジック（入力・移動・判定など）
+    Logic,
+
+    /// カードのアニメーション
+    Animation,
+
+    /// クライアント向け状態のシリアライズ
+    Serialization,
+
+    /// サーバーとの通信
+    Network,
+}
+
+impl TimingCategory {
+    /// システムの処理時間の種類
+    ///
+    /// # 引数
+    /// * `name` - システム名（型名の末尾部分、例："CardAnimationSystem"）
+    ///
+    /// # 戻り値
+    /// アニメーション・通信のシステムはそれぞれの種類、それ以外はLogic
+    pub fn of_system(name: &str) -> Self {
+        if ANIMATION_SYSTEMS.contains(&name) {
+            TimingCategory::Animation
+        } else if NETWORK_SYSTEMS.contains(&name) {
+            TimingCategory::Network
+        } else {
+            TimingCategory::Logic
+        }
+    }
+}
+
+/// 1フレーム分の種類ごとの処理時間（ミリ秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTimings {
+    /// ゲームロジック
+    pub logic_ms: f64,
+
+    /// アニメーション
+    pub animation_ms: f64,
+
+    /// シリアライズ
+    pub serialization_ms: f64,
+
+    /// 通信
+    pub network_ms: f64,
+}
+
+impl FrameTimings {
+    /// 処理時間を足し込む
+    ///
+    /// # 引数
+    /// * `category` - 処理時間の種類
+    /// * `elapsed_ms` - かかった時間（ミリ秒）
+    pub fn add(&mut self, category: TimingCategory, elapsed_ms: f64) {
+        let slot = match category {
+            TimingCategory::Logic => &mut self.logic_ms,
+            TimingCategory::Animation => &mut self.animation_ms,
+            TimingCategory::Serialization => &mut self.serialization_ms,
+            TimingCategory::Network => &mut self.network_ms,
+        };
+        *slot += elapsed_ms.max(0.0);
+    }
+
+    /// フレーム全体の処理時間（ミリ秒）
+    pub fn total_ms(&self) -> f64 {
+        self.logic_ms + self.animation_ms + self.serialization_ms + self.network_ms
+    }
+}
+
+/// 直近のフレームの処理時間
+#[derive(Debug, Clone, Default)]
+pub struct FrameTimingHistory {
+    /// フレームごとの処理時間（古い順、最後が現在のフレーム）
+    frames: VecDeque<FrameTimings>,
+}
+
+impl FrameTimingHistory {
+    /// 記録のない状態を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 新しいフレームを始める（残す数を超えた古いフレームは捨てる）
+    pub fn begin_frame(&mut self) {
+        if self.frames.len() == FRAME_TIMING_HISTORY {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(FrameTimings::default());
+    }
+
+    /// 現在のフレームに処理時間を足し込む（フレームを始める前なら始める）
+    ///
+    /// # 引数
+    /// * `category` - 処理時間の種類
+    /// * `elapsed_ms` - かかった時間（ミリ秒）
+    pub fn add(&mut self, category: TimingCategory, elapsed_ms: f64) {
+        if self.frames.is_empty() {
+            self.begin_frame();
+        }
+        if let Some(frame) = self.frames.back_mut() {
+            frame.add(category, elapsed_ms);
+        }
+    }
+
+    /// 記録しているフレーム（古い順）
+    pub fn frames(&self) -> impl Iterator<Item = &FrameTimings> {
+        self.frames.iter()
+    }
+
+    /// 時間予算を超えたフレームの割合（0.0〜1.0、記録がない場合は0.0）
+    ///
+    /// # 引数
+    /// * `budget_ms` - 1フレームの時間予算（ミリ秒）
+    pub fn over_budget_ratio(&self, budget_ms: f64) -> f64 {
+        if self.frames.is_empty() {
+            return 0.0;
+        }
+        let over = self
+            .frames
+            .iter()
+            .filter(|frame| frame.total_ms() > budget_ms)
+            .count();
+        over as f64 / self.frames.len() as f64
+    }
+
+    /// フレームごとの処理時間を1つの配列に並べる（get_frame_timings()の戻り値）
+    ///
+    /// # 戻り値
+    /// [ロジック, アニメーション, シリアライズ, 通信]をフレームの古い順に並べた配列（ミリ秒）
+    pub fn to_array(&self) -> Vec<f32> {
+        self.frames
+            .iter()
+            .flat_map(|frame| {
+                [
+                    frame.logic_ms,
+                    frame.animation_ms,
+                    frame.serialization_ms,
+                    frame.network_ms,
+                ]
+            })
+            .map(|ms| ms as f32)
+            .collect()
+    }
+}
@@ -0,0 +1,274 @@
+// =============================================================================
+// フレンドとルームへの招待（サーバー用）
+// =============================================================================
+// このファイルでは、プレイヤーごとのフレンドの一覧を保存するFriendStoreと、
+// 返事を待っているルームへの招待を管理するInviteBookを実装します。
+//
+// 仕組み：
+// - プレイヤーIDは接続ごとに変わり、表示名は変えられるため、フレンドの一覧は本人のセッショントークンをキーにして保存し、
+//   フレンドもセッショントークンで覚える（名前は最後に見たものを表示用に残す）
+// - フレンドの追加は接続中のプレイヤーのIDで行い、外すときは名前で指定する
+// - 在席状況（オフライン・接続中・ルームに参加中）は保存せず、接続中のプレイヤーから求める
+// - 在席状況はお互いにフレンドにしている相手にだけ知らせる（片方だけの追加では分からない）
+// - 招待は保存せず、INVITE_TTL_SECONDS秒で期限切れになる（招待した人・された人の切断でも消える）
+// =============================================================================
+
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use uuid::Uuid;
+
+/// フレンドの一覧の保存キー
+const STORAGE_KEY: &str = "friends";
+
+/// 1人が登録できるフレンドの数
+pub const MAX_FRIENDS: usize = 100;
+
+/// 招待の有効期間（秒）
+pub const INVITE_TTL_SECONDS: u64 = 300;
+
+/// フレンド1人分の記録
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FriendEntry {
+    /// フレンドのセッショントークン（表示名を変えても変わらない）
+    pub session_token: String,
+
+    /// 最後に見たフレンドの表示名（オフラインの間の表示と、外すときの指定に使う）
+    pub player_name: String,
+}
+
+/// 全プレイヤーのフレンドの一覧
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FriendStore {
+    /// セッショントークン → フレンド（追加した順）
+    records: HashMap<String, Vec<FriendEntry>>,
+}
+
+impl FriendStore {
+    /// 保存されているフレンドの一覧を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のFriendStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// フレンドの一覧を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("フレンドの一覧のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// プレイヤーのフレンドを取得
+    ///
+    /// # 引数
+    /// * `session_token` - プレイヤーのセッショントークン
+    ///
+    /// # 戻り値
+    /// 追加した順のスライス（フレンドがいない場合は空）
+    pub fn friends(&self, session_token: &str) -> &[FriendEntry] {
+        self.records
+            .get(session_token)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// フレンドを追加する
+    ///
+    /// # 引数
+    /// * `session_token` - 追加するプレイヤーのセッショントークン
+    /// * `friend_token` - フレンドにするプレイヤーのセッショントークン
+    /// * `friend_name` - フレンドにするプレイヤーの今の表示名
+    ///
+    /// # 戻り値
+    /// 追加した場合Ok(true)、既にフレンドの場合Ok(false)（表示名は新しくする）、上限に達している場合はエラーメッセージ
+    pub fn add(
+        &mut self,
+        session_token: &str,
+        friend_token: &str,
+        friend_name: &str,
+    ) -> Result<bool, String> {
+        let friends = self.records.entry(session_token.to_string()).or_default();
+        if let Some(friend) = friends
+            .iter_mut()
+            .find(|friend| friend.session_token == friend_token)
+        {
+            friend.player_name = friend_name.to_string();
+            return Ok(false);
+        }
+        if friends.len() >= MAX_FRIENDS {
+            return Err(format!("フレンドは{}人まで登録できます", MAX_FRIENDS));
+        }
+        friends.push(FriendEntry {
+            session_token: friend_token.to_string(),
+            player_name: friend_name.to_string(),
+        });
+        Ok(true)
+    }
+
+    /// フレンドを外す
+    ///
+    /// # 引数
+    /// * `session_token` - 外すプレイヤーのセッショントークン
+    /// * `friend_name` - 外すフレンドの名前
+    ///
+    /// # 戻り値
+    /// 外した場合true、フレンドではなかった場合false
+    pub fn remove(&mut self, session_token: &str, friend_name: &str) -> bool {
+        let Some(friends) = self.records.get_mut(session_token) else {
+            return false;
+        };
+        let before = friends.len();
+        friends.retain(|friend| friend.player_name != friend_name);
+        let removed = friends.len() != before;
+        if friends.is_empty() {
+            self.records.remove(session_token);
+        }
+        removed
+    }
+
+    /// 指定したプレイヤーをフレンドにしているか
+    ///
+    /// # 引数
+    /// * `session_token` - 確認するプレイヤーのセッショントークン
+    /// * `friend_token` - フレンドかどうか確認するプレイヤーのセッショントークン
+    pub fn is_friend(&self, session_token: &str, friend_token: &str) -> bool {
+        self.friends(session_token)
+            .iter()
+            .any(|friend| friend.session_token == friend_token)
+    }
+
+    /// 2人がお互いをフレンドにしているか（在席状況を知らせてよいか）
+    ///
+    /// # 引数
+    /// * `session_token` - 1人目のセッショントークン
+    /// * `other_token` - 2人目のセッショントークン
+    pub fn is_mutual(&self, session_token: &str, other_token: &str) -> bool {
+        self.is_friend(session_token, other_token) && self.is_friend(other_token, session_token)
+    }
+
+    /// プレイヤーの表示名が変わったとき、そのプレイヤーをフレンドにしている記録の名前を新しくする
+    ///
+    /// # 引数
+    /// * `friend_token` - 表示名が変わったプレイヤーのセッショントークン
+    /// * `friend_name` - 新しい表示名
+    ///
+    /// # 戻り値
+    /// 記録を書き換えた場合true
+    pub fn rename(&mut self, friend_token: &str, friend_name: &str) -> bool {
+        let mut renamed = false;
+        for friend in self.records.values_mut().flatten() {
+            if friend.session_token == friend_token && friend.player_name != friend_name {
+                friend.player_name = friend_name.to_string();
+                renamed = true;
+            }
+        }
+        renamed
+    }
+}
+
+/// 返事を待っているルームへの招待
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingInvite {
+    /// 招待先のルームID
+    pub room_id: String,
+
+    /// 招待したプレイヤーのID
+    pub from_player_id: String,
+
+    /// 招待されたプレイヤーのID
+    pub to_player_id: String,
+
+    /// 期限
+    pub expires_at: SystemTime,
+}
+
+/// 返事を待っている招待の一覧
+#[derive(Debug, Clone, Default)]
+pub struct InviteBook {
+    /// 招待ID → 招待
+    invites: HashMap<String, PendingInvite>,
+}
+
+impl InviteBook {
+    /// 招待を作成する（期限切れの招待はここで捨てる）
+    ///
+    /// # 引数
+    /// * `room_id` - 招待先のルームID
+    /// * `from_player_id` - 招待したプレイヤーのID
+    /// * `to_player_id` - 招待されたプレイヤーのID
+    /// * `now` - 現在時刻
+    ///
+    /// # 戻り値
+    /// 招待ID
+    pub fn create(
+        &mut self,
+        room_id: &str,
+        from_player_id: &str,
+        to_player_id: &str,
+        now: SystemTime,
+    ) -> String {
+        self.invites.retain(|_, invite| invite.expires_at > now);
+
+        let invite_id = Uuid::new_v4().to_string();
+        self.invites.insert(
+            invite_id.clone(),
+            PendingInvite {
+                room_id: room_id.to_string(),
+                from_player_id: from_player_id.to_string(),
+                to_player_id: to_player_id.to_string(),
+                expires_at: now + Duration::from_secs(INVITE_TTL_SECONDS),
+            },
+        );
+        invite_id
+    }
+
+    /// 招待された本人の返事を受けて、招待を一覧から取り出す
+    ///
+    /// # 引数
+    /// * `invite_id` - 招待ID
+    /// * `player_id` - 返事をしたプレイヤーのID
+    /// * `now` - 現在時刻
+    ///
+    /// # 戻り値
+    /// 招待、存在しない・本人宛てではない・期限切れの場合はエラーメッセージ
+    pub fn take(
+        &mut self,
+        invite_id: &str,
+        player_id: &str,
+        now: SystemTime,
+    ) -> Result<PendingInvite, String> {
+        match self.invites.get(invite_id) {
+            None => return Err("招待が見つかりません".to_string()),
+            Some(invite) if invite.to_player_id != player_id => {
+                return Err("他のプレイヤーへの招待には返事できません".to_string());
+            }
+            Some(_) => {}
+        }
+        let invite = self
+            .invites
+            .remove(invite_id)
+            .ok_or_else(|| "招待が見つかりません".to_string())?;
+        if invite.expires_at <= now {
+            return Err("招待の期限が切れています".to_string());
+        }
+        Ok(invite)
+    }
+
+    /// 切断したプレイヤーが招待した・招待された招待を捨てる
+    ///
+    /// # 引数
+    /// * `player_id` - 切断したプレイヤーのID
+    pub fn remove_player(&mut self, player_id: &str) {
+        self.invites.retain(|_, invite| {
+            invite.from_player_id != player_id && invite.to_player_id != player_id
+        });
+    }
+}
@@ -13,10 +13,25 @@
 // - ゲーム状態の永続化とシリアライゼーション
 // =============================================================================
 
-use crate::ecs::{World, Entity, Component, System};
+use crate::clock::GameClock;
+use crate::ecs::{World, Entity, Component, ComponentPool, MessageQueue, Resource, System};
+use crate::events::{EventQueue, GameEvent};
+use crate::hint::{HintEngine, HintLocation};
+use crate::permissions;
+use crate::protocol::MoveLocation;
+use crate::solitaire::SolitaireManager;
+use log::{debug, info, warn};
 use serde::{Serialize, Deserialize};
-use std::collections::VecDeque;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{HashMap, VecDeque};
+
+/// アクションの内容（JSON）の最大サイズ（バイト）
+const MAX_ACTION_PAYLOAD_BYTES: usize = 4 * 1024;
+
+/// 1回のカード移動で動かせる最大枚数（キングからエースまで）
+const MAX_MOVE_CARD_COUNT: usize = 13;
+
+/// チャットメッセージの最大長（バイト）
+const MAX_CHAT_BYTES: usize = 1024;
 
 // =============================================================================
 // ゲーム状態関連のコンポーネント定義
@@ -55,17 +70,15 @@ impl GameState {
     /// # 引数
     /// * `session_id` - ゲームセッションID
     /// * `max_players` - 最大プレイヤー数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 初期化されたGameStateインスタンス
-    pub fn new(session_id: String, max_players: u32) -> Self {
+    pub fn new(session_id: String, max_players: u32, clock: &GameClock) -> Self {
         Self {
             session_id,
             phase: GamePhase::WaitingForPlayers,
-            start_time: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            start_time: clock.now_secs(),
             max_players,
             current_players: 0,
             settings: GameSettings::default(),
@@ -200,6 +213,15 @@ pub struct GameSettings {
     /// ターン制限時間（秒）
     pub turn_time_limit: u32,
     
+    /// ターンの残り時間を警告するタイミング（残り秒数）
+    pub turn_warning_seconds: Vec<u32>,
+    
+    /// ターンの制限時間が切れたときに代わりに実行する操作
+    pub turn_timeout_action: TurnTimeoutAction,
+    
+    /// プレイヤーごとの持ち時間（秒）。ターンの制限時間を超えた分はここから消費する
+    pub turn_timebank_seconds: u32,
+    
     /// デバッグモードの有効/無効
     pub debug_mode: bool,
     
@@ -208,16 +230,142 @@ pub struct GameSettings {
     
     /// 観戦者の許可/禁止
     pub allow_spectators: bool,
+    
+    /// カードのアニメーションの速さ・省略の設定
+    #[serde(default)]
+    pub animation: AnimationSettings,
+    
+    /// 操作がないときに次の一手を知らせるまでの時間（秒）。0の場合は知らせない
+    #[serde(default = "default_idle_hint_seconds")]
+    pub idle_hint_seconds: u32,
+    
+    /// 勝ち筋がなくなったかの確認の設定
+    #[serde(default)]
+    pub unwinnable_check: UnwinnableCheckSettings,
+}
+
+/// 操作がないときに次の一手を知らせるまでの標準の時間（秒）
+pub const DEFAULT_IDLE_HINT_SECONDS: u32 = 30;
+
+fn default_idle_hint_seconds() -> u32 {
+    DEFAULT_IDLE_HINT_SECONDS
 }
 
+// ゲームランタイムではワールド全体の設定としてリソースにも登録する
+impl Resource for GameSettings {}
+
 impl Default for GameSettings {
     fn default() -> Self {
         Self {
             time_limit: 0,          // 制限なし
             turn_time_limit: 30,    // 30秒
+            turn_warning_seconds: vec![10, 5],
+            turn_timeout_action: TurnTimeoutAction::SkipTurn,
+            turn_timebank_seconds: 0, // 持ち時間なし
             debug_mode: false,
             auto_save: true,
             allow_spectators: true,
+            animation: AnimationSettings::default(),
+            idle_hint_seconds: DEFAULT_IDLE_HINT_SECONDS,
+            unwinnable_check: UnwinnableCheckSettings::default(),
+        }
+    }
+}
+
+/// アニメーションの速さの倍率の下限
+pub const MIN_ANIMATION_SPEED: f32 = 0.25;
+
+/// アニメーションの速さの倍率の上限
+pub const MAX_ANIMATION_SPEED: f32 = 4.0;
+
+/// 視差効果を減らす設定のときにカードの移動を速める倍率（動いている時間を短くする）
+pub const REDUCED_MOTION_SPEEDUP: f32 = 3.0;
+
+/// アニメーション設定
+/// 
+/// カードの移動の速さと、アニメーションを省略するかどうかを管理します。
+/// - `instant`：すべてのアニメーションを省略し、カードをすぐに移動先へ置く
+/// - `reduced_motion`：盤面の合わせ直し・配る・勝利などの演出を省略し、カードの移動は
+///   REDUCED_MOTION_SPEEDUP倍の速さで短く済ませる（どこからどこへ動いたかは分かるように残す）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AnimationSettings {
+    /// 速さの倍率（1.0で標準、MIN_ANIMATION_SPEED〜MAX_ANIMATION_SPEED）
+    pub speed_multiplier: f32,
+    
+    /// すべてのアニメーションを省略する
+    pub instant: bool,
+    
+    /// 演出のためのアニメーションを省略する（OSの「視差効果を減らす」設定に合わせる）
+    pub reduced_motion: bool,
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            speed_multiplier: 1.0,
+            instant: false,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl AnimationSettings {
+    /// 設定値が範囲内かチェック
+    /// 
+    /// # 戻り値
+    /// 範囲内の場合Ok(())、範囲外の場合はエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_ANIMATION_SPEED..=MAX_ANIMATION_SPEED).contains(&self.speed_multiplier) {
+            return Err(format!(
+                "アニメーションの速さは{}〜{}倍で指定してください: {}",
+                MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED, self.speed_multiplier
+            ));
+        }
+        Ok(())
+    }
+    
+    /// 盤面の合わせ直し・配る・勝利などの演出のアニメーションを再生するか
+    pub fn plays_decorations(&self) -> bool {
+        !self.instant && !self.reduced_motion
+    }
+
+    /// カードの移動の速さの倍率（視差効果を減らす設定ではさらにREDUCED_MOTION_SPEEDUP倍）
+    pub fn move_speed(&self) -> f32 {
+        if self.reduced_motion {
+            self.speed_multiplier * REDUCED_MOTION_SPEEDUP
+        } else {
+            self.speed_multiplier
+        }
+    }
+}
+
+/// 勝ち筋の確認で調べる局面数の標準の上限
+pub const DEFAULT_SOLVER_SEARCH_LIMIT: u32 = 5_000;
+
+/// 勝ち筋がなくなったかの確認の設定
+/// 
+/// 手が打たれるたびにソルバーで現在の局面を調べ、勝てなくなったら通知します。
+/// 調べる局面数が上限を超えた場合は判定を保留し、次の確認に回します。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct UnwinnableCheckSettings {
+    /// 何手ごとに確認するか（0の場合は自動では確認しない）
+    pub check_every_moves: u32,
+    
+    /// ソルバーが調べる局面数の上限
+    pub search_limit: u32,
+    
+    /// 勝ち筋がなくなったときに「やり直しますか？」と通知するか
+    pub notify: bool,
+}
+
+impl Default for UnwinnableCheckSettings {
+    fn default() -> Self {
+        Self {
+            check_every_moves: 1,
+            search_limit: DEFAULT_SOLVER_SEARCH_LIMIT,
+            notify: true,
         }
     }
 }
@@ -242,6 +390,12 @@ pub struct TurnManager {
     
     /// ターン制限時間（秒）
     pub turn_time_limit: u32,
+    
+    /// プレイヤーごとの残り持ち時間（秒）
+    pub timebanks: HashMap<Entity, u32>,
+    
+    /// 現在のターンで最後に警告した残り秒数（未警告の場合はNone）
+    pub last_warning: Option<u32>,
 }
 
 impl Component for TurnManager {}
@@ -252,10 +406,11 @@ impl TurnManager {
     /// # 引数
     /// * `players` - プレイヤーエンティティのリスト
     /// * `turn_time_limit` - ターン制限時間（秒）
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 初期化されたTurnManagerインスタンス
-    pub fn new(players: Vec<Entity>, turn_time_limit: u32) -> Self {
+    pub fn new(players: Vec<Entity>, turn_time_limit: u32, clock: &GameClock) -> Self {
         let turn_order = VecDeque::from(players);
         let current_player = turn_order.front().copied();
         
@@ -263,19 +418,50 @@ impl TurnManager {
             current_player,
             turn_order,
             turn_number: 1,
-            turn_start_time: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            turn_start_time: clock.now_secs(),
             turn_time_limit,
+            timebanks: HashMap::new(),
+            last_warning: None,
         }
     }
     
+    /// プレイヤーの持ち時間を設定
+    /// 
+    /// # 引数
+    /// * `player` - プレイヤーのエンティティID
+    /// * `seconds` - 持ち時間（秒）
+    pub fn set_timebank(&mut self, player: Entity, seconds: u32) {
+        self.timebanks.insert(player, seconds);
+    }
+    
+    /// プレイヤーの残り持ち時間を取得
+    /// 
+    /// # 引数
+    /// * `player` - プレイヤーのエンティティID
+    /// 
+    /// # 戻り値
+    /// 残り持ち時間（秒）。設定されていない場合は0
+    pub fn timebank(&self, player: Entity) -> u32 {
+        self.timebanks.get(&player).copied().unwrap_or(0)
+    }
+    
     /// 次のプレイヤーにターンを移す
     /// 
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    /// 
     /// # 戻り値
     /// 次のプレイヤーのエンティティID（Noneの場合は全員のターンが終了）
-    pub fn next_turn(&mut self) -> Option<Entity> {
+    pub fn next_turn(&mut self, clock: &GameClock) -> Option<Entity> {
+        // 制限時間を超えて使った分を持ち時間から差し引く
+        if let Some(player) = self.current_player {
+            let elapsed = clock.now_secs().saturating_sub(self.turn_start_time);
+            let overtime = elapsed.saturating_sub(self.turn_time_limit as u64);
+            if let Some(bank) = self.timebanks.get_mut(&player) {
+                *bank = bank.saturating_sub(overtime.min(u32::MAX as u64) as u32);
+            }
+        }
+        
         if let Some(current) = self.turn_order.pop_front() {
             // 現在のプレイヤーを末尾に移動（ラウンドロビン）
             self.turn_order.push_back(current);
@@ -283,44 +469,63 @@ impl TurnManager {
         
         // 次のプレイヤーを設定
         self.current_player = self.turn_order.front().copied();
-        self.turn_number += 1;
-        self.turn_start_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.turn_number = self.turn_number.saturating_add(1);
+        self.turn_start_time = clock.now_secs();
+        self.last_warning = None;
         
         self.current_player
     }
     
     /// 現在のターンの残り時間を取得
     /// 
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    /// 
     /// # 戻り値
-    /// 残り時間（秒）。制限なしの場合はNone
-    pub fn remaining_time(&self) -> Option<u32> {
+    /// 残り時間（秒、現在のプレイヤーの持ち時間を含む）。制限なしの場合はNone
+    pub fn remaining_time(&self, clock: &GameClock) -> Option<u32> {
         if self.turn_time_limit == 0 {
             return None; // 制限なし
         }
         
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        let elapsed = current_time.saturating_sub(self.turn_start_time);
+        let elapsed = clock.now_secs().saturating_sub(self.turn_start_time);
+        let timebank = self.current_player.map_or(0, |player| self.timebank(player));
+        let allowed = self.turn_time_limit as u64 + timebank as u64;
         
-        if elapsed >= self.turn_time_limit as u64 {
-            Some(0) // 時間切れ
-        } else {
-            Some(self.turn_time_limit - elapsed as u32)
-        }
+        // 時間切れの場合は0
+        Some(allowed.saturating_sub(elapsed) as u32)
+    }
+    
+    /// 警告すべき残り秒数を判定
+    /// 
+    /// 残り時間がしきい値以下になったしきい値のうち、このターンでまだ警告していない
+    /// 最も小さいものを返します。ターン開始直後に警告しないよう、
+    /// 制限時間以上のしきい値は無視します。
+    /// 
+    /// # 引数
+    /// * `remaining` - 残り時間（秒）
+    /// * `thresholds` - 警告するタイミング（残り秒数）
+    /// 
+    /// # 戻り値
+    /// 警告する場合はSome(しきい値)、警告しない場合はNone
+    pub fn pending_warning(&self, remaining: u32, thresholds: &[u32]) -> Option<u32> {
+        thresholds
+            .iter()
+            .copied()
+            .filter(|&threshold| threshold < self.turn_time_limit && remaining <= threshold)
+            .filter(|&threshold| self.last_warning.is_none_or(|last| threshold < last))
+            .min()
     }
     
     /// ターンの制限時間が切れているかチェック
     /// 
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    /// 
     /// # 戻り値
     /// 時間切れの場合true、まだ時間がある場合false
-    pub fn is_time_up(&self) -> bool {
-        if let Some(remaining) = self.remaining_time() {
+    pub fn is_time_up(&self, clock: &GameClock) -> bool {
+        if let Some(remaining) = self.remaining_time(clock) {
             remaining == 0
         } else {
             false // 制限なしの場合は常にfalse
@@ -364,14 +569,11 @@ pub struct GameAction {
     /// 行動を行ったプレイヤーのエンティティID
     pub player: Entity,
     
-    /// 行動の種類
-    pub action_type: ActionType,
+    /// 行動の内容（種類ごとの詳細データを含む）
+    pub payload: ActionPayload,
     
     /// 行動のタイムスタンプ
     pub timestamp: u64,
-    
-    /// 行動の詳細データ（JSON形式）
-    pub data: Option<String>,
 }
 
 impl Component for GameAction {}
@@ -381,24 +583,47 @@ impl GameAction {
     /// 
     /// # 引数
     /// * `player` - 行動を行ったプレイヤー
-    /// * `action_type` - 行動の種類
-    /// * `data` - 行動の詳細データ（オプション）
+    /// * `payload` - 行動の内容
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 新しいGameActionインスタンス
-    pub fn new(player: Entity, action_type: ActionType, data: Option<String>) -> Self {
+    pub fn new(player: Entity, payload: ActionPayload, clock: &GameClock) -> Self {
         Self {
             player,
-            action_type,
-            timestamp: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
-            data,
+            payload,
+            timestamp: clock.now_secs(),
         }
     }
+    
+    /// プールから取り出したアクションを新しい内容で初期化し直す
+    /// 
+    /// # 引数
+    /// * `player` - 行動を行ったプレイヤー
+    /// * `payload` - 行動の内容
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    fn reset(&mut self, player: Entity, payload: ActionPayload, clock: &GameClock) {
+        self.player = player;
+        self.payload = payload;
+        self.timestamp = clock.now_secs();
+    }
+    
+    /// 行動の種類を取得
+    pub fn action_type(&self) -> ActionType {
+        self.payload.action_type()
+    }
 }
 
+/// ゲームアクションのプール（リソース）
+/// 
+/// アクションは毎フレーム作成・破棄されるため、処理済みのアクションを再利用します。
+pub type GameActionPool = ComponentPool<GameAction>;
+
+/// 処理を待っているゲームアクションのキュー（リソース）
+/// 
+/// ActionProcessingSystemが次のフレームで記録した順に処理します。
+pub type ActionQueue = MessageQueue<GameAction>;
+
 /// ゲーム内で発生する行動の種類
 /// 
 /// プレイヤーが実行可能な全ての行動を定義します。
@@ -445,6 +670,149 @@ impl ActionType {
     }
 }
 
+/// ゲームアクションの内容
+/// 
+/// アクション処理・通信で共通に使う、種類ごとの詳細データを持つ型です。
+/// JSONでは`{"type": "move_card", "from": {"type": "tableau", "position": 3}, ...}`の形式になります。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionPayload {
+    /// 移動元の一番上から`count`枚のカードを移動先へ移動
+    MoveCard {
+        /// 移動元の場所
+        from: MoveLocation,
+        /// 移動先の場所
+        to: MoveLocation,
+        /// 移動する枚数（省略時は1枚）
+        #[serde(default = "default_move_count")]
+        count: usize,
+    },
+    
+    /// タブロー列の一番上のカードを表向きにする
+    FlipCard {
+        /// タブローの列番号（0から開始）
+        column: u32,
+    },
+    
+    /// デッキからカードを引く
+    DrawCard,
+    
+    /// ターンを終了
+    EndTurn,
+    
+    /// ゲームから退出
+    LeaveGame,
+    
+    /// チャットメッセージ送信
+    Chat {
+        /// 本文
+        text: String,
+    },
+    
+    /// ゲーム設定変更
+    ChangeSettings {
+        /// 変更後の設定
+        settings: GameSettings,
+    },
+}
+
+/// MoveCardで枚数が省略された場合の値
+fn default_move_count() -> usize {
+    1
+}
+
+impl ActionPayload {
+    /// 受信したJSONを解析し、内容を検証する
+    /// 
+    /// # 引数
+    /// * `json` - アクション内容のJSON文字列
+    /// 
+    /// # 戻り値
+    /// 成功時はActionPayload、サイズ超過・形式不正の場合はエラーメッセージ
+    pub fn parse(json: &str) -> Result<Self, String> {
+        if json.len() > MAX_ACTION_PAYLOAD_BYTES {
+            return Err(format!("アクションの内容が大きすぎます（{}バイト）", json.len()));
+        }
+        
+        let payload: Self = serde_json::from_str(json)
+            .map_err(|e| format!("アクションの内容の形式が不正です: {}", e))?;
+        payload.validate()?;
+        Ok(payload)
+    }
+    
+    /// 値の範囲を検証する
+    /// 
+    /// 場所指定の範囲はデシリアライズ時に検証済みのため、ここでは枚数や文字数を確認します。
+    /// 
+    /// # 戻り値
+    /// 問題がなければOk(())、不正な値があればエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ActionPayload::MoveCard { count, .. } if !(1..=MAX_MOVE_CARD_COUNT).contains(count) => {
+                Err(format!("移動する枚数が不正です: {}", count))
+            }
+            ActionPayload::Chat { text } if text.len() > MAX_CHAT_BYTES => {
+                Err(format!("チャットメッセージが長すぎます（{}バイト）", text.len()))
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// アクションの種類を取得
+    pub fn action_type(&self) -> ActionType {
+        match self {
+            ActionPayload::MoveCard { .. } => ActionType::MoveCard,
+            ActionPayload::FlipCard { .. } => ActionType::FlipCard,
+            ActionPayload::DrawCard => ActionType::DrawCard,
+            ActionPayload::EndTurn => ActionType::EndTurn,
+            ActionPayload::LeaveGame => ActionType::LeaveGame,
+            ActionPayload::Chat { .. } => ActionType::SendMessage,
+            ActionPayload::ChangeSettings { .. } => ActionType::ChangeSettings,
+        }
+    }
+}
+
+/// ターンの制限時間が切れたときの操作
+/// 
+/// 時間切れのプレイヤーに代わって実行する操作を定義します。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TurnTimeoutAction {
+    /// 何もせずに次のプレイヤーへ移る
+    SkipTurn,
+    
+    /// カードを1枚引いてからターンを終了
+    AutoDraw,
+    
+    /// ターン終了の操作を実行して次のプレイヤーへ移る
+    Pass,
+}
+
+impl TurnTimeoutAction {
+    /// 操作名を文字列で取得
+    /// 
+    /// # 戻り値
+    /// 操作名の文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TurnTimeoutAction::SkipTurn => "skip_turn",
+            TurnTimeoutAction::AutoDraw => "auto_draw",
+            TurnTimeoutAction::Pass => "pass",
+        }
+    }
+    
+    /// 代わりに実行するゲームアクション
+    /// 
+    /// # 戻り値
+    /// 実行するアクションの内容（何もしない場合はNone）
+    pub fn payload(&self) -> Option<ActionPayload> {
+        match self {
+            TurnTimeoutAction::SkipTurn => None,
+            TurnTimeoutAction::AutoDraw => Some(ActionPayload::DrawCard),
+            TurnTimeoutAction::Pass => Some(ActionPayload::EndTurn),
+        }
+    }
+}
+
 // =============================================================================
 // ゲーム状態管理システム群
 // =============================================================================
@@ -498,7 +866,7 @@ impl System for GameManagementSystem {
                     game_state.change_phase(new_phase);
                     
                     // フェーズ変更をログ出力
-                    println!(
+                    info!(
                         "🎮 ゲーム状態変更: {} -> {} (セッション: {})",
                         game_state.phase.as_str(),
                         new_phase.as_str(),
@@ -513,44 +881,106 @@ impl System for GameManagementSystem {
 /// ターン管理システム
 /// 
 /// プレイヤーのターン制御と時間管理を行うシステムです。
-/// ターンの切り替えや制限時間の監視を担当します。
+/// 残り時間が少なくなると警告イベントを通知し、時間切れになると
+/// 設定された操作（GameSettings::turn_timeout_action）を代わりに実行してからターンを進めます。
 pub struct TurnManagementSystem;
 
 impl System for TurnManagementSystem {
     fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let clock = GameClock::from_world(world);
+        let settings = world.get_resource::<GameSettings>().cloned().unwrap_or_default();
+        let mut warnings = Vec::new();
         let mut turn_changes = Vec::new();
         
         for (entity, turn_manager) in world.query::<TurnManager>() {
+            // 制限なしの場合は何もしない
+            let Some(remaining) = turn_manager.remaining_time(&clock) else {
+                continue;
+            };
+            
             // ターンの制限時間をチェック
-            if turn_manager.is_time_up() {
-                println!(
+            if remaining == 0 {
+                info!(
                     "⏰ ターン制限時間切れ: プレイヤー {:?} (ターン {})",
                     turn_manager.current_player,
                     turn_manager.turn_number
                 );
                 turn_changes.push(entity);
+                continue;
             }
             
-            // 現在のターン情報をデバッグ出力（制限時間がある場合のみ）
-            if let Some(remaining) = turn_manager.remaining_time() {
-                if remaining > 0 && remaining % 10 == 0 { // 10秒ごとに表示
-                    println!(
-                        "⏳ ターン残り時間: {}秒 (プレイヤー: {:?})",
-                        remaining,
-                        turn_manager.current_player
-                    );
-                }
+            if let Some(threshold) =
+                turn_manager.pending_warning(remaining, &settings.turn_warning_seconds)
+            {
+                warnings.push((entity, threshold, remaining));
+            }
+            
+            // 現在のターン情報をデバッグ出力（10秒ごとに表示）
+            if remaining % 10 == 0 {
+                debug!(
+                    "⏳ ターン残り時間: {}秒 (プレイヤー: {:?})",
+                    remaining,
+                    turn_manager.current_player
+                );
+            }
+        }
+        
+        // 残り時間の警告を通知
+        for (entity, threshold, remaining) in warnings {
+            let Some(turn_manager) = world.get_component_mut::<TurnManager>(entity) else {
+                continue;
+            };
+            turn_manager.last_warning = Some(threshold);
+            let turn_number = turn_manager.turn_number;
+            let Some(player) = turn_manager.current_player else {
+                continue;
+            };
+            
+            info!("⚠️ ターン残り{}秒: プレイヤー {:?} (ターン {})", remaining, player, turn_number);
+            if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                events.push(GameEvent::TurnTimeWarning {
+                    player: player.id(),
+                    turn_number,
+                    remaining_seconds: remaining,
+                });
             }
         }
         
-        // 時間切れのターンを次に進める
+        // 時間切れのプレイヤーに代わって操作し、ターンを次に進める
+        let timeout_action = settings.turn_timeout_action;
         for entity in turn_changes {
+            let Some(turn_manager) = world.get_component::<TurnManager>(entity) else {
+                continue;
+            };
+            let expired_player = turn_manager.current_player;
+            let turn_number = turn_manager.turn_number;
+            
+            if let Some(player) = expired_player {
+                if let Some(payload) = timeout_action.payload() {
+                    if let Err(reason) = ActionProcessingSystem::execute(world, player, &payload) {
+                        debug!("⚠️ 時間切れの操作を実行できません: {}", reason);
+                    }
+                }
+                
+                if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                    events.push(GameEvent::TurnTimedOut {
+                        player: player.id(),
+                        turn_number,
+                        auto_action: timeout_action.as_str().to_string(),
+                    });
+                }
+            }
+            
+            // 代わりの操作（ターン終了）でまだターンが進んでいなければ進める
             if let Some(turn_manager) = world.get_component_mut::<TurnManager>(entity) {
-                let next_player = turn_manager.next_turn();
-                println!(
-                    "🔄 ターン変更: 次のプレイヤー {:?} (ターン {})",
-                    next_player,
-                    turn_manager.turn_number
+                if turn_manager.turn_number == turn_number {
+                    turn_manager.next_turn(&clock);
+                }
+                info!(
+                    "🔄 ターン変更: 次のプレイヤー {:?} (ターン {}, 時間切れの操作: {})",
+                    turn_manager.current_player,
+                    turn_manager.turn_number,
+                    timeout_action.as_str()
                 );
             }
         }
@@ -560,68 +990,195 @@ impl System for TurnManagementSystem {
 /// アクション処理システム
 /// 
 /// プレイヤーのアクション（行動）を処理し、ゲーム状態に反映するシステムです。
-/// アクションの妥当性チェックや副作用の処理を行います。
+/// アクションごとに手番と詳細データを検証してから実行し、
+/// 実行できなかったアクションは理由を添えてActionRejectedイベントで通知します。
 pub struct ActionProcessingSystem;
 
 impl System for ActionProcessingSystem {
     fn update(&mut self, world: &mut World, _delta_time: f64) {
-        let mut processed_actions = Vec::new();
+        // 処理中にワールドを変更するため、先にアクションを取り出しておく
+        let actions = match world.get_resource_mut::<ActionQueue>() {
+            Some(queue) => queue.drain(),
+            None => return,
+        };
         
-        // 全てのアクションを取得して処理
-        for (entity, action) in world.query::<GameAction>() {
-            println!(
+        for action in &actions {
+            let action_type = action.action_type();
+            debug!(
                 "🎯 アクション処理: {} by {:?} at {}",
-                action.action_type.as_str(),
+                action_type.as_str(),
                 action.player,
                 action.timestamp
             );
             
-            // アクションの種類に応じて処理分岐
-            match action.action_type {
-                ActionType::MoveCard => {
-                    // カード移動の処理
-                    // TODO: カードの位置変更ロジックを実装
-                },
-                
-                ActionType::FlipCard => {
-                    // カード裏返しの処理
-                    // TODO: カードの表裏状態変更ロジックを実装
-                },
-                
-                ActionType::DrawCard => {
-                    // カード引きの処理
-                    // TODO: デッキからカードを引くロジックを実装
-                },
-                
-                ActionType::EndTurn => {
-                    // ターン終了の処理
-                    // TODO: ターン管理システムとの連携
-                },
-                
-                ActionType::LeaveGame => {
-                    // ゲーム退出の処理
-                    // TODO: プレイヤー削除とゲーム状態更新
-                },
-                
-                ActionType::SendMessage => {
-                    // チャットメッセージの処理
-                    // TODO: メッセージブロードキャスト
-                },
-                
-                ActionType::ChangeSettings => {
-                    // 設定変更の処理
-                    // TODO: ゲーム設定の更新
-                },
-            }
+            // 検証してから実行
+            let result = Self::validate(world, action)
+                .and_then(|()| Self::execute(world, action.player, &action.payload));
             
-            // 処理済みアクションとしてマーク
-            processed_actions.push(entity);
+            if let Err(reason) = result {
+                info!(
+                    "🚫 アクション拒否: {} by {:?} ({})",
+                    action_type.as_str(),
+                    action.player,
+                    reason
+                );
+                if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                    events.push(GameEvent::ActionRejected {
+                        player: action.player.id(),
+                        action: action_type.as_str().to_string(),
+                        reason,
+                    });
+                }
+            }
+        }
+        
+        // 処理済みアクションはプールへ戻す
+        if let Some(pool) = world.get_resource_mut::<GameActionPool>() {
+            for action in actions {
+                pool.release(action);
+            }
+        }
+    }
+}
+
+impl ActionProcessingSystem {
+    /// アクションを検証
+    /// 
+    /// 盤面やターンを動かすアクションは、ターン管理が行われている場合に
+    /// 手番のプレイヤーからのものだけを受け付けます（退出・チャット・設定変更はいつでも可能）。
+    /// 共有盤面では、観戦中・他のプレイヤーのカードを動かすアクションとホスト以外の山札の戻しも断ります。
+    /// 
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `action` - 検証するアクション
+    /// 
+    /// # 戻り値
+    /// 受け付けられる場合はOk(())、受け付けられない場合は拒否の理由
+    pub fn validate(world: &World, action: &GameAction) -> Result<(), String> {
+        match action.payload {
+            ActionPayload::LeaveGame
+            | ActionPayload::Chat { .. }
+            | ActionPayload::ChangeSettings { .. } => {}
+            _ => Self::check_turn(world, action.player)?,
+        }
+        permissions::check_payload(world, &action.payload)?;
+        action.payload.validate()
+    }
+    
+    /// プレイヤーの手番かどうかを確認
+    /// 
+    /// ターン管理が存在しない場合（1人プレイ）は常に手番として扱います。
+    fn check_turn(world: &World, player: Entity) -> Result<(), String> {
+        let mut managed = false;
+        for (_, turn_manager) in world.query::<TurnManager>() {
+            managed = true;
+            if !turn_manager.turn_order.contains(&player) {
+                continue;
+            }
+            return match turn_manager.current_player {
+                Some(current) if current == player => Ok(()),
+                Some(current) => Err(format!("プレイヤー{}の手番です", current.id())),
+                None => Err("ターンが開始されていません".to_string()),
+            };
+        }
+        
+        if managed {
+            Err("ゲームに参加していません".to_string())
+        } else {
+            Ok(())
+        }
+    }
+    
+    /// 検証済みのアクションを実行
+    /// 
+    /// カードの操作はSolitaireManager（移動はヒントエンジンと共通の処理）で行い、
+    /// 盤面のルールに合わない操作はエラーになります。
+    /// 
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `player` - アクションを行うプレイヤー
+    /// * `payload` - アクションの内容
+    /// 
+    /// # 戻り値
+    /// 成功時はOk、実行できない場合は理由を表すエラーメッセージ
+    pub fn execute(world: &mut World, player: Entity, payload: &ActionPayload) -> Result<(), String> {
+        match payload {
+            ActionPayload::MoveCard { from, to, count } => HintEngine::move_cards(
+                world,
+                HintLocation { location: from.location, index: from.index },
+                *count,
+                HintLocation { location: to.location, index: to.index },
+            ),
+            ActionPayload::FlipCard { column } => SolitaireManager::flip_tableau_top(world, *column),
+            ActionPayload::DrawCard => SolitaireManager::draw_card(world),
+            ActionPayload::EndTurn => Self::end_turn(world, player),
+            ActionPayload::LeaveGame => Self::leave_game(world, player),
+            ActionPayload::Chat { text } => {
+                // チャットメッセージの処理
+                // TODO: メッセージブロードキャスト
+                info!("💬 チャット: {:?}: {}", player, text);
+                Ok(())
+            }
+            ActionPayload::ChangeSettings { .. } => {
+                // 設定変更の処理
+                // TODO: ゲーム設定の更新
+                Ok(())
+            }
+        }
+    }
+    
+    /// プレイヤーのターンを終了し、次のプレイヤーへ移る
+    fn end_turn(world: &mut World, player: Entity) -> Result<(), String> {
+        let clock = GameClock::from_world(world);
+        let turn_entities: Vec<Entity> = world
+            .query::<TurnManager>()
+            .filter(|(_, turn_manager)| turn_manager.current_player == Some(player))
+            .map(|(entity, _)| entity)
+            .collect();
+        if turn_entities.is_empty() {
+            return Err("終了できるターンがありません".to_string());
         }
         
-        // 処理済みアクションを削除
-        for entity in processed_actions {
-            world.remove_component::<GameAction>(entity);
+        for entity in turn_entities {
+            if let Some(turn_manager) = world.get_component_mut::<TurnManager>(entity) {
+                let next_player = turn_manager.next_turn(&clock);
+                info!(
+                    "🔄 ターン終了: プレイヤー {:?} → 次のプレイヤー {:?} (ターン {})",
+                    player,
+                    next_player,
+                    turn_manager.turn_number
+                );
+            }
         }
+        Ok(())
+    }
+    
+    /// プレイヤーをターン順序とゲームから外す
+    fn leave_game(world: &mut World, player: Entity) -> Result<(), String> {
+        let turn_entities: Vec<Entity> = world
+            .query::<TurnManager>()
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in turn_entities {
+            if let Some(turn_manager) = world.get_component_mut::<TurnManager>(entity) {
+                turn_manager.remove_player(player);
+            }
+        }
+        
+        let game_entity = world.query::<GameState>().next().map(|(entity, _)| entity);
+        let game_state = game_entity
+            .and_then(|entity| world.get_component_mut::<GameState>(entity))
+            .ok_or_else(|| "参加中のゲームがありません".to_string())?;
+        if !game_state.remove_player() {
+            return Err("ゲームに参加しているプレイヤーがいません".to_string());
+        }
+        
+        info!(
+            "👋 プレイヤー {:?} がゲームから退出しました（残り{}人）",
+            player,
+            game_state.current_players
+        );
+        Ok(())
     }
 }
 
@@ -650,12 +1207,13 @@ impl GameManager {
         session_id: String,
         max_players: u32,
     ) -> Entity {
+        let clock = GameClock::from_world(world);
         let game_entity = world.create_entity();
-        let game_state = GameState::new(session_id.clone(), max_players);
+        let game_state = GameState::new(session_id.clone(), max_players, &clock);
         
         world.add_component(game_entity, game_state);
         
-        println!("🎮 新しいゲームセッション作成: {} (最大{}人)", session_id, max_players);
+        info!("🎮 新しいゲームセッション作成: {} (最大{}人)", session_id, max_players);
         game_entity
     }
     
@@ -675,7 +1233,7 @@ impl GameManager {
     ) -> bool {
         if let Some(game_state) = world.get_component_mut::<GameState>(game_entity) {
             if game_state.add_player() {
-                println!("👤 プレイヤー {:?} がゲームに参加しました", player_entity);
+                info!("👤 プレイヤー {:?} がゲームに参加しました", player_entity);
                 return true;
             }
         }
@@ -698,12 +1256,21 @@ impl GameManager {
         players: Vec<Entity>,
         turn_time_limit: u32,
     ) -> Entity {
+        let clock = GameClock::from_world(world);
+        let timebank_seconds = world
+            .get_resource::<GameSettings>()
+            .map_or(0, |settings| settings.turn_timebank_seconds);
         let turn_entity = world.create_entity();
-        let turn_manager = TurnManager::new(players.clone(), turn_time_limit);
+        let mut turn_manager = TurnManager::new(players.clone(), turn_time_limit, &clock);
+        if timebank_seconds > 0 {
+            for &player in &players {
+                turn_manager.set_timebank(player, timebank_seconds);
+            }
+        }
         
         world.add_component(turn_entity, turn_manager);
         
-        println!(
+        info!(
             "🔄 ターン管理開始: {}人のプレイヤー、制限時間{}秒",
             players.len(),
             turn_time_limit
@@ -712,33 +1279,54 @@ impl GameManager {
         turn_entity
     }
     
-    /// プレイヤーアクションを記録
+    /// プレイヤーアクションを記録（ActionQueueに追加する）
     /// 
     /// # 引数
     /// * `world` - ECSワールドへの可変参照
     /// * `player` - アクションを行ったプレイヤー
-    /// * `action_type` - アクションの種類
-    /// * `data` - アクションの詳細データ
+    /// * `payload` - アクションの内容
     /// 
     /// # 戻り値
-    /// 作成されたアクションエンティティ
-    pub fn record_action(
-        world: &mut World,
-        player: Entity,
-        action_type: ActionType,
-        data: Option<String>,
-    ) -> Entity {
-        let action_entity = world.create_entity();
-        let game_action = GameAction::new(player, action_type, data);
+    /// キューに追加できた場合true、キューが上限に達していて捨てた場合false
+    pub fn record_action(world: &mut World, player: Entity, payload: ActionPayload) -> bool {
+        let clock = GameClock::from_world(world);
+        let action_type = payload.action_type();
+        let game_action = match world.get_resource_mut::<GameActionPool>() {
+            Some(pool) => pool.acquire(|recycled| match recycled {
+                Some(mut action) => {
+                    action.reset(player, payload, &clock);
+                    action
+                }
+                None => GameAction::new(player, payload, &clock),
+            }),
+            None => GameAction::new(player, payload, &clock),
+        };
         
-        world.add_component(action_entity, game_action);
+        if !world.has_resource::<ActionQueue>() {
+            world.insert_resource(ActionQueue::new());
+        }
+        let rejected = match world.get_resource_mut::<ActionQueue>() {
+            Some(queue) => queue.push(game_action).err(),
+            None => None,
+        };
+        if let Some(action) = rejected {
+            warn!(
+                "⚠️ アクションキューが上限に達したため捨てました: {} by {:?}",
+                action_type.as_str(),
+                player
+            );
+            if let Some(pool) = world.get_resource_mut::<GameActionPool>() {
+                pool.release(action);
+            }
+            return false;
+        }
         
-        println!(
+        debug!(
             "📝 アクション記録: {} by {:?}",
             action_type.as_str(),
             player
         );
         
-        action_entity
+        true
     }
 }
\ No newline at end of file
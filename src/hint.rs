@@ -0,0 +1,870 @@
+// =============================================================================
+// ヒントエンジン
+// =============================================================================
+// このファイルでは、現在の盤面から「次に打つべき一手」を探すヒントエンジンを実装します。
+// ヒントボタン、自動プレイ、サーバーのボット対戦相手が共通で使用します。
+//
+// 仕組み：
+// 1. ECSワールドのカードコンポーネントから盤面（BoardView）を組み立てる
+// 2. 合法手をすべて列挙し、優先度の高い順に並べる
+// 3. 選ばれた手を通常の移動と同じようにワールドへ適用する（スコア・移動履歴も更新）
+//
+// 優先度（高い順）：
+// - ファウンデーションへ置ける手
+// - 裏向きカードをめくれるタブロー間の移動
+// - ウェイストからタブローへの移動
+// - デッキからカードを引く
+//
+// 各ヒントには「なぜその手が良いのか」の説明が付きます。
+// チュートリアル用には、効果の薄い手も含めた全合法手を評価順に並べた
+// 一覧（HintEngine::ranked_moves）も提供します。
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Entity, World};
+use crate::layout;
+use crate::solitaire::{
+    CardLocation, CardRank, CardSuit, MoveRecord, SolitaireCard, SolitaireManager,
+};
+use crate::transaction::MoveTransaction;
+use log::{debug, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// クロンダイクのタブロー列数
+const TABLEAU_COLUMNS: u32 = 7;
+
+/// ファウンデーションの数
+const FOUNDATION_COUNT: u32 = 4;
+
+/// ファウンデーションへ置いた時の得点
+const FOUNDATION_POINTS: u32 = 10;
+
+/// デッキから引く手の理由
+const DRAW_REASON: &str = "場で動かせる手が尽きたら、デッキから新しいカードを出しましょう";
+
+// =============================================================================
+// ヒントの定義
+// =============================================================================
+
+/// ヒントの種類
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum HintKind {
+    /// カードを移動する
+    Move,
+
+    /// デッキからカードを引く
+    Draw,
+}
+
+/// ヒントが指す場所（JavaScript向け）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct HintLocation {
+    /// 場所の種類
+    #[serde(rename = "type")]
+    pub location: CardLocation,
+
+    /// 場所のインデックス（タブローの列番号、ファウンデーション番号など）
+    pub index: u32,
+}
+
+/// 移動するカードの情報（JavaScript向け）
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+pub struct HintCard {
+    /// カードのエンティティ
+    #[serde(skip)]
+    pub entity: Entity,
+
+    /// スート
+    pub suit: CardSuit,
+
+    /// ランク
+    pub rank: CardRank,
+}
+
+/// ヒント（次の一手）
+#[derive(Debug, Clone, Serialize, PartialEq, TS)]
+#[ts(export)]
+pub struct Hint {
+    /// ヒントの種類
+    #[serde(rename = "type")]
+    pub kind: HintKind,
+
+    /// 移動するカード（タブロー間の移動では一番下のカード）
+    pub card: Option<HintCard>,
+
+    /// 一緒に移動するカードの枚数
+    pub card_count: usize,
+
+    /// 移動元
+    pub from: Option<HintLocation>,
+
+    /// 移動先
+    pub to: Option<HintLocation>,
+
+    /// 表示用メッセージ
+    pub message: String,
+
+    /// その手を勧める理由（チュートリアル表示用）
+    pub reason: String,
+
+    /// 優先度（大きいほど良い手、チュートリアルでは手の評価値として表示）
+    pub priority: u32,
+}
+
+impl Hint {
+    /// カード移動のヒントを作成
+    fn movement(
+        card: (Entity, &SolitaireCard),
+        card_count: usize,
+        to: HintLocation,
+        priority: u32,
+        reason: String,
+    ) -> Self {
+        let (entity, card) = card;
+        let message = format!(
+            "{}{}を{}{}に移動できます",
+            card.suit.symbol(),
+            card.rank.display(),
+            to.location.name(),
+            to.index + 1
+        );
+
+        Self {
+            kind: HintKind::Move,
+            card: Some(HintCard {
+                entity,
+                suit: card.suit,
+                rank: card.rank,
+            }),
+            card_count,
+            from: Some(HintLocation {
+                location: card.location_type,
+                index: card.position_in_location,
+            }),
+            to: Some(to),
+            message,
+            reason,
+            priority,
+        }
+    }
+
+    /// デッキから引くヒントを作成
+    ///
+    /// # 引数
+    /// * `reason` - 引くことを勧める理由
+    fn draw(reason: &str) -> Self {
+        Self {
+            kind: HintKind::Draw,
+            card: None,
+            card_count: 0,
+            from: Some(HintLocation {
+                location: CardLocation::Deck,
+                index: 0,
+            }),
+            to: Some(HintLocation {
+                location: CardLocation::Waste,
+                index: 0,
+            }),
+            message: "デッキからカードを引きましょう".to_string(),
+            reason: reason.to_string(),
+            priority: 0,
+        }
+    }
+}
+
+// =============================================================================
+// 盤面の読み取り
+// =============================================================================
+
+/// ヒント計算用の盤面ビュー
+///
+/// ワールド内のカードコンポーネントを場所ごとに整理したものです。
+#[derive(Debug, Clone, Default)]
+pub struct BoardView {
+    /// タブロー各列のカード（下から上の順）
+    pub tableau: Vec<Vec<(Entity, SolitaireCard)>>,
+
+    /// ファウンデーション各組の最上位カード
+    pub foundation_tops: Vec<Option<SolitaireCard>>,
+
+    /// ウェイストの最上位カード
+    pub waste_top: Option<(Entity, SolitaireCard)>,
+
+    /// デッキの残り枚数
+    pub deck_count: usize,
+
+    /// ウェイストの枚数
+    pub waste_count: usize,
+}
+
+impl BoardView {
+    /// ワールドから盤面を読み取る
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 盤面ビュー
+    pub fn from_world(world: &World) -> Self {
+        let mut view = Self {
+            tableau: vec![Vec::new(); TABLEAU_COLUMNS as usize],
+            foundation_tops: vec![None; FOUNDATION_COUNT as usize],
+            ..Self::default()
+        };
+
+        for (entity, card) in world.query::<SolitaireCard>() {
+            let index = card.position_in_location as usize;
+            match card.location_type {
+                CardLocation::Tableau if index < view.tableau.len() => {
+                    view.tableau[index].push((entity, card.clone()));
+                }
+                CardLocation::Foundation if index < view.foundation_tops.len() => {
+                    let top = &mut view.foundation_tops[index];
+                    if top.as_ref().is_none_or(|top| card.rank > top.rank) {
+                        *top = Some(card.clone());
+                    }
+                }
+                CardLocation::Waste => {
+                    view.waste_count += 1;
+                    let is_top = view
+                        .waste_top
+                        .as_ref()
+                        .is_none_or(|(_, top)| index > top.position_in_location as usize);
+                    if is_top {
+                        view.waste_top = Some((entity, card.clone()));
+                    }
+                }
+                CardLocation::Deck => view.deck_count += 1,
+                _ => {}
+            }
+        }
+
+        // タブローは表示位置（上から下）で並べる
+        for column in &mut view.tableau {
+            column.sort_by(|(_, a), (_, b)| a.display_y.total_cmp(&b.display_y));
+        }
+
+        view
+    }
+
+    /// カードを置けるファウンデーションを探す
+    fn foundation_for(&self, card: &SolitaireCard) -> Option<u32> {
+        (0..FOUNDATION_COUNT)
+            .find(|&i| card.can_place_on_foundation(self.foundation_tops[i as usize].as_ref()))
+    }
+
+    /// カード（またはカードを先頭とする列）を置けるタブロー列を探す
+    fn tableau_for(&self, card: &SolitaireCard, exclude_column: Option<u32>) -> Option<u32> {
+        self.tableau_targets(card, exclude_column).next()
+    }
+
+    /// カード（またはカードを先頭とする列）を置けるタブロー列をすべて列挙
+    fn tableau_targets<'a>(
+        &'a self,
+        card: &'a SolitaireCard,
+        exclude_column: Option<u32>,
+    ) -> impl Iterator<Item = u32> + 'a {
+        (0..TABLEAU_COLUMNS)
+            .filter(move |&column| Some(column) != exclude_column)
+            .filter(move |&column| match self.tableau[column as usize].last() {
+                Some((_, top)) => top.is_face_up && card.can_place_on_tableau(top),
+                None => card.can_place_on_empty_tableau(),
+            })
+    }
+
+    /// ファウンデーションへ置く手の理由を説明
+    ///
+    /// # 引数
+    /// * `card` - 置くカード
+    /// * `from_column` - タブローから置く場合はその列番号
+    fn explain_foundation(&self, card: &SolitaireCard, from_column: Option<u32>) -> String {
+        let base = format!("{}を組札に積み上げます", card_name(card));
+        let Some(column) = from_column else {
+            return base;
+        };
+
+        let cards = &self.tableau[column as usize];
+        match cards.len().checked_sub(2).map(|i| &cards[i].1) {
+            None => format!("{}。{}", base, self.explain_empty_column(column)),
+            Some(below) if !below.is_face_up => {
+                format!("{}。下の裏向きのカードもめくれます", base)
+            }
+            Some(_) => base,
+        }
+    }
+
+    /// 列が空くことの利点を説明（空き列を待っているKがあればそのKを挙げる）
+    fn explain_empty_column(&self, column: u32) -> String {
+        match self.waiting_king(column) {
+            Some(king) => format!(
+                "タブロー{}が空き、{}を置けるようになります",
+                column + 1,
+                card_name(king)
+            ),
+            None => format!("タブロー{}が空き、Kを置ける場所ができます", column + 1),
+        }
+    }
+
+    /// 空き列を待っているK（ウェイストの一番上か、裏向きカードの上に乗っているK）を探す
+    fn waiting_king(&self, exclude_column: u32) -> Option<&SolitaireCard> {
+        let from_waste = self
+            .waste_top
+            .as_ref()
+            .map(|(_, card)| card)
+            .filter(|card| card.rank == CardRank::King);
+
+        from_waste.or_else(|| {
+            self.tableau
+                .iter()
+                .enumerate()
+                .filter(|&(column, _)| column as u32 != exclude_column)
+                .find_map(|(_, cards)| {
+                    let first_face_up = cards.iter().position(|(_, card)| card.is_face_up)?;
+                    let (_, card) = &cards[first_face_up];
+                    (first_face_up > 0 && card.rank == CardRank::King).then_some(card)
+                })
+        })
+    }
+
+    /// タブロー間の移動を評価
+    ///
+    /// # 引数
+    /// * `column` - 移動元の列番号
+    /// * `start` - 移動する先頭カードの列内の位置（下から数えて0始まり）
+    ///
+    /// # 戻り値
+    /// (優先度, 理由)
+    fn evaluate_tableau_move(&self, column: u32, start: usize) -> (u32, String) {
+        let cards = &self.tableau[column as usize];
+        let Some((_, below)) = start.checked_sub(1).map(|i| &cards[i]) else {
+            // 列のカードをすべて動かす（Kは空き列にしか置けないので動かす意味がない）
+            return if cards[0].1.rank == CardRank::King {
+                (
+                    0,
+                    "Kを空き列から空き列へ動かしても盤面は変わりません".to_string(),
+                )
+            } else {
+                (40, self.explain_empty_column(column))
+            };
+        };
+
+        if !below.is_face_up {
+            return (
+                50 + start as u32,
+                format!(
+                    "下の裏向きのカードをめくれます（この列の裏向きは{}枚）",
+                    start
+                ),
+            );
+        }
+
+        if self.foundation_for(below).is_some() {
+            (
+                45,
+                format!("下の{}を組札へ置けるようになります", card_name(below)),
+            )
+        } else {
+            (
+                5,
+                "並びを組み替えるだけで、新しいカードは出てきません".to_string(),
+            )
+        }
+    }
+}
+
+// =============================================================================
+// ヒントエンジン本体
+// =============================================================================
+
+/// ヒントエンジン
+pub struct HintEngine;
+
+impl HintEngine {
+    /// 現在の盤面での合法手を優先度の高い順に列挙
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 優先度順のヒントのベクター（打てる手がない場合は空）
+    pub fn all_moves(world: &World) -> Vec<Hint> {
+        let view = BoardView::from_world(world);
+        let mut hints = Vec::new();
+
+        // ウェイストの最上位カード → ファウンデーション / タブロー
+        if let Some((entity, card)) = &view.waste_top {
+            if let Some(foundation) = view.foundation_for(card) {
+                hints.push(Hint::movement(
+                    (*entity, card),
+                    1,
+                    location(CardLocation::Foundation, foundation),
+                    100 - card.rank as u32,
+                    view.explain_foundation(card, None),
+                ));
+            }
+            if let Some(column) = view.tableau_for(card, None) {
+                hints.push(waste_to_tableau((*entity, card), column));
+            }
+        }
+
+        for (column_index, column) in view.tableau.iter().enumerate() {
+            let column_index = column_index as u32;
+
+            // タブロー最上位カード → ファウンデーション
+            if let Some((entity, card)) = column.last().filter(|(_, card)| card.is_face_up) {
+                if let Some(foundation) = view.foundation_for(card) {
+                    hints.push(Hint::movement(
+                        (*entity, card),
+                        1,
+                        location(CardLocation::Foundation, foundation),
+                        100 - card.rank as u32,
+                        view.explain_foundation(card, Some(column_index)),
+                    ));
+                }
+            }
+
+            // 表向き部分をまるごと移動して裏向きカードをめくれる場合
+            let Some(first_face_up) = column.iter().position(|(_, card)| card.is_face_up) else {
+                continue;
+            };
+            let hidden_below = first_face_up;
+            let (entity, card) = &column[first_face_up];
+
+            // 裏向きカードがない列のKを空き列へ動かしても意味がないので除外
+            if hidden_below == 0 {
+                continue;
+            }
+
+            if let Some(target) = view.tableau_for(card, Some(column_index)) {
+                let (priority, reason) = view.evaluate_tableau_move(column_index, first_face_up);
+                hints.push(Hint::movement(
+                    (*entity, card),
+                    column.len() - first_face_up,
+                    location(CardLocation::Tableau, target),
+                    priority,
+                    reason,
+                ));
+            }
+        }
+
+        // デッキ・ウェイストにカードが残っていれば引ける
+        if view.deck_count > 0 || view.waste_count > 0 {
+            hints.push(Hint::draw(DRAW_REASON));
+        }
+
+        hints.sort_by_key(|hint| std::cmp::Reverse(hint.priority));
+        hints
+    }
+
+    /// 全ての合法手を評価の高い順に列挙（チュートリアル用）
+    ///
+    /// all_movesが有望な手だけを返すのに対し、こちらは並びを組み替えるだけの手や
+    /// 意味のない手も含め、移動先ごとに評価値（priority）と理由を付けて返します。
+    /// 同じ種類の手の評価値はall_movesの優先度と一致します。
+    /// 組札からタブローへ戻す手は含みません。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 評価の高い順のヒントのベクター（打てる手がない場合は空）
+    pub fn ranked_moves(world: &World) -> Vec<Hint> {
+        let view = BoardView::from_world(world);
+        let mut hints = Vec::new();
+
+        // ウェイストの最上位カード → ファウンデーション / 置ける全てのタブロー列
+        if let Some((entity, card)) = &view.waste_top {
+            if let Some(foundation) = view.foundation_for(card) {
+                hints.push(Hint::movement(
+                    (*entity, card),
+                    1,
+                    location(CardLocation::Foundation, foundation),
+                    100 - card.rank as u32,
+                    view.explain_foundation(card, None),
+                ));
+            }
+            for column in view.tableau_targets(card, None) {
+                hints.push(waste_to_tableau((*entity, card), column));
+            }
+        }
+
+        for (column_index, column) in view.tableau.iter().enumerate() {
+            let column_index = column_index as u32;
+            let Some(first_face_up) = column.iter().position(|(_, card)| card.is_face_up) else {
+                continue;
+            };
+
+            // タブロー最上位カード → ファウンデーション
+            if let Some((entity, card)) = column.last() {
+                if let Some(foundation) = view.foundation_for(card) {
+                    hints.push(Hint::movement(
+                        (*entity, card),
+                        1,
+                        location(CardLocation::Foundation, foundation),
+                        100 - card.rank as u32,
+                        view.explain_foundation(card, Some(column_index)),
+                    ));
+                }
+            }
+
+            // 正しく並んだ表向きの部分列 → 置ける全てのタブロー列
+            for start in first_face_up..column.len() {
+                let run = &column[start..];
+                let ordered = run
+                    .windows(2)
+                    .all(|pair| pair[1].1.can_place_on_tableau(&pair[0].1));
+                if !ordered {
+                    continue;
+                }
+
+                let (entity, card) = &run[0];
+                for target in view.tableau_targets(card, Some(column_index)) {
+                    let (priority, reason) = view.evaluate_tableau_move(column_index, start);
+                    hints.push(Hint::movement(
+                        (*entity, card),
+                        run.len(),
+                        location(CardLocation::Tableau, target),
+                        priority,
+                        reason,
+                    ));
+                }
+            }
+        }
+
+        if view.deck_count > 0 || view.waste_count > 0 {
+            hints.push(Hint::draw(DRAW_REASON));
+        }
+
+        hints.sort_by_key(|hint| std::cmp::Reverse(hint.priority));
+        hints
+    }
+
+    /// 最善の一手を取得
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 打てる手がある場合はSome(Hint)、ない場合はNone
+    pub fn find_hint(world: &World) -> Option<Hint> {
+        Self::all_moves(world).into_iter().next()
+    }
+
+    /// デッキを一巡しても打てる手が見つからないかを判定するための周期を取得
+    ///
+    /// デッキ・ウェイストの合計枚数+1回連続で「引く」しか打てない場合、
+    /// 盤面は手詰まりとみなせます。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 手詰まり判定に使う連続ドロー回数の上限
+    pub fn draw_cycle_length(world: &World) -> usize {
+        let view = BoardView::from_world(world);
+        view.deck_count + view.waste_count + 1
+    }
+
+    /// 手詰まりになるかクリアするまで、一番良い手を打ち続ける
+    ///
+    /// 「引く」しか打てない状態がデッキ一巡分（draw_cycle_length()回）続いた場合は
+    /// 手詰まりとして止めます。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `max_moves` - 打つ手数の上限
+    ///
+    /// # 戻り値
+    /// 打った手数
+    pub fn play_until_stuck(world: &mut World, max_moves: u32) -> u32 {
+        Self::play_until_stuck_with(world, max_moves, |_| {})
+    }
+
+    /// 手詰まりになるまで最善手を打ち続け、1手打つごとに盤面を渡す
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `max_moves` - 打つ手数の上限
+    /// * `after_move` - 1手打つごとに呼ばれる関数（打った後のワールドを受け取る）
+    ///
+    /// # 戻り値
+    /// 打った手数
+    pub fn play_until_stuck_with(
+        world: &mut World,
+        max_moves: u32,
+        mut after_move: impl FnMut(&World),
+    ) -> u32 {
+        let mut moves_played = 0;
+        let mut forced_draws = 0;
+
+        while moves_played < max_moves && !Self::is_cleared(world) {
+            let moves = Self::all_moves(world);
+            let Some(best) = moves.first() else {
+                break;
+            };
+
+            // 「引く」しか打てない状態がデッキ一巡分続いたら手詰まり
+            if moves.len() == 1 && best.kind == HintKind::Draw {
+                forced_draws += 1;
+                if forced_draws > Self::draw_cycle_length(world) {
+                    break;
+                }
+            } else {
+                forced_draws = 0;
+            }
+
+            if !Self::apply(world, best) {
+                break;
+            }
+            moves_played += 1;
+            after_move(world);
+        }
+        moves_played
+    }
+
+    /// すべてのカードが組札に移ったかどうか（デッキ・ウェイスト・タブローが空）
+    fn is_cleared(world: &World) -> bool {
+        let view = BoardView::from_world(world);
+        view.deck_count == 0 && view.waste_count == 0 && view.tableau.iter().all(Vec::is_empty)
+    }
+
+    /// ヒントの手をワールドに適用
+    ///
+    /// 通常のカード移動と同様に、スコア・移動履歴の更新と
+    /// 移動元タブローの裏向きカードをめくる処理を行います。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `hint` - 適用するヒント
+    ///
+    /// # 戻り値
+    /// 適用できた場合true
+    pub fn apply(world: &mut World, hint: &Hint) -> bool {
+        match hint.kind {
+            HintKind::Draw => SolitaireManager::draw_card(world).is_ok(),
+            HintKind::Move => {
+                let (Some(card), Some(to)) = (hint.card, hint.to) else {
+                    return false;
+                };
+                Self::apply_move(world, card.entity, to)
+            }
+        }
+    }
+
+    /// 場所を指定してカードを移動
+    ///
+    /// 移動元の一番上から`count`枚をまとめて移動します。
+    /// 複数枚を移動できるのはタブローからの移動だけです。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `from` - 移動元の場所
+    /// * `count` - 移動する枚数
+    /// * `to` - 移動先の場所
+    ///
+    /// # 戻り値
+    /// 成功時はOk、移動できない場合は理由を表すエラーメッセージ
+    pub fn move_cards(
+        world: &mut World,
+        from: HintLocation,
+        count: usize,
+        to: HintLocation,
+    ) -> Result<(), String> {
+        let view = BoardView::from_world(world);
+
+        let entity = match from.location {
+            CardLocation::Tableau => {
+                let column = view
+                    .tableau
+                    .get(from.index as usize)
+                    .ok_or_else(|| format!("タブロー{}は存在しません", from.index + 1))?;
+                let start = column
+                    .len()
+                    .checked_sub(count)
+                    .filter(|_| count > 0)
+                    .ok_or_else(|| {
+                        format!(
+                            "タブロー{}に{}枚のカードはありません",
+                            from.index + 1,
+                            count
+                        )
+                    })?;
+                let (entity, card) = &column[start];
+                if !card.is_face_up {
+                    return Err("裏向きのカードは移動できません".to_string());
+                }
+                *entity
+            }
+            CardLocation::Waste => {
+                if count != 1 {
+                    return Err("ウェイストから移動できるのは1枚だけです".to_string());
+                }
+                view.waste_top
+                    .map(|(entity, _)| entity)
+                    .ok_or_else(|| "ウェイストにカードがありません".to_string())?
+            }
+            other => return Err(format!("{}からは移動できません", other.name())),
+        };
+
+        let in_range = match to.location {
+            CardLocation::Tableau => to.index < TABLEAU_COLUMNS,
+            CardLocation::Foundation => to.index < FOUNDATION_COUNT,
+            other => return Err(format!("{}へは移動できません", other.name())),
+        };
+        if !in_range {
+            return Err(format!(
+                "{}{}は存在しません",
+                to.location.name(),
+                to.index + 1
+            ));
+        }
+
+        if Self::apply_move(world, entity, to) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{}{}には置けません",
+                to.location.name(),
+                to.index + 1
+            ))
+        }
+    }
+
+    /// カード（とその上に重なるカード）を移動
+    fn apply_move(world: &mut World, entity: Entity, to: HintLocation) -> bool {
+        let view = BoardView::from_world(world);
+        let Some(card) = world.get_component::<SolitaireCard>(entity).cloned() else {
+            return false;
+        };
+
+        // 一緒に動かすカード（タブローでは対象カードより上にあるもの）
+        let moving: Vec<Entity> = match card.location_type {
+            CardLocation::Tableau => {
+                let column = &view.tableau[card.position_in_location as usize];
+                match column.iter().position(|(e, _)| *e == entity) {
+                    Some(start) => column[start..].iter().map(|(e, _)| *e).collect(),
+                    None => return false,
+                }
+            }
+            _ => vec![entity],
+        };
+
+        // 移動先の合法性を再確認
+        let legal = match to.location {
+            CardLocation::Foundation => {
+                moving.len() == 1
+                    && card
+                        .can_place_on_foundation(view.foundation_tops[to.index as usize].as_ref())
+            }
+            CardLocation::Tableau => match view.tableau[to.index as usize].last() {
+                Some((_, top)) => top.is_face_up && card.can_place_on_tableau(top),
+                None => card.can_place_on_empty_tableau(),
+            },
+            _ => false,
+        };
+        if !legal {
+            return false;
+        }
+
+        let points = match to.location {
+            CardLocation::Foundation => FOUNDATION_POINTS,
+            _ => 0,
+        };
+        let record = MoveRecord::new(
+            &card,
+            to.location,
+            to.index,
+            points,
+            &GameClock::from_world(world),
+        );
+        // 手元で書き換えてから、すべての書き換えをまとめて反映する
+        // （途中で失敗した場合は何も反映しない）
+        match Self::stage_move(world, &view, &card, &moving, to, points) {
+            Ok(mut transaction) => {
+                transaction.record(record);
+                transaction.commit(world);
+            }
+            Err(e) => {
+                warn!("⚠️ 移動を取り消しました: {}", e);
+                return false;
+            }
+        }
+
+        debug!(
+            "🤖 {}{}を{}{}へ移動（{}枚）",
+            card.suit.symbol(),
+            card.rank.display(),
+            to.location.name(),
+            to.index + 1,
+            moving.len()
+        );
+
+        true
+    }
+
+    /// 移動する手の書き換えをトランザクションに準備
+    ///
+    /// 動かすカードの場所・表示座標、移動元で露出した裏向きカードの向き、スコアと手数を書き換えます。
+    ///
+    /// # 戻り値
+    /// 成功時は反映前のトランザクション、書き換えるカードがない場合はエラーメッセージ
+    fn stage_move(
+        world: &World,
+        view: &BoardView,
+        card: &SolitaireCard,
+        moving: &[Entity],
+        to: HintLocation,
+        points: u32,
+    ) -> Result<MoveTransaction, String> {
+        let mut transaction = MoveTransaction::new();
+        let target_height = view.tableau[to.index as usize].len();
+
+        for (offset, moving_entity) in moving.iter().enumerate() {
+            let card_mut = transaction.card(world, *moving_entity)?;
+            card_mut.set_location(to.location, to.index);
+            let (x, y) = match to.location {
+                CardLocation::Foundation => layout::foundation_position(to.index),
+                _ => layout::tableau_position(to.index, target_height + offset),
+            };
+            card_mut.set_display_position(x, y);
+        }
+        if let Some(state) = transaction.game_state(world) {
+            state.record_move(points);
+        }
+
+        // 移動元のタブローで露出した裏向きカードをめくる
+        if card.location_type == CardLocation::Tableau {
+            let column = &view.tableau[card.position_in_location as usize];
+            let remaining = column.len() - moving.len();
+            if let Some((exposed, exposed_card)) = remaining.checked_sub(1).map(|i| &column[i]) {
+                if !exposed_card.is_face_up {
+                    transaction.reveal(world, *exposed, card.position_in_location)?;
+                }
+            }
+        }
+
+        Ok(transaction)
+    }
+}
+
+/// 場所を作成するヘルパー
+fn location(location: CardLocation, index: u32) -> HintLocation {
+    HintLocation { location, index }
+}
+
+/// カードの表示名を作成するヘルパー（例："♠A"）
+fn card_name(card: &SolitaireCard) -> String {
+    format!("{}{}", card.suit.symbol(), card.rank.display())
+}
+
+/// ウェイストからタブローへ移動するヒントを作成するヘルパー
+fn waste_to_tableau(card: (Entity, &SolitaireCard), column: u32) -> Hint {
+    let reason = format!(
+        "ウェイストの{}を場に出すと、その下のカードが使えるようになります",
+        card_name(card.1)
+    );
+    Hint::movement(card, 1, location(CardLocation::Tableau, column), 30, reason)
+}
@@ -0,0 +1,217 @@
+// =============================================================================
+// HTTP API（サーバー用）
+// =============================================================================
+// このファイルでは、ゲームのWebSocketとは別に、普通のHTTPで取得できる
+// 読み取り専用のJSON APIと、死活監視用のエンドポイントを実装します。
+//
+// エンドポイント：
+// - GET /health                  … プロセスが動いていれば200（liveness）
+// - GET /ready                   … WebSocketの待ち受けを始めていれば200、起動中は503（readiness）
+// - GET /api/rooms               … ルーム一覧（他のインスタンスのルームも含む）
+// - GET /api/leaderboard/{seed}  … 配り札のシードごとのリーダーボード（順位順）
+// - GET /api/players/{player_id} … 接続中のプレイヤーのプロフィール
+// - GET /api/daily               … 今日（UTC）の日替わりの配り札のシード
+// - GET /api/daily/archive       … 過去の日替わりの配り札とその日のリーダーボード（新しい順）
+// - GET /metrics                 … 接続数・ルーム数と、送信チャンネルの溜まり具合・送れずに捨てた数
+//
+// 状態はSolitaireServerと同じServerStateを共有します。
+// 状態の変更はこれまで通りWebSocketのメッセージでのみ行います。
+// 読み取り専用なので、別のオリジンで配信されるゲーム画面からも取得できるようにしています（CORS）。
+// =============================================================================
+
+use crate::daily_deal::DailyDeal;
+use crate::leaderboard::LeaderboardEntry;
+use crate::outbound::OutboundReport;
+use crate::protocol::{ArchivedDailyDeal, PlayerProfile, RoomInfo};
+use crate::rating::compare_race_results;
+use crate::solve_cache::SolvedDeal;
+use crate::{ServerState, SolitaireServer};
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use log::{error, info};
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+
+/// /healthと/readyの応答
+#[derive(Debug, Serialize)]
+struct StatusResponse {
+    status: &'static str,
+}
+
+/// /api/roomsの応答
+#[derive(Debug, Serialize)]
+struct RoomsResponse {
+    rooms: Vec<RoomInfo>,
+}
+
+/// /api/leaderboard/{seed}の応答
+#[derive(Debug, Serialize)]
+struct LeaderboardResponse {
+    seed: u64,
+    entries: Vec<LeaderboardEntry>, // 順位順（勝利・クリア時間・スコアの順に比較）
+}
+
+/// /api/dailyの応答
+#[derive(Debug, Serialize)]
+struct DailyResponse {
+    day: u64,            // UNIXエポックからの日数（UTC）
+    seed: u64,           // 今日の配り札のシード
+    next_change_ms: u64, // 次の配り札に変わる時刻（UNIX時刻、ミリ秒）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solved: Option<SolvedDeal>, // ソルバーで解いた結果（まだ解いていない場合はNone）
+}
+
+/// /api/daily/archiveの応答
+#[derive(Debug, Serialize)]
+struct DailyArchiveResponse {
+    deals: Vec<ArchivedDailyDeal>, // 新しい順
+}
+
+/// /metricsの応答
+#[derive(Debug, Serialize)]
+struct MetricsResponse {
+    connections: usize,       // 接続中のプレイヤーの数
+    rooms: usize,             // このインスタンスのルームの数
+    outbound: OutboundReport, // 接続ごとの送信チャンネルの溜まり具合（すべての接続の合計）
+}
+
+/// エラー時の応答
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// HTTP APIのルーターを作成
+///
+/// # 引数
+/// * `state` - WebSocketサーバーと共有する状態
+pub fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/api/rooms", get(rooms))
+        .route("/api/leaderboard/{seed}", get(leaderboard))
+        .route("/api/players/{player_id}", get(player))
+        .route("/api/daily", get(daily))
+        .route("/api/daily/archive", get(daily_archive))
+        .route("/metrics", get(metrics))
+        .layer(axum::middleware::map_response(allow_any_origin))
+        .with_state(state)
+}
+
+/// どのオリジンからの取得も許可するヘッダーを付ける
+async fn allow_any_origin(mut response: Response) -> Response {
+    response.headers_mut().insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    response
+}
+
+/// HTTP APIの待ち受けを開始
+///
+/// 待ち受けを開始できない場合はエラーを記録して戻ります（WebSocketサーバーは動き続けます）。
+///
+/// # 引数
+/// * `addr` - 待ち受けアドレス
+/// * `state` - WebSocketサーバーと共有する状態
+pub async fn serve(addr: String, state: ServerState) {
+    let listener = match tokio::net::TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("❌ HTTP APIを{}で開始できません: {}", addr, e);
+            return;
+        }
+    };
+    info!("🌐 HTTP APIを{}で開始しました", addr);
+
+    if let Err(e) = axum::serve(listener, router(state)).await {
+        error!("❌ HTTP APIエラー: {}", e);
+    }
+}
+
+/// プロセスが動いているか（常に200）
+async fn health() -> Json<StatusResponse> {
+    Json(StatusResponse { status: "ok" })
+}
+
+/// 接続を受け付けられるか（WebSocketの待ち受け前は503）
+async fn ready(State(state): State<ServerState>) -> Response {
+    if state.ready.load(Ordering::Acquire) {
+        Json(StatusResponse { status: "ready" }).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(StatusResponse { status: "starting" }),
+        )
+            .into_response()
+    }
+}
+
+/// ルーム一覧
+async fn rooms(State(state): State<ServerState>) -> Json<RoomsResponse> {
+    Json(RoomsResponse {
+        rooms: SolitaireServer::room_infos(&state),
+    })
+}
+
+/// シードごとのリーダーボード
+async fn leaderboard(
+    State(state): State<ServerState>,
+    Path(seed): Path<u64>,
+) -> Json<LeaderboardResponse> {
+    let mut entries = state.leaderboard.lock().unwrap().entries_for(seed).to_vec();
+    entries.sort_by(|a, b| compare_race_results(&b.to_result(seed), &a.to_result(seed)));
+    Json(LeaderboardResponse { seed, entries })
+}
+
+/// 接続中のプレイヤーのプロフィール
+async fn player(
+    State(state): State<ServerState>,
+    Path(player_id): Path<String>,
+) -> Result<Json<PlayerProfile>, (StatusCode, Json<ErrorResponse>)> {
+    state
+        .players
+        .lock()
+        .unwrap()
+        .get(&player_id)
+        .map(|player| Json(player.profile()))
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse {
+                    error: "プレイヤーが見つかりません".to_string(),
+                }),
+            )
+        })
+}
+
+/// 今日の日替わりの配り札
+async fn daily(State(state): State<ServerState>) -> Json<DailyResponse> {
+    let deal = DailyDeal::at(state.clock.now_ms());
+    Json(DailyResponse {
+        day: deal.day,
+        seed: deal.seed,
+        next_change_ms: deal.next_change_ms,
+        solved: SolitaireServer::solved_deal(&state, deal.seed),
+    })
+}
+
+/// 過去の日替わりの配り札
+async fn daily_archive(State(state): State<ServerState>) -> Json<DailyArchiveResponse> {
+    Json(DailyArchiveResponse {
+        deals: state.daily_archive.lock().unwrap().deals().to_vec(),
+    })
+}
+
+/// 接続数・ルーム数と送信チャンネルの溜まり具合
+async fn metrics(State(state): State<ServerState>) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        connections: state.senders.lock().unwrap().len(),
+        rooms: state.rooms.lock().unwrap().len(),
+        outbound: state.outbound.report(),
+    })
+}
@@ -0,0 +1,125 @@
+// =============================================================================
+// 言語に合わせた数値・時間の整形
+// =============================================================================
+// このファイルでは、経過時間やスコアをフロントエンドごとに整形し直さなくて済むよう、
+// 設定の言語（Preferences.locale）に合わせて文字列にする関数と、
+// get_solitaire_state()・get_game_result()に加える整形済みの値（FormattedStats）を実装します。
+//
+// 仕組み：
+// - 経過時間は"mm:ss"（1時間以上は"h:mm:ss"）で、言語によらず同じ形にする
+// - 数値は3桁ごとに区切り、区切り文字はBCP 47の言語タグの言語（とスイスの地域）で決める
+//   （知らない言語は","で区切る）
+// - 設定のformat_numbersが有効な場合だけ状態の出力に加える（無効の場合は整数だけを返す）
+// =============================================================================
+
+use crate::ecs::World;
+use crate::settings;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// "."で桁を区切る言語
+const DOT_GROUPING_LANGUAGES: &[&str] = &[
+    "de", "es", "it", "nl", "pt", "id", "tr", "da", "el", "ro", "hr", "sl", "sr", "vi",
+];
+
+/// 改行しない空白で桁を区切る言語
+const SPACE_GROUPING_LANGUAGES: &[&str] = &[
+    "fr", "ru", "uk", "pl", "cs", "sk", "sv", "fi", "nb", "no", "hu", "bg",
+];
+
+/// 状態の出力に加える、言語に合わせて整形した値
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, TS)]
+pub struct FormattedStats {
+    /// 経過時間（"mm:ss"、1時間以上は"h:mm:ss"）
+    pub elapsed: String,
+
+    /// スコア（言語に合わせて桁を区切った数値）
+    pub score: String,
+
+    /// 整形に使った言語（BCP 47の言語タグ）
+    pub locale: String,
+}
+
+impl FormattedStats {
+    /// スコアと経過時間を整形する
+    ///
+    /// # 引数
+    /// * `score` - スコア
+    /// * `elapsed_seconds` - 経過時間（秒）
+    /// * `locale` - 言語（BCP 47の言語タグ、例："ja"、"de-CH"）
+    pub fn new(score: u32, elapsed_seconds: u64, locale: &str) -> Self {
+        Self {
+            elapsed: format_elapsed(elapsed_seconds),
+            score: format_number(u64::from(score), locale),
+            locale: locale.to_string(),
+        }
+    }
+}
+
+/// 設定で有効な場合だけ、スコアと経過時間を現在の言語で整形する
+///
+/// # 引数
+/// * `world` - ECSワールドへの参照（設定を読む）
+/// * `score` - スコア
+/// * `elapsed_seconds` - 経過時間（秒）
+///
+/// # 戻り値
+/// 整形した値（設定のformat_numbersが無効の場合はNone）
+pub fn formatted_stats(world: &World, score: u32, elapsed_seconds: u64) -> Option<FormattedStats> {
+    let preferences = settings::current(world);
+    preferences
+        .format_numbers
+        .then(|| FormattedStats::new(score, elapsed_seconds, &preferences.locale))
+}
+
+/// 経過時間を"mm:ss"（1時間以上は"h:mm:ss"）にする
+///
+/// # 引数
+/// * `seconds` - 経過時間（秒）
+pub fn format_elapsed(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// 数値を言語に合わせて3桁ごとに区切る
+///
+/// # 引数
+/// * `value` - 数値
+/// * `locale` - 言語（BCP 47の言語タグ）
+pub fn format_number(value: u64, locale: &str) -> String {
+    let digits = value.to_string();
+    let separator = group_separator(locale);
+    let mut formatted = String::with_capacity(digits.len() * 2);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(separator);
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// 言語の桁区切りの文字
+///
+/// # 引数
+/// * `locale` - 言語（BCP 47の言語タグ、大文字・小文字は問わない）
+pub fn group_separator(locale: &str) -> char {
+    let mut subtags = locale.split('-');
+    let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+    let in_switzerland = subtags.any(|subtag| subtag.eq_ignore_ascii_case("CH"));
+
+    if in_switzerland && ["de", "it", "fr"].contains(&language.as_str()) {
+        '\u{2019}'
+    } else if DOT_GROUPING_LANGUAGES.contains(&language.as_str()) {
+        '.'
+    } else if SPACE_GROUPING_LANGUAGES.contains(&language.as_str()) {
+        '\u{a0}'
+    } else {
+        ','
+    }
+}
@@ -0,0 +1,449 @@
+// =============================================================================
+// 入力イベントキュー
+// =============================================================================
+// このファイルでは、JavaScriptから転送されたマウス・タッチ操作（ポインターイベント）を
+// ECSのエンティティとして溜めておき、InputSystemが毎フレーム順番に処理する仕組みを実装します。
+//
+// 仕組み：
+// - push_pointer()がポインターイベントを連番付きのInputEventエンティティとして追加する
+// - InputSystemが連番の順にイベントを処理し、処理済みのエンティティを削除する
+// - 押す：カードを選択してドラッグを開始 / 動かす：カードを追従 / 離す：ドロップ
+// - カードのない山（PilePlaceholder）を押す・その上で離すと、選択中のカードをその山へのドロップとして扱う
+// - ポインターの座標は画面の座標で届き、Viewportの変換で盤面の座標に直してから処理する
+// - 有限でない座標（NaN・無限大）のイベントは受け付けず、大きすぎる座標は±MAX_POINTER_COORDINATEに収める
+// - 2本の指で触れている間はピンチ操作として扱い、カードではなくViewportを拡大・移動する
+//
+// wasmの関数がその場でワールドを書き換えないため、同じイベント列を流せば
+// 同じ結果が再現でき、ブラウザなしでもテストできます。
+// =============================================================================
+
+use crate::ecs::{Component, Entity, Resource, System, World};
+use crate::layout::{CARD_HEIGHT, CARD_WIDTH};
+use crate::selection::{self, Dropped, PilePlaceholder};
+use crate::solitaire::SolitaireCard;
+use crate::viewport::Viewport;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// ドラッグとみなす移動距離（ピクセル、これ未満で離した場合はタップ）
+const DRAG_THRESHOLD: f32 = 5.0;
+
+/// ポインターの座標として受け付ける値の範囲（絶対値、超えた値はこの範囲に収める）
+pub const MAX_POINTER_COORDINATE: f32 = 100_000.0;
+
+// =============================================================================
+// 入力イベント
+// =============================================================================
+
+/// ポインター操作の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerKind {
+    /// 押した（pointerdown / touchstart）
+    Down,
+
+    /// 動かした（pointermove / touchmove）
+    Move,
+
+    /// 離した（pointerup / touchend）
+    Up,
+
+    /// 中断された（pointercancel / touchcancel）
+    Cancel,
+}
+
+/// JavaScriptから転送されるポインターイベント
+///
+/// JSONでは`{"kind": "down", "x": 120.0, "y": 180.0, "pointer_id": 1}`の形式になります。
+/// 座標は画面の座標系です（拡大・移動していない場合はカードの表示座標と同じ）。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PointerEvent {
+    /// 操作の種類
+    pub kind: PointerKind,
+
+    /// X座標
+    pub x: f32,
+
+    /// Y座標
+    pub y: f32,
+
+    /// ポインターのID（複数の指を見分ける。省略した場合は0）
+    #[serde(default)]
+    pub pointer_id: u32,
+}
+
+/// 未処理の入力イベントコンポーネント
+///
+/// 入力イベント1つにつき1つのエンティティが作られ、処理後に削除されます。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputEvent {
+    /// 受け付けた順番（InputSystemはこの順に処理する）
+    pub sequence: u64,
+
+    /// ポインターイベント
+    pub pointer: PointerEvent,
+}
+
+impl PointerEvent {
+    /// 座標を検証し、受け付ける範囲に収めたイベントを返す
+    ///
+    /// 補間や配置の計算が壊れないよう、JavaScriptから届いたイベントは入力キューに追加する前に通します。
+    ///
+    /// # 戻り値
+    /// 成功時は座標を±MAX_POINTER_COORDINATEに収めたイベント、座標が有限でない場合はエラーメッセージ
+    pub fn sanitized(self) -> Result<Self, String> {
+        for (field, value) in [("x", self.x), ("y", self.y)] {
+            if !value.is_finite() {
+                return Err(format!("ポインターの座標が不正です（{}）: {}", field, value));
+            }
+        }
+        Ok(Self {
+            x: self.x.clamp(-MAX_POINTER_COORDINATE, MAX_POINTER_COORDINATE),
+            y: self.y.clamp(-MAX_POINTER_COORDINATE, MAX_POINTER_COORDINATE),
+            ..self
+        })
+    }
+}
+
+impl Component for InputEvent {}
+
+/// ドラッグ中のカード
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    /// ドラッグしているカード
+    pub card: Entity,
+
+    /// 押した位置からカードの左上までのずれ
+    pub offset_x: f32,
+    pub offset_y: f32,
+
+    /// ドラッグ開始時のカードの表示座標（中断時に戻す）
+    pub origin_x: f32,
+    pub origin_y: f32,
+
+    /// ドラッグとみなす距離を動かしたかどうか
+    pub moved: bool,
+}
+
+/// 入力状態リソース
+#[derive(Debug, Default)]
+pub struct InputState {
+    /// 次の入力イベントに付ける連番
+    next_sequence: u64,
+
+    /// ドラッグ中のカード（ドラッグしていない場合はNone）
+    pub drag: Option<Drag>,
+
+    /// 押している指の(ポインターID, 画面のX座標, 画面のY座標)（押した順）
+    touches: Vec<(u32, f32, f32)>,
+}
+
+impl InputState {
+    /// 2本以上の指で触れている（ピンチ操作中）かどうか
+    pub fn is_pinching(&self) -> bool {
+        self.touches.len() >= 2
+    }
+}
+
+impl Resource for InputState {}
+
+/// ポインターイベントを入力キューに追加
+///
+/// ワールドはすぐには変更されず、次のフレームでInputSystemが処理します。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `pointer` - 追加するポインターイベント
+///
+/// # 戻り値
+/// 作成した入力イベントのエンティティ
+pub fn push_pointer(world: &mut World, pointer: PointerEvent) -> Entity {
+    if world.get_resource::<InputState>().is_none() {
+        world.insert_resource(InputState::default());
+    }
+    let sequence = world.get_resource_mut::<InputState>().map_or(0, |state| {
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        sequence
+    });
+
+    let entity = world.create_entity();
+    world.add_component(entity, InputEvent { sequence, pointer });
+    entity
+}
+
+// =============================================================================
+// 入力処理システム
+// =============================================================================
+
+/// 入力処理システム
+///
+/// 溜まっている入力イベントを連番の順に処理し、カードの選択・ドラッグ・ドロップに変換します。
+/// ドロップしたカードにはDroppedを付け、移動先の判定はCardMovementSystemに任せます。
+pub struct InputSystem;
+
+impl System for InputSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let mut events: Vec<(Entity, InputEvent)> = world
+            .query::<InputEvent>()
+            .map(|(entity, event)| (entity, *event))
+            .collect();
+        if events.is_empty() {
+            return;
+        }
+        events.sort_by_key(|(_, event)| event.sequence);
+
+        if world.get_resource::<InputState>().is_none() {
+            world.insert_resource(InputState::default());
+        }
+        for (entity, event) in events {
+            handle_pointer(world, event.pointer);
+            world.remove_entity(entity);
+        }
+    }
+}
+
+/// ポインターイベントを1つ処理
+fn handle_pointer(world: &mut World, pointer: PointerEvent) {
+    if track_touches(world, pointer) {
+        return;
+    }
+
+    // 新しいゲームの開始などでカードが消えていた場合はドラッグしていないものとして扱う
+    let drag = world
+        .get_resource::<InputState>()
+        .and_then(|state| state.drag)
+        .filter(|drag| world.has_component::<SolitaireCard>(drag.card));
+
+    // 画面の座標を盤面の座標に直す
+    let viewport = world
+        .get_resource::<Viewport>()
+        .copied()
+        .unwrap_or_default();
+    let (board_x, board_y) = viewport.to_board(pointer.x, pointer.y);
+    let pointer = PointerEvent {
+        x: board_x,
+        y: board_y,
+        ..pointer
+    };
+
+    match (pointer.kind, drag) {
+        (PointerKind::Down, _) => {
+            let new_drag = card_at(world, pointer.x, pointer.y)
+                .filter(|&card| selection::select(world, card))
+                .and_then(|card| {
+                    let card_ref = world.get_component::<SolitaireCard>(card)?;
+                    Some(Drag {
+                        card,
+                        offset_x: pointer.x - card_ref.display_x,
+                        offset_y: pointer.y - card_ref.display_y,
+                        origin_x: card_ref.display_x,
+                        origin_y: card_ref.display_y,
+                        moved: false,
+                    })
+                });
+            // カードのない山を押した場合は選択中のカードをその山へ置き、置けない・何もない場所の場合は選択を解除
+            if new_drag.is_none() && !drop_selected_on_pile(world, pointer.x, pointer.y) {
+                selection::clear_selection(world);
+            }
+            set_drag(world, new_drag);
+        }
+        (PointerKind::Move, Some(mut drag)) => {
+            let (x, y) = (pointer.x - drag.offset_x, pointer.y - drag.offset_y);
+            let distance = (x - drag.origin_x).hypot(y - drag.origin_y);
+            drag.moved |= distance >= DRAG_THRESHOLD;
+            if drag.moved {
+                if let Some(card) = world.get_component_mut::<SolitaireCard>(drag.card) {
+                    card.set_display_position(x, y);
+                }
+            }
+            set_drag(world, Some(drag));
+        }
+        (PointerKind::Up, Some(drag)) => {
+            // 動かさずに離した場合はタップなので、選択したままにする
+            if drag.moved {
+                // カードのない山の上で離した場合は、カードのずれにかかわらずその山へのドロップにする
+                if let Some(placeholder) = placeholder_at(world, pointer.x, pointer.y) {
+                    if let Some(card) = world.get_component_mut::<SolitaireCard>(drag.card) {
+                        card.set_display_position(placeholder.x, placeholder.y);
+                    }
+                }
+                debug!("🖱️ カードをドロップ: {:?}", drag.card);
+                world.add_component(drag.card, Dropped);
+            }
+            set_drag(world, None);
+        }
+        (PointerKind::Cancel, Some(drag)) => {
+            if let Some(card) = world.get_component_mut::<SolitaireCard>(drag.card) {
+                card.set_display_position(drag.origin_x, drag.origin_y);
+            }
+            selection::clear_selection(world);
+            set_drag(world, None);
+        }
+        // ドラッグしていない時の移動・離す操作は無視
+        (_, None) => {}
+    }
+}
+
+/// 押している指を記録し、2本指のピンチ操作をViewportの拡大・移動に変換する
+///
+/// # 戻り値
+/// ピンチ操作として処理した（カードの操作としては処理しない）場合true
+fn track_touches(world: &mut World, pointer: PointerEvent) -> bool {
+    let Some(state) = world.get_resource_mut::<InputState>() else {
+        return false;
+    };
+    let was_pinching = state.is_pinching();
+    let before = state.touches.clone();
+    let touch = (pointer.pointer_id, pointer.x, pointer.y);
+    let existing = state
+        .touches
+        .iter()
+        .position(|(id, _, _)| *id == pointer.pointer_id);
+    match (pointer.kind, existing) {
+        (PointerKind::Down, Some(index)) | (PointerKind::Move, Some(index)) => {
+            state.touches[index] = touch;
+        }
+        (PointerKind::Down, None) => state.touches.push(touch),
+        (PointerKind::Up | PointerKind::Cancel, Some(index)) => {
+            state.touches.remove(index);
+        }
+        _ => {}
+    }
+    let after = state.touches.clone();
+
+    match pointer.kind {
+        // 2本目の指が触れたらドラッグをやめてピンチ操作を始める
+        PointerKind::Down if after.len() >= 2 => {
+            if let Some(drag) = state.drag.take() {
+                if let Some(card) = world.get_component_mut::<SolitaireCard>(drag.card) {
+                    card.set_display_position(drag.origin_x, drag.origin_y);
+                }
+            }
+            debug!("🤏 ピンチ操作を開始");
+            true
+        }
+        PointerKind::Move if after.len() >= 2 => {
+            if let (Some(old), Some(new)) = (pinch_of(&before), pinch_of(&after)) {
+                if world.get_resource::<Viewport>().is_none() {
+                    world.insert_resource(Viewport::default());
+                }
+                if let Some(viewport) = world.get_resource_mut::<Viewport>() {
+                    // 指の中点の移動に合わせてずらし、新しい中点を中心に指の間隔の比で拡大・縮小する
+                    viewport.pan(new.1 - old.1, new.2 - old.2);
+                    viewport.zoom_at(new.0 / old.0, new.1, new.2);
+                }
+            }
+            true
+        }
+        // ピンチ操作の指を離した場合は、残った指でドラッグを始めない
+        _ => was_pinching,
+    }
+}
+
+/// 最初に触れた2本の指の(間隔, 中点のX座標, 中点のY座標)
+fn pinch_of(touches: &[(u32, f32, f32)]) -> Option<(f32, f32, f32)> {
+    let [(_, ax, ay), (_, bx, by), ..] = touches else {
+        return None;
+    };
+    let distance = (bx - ax).hypot(by - ay);
+    (distance > 0.0).then_some((distance, (ax + bx) / 2.0, (ay + by) / 2.0))
+}
+
+/// ドラッグ状態を更新
+fn set_drag(world: &mut World, drag: Option<Drag>) {
+    if let Some(state) = world.get_resource_mut::<InputState>() {
+        state.drag = drag;
+    }
+}
+
+/// 指定した座標にある一番手前の表向きのカードを探す
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `x` - X座標
+/// * `y` - Y座標
+///
+/// # 戻り値
+/// カードがある場合はSome(エンティティ)
+pub fn card_at(world: &World, x: f32, y: f32) -> Option<Entity> {
+    world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| {
+            card.is_face_up
+                && (card.display_x..card.display_x + CARD_WIDTH).contains(&x)
+                && (card.display_y..card.display_y + CARD_HEIGHT).contains(&y)
+        })
+        // 下に重なっているほど表示Y座標が大きく、同じ座標の山では積んだ順・ランクが後のものが手前
+        .max_by(|(_, a), (_, b)| {
+            a.display_y
+                .total_cmp(&b.display_y)
+                .then(a.position_in_location.cmp(&b.position_in_location))
+                .then(a.rank.cmp(&b.rank))
+        })
+        .map(|(entity, _)| entity)
+}
+
+/// 指定した座標にあるカードのない山の置き場所を探す
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `x` - X座標
+/// * `y` - Y座標
+///
+/// # 戻り値
+/// カードのない山の置き場所がある場合はSome(PilePlaceholderのエンティティ)
+pub fn pile_at(world: &World, x: f32, y: f32) -> Option<Entity> {
+    world
+        .query::<PilePlaceholder>()
+        .filter(|(_, placeholder)| {
+            (placeholder.x..placeholder.x + CARD_WIDTH).contains(&x)
+                && (placeholder.y..placeholder.y + CARD_HEIGHT).contains(&y)
+        })
+        .find(|(_, placeholder)| {
+            !world.query::<SolitaireCard>().any(|(_, card)| {
+                card.location_type == placeholder.location
+                    && card.position_in_location == placeholder.index
+            })
+        })
+        .map(|(entity, _)| entity)
+}
+
+/// 指定した座標にあるカードのない山の置き場所を取得
+fn placeholder_at(world: &World, x: f32, y: f32) -> Option<PilePlaceholder> {
+    pile_at(world, x, y)
+        .and_then(|entity| world.get_component::<PilePlaceholder>(entity).copied())
+}
+
+/// 選択中のカードを、指定した座標にあるカードのない山へ置く
+///
+/// 移動はドラッグのドロップと同じくCardMovementSystemが行います。
+///
+/// # 戻り値
+/// 選択中のカードを置ける山を押した場合true
+fn drop_selected_on_pile(world: &mut World, x: f32, y: f32) -> bool {
+    let (Some(card), Some(placeholder)) = (
+        selection::selected_card(world),
+        placeholder_at(world, x, y),
+    ) else {
+        return false;
+    };
+    let droppable = world
+        .get_component::<SolitaireCard>(card)
+        .is_some_and(|card_ref| {
+            selection::drop_targets(world, card_ref).contains(&placeholder.stack)
+        });
+    if !droppable {
+        return false;
+    }
+
+    if let Some(card_ref) = world.get_component_mut::<SolitaireCard>(card) {
+        card_ref.set_display_position(placeholder.x, placeholder.y);
+    }
+    debug!(
+        "🖱️ カードのない山へドロップ: {:?} -> {:?}{}",
+        card, placeholder.location, placeholder.index
+    );
+    world.add_component(card, Dropped);
+    true
+}
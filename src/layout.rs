@@ -0,0 +1,223 @@
+// =============================================================================
+// 盤面のレイアウト（山の位置とカードの並べ方）
+// =============================================================================
+// このファイルでは、クロンダイクの盤面で山（デッキ・ウェイスト・組札・場札）を置く
+// 座標と、山の中でカードをずらす間隔を定義し、盤面のレイアウトをまとめて返す
+// BoardLayoutを実装します。
+//
+// 仕組み：
+// - カードを置く座標は、配る・移動する・シナリオから組み立てる・自動プレイのすべてで
+//   この定数と関数から求める（CardStackの基準座標も同じ値にする）
+// - get_layout()は同じ計算で、山ごとの基準座標・ずらす間隔・枚数と各カードの座標を返す。
+//   JavaScript/canvasの描画はこれを使ってドロップ先や空の山の枠を描けるため、
+//   レイアウトの計算を2つの言語で持たずに済む
+// - 座標は盤面の座標（Viewportで画面の座標に変換する前）で、カードの左上を指す
+// - カードの座標はアニメーション中でも移動先（置かれる位置）を返す
+// =============================================================================
+
+use crate::ecs::World;
+use crate::solitaire::{CardLocation, SolitaireCard};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// カードの幅（ピクセル、当たり判定にも使用）
+pub const CARD_WIDTH: f32 = 80.0;
+
+/// カードの高さ（ピクセル、当たり判定にも使用）
+pub const CARD_HEIGHT: f32 = 120.0;
+
+/// デッキ（山札）の位置
+pub const DECK_POSITION: (f32, f32) = (20.0, 20.0);
+
+/// ウェイスト（捨て札）の位置（デッキの右隣）
+pub const WASTE_POSITION: (f32, f32) = (140.0, 20.0);
+
+/// 1つ目のファウンデーション（組札）の位置
+const FOUNDATION_ORIGIN: (f32, f32) = (400.0, 20.0);
+
+/// 1列目のタブロー（場札）の一番下のカードの位置
+const TABLEAU_ORIGIN: (f32, f32) = (20.0, 150.0);
+
+/// 横に並ぶ山（組札・場札の列）の間隔
+pub const COLUMN_SPACING: f32 = 100.0;
+
+/// タブローで1枚ごとに下へずらす間隔
+pub const TABLEAU_FAN: f32 = 25.0;
+
+/// タブローの列数
+pub const TABLEAU_COLUMNS: u32 = 7;
+
+/// ファウンデーションの数
+pub const FOUNDATION_PILES: u32 = 4;
+
+/// タブローのカードの位置
+///
+/// # 引数
+/// * `column` - 列番号（0〜6）
+/// * `row` - 列の中の位置（0が一番下）
+pub fn tableau_position(column: u32, row: usize) -> (f32, f32) {
+    (
+        TABLEAU_ORIGIN.0 + column as f32 * COLUMN_SPACING,
+        TABLEAU_ORIGIN.1 + row as f32 * TABLEAU_FAN,
+    )
+}
+
+/// ファウンデーションの位置
+///
+/// # 引数
+/// * `index` - ファウンデーションの番号（0〜3）
+pub fn foundation_position(index: u32) -> (f32, f32) {
+    (
+        FOUNDATION_ORIGIN.0 + index as f32 * COLUMN_SPACING,
+        FOUNDATION_ORIGIN.1,
+    )
+}
+
+/// 山の中の1枚のカードの位置（クライアント送信用）
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CardPosition {
+    /// カードのエンティティID
+    pub card_id: u32,
+
+    /// X座標
+    pub x: f32,
+
+    /// Y座標
+    pub y: f32,
+}
+
+/// 1つの山のレイアウト（クライアント送信用）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StackLayout {
+    /// 山のID（"deck" / "waste" / "foundation-0" / "tableau-0"など）
+    pub id: String,
+
+    /// 山の種類
+    pub stack_type: CardLocation,
+
+    /// 山の番号（組札・場札の何番目か、デッキ・ウェイストは0）
+    pub index: u32,
+
+    /// 一番下のカード（空の山の枠）を置くX座標
+    pub anchor_x: f32,
+
+    /// 一番下のカード（空の山の枠）を置くY座標
+    pub anchor_y: f32,
+
+    /// 1枚ごとに下へずらす間隔（重ねて置く山は0）
+    pub spacing: f32,
+
+    /// 山のカードの枚数
+    pub card_count: u32,
+
+    /// 山のカードの位置（下から順）
+    pub cards: Vec<CardPosition>,
+}
+
+/// 盤面全体のレイアウト（クライアント送信用）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct BoardLayout {
+    /// カードの幅
+    pub card_width: f32,
+
+    /// カードの高さ
+    pub card_height: f32,
+
+    /// 山のレイアウト（デッキ・ウェイスト・組札・場札の順）
+    pub stacks: Vec<StackLayout>,
+}
+
+impl BoardLayout {
+    /// ECSワールドのカードから盤面のレイアウトを作成
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    pub fn from_world(world: &World) -> Self {
+        let mut stacks = vec![
+            StackLayout::new(CardLocation::Deck, 0, DECK_POSITION, 0.0),
+            StackLayout::new(CardLocation::Waste, 0, WASTE_POSITION, 0.0),
+        ];
+        stacks.extend((0..FOUNDATION_PILES).map(|index| {
+            StackLayout::new(
+                CardLocation::Foundation,
+                index,
+                foundation_position(index),
+                0.0,
+            )
+        }));
+        stacks.extend((0..TABLEAU_COLUMNS).map(|column| {
+            StackLayout::new(
+                CardLocation::Tableau,
+                column,
+                tableau_position(column, 0),
+                TABLEAU_FAN,
+            )
+        }));
+
+        for stack in &mut stacks {
+            // 山の中の並び順：デッキ・ウェイストは積んだ順、組札はランク順、場札は上からの位置順
+            let mut cards: Vec<(f32, u32)> = world
+                .query::<SolitaireCard>()
+                .filter(|(_, card)| {
+                    card.location_type == stack.stack_type
+                        && (card.position_in_location == stack.index
+                            || matches!(stack.stack_type, CardLocation::Deck | CardLocation::Waste))
+                })
+                .map(|(entity, card)| {
+                    let order = match card.location_type {
+                        CardLocation::Deck | CardLocation::Waste => {
+                            card.position_in_location as f32
+                        }
+                        CardLocation::Foundation => card.rank as u32 as f32,
+                        _ if card.is_animating => card.target_y,
+                        _ => card.display_y,
+                    };
+                    (order, entity.id())
+                })
+                .collect();
+            cards.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            stack.card_count = cards.len() as u32;
+            stack.cards = cards
+                .into_iter()
+                .enumerate()
+                .map(|(row, (_, card_id))| CardPosition {
+                    card_id,
+                    x: stack.anchor_x,
+                    y: stack.anchor_y + row as f32 * stack.spacing,
+                })
+                .collect();
+        }
+
+        Self {
+            card_width: CARD_WIDTH,
+            card_height: CARD_HEIGHT,
+            stacks,
+        }
+    }
+}
+
+impl StackLayout {
+    /// カードのない山のレイアウトを作成
+    fn new(stack_type: CardLocation, index: u32, anchor: (f32, f32), spacing: f32) -> Self {
+        let id = match stack_type {
+            CardLocation::Deck => "deck".to_string(),
+            CardLocation::Waste => "waste".to_string(),
+            CardLocation::Foundation => format!("foundation-{}", index),
+            _ => format!("tableau-{}", index),
+        };
+        Self {
+            id,
+            stack_type,
+            index,
+            anchor_x: anchor.0,
+            anchor_y: anchor.1,
+            spacing,
+            card_count: 0,
+            cards: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// =============================================================================
+// リーダーボード（サーバー用）
+// =============================================================================
+// このファイルでは、クライアントから送信されたゲーム結果を
+// 配り札のシードごとに記録するリーダーボードを実装します。
+//
+// 主要な責務：
+// - クライアントから届いたゲーム結果JSONの解析
+// - シードごとの結果の記録・参照
+// =============================================================================
+
+use crate::clock::GameClock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// クライアントから送信されたゲーム結果の要約
+///
+/// クライアントのGameResult（JSON）から、順位付けに必要な値だけを取り出したものです。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct SubmittedResult {
+    /// 配り札のシード値
+    pub seed: u64,
+
+    /// 最終スコア
+    pub score: u32,
+
+    /// プレイ時間（秒）
+    pub duration_seconds: u64,
+
+    /// 勝利したかどうか
+    pub won: bool,
+}
+
+impl SubmittedResult {
+    /// ゲーム結果JSONから要約を作成
+    ///
+    /// # 引数
+    /// * `result` - クライアントから送信されたゲーム結果JSON
+    ///
+    /// # 戻り値
+    /// 必要なフィールドが揃っている場合はSome(SubmittedResult)、不正な場合はNone
+    pub fn from_json(result: &serde_json::Value) -> Option<Self> {
+        let seed = result.get("seed")?.as_u64()?;
+        let score = result.get("score")?.get("final_score")?.as_u64()?;
+        let duration_seconds = result.get("duration_seconds")?.as_u64()?;
+        let won = result.get("outcome")?.as_str()? == "Won";
+
+        Some(Self {
+            seed,
+            score: u32::try_from(score).ok()?,
+            duration_seconds,
+            won,
+        })
+    }
+}
+
+/// リーダーボードの1件分の記録
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// プレイヤーID
+    pub player_id: String,
+
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// 最終スコア
+    pub score: u32,
+
+    /// プレイ時間（秒）
+    pub duration_seconds: u64,
+
+    /// 勝利したかどうか
+    pub won: bool,
+
+    /// 記録した時刻（UNIXタイムスタンプ）
+    pub recorded_at: u64,
+}
+
+impl LeaderboardEntry {
+    /// 記録をゲーム結果の要約に変換
+    ///
+    /// # 引数
+    /// * `seed` - 記録が属する配り札のシード値
+    pub fn to_result(&self, seed: u64) -> SubmittedResult {
+        SubmittedResult {
+            seed,
+            score: self.score,
+            duration_seconds: self.duration_seconds,
+            won: self.won,
+        }
+    }
+}
+
+/// シードごとのリーダーボード
+#[derive(Debug, Clone, Default)]
+pub struct Leaderboard {
+    /// シード値 → 記録のリスト
+    entries: HashMap<u64, Vec<LeaderboardEntry>>,
+
+    /// 記録時刻の取得元
+    clock: GameClock,
+}
+
+impl Leaderboard {
+    /// 新しい空のリーダーボードを作成
+    ///
+    /// # 戻り値
+    /// 空のLeaderboardインスタンス
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ゲーム結果を記録
+    ///
+    /// # 引数
+    /// * `player_id` - プレイヤーID
+    /// * `player_name` - プレイヤー名
+    /// * `result` - ゲーム結果の要約
+    pub fn record(&mut self, player_id: &str, player_name: &str, result: &SubmittedResult) {
+        let recorded_at = self.clock.now_secs();
+
+        self.entries
+            .entry(result.seed)
+            .or_default()
+            .push(LeaderboardEntry {
+                player_id: player_id.to_string(),
+                player_name: player_name.to_string(),
+                score: result.score,
+                duration_seconds: result.duration_seconds,
+                won: result.won,
+                recorded_at,
+            });
+    }
+
+    /// シードの記録を取得
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード値
+    ///
+    /// # 戻り値
+    /// 記録順のスライス（記録がない場合は空）
+    pub fn entries_for(&self, seed: u64) -> &[LeaderboardEntry] {
+        self.entries.get(&seed).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
@@ -16,37 +16,180 @@
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
-// JavaScriptのコンソールログ出力用（WebAssembly機能有効時のみ）
+// ログ出力用のマクロ（出力先はlogging.rsのロガーがブラウザのコンソールに切り替える）
 #[cfg(feature = "wasm")]
-#[wasm_bindgen]
-extern "C" {
-    // JavaScriptのconsole.logを使用してデバッグ出力を行う
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
+use log::{debug, error, info, warn};
+
+// ゲームセッションのレジストリ（WebAssembly機能有効時のみ）
+// セッションIDごとにECSワールドとシステムを持つランタイムを保持し、
+// JavaScriptから呼び出される関数はすべてこのランタイムを通してECSワールドを操作する
+#[cfg(feature = "wasm")]
+thread_local! {
+    static SESSIONS: std::cell::RefCell<session::SessionRegistry<runtime::GameRuntime>> =
+        std::cell::RefCell::new(session::SessionRegistry::new());
+}
+
+// JavaScript側が持つWebSocketの接続品質の測定器（WebAssembly機能有効時のみ）
+// 接続はページ全体で1つなので、セッションごとではなく1つだけ持つ
+// （network_connect()・connect()で接続したセッションは、NetworkClientが自分で測定する）
+#[cfg(feature = "wasm")]
+thread_local! {
+    static CONNECTION: std::cell::RefCell<connection_quality::ConnectionMonitor> =
+        std::cell::RefCell::new(connection_quality::ConnectionMonitor::new());
+}
+
+// JavaScript側が持つWebSocketでのサーバーの時計とのずれの推定器（WebAssembly機能有効時のみ）
+#[cfg(feature = "wasm")]
+thread_local! {
+    static CLOCK_SYNC: std::cell::RefCell<time_sync::ClockSync> =
+        std::cell::RefCell::new(time_sync::ClockSync::new());
+}
+
+// 同じルームのプレイヤーとのデータチャネル（WebAssembly機能有効時のみ）
+#[cfg(feature = "wasm")]
+thread_local! {
+    static RTC: std::cell::RefCell<rtc::RtcPeers> = std::cell::RefCell::new(rtc::RtcPeers::new());
+}
+
+// 購読のコールバックへの配信を待っているメッセージ（WebAssembly機能有効時のみ）
+// コールバックの中からAPIを呼べるよう、セッションの借用を返してから呼び出す
+#[cfg(feature = "wasm")]
+thread_local! {
+    static SUBSCRIPTION_QUEUE: std::cell::RefCell<Vec<(js_sys::Function, String)>> =
+        std::cell::RefCell::new(Vec::new());
+}
+
+// 呼び出し順序の厳格モード（WebAssembly機能有効時のみ）
+// 有効な場合、ありえない順序で届いた呼び出しをJavaScriptの例外にする
+#[cfg(feature = "wasm")]
+thread_local! {
+    static STRICT_MODE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+// JavaScriptから登録されたイベントコールバック（WebAssembly機能有効時のみ）
+#[cfg(feature = "wasm")]
+thread_local! {
+    static EVENT_CALLBACK: std::cell::RefCell<Option<js_sys::Function>> =
+        std::cell::RefCell::new(None);
+}
+
+// セッションのランタイムに対して処理を実行するヘルパー（WebAssembly機能有効時のみ）
+// 一時停止中のセッションも対象になる（状態の取得や操作はできる）
+// 引数：session_id - セッションID（Noneの場合は既定のセッション）
+//       f - ランタイムへの可変参照を受け取るクロージャ
+// 戻り値：セッションが作成済みの場合はSome(クロージャの戻り値)、ない場合はNone
+#[cfg(feature = "wasm")]
+fn with_runtime<T>(
+    session_id: Option<&str>,
+    f: impl FnOnce(&mut runtime::GameRuntime) -> T,
+) -> Option<T> {
+    let session_id = session::SessionRegistry::<runtime::GameRuntime>::resolve_id(session_id);
+    SESSIONS.with(|sessions| sessions.borrow_mut().get_mut(session_id).map(f))
 }
 
-// マクロを定義：WebAssembly機能の有無に応じて出力先を切り替え
+// 呼び出しが届いた順序をチェックするヘルパー（WebAssembly機能有効時のみ）
+// 厳格モードでは、チェックに通らない呼び出しを理由付きのJavaScriptの例外にする（呼び出し元に戻らない）
+// 厳格モードでない場合は警告のログだけを出して戻る
+// 引数：session_id - セッションID（Noneの場合は既定のセッション）
+//       call - 呼び出された関数名
+//       kind - 呼び出しの種類
 #[cfg(feature = "wasm")]
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
+fn guard_call(session_id: Option<&str>, call: &str, kind: call_guard::CallKind) {
+    let session_id = session::SessionRegistry::<runtime::GameRuntime>::resolve_id(session_id);
+    let result =
+        SESSIONS.with(|sessions| call_guard::check(sessions.borrow().get(session_id), call, kind));
+    let Err(e) = result else {
+        return;
+    };
+    if STRICT_MODE.with(std::cell::Cell::get) {
+        wasm_bindgen::throw_str(&e);
+    }
+    warn!("⚠️ {}", e);
+}
+
+// セッションの乱数生成器で0以上bound未満の乱数を生成するヘルパー（WebAssembly機能有効時のみ）
+// セッションがない場合は、実行環境から得たシードの乱数生成器を使う
+#[cfg(feature = "wasm")]
+fn random_below(session_id: Option<&str>, bound: usize) -> usize {
+    with_runtime(session_id, |rt| {
+        rt.world.get_resource_mut::<rng::Rng>().map(|rng| rng.below(bound))
+    })
+    .flatten()
+    .unwrap_or_else(|| rng::Rng::from_entropy().below(bound))
+}
+
+// 溜まっているゲームイベントをJavaScriptのコールバックへ配信する（WebAssembly機能有効時のみ）
+// イベントは1件ずつJSON文字列としてコールバックの第1引数に、セッションIDが第2引数に渡される
+#[cfg(feature = "wasm")]
+fn dispatch_events() {
+    let events: Vec<(String, events::GameEvent)> = SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        let mut events = Vec::new();
+        for info in sessions.list() {
+            if let Some(rt) = sessions.get_mut(&info.id) {
+                events.extend(rt.drain_events().into_iter().map(|event| (info.id.clone(), event)));
+            }
+        }
+        events
+    });
+    if events.is_empty() {
+        return;
+    }
+    
+    EVENT_CALLBACK.with(|callback| {
+        let callback = callback.borrow();
+        let Some(callback) = callback.as_ref() else {
+            return;
+        };
+        
+        for (session_id, event) in events {
+            match serde_json::to_string(&event) {
+                Ok(json) => {
+                    let result = callback.call2(
+                        &JsValue::NULL,
+                        &JsValue::from_str(&json),
+                        &JsValue::from_str(&session_id),
+                    );
+                    if let Err(e) = result {
+                        error!("❌ イベントコールバックでエラー: {:?}", e);
+                    }
+                }
+                Err(e) => error!("❌ イベントのシリアライゼーション失敗: {}", e),
+            }
+        }
+    });
 }
 
-#[cfg(not(feature = "wasm"))]
-macro_rules! console_log {
-    ($($t:tt)*) => (println!($($t)*))
+// 溜まっている購読のメッセージをJavaScriptのコールバックへ配信する（WebAssembly機能有効時のみ）
+// セッションを借りていない状態で呼ぶこと（コールバックからnetwork_unsubscribe()などを呼べる）
+#[cfg(feature = "wasm")]
+fn dispatch_subscriptions() {
+    let queued = SUBSCRIPTION_QUEUE.with(|queue| std::mem::take(&mut *queue.borrow_mut()));
+    for (callback, json) in queued {
+        if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(&json)) {
+            error!("❌ 購読のコールバックでエラーが発生しました: {:?}", e);
+        }
+    }
 }
 
 // WebAssembly初期化時に実行される関数（WebAssembly機能有効時のみ）
 // パニック時のエラー情報をブラウザのコンソールに出力するよう設定
+// （名前をmainにするとwasm-bindgen-testのエントリーポイントと衝突するためstartとする）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen(start)]
-pub fn main() {
+pub fn start() {
     // パニック時のスタックトレースをコンソールに出力
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 
+    // パニック時に直前のゲームの状態をクラッシュレポートとして保存
+    crash_report::install_panic_hook();
+
+    // ログ出力先をブラウザのコンソールに設定
+    logging::init();
+
     // 初期化完了をログ出力
-    console_log!("🎮 ECS WASM ソリティアゲーム初期化完了！");
+    info!("🎮 ECS WASM ソリティアゲーム初期化完了！");
 }
 
 // =============================================================================
@@ -54,237 +197,2066 @@ pub fn main() {
 // =============================================================================
 
 // ゲームの初期化（WebAssembly機能有効時のみ）
+// 指定したIDのセッションを作成する（同じIDのセッションがある場合は作り直す）
+// 端末内に保存されている設定（テーマ・アニメーションの速さなど）を読み込んで適用する
+// 引数：session_id - セッションID（省略時は既定のセッション）
 // 戻り値：初期化が成功したかどうかを示すブール値
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn initialize_game() -> bool {
-    console_log!("🚀 ゲーム初期化開始...");
+pub fn initialize_game(session_id: Option<String>) -> bool {
+    let session_id =
+        session::SessionRegistry::<runtime::GameRuntime>::resolve_id(session_id.as_deref())
+            .to_string();
+    info!("🚀 ゲーム初期化開始... (セッション: {})", session_id);
     
-    // TODO: ECSワールドの初期化処理をここに追加
-    // TODO: 初期コンポーネントとシステムの登録
+    // ECSワールドとシステムを持つランタイムを作成し、保存されている設定を適用
+    let mut rt = runtime::GameRuntime::new();
+    rt.apply_preferences(settings::Preferences::load());
+    SESSIONS.with(|sessions| {
+        sessions.borrow_mut().insert(&session_id, rt);
+    });
     
-    console_log!("✅ ゲーム初期化完了！");
+    info!("✅ ゲーム初期化完了！");
     true
 }
 
 // 新しいゲームセッションを開始（WebAssembly機能有効時のみ）
 // 引数：player_name - プレイヤー名
-// 戻り値：セッションIDを表す文字列
+//       session_id - ゲームを開始するセッションのID（省略時は既定のセッション）
+// 戻り値：ゲームを開始したセッションのID（他のAPIのsession_idに渡せる）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn start_new_game(player_name: &str) -> String {
-    console_log!("🎯 新しいゲーム開始: プレイヤー「{}」", player_name);
-    
-    // TODO: 新しいゲームセッションの作成処理
-    // TODO: ECSエンティティの生成とコンポーネントの初期化
+pub fn start_new_game(player_name: &str, session_id: Option<String>) -> String {
+    guard_call(session_id.as_deref(), "start_new_game", call_guard::CallKind::Session);
+    info!("🎯 新しいゲーム開始: プレイヤー「{}」", player_name);
+    let session_id = session_id.as_deref();
     
-    // 一時的なセッションID（後でUUID生成に変更予定）
-    #[cfg(feature = "wasm")]
-    let session_id = format!("session_{}", js_sys::Date::now() as u64);
-    #[cfg(not(feature = "wasm"))]
-    let session_id = format!("session_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
+    // クロンダイクのゲームを開始（カードの生成と配布）
+    if with_runtime(session_id, |rt| rt.start_game(solitaire::SolitaireType::Klondike)).is_none() {
+        warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+    }
     
-    console_log!("📝 セッションID生成: {}", session_id);
-    session_id
+    session::SessionRegistry::<runtime::GameRuntime>::resolve_id(session_id).to_string()
 }
 
 // ゲーム状態の更新（WebAssembly機能有効時のみ）
-// デルタタイム（前回の更新からの経過時間）を受け取り、
-// ECSシステムを実行してゲーム状態を進行させる
+// デルタタイム（前回の更新からの経過時間、ミリ秒）を受け取り、
+// ECSシステムを実行してゲーム状態を進行させる（一時停止中のセッションは進めない）
+// 引数：delta_time - 経過時間（ミリ秒）
+//       session_id - 更新するセッションのID（省略時は実行中の全セッション）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn update_game(delta_time: f64) {
-    // TODO: ECSシステムの実行
-    // TODO: 各システムに delta_time を渡して状態更新
+pub fn update_game(delta_time: f64, session_id: Option<String>) {
+    // システムは秒単位のデルタタイムを受け取るため変換する
+    let delta_seconds = delta_time / 1000.0;
+    SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        match session_id.as_deref() {
+            Some(session_id) => {
+                if let Some(rt) = sessions.get_active_mut(session_id) {
+                    rt.update(delta_seconds);
+                    rt.remember_for_crash_report(session_id);
+                }
+            }
+            None => {
+                for (id, rt) in sessions.active_sessions_mut() {
+                    rt.update(delta_seconds);
+                    rt.remember_for_crash_report(id);
+                }
+            }
+        }
+    });
+    dispatch_events();
+    dispatch_subscriptions();
     
-    // デバッグ用（本番では削除予定）
     if delta_time > 16.0 { // 60FPS以下の場合のみログ出力
-        console_log!("⚠️  フレームレート低下検出: {}ms", delta_time);
+        debug!("⚠️  フレームレート低下検出: {}ms", delta_time);
     }
 }
 
+// セッションを一時停止する（WebAssembly機能有効時のみ）
+// 一時停止中はupdate_game()で進まず、状態はそのまま保持される
+// 引数：session_id - セッションID
+// 戻り値：一時停止できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn suspend_session(session_id: &str) -> bool {
+    SESSIONS.with(|sessions| match sessions.borrow_mut().suspend(session_id) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️ セッションを一時停止できません: {}", e);
+            false
+        }
+    })
+}
+
+// 一時停止中のセッションを再開する（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID
+// 戻り値：再開できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn resume_session(session_id: &str) -> bool {
+    SESSIONS.with(|sessions| match sessions.borrow_mut().resume(session_id) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️ セッションを再開できません: {}", e);
+            false
+        }
+    })
+}
+
+// タブの表示・非表示を知らせる（WebAssembly機能有効時のみ）
+// document.visibilitychangeイベントでdocument.hiddenを渡す
+// 非表示の間は実行中の全セッションのゲームループ・経過時間・カーソル位置の送信を止め、
+// 表示に戻ると非表示の間の経過時間を捨てて再開する
+// 引数：hidden - タブが非表示になった場合true
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn notify_visibility(hidden: bool) {
+    SESSIONS.with(|sessions| {
+        for (_, rt) in sessions.borrow_mut().active_sessions_mut() {
+            rt.set_hidden(hidden);
+        }
+    });
+}
+
+// セッションを破棄する（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID
+// 戻り値：破棄できたかどうかを示すブール値（セッションがない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn destroy_session(session_id: &str) -> bool {
+    crash_report::forget(session_id);
+    SESSIONS.with(|sessions| sessions.borrow_mut().destroy(session_id).is_some())
+}
+
+// 呼び出し順序の厳格モードを切り替える（WebAssembly機能有効時のみ）
+// 有効にすると、ありえない順序で届いた呼び出し（initialize_game()の前のmove_card()、
+// 他のプレイヤーのターン中のdraw_card_from_deck()、カードのアニメーション中のload_game()など）を
+// 何もせずに理由付きの例外にする（開発中に有効にして、組み込みの不具合をすぐに見つける）
+// 無効な場合は警告のログだけを出し、これまでどおり処理を続ける（既定）
+// 引数：enabled - 有効にする場合true
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_strict_mode(enabled: bool) {
+    STRICT_MODE.with(|strict| strict.set(enabled));
+    info!("🚦 呼び出し順序の厳格モード: {}", if enabled { "有効" } else { "無効" });
+}
+
+// セッションの一覧を取得（WebAssembly機能有効時のみ）
+// 戻り値：各セッションのIDと状態（"active" / "suspended"）をJSON配列の文字列で返す（ID順）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn list_sessions() -> String {
+    let sessions = SESSIONS.with(|sessions| sessions.borrow().list());
+    serde_json::to_string(&sessions).unwrap_or_default()
+}
+
 // WebSocket接続の状態を取得（WebAssembly機能有効時のみ）
-// 戻り値：接続状態を表す文字列（"connected", "disconnected", "connecting"）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：接続の有無・品質（"good" / "fair" / "poor" / "unknown" / "offline"）・往復時間・
+//         パケットロスと、品質に合わせたカーソル位置とキーフレームの送信間隔、
+//         送信待ちのキューの溜まり具合と溜めた・捨てた・送り直した数（queue）をJSONオブジェクトの文字列で返す
+//         （セッションがNetworkClientで接続している場合はその測定、それ以外はconnection_tick()・record_pong()の測定）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn get_connection_status() -> String {
-    // TODO: WebSocket接続状態の実装
-    "disconnected".to_string()
+pub fn get_connection_status(session_id: Option<String>) -> String {
+    let report = with_client_connection(session_id.as_deref(), |client| client.connection_report())
+        .unwrap_or_else(|| {
+            let mut report = CONNECTION.with(|connection| connection.borrow().report());
+            if let Some(queue) = with_runtime(session_id.as_deref(), |rt| rt.network.queue_metrics()) {
+                report.queue = queue;
+            }
+            report
+        });
+    serde_json::to_string(&report).unwrap_or_default()
 }
 
-// =============================================================================
-// Windowsソリティア専用のWebAssembly API
-// =============================================================================
+// NetworkClientで接続しているセッションの通信を参照する（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（Noneの場合は既定のセッション）
+//       f - 通信を受け取る関数
+// 戻り値：関数の戻り値、セッションがない・NetworkClientで接続していない場合はNone
+#[cfg(feature = "wasm")]
+fn with_client_connection<T>(
+    session_id: Option<&str>,
+    f: impl FnOnce(&network_client::NetworkClient) -> T,
+) -> Option<T> {
+    with_runtime(session_id, |rt| rt.network.connection().is_some().then(|| f(&rt.network))).flatten()
+}
 
-// ソリティアゲームの状態を取得（WebAssembly機能有効時のみ）
-// 戻り値：ゲーム状態をJSON文字列で返す
-#[cfg(feature = "wasm")]
-#[wasm_bindgen]
-pub fn get_solitaire_state() -> String {
-    use crate::solitaire::SolitaireManager;
-    
-    console_log!("📊 ソリティア状態取得リクエスト");
-    
-    // TODO: 実際のゲーム状態を取得
-    // 現在はテスト用の状態を返す
-    let test_state = serde_json::json!({
-        "tableau": [
-            [{"suit": "♠", "rank": "K", "face_up": true}],
-            [{"suit": "♥", "rank": "Q", "face_up": false}, {"suit": "♣", "rank": "J", "face_up": true}],
-            [{"suit": "♦", "rank": "10", "face_up": false}, {"suit": "♠", "rank": "9", "face_up": false}, {"suit": "♥", "rank": "8", "face_up": true}],
-            // ... 他の列
-        ],
-        "foundation": [[], [], [], []], // 4つのファウンデーション
-        "deck_count": 24,
-        "waste": [{"suit": "♣", "rank": "7", "face_up": true}],
-        "moves": 0,
-        "score": 0,
-        "time_elapsed": 0
+// JavaScript側が持つWebSocketの接続品質の測定を進める（WebAssembly機能有効時のみ）
+// 定期的に呼び出し、Pingを返した場合はそのままWebSocketで送信する
+// （network_connect()・connect()で接続した場合は、NetworkClientがupdate_game()のたびに自分でPingを送る）
+// 引数：connected - WebSocketが接続中かどうか
+// 戻り値：送信するPingメッセージ（JSON文字列）、送る時刻でない場合はundefined
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn connection_tick(connected: bool) -> Option<String> {
+    let now_ms = clock::monotonic_ms();
+    let ping_id = CONNECTION.with(|connection| {
+        let mut connection = connection.borrow_mut();
+        connection.set_connected(connected);
+        connection.tick(now_ms)
     });
-    
-    test_state.to_string()
+    let ping_id = ping_id?;
+    let clock_offset_ms = CLOCK_SYNC
+        .with(|sync| sync.borrow().offset_ms())
+        .map(|offset| offset.round() as i64);
+    let ping = protocol::WebSocketMessage::Ping {
+        ping_id,
+        client_time_ms: clock::unix_time_ms() as u64,
+        clock_offset_ms,
+    };
+    serde_json::to_string(&ping).ok()
 }
 
-// カードを移動する（WebAssembly機能有効時のみ）
-// 引数：from_location, to_location - 移動元と移動先の位置情報（JSON文字列）
-// 戻り値：移動が成功したかどうかを示すブール値
+// JavaScript側が持つWebSocketに届いたPongを記録する（WebAssembly機能有効時のみ）
+// 往復時間に加えて、サーバーの時計とのずれも推定し直す
+// （NetworkClientに届いたPongは、NetworkClientが自分で記録する）
+// 引数：message_json - サーバーから届いたメッセージ（例：{"type": "Pong", "ping_id": 3, "client_time_ms": 1700000000000, "server_time_ms": 1700000000040}）
+// 戻り値：応答待ちのPingへの応答として記録できたかどうかを示すブール値
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn move_card(from_location: &str, to_location: &str) -> bool {
-    console_log!("🎯 カード移動: {} -> {}", from_location, to_location);
+pub fn record_pong(message_json: &str) -> bool {
+    let pong = protocol::WebSocketMessage::parse(message_json);
+    let (ping_id, client_time_ms, server_time_ms) = match pong {
+        Ok(protocol::WebSocketMessage::Pong { ping_id, client_time_ms, server_time_ms }) => {
+            (ping_id, client_time_ms, server_time_ms)
+        }
+        _ => {
+            warn!("⚠️ Pongの形式が不正です: {}", message_json);
+            return false;
+        }
+    };
     
-    // TODO: 実際の移動処理を実装
-    // 現在はテスト用に常にtrueを返す
+    let now_ms = clock::monotonic_ms();
+    let rtt_ms = CONNECTION.with(|connection| connection.borrow_mut().record_pong(ping_id, now_ms));
+    let Some(rtt_ms) = rtt_ms else {
+        return false;
+    };
     
-    // JSONパースのテスト
-    match (serde_json::from_str::<serde_json::Value>(from_location), 
-           serde_json::from_str::<serde_json::Value>(to_location)) {
-        (Ok(from), Ok(to)) => {
-            console_log!("✅ 移動先パース成功: {:?} -> {:?}", from, to);
-            true
-        },
-        _ => {
-            console_log!("❌ 移動先パース失敗");
+    // 時間切れ後や重複したPongは、時計のずれの推定にも使わない
+    let offset_ms = CLOCK_SYNC.with(|sync| {
+        sync.borrow_mut().record(client_time_ms as f64, server_time_ms as f64, clock::unix_time_ms())
+    });
+    debug!("🏓 往復時間: {:.0}ms, 時計のずれ: {:?}ms", rtt_ms, offset_ms);
+    true
+}
+
+// サーバーの時計とのずれを取得（WebAssembly機能有効時のみ）
+// 既定のセッションがNetworkClientで接続している場合はその推定、それ以外はrecord_pong()の推定を返す
+// 戻り値：サーバーの時刻 - この端末の時刻（ミリ秒）、まだ推定できていない場合はundefined
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_clock_offset() -> Option<f64> {
+    with_client_connection(None, network_client::NetworkClient::clock_offset_ms)
+        .unwrap_or_else(|| CLOCK_SYNC.with(|sync| sync.borrow().offset_ms()))
+}
+
+// サーバーの時刻をこの端末の時刻（Date.now()と同じ基準）に直す（WebAssembly機能有効時のみ）
+// MousePosition / GameActionのタイムスタンプはサーバーの時刻で届くため、補間やターンの残り時間の計算前に使う
+// 引数：server_time_ms - サーバーの時刻（ミリ秒）
+// 戻り値：この端末の時刻（ミリ秒、ずれを推定できていない場合はそのまま）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn server_to_local_time(server_time_ms: f64) -> f64 {
+    with_client_connection(None, |client| client.server_to_local_time(server_time_ms))
+        .unwrap_or_else(|| CLOCK_SYNC.with(|sync| sync.borrow().to_local_time(server_time_ms)))
+}
+
+// ルームの参加者に合わせてデータチャネルを開く・閉じる（WebAssembly機能有効時のみ）
+// ルームに参加したときと、参加者が変わったときに呼び出す（接続交渉のメッセージはrtc_take_signals()で取り出す）
+// 引数：player_id - 自分のプレイヤーID
+//       room_id - 参加しているルームのID
+//       peer_ids_json - 同じルームの他のプレイヤーのID（例：["player-2", "player-3"]）
+// 戻り値：受け付けたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn rtc_set_room(player_id: &str, room_id: &str, peer_ids_json: &str) -> bool {
+    let peers = match serde_json::from_str::<Vec<String>>(peer_ids_json) {
+        Ok(peers) => peers,
+        Err(e) => {
+            error!("❌ 参加者の形式が不正です: {}", e);
+            return false;
+        }
+    };
+    let peers = peers.into_iter().filter(|peer| peer != player_id).collect();
+    RTC.with(|rtc| rtc.borrow_mut().set_room(player_id, room_id, peers));
+    true
+}
+
+// ルームを出たときに、すべてのデータチャネルを閉じる（WebAssembly機能有効時のみ）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn rtc_leave_room() {
+    RTC.with(|rtc| rtc.borrow_mut().leave_room());
+}
+
+// サーバーから届いた接続交渉のメッセージを処理する（WebAssembly機能有効時のみ）
+// 引数：message_json - RtcSignalメッセージ（例：{"type": "RtcSignal", "room_id": "room-1", "from_player_id": "player-1",
+//       "to_player_id": "player-2", "signal": {"kind": "offer", "sdp": "..."}}）
+// 戻り値：処理を始めたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn rtc_handle_signal(message_json: &str) -> bool {
+    let message = match protocol::WebSocketMessage::parse(message_json) {
+        Ok(message) => message,
+        Err(e) => {
+            warn!("⚠️ 接続交渉のメッセージが不正です: {}", e);
+            return false;
+        }
+    };
+    match RTC.with(|rtc| rtc.borrow_mut().handle_signal(message)) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️ {}", e);
             false
         }
     }
 }
 
-// デッキからカードを引く（WebAssembly機能有効時のみ）
-// 戻り値：引いたカードの情報をJSON文字列で返す（引けない場合は空文字列）
+// WebSocketで送る接続交渉のメッセージを取り出す（WebAssembly機能有効時のみ）
+// 定期的に呼び出し、取り出したメッセージをそのままWebSocketで送信する
+// 戻り値：RtcSignalメッセージのJSON配列（文字列）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn draw_card_from_deck() -> String {
-    console_log!("🎴 デッキからカードを引く");
-    
-    // TODO: 実際のデッキ処理を実装
-    // 現在はテスト用のランダムカードを返す
-    
-    use js_sys::Math;
-    let suits = ["♠", "♥", "♦", "♣"];
-    let ranks = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"];
-    
-    let suit_index = (Math::random() * 4.0) as usize;
-    let rank_index = (Math::random() * 13.0) as usize;
-    
-    let card = serde_json::json!({
-        "suit": suits[suit_index],
-        "rank": ranks[rank_index],
-        "face_up": true
-    });
-    
-    console_log!("🎴 引いたカード: {}", card.to_string());
-    card.to_string()
+pub fn rtc_take_signals() -> String {
+    let signals = RTC.with(|rtc| rtc.borrow_mut().take_signals());
+    serde_json::to_string(&signals).unwrap_or_else(|_| "[]".to_string())
 }
 
-// ゲームのリセット（WebAssembly機能有効時のみ）
-// 戻り値：リセットが成功したかどうかを示すブール値
+// データチャネルで届いたメッセージを取り出す（WebAssembly機能有効時のみ）
+// WebSocketで届いたメッセージと同じように処理する
+// 戻り値：メッセージのJSON配列（文字列）
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn reset_solitaire_game() -> bool {
-    console_log!("🔄 ソリティアゲームをリセット");
-    
-    // TODO: 実際のリセット処理を実装
-    // - カードの再配布
-    // - スコアのリセット
-    // - タイマーのリセット
-    
-    console_log!("✅ ゲームリセット完了");
-    true
+pub fn rtc_take_messages() -> String {
+    let messages = RTC.with(|rtc| rtc.borrow_mut().take_received());
+    let values: Vec<serde_json::Value> = messages
+        .iter()
+        .filter_map(|text| serde_json::from_str(text).ok())
+        .collect();
+    serde_json::to_string(&values).unwrap_or_else(|_| "[]".to_string())
 }
 
-// 自動配置を試行（WebAssembly機能有効時のみ）
-// 引数：card_info - カード情報（JSON文字列）
-// 戻り値：自動配置が成功したかどうかを示すブール値
+// メッセージを送る通信経路を選び、データチャネルで送れる場合は送る（WebAssembly機能有効時のみ）
+// WebSocketでの送信の代わりに呼び出す
+// 引数：message_json - 送信するメッセージ
+// 戻り値：WebSocketで送る場合はそのメッセージ（JSON文字列）、データチャネルで送った場合はundefined
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn try_auto_place(card_info: &str) -> bool {
-    console_log!("🚀 自動配置試行: {}", card_info);
-    
-    // TODO: 実際の自動配置ロジックを実装
-    // - ファウンデーションへの配置チェック
-    // - タブローへの配置チェック
-    
-    // テスト用：50%の確率で成功
-    let success = js_sys::Math::random() > 0.5;
-    
-    if success {
-        console_log!("✨ 自動配置成功");
+pub fn route_message(message_json: &str) -> Option<String> {
+    // 形式が不正なメッセージはサーバーにエラーを返してもらう
+    let Ok(message) = protocol::WebSocketMessage::parse(message_json) else {
+        return Some(message_json.to_string());
+    };
+    let sent = RTC.with(|rtc| {
+        let rtc = rtc.borrow();
+        network::NetworkManager::select_transport(&rtc.selector(), &message) == transport::Transport::DataChannel
+            && rtc.send(message_json)
+    });
+    if sent {
+        None
     } else {
-        console_log!("⚠️ 自動配置失敗");
+        Some(message_json.to_string())
     }
-    
-    success
 }
 
-// 勝利条件をチェック（WebAssembly機能有効時のみ）
-// 戻り値：ゲームが完了したかどうかを示すブール値
+// データチャネルの状態を取得（WebAssembly機能有効時のみ）
+// 戻り値：{"peers": 他の参加者数, "open_channels": 開いているデータチャネル数, "all_open": 全員と開いているか}
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn check_victory() -> bool {
-    console_log!("🏆 勝利条件チェック");
-    
-    // TODO: 実際の勝利条件チェックを実装
-    // - 全てのカードがファウンデーションに配置されているかチェック
-    
-    false // 現在は常にfalse
+pub fn get_transport_status() -> String {
+    let selector = RTC.with(|rtc| rtc.borrow().selector());
+    let open_channels = selector.room_peers().iter().filter(|peer| selector.is_open(peer)).count();
+    serde_json::json!({
+        "peers": selector.room_peers().len(),
+        "open_channels": open_channels,
+        "all_open": selector.all_channels_open(),
+    })
+    .to_string()
 }
 
-// ヒントを取得（WebAssembly機能有効時のみ）
-// 戻り値：ヒント情報をJSON文字列で返す
+// =============================================================================
+// サーバーとの通信（NetworkClient）のWebAssembly API
+// =============================================================================
+
+// サーバーへの接続を開始（WebAssembly機能有効時のみ）
+// 接続・受信・送信はupdate_game()のたびに進む
+// 引数：url - 接続先のWebSocket URL（例："ws://localhost:8101"）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：接続を開始できたかどうかを示すブール値
 #[cfg(feature = "wasm")]
 #[wasm_bindgen]
-pub fn get_hint() -> String {
-    console_log!("💡 ヒント取得");
-    
-    // TODO: 実際のヒント生成ロジックを実装
-    
-    let hint = serde_json::json!({
-        "type": "move",
-        "message": "♥のKをファウンデーションに移動できます",
-        "from": {"type": "tableau", "column": 0},
-        "to": {"type": "foundation", "suit": "♥"}
+pub fn network_connect(url: &str, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.connect(&mut rt.world, url)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            error!("❌ {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// サーバーとの接続を切断（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_disconnect(session_id: Option<String>) {
+    with_runtime(session_id.as_deref(), |rt| rt.network.disconnect(&mut rt.world));
+}
+
+// 表示名を指定してプレイヤーとして参加（WebAssembly機能有効時のみ）
+// 接続前に呼び出した場合は、接続した時点で参加する（接続し直した場合も同じ表示名で参加し直す）
+// 引数：player_name - 表示名
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：セッションがあるかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_join(player_name: &str, session_id: Option<String>) -> bool {
+    with_runtime(session_id.as_deref(), |rt| rt.network.join(&rt.world, player_name)).is_some()
+}
+
+// ルームに参加（WebAssembly機能有効時のみ）
+// 参加できたかどうかはJoinRoom / Errorメッセージの購読で受け取る
+// 引数：room_id - 参加するルームのID
+//       password - パスワード付きのルームに参加する場合のパスワード
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：申し込めたかどうかを示すブール値（network_join()の前はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_join_room(room_id: &str, password: Option<String>, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.join_room(room_id, password)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 参加中のルームから退出（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：退出を申し込めたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_leave_room(session_id: Option<String>) -> bool {
+    matches!(with_runtime(session_id.as_deref(), |rt| rt.network.leave_room()), Some(Ok(())))
+}
+
+// ゲームアクションを送信（WebAssembly機能有効時のみ）
+// 引数：action - アクションの内容（例："draw"）
+//       x, y - アクションの位置（位置がない場合は省略）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信待ちに追加できたかどうかを示すブール値（プレイヤーIDを受け取る前はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_send_action(action: &str, x: Option<f64>, y: Option<f64>, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.send_action(action, x, y)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// パワーアップを使う（WebAssembly機能有効時のみ）
+// パワーアップを有効にしたルームのレース中のみ使える。サーバーが受け付けると盤面に反映される
+// 引数：name - パワーアップの名前（"peek" / "free_shuffle" / "time_penalty"）
+//       column - 覗くタブローの列番号（0から開始、"peek"の場合のみ）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信待ちに追加できたかどうかを示すブール値（ルームに参加していない・名前や列が不正な場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_use_power_up(name: &str, column: Option<u32>, session_id: Option<String>) -> bool {
+    let result = power_up::PowerUp::parse(name).and_then(|power_up| {
+        with_runtime(session_id.as_deref(), |rt| rt.network.use_power_up(power_up, column))
+            .unwrap_or_else(|| Err("セッションが作成されていません".to_string()))
     });
-    
-    console_log!("💡 ヒント生成: {}", hint.to_string());
-    hint.to_string()
+    match result {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+    }
 }
 
-// =============================================================================
-// WebAssemblyメモリの最適化
-// =============================================================================
+// 共有盤面のルームで観戦に切り替える・操作できる参加者に戻る（WebAssembly機能有効時のみ）
+// 観戦中はすべてのカードがget_solitaire_state()のlockedになり、操作できない
+// 引数：spectating - trueで観戦に切り替え、falseで操作できる参加者に戻る
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信待ちに追加できたかどうかを示すブール値（ルームに参加していない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_set_spectating(spectating: bool, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.set_spectating(spectating)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
 
-// 軽量なアロケータを使用（WebAssembly向け最適化）
-#[cfg(all(feature = "wasm", feature = "wee_alloc"))]
-#[global_allocator]
-static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+// 共有盤面のルームでカードの持ち主を決める（ホストのみ、WebAssembly機能有効時のみ）
+// 持ち主の決まったカードは、他のプレイヤーの画面ではget_solitaire_state()のlockedになる
+// 引数：card_id - カードのID（get_solitaire_state()のカードのid）
+//       owner_id - 持ち主のプレイヤーID（省略時は持ち主を外す）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信待ちに追加できたかどうかを示すブール値（ルームに参加していない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_set_card_owner(card_id: u32, owner_id: Option<String>, session_id: Option<String>) -> bool {
+    let sent = with_runtime(session_id.as_deref(), |rt| {
+        // サーバーとはエンティティIDが一致しないため、スートとランクのIDにして送る
+        let card = rt
+            .world
+            .get_component::<solitaire::SolitaireCard>(ecs::Entity(card_id))
+            .map(permissions::card_id)
+            .ok_or_else(|| format!("カードが見つかりません: {}", card_id))?;
+        rt.network.set_card_owner(card, owner_id)
+    });
+    match sent {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
 
-// =============================================================================
-// モジュール宣言：他のファイルで定義される機能
-// =============================================================================
+// カーソル位置を送信（WebAssembly機能有効時のみ）
+// カーソルのチャネルの連番を付けるため、受信側は古い位置を捨てられる
+// 接続品質に合わせた間隔より早く呼ばれた場合は最新の位置だけを残し、間隔が空いたフレームで送る
+// 拡大率・表示位置は端末ごとに違うため、盤面の座標に直して送る（受信側は自分のviewportで画面の座標に直す）
+// 引数：x, y - カーソルの画面の座標
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信待ちに追加できたかどうかを示すブール値（プレイヤーIDを受け取る前・タブが非表示の間はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_send_cursor(x: f64, y: f64, session_id: Option<String>) -> bool {
+    if with_runtime(session_id.as_deref(), |rt| rt.is_hidden()).unwrap_or(false) {
+        return false;
+    }
+    match with_runtime(session_id.as_deref(), |rt| {
+        let viewport = rt.world.get_resource::<viewport::Viewport>().copied().unwrap_or_default();
+        let (board_x, board_y) = viewport.to_board(x as f32, y as f32);
+        rt.network.send_cursor(&rt.world, f64::from(board_x), f64::from(board_y))
+    }) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
 
-// ECS関連のモジュール
-mod ecs;       // ECSコンポーネント実装完了により有効化
-mod game;      // ゲーム状態管理システム実装完了により有効化
-mod network;   // WebSocket通信レイヤ実装完了により有効化
-mod solitaire; // ソリティアゲームロジック実装完了により有効化
\ No newline at end of file
+// サーバーから届いたメッセージを購読（WebAssembly機能有効時のみ）
+// メッセージはJSON文字列としてコールバックの第1引数に渡される
+// コールバックはメッセージを処理し終えてから（update_game()・network_receive()から戻る前に）呼ばれ、
+// その中からnetwork_unsubscribe()などのAPIを呼べる
+// 引数：message_type - 受け取るメッセージの種類（例："RoomList"、"*"の場合はすべて）
+//       callback - 呼び出すJavaScript関数
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：購読ID（network_unsubscribe()に渡す）、セッションがない場合はundefined
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_subscribe(message_type: &str, callback: js_sys::Function, session_id: Option<String>) -> Option<u32> {
+    // セッションを借りている間はJavaScriptを呼ばず、配信を待つメッセージとして溜める
+    let handler = Box::new(move |message: &protocol::WebSocketMessage| {
+        let Ok(json) = serde_json::to_string(message) else {
+            return;
+        };
+        SUBSCRIPTION_QUEUE.with(|queue| queue.borrow_mut().push((callback.clone(), json)));
+    });
+    with_runtime(session_id.as_deref(), |rt| rt.network.subscribe(message_type, handler))
+}
+
+// 購読を解除（WebAssembly機能有効時のみ）
+// 引数：subscription_id - network_subscribe()が返した購読ID
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：解除できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_unsubscribe(subscription_id: u32, session_id: Option<String>) -> bool {
+    with_runtime(session_id.as_deref(), |rt| rt.network.unsubscribe(subscription_id)).unwrap_or(false)
+}
+
+// JavaScript側が持つWebSocketの接続状態を知らせる（WebAssembly機能有効時のみ）
+// network_connect()を使わず、JavaScript側でWebSocketを開く場合に使う
+// 引数：connected - 接続中かどうか
+//       session_id - セッションID（省略時は既定のセッション）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_set_connected(connected: bool, session_id: Option<String>) {
+    with_runtime(session_id.as_deref(), |rt| rt.network.set_connected(&mut rt.world, connected));
+}
+
+// JavaScript側が持つWebSocketで受け取ったメッセージを渡す（WebAssembly機能有効時のみ）
+// 引数：message_json - サーバーから届いたメッセージ
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：処理できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_receive(message_json: &str, session_id: Option<String>) -> bool {
+    let received = with_runtime(session_id.as_deref(), |rt| rt.network.receive(&mut rt.world, message_json));
+    dispatch_subscriptions();
+    match received {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// JavaScript側が持つWebSocketで送るメッセージを取り出す（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：送信するメッセージのJSON配列（文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn network_take_outgoing(session_id: Option<String>) -> String {
+    let messages = with_runtime(session_id.as_deref(), |rt| rt.network.take_outgoing(&mut rt.world)).unwrap_or_default();
+    let values: Vec<serde_json::Value> = messages
+        .iter()
+        .filter_map(|text| serde_json::from_str(text).ok())
+        .collect();
+    serde_json::to_string(&values).unwrap_or_else(|_| "[]".to_string())
+}
+
+// サーバーとの通信の状態を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：{"status": 接続状態, "player_id": プレイヤーID, "room_id": 参加中のルームのID}のJSON文字列
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_network_status(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| {
+        serde_json::json!({
+            "status": rt.network.status(&rt.world).as_str(),
+            "player_id": rt.network.player_id(),
+            "room_id": rt.network.room_id(),
+        })
+        .to_string()
+    })
+    .unwrap_or_default()
+}
+
+// =============================================================================
+// マルチプレイの接続・ルームのWebAssembly API（Promiseを返す）
+// =============================================================================
+// どの関数もサーバーの応答が届いた時点でPromiseが解決される。
+// WebSocketの通信はupdate_game()のたびに進むため、ゲームループを動かしたまま待つこと。
+// エラー・切断・タイムアウトの場合はエラーメッセージ（文字列）でPromiseが拒否される。
+
+// 要求を送ってサーバーの応答を待つヘルパー（WebAssembly機能有効時のみ）
+// 応答はNetworkClientが要求のIDで対応付け、エラーの応答はPromiseの拒否としてJavaScriptに伝わる
+// 引数：session_id - セッションID（Noneの場合は既定のセッション）
+//       send - 要求のメッセージを送り、要求のIDを返すクロージャ
+// 戻り値：応答のメッセージ、送れなかった場合・応答がエラーの場合はエラーメッセージ
+#[cfg(feature = "wasm")]
+async fn request_reply(
+    session_id: Option<String>,
+    send: impl FnOnce(&mut runtime::GameRuntime) -> Result<String, String>,
+) -> Result<protocol::WebSocketMessage, JsValue> {
+    let mut send = Some(send);
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let Some(send) = send.take() else {
+            return;
+        };
+        let rejected = reject.clone();
+        let reply: network_client::Reply = Box::new(move |result| {
+            let outcome = match result.and_then(|message| serde_json::to_string(&message).map_err(|e| e.to_string())) {
+                Ok(json) => resolve.call1(&JsValue::NULL, &JsValue::from_str(&json)),
+                Err(e) => rejected.call1(&JsValue::NULL, &JsValue::from_str(&e)),
+            };
+            if let Err(e) = outcome {
+                error!("❌ Promiseを解決できません: {:?}", e);
+            }
+        });
+        
+        let sent = with_runtime(session_id.as_deref(), |rt| {
+            let request_id = send(rt)?;
+            rt.network.await_reply(&rt.world, &request_id, reply);
+            Ok(())
+        })
+        .unwrap_or_else(|| Err("セッションが作成されていません".to_string()));
+        if let Err(e) = sent {
+            let _ = reject.call1(&JsValue::NULL, &JsValue::from_str(&e));
+        }
+    });
+    
+    let json = wasm_bindgen_futures::JsFuture::from(promise).await?;
+    let json = json.as_string().unwrap_or_default();
+    serde_json::from_str(&json).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+// サーバーに接続してプレイヤーとして参加（WebAssembly機能有効時のみ）
+// 既に接続している場合は接続し直す
+// 引数：url - 接続先のWebSocket URL（例："ws://localhost:8101"）
+//       player_name - 表示名
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：サーバーが割り当てたプロフィールのJSON文字列で解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn connect(url: String, player_name: String, session_id: Option<String>) -> Result<String, JsValue> {
+    info!("🔌 サーバーに接続します: {}", url);
+    let reply = request_reply(session_id, |rt| {
+        rt.network.connect(&mut rt.world, &url)?;
+        Ok(rt.network.join(&rt.world, &player_name))
+    })
+    .await?;
+    
+    match reply {
+        protocol::WebSocketMessage::PlayerProfile { profile, .. } => {
+            serde_json::to_string(&profile).map_err(|e| JsValue::from_str(&e.to_string()))
+        }
+        _ => Err(JsValue::from_str("予期しない応答です")),
+    }
+}
+
+// サーバーとの接続を切断（WebAssembly機能有効時のみ）
+// 応答を待っている要求はすべて拒否される
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：切断した時点で解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn disconnect(session_id: Option<String>) {
+    network_disconnect(session_id);
+}
+
+// ルームに参加（WebAssembly機能有効時のみ）
+// 引数：room_id - 参加するルームのID
+//       password - パスワード付きのルームに参加する場合のパスワード
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：参加したルームのIDで解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn join_room(room_id: String, password: Option<String>, session_id: Option<String>) -> Result<String, JsValue> {
+    request_reply(session_id, |rt| rt.network.join_room(&room_id, password)).await?;
+    Ok(room_id)
+}
+
+// ルームを作成して参加（WebAssembly機能有効時のみ）
+// 作成者はそのルームのホストになる
+// 引数：options_json - ルームの設定のJSON文字列
+//                      （例：{"name": "ルーム", "max_players": 4, "password": "abc", "turn_time_limit": 30, "combo_window_seconds": 5}、名前以外は省略可）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：作成したルームのIDで解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn create_room(options_json: String, session_id: Option<String>) -> Result<String, JsValue> {
+    let options: network_client::RoomOptions = serde_json::from_str(&options_json)
+        .map_err(|e| JsValue::from_str(&format!("ルームの設定が不正です: {}", e)))?;
+    let reply = request_reply(session_id, |rt| rt.network.create_room(options)).await?;
+    
+    match reply {
+        protocol::WebSocketMessage::JoinRoom { room_id, .. } => {
+            info!("🏠 ルームを作成しました: {}", room_id);
+            Ok(room_id)
+        }
+        _ => Err(JsValue::from_str("予期しない応答です")),
+    }
+}
+
+// ルーム一覧を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：ルーム情報の配列のJSON文字列で解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn list_rooms(session_id: Option<String>) -> Result<String, JsValue> {
+    let reply = request_reply(session_id, |rt| rt.network.request_room_list()).await?;
+    
+    match reply {
+        protocol::WebSocketMessage::RoomList { rooms, .. } => {
+            serde_json::to_string(&rooms).map_err(|e| JsValue::from_str(&e.to_string()))
+        }
+        _ => Err(JsValue::from_str("予期しない応答です")),
+    }
+}
+
+// 配り札のシードごとのリーダーボードをサーバーのHTTP APIから取得（WebAssembly機能有効時のみ）
+// 引数：api_url - HTTP APIのURL（例："http://localhost:8102"）
+//       seed - 配り札のシード
+// 戻り値：{"seed": シード, "entries": 順位順の記録}のJSON文字列で解決されるPromise
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub async fn fetch_leaderboard(api_url: String, seed: u64) -> Result<String, JsValue> {
+    use wasm_bindgen::JsCast;
+    
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("windowオブジェクトがありません"))?;
+    let url = format!("{}/api/leaderboard/{}", api_url.trim_end_matches('/'), seed);
+    let response: web_sys::Response = wasm_bindgen_futures::JsFuture::from(window.fetch_with_str(&url))
+        .await?
+        .dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("リーダーボードを取得できません（HTTP {}）", response.status())));
+    }
+    
+    let text = wasm_bindgen_futures::JsFuture::from(response.text()?).await?;
+    text.as_string().ok_or_else(|| JsValue::from_str("リーダーボードの応答が文字列ではありません"))
+}
+
+// =============================================================================
+// Windowsソリティア専用のWebAssembly API
+// =============================================================================
+
+// ソリティアゲームの状態を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：ゲーム状態をJSON文字列で返す（形式はget_state_schema()のJSON Schemaを参照、
+//         設定のformat_numbersが有効な場合はscore.formattedに整形した経過時間・スコアを含める）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_solitaire_state(session_id: Option<String>) -> String {
+    debug!("📊 ソリティア状態取得リクエスト");
+    
+    // 状態を作ってJSONにした時間は、そのセッションの直近のフレームのシリアライズとして記録する
+    with_runtime(session_id.as_deref(), |rt| {
+        let start = clock::monotonic_ms();
+        let state = client_state::ClientState::from_world(&rt.world, rt.game_entity);
+        let json = serde_json::to_string(&state).unwrap_or_default();
+        rt.frame_timings.add(frame_timings::TimingCategory::Serialization, clock::monotonic_ms() - start);
+        json
+    })
+    .unwrap_or_else(|| {
+        let state = client_state::ClientState::from_world(&ecs::World::new(), None);
+        serde_json::to_string(&state).unwrap_or_default()
+    })
+}
+
+// 直近のフレームの処理時間を取得（WebAssembly機能有効時のみ）
+// WebAssembly側が16ms（60FPS）を超え続ける遅い端末で、影やアニメーションを切る判断に使う
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：1フレームあたり[ロジック, アニメーション, シリアライズ, 通信]の4つの値（ミリ秒）を
+//         古い順に並べたFloat32Array（直近60フレーム分まで、セッションがない場合は空）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_frame_timings(session_id: Option<String>) -> Vec<f32> {
+    with_runtime(session_id.as_deref(), |rt| rt.frame_timings.to_array()).unwrap_or_default()
+}
+
+// ゲーム状態JSONのスキーマを取得（WebAssembly機能有効時のみ）
+// フロントエンドはこのJSON Schemaでget_solitaire_state()の戻り値を検証できる
+// 戻り値：JSON Schema（JSON文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_state_schema() -> String {
+    debug!("📐 ゲーム状態スキーマ取得");
+    
+    serde_json::to_string(&client_state::ClientState::json_schema()).unwrap_or_default()
+}
+
+// カードを移動する（WebAssembly機能有効時のみ）
+// 引数：from_location, to_location - 移動元と移動先の位置情報（JSON文字列）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：移動が成功したかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn move_card(from_location: &str, to_location: &str, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "move_card", call_guard::CallKind::Move);
+    debug!("🎯 カード移動: {} -> {}", from_location, to_location);
+    
+    // TODO: 実際の移動処理を実装
+    // 現在は場所指定が正しければtrueを返す
+    
+    match (protocol::MoveLocation::parse(from_location),
+           protocol::MoveLocation::parse(to_location)) {
+        (Ok(from), Ok(to)) => {
+            debug!("✅ 移動先パース成功: {:?} -> {:?}", from, to);
+            true
+        },
+        (Err(e), _) | (_, Err(e)) => {
+            error!("❌ 移動先パース失敗: {}", e);
+            false
+        }
+    }
+}
+
+// デッキからカードを引く（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：引いたカードの情報をJSON文字列で返す（引けない場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn draw_card_from_deck(session_id: Option<String>) -> String {
+    guard_call(session_id.as_deref(), "draw_card_from_deck", call_guard::CallKind::Move);
+    debug!("🎴 デッキからカードを引く");
+    
+    // TODO: 実際のデッキ処理を実装
+    // 現在はテスト用のランダムカードを返す
+    
+    let suits = ["♠", "♥", "♦", "♣"];
+    let ranks = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"];
+    
+    let suit_index = random_below(session_id.as_deref(), suits.len());
+    let rank_index = random_below(session_id.as_deref(), ranks.len());
+    
+    let card = serde_json::json!({
+        "suit": suits[suit_index],
+        "rank": ranks[rank_index],
+        "face_up": true
+    });
+    
+    debug!("🎴 引いたカード: {}", card);
+    card.to_string()
+}
+
+// ゲームのリセット（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：リセットが成功したかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn reset_solitaire_game(_session_id: Option<String>) -> bool {
+    info!("🔄 ソリティアゲームをリセット");
+    
+    // TODO: 実際のリセット処理を実装
+    // - カードの再配布
+    // - スコアのリセット
+    // - タイマーのリセット
+    
+    info!("✅ ゲームリセット完了");
+    true
+}
+
+// 自動配置を試行（WebAssembly機能有効時のみ）
+// 引数：card_info - カード情報（JSON文字列）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：自動配置が成功したかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn try_auto_place(card_info: &str, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "try_auto_place", call_guard::CallKind::Move);
+    debug!("🚀 自動配置試行: {}", card_info);
+    
+    // TODO: 実際の自動配置ロジックを実装
+    // - ファウンデーションへの配置チェック
+    // - タブローへの配置チェック
+    
+    // テスト用：50%の確率で成功
+    let success = random_below(session_id.as_deref(), 2) == 0;
+    
+    if success {
+        debug!("✨ 自動配置成功");
+    } else {
+        debug!("⚠️ 自動配置失敗");
+    }
+    
+    success
+}
+
+// 勝利条件をチェック（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：ゲームが完了したかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn check_victory(_session_id: Option<String>) -> bool {
+    debug!("🏆 勝利条件チェック");
+    
+    // TODO: 実際の勝利条件チェックを実装
+    // - 全てのカードがファウンデーションに配置されているかチェック
+    
+    false // 現在は常にfalse
+}
+
+// ヒントを取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：ヒント情報をJSON文字列で返す
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_hint(session_id: Option<String>) -> String {
+    debug!("💡 ヒント取得");
+    
+    // ヒント使用回数を記録し（ゲーム結果レポートに反映される）、動かすカードを強調表示する
+    let hint = with_runtime(session_id.as_deref(), |rt| rt.show_hint()).flatten();
+    
+    let hint = match hint {
+        Some(hint) => serde_json::to_value(&hint).unwrap_or_default(),
+        None => serde_json::json!({
+            "type": "none",
+            "message": "打てる手がありません"
+        }),
+    };
+    
+    debug!("💡 ヒント生成: {}", hint);
+    hint.to_string()
+}
+
+// 操作がないときに次の一手を知らせるまでの時間を変更する（WebAssembly機能有効時のみ）
+// 知らせはイベントコールバックにhint_nudgeとして届き、動かすカードはpulsingで点滅表示になる
+// 引数：seconds - 知らせるまでの時間（秒、0の場合は知らせない、標準は30秒）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：変更できたかどうかを示すブール値（未初期化の場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_hint_nudge_delay(seconds: u32, session_id: Option<String>) -> bool {
+    with_runtime(session_id.as_deref(), |rt| rt.set_idle_hint_seconds(seconds)).is_some()
+}
+
+// 現在の局面にまだ勝ち筋があるかを確認する（WebAssembly機能有効時のみ）
+// 初めて勝てないと分かった場合は「やり直しますか？」の通知がイベントコールバックに届く
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：確認結果のJSON文字列
+//         （例：{"winnability": "unwinnable", "lost_at_move": 12, "moves_since_lost": 3}、
+//          調べる局面数の上限に達した場合のwinnabilityは"unknown"、ゲームがない場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn check_winnability(session_id: Option<String>) -> String {
+    debug!("🧮 勝ち筋の確認");
+    
+    with_runtime(session_id.as_deref(), |rt| rt.check_winnability())
+        .flatten()
+        .and_then(|report| serde_json::to_string(&report).ok())
+        .unwrap_or_default()
+}
+
+// 対局後の振り返りを取得する（WebAssembly機能有効時のみ）
+// 移動履歴の各手をソルバーの選ぶ手と比べ、best（最善）・good（良い）・inaccuracy（疑問手）・
+// blunder（悪手）に分類する（手数が多いと時間がかかるため、ゲーム終了後に1回だけ呼ぶ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：振り返りのJSON文字列（例：{"moves": [{"move_number": 1, "quality": "best", ...}], "best": 10, ...}、
+//         ゲームがない・パズルなどシードから再現できない盤面の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn analyze_game(session_id: Option<String>) -> String {
+    debug!("🔍 対局後の振り返り");
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.analyze_game()) {
+        Some(Ok(analysis)) => serde_json::to_string(&analysis).unwrap_or_default(),
+        Some(Err(e)) => {
+            warn!("⚠️ 振り返りできません: {}", e);
+            String::new()
+        }
+        None => String::new(),
+    }
+}
+
+// 盤面のレイアウトを取得する（WebAssembly機能有効時のみ）
+// canvasなどで描画するときに、山の枠・ドロップ先・空の山の置き場所をRust側と同じ座標で描くために使う
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：レイアウトのJSON文字列（例：{"card_width": 80.0, "card_height": 120.0,
+//         "stacks": [{"id": "tableau-0", "anchor_x": 20.0, "anchor_y": 150.0, "spacing": 25.0, "card_count": 1, "cards": [...]}, ...]}、
+//         ゲームがない・クロンダイク以外の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_layout(session_id: Option<String>) -> String {
+    match with_runtime(session_id.as_deref(), |rt| rt.layout()) {
+        Some(Ok(layout)) => serde_json::to_string(&layout).unwrap_or_default(),
+        Some(Err(e)) => {
+            warn!("⚠️ レイアウトを取得できません: {}", e);
+            String::new()
+        }
+        None => String::new(),
+    }
+}
+
+// 勝ち筋がなくなったかの確認の設定を変更する（WebAssembly機能有効時のみ）
+// 引数：settings_json - 設定（例：{"check_every_moves": 1, "search_limit": 5000, "notify": true}、
+//                       省略した項目は標準の値、check_every_movesが0の場合は自動では確認しない）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：設定できたかどうかを示すブール値（形式が不正・未初期化の場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_unwinnable_check(settings_json: &str, session_id: Option<String>) -> bool {
+    let settings = match serde_json::from_str::<game::UnwinnableCheckSettings>(settings_json) {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("⚠️ 勝ち筋の確認の設定の形式が不正: {}", e);
+            return false;
+        }
+    };
+    
+    with_runtime(session_id.as_deref(), |rt| rt.set_unwinnable_check(settings)).is_some()
+}
+
+// マウス・タッチ操作を入力キューに追加する（WebAssembly機能有効時のみ）
+// すぐには処理せず、次のupdate_game()で受け付けた順に処理する
+// （押す：カードを選択してドラッグ開始 / 動かす：カードを追従 / 離す：ドロップ）
+// 引数：event_json - ポインターイベント（例：{"kind": "down", "x": 120.0, "y": 180.0, "pointer_id": 1}、
+//       kindは"down" / "move" / "up" / "cancel"、座標は画面の座標系（set_viewport()の変換で盤面の座標に直す）、
+//       pointer_idは複数の指を見分けるID（省略時は0、2本の指で触れるとピンチ操作で拡大・移動する））
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：イベントを受け付けたかどうかを示すブール値（形式が不正・座標が有限でない場合はfalse、
+//         大きすぎる座標は受け付ける範囲に収める）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn push_pointer_event(event_json: &str, session_id: Option<String>) -> bool {
+    let pointer: input::PointerEvent = match serde_json::from_str(event_json) {
+        Ok(pointer) => pointer,
+        Err(e) => {
+            warn!("⚠️ ポインターイベントの形式が不正です: {}", e);
+            return false;
+        }
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.push_pointer(pointer)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 盤面の拡大率と表示位置を変更する（WebAssembly機能有効時のみ）
+// ピンチ操作でも変わり、現在の値はget_solitaire_state()のviewportで取得できる
+// 引数：scale - 拡大率（0.5〜3.0、1.0で等倍）
+//       x, y - 盤面の原点を表示する画面の座標
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：変更できたかどうかを示すブール値（拡大率が範囲外・未初期化の場合はfalse）
+// 変更後はpush_pointer_event()の座標を画面の座標として受け取り、盤面の座標に直して処理する
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_viewport(scale: f32, x: f32, y: f32, session_id: Option<String>) -> bool {
+    let viewport = match viewport::Viewport::new(scale, x, y) {
+        Ok(viewport) => viewport,
+        Err(e) => {
+            warn!("⚠️ 表示領域の変更失敗: {}", e);
+            return false;
+        }
+    };
+    
+    with_runtime(session_id.as_deref(), |rt| rt.set_viewport(viewport)).is_some()
+}
+
+// サーバーから届いたリアクションを受け付ける（WebAssembly機能有効時のみ）
+// 次のupdate_game()で送信者のカーソルの横に表示するリアクションとして追加される
+// 引数：message_json - サーバーから届いたメッセージ（例：{"type": "Reaction", "player_id": "p2", "emote": "party"}）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：リアクションを受け付けたかどうかを示すブール値（形式が不正な場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn push_reaction(message_json: &str, session_id: Option<String>) -> bool {
+    match protocol::WebSocketMessage::parse(message_json) {
+        Ok(protocol::WebSocketMessage::Reaction { .. }) => {}
+        Ok(_) => {
+            warn!("⚠️ リアクション以外のメッセージです");
+            return false;
+        }
+        Err(e) => {
+            warn!("⚠️ リアクションの形式が不正です: {}", e);
+            return false;
+        }
+    }
+    
+    with_runtime(session_id.as_deref(), |rt| {
+        network::NetworkManager::send_message(
+            &mut rt.world,
+            network::MessageType::Reaction,
+            message_json.to_string(),
+            None,
+            None,
+        )
+    })
+    .unwrap_or(false)
+}
+
+// 表示中のリアクションを取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：各リアクションの送信者ID・絵文字の種類・残り秒数をJSON配列の文字列で返す（送信者ID順）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_reactions(session_id: Option<String>) -> String {
+    let reactions = with_runtime(session_id.as_deref(), |rt| reaction::active(&rt.world))
+        .unwrap_or_default();
+    serde_json::to_string(&reactions).unwrap_or_default()
+}
+
+// 同じルームの他のプレイヤーの一覧を取得する（スコアボードの表示用、WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：各プレイヤーのID・表示名・色・スコア・ファウンデーションのカードの枚数・接続状態を
+//         JSON配列の文字列で返す（スコアの高い順、ルームに参加していない場合は空の配列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_players(session_id: Option<String>) -> String {
+    let players = with_runtime(session_id.as_deref(), |rt| scoreboard::players(&rt.world))
+        .unwrap_or_default();
+    serde_json::to_string(&players).unwrap_or_default()
+}
+
+// カードを選択する（WebAssembly機能有効時のみ）
+// 選択できるのは1枚だけで、他のカードの選択は外れる
+// 次のフレームから、置ける山がget_solitaire_state()のdrop_targetsに入る
+// 引数：card_id - 選択するカードのID（get_solitaire_state()のカードのid）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：選択できたかどうかを示すブール値（裏向き・組札のカードは選択できない）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn select_card(card_id: u32, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "select_card", call_guard::CallKind::Move);
+    debug!("👆 カード選択: {}", card_id);
+    with_runtime(session_id.as_deref(), |rt| rt.select_card(card_id)).unwrap_or(false)
+}
+
+// カードの選択を解除する（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn clear_selection(session_id: Option<String>) {
+    with_runtime(session_id.as_deref(), |rt| rt.clear_selection());
+}
+
+// 全ての合法手を評価順に取得（チュートリアルモード用、WebAssembly機能有効時のみ）
+// 効果の薄い手も含め、各手の評価値（priority）と理由（reason）を付けて返す
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：get_hint()と同じ形式のヒントのJSON配列文字列（評価の高い順）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_ranked_moves(session_id: Option<String>) -> String {
+    debug!("🎓 全合法手の評価取得");
+    
+    // 一覧を見るのもヒントの使用として記録する
+    let moves = with_runtime(session_id.as_deref(), |rt| {
+        rt.record_hint_used();
+        hint::HintEngine::ranked_moves(&rt.world)
+    })
+    .unwrap_or_default();
+    
+    serde_json::to_string(&moves).unwrap_or_default()
+}
+
+// ヒントエンジンの最善手を1手打つ「おまかせ」機能（WebAssembly機能有効時のみ）
+// 通常の操作と同じくスコアに反映され、カードは移動先へアニメーションする
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：打った手をget_hint()と同じ形式のJSON文字列で返す（打てる手がない場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn auto_play_one_move(session_id: Option<String>) -> String {
+    guard_call(session_id.as_deref(), "auto_play_one_move", call_guard::CallKind::Move);
+    debug!("🤖 自動プレイ（1手）");
+    
+    let played = with_runtime(session_id.as_deref(), |rt| rt.auto_play_one_move()).flatten();
+    
+    played
+        .and_then(|hint| serde_json::to_string(&hint).ok())
+        .unwrap_or_default()
+}
+
+// 手詰まりになるまで自動プレイを続ける（WebAssembly機能有効時のみ）
+// デモ表示や、行き詰まったプレイヤーがエンジンの続きを眺める用途を想定
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：打った手の数
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn auto_play_until_stuck(session_id: Option<String>) -> u32 {
+    guard_call(session_id.as_deref(), "auto_play_until_stuck", call_guard::CallKind::Move);
+    debug!("🤖 自動プレイ（手詰まりまで）");
+    
+    let moves_played =
+        with_runtime(session_id.as_deref(), |rt| rt.auto_play_until_stuck()).unwrap_or(0);
+    
+    info!("🤖 自動プレイ完了: {}手", moves_played);
+    moves_played
+}
+
+// ログレベルを変更（WebAssembly機能有効時のみ）
+// 引数：spec - レベル指定（例: "debug"、"info,network=debug,solitaire=warn"）
+// 戻り値：指定が正しく適用されたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_log_level(spec: &str) -> bool {
+    match logging::set_log_level(spec) {
+        Ok(()) => {
+            info!("🔧 ログレベルを変更: {}", spec);
+            true
+        }
+        Err(e) => {
+            warn!("⚠️ ログレベルの変更失敗: {}", e);
+            false
+        }
+    }
+}
+
+// デバッグモードを切り替える（WebAssembly機能有効時のみ）
+// 有効にするとシステムの実行時間計測や直近イベントの収集が行われる
+// 引数：enabled - 有効にする場合true
+//       session_id - セッションID（省略時は既定のセッション）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_debug_mode(enabled: bool, session_id: Option<String>) {
+    info!("🐛 デバッグモード: {}", if enabled { "有効" } else { "無効" });
+    
+    with_runtime(session_id.as_deref(), |rt| rt.set_debug_mode(enabled));
+}
+
+// 見ているもの（自分のゲーム・リプレイ・観戦）を切り替える（WebAssembly機能有効時のみ）
+// リプレイ・観戦中は、入力・選択・移動とスコア・勝敗・実績の判定のシステムを実行しない
+// 引数：mode - "play" / "replay" / "spectate"
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：切り替えられたかどうかを示すブール値（不明なモードの場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_view_mode(mode: &str, session_id: Option<String>) -> bool {
+    match schedule::ViewMode::parse(mode) {
+        Ok(view_mode) => with_runtime(session_id.as_deref(), |rt| rt.set_view_mode(view_mode)).is_some(),
+        Err(e) => {
+            warn!("⚠️ 表示モードの切り替え失敗: {}", e);
+            false
+        }
+    }
+}
+
+// 名前を指定してシステムを有効/無効にする（WebAssembly機能有効時のみ）
+// 再ビルドせずにアニメーションの停止・通信の停止などのデバッグや、機能の切り替えを行うために使う
+// 引数：name - システム名（例："CardAnimationSystem"、"MessageProcessingSystem"、
+//              配る・続けて打った手のアニメーションは"AnimationQueueSystem"）
+//       enabled - 実行する場合true
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：切り替えられたかどうかを示すブール値（その名前のシステムがない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn toggle_system(name: &str, enabled: bool, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.set_system_enabled(name, enabled)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ システムの切り替え失敗: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// アニメーション設定を変更する（WebAssembly機能有効時のみ）
+// 引数：settings_json - アニメーション設定（例：{"speed_multiplier": 2.0, "instant": false, "reduced_motion": true}、
+//                       省略した項目は標準の値）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：設定できたかどうかを示すブール値（形式が不正・速さの倍率が0.25〜4.0の範囲外の場合はfalse）
+// reduced_motionは盤面の合わせ直し・配る・勝利などの演出を省略してカードの移動を短くし、
+// instantはカードの移動も含めてすべて省略する
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_animation_settings(settings_json: &str, session_id: Option<String>) -> bool {
+    let animation = match serde_json::from_str::<game::AnimationSettings>(settings_json) {
+        Ok(animation) => animation,
+        Err(e) => {
+            warn!("⚠️ アニメーション設定の形式が不正: {}", e);
+            return false;
+        }
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.set_animation_settings(animation)) {
+        Some(Ok(())) => {
+            info!("🎞️ アニメーション設定を変更: {:?}", animation);
+            true
+        }
+        Some(Err(e)) => {
+            warn!("⚠️ アニメーション設定の変更失敗: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 順番待ち・進行中のアニメーションをすべて飛ばす（WebAssembly機能有効時のみ）
+// 続けて打った手のアニメーションは1手ずつ順番に再生されるため、タブに戻ったときなど
+// 溜まった手の再生を待たずに今の盤面を表示したい場合に呼ぶ（再接続したときは自動で飛ばす）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：飛ばした順番待ちの手の数
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn skip_animations(session_id: Option<String>) -> u32 {
+    let skipped = with_runtime(session_id.as_deref(), |rt| rt.skip_animations()).unwrap_or(0);
+    
+    debug!("⏩ アニメーションを飛ばしました: {}手", skipped);
+    skipped as u32
+}
+
+// カードの裏面とテーブルのテーマを変更する（WebAssembly機能有効時のみ）
+// 引数：theme_json - テーマ（例：{"card_back": "ocean", "table": "midnight", "share_with_room": true}、
+//                    省略した項目は標準の値）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：変更できたかどうかを示すブール値（形式が不正・未初期化の場合はfalse）
+// 変更したテーマは端末内に保存され、get_solitaire_state()のthemeに反映される
+// share_with_roomがtrueの場合、カードの裏面はルームの他のプレイヤーのスコアボードにも表示される
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_theme(theme_json: &str, session_id: Option<String>) -> bool {
+    let theme = match serde_json::from_str::<theme::Theme>(theme_json) {
+        Ok(theme) => theme,
+        Err(e) => {
+            warn!("⚠️ テーマの形式が不正: {}", e);
+            return false;
+        }
+    };
+    
+    info!("🎨 テーマを変更: {:?}", theme);
+    with_runtime(session_id.as_deref(), |rt| rt.set_theme(theme)).is_some()
+}
+
+// 現在の設定を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：引く枚数・テーマ・効果音・アニメーションの速さ・言語・プレイヤー名をJSON文字列で返す
+//         （セッションごとの上書きを適用した値、未初期化の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_preferences(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| serde_json::to_string(&rt.preferences()).ok())
+        .flatten()
+        .unwrap_or_default()
+}
+
+// 設定を変更する（WebAssembly機能有効時のみ）
+// 引数：preferences_json - 設定（例：{"draw_mode": "three", "sound_enabled": false, "locale": "en", "format_numbers": true}、
+//                          省略した項目は標準の値）
+// 戻り値：変更できたかどうかを示すブール値（形式が不正・範囲外の場合はfalse）
+// 変更した設定は端末内に保存され、すべてのセッションに適用される
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_preferences(preferences_json: &str) -> bool {
+    let preferences = match serde_json::from_str::<settings::Preferences>(preferences_json) {
+        Ok(preferences) => preferences,
+        Err(e) => {
+            warn!("⚠️ 設定の形式が不正: {}", e);
+            return false;
+        }
+    };
+    if let Err(e) = preferences.validate() {
+        warn!("⚠️ 設定の変更失敗: {}", e);
+        return false;
+    }
+    
+    if let Err(e) = preferences.save() {
+        warn!("⚠️ 設定の保存失敗: {}", e);
+    }
+    SESSIONS.with(|sessions| {
+        for (_, rt) in sessions.borrow_mut().sessions_mut() {
+            rt.apply_preferences(preferences.clone());
+        }
+    });
+    info!("⚙️ 設定を変更: {:?}", preferences);
+    true
+}
+
+// このセッションだけの設定の上書きを変更する（WebAssembly機能有効時のみ）
+// 引数：overrides_json - 上書きする項目だけを指定した設定（例：{"animation_speed": 2.0}、
+//                        {}で上書きをすべて取り消す）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：変更できたかどうかを示すブール値（形式が不正・範囲外・未初期化の場合はfalse）
+// 上書きは保存されず、セッションを作り直すと取り消される
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_preference_overrides(overrides_json: &str, session_id: Option<String>) -> bool {
+    let overrides = match serde_json::from_str::<settings::PreferenceOverrides>(overrides_json) {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            warn!("⚠️ 設定の上書きの形式が不正: {}", e);
+            return false;
+        }
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.set_preference_overrides(overrides)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 設定の上書きの変更失敗: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// デバッグ用オーバーレイの情報を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：エンティティ数・システム実行時間・キューの長さなどをJSON文字列で返す（未初期化の場合は空文字列）
+// システム実行時間・直近イベント・メモリ情報はデバッグモード時のみ含まれる
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_debug_info(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| serde_json::to_string(&rt.debug_info()).ok())
+        .flatten()
+        .unwrap_or_default()
+}
+
+// タイムラインの記録を取得（WebAssembly機能有効時のみ）
+// デバッグ用オーバーレイ・サーバーとの食い違いの調査で、イベント・移動・操作を起きた順に読むために使う
+// 引数：since_tick - このティック以降（このティックを含む）の記録を返す（0ですべて、記録は最新の1000件まで）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：記録のJSON配列文字列（例：[{"tick": 12, "kind": "move", "record": {...}},
+//         {"tick": 12, "kind": "event", "event": {"type": "score_changed", ...}}]、未初期化の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_timeline(since_tick: f64, session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| serde_json::to_string(&rt.timeline(since_tick.max(0.0) as u64)).ok())
+        .flatten()
+        .unwrap_or_default()
+}
+
+// 保存されているクラッシュレポートを取得（WebAssembly機能有効時のみ）
+// 不具合の報告に添付し、盤面はScenario::from_compact()で復元できる
+// 戻り値：パニックのメッセージ・位置・時刻と、セッションごとの直前の状態
+//         （直近のイベント・移動の記録・盤面の短い表記）をJSON文字列で返す（保存されていない場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_crash_report() -> String {
+    crash_report::load()
+        .and_then(|report| serde_json::to_string(&report).ok())
+        .unwrap_or_default()
+}
+
+// 保存されているクラッシュレポートを消す（WebAssembly機能有効時のみ）
+// 報告を送った後に呼び出す
+// 戻り値：消去できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn clear_crash_report() -> bool {
+    match crash_report::clear() {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("⚠️ クラッシュレポートを消せません: {}", e);
+            false
+        }
+    }
+}
+
+// メモリ使用状況を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：線形メモリのサイズ、エンティティ・コンポーネント数、使用量の多い格納庫をJSON文字列で返す（未初期化の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_memory_stats(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| serde_json::to_string(&rt.memory_stats()).ok())
+        .flatten()
+        .unwrap_or_default()
+}
+
+// ワールドの中身を一覧にする（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：全エンティティのIDと持っているコンポーネントの型名をJSON文字列で返す（未初期化の場合は空文字列）
+// クライアント間の状態のずれの調査に使う
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn dump_world(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| serde_json::to_string(&rt.world.debug_dump()).ok())
+        .flatten()
+        .unwrap_or_default()
+}
+
+// 受信メッセージに遅延・揺らぎ・順序の入れ替わり・欠落を加える（netsim機能有効時のみ、デバッグ用）
+// 引数：settings_json - 再現する通信状態（例：{"latency_ms": 200, "jitter_ms": 50, "reorder_rate": 0.1, "drop_rate": 0.05, "seed": 1}、
+//                       省略した項目は0）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：設定できたかどうかを示すブール値（形式・値が不正な場合はfalse）
+#[cfg(feature = "netsim")]
+#[wasm_bindgen]
+pub fn set_network_conditions(settings_json: &str, session_id: Option<String>) -> bool {
+    let conditioner = serde_json::from_str::<network_conditioner::ConditionerSettings>(settings_json)
+        .map_err(|e| format!("通信状態の設定の形式が不正です: {}", e))
+        .and_then(network_conditioner::NetworkConditioner::<network::NetworkMessage>::new);
+    let conditioner = match conditioner {
+        Ok(conditioner) => conditioner,
+        Err(e) => {
+            warn!("⚠️ {}", e);
+            return false;
+        }
+    };
+
+    info!("🐢 通信状態の再現を開始: {:?}", conditioner.settings());
+    with_runtime(session_id.as_deref(), |rt| rt.world.insert_resource(conditioner)).is_some()
+}
+
+// 通信状態の再現をやめる（netsim機能有効時のみ、デバッグ用）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 配達待ちのメッセージは捨てられる
+#[cfg(feature = "netsim")]
+#[wasm_bindgen]
+pub fn clear_network_conditions(session_id: Option<String>) {
+    with_runtime(session_id.as_deref(), |rt| {
+        rt.world.remove_resource::<network_conditioner::NetworkConditioner<network::NetworkMessage>>()
+    });
+    info!("🐇 通信状態の再現を終了");
+}
+
+// ゲーム結果レポートを取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：ゲーム結果をJSON文字列で返す（ゲームが終了していない場合は空文字列、
+//         設定のformat_numbersが有効な場合は整形した最終スコア・プレイ時間をformattedに含める）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_game_result(session_id: Option<String>) -> String {
+    debug!("📋 ゲーム結果取得");
+    
+    with_runtime(session_id.as_deref(), |rt| {
+        rt.game_result()
+            .map(|report| result::GameResult {
+                formatted: i18n::formatted_stats(&rt.world, report.score.final_score, report.duration_seconds),
+                ..report.clone()
+            })
+            .and_then(|report| serde_json::to_string(&report).ok())
+    })
+    .flatten()
+    .unwrap_or_default()
+}
+
+// イベントコールバックを登録（WebAssembly機能有効時のみ）
+// 引数：callback - イベント発生時に呼ばれる関数（引数はイベントのJSON文字列）
+// 実績解除などのイベントはupdate_game()の最後にまとめて配信される
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn set_event_callback(callback: js_sys::Function) {
+    info!("📡 イベントコールバックを登録");
+    
+    EVENT_CALLBACK.with(|cell| {
+        *cell.borrow_mut() = Some(callback);
+    });
+}
+
+// 実績と通算成績を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：全実績の解除状況と通算成績をJSON文字列で返す（未初期化の場合は空文字列）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_achievements(session_id: Option<String>) -> String {
+    debug!("🏅 実績取得");
+    
+    with_runtime(session_id.as_deref(), |rt| {
+        rt.achievements()
+            .map(|store| store.to_summary_json().to_string())
+    })
+    .flatten()
+    .unwrap_or_default()
+}
+
+// サーバーが記録している同期アカウントの実績と通算成績の書き出しを要求（WebAssembly機能有効時のみ）
+// 署名付きの書き出しはStatsSignedメッセージの購読で受け取り、別の端末のimport_statistics()で読み込む
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：要求できたかどうかを示すブール値（network_join()の前はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn export_statistics(session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.sign_stats()) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 成績の書き出しを要求できません: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 別の端末から持ってきた書き出しの署名をサーバーに確かめてもらう（WebAssembly機能有効時のみ）
+// 結果はStatsVerifiedメッセージの購読で受け取り、正しい場合だけimport_statistics()で読み込める
+// 引数：export_json - StatsSignedで届いた署名付きの書き出しのJSON文字列
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：要求できたかどうかを示すブール値（network_join()の前はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn verify_statistics(export_json: &str, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.network.verify_stats(export_json)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 成績の署名の確認を要求できません: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 別の端末で書き出した実績と通算成績を読み込んでまとめる（WebAssembly機能有効時のみ）
+// ゲーム数・勝利数は同じ出どころから前に読み込んだ分を除いて足し合わせ、
+// 最短クリア時間・最高スコア・最長連勝は良い方を残す
+// 署名のない書き出しや、verify_statistics()で正しいと確かめていない書き出しは読み込まない
+// 引数：export_json - StatsSignedで届いた署名付きの書き出しのJSON文字列
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：まとめられたかどうかを示すブール値（形式不正・署名を確かめていない・読み込み済み・未初期化の場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn import_statistics(export_json: &str, session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.import_statistics(export_json)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 成績を読み込めません: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// 組み込みのパズル一覧を取得（WebAssembly機能有効時のみ）
+// 戻り値：各パズルのID・名前・目標をJSON配列の文字列で返す
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn list_puzzles() -> String {
+    let summaries: Vec<puzzle::PuzzleSummary> = puzzle::builtin_puzzles()
+        .iter()
+        .map(puzzle::Puzzle::summary)
+        .collect();
+    serde_json::to_string(&summaries).unwrap_or_default()
+}
+
+// 進行中のゲームを端末内に保存（WebAssembly機能有効時のみ）
+// 前回の保存データは上書きされる
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：保存できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn save_game(session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "save_game", call_guard::CallKind::Session);
+    match with_runtime(session_id.as_deref(), |rt| rt.save_game()) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ ゲームを保存できません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// 端末内に保存したゲームを続きから始める（WebAssembly機能有効時のみ）
+// 古いバージョンのアプリで保存したデータは現在の形式に変換して読み込む
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：読み込めたかどうかを示すブール値（読み込めない場合は進行中のゲームをそのまま残す）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn load_game(session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "load_game", call_guard::CallKind::ReplaceBoard);
+    match with_runtime(session_id.as_deref(), |rt| rt.load_game()) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 保存したゲームを読み込めません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// リプレイ・進行中のゲームの好きな局面から練習を始める（WebAssembly機能有効時のみ）
+// 元のセッションはそのまま残し、局面を写した練習用のセッションを新しく作成する
+// 練習用のセッションはサーバーに接続せず、元に戻すのは何回でもでき、結果は実績・通算成績に数えない
+// 引数：move_index - 何手目の局面から分岐するか（0は配られた直後、現在の手数は現在の局面）
+//       session_id - 分岐元のセッションID（省略時は既定のセッション）
+// 戻り値：作成した練習用のセッションID（例："default-practice-1"）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn fork_practice_game(move_index: u32, session_id: Option<String>) -> Result<String, JsValue> {
+    let source_id =
+        session::SessionRegistry::<runtime::GameRuntime>::resolve_id(session_id.as_deref())
+            .to_string();
+    let sandbox = with_runtime(Some(&source_id), |rt| rt.fork_practice(move_index as usize))
+        .ok_or_else(|| JsValue::from_str("セッションが作成されていません"))?
+        .map_err(|e| JsValue::from_str(&e))?;
+    
+    SESSIONS.with(|sessions| {
+        let mut sessions = sessions.borrow_mut();
+        let practice_id = (1..)
+            .map(|number| format!("{}-practice-{}", source_id, number))
+            .find(|id| sessions.status(id).is_none())
+            .unwrap_or_default();
+        sessions.insert(&practice_id, sandbox);
+        info!("🧪 練習用のセッションを作成しました: {}", practice_id);
+        Ok(practice_id)
+    })
+}
+
+// 練習のゲームの最後の手を元に戻す（WebAssembly機能有効時のみ）
+// 練習中は何回でも元に戻せる
+// 引数：session_id - 練習用のセッションID
+// 戻り値：元に戻せたかどうかを示すブール値（練習中でない・戻す手がない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn undo_practice_move(session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.undo_practice_move()) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 元に戻せません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// サーバーから届いた正しい盤面に合わせ直す（WebAssembly機能有効時のみ）
+// 手元と位置の違うカードは、瞬間移動させずに約0.2秒で正しい位置へ動かす（state_reconciledイベントで知らせる）
+// 引数：snapshot_json - 正しい盤面（保存データと同じ形式のJSON文字列）, session_id - セッションID（省略時は既定のセッション）
+// 戻り値：合わせ直せたかどうかを示すブール値（盤面が不正な場合は進行中のゲームをそのまま残す）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn reconcile_state(snapshot_json: &str, session_id: Option<String>) -> bool {
+    let snapshot = match save_game::SavedGame::from_json(snapshot_json) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!("⚠️ サーバーの盤面を読み込めません: {}", e);
+            return false;
+        }
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.reconcile(&snapshot)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ サーバーの盤面に合わせ直せません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// 続きから遊べる保存データがあるか確認（WebAssembly機能有効時のみ）
+// 戻り値：読み込める保存データがあるかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn has_saved_game() -> bool {
+    save_game::load().is_some()
+}
+
+// 中断したゲームの一覧を取得（WebAssembly機能有効時のみ）
+// 1人用の途中のゲームを残したまま別のゲームを始めると、そのゲームが一覧に加わる
+// 戻り値：各ゲームのID・種類・進み具合・経過時間・盤面の縮小表示の状態をJSON配列の文字列で返す（新しい順）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn list_saved_games() -> String {
+    serde_json::to_string(&save_game::list()).unwrap_or_default()
+}
+
+// 中断したゲームの一覧にあるゲームを続きから始める（WebAssembly機能有効時のみ）
+// 再開したゲームは一覧から外れ、進行中の途中のゲームが代わりに一覧に加わる
+// 引数：id - list_saved_games()で得たID
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：再開できたかどうかを示すブール値（再開できない場合は進行中のゲームをそのまま残す）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn resume_game(id: &str, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "resume_game", call_guard::CallKind::ReplaceBoard);
+    match with_runtime(session_id.as_deref(), |rt| rt.resume_saved_game(id)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ 中断したゲームを再開できません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// 中断したゲームを一覧から消す（WebAssembly機能有効時のみ）
+// 引数：id - list_saved_games()で得たID
+// 戻り値：消せたかどうかを示すブール値（一覧にない場合はfalse）
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn delete_saved_game(id: &str) -> bool {
+    match save_game::remove_from_list(id) {
+        Ok(removed) => removed,
+        Err(e) => {
+            warn!("⚠️ 中断したゲームを消せません: {}", e);
+            false
+        }
+    }
+}
+
+// パズルを開始（WebAssembly機能有効時のみ）
+// 引数：puzzle_id - list_puzzles()で得たパズルID
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：開始できたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn start_puzzle(puzzle_id: &str, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "start_puzzle", call_guard::CallKind::ReplaceBoard);
+    let Some(puzzle) = puzzle::find_puzzle(puzzle_id) else {
+        warn!("⚠️ パズルが見つかりません: {}", puzzle_id);
+        return false;
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.start_puzzle(&puzzle)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            error!("❌ パズルを開始できません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// パズルの進み具合を取得（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：目標・使った手数・状態をJSON文字列で返す（パズルに挑戦中でない場合は空文字列）
+// 達成・失敗はイベント（puzzle_solved / puzzle_failed）でも通知される
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn get_puzzle_progress(session_id: Option<String>) -> String {
+    with_runtime(session_id.as_deref(), |rt| {
+        rt.puzzle_progress()
+            .and_then(|progress| serde_json::to_string(progress).ok())
+    })
+    .flatten()
+    .unwrap_or_default()
+}
+
+// 組み込みのチュートリアル一覧を取得（WebAssembly機能有効時のみ）
+// 戻り値：各チュートリアルのID・名前・手順数をJSON配列の文字列で返す
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn list_tutorials() -> String {
+    let summaries: Vec<tutorial::TutorialSummary> = tutorial::builtin_tutorials()
+        .iter()
+        .map(tutorial::Tutorial::summary)
+        .collect();
+    serde_json::to_string(&summaries).unwrap_or_default()
+}
+
+// チュートリアルを開始（WebAssembly機能有効時のみ）
+// 引数：tutorial_id - list_tutorials()で得たチュートリアルID
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：開始できたかどうかを示すブール値
+// 現在の手順の案内文と強調表示する対象はget_solitaire_state()のtutorialに入る
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn start_tutorial(tutorial_id: &str, session_id: Option<String>) -> bool {
+    guard_call(session_id.as_deref(), "start_tutorial", call_guard::CallKind::ReplaceBoard);
+    let Some(tutorial) = tutorial::find_tutorial(tutorial_id) else {
+        warn!("⚠️ チュートリアルが見つかりません: {}", tutorial_id);
+        return false;
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.start_tutorial(&tutorial)) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            error!("❌ チュートリアルを開始できません: {}", e);
+            false
+        }
+        None => {
+            warn!("⚠️ ゲームが初期化されていません。initialize_game()を先に呼び出してください");
+            false
+        }
+    }
+}
+
+// 進行中のチュートリアルを最初からやり直す（WebAssembly機能有効時のみ）
+// 引数：session_id - セッションID（省略時は既定のセッション）
+// 戻り値：やり直せたかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn restart_tutorial(session_id: Option<String>) -> bool {
+    match with_runtime(session_id.as_deref(), |rt| rt.restart_tutorial()) {
+        Some(Ok(_)) => true,
+        Some(Err(e)) => {
+            warn!("⚠️ チュートリアルをやり直せません: {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// チュートリアルの手を打つ（WebAssembly機能有効時のみ）
+// 引数：action_json - 打つ手（例：{"type": "move", "from": {"type": "tableau", "position": 0},
+//       "to": {"type": "foundation", "position": 0}, "count": 1} / {"type": "draw"}）
+//       session_id - セッションID（省略時は既定のセッション）
+// 戻り値：現在の手順で決められた手と一致し、次の手順へ進んだかどうかを示すブール値
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+pub fn tutorial_action(action_json: &str, session_id: Option<String>) -> bool {
+    let action = match serde_json::from_str::<tutorial::TutorialAction>(action_json) {
+        Ok(action) => action,
+        Err(e) => {
+            error!("❌ チュートリアルの手の形式が不正です: {}", e);
+            return false;
+        }
+    };
+    
+    match with_runtime(session_id.as_deref(), |rt| rt.tutorial_action(&action)) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            info!("🎓 {}", e);
+            false
+        }
+        None => false,
+    }
+}
+
+// =============================================================================
+// WebAssemblyメモリの最適化
+// =============================================================================
+
+// 軽量なアロケータを使用（WebAssembly向け最適化）
+#[cfg(all(feature = "wasm", feature = "wee_alloc"))]
+#[global_allocator]
+static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+
+// =============================================================================
+// モジュール宣言：他のファイルで定義される機能
+// =============================================================================
+
+// ECS関連のモジュール
+pub mod ecs;   // ECSコンポーネント実装完了により有効化（ベンチマークから使うため公開）
+pub mod clock; // 単調増加する時計を基準にしたゲーム時計
+pub mod game;  // ゲーム状態管理システム実装完了により有効化（サーバーのルームのシミュレーションと共有するため公開）
+pub mod network; // WebSocket通信レイヤ実装完了により有効化（ファジングから使うため公開）
+pub mod network_client; // 接続・ルームへの参加・アクションの送信・購読をまとめたネットワーククライアント
+pub mod solitaire; // ソリティアゲームロジック実装完了により有効化（ベンチマークから使うため公開）
+pub mod result; // ゲーム結果レポート（サーバーで同期アカウントの成績を記録するため公開）
+pub mod runtime; // ECSワールドとシステムをまとめたゲームランタイム（デスクトップ版から使うため公開）
+pub mod events; // JavaScriptへ通知するゲームイベント（テストから使うため公開）
+pub mod storage; // 端末内へのデータ保存（localStorage / ファイル）（ブラウザテストから使うため公開）
+pub mod achievements; // 実績・連勝記録（成績の書き出し・読み込みのテストから使うため公開）
+pub mod client_state; // フロントエンドへ返すゲーム状態の型とJSON Schema
+pub mod logging;  // ログの出力先とモジュールごとのレベル管理（サーバーと共有するため公開）
+mod debug_info;   // デバッグ用オーバーレイ向けの情報収集
+pub mod hint;  // 次の一手を探すヒントエンジン
+pub mod protocol; // 通信メッセージの形式と検証（ファジングから使うため公開）
+pub mod rng;   // シード付きの乱数生成器（WebAssemblyでも動作）
+pub mod scenario; // 任意の途中盤面を組み立てるシナリオ（テスト・パズル・不具合の再現用）
+pub mod puzzle;   // 目標付きの問題を解くパズルモード
+pub mod tutorial; // 手順を1つずつ案内するチュートリアル（定義はdata/tutorials.json）
+pub mod selection; // 選択・強調表示・ドロップ先のマーカーコンポーネント
+pub mod input;    // マウス・タッチ操作の入力イベントキュー
+pub mod notification; // 減点・手詰まりなど進行の通知
+pub mod session;  // 複数のゲームセッションをIDで振り分けるレジストリ
+pub mod reaction; // カーソルの横に数秒間表示するクイックリアクション
+pub mod connection_quality; // 往復時間とパケットロスによる接続品質の判定と送信間隔の調整
+pub mod network_conditioner; // 遅延・欠落などの通信状態の再現（テスト・デバッグ用）
+pub mod time_sync; // Ping/Pongによるサーバーの時計とのずれの推定とタイムスタンプの変換
+pub mod transport; // WebSocketとデータチャネルのどちらでメッセージを送るかの選択
+pub mod reliable; // 受け取りの確認（Ack）を待つ再送と、重複したメッセージの除外
+pub mod sequence; // チャネルごとの連番と、抜け・順序の入れ替わりの検出
+pub mod scoreboard; // 同じルームの他のプレイヤーのスコア・接続状態
+pub mod state_observer; // スコア・手数・経過時間などゲーム状態の変化のイベント
+pub mod theme;    // カードの裏面とテーブルのテーマ
+pub mod viewport; // 盤面の拡大・移動（ズーム・パン）と画面・盤面の座標変換
+pub mod solver;   // 現在の局面にまだ勝ち筋があるかを調べるソルバー
+pub mod analysis; // 対局後に各手をソルバーの選ぶ手と比べる振り返り
+pub mod timeslice; // 重い探索をフレームごとの時間予算の中で少しずつ進める仕組み
+pub mod crash_report; // パニック時に直前のゲームの状態を保存するクラッシュレポート
+pub mod save_game; // 中断したゲームの保存と、古い形式の保存データの変換
+pub mod settings; // 引く枚数・テーマ・効果音・言語などゲームをまたいで引き継ぐ設定
+pub mod stats_transfer; // 実績・通算成績の端末間の引き継ぎと改ざんの検出
+pub mod reconcile; // サーバーの正しい盤面に合わせ直すときの、手元の位置からのアニメーション
+pub mod animation_queue; // 続けて打った手のアニメーションを1手ずつ順番に再生する順番待ち
+pub mod layout;    // 山の位置とカードを置く座標（描画・配布・移動で共有する盤面のレイアウト）
+pub mod schedule;  // メニュー・プレイ中・リプレイ・観戦の場面ごとに実行するシステムのスケジュール
+pub mod timeline;  // イベント・移動・操作をティック付きで記録するタイムライン（デバッグ表示・クラッシュレポート・食い違いの調査用）
+pub mod solve_cache; // シードごとのソルバーの結果（勝ち筋の有無・難しさ・手数）のキャッシュ
+pub mod combo;     // レースでファウンデーションに続けて置くと倍率が上がるコンボ
+pub mod power_up;  // カジュアルなルームで使える覗き見・山札のシャッフル・相手へのタイム加算のパワーアップ
+pub mod practice;  // リプレイ・進行中のゲームの好きな局面から分岐する、結果を記録しない練習モード
+pub mod animation_watchdog; // 移動先に近づかないまま止まったカードのアニメーションを見つけて完了させる見張り
+pub mod call_guard; // ありえない順序で届いたJavaScriptからの呼び出しを見つける厳格モードのチェック
+pub mod transaction; // 複数のカード・スタック・スコア・移動履歴を書き換える1手を、まとめて反映するか何も反映しないトランザクション
+pub mod permissions; // 共有盤面のルームの観戦者・カードの持ち主・山札の戻しの権限（サーバーの検証とクライアントの合法手の判定で共通）
+pub mod send_queue; // 接続が切れている間に送ろうとしたメッセージを溜める上限付きのキューと、溜まり具合・捨てた数の記録
+pub mod frame_timings; // フレームごとのロジック・アニメーション・シリアライズ・通信の処理時間の記録（遅い端末で描画を軽くする判断に使う）
+pub mod afk; // マルチプレイで操作の止まったプレイヤーを離席中にする判定と、操作していない時間を知らせる間隔
+pub mod i18n; // 設定の言語に合わせた経過時間（mm:ss）・スコアの桁区切りの整形（状態の出力に加える整形済みの値）
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tui; // ターミナル版の画面とキー入力（開発用バイナリのplay、描画・キー操作のテストから使うため公開）
+#[cfg(feature = "wasm")]
+mod rtc;       // WebRTCのデータチャネルによるルームのプレイヤーとの直接通信
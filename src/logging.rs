@@ -0,0 +1,215 @@
+// =============================================================================
+// ログ出力
+// =============================================================================
+// このファイルでは、logクレートのマクロ（info!、warn!など）の出力先となる
+// ロガーを実装します。各モジュールは println! ではなく log のマクロでログを出し、
+// 出力先と出力レベルはここで一括して管理します。
+//
+// 出力先：
+// - WebAssembly環境：ブラウザのコンソール（console.log / warn / error）
+// - ネイティブ環境：env_logger（標準エラー出力）
+//
+// レベル指定の書式（RUST_LOGと同じ形式）：
+// - "debug"                     … 全モジュールをdebug以上
+// - "info,network=debug"        … 既定はinfo、networkモジュールだけdebug以上
+// - "warn,solitaire=off"        … solitaireモジュールのログを出さない
+// =============================================================================
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::sync::{Once, RwLock};
+
+/// レベル指定がない場合の既定のレベル
+const DEFAULT_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// 初期化を1回だけ行うためのフラグ
+static INIT: Once = Once::new();
+
+/// 現在のレベル指定
+static FILTER: RwLock<LogFilter> = RwLock::new(LogFilter::new());
+
+/// モジュールごとのレベル指定
+#[derive(Debug, Clone, PartialEq)]
+struct LogFilter {
+    /// 既定のレベル
+    default: LevelFilter,
+
+    /// (モジュール名, レベル)のリスト
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// 既定のレベルだけを持つ指定を作成
+    const fn new() -> Self {
+        Self {
+            default: DEFAULT_LEVEL,
+            modules: Vec::new(),
+        }
+    }
+
+    /// "info,network=debug" 形式の文字列を解析
+    ///
+    /// # 引数
+    /// * `spec` - レベル指定文字列
+    ///
+    /// # 戻り値
+    /// 成功時はLogFilter、不正なレベル名があればエラーメッセージ
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut filter = Self::new();
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    filter
+                        .modules
+                        .push((module.trim().to_string(), parse_level(level)?));
+                }
+                None => filter.default = parse_level(directive)?,
+            }
+        }
+
+        // 長い（より具体的な）モジュール名を先に照合する
+        filter
+            .modules
+            .sort_by_key(|(module, _)| std::cmp::Reverse(module.len()));
+        Ok(filter)
+    }
+
+    /// ログのターゲット（モジュールパス）に適用されるレベルを取得
+    fn level_for(&self, target: &str) -> LevelFilter {
+        // "ecs_wasm_solitaire::network" のようにクレート名が付くので取り除いて比較する
+        let path = target.split_once("::").map_or(target, |(_, rest)| rest);
+
+        self.modules
+            .iter()
+            .find(|(module, _)| matches_module(path, module) || matches_module(target, module))
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// 出力され得る最も詳細なレベル
+    fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// モジュールパスが指定モジュール（またはその子モジュール）かどうか
+fn matches_module(path: &str, module: &str) -> bool {
+    path == module
+        || path
+            .strip_prefix(module)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+/// レベル名を解析
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .trim()
+        .parse()
+        .map_err(|_| format!("不正なログレベルです: {}", level.trim()))
+}
+
+/// モジュールごとのレベル指定を適用してから出力先へ渡すロガー
+struct FilteredLogger {
+    /// 実際の出力先
+    #[cfg(not(feature = "wasm"))]
+    output: env_logger::Logger,
+}
+
+impl Log for FilteredLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        FILTER
+            .read()
+            .map(|filter| metadata.level() <= filter.level_for(metadata.target()))
+            .unwrap_or(true)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        #[cfg(feature = "wasm")]
+        {
+            let message = wasm_bindgen::JsValue::from_str(&format!(
+                "[{}] {}",
+                record.target(),
+                record.args()
+            ));
+            match record.level() {
+                log::Level::Error => web_sys::console::error_1(&message),
+                log::Level::Warn => web_sys::console::warn_1(&message),
+                log::Level::Info => web_sys::console::log_1(&message),
+                log::Level::Debug | log::Level::Trace => web_sys::console::debug_1(&message),
+            }
+        }
+
+        #[cfg(not(feature = "wasm"))]
+        self.output.log(record);
+    }
+
+    fn flush(&self) {
+        #[cfg(not(feature = "wasm"))]
+        self.output.flush();
+    }
+}
+
+/// ロガーを初期化
+///
+/// ネイティブ環境では環境変数RUST_LOGをレベル指定として読み込みます。
+/// 2回目以降の呼び出しは何もしません。
+pub fn init() {
+    INIT.call_once(|| {
+        #[cfg(not(feature = "wasm"))]
+        let logger = {
+            if let Ok(spec) = std::env::var("RUST_LOG") {
+                if let Err(e) = set_log_level(&spec) {
+                    eprintln!("⚠️ RUST_LOGを無視しました: {}", e);
+                }
+            }
+            FilteredLogger {
+                // レベルの判定はFilteredLoggerで行うため、env_logger側では絞り込まない
+                output: env_logger::Builder::new()
+                    .filter_level(LevelFilter::Trace)
+                    .build(),
+            }
+        };
+
+        #[cfg(feature = "wasm")]
+        let logger = FilteredLogger {};
+
+        if log::set_boxed_logger(Box::new(logger)).is_ok() {
+            log::set_max_level(current_max_level());
+        }
+    });
+}
+
+/// ログレベルを変更
+///
+/// # 引数
+/// * `spec` - レベル指定文字列（例: "info,network=debug"）
+///
+/// # 戻り値
+/// 成功時Ok(())、不正な指定の場合はエラーメッセージ
+pub fn set_log_level(spec: &str) -> Result<(), String> {
+    let parsed = LogFilter::parse(spec)?;
+    let max_level = parsed.max_level();
+
+    let mut filter = FILTER
+        .write()
+        .map_err(|_| "ログ設定のロックに失敗しました".to_string())?;
+    *filter = parsed;
+    drop(filter);
+
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+/// 現在の指定で出力され得る最も詳細なレベル
+fn current_max_level() -> LevelFilter {
+    FILTER
+        .read()
+        .map(|filter| filter.max_level())
+        .unwrap_or(DEFAULT_LEVEL)
+}
@@ -11,20 +11,37 @@
 // - ゲーム状態の同期機能
 // - エラーハンドリングと接続品質の監視
 // - 複数プレイヤー間でのメッセージブロードキャスト
+// - メッセージの優先度による通信経路（WebSocket / データチャネル）の選択
 // =============================================================================
 
-use crate::ecs::{World, Entity, Component, System};
+use crate::clock::GameClock;
+use crate::ecs::{World, Entity, Component, ComponentPool, MessageQueue, Resource, System};
+use crate::events::{EventQueue, NotificationSeverity};
+use crate::game::ActionPayload;
+use crate::protocol::{WebSocketMessage, MAX_FIELD_BYTES, MAX_MESSAGE_BYTES};
+use crate::reaction;
+use crate::rng::Rng;
+use crate::transport::{self, Transport, TransportSelector};
+use log::{debug, info, warn};
 use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
 // use std::collections::HashMap; // 未使用のため一時的にコメントアウト
-use std::time::{SystemTime, UNIX_EPOCH};
 
 // WebAssembly機能が有効な場合のみWebSocket関連のインポート
 #[cfg(feature = "wasm")]
 use web_sys::{WebSocket, MessageEvent, CloseEvent, ErrorEvent};
 #[cfg(feature = "wasm")]
+use log::error;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 #[cfg(feature = "wasm")]
 use wasm_bindgen::JsCast;
+#[cfg(feature = "wasm")]
+use std::cell::RefCell;
+#[cfg(feature = "wasm")]
+use std::rc::Rc;
+#[cfg(feature = "wasm")]
+use crate::send_queue::{QueueMetrics, SendQueue};
 
 // =============================================================================
 // ネットワーク関連のコンポーネント定義
@@ -69,18 +86,16 @@ impl NetworkConnection {
     /// # 引数
     /// * `connection_id` - 接続の一意識別子
     /// * `url` - 接続先のWebSocket URL
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 初期化されたNetworkConnectionインスタンス
-    pub fn new(connection_id: String, url: String) -> Self {
+    pub fn new(connection_id: String, url: String, clock: &GameClock) -> Self {
         Self {
             connection_id,
             status: ConnectionStatus::Disconnected,
             url,
-            last_activity: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            last_activity: clock.now_secs(),
             retry_count: 0,
             latency_ms: None,
             sent_messages: 0,
@@ -92,63 +107,55 @@ impl NetworkConnection {
     /// 
     /// # 引数
     /// * `new_status` - 新しい接続状態
-    pub fn update_status(&mut self, new_status: ConnectionStatus) {
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    pub fn update_status(&mut self, new_status: ConnectionStatus, clock: &GameClock) {
         self.status = new_status;
-        self.last_activity = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.last_activity = clock.now_secs();
     }
     
     /// メッセージ送信カウンターを増加
-    pub fn increment_sent(&mut self) {
+    /// 
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    pub fn increment_sent(&mut self, clock: &GameClock) {
         self.sent_messages += 1;
-        self.last_activity = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.last_activity = clock.now_secs();
     }
     
     /// メッセージ受信カウンターを増加
-    pub fn increment_received(&mut self) {
+    /// 
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    pub fn increment_received(&mut self, clock: &GameClock) {
         self.received_messages += 1;
-        self.last_activity = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.last_activity = clock.now_secs();
     }
     
     /// 再試行カウンターを増加
     pub fn increment_retry(&mut self) {
-        self.retry_count += 1;
+        self.retry_count = self.retry_count.saturating_add(1);
     }
     
     /// 遅延を更新
     /// 
     /// # 引数
     /// * `latency_ms` - 新しい遅延時間（ミリ秒）
-    pub fn update_latency(&mut self, latency_ms: u32) {
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    pub fn update_latency(&mut self, latency_ms: u32, clock: &GameClock) {
         self.latency_ms = Some(latency_ms);
-        self.last_activity = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.last_activity = clock.now_secs();
     }
     
     /// 接続がアクティブかどうかチェック
     /// 
     /// # 引数
     /// * `timeout_seconds` - タイムアウト時間（秒）
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// アクティブな場合true、タイムアウトした場合false
-    pub fn is_active(&self, timeout_seconds: u64) -> bool {
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        current_time - self.last_activity < timeout_seconds
+    pub fn is_active(&self, timeout_seconds: u64, clock: &GameClock) -> bool {
+        clock.now_secs().saturating_sub(self.last_activity) < timeout_seconds
     }
 }
 
@@ -193,10 +200,10 @@ impl ConnectionStatus {
     }
 }
 
-/// ネットワークメッセージを表すコンポーネント
+/// ネットワークメッセージ
 /// 
 /// WebSocketで送受信されるメッセージを管理します。
-/// メッセージの種類、内容、タイムスタンプなどを保持します。
+/// メッセージの種類、内容、タイムスタンプなどを保持し、NetworkQueuesに溜めて処理します。
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct NetworkMessage {
     /// メッセージの一意識別子
@@ -234,6 +241,8 @@ impl NetworkMessage {
     /// * `payload` - メッセージの内容
     /// * `sender` - 送信者（オプション）
     /// * `recipient` - 受信者（オプション）
+    /// * `nonce` - 同じ秒に作られたメッセージIDが重複しないよう付ける乱数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 新しいNetworkMessageインスタンス
@@ -242,14 +251,13 @@ impl NetworkMessage {
         payload: String,
         sender: Option<Entity>,
         recipient: Option<Entity>,
+        nonce: u32,
+        clock: &GameClock,
     ) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = clock.now_secs();
             
         Self {
-            message_id: format!("msg_{}_{}", timestamp, rand::random::<u32>()),
+            message_id: format!("msg_{}_{}", timestamp, nonce),
             message_type,
             sender,
             recipient,
@@ -260,46 +268,105 @@ impl NetworkMessage {
         }
     }
     
-    /// 高優先度メッセージとして作成
+    /// プールから取り出したメッセージを新しい内容で初期化し直す
+    /// 
+    /// メッセージIDの文字列バッファはそのまま再利用されます。
     /// 
     /// # 引数
     /// * `message_type` - メッセージの種類
     /// * `payload` - メッセージの内容
     /// * `sender` - 送信者（オプション）
     /// * `recipient` - 受信者（オプション）
-    /// 
-    /// # 戻り値
-    /// 高優先度のNetworkMessageインスタンス
-    pub fn new_high_priority(
+    /// * `nonce` - 同じ秒に作られたメッセージIDが重複しないよう付ける乱数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    fn reset(
+        &mut self,
         message_type: MessageType,
         payload: String,
         sender: Option<Entity>,
         recipient: Option<Entity>,
-    ) -> Self {
-        let mut message = Self::new(message_type, payload, sender, recipient);
-        message.priority = MessagePriority::High;
-        message
+        nonce: u32,
+        clock: &GameClock,
+    ) {
+        use std::fmt::Write;
+
+        let timestamp = clock.now_secs();
+
+        self.message_id.clear();
+        let _ = write!(self.message_id, "msg_{}_{}", timestamp, nonce);
+        self.message_type = message_type;
+        self.sender = sender;
+        self.recipient = recipient;
+        self.payload = payload;
+        self.timestamp = timestamp;
+        self.priority = MessagePriority::Normal;
+        self.retry_count = 0;
     }
     
     /// 再送信カウンターを増加
     pub fn increment_retry(&mut self) {
-        self.retry_count += 1;
+        self.retry_count = self.retry_count.saturating_add(1);
+    }
+    
+    /// 受信したテキストを解析し、内容を検証する
+    /// 
+    /// # 引数
+    /// * `text` - 受信したテキスト（JSON）
+    /// 
+    /// # 戻り値
+    /// 成功時はNetworkMessage、サイズ超過・形式不正の場合はエラーメッセージ
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.len() > MAX_MESSAGE_BYTES {
+            return Err(format!("メッセージが大きすぎます（{}バイト）", text.len()));
+        }
+
+        let message: Self = serde_json::from_str(text)
+            .map_err(|e| format!("メッセージの形式が不正です: {}", e))?;
+        if message.message_id.len() > MAX_FIELD_BYTES {
+            return Err(format!("メッセージIDが長すぎます（{}バイト）", message.message_id.len()));
+        }
+        Ok(message)
     }
     
     /// メッセージが古すぎるかチェック
     /// 
     /// # 引数
     /// * `max_age_seconds` - 最大許容経過時間（秒）
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     /// 
     /// # 戻り値
     /// 古すぎる場合true、まだ有効な場合false
-    pub fn is_expired(&self, max_age_seconds: u64) -> bool {
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-            
-        current_time - self.timestamp > max_age_seconds
+    pub fn is_expired(&self, max_age_seconds: u64, clock: &GameClock) -> bool {
+        // 受信したメッセージのタイムスタンプは未来の値になり得るため、引き算は0で止める
+        clock.now_secs().saturating_sub(self.timestamp) > max_age_seconds
+    }
+}
+
+/// ネットワークメッセージのプール（リソース）
+/// 
+/// メッセージは毎フレーム作成・破棄されるため、処理済みのメッセージを再利用します。
+pub type NetworkMessagePool = ComponentPool<NetworkMessage>;
+
+/// ネットワークメッセージのキュー（リソース）
+/// 
+/// 受信したメッセージはinboundに溜めてMessageProcessingSystemが処理し、
+/// サーバーへ送るメッセージはoutboundに溜めてNetworkClientが送信します。
+/// メッセージごとにエンティティを作らないため、エンティティの一覧を汚しません。
+#[derive(Debug, Default)]
+pub struct NetworkQueues {
+    /// 受信したメッセージ（MessageProcessingSystemが処理する）
+    pub inbound: MessageQueue<NetworkMessage>,
+    
+    /// サーバーへ送るメッセージ（NetworkClientが送信する）
+    pub outbound: MessageQueue<NetworkMessage>,
+}
+
+impl Resource for NetworkQueues {}
+
+impl NetworkQueues {
+    /// 既定の最大数を持つ空のキューを作成
+    pub fn new() -> Self {
+        Self::default()
     }
 }
 
@@ -335,6 +402,12 @@ pub enum MessageType {
     
     /// ゲーム設定変更
     GameSettings,
+
+    /// ゲーム結果の送信
+    GameResult,
+
+    /// クイックリアクション（絵文字）
+    Reaction,
 }
 
 impl MessageType {
@@ -354,6 +427,8 @@ impl MessageType {
             MessageType::Error => "error",
             MessageType::Authentication => "authentication",
             MessageType::GameSettings => "game_settings",
+            MessageType::GameResult => "game_result",
+            MessageType::Reaction => "reaction",
         }
     }
 }
@@ -380,29 +455,36 @@ pub enum MessagePriority {
 // WebSocket管理クラス（WebAssembly環境用）
 // =============================================================================
 
+/// WebSocketのイベントハンドラーと共有する状態（WebAssembly用）
+#[cfg(feature = "wasm")]
+#[derive(Debug)]
+struct SocketState {
+    /// 接続状態
+    status: ConnectionStatus,
+    
+    /// 受信したメッセージ（JSON文字列、take_received()で取り出すまで溜める）
+    received: Vec<String>,
+}
+
 /// WebSocket接続マネージャー（WebAssembly用）
 /// 
 /// ブラウザ環境でのWebSocket接続を管理します。
 /// 接続の確立、メッセージの送受信、エラーハンドリングを行います。
+/// 接続状態と受信したメッセージはイベントハンドラーが更新し、
+/// NetworkClientが毎フレーム読み取ります。
 #[cfg(feature = "wasm")]
 pub struct WebSocketManager {
     /// WebSocketインスタンス
     websocket: Option<WebSocket>,
     
-    /// 接続状態
-    status: ConnectionStatus,
+    /// イベントハンドラーと共有する状態（接続状態・受信したメッセージ）
+    state: Rc<RefCell<SocketState>>,
     
     /// 接続URL
     url: String,
     
-    /// メッセージキュー（送信待ち）
-    message_queue: Vec<NetworkMessage>,
-    
-    /// 最大再試行回数
-    max_retries: u32,
-    
-    /// 現在の再試行回数
-    current_retries: u32,
+    /// メッセージキュー（送信待ち、JSON文字列、上限を超えると優先度の低いものから捨てる）
+    message_queue: SendQueue,
 }
 
 #[cfg(feature = "wasm")]
@@ -417,11 +499,12 @@ impl WebSocketManager {
     pub fn new(url: String) -> Self {
         Self {
             websocket: None,
-            status: ConnectionStatus::Disconnected,
+            state: Rc::new(RefCell::new(SocketState {
+                status: ConnectionStatus::Disconnected,
+                received: Vec::new(),
+            })),
             url,
-            message_queue: Vec::new(),
-            max_retries: 3,
-            current_retries: 0,
+            message_queue: SendQueue::default(),
         }
     }
     
@@ -430,11 +513,12 @@ impl WebSocketManager {
     /// # 戻り値
     /// 接続開始が成功した場合Ok(())、失敗した場合Err
     pub fn connect(&mut self) -> Result<(), String> {
-        if self.status == ConnectionStatus::Connected {
+        if self.get_status() == ConnectionStatus::Connected {
             return Ok(()); // 既に接続済み
         }
-        
-        self.status = ConnectionStatus::Connecting;
+        // 再接続の場合は、前のWebSocketを閉じてから接続し直す
+        self.close_websocket();
+        self.set_status(ConnectionStatus::Connecting);
         
         match WebSocket::new(&self.url) {
             Ok(ws) => {
@@ -445,13 +529,13 @@ impl WebSocketManager {
                 self.setup_event_handlers(&ws);
                 
                 self.websocket = Some(ws);
-                println!("🌐 WebSocket接続開始: {}", self.url);
+                info!("🌐 WebSocket接続開始: {}", self.url);
                 Ok(())
             }
             Err(e) => {
-                self.status = ConnectionStatus::Error;
+                self.set_status(ConnectionStatus::Error);
                 let error_msg = format!("WebSocket接続失敗: {:?}", e);
-                println!("❌ {}", error_msg);
+                error!("❌ {}", error_msg);
                 Err(error_msg)
             }
         }
@@ -459,12 +543,22 @@ impl WebSocketManager {
     
     /// WebSocket接続を切断
     pub fn disconnect(&mut self) {
-        if let Some(ws) = &self.websocket {
+        self.close_websocket();
+        self.set_status(ConnectionStatus::Disconnected);
+        info!("🔌 WebSocket接続を切断しました");
+    }
+    
+    /// WebSocketを閉じる
+    /// 
+    /// 閉じた後のイベントで状態が変わらないよう、ハンドラーを外してから閉じます。
+    fn close_websocket(&mut self) {
+        if let Some(ws) = self.websocket.take() {
+            ws.set_onopen(None);
+            ws.set_onmessage(None);
+            ws.set_onclose(None);
+            ws.set_onerror(None);
             let _ = ws.close();
         }
-        self.websocket = None;
-        self.status = ConnectionStatus::Disconnected;
-        println!("🔌 WebSocket接続を切断しました");
     }
     
     /// メッセージを送信
@@ -475,54 +569,100 @@ impl WebSocketManager {
     /// # 戻り値
     /// 送信成功時Ok(())、失敗時Err
     pub fn send_message(&mut self, message: NetworkMessage) -> Result<(), String> {
-        if self.status != ConnectionStatus::Connected {
+        let json_str = serde_json::to_string(&message).map_err(|e| {
+            let error_msg = format!("メッセージシリアライゼーション失敗: {}", e);
+            error!("❌ {}", error_msg);
+            error_msg
+        })?;
+        self.send_with_priority(json_str, message.priority)?;
+        debug!("📤 メッセージ送信: {} ({})", message.message_type.as_str(), message.message_id);
+        Ok(())
+    }
+    
+    /// JSON文字列をそのまま送信（接続されていない場合はキューに追加）
+    /// 
+    /// # 引数
+    /// * `text` - 送信するテキスト（WebSocketMessageのJSON文字列）
+    /// 
+    /// # 戻り値
+    /// 送信成功時Ok(())、失敗時Err
+    pub fn send_text(&mut self, text: String) -> Result<(), String> {
+        let priority = WebSocketMessage::parse(&text)
+            .map_or(MessagePriority::Normal, |message| transport::message_priority(&message));
+        self.send_with_priority(text, priority)
+    }
+    
+    /// 優先度を指定してJSON文字列を送信（接続されていない場合はキューに追加）
+    /// 
+    /// # 引数
+    /// * `text` - 送信するテキスト
+    /// * `priority` - キューがいっぱいのときにどれを捨てるかを決める優先度
+    /// 
+    /// # 戻り値
+    /// 送信成功時Ok(())、失敗時Err（キューに入らず捨てた場合もOk(())）
+    fn send_with_priority(&mut self, text: String, priority: MessagePriority) -> Result<(), String> {
+        if self.get_status() != ConnectionStatus::Connected {
             // 接続されていない場合はキューに追加
-            self.message_queue.push(message);
+            self.message_queue.push(priority, text);
             return Ok(());
         }
         
-        if let Some(ws) = &self.websocket {
-            match serde_json::to_string(&message) {
-                Ok(json_str) => {
-                    if let Err(e) = ws.send_with_str(&json_str) {
-                        let error_msg = format!("メッセージ送信失敗: {:?}", e);
-                        println!("❌ {}", error_msg);
-                        return Err(error_msg);
-                    }
-                    println!("📤 メッセージ送信: {} ({})", message.message_type.as_str(), message.message_id);
-                    Ok(())
-                }
-                Err(e) => {
-                    let error_msg = format!("メッセージシリアライゼーション失敗: {}", e);
-                    println!("❌ {}", error_msg);
-                    Err(error_msg)
-                }
-            }
-        } else {
-            Err("WebSocket接続が存在しません".to_string())
-        }
+        let ws = self
+            .websocket
+            .as_ref()
+            .ok_or_else(|| "WebSocket接続が存在しません".to_string())?;
+        ws.send_with_str(&text).map_err(|e| {
+            let error_msg = format!("メッセージ送信失敗: {:?}", e);
+            error!("❌ {}", error_msg);
+            error_msg
+        })
     }
     
     /// キューに溜まったメッセージを送信
+    /// 
+    /// 送れなかった場合は、そのメッセージ以降を溜まっていた順のままキューに戻します。
     pub fn flush_message_queue(&mut self) {
-        if self.status != ConnectionStatus::Connected {
+        if self.get_status() != ConnectionStatus::Connected || self.message_queue.is_empty() {
             return;
         }
         
-        let messages = std::mem::take(&mut self.message_queue);
-        for message in messages {
-            if let Err(e) = self.send_message(message) {
-                println!("⚠️ キューからのメッセージ送信失敗: {}", e);
+        let mut messages = self.message_queue.take_for_retry().into_iter();
+        info!("📤 溜まっていたメッセージを送り直します: {}件", messages.len());
+        while let Some((priority, message)) = messages.next() {
+            if let Err(e) = self.send_with_priority(message.clone(), priority) {
+                warn!("⚠️ キューからのメッセージ送信失敗: {}", e);
+                let mut rest = vec![(priority, message)];
+                rest.extend(messages);
+                self.message_queue.restore(rest);
+                return;
             }
         }
     }
     
+    /// 送信待ちのキューの溜まり具合と、溜めた・捨てた・送り直した数
+    pub fn queue_metrics(&self) -> QueueMetrics {
+        self.message_queue.metrics()
+    }
+    
+    /// 受信したメッセージを取り出す
+    /// 
+    /// # 戻り値
+    /// 受信順のメッセージ（JSON文字列）
+    pub fn take_received(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.state.borrow_mut().received)
+    }
+    
     /// 現在の接続状態を取得
     /// 
     /// # 戻り値
     /// 現在の接続状態
     pub fn get_status(&self) -> ConnectionStatus {
-        self.status
+        self.state.borrow().status
+    }
+    
+    /// 接続状態を変更
+    fn set_status(&self, status: ConnectionStatus) {
+        self.state.borrow_mut().status = status;
     }
     
     /// イベントハンドラーを設定
@@ -531,43 +671,41 @@ impl WebSocketManager {
     /// * `ws` - WebSocketインスタンス
     fn setup_event_handlers(&mut self, ws: &WebSocket) {
         // 接続開始イベント
+        let state = Rc::clone(&self.state);
         let onopen_callback = Closure::wrap(Box::new(move |_| {
-            println!("✅ WebSocket接続が確立されました");
+            info!("✅ WebSocket接続が確立されました");
+            state.borrow_mut().status = ConnectionStatus::Connected;
         }) as Box<dyn FnMut(JsValue)>);
         ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
         onopen_callback.forget();
         
-        // メッセージ受信イベント
+        // メッセージ受信イベント（解析・検証は取り出す側で行う）
+        let state = Rc::clone(&self.state);
         let onmessage_callback = Closure::wrap(Box::new(move |e: MessageEvent| {
             if let Ok(txt) = e.data().dyn_into::<js_sys::JsString>() {
                 let message_str = String::from(txt);
-                println!("📥 メッセージ受信: {}", message_str);
-                
-                // メッセージをパースして処理
-                if let Ok(message) = serde_json::from_str::<NetworkMessage>(&message_str) {
-                    println!("🔍 メッセージ解析完了: {} ({})", 
-                        message.message_type.as_str(), 
-                        message.message_id
-                    );
-                    // TODO: ECSシステムにメッセージを渡す処理を追加
-                } else {
-                    println!("⚠️ メッセージのパースに失敗しました");
-                }
+                debug!("📥 メッセージ受信: {}バイト", message_str.len());
+                state.borrow_mut().received.push(message_str);
             }
         }) as Box<dyn FnMut(MessageEvent)>);
         ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
         onmessage_callback.forget();
         
         // 接続終了イベント
+        let state = Rc::clone(&self.state);
         let onclose_callback = Closure::wrap(Box::new(move |e: CloseEvent| {
-            println!("🔌 WebSocket接続が終了されました (コード: {})", e.code());
+            // 切断を要求していない終了なので、NetworkConnectionSystemの再接続の対象にする
+            info!("🔌 WebSocket接続が終了されました (コード: {})", e.code());
+            state.borrow_mut().status = ConnectionStatus::Error;
         }) as Box<dyn FnMut(CloseEvent)>);
         ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
         onclose_callback.forget();
         
         // エラーイベント
+        let state = Rc::clone(&self.state);
         let onerror_callback = Closure::wrap(Box::new(move |e: ErrorEvent| {
-            println!("❌ WebSocketエラーが発生しました: {:?}", e);
+            error!("❌ WebSocketエラーが発生しました: {:?}", e);
+            state.borrow_mut().status = ConnectionStatus::Error;
         }) as Box<dyn FnMut(ErrorEvent)>);
         ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
         onerror_callback.forget();
@@ -586,6 +724,7 @@ pub struct NetworkConnectionSystem;
 
 impl System for NetworkConnectionSystem {
     fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let clock = GameClock::from_world(world);
         let mut reconnection_needed = Vec::new();
         let mut timeout_connections = Vec::new();
         
@@ -599,7 +738,7 @@ impl System for NetworkConnectionSystem {
                 }
                 ConnectionStatus::Connected => {
                     // 60秒間アクティビティがない場合はタイムアウト
-                    if !connection.is_active(60) {
+                    if !connection.is_active(60, &clock) {
                         timeout_connections.push(entity);
                     }
                 }
@@ -608,7 +747,7 @@ impl System for NetworkConnectionSystem {
             
             // 接続統計をデバッグ出力（定期的に）
             if connection.sent_messages > 0 || connection.received_messages > 0 {
-                println!(
+                debug!(
                     "📊 接続統計 [{}]: 送信{}件, 受信{}件, 遅延{:?}ms, 状態:{}",
                     connection.connection_id,
                     connection.sent_messages,
@@ -623,16 +762,22 @@ impl System for NetworkConnectionSystem {
         for entity in reconnection_needed {
             if let Some(connection) = world.get_component_mut::<NetworkConnection>(entity) {
                 connection.increment_retry();
-                connection.update_status(ConnectionStatus::Reconnecting);
-                println!("🔄 接続再試行: {} ({}回目)", connection.connection_id, connection.retry_count);
+                connection.update_status(ConnectionStatus::Reconnecting, &clock);
+                info!("🔄 接続再試行: {} ({}回目)", connection.connection_id, connection.retry_count);
             }
         }
         
         // タイムアウト処理
         for entity in timeout_connections {
             if let Some(connection) = world.get_component_mut::<NetworkConnection>(entity) {
-                connection.update_status(ConnectionStatus::Error);
-                println!("⏰ 接続タイムアウト: {}", connection.connection_id);
+                connection.update_status(ConnectionStatus::Error, &clock);
+                info!("⏰ 接続タイムアウト: {}", connection.connection_id);
+            }
+            if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                events.notify(
+                    NotificationSeverity::Error,
+                    "⏰ サーバーからの応答がないため接続が切れました",
+                );
             }
         }
     }
@@ -645,18 +790,27 @@ pub struct MessageProcessingSystem;
 
 impl System for MessageProcessingSystem {
     fn update(&mut self, world: &mut World, _delta_time: f64) {
-        let mut processed_messages = Vec::new();
-        let mut expired_messages = Vec::new();
+        let clock = GameClock::from_world(world);
+        let mut messages = match world.get_resource_mut::<NetworkQueues>() {
+            Some(queues) => queues.inbound.drain(),
+            None => return,
+        };
+        let mut expired_count = 0;
+        let mut notifications = Vec::new();
+        let mut reactions = Vec::new();
+        
+        // 優先度の高いメッセージから処理する（同じ優先度は届いた順）
+        messages.sort_by_key(|message| Reverse(message.priority));
         
         // 全てのメッセージを処理
-        for (entity, message) in world.query::<NetworkMessage>() {
+        for message in &messages {
             // 古いメッセージをチェック（300秒でタイムアウト）
-            if message.is_expired(300) {
-                expired_messages.push(entity);
+            if message.is_expired(300, &clock) {
+                expired_count += 1;
                 continue;
             }
             
-            println!(
+            debug!(
                 "📨 メッセージ処理: {} -> {:?} (優先度: {:?}, {}回目)",
                 message.message_type.as_str(),
                 message.recipient,
@@ -667,47 +821,82 @@ impl System for MessageProcessingSystem {
             // メッセージタイプに応じた処理
             match message.message_type {
                 MessageType::PlayerAction => {
-                    // プレイヤーアクションの処理
-                    println!("🎯 プレイヤーアクション処理: {}", message.payload);
+                    // プレイヤーアクションの処理（内容はゲーム側と共通の型で解析する）
+                    match ActionPayload::parse(&message.payload) {
+                        Ok(action) => debug!("🎯 プレイヤーアクション処理: {:?}", action),
+                        Err(e) => warn!("⚠️ 不正なプレイヤーアクション: {}", e),
+                    }
                 }
                 
                 MessageType::GameStateSync => {
                     // ゲーム状態同期の処理
-                    println!("🔄 ゲーム状態同期: {}", message.payload);
+                    debug!("🔄 ゲーム状態同期: {}", message.payload);
                 }
                 
                 MessageType::Chat => {
                     // チャットメッセージの処理
-                    println!("💬 チャット: {}", message.payload);
+                    info!("💬 チャット: {}", message.payload);
+                }
+                
+                MessageType::PlayerJoinLeave => {
+                    // 相手の退出・切断はプレイヤーに知らせる
+                    if let Ok(WebSocketMessage::PlayerLeft { player_name, .. }) =
+                        WebSocketMessage::parse(&message.payload)
+                    {
+                        info!("👋 対戦相手が切断しました: {}", player_name);
+                        notifications.push((
+                            NotificationSeverity::Warning,
+                            format!("👋 {}さんとの接続が切れました", player_name),
+                        ));
+                    }
+                }
+                
+                MessageType::Reaction => {
+                    // 送信者のカーソルの横に表示する
+                    match WebSocketMessage::parse(&message.payload) {
+                        Ok(WebSocketMessage::Reaction { player_id, emote }) => {
+                            reactions.push((player_id, emote))
+                        }
+                        Ok(_) => warn!("⚠️ リアクション以外のメッセージです: {}", message.payload),
+                        Err(e) => warn!("⚠️ 不正なリアクション: {}", e),
+                    }
                 }
                 
                 MessageType::Ping => {
                     // Pingに対してPongを返す
-                    println!("🏓 Ping受信、Pong送信");
+                    debug!("🏓 Ping受信、Pong送信");
                 }
                 
                 MessageType::Pong => {
                     // Pongを受信（遅延測定に使用）
-                    println!("🏓 Pong受信");
+                    debug!("🏓 Pong受信");
                 }
                 
                 _ => {
-                    println!("📄 その他のメッセージ処理: {}", message.message_type.as_str());
+                    debug!("📄 その他のメッセージ処理: {}", message.message_type.as_str());
                 }
             }
-            
-            processed_messages.push(entity);
         }
         
-        // 処理済みメッセージを削除
-        for entity in processed_messages {
-            world.remove_component::<NetworkMessage>(entity);
+        if expired_count > 0 {
+            debug!("🗑️ 期限切れメッセージを削除: {}件", expired_count);
+        }
+        
+        if let Some(events) = world.get_resource_mut::<EventQueue>() {
+            for (severity, message) in notifications {
+                events.notify(severity, message);
+            }
+        }
+        
+        for (player_id, emote) in reactions {
+            reaction::show(world, &player_id, emote);
         }
         
-        // 期限切れメッセージを削除
-        for entity in expired_messages {
-            println!("🗑️ 期限切れメッセージを削除");
-            world.remove_component::<NetworkMessage>(entity);
+        // 処理済み・期限切れのメッセージはプールへ戻す
+        if let Some(pool) = world.get_resource_mut::<NetworkMessagePool>() {
+            for message in messages {
+                pool.release(message);
+            }
         }
     }
 }
@@ -722,6 +911,21 @@ impl System for MessageProcessingSystem {
 pub struct NetworkManager;
 
 impl NetworkManager {
+    /// メッセージを送る通信経路を選ぶ
+    /// 
+    /// カーソル位置など優先度がLowのメッセージは、ルームの全員とデータチャネルが
+    /// 開いていればデータチャネルで送り、それ以外はWebSocketで送ります。
+    /// 
+    /// # 引数
+    /// * `selector` - データチャネルの状態
+    /// * `message` - 送信するメッセージ
+    /// 
+    /// # 戻り値
+    /// 選ばれた通信経路
+    pub fn select_transport(selector: &TransportSelector, message: &WebSocketMessage) -> Transport {
+        selector.select(transport::message_priority(message))
+    }
+    
     /// 新しいネットワーク接続を作成
     /// 
     /// # 引数
@@ -736,16 +940,17 @@ impl NetworkManager {
         connection_id: String,
         url: String,
     ) -> Entity {
+        let clock = GameClock::from_world(world);
         let connection_entity = world.create_entity();
-        let connection = NetworkConnection::new(connection_id.clone(), url.clone());
+        let connection = NetworkConnection::new(connection_id.clone(), url.clone(), &clock);
         
         world.add_component(connection_entity, connection);
         
-        println!("🌐 新しいネットワーク接続作成: {} -> {}", connection_id, url);
+        info!("🌐 新しいネットワーク接続作成: {} -> {}", connection_id, url);
         connection_entity
     }
     
-    /// メッセージを送信キューに追加
+    /// メッセージを受信キューに追加（MessageProcessingSystemが次のフレームで処理する）
     /// 
     /// # 引数
     /// * `world` - ECSワールドへの可変参照
@@ -755,21 +960,21 @@ impl NetworkManager {
     /// * `recipient` - 受信者（オプション）
     /// 
     /// # 戻り値
-    /// 作成されたメッセージエンティティ
+    /// キューに追加できた場合true、キューが上限に達していて捨てた場合false
     pub fn send_message(
         world: &mut World,
         message_type: MessageType,
         payload: String,
         sender: Option<Entity>,
         recipient: Option<Entity>,
-    ) -> Entity {
-        let message_entity = world.create_entity();
-        let message = NetworkMessage::new(message_type, payload, sender, recipient);
+    ) -> bool {
+        let message = Self::acquire_message(world, message_type, payload, sender, recipient);
+        let queued = Self::enqueue(world, message, false);
         
-        world.add_component(message_entity, message);
-        
-        println!("📤 メッセージキューに追加: {}", message_type.as_str());
-        message_entity
+        if queued {
+            debug!("📤 メッセージキューに追加: {}", message_type.as_str());
+        }
+        queued
     }
     
     /// 高優先度メッセージを送信
@@ -782,21 +987,118 @@ impl NetworkManager {
     /// * `recipient` - 受信者（オプション）
     /// 
     /// # 戻り値
-    /// 作成されたメッセージエンティティ
+    /// キューに追加できた場合true、キューが上限に達していて捨てた場合false
     pub fn send_priority_message(
         world: &mut World,
         message_type: MessageType,
         payload: String,
         sender: Option<Entity>,
         recipient: Option<Entity>,
-    ) -> Entity {
-        let message_entity = world.create_entity();
-        let message = NetworkMessage::new_high_priority(message_type, payload, sender, recipient);
+    ) -> bool {
+        let mut message = Self::acquire_message(world, message_type, payload, sender, recipient);
+        message.priority = MessagePriority::High;
+        let queued = Self::enqueue(world, message, false);
         
-        world.add_component(message_entity, message);
+        if queued {
+            info!("🚨 高優先度メッセージキューに追加: {}", message_type.as_str());
+        }
+        queued
+    }
+    
+    /// サーバーへ送るメッセージを送信キューに追加（NetworkClientが次に送信する）
+    /// 
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `message_type` - メッセージタイプ
+    /// * `payload` - メッセージの内容
+    /// 
+    /// # 戻り値
+    /// キューに追加できた場合true、キューが上限に達していて捨てた場合false
+    pub fn queue_outbound(world: &mut World, message_type: MessageType, payload: String) -> bool {
+        let message = Self::acquire_message(world, message_type, payload, None, None);
+        let queued = Self::enqueue(world, message, true);
         
-        println!("🚨 高優先度メッセージキューに追加: {}", message_type.as_str());
-        message_entity
+        if queued {
+            debug!("📤 送信キューに追加: {}", message_type.as_str());
+        }
+        queued
+    }
+    
+    /// 送信キューに溜まったメッセージをすべて取り出す
+    /// 
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// 
+    /// # 戻り値
+    /// 送信するメッセージ（追加した順）
+    pub fn take_outbound(world: &mut World) -> Vec<NetworkMessage> {
+        world
+            .get_resource_mut::<NetworkQueues>()
+            .map(|queues| queues.outbound.drain())
+            .unwrap_or_default()
+    }
+    
+    /// メッセージをキューに追加する
+    /// 
+    /// ワールドにキューが登録されていない場合は登録します。
+    /// キューが上限に達している場合はメッセージをプールへ戻して捨てます。
+    fn enqueue(world: &mut World, message: NetworkMessage, outbound: bool) -> bool {
+        if !world.has_resource::<NetworkQueues>() {
+            world.insert_resource(NetworkQueues::new());
+        }
+        let Some(queues) = world.get_resource_mut::<NetworkQueues>() else {
+            return false;
+        };
+        let queue = if outbound { &mut queues.outbound } else { &mut queues.inbound };
+        
+        match queue.push(message) {
+            Ok(()) => true,
+            Err(message) => {
+                warn!(
+                    "⚠️ メッセージキューが上限に達したため捨てました: {}",
+                    message.message_type.as_str()
+                );
+                if let Some(pool) = world.get_resource_mut::<NetworkMessagePool>() {
+                    pool.release(message);
+                }
+                false
+            }
+        }
+    }
+    
+    /// メッセージを作成（プールがあれば再利用）
+    fn acquire_message(
+        world: &mut World,
+        message_type: MessageType,
+        payload: String,
+        sender: Option<Entity>,
+        recipient: Option<Entity>,
+    ) -> NetworkMessage {
+        let nonce = Self::next_nonce(world);
+        let clock = GameClock::from_world(world);
+
+        match world.get_resource_mut::<NetworkMessagePool>() {
+            Some(pool) => pool.acquire(|recycled| match recycled {
+                Some(mut message) => {
+                    message.reset(message_type, payload, sender, recipient, nonce, &clock);
+                    message
+                }
+                None => NetworkMessage::new(message_type, payload, sender, recipient, nonce, &clock),
+            }),
+            None => NetworkMessage::new(message_type, payload, sender, recipient, nonce, &clock),
+        }
+    }
+    
+    /// メッセージID用の乱数を取得
+    /// 
+    /// ワールドに乱数生成器が登録されていない場合は、実行環境から得たシードで登録します。
+    fn next_nonce(world: &mut World) -> u32 {
+        if !world.has_resource::<Rng>() {
+            world.insert_resource(Rng::from_entropy());
+        }
+        world
+            .get_resource_mut::<Rng>()
+            .map_or(0, |rng| rng.next_u32())
     }
     
     /// 接続状態を更新
@@ -810,11 +1112,12 @@ impl NetworkManager {
         connection_entity: Entity,
         new_status: ConnectionStatus,
     ) {
+        let clock = GameClock::from_world(world);
         if let Some(connection) = world.get_component_mut::<NetworkConnection>(connection_entity) {
             let old_status = connection.status;
-            connection.update_status(new_status);
+            connection.update_status(new_status, &clock);
             
-            println!(
+            info!(
                 "🔄 接続状態変更: {} -> {} ({})",
                 old_status.as_str(),
                 new_status.as_str(),
@@ -823,23 +1126,3 @@ impl NetworkManager {
         }
     }
 }
-
-// =============================================================================
-// 乱数生成のモック（WebAssembly環境では実際のrand crateが必要）
-// =============================================================================
-
-/// 簡単な乱数生成（開発用、本番では適切なrand crateを使用）
-mod rand {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    
-    pub fn random<T>() -> T 
-    where 
-        T: From<u32>,
-    {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u32;
-        T::from(timestamp % 100000)
-    }
-}
\ No newline at end of file
@@ -0,0 +1,1537 @@
+// =============================================================================
+// ネットワーククライアント
+// =============================================================================
+// このファイルでは、サーバーとの通信をひとまとめにして扱うNetworkClientを実装します。
+// GameRuntimeが1つ所有し、WebAssemblyとして公開される関数はこのクライアントを通して
+// サーバーに接続し、ルームへの参加やアクションの送信を行います。
+//
+// 仕組み：
+// - 接続の状態はECSワールドのNetworkConnectionコンポーネントに記録する
+//   （タイムアウト・再接続の判定はこれまで通りNetworkConnectionSystemが行う）
+// - connect()でWebSocketManagerを作ってブラウザのWebSocketで接続する
+//   （JavaScript側がWebSocketを持つ場合は、set_connected()・receive()・take_outgoing()で中継する）
+// - 送信するメッセージはWebSocketMessageの形式で溜め、毎フレームのpoll()で送る
+// - 届いたメッセージは検証してから購読者へ渡し、退出の通知・リアクションなど
+//   MessageProcessingSystemが扱う種類はNetworkMessageとしてワールドにも追加する
+// - 接続状態の変化はEventQueueでJavaScriptへ通知する
+// - 接続し直した場合は、同じ表示名・セッショントークンで参加し直し、参加していたルームにも戻る
+// - 接続・ルームへの参加・ルームの作成・ルーム一覧の取得の要求には要求のIDを付け、
+//   await_reply()で登録した関数に、同じIDが付いたサーバーの応答（またはエラー・切断・
+//   タイムアウト）を1回だけ渡す
+// - 優先度がNormal以上のメッセージはReliableで包んで送り、サーバーから受け取りの確認（Ack）が
+//   届くまで間隔を空けながら再送する。Reliableで届いたメッセージにはAckを返し、重複は処理しない
+// - 送るメッセージにはチャネルごとの連番を付け、サーバーから抜けている連番の再送を要求されたら
+//   すぐに送り直す。他のプレイヤーのカーソル位置は、新しい位置より後に届いた古い位置を捨てる
+// - 同じルームの他のプレイヤーの表示名・スコア・接続状態は、届いたメッセージから
+//   RemotePlayerとしてワールドに反映する（scoreboard.rs）。自分のスコアは変わったときだけ送る
+// - 共有盤面のルームの自分の立場とカードの持ち主は、PermissionsChangedからワールドに反映する
+//   （permissions.rs）
+// - 接続が切れている間に送ろうとしたメッセージはWebSocketManagerの上限付きのキューに溜め、
+//   接続し直したら先に送る。溜まり具合と捨てた数はqueue_metrics()で確認できる（send_queue.rs）
+// - ルームでは操作していない時間を段階が変わったときだけIdleStatusで送る（afk.rs）
+// - 接続中はpoll()で一定間隔ごとにPingを送り、届いたPongから接続品質（connection_quality.rs）と
+//   サーバーの時計とのずれ（time_sync.rs）を求める。推定したずれは次のPingでサーバーに伝える
+// - 接続品質に合わせた送信間隔で、間隔より早く送ろうとしたカーソル位置は最新の位置だけを残して、
+//   間隔が空いたらpoll()で送る。ルームでは自分のスコアを変わっていなくてもキーフレームの間隔ごとに送り直す
+// =============================================================================
+
+use crate::afk;
+use crate::animation_queue;
+use crate::clock;
+use crate::connection_quality::{ConnectionMonitor, ConnectionReport, UpdateRates};
+use crate::ecs::{Entity, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::network::{
+    ConnectionStatus, MessagePriority, MessageType, NetworkConnection, NetworkManager,
+    NetworkMessagePool,
+};
+use crate::permissions;
+use crate::power_up::{self, PowerUp};
+use crate::protocol::{self, Channel, WebSocketMessage};
+use crate::reliable::{DuplicateFilter, ReliableSender, RECENT_ID_WINDOW};
+use crate::rng::Rng;
+use crate::scoreboard;
+use crate::send_queue::{QueueMetrics, SendQueue};
+use crate::sequence::{Arrival, SequenceCounter, SequenceTracker};
+use crate::time_sync::ClockSync;
+use crate::theme::CardBack;
+use crate::transport;
+use log::{debug, info, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[cfg(feature = "wasm")]
+use crate::network::WebSocketManager;
+
+/// すべての種類のメッセージを購読する場合に指定する種類
+pub const ALL_MESSAGES: &str = "*";
+
+/// メッセージの購読ID
+pub type SubscriptionId = u32;
+
+/// 届いたメッセージを受け取る関数
+///
+/// receive()の中でクライアントを借りたまま呼ばれるため、ランタイムのAPIは呼べません
+/// （JavaScriptのコールバックはlib.rsが溜めて、セッションの借用を返してから呼びます）。
+pub type MessageHandler = Box<dyn FnMut(&WebSocketMessage)>;
+
+/// 要求への応答を受け取る関数（応答のメッセージ、またはエラーメッセージ）
+pub type Reply = Box<dyn FnOnce(Result<WebSocketMessage, String>)>;
+
+/// サーバーの応答を待つ時間の上限（ミリ秒）
+pub const REQUEST_TIMEOUT_MS: f64 = 10_000.0;
+
+/// 読み込むまで覚えておく、サーバーが確かめた成績の書き出しの署名の数
+const MAX_VERIFIED_STATS: usize = 8;
+
+/// 作成するルームの設定（JavaScriptからはJSONで渡される）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoomOptions {
+    /// ルーム名
+    pub name: String,
+
+    /// 定員（省略時はサーバーの既定値）
+    #[serde(default)]
+    pub max_players: Option<u8>,
+
+    /// パスワード（省略時はパスワードなし）
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// ターンの制限時間（秒、省略時はターン制にしない）
+    #[serde(default)]
+    pub turn_time_limit: Option<u32>,
+
+    /// レースのコンボの受付時間（秒、省略時はコンボを数えない）
+    #[serde(default)]
+    pub combo_window_seconds: Option<u32>,
+
+    /// パワーアップを使えるカジュアルなルームにするか（結果はランク外になる）
+    #[serde(default)]
+    pub power_ups: Option<bool>,
+
+    /// 参加者全員で1つの盤面を動かすルームにするか（観戦者・カードの持ち主の権限を使う）
+    #[serde(default)]
+    pub shared_board: Option<bool>,
+}
+
+/// メッセージの購読
+struct Subscription {
+    /// 購読ID
+    id: SubscriptionId,
+
+    /// 受け取るメッセージの種類（"RoomList"など、ALL_MESSAGESの場合はすべて）
+    message_type: String,
+
+    /// 届いたメッセージを受け取る関数
+    handler: MessageHandler,
+}
+
+/// 応答を待っている要求
+struct PendingRequest {
+    /// 応答を待つ期限（ゲーム時計の経過時間、ミリ秒）
+    deadline_ms: f64,
+
+    /// 応答を受け取る関数
+    reply: Reply,
+}
+
+/// 参加を申し込んだルーム
+#[derive(Debug, Clone)]
+struct RoomRequest {
+    room_id: String,
+    password: Option<String>,
+    request_id: Option<String>, // 参加の応答を待つ要求のID（接続し直して戻る場合はNone）
+}
+
+/// サーバーとの通信をまとめたクライアント
+pub struct NetworkClient {
+    /// 接続の状態を記録するエンティティ（connect()前はNone）
+    connection: Option<Entity>,
+
+    /// 参加に使う表示名（join()前はNone）
+    player_name: Option<String>,
+
+    /// サーバーが割り当てたプレイヤーID（PlayerProfileを受け取るまではNone）
+    player_id: Option<String>,
+
+    /// サーバーが発行したセッショントークン（接続し直したときに設定を引き継ぐ）
+    session_token: Option<String>,
+
+    /// 参加を申し込んだルーム（接続し直したときに戻る）
+    requested_room: Option<RoomRequest>,
+
+    /// 参加中のルームのID
+    room_id: Option<String>,
+
+    /// 送信待ちのメッセージ（JSON文字列）
+    outgoing: Vec<String>,
+
+    /// メッセージの購読
+    subscriptions: Vec<Subscription>,
+
+    /// 次に発行する購読ID
+    next_subscription_id: SubscriptionId,
+
+    /// 応答を待っている要求（要求のIDごと）
+    pending: HashMap<String, PendingRequest>,
+
+    /// 要求のIDの接頭辞（他のクライアントのIDと重ならないよう乱数で決める）
+    request_prefix: String,
+
+    /// 次に発行する要求の番号
+    next_request_number: u64,
+
+    /// Reliableで包むメッセージのIDの接頭辞（チャネルと連番を付けてIDにする）
+    message_prefix: String,
+
+    /// チャネルごとの送るメッセージの連番
+    sequences: SequenceCounter,
+
+    /// 他のプレイヤーごとの届いたカーソル位置の連番（古い位置を捨てるため）
+    cursor_sequences: HashMap<String, SequenceTracker>,
+
+    /// 受け取りの確認（Ack）を待っているメッセージ
+    reliable: ReliableSender,
+
+    /// 最近Reliableで届いたメッセージのID（重複の除外用）
+    recent_ids: DuplicateFilter,
+
+    /// 参加（PlayerJoin）の要求のID
+    join_request_id: Option<String>,
+
+    /// 作成を申し込んだルームのパスワード（作成したルームに参加するまで保持し、接続し直したときに使う）
+    created_room_password: Option<Option<String>>,
+
+    /// 最後に送ったスコアとファウンデーションのカードの枚数（変わった場合だけ送るため）
+    last_score: Option<(u32, u16)>,
+
+    /// スコアを最後に送った時刻（ゲーム時計の経過時間、ミリ秒。次のpoll()で記録する場合はNone）
+    last_score_sent_ms: Option<f64>,
+
+    /// 接続品質の測定器（Ping/Pongの往復時間と応答のなかった割合）
+    monitor: ConnectionMonitor,
+
+    /// サーバーの時計とのずれの推定器
+    clock_sync: ClockSync,
+
+    /// カーソル位置を最後に送った時刻（ゲーム時計の経過時間、ミリ秒）
+    last_cursor_sent_ms: Option<f64>,
+
+    /// 送信間隔が空くのを待っているカーソル位置（最新のものだけ）
+    pending_cursor: Option<(f64, f64)>,
+
+    /// 最後に送ったカードの裏面（まだ送っていない・見せるのをやめた場合はNone）
+    last_card_back: Option<CardBack>,
+
+    /// 最後に送った操作していない時間の段階（まだ送っていない場合はNone）
+    last_idle_step: Option<u32>,
+
+    /// ターン制のルームで現在ターンのプレイヤーID（ターン制でない・ルーム外の場合はNone）
+    turn_player_id: Option<String>,
+
+    /// サーバーが正しいと確かめた成績の書き出しの署名（読み込むまで、新しい順にMAX_VERIFIED_STATS件まで）
+    verified_stats: Vec<String>,
+
+    /// ブラウザのWebSocket（JavaScript側がWebSocketを持つ場合はNone）
+    #[cfg(feature = "wasm")]
+    socket: Option<WebSocketManager>,
+}
+
+impl NetworkClient {
+    /// 未接続のクライアントを作成
+    pub fn new() -> Self {
+        let nonce = Rng::from_entropy().next_u32();
+        Self {
+            connection: None,
+            player_name: None,
+            player_id: None,
+            session_token: None,
+            requested_room: None,
+            room_id: None,
+            outgoing: Vec::new(),
+            subscriptions: Vec::new(),
+            next_subscription_id: 1,
+            pending: HashMap::new(),
+            request_prefix: format!("req_{:08x}", nonce),
+            next_request_number: 1,
+            message_prefix: format!("msg_{:08x}", nonce),
+            sequences: SequenceCounter::new(),
+            cursor_sequences: HashMap::new(),
+            reliable: ReliableSender::new(),
+            recent_ids: DuplicateFilter::new(RECENT_ID_WINDOW),
+            join_request_id: None,
+            created_room_password: None,
+            last_score: None,
+            last_score_sent_ms: None,
+            monitor: ConnectionMonitor::new(),
+            clock_sync: ClockSync::new(),
+            last_cursor_sent_ms: None,
+            pending_cursor: None,
+            last_card_back: None,
+            last_idle_step: None,
+            turn_player_id: None,
+            verified_stats: Vec::new(),
+            #[cfg(feature = "wasm")]
+            socket: None,
+        }
+    }
+
+    /// サーバーへの接続を開始
+    ///
+    /// WebAssembly環境ではブラウザのWebSocketで接続します。
+    /// それ以外の環境では接続の記録だけを作り、set_connected()で接続完了を知らせます。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `url` - 接続先のWebSocket URL
+    ///
+    /// # 戻り値
+    /// 接続を開始できた場合Ok(())、WebSocketを作成できない場合はエラーメッセージ
+    pub fn connect(&mut self, world: &mut World, url: &str) -> Result<(), String> {
+        self.close_connection(world);
+
+        let connection =
+            NetworkManager::create_connection(world, "server".to_string(), url.to_string());
+        self.connection = Some(connection);
+        self.set_status(world, ConnectionStatus::Connecting);
+
+        #[cfg(feature = "wasm")]
+        {
+            let mut socket = WebSocketManager::new(url.to_string());
+            if let Err(e) = socket.connect() {
+                self.set_status(world, ConnectionStatus::Error);
+                return Err(e);
+            }
+            self.socket = Some(socket);
+        }
+        Ok(())
+    }
+
+    /// サーバーとの接続を切断
+    ///
+    /// 表示名と参加していたルームは忘れるため、次のconnect()の後はjoin()から始めます。
+    pub fn disconnect(&mut self, world: &mut World) {
+        self.close_connection(world);
+        self.player_name = None;
+        self.player_id = None;
+        self.requested_room = None;
+        self.room_id = None;
+        self.created_room_password = None;
+        self.outgoing.clear();
+    }
+
+    /// JavaScript側が持つWebSocketの接続状態を知らせる
+    ///
+    /// connect()を呼ばずに使う場合は、ここで接続の記録を作ります（接続先はJavaScript側が決めるためURLは空）。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `connected` - 接続中の場合true
+    pub fn set_connected(&mut self, world: &mut World, connected: bool) {
+        if self.connection.is_none() {
+            let connection =
+                NetworkManager::create_connection(world, "server".to_string(), String::new());
+            self.connection = Some(connection);
+        }
+        let status = if connected {
+            ConnectionStatus::Connected
+        } else {
+            ConnectionStatus::Disconnected
+        };
+        self.set_status(world, status);
+    }
+
+    /// 表示名を指定してプレイヤーとして参加
+    ///
+    /// 接続前に呼び出した場合は、接続した時点で参加します。
+    /// 参加できるとプロフィール（PlayerProfile）が要求のIDを付けて届きます。
+    ///
+    /// # 引数
+    /// * `player_name` - 表示名
+    ///
+    /// # 戻り値
+    /// 要求のID
+    pub fn join(&mut self, world: &World, player_name: &str) -> String {
+        let request_id = self.next_request_id();
+        self.player_name = Some(player_name.to_string());
+        self.join_request_id = Some(request_id.clone());
+        if self.status(world) == ConnectionStatus::Connected {
+            self.send_player_join();
+        }
+        request_id
+    }
+
+    /// ルームに参加
+    ///
+    /// プレイヤーIDを受け取る前に呼び出した場合は、受け取った時点で参加します。
+    ///
+    /// # 引数
+    /// * `room_id` - 参加するルームのID
+    /// * `password` - パスワード付きのルームに参加する場合のパスワード
+    ///
+    /// # 戻り値
+    /// 申し込めた場合は要求のID、join()の前に呼び出した場合はエラーメッセージ
+    pub fn join_room(&mut self, room_id: &str, password: Option<String>) -> Result<String, String> {
+        if self.player_name.is_none() {
+            return Err("ルームに参加する前にプレイヤーとして参加してください".to_string());
+        }
+
+        let request_id = self.next_request_id();
+        self.requested_room = Some(RoomRequest {
+            room_id: room_id.to_string(),
+            password,
+            request_id: Some(request_id.clone()),
+        });
+        self.room_id = None;
+        if self.player_id.is_some() {
+            self.send_join_room();
+        }
+        Ok(request_id)
+    }
+
+    /// ルームを作成して参加
+    ///
+    /// 作成者はそのルームのホストになります。参加できたかどうかはJoinRoomで届きます。
+    ///
+    /// # 引数
+    /// * `options` - 作成するルームの設定
+    ///
+    /// # 戻り値
+    /// 申し込めた場合は要求のID、プレイヤーIDを受け取る前・設定が不正な場合はエラーメッセージ
+    pub fn create_room(&mut self, options: RoomOptions) -> Result<String, String> {
+        let Some(player_id) = self.player_id.clone() else {
+            return Err("サーバーに参加していません".to_string());
+        };
+
+        let request_id = self.next_request_id();
+        let message = WebSocketMessage::CreateRoom {
+            player_id,
+            name: options.name,
+            max_players: options.max_players,
+            password: options.password.clone(),
+            turn_time_limit: options.turn_time_limit,
+            combo_window_seconds: options.combo_window_seconds,
+            power_ups: options.power_ups,
+            shared_board: options.shared_board,
+            request_id: Some(request_id.clone()),
+        };
+        message.validate()?;
+        self.requested_room = None;
+        self.room_id = None;
+        self.created_room_password = Some(options.password);
+        self.send(&message);
+        Ok(request_id)
+    }
+
+    /// ルーム一覧を要求（一覧はRoomListで届く）
+    ///
+    /// # 戻り値
+    /// 要求できた場合は要求のID、プレイヤーIDを受け取る前はエラーメッセージ
+    pub fn request_room_list(&mut self) -> Result<String, String> {
+        let Some(player_id) = self.player_id.clone() else {
+            return Err("サーバーに参加していません".to_string());
+        };
+        let request_id = self.next_request_id();
+        self.send(&WebSocketMessage::GetRoomList {
+            player_id,
+            request_id: Some(request_id.clone()),
+        });
+        Ok(request_id)
+    }
+
+    /// サーバーが記録している同期アカウントの成績の書き出しを要求（署名付きの書き出しはStatsSignedで届く）
+    ///
+    /// # 戻り値
+    /// 要求できた場合は要求のID、プレイヤーIDを受け取る前はエラーメッセージ
+    pub fn sign_stats(&mut self) -> Result<String, String> {
+        let Some(player_id) = self.player_id.clone() else {
+            return Err("サーバーに参加していません".to_string());
+        };
+        let request_id = self.next_request_id();
+        self.send(&WebSocketMessage::SignStats {
+            player_id,
+            request_id: Some(request_id.clone()),
+        });
+        Ok(request_id)
+    }
+
+    /// 別の端末から持ってきた成績の書き出しの署名を、サーバーに確かめてもらう（結果はStatsVerifiedで届く）
+    ///
+    /// 正しいと返ってきた書き出しだけを、GameRuntime::import_statistics()で読み込めます。
+    ///
+    /// # 引数
+    /// * `export` - 署名付きの書き出しのJSON文字列
+    ///
+    /// # 戻り値
+    /// 要求できた場合は要求のID、プレイヤーIDを受け取る前はエラーメッセージ
+    pub fn verify_stats(&mut self, export: &str) -> Result<String, String> {
+        let Some(player_id) = self.player_id.clone() else {
+            return Err("サーバーに参加していません".to_string());
+        };
+        let request_id = self.next_request_id();
+        self.send(&WebSocketMessage::VerifyStats {
+            player_id,
+            export: export.to_string(),
+            request_id: Some(request_id.clone()),
+        });
+        Ok(request_id)
+    }
+
+    /// サーバーが正しいと確かめた署名なら、読み込みに使ったものとして取り除く
+    ///
+    /// # 引数
+    /// * `signature` - 成績の書き出しの署名
+    ///
+    /// # 戻り値
+    /// 確かめた署名だった場合true
+    pub fn take_verified_stats(&mut self, signature: &str) -> bool {
+        let count = self.verified_stats.len();
+        self.verified_stats.retain(|verified| verified != signature);
+        self.verified_stats.len() != count
+    }
+
+    /// サーバーの応答を待つ
+    ///
+    /// 同じ要求のIDが付いた応答が届いた場合はそのメッセージを、同じIDが付いたErrorが届いた場合・
+    /// 切断された場合・REQUEST_TIMEOUT_MS以内に応答がない場合はエラーメッセージを、
+    /// replyに1回だけ渡します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照（期限の計算にゲーム時計を使う）
+    /// * `request_id` - join()・join_room()などが返した要求のID
+    /// * `reply` - 応答を受け取る関数
+    pub fn await_reply(&mut self, world: &World, request_id: &str, reply: Reply) {
+        self.pending.insert(
+            request_id.to_string(),
+            PendingRequest {
+                deadline_ms: clock_of(world).elapsed_ms() + REQUEST_TIMEOUT_MS,
+                reply,
+            },
+        );
+    }
+
+    /// 参加中のルームから退出
+    ///
+    /// # 戻り値
+    /// 退出を申し込めた場合Ok(())、ルームに参加していない場合はエラーメッセージ
+    pub fn leave_room(&mut self) -> Result<(), String> {
+        let request = self
+            .requested_room
+            .take()
+            .ok_or_else(|| "ルームに参加していません".to_string())?;
+        self.room_id = None;
+        if let Some(player_id) = self.player_id.clone() {
+            self.send(&WebSocketMessage::LeaveRoom {
+                room_id: request.room_id,
+                player_id,
+            });
+        }
+        Ok(())
+    }
+
+    /// ゲームアクションを送信
+    ///
+    /// 座標はサーバーが受け付ける範囲（±MAX_COORDINATE）に収めてから送ります。
+    ///
+    /// # 引数
+    /// * `action` - アクションの内容
+    /// * `x` - アクションの位置のX座標（位置がない場合はNone）
+    /// * `y` - アクションの位置のY座標（位置がない場合はNone）
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加できた場合Ok(())、プレイヤーIDを受け取る前・座標が有限でない場合はエラーメッセージ
+    pub fn send_action(
+        &mut self,
+        action: &str,
+        x: Option<f64>,
+        y: Option<f64>,
+    ) -> Result<(), String> {
+        let (Some(player_id), Some(player_name)) =
+            (self.player_id.clone(), self.player_name.clone())
+        else {
+            return Err("サーバーに参加していません".to_string());
+        };
+
+        let message = WebSocketMessage::GameAction {
+            player_id,
+            player_name,
+            action: action.to_string(),
+            x: x.map(protocol::clamp_coordinate).transpose()?,
+            y: y.map(protocol::clamp_coordinate).transpose()?,
+            timestamp: clock::unix_time_ms() as u64,
+        };
+        message.validate()?;
+        self.send(&message);
+        Ok(())
+    }
+
+    /// パワーアップを使う
+    ///
+    /// サーバーが受け付けると同じGameActionが自分にも送り返され、届いたときに盤面に反映します。
+    /// クールダウン中などで断られた場合はErrorが届きます。
+    ///
+    /// # 引数
+    /// * `power_up` - 使うパワーアップ
+    /// * `column` - 覗くタブローの列番号（0から開始、覗く場合のみ）
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(())、ルームに参加していない・列が不正な場合はエラーメッセージ
+    pub fn use_power_up(&mut self, power_up: PowerUp, column: Option<u32>) -> Result<(), String> {
+        if self.room_id.is_none() {
+            return Err("ルームに参加していません".to_string());
+        }
+        self.send_action(&power_up.action(), column.map(f64::from), None)
+    }
+
+    /// カーソル位置を送信
+    ///
+    /// カーソルのチャネルの連番を付けて送ります（受け取りの確認は待たない）。
+    /// 座標はサーバーが受け付ける範囲（±MAX_COORDINATE）に収めてから送ります。
+    /// 前に送ってから接続品質に合わせた間隔が経っていない場合は、最新の位置だけを残して
+    /// 間隔が空いた後のpoll()で送ります。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照（ゲーム時計の読み取り用）
+    /// * `x` - カーソルのX座標
+    /// * `y` - カーソルのY座標
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(true)、間隔が空くまで残した場合Ok(false)、
+    /// プレイヤーIDを受け取る前・座標が有限でない場合はエラーメッセージ
+    pub fn send_cursor(&mut self, world: &World, x: f64, y: f64) -> Result<bool, String> {
+        if self.player_id.is_none() {
+            return Err("サーバーに参加していません".to_string());
+        }
+        let position = (protocol::clamp_coordinate(x)?, protocol::clamp_coordinate(y)?);
+
+        let now_ms = clock_of(world).elapsed_ms();
+        let interval_ms = f64::from(self.update_rates().cursor_interval_ms);
+        if self
+            .last_cursor_sent_ms
+            .is_some_and(|last| now_ms - last < interval_ms)
+        {
+            self.pending_cursor = Some(position);
+            return Ok(false);
+        }
+        self.send_cursor_now(position, now_ms)?;
+        Ok(true)
+    }
+
+    /// 接続品質に合わせた送信間隔
+    pub fn update_rates(&self) -> UpdateRates {
+        self.monitor.quality().update_rates()
+    }
+
+    /// 接続状態の報告（接続品質・往復時間・パケットロス・送信間隔・送信待ちのキューの溜まり具合）
+    pub fn connection_report(&self) -> ConnectionReport {
+        ConnectionReport {
+            queue: self.queue_metrics(),
+            ..self.monitor.report()
+        }
+    }
+
+    /// サーバーの時計とのずれ（サーバーの時刻 - この端末の時刻、ミリ秒。Pongを受け取るまではNone）
+    pub fn clock_offset_ms(&self) -> Option<f64> {
+        self.clock_sync.offset_ms()
+    }
+
+    /// サーバーの時刻をこの端末の時刻に直す（ずれを推定できていない場合はそのまま）
+    pub fn server_to_local_time(&self, server_time_ms: f64) -> f64 {
+        self.clock_sync.to_local_time(server_time_ms)
+    }
+
+    /// 自分のスコアを同じルームの他のプレイヤーに送信
+    ///
+    /// 前回送った値から変わっていない場合は送りません。
+    ///
+    /// # 引数
+    /// * `score` - 現在のスコア
+    /// * `foundation_cards` - ファウンデーションに置いたカードの枚数
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(true)、変わっていない場合Ok(false)、
+    /// ルームに参加していない・値が不正な場合はエラーメッセージ
+    pub fn send_score(&mut self, score: u32, foundation_cards: u16) -> Result<bool, String> {
+        let (Some(player_id), Some(room_id)) = (self.player_id.clone(), self.room_id.clone())
+        else {
+            return Err("ルームに参加していません".to_string());
+        };
+        if self.last_score == Some((score, foundation_cards)) {
+            return Ok(false);
+        }
+
+        let message = WebSocketMessage::ScoreUpdate {
+            room_id,
+            player_id,
+            score,
+            foundation_cards,
+        };
+        message.validate()?;
+        self.send(&message);
+        self.last_score = Some((score, foundation_cards));
+        self.last_score_sent_ms = None;
+        Ok(true)
+    }
+
+    /// 参加中のルームで自分のスコアを送ったかどうか
+    pub fn has_sent_score(&self) -> bool {
+        self.last_score.is_some()
+    }
+
+    /// 自分のカードの裏面を同じルームの他のプレイヤーに見せる
+    ///
+    /// 前回送ったものから変わっていない場合は送りません。
+    ///
+    /// # 引数
+    /// * `card_back` - 見せるカードの裏面（見せるのをやめる場合はNone）
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(true)、変わっていない場合Ok(false)、
+    /// ルームに参加していない場合はエラーメッセージ
+    pub fn send_card_back(&mut self, card_back: Option<CardBack>) -> Result<bool, String> {
+        let (Some(player_id), Some(room_id)) = (self.player_id.clone(), self.room_id.clone())
+        else {
+            return Err("ルームに参加していません".to_string());
+        };
+        if self.last_card_back == card_back {
+            return Ok(false);
+        }
+
+        let message = WebSocketMessage::CardBackChanged {
+            room_id,
+            player_id,
+            card_back,
+        };
+        message.validate()?;
+        self.send(&message);
+        self.last_card_back = card_back;
+        Ok(true)
+    }
+
+    /// 操作していない時間を同じルームの参加者の離席の判定のために送る
+    ///
+    /// IDLE_REPORT_STEP_SECONDSごとの段階が前回送ったものから変わった場合だけ送ります
+    /// （操作を再開して段階が0に戻った場合は0秒を送る）。
+    ///
+    /// # 引数
+    /// * `idle_seconds` - 操作していない時間（秒、SolitaireGameState.idle_time）
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(true)、段階が変わっていない場合Ok(false)、
+    /// ルームに参加していない場合はエラーメッセージ
+    pub fn send_idle_status(&mut self, idle_seconds: f64) -> Result<bool, String> {
+        let (Some(player_id), Some(room_id)) = (self.player_id.clone(), self.room_id.clone())
+        else {
+            return Err("ルームに参加していません".to_string());
+        };
+        let step = afk::idle_report_step(idle_seconds);
+        if self.last_idle_step.unwrap_or(0) == step {
+            return Ok(false);
+        }
+
+        let message = WebSocketMessage::IdleStatus {
+            room_id,
+            player_id,
+            idle_seconds: if step == 0 { 0 } else { idle_seconds as u32 },
+        };
+        message.validate()?;
+        self.send(&message);
+        self.last_idle_step = Some(step);
+        Ok(true)
+    }
+
+    /// 共有盤面のルームで観戦に切り替える・操作できる参加者に戻る
+    ///
+    /// サーバーが受け付けると、自分の立場はPermissionsChangedで届きます。
+    ///
+    /// # 引数
+    /// * `spectating` - trueで観戦に切り替え、falseで操作できる参加者に戻る
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(())、ルームに参加していない場合はエラーメッセージ
+    pub fn set_spectating(&mut self, spectating: bool) -> Result<(), String> {
+        let (Some(player_id), Some(room_id)) = (self.player_id.clone(), self.room_id.clone())
+        else {
+            return Err("ルームに参加していません".to_string());
+        };
+
+        self.send(&WebSocketMessage::SetSpectating {
+            room_id,
+            player_id,
+            spectating,
+        });
+        Ok(())
+    }
+
+    /// 共有盤面のルームでカードの持ち主を決める（ホストのみ）
+    ///
+    /// # 引数
+    /// * `card_id` - カードのID（permissions::card_id()）
+    /// * `owner_id` - 持ち主のプレイヤーID（持ち主を外す場合はNone）
+    ///
+    /// # 戻り値
+    /// 送信待ちに追加した場合Ok(())、ルームに参加していない・IDが不正な場合はエラーメッセージ
+    pub fn set_card_owner(&mut self, card_id: String, owner_id: Option<String>) -> Result<(), String> {
+        let (Some(player_id), Some(room_id)) = (self.player_id.clone(), self.room_id.clone())
+        else {
+            return Err("ルームに参加していません".to_string());
+        };
+
+        let message = WebSocketMessage::SetCardOwner {
+            room_id,
+            player_id,
+            card_id,
+            owner_id,
+        };
+        message.validate()?;
+        self.send(&message);
+        Ok(())
+    }
+
+    /// 届いたメッセージを購読
+    ///
+    /// # 引数
+    /// * `message_type` - 受け取るメッセージの種類（"RoomList"など、ALL_MESSAGESの場合はすべて）
+    /// * `handler` - 届いたメッセージを受け取る関数
+    ///
+    /// # 戻り値
+    /// 購読の解除に使う購読ID
+    pub fn subscribe(&mut self, message_type: &str, handler: MessageHandler) -> SubscriptionId {
+        let id = self.next_subscription_id;
+        self.next_subscription_id += 1;
+        self.subscriptions.push(Subscription {
+            id,
+            message_type: message_type.to_string(),
+            handler,
+        });
+        id
+    }
+
+    /// 購読を解除
+    ///
+    /// # 戻り値
+    /// 解除できた場合true、存在しない購読IDの場合false
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let count = self.subscriptions.len();
+        self.subscriptions
+            .retain(|subscription| subscription.id != id);
+        self.subscriptions.len() != count
+    }
+
+    /// サーバーから届いたメッセージを処理
+    ///
+    /// プレイヤーID・参加中のルームを更新してから、購読者へ渡し、
+    /// MessageProcessingSystemが扱う種類のメッセージはワールドにも追加します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `text` - 届いたテキスト（JSON）
+    ///
+    /// # 戻り値
+    /// 処理できた場合Ok(())、形式が不正な場合はエラーメッセージ
+    pub fn receive(&mut self, world: &mut World, text: &str) -> Result<(), String> {
+        let message = WebSocketMessage::parse(text)?;
+
+        let clock = clock_of(world);
+        if let Some(connection) = self.connection_mut(world) {
+            connection.increment_received(&clock);
+        }
+
+        // 受け取りの確認はここで処理し、Reliableで届いたメッセージは確認を返してから中身を処理する
+        let (message, payload) = match message {
+            WebSocketMessage::Ack { message_id } => {
+                if !self.reliable.ack(&message_id) {
+                    debug!("🔁 待っていないメッセージの受け取りの確認: {}", message_id);
+                }
+                return Ok(());
+            }
+            WebSocketMessage::Reliable {
+                message_id,
+                message,
+                ..
+            } => {
+                self.send(&WebSocketMessage::Ack {
+                    message_id: message_id.clone(),
+                });
+                if !self.recent_ids.is_new(&message_id) {
+                    debug!("🔁 重複したメッセージを無視: {}", message_id);
+                    return Ok(());
+                }
+                let payload = serde_json::to_string(&message).map_err(|e| e.to_string())?;
+                (*message, payload)
+            }
+            WebSocketMessage::ResendRequest { channel, sequences } => {
+                debug!("📭 再送の要求: {} {:?}", channel.as_str(), sequences);
+                self.resend_sequences(channel, &sequences);
+                return Ok(());
+            }
+            message => (message, text.to_string()),
+        };
+
+        // 他のプレイヤーのカーソル位置は、新しい位置より後に届いた古い位置を捨てる
+        if let WebSocketMessage::MousePosition {
+            player_id,
+            sequence: Some(sequence),
+            ..
+        } = &message
+        {
+            let arrival = self
+                .cursor_sequences
+                .entry(player_id.clone())
+                .or_default()
+                .record(Channel::Cursor, *sequence);
+            if matches!(arrival, Arrival::Late | Arrival::Stale) {
+                debug!("🗑️ 古いカーソル位置を破棄: {}", player_id);
+                return Ok(());
+            }
+        }
+
+        if let WebSocketMessage::Pong {
+            ping_id,
+            client_time_ms,
+            server_time_ms,
+        } = &message
+        {
+            self.record_pong(world, *ping_id, *client_time_ms, *server_time_ms);
+        }
+
+        self.track_session(&message);
+        self.settle_request(&message);
+        scoreboard::apply(world, self.player_id.as_deref(), &message);
+        power_up::apply(world, self.player_id.as_deref(), &message);
+        permissions::apply(world, self.player_id.as_deref(), &message);
+
+        let message_type = type_name(&message);
+        for subscription in &mut self.subscriptions {
+            if subscription.message_type == ALL_MESSAGES
+                || subscription.message_type == message_type
+            {
+                (subscription.handler)(&message);
+            }
+        }
+
+        if let Some(network_type) = network_message_type(&message) {
+            NetworkManager::send_message(world, network_type, payload, None, None);
+        }
+        Ok(())
+    }
+
+    /// 送信待ちのメッセージを取り出す（JavaScript側がWebSocketを持つ場合）
+    ///
+    /// # 戻り値
+    /// 送信するメッセージ（JSON文字列）
+    pub fn take_outgoing(&mut self, world: &mut World) -> Vec<String> {
+        let messages = std::mem::take(&mut self.outgoing);
+        let clock = clock_of(world);
+        self.reliable.mark_sent(clock.elapsed_ms());
+        if let Some(connection) = self.connection_mut(world) {
+            for _ in &messages {
+                connection.increment_sent(&clock);
+            }
+        }
+        messages
+    }
+
+    /// 1フレーム分の通信を処理
+    ///
+    /// WebSocketの接続状態を反映し、届いたメッセージを処理して、送信待ちのメッセージを送ります。
+    /// 受け取りの確認が届かないメッセージは送信待ちに戻して再送します。
+    /// 送信間隔が空くのを待っていたカーソル位置と、キーフレームの時刻になったスコア、
+    /// 接続品質を測るPingもここで送ります。
+    /// NetworkConnectionSystemが再接続すると判定した場合は、接続し直します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    pub fn poll(&mut self, world: &mut World) {
+        self.expire_requests(world);
+        self.queue_retries(world);
+        self.send_queued(world);
+        self.send_pending_cursor(world);
+        self.send_score_keyframe(world);
+        self.send_ping(world);
+
+        #[cfg(feature = "wasm")]
+        {
+            let status = self.status(world);
+            let Some(socket) = self.socket.as_mut() else {
+                return;
+            };
+
+            if status == ConnectionStatus::Reconnecting {
+                if let Err(e) = socket.connect() {
+                    warn!("⚠️ 再接続できません: {}", e);
+                }
+            }
+            let socket_status = socket.get_status();
+            let received = socket.take_received();
+            if socket_status != status {
+                self.set_status(world, socket_status);
+            }
+
+            for text in received {
+                if let Err(e) = self.receive(world, &text) {
+                    warn!("⚠️ サーバーからのメッセージを処理できません: {}", e);
+                }
+            }
+
+            if self.status(world) == ConnectionStatus::Connected {
+                // 接続が切れている間にWebSocketManagerに溜まったメッセージから先に送る
+                if let Some(socket) = self.socket.as_mut() {
+                    socket.flush_message_queue();
+                }
+                for text in self.take_outgoing(world) {
+                    let Some(socket) = self.socket.as_mut() else {
+                        break;
+                    };
+                    if let Err(e) = socket.send_text(text) {
+                        warn!("⚠️ メッセージを送信できません: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 現在の接続状態
+    pub fn status(&self, world: &World) -> ConnectionStatus {
+        self.connection
+            .and_then(|connection| world.get_component::<NetworkConnection>(connection))
+            .map_or(ConnectionStatus::Disconnected, |connection| {
+                connection.status
+            })
+    }
+
+    /// サーバーが割り当てたプレイヤーID
+    pub fn player_id(&self) -> Option<&str> {
+        self.player_id.as_deref()
+    }
+
+    /// 参加中のルームのID
+    pub fn room_id(&self) -> Option<&str> {
+        self.room_id.as_deref()
+    }
+
+    /// ターン制のルームで現在ターンのプレイヤーID
+    pub fn turn_player_id(&self) -> Option<&str> {
+        self.turn_player_id.as_deref()
+    }
+
+    /// ターン制のルームで他のプレイヤーのターン中かどうか
+    pub fn is_opponents_turn(&self) -> bool {
+        self.turn_player_id
+            .as_deref()
+            .is_some_and(|player_id| self.player_id.as_deref() != Some(player_id))
+    }
+
+    /// 接続の状態を記録するエンティティ
+    pub fn connection(&self) -> Option<Entity> {
+        self.connection
+    }
+
+    /// 受け取りの確認（Ack）を待っているメッセージの数
+    pub fn unacked_count(&self) -> usize {
+        self.reliable.pending_count()
+    }
+
+    /// WebSocketの送信待ちのキューの溜まり具合と、溜めた・捨てた・送り直した数
+    ///
+    /// 接続していない場合は、空のキューの値を返します。
+    pub fn queue_metrics(&self) -> QueueMetrics {
+        #[cfg(feature = "wasm")]
+        if let Some(socket) = &self.socket {
+            return socket.queue_metrics();
+        }
+        SendQueue::default().metrics()
+    }
+
+    /// WebSocketを閉じ、接続の記録を削除
+    fn close_connection(&mut self, world: &mut World) {
+        #[cfg(feature = "wasm")]
+        if let Some(mut socket) = self.socket.take() {
+            socket.disconnect();
+        }
+
+        if let Some(connection) = self.connection.take() {
+            world.remove_entity(connection);
+            info!("🔌 サーバーとの接続を終了しました");
+        }
+        self.monitor.set_connected(false);
+        self.player_id = None;
+        self.room_id = None;
+        self.reliable.clear();
+        self.sequences.reset();
+        self.cursor_sequences.clear();
+        self.last_score = None;
+        self.last_cursor_sent_ms = None;
+        self.pending_cursor = None;
+        self.last_card_back = None;
+        self.last_idle_step = None;
+        scoreboard::clear(world);
+        self.reject_requests("サーバーとの接続を終了しました");
+    }
+
+    /// カーソル位置を連番を付けて送信待ちに追加
+    fn send_cursor_now(&mut self, (x, y): (f64, f64), now_ms: f64) -> Result<(), String> {
+        let Some(player_id) = self.player_id.clone() else {
+            return Err("サーバーに参加していません".to_string());
+        };
+        let message = WebSocketMessage::MousePosition {
+            player_id,
+            x,
+            y,
+            timestamp: clock::unix_time_ms() as u64,
+            sequence: Some(self.sequences.next(Channel::Cursor)),
+        };
+        message.validate()?;
+        self.send(&message);
+        self.last_cursor_sent_ms = Some(now_ms);
+        self.pending_cursor = None;
+        Ok(())
+    }
+
+    /// 送信間隔が空くのを待っていたカーソル位置を、間隔が空いていれば送る
+    fn send_pending_cursor(&mut self, world: &World) {
+        let Some(position) = self.pending_cursor else {
+            return;
+        };
+        let now_ms = clock_of(world).elapsed_ms();
+        let interval_ms = f64::from(self.update_rates().cursor_interval_ms);
+        if self
+            .last_cursor_sent_ms
+            .is_some_and(|last| now_ms - last < interval_ms)
+        {
+            return;
+        }
+        if let Err(e) = self.send_cursor_now(position, now_ms) {
+            debug!("🗑️ 待っていたカーソル位置を送れません: {}", e);
+            self.pending_cursor = None;
+        }
+    }
+
+    /// ルームで最後に送ったスコアを、キーフレームの間隔が経っていれば変わっていなくても送り直す
+    ///
+    /// 途中で届かなかった更新があっても、同じルームの他のプレイヤーの表示が追いつくようにします。
+    fn send_score_keyframe(&mut self, world: &World) {
+        let (Some(player_id), Some(room_id), Some((score, foundation_cards))) =
+            (self.player_id.clone(), self.room_id.clone(), self.last_score)
+        else {
+            return;
+        };
+        let now_ms = clock_of(world).elapsed_ms();
+        let Some(last_sent_ms) = self.last_score_sent_ms else {
+            self.last_score_sent_ms = Some(now_ms);
+            return;
+        };
+        if now_ms - last_sent_ms < f64::from(self.update_rates().keyframe_interval_ms) {
+            return;
+        }
+
+        debug!("🔑 スコアのキーフレーム: {} ({}枚)", score, foundation_cards);
+        self.send(&WebSocketMessage::ScoreUpdate {
+            room_id,
+            player_id,
+            score,
+            foundation_cards,
+        });
+        self.last_score_sent_ms = Some(now_ms);
+    }
+
+    /// Pingを送る時刻になっていれば、推定した時計のずれを付けて送信待ちに追加
+    ///
+    /// 応答のないまま時間切れになったPingは、ここで失われたものとして数えます。
+    fn send_ping(&mut self, world: &World) {
+        let clock = clock_of(world);
+        let Some(ping_id) = self.monitor.tick(clock.elapsed_ms()) else {
+            return;
+        };
+        self.send(&WebSocketMessage::Ping {
+            ping_id,
+            client_time_ms: clock.now_ms(),
+            clock_offset_ms: self.clock_sync.offset_ms().map(|offset| offset.round() as i64),
+        });
+    }
+
+    /// 届いたPongから往復時間とサーバーの時計とのずれを記録
+    ///
+    /// 時間切れ後や重複したPongは、時計のずれの推定にも使いません。
+    fn record_pong(&mut self, world: &World, ping_id: u32, client_time_ms: u64, server_time_ms: u64) {
+        let clock = clock_of(world);
+        let Some(rtt_ms) = self.monitor.record_pong(ping_id, clock.elapsed_ms()) else {
+            debug!("🏓 待っていないPong: {}", ping_id);
+            return;
+        };
+        let offset_ms = self.clock_sync.record(
+            client_time_ms as f64,
+            server_time_ms as f64,
+            clock.now_ms() as f64,
+        );
+        debug!("🏓 往復時間: {:.0}ms, 時計のずれ: {:?}ms", rtt_ms, offset_ms);
+    }
+
+    /// メッセージを送信待ちに追加
+    ///
+    /// 受け取りの確認が必要なメッセージはReliableで包み、確認が届くまで再送の対象にします。
+    fn send(&mut self, message: &WebSocketMessage) {
+        if !needs_ack(message) {
+            self.push_outgoing(message);
+            return;
+        }
+
+        let channel = message.channel();
+        let sequence = self.sequences.next(channel);
+        let message_id = self.message_id(channel, sequence);
+        let reliable = WebSocketMessage::Reliable {
+            message_id: message_id.clone(),
+            message: Box::new(message.clone()),
+            sequence: Some(sequence),
+        };
+        if let Some(text) = self.push_outgoing(&reliable) {
+            self.reliable.track(message_id, text);
+        }
+    }
+
+    /// Reliableで包むメッセージのID
+    ///
+    /// チャネルと連番から決まるため、再送を要求された連番のメッセージを探せます。
+    fn message_id(&self, channel: Channel, sequence: u32) -> String {
+        format!("{}_{}_{}", self.message_prefix, channel.as_str(), sequence)
+    }
+
+    /// 再送を要求された連番のメッセージを送信待ちに戻す
+    fn resend_sequences(&mut self, channel: Channel, sequences: &[u32]) {
+        for &sequence in sequences {
+            let message_id = self.message_id(channel, sequence);
+            match self.reliable.unacked_text(&message_id) {
+                Some(text) => self.outgoing.push(text.to_string()),
+                None => warn!("⚠️ 再送できないメッセージです: {}", message_id),
+            }
+        }
+    }
+
+    /// メッセージをJSONに変換して送信待ちに追加
+    ///
+    /// # 戻り値
+    /// 追加したテキスト、変換できない場合はNone
+    fn push_outgoing(&mut self, message: &WebSocketMessage) -> Option<String> {
+        match serde_json::to_string(message) {
+            Ok(text) => {
+                self.outgoing.push(text.clone());
+                Some(text)
+            }
+            Err(e) => {
+                warn!("⚠️ メッセージを変換できません: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 受け取りの確認が届かないメッセージを送信待ちに戻す（再送の上限に達したものは諦める）
+    fn queue_retries(&mut self, world: &World) {
+        let retries = self.reliable.due(clock_of(world).elapsed_ms());
+        self.outgoing.extend(retries.resend);
+        for message_id in retries.given_up {
+            warn!(
+                "⚠️ 受け取りの確認が届かないため再送をやめます: {}",
+                message_id
+            );
+        }
+    }
+
+    /// ワールドの送信キューに溜まったメッセージを送信待ちに追加
+    ///
+    /// ゲーム結果は参加中のプレイヤーIDを付けたGameResultとして送り（ターン制のルームの手番も忘れる）、
+    /// それ以外はサーバーとの共通形式のJSONとして解析できたものだけを送ります。
+    fn send_queued(&mut self, world: &mut World) {
+        let queued = NetworkManager::take_outbound(world);
+        if queued.is_empty() {
+            return;
+        }
+
+        for queued_message in &queued {
+            let message = match queued_message.message_type {
+                MessageType::GameResult => {
+                    // ゲームが終わったので、ターン制のルームの手番は次のゲームのTurnStartedまで持たない
+                    self.turn_player_id = None;
+                    match (
+                        self.player_id.clone(),
+                        serde_json::from_str(&queued_message.payload),
+                    ) {
+                        (Some(player_id), Ok(result)) => {
+                            Ok(WebSocketMessage::GameResult { player_id, result })
+                        }
+                        (None, _) => Err("まだ参加していません".to_string()),
+                        (_, Err(e)) => Err(e.to_string()),
+                    }
+                }
+                _ => WebSocketMessage::parse(&queued_message.payload),
+            };
+            match message {
+                Ok(message) => self.send(&message),
+                Err(e) => debug!(
+                    "🗑️ 送信キューのメッセージを送れません: {} ({})",
+                    queued_message.message_type.as_str(),
+                    e
+                ),
+            }
+        }
+
+        if let Some(pool) = world.get_resource_mut::<NetworkMessagePool>() {
+            for message in queued {
+                pool.release(message);
+            }
+        }
+    }
+
+    /// 表示名とセッショントークンを付けて参加を申し込む
+    fn send_player_join(&mut self) {
+        let Some(player_name) = self.player_name.clone() else {
+            return;
+        };
+        self.send(&WebSocketMessage::PlayerJoin {
+            player_id: String::new(),
+            player_name,
+            player_index: 0,
+            session_token: self.session_token.clone(),
+            request_id: self.join_request_id.clone(),
+        });
+    }
+
+    /// 申し込んだルームへの参加を送る
+    fn send_join_room(&mut self) {
+        let (Some(player_id), Some(request)) =
+            (self.player_id.clone(), self.requested_room.clone())
+        else {
+            return;
+        };
+        self.send(&WebSocketMessage::JoinRoom {
+            room_id: request.room_id,
+            player_id,
+            password: request.password,
+            request_id: request.request_id,
+        });
+    }
+
+    /// 接続状態を変更し、JavaScriptへ通知する
+    fn set_status(&mut self, world: &mut World, status: ConnectionStatus) {
+        let Some(connection) = self.connection else {
+            return;
+        };
+        let previous = self.status(world);
+        if previous == status {
+            return;
+        }
+        NetworkManager::update_connection_status(world, connection, status);
+        self.monitor.set_connected(status == ConnectionStatus::Connected);
+        if let Some(events) = world.get_resource_mut::<EventQueue>() {
+            events.push(GameEvent::ConnectionChanged {
+                status: status.as_str().to_string(),
+            });
+        }
+
+        match status {
+            ConnectionStatus::Connected => {
+                // 切れていた間に溜まった手のアニメーションは再生せず、今の盤面まで飛ばす
+                if previous == ConnectionStatus::Reconnecting {
+                    animation_queue::fast_forward(world);
+                }
+                // 接続し直した場合も、同じ表示名で参加し直す（プレイヤーIDは新しく割り当てられる）
+                self.outgoing.clear();
+                self.reliable.clear();
+                self.sequences.reset();
+                self.cursor_sequences.clear();
+                self.send_player_join();
+            }
+            ConnectionStatus::Disconnected | ConnectionStatus::Error | ConnectionStatus::Closed => {
+                self.player_id = None;
+                self.room_id = None;
+                self.last_score = None;
+                self.last_cursor_sent_ms = None;
+                self.pending_cursor = None;
+                self.last_card_back = None;
+                self.last_idle_step = None;
+                scoreboard::clear(world);
+                self.reject_requests("サーバーとの接続が切れました");
+            }
+            _ => {}
+        }
+    }
+
+    /// 届いたメッセージからプレイヤーID・参加中のルームを更新
+    fn track_session(&mut self, message: &WebSocketMessage) {
+        let own_id = self.player_id.clone();
+        let is_own = |player_id: &str| own_id.as_deref() == Some(player_id);
+        match message {
+            WebSocketMessage::PlayerProfile { profile, .. } if self.player_id.is_none() => {
+                info!("🪪 プレイヤーID: {}", profile.player_id);
+                self.player_id = Some(profile.player_id.clone());
+                self.send_join_room();
+            }
+            WebSocketMessage::SessionToken { session_token } => {
+                self.session_token = Some(session_token.clone());
+            }
+            WebSocketMessage::PlayerLeft { player_id, .. } => {
+                self.cursor_sequences.remove(player_id);
+            }
+            WebSocketMessage::JoinRoom {
+                room_id, player_id, ..
+            } if is_own(player_id) => {
+                debug!("🚪 ルームに参加しました: {}", room_id);
+                self.room_id = Some(room_id.clone());
+                self.last_score = None;
+                self.last_card_back = None;
+                self.last_idle_step = None;
+                self.turn_player_id = None;
+
+                // 作成したルーム・クイックマッチのルームにも、接続し直したときに戻る
+                let requested = self
+                    .requested_room
+                    .as_ref()
+                    .is_some_and(|request| &request.room_id == room_id);
+                if !requested {
+                    self.requested_room = Some(RoomRequest {
+                        room_id: room_id.clone(),
+                        password: self.created_room_password.take().flatten(),
+                        request_id: None,
+                    });
+                }
+            }
+            WebSocketMessage::RoomRestored { room_id, .. } => {
+                self.room_id = Some(room_id.clone());
+                self.last_score = None;
+                self.last_card_back = None;
+                self.last_idle_step = None;
+                self.turn_player_id = None;
+                self.requested_room = Some(RoomRequest {
+                    room_id: room_id.clone(),
+                    password: None,
+                    request_id: None,
+                });
+            }
+            WebSocketMessage::LeaveRoom { player_id, .. }
+            | WebSocketMessage::Kicked { player_id, .. }
+                if is_own(player_id) =>
+            {
+                self.room_id = None;
+                self.requested_room = None;
+                self.turn_player_id = None;
+            }
+            WebSocketMessage::TurnStarted {
+                room_id, player_id, ..
+            } if self.room_id.as_deref() == Some(room_id.as_str()) => {
+                self.turn_player_id = Some(player_id.clone());
+            }
+            WebSocketMessage::StatsVerified {
+                signature: Some(signature),
+                ..
+            } => {
+                self.verified_stats.insert(0, signature.clone());
+                self.verified_stats.truncate(MAX_VERIFIED_STATS);
+            }
+            _ => {}
+        }
+    }
+
+    /// 届いたメッセージが応答を待っている要求への応答なら、その要求を完了する
+    fn settle_request(&mut self, message: &WebSocketMessage) {
+        let Some(pending) = message
+            .request_id()
+            .and_then(|request_id| self.pending.remove(request_id))
+        else {
+            return;
+        };
+
+        match message {
+            WebSocketMessage::Error { message, .. } => (pending.reply)(Err(message.clone())),
+            _ => (pending.reply)(Ok(message.clone())),
+        }
+    }
+
+    /// 期限までに応答がなかった要求をエラーとして完了する
+    fn expire_requests(&mut self, world: &World) {
+        let now = clock_of(world).elapsed_ms();
+        let (expired, pending): (HashMap<_, _>, HashMap<_, _>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(_, pending)| pending.deadline_ms <= now);
+        self.pending = pending;
+
+        for (request_id, pending) in expired {
+            warn!("⏰ サーバーからの応答がありません: {}", request_id);
+            (pending.reply)(Err("サーバーからの応答がありません".to_string()));
+        }
+    }
+
+    /// 応答を待っているすべての要求をエラーとして完了する
+    fn reject_requests(&mut self, reason: &str) {
+        for (_, pending) in std::mem::take(&mut self.pending) {
+            (pending.reply)(Err(reason.to_string()));
+        }
+    }
+
+    /// 新しい要求のIDを発行
+    fn next_request_id(&mut self) -> String {
+        let request_id = format!("{}_{}", self.request_prefix, self.next_request_number);
+        self.next_request_number += 1;
+        request_id
+    }
+
+    /// 接続の記録を取得（可変参照）
+    fn connection_mut<'a>(&self, world: &'a mut World) -> Option<&'a mut NetworkConnection> {
+        world.get_component_mut::<NetworkConnection>(self.connection?)
+    }
+}
+
+impl Default for NetworkClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// ワールドのゲーム時計を取得
+fn clock_of(world: &World) -> clock::GameClock {
+    clock::GameClock::from_world(world)
+}
+
+/// メッセージの種類名（JSONの"type"フィールドの値）
+///
+/// # 引数
+/// * `message` - メッセージ
+///
+/// # 戻り値
+/// "RoomList"などの種類名
+fn type_name(message: &WebSocketMessage) -> String {
+    serde_json::to_value(message)
+        .ok()
+        .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 受け取りの確認（Ack）が必要なメッセージかどうか
+///
+/// 次の値で上書きされるメッセージ（優先度Low）と、往復時間を測るPing/Pongは再送しません。
+fn needs_ack(message: &WebSocketMessage) -> bool {
+    match message {
+        WebSocketMessage::Ping { .. }
+        | WebSocketMessage::Pong { .. }
+        | WebSocketMessage::Ack { .. }
+        | WebSocketMessage::Reliable { .. } => false,
+        _ => transport::message_priority(message) >= MessagePriority::Normal,
+    }
+}
+
+/// MessageProcessingSystemで処理するメッセージの種類
+///
+/// # 戻り値
+/// ワールドに追加する種類、購読者へ渡すだけのメッセージはNone
+fn network_message_type(message: &WebSocketMessage) -> Option<MessageType> {
+    match message {
+        WebSocketMessage::PlayerJoin { .. } | WebSocketMessage::PlayerLeft { .. } => {
+            Some(MessageType::PlayerJoinLeave)
+        }
+        WebSocketMessage::Reaction { .. } => Some(MessageType::Reaction),
+        WebSocketMessage::Pong { .. } => Some(MessageType::Pong),
+        WebSocketMessage::RoomSettingsChanged { .. } => Some(MessageType::GameSettings),
+        WebSocketMessage::GameResult { .. } => Some(MessageType::GameResult),
+        WebSocketMessage::Error { .. } => Some(MessageType::Error),
+        _ => None,
+    }
+}
@@ -0,0 +1,284 @@
+// =============================================================================
+// 通信状態の再現（ネットワークシミュレーション）
+// =============================================================================
+// このファイルでは、メッセージの受け渡しに遅延・揺らぎ・順序の入れ替わり・欠落を
+// 意図的に加えるNetworkConditionerを実装します。
+// 遅い回線や不安定な回線での予測・補正や再接続の処理を、テストで再現できるようにします。
+//
+// 仕組み：
+// - send()で渡したメッセージは、遅延と揺らぎを足した時刻まで手元に留める
+// - 欠落させるかどうか・揺らぎ・入れ替えの判定はシード付きの乱数で決めるため、
+//   同じシードと同じ操作からは常に同じ結果になる
+// - 順序を入れ替えるメッセージは、次のメッセージが届くまで（最大MAX_REORDER_HOLD_MS）留める
+// - NetworkConditionerSystemは、ワールドにNetworkConditionerがある場合のみ
+//   受信キューのメッセージを通し、MessageProcessingSystemへ渡す時刻を遅らせる
+//
+// 時間は呼び出し側から渡すため、ブラウザ・ネイティブのどちらでも同じように動作します。
+// =============================================================================
+
+use crate::ecs::{Resource, System, World};
+use crate::network::{NetworkMessage, NetworkQueues};
+use crate::rng::Rng;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// 順序を入れ替えるメッセージを、次のメッセージを待って留めておく時間の上限（ミリ秒）
+pub const MAX_REORDER_HOLD_MS: f64 = 500.0;
+
+/// 再現する通信状態の設定
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct ConditionerSettings {
+    /// 遅延（ミリ秒）
+    pub latency_ms: f64,
+
+    /// 遅延の揺らぎ（ミリ秒、遅延の前後にこの幅で均等にばらつく）
+    pub jitter_ms: f64,
+
+    /// 次のメッセージと順序を入れ替える割合（0.0〜1.0）
+    pub reorder_rate: f64,
+
+    /// メッセージを欠落させる割合（0.0〜1.0）
+    pub drop_rate: f64,
+
+    /// 乱数のシード（同じシードなら同じ結果になる）
+    pub seed: u32,
+}
+
+impl ConditionerSettings {
+    /// 設定値が有効な範囲にあるか検証する
+    ///
+    /// # 戻り値
+    /// 有効な場合Ok(())、無効な場合はエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("latency_ms", self.latency_ms),
+            ("jitter_ms", self.jitter_ms),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                return Err(format!(
+                    "{}は0以上の数値を指定してください: {}",
+                    name, value
+                ));
+            }
+        }
+        for (name, value) in [
+            ("reorder_rate", self.reorder_rate),
+            ("drop_rate", self.drop_rate),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!(
+                    "{}は0.0〜1.0の範囲で指定してください: {}",
+                    name, value
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 配達待ちのメッセージ
+#[derive(Debug, Clone)]
+struct InFlight<T> {
+    /// 届ける時刻（ミリ秒）
+    deliver_at_ms: f64,
+
+    /// 送った順番（同じ時刻に届くメッセージの順序を決める）
+    sequence: u64,
+
+    /// 次のメッセージと順序を入れ替えるかどうか
+    reorder: bool,
+
+    /// メッセージ本体
+    item: T,
+}
+
+/// 通信状態を再現する中継器
+///
+/// 型引数`T`は中継するメッセージの型です。ECSの受信キューでは
+/// `NetworkMessage`を、テストでは任意の型（PingのIDなど）を中継できます。
+#[derive(Debug, Clone)]
+pub struct NetworkConditioner<T> {
+    /// 再現する通信状態
+    settings: ConditionerSettings,
+
+    /// 欠落・揺らぎ・入れ替えの判定に使う乱数生成器
+    rng: Rng,
+
+    /// 中継器の中の現在時刻（ミリ秒）
+    now_ms: f64,
+
+    /// 次に送るメッセージの順番
+    next_sequence: u64,
+
+    /// 配達待ちのメッセージ
+    in_flight: Vec<InFlight<T>>,
+
+    /// 順序を入れ替えるために留めているメッセージ（留め始めた時刻）
+    held: Option<(f64, T)>,
+
+    /// これまでに欠落させたメッセージの数
+    dropped: u64,
+}
+
+impl<T: Send + Sync + 'static> Resource for NetworkConditioner<T> {}
+
+impl<T> NetworkConditioner<T> {
+    /// 通信状態を指定して中継器を作成
+    ///
+    /// # 引数
+    /// * `settings` - 再現する通信状態
+    ///
+    /// # 戻り値
+    /// 新しいNetworkConditioner、設定値が無効な場合はエラーメッセージ
+    pub fn new(settings: ConditionerSettings) -> Result<Self, String> {
+        settings.validate()?;
+        Ok(Self {
+            rng: Rng::new(settings.seed as u64),
+            settings,
+            now_ms: 0.0,
+            next_sequence: 0,
+            in_flight: Vec::new(),
+            held: None,
+            dropped: 0,
+        })
+    }
+
+    /// 再現している通信状態
+    pub fn settings(&self) -> &ConditionerSettings {
+        &self.settings
+    }
+
+    /// これまでに欠落させたメッセージの数
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped
+    }
+
+    /// まだ届けていないメッセージの数
+    pub fn pending_count(&self) -> usize {
+        self.in_flight.len() + usize::from(self.held.is_some())
+    }
+
+    /// メッセージを送る（現在時刻から遅延後に届く）
+    ///
+    /// # 引数
+    /// * `item` - 送るメッセージ
+    ///
+    /// # 戻り値
+    /// 送れた場合true、欠落させた場合false
+    pub fn send(&mut self, item: T) -> bool {
+        if self.rng.next_f64() < self.settings.drop_rate {
+            self.dropped += 1;
+            return false;
+        }
+
+        let jitter = (self.rng.next_f64() * 2.0 - 1.0) * self.settings.jitter_ms;
+        let delay_ms = (self.settings.latency_ms + jitter).max(0.0);
+        let reorder = self.rng.next_f64() < self.settings.reorder_rate;
+
+        self.in_flight.push(InFlight {
+            deliver_at_ms: self.now_ms + delay_ms,
+            sequence: self.next_sequence,
+            reorder,
+            item,
+        });
+        self.next_sequence += 1;
+        true
+    }
+
+    /// 時間を進め、届く時刻になったメッセージを取り出す
+    ///
+    /// # 引数
+    /// * `elapsed_ms` - 進める時間（ミリ秒）
+    ///
+    /// # 戻り値
+    /// 届いたメッセージ（届いた順）
+    pub fn advance(&mut self, elapsed_ms: f64) -> Vec<T> {
+        self.now_ms += elapsed_ms.max(0.0);
+        let now_ms = self.now_ms;
+
+        let (mut due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.in_flight)
+            .into_iter()
+            .partition(|message| message.deliver_at_ms <= now_ms);
+        self.in_flight = waiting;
+        due.sort_by(|a, b| {
+            a.deliver_at_ms
+                .total_cmp(&b.deliver_at_ms)
+                .then(a.sequence.cmp(&b.sequence))
+        });
+
+        let mut delivered = Vec::with_capacity(due.len());
+        for message in due {
+            if message.reorder && self.held.is_none() {
+                // 次のメッセージに追い越させる
+                self.held = Some((message.deliver_at_ms, message.item));
+                continue;
+            }
+            delivered.push(message.item);
+            if let Some((_, held)) = self.held.take() {
+                delivered.push(held);
+            }
+        }
+
+        // 後続のメッセージが来ないまま待ちすぎた場合はそのまま届ける
+        if self
+            .held
+            .as_ref()
+            .is_some_and(|(held_at, _)| now_ms - held_at >= MAX_REORDER_HOLD_MS)
+        {
+            if let Some((_, held)) = self.held.take() {
+                delivered.push(held);
+            }
+        }
+        delivered
+    }
+}
+
+/// 受信キューに通信状態を再現するシステム
+///
+/// ワールドに`NetworkConditioner<NetworkMessage>`がある場合、受信キューのメッセージを
+/// いったん預かり、届く時刻になったものだけを受信キューに戻します。
+/// MessageProcessingSystemの直前に登録してください。
+pub struct NetworkConditionerSystem;
+
+impl System for NetworkConditionerSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        if !world.has_resource::<NetworkConditioner<NetworkMessage>>() {
+            return;
+        }
+
+        // このフレームで受信キューに入ったメッセージを預かる
+        let messages = match world.get_resource_mut::<NetworkQueues>() {
+            Some(queues) => queues.inbound.drain(),
+            None => Vec::new(),
+        };
+
+        let delivered = match world.get_resource_mut::<NetworkConditioner<NetworkMessage>>() {
+            Some(conditioner) => {
+                for message in messages {
+                    if !conditioner.send(message) {
+                        debug!("🕳️ 通信状態の再現: メッセージを欠落させました");
+                    }
+                }
+                conditioner.advance(delta_time * 1000.0)
+            }
+            None => return,
+        };
+
+        if delivered.is_empty() {
+            return;
+        }
+        if !world.has_resource::<NetworkQueues>() {
+            world.insert_resource(NetworkQueues::new());
+        }
+        if let Some(queues) = world.get_resource_mut::<NetworkQueues>() {
+            for message in delivered {
+                if queues.inbound.push(message).is_err() {
+                    debug!("🕳️ 通信状態の再現: 受信キューが上限に達したため捨てました");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// =============================================================================
+// ゲーム進行の通知
+// =============================================================================
+// このファイルでは、ソリティアの進行を見守り、プレイヤーに知らせるべき出来事を
+// 通知イベント（GameEvent::Notification）としてJavaScriptへ送るシステムを実装します。
+//
+// 通知する出来事：
+// - ウェイストをデッキに戻して減点された
+// - 打てる手がなくなった（手詰まり、確認が必要なので自動では閉じない）
+//
+// 前回見た手数やデッキの周回数はゲーム状態エンティティのNotificationWatchに保持します。
+// =============================================================================
+
+use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent, NotificationSeverity};
+use crate::hint::{HintEngine, HintKind};
+use crate::solitaire::{
+    CardLocation, MoveLog, SolitaireGameState, DECK_TURN_PENALTY, FREE_DECK_TURNS,
+};
+use log::info;
+
+/// 通知済みの状態コンポーネント
+///
+/// 同じ出来事を何度も通知しないよう、前回確認した時点の状態を保持します。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotificationWatch {
+    /// 確認済みの移動履歴の件数
+    pub moves_seen: usize,
+
+    /// 確認済みのデッキの周回数
+    pub deck_turns_seen: u32,
+
+    /// 「引く」以外の手を打たずに続けて引いた回数
+    pub draws_in_a_row: usize,
+
+    /// 手詰まりを通知済みかどうか（「引く」以外の手を打つと戻る）
+    pub stuck_notified: bool,
+}
+
+impl Component for NotificationWatch {}
+
+/// 進行通知システム
+///
+/// 手が打たれたフレームだけ盤面を調べ、減点・手詰まりを通知します。
+pub struct NotificationSystem;
+
+impl System for NotificationSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let games: Vec<(Entity, u32, usize)> = world
+            .query::<SolitaireGameState>()
+            .filter(|(_, state)| !state.is_completed)
+            .map(|(entity, state)| {
+                let moves = world
+                    .get_component::<MoveLog>(entity)
+                    .map_or(0, |log| log.moves.len());
+                (entity, state.deck_turns, moves)
+            })
+            .collect();
+
+        for (entity, deck_turns, moves) in games {
+            let mut watch = world
+                .get_component::<NotificationWatch>(entity)
+                .cloned()
+                .unwrap_or_default();
+            let mut notifications = Vec::new();
+
+            if deck_turns > watch.deck_turns_seen && deck_turns > FREE_DECK_TURNS {
+                notifications.push(GameEvent::notification(
+                    NotificationSeverity::Warning,
+                    format!(
+                        "♻️ ウェイストを戻すのが{}回目なので{}点減点されました",
+                        deck_turns, DECK_TURN_PENALTY
+                    ),
+                ));
+            }
+            watch.deck_turns_seen = deck_turns;
+
+            // アンドゥなどで履歴が短くなった場合は数え直す
+            if moves < watch.moves_seen {
+                watch.moves_seen = 0;
+                watch.draws_in_a_row = 0;
+            }
+            if moves > watch.moves_seen {
+                count_draws(world, entity, &mut watch, moves);
+                if !watch.stuck_notified && is_stuck(world, watch.draws_in_a_row) {
+                    watch.stuck_notified = true;
+                    info!("🧱 手詰まりを検出しました");
+                    notifications.push(GameEvent::Notification {
+                        severity: NotificationSeverity::Warning,
+                        message: "🧱 打てる手がなくなりました。新しいゲームを始めますか？"
+                            .to_string(),
+                        auto_dismiss_ms: None,
+                    });
+                }
+            }
+
+            world.add_component(entity, watch);
+            if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                for notification in notifications {
+                    events.push(notification);
+                }
+            }
+        }
+    }
+}
+
+/// 新しく打たれた手から、続けて引いた回数を数え直す
+fn count_draws(world: &World, entity: Entity, watch: &mut NotificationWatch, moves: usize) {
+    let Some(log) = world.get_component::<MoveLog>(entity) else {
+        return;
+    };
+    for record in &log.moves[watch.moves_seen..moves] {
+        if record.from == CardLocation::Deck && record.to == CardLocation::Waste {
+            watch.draws_in_a_row += 1;
+        } else {
+            watch.draws_in_a_row = 0;
+            watch.stuck_notified = false;
+        }
+    }
+    watch.moves_seen = moves;
+}
+
+/// 手詰まりかどうか判定
+///
+/// 打てる手がない場合、または「引く」しか打てない状態でデッキを一巡した場合に手詰まりとみなします。
+fn is_stuck(world: &World, draws_in_a_row: usize) -> bool {
+    let moves = HintEngine::all_moves(world);
+    if moves.is_empty() {
+        return true;
+    }
+    moves.iter().all(|hint| hint.kind == HintKind::Draw)
+        && draws_in_a_row > HintEngine::draw_cycle_length(world)
+}
@@ -0,0 +1,231 @@
+// =============================================================================
+// 接続ごとの送信チャンネルの記録（サーバー用）
+// =============================================================================
+// このファイルでは、サーバーが各接続へ送るメッセージを溜める送信チャンネルと、
+// そこを通ったメッセージの数の記録（HTTP APIの/metricsで返す）を実装します。
+//
+// 仕組み：
+// - 送信チャンネルに入れた数・WebSocketに書き込んだ数・送れずに捨てた数を
+//   すべての接続で共有するOutboundCountersに数える
+// - 接続が閉じた後に送ろうとしたメッセージと、書き込む前に接続が閉じて
+//   チャンネルに残ったメッセージを、送れずに捨てた数に入れる
+// - 溜まっている数（backlog）は、入れた数から書き込んだ数と残して捨てた数を引いたもの
+// - 状態を変えるメッセージは、送信タスクがReliableOutboxでReliableに包んで送り、クライアントから
+//   受け取りの確認（Ack）が届くまで再送する（クライアントからサーバーへの送信と同じ仕組み）。
+//   次の値で上書きされるカーソル位置・数秒で消えるリアクションと、Ping/Pong・Ack・再送の要求は包まない
+// =============================================================================
+
+use crate::reliable::ReliableSender;
+use log::debug;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use uuid::Uuid;
+
+/// Reliableで包まずに送るメッセージの種類（"type"の値）
+const UNRELIABLE_TYPES: [&str; 7] = [
+    "MousePosition",
+    "Reaction",
+    "Ping",
+    "Pong",
+    "Ack",
+    "Reliable",
+    "ResendRequest",
+];
+
+/// すべての接続の送信チャンネルを通ったメッセージの数
+#[derive(Debug, Default)]
+pub struct OutboundCounters {
+    queued: AtomicU64,        // 送信チャンネルに入れた数
+    sent: AtomicU64,          // WebSocketに書き込んだ数
+    undeliverable: AtomicU64, // 接続が閉じた後に送ろうとして捨てた数
+    discarded: AtomicU64,     // チャンネルに入れた後、書き込む前に接続が閉じて捨てた数
+}
+
+/// 送信チャンネルの溜まり具合（/metricsのoutbound）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutboundReport {
+    pub backlog: u64, // いまチャンネルに溜まっている数
+    pub queued: u64,  // これまでにチャンネルに入れた数
+    pub sent: u64,    // これまでにWebSocketに書き込んだ数
+    pub dropped: u64, // これまでに送れずに捨てた数
+}
+
+impl OutboundCounters {
+    /// 溜まり具合と、入れた・書き込んだ・捨てた数
+    pub fn report(&self) -> OutboundReport {
+        let queued = self.queued.load(Ordering::Relaxed);
+        let sent = self.sent.load(Ordering::Relaxed);
+        let undeliverable = self.undeliverable.load(Ordering::Relaxed);
+        let discarded = self.discarded.load(Ordering::Relaxed);
+        OutboundReport {
+            backlog: queued.saturating_sub(sent + discarded),
+            queued,
+            sent,
+            dropped: undeliverable + discarded,
+        }
+    }
+}
+
+/// 送った数を数える送信チャンネルを作成
+///
+/// # 引数
+/// * `counters` - すべての接続で共有する記録
+pub fn channel(counters: &Arc<OutboundCounters>) -> (OutboundSender, OutboundReceiver) {
+    let (sender, receiver) = unbounded_channel();
+    (
+        OutboundSender {
+            sender,
+            counters: Arc::clone(counters),
+        },
+        OutboundReceiver {
+            receiver,
+            counters: Arc::clone(counters),
+        },
+    )
+}
+
+/// 送信チャンネルの送り手
+#[derive(Debug, Clone)]
+pub struct OutboundSender {
+    sender: UnboundedSender<String>,
+    counters: Arc<OutboundCounters>,
+}
+
+impl OutboundSender {
+    /// メッセージを送信チャンネルに入れる
+    ///
+    /// # 戻り値
+    /// 入れた場合Ok(())、接続が閉じていた場合はErr
+    pub fn send(&self, text: String) -> Result<(), SendError<String>> {
+        let result = self.sender.send(text);
+        let counter = if result.is_ok() {
+            &self.counters.queued
+        } else {
+            &self.counters.undeliverable
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+}
+
+/// 送信チャンネルの受け手（送信タスクが持つ）
+///
+/// 送信タスクが終わって捨てられるとき、書き込まずに残ったメッセージを捨てた数に入れます。
+#[derive(Debug)]
+pub struct OutboundReceiver {
+    receiver: UnboundedReceiver<String>,
+    counters: Arc<OutboundCounters>,
+}
+
+impl OutboundReceiver {
+    /// 次のメッセージを待つ
+    pub async fn recv(&mut self) -> Option<String> {
+        self.receiver.recv().await
+    }
+
+    /// WebSocketに書き込めたことを記録する
+    pub fn mark_sent(&self) {
+        self.counters.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// WebSocketに書き込めずに捨てたことを記録する
+    pub fn mark_discarded(&self) {
+        self.counters.discarded.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Drop for OutboundReceiver {
+    fn drop(&mut self) {
+        let remaining = self.receiver.len() as u64;
+        self.counters
+            .discarded
+            .fetch_add(remaining, Ordering::Relaxed);
+    }
+}
+
+/// 接続ごとの、受け取りの確認（Ack）を待つ送信の記録（送信タスクが持つ）
+#[derive(Debug)]
+pub struct ReliableOutbox {
+    /// この接続で送るメッセージIDの接頭辞（接続し直したクライアントが前の接続のIDと取り違えないように）
+    prefix: String,
+
+    /// 次に使うメッセージIDの連番
+    next_id: u64,
+
+    /// Ackを待っているメッセージ
+    sender: ReliableSender,
+}
+
+impl Default for ReliableOutbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReliableOutbox {
+    /// Ackを待っているメッセージがない状態で作成
+    pub fn new() -> Self {
+        Self {
+            prefix: format!("s{}", &Uuid::new_v4().simple().to_string()[..8]),
+            next_id: 0,
+            sender: ReliableSender::new(),
+        }
+    }
+
+    /// 送るテキストを、状態を変えるメッセージならReliableで包んでAckを待つ記録に入れる
+    ///
+    /// # 引数
+    /// * `text` - 送信チャンネルから取り出したJSONのテキスト
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    ///
+    /// # 戻り値
+    /// WebSocketに書き込むテキスト
+    pub fn wrap(&mut self, text: String, now_ms: f64) -> String {
+        if !needs_ack(&text) {
+            return text;
+        }
+        self.next_id += 1;
+        let message_id = format!("{}_{}", self.prefix, self.next_id);
+        let wrapped = format!(
+            r#"{{"type":"Reliable","message_id":"{}","message":{}}}"#,
+            message_id, text
+        );
+        self.sender.track(message_id, wrapped.clone());
+        self.sender.mark_sent(now_ms);
+        wrapped
+    }
+
+    /// クライアントからAckを受け取る
+    ///
+    /// # 引数
+    /// * `message_id` - Ackに含まれていたメッセージID
+    pub fn ack(&mut self, message_id: &str) {
+        if !self.sender.ack(message_id) {
+            debug!("🔁 待っていないメッセージの受け取りの確認: {}", message_id);
+        }
+    }
+
+    /// 再送するテキストを取り出す（上限まで再送しても確認が届かないメッセージは諦める）
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    pub fn due(&mut self, now_ms: f64) -> Vec<String> {
+        let retries = self.sender.due(now_ms);
+        for message_id in &retries.given_up {
+            debug!("📪 受け取りの確認が届かないため再送を諦めました: {}", message_id);
+        }
+        retries.resend
+    }
+}
+
+/// Reliableで包んで送るメッセージかどうか
+///
+/// メッセージはserdeのタグ"type"が先頭に来る形で書き出されるため、先頭から種類を読み取ります。
+fn needs_ack(text: &str) -> bool {
+    text.strip_prefix(r#"{"type":""#)
+        .and_then(|rest| rest.split('"').next())
+        .is_some_and(|message_type| !UNRELIABLE_TYPES.contains(&message_type))
+}
@@ -0,0 +1,337 @@
+// =============================================================================
+// 共有盤面のカードの持ち主と操作の権限
+// =============================================================================
+// このファイルでは、共有盤面のルーム（参加者全員で1つの盤面を動かす協力プレイ）で
+// 誰がどのカードを動かせるかを決める権限の仕組みを実装します。
+// サーバーの検証とクライアントの合法手の判定で同じ規則を使います。
+//
+// 仕組み：
+// - 参加者はホスト・プレイヤー・観戦者のいずれかの立場（SeatRole）になる
+// - 観戦者は盤面を見るだけで、カードを掴む・動かす・引く操作はすべて断る
+// - カードにOwnerコンポーネントが付いている場合、そのプレイヤーだけが動かせる
+// - ウェイストを山札に戻す操作（"recycle"のGameAction）はホストだけができる
+// - サーバーはルームの権限が変わるたびにPermissionsChangedを配信し、
+//   クライアントはSharedBoardリソースとOwnerコンポーネントに反映する
+//   （動かせないカードはget_solitaire_state()のlockedで分かり、画面で灰色にできる）
+// - サーバーも同じシードで配った盤面の写しにアクションを反映し、同じ規則で確かめる
+// - カードのIDはスートとランクの文字列（例："hearts-7"、card_id()）
+//   （エンティティIDはクライアントとサーバーの盤面で一致しないため使わない）
+// =============================================================================
+
+use crate::ecs::{Component, Entity, Resource, World};
+use crate::game::ActionPayload;
+use crate::protocol::WebSocketMessage;
+use crate::solitaire::{CardLocation, CardSuit, SolitaireCard};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// ウェイストを山札に戻す操作のGameAction
+pub const RECYCLE_ACTION: &str = "recycle";
+
+/// 共有盤面のルームでの参加者の立場
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SeatRole {
+    /// ホスト（カードの持ち主を決められ、山札を戻せる）
+    Host,
+
+    /// プレイヤー（持ち主のいない・自分のカードを動かせる）
+    Player,
+
+    /// 観戦者（盤面を見るだけで操作できない）
+    Spectator,
+}
+
+impl SeatRole {
+    /// 立場を文字列で取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeatRole::Host => "host",
+            SeatRole::Player => "player",
+            SeatRole::Spectator => "spectator",
+        }
+    }
+
+    /// ルームの権限から参加者の立場を決める
+    ///
+    /// # 引数
+    /// * `player_id` - 参加者のプレイヤーID
+    /// * `host_id` - ホストのプレイヤーID
+    /// * `spectators` - 観戦者のプレイヤーID
+    pub fn of<'a>(
+        player_id: &str,
+        host_id: Option<&str>,
+        mut spectators: impl Iterator<Item = &'a String>,
+    ) -> Self {
+        if spectators.any(|id| id == player_id) {
+            SeatRole::Spectator
+        } else if host_id == Some(player_id) {
+            SeatRole::Host
+        } else {
+            SeatRole::Player
+        }
+    }
+}
+
+/// カードの持ち主のコンポーネント
+///
+/// 付いているカードは持ち主だけが動かせます。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Owner {
+    /// 持ち主のプレイヤーID
+    pub player_id: String,
+}
+
+impl Component for Owner {}
+
+/// 共有盤面のルームでの自分の立場（リソース）
+///
+/// このリソースがない場合（1人プレイ・レース）は権限を確かめません。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBoard {
+    /// 自分のプレイヤーID
+    pub player_id: String,
+
+    /// 自分の立場
+    pub role: SeatRole,
+}
+
+impl Resource for SharedBoard {}
+
+/// カードを掴む・動かせるかを確かめる（サーバーとクライアントで共通）
+///
+/// # 引数
+/// * `role` - 操作するプレイヤーの立場
+/// * `player_id` - 操作するプレイヤーのID
+/// * `owner_id` - カードの持ち主のプレイヤーID（持ち主がいない場合はNone）
+///
+/// # 戻り値
+/// 動かせる場合はOk(())、動かせない場合は理由を表すエラーメッセージ
+pub fn check_card(role: SeatRole, player_id: &str, owner_id: Option<&str>) -> Result<(), String> {
+    if role == SeatRole::Spectator {
+        return Err("観戦中は盤面を操作できません".to_string());
+    }
+    match owner_id {
+        Some(owner_id) if owner_id != player_id => {
+            Err(format!("{}のカードは動かせません", owner_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// GameActionを送れるかを確かめる（サーバーとクライアントで共通）
+///
+/// # 引数
+/// * `role` - 送ったプレイヤーの立場
+/// * `action` - アクションの内容
+///
+/// # 戻り値
+/// 送れる場合はOk(())、送れない場合は理由を表すエラーメッセージ
+pub fn check_action(role: SeatRole, action: &str) -> Result<(), String> {
+    match role {
+        SeatRole::Spectator => Err("観戦中は盤面を操作できません".to_string()),
+        SeatRole::Player if action == RECYCLE_ACTION => {
+            Err("山札に戻せるのはホストだけです".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 自分がカードを動かせるかを確かめる
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `entity` - カードのエンティティ
+///
+/// # 戻り値
+/// 動かせる場合（共有盤面でない場合を含む）はOk(())、動かせない場合は理由を表すエラーメッセージ
+pub fn check_own_card(world: &World, entity: Entity) -> Result<(), String> {
+    let Some(seat) = world.get_resource::<SharedBoard>() else {
+        return Ok(());
+    };
+    let owner = world.get_component::<Owner>(entity);
+    check_card(
+        seat.role,
+        &seat.player_id,
+        owner.map(|o| o.player_id.as_str()),
+    )
+}
+
+/// 自分がアクションを実行できるかを確かめる（ActionProcessingSystemの検証で使う）
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `payload` - アクションの内容
+///
+/// # 戻り値
+/// 実行できる場合（共有盤面でない場合を含む）はOk(())、できない場合は理由を表すエラーメッセージ
+pub fn check_payload(world: &World, payload: &ActionPayload) -> Result<(), String> {
+    let Some(seat) = world.get_resource::<SharedBoard>() else {
+        return Ok(());
+    };
+    check_payload_as(world, seat.role, &seat.player_id, payload)
+}
+
+/// 参加者がアクションを実行できるかを盤面で確かめる（サーバーとクライアントで共通）
+///
+/// 移動は移動元の上から動かす枚数のカード、めくる操作はめくるカードの持ち主を確かめ、
+/// デッキが空のときに引く操作はウェイストを山札に戻す操作として確かめます。
+///
+/// # 引数
+/// * `world` - カードの持ち主を反映した盤面
+/// * `role` - 操作する参加者の立場
+/// * `player_id` - 操作する参加者のプレイヤーID
+/// * `payload` - アクションの内容
+///
+/// # 戻り値
+/// 実行できる場合はOk(())、できない場合は理由を表すエラーメッセージ
+pub fn check_payload_as(
+    world: &World,
+    role: SeatRole,
+    player_id: &str,
+    payload: &ActionPayload,
+) -> Result<(), String> {
+    let (location, index, count) = match payload {
+        ActionPayload::MoveCard { from, count, .. } => (from.location, from.index, *count),
+        ActionPayload::FlipCard { column } => (CardLocation::Tableau, *column, 1),
+        ActionPayload::DrawCard => {
+            let recycling = !world
+                .query::<SolitaireCard>()
+                .any(|(_, card)| card.location_type == CardLocation::Deck);
+            let action = if recycling { RECYCLE_ACTION } else { "draw" };
+            return check_action(role, action);
+        }
+        _ => return Ok(()),
+    };
+
+    let mut cards: Vec<(Entity, f32)> = world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| card.location_type == location && card.position_in_location == index)
+        .map(|(entity, card)| (entity, card.display_y))
+        .collect();
+    cards.sort_by(|a, b| b.1.total_cmp(&a.1));
+    cards.iter().take(count).try_for_each(|(entity, _)| {
+        let owner = world.get_component::<Owner>(*entity);
+        check_card(role, player_id, owner.map(|o| o.player_id.as_str()))
+    })
+}
+
+/// GameActionの内容を共有盤面の操作として読む
+///
+/// 盤面の操作はActionPayloadのJSONで送り、"draw"・"recycle"は山札から引く操作として扱います。
+///
+/// # 引数
+/// * `action` - GameActionの内容
+///
+/// # 戻り値
+/// 盤面の操作（移動・めくる・引く）の場合はその内容、それ以外（パワーアップなど）はNone
+pub fn board_payload(action: &str) -> Option<ActionPayload> {
+    if action == "draw" || action == RECYCLE_ACTION {
+        return Some(ActionPayload::DrawCard);
+    }
+    ActionPayload::parse(action).ok().filter(|payload| {
+        matches!(
+            payload,
+            ActionPayload::MoveCard { .. } | ActionPayload::FlipCard { .. } | ActionPayload::DrawCard
+        )
+    })
+}
+
+/// 共有盤面でのカードのID（例："hearts-7"）
+///
+/// エンティティIDはクライアントとサーバーの盤面で一致しないため、スートとランクから作ります。
+///
+/// # 引数
+/// * `card` - カード
+pub fn card_id(card: &SolitaireCard) -> String {
+    let suit = match card.suit {
+        CardSuit::Hearts => "hearts",
+        CardSuit::Diamonds => "diamonds",
+        CardSuit::Clubs => "clubs",
+        CardSuit::Spades => "spades",
+    };
+    format!("{}-{}", suit, card.rank as u8)
+}
+
+/// サーバーから届いたルームの権限を盤面に反映する
+///
+/// PermissionsChangedで自分の立場とカードの持ち主を置き換え、
+/// 自分がルームから抜けた・キックされた場合は権限を外します。
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `own_player_id` - 自分のプレイヤーID
+/// * `message` - サーバーから届いたメッセージ
+///
+/// # 戻り値
+/// 権限を変えた場合true
+pub fn apply(world: &mut World, own_player_id: Option<&str>, message: &WebSocketMessage) -> bool {
+    let Some(own_player_id) = own_player_id else {
+        return false;
+    };
+    match message {
+        WebSocketMessage::PermissionsChanged {
+            host_id,
+            spectators,
+            card_owners,
+            ..
+        } => {
+            let role = SeatRole::of(own_player_id, host_id.as_deref(), spectators.iter());
+            debug!(
+                "🔐 共有盤面の権限: {}（持ち主の決まったカード{}枚）",
+                role.as_str(),
+                card_owners.len()
+            );
+            world.insert_resource(SharedBoard {
+                player_id: own_player_id.to_string(),
+                role,
+            });
+            set_owners(world, card_owners);
+            true
+        }
+        WebSocketMessage::LeaveRoom { player_id, .. }
+        | WebSocketMessage::Kicked { player_id, .. }
+            if player_id == own_player_id =>
+        {
+            if world.remove_resource::<SharedBoard>().is_none() {
+                return false;
+            }
+            info!("🔓 共有盤面の権限を外しました");
+            set_owners(world, &BTreeMap::new());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// カードの持ち主を置き換える
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `card_owners` - カードのID（card_id()）→ 持ち主のプレイヤーID
+pub fn set_owners(world: &mut World, card_owners: &BTreeMap<String, String>) {
+    let owned: Vec<Entity> = world.query::<Owner>().map(|(entity, _)| entity).collect();
+    for entity in owned {
+        world.remove_component::<Owner>(entity);
+    }
+    if card_owners.is_empty() {
+        return;
+    }
+    let cards: Vec<(Entity, String)> = world
+        .query::<SolitaireCard>()
+        .map(|(entity, card)| (entity, card_id(card)))
+        .collect();
+    for (entity, id) in cards {
+        if let Some(player_id) = card_owners.get(&id) {
+            world.add_component(
+                entity,
+                Owner {
+                    player_id: player_id.clone(),
+                },
+            );
+        }
+    }
+}
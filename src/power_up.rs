@@ -0,0 +1,367 @@
+// =============================================================================
+// カジュアルなマルチプレイのパワーアップ
+// =============================================================================
+// このファイルでは、パワーアップを有効にしたルームで使える3種類のパワーアップ
+// （裏向きのカードを覗く・山札を1回だけ無料でシャッフルする・相手のタイムに+30秒）を定義します。
+//
+// 仕組み：
+// - パワーアップは"power_up:<名前>"のGameActionとして送る（覗く列はGameActionのxで指定する）。
+//   名前と列はメッセージの検証で確かめる
+// - サーバーはルームがパワーアップを有効にしていて、レース中で、クールダウンが
+//   明けている場合だけ受け付け、使ったプレイヤーを含むルームの全員に送り返す
+// - クライアントは自分のパワーアップが送り返されてから盤面に反映する
+//   （サーバーに断られたパワーアップは使わなかったことになる）
+// - 相手のタイムの加算はサーバーが覚えておき、ゲーム結果のプレイ時間に足す
+// - パワーアップを有効にしたルームの結果は"ranked": falseを付けて配信し、
+//   リーダーボードとレーティングには記録しない
+// =============================================================================
+
+use crate::ecs::{Entity, World};
+use crate::events::{EventQueue, NotificationSeverity};
+use crate::layout;
+use crate::protocol::WebSocketMessage;
+use crate::rng::Rng;
+use crate::solitaire::{CardLocation, SolitaireCard, SolitaireGameState};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use ts_rs::TS;
+
+/// パワーアップのGameActionの接頭辞
+pub const ACTION_PREFIX: &str = "power_up:";
+
+/// 相手のタイムに足す秒数
+pub const TIME_PENALTY_SECONDS: u32 = 30;
+
+/// 覗けるタブローの列数
+const TABLEAU_COLUMNS: u32 = 7;
+
+/// パワーアップの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUp {
+    /// タブローの列の一番上の裏向きのカードを、めくらずに自分だけ見る
+    Peek,
+
+    /// デッキとウェイストのカードをまとめてシャッフルしてデッキに戻す（1レースに1回）
+    FreeShuffle,
+
+    /// 同じルームの相手全員のタイムにTIME_PENALTY_SECONDS秒を足す
+    TimePenalty,
+}
+
+impl PowerUp {
+    /// すべてのパワーアップ（ボタンの並び順）
+    pub const ALL: [PowerUp; 3] = [PowerUp::Peek, PowerUp::FreeShuffle, PowerUp::TimePenalty];
+
+    /// パワーアップの名前を取得
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PowerUp::Peek => "peek",
+            PowerUp::FreeShuffle => "free_shuffle",
+            PowerUp::TimePenalty => "time_penalty",
+        }
+    }
+
+    /// 名前からパワーアップを取得
+    ///
+    /// # 引数
+    /// * `name` - "peek" / "free_shuffle" / "time_penalty"
+    ///
+    /// # 戻り値
+    /// 成功時はPowerUp、不明な名前の場合はエラーメッセージ
+    pub fn parse(name: &str) -> Result<Self, String> {
+        PowerUp::ALL
+            .into_iter()
+            .find(|power_up| power_up.as_str() == name)
+            .ok_or_else(|| format!("不明なパワーアップです: {}", name))
+    }
+
+    /// GameActionのアクションからパワーアップを取得
+    ///
+    /// # 引数
+    /// * `action` - GameActionのアクション
+    ///
+    /// # 戻り値
+    /// パワーアップのアクションでなければNone、パワーアップの名前が不明な場合はSome(Err)
+    pub fn from_action(action: &str) -> Option<Result<Self, String>> {
+        action.strip_prefix(ACTION_PREFIX).map(Self::parse)
+    }
+
+    /// GameActionで送るアクション
+    pub fn action(&self) -> String {
+        format!("{}{}", ACTION_PREFIX, self.as_str())
+    }
+
+    /// 次に使えるまでの秒数
+    pub fn cooldown_seconds(&self) -> u64 {
+        match self {
+            PowerUp::Peek => 20,
+            PowerUp::FreeShuffle => 0,
+            PowerUp::TimePenalty => 60,
+        }
+    }
+
+    /// 1レースに1回だけ使えるかどうか
+    pub fn once_per_race(&self) -> bool {
+        matches!(self, PowerUp::FreeShuffle)
+    }
+
+    /// GameActionの位置がこのパワーアップに合っているかチェック
+    ///
+    /// # 引数
+    /// * `x` - GameActionのx（覗く場合はタブローの列番号、0から開始）
+    pub fn check_target(&self, x: Option<f64>) -> Result<(), String> {
+        match (self, x) {
+            (PowerUp::Peek, Some(column))
+                if column.fract() == 0.0 && (0.0..TABLEAU_COLUMNS as f64).contains(&column) =>
+            {
+                Ok(())
+            }
+            (PowerUp::Peek, _) => Err("覗くタブローの列を指定してください".to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// ルーム1つ分のパワーアップの使用状況（サーバー用）
+#[derive(Debug, Clone, Default)]
+pub struct PowerUpTracker {
+    /// プレイヤーとパワーアップごとに、最後に使った時刻（UNIX時刻、ミリ秒）
+    last_used: HashMap<(String, PowerUp), u64>,
+
+    /// このレースで1回だけのパワーアップを使ったプレイヤーとパワーアップ
+    used_once: HashSet<(String, PowerUp)>,
+
+    /// プレイヤーごとの、相手から足されたタイムの合計（秒）
+    time_penalties: HashMap<String, u32>,
+}
+
+impl PowerUpTracker {
+    /// 空のトラッカーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// パワーアップを使う（クールダウン中・使用済みの場合は断る）
+    ///
+    /// # 引数
+    /// * `player_id` - 使うプレイヤー
+    /// * `power_up` - 使うパワーアップ
+    /// * `opponents` - 同じルームの相手（タイムを足す対象）
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 使えた場合Ok(())、使えない場合はエラーメッセージ
+    pub fn use_power_up(
+        &mut self,
+        player_id: &str,
+        power_up: PowerUp,
+        opponents: &[String],
+        now_ms: u64,
+    ) -> Result<(), String> {
+        let key = (player_id.to_string(), power_up);
+        if power_up.once_per_race() && self.used_once.contains(&key) {
+            return Err(format!("{}はこのレースで使用済みです", power_up.as_str()));
+        }
+        if let Some(&last_used) = self.last_used.get(&key) {
+            let ready_at = last_used + power_up.cooldown_seconds() * 1000;
+            if now_ms < ready_at {
+                return Err(format!(
+                    "{}はあと{}秒使えません",
+                    power_up.as_str(),
+                    (ready_at - now_ms).div_ceil(1000)
+                ));
+            }
+        }
+
+        if power_up == PowerUp::TimePenalty {
+            for opponent in opponents.iter().filter(|opponent| *opponent != player_id) {
+                *self.time_penalties.entry(opponent.clone()).or_default() += TIME_PENALTY_SECONDS;
+            }
+        }
+        if power_up.once_per_race() {
+            self.used_once.insert(key.clone());
+        }
+        self.last_used.insert(key, now_ms);
+        Ok(())
+    }
+
+    /// 相手から足されたタイムの合計（秒）
+    pub fn time_penalty(&self, player_id: &str) -> u32 {
+        self.time_penalties.get(player_id).copied().unwrap_or(0)
+    }
+
+    /// レースの開始時に使用状況を数え直す
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// パワーアップを有効にしたルームのゲーム結果に印を付ける
+///
+/// 相手から足されたタイムをプレイ時間に加え、ランク外であることを示す
+/// "ranked": falseと"time_penalty_seconds"を追加します。
+///
+/// # 引数
+/// * `result` - ゲーム結果JSON
+/// * `time_penalty_seconds` - 相手から足されたタイムの合計（秒）
+pub fn mark_unranked(result: &mut serde_json::Value, time_penalty_seconds: u32) {
+    let Some(fields) = result.as_object_mut() else {
+        return;
+    };
+    if let Some(duration) = fields
+        .get("duration_seconds")
+        .and_then(|value| value.as_u64())
+    {
+        fields.insert(
+            "duration_seconds".to_string(),
+            (duration + time_penalty_seconds as u64).into(),
+        );
+    }
+    fields.insert(
+        "time_penalty_seconds".to_string(),
+        time_penalty_seconds.into(),
+    );
+    fields.insert("ranked".to_string(), false.into());
+}
+
+/// ゲーム結果がランク外（リーダーボード・レーティングの対象外）かどうか
+pub fn is_unranked(result: &serde_json::Value) -> bool {
+    result.get("ranked").and_then(|ranked| ranked.as_bool()) == Some(false)
+}
+
+/// タブローの列の一番上の裏向きのカードを、めくらずに見る
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `column` - タブローの列番号（0から開始）
+///
+/// # 戻り値
+/// 見えたカード、裏向きのカードがない場合はエラーメッセージ
+pub fn peek(world: &World, column: u32) -> Result<SolitaireCard, String> {
+    world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| {
+            card.location_type == CardLocation::Tableau
+                && card.position_in_location == column
+                && !card.is_face_up
+        })
+        .max_by(|(_, a), (_, b)| a.display_y.total_cmp(&b.display_y))
+        .map(|(_, card)| card.clone())
+        .ok_or_else(|| format!("タブロー{}に裏向きのカードがありません", column + 1))
+}
+
+/// デッキとウェイストのカードをまとめてシャッフルし、裏向きでデッキに戻す
+///
+/// 同じ盤面からは同じ並びになるよう、配り札のシードと手数から決めた乱数を使います。
+///
+/// # 引数
+/// * `world` - ECSワールド
+///
+/// # 戻り値
+/// シャッフルしたカードの枚数、デッキもウェイストも空の場合はエラーメッセージ
+pub fn shuffle_stock(world: &mut World) -> Result<usize, String> {
+    let mut cards: Vec<Entity> = world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| matches!(card.location_type, CardLocation::Deck | CardLocation::Waste))
+        .map(|(entity, _)| entity)
+        .collect();
+    if cards.is_empty() {
+        return Err("シャッフルするカードがありません".to_string());
+    }
+
+    // クエリの順序に左右されないよう、エンティティの順に並べてからシャッフルする
+    cards.sort_by_key(|entity| entity.0);
+    let seed = world
+        .query::<SolitaireGameState>()
+        .next()
+        .map(|(_, state)| {
+            state.seed ^ (state.move_count as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        })
+        .unwrap_or(0);
+    Rng::new(seed).shuffle(&mut cards);
+
+    for (position, entity) in cards.iter().enumerate() {
+        if let Some(card) = world.get_component_mut::<SolitaireCard>(*entity) {
+            card.set_location(CardLocation::Deck, position as u32);
+            card.set_display_position(layout::DECK_POSITION.0, layout::DECK_POSITION.1);
+            card.flip_down();
+            card.is_movable = false;
+        }
+    }
+    info!("🔀 山札をシャッフルしました（{}枚）", cards.len());
+    Ok(cards.len())
+}
+
+/// サーバーから送り返されたパワーアップを盤面と通知に反映する
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `own_player_id` - 自分のプレイヤーID
+/// * `message` - サーバーから届いたメッセージ
+///
+/// # 戻り値
+/// パワーアップを反映した場合true
+pub fn apply(world: &mut World, own_player_id: Option<&str>, message: &WebSocketMessage) -> bool {
+    let WebSocketMessage::GameAction {
+        player_id,
+        player_name,
+        action,
+        x,
+        ..
+    } = message
+    else {
+        return false;
+    };
+    let Some(Ok(power_up)) = PowerUp::from_action(action) else {
+        return false;
+    };
+
+    let own = own_player_id == Some(player_id.as_str());
+    let notice = match (power_up, own) {
+        (PowerUp::Peek, true) => {
+            let column = x.unwrap_or(0.0) as u32;
+            peek(world, column).map(|card| {
+                (
+                    NotificationSeverity::Info,
+                    format!(
+                        "🔍 タブロー{}の裏のカード: {}{}",
+                        column + 1,
+                        card.suit.symbol(),
+                        card.rank.display()
+                    ),
+                )
+            })
+        }
+        (PowerUp::FreeShuffle, true) => shuffle_stock(world).map(|count| {
+            (
+                NotificationSeverity::Info,
+                format!("🔀 山札をシャッフルしました（{}枚）", count),
+            )
+        }),
+        (PowerUp::TimePenalty, true) => Ok((
+            NotificationSeverity::Info,
+            format!("⏱️ 相手のタイムに+{}秒", TIME_PENALTY_SECONDS),
+        )),
+        (PowerUp::TimePenalty, false) => Ok((
+            NotificationSeverity::Warning,
+            format!(
+                "⏱️ {}のパワーアップでタイムに+{}秒",
+                player_name, TIME_PENALTY_SECONDS
+            ),
+        )),
+        // 相手が覗いた・シャッフルした盤面は自分の盤面とは別
+        (_, false) => return false,
+    };
+
+    let (severity, text) = match notice {
+        Ok(notice) => notice,
+        Err(e) => (NotificationSeverity::Warning, e),
+    };
+    if let Some(events) = world.get_resource_mut::<EventQueue>() {
+        events.notify(severity, text);
+    }
+    true
+}
@@ -0,0 +1,128 @@
+// =============================================================================
+// 練習モード（局面からの分岐）
+// =============================================================================
+// このファイルでは、リプレイや進行中のゲームの好きな手数の局面から、
+// 別のセッションで練習を始めるための仕組みを実装します。
+//
+// 仕組み：
+// - 分岐する局面は、配られた時のシードで配り直し、移動履歴を指定の手数まで再生して作る
+//   （シードから配っていないパズル・チュートリアルは、現在の局面からだけ分岐できる）
+// - 練習のゲーム状態エンティティにはPracticeGameを添付し、分岐した時点の盤面を覚えておく
+// - 練習では元に戻すのを何回でもできる（分岐した時点の盤面から、最後の手を除いて再生し直す）
+// - 練習の結果は実績・通算成績に数えず、サーバーにも送らない
+// =============================================================================
+
+use crate::analysis::replay_move;
+use crate::ecs::{Component, Entity, World};
+use crate::scenario::{BoardBuilder, Scenario};
+use crate::solitaire::{MoveLog, MoveRecord, SolitaireGameState, SolitaireManager, SolitaireType};
+
+/// 練習のゲームを表すコンポーネント
+///
+/// ゲーム状態エンティティに添付されます。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PracticeGame {
+    /// 分岐元のゲームの何手目の局面から分岐したか
+    pub forked_at_move: usize,
+
+    /// 分岐した時点の盤面
+    pub start: Scenario,
+
+    /// 元に戻した回数（上限なし）
+    pub undos_used: u32,
+}
+
+impl Component for PracticeGame {}
+
+/// ゲームの指定した手数の局面を作る
+///
+/// 配り直せるゲームは現在の局面も移動履歴の再生で作ります。
+/// 配り直せないゲームの現在の局面は盤面をそのまま写すため、カードが最終位置にある状態で呼び出してください。
+///
+/// # 引数
+/// * `world` - ECSワールドへの参照
+/// * `game` - ゲーム状態エンティティ
+/// * `move_index` - 何手目の局面か（0は配られた直後、移動履歴の長さは現在の局面）
+///
+/// # 戻り値
+/// 成功時はその局面の盤面、手数が範囲外・局面を作れない場合はエラーメッセージ
+pub fn position_at(world: &World, game: Entity, move_index: usize) -> Result<Scenario, String> {
+    let state = world
+        .get_component::<SolitaireGameState>(game)
+        .ok_or_else(|| "ゲームが始まっていません".to_string())?;
+    let moves = world
+        .get_component::<MoveLog>(game)
+        .map(|log| log.moves.as_slice())
+        .unwrap_or_default();
+    if move_index > moves.len() {
+        return Err(format!(
+            "{}手目はありません（現在{}手目です）",
+            move_index,
+            moves.len()
+        ));
+    }
+
+    let mut replayed = World::new();
+    if let Some(practice) = world.get_component::<PracticeGame>(game) {
+        BoardBuilder::from_scenario(practice.start.clone()).build(&mut replayed)?;
+    } else if state.game_type == SolitaireType::Klondike && state.seed != 0 {
+        SolitaireManager::start_new_game_with_seed(&mut replayed, state.game_type, state.seed);
+    } else if move_index == moves.len() {
+        return Ok(Scenario::from_world(world));
+    } else {
+        return Err("シードから配っていないゲームは現在の局面からだけ練習できます".to_string());
+    }
+    replay(&mut replayed, &moves[..move_index])?;
+    Ok(Scenario::from_world(&replayed))
+}
+
+/// 練習のゲームを作成
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `start` - 分岐した時点の盤面
+/// * `forked_at_move` - 分岐元のゲームの何手目から分岐したか
+/// * `moves` - 分岐した後に打った手（元に戻した後の作り直しで使う）
+/// * `undos_used` - それまでに元に戻した回数
+///
+/// # 戻り値
+/// 成功時はゲーム状態エンティティ、盤面が不正・手を再生できない場合はエラーメッセージ
+pub fn build(
+    world: &mut World,
+    start: &Scenario,
+    forked_at_move: usize,
+    moves: &[MoveRecord],
+    undos_used: u32,
+) -> Result<Entity, String> {
+    let game = BoardBuilder::from_scenario(start.clone()).build(world)?;
+    replay(world, moves)?;
+    if let Some(state) = world.get_component_mut::<SolitaireGameState>(game) {
+        state.undos_used = undos_used;
+    }
+    world.add_component(
+        game,
+        PracticeGame {
+            forked_at_move,
+            start: start.clone(),
+            undos_used,
+        },
+    );
+    Ok(game)
+}
+
+/// 練習のゲームかチェック
+///
+/// # 引数
+/// * `world` - ECSワールドへの参照
+/// * `game` - ゲーム状態エンティティ
+pub fn is_practice(world: &World, game: Entity) -> bool {
+    world.has_component::<PracticeGame>(game)
+}
+
+/// 移動履歴の手を順に再生する
+fn replay(world: &mut World, moves: &[MoveRecord]) -> Result<(), String> {
+    for (number, record) in (1..).zip(moves) {
+        replay_move(world, record).map_err(|e| format!("{}手目を再現できません: {}", number, e))?;
+    }
+    Ok(())
+}
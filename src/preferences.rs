@@ -0,0 +1,132 @@
+// =============================================================================
+// プレイヤー設定の保存（サーバー用）
+// =============================================================================
+// このファイルでは、プレイヤーが選んだカーソルの色と表示名を
+// セッショントークンごとに保存し、次の接続で引き継ぐための保存領域を実装します。
+//
+// 仕組み：
+// - 初めて接続したプレイヤーにはサーバーがセッショントークンを発行する
+// - クライアントはトークンを保存し、次の接続時にPlayerJoinで送る
+// - プレイヤーIDは接続ごとに変わるため、設定はトークンをキーにして保存する
+// - レーティングなどは表示名で記録するため、使った表示名はトークンのものとして押さえ、
+//   他のトークンのプレイヤーが同じ名前を名乗れないようにする
+// =============================================================================
+
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 設定データの保存キー
+const STORAGE_KEY: &str = "preferences";
+
+/// プレイヤー1人分の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerPreferences {
+    /// カーソルの色（Noneの場合は接続順に割り当てる）
+    pub color_index: Option<u8>,
+
+    /// 表示名（Noneの場合は参加時に送られた名前を使う）
+    pub player_name: Option<String>,
+}
+
+/// 全プレイヤーの設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreferenceStore {
+    /// セッショントークン → 設定
+    records: HashMap<String, PlayerPreferences>,
+
+    /// 表示名（小文字にしたもの） → その名前を押さえているセッショントークン
+    #[serde(default)]
+    names: HashMap<String, String>,
+}
+
+impl PreferenceStore {
+    /// 保存されている設定を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のPreferenceStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 設定を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("設定のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// セッショントークンに保存された設定を取得
+    ///
+    /// # 引数
+    /// * `session_token` - セッショントークン
+    ///
+    /// # 戻り値
+    /// 保存された設定（ない場合はNone）
+    pub fn get(&self, session_token: &str) -> Option<&PlayerPreferences> {
+        self.records.get(session_token)
+    }
+
+    /// 設定を更新する（指定されなかった項目は前回の値のまま）
+    ///
+    /// # 引数
+    /// * `session_token` - セッショントークン
+    /// * `color_index` - 新しいカーソルの色
+    /// * `player_name` - 新しい表示名
+    pub fn update(
+        &mut self,
+        session_token: &str,
+        color_index: Option<u8>,
+        player_name: Option<String>,
+    ) {
+        let record = self.records.entry(session_token.to_string()).or_default();
+        if color_index.is_some() {
+            record.color_index = color_index;
+        }
+        if player_name.is_some() {
+            record.player_name = player_name;
+        }
+    }
+
+    /// 表示名を押さえているセッショントークンを取得
+    ///
+    /// # 引数
+    /// * `player_name` - 表示名（大文字・小文字は区別しない）
+    ///
+    /// # 戻り値
+    /// 押さえているトークン（まだ誰も使っていない名前の場合はNone）
+    pub fn name_holder(&self, player_name: &str) -> Option<&str> {
+        self.names.get(&player_name.to_lowercase()).map(String::as_str)
+    }
+
+    /// 表示名をセッショントークンのものとして押さえる
+    ///
+    /// 変更前の名前もレーティングなどの記録が残っているため、手放さずに押さえたままにします。
+    ///
+    /// # 引数
+    /// * `session_token` - セッショントークン
+    /// * `player_name` - 表示名
+    ///
+    /// # 戻り値
+    /// 押さえられた場合（既に同じトークンのものだった場合も含む）Ok(())、
+    /// 他のトークンのプレイヤーが使っている場合はErr
+    pub fn claim_name(&mut self, session_token: &str, player_name: &str) -> Result<(), String> {
+        match self.name_holder(player_name) {
+            Some(holder) if holder != session_token => {
+                Err(format!("「{}」は他のプレイヤーが使っている表示名です", player_name))
+            }
+            _ => {
+                self.names
+                    .insert(player_name.to_lowercase(), session_token.to_string());
+                // 名前を押さえたトークンは、設定を変えていなくても次の接続で使い続けられるようにする
+                self.records.entry(session_token.to_string()).or_default();
+                Ok(())
+            }
+        }
+    }
+}
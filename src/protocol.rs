@@ -0,0 +1,1561 @@
+// =============================================================================
+// 通信メッセージの形式と検証
+// =============================================================================
+// このファイルでは、外部から受け取るメッセージの形式を定義し、
+// 不正な値（巨大な文字列、負のインデックス、NaNの座標など）を
+// ハンドラーに渡す前に弾くための検証を行います。
+//
+// 主要な責務：
+// - WebSocketサーバーとクライアントの間でやり取りするメッセージの型定義
+// - 受信したWebSocketメッセージのサイズ・フィールドの検証
+// - move_card()に渡される場所指定（JSON）の解析
+//
+// ここで検証を通ったメッセージだけをハンドラーが扱うため、
+// ハンドラー側では各フィールドの長さや範囲を信頼できます。
+// =============================================================================
+
+use crate::afk::MAX_AFK_SECONDS;
+use crate::combo::MAX_COMBO_WINDOW_SECONDS;
+use crate::power_up::PowerUp;
+use crate::solitaire::CardLocation;
+use crate::solve_cache::SolvedDeal;
+use crate::stats_transfer::MAX_STATS_EXPORT_BYTES;
+use crate::theme::CardBack;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// 受け付けるWebSocketメッセージの最大サイズ（バイト）
+pub const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// ID・名前などの文字列フィールドの最大長（バイト）
+pub const MAX_FIELD_BYTES: usize = 128;
+
+/// 座標として受け付ける値の範囲（絶対値）
+pub const MAX_COORDINATE: f64 = 100_000.0;
+
+/// トーナメントのラウンド数の上限
+const MAX_TOURNAMENT_ROUNDS: u8 = 20;
+
+/// ルームの定員の上限
+pub const MAX_ROOM_PLAYERS: u8 = 8;
+
+/// ターンの制限時間（秒）の上限
+const MAX_TURN_TIME_LIMIT_SECONDS: u32 = 600;
+
+/// カーソルの色の数（同じルームの全員が別の色を使えるよう定員と同じ数）
+pub const CURSOR_COLOR_COUNT: u8 = MAX_ROOM_PLAYERS;
+
+/// 表示名の最大文字数
+pub const MAX_DISPLAY_NAME_CHARS: usize = 20;
+
+/// WebRTCの接続交渉で送るSDP・ICE候補の最大長（バイト）
+const MAX_RTC_SIGNAL_BYTES: usize = 16 * 1024;
+
+/// 通知の送り先（プッシュ通知の購読のエンドポイント）の最大サイズ（バイト）
+const MAX_PUSH_ENDPOINT_BYTES: usize = 1024;
+
+/// 再送を要求できる連番の数（1回のResendRequestあたり）
+pub const MAX_RESEND_SEQUENCES: usize = 64;
+
+/// ファウンデーションに置けるカードの最大数（スパイダーの2デッキ分）
+pub const MAX_FOUNDATION_CARDS: u16 = 104;
+
+/// move_card()に渡される場所指定の最大サイズ（バイト）
+const MAX_LOCATION_BYTES: usize = 256;
+
+/// 場所ごとのインデックスの上限（全ゲーム種類の中で最大の値）
+/// タブローはスパイダーの10列、ファウンデーションはスパイダーの8組
+const MAX_TABLEAU_COLUMNS: i64 = 10;
+const MAX_FOUNDATIONS: i64 = 8;
+const MAX_FREE_CELLS: i64 = 4;
+
+// =============================================================================
+// WebSocketメッセージ
+// =============================================================================
+
+/// プレイヤーのプロフィール情報（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct PlayerProfile {
+    pub player_id: String,
+    pub player_name: String,
+    pub color_index: u8,
+    pub rating: u32,
+    pub games_rated: u32,
+    pub is_bot: bool,
+}
+
+/// クイックリアクションで送れる絵文字
+///
+/// 決められた絵文字だけを受け付けるため、任意の文字列は送れません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    /// 👍
+    ThumbsUp,
+
+    /// 😮
+    Surprised,
+
+    /// 🐌
+    Snail,
+
+    /// 🎉
+    Party,
+}
+
+impl Emote {
+    /// すべての絵文字（リアクションボタンの並び順）
+    pub const ALL: [Emote; 4] = [Emote::ThumbsUp, Emote::Surprised, Emote::Snail, Emote::Party];
+
+    /// 表示する絵文字を取得
+    pub fn as_emoji(&self) -> &'static str {
+        match self {
+            Emote::ThumbsUp => "👍",
+            Emote::Surprised => "😮",
+            Emote::Snail => "🐌",
+            Emote::Party => "🎉",
+        }
+    }
+}
+
+/// メッセージの連番を数えるチャネル
+///
+/// チャネルごとに1から始まる連番を付け、受信側は抜け・順序の入れ替わりを検出します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum Channel {
+    /// カーソル位置（古い位置は捨てる）
+    Cursor,
+
+    /// ゲームアクション・カードの取り合い
+    Actions,
+
+    /// リアクション
+    Chat,
+
+    /// 参加・ルーム・設定など、それ以外の状態の同期
+    Sync,
+}
+
+impl Channel {
+    /// メッセージIDなどに使う名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Cursor => "cursor",
+            Channel::Actions => "actions",
+            Channel::Chat => "chat",
+            Channel::Sync => "sync",
+        }
+    }
+}
+
+/// ゲーム状態
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub enum GameState {
+    Waiting,    // プレイヤー待機中
+    Starting,   // 開始のカウントダウン中
+    Playing,    // ゲーム進行中
+    Finished,   // ゲーム終了
+}
+
+/// WebSocketメッセージタイプ
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "type")]
+pub enum WebSocketMessage {
+    // 接続関連
+    PlayerJoin {
+        player_id: String,
+        player_name: String,
+        player_index: u8,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>, // 前回の接続で受け取ったトークン（保存した設定を引き継ぐ場合のみ）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    SessionToken {
+        session_token: String, // 設定の保存先を表すトークン（本人にだけ送る）
+    },
+    
+    // 接続品質の測定と時刻合わせ（サーバーは同じIDのPongに受信時刻を付けてすぐに返す）
+    Ping {
+        ping_id: u32,
+        #[serde(default)]
+        client_time_ms: u64, // 送信時刻（クライアントの時計）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        clock_offset_ms: Option<i64>, // 推定したサーバーの時計とのずれ（推定前はNone）
+    },
+    Pong {
+        ping_id: u32,
+        client_time_ms: u64, // Pingに含まれていた送信時刻
+        server_time_ms: u64, // サーバーがPingを受け取った時刻
+    },
+    PlayerLeft {
+        player_id: String,
+        player_name: String,
+    },
+    
+    // カーソルの色・表示名の変更（色は同じルーム内で重複しない）
+    UpdatePreferences {
+        player_id: String,
+        #[serde(default)]
+        color_index: Option<u8>,
+        #[serde(default)]
+        player_name: Option<String>,
+    },
+    PlayerUpdated {
+        player_id: String,
+        player_name: String,
+        color_index: u8,
+    },
+    
+    // マウスカーソル関連
+    MousePosition {
+        player_id: String,
+        x: f64,
+        y: f64,
+        timestamp: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sequence: Option<u32>, // カーソルのチャネルでの送信者ごとの連番（古い位置を捨てるため）
+    },
+    
+    // クイックリアクション（カーソルの横に数秒間表示される。観戦者も送信できる）
+    Reaction {
+        player_id: String,
+        emote: Emote,
+    },
+    
+    // ゲームアクション関連
+    GameAction {
+        player_id: String,
+        player_name: String,
+        action: String,
+        x: Option<f64>,
+        y: Option<f64>,
+        timestamp: u64,
+    },
+    
+    // 協力プレイでのカードの取り合い（遅延を補正した時刻で、先に掴んだプレイヤーが取る）
+    GrabCard {
+        room_id: String,
+        player_id: String,
+        card_id: String,
+        timestamp: u64, // 掴んだ時刻（クライアントの時計、サーバーが時計のずれを補正する）
+    },
+    CardGrabbed {
+        room_id: String,
+        player_id: String, // カードを掴んだプレイヤー
+        card_id: String,
+    },
+    GrabRejected {
+        room_id: String,
+        card_id: String,
+        owner_id: String, // 先に掴んでいたプレイヤー（受け取ったらカードを元の位置に戻す）
+    },
+    ReleaseCard {
+        room_id: String,
+        player_id: String,
+        card_id: String,
+    },
+    CardReleased {
+        room_id: String,
+        card_id: String,
+    },
+    
+    // 共有盤面のルームの権限（観戦者は操作できず、持ち主の決まったカードは持ち主だけが動かせる）
+    SetSpectating {
+        room_id: String,
+        player_id: String,
+        spectating: bool, // trueで観戦に切り替え、falseで操作できる参加者に戻る（ホストは観戦にできない）
+    },
+    SetCardOwner {
+        room_id: String,
+        player_id: String, // ホストのみ実行できる
+        card_id: String,
+        owner_id: Option<String>, // Noneの場合は持ち主を外す
+    },
+    // 共有盤面のルームの権限が変わったとき（参加したときは本人にも）送る
+    PermissionsChanged {
+        room_id: String,
+        host_id: Option<String>,
+        spectators: Vec<String>,                // 観戦者のプレイヤーID
+        card_owners: BTreeMap<String, String>,  // カードのID → 持ち主のプレイヤーID
+    },
+    
+    // ルーム関連
+    JoinRoom {
+        room_id: String,
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        password: Option<String>, // パスワード付きのルームに参加する場合のみ
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（参加の通知に同じIDが付いて返る）
+    },
+    // ルームを作成して作成者が参加する（作成者がホストになり、JoinRoomが返る）
+    CreateRoom {
+        player_id: String,
+        name: String,
+        #[serde(default)]
+        max_players: Option<u8>, // 省略時は4人
+        #[serde(default)]
+        password: Option<String>, // 空文字列の場合はパスワードなし
+        #[serde(default)]
+        turn_time_limit: Option<u32>, // ターンの制限時間（秒、省略時はターン制にしない）
+        #[serde(default)]
+        combo_window_seconds: Option<u32>, // レースのコンボの受付時間（秒、省略時はコンボを数えない）
+        #[serde(default)]
+        power_ups: Option<bool>, // パワーアップを使えるカジュアルなルームにするか（省略時は使えない、結果はランク外になる）
+        #[serde(default)]
+        shared_board: Option<bool>, // 参加者全員で1つの盤面を動かすルームにするか（省略時は各自の盤面）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（参加の通知に同じIDが付いて返る）
+    },
+    LeaveRoom {
+        room_id: String,
+        player_id: String,
+    },
+    RoomList {
+        rooms: Vec<RoomInfo>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    GetRoomList {
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    QuickMatch {
+        player_id: String,
+    },
+    // サーバーの再起動後、同じセッショントークンで接続して元のルームに戻ったプレイヤーにだけ送る
+    RoomRestored {
+        room_id: String,
+        seed: Option<u64>, // 進行中の配り札のシード（ゲーム中でなければNone）
+        actions: Vec<LoggedAction>, // 配り札の開始からのアクション（盤面の再現に使う）
+    },
+    
+    // ホスト関連（設定変更・キック・ゲーム開始はホストのみ実行できる）
+    HostChanged {
+        room_id: String,
+        host_id: Option<String>,   // 人間の参加者がいなくなった場合はNone
+        host_name: Option<String>,
+    },
+    KickPlayer {
+        room_id: String,
+        player_id: String,
+        target_id: String,
+    },
+    Kicked {
+        room_id: String,
+        player_id: String,
+        banned: bool,
+        rejoin_after_seconds: Option<u64>, // 再参加できるまでの秒数（参加禁止の場合はNone）
+    },
+    BanPlayer {
+        room_id: String,
+        player_id: String,
+        target_id: String,
+    },
+    UnbanPlayer {
+        room_id: String,
+        player_id: String,
+        target_name: String, // 参加禁止にしたときの表示名（BanListのbanned_namesの名前）
+    },
+    BanList {
+        room_id: String,
+        banned_names: Vec<String>, // 参加禁止にしたときの表示名（判定はセッショントークンで行うため、名前を変えても禁止のまま）
+    },
+    UpdateRoomSettings {
+        room_id: String,
+        player_id: String,
+        name: Option<String>,
+        max_players: Option<u8>,
+        #[serde(default)]
+        password: Option<String>, // 空文字列の場合はパスワードを外す
+        #[serde(default)]
+        turn_time_limit: Option<u32>, // ターンの制限時間（秒、0の場合はターン制にしない）
+        #[serde(default)]
+        combo_window_seconds: Option<u32>, // レースのコンボの受付時間（秒、0の場合はコンボを数えない）
+        #[serde(default)]
+        power_ups: Option<bool>, // パワーアップを使えるカジュアルなルームにするか
+        #[serde(default)]
+        afk_seconds: Option<u32>, // 操作がないまま離席中とみなすまでの時間（秒、0の場合は判定しない）
+        #[serde(default)]
+        pause_on_afk: Option<bool>, // 共有盤面のルームで誰かが離席している間ゲームを一時停止するか
+        #[serde(default)]
+        afk_forfeit_turns: Option<u32>, // ターン制で離席中に続けて飛ばしたら席を没収するターン数（0の場合は没収しない）
+    },
+    RoomSettingsChanged {
+        room_id: String,
+        name: String,
+        max_players: u8,
+        has_password: bool,
+        turn_time_limit: u32,
+        #[serde(default)]
+        combo_window_seconds: u32,
+        #[serde(default)]
+        power_ups: bool,
+        #[serde(default)]
+        afk_seconds: u32,
+        #[serde(default)]
+        pause_on_afk: bool,
+        #[serde(default)]
+        afk_forfeit_turns: u32,
+    },
+    
+    // ターン制のゲーム進行（サーバーのティックで制限時間を数え、時間切れのターンは飛ばす）
+    TurnStarted {
+        room_id: String,
+        player_id: String,
+        turn_number: u32,
+        time_limit_seconds: u32,
+    },
+    TurnTimeWarning {
+        room_id: String,
+        player_id: String,
+        turn_number: u32,
+        remaining_seconds: u32,
+    },
+    TurnTimedOut {
+        room_id: String,
+        player_id: String,
+        turn_number: u32,
+        auto_action: String, // 代わりに実行した操作（"skip_turn" / "auto_draw" / "pass"、離席中で飛ばした場合は"afk_skip"）
+    },
+    
+    // ボット・レース関連
+    AddBot {
+        room_id: String,
+        player_id: String,
+        count: Option<u8>, // 追加する人数（Noneの場合は空席をすべて埋める）
+        moves_per_second: Option<f64>,
+        mistake_probability: Option<f64>,
+    },
+    StartRace {
+        room_id: String,
+        player_id: String,
+        seed: Option<u64>,
+    },
+    RaceStart {
+        room_id: String,
+        seed: u64,
+    },
+    
+    // 準備完了・開始のカウントダウン関連
+    SetReady {
+        room_id: String,
+        player_id: String,
+        ready: bool,
+    },
+    ReadyStatus {
+        room_id: String,
+        ready_player_ids: Vec<String>, // 準備完了したプレイヤー（ボットは常に準備完了）
+        all_ready: bool,
+    },
+    StartCountdown {
+        room_id: String,
+        seconds_remaining: u8,
+    },
+    
+    // レーティング関連
+    PlayerProfile {
+        profile: PlayerProfile,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（PlayerJoinにIDが付いていた場合、本人に送るときのみ）
+    },
+    RatingChanged {
+        player_id: String,
+        player_name: String,
+        old_rating: u32,
+        new_rating: u32,
+    },
+    
+    // 対戦中のスコアの共有（クライアントはスコアが変わるたびに送り、サーバーはルームの他の参加者に中継する）
+    ScoreUpdate {
+        room_id: String,
+        player_id: String,
+        score: u32,
+        foundation_cards: u16, // ファウンデーションに置いたカードの枚数
+    },
+    // レースのコンボ（コンボを数えるルームで、伸びたとき・途切れたときにサーバーが参加者全員に送る）
+    ComboUpdate {
+        room_id: String,
+        player_id: String,
+        combo: u32,       // 続けてファウンデーションに置いた枚数（途切れた場合は0）
+        multiplier: u32,  // 現在の倍率（1〜4）
+        bonus_score: u32, // このレースで貯まったボーナスの合計
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at_ms: Option<u64>, // 次の1枚を置かないとコンボが途切れる時刻（UNIX時刻、ミリ秒、途切れた場合はNone）
+    },
+    // ルームに参加したプレイヤーにだけ送る、他の参加者の最新のスコア
+    Scoreboard {
+        room_id: String,
+        players: Vec<ScoreboardEntry>,
+    },
+    // カードの裏面の共有（Noneは見せるのをやめた。サーバーはルームの他の参加者に中継する）
+    CardBackChanged {
+        room_id: String,
+        player_id: String,
+        card_back: Option<CardBack>,
+    },
+    // 離席（クライアントは操作していない時間を段階ごとに送り、サーバーは離席中になった・戻ったことを配信する）
+    IdleStatus {
+        room_id: String,
+        player_id: String,
+        idle_seconds: u32, // 操作していない時間（秒、0の場合は操作を再開した）
+    },
+    PlayerAfk {
+        room_id: String,
+        player_id: String,
+        afk: bool,         // trueで離席中になった、falseで戻った
+        idle_seconds: u32, // 離席中になった時点で操作していなかった時間（秒）
+        paused: bool,      // 協力プレイのゲームが一時停止しているかどうか
+    },
+    // ターン制で離席中に続けてターンを飛ばしたため、ゲームが終わるまで席を没収した
+    SeatForfeited {
+        room_id: String,
+        player_id: String,
+        afk_turns: u32,
+    },
+    
+    // ゲーム結果（ゲーム終了時にクライアントから送信される）
+    GameResult {
+        player_id: String,
+        result: serde_json::Value,
+    },
+    
+    // フレンドと在席状況（フレンドはセッショントークンごとに保存し、相手もセッショントークンで覚える）
+    AddFriend {
+        player_id: String,
+        friend_id: String, // フレンドにする接続中のプレイヤーのID
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（FriendListに同じIDが付いて返る）
+    },
+    RemoveFriend {
+        player_id: String,
+        friend_name: String, // FriendListに含まれるフレンドの名前（オフラインのフレンドも外せるように名前で指定する）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（FriendListに同じIDが付いて返る）
+    },
+    GetFriends {
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    FriendList {
+        friends: Vec<FriendStatus>, // 追加した順
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    // フレンドの在席状況が変わったとき（接続・切断・ルームへの参加と退室・表示名の変更）に、お互いにフレンドにしている人に送る
+    FriendPresence {
+        friend: FriendStatus,
+    },
+    
+    // ルームへの招待（招待できるのは自分がいるルームに、フレンドにしている接続中のプレイヤーだけ）
+    InviteToRoom {
+        player_id: String,
+        target_id: String,
+        room_id: String,
+    },
+    RoomInvite {
+        invite_id: String,
+        room_id: String,
+        room_name: String,
+        from_player_id: String,
+        from_player_name: String,
+        join_link: String, // ゲーム画面のURLに付けるとそのルームに参加できるクエリ（例：?room=ルームID）
+    },
+    RespondToInvite {
+        player_id: String,
+        invite_id: String,
+        accept: bool, // 受ける場合はtrue（パスワード付きのルームにもそのまま参加する）
+    },
+    // 招待した人に送る招待の結果（受けたがルームに入れなかった場合もaccepted: false）
+    InviteAnswered {
+        invite_id: String,
+        player_id: String,
+        player_name: String,
+        accepted: bool,
+    },
+    
+    // 通知の設定（ターン制のルームで自分のターンになったとき・ホストのルームが満員になったときに通知する）
+    // 送り先はセッショントークンごとに保存する（どちらの通知も明示的に有効にした場合のみ送る）
+    UpdateNotificationSettings {
+        player_id: String,
+        #[serde(default)]
+        endpoint: Option<String>, // プッシュ通知の購読のエンドポイント（Noneの場合は通知をやめて送り先を消す）
+        #[serde(default)]
+        turn: bool,               // 自分のターンになったときに通知する
+        #[serde(default)]
+        room_full: bool,          // ホストをしているルームが満員になったときに通知する
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    NotificationSettings {
+        enabled: bool, // 送り先が登録されているか
+        turn: bool,
+        room_full: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    
+    // 日替わりの配り札（サーバーが日付（UTC）から決め、日付が変わると全員にDailyDealを送る）
+    GetDailyDeal {
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    DailyDeal {
+        day: u64,            // UNIXエポックからの日数（UTC）
+        seed: u64,           // その日の配り札のシード
+        next_change_ms: u64, // 次の配り札に変わる時刻（UNIX時刻、ミリ秒）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        solved: Option<SolvedDeal>, // ソルバーで解いた結果（勝ち筋の有無・難しさ・手数、サーバーがまだ解いていない場合はNone）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（日付が変わったときの通知ではNone）
+    },
+    // 過去の日替わりの配り札とその日のリーダーボード
+    GetDailyArchive {
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    DailyArchive {
+        deals: Vec<ArchivedDailyDeal>, // 新しい順
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    
+    // サーバーが記録している実績・通算成績の署名付きの書き出し（同期アカウント、セッショントークンを持つプレイヤーのみ）
+    SignStats {
+        player_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    StatsSigned {
+        export: String, // 署名を付けたStatsExportのJSON文字列
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    // 別の端末から持ってきた書き出しの署名の確認（読み込む前に送る）
+    VerifyStats {
+        player_id: String,
+        export: String, // 署名付きのStatsExportのJSON文字列
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // 応答を待つ場合に付ける要求のID（サーバーは応答に同じIDを付けて返す）
+    },
+    StatsVerified {
+        valid: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>, // 正しくない場合の理由（署名がない・内容が書き換えられているなど）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>, // 確かめた書き出しの署名（正しい場合のみ、クライアントはこの署名の書き出しだけを読み込む）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（要求にIDが付いていた場合のみ）
+    },
+    
+    // トーナメント関連
+    CreateTournament {
+        room_id: String,
+        player_id: String,
+        rounds: u8,
+        base_seed: Option<u64>,
+    },
+    StartTournament {
+        room_id: String,
+        player_id: String,
+    },
+    TournamentCreated {
+        tournament_id: String,
+        room_id: String,
+        host_id: String,
+        rounds: u8,
+    },
+    TournamentRoundStart {
+        tournament_id: String,
+        round: u8,
+        total_rounds: u8,
+        seed: u64,
+    },
+    TournamentStandings {
+        tournament_id: String,
+        round: u8,
+        standings: Vec<TournamentStanding>,
+    },
+    TournamentFinished {
+        tournament_id: String,
+        winner_id: String,
+        winner_name: String,
+        standings: Vec<TournamentStanding>,
+    },
+    
+    // WebRTCのデータチャネルの接続交渉（サーバーは同じルームの相手にだけ中継する）
+    RtcSignal {
+        room_id: String,
+        from_player_id: String, // 送信者（サーバーが接続のプレイヤーIDと一致するか確認する）
+        to_player_id: String,   // 交渉の相手
+        signal: RtcSignalPayload,
+    },
+    
+    // 受け取りの確認（Ack）が必要なメッセージ（受信側はAckを返し、同じIDのメッセージは一度だけ処理する。
+    // サーバーは状態を変えるメッセージをこれで包んで送り、Ackが届くまで再送する）
+    Reliable {
+        message_id: String,
+        message: Box<WebSocketMessage>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sequence: Option<u32>, // 中のメッセージのチャネルでの連番（抜けを検出して再送を要求するため）
+    },
+    
+    // Reliableで受け取ったメッセージの受け取りの確認
+    Ack {
+        message_id: String,
+    },
+    
+    // 連番の抜けに気付いた受信側からの再送の要求
+    ResendRequest {
+        channel: Channel,
+        sequences: Vec<u32>,
+    },
+    
+    // エラー
+    Error {
+        message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        request_id: Option<String>, // この応答が答える要求のID（エラーの原因になった要求にIDが付いていた場合のみ）
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        field: Option<String>, // 値が不正だったフィールド名（座標が有限でない・範囲外の場合のみ）
+    },
+}
+
+/// WebRTCの接続交渉の内容
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RtcSignalPayload {
+    Offer {
+        sdp: String,
+    },
+    Answer {
+        sdp: String,
+    },
+    IceCandidate {
+        candidate: String,
+        sdp_mid: Option<String>,
+        sdp_m_line_index: Option<u16>,
+    },
+}
+
+/// ルーム情報（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RoomInfo {
+    pub id: String,
+    pub name: String,
+    pub player_count: u8,
+    pub max_players: u8,
+    pub game_state: GameState,
+    pub host_id: Option<String>,     // ホストのプレイヤーID（人間の参加者がいない場合はNone）
+    pub has_password: bool,          // 参加にパスワードが必要かどうか
+    pub ready_player_ids: Vec<String>, // 準備完了したプレイヤーのID
+    pub average_rating: Option<u32>, // 参加者の平均レーティング（空室の場合はNone）
+    #[serde(default)]
+    pub power_ups: bool,             // パワーアップを使えるカジュアルなルームかどうか（結果はランク外）
+    #[serde(default)]
+    pub shared_board: bool,          // 参加者全員で1つの盤面を動かすルームかどうか
+    pub players: Vec<PlayerProfile>, // 参加者のプロフィール
+}
+
+/// ルーム内で行われたアクションの記録（再起動後の盤面の再現用）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+pub struct LoggedAction {
+    pub player_id: String,
+    pub action: String,
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub timestamp: u64, // サーバーの時刻
+}
+
+/// スコアボードに表示する参加者の最新のスコア（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct ScoreboardEntry {
+    /// プレイヤーID
+    pub player_id: String,
+
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// カーソルの色のインデックス
+    pub color_index: u8,
+
+    /// 現在のスコア
+    pub score: u32,
+
+    /// ファウンデーションに置いたカードの枚数
+    pub foundation_cards: u16,
+
+    /// 見せているカードの裏面（見せていない場合はNone）
+    #[serde(default)]
+    pub card_back: Option<CardBack>,
+}
+
+/// トーナメント参加者の順位情報（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct TournamentStanding {
+    /// 順位（1始まり）
+    pub rank: u32,
+
+    /// プレイヤーID
+    pub player_id: String,
+
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// 累計スコア
+    pub total_score: u32,
+
+    /// 累計プレイ時間（秒）
+    pub total_time_seconds: u64,
+
+    /// 結果を送信したラウンド数
+    pub rounds_played: u32,
+
+    /// 勝利したラウンド数
+    pub rounds_won: u32,
+}
+
+/// フレンドの在席状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+pub enum PresenceStatus {
+    Offline, // 接続していない
+    Online,  // 接続中（ルームには参加していない）
+    InRoom,  // ルームに参加中
+}
+
+/// フレンド1人分の情報（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct FriendStatus {
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// 接続中のプレイヤーID（オフライン・相手がこちらをフレンドにしていない場合はNone）
+    pub player_id: Option<String>,
+
+    /// 在席状況（相手がこちらをフレンドにしていない場合は常にOffline）
+    pub status: PresenceStatus,
+
+    /// 参加中のルーム（ルームにいない・パスワード付きのルームにいる場合はNone）
+    pub room_id: Option<String>,
+}
+
+/// 過去の日替わりの配り札の結果1件分（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct DailyResult {
+    /// 順位（1始まり）
+    pub rank: u32,
+
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// 最終スコア
+    pub score: u32,
+
+    /// プレイ時間（秒）
+    pub duration_seconds: u64,
+
+    /// 勝利したかどうか
+    pub won: bool,
+}
+
+/// 過去の日替わりの配り札とその日のリーダーボード（クライアント送信用）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct ArchivedDailyDeal {
+    /// UNIXエポックからの日数（UTC）
+    pub day: u64,
+
+    /// 配り札のシード
+    pub seed: u64,
+
+    /// その日の結果（順位順）
+    pub results: Vec<DailyResult>,
+}
+
+impl WebSocketMessage {
+    /// 受信したテキストを解析し、内容を検証する
+    ///
+    /// # 引数
+    /// * `text` - 受信したテキスト（JSON）
+    ///
+    /// # 戻り値
+    /// 成功時はWebSocketMessage、サイズ超過・形式不正・不正な値の場合はエラーメッセージ
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.len() > MAX_MESSAGE_BYTES {
+            return Err(format!("メッセージが大きすぎます（{}バイト）", text.len()));
+        }
+
+        let message: Self = serde_json::from_str(text)
+            .map_err(|e| format!("メッセージの形式が不正です: {}", e))?;
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// 要求・応答に付いている要求のID
+    ///
+    /// # 戻り値
+    /// 要求のID（IDを付けられない種類のメッセージ、IDが付いていない場合はNone）
+    pub fn request_id(&self) -> Option<&String> {
+        match self {
+            WebSocketMessage::PlayerJoin { request_id, .. }
+            | WebSocketMessage::PlayerProfile { request_id, .. }
+            | WebSocketMessage::JoinRoom { request_id, .. }
+            | WebSocketMessage::CreateRoom { request_id, .. }
+            | WebSocketMessage::GetRoomList { request_id, .. }
+            | WebSocketMessage::RoomList { request_id, .. }
+            | WebSocketMessage::AddFriend { request_id, .. }
+            | WebSocketMessage::RemoveFriend { request_id, .. }
+            | WebSocketMessage::GetFriends { request_id, .. }
+            | WebSocketMessage::FriendList { request_id, .. }
+            | WebSocketMessage::UpdateNotificationSettings { request_id, .. }
+            | WebSocketMessage::NotificationSettings { request_id, .. }
+            | WebSocketMessage::GetDailyDeal { request_id, .. }
+            | WebSocketMessage::DailyDeal { request_id, .. }
+            | WebSocketMessage::GetDailyArchive { request_id, .. }
+            | WebSocketMessage::DailyArchive { request_id, .. }
+            | WebSocketMessage::SignStats { request_id, .. }
+            | WebSocketMessage::StatsSigned { request_id, .. }
+            | WebSocketMessage::VerifyStats { request_id, .. }
+            | WebSocketMessage::StatsVerified { request_id, .. }
+            | WebSocketMessage::Error { request_id, .. } => request_id.as_ref(),
+            WebSocketMessage::Reliable { message, .. } => message.request_id(),
+            _ => None,
+        }
+    }
+
+    /// メッセージの連番を数えるチャネル
+    ///
+    /// Reliableで包まれたメッセージは中のメッセージのチャネルになります。
+    pub fn channel(&self) -> Channel {
+        match self {
+            WebSocketMessage::MousePosition { .. } => Channel::Cursor,
+            WebSocketMessage::GameAction { .. }
+            | WebSocketMessage::GrabCard { .. }
+            | WebSocketMessage::ReleaseCard { .. } => Channel::Actions,
+            WebSocketMessage::Reaction { .. } => Channel::Chat,
+            WebSocketMessage::Reliable { message, .. } => message.channel(),
+            _ => Channel::Sync,
+        }
+    }
+
+    /// メッセージに付いている連番
+    ///
+    /// # 戻り値
+    /// チャネルと連番（連番が付いていない場合はNone）
+    pub fn sequence(&self) -> Option<(Channel, u32)> {
+        match self {
+            WebSocketMessage::MousePosition { sequence, .. }
+            | WebSocketMessage::Reliable { sequence, .. } => {
+                sequence.map(|sequence| (self.channel(), sequence))
+            }
+            _ => None,
+        }
+    }
+
+    /// クライアントがメッセージに送信者として書いたプレイヤーID
+    ///
+    /// PlayerJoin（参加前でまだIDがない）と、サーバーから送信するだけのメッセージはNoneになります。
+    /// Reliableで包まれたメッセージは中のメッセージの送信者になります。
+    pub fn sender_id(&self) -> Option<&str> {
+        match self {
+            WebSocketMessage::UpdatePreferences { player_id, .. }
+            | WebSocketMessage::MousePosition { player_id, .. }
+            | WebSocketMessage::Reaction { player_id, .. }
+            | WebSocketMessage::GameAction { player_id, .. }
+            | WebSocketMessage::GrabCard { player_id, .. }
+            | WebSocketMessage::ReleaseCard { player_id, .. }
+            | WebSocketMessage::SetSpectating { player_id, .. }
+            | WebSocketMessage::SetCardOwner { player_id, .. }
+            | WebSocketMessage::JoinRoom { player_id, .. }
+            | WebSocketMessage::CreateRoom { player_id, .. }
+            | WebSocketMessage::LeaveRoom { player_id, .. }
+            | WebSocketMessage::GetRoomList { player_id, .. }
+            | WebSocketMessage::QuickMatch { player_id }
+            | WebSocketMessage::KickPlayer { player_id, .. }
+            | WebSocketMessage::BanPlayer { player_id, .. }
+            | WebSocketMessage::UnbanPlayer { player_id, .. }
+            | WebSocketMessage::UpdateRoomSettings { player_id, .. }
+            | WebSocketMessage::AddBot { player_id, .. }
+            | WebSocketMessage::StartRace { player_id, .. }
+            | WebSocketMessage::SetReady { player_id, .. }
+            | WebSocketMessage::ScoreUpdate { player_id, .. }
+            | WebSocketMessage::CardBackChanged { player_id, .. }
+            | WebSocketMessage::IdleStatus { player_id, .. }
+            | WebSocketMessage::GameResult { player_id, .. }
+            | WebSocketMessage::AddFriend { player_id, .. }
+            | WebSocketMessage::RemoveFriend { player_id, .. }
+            | WebSocketMessage::GetFriends { player_id, .. }
+            | WebSocketMessage::InviteToRoom { player_id, .. }
+            | WebSocketMessage::RespondToInvite { player_id, .. }
+            | WebSocketMessage::UpdateNotificationSettings { player_id, .. }
+            | WebSocketMessage::GetDailyDeal { player_id, .. }
+            | WebSocketMessage::GetDailyArchive { player_id, .. }
+            | WebSocketMessage::SignStats { player_id, .. }
+            | WebSocketMessage::VerifyStats { player_id, .. }
+            | WebSocketMessage::CreateTournament { player_id, .. }
+            | WebSocketMessage::StartTournament { player_id, .. }
+            | WebSocketMessage::RtcSignal { from_player_id: player_id, .. } => Some(player_id),
+            WebSocketMessage::Reliable { message, .. } => message.sender_id(),
+            _ => None,
+        }
+    }
+
+    /// メッセージに書かれた送信者がこの接続のプレイヤーか確認する
+    ///
+    /// プレイヤーIDは参加の通知やHTTP APIで他のプレイヤーにも見えるので、
+    /// サーバーは書かれたIDではなく接続のプレイヤーIDで権限を判断します。
+    ///
+    /// # 引数
+    /// * `connection_player_id` - この接続で参加したプレイヤーのID（参加前はNone）
+    ///
+    /// # 戻り値
+    /// 送信者が書かれていないか接続のプレイヤーと一致する場合はOk(())、
+    /// 他のプレイヤーを名乗っている（参加前に送信者を書いた）場合はエラーメッセージ
+    pub fn check_sender(&self, connection_player_id: Option<&str>) -> Result<(), String> {
+        match self.sender_id() {
+            Some(sender_id) if connection_player_id != Some(sender_id) => {
+                Err("他のプレイヤーとしてメッセージは送れません".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 解析できなかったテキストから要求のIDだけを取り出す
+    ///
+    /// 形式が不正なメッセージへのエラーにも要求のIDを付けて、
+    /// クライアントが応答を待ち続けないようにするために使います。
+    /// Reliableで包まれたメッセージの場合は中のメッセージの要求のIDを取り出します。
+    ///
+    /// # 引数
+    /// * `text` - 受信したテキスト（JSON）
+    ///
+    /// # 戻り値
+    /// 要求のID（取り出せない場合・長すぎる場合はNone）
+    pub fn request_id_of(text: &str) -> Option<String> {
+        if text.len() > MAX_MESSAGE_BYTES {
+            return None;
+        }
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        value
+            .get("request_id")
+            .or_else(|| value.get("message")?.get("request_id"))?
+            .as_str()
+            .filter(|request_id| request_id.len() <= MAX_FIELD_BYTES)
+            .map(str::to_string)
+    }
+
+    /// 解析できたが検証に失敗したテキストから、値が不正だった数値のフィールド名を取り出す
+    ///
+    /// エラーにフィールド名を付けて、クライアントがどの値を直せばよいか分かるようにするために使います。
+    ///
+    /// # 引数
+    /// * `text` - 受信したテキスト（JSON）
+    ///
+    /// # 戻り値
+    /// フィールド名（数値が不正でない・解析できない場合はNone）
+    pub fn invalid_field_of(text: &str) -> Option<String> {
+        if text.len() > MAX_MESSAGE_BYTES {
+            return None;
+        }
+        let message: Self = serde_json::from_str(text).ok()?;
+        message
+            .invalid_coordinate()
+            .map(|(field, _)| field.to_string())
+    }
+
+    /// 有限でない・範囲外の座標を探す
+    ///
+    /// Reliableで包まれたメッセージの場合は中のメッセージを調べます。
+    ///
+    /// # 戻り値
+    /// 最初に見つかった不正な座標の(フィールド名, 値)（すべて正しい場合はNone）
+    pub fn invalid_coordinate(&self) -> Option<(&'static str, f64)> {
+        let coordinates = match self {
+            WebSocketMessage::MousePosition { x, y, .. } => [Some(*x), Some(*y)],
+            WebSocketMessage::GameAction { x, y, .. } => [*x, *y],
+            WebSocketMessage::Reliable { message, .. } => return message.invalid_coordinate(),
+            _ => return None,
+        };
+        ["x", "y"]
+            .into_iter()
+            .zip(coordinates)
+            .find_map(|(field, value)| {
+                value
+                    .filter(|value| check_coordinate(*value).is_err())
+                    .map(|value| (field, value))
+            })
+    }
+
+    /// 座標が有限かつ範囲内かチェック（エラーメッセージにフィールド名を含める）
+    fn check_coordinates(&self) -> Result<(), String> {
+        match self.invalid_coordinate() {
+            Some((field, value)) => Err(format!("不正な座標です（{}）: {}", field, value)),
+            None => Ok(()),
+        }
+    }
+
+    /// クライアントから送られるメッセージの各フィールドを検証
+    ///
+    /// サーバーから送信するだけのメッセージは検証せずに受け付けます
+    /// （ハンドラー側で未対応メッセージとして扱われます）。
+    ///
+    /// # 戻り値
+    /// 問題がなければOk(())、不正な値があればエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(request_id) = self.request_id() {
+            check_fields(&[request_id])?;
+        }
+
+        match self {
+            WebSocketMessage::PlayerJoin { player_id, player_name, session_token, .. } => {
+                check_fields(&[player_id, player_name])?;
+                session_token.as_ref().map_or(Ok(()), |token| check_fields(&[token]))
+            }
+
+            WebSocketMessage::PlayerLeft { player_id, player_name } => {
+                check_fields(&[player_id, player_name])
+            }
+
+            WebSocketMessage::UpdatePreferences { player_id, color_index, player_name } => {
+                check_fields(&[player_id])?;
+                if let Some(player_name) = player_name {
+                    check_fields(&[player_name])?;
+                }
+                match color_index {
+                    Some(color) if *color == 0 || *color > CURSOR_COLOR_COUNT => Err(format!(
+                        "カーソルの色は1〜{}で指定してください",
+                        CURSOR_COLOR_COUNT
+                    )),
+                    _ => Ok(()),
+                }
+            }
+
+            WebSocketMessage::MousePosition { player_id, .. } => {
+                check_fields(&[player_id])?;
+                self.check_coordinates()
+            }
+
+            WebSocketMessage::GameAction { player_id, player_name, action, x, .. } => {
+                check_fields(&[player_id, player_name, action])?;
+                self.check_coordinates()?;
+                match PowerUp::from_action(action) {
+                    Some(power_up) => power_up?.check_target(*x),
+                    None => Ok(()),
+                }
+            }
+
+            WebSocketMessage::GrabCard { room_id, player_id, card_id, .. }
+            | WebSocketMessage::ReleaseCard { room_id, player_id, card_id } => {
+                check_fields(&[room_id, player_id, card_id])
+            }
+
+            WebSocketMessage::SetCardOwner { room_id, player_id, card_id, owner_id } => {
+                check_fields(&[room_id, player_id, card_id])?;
+                owner_id.as_ref().map_or(Ok(()), |owner_id| check_fields(&[owner_id]))
+            }
+
+            WebSocketMessage::JoinRoom { room_id, player_id, password, .. } => {
+                check_fields(&[room_id, player_id])?;
+                password.as_ref().map_or(Ok(()), |password| check_fields(&[password]))
+            }
+
+            WebSocketMessage::LeaveRoom { room_id, player_id }
+            | WebSocketMessage::StartRace { room_id, player_id, .. }
+            | WebSocketMessage::SetReady { room_id, player_id, .. }
+            | WebSocketMessage::CardBackChanged { room_id, player_id, .. }
+            | WebSocketMessage::IdleStatus { room_id, player_id, .. }
+            | WebSocketMessage::SetSpectating { room_id, player_id, .. }
+            | WebSocketMessage::StartTournament { room_id, player_id } => {
+                check_fields(&[room_id, player_id])
+            }
+
+            WebSocketMessage::KickPlayer { room_id, player_id, target_id }
+            | WebSocketMessage::BanPlayer { room_id, player_id, target_id } => {
+                check_fields(&[room_id, player_id, target_id])
+            }
+
+            WebSocketMessage::UnbanPlayer { room_id, player_id, target_name } => {
+                check_fields(&[room_id, player_id, target_name])
+            }
+
+            WebSocketMessage::UpdateRoomSettings { room_id, player_id, name, max_players, password, turn_time_limit, combo_window_seconds, afk_seconds, .. } => {
+                check_fields(&[room_id, player_id])?;
+                if afk_seconds.is_some_and(|seconds| seconds > MAX_AFK_SECONDS) {
+                    return Err(format!(
+                        "離席とみなすまでの時間は{}秒以下にしてください",
+                        MAX_AFK_SECONDS
+                    ));
+                }
+                check_room_settings(name.as_ref(), *max_players, password.as_ref(), *turn_time_limit, *combo_window_seconds)
+            }
+
+            WebSocketMessage::CreateRoom { player_id, name, max_players, password, turn_time_limit, combo_window_seconds, .. } => {
+                check_fields(&[player_id])?;
+                check_room_settings(Some(name), *max_players, password.as_ref(), *turn_time_limit, *combo_window_seconds)
+            }
+
+            WebSocketMessage::RtcSignal { room_id, from_player_id, to_player_id, signal } => {
+                check_fields(&[room_id, from_player_id, to_player_id])?;
+                let (body, sdp_mid) = match signal {
+                    RtcSignalPayload::Offer { sdp } | RtcSignalPayload::Answer { sdp } => (sdp, None),
+                    RtcSignalPayload::IceCandidate { candidate, sdp_mid, .. } => (candidate, sdp_mid.as_ref()),
+                };
+                if body.len() > MAX_RTC_SIGNAL_BYTES {
+                    return Err(format!(
+                        "接続交渉の内容が長すぎます（{}バイト、上限{}バイト）",
+                        body.len(),
+                        MAX_RTC_SIGNAL_BYTES
+                    ));
+                }
+                sdp_mid.map_or(Ok(()), |sdp_mid| check_fields(&[sdp_mid]))
+            }
+
+            WebSocketMessage::Reliable { message_id, message, .. } => {
+                check_fields(&[message_id])?;
+                if matches!(**message, WebSocketMessage::Reliable { .. } | WebSocketMessage::Ack { .. }) {
+                    return Err("Reliable・Ackは入れ子にできません".to_string());
+                }
+                message.validate()
+            }
+
+            WebSocketMessage::Ack { message_id } => check_fields(&[message_id]),
+
+            WebSocketMessage::ResendRequest { sequences, .. } => {
+                if sequences.len() > MAX_RESEND_SEQUENCES {
+                    return Err(format!(
+                        "再送を要求できる連番は{}個までです",
+                        MAX_RESEND_SEQUENCES
+                    ));
+                }
+                Ok(())
+            }
+
+            WebSocketMessage::ScoreUpdate { room_id, player_id, foundation_cards, .. } => {
+                check_fields(&[room_id, player_id])?;
+                if *foundation_cards > MAX_FOUNDATION_CARDS {
+                    return Err(format!(
+                        "ファウンデーションのカードは{}枚以下にしてください",
+                        MAX_FOUNDATION_CARDS
+                    ));
+                }
+                Ok(())
+            }
+
+            WebSocketMessage::GetRoomList { player_id, .. }
+            | WebSocketMessage::GetFriends { player_id, .. }
+            | WebSocketMessage::GetDailyDeal { player_id, .. }
+            | WebSocketMessage::GetDailyArchive { player_id, .. }
+            | WebSocketMessage::QuickMatch { player_id }
+            | WebSocketMessage::Reaction { player_id, .. }
+            | WebSocketMessage::GameResult { player_id, .. } => check_fields(&[player_id]),
+
+            WebSocketMessage::AddFriend { player_id, friend_id, .. } => {
+                check_fields(&[player_id, friend_id])
+            }
+
+            WebSocketMessage::RemoveFriend { player_id, friend_name, .. } => {
+                check_fields(&[player_id, friend_name])
+            }
+
+            WebSocketMessage::InviteToRoom { player_id, target_id, room_id } => {
+                check_fields(&[player_id, target_id, room_id])
+            }
+
+            WebSocketMessage::RespondToInvite { player_id, invite_id, .. } => {
+                check_fields(&[player_id, invite_id])
+            }
+
+            WebSocketMessage::UpdateNotificationSettings { player_id, endpoint, .. } => {
+                check_fields(&[player_id])?;
+                match endpoint {
+                    Some(endpoint) if endpoint.is_empty() || endpoint.len() > MAX_PUSH_ENDPOINT_BYTES => Err(format!(
+                        "通知の送り先は1〜{}バイトで指定してください",
+                        MAX_PUSH_ENDPOINT_BYTES
+                    )),
+                    Some(endpoint) if endpoint.chars().any(char::is_control) => {
+                        Err("通知の送り先に制御文字は使えません".to_string())
+                    }
+                    _ => Ok(()),
+                }
+            }
+
+            WebSocketMessage::SignStats { player_id, .. } => check_fields(&[player_id]),
+
+            WebSocketMessage::VerifyStats { player_id, export, .. } => {
+                check_fields(&[player_id])?;
+                if export.len() > MAX_STATS_EXPORT_BYTES {
+                    return Err(format!(
+                        "成績の書き出しが大きすぎます（{}バイト、上限{}バイト）",
+                        export.len(),
+                        MAX_STATS_EXPORT_BYTES
+                    ));
+                }
+                Ok(())
+            }
+
+            WebSocketMessage::AddBot {
+                room_id,
+                player_id,
+                moves_per_second,
+                mistake_probability,
+                ..
+            } => {
+                check_fields(&[room_id, player_id])?;
+                check_finite("moves_per_second", *moves_per_second)?;
+                check_finite("mistake_probability", *mistake_probability)
+            }
+
+            WebSocketMessage::CreateTournament { room_id, player_id, rounds, .. } => {
+                check_fields(&[room_id, player_id])?;
+                if *rounds > MAX_TOURNAMENT_ROUNDS {
+                    return Err(format!(
+                        "ラウンド数は{}以下にしてください",
+                        MAX_TOURNAMENT_ROUNDS
+                    ));
+                }
+                Ok(())
+            }
+
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 表示名を整える
+///
+/// 制御文字と見えない文字（ゼロ幅文字や文字の向きを変える文字）を取り除き、
+/// 連続する空白を1つにまとめ、前後の空白を削ってから
+/// MAX_DISPLAY_NAME_CHARS文字に切り詰めます。
+///
+/// # 引数
+/// * `name` - 入力された表示名
+///
+/// # 戻り値
+/// 整えた表示名、何も残らない場合はエラーメッセージ
+pub fn sanitize_display_name(name: &str) -> Result<String, String> {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .filter(|c| !c.is_control() && !is_invisible(*c))
+        .collect();
+    let sanitized: String = cleaned
+        .split(' ')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_DISPLAY_NAME_CHARS)
+        .collect();
+
+    let sanitized = sanitized.trim_end().to_string();
+    if sanitized.is_empty() {
+        return Err("表示名が空です".to_string());
+    }
+    Ok(sanitized)
+}
+
+/// 表示名から取り除く見えない文字かどうか
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2069}' | '\u{FEFF}'
+    )
+}
+
+/// 文字列フィールドが長すぎないかチェック
+fn check_fields(fields: &[&String]) -> Result<(), String> {
+    match fields.iter().find(|field| field.len() > MAX_FIELD_BYTES) {
+        Some(field) => Err(format!(
+            "文字列が長すぎます（{}バイト、上限{}バイト）",
+            field.len(),
+            MAX_FIELD_BYTES
+        )),
+        None => Ok(()),
+    }
+}
+
+/// ルームの設定（作成時・変更時）が範囲内かチェック
+///
+/// # 引数
+/// * `name` - ルーム名（変更しない場合はNone）
+/// * `max_players` - 定員（変更しない場合はNone）
+/// * `password` - パスワード（変更しない場合はNone）
+/// * `turn_time_limit` - ターンの制限時間（秒、変更しない場合はNone）
+/// * `combo_window_seconds` - コンボの受付時間（秒、変更しない場合はNone）
+fn check_room_settings(
+    name: Option<&String>,
+    max_players: Option<u8>,
+    password: Option<&String>,
+    turn_time_limit: Option<u32>,
+    combo_window_seconds: Option<u32>,
+) -> Result<(), String> {
+    if let Some(password) = password {
+        check_fields(&[password])?;
+    }
+    if let Some(name) = name {
+        check_fields(&[name])?;
+        if name.trim().is_empty() {
+            return Err("ルーム名が空です".to_string());
+        }
+    }
+    if turn_time_limit.is_some_and(|limit| limit > MAX_TURN_TIME_LIMIT_SECONDS) {
+        return Err(format!(
+            "ターンの制限時間は{}秒以下にしてください",
+            MAX_TURN_TIME_LIMIT_SECONDS
+        ));
+    }
+    if combo_window_seconds.is_some_and(|window| window > MAX_COMBO_WINDOW_SECONDS) {
+        return Err(format!(
+            "コンボの受付時間は{}秒以下にしてください",
+            MAX_COMBO_WINDOW_SECONDS
+        ));
+    }
+    match max_players {
+        Some(max) if max == 0 || max > MAX_ROOM_PLAYERS => Err(format!(
+            "定員は1〜{}人にしてください",
+            MAX_ROOM_PLAYERS
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 座標が有限かつ範囲内かチェック
+fn check_coordinate(value: f64) -> Result<(), String> {
+    if value.is_finite() && value.abs() <= MAX_COORDINATE {
+        Ok(())
+    } else {
+        Err(format!("不正な座標です: {}", value))
+    }
+}
+
+/// 送信する座標を受け付ける範囲（±MAX_COORDINATE）に収める
+///
+/// # 引数
+/// * `value` - 座標
+///
+/// # 戻り値
+/// 成功時は範囲に収めた座標、有限でない（NaN・無限大）場合はエラーメッセージ
+pub fn clamp_coordinate(value: f64) -> Result<f64, String> {
+    if value.is_finite() {
+        Ok(value.clamp(-MAX_COORDINATE, MAX_COORDINATE))
+    } else {
+        Err(format!("不正な座標です: {}", value))
+    }
+}
+
+/// 数値（オプション）が有限かチェック
+fn check_finite(name: &str, value: Option<f64>) -> Result<(), String> {
+    match value {
+        Some(value) if !value.is_finite() => Err(format!("{}が不正です: {}", name, value)),
+        _ => Ok(()),
+    }
+}
+
+// =============================================================================
+// move_card()の場所指定
+// =============================================================================
+
+/// move_card()に渡される場所指定
+///
+/// JavaScriptからは `{"type": "tableau", "position": 3}` の形で渡されます。
+/// ゲームアクションの詳細データ（ActionPayload）でも同じ形式でシリアライズされ、
+/// デシリアライズ時にはparse()と同じ範囲の検証が行われます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawMoveLocation", into = "RawMoveLocation")]
+pub struct MoveLocation {
+    /// 場所の種類
+    pub location: CardLocation,
+
+    /// 場所のインデックス（タブローの列番号、ファウンデーション番号など）
+    pub index: u32,
+}
+
+/// JavaScriptから渡される場所指定（検証前）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RawMoveLocation {
+    /// 場所の種類（"tableau"、"waste"など）
+    #[serde(rename = "type")]
+    kind: String,
+
+    /// 場所のインデックス（省略またはnullの場合は0）
+    #[serde(default)]
+    position: Option<i64>,
+}
+
+impl MoveLocation {
+    /// 場所指定のJSONを解析し、値の範囲を検証する
+    ///
+    /// インデックスの上限は全ゲーム種類の中で最大の値で判定します。
+    /// 実際のゲームでその場所に置けるかどうかは移動処理の側で判定してください。
+    ///
+    /// # 引数
+    /// * `json` - 場所指定のJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はMoveLocation、不正な指定の場合はエラーメッセージ
+    pub fn parse(json: &str) -> Result<Self, String> {
+        if json.len() > MAX_LOCATION_BYTES {
+            return Err(format!("場所指定が大きすぎます（{}バイト）", json.len()));
+        }
+
+        let raw: RawMoveLocation = serde_json::from_str(json)
+            .map_err(|e| format!("場所指定の形式が不正です: {}", e))?;
+        Self::try_from(raw)
+    }
+}
+
+impl TryFrom<RawMoveLocation> for MoveLocation {
+    type Error = String;
+
+    /// 検証前の場所指定の値の範囲を検証する
+    fn try_from(raw: RawMoveLocation) -> Result<Self, Self::Error> {
+        let (location, limit) = match raw.kind.as_str() {
+            "deck" => (CardLocation::Deck, 1),
+            "waste" => (CardLocation::Waste, 1),
+            "tableau" => (CardLocation::Tableau, MAX_TABLEAU_COLUMNS),
+            "foundation" => (CardLocation::Foundation, MAX_FOUNDATIONS),
+            "freecell" => (CardLocation::FreeCell, MAX_FREE_CELLS),
+            _ => return Err("不明な場所の種類です".to_string()),
+        };
+
+        let position = raw.position.unwrap_or(0);
+        if !(0..limit).contains(&position) {
+            return Err(format!(
+                "{}のインデックスが範囲外です: {}",
+                location.name(),
+                position
+            ));
+        }
+
+        Ok(Self {
+            location,
+            index: position as u32,
+        })
+    }
+}
+
+impl From<MoveLocation> for RawMoveLocation {
+    fn from(location: MoveLocation) -> Self {
+        let kind = match location.location {
+            CardLocation::Deck => "deck",
+            CardLocation::Waste => "waste",
+            CardLocation::Tableau => "tableau",
+            CardLocation::Foundation => "foundation",
+            CardLocation::FreeCell => "freecell",
+            CardLocation::Hand => "hand",
+        };
+        Self {
+            kind: kind.to_string(),
+            position: Some(i64::from(location.index)),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+// =============================================================================
+// プッシュ通知の送り先と中継サーバーへの送信（サーバー用）
+// =============================================================================
+// このファイルでは、ターン制のルームのようにゆっくり進むゲームで、画面を見ていない
+// プレイヤーに知らせるための通知の設定（NotificationStore）と、通知を中継サーバー
+// （プッシュ通知ゲートウェイ）に送るPushGatewayを実装します。
+//
+// 仕組み：
+// - プレイヤーはUpdateNotificationSettingsで、ブラウザのプッシュ通知の購読の
+//   エンドポイントと、どの通知を受け取るかを登録する（どちらの通知も初期状態では送らない）
+// - 設定はセッショントークンごとに保存し、次の接続でも引き継ぐ
+// - 自分のターンになったとき・ホストをしているルームが満員になったときに、
+//   サーバーは環境変数PUSH_GATEWAY_URLの中継サーバーに通知をJSONでPOSTする
+// - Web Pushの暗号化と署名（VAPIDの鍵）は中継サーバーが行う。ゲームサーバーは
+//   プレイヤーが登録したURLには直接接続しない（任意の宛先に送らされないように）
+// - PUSH_GATEWAY_URLが設定されていない場合、設定は保存するが通知は送らない
+// - 中継サーバーが受け取らなかった通知は、セッショントークンごとに溜めて保存し
+//   （プレイヤーがゲームを閉じていても失われないように）、PUSH_RETRY_INTERVAL_MSごとに送り直す。
+//   溜めておくのはプレイヤーごとにMAX_PENDING_NOTIFICATIONS件まで（超えた分は古いものから捨てる）
+//
+// 中継サーバーとの通信にはHTTP/1.1のPOSTに必要な部分だけを実装しています（http://のみ）。
+// =============================================================================
+
+use crate::storage;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// 通知の設定の保存キー
+const STORAGE_KEY: &str = "notifications";
+
+/// 中継サーバーへの送信を待つ時間の上限
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// HTTPの既定のポート番号
+const DEFAULT_HTTP_PORT: u16 = 80;
+
+/// 中継サーバーの応答を読み込む上限（バイト、使うのはステータス行だけのため残りは読まない）
+const MAX_RESPONSE_BYTES: u64 = 16 * 1024;
+
+/// 届けられなかった通知をプレイヤーごとに溜めておく上限
+pub const MAX_PENDING_NOTIFICATIONS: usize = 20;
+
+/// 届けられなかった通知を送り直す間隔の既定値（ミリ秒）
+const DEFAULT_RETRY_INTERVAL_MS: u64 = 30_000;
+
+/// プレイヤー1人分の通知の設定
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// プッシュ通知の購読のエンドポイント
+    pub endpoint: String,
+
+    /// 自分のターンになったときに通知する
+    pub turn: bool,
+
+    /// ホストをしているルームが満員になったときに通知する
+    pub room_full: bool,
+}
+
+/// 全プレイヤーの通知の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationStore {
+    /// セッショントークン → 通知の設定
+    records: HashMap<String, NotificationSettings>,
+
+    /// セッショントークン → 中継サーバーに届けられず送り直しを待っている通知（古い順）
+    #[serde(default)]
+    pending: HashMap<String, VecDeque<PushNotification>>,
+}
+
+impl NotificationStore {
+    /// 保存されている通知の設定を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のNotificationStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 通知の設定を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("通知の設定のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// セッショントークンの通知の設定を取得
+    ///
+    /// # 引数
+    /// * `session_token` - プレイヤーのセッショントークン
+    ///
+    /// # 戻り値
+    /// 送り先が登録されていればSome(設定)、なければNone
+    pub fn get(&self, session_token: &str) -> Option<&NotificationSettings> {
+        self.records.get(session_token)
+    }
+
+    /// 通知の設定を変更する
+    ///
+    /// # 引数
+    /// * `session_token` - プレイヤーのセッショントークン
+    /// * `settings` - 新しい設定（Noneの場合は送り先を消して通知をやめる）
+    pub fn set(&mut self, session_token: &str, settings: Option<NotificationSettings>) {
+        match settings {
+            Some(settings) => {
+                self.records.insert(session_token.to_string(), settings);
+            }
+            None => {
+                self.records.remove(session_token);
+                self.pending.remove(session_token);
+            }
+        }
+    }
+
+    /// 届けられなかった通知を送り直すまで溜めておく
+    ///
+    /// # 引数
+    /// * `session_token` - 通知を受け取るプレイヤーのセッショントークン
+    /// * `notification` - 届けられなかった通知
+    pub fn queue(&mut self, session_token: &str, notification: PushNotification) {
+        let queue = self.pending.entry(session_token.to_string()).or_default();
+        queue.push_back(notification);
+        while queue.len() > MAX_PENDING_NOTIFICATIONS {
+            queue.pop_front();
+        }
+    }
+
+    /// 送り直しを待っている通知をすべて取り出す
+    ///
+    /// 溜めている間に通知をやめた・送り先を変えた・その種類の通知を切ったプレイヤーの通知は捨てます。
+    ///
+    /// # 戻り値
+    /// セッショントークンと通知の組（プレイヤーごとに古い順）
+    pub fn take_pending(&mut self) -> Vec<(String, PushNotification)> {
+        let pending = std::mem::take(&mut self.pending);
+        pending
+            .into_iter()
+            .flat_map(|(session_token, queue)| {
+                queue.into_iter().map(move |notification| (session_token.clone(), notification))
+            })
+            .filter(|(session_token, notification)| {
+                self.records.get(session_token).is_some_and(|settings| {
+                    settings.endpoint == notification.endpoint
+                        && match notification.kind {
+                            PushKind::Turn => settings.turn,
+                            PushKind::RoomFull => settings.room_full,
+                        }
+                })
+            })
+            .collect()
+    }
+
+    /// 送り直しを待っている通知の件数
+    pub fn pending_count(&self) -> usize {
+        self.pending.values().map(VecDeque::len).sum()
+    }
+}
+
+/// 中継サーバーに送る通知の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PushKind {
+    /// 自分のターンになった
+    Turn,
+
+    /// ホストをしているルームが満員になった
+    RoomFull,
+}
+
+/// 中継サーバーに送る通知
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushNotification {
+    /// プッシュ通知の購読のエンドポイント
+    pub endpoint: String,
+
+    /// 通知の種類
+    pub kind: PushKind,
+
+    /// ルームID
+    pub room_id: String,
+
+    /// ルーム名
+    pub room_name: String,
+
+    /// 通知の見出し
+    pub title: String,
+
+    /// 通知の本文
+    pub body: String,
+}
+
+impl PushNotification {
+    /// 自分のターンになったことを知らせる通知を作成
+    pub fn turn(endpoint: &str, room_id: &str, room_name: &str, turn_number: u32) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            kind: PushKind::Turn,
+            room_id: room_id.to_string(),
+            room_name: room_name.to_string(),
+            title: "あなたのターンです".to_string(),
+            body: format!("{}（{}ターン目）", room_name, turn_number),
+        }
+    }
+
+    /// ホストをしているルームが満員になったことを知らせる通知を作成
+    pub fn room_full(endpoint: &str, room_id: &str, room_name: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            kind: PushKind::RoomFull,
+            room_id: room_id.to_string(),
+            room_name: room_name.to_string(),
+            title: "ルームが満員になりました".to_string(),
+            body: format!("{}の参加者が揃いました", room_name),
+        }
+    }
+}
+
+/// 通知の中継サーバー
+#[derive(Debug, Clone, Default)]
+pub struct PushGateway {
+    /// 接続先（ホスト:ポートとパス、設定されていない場合はNone）
+    target: Option<(String, String)>,
+
+    /// 届けられなかった通知を送り直す間隔
+    retry_interval: Duration,
+}
+
+impl PushGateway {
+    /// 環境変数PUSH_GATEWAY_URLから中継サーバーを設定する
+    ///
+    /// 環境変数PUSH_RETRY_INTERVAL_MSが設定されていれば、届けられなかった通知をその間隔で送り直します。
+    ///
+    /// # 戻り値
+    /// 設定されていない・URLが不正な場合は通知を送らないPushGateway
+    pub fn from_env() -> Self {
+        let Ok(url) = std::env::var("PUSH_GATEWAY_URL") else {
+            info!(
+                "ℹ️ プッシュ通知の中継サーバー未設定: 通知の設定は保存しますが、通知は送りません"
+            );
+            return Self::default();
+        };
+        match parse_http_url(&url) {
+            Ok(target) => {
+                info!("🔔 プッシュ通知の中継サーバー: {}", url);
+                Self {
+                    target: Some(target),
+                    retry_interval: Duration::from_millis(
+                        std::env::var("PUSH_RETRY_INTERVAL_MS")
+                            .ok()
+                            .and_then(|ms| ms.parse().ok())
+                            .unwrap_or(DEFAULT_RETRY_INTERVAL_MS),
+                    ),
+                }
+            }
+            Err(e) => {
+                warn!("⚠️ {}（通知は送りません）", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// 通知を中継サーバーに送る
+    ///
+    /// # 引数
+    /// * `notification` - 送る通知
+    ///
+    /// # 戻り値
+    /// 中継サーバーが2xxを返した場合Ok(())、未設定・接続できない・エラーの場合はエラーメッセージ
+    pub async fn send(&self, notification: &PushNotification) -> Result<(), String> {
+        let (addr, path) = self
+            .target
+            .as_ref()
+            .ok_or_else(|| "プッシュ通知の中継サーバーが設定されていません".to_string())?;
+        let body = serde_json::to_string(notification)
+            .map_err(|e| format!("通知のシリアライゼーション失敗: {}", e))?;
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            addr,
+            body.len(),
+            body
+        );
+
+        let exchange = async {
+            let mut stream = TcpStream::connect(addr).await?;
+            stream.write_all(request.as_bytes()).await?;
+            let mut response = Vec::new();
+            stream
+                .take(MAX_RESPONSE_BYTES)
+                .read_to_end(&mut response)
+                .await?;
+            Ok::<_, std::io::Error>(response)
+        };
+        let response = tokio::time::timeout(SEND_TIMEOUT, exchange)
+            .await
+            .map_err(|_| "中継サーバーの応答がありません".to_string())?
+            .map_err(|e| format!("中継サーバーに送信できません: {}", e))?;
+
+        let status = String::from_utf8_lossy(&response)
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| "中継サーバーの応答の形式が不正です".to_string())?;
+        if !(200..300).contains(&status) {
+            return Err(format!("中継サーバーがエラーを返しました: {}", status));
+        }
+        debug!(
+            "🔔 通知を送信: {:?} {}",
+            notification.kind, notification.room_id
+        );
+        Ok(())
+    }
+
+    /// 中継サーバーが設定されているか
+    pub fn is_enabled(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// 届けられなかった通知を送り直す間隔
+    pub fn retry_interval(&self) -> Duration {
+        self.retry_interval
+    }
+}
+
+/// 中継サーバーのURL（http://ホスト[:ポート][/パス]）から接続先とパスを取り出す
+fn parse_http_url(url: &str) -> Result<(String, String), String> {
+    let rest = url.strip_prefix("http://").ok_or_else(|| {
+        format!(
+            "対応していない中継サーバーのURLです（http://のみ）: {}",
+            url
+        )
+    })?;
+    let (host, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    if host.is_empty() {
+        return Err("中継サーバーのホストが指定されていません".to_string());
+    }
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, DEFAULT_HTTP_PORT)
+    };
+    Ok((addr, path.to_string()))
+}
@@ -0,0 +1,432 @@
+// =============================================================================
+// パズルモード
+// =============================================================================
+// このファイルでは、用意された局面から目標（○手以内にクリア、
+// ♠Aを5手以内に組札へ、など）を達成するパズルモードを実装します。
+// 局面はシナリオ形式（scenario.rs）で定義し、BoardBuilderで組み立てます。
+//
+// 主要な責務：
+// - パズルと目標の定義
+// - 組み込みパズル集の提供
+// - 目標の進み具合（PuzzleProgressコンポーネント）の追跡と達成・失敗の判定
+//
+// 手数の数え方：
+// - 移動履歴（MoveLog）の件数を手数とする（カードの移動とデッキから引く操作）
+// - 裏向きカードのめくりやウェイストの戻しは手数に含めない
+// =============================================================================
+
+use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::scenario::{parse_card, BoardBuilder, Scenario};
+use crate::solitaire::{CardLocation, MoveLog, SolitaireCard};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// パズルの定義
+// =============================================================================
+
+/// パズルの目標
+///
+/// JSONでは`{"type": "win_within", "moves": 3}`の形式になります。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PuzzleObjective {
+    /// 指定した手数以内に盤面のカードをすべてファウンデーションへ置く
+    WinWithin {
+        /// 使える手数
+        moves: u32,
+    },
+
+    /// 指定した手数以内に特定のカードをファウンデーションへ置く
+    FreeCard {
+        /// 対象のカード（例："AS"）
+        card: String,
+        /// 使える手数
+        moves: u32,
+    },
+}
+
+impl PuzzleObjective {
+    /// 使える手数を取得
+    ///
+    /// # 戻り値
+    /// 手数の上限
+    pub fn move_limit(&self) -> u32 {
+        match self {
+            PuzzleObjective::WinWithin { moves } | PuzzleObjective::FreeCard { moves, .. } => {
+                *moves
+            }
+        }
+    }
+
+    /// 目標の説明を取得
+    ///
+    /// # 戻り値
+    /// 表示用の説明文字列
+    pub fn description(&self) -> String {
+        match self {
+            PuzzleObjective::WinWithin { moves } => {
+                format!("{}手以内にすべてのカードを組札へ", moves)
+            }
+            PuzzleObjective::FreeCard { card, moves } => match parse_card(card) {
+                Ok((suit, rank)) => {
+                    format!(
+                        "{}手以内に{}{}を組札へ",
+                        moves,
+                        suit.symbol(),
+                        rank.display()
+                    )
+                }
+                Err(_) => format!("{}手以内に{}を組札へ", moves, card),
+            },
+        }
+    }
+
+    /// 目標を検証
+    ///
+    /// # 戻り値
+    /// 正しい場合はOk、手数が0・カードの表記が不正な場合はエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        if self.move_limit() == 0 {
+            return Err("手数の上限は1以上にしてください".to_string());
+        }
+        if let PuzzleObjective::FreeCard { card, .. } = self {
+            parse_card(card)?;
+        }
+        Ok(())
+    }
+
+    /// 盤面が目標を満たしているかチェック
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 目標を満たしている場合true
+    pub fn is_met(&self, world: &World) -> bool {
+        match self {
+            PuzzleObjective::WinWithin { .. } => world
+                .query::<SolitaireCard>()
+                .all(|(_, card)| card.location_type == CardLocation::Foundation),
+            PuzzleObjective::FreeCard { card, .. } => {
+                let Ok((suit, rank)) = parse_card(card) else {
+                    return false;
+                };
+                world.query::<SolitaireCard>().any(|(_, c)| {
+                    c.suit == suit && c.rank == rank && c.location_type == CardLocation::Foundation
+                })
+            }
+        }
+    }
+}
+
+/// パズル
+///
+/// 問題の局面（シナリオ）と達成すべき目標の組です。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// パズルID
+    pub id: String,
+
+    /// パズル名
+    pub title: String,
+
+    /// 達成すべき目標
+    pub objective: PuzzleObjective,
+
+    /// 問題の局面
+    pub scenario: Scenario,
+}
+
+/// パズル一覧に表示する情報（局面は含まない）
+#[derive(Debug, Clone, Serialize)]
+pub struct PuzzleSummary {
+    /// パズルID
+    pub id: String,
+
+    /// パズル名
+    pub title: String,
+
+    /// 目標の説明
+    pub description: String,
+
+    /// 達成すべき目標
+    pub objective: PuzzleObjective,
+}
+
+impl Puzzle {
+    /// 新しいパズルを作成
+    ///
+    /// # 引数
+    /// * `id` - パズルID
+    /// * `title` - パズル名
+    /// * `objective` - 達成すべき目標
+    /// * `board` - 問題の局面を組み立てたビルダー
+    pub fn new(id: &str, title: &str, objective: PuzzleObjective, board: BoardBuilder) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            objective,
+            scenario: board.scenario().clone(),
+        }
+    }
+
+    /// 一覧表示用の情報を取得
+    ///
+    /// # 戻り値
+    /// 局面を除いたPuzzleSummary
+    pub fn summary(&self) -> PuzzleSummary {
+        PuzzleSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            description: self.objective.description(),
+            objective: self.objective.clone(),
+        }
+    }
+
+    /// パズルの局面をワールドに作成し、進み具合の追跡を始める
+    ///
+    /// 目標か局面が不正な場合、ワールドは変更されません。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ（PuzzleProgressを持つ）、不正な場合はエラーメッセージ
+    pub fn start(&self, world: &mut World) -> Result<Entity, String> {
+        self.objective.validate()?;
+        let game_entity = BoardBuilder::from_scenario(self.scenario.clone()).build(world)?;
+        world.add_component(game_entity, PuzzleProgress::new(self));
+
+        info!(
+            "🧩 パズル「{}」開始: {}",
+            self.title,
+            self.objective.description()
+        );
+        Ok(game_entity)
+    }
+}
+
+// =============================================================================
+// 組み込みパズル集
+// =============================================================================
+
+/// 組み込みのパズル集を取得
+///
+/// # 戻り値
+/// やさしい順のパズルのベクター
+pub fn builtin_puzzles() -> Vec<Puzzle> {
+    vec![
+        Puzzle::new(
+            "finishing_touch",
+            "最後の仕上げ",
+            PuzzleObjective::WinWithin { moves: 3 },
+            BoardBuilder::new()
+                .tableau(0, 0, &["KC"])
+                .tableau(1, 0, &["KS", "QS"])
+                .foundation(0, &run('H', 13))
+                .foundation(1, &run('D', 13))
+                .foundation(2, &run('C', 12))
+                .foundation(3, &run('S', 11)),
+        ),
+        Puzzle::new(
+            "free_the_spade_ace",
+            "♠Aを救え",
+            PuzzleObjective::FreeCard {
+                card: "AS".to_string(),
+                moves: 5,
+            },
+            BoardBuilder::new()
+                .tableau(0, 0, &["AS", "6H", "9D", "8C"])
+                .tableau(1, 1, &["2S", "10S"])
+                .tableau(2, 0, &["7C"])
+                .tableau(3, 0, &["QD"])
+                .deck(&["4C", "JH"]),
+        ),
+        Puzzle::new(
+            "from_the_deck",
+            "山札から",
+            PuzzleObjective::WinWithin { moves: 5 },
+            BoardBuilder::new()
+                .tableau(0, 0, &["KH"])
+                .foundation(0, &run('H', 10))
+                .foundation(1, &run('D', 13))
+                .foundation(2, &run('C', 13))
+                .foundation(3, &run('S', 13))
+                .deck(&["QH", "JH"]),
+        ),
+        Puzzle::new(
+            "buried_heart",
+            "埋もれたハート",
+            PuzzleObjective::FreeCard {
+                card: "AH".to_string(),
+                moves: 4,
+            },
+            BoardBuilder::new()
+                .tableau(0, 2, &["AH", "3C", "QD"])
+                .tableau(1, 0, &["KS"])
+                .tableau(2, 0, &["4D"])
+                .waste(&["9C"]),
+        ),
+    ]
+}
+
+/// 組み込みのパズルをIDで探す
+///
+/// # 引数
+/// * `id` - パズルID
+///
+/// # 戻り値
+/// 見つかった場合はSome(Puzzle)
+pub fn find_puzzle(id: &str) -> Option<Puzzle> {
+    builtin_puzzles().into_iter().find(|puzzle| puzzle.id == id)
+}
+
+/// 指定したスートのAから`top`までの表記
+fn run(suit: char, top: usize) -> Vec<&'static str> {
+    const SPADES: [&str; 13] = [
+        "AS", "2S", "3S", "4S", "5S", "6S", "7S", "8S", "9S", "10S", "JS", "QS", "KS",
+    ];
+    const HEARTS: [&str; 13] = [
+        "AH", "2H", "3H", "4H", "5H", "6H", "7H", "8H", "9H", "10H", "JH", "QH", "KH",
+    ];
+    const DIAMONDS: [&str; 13] = [
+        "AD", "2D", "3D", "4D", "5D", "6D", "7D", "8D", "9D", "10D", "JD", "QD", "KD",
+    ];
+    const CLUBS: [&str; 13] = [
+        "AC", "2C", "3C", "4C", "5C", "6C", "7C", "8C", "9C", "10C", "JC", "QC", "KC",
+    ];
+    let codes = match suit {
+        'S' => &SPADES,
+        'H' => &HEARTS,
+        'D' => &DIAMONDS,
+        _ => &CLUBS,
+    };
+    codes[..top].to_vec()
+}
+
+// =============================================================================
+// 進み具合の追跡
+// =============================================================================
+
+/// パズルの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PuzzleStatus {
+    /// 挑戦中
+    InProgress,
+
+    /// 目標を達成した
+    Solved,
+
+    /// 目標を達成できずに手数を使い切った
+    Failed,
+}
+
+/// パズルの進み具合コンポーネント
+///
+/// パズルのゲーム状態エンティティに添付され、
+/// PuzzleSystemが毎フレーム手数と目標の達成を判定します。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PuzzleProgress {
+    /// パズルID
+    pub puzzle_id: String,
+
+    /// 達成すべき目標
+    pub objective: PuzzleObjective,
+
+    /// 使った手数
+    pub moves_used: u32,
+
+    /// パズルの状態
+    pub status: PuzzleStatus,
+}
+
+impl Component for PuzzleProgress {}
+
+impl PuzzleProgress {
+    /// パズルの進み具合を作成
+    ///
+    /// # 引数
+    /// * `puzzle` - 挑戦するパズル
+    pub fn new(puzzle: &Puzzle) -> Self {
+        Self {
+            puzzle_id: puzzle.id.clone(),
+            objective: puzzle.objective.clone(),
+            moves_used: 0,
+            status: PuzzleStatus::InProgress,
+        }
+    }
+
+    /// 残りの手数を取得
+    ///
+    /// # 戻り値
+    /// 使える残りの手数
+    pub fn moves_remaining(&self) -> u32 {
+        self.objective.move_limit().saturating_sub(self.moves_used)
+    }
+}
+
+/// パズル判定システム
+///
+/// 挑戦中のパズルについて、移動履歴から手数を数え、
+/// 目標を達成したか手数を使い切ったかを判定してイベントを通知します。
+/// 上限ちょうどの手で目標を達成した場合は達成として扱います。
+pub struct PuzzleSystem;
+
+impl System for PuzzleSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let in_progress: Vec<(Entity, PuzzleObjective, u32)> = world
+            .query::<PuzzleProgress>()
+            .filter(|(_, progress)| progress.status == PuzzleStatus::InProgress)
+            .map(|(entity, progress)| {
+                let moves_used = world
+                    .get_component::<MoveLog>(entity)
+                    .map_or(0, |log| log.moves.len() as u32);
+                (entity, progress.objective.clone(), moves_used)
+            })
+            .collect();
+
+        for (entity, objective, moves_used) in in_progress {
+            let status = if objective.is_met(world) {
+                PuzzleStatus::Solved
+            } else if moves_used >= objective.move_limit() {
+                PuzzleStatus::Failed
+            } else {
+                PuzzleStatus::InProgress
+            };
+
+            let Some(progress) = world.get_component_mut::<PuzzleProgress>(entity) else {
+                continue;
+            };
+            progress.moves_used = moves_used;
+            progress.status = status;
+            let puzzle_id = progress.puzzle_id.clone();
+
+            let event = match status {
+                PuzzleStatus::InProgress => continue,
+                PuzzleStatus::Solved => {
+                    info!("🏆 パズル「{}」達成: {}手", puzzle_id, moves_used);
+                    GameEvent::PuzzleSolved {
+                        id: puzzle_id,
+                        moves_used,
+                    }
+                }
+                PuzzleStatus::Failed => {
+                    info!(
+                        "💦 パズル「{}」失敗: {}手を使い切りました",
+                        puzzle_id, moves_used
+                    );
+                    GameEvent::PuzzleFailed {
+                        id: puzzle_id,
+                        moves_used,
+                    }
+                }
+            };
+            if let Some(events) = world.get_resource_mut::<EventQueue>() {
+                events.push(event);
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// =============================================================================
+// Eloレーティング（サーバー用）
+// =============================================================================
+// このファイルでは、対戦（同じ配り札での早解き勝負）の結果から
+// プレイヤーの強さを数値化するEloレーティングを実装します。
+//
+// 主要な責務：
+// - 1対1の対戦結果の判定（勝利 → クリア時間 → スコアの順で比較）
+// - Eloの式によるレーティングの更新
+// - マッチング用のレーティング帯（バケット）の算出
+// - レーティングの保存・読み込み
+// =============================================================================
+
+use crate::leaderboard::SubmittedResult;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// レーティングデータの保存キー
+const STORAGE_KEY: &str = "ratings";
+
+/// 初期レーティング
+pub const INITIAL_RATING: u32 = 1200;
+
+/// 1試合でのレーティング変動の大きさ（Kファクター）
+const K_FACTOR: f64 = 32.0;
+
+/// マッチングで同じ帯とみなすレーティング幅
+const BUCKET_WIDTH: u32 = 200;
+
+/// レーティングの最低値
+const MIN_RATING: f64 = 100.0;
+
+/// プレイヤー1人分のレーティング記録
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RatingRecord {
+    /// 現在のレーティング
+    pub rating: u32,
+
+    /// レーティング対象の対戦数
+    pub games_rated: u32,
+}
+
+impl Default for RatingRecord {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            games_rated: 0,
+        }
+    }
+}
+
+/// レーティングからマッチング用のレーティング帯を算出
+///
+/// # 引数
+/// * `rating` - レーティング
+///
+/// # 戻り値
+/// レーティング帯（0始まり、BUCKET_WIDTHごと）
+pub fn rating_bucket(rating: u32) -> u32 {
+    rating / BUCKET_WIDTH
+}
+
+/// 対戦結果を比較
+///
+/// 勝利した方が上、両者勝利ならクリア時間が短い方が上、
+/// 両者敗北ならスコアが高い方が上になります。
+///
+/// # 引数
+/// * `a` - プレイヤーAの結果
+/// * `b` - プレイヤーBの結果
+///
+/// # 戻り値
+/// Aが勝ちの場合Greater、引き分けEqual、負けLess
+pub fn compare_race_results(a: &SubmittedResult, b: &SubmittedResult) -> Ordering {
+    match (a.won, b.won) {
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (true, true) => b.duration_seconds.cmp(&a.duration_seconds),
+        (false, false) => a.score.cmp(&b.score),
+    }
+}
+
+/// レーティング変更の記録
+#[derive(Debug, Clone, PartialEq)]
+pub struct RatingChange {
+    /// プレイヤー名
+    pub player_name: String,
+
+    /// 変更前のレーティング
+    pub old_rating: u32,
+
+    /// 変更後のレーティング
+    pub new_rating: u32,
+}
+
+/// 全プレイヤーのレーティング
+///
+/// プレイヤーIDは接続ごとに変わるため、プレイヤー名をキーにして保存します。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RatingStore {
+    /// プレイヤー名 → レーティング記録
+    records: HashMap<String, RatingRecord>,
+}
+
+impl RatingStore {
+    /// 保存されているレーティングを読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ空のRatingStore
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// レーティングを保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("レーティングのシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// プレイヤーのレーティング記録を取得
+    ///
+    /// # 引数
+    /// * `player_name` - プレイヤー名
+    ///
+    /// # 戻り値
+    /// 記録がない場合は初期レーティングの記録
+    pub fn get(&self, player_name: &str) -> RatingRecord {
+        self.records.get(player_name).copied().unwrap_or_default()
+    }
+
+    /// 1対1の対戦結果でレーティングを更新
+    ///
+    /// # 引数
+    /// * `player_a` - プレイヤーAの名前
+    /// * `player_b` - プレイヤーBの名前
+    /// * `outcome` - Aから見た対戦結果（Greater=勝ち、Equal=引き分け、Less=負け）
+    ///
+    /// # 戻り値
+    /// A、Bそれぞれのレーティング変更
+    pub fn record_match(
+        &mut self,
+        player_a: &str,
+        player_b: &str,
+        outcome: Ordering,
+    ) -> (RatingChange, RatingChange) {
+        let a = self.get(player_a);
+        let b = self.get(player_b);
+
+        let score_a = match outcome {
+            Ordering::Greater => 1.0,
+            Ordering::Equal => 0.5,
+            Ordering::Less => 0.0,
+        };
+
+        // 期待勝率: E_a = 1 / (1 + 10^((R_b - R_a) / 400))
+        let expected_a = 1.0 / (1.0 + 10f64.powf((b.rating as f64 - a.rating as f64) / 400.0));
+        let delta = K_FACTOR * (score_a - expected_a);
+
+        let new_a = (a.rating as f64 + delta).max(MIN_RATING).round() as u32;
+        let new_b = (b.rating as f64 - delta).max(MIN_RATING).round() as u32;
+
+        self.records.insert(
+            player_a.to_string(),
+            RatingRecord {
+                rating: new_a,
+                games_rated: a.games_rated + 1,
+            },
+        );
+        self.records.insert(
+            player_b.to_string(),
+            RatingRecord {
+                rating: new_b,
+                games_rated: b.games_rated + 1,
+            },
+        );
+
+        (
+            RatingChange {
+                player_name: player_a.to_string(),
+                old_rating: a.rating,
+                new_rating: new_a,
+            },
+            RatingChange {
+                player_name: player_b.to_string(),
+                old_rating: b.rating,
+                new_rating: new_b,
+            },
+        )
+    }
+}
@@ -0,0 +1,103 @@
+// =============================================================================
+// クイックリアクション
+// =============================================================================
+// このファイルでは、他のプレイヤー（観戦者を含む）から届いたリアクション
+// （👍 😮 🐌 🎉）を、送信者のカーソルの横に数秒間表示するための
+// コンポーネントとシステムを実装します。
+//
+// 仕組み：
+// - 受信したリアクションはMessageProcessingSystemがshow()でTimedReactionとして追加する
+// - 同じプレイヤーから続けて届いた場合は、前のリアクションを新しいものに置き換える
+// - ReactionSystemが残り時間を減らし、時間切れになったリアクションを消す
+// - JavaScript側はget_reactions()で表示中のリアクションを取得し、カーソルの横に描画する
+// =============================================================================
+
+use crate::ecs::{Component, Entity, System, World};
+use crate::protocol::Emote;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// リアクションを表示しておく時間（秒）
+pub const REACTION_DISPLAY_SECONDS: f64 = 3.0;
+
+/// 表示中のリアクション
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TimedReaction {
+    /// 送信したプレイヤーのID（カーソルの特定に使う）
+    pub player_id: String,
+
+    /// 絵文字の種類
+    pub emote: Emote,
+
+    /// 表示の残り時間（秒）
+    pub remaining_seconds: f64,
+}
+
+impl Component for TimedReaction {}
+
+/// リアクションを表示する
+///
+/// 同じプレイヤーのリアクションが表示中の場合は置き換えます。
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `player_id` - 送信したプレイヤーのID
+/// * `emote` - 絵文字の種類
+///
+/// # 戻り値
+/// リアクションのエンティティ
+pub fn show(world: &mut World, player_id: &str, emote: Emote) -> Entity {
+    let previous: Vec<Entity> = world
+        .query::<TimedReaction>()
+        .filter(|(_, reaction)| reaction.player_id == player_id)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in previous {
+        world.remove_entity(entity);
+    }
+
+    debug!("{} リアクションを表示: {}", emote.as_emoji(), player_id);
+    let entity = world.create_entity();
+    world.add_component(
+        entity,
+        TimedReaction {
+            player_id: player_id.to_string(),
+            emote,
+            remaining_seconds: REACTION_DISPLAY_SECONDS,
+        },
+    );
+    entity
+}
+
+/// 表示中のリアクションの一覧（プレイヤーID順）
+pub fn active(world: &World) -> Vec<TimedReaction> {
+    let mut reactions: Vec<TimedReaction> = world
+        .query::<TimedReaction>()
+        .map(|(_, reaction)| reaction.clone())
+        .collect();
+    reactions.sort_by(|a, b| a.player_id.cmp(&b.player_id));
+    reactions
+}
+
+/// リアクション管理システム
+///
+/// リアクションの残り時間を減らし、時間切れになったものを消します。
+pub struct ReactionSystem;
+
+impl System for ReactionSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        let mut expired = Vec::new();
+        for (entity, reaction) in world.query_mut::<TimedReaction>() {
+            reaction.remaining_seconds -= delta_time;
+            if reaction.remaining_seconds <= 0.0 {
+                expired.push(entity);
+            }
+        }
+
+        for entity in expired {
+            world.remove_entity(entity);
+        }
+    }
+}
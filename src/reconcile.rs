@@ -0,0 +1,230 @@
+// =============================================================================
+// サーバーの盤面に合わせ直すときのアニメーション
+// =============================================================================
+// このファイルでは、手元で予測して進めていた盤面を、サーバーから届いた正しい盤面
+// （スナップショット）に置き換えるときに、位置の違うカードを瞬間移動させずに
+// 手元の位置から正しい位置へRECONCILE_SECONDS秒かけて動かす仕組みを実装します。
+//
+// 仕組み：
+// - 置き換える前に、カード（スートとランク）ごとの表示座標をLocalPredictionに記録する
+// - 盤面を置き換えた後、位置が違うカードにReconcileTweenを付けて記録した座標から動かす
+//   （移動の速さではなく時間で進めるため、遠くのカードも同じ時間で揃う）
+// - アニメーションを省略する・視差効果を減らす設定の場合は、すぐに正しい位置へ置く
+//
+// 合わせ直しは移動ではないため、採点を二重に数えないようにします：
+// - ゲーム状態の監視の前回の値を正しい盤面の値にしておき、スコア・手数の変化を送らない
+//   （代わりにStateReconciledイベントでスコアの修正を1回だけ知らせる）
+// - 手元で作成・記録済みのゲーム結果は引き継ぎ、結果の送信と通算成績の更新を繰り返さない
+// =============================================================================
+
+use crate::achievements::AchievementsEvaluated;
+use crate::clock::GameClock;
+use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::game::GameSettings;
+use crate::result::GameResult;
+use crate::solitaire::{CardRank, CardSuit, SolitaireCard, SolitaireGameState};
+use crate::state_observer::GameStateWatch;
+use log::debug;
+
+/// 正しい位置まで動かす時間（秒）
+pub const RECONCILE_SECONDS: f64 = 0.2;
+
+/// 同じ位置とみなす座標の差（ピクセル）
+const POSITION_EPSILON: f32 = 0.5;
+
+/// サーバーの盤面に合わせて正しい位置へ動かしているカードのコンポーネント
+///
+/// 移動先はカードのアニメーションの目標座標（target_x・target_y）に入っています。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconcileTween {
+    /// 動かし始めたX座標（手元の盤面での位置）
+    pub from_x: f32,
+
+    /// 動かし始めたY座標（手元の盤面での位置）
+    pub from_y: f32,
+
+    /// 動かし始めてからの経過時間（秒）
+    pub elapsed: f64,
+}
+
+impl Component for ReconcileTween {}
+
+impl ReconcileTween {
+    /// 経過時間から進み具合を求める（0.0〜1.0、終わりに向かって減速する）
+    pub fn progress(&self) -> f32 {
+        let t = (self.elapsed / RECONCILE_SECONDS).clamp(0.0, 1.0) as f32;
+        1.0 - (1.0 - t) * (1.0 - t)
+    }
+}
+
+/// 置き換える前の手元の盤面の記録
+#[derive(Debug, Clone, Default)]
+pub struct LocalPrediction {
+    /// カードごとの表示座標
+    cards: Vec<(CardSuit, CardRank, f32, f32)>,
+
+    /// 手元のスコア
+    score: u32,
+
+    /// 手元で作成済みのゲーム結果
+    result: Option<GameResult>,
+
+    /// 手元のゲーム結果で通算成績を更新済みか
+    evaluated: bool,
+}
+
+impl LocalPrediction {
+    /// 置き換える前の盤面を記録する
+    ///
+    /// # 引数
+    /// * `world` - 手元のECSワールド
+    /// * `game` - 手元のゲーム状態エンティティ（ゲームがない場合はNone）
+    pub fn capture(world: &World, game: Option<Entity>) -> Self {
+        let cards = world
+            .query::<SolitaireCard>()
+            .map(|(_, card)| (card.suit, card.rank, card.display_x, card.display_y))
+            .collect();
+        let Some(game) = game else {
+            return Self {
+                cards,
+                ..Self::default()
+            };
+        };
+        Self {
+            cards,
+            score: world
+                .get_component::<SolitaireGameState>(game)
+                .map_or(0, |state| state.score),
+            result: world.get_component::<GameResult>(game).cloned(),
+            evaluated: world.has_component::<AchievementsEvaluated>(game),
+        }
+    }
+
+    /// 置き換えた後の盤面に、記録した位置からのアニメーションと採点済みの印を付ける
+    ///
+    /// # 引数
+    /// * `world` - 正しい盤面に置き換えたECSワールド
+    /// * `game` - 正しい盤面のゲーム状態エンティティ
+    ///
+    /// # 戻り値
+    /// 位置を直したカードの枚数（StateReconciledイベントでも知らせる）
+    pub fn apply(self, world: &mut World, game: Entity) -> u32 {
+        // アニメーションを省略する・視差効果を減らす設定では、正しい位置にそのまま置く
+        let instant = world
+            .get_resource::<GameSettings>()
+            .is_some_and(|settings| !settings.animation.plays_decorations());
+        let mut cards_moved: u32 = 0;
+        let entities: Vec<Entity> = world
+            .query::<SolitaireCard>()
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in entities {
+            let Some(card) = world.get_component_mut::<SolitaireCard>(entity) else {
+                continue;
+            };
+            let Some(&(_, _, from_x, from_y)) = self
+                .cards
+                .iter()
+                .find(|(suit, rank, _, _)| *suit == card.suit && *rank == card.rank)
+            else {
+                continue;
+            };
+            let (to_x, to_y) = (card.display_x, card.display_y);
+            if (to_x - from_x).abs() < POSITION_EPSILON && (to_y - from_y).abs() < POSITION_EPSILON
+            {
+                continue;
+            }
+            cards_moved += 1;
+            if instant {
+                continue;
+            }
+            card.set_display_position(from_x, from_y);
+            card.start_animation(to_x, to_y);
+            world.add_component(
+                entity,
+                ReconcileTween {
+                    from_x,
+                    from_y,
+                    elapsed: 0.0,
+                },
+            );
+        }
+
+        // 合わせ直しでのスコア・手数の変化は、移動によるものとして送らない
+        let clock = GameClock::from_world(world);
+        let score = match world.get_component::<SolitaireGameState>(game) {
+            Some(state) => {
+                let watch = GameStateWatch {
+                    score: state.score,
+                    move_count: state.move_count,
+                    elapsed_seconds: state.elapsed_seconds(&clock),
+                    deck_turns: state.deck_turns,
+                };
+                let (score, completed) = (state.score, state.is_completed);
+                world.add_component(game, watch);
+                if completed {
+                    if let Some(result) = self.result {
+                        world.add_component(game, result);
+                        if self.evaluated {
+                            world.add_component(game, AchievementsEvaluated);
+                        }
+                    }
+                }
+                score
+            }
+            None => 0,
+        };
+
+        debug!(
+            "🔁 サーバーの盤面に合わせ直し: {}枚のカードを移動（スコア: {} -> {}）",
+            cards_moved, self.score, score
+        );
+        if let Some(events) = world.get_resource_mut::<EventQueue>() {
+            events.push(GameEvent::StateReconciled {
+                score,
+                previous_score: self.score,
+                cards_moved,
+            });
+        }
+        cards_moved
+    }
+}
+
+/// 合わせ直しのアニメーションシステム
+///
+/// ReconcileTweenの付いたカードを、経過時間に応じて正しい位置へ動かします。
+/// 途中で他の処理がアニメーションを完了させた場合（自動プレイの前など）は、そこで止めます。
+pub struct ReconcileSystem;
+
+impl System for ReconcileSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        let tweens: Vec<(Entity, ReconcileTween)> = world
+            .query::<ReconcileTween>()
+            .map(|(entity, tween)| (entity, *tween))
+            .collect();
+
+        for (entity, mut tween) in tweens {
+            tween.elapsed += delta_time;
+            let done = match world.get_component_mut::<SolitaireCard>(entity) {
+                Some(card) if card.is_animating => {
+                    if tween.elapsed >= RECONCILE_SECONDS {
+                        card.finish_animation();
+                        true
+                    } else {
+                        let progress = tween.progress();
+                        card.display_x = tween.from_x + (card.target_x - tween.from_x) * progress;
+                        card.display_y = tween.from_y + (card.target_y - tween.from_y) * progress;
+                        false
+                    }
+                }
+                _ => true,
+            };
+            if done {
+                world.remove_component::<ReconcileTween>(entity);
+            } else {
+                world.add_component(entity, tween);
+            }
+        }
+    }
+}
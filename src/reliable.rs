@@ -0,0 +1,225 @@
+// =============================================================================
+// メッセージの確実な配送
+// =============================================================================
+// このファイルでは、受け取りの確認（Ack）を待って再送するReliableSenderと、
+// 再送で重複して届いたメッセージを1回だけ処理するためのDuplicateFilterを実装します。
+//
+// 仕組み：
+// - 送る側はメッセージにIDを付けてReliableで包み、Ackが届くまで記録しておく
+// - 受け取った側はすぐに同じIDのAckを返し、最近処理したIDのメッセージは処理しない
+// - Ackが届かないメッセージは、間隔を倍にしながら（上限MAX_RETRY_DELAY_MS）再送し、
+//   MAX_RESENDS回再送しても届かなければ諦める
+//
+// クライアント（NetworkClient）とサーバーの両方で使います。
+// 時刻は呼び出し側の時計の値（ミリ秒）をそのまま使うため、どちらの時計でも構いません。
+// =============================================================================
+
+use std::collections::{HashSet, VecDeque};
+
+/// 最初の再送までの時間（ミリ秒）
+pub const RETRY_BASE_MS: f64 = 500.0;
+
+/// 再送の間隔の上限（ミリ秒）
+pub const MAX_RETRY_DELAY_MS: f64 = 8_000.0;
+
+/// 諦めるまでに再送する回数
+pub const MAX_RESENDS: u32 = 5;
+
+/// 重複の判定のために覚えておく最近のメッセージIDの数
+pub const RECENT_ID_WINDOW: usize = 256;
+
+/// n回目の再送までの待ち時間
+///
+/// # 引数
+/// * `resends` - これまでに再送した回数
+///
+/// # 戻り値
+/// 待ち時間（ミリ秒）
+pub fn retry_delay_ms(resends: u32) -> f64 {
+    (RETRY_BASE_MS * 2f64.powi(resends.min(16) as i32)).min(MAX_RETRY_DELAY_MS)
+}
+
+/// Ackを待っているメッセージ
+#[derive(Debug, Clone)]
+struct Unacked {
+    /// メッセージID
+    message_id: String,
+
+    /// 送信するテキスト（Reliableで包んだJSON）
+    text: String,
+
+    /// これまでに再送した回数
+    resends: u32,
+
+    /// 次に再送する時刻（ミリ秒、まだ一度も送っていない場合はNone）
+    next_retry_ms: Option<f64>,
+}
+
+/// 再送の判定結果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Retries {
+    /// 再送するテキスト
+    pub resend: Vec<String>,
+
+    /// 再送の回数が上限に達して諦めたメッセージID
+    pub given_up: Vec<String>,
+}
+
+/// Ackを待って再送する送信側の記録
+#[derive(Debug, Clone, Default)]
+pub struct ReliableSender {
+    /// Ackを待っているメッセージ（送った順）
+    unacked: Vec<Unacked>,
+}
+
+impl ReliableSender {
+    /// Ackを待っているメッセージがない状態で作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ackを待つメッセージを記録する（再送の時間はmark_sent()で送った時点から数える）
+    ///
+    /// # 引数
+    /// * `message_id` - メッセージID
+    /// * `text` - 送信するテキスト
+    pub fn track(&mut self, message_id: String, text: String) {
+        self.unacked.push(Unacked {
+            message_id,
+            text,
+            resends: 0,
+            next_retry_ms: None,
+        });
+    }
+
+    /// 記録したメッセージを送ったことを記録する
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    pub fn mark_sent(&mut self, now_ms: f64) {
+        for unacked in &mut self.unacked {
+            if unacked.next_retry_ms.is_none() {
+                unacked.next_retry_ms = Some(now_ms + retry_delay_ms(0));
+            }
+        }
+    }
+
+    /// Ackを受け取る
+    ///
+    /// # 引数
+    /// * `message_id` - Ackに含まれていたメッセージID
+    ///
+    /// # 戻り値
+    /// Ackを待っていたメッセージの場合true（重複したAckや知らないIDの場合false）
+    pub fn ack(&mut self, message_id: &str) -> bool {
+        let count = self.unacked.len();
+        self.unacked
+            .retain(|unacked| unacked.message_id != message_id);
+        self.unacked.len() != count
+    }
+
+    /// Ackを待っているメッセージのテキスト（受信側から再送を要求された場合）
+    ///
+    /// # 引数
+    /// * `message_id` - メッセージID
+    ///
+    /// # 戻り値
+    /// 送信するテキスト（Ackが届いた・再送を諦めたメッセージの場合はNone）
+    pub fn unacked_text(&self, message_id: &str) -> Option<&str> {
+        self.unacked
+            .iter()
+            .find(|unacked| unacked.message_id == message_id)
+            .map(|unacked| unacked.text.as_str())
+    }
+
+    /// 再送するメッセージを取り出す
+    ///
+    /// 再送するメッセージは次の再送の時刻を延ばし、上限まで再送したメッセージは記録から外します。
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（ミリ秒）
+    pub fn due(&mut self, now_ms: f64) -> Retries {
+        let mut retries = Retries::default();
+        self.unacked.retain_mut(|unacked| {
+            if unacked
+                .next_retry_ms
+                .is_none_or(|retry_at| retry_at > now_ms)
+            {
+                return true;
+            }
+            if unacked.resends >= MAX_RESENDS {
+                retries.given_up.push(unacked.message_id.clone());
+                return false;
+            }
+            unacked.resends += 1;
+            unacked.next_retry_ms = Some(now_ms + retry_delay_ms(unacked.resends));
+            retries.resend.push(unacked.text.clone());
+            true
+        });
+        retries
+    }
+
+    /// Ackを待っているメッセージの数
+    pub fn pending_count(&self) -> usize {
+        self.unacked.len()
+    }
+
+    /// 記録をすべて破棄する（接続し直した場合など）
+    pub fn clear(&mut self) {
+        self.unacked.clear();
+    }
+}
+
+/// 最近処理したメッセージIDの記録（重複したメッセージを処理しないため）
+#[derive(Debug, Clone)]
+pub struct DuplicateFilter {
+    /// 覚えておくIDの数
+    capacity: usize,
+
+    /// 覚えているID（古い順）
+    order: VecDeque<String>,
+
+    /// 覚えているID（検索用）
+    seen: HashSet<String>,
+}
+
+impl DuplicateFilter {
+    /// 空の記録を作成
+    ///
+    /// # 引数
+    /// * `capacity` - 覚えておくIDの数（超えた分は古いIDから忘れる）
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            order: VecDeque::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// 初めて届いたメッセージIDかどうかを調べ、記録する
+    ///
+    /// # 引数
+    /// * `message_id` - 届いたメッセージのID
+    ///
+    /// # 戻り値
+    /// 初めて届いた場合true、最近処理したIDの場合false
+    pub fn is_new(&mut self, message_id: &str) -> bool {
+        if self.seen.contains(message_id) {
+            return false;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.order.push_back(message_id.to_string());
+        self.seen.insert(message_id.to_string());
+        true
+    }
+}
+
+impl Default for DuplicateFilter {
+    fn default() -> Self {
+        Self::new(RECENT_ID_WINDOW)
+    }
+}
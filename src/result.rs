@@ -0,0 +1,246 @@
+// =============================================================================
+// ゲーム結果レポート
+// =============================================================================
+// このファイルでは、ゲーム終了時に作成される結果レポート（GameResult）を
+// 実装します。勝敗、ゲームの種類、シード、スコア内訳、移動回数、
+// プレイ時間などをひとまとめにし、JavaScriptへの公開や
+// マルチプレイ時のサーバーへの送信に使用します。
+//
+// 主要な責務：
+// - ゲーム終了の検出と結果レポートの作成
+// - 解いた配り札のキャッシュにある勝ち筋の手数と比べた効率の計算
+// - 結果レポートのゲーム状態エンティティへの添付
+// - マルチプレイ接続中であればサーバーへ結果を送信
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Component, System, World};
+use crate::i18n::FormattedStats;
+use crate::network::{ConnectionStatus, MessageType, NetworkConnection, NetworkManager};
+use crate::practice;
+use crate::solitaire::{ScoreBreakdown, SolitaireGameState, SolitaireType};
+use crate::solve_cache::{self, SolveCache};
+use crate::solver::WinnabilityWatch;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// =============================================================================
+// 結果レポートのデータ定義
+// =============================================================================
+
+/// ゲームの勝敗
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub enum GameOutcome {
+    /// 全カードをファウンデーションに積み終えた
+    Won,
+
+    /// 投了・リセットなどでクリアせずに終了した
+    Lost,
+}
+
+impl GameOutcome {
+    /// 勝敗名を文字列で取得
+    ///
+    /// # 戻り値
+    /// 勝敗名の文字列
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameOutcome::Won => "won",
+            GameOutcome::Lost => "lost",
+        }
+    }
+}
+
+/// ゲーム結果レポートコンポーネント
+///
+/// ゲーム終了時にゲーム状態エンティティへ添付されます。
+/// JavaScriptからは`get_game_result()`でJSONとして取得できます。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[ts(export)]
+pub struct GameResult {
+    /// 勝敗
+    pub outcome: GameOutcome,
+
+    /// ゲームの種類
+    pub game_type: SolitaireType,
+
+    /// カード配布に使用したシード値
+    pub seed: u64,
+
+    /// スコアの内訳
+    pub score: ScoreBreakdown,
+
+    /// 移動回数
+    pub move_count: u32,
+
+    /// デッキをめくった回数
+    pub deck_turns: u32,
+
+    /// プレイ時間（秒）
+    pub duration_seconds: u64,
+
+    /// ヒントを使用した回数
+    pub hints_used: u32,
+
+    /// アンドゥを使用した回数
+    pub undos_used: u32,
+
+    /// ソルバーが配られた直後の局面から見つけた勝ち筋の手数
+    /// （最短とは限らない、勝ち筋を覚えていない配り札ではNone）
+    pub solver_optimal_moves: Option<u32>,
+
+    /// ソルバーの勝ち筋の手数に対する効率（1.0が最善、勝った場合のみ）
+    pub efficiency: Option<f64>,
+
+    /// 勝ち筋がなくなった手（0は配られた時点、勝ち筋が残っていた場合・分からない場合はNone）
+    #[serde(default)]
+    pub lost_at_move: Option<u32>,
+
+    /// 結果を作成した時刻（UNIXタイムスタンプ）
+    pub finished_at: u64,
+
+    /// 言語に合わせて整形した最終スコア・プレイ時間（get_game_result()で設定が有効な場合のみ）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<FormattedStats>,
+}
+
+impl Component for GameResult {}
+
+impl GameResult {
+    /// 終了したゲーム状態から結果レポートを作成
+    ///
+    /// # 引数
+    /// * `state` - ソリティアゲーム状態
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    ///
+    /// # 戻り値
+    /// ゲームが終了している場合はSome(GameResult)、進行中の場合はNone
+    pub fn from_state(state: &SolitaireGameState, clock: &GameClock) -> Option<Self> {
+        if !state.is_completed {
+            return None;
+        }
+
+        let outcome = if state.is_won {
+            GameOutcome::Won
+        } else {
+            GameOutcome::Lost
+        };
+
+        // 負けた場合はボーナス・ペナルティを適用しない
+        let score = state.score_breakdown.unwrap_or(ScoreBreakdown {
+            base_score: state.score,
+            time_bonus: 0,
+            move_penalty: 0,
+            final_score: state.score,
+        });
+
+        Some(Self {
+            outcome,
+            game_type: state.game_type,
+            seed: state.seed,
+            score,
+            move_count: state.move_count,
+            deck_turns: state.deck_turns,
+            duration_seconds: state.elapsed_seconds(clock),
+            hints_used: state.hints_used,
+            undos_used: state.undos_used,
+            solver_optimal_moves: None,
+            efficiency: None,
+            lost_at_move: None,
+            finished_at: clock.now_secs(),
+            formatted: None,
+        })
+    }
+
+    /// ソルバーの勝ち筋の手数を設定し、勝った場合は効率を求める
+    ///
+    /// ソルバーは組札へ置いても困らないカードを置く手やデッキから引く手を数えないため、
+    /// 実際の移動回数の方が多くなりやすく、効率は1.0を超えないよう切り詰めます。
+    ///
+    /// # 引数
+    /// * `line_length` - ソルバーが見つけた勝ち筋の手数
+    pub fn set_solver_line(&mut self, line_length: u32) {
+        self.solver_optimal_moves = Some(line_length);
+        self.efficiency = (self.outcome == GameOutcome::Won)
+            .then(|| (line_length as f64 / self.move_count.max(1) as f64).min(1.0));
+    }
+}
+
+// =============================================================================
+// 結果レポート作成システム
+// =============================================================================
+
+/// ゲーム結果レポートシステム
+///
+/// 終了したゲーム状態を検出して結果レポートを作成し、
+/// マルチプレイ接続中であればサーバーへ送信するシステムです。
+pub struct GameResultSystem;
+
+impl System for GameResultSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let clock = GameClock::from_world(world);
+
+        // 終了済みでまだ結果が作成されていないゲームを探す
+        let mut new_results = Vec::new();
+        for (entity, game_state) in world.query::<SolitaireGameState>() {
+            if world.has_component::<GameResult>(entity) {
+                continue;
+            }
+            if let Some(mut result) = GameResult::from_state(game_state, &clock) {
+                result.lost_at_move = world
+                    .get_component::<WinnabilityWatch>(entity)
+                    .and_then(|watch| watch.lost_at_move);
+                new_results.push((entity, result, solve_cache::seeded_deal(game_state)));
+            }
+        }
+
+        // 配られた直後の確認で覚えた勝ち筋の手数と比べる（覚えていなければ探索はしない）
+        let new_results: Vec<(_, GameResult)> = new_results
+            .into_iter()
+            .map(|(entity, mut result, seed)| {
+                let line_length = seed.and_then(|seed| {
+                    world.get_resource_mut::<SolveCache>()?.line_length(seed)
+                });
+                if let Some(line_length) = line_length {
+                    result.set_solver_line(line_length);
+                }
+                (entity, result)
+            })
+            .collect();
+
+        if new_results.is_empty() {
+            return;
+        }
+
+        // 接続済みのネットワーク接続があればマルチプレイ中とみなす
+        let is_multiplayer = world
+            .query::<NetworkConnection>()
+            .any(|(_, connection)| connection.status == ConnectionStatus::Connected);
+
+        for (entity, result) in new_results {
+            info!(
+                "📋 ゲーム結果: {} (スコア: {}, 移動: {}回, 時間: {}秒)",
+                result.outcome.as_str(),
+                result.score.final_score,
+                result.move_count,
+                result.duration_seconds
+            );
+
+            // 練習の結果はサーバーに送らない
+            if is_multiplayer && !practice::is_practice(world, entity) {
+                match serde_json::to_string(&result) {
+                    Ok(payload) => {
+                        // サーバーへの送信はNetworkClientが送信キューから行う
+                        NetworkManager::queue_outbound(world, MessageType::GameResult, payload);
+                    }
+                    Err(e) => {
+                        error!("❌ ゲーム結果のシリアライゼーション失敗: {}", e);
+                    }
+                }
+            }
+
+            world.add_component(entity, result);
+        }
+    }
+}
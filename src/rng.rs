@@ -0,0 +1,129 @@
+// =============================================================================
+// 乱数生成器
+// =============================================================================
+// このファイルでは、ゲーム全体で共有するシード付きの乱数生成器を実装します。
+// WebAssembly環境でも動作するよう、外部のcrateやOSの乱数に頼らず
+// xorshift64で乱数を生成します。
+//
+// 主要な用途：
+// - カードのシャッフル（同じシードなら同じ配り札になる）
+// - ネットワークメッセージIDの生成
+// - ボットのミス判定
+// - シード未指定時のゲームシードの決定
+// - 日替わりの配り札のシードの算出
+// =============================================================================
+
+use crate::ecs::Resource;
+
+/// シード付きの乱数生成器（xorshift64）
+///
+/// 同じシードからは常に同じ乱数列が生成されるため、
+/// 配り札の再現やテストに使用できます。
+/// ワールドのリソースとして登録し、各システムで共有します。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    /// 内部状態（0にはならない）
+    state: u64,
+}
+
+impl Resource for Rng {}
+
+impl Rng {
+    /// シードを指定して乱数生成器を作成
+    ///
+    /// # 引数
+    /// * `seed` - シード値
+    ///
+    /// # 戻り値
+    /// 新しいRngインスタンス
+    pub fn new(seed: u64) -> Self {
+        // xorshiftは状態が0だと0しか生成しないため、0は1に置き換える
+        Self { state: seed.max(1) }
+    }
+
+    /// 実行環境から得た値をシードにして乱数生成器を作成
+    ///
+    /// WebAssembly環境ではJavaScriptのMath.random()とDate.now()、
+    /// それ以外では現在時刻を使います（SystemTimeはwasm32では使えないため）。
+    ///
+    /// # 戻り値
+    /// 新しいRngインスタンス
+    pub fn from_entropy() -> Self {
+        Self::new(entropy_seed())
+    }
+
+    /// 次の64ビットの乱数を生成
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// 次の32ビットの乱数を生成
+    pub fn next_u32(&mut self) -> u32 {
+        // 下位ビットは偏りやすいため、上位32ビットを使う
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// 0.0以上1.0未満の乱数を生成
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// 0以上`bound`未満の整数の乱数を生成
+    ///
+    /// # 引数
+    /// * `bound` - 上限（この値は含まない、0の場合は常に0を返す）
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// スライスの要素をシャッフル（Fisher-Yates）
+    ///
+    /// # 引数
+    /// * `items` - シャッフルするスライス
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// 1日の長さ（ミリ秒）
+pub const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// 日替わりの配り札のシードを算出
+///
+/// 同じ日（UTC）には誰が求めても同じ配り札になるよう、日付だけから決まるシードを返します。
+///
+/// # 引数
+/// * `day` - UNIXエポックからの日数（UTC）
+///
+/// # 戻り値
+/// その日の配り札のシード値
+pub fn daily_seed(day: u64) -> u64 {
+    // 連続した日付が似たシードにならないよう、黄金比の定数で散らしてから乱数を1つ取る
+    Rng::new(day.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15)).next_u64()
+}
+
+/// 実行環境からシード値を取得（WebAssembly版）
+#[cfg(feature = "wasm")]
+fn entropy_seed() -> u64 {
+    let random = (js_sys::Math::random() * (1u64 << 53) as f64) as u64;
+    let now = js_sys::Date::now() as u64;
+    random ^ now.rotate_left(32)
+}
+
+/// 実行環境からシード値を取得（ネイティブ版）
+#[cfg(not(feature = "wasm"))]
+fn entropy_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
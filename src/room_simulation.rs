@@ -0,0 +1,420 @@
+// =============================================================================
+// ルームごとのゲームシミュレーション（サーバー用）
+// =============================================================================
+// このファイルでは、サーバーの各ルームが持つECSワールドとシステムスケジューラ
+// （RoomSimulation）を実装します。
+//
+// 仕組み：
+// - サーバーはルームごとにTICK_INTERVAL_MSごとのティックタスクを動かし、
+//   メッセージが届かなくてもシステム（ゲーム状態の管理・ターンの制限時間）を実行する
+// - ターンの制限時間が設定されたルームでゲームが始まると、参加順にターン管理を開始し、
+//   ゲームが終わるかルームが空になったら終了する
+// - システムがEventQueueに追加したイベントは、エンティティIDをプレイヤーIDに直して
+//   ルームに配信するメッセージにする
+// - 離席中のプレイヤーのターンは、サーバーがskip_turn()で制限時間を待たずに飛ばす
+// - ターンの状態はTurnSnapshotとして取り出せ、サーバーの再起動後に同じ順番から再開できる
+// =============================================================================
+
+use crate::clock::{FrameSteps, GameClock};
+use crate::ecs::{Entity, SystemScheduler, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::game::{
+    GameManagementSystem, GameManager, GameSettings, TurnManagementSystem, TurnManager,
+};
+use crate::protocol::WebSocketMessage;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// ティックの間隔（ミリ秒）
+pub const TICK_INTERVAL_MS: u64 = 100;
+
+/// 離席中のプレイヤーのターンを飛ばしたときのTurnTimedOutのauto_action
+pub const AFK_SKIP_ACTION: &str = "afk_skip";
+
+/// ターンの状態のスナップショット（ルームの保存用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnSnapshot {
+    /// ターン順のプレイヤーID（先頭がターン中のプレイヤー）
+    pub order: Vec<String>,
+
+    /// 現在のターン番号
+    pub turn_number: u32,
+
+    /// ターンの制限時間（秒）
+    pub turn_time_limit: u32,
+}
+
+impl TurnSnapshot {
+    /// プレイヤーIDを置き換える（再接続でIDが変わった場合）
+    pub fn rename_player(&mut self, old_id: &str, new_id: &str) {
+        for player_id in self.order.iter_mut().filter(|id| id.as_str() == old_id) {
+            *player_id = new_id.to_string();
+        }
+    }
+}
+
+/// ターン制のゲーム1回分のエンティティ
+struct TurnGame {
+    /// ゲーム状態のエンティティ
+    game: Entity,
+
+    /// ターン管理のエンティティ
+    turns: Entity,
+
+    /// 参加しているプレイヤー（プレイヤーIDとエンティティ）
+    players: Vec<(String, Entity)>,
+
+    /// 最後に配信したターン（ターン番号とプレイヤー）
+    announced: Option<(u32, Entity)>,
+}
+
+/// ルームのECSワールドと、それを進めるシステムスケジューラ
+pub struct RoomSimulation {
+    /// ルームID（配信するメッセージに入れる）
+    room_id: String,
+
+    /// ルームのワールド（GameClock・EventQueue・GameSettingsをリソースに持つ）
+    world: World,
+
+    /// ティックごとに実行するシステム
+    scheduler: SystemScheduler,
+
+    /// 進行中のターン制のゲーム
+    turn_game: Option<TurnGame>,
+
+    /// 次にゲームが進むときに再開するターンの状態
+    restored_turns: Option<TurnSnapshot>,
+}
+
+impl RoomSimulation {
+    /// ルームのシミュレーションを作成
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `clock` - サーバーの時計
+    pub fn new(room_id: String, clock: GameClock) -> Self {
+        let mut world = World::new();
+        world.insert_resource(clock);
+        world.insert_resource(EventQueue::new());
+        world.insert_resource(GameSettings::default());
+
+        let mut scheduler = SystemScheduler::new();
+        scheduler.add_system(GameManagementSystem);
+        scheduler.add_system(TurnManagementSystem);
+
+        Self {
+            room_id,
+            world,
+            scheduler,
+            turn_game: None,
+            restored_turns: None,
+        }
+    }
+
+    /// 保存されていたターンの状態を、次にゲームが進むときに再開するよう登録
+    ///
+    /// # 引数
+    /// * `snapshot` - 再起動前のターンの状態（プレイヤーIDは再接続後のもの）
+    pub fn restore_turns(&mut self, snapshot: TurnSnapshot) {
+        self.restored_turns = Some(snapshot);
+    }
+
+    /// ターンの状態のスナップショットを作成
+    ///
+    /// # 戻り値
+    /// ターン制のゲーム中はSome(スナップショット)、それ以外はNone
+    pub fn snapshot(&self) -> Option<TurnSnapshot> {
+        let turn_game = self.turn_game.as_ref()?;
+        let turn_manager = self.world.get_component::<TurnManager>(turn_game.turns)?;
+        let order = turn_manager
+            .turn_order
+            .iter()
+            .filter_map(|entity| self.player_id(entity.id()))
+            .collect();
+        Some(TurnSnapshot {
+            order,
+            turn_number: turn_manager.turn_number,
+            turn_time_limit: turn_manager.turn_time_limit,
+        })
+    }
+
+    /// ルームの状態に合わせてターン管理を開始・終了する
+    ///
+    /// # 引数
+    /// * `playing` - ルームがゲーム中かどうか
+    /// * `player_ids` - ルームの参加者（参加順）
+    /// * `turn_time_limit` - ターンの制限時間（秒、0の場合はターン制にしない）
+    ///
+    /// # 戻り値
+    /// ルームに配信するメッセージ（ターンが変わった場合）
+    pub fn sync_room(
+        &mut self,
+        playing: bool,
+        player_ids: &[String],
+        turn_time_limit: u32,
+    ) -> Vec<WebSocketMessage> {
+        if !playing || player_ids.is_empty() {
+            self.stop_turns();
+            return Vec::new();
+        }
+
+        match self.turn_game.as_ref() {
+            None => {
+                if let Some(snapshot) = self.restored_turns.take() {
+                    self.resume_turns(snapshot, player_ids);
+                } else if turn_time_limit > 0 {
+                    self.start_turns(player_ids, turn_time_limit);
+                }
+            }
+            Some(turn_game) => {
+                let departed: Vec<Entity> = turn_game
+                    .players
+                    .iter()
+                    .filter(|(player_id, _)| !player_ids.contains(player_id))
+                    .map(|&(_, entity)| entity)
+                    .collect();
+                for entity in departed {
+                    self.remove_player(entity);
+                }
+            }
+        }
+        self.turn_messages()
+    }
+
+    /// 1ティック分システムを実行する
+    ///
+    /// 経過時間はクライアントと同じく上限で切り詰め、最大MAX_STEPS_PER_FRAME回に分けて実行します。
+    ///
+    /// # 引数
+    /// * `delta_seconds` - 前のティックからの経過時間（秒）
+    ///
+    /// # 戻り値
+    /// ルームに配信するメッセージ（発生したイベントとターンの変更）
+    pub fn tick(&mut self, delta_seconds: f64) -> Vec<WebSocketMessage> {
+        let frame = match self.world.get_resource_mut::<GameClock>() {
+            Some(clock) => clock.tick_steps(delta_seconds),
+            None => FrameSteps::split(0.0),
+        };
+        if frame.is_clock_jump() {
+            warn!(
+                "⏱️ ルーム{}のティックが{:.1}秒遅れました（{:.1}秒分は進めません）",
+                self.room_id,
+                frame.raw_seconds,
+                frame.dropped_seconds()
+            );
+        }
+        for _ in 0..frame.steps {
+            self.scheduler.update(&mut self.world, frame.step_seconds);
+        }
+
+        let events = self
+            .world
+            .get_resource_mut::<EventQueue>()
+            .map(EventQueue::drain)
+            .unwrap_or_default();
+        let mut messages: Vec<WebSocketMessage> = events
+            .into_iter()
+            .filter_map(|event| self.event_message(event))
+            .collect();
+        messages.extend(self.turn_messages());
+        messages
+    }
+
+    /// 現在ターン中のプレイヤーID（ターン制のゲーム中でない場合はNone）
+    pub fn current_player(&self) -> Option<String> {
+        let turn_game = self.turn_game.as_ref()?;
+        let turn_manager = self.world.get_component::<TurnManager>(turn_game.turns)?;
+        self.player_id(turn_manager.current_player?.id())
+    }
+
+    /// 離席中のプレイヤーのターンを飛ばし、次のプレイヤーのターンを始める
+    ///
+    /// # 引数
+    /// * `player_id` - 飛ばすプレイヤーのID
+    ///
+    /// # 戻り値
+    /// ターン中のプレイヤーだった場合は、飛ばしたこと（auto_actionはAFK_SKIP_ACTION）と
+    /// 次のターンの開始を知らせるメッセージ。それ以外の場合は空
+    pub fn skip_turn(&mut self, player_id: &str) -> Vec<WebSocketMessage> {
+        if self.current_player().as_deref() != Some(player_id) {
+            return Vec::new();
+        }
+        let clock = GameClock::from_world(&self.world);
+        let Some(turn_game) = self.turn_game.as_ref() else {
+            return Vec::new();
+        };
+        let Some(turn_manager) = self.world.get_component_mut::<TurnManager>(turn_game.turns) else {
+            return Vec::new();
+        };
+        let turn_number = turn_manager.turn_number;
+        turn_manager.next_turn(&clock);
+
+        let mut messages = vec![WebSocketMessage::TurnTimedOut {
+            room_id: self.room_id.clone(),
+            player_id: player_id.to_string(),
+            turn_number,
+            auto_action: AFK_SKIP_ACTION.to_string(),
+        }];
+        messages.extend(self.turn_messages());
+        messages
+    }
+
+    /// 参加順にターン管理を開始
+    fn start_turns(&mut self, player_ids: &[String], turn_time_limit: u32) {
+        let game = GameManager::create_game_session(
+            &mut self.world,
+            self.room_id.clone(),
+            player_ids.len() as u32,
+        );
+        let players: Vec<(String, Entity)> = player_ids
+            .iter()
+            .map(|player_id| {
+                let entity = self.world.create_entity();
+                GameManager::join_player(&mut self.world, game, entity);
+                (player_id.clone(), entity)
+            })
+            .collect();
+        let entities = players.iter().map(|&(_, entity)| entity).collect();
+        let turns =
+            GameManager::start_turn_management(&mut self.world, game, entities, turn_time_limit);
+
+        self.turn_game = Some(TurnGame {
+            game,
+            turns,
+            players,
+            announced: None,
+        });
+    }
+
+    /// 保存されていたターンの状態からターン管理を再開
+    ///
+    /// 保存時のターン順に並べ（戻ってこなかったプレイヤーは除き、新しい参加者は最後に加える）、
+    /// ターン番号を引き継ぎます。ターン中だったプレイヤーの制限時間は最初から数え直します。
+    fn resume_turns(&mut self, snapshot: TurnSnapshot, player_ids: &[String]) {
+        let mut order: Vec<String> = snapshot
+            .order
+            .into_iter()
+            .filter(|player_id| player_ids.contains(player_id))
+            .collect();
+        for player_id in player_ids {
+            if !order.contains(player_id) {
+                order.push(player_id.clone());
+            }
+        }
+
+        self.start_turns(&order, snapshot.turn_time_limit);
+        if let Some(turn_game) = self.turn_game.as_ref() {
+            if let Some(turn_manager) = self.world.get_component_mut::<TurnManager>(turn_game.turns)
+            {
+                turn_manager.turn_number = snapshot.turn_number;
+            }
+        }
+        info!(
+            "♻️ ターン管理を再開: ルーム{} (ターン{})",
+            self.room_id, snapshot.turn_number
+        );
+    }
+
+    /// ターン管理を終了し、エンティティを削除
+    fn stop_turns(&mut self) {
+        let Some(turn_game) = self.turn_game.take() else {
+            return;
+        };
+        self.world.remove_entity(turn_game.game);
+        self.world.remove_entity(turn_game.turns);
+        for (_, entity) in turn_game.players {
+            self.world.remove_entity(entity);
+        }
+        if let Some(events) = self.world.get_resource_mut::<EventQueue>() {
+            events.drain();
+        }
+        info!("🔄 ターン管理終了: ルーム{}", self.room_id);
+    }
+
+    /// 退室したプレイヤーをターン順から外す
+    ///
+    /// ターン中のプレイヤーが退室した場合は、次のプレイヤーのターンを新しく始めます。
+    fn remove_player(&mut self, entity: Entity) {
+        let clock = GameClock::from_world(&self.world);
+        let Some(turn_game) = self.turn_game.as_mut() else {
+            return;
+        };
+        turn_game.players.retain(|&(_, player)| player != entity);
+        if let Some(turn_manager) = self.world.get_component_mut::<TurnManager>(turn_game.turns) {
+            let was_current = turn_manager.current_player == Some(entity);
+            turn_manager.remove_player(entity);
+            if was_current {
+                turn_manager.turn_number += 1;
+                turn_manager.turn_start_time = clock.now_secs();
+                turn_manager.last_warning = None;
+            }
+        }
+        self.world.remove_entity(entity);
+    }
+
+    /// ターンが変わっていれば、新しいターンの開始を知らせるメッセージを作成
+    fn turn_messages(&mut self) -> Vec<WebSocketMessage> {
+        let Some(turn_game) = self.turn_game.as_mut() else {
+            return Vec::new();
+        };
+        let Some(turn_manager) = self.world.get_component::<TurnManager>(turn_game.turns) else {
+            return Vec::new();
+        };
+        let Some(current) = turn_manager.current_player else {
+            return Vec::new();
+        };
+        let turn = (turn_manager.turn_number, current);
+        if turn_game.announced == Some(turn) {
+            return Vec::new();
+        }
+        turn_game.announced = Some(turn);
+
+        let time_limit_seconds = turn_manager.turn_time_limit;
+        self.player_id(current.id())
+            .map(|player_id| WebSocketMessage::TurnStarted {
+                room_id: self.room_id.clone(),
+                player_id,
+                turn_number: turn.0,
+                time_limit_seconds,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// システムが発生させたイベントを配信するメッセージに直す
+    fn event_message(&self, event: GameEvent) -> Option<WebSocketMessage> {
+        match event {
+            GameEvent::TurnTimeWarning {
+                player,
+                turn_number,
+                remaining_seconds,
+            } => Some(WebSocketMessage::TurnTimeWarning {
+                room_id: self.room_id.clone(),
+                player_id: self.player_id(player)?,
+                turn_number,
+                remaining_seconds,
+            }),
+            GameEvent::TurnTimedOut {
+                player,
+                turn_number,
+                auto_action,
+            } => Some(WebSocketMessage::TurnTimedOut {
+                room_id: self.room_id.clone(),
+                player_id: self.player_id(player)?,
+                turn_number,
+                auto_action,
+            }),
+            _ => None,
+        }
+    }
+
+    /// エンティティIDからプレイヤーIDを取得
+    fn player_id(&self, entity_id: u32) -> Option<String> {
+        self.turn_game
+            .as_ref()?
+            .players
+            .iter()
+            .find(|(_, entity)| entity.id() == entity_id)
+            .map(|(player_id, _)| player_id.clone())
+    }
+}
@@ -0,0 +1,222 @@
+// =============================================================================
+// ルームの保存と復元（サーバー用）
+// =============================================================================
+// このファイルでは、サーバーの更新やクラッシュで進行中のマルチプレイが
+// 失われないよう、ルームの状態を保存・復元するRoomStoreを実装します。
+//
+// 仕組み：
+// - サーバーはSNAPSHOT_INTERVAL_MSごとと終了時に、全ルームのスナップショット
+//   （設定・参加者・配り札のシード・ターンの状態・アクションの記録・共有盤面のワールドの盤面）を保存する
+// - ルームのパスワードはソルト付きのハッシュ（PasswordHash）だけを持ち、そのままでは保存しない
+// - 内容が前回の保存から変わっていない場合は書き込まない
+// - プレイヤーIDは接続ごとに変わるため、参加者はセッショントークンで保存し、
+//   再起動後に同じトークンで接続したプレイヤーを元のルームに戻す
+// - 参加者が全員戻るか、SEAT_RESERVATION_SECONDSが過ぎるまではゲームを進めない
+// - カウントダウン中だったルームはカウントダウンのタスクが失われるため、待機中に戻す
+// =============================================================================
+
+use crate::protocol::{GameState, LoggedAction};
+use crate::room_simulation::TurnSnapshot;
+use crate::scenario::Scenario;
+use crate::storage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// ルームのスナップショットの保存キー
+const STORAGE_KEY: &str = "rooms";
+
+/// スナップショットを保存する間隔（ミリ秒）
+pub const SNAPSHOT_INTERVAL_MS: u64 = 1000;
+
+/// 再起動後、参加していたプレイヤーの席を空けておく時間（秒）
+pub const SEAT_RESERVATION_SECONDS: u64 = 120;
+
+/// 再起動前にルームにいたプレイヤー1人分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeatSnapshot {
+    /// 再起動前のプレイヤーID（ターンの状態とアクションの記録で使われている）
+    pub player_id: String,
+
+    /// セッショントークン（再接続したプレイヤーを見分けるのに使う）
+    pub session_token: String,
+
+    /// 表示名
+    pub player_name: String,
+}
+
+/// ルームのパスワードのハッシュ
+///
+/// パスワードそのものはメモリにもディスクにも残さず、ルームごとのソルトと
+/// パスワードを続けたもののSHA-256だけを持ちます。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasswordHash {
+    /// ルームごとのソルト
+    salt: String,
+
+    /// ソルトとパスワードのSHA-256（16進数）
+    digest: String,
+}
+
+impl PasswordHash {
+    /// 新しいソルトでパスワードのハッシュを作成
+    ///
+    /// # 引数
+    /// * `password` - ルームのパスワード
+    pub fn new(password: &str) -> Self {
+        let salt = Uuid::new_v4().simple().to_string();
+        let digest = Self::digest(&salt, password);
+        Self { salt, digest }
+    }
+
+    /// 入力されたパスワードが一致するかチェック
+    ///
+    /// # 引数
+    /// * `password` - 入力されたパスワード
+    pub fn matches(&self, password: &str) -> bool {
+        Self::digest(&self.salt, password) == self.digest
+    }
+
+    /// ソルトとパスワードのSHA-256を16進数の文字列にする
+    fn digest(salt: &str, password: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(salt.as_bytes());
+        hasher.update(password.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// 参加禁止にしたプレイヤー1人分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BanSnapshot {
+    /// 本人の識別子（セッショントークン、ボットは名前）
+    pub identity: String,
+
+    /// 参加禁止にしたときの表示名（一覧の表示と解除に使う）
+    pub player_name: String,
+}
+
+/// ルーム1つ分のスナップショット
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomSnapshot {
+    pub id: String,
+    pub name: String,
+    pub max_players: u8,
+    pub game_state: GameState,
+    #[serde(default)]
+    pub password_hash: Option<PasswordHash>, // 参加に必要なパスワードのハッシュ
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,      // 以前の形式で保存されたパスワード（読み込み時にハッシュにする）
+    #[serde(default)]
+    pub banned_players: Vec<BanSnapshot>, // 参加禁止のプレイヤー（以前の名前だけの記録は引き継がない）
+    pub turn_time_limit: u32,
+    #[serde(default)]
+    pub combo_window_seconds: u32,     // レースのコンボの受付時間（秒、0の場合はコンボを数えない）
+    #[serde(default)]
+    pub power_ups: bool,               // パワーアップを使えるカジュアルなルームかどうか
+    #[serde(default)]
+    pub shared_board: bool,            // 参加者全員で1つの盤面を動かすルームかどうか
+    #[serde(default)]
+    pub card_owners: BTreeMap<String, String>, // 共有盤面のカードのID → 持ち主のプレイヤーID
+    #[serde(default)]
+    pub afk_seconds: u32,              // 離席中とみなすまでの時間（秒、0の場合は判定しない）
+    #[serde(default)]
+    pub pause_on_afk: bool,            // 誰かが離席している間、共有盤面のゲームを一時停止するか
+    #[serde(default)]
+    pub afk_forfeit_turns: u32,        // 離席中に続けて飛ばしたら席を没収するターン数（0の場合は没収しない）
+    pub seed: Option<u64>,             // 進行中の配り札のシード
+    pub seats: Vec<SeatSnapshot>,      // 参加していたプレイヤー（ボットは戻れないため含めない）
+    pub turns: Option<TurnSnapshot>,   // ルームのワールドのターンの状態
+    pub action_log: Vec<LoggedAction>, // 配り札の開始からのアクション
+    #[serde(default)]
+    pub permanent: bool,               // 空になっても削除しないルームかどうか（デフォルトルーム）
+    #[serde(default)]
+    pub creator: Option<String>,       // 作成したプレイヤー（ルームの数の上限を数えるため）
+    #[serde(default)]
+    pub mirrored_from: Option<String>, // 他のインスタンスのルームを中継している場合、そのインスタンスのID
+    #[serde(default)]
+    pub board: Option<Scenario>,       // 共有盤面のルームのワールドの盤面（ない場合はシードとアクションの記録から作り直す）
+}
+
+/// 再起動後、参加していたプレイヤーが戻るのを待っているルームの状態
+#[derive(Debug, Clone)]
+pub struct PendingRestore {
+    /// まだ戻っていないプレイヤー
+    pub seats: Vec<SeatSnapshot>,
+
+    /// 再開するターンの状態
+    pub turns: Option<TurnSnapshot>,
+
+    /// 席を空けておく期限（UNIX時刻、ミリ秒）
+    pub expires_at_ms: u64,
+}
+
+impl PendingRestore {
+    /// まだ参加者が戻るのを待つかチェック
+    ///
+    /// # 引数
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    pub fn is_waiting(&self, now_ms: u64) -> bool {
+        !self.seats.is_empty() && now_ms < self.expires_at_ms
+    }
+
+    /// 戻ってきたプレイヤーの席を取り出す
+    ///
+    /// # 引数
+    /// * `session_token` - 接続したプレイヤーのセッショントークン
+    ///
+    /// # 戻り値
+    /// 再起動前にこのルームにいた場合はSome(席)、いなかった場合はNone
+    pub fn take_seat(&mut self, session_token: &str) -> Option<SeatSnapshot> {
+        let index = self
+            .seats
+            .iter()
+            .position(|seat| seat.session_token == session_token)?;
+        Some(self.seats.remove(index))
+    }
+}
+
+/// ルームのスナップショットの保存領域
+#[derive(Debug, Default)]
+pub struct RoomStore {
+    /// 最後に保存した内容（変わっていなければ書き込まない）
+    last_saved: Option<String>,
+}
+
+impl RoomStore {
+    /// 保存されているルームを読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればそのルーム、なければ空のベクター
+    pub fn load(&mut self) -> Vec<RoomSnapshot> {
+        let Some(json) = storage::load(STORAGE_KEY) else {
+            return Vec::new();
+        };
+        let rooms: Vec<RoomSnapshot> = serde_json::from_str(&json).unwrap_or_default();
+        self.last_saved = Some(json);
+        rooms
+    }
+
+    /// ルームを保存する
+    ///
+    /// # 引数
+    /// * `rooms` - 全ルームのスナップショット
+    ///
+    /// # 戻り値
+    /// 書き込んだ場合はOk(true)、前回から変わっていない場合はOk(false)、失敗時Err
+    pub fn save(&mut self, rooms: &[RoomSnapshot]) -> Result<bool, String> {
+        let json = serde_json::to_string(rooms)
+            .map_err(|e| format!("ルームのシリアライゼーション失敗: {}", e))?;
+        if self.last_saved.as_deref() == Some(json.as_str()) {
+            return Ok(false);
+        }
+        storage::save(STORAGE_KEY, &json)?;
+        self.last_saved = Some(json);
+        Ok(true)
+    }
+}
@@ -0,0 +1,514 @@
+// =============================================================================
+// WebRTCのデータチャネル（WebAssembly用）
+// =============================================================================
+// このファイルでは、同じルームのプレイヤーとブラウザ同士で直接つながる
+// データチャネルを管理するRtcPeersを実装します。
+//
+// 仕組み：
+// - ルームの参加者が分かったら、プレイヤーIDが小さい側がRTCPeerConnectionと
+//   データチャネルを作り、Offerを送る（受けた側はAnswerを返す）
+// - Offer・Answer・ICE候補はRtcSignalとして溜めておき、JavaScriptが
+//   rtc_take_signals()で取り出してWebSocketで送る（サーバーが相手に中継する）
+// - データチャネルは順序も再送もない設定で開き、カーソル位置のような
+//   次の値で上書きされるメッセージだけを送る（経路の選択はtransport.rsを参照）
+// - データチャネルで届いたメッセージは検証してから溜め、rtc_take_messages()で取り出す
+//   （順序を保証しないため、カーソル位置は連番を見て新しい位置より後に届いた古い位置を捨てる）
+//
+// ブラウザのAPIは非同期（Promise）のため、交渉の各手順はspawn_localで進め、
+// コールバックとの共有状態はRc<RefCell<...>>で持ちます。
+// =============================================================================
+
+use crate::protocol::{Channel, RtcSignalPayload, WebSocketMessage};
+use crate::sequence::{Arrival, SequenceTracker};
+use crate::transport::{should_initiate, TransportSelector};
+use log::{debug, info, warn};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{
+    MessageEvent, RtcConfiguration, RtcDataChannel, RtcDataChannelEvent, RtcDataChannelInit,
+    RtcIceCandidateInit, RtcIceServer, RtcPeerConnection, RtcPeerConnectionIceEvent, RtcSdpType,
+    RtcSessionDescriptionInit,
+};
+
+/// データチャネルのラベル
+const DATA_CHANNEL_LABEL: &str = "realtime";
+
+/// 接続先の候補を調べるSTUNサーバー
+const STUN_SERVER: &str = "stun:stun.l.google.com:19302";
+
+/// コールバックと共有する状態
+#[derive(Default)]
+struct Shared {
+    /// データチャネルの状態と通信経路の選択
+    selector: TransportSelector,
+
+    /// 相手ごとの開いているデータチャネル
+    channels: HashMap<String, RtcDataChannel>,
+
+    /// WebSocketで送る接続交渉のメッセージ
+    outgoing_signals: Vec<WebSocketMessage>,
+
+    /// データチャネルで届いたメッセージ（検証済みのJSON）
+    received: Vec<String>,
+
+    /// 相手ごとの届いたカーソル位置の連番
+    cursor_sequences: HashMap<String, SequenceTracker>,
+
+    /// 相手の接続情報を設定するまで待っているICE候補
+    pending_candidates: HashMap<String, Vec<RtcIceCandidateInit>>,
+
+    /// 相手の接続情報（Offer / Answer）を設定済みの相手
+    remote_ready: Vec<String>,
+}
+
+/// 同じルームのプレイヤーとのデータチャネル
+pub struct RtcPeers {
+    /// 自分のプレイヤーID
+    own_id: String,
+
+    /// 参加しているルームのID
+    room_id: String,
+
+    /// 相手ごとのRTCPeerConnection
+    connections: HashMap<String, RtcPeerConnection>,
+
+    /// コールバックと共有する状態
+    shared: Rc<RefCell<Shared>>,
+}
+
+impl Default for RtcPeers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RtcPeers {
+    /// どのルームにも参加していない状態で作成
+    pub fn new() -> Self {
+        Self {
+            own_id: String::new(),
+            room_id: String::new(),
+            connections: HashMap::new(),
+            shared: Rc::new(RefCell::new(Shared::default())),
+        }
+    }
+
+    /// ルームの参加者に合わせてデータチャネルを開く・閉じる
+    ///
+    /// # 引数
+    /// * `own_id` - 自分のプレイヤーID
+    /// * `room_id` - 参加しているルームのID
+    /// * `peers` - 同じルームの他のプレイヤーのID
+    pub fn set_room(&mut self, own_id: &str, room_id: &str, peers: Vec<String>) {
+        if self.own_id != own_id || self.room_id != room_id {
+            self.close_all();
+            self.own_id = own_id.to_string();
+            self.room_id = room_id.to_string();
+        }
+
+        let departed: Vec<String> = self
+            .connections
+            .keys()
+            .filter(|peer| !peers.contains(peer))
+            .cloned()
+            .collect();
+        for peer_id in departed {
+            self.close_peer(&peer_id);
+        }
+        self.shared
+            .borrow_mut()
+            .selector
+            .set_room_peers(peers.clone());
+
+        for peer_id in peers {
+            if !self.connections.contains_key(&peer_id) && should_initiate(own_id, &peer_id) {
+                if let Err(e) = self.start_offer(&peer_id) {
+                    warn!("⚠️ データチャネルを開けません（{}）: {}", peer_id, e);
+                }
+            }
+        }
+    }
+
+    /// ルームを出たときに、すべてのデータチャネルを閉じる
+    pub fn leave_room(&mut self) {
+        self.close_all();
+        self.room_id.clear();
+        self.shared.borrow_mut().selector.set_room_peers(Vec::new());
+    }
+
+    /// 相手から届いた接続交渉のメッセージを処理
+    ///
+    /// # 引数
+    /// * `message` - サーバーが中継したRtcSignal
+    ///
+    /// # 戻り値
+    /// 処理を始めた場合はOk(())、自分宛てでない・ルームが違う場合などはエラーメッセージ
+    pub fn handle_signal(&mut self, message: WebSocketMessage) -> Result<(), String> {
+        let WebSocketMessage::RtcSignal {
+            room_id,
+            from_player_id,
+            to_player_id,
+            signal,
+        } = message
+        else {
+            return Err("接続交渉のメッセージではありません".to_string());
+        };
+        if to_player_id != self.own_id || room_id != self.room_id {
+            return Err("このルームの自分宛ての接続交渉ではありません".to_string());
+        }
+        if !self
+            .shared
+            .borrow()
+            .selector
+            .room_peers()
+            .contains(&from_player_id)
+        {
+            return Err(format!(
+                "ルームにいない相手からの接続交渉です: {}",
+                from_player_id
+            ));
+        }
+
+        match signal {
+            RtcSignalPayload::Offer { sdp } => {
+                // 相手が申し込み直した場合は、古い接続を閉じてから応じる
+                self.close_peer(&from_player_id);
+                let connection = self.create_connection(&from_player_id)?;
+                self.answer_offer(connection, from_player_id, sdp);
+            }
+            RtcSignalPayload::Answer { sdp } => {
+                let connection =
+                    self.connections
+                        .get(&from_player_id)
+                        .cloned()
+                        .ok_or_else(|| {
+                            format!("申し込んでいない相手からの応答です: {}", from_player_id)
+                        })?;
+                let shared = Rc::clone(&self.shared);
+                spawn_local(async move {
+                    let description = session_description(RtcSdpType::Answer, &sdp);
+                    match JsFuture::from(connection.set_remote_description(&description)).await {
+                        Ok(_) => remote_description_applied(&shared, &connection, &from_player_id),
+                        Err(e) => warn!("⚠️ 応答を設定できません: {:?}", e),
+                    }
+                });
+            }
+            RtcSignalPayload::IceCandidate {
+                candidate,
+                sdp_mid,
+                sdp_m_line_index,
+            } => {
+                let init = RtcIceCandidateInit::new(&candidate);
+                init.set_sdp_mid(sdp_mid.as_deref());
+                init.set_sdp_m_line_index(sdp_m_line_index);
+
+                let mut shared = self.shared.borrow_mut();
+                match self.connections.get(&from_player_id) {
+                    Some(connection) if shared.remote_ready.contains(&from_player_id) => {
+                        add_ice_candidate(connection.clone(), init);
+                    }
+                    _ => shared
+                        .pending_candidates
+                        .entry(from_player_id)
+                        .or_default()
+                        .push(init),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 開いているすべてのデータチャネルにメッセージを送る
+    ///
+    /// # 戻り値
+    /// ルームの全員に送れた場合true（1人でも送れなかった場合はWebSocketで送り直す）
+    pub fn send(&self, text: &str) -> bool {
+        let shared = self.shared.borrow();
+        if !shared.selector.all_channels_open() {
+            return false;
+        }
+        shared.selector.room_peers().iter().all(|peer_id| {
+            shared
+                .channels
+                .get(peer_id)
+                .is_some_and(|channel| channel.send_with_str(text).is_ok())
+        })
+    }
+
+    /// 現在の通信経路の選択（データチャネルの状態）
+    pub fn selector(&self) -> TransportSelector {
+        self.shared.borrow().selector.clone()
+    }
+
+    /// WebSocketで送る接続交渉のメッセージを取り出す
+    pub fn take_signals(&mut self) -> Vec<WebSocketMessage> {
+        std::mem::take(&mut self.shared.borrow_mut().outgoing_signals)
+    }
+
+    /// データチャネルで届いたメッセージを取り出す
+    pub fn take_received(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.shared.borrow_mut().received)
+    }
+
+    /// 相手への接続とデータチャネルを作り、Offerを送る
+    fn start_offer(&mut self, peer_id: &str) -> Result<(), String> {
+        let connection = self.create_connection(peer_id)?;
+
+        // 順序も再送もない設定（遅れて届いたカーソル位置は不要なため）
+        let init = RtcDataChannelInit::new();
+        init.set_ordered(false);
+        init.set_max_retransmits(0);
+        let channel =
+            connection.create_data_channel_with_data_channel_dict(DATA_CHANNEL_LABEL, &init);
+        setup_channel(&self.shared, peer_id, channel);
+
+        let shared = Rc::clone(&self.shared);
+        let (own_id, room_id, peer_id) = (
+            self.own_id.clone(),
+            self.room_id.clone(),
+            peer_id.to_string(),
+        );
+        spawn_local(async move {
+            match create_description(&connection, RtcSdpType::Offer).await {
+                Ok(sdp) => {
+                    info!("📡 データチャネルを申し込みます: {}", peer_id);
+                    shared
+                        .borrow_mut()
+                        .outgoing_signals
+                        .push(WebSocketMessage::RtcSignal {
+                            room_id,
+                            from_player_id: own_id,
+                            to_player_id: peer_id,
+                            signal: RtcSignalPayload::Offer { sdp },
+                        });
+                }
+                Err(e) => warn!("⚠️ 申し込みを作成できません: {:?}", e),
+            }
+        });
+        Ok(())
+    }
+
+    /// 届いたOfferを設定し、Answerを送る
+    fn answer_offer(&self, connection: RtcPeerConnection, peer_id: String, sdp: String) {
+        let shared = Rc::clone(&self.shared);
+        let (own_id, room_id) = (self.own_id.clone(), self.room_id.clone());
+        spawn_local(async move {
+            let offer = session_description(RtcSdpType::Offer, &sdp);
+            if let Err(e) = JsFuture::from(connection.set_remote_description(&offer)).await {
+                warn!("⚠️ 申し込みを設定できません: {:?}", e);
+                return;
+            }
+            remote_description_applied(&shared, &connection, &peer_id);
+
+            match create_description(&connection, RtcSdpType::Answer).await {
+                Ok(sdp) => shared
+                    .borrow_mut()
+                    .outgoing_signals
+                    .push(WebSocketMessage::RtcSignal {
+                        room_id,
+                        from_player_id: own_id,
+                        to_player_id: peer_id,
+                        signal: RtcSignalPayload::Answer { sdp },
+                    }),
+                Err(e) => warn!("⚠️ 応答を作成できません: {:?}", e),
+            }
+        });
+    }
+
+    /// 相手へのRTCPeerConnectionを作成し、ICE候補と相手が開いたチャネルを受け取るよう設定
+    fn create_connection(&mut self, peer_id: &str) -> Result<RtcPeerConnection, String> {
+        let server = RtcIceServer::new();
+        server.set_urls_str(STUN_SERVER);
+        let configuration = RtcConfiguration::new();
+        configuration.set_ice_servers(&js_sys::Array::of1(&server));
+        let connection = RtcPeerConnection::new_with_configuration(&configuration)
+            .map_err(|e| format!("RTCPeerConnectionを作成できません: {:?}", e))?;
+
+        // 見つかったICE候補を相手に送る
+        let shared = Rc::clone(&self.shared);
+        let (own_id, room_id, peer) = (
+            self.own_id.clone(),
+            self.room_id.clone(),
+            peer_id.to_string(),
+        );
+        let onicecandidate = Closure::wrap(Box::new(move |event: RtcPeerConnectionIceEvent| {
+            let Some(candidate) = event.candidate() else {
+                return;
+            };
+            shared
+                .borrow_mut()
+                .outgoing_signals
+                .push(WebSocketMessage::RtcSignal {
+                    room_id: room_id.clone(),
+                    from_player_id: own_id.clone(),
+                    to_player_id: peer.clone(),
+                    signal: RtcSignalPayload::IceCandidate {
+                        candidate: candidate.candidate(),
+                        sdp_mid: candidate.sdp_mid(),
+                        sdp_m_line_index: candidate.sdp_m_line_index(),
+                    },
+                });
+        }) as Box<dyn FnMut(RtcPeerConnectionIceEvent)>);
+        connection.set_onicecandidate(Some(onicecandidate.as_ref().unchecked_ref()));
+        onicecandidate.forget();
+
+        // 申し込まれた側は、相手が作ったデータチャネルを受け取る
+        let shared = Rc::clone(&self.shared);
+        let peer = peer_id.to_string();
+        let ondatachannel = Closure::wrap(Box::new(move |event: RtcDataChannelEvent| {
+            setup_channel(&shared, &peer, event.channel());
+        }) as Box<dyn FnMut(RtcDataChannelEvent)>);
+        connection.set_ondatachannel(Some(ondatachannel.as_ref().unchecked_ref()));
+        ondatachannel.forget();
+
+        self.connections
+            .insert(peer_id.to_string(), connection.clone());
+        Ok(connection)
+    }
+
+    /// 相手との接続を閉じる
+    fn close_peer(&mut self, peer_id: &str) {
+        if let Some(connection) = self.connections.remove(peer_id) {
+            connection.close();
+            debug!("🔌 データチャネルを閉じました: {}", peer_id);
+        }
+        let mut shared = self.shared.borrow_mut();
+        if let Some(channel) = shared.channels.remove(peer_id) {
+            channel.close();
+        }
+        shared.selector.channel_closed(peer_id);
+        shared.pending_candidates.remove(peer_id);
+        shared.cursor_sequences.remove(peer_id);
+        shared.remote_ready.retain(|peer| peer != peer_id);
+    }
+
+    /// すべての相手との接続を閉じる
+    fn close_all(&mut self) {
+        let peers: Vec<String> = self.connections.keys().cloned().collect();
+        for peer_id in peers {
+            self.close_peer(&peer_id);
+        }
+    }
+}
+
+/// データチャネルの開閉とメッセージの受信を設定
+fn setup_channel(shared: &Rc<RefCell<Shared>>, peer_id: &str, channel: RtcDataChannel) {
+    let state = Rc::clone(shared);
+    let (peer, opened) = (peer_id.to_string(), channel.clone());
+    let onopen = Closure::wrap(Box::new(move |_: JsValue| {
+        info!("✅ データチャネルが開きました: {}", peer);
+        let mut state = state.borrow_mut();
+        state.channels.insert(peer.clone(), opened.clone());
+        state.selector.channel_opened(&peer);
+    }) as Box<dyn FnMut(JsValue)>);
+    channel.set_onopen(Some(onopen.as_ref().unchecked_ref()));
+    onopen.forget();
+
+    let state = Rc::clone(shared);
+    let peer = peer_id.to_string();
+    let onclose = Closure::wrap(Box::new(move |_: JsValue| {
+        info!("🔌 データチャネルが閉じました: {}", peer);
+        let mut state = state.borrow_mut();
+        state.channels.remove(&peer);
+        state.selector.channel_closed(&peer);
+    }) as Box<dyn FnMut(JsValue)>);
+    channel.set_onclose(Some(onclose.as_ref().unchecked_ref()));
+    onclose.forget();
+
+    let state = Rc::clone(shared);
+    let peer = peer_id.to_string();
+    let onmessage = Closure::wrap(Box::new(move |event: MessageEvent| {
+        let Some(text) = event.data().as_string() else {
+            return;
+        };
+        // データチャネルはサーバーを通らないため、相手本人のカーソル位置などだけを受け付ける
+        match WebSocketMessage::parse(&text) {
+            Ok(WebSocketMessage::MousePosition {
+                player_id,
+                sequence,
+                ..
+            }) if player_id == peer => {
+                let mut state = state.borrow_mut();
+                let arrival = sequence.map(|sequence| {
+                    state
+                        .cursor_sequences
+                        .entry(player_id)
+                        .or_default()
+                        .record(Channel::Cursor, sequence)
+                });
+                if matches!(arrival, Some(Arrival::Late | Arrival::Stale)) {
+                    debug!("🗑️ 古いカーソル位置を破棄: {}", peer);
+                    return;
+                }
+                state.received.push(text);
+            }
+            Ok(_) => warn!(
+                "⚠️ データチャネルでは受け付けないメッセージです（{}）",
+                peer
+            ),
+            Err(e) => warn!("⚠️ データチャネルのメッセージが不正です（{}）: {}", peer, e),
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+    channel.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+    onmessage.forget();
+}
+
+/// OfferまたはAnswerを作成し、自分の接続情報として設定
+///
+/// # 戻り値
+/// 相手に送るSDP
+async fn create_description(
+    connection: &RtcPeerConnection,
+    sdp_type: RtcSdpType,
+) -> Result<String, JsValue> {
+    let promise = match sdp_type {
+        RtcSdpType::Offer => connection.create_offer(),
+        _ => connection.create_answer(),
+    };
+    let description = JsFuture::from(promise).await?;
+    let sdp = js_sys::Reflect::get(&description, &JsValue::from_str("sdp"))?
+        .as_string()
+        .unwrap_or_default();
+    JsFuture::from(connection.set_local_description(&session_description(sdp_type, &sdp))).await?;
+    Ok(sdp)
+}
+
+/// SDPから接続情報を作成
+fn session_description(sdp_type: RtcSdpType, sdp: &str) -> RtcSessionDescriptionInit {
+    let description = RtcSessionDescriptionInit::new(sdp_type);
+    description.set_sdp(sdp);
+    description
+}
+
+/// 相手の接続情報を設定できたので、待たせていたICE候補を追加する
+fn remote_description_applied(
+    shared: &Rc<RefCell<Shared>>,
+    connection: &RtcPeerConnection,
+    peer_id: &str,
+) {
+    let pending = {
+        let mut shared = shared.borrow_mut();
+        shared.remote_ready.push(peer_id.to_string());
+        shared
+            .pending_candidates
+            .remove(peer_id)
+            .unwrap_or_default()
+    };
+    for candidate in pending {
+        add_ice_candidate(connection.clone(), candidate);
+    }
+}
+
+/// ICE候補を追加
+fn add_ice_candidate(connection: RtcPeerConnection, candidate: RtcIceCandidateInit) {
+    spawn_local(async move {
+        let added = connection.add_ice_candidate_with_opt_rtc_ice_candidate_init(Some(&candidate));
+        if let Err(e) = JsFuture::from(added).await {
+            warn!("⚠️ ICE候補を追加できません: {:?}", e);
+        }
+    });
+}
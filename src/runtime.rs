@@ -0,0 +1,1254 @@
+// =============================================================================
+// ゲームランタイム
+// =============================================================================
+// このファイルでは、ECSワールドとシステムスケジューラをひとまとめにした
+// ゲームランタイム（GameRuntime）を実装します。
+// WebAssemblyとして公開される関数はこのランタイムを通してゲームを操作します。
+//
+// 主要な責務：
+// - ECSワールドとシステムスケジューラの所有
+// - ゲームで使用するシステムの登録（実行順序の管理）
+// - 現在のゲーム状態エンティティの追跡
+// - サーバーとの通信（NetworkClient）の所有と、毎フレームの送受信
+// =============================================================================
+
+use crate::achievements::{AchievementId, AchievementStore, AchievementSystem};
+use crate::animation_queue::{self, AnimationQueue, AnimationQueueSystem};
+use crate::animation_watchdog::AnimationWatchdogSystem;
+use crate::analysis::GameAnalysis;
+use crate::client_state::{self, GamePhase};
+use crate::clock::{monotonic_ms, FrameSteps, GameClock};
+use crate::crash_report;
+use crate::debug_info::{DebugInfo, MemoryStats};
+use crate::ecs::{Entity, SystemScheduler, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::frame_timings::{FrameTimingHistory, TimingCategory};
+use crate::game::{
+    ActionQueue, AnimationSettings, GameActionPool, GameSettings, UnwinnableCheckSettings,
+};
+use crate::hint::{Hint, HintEngine};
+use crate::input::{self, InputEvent, InputState, InputSystem, PointerEvent, PointerKind};
+use crate::layout::BoardLayout;
+use crate::network::{
+    MessageProcessingSystem, NetworkConnectionSystem, NetworkMessagePool, NetworkQueues,
+};
+use crate::network_client::NetworkClient;
+use crate::network_conditioner::NetworkConditionerSystem;
+use crate::practice::{self, PracticeGame};
+use crate::notification::NotificationSystem;
+use crate::puzzle::{Puzzle, PuzzleProgress, PuzzleSystem};
+use crate::reaction::ReactionSystem;
+use crate::reconcile::{LocalPrediction, ReconcileSystem};
+use crate::result::{GameResult, GameResultSystem};
+use crate::rng::Rng;
+use crate::schedule::{Schedule, ViewMode, PLAYING_ONLY};
+use crate::save_game::{self, SavedGame};
+use crate::selection::{self, HighlightSystem, PilePlaceholder, SelectionSystem};
+use crate::settings::{self, PreferenceOverrides, Preferences};
+use crate::solitaire::{
+    CardAnimationSystem, CardLocation, CardMovementSystem, CardStack, MoveLog, SolitaireCard,
+    SolitaireGameState, SolitaireManager, SolitaireProgressSystem, SolitaireType,
+};
+use crate::solve_cache::SolveCache;
+use crate::solver::{Solver, WinnabilityReport, WinnabilitySystem};
+use crate::state_observer::{GameStateObserverSystem, StateChanges};
+use crate::stats_transfer::StatsExport;
+use crate::theme::Theme;
+use crate::timeline::{self, TimelineEntry, TimelineRecord, TimelineRecorder};
+use crate::tutorial::{self, Tutorial, TutorialAction, TutorialProgress};
+use crate::viewport::Viewport;
+use log::{debug, info, warn};
+
+/// 自動プレイで1回に打つ手の上限（念のための無限ループ防止）
+const MAX_AUTO_PLAY_MOVES: u32 = 1000;
+
+/// ヒントのカードを強調表示しておく時間（秒）
+const HINT_HIGHLIGHT_SECONDS: f64 = 3.0;
+
+/// 配るアニメーションでカードを落とし始める高さ（移動先からの距離、ピクセル）
+const DEAL_DROP_DISTANCE: f32 = 120.0;
+
+/// ゲームランタイム
+///
+/// 1つのゲームセッションに必要なECSワールドとシステムを保持します。
+/// WebAssembly環境ではスレッドローカルに1つだけ存在します。
+pub struct GameRuntime {
+    /// ECSワールド
+    pub world: World,
+
+    /// システムスケジューラ
+    pub scheduler: SystemScheduler,
+
+    /// 現在のソリティアゲーム状態エンティティ（ゲーム開始前はNone）
+    pub game_entity: Option<Entity>,
+
+    /// サーバーとの通信
+    pub network: NetworkClient,
+
+    /// タブが非表示になった時刻（UNIX時刻のミリ秒、表示中はNone）
+    hidden_since_ms: Option<u64>,
+
+    /// 次のフレームの経過時間を捨てるか（非表示から戻った直後のフレーム）
+    discard_next_delta: bool,
+
+    /// 見ているもの（自分のゲーム・リプレイ・観戦）
+    view_mode: ViewMode,
+
+    /// 直近のフレームの種類ごとの処理時間（get_frame_timings()で返す）
+    pub frame_timings: FrameTimingHistory,
+}
+
+impl GameRuntime {
+    /// 新しいゲームランタイムを作成
+    ///
+    /// システムは依存関係を考慮した順序で登録されます：
+    /// 入力 → 選択 → 移動 → アニメーション（順番待ち → 通常 → 合わせ直し → 止まったアニメーションの見張り） → 強調表示 → リアクション → 進行チェック → パズル判定 → 進行通知 → 結果作成 → 実績判定 → 状態の変化の監視 → ネットワーク
+    /// （受信メッセージの処理の直前に、通信状態の再現を設定した場合のみ働く中継を挟みます）
+    /// 入力・選択・移動・強調表示と、進行チェック・パズル判定・勝ち筋の確認・結果作成・実績判定は
+    /// プレイ中のスケジュールでだけ実行します（メニュー・リプレイ・観戦中は実行しない）。
+    ///
+    /// # 戻り値
+    /// 初期化されたGameRuntimeインスタンス
+    pub fn new() -> Self {
+        let mut scheduler = SystemScheduler::new();
+        scheduler.add_system_to(InputSystem, PLAYING_ONLY);
+        scheduler.add_system_to(SelectionSystem, PLAYING_ONLY);
+        scheduler.add_system_to(CardMovementSystem, PLAYING_ONLY);
+        scheduler.add_system(AnimationQueueSystem);
+        scheduler.add_system(CardAnimationSystem);
+        scheduler.add_system(ReconcileSystem);
+        scheduler.add_system(AnimationWatchdogSystem);
+        scheduler.add_system_to(HighlightSystem, PLAYING_ONLY);
+        scheduler.add_system(ReactionSystem);
+        scheduler.add_system_to(SolitaireProgressSystem, PLAYING_ONLY);
+        scheduler.add_system_to(PuzzleSystem, PLAYING_ONLY);
+        scheduler.add_system(NotificationSystem);
+        scheduler.add_system_to(WinnabilitySystem, PLAYING_ONLY);
+        scheduler.add_system_to(GameResultSystem, PLAYING_ONLY);
+        scheduler.add_system_to(AchievementSystem, PLAYING_ONLY);
+        scheduler.add_system(GameStateObserverSystem);
+        scheduler.add_system(NetworkConnectionSystem);
+        scheduler.add_system(NetworkConditionerSystem);
+        scheduler.add_system(MessageProcessingSystem);
+
+        let mut world = World::new();
+        world.insert_resource(EventQueue::new());
+        world.insert_resource(AchievementStore::load());
+        world.insert_resource(Theme::default());
+        world.insert_resource(Preferences::default());
+        world.insert_resource(PreferenceOverrides::default());
+        world.insert_resource(GameSettings::default());
+        world.insert_resource(NetworkMessagePool::new());
+        world.insert_resource(GameActionPool::new());
+        world.insert_resource(NetworkQueues::new());
+        world.insert_resource(ActionQueue::new());
+        world.insert_resource(StateChanges::new());
+        world.insert_resource(Rng::from_entropy());
+        world.insert_resource(GameClock::new());
+        world.insert_resource(InputState::default());
+        world.insert_resource(AnimationQueue::default());
+        world.insert_resource(TimelineRecorder::default());
+        world.insert_resource(SolveCache::default());
+        world.insert_resource(Viewport::default());
+        scheduler.set_schedule(Schedule::MainMenu.as_str());
+
+        Self {
+            world,
+            scheduler,
+            game_entity: None,
+            network: NetworkClient::new(),
+            hidden_since_ms: None,
+            discard_next_delta: false,
+            view_mode: ViewMode::Play,
+            frame_timings: FrameTimingHistory::new(),
+        }
+    }
+
+    /// 新しいソリティアゲームを開始
+    ///
+    /// 1人用の途中のゲームは、中断したゲームの一覧に加えます。
+    /// デッキから引く枚数は現在の設定（draw_mode）に従います。
+    ///
+    /// # 引数
+    /// * `game_type` - ゲームの種類
+    ///
+    /// # 戻り値
+    /// ゲーム状態エンティティ
+    pub fn start_game(&mut self, game_type: SolitaireType) -> Entity {
+        self.shelve_unfinished_game();
+        let entity = SolitaireManager::start_new_game(&mut self.world, game_type);
+        let draw_count = self.preferences().draw_mode.count();
+        if let Some(state) = self.world.get_component_mut::<SolitaireGameState>(entity) {
+            state.draw_count = draw_count;
+        }
+        self.game_entity = Some(entity);
+        self.animate_deal();
+        entity
+    }
+
+    /// パズルを開始
+    ///
+    /// 途中のゲームを中断したゲームの一覧に加え、盤面を片付けてから、パズルの局面を作成します。
+    /// パズルが不正な場合は進行中のゲームをそのまま残します。
+    ///
+    /// # 引数
+    /// * `puzzle` - 挑戦するパズル
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、パズルが不正な場合はエラーメッセージ
+    pub fn start_puzzle(&mut self, puzzle: &Puzzle) -> Result<Entity, String> {
+        self.replace_unfinished_board(|world| puzzle.start(world))
+    }
+
+    /// チュートリアルを開始
+    ///
+    /// 途中のゲームを中断したゲームの一覧に加え、盤面を片付けてから、チュートリアルの盤面を作成します。
+    /// チュートリアルが不正な場合は進行中のゲームをそのまま残します。
+    ///
+    /// # 引数
+    /// * `tutorial` - 進めるチュートリアル
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、チュートリアルが不正な場合はエラーメッセージ
+    pub fn start_tutorial(&mut self, tutorial: &Tutorial) -> Result<Entity, String> {
+        self.replace_unfinished_board(|world| tutorial.start(world))
+    }
+
+    /// 進行中のチュートリアルを最初の手順からやり直す
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、チュートリアル中でない場合はエラーメッセージ
+    pub fn restart_tutorial(&mut self) -> Result<Entity, String> {
+        let tutorial = self
+            .tutorial_progress()
+            .map(|progress| progress.tutorial.clone())
+            .ok_or_else(|| "チュートリアル中ではありません".to_string())?;
+        self.start_tutorial(&tutorial)
+    }
+
+    /// チュートリアルの手を打つ
+    ///
+    /// 現在の手順で決められた手だけを受け付け、動いたカードは移動先へアニメーションします。
+    ///
+    /// # 引数
+    /// * `action` - プレイヤーが打った手
+    ///
+    /// # 戻り値
+    /// 成功時はOk、決められた手と違う・チュートリアル中でない場合は理由を表すエラーメッセージ
+    pub fn tutorial_action(&mut self, action: &TutorialAction) -> Result<(), String> {
+        let entity = self
+            .game_entity
+            .ok_or_else(|| "チュートリアル中ではありません".to_string())?;
+
+        let before = self.settle_card_positions();
+        let result = tutorial::perform(&mut self.world, entity, action);
+        self.animate_from(&before);
+        result
+    }
+
+    /// 現在のチュートリアルの進み具合を取得
+    ///
+    /// # 戻り値
+    /// チュートリアル中の場合はSome(&TutorialProgress)、通常のゲームの場合はNone
+    pub fn tutorial_progress(&self) -> Option<&TutorialProgress> {
+        self.world.get_component::<TutorialProgress>(self.game_entity?)
+    }
+
+    /// 進行中のゲームを端末内に保存
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、ゲームがない・保存できない場合はエラーメッセージ
+    pub fn save_game(&self) -> Result<(), String> {
+        let game = self
+            .game_entity
+            .ok_or_else(|| "ゲームが始まっていません".to_string())?;
+        if practice::is_practice(&self.world, game) {
+            return Err("練習のゲームは保存できません".to_string());
+        }
+        save_game::save(&SavedGame::capture(&self.world, game)?)?;
+        info!("💾 ゲームを保存しました");
+        Ok(())
+    }
+
+    /// 端末内に保存したゲームを続きから始める
+    ///
+    /// 保存データが古い形式の場合は現在の形式に変換してから読み込みます。
+    /// 進行中の途中のゲームは中断したゲームの一覧に加えます。
+    /// 保存データがない・不正な場合は進行中のゲームをそのまま残します。
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、保存データがない・不正な場合はエラーメッセージ
+    pub fn load_game(&mut self) -> Result<Entity, String> {
+        let saved = save_game::load().ok_or_else(|| "保存されたゲームがありません".to_string())?;
+        let entity = self.replace_unfinished_board(|world| saved.restore(world))?;
+        info!("💾 保存したゲームを読み込みました（{}手目から）", saved.state.move_count);
+        Ok(entity)
+    }
+
+    /// 中断したゲームの一覧にあるゲームを続きから始め、一覧から外す
+    ///
+    /// 進行中の途中のゲームは、代わりに一覧に加えます。
+    /// 保存データがない・不正な場合は進行中のゲームをそのまま残します。
+    ///
+    /// # 引数
+    /// * `id` - list_saved_games()で得たID
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、一覧にない・保存データが不正な場合はエラーメッセージ
+    pub fn resume_saved_game(&mut self, id: &str) -> Result<Entity, String> {
+        let saved = save_game::load_from_list(id)?;
+        let entity = self.replace_unfinished_board(|world| saved.restore(world))?;
+        save_game::remove_from_list(id)?;
+        info!("💾 中断したゲームを再開しました: {}（{}手目から）", id, saved.state.move_count);
+        Ok(entity)
+    }
+
+    /// 1人用の途中のゲームを、別のゲームを始める前に中断したゲームの一覧に加える
+    ///
+    /// 1手も動かしていない・終わった・練習・パズル・チュートリアル・ルームのゲームは加えません。
+    fn shelve_unfinished_game(&self) {
+        let Some(game) = self.game_entity else {
+            return;
+        };
+        let unfinished = self
+            .game_state()
+            .is_some_and(|state| !state.is_won && state.move_count > 0);
+        if !unfinished
+            || self.is_practice()
+            || self.puzzle_progress().is_some()
+            || self.tutorial_progress().is_some()
+            || self.network.room_id().is_some()
+        {
+            return;
+        }
+        match SavedGame::capture(&self.world, game).and_then(|saved| save_game::add_to_list(&saved)) {
+            Ok(info) => info!("🗂️ 途中のゲームを中断したゲームの一覧に加えました: {}", info.id),
+            Err(e) => debug!("🗂️ 途中のゲームを一覧に加えられません: {}", e),
+        }
+    }
+
+    /// 手元で進めていた盤面を、サーバーから届いた正しい盤面に合わせ直す
+    ///
+    /// 位置の違うカードは瞬間移動させず、手元の位置から正しい位置へ短いアニメーションで動かします。
+    /// スコアの修正は移動の得点として数えず、StateReconciledイベントで1回だけ知らせます。
+    /// 盤面が不正な場合は進行中のゲームをそのまま残します。
+    ///
+    /// # 引数
+    /// * `snapshot` - サーバーの正しい盤面
+    ///
+    /// # 戻り値
+    /// 成功時は位置を直したカードの枚数、盤面が不正な場合はエラーメッセージ
+    pub fn reconcile(&mut self, snapshot: &SavedGame) -> Result<u32, String> {
+        let local = LocalPrediction::capture(&self.world, self.game_entity);
+        let entity = self.replace_board(|world| snapshot.restore(world))?;
+        Ok(local.apply(&mut self.world, entity))
+    }
+
+    /// 現在のゲームの指定した手数の局面から、練習のゲームを別のランタイムで始める
+    ///
+    /// 進行中のアニメーションは完了させてから局面を写します。
+    /// 練習のランタイムはサーバーに接続せず、設定だけを引き継ぎます。
+    /// 練習では元に戻すのを何回でもでき、結果は実績・通算成績に数えません。
+    ///
+    /// # 引数
+    /// * `move_index` - 何手目の局面から分岐するか（0は配られた直後、現在の手数は現在の局面）
+    ///
+    /// # 戻り値
+    /// 成功時は練習のランタイム、ゲームがない・局面を作れない場合はエラーメッセージ
+    pub fn fork_practice(&mut self, move_index: usize) -> Result<GameRuntime, String> {
+        let game = self
+            .game_entity
+            .ok_or_else(|| "ゲームが始まっていません".to_string())?;
+        self.settle_card_positions();
+        let start = practice::position_at(&self.world, game, move_index)?;
+
+        let mut sandbox = GameRuntime::new();
+        sandbox.apply_preferences(self.preferences());
+        sandbox.replace_board(|world| practice::build(world, &start, move_index, &[], 0))?;
+        info!("🧪 {}手目の局面から練習を始めます", move_index);
+        Ok(sandbox)
+    }
+
+    /// 練習のゲームの最後の手を元に戻す
+    ///
+    /// 分岐した時点の盤面から、最後の手を除いて打ち直した盤面に置き換えます。
+    ///
+    /// # 戻り値
+    /// 成功時は新しいゲーム状態エンティティ、練習中でない・戻す手がない場合はエラーメッセージ
+    pub fn undo_practice_move(&mut self) -> Result<Entity, String> {
+        let game = self
+            .game_entity
+            .ok_or_else(|| "練習中ではありません".to_string())?;
+        let practice = self
+            .world
+            .get_component::<PracticeGame>(game)
+            .cloned()
+            .ok_or_else(|| "練習中ではありません".to_string())?;
+        let mut moves = self
+            .world
+            .get_component::<MoveLog>(game)
+            .map(|log| log.moves.clone())
+            .unwrap_or_default();
+        if moves.pop().is_none() {
+            return Err("元に戻す手がありません".to_string());
+        }
+
+        let undos_used = practice.undos_used + 1;
+        let entity = self.replace_board(|world| {
+            practice::build(
+                world,
+                &practice.start,
+                practice.forked_at_move,
+                &moves,
+                undos_used,
+            )
+        })?;
+        debug!("↩️ 練習の手を元に戻しました（{}手目）", moves.len());
+        Ok(entity)
+    }
+
+    /// 練習のゲーム中かチェック
+    pub fn is_practice(&self) -> bool {
+        self.game_entity
+            .is_some_and(|game| practice::is_practice(&self.world, game))
+    }
+
+    /// 現在のパズルの進み具合を取得
+    ///
+    /// # 戻り値
+    /// パズルに挑戦中の場合はSome(&PuzzleProgress)、通常のゲームの場合はNone
+    pub fn puzzle_progress(&self) -> Option<&PuzzleProgress> {
+        self.world.get_component::<PuzzleProgress>(self.game_entity?)
+    }
+
+    /// 全システムを1フレーム分実行
+    ///
+    /// 経過時間はゲーム時計に記録され、上限（MAX_FRAME_SECONDS）で切り詰めた値を
+    /// 最大MAX_STEPS_PER_FRAME回に分けて各システム（アニメーションなど）に渡します。
+    /// 経過時間が大きく飛んだ場合（スリープ復帰など）はClockJumpDetectedイベントで知らせます。
+    /// 1回の呼び出しをタイムラインの1ティックとして数え、フレーム中のイベントを記録します。
+    /// サーバーから届いたメッセージは、同じフレームのシステムで処理されるよう先に取り込みます。
+    /// ルームに参加中は、スコアが変わっていれば他のプレイヤーに送ります。
+    /// 各システムと通信にかかった時間は、種類ごとにframe_timingsへ記録します。
+    ///
+    /// タブが非表示の間は何もしません。表示に戻った直後のフレームは、
+    /// 非表示の間の経過時間がまとめて渡されるため、経過時間0として扱います。
+    ///
+    /// # 引数
+    /// * `delta_time` - 前フレームからの経過時間（秒）
+    pub fn update(&mut self, delta_time: f64) {
+        if self.is_hidden() {
+            return;
+        }
+        let delta_time = if std::mem::take(&mut self.discard_next_delta) {
+            0.0
+        } else {
+            delta_time
+        };
+        self.frame_timings.begin_frame();
+        let frame = self.world.get_resource_mut::<GameClock>().map_or_else(
+            || FrameSteps::split(delta_time),
+            |clock| clock.tick_steps(delta_time),
+        );
+        // 前のフレームの後に（ゲームの開始などで）発生したイベントは前のティックに記録する
+        timeline::record_events(&mut self.world);
+        if let Some(timeline) = self.world.get_resource_mut::<TimelineRecorder>() {
+            timeline.advance_tick();
+        }
+        if frame.is_clock_jump() {
+            self.notify_clock_jump(&frame);
+        }
+
+        let start = monotonic_ms();
+        self.network.poll(&mut self.world);
+        self.frame_timings.add(TimingCategory::Network, monotonic_ms() - start);
+        self.sync_schedule();
+        let frame_timings = &mut self.frame_timings;
+        for _ in 0..frame.steps {
+            self.scheduler.update_timed(&mut self.world, frame.step_seconds, |name, elapsed_ms| {
+                frame_timings.add(TimingCategory::of_system(name), elapsed_ms);
+            });
+        }
+        let start = monotonic_ms();
+        self.report_score();
+        self.report_card_back();
+        self.report_idle();
+        self.frame_timings.add(TimingCategory::Network, monotonic_ms() - start);
+        timeline::record_events(&mut self.world);
+    }
+
+    /// ゲームの進行段階と見ているものに合わせてスケジュールを切り替える
+    fn sync_schedule(&mut self) {
+        let schedule = self.schedule();
+        if self.scheduler.schedule() != Some(schedule.as_str()) {
+            debug!("🗓️ スケジュールを切り替えました: {}", schedule.as_str());
+            self.scheduler.set_schedule(schedule.as_str());
+        }
+    }
+
+    /// 見ているもの（自分のゲーム・リプレイ・観戦）を切り替える
+    ///
+    /// 次のupdate()から、リプレイ・観戦中は入力とスコア・勝敗の判定のシステムを実行しません。
+    ///
+    /// # 引数
+    /// * `view_mode` - 見ているもの
+    pub fn set_view_mode(&mut self, view_mode: ViewMode) {
+        self.view_mode = view_mode;
+    }
+
+    /// 現在のスケジュールを取得
+    ///
+    /// # 戻り値
+    /// ゲームの進行段階と見ているものから選ばれるスケジュール
+    pub fn schedule(&self) -> Schedule {
+        let phase = self
+            .game_state()
+            .map_or(GamePhase::NotStarted, client_state::phase_of);
+        Schedule::select(phase, self.view_mode)
+    }
+
+    /// 経過時間が大きく飛んだことを知らせる
+    fn notify_clock_jump(&mut self, frame: &FrameSteps) {
+        let gap_ms = (frame.raw_seconds * 1000.0) as u64;
+        let dropped_ms = (frame.dropped_seconds() * 1000.0) as u64;
+        warn!(
+            "⏱️ 経過時間が飛びました: {}ms（{}msは進めずに捨てます）",
+            gap_ms, dropped_ms
+        );
+        if let Some(events) = self.world.get_resource_mut::<EventQueue>() {
+            events.push(GameEvent::ClockJumpDetected { gap_ms, dropped_ms });
+        }
+    }
+
+    /// タブの表示・非表示を切り替える（Page Visibility API）
+    ///
+    /// 非表示の間はゲームループ・経過時間・カーソル位置の送信を止めます。
+    /// 表示に戻ったときは、非表示だった時間をゲームの経過時間から除きます。
+    ///
+    /// # 引数
+    /// * `hidden` - タブが非表示になった場合true
+    pub fn set_hidden(&mut self, hidden: bool) {
+        let now_ms = GameClock::from_world(&self.world).now_ms();
+        match (hidden, self.hidden_since_ms) {
+            (true, None) => {
+                self.hidden_since_ms = Some(now_ms);
+                info!("🙈 タブが非表示になったためゲームを止めます");
+            }
+            (false, Some(since_ms)) => {
+                self.hidden_since_ms = None;
+                self.discard_next_delta = true;
+                let paused_seconds =
+                    (now_ms.saturating_sub(since_ms) as f64 / 1000.0).round() as u64;
+                if let Some(game_state) = self.game_state_mut() {
+                    game_state.add_paused_time(paused_seconds);
+                }
+                info!(
+                    "👀 タブが表示されたためゲームを再開します（{}秒止めていました）",
+                    paused_seconds
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// タブが非表示かどうか
+    pub fn is_hidden(&self) -> bool {
+        self.hidden_since_ms.is_some()
+    }
+
+    /// 自分のスコアが変わっていれば、同じルームの他のプレイヤーに送る（次のフレームで送信）
+    ///
+    /// GameStateObserverSystemが検出したスコア・手数の変化を見て送ります。
+    /// ルームに参加してまだ送っていない場合は、変化がなくても現在のスコアを送ります。
+    fn report_score(&mut self) {
+        let changed = self
+            .world
+            .get_resource_mut::<StateChanges>()
+            .map(|changes| changes.drain())
+            .unwrap_or_default()
+            .iter()
+            .any(|event| {
+                matches!(
+                    event,
+                    GameEvent::ScoreChanged { .. } | GameEvent::MovesChanged { .. }
+                )
+            });
+        if self.network.room_id().is_none() || (!changed && self.network.has_sent_score()) {
+            return;
+        }
+        let Some(score) = self.game_state().map(|state| state.score) else {
+            return;
+        };
+        let foundation_cards = self
+            .world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.location_type == CardLocation::Foundation)
+            .count();
+        let foundation_cards = u16::try_from(foundation_cards).unwrap_or(u16::MAX);
+        if let Err(e) = self.network.send_score(score, foundation_cards) {
+            debug!("📊 スコアを送信できません: {}", e);
+        }
+    }
+
+    /// 見せる設定のカードの裏面を、同じルームの他のプレイヤーに送る（次のフレームで送信）
+    ///
+    /// 前回送ったものから変わった場合・見せるのをやめた場合だけ送ります。
+    fn report_card_back(&mut self) {
+        if self.network.room_id().is_none() {
+            return;
+        }
+        let card_back = self
+            .world
+            .get_resource::<Theme>()
+            .and_then(Theme::shared_card_back);
+        if let Err(e) = self.network.send_card_back(card_back) {
+            debug!("🎴 カードの裏面を送信できません: {}", e);
+        }
+    }
+
+    /// 操作していない時間を、同じルームの参加者の離席の判定のために送る（次のフレームで送信）
+    ///
+    /// 段階が変わった場合だけ送ります（afk.rs）。
+    fn report_idle(&mut self) {
+        if self.network.room_id().is_none() {
+            return;
+        }
+        let Some(idle_time) = self.game_state().map(|state| state.idle_time) else {
+            return;
+        };
+        if let Err(e) = self.network.send_idle_status(idle_time) {
+            debug!("💤 操作していない時間を送信できません: {}", e);
+        }
+    }
+
+    /// 現在のゲーム状態を取得
+    ///
+    /// # 戻り値
+    /// ゲーム進行中の場合はSome(&SolitaireGameState)、ゲーム開始前はNone
+    pub fn game_state(&self) -> Option<&SolitaireGameState> {
+        self.world.get_component::<SolitaireGameState>(self.game_entity?)
+    }
+
+    /// 現在のゲーム状態を取得（可変参照）
+    ///
+    /// # 戻り値
+    /// ゲーム進行中の場合はSome(&mut SolitaireGameState)、ゲーム開始前はNone
+    pub fn game_state_mut(&mut self) -> Option<&mut SolitaireGameState> {
+        self.world
+            .get_component_mut::<SolitaireGameState>(self.game_entity?)
+    }
+
+    /// 現在のゲームの結果レポートを取得
+    ///
+    /// # 戻り値
+    /// ゲームが終了している場合はSome(&GameResult)、進行中の場合はNone
+    pub fn game_result(&self) -> Option<&GameResult> {
+        self.world.get_component::<GameResult>(self.game_entity?)
+    }
+
+    /// 溜まっているイベントをすべて取り出す
+    ///
+    /// 取り出す前に、まだタイムラインに記録していないイベントを記録します。
+    ///
+    /// # 戻り値
+    /// 発生順のイベントのベクター
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        timeline::record_events(&mut self.world);
+        self.world
+            .get_resource_mut::<EventQueue>()
+            .map(|events| events.drain())
+            .unwrap_or_default()
+    }
+
+    /// 実績・通算成績を取得
+    ///
+    /// # 戻り値
+    /// 実績データ（読み込み前の場合はNone）
+    pub fn achievements(&self) -> Option<&AchievementStore> {
+        self.world.get_resource::<AchievementStore>()
+    }
+
+    /// サーバーが署名した実績・通算成績の書き出しを読み込んでまとめ、端末内に保存する
+    ///
+    /// クライアントは署名鍵を持たないため、先にNetworkClient::verify_stats()でサーバーに署名を
+    /// 確かめてもらい、正しいと返ってきた（StatsVerified）書き出しだけを読み込みます。
+    ///
+    /// # 引数
+    /// * `json` - 書き出しのJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時は新しく解除済みになった実績ID、形式不正・署名がない・署名を確かめていない・
+    /// 読み込み済みの場合はエラーメッセージ
+    pub fn import_statistics(&mut self, json: &str) -> Result<Vec<AchievementId>, String> {
+        let export = StatsExport::parse(json)?;
+        let signature = export
+            .signature
+            .as_deref()
+            .ok_or_else(|| "署名のない成績は読み込めません".to_string())?;
+        if !self.network.take_verified_stats(signature) {
+            return Err("サーバーで署名を確かめてから読み込んでください".to_string());
+        }
+        let store = self
+            .world
+            .get_resource_mut::<AchievementStore>()
+            .ok_or_else(|| "実績データがありません".to_string())?;
+        let newly_unlocked = store.merge(&export.source_id, &export.export_id, &export.achievements)?;
+        if let Err(e) = store.save() {
+            warn!("⚠️ 実績データの保存失敗: {}", e);
+        }
+        info!(
+            "📥 成績を読み込みました: {}ゲーム分、新しい実績{}件",
+            export.achievements.stats.games_played,
+            newly_unlocked.len()
+        );
+        Ok(newly_unlocked)
+    }
+
+    /// デバッグモードを切り替える
+    ///
+    /// デバッグモード中はシステムの実行時間を計測し、
+    /// デバッグ情報に収集コストの高い項目も含めます。
+    ///
+    /// # 引数
+    /// * `enabled` - 有効にする場合true
+    pub fn set_debug_mode(&mut self, enabled: bool) {
+        if let Some(settings) = self.world.get_resource_mut::<GameSettings>() {
+            settings.debug_mode = enabled;
+        }
+        self.scheduler.set_profiling(enabled);
+    }
+
+    /// 名前を指定してシステムを有効/無効にする
+    ///
+    /// 無効にしたシステムは有効に戻すまで実行されません
+    /// （アニメーションの停止・通信の停止などのデバッグや、ヒントを出さないなどの機能の切り替えに使う）。
+    ///
+    /// # 引数
+    /// * `name` - システム名（例："CardAnimationSystem"）
+    /// * `enabled` - 実行する場合true
+    ///
+    /// # 戻り値
+    /// 成功時はOk(())、その名前のシステムがない場合はエラーメッセージ
+    pub fn set_system_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        if !self.scheduler.set_enabled(name, enabled) {
+            return Err(format!("システムが見つかりません: {}", name));
+        }
+        info!(
+            "⚙️ システムを{}にしました: {}",
+            if enabled { "有効" } else { "無効" },
+            name
+        );
+        Ok(())
+    }
+
+    /// アニメーション設定を取得
+    pub fn animation_settings(&self) -> AnimationSettings {
+        self.world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.animation)
+            .unwrap_or_default()
+    }
+
+    /// アニメーション設定を変更する
+    ///
+    /// すべてのアニメーションを省略する設定にした場合、進行中のアニメーションもすぐに完了させます。
+    ///
+    /// # 引数
+    /// * `animation` - 新しいアニメーション設定
+    ///
+    /// # 戻り値
+    /// 変更できた場合Ok(())、設定値が範囲外の場合はエラーメッセージ
+    pub fn set_animation_settings(&mut self, animation: AnimationSettings) -> Result<(), String> {
+        animation.validate()?;
+        if let Some(settings) = self.world.get_resource_mut::<GameSettings>() {
+            settings.animation = animation;
+        }
+        if animation.instant {
+            self.settle_card_positions();
+        }
+        Ok(())
+    }
+
+    /// 操作がないときに次の一手を知らせるまでの時間を変更する
+    ///
+    /// # 引数
+    /// * `seconds` - 知らせるまでの時間（秒、0の場合は知らせない）
+    pub fn set_idle_hint_seconds(&mut self, seconds: u32) {
+        if let Some(settings) = self.world.get_resource_mut::<GameSettings>() {
+            settings.idle_hint_seconds = seconds;
+        }
+    }
+
+    /// 勝ち筋がなくなったかの確認の設定を変更する
+    ///
+    /// # 引数
+    /// * `unwinnable_check` - 新しい設定
+    pub fn set_unwinnable_check(&mut self, unwinnable_check: UnwinnableCheckSettings) {
+        if let Some(settings) = self.world.get_resource_mut::<GameSettings>() {
+            settings.unwinnable_check = unwinnable_check;
+        }
+    }
+
+    /// 現在の局面にまだ勝ち筋があるかをすぐに確認する
+    ///
+    /// 自動の確認と同じく、初めて勝てないと分かった場合は設定に応じて通知します。
+    ///
+    /// # 戻り値
+    /// 確認結果（ゲームがない場合はNone）
+    pub fn check_winnability(&mut self) -> Option<WinnabilityReport> {
+        let settings = self
+            .world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.unwinnable_check)
+            .unwrap_or_default();
+        Solver::check_game(&mut self.world, self.game_entity?, settings)
+    }
+
+    /// ゲームの各手をソルバーの選ぶ手と比べて振り返る
+    ///
+    /// # 戻り値
+    /// 成功時は振り返りの結果、ゲームがない・シードから盤面を再現できない場合はエラーメッセージ
+    pub fn analyze_game(&self) -> Result<GameAnalysis, String> {
+        let game = self.game_entity.ok_or_else(|| "ゲームがありません".to_string())?;
+        let search_limit = self
+            .world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.unwinnable_check)
+            .unwrap_or_default()
+            .search_limit;
+        GameAnalysis::analyze(&self.world, game, search_limit)
+    }
+
+    /// 盤面のレイアウト（山ごとの位置・ずらす間隔・枚数と各カードの位置）を取得
+    ///
+    /// # 戻り値
+    /// 成功時はレイアウト、ゲームがない・クロンダイク以外の場合はエラーメッセージ
+    pub fn layout(&self) -> Result<BoardLayout, String> {
+        let state = self.game_state().ok_or_else(|| "ゲームがありません".to_string())?;
+        if state.game_type != SolitaireType::Klondike {
+            return Err("レイアウトはクロンダイクのみ対応しています".to_string());
+        }
+        Ok(BoardLayout::from_world(&self.world))
+    }
+
+    /// テーマを変更し、端末内に保存する
+    ///
+    /// テーマは設定（Preferences）の一部として保存されます。
+    /// このセッションの上書きでテーマを指定している場合は、上書きのテーマが優先されます。
+    /// ルームに参加中で他のプレイヤーに見せる設定の場合、カードの裏面は次のフレームで送ります。
+    ///
+    /// # 引数
+    /// * `theme` - 新しいテーマ
+    pub fn set_theme(&mut self, theme: Theme) {
+        let mut preferences = self
+            .world
+            .get_resource::<Preferences>()
+            .cloned()
+            .unwrap_or_default();
+        preferences.theme = theme;
+        if let Err(e) = preferences.save() {
+            warn!("⚠️ テーマの保存失敗: {}", e);
+        }
+        self.apply_preferences(preferences);
+    }
+
+    /// 保存されている設定をこのセッションに適用する
+    ///
+    /// セッションごとの上書きがある項目は上書きの値が優先されます。
+    ///
+    /// # 引数
+    /// * `preferences` - 保存されている設定
+    pub fn apply_preferences(&mut self, preferences: Preferences) {
+        let previous = self.applied_preferences();
+        self.world.insert_resource(preferences);
+        self.sync_preferences(previous);
+    }
+
+    /// このセッションだけの設定の上書きを変更する（保存しない）
+    ///
+    /// # 引数
+    /// * `overrides` - 新しい上書き（指定しなかった項目は保存されている設定を使う）
+    ///
+    /// # 戻り値
+    /// 変更できた場合Ok(())、上書きした値が範囲外の場合はエラーメッセージ
+    pub fn set_preference_overrides(
+        &mut self,
+        overrides: PreferenceOverrides,
+    ) -> Result<(), String> {
+        self.world
+            .get_resource::<Preferences>()
+            .cloned()
+            .unwrap_or_default()
+            .merged(&overrides)
+            .validate()?;
+        let previous = self.applied_preferences();
+        self.world.insert_resource(overrides);
+        self.sync_preferences(previous);
+        Ok(())
+    }
+
+    /// セッションごとの上書きを適用した現在の設定を取得
+    pub fn preferences(&self) -> Preferences {
+        settings::current(&self.world)
+    }
+
+    /// 適用済みの設定（まだ設定を適用していない場合はNone）
+    fn applied_preferences(&self) -> Option<Preferences> {
+        self.world
+            .has_resource::<Preferences>()
+            .then(|| self.preferences())
+    }
+
+    /// 現在の設定をテーマとアニメーションの速さに反映する
+    ///
+    /// アニメーションの速さは設定の値が変わった場合だけ書き換えます
+    /// （set_animation_settings()で変えた速さを、関係のない設定の変更で戻さないため）。
+    ///
+    /// # 引数
+    /// * `previous` - 変更前に適用されていた設定（初めて適用する場合はNone）
+    fn sync_preferences(&mut self, previous: Option<Preferences>) {
+        let preferences = self.preferences();
+        self.world.insert_resource(preferences.theme);
+        let speed_changed = previous
+            .is_none_or(|previous| previous.animation_speed != preferences.animation_speed);
+        if speed_changed {
+            if let Some(settings) = self.world.get_resource_mut::<GameSettings>() {
+                settings.animation.speed_multiplier = preferences.animation_speed;
+            }
+        }
+    }
+
+    /// 指定したティック以降のタイムラインの記録を取得
+    ///
+    /// 取得する前に、まだ記録していないイベントを記録します。
+    ///
+    /// # 引数
+    /// * `since_tick` - このティック以降（このティックを含む）の記録を返す
+    ///
+    /// # 戻り値
+    /// 古い順の記録
+    pub fn timeline(&mut self, since_tick: u64) -> Vec<TimelineEntry> {
+        timeline::record_events(&mut self.world);
+        self.world
+            .get_resource::<TimelineRecorder>()
+            .map(|timeline| timeline.since(since_tick).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// デバッグ用オーバーレイに表示する情報を集める
+    ///
+    /// # 戻り値
+    /// 現在のDebugInfo
+    pub fn debug_info(&self) -> DebugInfo {
+        DebugInfo::collect(&self.world, &self.scheduler)
+    }
+
+    /// クラッシュレポート用に現在のゲームの状態を控える（毎フレームの更新後に呼び出す）
+    ///
+    /// # 引数
+    /// * `session_id` - このランタイムのセッションID
+    pub fn remember_for_crash_report(&self, session_id: &str) {
+        crash_report::remember(session_id, &self.world, self.game_entity);
+    }
+
+    /// メモリの使用状況を集める
+    ///
+    /// # 戻り値
+    /// 現在のMemoryStats
+    pub fn memory_stats(&self) -> MemoryStats {
+        MemoryStats::collect(&self.world)
+    }
+
+    /// ヒントの使用を記録
+    pub fn record_hint_used(&mut self) {
+        if let Some(game_state) = self.game_state_mut() {
+            game_state.hints_used = game_state.hints_used.saturating_add(1);
+        }
+    }
+
+    /// ヒントを探し、動かすカードをしばらく強調表示する
+    ///
+    /// ヒントの使用として記録されます。
+    ///
+    /// # 戻り値
+    /// 打てる手がある場合はSome(ヒント)、ない場合はNone
+    pub fn show_hint(&mut self) -> Option<Hint> {
+        self.record_hint_used();
+        let hint = HintEngine::find_hint(&self.world)?;
+
+        selection::clear_highlights(&mut self.world);
+        if let Some(card) = hint.card {
+            selection::highlight(&mut self.world, card.entity, Some(HINT_HIGHLIGHT_SECONDS));
+        }
+        Some(hint)
+    }
+
+    /// ポインターイベントを入力キューに追加
+    ///
+    /// イベントは次のupdate()でInputSystemが受け付けた順に処理します。
+    /// 観戦・リプレイ中は受け付けません。大きすぎる座標は受け付ける範囲に収めます。
+    ///
+    /// # 引数
+    /// * `pointer` - JavaScriptから転送されたポインターイベント
+    ///
+    /// # 戻り値
+    /// 成功時はOk（観戦・リプレイ中に捨てた場合も含む）、座標が有限でない場合はエラーメッセージ
+    pub fn push_pointer(&mut self, pointer: PointerEvent) -> Result<(), String> {
+        let pointer = pointer.sanitized()?;
+
+        // 観戦・リプレイ中の操作は、自分のゲームに戻ったときにまとめて処理されないよう捨てる
+        if self.view_mode != ViewMode::Play {
+            return Ok(());
+        }
+        if pointer.kind != PointerKind::Move {
+            timeline::record(&mut self.world, TimelineRecord::Pointer { pointer });
+        }
+        input::push_pointer(&mut self.world, pointer);
+        Ok(())
+    }
+
+    /// 盤面の拡大率と表示位置を変更する
+    ///
+    /// 以降のポインターイベントの座標はこの変換で盤面の座標に直されます。
+    ///
+    /// # 引数
+    /// * `viewport` - 新しい表示領域
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.world.insert_resource(viewport);
+    }
+
+    /// カードを選択する（他のカードの選択は外れる）
+    ///
+    /// 選択中のカードを置ける山は次のフレームでドロップ先として計算されます。
+    ///
+    /// # 引数
+    /// * `card_id` - 選択するカードのエンティティID
+    ///
+    /// # 戻り値
+    /// 選択できた場合true、存在しない・動かせないカードの場合false
+    pub fn select_card(&mut self, card_id: u32) -> bool {
+        selection::select(&mut self.world, Entity::new(card_id))
+    }
+
+    /// カードの選択を解除する
+    pub fn clear_selection(&mut self) {
+        selection::clear_selection(&mut self.world);
+    }
+
+    /// ヒントエンジンの最善手を1手だけ打つ（自動プレイ）
+    ///
+    /// 手は通常の操作と同じくスコア・移動履歴に反映され、
+    /// 動いたカードは元の位置から移動先へアニメーションします。
+    ///
+    /// # 戻り値
+    /// 手を打った場合はSome(打った手)、打てる手がない・ゲーム終了済みの場合はNone
+    pub fn auto_play_one_move(&mut self) -> Option<Hint> {
+        if self.game_state()?.is_completed || self.in_tutorial() {
+            return None;
+        }
+
+        let before = self.settle_card_positions();
+        let hint = HintEngine::find_hint(&self.world)?;
+        let applied = HintEngine::apply(&mut self.world, &hint);
+        self.animate_from(&before);
+
+        applied.then_some(hint)
+    }
+
+    /// 手詰まりになるまで自動プレイを続ける
+    ///
+    /// デッキを一巡しても「引く」以外の手が見つからない場合を手詰まりとみなします。
+    /// 打った手は1手ずつアニメーションの順番待ちに登録し、打った順に再生します。
+    ///
+    /// # 戻り値
+    /// 打った手の数
+    pub fn auto_play_until_stuck(&mut self) -> u32 {
+        if self.game_state().is_none_or(|state| state.is_completed) || self.in_tutorial() {
+            return 0;
+        }
+
+        let mut positions = vec![self.settle_card_positions()];
+        let moves_played =
+            HintEngine::play_until_stuck_with(&mut self.world, MAX_AUTO_PLAY_MOVES, |world| {
+                positions.push(card_positions(world));
+            });
+        self.animate_steps(&positions);
+        info!("🤖 自動プレイ: {}手", moves_played);
+        moves_played
+    }
+
+    /// 手順の決まったチュートリアルを進めている最中かチェック（自動プレイは受け付けない）
+    fn in_tutorial(&self) -> bool {
+        self.tutorial_progress()
+            .is_some_and(|progress| !progress.is_completed())
+    }
+
+    /// 進行中のゲームの盤面を新しい盤面に置き換える
+    ///
+    /// # 引数
+    /// * `start` - ワールドに新しい盤面を作成し、ゲーム状態エンティティを返すクロージャ
+    ///
+    /// # 戻り値
+    /// 成功時は新しいゲーム状態エンティティ、盤面が不正な場合はエラーメッセージ
+    fn replace_board(
+        &mut self,
+        start: impl Fn(&mut World) -> Result<Entity, String>,
+    ) -> Result<Entity, String> {
+        // 先に空のワールドで組み立てて検証する（不正な盤面で進行中のゲームを消さないため）
+        start(&mut World::new())?;
+
+        self.clear_board();
+        let entity = start(&mut self.world)?;
+        self.game_entity = Some(entity);
+        Ok(entity)
+    }
+
+    /// 途中のゲームを中断したゲームの一覧に加えてから、盤面を新しい盤面に置き換える
+    ///
+    /// 新しい盤面が不正な場合は、一覧に加えずに進行中のゲームをそのまま残します。
+    fn replace_unfinished_board(
+        &mut self,
+        start: impl Fn(&mut World) -> Result<Entity, String>,
+    ) -> Result<Entity, String> {
+        start(&mut World::new())?;
+        self.shelve_unfinished_game();
+        self.replace_board(start)
+    }
+
+    /// カード・スタック（置き場所を含む）・ゲーム状態のエンティティと、未処理の入力・ドラッグをすべて削除
+    ///
+    /// 削除したエンティティのIDは新しい盤面のカードに使い回されるため、
+    /// 古い盤面のカードを指すドラッグや古い盤面の座標の入力が新しい盤面のカードを動かさないよう、一緒に捨てます。
+    fn clear_board(&mut self) {
+        let entities: Vec<Entity> = self
+            .world
+            .entities()
+            .iter()
+            .copied()
+            .filter(|&entity| {
+                self.world.has_component::<SolitaireCard>(entity)
+                    || self.world.has_component::<CardStack>(entity)
+                    || self.world.has_component::<PilePlaceholder>(entity)
+                    || self.world.has_component::<SolitaireGameState>(entity)
+                    || self.world.has_component::<InputEvent>(entity)
+            })
+            .collect();
+        for entity in entities {
+            self.world.remove_entity(entity);
+        }
+        if let Some(state) = self.world.get_resource_mut::<InputState>() {
+            state.drag = None;
+        }
+        self.game_entity = None;
+    }
+
+    /// 進行中・順番待ちのアニメーションを完了させ、全カードの表示座標を記録
+    ///
+    /// ヒントエンジンは表示座標から盤面の並び順を読み取るため、
+    /// 自動プレイの前にカードを最終位置へ揃えておく必要があります。
+    ///
+    /// # 戻り値
+    /// (エンティティ, 表示X座標, 表示Y座標)のベクター
+    fn settle_card_positions(&mut self) -> Vec<(Entity, f32, f32)> {
+        animation_queue::fast_forward(&mut self.world);
+        let animating: Vec<Entity> = self
+            .world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.is_animating)
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in animating {
+            if let Some(card) = self.world.get_component_mut::<SolitaireCard>(entity) {
+                card.finish_animation();
+            }
+        }
+
+        card_positions(&self.world)
+    }
+
+    /// 配ったカードを少し上から配置先へ落とすアニメーションを始める
+    ///
+    /// 演出のためのアニメーションなので、省略する設定の場合は何もしません。
+    /// 列の中のカードは同じ距離だけずらすため、アニメーション中も縦の並び順は変わりません。
+    fn animate_deal(&mut self) {
+        if !self.animation_settings().plays_decorations() {
+            return;
+        }
+        let before: Vec<(Entity, f32, f32)> = self
+            .world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.location_type == CardLocation::Tableau)
+            .map(|(entity, card)| (entity, card.display_x, card.display_y - DEAL_DROP_DISTANCE))
+            .collect();
+        self.animate_from(&before);
+    }
+
+    /// 移動したカードを記録した座標から現在の座標へアニメーションさせる
+    ///
+    /// 移動は1つのステップとして順番待ちに登録し、先に登録したアニメーションの後に再生します。
+    ///
+    /// # 引数
+    /// * `before` - 移動前に記録した(エンティティ, 表示X座標, 表示Y座標)
+    fn animate_from(&mut self, before: &[(Entity, f32, f32)]) {
+        let after = card_positions(&self.world);
+        self.animate_steps(&[before.to_vec(), after]);
+    }
+
+    /// 1手ごとに記録した座標を順にたどるアニメーションを登録する
+    ///
+    /// 表示座標を最初の座標に戻し、隣り合う記録の間で動いたカードを1手ずつステップとして登録します。
+    ///
+    /// # 引数
+    /// * `positions` - 最初と、各手を打った後に記録した(エンティティ, 表示X座標, 表示Y座標)
+    fn animate_steps(&mut self, positions: &[Vec<(Entity, f32, f32)>]) {
+        let Some(first) = positions.first() else {
+            return;
+        };
+        for &(entity, x, y) in first {
+            if let Some(card) = self.world.get_component_mut::<SolitaireCard>(entity) {
+                card.set_display_position(x, y);
+            }
+        }
+        for pair in positions.windows(2) {
+            let moved: Vec<(Entity, f32, f32)> = pair[1]
+                .iter()
+                .filter(|after| !pair[0].contains(after))
+                .copied()
+                .collect();
+            animation_queue::enqueue(&mut self.world, &moved);
+        }
+    }
+
+    /// 順番待ち・進行中のアニメーションをすべて飛ばし、カードを移動先へすぐに置く
+    ///
+    /// 再接続した後など、溜まった手の再生を待たずに今の盤面を表示したい場合に使います。
+    ///
+    /// # 戻り値
+    /// 飛ばした順番待ちのステップの数
+    pub fn skip_animations(&mut self) -> u64 {
+        let skipped = animation_queue::fast_forward(&mut self.world);
+        self.settle_card_positions();
+        skipped
+    }
+
+    /// 進行中・順番待ちのカードのアニメーションがあるかチェック
+    pub fn is_animating(&self) -> bool {
+        self.world
+            .query::<SolitaireCard>()
+            .any(|(_, card)| card.is_animating)
+    }
+}
+
+/// 全カードの表示座標を記録
+fn card_positions(world: &World) -> Vec<(Entity, f32, f32)> {
+    world
+        .query::<SolitaireCard>()
+        .map(|(entity, card)| (entity, card.display_x, card.display_y))
+        .collect()
+}
+
+impl Default for GameRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,540 @@
+// =============================================================================
+// 進行中のゲームの保存と読み込み
+// =============================================================================
+// このファイルでは、1人用のゲームを中断して後で続きから遊べるよう、
+// 盤面・ゲーム状態・移動の記録をまとめて端末内に保存するSavedGameと、
+// 古い形式の保存データを現在の形式に変換するMigrationRegistryを実装します。
+//
+// 保存データの形式のバージョン（schema_version）：
+// - コンポーネントのフィールドを増やす・名前を変えるなど、保存データの形が
+//   変わる場合はSAVE_SCHEMA_VERSIONを1つ上げる
+// - 1つ前のバージョンから変換する関数をMigrationRegistry::builtin()に登録する
+// - tests/fixtures/save_v<バージョン>.jsonに変更前の保存データをそのまま残し、
+//   tests/save_game.rsで読み込めることを確認する（フィクスチャは書き換えない）
+//
+// 読み込み時は保存データのバージョンから1つずつ順番に変換するため、
+// 何バージョン前の保存データでも読み込めます。
+// 現在より新しいバージョン（新しいアプリで保存したデータ）は読み込みません。
+//
+// バージョンの履歴：
+// - 1：最初の形式（ゲーム状態・盤面・移動の記録・経過時間）
+// - 2：移動の記録に、移動元の裏向きのカードが表向きになったか（revealed）を追加
+//
+// 中断したゲームの一覧：
+// - 1人用の途中のゲームを残したまま別のゲームを始めた場合は、そのゲームを一覧に加える
+//   （保存データはゲームごとに別のキーに置き、一覧には種類・進み具合・経過時間・
+//    盤面の縮小表示に使う状態だけを持つ）
+// - 一覧に残すのはMAX_SAVED_GAMES件までで、超えた分は古いものから消す
+// - 一覧から続きを始めたゲームは一覧から外す
+// =============================================================================
+
+use crate::clock::{unix_time_ms, GameClock};
+use crate::ecs::{Entity, World};
+use crate::scenario::{BoardBuilder, Scenario};
+use crate::solitaire::{MoveLog, MoveRecord, SolitaireGameState, SolitaireType};
+use crate::storage;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+/// 現在の保存データの形式のバージョン
+pub const SAVE_SCHEMA_VERSION: u32 = 2;
+
+/// 保存データの保存キー
+const STORAGE_KEY: &str = "saved_game";
+
+/// 中断したゲームの一覧の保存キー
+const LIST_STORAGE_KEY: &str = "saved_games";
+
+/// 一覧に残す中断したゲームの数の上限
+pub const MAX_SAVED_GAMES: usize = 10;
+
+// =============================================================================
+// 形式の変換（マイグレーション）
+// =============================================================================
+
+/// 保存データを1つ後のバージョンの形に書き換える関数
+///
+/// schema_versionの更新はMigrationRegistryが行うため、変換関数では書き換えません。
+pub type MigrationFn = fn(&mut Value) -> Result<(), String>;
+
+/// 1つのバージョンから次のバージョンへの変換
+#[derive(Debug, Clone, Copy)]
+struct Migration {
+    /// 変換の内容（ログに出力する）
+    description: &'static str,
+
+    /// 変換関数
+    migrate: MigrationFn,
+}
+
+/// 変換元のバージョンごとの変換の一覧
+#[derive(Debug, Clone)]
+pub struct MigrationRegistry {
+    /// 変換後の最終的なバージョン
+    current_version: u32,
+
+    /// 変換元のバージョン → 変換
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl MigrationRegistry {
+    /// 変換を持たない一覧を作成
+    ///
+    /// # 引数
+    /// * `current_version` - 変換後の最終的なバージョン
+    pub fn new(current_version: u32) -> Self {
+        Self {
+            current_version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// 保存データの形式の変換の一覧を取得
+    ///
+    /// # 戻り値
+    /// SAVE_SCHEMA_VERSIONまでの変換を登録した一覧
+    pub fn builtin() -> Self {
+        // 形式を変えた場合は、ここに「変更前のバージョン → 次のバージョン」の変換を追加する
+        Self::new(SAVE_SCHEMA_VERSION).register(
+            1,
+            "移動の記録にrevealedを追加",
+            add_revealed_to_moves,
+        )
+    }
+
+    /// 変換を登録する
+    ///
+    /// # 引数
+    /// * `from_version` - 変換元のバージョン（変換後はfrom_version + 1になる）
+    /// * `description` - 変換の内容
+    /// * `migrate` - 変換関数
+    pub fn register(
+        mut self,
+        from_version: u32,
+        description: &'static str,
+        migrate: MigrationFn,
+    ) -> Self {
+        self.migrations.insert(
+            from_version,
+            Migration {
+                description,
+                migrate,
+            },
+        );
+        self
+    }
+
+    /// 変換後の最終的なバージョンを取得
+    pub fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// 保存データを現在のバージョンまで1つずつ変換する
+    ///
+    /// # 引数
+    /// * `data` - 保存データのJSON（schema_versionを含むオブジェクト）
+    ///
+    /// # 戻り値
+    /// 成功時は現在のバージョンのJSON、バージョンが不明・新しすぎる・変換できない場合はエラーメッセージ
+    pub fn upgrade(&self, mut data: Value) -> Result<Value, String> {
+        let mut version = data
+            .get("schema_version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| "保存データにschema_versionがありません".to_string())?;
+        if version > self.current_version {
+            return Err(format!(
+                "保存データのバージョン{}はこのアプリ（バージョン{}）より新しいため読み込めません",
+                version, self.current_version
+            ));
+        }
+
+        while version < self.current_version {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or_else(|| format!("バージョン{}からの変換がありません", version))?;
+            (migration.migrate)(&mut data)
+                .map_err(|e| format!("バージョン{}からの変換に失敗しました: {}", version, e))?;
+            version += 1;
+            data["schema_version"] = Value::from(version);
+            info!(
+                "💾 保存データをバージョン{}に変換しました: {}",
+                version, migration.description
+            );
+        }
+        Ok(data)
+    }
+}
+
+/// バージョン1 → 2：移動の記録にrevealedを追加する
+///
+/// バージョン1では表向きになったかを記録していないため、すべてfalseにします。
+fn add_revealed_to_moves(data: &mut Value) -> Result<(), String> {
+    let moves = data
+        .get_mut("moves")
+        .and_then(Value::as_array_mut)
+        .ok_or("移動の記録がありません")?;
+    for record in moves {
+        record
+            .as_object_mut()
+            .ok_or("移動の記録の形が不正です")?
+            .entry("revealed")
+            .or_insert(Value::Bool(false));
+    }
+    Ok(())
+}
+
+// =============================================================================
+// 保存データ
+// =============================================================================
+
+/// 中断したゲームの保存データ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedGame {
+    /// 保存データの形式のバージョン
+    pub schema_version: u32,
+
+    /// 保存した時刻（UNIX時刻のミリ秒）
+    pub saved_at: u64,
+
+    /// 保存した時点までの経過時間（秒、止めていた時間を除く）
+    pub elapsed_seconds: u64,
+
+    /// ゲーム状態（スコア・手数・シードなど）
+    pub state: SolitaireGameState,
+
+    /// 盤面
+    pub board: Scenario,
+
+    /// 移動の記録（古い順）
+    pub moves: Vec<MoveRecord>,
+}
+
+impl SavedGame {
+    /// ワールドから進行中のゲームを集める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `game` - ゲーム状態エンティティ
+    ///
+    /// # 戻り値
+    /// 成功時はSavedGame、ゲームがない・クロンダイク以外の場合はエラーメッセージ
+    pub fn capture(world: &World, game: Entity) -> Result<Self, String> {
+        let state = world
+            .get_component::<SolitaireGameState>(game)
+            .ok_or_else(|| "ゲームが始まっていません".to_string())?;
+        if state.game_type != SolitaireType::Klondike {
+            return Err("保存はクロンダイクのみ対応しています".to_string());
+        }
+        let moves = world
+            .get_component::<MoveLog>(game)
+            .map(|log| log.moves.clone())
+            .unwrap_or_default();
+
+        Ok(Self {
+            schema_version: SAVE_SCHEMA_VERSION,
+            saved_at: unix_time_ms() as u64,
+            elapsed_seconds: state.elapsed_seconds(&GameClock::from_world(world)),
+            state: state.clone(),
+            board: Scenario::from_world(world),
+            moves,
+        })
+    }
+
+    /// 保存したゲームの盤面をワールドに作成
+    ///
+    /// 経過時間は保存した時点から数え直します（保存していた間は含めない）。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、盤面が不正な場合はエラーメッセージ
+    pub fn restore(&self, world: &mut World) -> Result<Entity, String> {
+        let game = BoardBuilder::from_scenario(self.board.clone()).build(world)?;
+
+        let mut state = self.state.clone();
+        if state.end_time.is_none() {
+            state.start_time = GameClock::from_world(world)
+                .now_secs()
+                .saturating_sub(self.elapsed_seconds);
+            state.paused_seconds = 0;
+        }
+        world.add_component(game, state);
+        world.add_component(
+            game,
+            MoveLog {
+                moves: self.moves.clone(),
+            },
+        );
+        Ok(game)
+    }
+
+    /// JSON文字列に変換
+    ///
+    /// # 戻り値
+    /// 成功時はJSON文字列、失敗時はエラーメッセージ
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self)
+            .map_err(|e| format!("保存データのシリアライゼーション失敗: {}", e))
+    }
+
+    /// JSON文字列から読み込む（古いバージョンは現在の形式に変換する）
+    ///
+    /// # 引数
+    /// * `json` - 保存データのJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はSavedGame、形式不正・変換できない場合はエラーメッセージ
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Self::from_json_with(json, &MigrationRegistry::builtin())
+    }
+
+    /// 指定した変換の一覧を使ってJSON文字列から読み込む
+    ///
+    /// # 引数
+    /// * `json` - 保存データのJSON文字列
+    /// * `registry` - 変換の一覧
+    ///
+    /// # 戻り値
+    /// 成功時はSavedGame、形式不正・変換できない場合はエラーメッセージ
+    pub fn from_json_with(json: &str, registry: &MigrationRegistry) -> Result<Self, String> {
+        let data: Value =
+            serde_json::from_str(json).map_err(|e| format!("保存データの形式が不正です: {}", e))?;
+        let data = registry.upgrade(data)?;
+        serde_json::from_value(data).map_err(|e| format!("保存データを読み込めません: {}", e))
+    }
+}
+
+/// ゲームを端末内に保存する（前回の保存データは上書きする）
+///
+/// # 引数
+/// * `game` - 保存するゲーム
+///
+/// # 戻り値
+/// 保存成功時Ok(())、失敗時Err
+pub fn save(game: &SavedGame) -> Result<(), String> {
+    storage::save(STORAGE_KEY, &game.to_json()?)
+}
+
+/// 端末内に保存されているゲームを読み込む
+///
+/// # 戻り値
+/// 保存されている場合はSome(SavedGame)、ない・読み込めない場合はNone
+pub fn load() -> Option<SavedGame> {
+    let json = storage::load(STORAGE_KEY)?;
+    SavedGame::from_json(&json)
+        .map_err(|e| warn!("⚠️ 保存されているゲームを読み込めません: {}", e))
+        .ok()
+}
+
+/// 端末内に保存されているゲームを消す
+///
+/// # 戻り値
+/// 消去成功時Ok(())、失敗時Err
+pub fn clear() -> Result<(), String> {
+    storage::remove(STORAGE_KEY)
+}
+
+// =============================================================================
+// 中断したゲームの一覧
+// =============================================================================
+
+/// 一覧に表示する盤面の縮小表示の状態（カードは"AH"のような表記）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SavedGameThumbnail {
+    /// タブロー各列の表向きのカード（下から上の順）
+    pub tableau: Vec<Vec<String>>,
+
+    /// タブロー各列の裏向きのカードの枚数
+    pub face_down: Vec<u32>,
+
+    /// ファウンデーション各組の一番上のカード（空の組はNone）
+    pub foundations: Vec<Option<String>>,
+
+    /// ウェイストの一番上のカード（空の場合はNone）
+    pub waste_top: Option<String>,
+
+    /// デッキに残っているカードの枚数
+    pub deck_count: u32,
+}
+
+impl SavedGameThumbnail {
+    /// 盤面から縮小表示の状態を作成
+    pub fn from_board(board: &Scenario) -> Self {
+        let face_down: Vec<usize> = (0..board.tableau.len())
+            .map(|column| board.face_down.get(column).copied().unwrap_or(0))
+            .collect();
+        Self {
+            tableau: board
+                .tableau
+                .iter()
+                .zip(&face_down)
+                .map(|(cards, &hidden)| cards.iter().skip(hidden).cloned().collect())
+                .collect(),
+            face_down: face_down.iter().map(|&hidden| hidden as u32).collect(),
+            foundations: board
+                .foundations
+                .iter()
+                .map(|cards| cards.last().cloned())
+                .collect(),
+            waste_top: board.waste.last().cloned(),
+            deck_count: board.deck.len() as u32,
+        }
+    }
+}
+
+/// 中断したゲームの一覧の1件（list_saved_games()の戻り値）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SavedGameInfo {
+    /// 一覧の中でゲームを見分けるID（resume_game()・delete_saved_game()に渡す）
+    pub id: String,
+
+    /// ゲームの種類
+    pub variant: SolitaireType,
+
+    /// 進み具合（ファウンデーションに置いたカードの割合、0〜100）
+    pub progress_percent: u8,
+
+    /// 中断した時点までの経過時間（秒）
+    pub elapsed_seconds: u64,
+
+    /// 中断した時刻（UNIX時刻のミリ秒）
+    pub saved_at: u64,
+
+    /// 中断した時点のスコア
+    pub score: u32,
+
+    /// 中断した時点の手数
+    pub move_count: u32,
+
+    /// 盤面の縮小表示の状態
+    pub thumbnail: SavedGameThumbnail,
+}
+
+impl SavedGameInfo {
+    /// 保存データから一覧の1件を作成
+    ///
+    /// # 引数
+    /// * `id` - 一覧の中でのID
+    /// * `game` - 保存データ
+    pub fn describe(id: String, game: &SavedGame) -> Self {
+        let board = &game.board;
+        let placed: usize = board.foundations.iter().map(Vec::len).sum();
+        let total = placed
+            + board.tableau.iter().map(Vec::len).sum::<usize>()
+            + board.waste.len()
+            + board.deck.len();
+        let progress_percent = (placed * 100).checked_div(total).unwrap_or(0) as u8;
+        Self {
+            id,
+            variant: game.state.game_type,
+            progress_percent,
+            elapsed_seconds: game.elapsed_seconds,
+            saved_at: game.saved_at,
+            score: game.state.score,
+            move_count: game.state.move_count,
+            thumbnail: SavedGameThumbnail::from_board(board),
+        }
+    }
+}
+
+/// ゲームごとの保存データの保存キー
+fn list_entry_key(id: &str) -> String {
+    format!("{}.{}", LIST_STORAGE_KEY, id)
+}
+
+/// 中断したゲームの一覧を保存する
+fn store_list(list: &[SavedGameInfo]) -> Result<(), String> {
+    let json = serde_json::to_string(list)
+        .map_err(|e| format!("中断したゲームの一覧のシリアライゼーション失敗: {}", e))?;
+    storage::save(LIST_STORAGE_KEY, &json)
+}
+
+/// 中断したゲームを一覧に加える
+///
+/// 一覧がMAX_SAVED_GAMES件を超えた場合は、古いものから保存データごと消します。
+///
+/// # 引数
+/// * `game` - 中断したゲーム
+///
+/// # 戻り値
+/// 成功時は加えた一覧の1件、保存に失敗した場合はエラーメッセージ
+pub fn add_to_list(game: &SavedGame) -> Result<SavedGameInfo, String> {
+    let mut list = list();
+    let base_id = format!("{:x}-{:x}", game.saved_at, game.state.seed);
+    let id = (1..)
+        .map(|number| match number {
+            1 => base_id.clone(),
+            _ => format!("{}-{}", base_id, number),
+        })
+        .find(|id| list.iter().all(|entry| &entry.id != id))
+        .unwrap_or(base_id);
+
+    storage::save(&list_entry_key(&id), &game.to_json()?)?;
+    let info = SavedGameInfo::describe(id, game);
+    list.insert(0, info.clone());
+    for removed in list.split_off(list.len().min(MAX_SAVED_GAMES)) {
+        info!("🗑️ 古い中断したゲームを一覧から消しました: {}", removed.id);
+        if let Err(e) = storage::remove(&list_entry_key(&removed.id)) {
+            warn!("⚠️ 中断したゲームの保存データを消せません: {}", e);
+        }
+    }
+    store_list(&list)?;
+    Ok(info)
+}
+
+/// 中断したゲームの一覧（新しい順）
+///
+/// # 戻り値
+/// 一覧（保存されていない・読み込めない場合は空）
+pub fn list() -> Vec<SavedGameInfo> {
+    storage::load(LIST_STORAGE_KEY)
+        .and_then(|json| {
+            serde_json::from_str(&json)
+                .map_err(|e| warn!("⚠️ 中断したゲームの一覧を読み込めません: {}", e))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// 一覧にある中断したゲームを読み込む（古いバージョンは現在の形式に変換する）
+///
+/// # 引数
+/// * `id` - 一覧の中でのID
+///
+/// # 戻り値
+/// 成功時はSavedGame、一覧にない・読み込めない場合はエラーメッセージ
+pub fn load_from_list(id: &str) -> Result<SavedGame, String> {
+    if list().iter().all(|entry| entry.id != id) {
+        return Err(format!("中断したゲームが見つかりません: {}", id));
+    }
+    let json = storage::load(&list_entry_key(id))
+        .ok_or_else(|| format!("中断したゲームの保存データがありません: {}", id))?;
+    SavedGame::from_json(&json)
+}
+
+/// 中断したゲームを一覧から保存データごと消す
+///
+/// # 引数
+/// * `id` - 一覧の中でのID
+///
+/// # 戻り値
+/// 消した場合Ok(true)、一覧にない場合Ok(false)、保存に失敗した場合はエラーメッセージ
+pub fn remove_from_list(id: &str) -> Result<bool, String> {
+    let mut list = list();
+    let before = list.len();
+    list.retain(|entry| entry.id != id);
+    if list.len() == before {
+        return Ok(false);
+    }
+    storage::remove(&list_entry_key(id))?;
+    store_list(&list)?;
+    Ok(true)
+}
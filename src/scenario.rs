@@ -0,0 +1,556 @@
+// =============================================================================
+// 盤面シナリオ
+// =============================================================================
+// このファイルでは、任意の途中盤面（どのカードがどの場所にあるか）を
+// 直接組み立てるためのシナリオ形式（JSON）とBoardBuilderを実装します。
+// 配り札を再生しなくても決まった局面を作れるため、次の用途に使います。
+//
+// 主要な用途：
+// - テストで特定の局面を用意する
+// - パズル（今日の一問など）の問題盤面
+// - 不具合の再現（現在の盤面をシナリオとして書き出して読み込む）
+//
+// シナリオのJSON形式（クロンダイク）：
+// {
+//   "tableau": [["KS", "QH"], ["5D"], ...],   // 各列のカード（下から上の順、最大7列）
+//   "face_down": [1, 0, ...],                 // 各列の下から何枚が裏向きか（省略時は0）
+//   "foundations": [["AH", "2H"], [], ...],   // 各組のカード（Aから順、最大4組）
+//   "waste": ["3C"],                          // ウェイストのカード（下から上の順）
+//   "deck": ["4D", "9S"]                      // デッキのカード（最後のカードから引かれる）
+// }
+//
+// カードは「ランク + スート」で表記します（例："AS"、"10H"、"QD"、"K♣"）。
+// スートはS/H/D/C（小文字も可）または記号（♠♥♦♣）で指定できます。
+//
+// 不具合の報告に添付する場合は、JSONより短い表記（Scenario::to_compact()）も使えます。
+// 手順の書き出し（ソルバーの勝ち筋など）には、1手ずつの短い表記（move_notation()）を使います。
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Entity, World};
+use crate::layout;
+use crate::solitaire::{
+    CardLocation, CardRank, CardSuit, MoveLog, MoveRecord, SolitaireCard, SolitaireGameState,
+    SolitaireManager, SolitaireType,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// クロンダイクのタブロー列数
+const TABLEAU_COLUMNS: usize = 7;
+
+/// ファウンデーションの数
+const FOUNDATION_COUNT: usize = 4;
+
+/// 受け付けるシナリオJSONの最大サイズ（バイト）
+const MAX_SCENARIO_BYTES: usize = 16 * 1024;
+
+// =============================================================================
+// シナリオの定義
+// =============================================================================
+
+/// 盤面シナリオ（クロンダイク）
+///
+/// 各場所に置くカードを表記文字列で持ちます。
+/// 52枚すべてを置く必要はなく、置かなかったカードは盤面に存在しません。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scenario {
+    /// タブロー各列のカード（下から上の順）
+    #[serde(default)]
+    pub tableau: Vec<Vec<String>>,
+
+    /// タブロー各列の下から何枚が裏向きか（省略された列は0）
+    #[serde(default)]
+    pub face_down: Vec<usize>,
+
+    /// ファウンデーション各組のカード（Aから順）
+    #[serde(default)]
+    pub foundations: Vec<Vec<String>>,
+
+    /// ウェイストのカード（下から上の順）
+    #[serde(default)]
+    pub waste: Vec<String>,
+
+    /// デッキのカード（最後のカードから引かれる）
+    #[serde(default)]
+    pub deck: Vec<String>,
+}
+
+impl Scenario {
+    /// シナリオのJSONを解析
+    ///
+    /// # 引数
+    /// * `json` - シナリオのJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はScenario、形式不正の場合はエラーメッセージ
+    pub fn parse(json: &str) -> Result<Self, String> {
+        if json.len() > MAX_SCENARIO_BYTES {
+            return Err(format!("シナリオが大きすぎます（{}バイト）", json.len()));
+        }
+        serde_json::from_str(json).map_err(|e| format!("シナリオの形式が不正です: {}", e))
+    }
+
+    /// ワールドの現在の盤面をシナリオとして書き出す
+    ///
+    /// 不具合の再現用に、その時点の局面を保存するのに使います。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    ///
+    /// # 戻り値
+    /// 現在の盤面を表すシナリオ
+    pub fn from_world(world: &World) -> Self {
+        let mut tableau: Vec<Vec<&SolitaireCard>> = vec![Vec::new(); TABLEAU_COLUMNS];
+        let mut foundations: Vec<Vec<&SolitaireCard>> = vec![Vec::new(); FOUNDATION_COUNT];
+        let mut waste = Vec::new();
+        let mut deck = Vec::new();
+
+        for (_, card) in world.query::<SolitaireCard>() {
+            let index = card.position_in_location as usize;
+            match card.location_type {
+                CardLocation::Tableau if index < TABLEAU_COLUMNS => tableau[index].push(card),
+                CardLocation::Foundation if index < FOUNDATION_COUNT => {
+                    foundations[index].push(card)
+                }
+                CardLocation::Waste => waste.push(card),
+                CardLocation::Deck => deck.push(card),
+                _ => {}
+            }
+        }
+
+        // 場所ごとに積まれている順へ並べる
+        for column in &mut tableau {
+            column.sort_by(|a, b| a.display_y.total_cmp(&b.display_y));
+        }
+        for foundation in &mut foundations {
+            foundation.sort_by_key(|card| card.rank);
+        }
+        waste.sort_by_key(|card| card.position_in_location);
+        deck.sort_by_key(|card| card.position_in_location);
+
+        let codes = |cards: &[&SolitaireCard]| -> Vec<String> {
+            cards
+                .iter()
+                .map(|card| card_code(card.suit, card.rank))
+                .collect()
+        };
+
+        Self {
+            face_down: tableau
+                .iter()
+                .map(|column| column.iter().filter(|card| !card.is_face_up).count())
+                .collect(),
+            tableau: tableau.iter().map(|column| codes(column)).collect(),
+            foundations: foundations
+                .iter()
+                .map(|foundation| codes(foundation))
+                .collect(),
+            waste: codes(&waste),
+            deck: codes(&deck),
+        }
+    }
+
+    /// シナリオを短い文字列に書き出す
+    ///
+    /// JSONより短いため、不具合の報告に盤面を添付するのに使います。
+    ///
+    /// 形式："<タブロー>/<組札>/<ウェイスト>/<デッキ>"
+    /// - タブローは列を","で区切り、各列は"<裏向きの枚数>:<カード>"
+    /// - 組札は組を","で区切る
+    /// - カードは2文字ずつ続けて書く（10は"T"）
+    ///
+    /// 例："1:KSQH,0:5D/AH2H,,,/3C/4D9S"
+    ///
+    /// # 戻り値
+    /// 成功時は書き出した文字列、カードの表記が不正な場合はエラーメッセージ
+    pub fn to_compact(&self) -> Result<String, String> {
+        let tableau = self
+            .tableau
+            .iter()
+            .enumerate()
+            .map(|(column, cards)| {
+                let face_down = self.face_down.get(column).copied().unwrap_or(0);
+                Ok(format!("{}:{}", face_down, compact_cards(cards)?))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let foundations = self
+            .foundations
+            .iter()
+            .map(|cards| compact_cards(cards))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(format!(
+            "{}/{}/{}/{}",
+            tableau.join(","),
+            foundations.join(","),
+            compact_cards(&self.waste)?,
+            compact_cards(&self.deck)?
+        ))
+    }
+
+    /// to_compact()で書き出した文字列を読み込む
+    ///
+    /// # 引数
+    /// * `text` - 書き出した文字列
+    ///
+    /// # 戻り値
+    /// 成功時はScenario、形式不正の場合はエラーメッセージ
+    pub fn from_compact(text: &str) -> Result<Self, String> {
+        if text.len() > MAX_SCENARIO_BYTES {
+            return Err(format!("シナリオが大きすぎます（{}バイト）", text.len()));
+        }
+        let sections: Vec<&str> = text.split('/').collect();
+        let [tableau, foundations, waste, deck] = sections[..] else {
+            return Err(format!(
+                "シナリオの区切り（/）が{}個あります（3個必要）",
+                sections.len() - 1
+            ));
+        };
+
+        let mut scenario = Self::default();
+        for column in tableau.split(',').filter(|column| !column.is_empty()) {
+            let (face_down, cards) = column
+                .split_once(':')
+                .ok_or_else(|| format!("タブローの列に裏向きの枚数がありません: {}", column))?;
+            scenario.face_down.push(
+                face_down
+                    .parse()
+                    .map_err(|_| format!("裏向きの枚数が不正です: {}", face_down))?,
+            );
+            scenario.tableau.push(expand_cards(cards)?);
+        }
+        if !foundations.is_empty() {
+            scenario.foundations = foundations
+                .split(',')
+                .map(expand_cards)
+                .collect::<Result<_, _>>()?;
+        }
+        scenario.waste = expand_cards(waste)?;
+        scenario.deck = expand_cards(deck)?;
+        Ok(scenario)
+    }
+}
+
+// =============================================================================
+// 盤面の組み立て
+// =============================================================================
+
+/// 盤面ビルダー
+///
+/// シナリオを組み立て、ワールドにその盤面のゲームを作成します。
+///
+/// 使用例：
+/// ```rust
+/// let game_entity = BoardBuilder::new()
+///     .tableau(0, 1, &["7S", "KH"])
+///     .foundation(0, &["AH", "2H"])
+///     .deck(&["3H"])
+///     .build(&mut world)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BoardBuilder {
+    /// 組み立て中のシナリオ
+    scenario: Scenario,
+}
+
+impl BoardBuilder {
+    /// 空の盤面から始めるビルダーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// シナリオから始めるビルダーを作成
+    ///
+    /// # 引数
+    /// * `scenario` - 元にするシナリオ
+    pub fn from_scenario(scenario: Scenario) -> Self {
+        Self { scenario }
+    }
+
+    /// シナリオのJSONから始めるビルダーを作成
+    ///
+    /// # 引数
+    /// * `json` - シナリオのJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はBoardBuilder、形式不正の場合はエラーメッセージ
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        Scenario::parse(json).map(Self::from_scenario)
+    }
+
+    /// タブローの列にカードを置く（既にある場合は置き換える）
+    ///
+    /// # 引数
+    /// * `column` - 列番号（0から開始）
+    /// * `face_down` - 下から何枚を裏向きにするか
+    /// * `cards` - カードの表記（下から上の順）
+    pub fn tableau(mut self, column: usize, face_down: usize, cards: &[&str]) -> Self {
+        if self.scenario.tableau.len() <= column {
+            self.scenario.tableau.resize(column + 1, Vec::new());
+        }
+        if self.scenario.face_down.len() <= column {
+            self.scenario.face_down.resize(column + 1, 0);
+        }
+        self.scenario.tableau[column] = to_codes(cards);
+        self.scenario.face_down[column] = face_down;
+        self
+    }
+
+    /// ファウンデーションにカードを置く（既にある場合は置き換える）
+    ///
+    /// # 引数
+    /// * `index` - ファウンデーション番号（0から開始）
+    /// * `cards` - カードの表記（Aから順）
+    pub fn foundation(mut self, index: usize, cards: &[&str]) -> Self {
+        if self.scenario.foundations.len() <= index {
+            self.scenario.foundations.resize(index + 1, Vec::new());
+        }
+        self.scenario.foundations[index] = to_codes(cards);
+        self
+    }
+
+    /// ウェイストにカードを置く（下から上の順）
+    pub fn waste(mut self, cards: &[&str]) -> Self {
+        self.scenario.waste = to_codes(cards);
+        self
+    }
+
+    /// デッキにカードを置く（最後のカードから引かれる）
+    pub fn deck(mut self, cards: &[&str]) -> Self {
+        self.scenario.deck = to_codes(cards);
+        self
+    }
+
+    /// 組み立て中のシナリオを取得
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    /// シナリオを検証し、ワールドにその盤面のゲームを作成
+    ///
+    /// 同じカードの重複、列数・組数の超過、ファウンデーションの並び
+    /// （同じスートでAから1つずつ）を検証します。
+    /// 検証に失敗した場合、ワールドは変更されません。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、シナリオが不正な場合はエラーメッセージ
+    pub fn build(&self, world: &mut World) -> Result<Entity, String> {
+        let cards = self.layout()?;
+
+        let game_entity = world.create_entity();
+        let game_state =
+            SolitaireGameState::new(SolitaireType::Klondike, &GameClock::from_world(world));
+        world.add_component(game_entity, game_state);
+        world.add_component(game_entity, MoveLog::default());
+        world.spawn_batch(cards);
+        SolitaireManager::create_stacks(world, SolitaireType::Klondike);
+
+        info!(
+            "🧩 シナリオから盤面を作成しました: タブロー{}枚, ファウンデーション{}枚, ウェイスト{}枚, デッキ{}枚",
+            self.scenario.tableau.iter().map(Vec::len).sum::<usize>(),
+            self.scenario.foundations.iter().map(Vec::len).sum::<usize>(),
+            self.scenario.waste.len(),
+            self.scenario.deck.len()
+        );
+        Ok(game_entity)
+    }
+
+    /// シナリオを検証し、配置済みのカードを作成
+    fn layout(&self) -> Result<Vec<SolitaireCard>, String> {
+        let scenario = &self.scenario;
+        if scenario.tableau.len() > TABLEAU_COLUMNS {
+            return Err(format!("タブローは{}列までです", TABLEAU_COLUMNS));
+        }
+        if scenario.foundations.len() > FOUNDATION_COUNT {
+            return Err(format!(
+                "ファウンデーションは{}組までです",
+                FOUNDATION_COUNT
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        let mut parse = |code: &String| -> Result<SolitaireCard, String> {
+            let (suit, rank) = parse_card(code)?;
+            if !seen.insert((suit as u8, rank as u8)) {
+                return Err(format!("カードが重複しています: {}", code));
+            }
+            Ok(SolitaireCard::new(suit, rank))
+        };
+        let mut cards = Vec::new();
+
+        // タブロー（下から25pxずつずらして積む）
+        for (column, codes) in scenario.tableau.iter().enumerate() {
+            let face_down = scenario.face_down.get(column).copied().unwrap_or(0);
+            if face_down > codes.len() {
+                return Err(format!(
+                    "タブロー{}の裏向きの枚数がカードの枚数を超えています",
+                    column + 1
+                ));
+            }
+            for (row, code) in codes.iter().enumerate() {
+                let mut card = parse(code)?;
+                card.set_location(CardLocation::Tableau, column as u32);
+                let (x, y) = layout::tableau_position(column as u32, row);
+                card.set_display_position(x, y);
+                if row < face_down {
+                    card.flip_down();
+                } else {
+                    card.flip_up();
+                }
+                cards.push(card);
+            }
+        }
+
+        // ファウンデーション（同じスートでAから1つずつ）
+        for (index, codes) in scenario.foundations.iter().enumerate() {
+            let mut previous: Option<SolitaireCard> = None;
+            for code in codes {
+                let mut card = parse(code)?;
+                if !card.can_place_on_foundation(previous.as_ref()) {
+                    return Err(format!(
+                        "ファウンデーション{}に{}は置けません",
+                        index + 1,
+                        code
+                    ));
+                }
+                card.set_location(CardLocation::Foundation, index as u32);
+                let (x, y) = layout::foundation_position(index as u32);
+                card.set_display_position(x, y);
+                card.flip_up();
+                previous = Some(card.clone());
+                cards.push(card);
+            }
+        }
+
+        // ウェイスト（積んだ順番を位置として持つ）
+        for (position, code) in scenario.waste.iter().enumerate() {
+            let mut card = parse(code)?;
+            card.set_location(CardLocation::Waste, position as u32);
+            card.set_display_position(layout::WASTE_POSITION.0, layout::WASTE_POSITION.1);
+            card.flip_up();
+            cards.push(card);
+        }
+
+        // デッキ（位置が大きいカードから引かれる）
+        for (position, code) in scenario.deck.iter().enumerate() {
+            let mut card = parse(code)?;
+            card.set_location(CardLocation::Deck, position as u32);
+            card.set_display_position(layout::DECK_POSITION.0, layout::DECK_POSITION.1);
+            card.flip_down();
+            cards.push(card);
+        }
+
+        Ok(cards)
+    }
+}
+
+// =============================================================================
+// カードの表記
+// =============================================================================
+
+/// カードの表記を解析
+///
+/// # 引数
+/// * `code` - カードの表記（例："AS"、"10H"、"Q♦"）
+///
+/// # 戻り値
+/// 成功時はスートとランク、不正な表記の場合はエラーメッセージ
+pub fn parse_card(code: &str) -> Result<(CardSuit, CardRank), String> {
+    let code = code.trim();
+    let mut chars = code.chars();
+    let suit_char = chars
+        .next_back()
+        .ok_or_else(|| "カードの表記が空です".to_string())?;
+    // 10は"T"とも書ける
+    let rank_text = match chars.as_str() {
+        "T" | "t" => "10",
+        text => text,
+    };
+
+    let suit = match suit_char {
+        'S' | 's' | '♠' => CardSuit::Spades,
+        'H' | 'h' | '♥' => CardSuit::Hearts,
+        'D' | 'd' | '♦' => CardSuit::Diamonds,
+        'C' | 'c' | '♣' => CardSuit::Clubs,
+        _ => return Err(format!("不明なスートです: {}", code)),
+    };
+    let rank = CardRank::all()
+        .into_iter()
+        .find(|rank| rank.display().eq_ignore_ascii_case(rank_text))
+        .ok_or_else(|| format!("不明なランクです: {}", code))?;
+
+    Ok((suit, rank))
+}
+
+/// カードの表記を作成（例：スペードのA → "AS"）
+///
+/// # 引数
+/// * `suit` - スート
+/// * `rank` - ランク
+pub fn card_code(suit: CardSuit, rank: CardRank) -> String {
+    let suit = match suit {
+        CardSuit::Spades => 'S',
+        CardSuit::Hearts => 'H',
+        CardSuit::Diamonds => 'D',
+        CardSuit::Clubs => 'C',
+    };
+    format!("{}{}", rank.display(), suit)
+}
+
+/// 移動記録の1手を短い表記にする（例：♥Aをタブロー3から組札1へ → "AH t3-f1"）
+///
+/// 場所はデッキ"d"、ウェイスト"w"、組札"f1"〜、タブロー"t1"〜、フリーセル"c1"〜で表し、
+/// デッキから引く手は引いたカードで"5D d-w"のように書きます。カードの10は"T"です。
+///
+/// # 引数
+/// * `record` - 移動記録
+pub fn move_notation(record: &MoveRecord) -> String {
+    let place = |location: CardLocation, index: u32| match location {
+        CardLocation::Deck => "d".to_string(),
+        CardLocation::Waste => "w".to_string(),
+        CardLocation::Foundation => format!("f{}", index + 1),
+        CardLocation::Tableau => format!("t{}", index + 1),
+        CardLocation::FreeCell => format!("c{}", index + 1),
+        CardLocation::Hand => "h".to_string(),
+    };
+    format!(
+        "{} {}-{}",
+        card_code(record.suit, record.rank).replace("10", "T"),
+        place(record.from, record.from_index),
+        place(record.to, record.to_index)
+    )
+}
+
+/// カードの表記を2文字ずつの短い表記にして続ける（10は"T"）
+fn compact_cards(codes: &[String]) -> Result<String, String> {
+    codes
+        .iter()
+        .map(|code| {
+            let (suit, rank) = parse_card(code)?;
+            Ok(card_code(suit, rank).replace("10", "T"))
+        })
+        .collect()
+}
+
+/// 2文字ずつの短い表記をカードの表記に戻す
+fn expand_cards(text: &str) -> Result<Vec<String>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(2)
+        .map(|chunk| {
+            let code: String = chunk.iter().collect();
+            let (suit, rank) = parse_card(&code)?;
+            Ok(card_code(suit, rank))
+        })
+        .collect()
+}
+
+/// 文字列スライスを所有する文字列に変換
+fn to_codes(cards: &[&str]) -> Vec<String> {
+    cards.iter().map(|code| code.to_string()).collect()
+}
@@ -0,0 +1,100 @@
+// =============================================================================
+// 場面ごとのシステムのスケジュール
+// =============================================================================
+// このファイルでは、メニュー・プレイ中・リプレイ・観戦の場面ごとに、
+// どのシステムを実行するかを切り替えるスケジュールを定義します。
+//
+// 仕組み：
+// - システムはSystemScheduler::add_system_to()で実行するスケジュールを指定して登録する
+//   （指定しないシステムはすべてのスケジュールで実行する）
+// - GameRuntimeは毎フレーム、ゲームの進行段階（GamePhase）と見ているもの（ViewMode）から
+//   スケジュールを選び、変わったときだけ切り替える
+// - 入力・選択・移動と、スコア・勝敗・実績の判定はプレイ中だけ実行する。
+//   観戦・リプレイ中は盤面を外から動かすため、操作を受け付けたりスコアを数え直したりしない
+// - アニメーション・通知・通信はどの場面でも実行する
+// =============================================================================
+
+use crate::client_state::GamePhase;
+use serde::{Deserialize, Serialize};
+
+/// 場面ごとのシステムのスケジュール
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Schedule {
+    /// メニュー（ゲーム開始前）
+    MainMenu,
+
+    /// プレイ中（ゲーム終了後の結果画面を含む）
+    Playing,
+
+    /// 記録した手のリプレイ
+    Replay,
+
+    /// 他のプレイヤーのゲームの観戦
+    Spectating,
+}
+
+/// プレイ中だけ実行するシステムのスケジュール
+pub const PLAYING_ONLY: &[&str] = &[Schedule::Playing.as_str()];
+
+impl Schedule {
+    /// スケジュールの名前を取得
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Schedule::MainMenu => "main_menu",
+            Schedule::Playing => "playing",
+            Schedule::Replay => "replay",
+            Schedule::Spectating => "spectating",
+        }
+    }
+
+    /// ゲームの進行段階と見ているものからスケジュールを選ぶ
+    ///
+    /// # 引数
+    /// * `phase` - ゲームの進行段階
+    /// * `view` - 見ているもの
+    ///
+    /// # 戻り値
+    /// 実行するスケジュール
+    pub fn select(phase: GamePhase, view: ViewMode) -> Self {
+        match (phase, view) {
+            (GamePhase::NotStarted, _) => Schedule::MainMenu,
+            (_, ViewMode::Play) => Schedule::Playing,
+            (_, ViewMode::Replay) => Schedule::Replay,
+            (_, ViewMode::Spectate) => Schedule::Spectating,
+        }
+    }
+}
+
+/// 見ているもの（自分のゲーム・リプレイ・観戦）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    /// 自分のゲームをプレイしている
+    #[default]
+    Play,
+
+    /// 記録した手をリプレイしている
+    Replay,
+
+    /// 他のプレイヤーのゲームを観戦している
+    Spectate,
+}
+
+impl ViewMode {
+    /// 名前から見ているものを取得
+    ///
+    /// # 引数
+    /// * `name` - "play" / "replay" / "spectate"
+    ///
+    /// # 戻り値
+    /// 成功時はViewMode、不明な名前の場合はエラーメッセージ
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "play" => Ok(ViewMode::Play),
+            "replay" => Ok(ViewMode::Replay),
+            "spectate" => Ok(ViewMode::Spectate),
+            _ => Err(format!("不明な表示モードです: {}", name)),
+        }
+    }
+}
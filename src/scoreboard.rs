@@ -0,0 +1,276 @@
+// =============================================================================
+// 対戦相手のスコアボード
+// =============================================================================
+// このファイルでは、マルチプレイで同じルームにいる他のプレイヤーの表示名・色・スコア・
+// ファウンデーションの進み具合・接続状態を、クライアントのワールドに
+// RemotePlayerコンポーネントとして保持します。
+//
+// 仕組み：
+// - ルームに参加すると、サーバーから他の参加者の最新のスコア（Scoreboard）が届く
+// - その後に参加したプレイヤーはPlayerProfileで、スコアの変化はScoreUpdateで届く
+// - カードの裏面はCardBackChangedで届く（見せていないプレイヤーはNone）
+// - コンボを数えるルームのレースでは、コンボ・倍率・ボーナスがComboUpdateで届く
+// - 離席の判定を有効にしたルームでは、離席中になった・戻ったことがPlayerAfkで届く
+// - 表示名・色の変更（PlayerUpdated）も反映し、切断したプレイヤーは切断中として残す
+// - 退室・キックされたプレイヤーは消し、自分が退室した場合はすべて消す
+// - JavaScript側はget_players()でスコアの高い順の一覧を取得し、スコアボードを描画する
+// =============================================================================
+
+use crate::ecs::{Component, Entity, World};
+use crate::protocol::{PlayerProfile, ScoreboardEntry, WebSocketMessage};
+use crate::theme::CardBack;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// 他のプレイヤーの接続状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteConnection {
+    /// 接続中
+    Connected,
+
+    /// 切断した（接続し直すとプレイヤーIDが変わるため、戻ってきても別のプレイヤーになる）
+    Disconnected,
+}
+
+/// 同じルームにいる他のプレイヤー
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RemotePlayer {
+    /// プレイヤーID
+    pub player_id: String,
+
+    /// 表示名
+    pub player_name: String,
+
+    /// カーソルの色のインデックス
+    pub color_index: u8,
+
+    /// 現在のスコア
+    pub score: u32,
+
+    /// ファウンデーションに置いたカードの枚数
+    pub foundation_cards: u16,
+
+    /// 見せているカードの裏面（見せていない場合はNone）
+    pub card_back: Option<CardBack>,
+
+    /// 続けてファウンデーションに置いている枚数（途切れている場合は0）
+    pub combo: u32,
+
+    /// コンボの倍率
+    pub multiplier: u32,
+
+    /// このレースで貯まったコンボのボーナス
+    pub bonus_score: u32,
+
+    /// 離席中かどうか
+    pub afk: bool,
+
+    /// 接続状態
+    pub connection: RemoteConnection,
+}
+
+impl Component for RemotePlayer {}
+
+impl RemotePlayer {
+    /// まだスコアが届いていないプレイヤーを作成
+    ///
+    /// # 引数
+    /// * `player_id` - プレイヤーID
+    pub fn new(player_id: &str) -> Self {
+        Self {
+            player_id: player_id.to_string(),
+            player_name: String::new(),
+            color_index: 0,
+            score: 0,
+            foundation_cards: 0,
+            card_back: None,
+            combo: 0,
+            multiplier: 1,
+            bonus_score: 0,
+            afk: false,
+            connection: RemoteConnection::Connected,
+        }
+    }
+}
+
+/// 届いたメッセージをスコアボードに反映する
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `own_player_id` - 自分のプレイヤーID（自分は一覧に含めない）
+/// * `message` - サーバーから届いたメッセージ
+///
+/// # 戻り値
+/// スコアボードが変わった場合true
+pub fn apply(world: &mut World, own_player_id: Option<&str>, message: &WebSocketMessage) -> bool {
+    let is_own = |player_id: &str| own_player_id == Some(player_id);
+    match message {
+        WebSocketMessage::Scoreboard { players, .. } => {
+            for entry in players.iter().filter(|entry| !is_own(&entry.player_id)) {
+                set_entry(world, entry);
+            }
+            true
+        }
+        WebSocketMessage::PlayerProfile { profile, .. } if !is_own(&profile.player_id) => {
+            set_profile(world, profile);
+            true
+        }
+        WebSocketMessage::ScoreUpdate {
+            player_id,
+            score,
+            foundation_cards,
+            ..
+        } if !is_own(player_id) => {
+            let player = upsert(world, player_id);
+            player.score = *score;
+            player.foundation_cards = *foundation_cards;
+            true
+        }
+        WebSocketMessage::CardBackChanged {
+            player_id,
+            card_back,
+            ..
+        } if !is_own(player_id) => {
+            upsert(world, player_id).card_back = *card_back;
+            true
+        }
+        WebSocketMessage::ComboUpdate {
+            player_id,
+            combo,
+            multiplier,
+            bonus_score,
+            ..
+        } if !is_own(player_id) => {
+            let player = upsert(world, player_id);
+            player.combo = *combo;
+            player.multiplier = *multiplier;
+            player.bonus_score = *bonus_score;
+            true
+        }
+        WebSocketMessage::PlayerAfk { player_id, afk, .. } if !is_own(player_id) => {
+            upsert(world, player_id).afk = *afk;
+            true
+        }
+        WebSocketMessage::PlayerUpdated {
+            player_id,
+            player_name,
+            color_index,
+        } => match find_mut(world, player_id) {
+            Some(player) => {
+                player.player_name = player_name.clone();
+                player.color_index = *color_index;
+                true
+            }
+            None => false,
+        },
+        WebSocketMessage::PlayerLeft { player_id, .. } => match find_mut(world, player_id) {
+            Some(player) => {
+                debug!("📴 スコアボード: 切断 {}", player_id);
+                player.connection = RemoteConnection::Disconnected;
+                true
+            }
+            None => false,
+        },
+        WebSocketMessage::LeaveRoom { player_id, .. }
+        | WebSocketMessage::Kicked { player_id, .. } => {
+            if is_own(player_id) {
+                clear(world);
+                true
+            } else {
+                remove(world, player_id)
+            }
+        }
+        _ => false,
+    }
+}
+
+/// スコアボードの一覧（スコアの高い順、同点はプレイヤーID順）
+pub fn players(world: &World) -> Vec<RemotePlayer> {
+    let mut players: Vec<RemotePlayer> = world
+        .query::<RemotePlayer>()
+        .map(|(_, player)| player.clone())
+        .collect();
+    players.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.player_id.cmp(&b.player_id))
+    });
+    players
+}
+
+/// スコアボードを空にする（ルームから出た場合・接続が切れた場合）
+pub fn clear(world: &mut World) {
+    let entities: Vec<Entity> = world
+        .query::<RemotePlayer>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        world.remove_entity(entity);
+    }
+}
+
+/// スコアの一覧の1人分を反映する
+fn set_entry(world: &mut World, entry: &ScoreboardEntry) {
+    let player = upsert(world, &entry.player_id);
+    player.player_name = entry.player_name.clone();
+    player.color_index = entry.color_index;
+    player.score = entry.score;
+    player.foundation_cards = entry.foundation_cards;
+    player.card_back = entry.card_back;
+    player.connection = RemoteConnection::Connected;
+}
+
+/// プロフィールの表示名・色を反映する
+fn set_profile(world: &mut World, profile: &PlayerProfile) {
+    let player = upsert(world, &profile.player_id);
+    player.player_name = profile.player_name.clone();
+    player.color_index = profile.color_index;
+    player.connection = RemoteConnection::Connected;
+}
+
+/// プレイヤーを一覧から消す
+///
+/// # 戻り値
+/// 一覧にいた場合true
+fn remove(world: &mut World, player_id: &str) -> bool {
+    match find(world, player_id) {
+        Some(entity) => {
+            world.remove_entity(entity);
+            true
+        }
+        None => false,
+    }
+}
+
+/// プレイヤーのエンティティを探す
+fn find(world: &World, player_id: &str) -> Option<Entity> {
+    world
+        .query::<RemotePlayer>()
+        .find(|(_, player)| player.player_id == player_id)
+        .map(|(entity, _)| entity)
+}
+
+/// プレイヤーを探す（可変参照）
+fn find_mut<'a>(world: &'a mut World, player_id: &str) -> Option<&'a mut RemotePlayer> {
+    let entity = find(world, player_id)?;
+    world.get_component_mut::<RemotePlayer>(entity)
+}
+
+/// プレイヤーを探し、一覧にいなければ追加する
+fn upsert<'a>(world: &'a mut World, player_id: &str) -> &'a mut RemotePlayer {
+    let entity = match find(world, player_id) {
+        Some(entity) => entity,
+        None => {
+            let entity = world.create_entity();
+            world.add_component(entity, RemotePlayer::new(player_id));
+            entity
+        }
+    };
+    world
+        .get_component_mut::<RemotePlayer>(entity)
+        .expect("追加したRemotePlayerが見つかる")
+}
@@ -0,0 +1,317 @@
+// =============================================================================
+// 選択・強調表示
+// =============================================================================
+// このファイルでは、カードの見た目の状態（選択中・強調表示・ドロップ先）を
+// カードコンポーネントのフラグではなく、独立したマーカーコンポーネントとして実装します。
+//
+// コンポーネント：
+// - Selected    : プレイヤーが選択中のカード（同時に1枚だけ）
+// - Highlighted : ヒントやチュートリアルで目立たせるカード（時間切れで消える、ヒントの知らせでは点滅させる）
+// - DropTarget  : 選択中のカードを置ける山（CardStackエンティティと、その山の置き場所に付く）
+// - Dropped     : ドラッグを離したカード（CardMovementSystemが次のフレームで移動先を判定する）
+// - PilePlaceholder : 山の置き場所（カードのない組札・空いた列でも押す・離す・強調表示の対象になる）
+//
+// システム：
+// - SelectionSystem : 選択を1枚に保ち、選択中のカードに応じてDropTargetを付け直す
+// - HighlightSystem : 強調表示の残り時間を減らし、時間切れのものを外す
+// =============================================================================
+
+use crate::ecs::{Component, Entity, System, World};
+use crate::permissions;
+use crate::solitaire::{CardLocation, CardStack, SolitaireCard};
+use log::debug;
+
+// =============================================================================
+// マーカーコンポーネント
+// =============================================================================
+
+/// 選択中のカード
+///
+/// 同時に選択できるのは1枚だけです。select()で選択すると他のカードの選択は外れ、
+/// 直接追加された場合もSelectionSystemが一番新しい選択だけを残します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selected {
+    /// 選択した順番（大きいほど新しい）
+    pub order: u64,
+}
+
+impl Component for Selected {}
+
+/// 強調表示中のカード
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlighted {
+    /// 強調表示の残り時間（秒、Noneの場合は外すまで続く）
+    pub remaining_seconds: Option<f64>,
+
+    /// 点滅させて目を引くかどうか（操作がないときのヒントの知らせ）
+    pub pulse: bool,
+}
+
+impl Component for Highlighted {}
+
+/// 選択中のカードを置ける山
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropTarget;
+
+impl Component for DropTarget {}
+
+/// ドラッグを離したカード
+///
+/// CardMovementSystemが表示座標の近くの山へ移動し、成否にかかわらず
+/// DroppedとSelectedを外します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dropped;
+
+impl Component for Dropped {}
+
+/// 山の置き場所
+///
+/// CardStackごとに1つのエンティティとして作られ、山の一番下のカードを置く位置に
+/// カード1枚分の大きさで置かれます。カードのない山を押す・カードを離す操作は
+/// この置き場所で山に結び付け、置ける山の場合はCardStackと同じくDropTargetが付きます。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PilePlaceholder {
+    /// 対応する山（CardStackエンティティ）
+    pub stack: Entity,
+
+    /// 山の種類
+    pub location: CardLocation,
+
+    /// 山の番号
+    pub index: u32,
+
+    /// 置き場所のX座標（左上）
+    pub x: f32,
+
+    /// 置き場所のY座標（左上）
+    pub y: f32,
+}
+
+impl Component for PilePlaceholder {}
+
+// =============================================================================
+// 操作ヘルパー
+// =============================================================================
+
+/// カードを選択する（他のカードの選択は外れる）
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - 選択するカードのエンティティ
+///
+/// # 戻り値
+/// 選択できた場合true、動かせないカード（裏向き・組札など）の場合false
+pub fn select(world: &mut World, entity: Entity) -> bool {
+    // 共有盤面では、観戦中・他のプレイヤーのカードは選択できない
+    let selectable = world
+        .get_component::<SolitaireCard>(entity)
+        .is_some_and(|card| card.is_movable)
+        && permissions::check_own_card(world, entity).is_ok();
+    if !selectable {
+        return false;
+    }
+
+    let order = world
+        .query::<Selected>()
+        .map(|(_, selected)| selected.order + 1)
+        .max()
+        .unwrap_or(0);
+    clear_selection(world);
+    world.add_component(entity, Selected { order });
+    true
+}
+
+/// カードの選択をすべて外す
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+pub fn clear_selection(world: &mut World) {
+    let selected: Vec<Entity> = world
+        .query::<Selected>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in selected {
+        world.remove_component::<Selected>(entity);
+    }
+}
+
+/// 選択中のカードを取得
+///
+/// # 引数
+/// * `world` - ECSワールド
+///
+/// # 戻り値
+/// 選択中のカードがある場合はSome(エンティティ)
+pub fn selected_card(world: &World) -> Option<Entity> {
+    world
+        .query::<Selected>()
+        .max_by_key(|(_, selected)| selected.order)
+        .map(|(entity, _)| entity)
+}
+
+/// カードを強調表示する
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - 強調表示するカードのエンティティ
+/// * `seconds` - 強調表示を続ける時間（秒、Noneの場合は外すまで続く）
+pub fn highlight(world: &mut World, entity: Entity, seconds: Option<f64>) {
+    world.add_component(
+        entity,
+        Highlighted {
+            remaining_seconds: seconds,
+            pulse: false,
+        },
+    );
+}
+
+/// カードを点滅させて強調表示する
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - 点滅させるカードのエンティティ
+/// * `seconds` - 点滅を続ける時間（秒、Noneの場合は外すまで続く）
+pub fn pulse(world: &mut World, entity: Entity, seconds: Option<f64>) {
+    world.add_component(
+        entity,
+        Highlighted {
+            remaining_seconds: seconds,
+            pulse: true,
+        },
+    );
+}
+
+/// 強調表示をすべて外す
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+pub fn clear_highlights(world: &mut World) {
+    let highlighted: Vec<Entity> = world
+        .query::<Highlighted>()
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in highlighted {
+        world.remove_component::<Highlighted>(entity);
+    }
+}
+
+// =============================================================================
+// システム
+// =============================================================================
+
+/// 選択管理システム
+///
+/// 選択を一番新しい1枚に保ち、動かせなくなったカード（裏返された・組札に置かれた）の
+/// 選択を外した上で、選択中のカードを置ける山にDropTargetを付け直します。
+pub struct SelectionSystem;
+
+impl System for SelectionSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let newest = selected_card(world);
+        let stale: Vec<Entity> = world
+            .query::<Selected>()
+            .map(|(entity, _)| entity)
+            .filter(|&entity| {
+                Some(entity) != newest
+                    || !world
+                        .get_component::<SolitaireCard>(entity)
+                        .is_some_and(|card| card.is_movable)
+            })
+            .collect();
+        for entity in stale {
+            world.remove_component::<Selected>(entity);
+            debug!("👆 選択を解除: {:?}", entity);
+        }
+
+        let mut targets = selected_card(world)
+            .and_then(|entity| world.get_component::<SolitaireCard>(entity))
+            .map(|card| drop_targets(world, card))
+            .unwrap_or_default();
+
+        // カードのない山でも強調表示できるように、置ける山の置き場所にも付ける
+        let placeholders: Vec<Entity> = world
+            .query::<PilePlaceholder>()
+            .filter(|(_, placeholder)| targets.contains(&placeholder.stack))
+            .map(|(entity, _)| entity)
+            .collect();
+        targets.extend(placeholders);
+
+        let previous: Vec<Entity> = world
+            .query::<DropTarget>()
+            .map(|(entity, _)| entity)
+            .filter(|entity| !targets.contains(entity))
+            .collect();
+        for entity in previous {
+            world.remove_component::<DropTarget>(entity);
+        }
+        for entity in targets {
+            if !world.has_component::<DropTarget>(entity) {
+                world.add_component(entity, DropTarget);
+            }
+        }
+    }
+}
+
+/// カードを置けるタブロー・ファウンデーションの山を探す
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `card` - 置くカード
+///
+/// # 戻り値
+/// 置ける山（CardStackエンティティ）のベクター
+pub(crate) fn drop_targets(world: &World, card: &SolitaireCard) -> Vec<Entity> {
+    world
+        .query::<CardStack>()
+        .filter(|(_, stack)| {
+            stack.stack_type != card.location_type || stack.stack_index != card.position_in_location
+        })
+        .filter(|(_, stack)| {
+            let top = top_card(world, stack.stack_type, stack.stack_index);
+            match stack.stack_type {
+                CardLocation::Tableau => match top {
+                    Some(top) => top.is_face_up && card.can_place_on_tableau(top),
+                    None => card.can_place_on_empty_tableau(),
+                },
+                CardLocation::Foundation => card.can_place_on_foundation(top),
+                _ => false,
+            }
+        })
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// 山の一番上のカードを取得（タブローは表示位置、ファウンデーションはランクで判定）
+fn top_card(world: &World, location: CardLocation, index: u32) -> Option<&SolitaireCard> {
+    let cards = world
+        .query::<SolitaireCard>()
+        .map(|(_, card)| card)
+        .filter(|card| card.location_type == location && card.position_in_location == index);
+    match location {
+        CardLocation::Tableau => cards.max_by(|a, b| a.display_y.total_cmp(&b.display_y)),
+        _ => cards.max_by_key(|card| card.rank),
+    }
+}
+
+/// 強調表示管理システム
+///
+/// 強調表示の残り時間を減らし、時間切れになったものを外します。
+pub struct HighlightSystem;
+
+impl System for HighlightSystem {
+    fn update(&mut self, world: &mut World, delta_time: f64) {
+        let mut expired = Vec::new();
+        for (entity, highlighted) in world.query_mut::<Highlighted>() {
+            if let Some(remaining) = highlighted.remaining_seconds.as_mut() {
+                *remaining -= delta_time;
+                if *remaining <= 0.0 {
+                    expired.push(entity);
+                }
+            }
+        }
+
+        for entity in expired {
+            world.remove_component::<Highlighted>(entity);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// =============================================================================
+// 送信待ちのメッセージのキュー
+// =============================================================================
+// このファイルでは、WebSocketが接続されていない間に送ろうとしたメッセージを
+// 溜めておく上限付きのキューと、その溜まり具合・捨てた数の記録を実装します。
+//
+// 仕組み：
+// - 溜められるのは上限（既定ではDEFAULT_SEND_QUEUE_CAPACITY件）まで
+// - いっぱいのときは、溜まっている中で最も優先度の低い一番古いメッセージを捨てて入れる
+// - 同じ優先度同士の場合は優先度ごとの捨て方（DropPolicy）に従う
+//   （カーソル位置・接続確認などは新しい方が役に立つので古い方を捨て、
+//    ゲームアクションなどは順番に意味があるので後から来た方を捨てる）
+// - 溜めたメッセージより優先度が低いメッセージは溜めずに捨てる
+// - 溜めた数・捨てた数・接続し直してから送り直した数を数え、
+//   get_connection_status()のqueueで確認できる
+// =============================================================================
+
+use crate::network::MessagePriority;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use ts_rs::TS;
+
+/// 送信待ちのキューに溜められるメッセージの数の既定値
+pub const DEFAULT_SEND_QUEUE_CAPACITY: usize = 256;
+
+/// キューがいっぱいのときに、同じ優先度のメッセージのどちらを捨てるか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// 溜まっている一番古いメッセージを捨てて、新しいメッセージを入れる
+    DropOldest,
+
+    /// 新しいメッセージを捨てる（溜まっているメッセージの順番を崩さない）
+    DropNewest,
+}
+
+impl DropPolicy {
+    /// 優先度ごとの捨て方
+    ///
+    /// # 引数
+    /// * `priority` - 入れようとしたメッセージの優先度
+    ///
+    /// # 戻り値
+    /// 通常優先度はDropNewest、それ以外はDropOldest
+    pub fn for_priority(priority: MessagePriority) -> Self {
+        match priority {
+            MessagePriority::Normal => DropPolicy::DropNewest,
+            _ => DropPolicy::DropOldest,
+        }
+    }
+}
+
+/// 送信待ちのキューの溜まり具合（get_connection_status()のqueue）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QueueMetrics {
+    /// 溜められるメッセージの数の上限
+    pub capacity: usize,
+
+    /// いま溜まっているメッセージの数
+    pub backlog: usize,
+
+    /// これまでに溜めたメッセージの数
+    pub queued: u64,
+
+    /// これまでに捨てたメッセージの数（溜めずに捨てた分と、押し出して捨てた分）
+    pub dropped: u64,
+
+    /// 接続し直してから溜まっていた中から送り直したメッセージの数
+    pub retried: u64,
+}
+
+/// 上限付きの送信待ちのキュー
+#[derive(Debug, Clone)]
+pub struct SendQueue {
+    /// 溜まっているメッセージ（優先度, JSON文字列）、古い順
+    messages: VecDeque<(MessagePriority, String)>,
+
+    /// 溜められるメッセージの数の上限
+    capacity: usize,
+
+    /// これまでに溜めたメッセージの数
+    queued: u64,
+
+    /// これまでに捨てたメッセージの数
+    dropped: u64,
+
+    /// これまでに送り直したメッセージの数
+    retried: u64,
+}
+
+impl Default for SendQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEND_QUEUE_CAPACITY)
+    }
+}
+
+impl SendQueue {
+    /// 空のキューを作成
+    ///
+    /// # 引数
+    /// * `capacity` - 溜められるメッセージの数の上限（1件以上）
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            capacity: capacity.max(1),
+            queued: 0,
+            dropped: 0,
+            retried: 0,
+        }
+    }
+
+    /// メッセージを溜める
+    ///
+    /// いっぱいの場合は、優先度と捨て方に従って溜まっているメッセージか
+    /// 入れようとしたメッセージのどちらかを捨てます。
+    ///
+    /// # 引数
+    /// * `priority` - メッセージの優先度
+    /// * `text` - 送信するメッセージ（JSON文字列）
+    ///
+    /// # 戻り値
+    /// 溜めた場合true、入れようとしたメッセージを捨てた場合false
+    pub fn push(&mut self, priority: MessagePriority, text: String) -> bool {
+        if self.messages.len() >= self.capacity {
+            // 最も優先度の低いメッセージのうち一番古いもの
+            let victim = self
+                .messages
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, (queued, _))| (*queued, *index))
+                .map(|(index, (queued, _))| (index, *queued));
+            let evict = match victim {
+                Some((index, queued)) if queued < priority => Some(index),
+                Some((index, queued))
+                    if queued == priority
+                        && DropPolicy::for_priority(priority) == DropPolicy::DropOldest =>
+                {
+                    Some(index)
+                }
+                _ => None,
+            };
+            self.dropped += 1;
+            let Some(index) = evict else {
+                warn!(
+                    "⚠️ 送信待ちのキューがいっぱいなのでメッセージを捨てました ({:?})",
+                    priority
+                );
+                return false;
+            };
+            if let Some((evicted, _)) = self.messages.remove(index) {
+                debug!(
+                    "🗑️ 送信待ちのメッセージを押し出して捨てました ({:?})",
+                    evicted
+                );
+            }
+        }
+
+        self.messages.push_back((priority, text));
+        self.queued += 1;
+        true
+    }
+
+    /// 送り直すために溜まっているメッセージを古い順にすべて取り出す
+    ///
+    /// # 戻り値
+    /// 溜まっていたメッセージ（優先度, JSON文字列）
+    pub fn take_for_retry(&mut self) -> Vec<(MessagePriority, String)> {
+        let messages: Vec<_> = self.messages.drain(..).collect();
+        self.retried += messages.len() as u64;
+        messages
+    }
+
+    /// 送り直せなかったメッセージを先頭に戻す（送り直した数からは除き、溜めた数は変えない）
+    ///
+    /// # 引数
+    /// * `messages` - 戻すメッセージ（古い順）
+    pub fn restore(&mut self, messages: Vec<(MessagePriority, String)>) {
+        self.retried = self.retried.saturating_sub(messages.len() as u64);
+        for message in messages.into_iter().rev() {
+            self.messages.push_front(message);
+        }
+        while self.messages.len() > self.capacity {
+            self.messages.pop_back();
+            self.dropped += 1;
+        }
+    }
+
+    /// 溜まっているメッセージの数
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// 溜まっているメッセージがないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// 溜まり具合と、溜めた・捨てた・送り直した数
+    pub fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            capacity: self.capacity,
+            backlog: self.messages.len(),
+            queued: self.queued,
+            dropped: self.dropped,
+            retried: self.retried,
+        }
+    }
+}
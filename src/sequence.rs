@@ -0,0 +1,140 @@
+// =============================================================================
+// チャネルごとの連番
+// =============================================================================
+// このファイルでは、送るメッセージにチャネル（カーソル・アクション・チャット・同期）ごとの
+// 連番を付けるSequenceCounterと、届いた連番から抜け・順序の入れ替わり・古いメッセージを
+// 判定するSequenceTrackerを実装します。
+//
+// 仕組み：
+// - 送る側はチャネルごとに1から順に連番を付ける（接続し直したら1から数え直す）
+// - 受け取る側はチャネルごとに届いた最大の連番と、まだ届いていない連番を覚えておく
+// - 最大の連番より2つ以上先の連番が届いたら、その間の連番を抜けとして記録し、
+//   Reliableのメッセージであれば送った側に再送を要求する
+// - 抜けていた連番が後から届いた場合は順序の入れ替わり、最大の連番以下の届いたことがある
+//   連番は古いメッセージとして扱う（カーソル位置は新しい位置が届いていれば捨てる）
+//
+// 予測・巻き戻しの処理で、アクションを送った順に適用できているかを確かめるためにも使います。
+// =============================================================================
+
+use crate::protocol::Channel;
+use std::collections::{BTreeSet, HashMap};
+
+/// 抜けとして覚えておく連番の数の上限（1つのチャネルあたり）
+///
+/// 極端に先の連番が届いた場合も、直前のこの数だけを抜けとして扱います。
+pub const MAX_TRACKED_GAP: u32 = 64;
+
+/// チャネルごとに送るメッセージの連番を発行する
+#[derive(Debug, Clone, Default)]
+pub struct SequenceCounter {
+    /// チャネルごとの最後に発行した連番
+    last: HashMap<Channel, u32>,
+}
+
+impl SequenceCounter {
+    /// まだ連番を発行していない状態で作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 次の連番を発行する
+    ///
+    /// # 引数
+    /// * `channel` - メッセージを送るチャネル
+    ///
+    /// # 戻り値
+    /// 1から始まる連番
+    pub fn next(&mut self, channel: Channel) -> u32 {
+        let last = self.last.entry(channel).or_insert(0);
+        *last = last.wrapping_add(1).max(1);
+        *last
+    }
+
+    /// 連番を1から数え直す（接続し直した場合）
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+}
+
+/// 届いた連番の判定結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arrival {
+    /// 次の連番が順番通りに届いた
+    InOrder,
+
+    /// 連番が飛んで届いた（抜けている連番）
+    Gap(Vec<u32>),
+
+    /// 抜けていた連番が後から届いた（順序が入れ替わった）
+    Late,
+
+    /// 届いたことがある連番、または抜けとして覚えていないほど古い連番
+    Stale,
+}
+
+/// 1つのチャネルの受信状況
+#[derive(Debug, Clone, Default)]
+struct ChannelState {
+    /// 届いた最大の連番
+    highest: u32,
+
+    /// まだ届いていない連番
+    missing: BTreeSet<u32>,
+}
+
+/// 届いた連番を記録し、抜け・順序の入れ替わりを判定する
+#[derive(Debug, Clone, Default)]
+pub struct SequenceTracker {
+    /// チャネルごとの受信状況
+    channels: HashMap<Channel, ChannelState>,
+}
+
+impl SequenceTracker {
+    /// 何も届いていない状態で作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 届いた連番を記録する
+    ///
+    /// # 引数
+    /// * `channel` - メッセージのチャネル
+    /// * `sequence` - メッセージの連番
+    ///
+    /// # 戻り値
+    /// 順番通り・抜けあり・順序の入れ替わり・古いメッセージのいずれか
+    pub fn record(&mut self, channel: Channel, sequence: u32) -> Arrival {
+        let state = self.channels.entry(channel).or_default();
+        if sequence <= state.highest {
+            return if state.missing.remove(&sequence) {
+                Arrival::Late
+            } else {
+                Arrival::Stale
+            };
+        }
+
+        let gap_start = (state.highest + 1).max(sequence.saturating_sub(MAX_TRACKED_GAP));
+        let gap: Vec<u32> = (gap_start..sequence).collect();
+        state.highest = sequence;
+        if gap.is_empty() {
+            return Arrival::InOrder;
+        }
+
+        state.missing.extend(gap.iter().copied());
+        while state.missing.len() > MAX_TRACKED_GAP as usize {
+            state.missing.pop_first();
+        }
+        Arrival::Gap(gap)
+    }
+
+    /// まだ届いていない連番
+    ///
+    /// # 引数
+    /// * `channel` - 調べるチャネル
+    pub fn missing(&self, channel: Channel) -> Vec<u32> {
+        self.channels
+            .get(&channel)
+            .map(|state| state.missing.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
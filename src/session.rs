@@ -0,0 +1,225 @@
+// =============================================================================
+// ゲームセッションの管理
+// =============================================================================
+// このファイルでは、複数のゲームセッションを同時に保持し、セッションIDで
+// 振り分けるためのセッションレジストリを実装します。
+//
+// 仕組み：
+// - セッションごとにワールドとシステム一式（ブラウザではGameRuntime、
+//   サーバーではボットのBotPlayer）を持つ
+// - 一時停止したセッションは更新されず、再開するまでそのまま保持される
+// - セッションIDを省略した呼び出しは既定のセッション（"default"）に振り分ける
+//
+// ブラウザとサーバーの両方で使うため、中身の型は問いません。
+// =============================================================================
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use ts_rs::TS;
+
+/// セッションIDを省略した場合に使う既定のセッションID
+pub const DEFAULT_SESSION_ID: &str = "default";
+
+/// セッションの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// 実行中（毎フレーム更新される）
+    Active,
+
+    /// 一時停止中（再開するまで更新されない）
+    Suspended,
+}
+
+/// セッション一覧の1件分
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SessionInfo {
+    /// セッションID
+    pub id: String,
+
+    /// セッションの状態
+    pub status: SessionStatus,
+}
+
+/// 登録されたセッション
+#[derive(Debug)]
+struct SessionEntry<S> {
+    session: S,
+    status: SessionStatus,
+}
+
+/// セッションレジストリ
+///
+/// セッションIDからセッション（ワールドとシステム一式）を引けるようにします。
+#[derive(Debug)]
+pub struct SessionRegistry<S> {
+    sessions: HashMap<String, SessionEntry<S>>,
+}
+
+impl<S> Default for SessionRegistry<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> SessionRegistry<S> {
+    /// 空のレジストリを作成
+    pub fn new() -> Self {
+        Self {
+            sessions: HashMap::new(),
+        }
+    }
+
+    /// セッションIDを解決する
+    ///
+    /// # 引数
+    /// * `session_id` - セッションID（Noneの場合は既定のセッション）
+    ///
+    /// # 戻り値
+    /// 使用するセッションID
+    pub fn resolve_id(session_id: Option<&str>) -> &str {
+        session_id.unwrap_or(DEFAULT_SESSION_ID)
+    }
+
+    /// セッションを登録する
+    ///
+    /// 同じIDのセッションがある場合は置き換えます（新しいセッションは実行中になる）。
+    ///
+    /// # 引数
+    /// * `session_id` - セッションID
+    /// * `session` - 登録するセッション
+    ///
+    /// # 戻り値
+    /// 置き換えられた古いセッション（なかった場合はNone）
+    pub fn insert(&mut self, session_id: &str, session: S) -> Option<S> {
+        info!("🗂️ セッションを登録: {}", session_id);
+        self.sessions
+            .insert(
+                session_id.to_string(),
+                SessionEntry {
+                    session,
+                    status: SessionStatus::Active,
+                },
+            )
+            .map(|entry| entry.session)
+    }
+
+    /// セッションを取得する（一時停止中のセッションも取得できる）
+    pub fn get(&self, session_id: &str) -> Option<&S> {
+        self.sessions.get(session_id).map(|entry| &entry.session)
+    }
+
+    /// セッションを可変参照で取得する（一時停止中のセッションも取得できる）
+    pub fn get_mut(&mut self, session_id: &str) -> Option<&mut S> {
+        self.sessions
+            .get_mut(session_id)
+            .map(|entry| &mut entry.session)
+    }
+
+    /// 実行中のセッションだけを可変参照で取得する
+    ///
+    /// フレームの更新など、一時停止中には行わない処理に使います。
+    pub fn get_active_mut(&mut self, session_id: &str) -> Option<&mut S> {
+        self.sessions
+            .get_mut(session_id)
+            .filter(|entry| entry.status == SessionStatus::Active)
+            .map(|entry| &mut entry.session)
+    }
+
+    /// セッションの状態を取得する
+    pub fn status(&self, session_id: &str) -> Option<SessionStatus> {
+        self.sessions.get(session_id).map(|entry| entry.status)
+    }
+
+    /// セッションを一時停止する
+    ///
+    /// # 戻り値
+    /// 成功時はOk(())、セッションがない・既に一時停止中の場合はエラーメッセージ
+    pub fn suspend(&mut self, session_id: &str) -> Result<(), String> {
+        self.set_status(session_id, SessionStatus::Suspended)?;
+        info!("⏸️ セッションを一時停止: {}", session_id);
+        Ok(())
+    }
+
+    /// 一時停止中のセッションを再開する
+    ///
+    /// # 戻り値
+    /// 成功時はOk(())、セッションがない・一時停止中でない場合はエラーメッセージ
+    pub fn resume(&mut self, session_id: &str) -> Result<(), String> {
+        self.set_status(session_id, SessionStatus::Active)?;
+        info!("▶️ セッションを再開: {}", session_id);
+        Ok(())
+    }
+
+    /// セッションを破棄する
+    ///
+    /// # 戻り値
+    /// 破棄したセッション（なかった場合はNone）
+    pub fn destroy(&mut self, session_id: &str) -> Option<S> {
+        let entry = self.sessions.remove(session_id)?;
+        info!("🗑️ セッションを破棄: {}", session_id);
+        Some(entry.session)
+    }
+
+    /// 登録されているセッションの一覧（ID順）
+    pub fn list(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> = self
+            .sessions
+            .iter()
+            .map(|(id, entry)| SessionInfo {
+                id: id.clone(),
+                status: entry.status,
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.id.cmp(&b.id));
+        sessions
+    }
+
+    /// 実行中のセッションをすべて可変参照で取得する
+    pub fn active_sessions_mut(&mut self) -> impl Iterator<Item = (&str, &mut S)> {
+        self.sessions
+            .iter_mut()
+            .filter(|(_, entry)| entry.status == SessionStatus::Active)
+            .map(|(id, entry)| (id.as_str(), &mut entry.session))
+    }
+
+    /// 一時停止中も含めてすべてのセッションを可変参照で取得する
+    pub fn sessions_mut(&mut self) -> impl Iterator<Item = (&str, &mut S)> {
+        self.sessions
+            .iter_mut()
+            .map(|(id, entry)| (id.as_str(), &mut entry.session))
+    }
+
+    /// 登録されているセッション数
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// セッションが1つもないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// セッションの状態を変更する
+    fn set_status(&mut self, session_id: &str, status: SessionStatus) -> Result<(), String> {
+        let entry = self
+            .sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("セッションが見つかりません: {}", session_id))?;
+        if entry.status == status {
+            return Err(format!(
+                "セッションは既に{}です: {}",
+                match status {
+                    SessionStatus::Active => "実行中",
+                    SessionStatus::Suspended => "一時停止中",
+                },
+                session_id
+            ));
+        }
+        entry.status = status;
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+// =============================================================================
+// プレイヤーの設定（ゲームをまたいで引き継ぐ）
+// =============================================================================
+// このファイルでは、引く枚数・テーマ・効果音・アニメーションの速さ・言語・
+// プレイヤー名など、ゲームをまたいで引き継ぐ設定（Preferences）を実装します。
+//
+// 保存先はstorageと同じです（ブラウザではlocalStorage、ネイティブ環境では
+// save_data/settings.json）。サーバーのプレイヤー設定（preferences.rs）とは別のものです。
+//
+// 仕組み：
+// - initialize_game()で保存されている設定を読み込み、セッションのランタイムに適用する
+// - set_preferences()で変えた設定は保存され、すべてのセッションに適用される
+// - セッションごとの上書き（PreferenceOverrides）は保存せず、そのセッションだけに適用する
+//   （例：観戦用のセッションだけアニメーションを速くする）
+// - 以前のバージョンでテーマだけを保存していた場合は、そのテーマを引き継ぐ
+// - format_numbersを有効にすると、状態の出力に言語に合わせて整形した経過時間・スコアが加わる（i18n.rs）
+// =============================================================================
+
+use crate::ecs::{Resource, World};
+use crate::game::{MAX_ANIMATION_SPEED, MIN_ANIMATION_SPEED};
+use crate::protocol::MAX_DISPLAY_NAME_CHARS;
+use crate::storage;
+use crate::theme::Theme;
+use serde::{Deserialize, Serialize};
+
+/// 設定の保存キー
+const STORAGE_KEY: &str = "settings";
+
+/// 言語の指定の最大の長さ（BCP 47の言語タグ、例："ja"、"en-US"）
+const MAX_LOCALE_CHARS: usize = 35;
+
+/// 標準の言語
+const DEFAULT_LOCALE: &str = "ja";
+
+/// デッキから1回に引く枚数
+///
+/// 次にstart_game()で始める1人用のゲームから適用します（進行中のゲームの枚数は変えない）。
+/// マルチプレイの配り札は参加者で揃えるため、常に1枚引きです。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawMode {
+    /// 1枚ずつ引く（標準）
+    #[default]
+    One,
+
+    /// 3枚ずつ引く
+    Three,
+}
+
+impl DrawMode {
+    /// 1回に引く枚数
+    pub fn count(&self) -> u32 {
+        match self {
+            DrawMode::One => 1,
+            DrawMode::Three => 3,
+        }
+    }
+}
+
+/// ゲームをまたいで引き継ぐ設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// デッキから1回に引く枚数
+    pub draw_mode: DrawMode,
+
+    /// カードの裏面とテーブルのテーマ
+    pub theme: Theme,
+
+    /// 効果音を鳴らすか
+    pub sound_enabled: bool,
+
+    /// 効果音の音量（0.0〜1.0）
+    pub sound_volume: f32,
+
+    /// アニメーションの速さの倍率（MIN_ANIMATION_SPEED〜MAX_ANIMATION_SPEED）
+    pub animation_speed: f32,
+
+    /// 表示する言語（BCP 47の言語タグ）
+    pub locale: String,
+
+    /// get_solitaire_state()・get_game_result()に、言語に合わせて整形した経過時間・スコアを含めるか
+    pub format_numbers: bool,
+
+    /// プレイヤー名（Noneの場合は毎回入力してもらう）
+    pub player_name: Option<String>,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            draw_mode: DrawMode::default(),
+            theme: Theme::default(),
+            sound_enabled: true,
+            sound_volume: 0.8,
+            animation_speed: 1.0,
+            locale: DEFAULT_LOCALE.to_string(),
+            format_numbers: false,
+            player_name: None,
+        }
+    }
+}
+
+impl Resource for Preferences {}
+
+impl Preferences {
+    /// 保存されている設定を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ標準の設定（以前に保存したテーマは引き継ぐ）
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_else(|| Self {
+                theme: Theme::load(),
+                ..Self::default()
+            })
+    }
+
+    /// 設定を保存する
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self)
+            .map_err(|e| format!("設定のシリアライゼーション失敗: {}", e))?;
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    /// 設定値が範囲内かチェック
+    ///
+    /// # 戻り値
+    /// 範囲内の場合Ok(())、範囲外の場合はエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_ANIMATION_SPEED..=MAX_ANIMATION_SPEED).contains(&self.animation_speed) {
+            return Err(format!(
+                "アニメーションの速さは{}〜{}倍で指定してください: {}",
+                MIN_ANIMATION_SPEED, MAX_ANIMATION_SPEED, self.animation_speed
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.sound_volume) {
+            return Err(format!(
+                "音量は0.0〜1.0で指定してください: {}",
+                self.sound_volume
+            ));
+        }
+        let locale_is_valid = !self.locale.is_empty()
+            && self.locale.chars().count() <= MAX_LOCALE_CHARS
+            && self
+                .locale
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !locale_is_valid {
+            return Err(format!("言語の指定が不正です: {:?}", self.locale));
+        }
+        if let Some(name) = &self.player_name {
+            if name.trim().is_empty() || name.chars().count() > MAX_DISPLAY_NAME_CHARS {
+                return Err(format!(
+                    "プレイヤー名は1〜{}文字で指定してください",
+                    MAX_DISPLAY_NAME_CHARS
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// セッションごとの上書きを適用した設定を作成
+    ///
+    /// # 引数
+    /// * `overrides` - セッションごとの上書き
+    ///
+    /// # 戻り値
+    /// 上書きした項目だけを置き換えた設定
+    pub fn merged(&self, overrides: &PreferenceOverrides) -> Self {
+        Self {
+            draw_mode: overrides.draw_mode.unwrap_or(self.draw_mode),
+            theme: overrides.theme.unwrap_or(self.theme),
+            sound_enabled: overrides.sound_enabled.unwrap_or(self.sound_enabled),
+            sound_volume: overrides.sound_volume.unwrap_or(self.sound_volume),
+            animation_speed: overrides.animation_speed.unwrap_or(self.animation_speed),
+            locale: overrides
+                .locale
+                .clone()
+                .unwrap_or_else(|| self.locale.clone()),
+            format_numbers: overrides.format_numbers.unwrap_or(self.format_numbers),
+            player_name: overrides
+                .player_name
+                .clone()
+                .or_else(|| self.player_name.clone()),
+        }
+    }
+}
+
+/// セッションごとの設定の上書き（保存しない）
+///
+/// 指定した項目だけが保存されている設定より優先されます。
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreferenceOverrides {
+    /// デッキから1回に引く枚数
+    pub draw_mode: Option<DrawMode>,
+
+    /// カードの裏面とテーブルのテーマ
+    pub theme: Option<Theme>,
+
+    /// 効果音を鳴らすか
+    pub sound_enabled: Option<bool>,
+
+    /// 効果音の音量（0.0〜1.0）
+    pub sound_volume: Option<f32>,
+
+    /// アニメーションの速さの倍率
+    pub animation_speed: Option<f32>,
+
+    /// 表示する言語
+    pub locale: Option<String>,
+
+    /// 整形した経過時間・スコアを状態の出力に含めるか
+    pub format_numbers: Option<bool>,
+
+    /// プレイヤー名
+    pub player_name: Option<String>,
+}
+
+impl Resource for PreferenceOverrides {}
+
+/// ワールドに適用されている、セッションごとの上書きを反映した現在の設定を取得
+///
+/// # 引数
+/// * `world` - ECSワールドへの参照
+///
+/// # 戻り値
+/// 現在の設定（適用されていない場合は標準の設定）
+pub fn current(world: &World) -> Preferences {
+    let preferences = world
+        .get_resource::<Preferences>()
+        .cloned()
+        .unwrap_or_default();
+    match world.get_resource::<PreferenceOverrides>() {
+        Some(overrides) => preferences.merged(overrides),
+        None => preferences,
+    }
+}
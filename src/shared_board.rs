@@ -0,0 +1,139 @@
+// =============================================================================
+// 共有盤面のルームの盤面の写し（サーバー用）
+// =============================================================================
+// このファイルでは、共有盤面のルームでサーバーが持つ盤面の写し（SharedBoards）を実装します。
+// GameActionの文字列だけでは、山札から引くのかウェイストを戻すのか、
+// どのカードを動かすのかが分からないため、サーバーもクライアントと同じシードで
+// 配った盤面にアクションを反映し、その盤面でカードの持ち主と参加者の立場を確かめます。
+//
+// 仕組み：
+// - 配り札が始まる（シードが決まる）たびに盤面を配り直す
+// - ルームの保存時には盤面（Scenario）も保存し、再起動後はその盤面から作り直す
+// - 保存した盤面もない場合は、シードとアクションの記録から作り直す
+// - 盤面の操作の読み方と確かめる規則は、permissionsのクライアントと同じ関数を使う
+// =============================================================================
+
+use crate::ecs::{Entity, World};
+use crate::game::{ActionPayload, ActionProcessingSystem};
+use crate::permissions::{self, SeatRole};
+use crate::protocol::LoggedAction;
+use crate::scenario::{BoardBuilder, Scenario};
+use crate::solitaire::{SolitaireManager, SolitaireType};
+use log::debug;
+use std::collections::{BTreeMap, HashMap};
+
+/// 共有盤面のルームごとの盤面の写し
+#[derive(Default)]
+pub struct SharedBoards {
+    /// ルームID → 盤面
+    boards: HashMap<String, World>,
+}
+
+impl SharedBoards {
+    /// 盤面の写しのないSharedBoardsを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ルームの盤面を新しいシードで配り直す
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `seed` - 配り札のシード
+    pub fn deal(&mut self, room_id: &str, seed: u64) {
+        self.boards
+            .insert(room_id.to_string(), Self::replay(seed, &[]));
+    }
+
+    /// ルームの盤面のスナップショットを取得（ルームの保存用）
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    ///
+    /// # 戻り値
+    /// 盤面がある場合はSome(盤面)、まだ配っていない場合はNone
+    pub fn snapshot(&self, room_id: &str) -> Option<Scenario> {
+        self.boards.get(room_id).map(Scenario::from_world)
+    }
+
+    /// 保存していた盤面からルームの盤面を作り直す（再起動後）
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `board` - 保存していた盤面
+    ///
+    /// # 戻り値
+    /// 成功時はOk(())、盤面が不正な場合はエラーメッセージ
+    pub fn restore(&mut self, room_id: &str, board: Scenario) -> Result<(), String> {
+        let mut world = World::new();
+        BoardBuilder::from_scenario(board).build(&mut world)?;
+        self.boards.insert(room_id.to_string(), world);
+        Ok(())
+    }
+
+    /// ルームの盤面を捨てる（ルームを削除した場合）
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    pub fn remove(&mut self, room_id: &str) {
+        self.boards.remove(room_id);
+    }
+
+    /// ルームの盤面を取得（ない場合はシードとアクションの記録から作り直す）
+    ///
+    /// # 引数
+    /// * `room_id` - ルームID
+    /// * `seed` - 配り札のシード
+    /// * `action_log` - 配り札の開始からのアクション
+    pub fn board(&mut self, room_id: &str, seed: u64, action_log: &[LoggedAction]) -> &mut World {
+        self.boards
+            .entry(room_id.to_string())
+            .or_insert_with(|| Self::replay(seed, action_log))
+    }
+
+    /// 盤面の操作を確かめて、盤面に反映する
+    ///
+    /// # 引数
+    /// * `board` - ルームの盤面
+    /// * `card_owners` - カードのID → 持ち主のプレイヤーID
+    /// * `role` - 操作した参加者の立場
+    /// * `player_id` - 操作した参加者のプレイヤーID
+    /// * `payload` - 盤面の操作（permissions::board_payload()で読んだもの）
+    ///
+    /// # 戻り値
+    /// 反映した場合はOk(())、権限がない・盤面で実行できない場合は理由を表すエラーメッセージ
+    pub fn apply(
+        board: &mut World,
+        card_owners: &BTreeMap<String, String>,
+        role: SeatRole,
+        player_id: &str,
+        payload: &ActionPayload,
+    ) -> Result<(), String> {
+        permissions::set_owners(board, card_owners);
+        permissions::check_payload_as(board, role, player_id, payload)?;
+        // 盤面の操作（移動・めくる・引く）は操作したプレイヤーのエンティティを使わない
+        ActionProcessingSystem::execute(board, Entity(0), payload)
+    }
+
+    /// シードで配った盤面にアクションの記録を反映して作り直す
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード
+    /// * `action_log` - 配り札の開始からのアクション
+    fn replay(seed: u64, action_log: &[LoggedAction]) -> World {
+        let mut world = World::new();
+        SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+        for logged in action_log {
+            let Some(payload) = permissions::board_payload(&logged.action) else {
+                continue;
+            };
+            if let Err(e) = ActionProcessingSystem::execute(&mut world, Entity(0), &payload) {
+                debug!(
+                    "記録のアクションを盤面に反映できません: {} ({})",
+                    logged.action, e
+                );
+            }
+        }
+        world
+    }
+}
@@ -5,6 +5,16 @@
 // プレイヤー間のリアルタイム通信を実現します。
 // =============================================================================
 
+// サーバーはtokio・tungsteniteなどネイティブ環境の非同期I/Oを使うため、WebAssembly向けにはビルドできない
+#[cfg(target_arch = "wasm32")]
+compile_error!("simple_websocket_server はネイティブ環境でのみビルドできます（--features serverはWebAssembly向けには使えません）");
+
+// ログの出力先（クライアントと共有）
+use ecs_wasm_solitaire::logging;
+// 座標として受け付ける値の範囲（クライアントと共有）
+use ecs_wasm_solitaire::protocol::MAX_COORDINATE;
+
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
@@ -66,8 +76,14 @@ pub enum WebSocketMessage {
         y: Option<f64>,
         timestamp: u64,
     },
+    GameResult {
+        player_id: String,
+        result: serde_json::Value,
+    },
     Error {
         message: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        field: Option<String>, // 値が不正だったフィールド名（座標が有限でない・範囲外の場合のみ）
     },
 }
 
@@ -75,6 +91,9 @@ pub enum WebSocketMessage {
 // 簡単なサーバー実装
 // =============================================================================
 
+/// 待ち受けアドレスの既定値
+const DEFAULT_ADDR: &str = "162.43.8.148:8101";
+
 type Players = Arc<Mutex<HashMap<String, Player>>>;
 type Senders = Arc<Mutex<HashMap<String, tokio::sync::mpsc::UnboundedSender<String>>>>;
 
@@ -96,10 +115,10 @@ impl SimpleWebSocketServer {
     /// サーバーを開始
     pub async fn start(&self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
         let listener = TcpListener::bind(addr).await?;
-        println!("🌐 シンプルWebSocketサーバーを{}で開始しました", addr);
+        info!("🌐 シンプルWebSocketサーバーを{}で開始しました", addr);
 
         while let Ok((stream, addr)) = listener.accept().await {
-            println!("🔗 新しい接続: {}", addr);
+            info!("🔗 新しい接続: {}", addr);
             
             let players = Arc::clone(&self.players);
             let senders = Arc::clone(&self.senders);
@@ -107,7 +126,7 @@ impl SimpleWebSocketServer {
 
             tokio::spawn(async move {
                 if let Err(e) = Self::handle_connection(stream, players, senders, next_color_index).await {
-                    println!("❌ 接続処理エラー: {}", e);
+                    error!("❌ 接続処理エラー: {}", e);
                 }
             });
         }
@@ -141,7 +160,7 @@ impl SimpleWebSocketServer {
         while let Some(message) = ws_receiver.next().await {
             match message? {
                 Message::Text(text) => {
-                    println!("📥 受信メッセージ: {}", text);
+                    debug!("📥 受信メッセージ: {}", text);
                     
                     match serde_json::from_str::<WebSocketMessage>(&text) {
                         Ok(msg) => {
@@ -171,7 +190,7 @@ impl SimpleWebSocketServer {
                                         senders_map.insert(player.id.clone(), tx.clone());
                                     }
                                     
-                                    println!("👤 プレイヤー参加: {} ({})", player.name, player.id);
+                                    info!("👤 プレイヤー参加: {} ({})", player.name, player.id);
                                     
                                     // 他のプレイヤーに通知
                                     Self::broadcast_to_others(
@@ -186,6 +205,13 @@ impl SimpleWebSocketServer {
                                 }
                                 
                                 WebSocketMessage::MousePosition { player_id: msg_player_id, x, y, timestamp } => {
+                                    // 不正な座標は中継せず、送信者にエラーを返す
+                                    if let Some(error) = invalid_coordinate(Some(x), Some(y)) {
+                                        warn!("⚠️ 不正な座標を破棄: {}", msg_player_id);
+                                        let _ = tx.send(serde_json::to_string(&error)?);
+                                        continue;
+                                    }
+                                    
                                     // プレイヤーのマウス位置を更新
                                     {
                                         let mut players_map = players.lock().unwrap();
@@ -209,7 +235,14 @@ impl SimpleWebSocketServer {
                                 }
                                 
                                 WebSocketMessage::GameAction { player_id: msg_player_id, player_name, action, x, y, timestamp } => {
-                                    println!("🎯 ゲームアクション: {} by {}", action, player_name);
+                                    debug!("🎯 ゲームアクション: {} by {}", action, player_name);
+                                    
+                                    // 不正な座標は中継せず、送信者にエラーを返す
+                                    if let Some(error) = invalid_coordinate(x, y) {
+                                        warn!("⚠️ 不正な座標を破棄: {}", msg_player_id);
+                                        let _ = tx.send(serde_json::to_string(&error)?);
+                                        continue;
+                                    }
                                     
                                     // 他のプレイヤーにアクションをブロードキャスト
                                     Self::broadcast_to_others(
@@ -226,18 +259,32 @@ impl SimpleWebSocketServer {
                                     ).await;
                                 }
                                 
+                                WebSocketMessage::GameResult { player_id: msg_player_id, result } => {
+                                    info!("📋 ゲーム結果受信: {} -> {}", msg_player_id, result);
+                                    
+                                    // 他のプレイヤーに結果をブロードキャスト
+                                    Self::broadcast_to_others(
+                                        &WebSocketMessage::GameResult {
+                                            player_id: msg_player_id.clone(),
+                                            result,
+                                        },
+                                        &senders,
+                                        &msg_player_id
+                                    ).await;
+                                }
+                                
                                 _ => {
-                                    println!("⚠️ 未対応メッセージタイプ: {:?}", msg);
+                                    warn!("⚠️ 未対応メッセージタイプ: {:?}", msg);
                                 }
                             }
                         }
                         Err(e) => {
-                            println!("❌ メッセージパースエラー: {}", e);
+                            error!("❌ メッセージパースエラー: {}", e);
                         }
                     }
                 }
                 Message::Close(_) => {
-                    println!("🔌 接続クローズ");
+                    info!("🔌 接続クローズ");
                     break;
                 }
                 _ => {}
@@ -260,7 +307,7 @@ impl SimpleWebSocketServer {
                 senders_map.remove(&pid);
             }
             
-            println!("👋 プレイヤー退出: {} ({})", player_name, pid);
+            info!("👋 プレイヤー退出: {} ({})", player_name, pid);
             
             // 他のプレイヤーに退出を通知
             Self::broadcast_to_others(
@@ -288,7 +335,7 @@ impl SimpleWebSocketServer {
         let message_text = match serde_json::to_string(message) {
             Ok(text) => text,
             Err(e) => {
-                println!("❌ メッセージシリアライゼーションエラー: {}", e);
+                error!("❌ メッセージシリアライゼーションエラー: {}", e);
                 return;
             }
         };
@@ -297,23 +344,49 @@ impl SimpleWebSocketServer {
         for (player_id, sender) in senders_map.iter() {
             if player_id != exclude_player_id {
                 if let Err(_) = sender.send(message_text.clone()) {
-                    println!("⚠️ プレイヤー{}への送信失敗", player_id);
+                    warn!("⚠️ プレイヤー{}への送信失敗", player_id);
                 }
             }
         }
     }
 }
 
+/// 座標が有限かつ範囲内（±MAX_COORDINATE）かチェック
+///
+/// # 引数
+/// * `x` - X座標（位置がない場合はNone）
+/// * `y` - Y座標（位置がない場合はNone）
+///
+/// # 戻り値
+/// 不正な座標がある場合は送信者に返すエラー、問題がなければNone
+fn invalid_coordinate(x: Option<f64>, y: Option<f64>) -> Option<WebSocketMessage> {
+    [("x", x), ("y", y)].into_iter().find_map(|(field, value)| {
+        let value = value?;
+        if value.is_finite() && value.abs() <= MAX_COORDINATE {
+            return None;
+        }
+        Some(WebSocketMessage::Error {
+            message: format!("不正な座標です（{}）: {}", field, value),
+            field: Some(field.to_string()),
+        })
+    })
+}
+
 // =============================================================================
 // サーバー起動用のメイン関数
 // =============================================================================
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 マルチプレイソリティア Simple WebSocketサーバー起動中...");
+    // ログ出力を初期化（環境変数RUST_LOGでレベルを変更できる）
+    logging::init();
+
+    info!("🚀 マルチプレイソリティア Simple WebSocketサーバー起動中...");
     
+    // 第1引数で待ち受けアドレスを変更できる（テストではローカルの空きポートを指定する）
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_ADDR.to_string());
     let server = SimpleWebSocketServer::new();
-    server.start("162.43.8.148:8101").await?;
+    server.start(&addr).await?;
     
     Ok(())
 }
\ No newline at end of file
@@ -17,11 +17,40 @@
 // - スコア計算とランキング管理
 // =============================================================================
 
+use crate::animation_queue::MoveQueue;
+use crate::clock::GameClock;
 use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::game::{GameSettings, DEFAULT_IDLE_HINT_SECONDS};
+use crate::hint::HintEngine;
+use crate::layout;
+use crate::reconcile::ReconcileTween;
+use crate::rng::Rng;
+use crate::selection::{self, Dropped, PilePlaceholder, Selected};
+use crate::timeline::{self, TimelineRecord};
+use crate::transaction::MoveTransaction;
+use log::{debug, info, warn};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 use std::collections::VecDeque;
 // use std::time::{SystemTime, UNIX_EPOCH}; // 未使用のため一時的にコメントアウト
 
+/// 減点なしでウェイストをデッキに戻せる回数
+pub const FREE_DECK_TURNS: u32 = 2;
+
+/// それ以降にウェイストをデッキに戻すたびに引かれる点数
+pub const DECK_TURN_PENALTY: u32 = 2;
+
+/// ヒントの知らせで動かすカードを点滅させる時間（秒）
+pub const HINT_NUDGE_PULSE_SECONDS: f64 = 5.0;
+
+/// 標準のカードの移動速度（ピクセル/秒、GameSettingsのアニメーション設定の倍率を掛ける）
+pub const CARD_ANIMATION_SPEED: f32 = 500.0;
+
+/// タブローの裏向きのカードが表向きになった時の得点
+pub const REVEAL_POINTS: u32 = 5;
+
 // =============================================================================
 // ソリティアゲーム専用のコンポーネント定義
 // =============================================================================
@@ -50,9 +79,6 @@ pub struct SolitaireCard {
     /// カードが移動可能かどうか
     pub is_movable: bool,
 
-    /// カードが選択されているかどうか
-    pub is_selected: bool,
-
     /// カードの表示座標（アニメーション用）
     pub display_x: f32,
     pub display_y: f32,
@@ -84,7 +110,6 @@ impl SolitaireCard {
             location_type: CardLocation::Deck,
             position_in_location: 0,
             is_movable: false,
-            is_selected: false,
             display_x: 0.0,
             display_y: 0.0,
             target_x: 0.0,
@@ -103,7 +128,6 @@ impl SolitaireCard {
     pub fn flip_down(&mut self) {
         self.is_face_up = false;
         self.is_movable = false;
-        self.is_selected = false;
     }
 
     /// カードの位置を設定
@@ -234,7 +258,7 @@ impl SolitaireCard {
 }
 
 /// カードのスート（絵柄）
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema, TS)]
 pub enum CardSuit {
     Hearts,   // ♥ ハート
     Diamonds, // ♦ ダイヤ
@@ -271,7 +295,7 @@ impl CardSuit {
 }
 
 /// カードのランク（数値・絵札）
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, JsonSchema, TS)]
 pub enum CardRank {
     Ace = 1,
     Two = 2,
@@ -342,7 +366,7 @@ pub enum CardColor {
 }
 
 /// カードの配置場所（Windowsソリティア準拠）
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema, TS)]
 pub enum CardLocation {
     /// デッキ（山札）- 左上の裏向きカード置き場
     Deck,
@@ -381,7 +405,7 @@ impl CardLocation {
 }
 
 /// ゲームタイプ
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema, TS)]
 pub enum SolitaireType {
     /// クロンダイク（通常のソリティア）
     Klondike,
@@ -407,6 +431,161 @@ impl SolitaireType {
     }
 }
 
+/// 1つのゲームで使えるデッキ数の上限
+pub const MAX_DECKS: u32 = 8;
+
+/// デッキの構成
+///
+/// 何組のデッキを使うか、各デッキにどのスートを含めるかを表します。
+/// 1デッキは使うスートごとにA〜Kの13枚を持つため、1スートのスパイダーは
+/// スペードだけのデッキ8組（104枚）になります。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DeckSpec {
+    /// 使うデッキの数
+    pub decks: u32,
+
+    /// 各デッキに含めるスート（1スート・2スートのスパイダーでは一部だけ）
+    pub suits: Vec<CardSuit>,
+
+    /// ジョーカーの枚数（将来のバリアント用、現在のゲームでは0のみ）
+    pub jokers: u32,
+}
+
+impl Default for DeckSpec {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+impl DeckSpec {
+    /// 52枚のフレンチデッキ1組
+    pub fn standard() -> Self {
+        Self {
+            decks: 1,
+            suits: CardSuit::all().to_vec(),
+            jokers: 0,
+        }
+    }
+
+    /// ゲームの種類ごとの標準の構成
+    ///
+    /// # 引数
+    /// * `game_type` - ゲームの種類
+    pub fn for_game(game_type: SolitaireType) -> Self {
+        match game_type {
+            SolitaireType::Spider => Self {
+                decks: 2,
+                ..Self::standard()
+            },
+            SolitaireType::Klondike | SolitaireType::FreeCell => Self::standard(),
+        }
+    }
+
+    /// スパイダーの構成（どのスート数でも合計104枚）
+    ///
+    /// # 引数
+    /// * `suit_count` - 使うスートの数（1：スペード、2：スペードとハート、4：全スート）
+    ///
+    /// # 戻り値
+    /// 成功時はDeckSpec、1・2・4以外のスート数の場合はエラーメッセージ
+    pub fn spider(suit_count: usize) -> Result<Self, String> {
+        let suits = match suit_count {
+            1 => vec![CardSuit::Spades],
+            2 => vec![CardSuit::Spades, CardSuit::Hearts],
+            4 => CardSuit::all().to_vec(),
+            _ => {
+                return Err(format!(
+                    "スパイダーのスート数は1・2・4のいずれかです: {}",
+                    suit_count
+                ))
+            }
+        };
+        Ok(Self {
+            decks: 8 / suit_count as u32,
+            suits,
+            jokers: 0,
+        })
+    }
+
+    /// 構成が正しいかチェック
+    ///
+    /// # 戻り値
+    /// 正しい場合Ok(())、デッキ数・スートが不正な場合はエラーメッセージ
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_DECKS).contains(&self.decks) {
+            return Err(format!(
+                "デッキの数は1〜{}で指定してください: {}",
+                MAX_DECKS, self.decks
+            ));
+        }
+        if self.suits.is_empty() {
+            return Err("スートを1つ以上指定してください".to_string());
+        }
+        for (i, suit) in self.suits.iter().enumerate() {
+            if self.suits[..i].contains(suit) {
+                return Err(format!("スートが重複しています: {}", suit.symbol()));
+            }
+        }
+        if self.jokers > 0 {
+            return Err("ジョーカーを使うゲームはまだありません".to_string());
+        }
+        Ok(())
+    }
+
+    /// 全てのカードの枚数
+    pub fn total_cards(&self) -> usize {
+        self.decks as usize * self.suits.len() * CardRank::all().len() + self.jokers as usize
+    }
+
+    /// 構成に含まれる全てのカード（デッキごとにスート・ランクの順）
+    pub fn cards(&self) -> impl Iterator<Item = (CardSuit, CardRank)> + '_ {
+        (0..self.decks).flat_map(move |_| {
+            self.suits
+                .iter()
+                .flat_map(|&suit| CardRank::all().into_iter().map(move |rank| (suit, rank)))
+        })
+    }
+
+    /// カードの組み合わせが構成どおりかチェック（不変条件の確認）
+    ///
+    /// 各カードがちょうどデッキの数だけあり、構成にないカードがないことを確認します。
+    ///
+    /// # 引数
+    /// * `cards` - 盤面にある全てのカードのスートとランク
+    ///
+    /// # 戻り値
+    /// 構成どおりの場合Ok(())、違う場合は最初に見つかった違いを表すエラーメッセージ
+    pub fn check_cards(
+        &self,
+        cards: impl IntoIterator<Item = (CardSuit, CardRank)>,
+    ) -> Result<(), String> {
+        let mut counts = std::collections::HashMap::new();
+        for (suit, rank) in cards {
+            if !self.suits.contains(&suit) {
+                return Err(format!(
+                    "構成にないスートのカードがあります: {}{}",
+                    suit.symbol(),
+                    rank.display()
+                ));
+            }
+            *counts.entry((suit as u8, rank as u8)).or_insert(0u32) += 1;
+        }
+        for (suit, rank) in self.cards().take(self.suits.len() * CardRank::all().len()) {
+            let count = counts.get(&(suit as u8, rank as u8)).copied().unwrap_or(0);
+            if count != self.decks {
+                return Err(format!(
+                    "{}{}が{}枚あります（{}枚のはずです）",
+                    suit.symbol(),
+                    rank.display(),
+                    count,
+                    self.decks
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
 /// ソリティアゲーム状態コンポーネント
 ///
 /// ゲーム全体の状態（ゲームタイプ、スコア、経過時間など）を管理します。
@@ -433,37 +612,152 @@ pub struct SolitaireGameState {
     /// デッキから引いた回数
     pub deck_turns: u32,
 
-    /// ヒントが利用可能かどうか
+    /// 操作がないときのヒントの知らせ（HintNudge）を送れるかどうか（送ると次の操作まで送らない）
     pub hint_available: bool,
 
     /// 最後の操作からの経過時間（秒）
-    pub idle_time: u64,
+    pub idle_time: f64,
+
+    /// カード配布に使用したシード値（同じシードなら同じ配置を再現できる）
+    pub seed: u64,
+
+    /// ゲーム終了時刻（UNIXタイムスタンプ、進行中はNone）
+    pub end_time: Option<u64>,
+
+    /// ヒントを使用した回数
+    pub hints_used: u32,
+
+    /// アンドゥを使用した回数
+    pub undos_used: u32,
+
+    /// 最終スコアの内訳（勝利時に計算される）
+    pub score_breakdown: Option<ScoreBreakdown>,
+
+    /// 使っているデッキの構成
+    #[serde(default)]
+    pub deck: DeckSpec,
+
+    /// タブが非表示などでゲームを止めていた時間の合計（秒、経過時間に含めない）
+    #[serde(default)]
+    pub paused_seconds: u64,
+
+    /// デッキから1回に引く枚数（1枚引き・3枚引き、ゲームの開始時に決める）
+    #[serde(default = "default_draw_count")]
+    pub draw_count: u32,
 }
 
 impl Component for SolitaireGameState {}
 
+/// 保存データに引く枚数がない場合は1枚引き
+fn default_draw_count() -> u32 {
+    1
+}
+
+/// 最終スコアの内訳
+///
+/// 基本スコアにボーナスとペナルティを適用した結果を保持します。
+/// ゲーム結果レポートでプレイヤーにスコアの理由を説明するために使います。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ScoreBreakdown {
+    /// 移動によって獲得した基本スコア
+    pub base_score: u32,
+
+    /// 時間ボーナス（早くクリアするほど高い）
+    pub time_bonus: u32,
+
+    /// 移動回数ペナルティ（移動が多いほど高い）
+    pub move_penalty: u32,
+
+    /// 最終スコア
+    pub final_score: u32,
+}
+
+impl ScoreBreakdown {
+    /// 基本スコア・経過時間・移動回数から内訳を計算
+    ///
+    /// # 引数
+    /// * `base_score` - 移動によって獲得した基本スコア
+    /// * `elapsed_seconds` - ゲーム開始からの経過時間（秒）
+    /// * `move_count` - 移動回数
+    ///
+    /// # 戻り値
+    /// 計算されたScoreBreakdown
+    pub fn calculate(base_score: u32, elapsed_seconds: u64, move_count: u32) -> Self {
+        // 時間ボーナス（早いほど高得点）
+        let time_bonus = if elapsed_seconds < 300 {
+            // 5分以内
+            100
+        } else if elapsed_seconds < 600 {
+            // 10分以内
+            50
+        } else {
+            0
+        };
+
+        // 移動回数ペナルティ（少ないほど高得点）
+        let move_penalty = if move_count > 200 {
+            20
+        } else if move_count > 100 {
+            10
+        } else {
+            0
+        };
+
+        Self {
+            base_score,
+            time_bonus,
+            move_penalty,
+            final_score: base_score
+                .saturating_add(time_bonus)
+                .saturating_sub(move_penalty),
+        }
+    }
+}
+
 impl SolitaireGameState {
     /// 新しいソリティアゲーム状態を作成
     ///
     /// # 引数
     /// * `game_type` - ゲームの種類
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
     ///
     /// # 戻り値
     /// 初期化されたSolitaireGameStateインスタンス
-    pub fn new(game_type: SolitaireType) -> Self {
+    pub fn new(game_type: SolitaireType, clock: &GameClock) -> Self {
         Self {
             game_type,
             score: 0,
             move_count: 0,
-            start_time: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            start_time: clock.now_secs(),
             is_completed: false,
             is_won: false,
             deck_turns: 0,
             hint_available: true,
-            idle_time: 0,
+            idle_time: 0.0,
+            seed: 0,
+            end_time: None,
+            hints_used: 0,
+            undos_used: 0,
+            score_breakdown: None,
+            deck: DeckSpec::for_game(game_type),
+            paused_seconds: 0,
+            draw_count: default_draw_count(),
+        }
+    }
+
+    /// シード値付きでソリティアゲーム状態を作成
+    ///
+    /// # 引数
+    /// * `game_type` - ゲームの種類
+    /// * `seed` - カード配布に使用するシード値
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    ///
+    /// # 戻り値
+    /// 初期化されたSolitaireGameStateインスタンス
+    pub fn with_seed(game_type: SolitaireType, seed: u64, clock: &GameClock) -> Self {
+        Self {
+            seed,
+            ..Self::new(game_type, clock)
         }
     }
 
@@ -472,42 +766,52 @@ impl SolitaireGameState {
     /// # 引数
     /// * `points` - この移動で獲得するポイント
     pub fn record_move(&mut self, points: u32) {
-        self.move_count += 1;
-        self.score += points;
-        self.idle_time = 0;
+        self.move_count = self.move_count.saturating_add(1);
+        self.score = self.score.saturating_add(points);
+        self.reset_idle_time();
 
         // 移動に応じたスコア調整
         match points {
             10 => {
                 // ファウンデーションに配置：+10点
             }
-            5 => {
-                // タブローで表向きにする：+5点
-            }
             _ => {
                 // その他の移動
             }
         }
 
-        println!(
+        debug!(
             "📊 移動記録: {}回目, スコア: {}, 獲得ポイント: {}",
             self.move_count, self.score, points
         );
     }
 
+    /// タブローの裏向きのカードが表向きになったことを記録してスコアを更新
+    ///
+    /// めくったのは移動の結果なので、手数には数えません。
+    pub fn record_reveal(&mut self) {
+        self.score = self.score.saturating_add(REVEAL_POINTS);
+        self.reset_idle_time();
+
+        debug!(
+            "📊 カードが表向きになりました: スコア: {}, 獲得ポイント: {}",
+            self.score, REVEAL_POINTS
+        );
+    }
+
     /// デッキをめくった回数を記録
     pub fn record_deck_turn(&mut self) {
-        self.deck_turns += 1;
-        self.idle_time = 0;
+        self.deck_turns = self.deck_turns.saturating_add(1);
+        self.reset_idle_time();
 
         // 3回目以降はスコア減点
-        if self.deck_turns > 2 {
-            if self.score >= 2 {
-                self.score -= 2;
+        if self.deck_turns > FREE_DECK_TURNS {
+            if self.score >= DECK_TURN_PENALTY {
+                self.score -= DECK_TURN_PENALTY;
             }
         }
 
-        println!(
+        info!(
             "🎴 デッキターン: {}回目, スコア: {}",
             self.deck_turns, self.score
         );
@@ -533,64 +837,79 @@ impl SolitaireGameState {
             }
         }
 
-        // 全カード（52枚）がファウンデーションに配置されたら勝利
-        let required_cards = match self.game_type {
-            SolitaireType::Klondike => 52,
-            SolitaireType::FreeCell => 52,
-            SolitaireType::Spider => 104, // 2デッキ使用
-        };
+        // デッキの全てのカードがファウンデーションに配置されたら勝利
+        let required_cards = self.deck.total_cards();
 
         if foundation_count == required_cards {
-            self.is_completed = true;
-            self.is_won = true;
-            self.calculate_final_score();
+            self.finish_game(true, &GameClock::from_world(world));
 
-            println!("🎉 ゲーム完了！勝利！最終スコア: {}", self.score);
+            info!("🎉 ゲーム完了！勝利！最終スコア: {}", self.score);
             return true;
         }
 
         false
     }
 
-    /// 最終スコアを計算
-    fn calculate_final_score(&mut self) {
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    /// ゲームを終了状態にする
+    ///
+    /// 終了時刻を記録し、勝利した場合は最終スコアを計算します。
+    /// 既に終了している場合は何もしません。
+    ///
+    /// # 引数
+    /// * `won` - 勝利で終了した場合true、投了・中断の場合false
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    pub fn finish_game(&mut self, won: bool, clock: &GameClock) {
+        if self.end_time.is_some() {
+            return;
+        }
 
-        let elapsed_time = current_time - self.start_time;
+        self.is_completed = true;
+        self.is_won = won;
+        self.end_time = Some(clock.now_secs());
 
-        // 時間ボーナス（早いほど高得点）
-        let time_bonus = if elapsed_time < 300 {
-            // 5分以内
-            100
-        } else if elapsed_time < 600 {
-            // 10分以内
-            50
-        } else {
-            0
-        };
+        if won {
+            self.calculate_final_score(clock);
+        }
+    }
 
-        // 移動回数ペナルティ（少ないほど高得点）
-        let move_penalty = if self.move_count > 200 {
-            20
-        } else if self.move_count > 100 {
-            10
-        } else {
-            0
-        };
+    /// ゲーム開始からの経過時間を取得
+    ///
+    /// # 引数
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    ///
+    /// # 戻り値
+    /// 経過時間（秒）。終了済みの場合は終了時刻までの時間。止めていた時間は含めない
+    pub fn elapsed_seconds(&self, clock: &GameClock) -> u64 {
+        let end = self.end_time.unwrap_or_else(|| clock.now_secs());
 
-        self.score = self
-            .score
-            .saturating_add(time_bonus)
-            .saturating_sub(move_penalty);
+        end.saturating_sub(self.start_time)
+            .saturating_sub(self.paused_seconds)
+    }
 
-        println!("⭐ 最終スコア計算:");
-        println!("  基本スコア: {}", self.score - time_bonus + move_penalty);
-        println!("  時間ボーナス: +{}", time_bonus);
-        println!("  移動ペナルティ: -{}", move_penalty);
-        println!("  最終スコア: {}", self.score);
+    /// ゲームを止めていた時間を記録（経過時間から除く）
+    ///
+    /// 終了済みのゲームでは何もしません。
+    ///
+    /// # 引数
+    /// * `seconds` - 止めていた時間（秒）
+    pub fn add_paused_time(&mut self, seconds: u64) {
+        if !self.is_completed {
+            self.paused_seconds = self.paused_seconds.saturating_add(seconds);
+        }
+    }
+
+    /// 最終スコアを計算
+    fn calculate_final_score(&mut self, clock: &GameClock) {
+        let breakdown =
+            ScoreBreakdown::calculate(self.score, self.elapsed_seconds(clock), self.move_count);
+        self.score = breakdown.final_score;
+        self.score_breakdown = Some(breakdown);
+
+        info!("⭐ 最終スコア計算:");
+        debug!("  基本スコア: {}", breakdown.base_score);
+        debug!("  時間ボーナス: +{}", breakdown.time_bonus);
+        debug!("  移動ペナルティ: -{}", breakdown.move_penalty);
+        debug!("  最終スコア: {}", breakdown.final_score);
     }
 
     /// 経過時間を更新
@@ -598,7 +917,114 @@ impl SolitaireGameState {
     /// # 引数
     /// * `delta_time` - フレーム間の経過時間（秒）
     pub fn update_idle_time(&mut self, delta_time: f64) {
-        self.idle_time += delta_time as u64;
+        self.idle_time += delta_time;
+    }
+
+    /// 操作があったので経過時間を戻し、ヒントの知らせを再び送れるようにする
+    fn reset_idle_time(&mut self) {
+        self.idle_time = 0.0;
+        self.hint_available = true;
+    }
+}
+
+/// 移動履歴の1手分の記録
+///
+/// どのカードをどこからどこへ動かしたかを記録します。
+/// 実績の判定や対局後の振り返りに使用します。
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MoveRecord {
+    /// 移動したカードのスート
+    pub suit: CardSuit,
+
+    /// 移動したカードのランク
+    pub rank: CardRank,
+
+    /// 移動元の場所
+    pub from: CardLocation,
+
+    /// 移動元の場所内のインデックス（列番号など）
+    pub from_index: u32,
+
+    /// 移動先の場所
+    pub to: CardLocation,
+
+    /// 移動先の場所内のインデックス（列番号など）
+    pub to_index: u32,
+
+    /// この移動で獲得したポイント
+    pub points: u32,
+
+    /// 移動した時刻（UNIXタイムスタンプ）
+    pub timestamp: u64,
+
+    /// この移動で移動元のタブローの裏向きのカードが表向きになったか
+    /// （記録を見返すための情報。練習モードの「手を戻す」は最初の盤面から手を再現し直すため、
+    /// めくったカードとREVEAL_POINTSは再現の中で付け直され、この値は使わない）
+    #[serde(default)]
+    pub revealed: bool,
+}
+
+impl MoveRecord {
+    /// カードの移動前の状態から移動記録を作成
+    ///
+    /// # 引数
+    /// * `card` - 移動前のカード
+    /// * `to` - 移動先の場所
+    /// * `to_index` - 移動先の場所内のインデックス
+    /// * `points` - この移動で獲得したポイント
+    /// * `clock` - 現在時刻の取得元となるゲーム時計
+    ///
+    /// # 戻り値
+    /// 新しいMoveRecordインスタンス
+    pub fn new(
+        card: &SolitaireCard,
+        to: CardLocation,
+        to_index: u32,
+        points: u32,
+        clock: &GameClock,
+    ) -> Self {
+        Self {
+            suit: card.suit,
+            rank: card.rank,
+            from: card.location_type,
+            from_index: card.position_in_location,
+            to,
+            to_index,
+            points,
+            timestamp: clock.now_secs(),
+            revealed: false,
+        }
+    }
+}
+
+/// 移動履歴コンポーネント
+///
+/// ゲーム状態エンティティに添付され、ゲーム中の全ての移動を順番に保持します。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MoveLog {
+    /// 移動記録（古い順）
+    pub moves: Vec<MoveRecord>,
+}
+
+impl Component for MoveLog {}
+
+impl MoveLog {
+    /// 移動を記録
+    ///
+    /// # 引数
+    /// * `record` - 追加する移動記録
+    pub fn push(&mut self, record: MoveRecord) {
+        self.moves.push(record);
+    }
+
+    /// ファウンデーションへの移動だけを取得
+    ///
+    /// # 戻り値
+    /// ファウンデーションへの移動記録のイテレータ（古い順）
+    pub fn foundation_moves(&self) -> impl Iterator<Item = &MoveRecord> {
+        self.moves
+            .iter()
+            .filter(|record| record.to == CardLocation::Foundation)
     }
 }
 
@@ -741,10 +1167,12 @@ pub struct CardMovementSystem;
 
 impl System for CardMovementSystem {
     fn update(&mut self, world: &mut World, _delta_time: f64) {
-        // 選択されたカードを検索
+        let clock = GameClock::from_world(world);
+
+        // ドロップされたカードを検索
         let mut selected_entities = Vec::new();
-        for (entity, card) in world.query::<SolitaireCard>() {
-            if card.is_selected {
+        for (entity, _) in world.query::<Dropped>() {
+            if let Some(card) = world.get_component::<SolitaireCard>(entity) {
                 selected_entities.push((entity, card.suit, card.rank, card.location_type));
             }
         }
@@ -759,13 +1187,10 @@ impl System for CardMovementSystem {
             .map(|(e, s)| (e, s.clone()))
             .collect();
 
-        // ゲーム状態エンティティ（1つだけの想定）
-        let game_state_entity = world.query::<SolitaireGameState>().next().map(|(e, _)| e);
-
-        // 選択されたカードの移動処理
+        // ドロップされたカードの移動処理
         for (entity, suit, rank, location_type) in selected_entities {
-            println!(
-                "🎯 選択されたカード: {}{} ({})",
+            debug!(
+                "🎯 ドロップされたカード: {}{} ({})",
                 suit.symbol(),
                 rank.display(),
                 location_type.name()
@@ -778,6 +1203,10 @@ impl System for CardMovementSystem {
                 continue;
             };
 
+            // ドロップは1回で処理し終えるので、移動できてもできなくても選択を解除
+            world.remove_component::<Dropped>(entity);
+            world.remove_component::<Selected>(entity);
+
             // 近くのスタックを検出（ユーザーがドロップしたとみなす）
             let mut target_stack: Option<(Entity, CardStack, f32)> = None;
             for (stack_entity, stack) in &stacks {
@@ -796,9 +1225,6 @@ impl System for CardMovementSystem {
                 if stack.stack_type == card_copy.location_type
                     && stack.stack_index == card_copy.position_in_location
                 {
-                    if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(entity) {
-                        card_mut.is_selected = false;
-                    }
                     continue;
                 }
 
@@ -842,75 +1268,20 @@ impl System for CardMovementSystem {
                 }
 
                 if valid_move {
-                    // 元のスタックから取り除く
-                    for (se, _) in &stacks {
-                        if let Some(stack_mut) = world.get_component_mut::<CardStack>(*se) {
-                            if stack_mut.stack_type == card_copy.location_type
-                                && stack_mut.stack_index == card_copy.position_in_location
-                            {
-                                if let Some(pos) = stack_mut.cards.iter().position(|&c| c == entity)
-                                {
-                                    stack_mut.cards.remove(pos);
-                                }
-                            }
-                        }
-                    }
-
-                    // 追加先スタックに登録
-                    if let Some(target_stack_mut) =
-                        world.get_component_mut::<CardStack>(target_entity)
-                    {
-                        let idx = target_stack_mut.len();
-                        target_stack_mut.push_card(entity);
-                        let (new_x, new_y) = target_stack_mut.calculate_card_position(idx);
-
-                        if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(entity) {
-                            card_mut.set_location(stack.stack_type, stack.stack_index);
-                            card_mut.set_display_position(new_x, new_y);
-                            card_mut.is_selected = false;
-                        }
-
-                        // スコア更新
-                        if let Some(gs_entity) = game_state_entity {
-                            if let Some(gs) =
-                                world.get_component_mut::<SolitaireGameState>(gs_entity)
-                            {
-                                gs.record_move(points);
-                            }
-                        }
-                    }
-
-                    // もし元がタブロー列なら、次のカードを表向きにする
-                    if card_copy.location_type == CardLocation::Tableau {
-                        let column = card_copy.position_in_location;
-                        let mut column_cards: Vec<(Entity, SolitaireCard)> = world
-                            .query::<SolitaireCard>()
-                            .filter(|(_, c)| {
-                                c.location_type == CardLocation::Tableau
-                                    && c.position_in_location == column
-                            })
-                            .map(|(e, c)| (e, c.clone()))
-                            .collect();
-
-                        if let Some((top_e, top_card)) =
-                            column_cards.iter().max_by_key(|(_, c)| c.display_y as i32)
-                        {
-                            if !top_card.is_face_up {
-                                if let Some(card_mut) =
-                                    world.get_component_mut::<SolitaireCard>(*top_e)
-                                {
-                                    card_mut.flip_up();
-                                }
-
-                                if let Some(gs_entity) = game_state_entity {
-                                    if let Some(gs) =
-                                        world.get_component_mut::<SolitaireGameState>(gs_entity)
-                                    {
-                                        gs.record_move(5);
-                                    }
-                                }
-                            }
-                        }
+                    // 手元で書き換えてから、すべての書き換えをまとめて反映する
+                    // （途中で失敗した場合は何も反映しない）
+                    let staged = Self::stage_drop(
+                        world,
+                        &stacks,
+                        entity,
+                        &card_copy,
+                        (target_entity, &stack),
+                        points,
+                        &clock,
+                    );
+                    match staged {
+                        Ok(transaction) => transaction.commit(world),
+                        Err(e) => warn!("⚠️ 移動を取り消しました: {}", e),
                     }
                 } else {
                     // 移動できない場合は位置を戻す
@@ -928,43 +1299,137 @@ impl System for CardMovementSystem {
                             if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(entity)
                             {
                                 card_mut.set_display_position(x, y);
-                                card_mut.is_selected = false;
                             }
                         }
                     }
                 }
-            } else {
-                // 対象スタックが見つからない場合は選択解除
-                if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(entity) {
-                    card_mut.is_selected = false;
+            }
+        }
+    }
+}
+
+impl CardMovementSystem {
+    /// ドロップした手の書き換えをトランザクションに準備
+    ///
+    /// スタックの登録、カードの場所・表示座標、移動元で露出した裏向きカードの向き、
+    /// スコアと手数、移動履歴を書き換えます。
+    ///
+    /// # 戻り値
+    /// 成功時は反映前のトランザクション、書き換えるカード・スタックがない場合はエラーメッセージ
+    fn stage_drop(
+        world: &World,
+        stacks: &[(Entity, CardStack)],
+        entity: Entity,
+        card_copy: &SolitaireCard,
+        (target_entity, stack): (Entity, &CardStack),
+        points: u32,
+        clock: &GameClock,
+    ) -> Result<MoveTransaction, String> {
+        let mut transaction = MoveTransaction::new();
+
+        // 元のスタックから取り除く
+        for (se, s) in stacks {
+            if s.stack_type == card_copy.location_type
+                && s.stack_index == card_copy.position_in_location
+            {
+                let stack_mut = transaction.stack(world, *se)?;
+                if let Some(pos) = stack_mut.cards.iter().position(|&c| c == entity) {
+                    stack_mut.cards.remove(pos);
+                }
+            }
+        }
+
+        // 置く位置は追加先の山に今あるカードの枚数から求める
+        // （配ったカードはスタックに登録されていないため、スタックの枚数は使わない）
+        let idx = world
+            .query::<SolitaireCard>()
+            .filter(|(_, c)| {
+                c.location_type == stack.stack_type && c.position_in_location == stack.stack_index
+            })
+            .count();
+
+        // 追加先スタックに登録
+        let target_stack_mut = transaction.stack(world, target_entity)?;
+        target_stack_mut.push_card(entity);
+        let (new_x, new_y) = target_stack_mut.calculate_card_position(idx);
+
+        let card_mut = transaction.card(world, entity)?;
+        card_mut.set_location(stack.stack_type, stack.stack_index);
+        card_mut.set_display_position(new_x, new_y);
+
+        transaction.record(MoveRecord::new(
+            card_copy,
+            stack.stack_type,
+            stack.stack_index,
+            points,
+            clock,
+        ));
+
+        // スコア更新
+        if let Some(gs) = transaction.game_state(world) {
+            gs.record_move(points);
+        }
+
+        // もし元がタブロー列なら、次のカードを表向きにする
+        // （移動したカードはまだワールドでは元の列にあるため除く）
+        if card_copy.location_type == CardLocation::Tableau {
+            let column = card_copy.position_in_location;
+            let column_cards: Vec<(Entity, SolitaireCard)> = world
+                .query::<SolitaireCard>()
+                .filter(|(e, c)| {
+                    *e != entity
+                        && c.location_type == CardLocation::Tableau
+                        && c.position_in_location == column
+                })
+                .map(|(e, c)| (e, c.clone()))
+                .collect();
+
+            if let Some((top_e, top_card)) =
+                column_cards.iter().max_by_key(|(_, c)| c.display_y as i32)
+            {
+                if !top_card.is_face_up {
+                    transaction.reveal(world, *top_e, column)?;
                 }
             }
         }
+
+        Ok(transaction)
     }
 }
 
 /// カードアニメーションシステム
 ///
 /// カードの移動アニメーションを管理するシステムです。
+/// 移動速度はGameSettingsのアニメーション設定に従い、省略する設定の場合はすぐに移動先へ置きます。
 pub struct CardAnimationSystem;
 
 impl System for CardAnimationSystem {
     fn update(&mut self, world: &mut World, delta_time: f64) {
-        let animation_speed = 500.0; // ピクセル/秒
+        let settings = world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.animation)
+            .unwrap_or_default();
+        let animation_speed = CARD_ANIMATION_SPEED * settings.move_speed(); // ピクセル/秒
         let mut animating_cards = Vec::new();
         let mut completed_animations = Vec::new();
 
         // アニメーション中のカードを特定
+        // サーバーの盤面に合わせ直しているカードは、ReconcileSystemが時間で動かす
+        // 順番待ちのアニメーションがあるカードは、AnimationQueueSystemが順番に動かす
         for (entity, card) in world.query::<SolitaireCard>() {
-            if card.is_animating {
+            if card.is_animating
+                && !world.has_component::<ReconcileTween>(entity)
+                && !world.has_component::<MoveQueue>(entity)
+            {
                 let dx = card.target_x - card.display_x;
                 let dy = card.target_y - card.display_y;
                 let distance = (dx * dx + dy * dy).sqrt();
+                let move_distance = animation_speed * delta_time as f32;
 
-                if distance < 2.0 {
+                // このフレームで移動先に届く場合は、行き過ぎないようにそのまま完了させる
+                if settings.instant || distance < 2.0 || move_distance >= distance {
                     completed_animations.push(entity);
                 } else {
-                    let move_distance = animation_speed * delta_time as f32;
                     let move_ratio = move_distance / distance;
                     animating_cards.push((entity, dx * move_ratio, dy * move_ratio));
                 }
@@ -985,7 +1450,7 @@ impl System for CardAnimationSystem {
                 let suit_symbol = card.suit.symbol();
                 let rank_display = card.rank.display();
                 card.finish_animation();
-                println!(
+                debug!(
                     "✨ カードアニメーション完了: {}{}",
                     suit_symbol, rank_display
                 );
@@ -1001,6 +1466,10 @@ pub struct SolitaireProgressSystem;
 
 impl System for SolitaireProgressSystem {
     fn update(&mut self, world: &mut World, delta_time: f64) {
+        let clock = GameClock::from_world(world);
+        let idle_hint_seconds = world
+            .get_resource::<GameSettings>()
+            .map_or(DEFAULT_IDLE_HINT_SECONDS, |settings| settings.idle_hint_seconds);
         let mut game_completed = false;
 
         // ゲーム状態を取得して更新
@@ -1037,30 +1506,64 @@ impl System for SolitaireProgressSystem {
                         if let Some(game_state_mut) =
                             world.get_component_mut::<SolitaireGameState>(entity)
                         {
-                            game_state_mut.is_completed = true;
-                            game_state_mut.is_won = true;
+                            game_state_mut.finish_game(true, &clock);
                         }
                         game_completed = true;
                     }
                 }
 
-                // 長時間アイドル時のヒント表示（再度borrowする）
-                if let Some(game_state_mut) = world.get_component_mut::<SolitaireGameState>(entity)
-                {
-                    if game_state_mut.idle_time > 30 && game_state_mut.hint_available {
-                        println!("💡 ヒント: 移動可能なカードを探してみてください");
-                        game_state_mut.hint_available = false;
-                    }
-                }
+                // しばらく操作がない場合は次の一手を知らせる
+                nudge_if_idle(world, entity, idle_hint_seconds);
             }
         }
 
         if game_completed {
-            println!("🏆 ゲーム完了！おめでとうございます！");
+            info!("🏆 ゲーム完了！おめでとうございます！");
         }
     }
 }
 
+/// しばらく操作がない場合に、次の一手をHintNudgeイベントで知らせ、動かすカードを点滅させる
+///
+/// 知らせるのは操作が止まってから1回だけで、次の移動・デッキの操作で再び知らせるようになります。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - ゲーム状態エンティティ
+/// * `idle_hint_seconds` - 知らせるまでの時間（秒、0の場合は知らせない）
+fn nudge_if_idle(world: &mut World, entity: Entity, idle_hint_seconds: u32) {
+    let Some(game_state) = world.get_component_mut::<SolitaireGameState>(entity) else {
+        return;
+    };
+    if idle_hint_seconds == 0
+        || game_state.is_completed
+        || !game_state.hint_available
+        || game_state.idle_time < f64::from(idle_hint_seconds)
+    {
+        return;
+    }
+    game_state.hint_available = false;
+    let idle_seconds = game_state.idle_time as u32;
+
+    let Some(hint) = HintEngine::find_hint(world) else {
+        debug!("💡 知らせる手がありません");
+        return;
+    };
+    let card_id = hint.card.map(|card| {
+        selection::clear_highlights(world);
+        selection::pulse(world, card.entity, Some(HINT_NUDGE_PULSE_SECONDS));
+        card.entity.id()
+    });
+    debug!("💡 ヒントの知らせ: {}", hint.message);
+    if let Some(queue) = world.get_resource_mut::<EventQueue>() {
+        queue.push(GameEvent::HintNudge {
+            card_id,
+            message: hint.message,
+            idle_seconds,
+        });
+    }
+}
+
 // =============================================================================
 // ソリティアゲーム管理のユーティリティ関数
 // =============================================================================
@@ -1080,74 +1583,145 @@ impl SolitaireManager {
     /// # 戻り値
     /// ゲーム状態エンティティ
     pub fn start_new_game(world: &mut World, game_type: SolitaireType) -> Entity {
-        println!("🎮 新しい{}ゲームを開始します", game_type.name());
+        // シードはワールドの乱数生成器から生成（毎回異なる配置になる）
+        let seed = match world.get_resource_mut::<Rng>() {
+            Some(rng) => rng.next_u64(),
+            None => Rng::from_entropy().next_u64(),
+        };
+
+        Self::start_new_game_with_seed(world, game_type, seed)
+    }
+
+    /// シードを指定して新しいソリティアゲームを開始
+    ///
+    /// 同じシードを指定すると同じカード配置が再現されるため、
+    /// マルチプレイでの同一ディールやリプレイに使用できます。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `game_type` - ゲームの種類
+    /// * `seed` - カード配布に使用するシード値
+    ///
+    /// # 戻り値
+    /// ゲーム状態エンティティ
+    pub fn start_new_game_with_seed(
+        world: &mut World,
+        game_type: SolitaireType,
+        seed: u64,
+    ) -> Entity {
+        Self::deal_new_game(world, game_type, DeckSpec::for_game(game_type), seed)
+    }
+
+    /// デッキの構成とシードを指定して新しいソリティアゲームを開始
+    ///
+    /// 1スート・2スートのスパイダーなど、標準と異なる構成で遊ぶ場合に使います。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `game_type` - ゲームの種類
+    /// * `deck` - デッキの構成
+    /// * `seed` - カード配布に使用するシード値
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ、構成が不正な場合はエラーメッセージ
+    pub fn start_new_game_with_deck(
+        world: &mut World,
+        game_type: SolitaireType,
+        deck: DeckSpec,
+        seed: u64,
+    ) -> Result<Entity, String> {
+        deck.validate()?;
+        Ok(Self::deal_new_game(world, game_type, deck, seed))
+    }
+
+    /// ゲーム状態を作成してカードを配る
+    fn deal_new_game(
+        world: &mut World,
+        game_type: SolitaireType,
+        deck: DeckSpec,
+        seed: u64,
+    ) -> Entity {
+        info!("🎮 新しい{}ゲームを開始します（シード: {}）", game_type.name(), seed);
 
         // ゲーム状態を作成
         let game_entity = world.create_entity();
-        let game_state = SolitaireGameState::new(game_type);
+        let game_state = SolitaireGameState {
+            deck: deck.clone(),
+            ..SolitaireGameState::with_seed(game_type, seed, &GameClock::from_world(world))
+        };
         world.add_component(game_entity, game_state);
+        world.add_component(game_entity, MoveLog::default());
 
         // カードデッキを作成・配布
-        let cards = Self::create_deck(world, game_type);
+        let cards = Self::create_deck(world, &deck, seed);
         Self::deal_cards(world, game_type, cards);
 
         // カードスタックを作成
         Self::create_stacks(world, game_type);
 
-        println!("✅ ゲーム初期化完了");
+        if let Err(e) = Self::check_invariants(world) {
+            warn!("⚠️ 配ったカードが構成と一致しません: {}", e);
+        }
+
+        info!("✅ ゲーム初期化完了");
         game_entity
     }
 
+    /// 盤面のカードがゲームのデッキの構成どおりかチェック
+    ///
+    /// 配り直しや移動の処理でカードが増えたり消えたりしていないことの確認に使います。
+    /// 一部のカードだけを置くシナリオの盤面では失敗します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 構成どおりの場合Ok(())、違う場合・ゲームがない場合はエラーメッセージ
+    pub fn check_invariants(world: &World) -> Result<(), String> {
+        let (_, state) = world
+            .query::<SolitaireGameState>()
+            .next()
+            .ok_or_else(|| "ゲームがありません".to_string())?;
+        state.deck.check_cards(
+            world
+                .query::<SolitaireCard>()
+                .map(|(_, card)| (card.suit, card.rank)),
+        )
+    }
+
     /// カードデッキを作成
     ///
     /// # 引数
     /// * `world` - ECSワールドへの可変参照
-    /// * `game_type` - ゲームの種類
+    /// * `deck` - デッキの構成
+    /// * `seed` - シャッフルに使用するシード値
     ///
     /// # 戻り値
-    /// 作成されたカードエンティティのベクター
-    fn create_deck(world: &mut World, game_type: SolitaireType) -> Vec<Entity> {
-        let mut cards = Vec::new();
-        let deck_count = match game_type {
-            SolitaireType::Spider => 2, // スパイダーは2デッキ
-            _ => 1,
-        };
-
-        for _ in 0..deck_count {
-            for suit in CardSuit::all() {
-                for rank in CardRank::all() {
-                    let card_entity = world.create_entity();
-                    let card = SolitaireCard::new(suit, rank);
-                    world.add_component(card_entity, card);
-                    cards.push(card_entity);
-                }
-            }
-        }
+    /// シャッフル済みのカードエンティティのベクター
+    fn create_deck(world: &mut World, deck: &DeckSpec, seed: u64) -> Vec<Entity> {
+        // 全カードをまとめて生成（1枚ずつ追加するより格納庫の再確保が少ない）
+        let mut cards = world.spawn_batch(
+            deck.cards()
+                .map(|(suit, rank)| SolitaireCard::new(suit, rank)),
+        );
 
-        // カードをシャッフル（簡単な実装）
-        Self::shuffle_cards(&mut cards);
+        // カードをシャッフル（シードから決定的に並べ替え）
+        Self::shuffle_cards(&mut cards, seed);
 
-        println!("🎴 {}デッキ作成完了: {}枚", deck_count, cards.len());
+        info!("🎴 {}デッキ作成完了: {}枚", deck.decks, cards.len());
         cards
     }
 
     /// カードをシャッフル
     ///
+    /// xorshift64による疑似乱数でFisher-Yatesシャッフルを行います。
+    /// 同じシードからは必ず同じ並びが得られます。
+    ///
     /// # 引数
-    /// * `cards` - シャッフルするカードエンティティのベクター
-    fn shuffle_cards(cards: &mut Vec<Entity>) {
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as usize;
-
-        // 簡単なシャッフルアルゴリズム
-        for i in (1..cards.len()).rev() {
-            let j = (seed * (i + 1) * 31) % (i + 1);
-            cards.swap(i, j);
-        }
+    /// * `cards` - シャッフルするカードエンティティのスライス
+    /// * `seed` - シャッフルに使用するシード値
+    fn shuffle_cards(cards: &mut [Entity], seed: u64) {
+        Rng::new(seed).shuffle(cards);
     }
 
     /// カードを配布
@@ -1197,9 +1771,8 @@ impl SolitaireManager {
                 if let Some(card) = world.get_component_mut::<SolitaireCard>(card_entity) {
                     card.set_location(CardLocation::Tableau, column);
 
-                    // Windowsソリティアの正確な配置座標
-                    let base_x = 20.0 + column as f32 * 100.0; // 左端から20px、間隔100px
-                    let base_y = 150.0 + row as f32 * 25.0; // 上から150px、重なり25px
+                    // Windowsソリティアの正確な配置座標（左端から20px・上から150px、間隔100px、重なり25px）
+                    let (base_x, base_y) = layout::tableau_position(column, row as usize);
                     card.set_display_position(base_x, base_y);
 
                     // 各列の最上位カードのみ表向き（Windowsソリティアルール）
@@ -1221,25 +1794,25 @@ impl SolitaireManager {
             let card_entity = cards[i];
             if let Some(card) = world.get_component_mut::<SolitaireCard>(card_entity) {
                 card.set_location(CardLocation::Deck, i as u32 - card_index as u32);
-                card.set_display_position(20.0, 20.0); // 左上のデッキ位置
+                card.set_display_position(layout::DECK_POSITION.0, layout::DECK_POSITION.1); // 左上のデッキ位置
                 card.flip_down(); // デッキのカードは裏向き
                 card.is_movable = false;
             }
         }
 
-        println!(
+        info!(
             "📋 Windowsクロンダイク配布完了: タブロー{}枚, デッキ{}枚",
             card_index,
             cards.len() - card_index
         );
 
         // 配置詳細をログ出力
-        println!("  タブロー配置:");
+        debug!("  タブロー配置:");
         for i in 0..7 {
-            println!("    列{}: {}枚（最上位のみ表向き）", i + 1, i + 1);
+            debug!("    列{}: {}枚（最上位のみ表向き）", i + 1, i + 1);
         }
-        println!("  デッキ: 24枚（全て裏向き）");
-        println!("  ファウンデーション: 4つの空スペース（A〜K順に積む）");
+        debug!("  デッキ: 24枚（全て裏向き）");
+        debug!("  ファウンデーション: 4つの空スペース（A〜K順に積む）");
     }
 
     /// フリーセル用のカード配布
@@ -1260,7 +1833,7 @@ impl SolitaireManager {
             }
         }
 
-        println!("📋 フリーセル配布完了: 8列に52枚配布");
+        info!("📋 フリーセル配布完了: 8列に52枚配布");
     }
 
     /// スパイダー用のカード配布
@@ -1307,7 +1880,7 @@ impl SolitaireManager {
             }
         }
 
-        println!(
+        info!(
             "📋 スパイダー配布完了: タブロー{}枚, デッキ{}枚",
             card_index,
             cards.len() - card_index
@@ -1319,72 +1892,208 @@ impl SolitaireManager {
     /// # 引数
     /// * `world` - ECSワールドへの可変参照
     /// * `game_type` - ゲームの種類
-    fn create_stacks(world: &mut World, game_type: SolitaireType) {
+    pub(crate) fn create_stacks(world: &mut World, game_type: SolitaireType) {
+        let mut stacks = Vec::new();
+
         match game_type {
             SolitaireType::Klondike => {
-                // タブロー（7列）
-                for i in 0..7 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Tableau, i, 100.0 + i as f32 * 120.0, 200.0);
-                    world.add_component(stack_entity, stack);
+                // タブロー（7列、カードを置く位置と同じ座標・重なり）
+                for i in 0..layout::TABLEAU_COLUMNS {
+                    let (x, y) = layout::tableau_position(i, 0);
+                    let mut stack = CardStack::new(CardLocation::Tableau, i, x, y);
+                    stack.card_spacing = layout::TABLEAU_FAN;
+                    stacks.push(stack);
                 }
 
                 // ファウンデーション（4組）
-                for i in 0..4 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Foundation, i, 400.0 + i as f32 * 120.0, 50.0);
-                    world.add_component(stack_entity, stack);
+                for i in 0..layout::FOUNDATION_PILES {
+                    let (x, y) = layout::foundation_position(i);
+                    stacks.push(CardStack::new(CardLocation::Foundation, i, x, y));
                 }
             }
 
             SolitaireType::FreeCell => {
                 // タブロー（8列）
                 for i in 0..8 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Tableau, i, 50.0 + i as f32 * 100.0, 200.0);
-                    world.add_component(stack_entity, stack);
+                    stacks.push(CardStack::new(
+                        CardLocation::Tableau,
+                        i,
+                        50.0 + i as f32 * 100.0,
+                        200.0,
+                    ));
                 }
 
                 // フリーセル（4つ）
                 for i in 0..4 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::FreeCell, i, 50.0 + i as f32 * 100.0, 50.0);
-                    world.add_component(stack_entity, stack);
+                    stacks.push(CardStack::new(
+                        CardLocation::FreeCell,
+                        i,
+                        50.0 + i as f32 * 100.0,
+                        50.0,
+                    ));
                 }
 
                 // ファウンデーション（4組）
                 for i in 0..4 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Foundation, i, 450.0 + i as f32 * 100.0, 50.0);
-                    world.add_component(stack_entity, stack);
+                    stacks.push(CardStack::new(
+                        CardLocation::Foundation,
+                        i,
+                        450.0 + i as f32 * 100.0,
+                        50.0,
+                    ));
                 }
             }
 
             SolitaireType::Spider => {
                 // タブロー（10列）
                 for i in 0..10 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Tableau, i, 50.0 + i as f32 * 80.0, 200.0);
-                    world.add_component(stack_entity, stack);
+                    stacks.push(CardStack::new(
+                        CardLocation::Tableau,
+                        i,
+                        50.0 + i as f32 * 80.0,
+                        200.0,
+                    ));
                 }
 
                 // ファウンデーション（8組、2デッキ分）
                 for i in 0..8 {
-                    let stack_entity = world.create_entity();
-                    let stack =
-                        CardStack::new(CardLocation::Foundation, i, 50.0 + i as f32 * 80.0, 50.0);
-                    world.add_component(stack_entity, stack);
+                    stacks.push(CardStack::new(
+                        CardLocation::Foundation,
+                        i,
+                        50.0 + i as f32 * 80.0,
+                        50.0,
+                    ));
                 }
             }
         }
 
-        println!("📚 {}用スタック作成完了", game_type.name());
+        // 山ごとに、カードのない山でも押せる置き場所を作る
+        let anchors: Vec<(CardLocation, u32, f32, f32)> = stacks
+            .iter()
+            .map(|stack| (stack.stack_type, stack.stack_index, stack.base_x, stack.base_y))
+            .collect();
+        let stack_entities = world.spawn_batch(stacks);
+        world.spawn_batch(stack_entities.into_iter().zip(anchors).map(
+            |(stack, (location, index, x, y))| PilePlaceholder {
+                stack,
+                location,
+                index,
+                x,
+                y,
+            },
+        ));
+        info!("📚 {}用スタック作成完了", game_type.name());
+    }
+
+    /// プレイヤーの操作としてデッキからカードを引く
+    ///
+    /// ゲームの引く枚数（draw_count）だけ続けて引きます。
+    /// デッキが空の場合はウェイストをデッキに戻し、デッキの周回として記録します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    ///
+    /// # 戻り値
+    /// 成功時はOk、デッキもウェイストも空の場合はエラーメッセージ
+    pub fn draw_card(world: &mut World) -> Result<(), String> {
+        let deck_has_cards = |world: &World| {
+            world
+                .query::<SolitaireCard>()
+                .any(|(_, card)| card.location_type == CardLocation::Deck)
+        };
+        let recycling = !deck_has_cards(world);
+        let draw_count = world
+            .query::<SolitaireGameState>()
+            .next()
+            .map_or(1, |(_, state)| state.draw_count.max(1));
+
+        if !Self::draw_from_deck(world) {
+            return Err("引けるカードがありません".to_string());
+        }
+
+        // 3枚引きでは続けて引く（デッキが途中で尽きた場合はそこまで）
+        if !recycling {
+            for _ in 1..draw_count {
+                if !deck_has_cards(world) {
+                    break;
+                }
+                Self::draw_from_deck(world);
+            }
+        }
+
+        if recycling {
+            let state_entity = world.query::<SolitaireGameState>().next().map(|(e, _)| e);
+            if let Some(state) =
+                state_entity.and_then(|e| world.get_component_mut::<SolitaireGameState>(e))
+            {
+                state.record_deck_turn();
+            }
+        }
+        Ok(())
+    }
+
+    /// タブロー列の一番上にある裏向きのカードをめくる
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `column` - タブローの列番号（0から開始）
+    ///
+    /// # 戻り値
+    /// 成功時はOk、めくれるカードがない場合はエラーメッセージ
+    pub fn flip_tableau_top(world: &mut World, column: u32) -> Result<(), String> {
+        let top = world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| {
+                card.location_type == CardLocation::Tableau && card.position_in_location == column
+            })
+            .max_by(|(_, a), (_, b)| a.display_y.total_cmp(&b.display_y))
+            .map(|(entity, card)| (entity, card.is_face_up));
+
+        let Some((entity, is_face_up)) = top else {
+            return Err(format!("タブロー{}にカードがありません", column + 1));
+        };
+        if is_face_up {
+            return Err(format!(
+                "タブロー{}の一番上のカードは既に表向きです",
+                column + 1
+            ));
+        }
+
+        if let Some(card) = world.get_component_mut::<SolitaireCard>(entity) {
+            card.flip_up();
+            debug!(
+                "🔄 タブロー{}のカードをめくりました: {}{}",
+                column + 1,
+                card.suit.symbol(),
+                card.rank.display()
+            );
+        }
+
+        // 裏向きのカードをめくるとREVEAL_POINTS点（手数には数えない）
+        let state_entity = world.query::<SolitaireGameState>().next().map(|(e, _)| e);
+        if let Some(state) =
+            state_entity.and_then(|e| world.get_component_mut::<SolitaireGameState>(e))
+        {
+            state.record_reveal();
+        }
+
+        // 直前の手でこの列から動かした場合は、その手でめくったことにする
+        if let Some(log) = state_entity.and_then(|e| world.get_component_mut::<MoveLog>(e)) {
+            if let Some(last) = log.moves.last_mut() {
+                if last.from == CardLocation::Tableau && last.from_index == column {
+                    last.revealed = true;
+                }
+            }
+        }
+
+        if let Some(queue) = world.get_resource_mut::<EventQueue>() {
+            queue.push(GameEvent::CardRevealed {
+                card_id: entity.id(),
+                column,
+                points: REVEAL_POINTS,
+            });
+        }
+        Ok(())
     }
 
     /// Windowsソリティア専用：デッキからカードを引く
@@ -1395,11 +2104,16 @@ impl SolitaireManager {
     /// # 戻り値
     /// カードを引けた場合true、デッキが空の場合false
     pub fn draw_from_deck(world: &mut World) -> bool {
-        // デッキのカードを探す
+        let clock = GameClock::from_world(world);
+
+        // デッキのカードを探す（ウェイストの枚数は積む位置の計算に使う）
         let mut deck_cards = Vec::new();
+        let mut waste_count = 0;
         for (entity, card) in world.query::<SolitaireCard>() {
-            if card.location_type == CardLocation::Deck {
-                deck_cards.push((entity, card.position_in_location));
+            match card.location_type {
+                CardLocation::Deck => deck_cards.push((entity, card.position_in_location)),
+                CardLocation::Waste => waste_count += 1,
+                _ => {}
             }
         }
 
@@ -1412,17 +2126,21 @@ impl SolitaireManager {
         deck_cards.sort_by_key(|(_, pos)| *pos);
         if let Some((card_entity, _)) = deck_cards.last() {
             if let Some(card) = world.get_component_mut::<SolitaireCard>(*card_entity) {
-                // ウェイストパイルに移動
-                card.set_location(CardLocation::Waste, 0);
-                card.set_display_position(140.0, 20.0); // デッキの右隣
+                let record = MoveRecord::new(card, CardLocation::Waste, waste_count, 0, &clock);
+
+                // ウェイストパイルの一番上に移動（位置は積んだ順番）
+                card.set_location(CardLocation::Waste, waste_count);
+                card.set_display_position(layout::WASTE_POSITION.0, layout::WASTE_POSITION.1); // デッキの右隣
                 card.flip_up();
                 card.is_movable = true;
 
-                println!(
+                debug!(
                     "🎴 デッキからカードを引きました: {}{}",
                     card.suit.symbol(),
                     card.rank.display()
                 );
+
+                Self::record_to_move_log(world, record);
                 return true;
             }
         }
@@ -1439,27 +2157,30 @@ impl SolitaireManager {
     /// カードを戻せた場合true、ウェイストも空の場合false
     fn recycle_waste_to_deck(world: &mut World) -> bool {
         let mut waste_cards = Vec::new();
-        for (entity, _card) in world.query::<SolitaireCard>() {
-            if _card.location_type == CardLocation::Waste {
-                waste_cards.push(entity);
+        for (entity, card) in world.query::<SolitaireCard>() {
+            if card.location_type == CardLocation::Waste {
+                waste_cards.push((entity, card.position_in_location));
             }
         }
+        // 積んだ順番（古い順）に並べる
+        waste_cards.sort_by_key(|(_, pos)| *pos);
 
         if waste_cards.is_empty() {
-            println!("⚠️ デッキもウェイストも空です");
+            warn!("⚠️ デッキもウェイストも空です");
             return false;
         }
 
-        println!(
+        info!(
             "♻️ ウェイストパイルをデッキに戻します（{}枚）",
             waste_cards.len()
         );
 
         // ウェイストのカードを逆順でデッキに戻す（Windowsソリティアの仕様）
-        for (i, card_entity) in waste_cards.iter().rev().enumerate() {
+        // 最初に引いたカードが再びデッキの一番上（position最大）になる
+        for (i, (card_entity, _)) in waste_cards.iter().rev().enumerate() {
             if let Some(card) = world.get_component_mut::<SolitaireCard>(*card_entity) {
                 card.set_location(CardLocation::Deck, i as u32);
-                card.set_display_position(20.0, 20.0);
+                card.set_display_position(layout::DECK_POSITION.0, layout::DECK_POSITION.1);
                 card.flip_down();
                 card.is_movable = false;
             }
@@ -1503,6 +2224,8 @@ impl SolitaireManager {
         card_entity: Entity,
         card: &SolitaireCard,
     ) -> bool {
+        let clock = GameClock::from_world(world);
+
         // 各ファウンデーションをチェック
         for foundation_index in 0..4 {
             // 該当するファウンデーションの最上位カードを取得
@@ -1510,16 +2233,27 @@ impl SolitaireManager {
 
             if card.can_place_on_foundation(foundation_top.as_ref()) {
                 if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(card_entity) {
-                    let foundation_x = 400.0 + foundation_index as f32 * 100.0;
+                    let (foundation_x, foundation_y) = layout::foundation_position(foundation_index);
                     card_mut.set_location(CardLocation::Foundation, foundation_index);
-                    card_mut.set_display_position(foundation_x, 20.0);
+                    card_mut.set_display_position(foundation_x, foundation_y);
 
-                    println!(
+                    info!(
                         "✨ ファウンデーション{}に自動配置: {}{}",
                         foundation_index + 1,
                         card.suit.symbol(),
                         card.rank.display()
                     );
+
+                    Self::record_to_move_log(
+                        world,
+                        MoveRecord::new(
+                            card,
+                            CardLocation::Foundation,
+                            foundation_index,
+                            10,
+                            &clock,
+                        ),
+                    );
                     return true;
                 }
             }
@@ -1529,6 +2263,8 @@ impl SolitaireManager {
 
     /// タブローへの配置を試行
     fn try_place_on_tableau(world: &mut World, card_entity: Entity, card: &SolitaireCard) -> bool {
+        let clock = GameClock::from_world(world);
+
         // 各タブロー列をチェック
         for column in 0..7 {
             let tableau_top = Self::get_tableau_top(world, column);
@@ -1543,18 +2279,22 @@ impl SolitaireManager {
                 let card_count = Self::count_tableau_cards(world, column);
 
                 if let Some(card_mut) = world.get_component_mut::<SolitaireCard>(card_entity) {
-                    let column_x = 20.0 + column as f32 * 100.0;
-                    let column_y = 150.0 + card_count as f32 * 25.0;
+                    let (column_x, column_y) = layout::tableau_position(column, card_count);
 
                     card_mut.set_location(CardLocation::Tableau, column);
                     card_mut.set_display_position(column_x, column_y);
 
-                    println!(
+                    info!(
                         "✨ タブロー列{}に自動配置: {}{}",
                         column + 1,
                         card.suit.symbol(),
                         card.rank.display()
                     );
+
+                    Self::record_to_move_log(
+                        world,
+                        MoveRecord::new(card, CardLocation::Tableau, column, 0, &clock),
+                    );
                     return true;
                 }
             }
@@ -1562,6 +2302,19 @@ impl SolitaireManager {
         false
     }
 
+    /// 移動履歴に記録を追加（タイムラインにも記録する）
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `record` - 追加する移動記録
+    pub fn record_to_move_log(world: &mut World, record: MoveRecord) {
+        let log_entity = world.query::<MoveLog>().next().map(|(e, _)| e);
+        if let Some(move_log) = log_entity.and_then(|e| world.get_component_mut::<MoveLog>(e)) {
+            move_log.push(record);
+        }
+        timeline::record(world, TimelineRecord::Move { record });
+    }
+
     /// ファウンデーションの最上位カードを取得
     fn get_foundation_top(world: &World, foundation_index: u32) -> Option<SolitaireCard> {
         let mut foundation_cards = Vec::new();
@@ -1625,7 +2378,7 @@ impl SolitaireManager {
         }
 
         if completed_foundations == 4 {
-            println!("🎉 おめでとうございます！Windowsソリティアをクリアしました！");
+            info!("🎉 おめでとうございます！Windowsソリティアをクリアしました！");
             return true;
         }
 
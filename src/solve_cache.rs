@@ -0,0 +1,363 @@
+// =============================================================================
+// 解いた配り札のキャッシュ
+// =============================================================================
+// このファイルでは、配り札のシードごとにソルバーの結果（勝ち筋の有無・難しさ・勝ち筋の手数）を
+// 覚えておくSolveCacheを実装します。日替わりの配り札や人気のシードで、
+// 時間のかかる探索を何度もやり直さないようにするためのものです。
+//
+// 仕組み：
+// - 最近使った順に並べ、上限を超えると一番長く使っていない結果から捨てる（LRU）
+// - クライアント：GameRuntimeのリソースとして上限CLIENT_CAPACITY件をメモリに持ち、
+//   WinnabilitySystemが配られた直後（0手目）の局面を調べる前に引く
+// - サーバー：上限SERVER_CAPACITY件を保存領域（storage）に短い形式（SolvedDeal::to_compact()）で
+//   保存し、再起動後も引き継ぐ。日替わりの配り札は切り替わったときに、レースの配り札は始まったときに、
+//   届いたゲーム結果の配り札は記録したときに解いておく（日替わりの配り札はDailyDealに結果を付けて送る）
+// - ゲーム結果レポートは、覚えている勝ち筋の手数を最短手数として効率を求める
+// - 勝ち筋の有無が分かった結果は、どの上限で調べ直しても変わらないためいつでも使う。
+//   分からなかった（Unknown）結果は、そのときと同じか小さい上限で調べる場合だけ使う
+// - シードから配った直後の標準のクロンダイクの盤面だけを覚える
+//   （パズル・シナリオの盤面はシードから再現できないため使わない）
+// =============================================================================
+
+use crate::ecs::{Entity, Resource, World};
+use crate::solitaire::{DeckSpec, SolitaireGameState, SolitaireManager, SolitaireType};
+use crate::solver::{Solution, Solver, Winnability};
+use crate::storage;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use ts_rs::TS;
+
+/// クライアントで覚えておく結果の上限
+pub const CLIENT_CAPACITY: usize = 64;
+
+/// サーバーで保存しておく結果の上限
+pub const SERVER_CAPACITY: usize = 10_000;
+
+/// サーバーで配り札を解くときに調べる局面数の上限（開発用バイナリのsolveと同じ）
+pub const SERVER_SEARCH_LIMIT: u32 = 100_000;
+
+/// サーバーの保存キー
+const STORAGE_KEY: &str = "solved_deals";
+
+/// 調べた局面数がこれ未満で勝ち筋が見つかった配り札は「易しい」
+const EASY_NODES: u32 = 1_000;
+
+/// 調べた局面数がこれ未満で勝ち筋が見つかった配り札は「普通」（以上は「難しい」）
+const MEDIUM_NODES: u32 = 10_000;
+
+/// 配り札の難しさ（ソルバーが勝ち筋を見つけるまでに調べた局面数から決める）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    /// 易しい
+    Easy,
+
+    /// 普通
+    Medium,
+
+    /// 難しい
+    Hard,
+}
+
+impl Difficulty {
+    /// 調べた局面数から難しさを決める
+    ///
+    /// # 引数
+    /// * `nodes` - 勝ち筋が見つかるまでに調べた局面数
+    pub fn from_nodes(nodes: u32) -> Self {
+        match nodes {
+            n if n < EASY_NODES => Difficulty::Easy,
+            n if n < MEDIUM_NODES => Difficulty::Medium,
+            _ => Difficulty::Hard,
+        }
+    }
+}
+
+/// 1つの配り札を解いた結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SolvedDeal {
+    /// 配り札のシード
+    pub seed: u64,
+
+    /// 勝ち筋の有無
+    pub winnability: Winnability,
+
+    /// 難しさ（勝ち筋が見つかった場合のみ）
+    pub difficulty: Option<Difficulty>,
+
+    /// 見つかった勝ち筋の手数（最短とは限らない、勝ち筋が見つかった場合のみ）
+    pub line_length: Option<u32>,
+
+    /// 調べた局面数の上限
+    pub search_limit: u32,
+}
+
+impl SolvedDeal {
+    /// ソルバーの探索結果から作成
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード
+    /// * `solution` - 配られた直後の局面の探索結果
+    /// * `search_limit` - 調べた局面数の上限
+    pub fn new(seed: u64, solution: &Solution, search_limit: u32) -> Self {
+        let winnable = solution.winnability == Winnability::Winnable;
+        Self {
+            seed,
+            winnability: solution.winnability,
+            difficulty: winnable.then(|| Difficulty::from_nodes(solution.nodes)),
+            line_length: solution.line_length.filter(|_| winnable),
+            search_limit,
+        }
+    }
+
+    /// 結果を短い文字列に書き出す
+    ///
+    /// サーバーでは多くの結果を保存するため、JSONより短いこの形式で1件1行に保存します。
+    ///
+    /// 形式："<シード>:<勝ち筋>:<難しさ>:<手数>:<上限>"
+    /// - 勝ち筋はW（あり）・L（なし）・?（分からない）
+    /// - 難しさはE・M・H、手数は数字（勝ち筋が見つからなかった場合はどちらも空）
+    ///
+    /// 例："12345:W:E:87:100000"、"678:?:::100000"
+    ///
+    /// # 戻り値
+    /// 書き出した文字列
+    pub fn to_compact(&self) -> String {
+        let winnability = match self.winnability {
+            Winnability::Winnable => "W",
+            Winnability::Unwinnable => "L",
+            Winnability::Unknown => "?",
+        };
+        let difficulty = match self.difficulty {
+            Some(Difficulty::Easy) => "E",
+            Some(Difficulty::Medium) => "M",
+            Some(Difficulty::Hard) => "H",
+            None => "",
+        };
+        let line_length = self.line_length.map(|length| length.to_string()).unwrap_or_default();
+        format!(
+            "{}:{}:{}:{}:{}",
+            self.seed, winnability, difficulty, line_length, self.search_limit
+        )
+    }
+
+    /// to_compact()で書き出した文字列を読み込む
+    ///
+    /// # 引数
+    /// * `text` - 書き出した文字列
+    ///
+    /// # 戻り値
+    /// 成功時はSolvedDeal、形式不正の場合はエラーメッセージ
+    pub fn from_compact(text: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = text.split(':').collect();
+        let [seed, winnability, difficulty, line_length, search_limit] = fields[..] else {
+            return Err(format!(
+                "解いた配り札の区切り（:）が{}個あります（4個必要）",
+                fields.len() - 1
+            ));
+        };
+        let number = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("解いた配り札の数値が不正です: {}", field))
+        };
+        Ok(Self {
+            seed: number(seed)?,
+            winnability: match winnability {
+                "W" => Winnability::Winnable,
+                "L" => Winnability::Unwinnable,
+                "?" => Winnability::Unknown,
+                _ => return Err(format!("勝ち筋の有無が不正です: {}", winnability)),
+            },
+            difficulty: match difficulty {
+                "E" => Some(Difficulty::Easy),
+                "M" => Some(Difficulty::Medium),
+                "H" => Some(Difficulty::Hard),
+                "" => None,
+                _ => return Err(format!("難しさが不正です: {}", difficulty)),
+            },
+            line_length: match line_length {
+                "" => None,
+                length => Some(number(length)? as u32),
+            },
+            search_limit: number(search_limit)? as u32,
+        })
+    }
+
+    /// 指定した上限で調べ直す代わりに使えるか
+    fn answers(&self, search_limit: u32) -> bool {
+        self.winnability != Winnability::Unknown || self.search_limit >= search_limit
+    }
+}
+
+/// 解いた配り札のキャッシュ（クライアントではリソースとして使う）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolveCache {
+    /// 解いた結果（最近使った順、先頭が一番新しい）
+    entries: VecDeque<SolvedDeal>,
+
+    /// 覚えておく結果の上限
+    capacity: usize,
+}
+
+impl Resource for SolveCache {}
+
+impl SolveCache {
+    /// 上限を指定して空のキャッシュを作成
+    ///
+    /// # 引数
+    /// * `capacity` - 覚えておく結果の上限（0の場合は1）
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// サーバーに保存されている結果を読み込む
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容（上限はSERVER_CAPACITY件）、なければ空のキャッシュ
+    pub fn load() -> Self {
+        let mut cache = Self::with_capacity(SERVER_CAPACITY);
+        let Some(text) = storage::load(STORAGE_KEY) else {
+            return cache;
+        };
+        for line in text.lines().take(SERVER_CAPACITY) {
+            match SolvedDeal::from_compact(line) {
+                Ok(solved) => cache.entries.push_back(solved),
+                Err(e) => warn!("⚠️ 解いた配り札を読み込めません: {}", e),
+            }
+        }
+        cache
+    }
+
+    /// 結果を保存する（1件ずつSolvedDeal::to_compact()の形式で、最近使った順に1行ずつ）
+    ///
+    /// # 戻り値
+    /// 保存成功時Ok(())、失敗時Err
+    pub fn save(&self) -> Result<(), String> {
+        let lines: Vec<String> = self.entries.iter().map(SolvedDeal::to_compact).collect();
+        storage::save(STORAGE_KEY, &lines.join("\n"))
+    }
+
+    /// シードの結果を引く（見つかった結果は最近使ったものとして先頭に移す）
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード
+    /// * `search_limit` - 調べ直す場合の局面数の上限
+    ///
+    /// # 戻り値
+    /// 調べ直す代わりに使える結果がある場合はSome、ない場合はNone
+    pub fn get(&mut self, seed: u64, search_limit: u32) -> Option<SolvedDeal> {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| entry.seed == seed && entry.answers(search_limit))?;
+        let entry = self.entries.remove(index)?;
+        self.entries.push_front(entry);
+        Some(entry)
+    }
+
+    /// 結果を覚える（同じシードの結果は置き換え、上限を超えた場合は一番長く使っていない結果を捨てる）
+    ///
+    /// # 引数
+    /// * `solved` - 解いた結果
+    pub fn insert(&mut self, solved: SolvedDeal) {
+        self.entries.retain(|entry| entry.seed != solved.seed);
+        self.entries.push_front(solved);
+        self.entries.truncate(self.capacity);
+    }
+
+    /// シードの配り札を解く（覚えている結果があれば探索しない）
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード（標準のクロンダイク）
+    /// * `search_limit` - 調べる局面数の上限
+    ///
+    /// # 戻り値
+    /// 解いた結果
+    pub fn solve(&mut self, seed: u64, search_limit: u32) -> SolvedDeal {
+        if let Some(solved) = self.get(seed, search_limit) {
+            return solved;
+        }
+        let solved = solve_seed(seed, search_limit);
+        self.insert(solved);
+        solved
+    }
+
+    /// 覚えている勝ち筋の手数を取得
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード
+    ///
+    /// # 戻り値
+    /// 勝ち筋が見つかった結果を覚えている場合はその手数、ない場合はNone
+    pub fn line_length(&mut self, seed: u64) -> Option<u32> {
+        self.get(seed, 0)?.line_length
+    }
+
+    /// 覚えている結果の数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 覚えている結果がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for SolveCache {
+    fn default() -> Self {
+        Self::with_capacity(CLIENT_CAPACITY)
+    }
+}
+
+/// シードの配り札をキャッシュを使わずに解く
+///
+/// サーバーではロックを持ったまま探索しないよう、これで解いてからSolveCache::insert()で覚えます。
+///
+/// # 引数
+/// * `seed` - 配り札のシード（標準のクロンダイク）
+/// * `search_limit` - 調べる局面数の上限
+pub fn solve_seed(seed: u64, search_limit: u32) -> SolvedDeal {
+    let mut world = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+    SolvedDeal::new(seed, &Solver::solve(&world, search_limit), search_limit)
+}
+
+/// 配られた直後の標準のクロンダイクの盤面なら、その配り札のシードを取得
+///
+/// シードから配り直すと同じ盤面になる場合だけ、キャッシュの結果を使えます。
+///
+/// # 引数
+/// * `world` - ECSワールド
+/// * `game` - ゲーム状態エンティティ
+///
+/// # 戻り値
+/// キャッシュを使える場合はシード、使えない場合（手を打った後・パズル・シナリオの盤面など）はNone
+pub fn deal_seed(world: &World, game: Entity) -> Option<u64> {
+    let state = world.get_component::<SolitaireGameState>(game)?;
+    seeded_deal(state).filter(|_| state.move_count == 0)
+}
+
+/// シードから配り直せる標準のクロンダイクのゲームなら、その配り札のシードを取得
+///
+/// 手を打った後のゲームでも、配られた直後の結果をキャッシュから引くのに使えます。
+///
+/// # 引数
+/// * `state` - ソリティアゲーム状態
+///
+/// # 戻り値
+/// シードから配り直せる場合はシード、パズル・シナリオの盤面などはNone
+pub fn seeded_deal(state: &SolitaireGameState) -> Option<u64> {
+    let seeded = state.game_type == SolitaireType::Klondike
+        && state.deck == DeckSpec::standard()
+        // シナリオから組み立てた盤面はシード0のまま
+        && state.seed != 0;
+    seeded.then_some(state.seed)
+}
@@ -0,0 +1,926 @@
+// =============================================================================
+// 勝ち筋の確認（ソルバー）
+// =============================================================================
+// このファイルでは、現在の盤面からまだ勝てるかどうかを調べるソルバーと、
+// 手が打たれるたびに確認して「勝ち筋がなくなった」ことを知らせるシステムを実装します。
+//
+// ソルバーの仕組み：
+// - 盤面をスートとランクだけの小さな局面（Position）に写し、深さ優先で手を探す
+// - 1枚ずつ引いて何度でも戻せるルールでは、デッキ・ウェイストのどのカードもいずれ使えるため、
+//   両方をまとめて「使える山（talon）」として扱う
+// - 置いても困らないカード（エースと2、反対の色の1つ小さいカードが両方とも組札にあるカード）は
+//   探索せずに組札へ置く
+// - 調べる局面数に上限を設け、超えた場合は判定を保留する（Unknown）
+//   裏向きのカードが多い序盤は保留になりやすく、カードが減るほど確実に判定できる
+// - WinnabilitySystemでは探索をフレームごとの時間予算の中で少しずつ進め（timeslice.rs）、
+//   1回の探索でフレームが止まらないようにする
+// - 配られた直後の局面は、シードごとの結果のキャッシュ（solve_cache.rs）を先に引き、
+//   覚えている結果があれば探索しない
+// - 勝ち筋の手順が必要な場合（開発用バイナリのsolve）は、局面ごとの1つ前の局面を記録して
+//   勝ち筋をたどり、同じ局面になる実際の手を1手ずつ探して打つ（Solver::play_solution()）
+//
+// 勝ち筋がなくなったと分かった場合：
+// - 通知イベントで「やり直しますか？」と確認する（GameSettingsで無効にできる）
+// - 最後に勝ち筋が残っていた手から、何手前に勝てなくなったかを記録する
+//   （ゲーム結果レポートのlost_at_moveにも残る）
+// =============================================================================
+
+use crate::ecs::{Component, Entity, System, World};
+use crate::events::{EventQueue, GameEvent, NotificationSeverity};
+use crate::game::{GameSettings, UnwinnableCheckSettings};
+use crate::hint::{BoardView, Hint, HintEngine, HintKind};
+use crate::scenario::{BoardBuilder, Scenario};
+use crate::solitaire::{
+    CardLocation, CardSuit, SolitaireCard, SolitaireGameState, SolitaireManager,
+};
+use crate::solve_cache::{self, SolveCache, SolvedDeal};
+use crate::timeslice::{self, FrameBudget, Incremental, Progress};
+use log::{debug, info};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use ts_rs::TS;
+
+/// ランクの最大値（K）
+const KING: u8 = 13;
+
+// =============================================================================
+// 確認結果の定義
+// =============================================================================
+
+/// 勝ち筋の有無
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum Winnability {
+    /// 勝てる手順が見つかった
+    Winnable,
+
+    /// どう打っても勝てない
+    Unwinnable,
+
+    /// 調べる局面数の上限に達したため分からない
+    Unknown,
+}
+
+/// 勝ち筋の確認結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+pub struct WinnabilityReport {
+    /// 勝ち筋の有無
+    pub winnability: Winnability,
+
+    /// 勝てなくなった手（何手目で勝ち筋がなくなったか、0は配られた時点、勝ち筋がある場合はNone）
+    pub lost_at_move: Option<u32>,
+
+    /// 勝てなくなってから打った手数（勝ち筋がある場合はNone）
+    pub moves_since_lost: Option<u32>,
+}
+
+/// 勝ち筋の確認状況コンポーネント
+///
+/// ゲーム状態エンティティに付き、最後に確認した手数と勝てなくなった手を保持します。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WinnabilityWatch {
+    /// 最後に確認した時点の手数（まだ確認していない場合はNone）
+    pub checked_moves: Option<u32>,
+
+    /// 勝ち筋がなくなっていないと確認できた最後の手数
+    pub last_open_move: Option<u32>,
+
+    /// 勝てなくなった手（分かっていない場合はNone）
+    pub lost_at_move: Option<u32>,
+}
+
+impl Component for WinnabilityWatch {}
+
+impl WinnabilityWatch {
+    /// 確認結果を記録する
+    ///
+    /// # 引数
+    /// * `winnability` - 確認した局面の勝ち筋の有無
+    /// * `move_count` - 確認した局面の手数
+    ///
+    /// # 戻り値
+    /// 今回の確認で初めて勝てないと分かった場合true
+    pub fn record(&mut self, winnability: Winnability, move_count: u32) -> bool {
+        self.checked_moves = Some(move_count);
+        if winnability != Winnability::Unwinnable {
+            self.last_open_move = Some(move_count);
+            return false;
+        }
+        if self.lost_at_move.is_some() {
+            return false;
+        }
+        // 勝ち筋が残っていた手の次の手で勝てなくなった（確認したことがなければ配られた時点）
+        self.lost_at_move = Some(self.last_open_move.map_or(0, |open| open + 1));
+        true
+    }
+
+    /// 記録した内容を確認結果として取得
+    ///
+    /// # 引数
+    /// * `winnability` - 確認した局面の勝ち筋の有無
+    /// * `move_count` - 現在の手数
+    pub fn report(&self, winnability: Winnability, move_count: u32) -> WinnabilityReport {
+        let lost_at_move = self
+            .lost_at_move
+            .filter(|_| winnability == Winnability::Unwinnable);
+        WinnabilityReport {
+            winnability,
+            lost_at_move,
+            moves_since_lost: lost_at_move.map(|lost| move_count.saturating_sub(lost)),
+        }
+    }
+}
+
+// =============================================================================
+// 探索用の局面
+// =============================================================================
+
+/// 探索用のカード（スート番号とランクだけを持つ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Card {
+    /// スート番号（0:ハート、1:ダイヤ、2:クラブ、3:スペード）
+    suit: u8,
+
+    /// ランク（1〜13）
+    rank: u8,
+}
+
+impl Card {
+    fn new(card: &SolitaireCard) -> Self {
+        Self {
+            suit: suit_index(card.suit),
+            rank: card.rank as u8,
+        }
+    }
+
+    fn is_red(self) -> bool {
+        self.suit < 2
+    }
+
+    /// タブローで`other`の上に置けるか
+    fn fits_on(self, other: Card) -> bool {
+        self.is_red() != other.is_red() && other.rank == self.rank + 1
+    }
+}
+
+/// 探索用のタブロー列
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Column {
+    /// 裏向きのカード（下から上の順）
+    hidden: Vec<Card>,
+
+    /// 表向きのカード（下から上の順）
+    face_up: Vec<Card>,
+}
+
+impl Column {
+    fn is_empty(&self) -> bool {
+        self.hidden.is_empty() && self.face_up.is_empty()
+    }
+
+    /// `start`から上の表向きのカードが交互の色の連続した列になっているか
+    fn is_run_from(&self, start: usize) -> bool {
+        self.face_up[start..]
+            .windows(2)
+            .all(|pair| pair[1].fits_on(pair[0]))
+    }
+}
+
+/// 探索用の局面
+///
+/// タブロー列の並びは勝敗に関係しないため、列を並べ替えて同じ局面を1つにまとめます。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Position {
+    /// タブロー各列
+    tableau: Vec<Column>,
+
+    /// スートごとに組札へ置いた最大のランク（0は空）
+    foundations: [u8; 4],
+
+    /// デッキとウェイストのカード（順番は関係ないので並べておく）
+    talon: Vec<Card>,
+}
+
+impl Position {
+    /// ワールドの盤面から局面を作る
+    fn from_world(world: &World) -> Self {
+        let view = BoardView::from_world(world);
+        let tableau = view
+            .tableau
+            .iter()
+            .map(|cards| {
+                let (face_up, hidden): (Vec<_>, Vec<_>) =
+                    cards.iter().partition(|(_, card)| card.is_face_up);
+                Column {
+                    hidden: hidden.iter().map(|(_, card)| Card::new(card)).collect(),
+                    face_up: face_up.iter().map(|(_, card)| Card::new(card)).collect(),
+                }
+            })
+            .collect();
+
+        let mut foundations = [0; 4];
+        for top in view.foundation_tops.iter().flatten() {
+            let top = Card::new(top);
+            let rank = &mut foundations[top.suit as usize];
+            *rank = (*rank).max(top.rank);
+        }
+
+        let talon = world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| {
+                matches!(card.location_type, CardLocation::Deck | CardLocation::Waste)
+            })
+            .map(|(_, card)| Card::new(card))
+            .collect();
+
+        let mut position = Self {
+            tableau,
+            foundations,
+            talon,
+        };
+        position.settle();
+        position
+    }
+
+    /// 全てのカードを組札に置き終えたか
+    fn is_solved(&self) -> bool {
+        self.talon.is_empty() && self.tableau.iter().all(Column::is_empty)
+    }
+
+    /// 組札に置けるか
+    fn can_found(&self, card: Card) -> bool {
+        self.foundations[card.suit as usize] + 1 == card.rank
+    }
+
+    /// 組札に置いても困らないか（このカードの上に置くカードが残っていない）
+    fn is_safe_to_found(&self, card: Card) -> bool {
+        let opposite = if card.is_red() { [2, 3] } else { [0, 1] };
+        self.can_found(card)
+            && (card.rank <= 2
+                || opposite
+                    .iter()
+                    .all(|&suit| self.foundations[suit] + 1 >= card.rank))
+    }
+
+    /// 手を打った後の局面を整える
+    ///
+    /// 裏向きのカードをめくり、置いても困らないカードを組札へ置き、列を並べ替えます。
+    fn settle(&mut self) {
+        loop {
+            for column in &mut self.tableau {
+                if column.face_up.is_empty() {
+                    if let Some(card) = column.hidden.pop() {
+                        column.face_up.push(card);
+                    }
+                }
+            }
+
+            let from_column = (0..self.tableau.len()).find(|&i| {
+                self.tableau[i]
+                    .face_up
+                    .last()
+                    .is_some_and(|&card| self.is_safe_to_found(card))
+            });
+            if let Some(i) = from_column {
+                let card = self.tableau[i].face_up.pop().unwrap();
+                self.foundations[card.suit as usize] = card.rank;
+                continue;
+            }
+            if let Some(i) = (0..self.talon.len()).find(|&i| self.is_safe_to_found(self.talon[i])) {
+                let card = self.talon.remove(i);
+                self.foundations[card.suit as usize] = card.rank;
+                continue;
+            }
+            break;
+        }
+        self.tableau.sort();
+        self.talon.sort();
+    }
+
+    /// 列の途中から上を動かした場合に、残ったカードを使えるか
+    ///
+    /// 残ったカードを組札に置けるか、他のカードをその上に置ける場合だけ途中から動かします
+    /// （使い道がないうちに動かしても、後で同じ手を打てるため）。
+    fn exposes_usable_card(&self, column: usize, start: usize) -> bool {
+        let Some(&exposed) = start
+            .checked_sub(1)
+            .and_then(|below| self.tableau[column].face_up.get(below))
+        else {
+            return true;
+        };
+        self.can_found(exposed)
+            || self.talon.iter().any(|card| card.fits_on(exposed))
+            || self
+                .tableau
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != column)
+                .any(|(_, other)| {
+                    (0..other.face_up.len())
+                        .any(|k| other.is_run_from(k) && other.face_up[k].fits_on(exposed))
+                })
+    }
+
+    /// 局面を見分けるためのハッシュ値
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// 次の局面を有望な順に列挙
+    ///
+    /// 組札へ置く手、裏向きのカードをめくる手、使える山からタブローへの手、その他の手の順です。
+    fn successors(&self) -> Vec<Position> {
+        let mut founding = Vec::new();
+        let mut revealing = Vec::new();
+        let mut from_talon = Vec::new();
+        let mut others = Vec::new();
+        let first_empty = self.tableau.iter().position(Column::is_empty);
+
+        for (i, column) in self.tableau.iter().enumerate() {
+            let Some(&top) = column.face_up.last() else {
+                continue;
+            };
+            if self.can_found(top) {
+                let mut next = self.clone();
+                next.tableau[i].face_up.pop();
+                next.foundations[top.suit as usize] = top.rank;
+                founding.push(next);
+            }
+
+            for start in 0..column.face_up.len() {
+                if !column.is_run_from(start) || !self.exposes_usable_card(i, start) {
+                    continue;
+                }
+                let head = column.face_up[start];
+                let whole_column = start == 0 && column.hidden.is_empty();
+                for (j, target) in self.tableau.iter().enumerate() {
+                    let fits = match target.face_up.last() {
+                        Some(&target_top) => head.fits_on(target_top),
+                        // 空き列へはKだけ、同じ局面になるので最初の空き列だけ試す
+                        None => head.rank == KING && Some(j) == first_empty && !whole_column,
+                    };
+                    if i == j || !fits {
+                        continue;
+                    }
+                    let mut next = self.clone();
+                    let run = next.tableau[i].face_up.split_off(start);
+                    next.tableau[j].face_up.extend(run);
+                    if start == 0 && !column.hidden.is_empty() {
+                        revealing.push(next);
+                    } else {
+                        others.push(next);
+                    }
+                }
+            }
+        }
+
+        for (k, &card) in self.talon.iter().enumerate() {
+            if k > 0 && self.talon[k - 1] == card {
+                continue;
+            }
+            if self.can_found(card) {
+                let mut next = self.clone();
+                next.talon.remove(k);
+                next.foundations[card.suit as usize] = card.rank;
+                founding.push(next);
+            }
+            for (j, target) in self.tableau.iter().enumerate() {
+                let fits = match target.face_up.last() {
+                    Some(&target_top) => card.fits_on(target_top),
+                    None => card.rank == KING && Some(j) == first_empty,
+                };
+                if !fits {
+                    continue;
+                }
+                let mut next = self.clone();
+                next.talon.remove(k);
+                next.tableau[j].face_up.push(card);
+                from_talon.push(next);
+            }
+        }
+
+        let mut successors = founding;
+        successors.extend(revealing);
+        successors.extend(from_talon);
+        successors.extend(others);
+        for next in &mut successors {
+            next.settle();
+        }
+        successors
+    }
+}
+
+/// スート番号を取得
+fn suit_index(suit: CardSuit) -> u8 {
+    match suit {
+        CardSuit::Hearts => 0,
+        CardSuit::Diamonds => 1,
+        CardSuit::Clubs => 2,
+        CardSuit::Spades => 3,
+    }
+}
+
+// =============================================================================
+// ソルバー
+// =============================================================================
+
+/// 局面を見分けるキー
+///
+/// 手を打った後の局面がソルバーの選んだ一手の後の局面と同じかを比べるために使います。
+/// 組札へ置いても困らないカードは置いた後の局面として扱うため、そうしたカードを
+/// 組札へ置く手や、デッキから引く手では局面が変わりません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PositionKey(u64);
+
+impl PositionKey {
+    /// ワールドの盤面のキーを取得
+    pub fn of(world: &World) -> Self {
+        Self(Position::from_world(world).fingerprint())
+    }
+}
+
+/// 勝ち筋の探索結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    /// 勝ち筋の有無
+    pub winnability: Winnability,
+
+    /// 見つかった勝ち筋の手数（最短とは限らない、勝ち筋がない場合・分からない場合はNone）
+    pub line_length: Option<u32>,
+
+    /// 勝ち筋の最初の一手を打った後の局面（勝ち筋がない場合・分からない場合はNone）
+    pub next: Option<PositionKey>,
+
+    /// 調べた（調べる予定に積んだ）局面の数
+    pub nodes: u32,
+}
+
+impl Solution {
+    /// 勝ち筋が見つからなかった結果
+    fn undecided(winnability: Winnability, nodes: usize) -> Self {
+        Self {
+            winnability,
+            line_length: None,
+            next: None,
+            nodes: nodes as u32,
+        }
+    }
+}
+
+/// 途中で止めて続きから進められる勝ち筋の探索
+///
+/// 1単位で局面を1つ調べます。Solver::solve()は最後まで一度に進め、
+/// WinnabilitySystemはフレームごとの時間予算の中で少しずつ進めます。
+pub struct SolverSearch {
+    /// 調べた（調べる予定に積んだ）局面
+    visited: HashSet<u64>,
+
+    /// これから調べる局面（局面, 手順の長さ, 最初の一手の後の局面）
+    stack: Vec<(Position, u32, Option<u64>)>,
+
+    /// 調べる局面数の上限
+    search_limit: u32,
+
+    /// 局面ごとの1つ前の局面（勝ち筋の手順を記録しない場合はNone）
+    parents: Option<HashMap<u64, u64>>,
+
+    /// 全てのカードを組札に置き終えた局面（見つかった場合）
+    solved: Option<u64>,
+}
+
+impl SolverSearch {
+    /// 現在の盤面から探索を始める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `search_limit` - 調べる局面数の上限
+    pub fn new(world: &World, search_limit: u32) -> Self {
+        let start = Position::from_world(world);
+        Self {
+            visited: HashSet::from([start.fingerprint()]),
+            stack: vec![(start, 0, None)],
+            search_limit,
+            parents: None,
+            solved: None,
+        }
+    }
+
+    /// 勝ち筋の手順を記録しながら探索を始める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `search_limit` - 調べる局面数の上限
+    fn with_line(world: &World, search_limit: u32) -> Self {
+        Self {
+            parents: Some(HashMap::new()),
+            ..Self::new(world, search_limit)
+        }
+    }
+
+    /// 見つかった勝ち筋の各手を打った後の局面を順に取得
+    ///
+    /// # 戻り値
+    /// 勝ち筋の局面（最初の局面は含まない、勝ち筋がない場合・記録していない場合は空）
+    fn line(&self) -> Vec<PositionKey> {
+        let (Some(parents), Some(mut key)) = (&self.parents, self.solved) else {
+            return Vec::new();
+        };
+        let mut line = Vec::new();
+        while let Some(&parent) = parents.get(&key) {
+            line.push(PositionKey(key));
+            key = parent;
+        }
+        line.reverse();
+        line
+    }
+}
+
+impl Incremental for SolverSearch {
+    type Output = Solution;
+
+    fn advance(&mut self, steps: u32) -> Progress<Solution> {
+        for _ in 0..steps {
+            let Some((position, depth, first)) = self.stack.pop() else {
+                return Progress::Done(Solution::undecided(
+                    Winnability::Unwinnable,
+                    self.visited.len(),
+                ));
+            };
+            if position.is_solved() {
+                self.solved = Some(position.fingerprint());
+                return Progress::Done(Solution {
+                    winnability: Winnability::Winnable,
+                    line_length: Some(depth),
+                    next: first.map(PositionKey),
+                    nodes: self.visited.len() as u32,
+                });
+            }
+            if self.visited.len() > self.search_limit as usize {
+                debug!("🧮 調べる局面数の上限に達しました: {}", self.search_limit);
+                return Progress::Done(Solution::undecided(
+                    Winnability::Unknown,
+                    self.visited.len(),
+                ));
+            }
+            let parent = position.fingerprint();
+            // 有望な手から調べるため、逆順に積む
+            for next in position.successors().into_iter().rev() {
+                let key = next.fingerprint();
+                if self.visited.insert(key) {
+                    if let Some(parents) = &mut self.parents {
+                        parents.insert(key, parent);
+                    }
+                    self.stack.push((next, depth + 1, first.or(Some(key))));
+                }
+            }
+        }
+        Progress::Pending((self.visited.len() as f32 / self.search_limit.max(1) as f32).min(1.0))
+    }
+}
+
+/// 勝ち筋を調べるソルバー
+pub struct Solver;
+
+impl Solver {
+    /// 現在の盤面から勝てるかどうかを調べる
+    ///
+    /// 裏向きのカードも含めて盤面を知っている前提で調べます。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `search_limit` - 調べる局面数の上限
+    ///
+    /// # 戻り値
+    /// 勝ち筋の有無（上限に達した場合はUnknown）
+    pub fn check(world: &World, search_limit: u32) -> Winnability {
+        Self::solve(world, search_limit).winnability
+    }
+
+    /// 現在の盤面から勝ち筋を探す
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    /// * `search_limit` - 調べる局面数の上限
+    ///
+    /// # 戻り値
+    /// 勝ち筋の有無と、見つかった場合はその手順の長さと最初の一手の後の局面
+    pub fn solve(world: &World, search_limit: u32) -> Solution {
+        timeslice::run_to_completion(&mut SolverSearch::new(world, search_limit))
+    }
+
+    /// 勝ち筋を探し、見つかった場合はその手順を最後まで打つ
+    ///
+    /// ソルバーの勝ち筋は局面の並びのため、局面ごとに同じ局面になる実際の手を探して打ちます
+    /// （デッキから引く手や、組札へ置いても困らないカードを置く手も含めて打ちます）。
+    /// 打った手は通常の移動と同様に移動履歴（MoveLog）に残ります。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `search_limit` - 調べる局面数の上限
+    ///
+    /// # 戻り値
+    /// 成功時は探索結果（勝ち筋がない・分からない場合は何も打たない）、
+    /// 勝ち筋の手を打てなかった場合はエラーメッセージ
+    pub fn play_solution(world: &mut World, search_limit: u32) -> Result<Solution, String> {
+        let mut search = SolverSearch::with_line(world, search_limit);
+        let solution = timeslice::run_to_completion(&mut search);
+        if solution.winnability != Winnability::Winnable {
+            return Ok(solution);
+        }
+
+        for (step, key) in (1..).zip(search.line()) {
+            play_until(world, |world| PositionKey::of(world) == key)
+                .map_err(|e| format!("勝ち筋の{}手目を打てません: {}", step, e))?;
+        }
+        // 残りは組札へ置いても困らないカードだけ
+        play_until(world, SolitaireManager::check_windows_solitaire_win)
+            .map_err(|e| format!("最後のカードを組札へ置けません: {}", e))?;
+        Ok(solution)
+    }
+
+    /// ゲームの現在の局面を確認し、確認状況に記録する
+    ///
+    /// 初めて勝てないと分かった場合は、設定に応じて「やり直しますか？」と通知します。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    /// * `entity` - ゲーム状態エンティティ
+    /// * `settings` - 勝ち筋の確認の設定
+    ///
+    /// # 戻り値
+    /// 確認結果（ゲーム状態がない場合はNone）
+    pub fn check_game(
+        world: &mut World,
+        entity: Entity,
+        settings: UnwinnableCheckSettings,
+    ) -> Option<WinnabilityReport> {
+        let move_count = world
+            .get_component::<SolitaireGameState>(entity)?
+            .move_count;
+        let winnability = Self::check(world, settings.search_limit);
+        Some(record_check(
+            world,
+            entity,
+            winnability,
+            move_count,
+            settings,
+        ))
+    }
+}
+
+/// 目標の局面に着くまで実際の手を打つ
+///
+/// 目標の局面になる手があれば打ち、なければ局面の変わらない手（組札へ置いても困らない
+/// カードを置く手、デッキから引く手）を打って探します。デッキを一巡しても見つからない場合は
+/// エラーにします。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `reached` - 目標の局面に着いたかを判定する関数
+fn play_until(world: &mut World, reached: impl Fn(&World) -> bool) -> Result<(), String> {
+    let mut draws = 0;
+    while !reached(world) {
+        let key = PositionKey::of(world);
+        let moves: Vec<Hint> = HintEngine::ranked_moves(world)
+            .into_iter()
+            .filter(|hint| hint.kind == HintKind::Move)
+            .collect();
+        let scenario = Scenario::from_world(world);
+
+        let found = moves
+            .iter()
+            .find(|hint| try_move(&scenario, hint).is_some_and(|after| reached(&after)))
+            .or_else(|| {
+                moves.iter().find(|hint| {
+                    hint.to
+                        .is_some_and(|to| to.location == CardLocation::Foundation)
+                        && try_move(&scenario, hint)
+                            .is_some_and(|after| PositionKey::of(&after) == key)
+                })
+            });
+        if let Some(hint) = found {
+            play_move(world, hint)?;
+            draws = 0;
+            continue;
+        }
+
+        if draws > HintEngine::draw_cycle_length(world) {
+            return Err("同じ局面になる手が見つかりません".to_string());
+        }
+        SolitaireManager::draw_card(world)?;
+        draws += 1;
+    }
+    Ok(())
+}
+
+/// シナリオの盤面を別のワールドに作り、手を打った後のワールドを取得
+///
+/// # 戻り値
+/// 打てた場合は打った後のワールド、打てない場合はNone
+fn try_move(scenario: &Scenario, hint: &Hint) -> Option<World> {
+    let mut world = World::new();
+    BoardBuilder::from_scenario(scenario.clone())
+        .build(&mut world)
+        .ok()?;
+    play_move(&mut world, hint).ok()?;
+    Some(world)
+}
+
+/// ヒントの手を場所の指定で打つ（ヒントを作ったのとは別のワールドでも打てる）
+fn play_move(world: &mut World, hint: &Hint) -> Result<(), String> {
+    let (Some(from), Some(to)) = (hint.from, hint.to) else {
+        return Err("移動元・移動先のない手です".to_string());
+    };
+    HintEngine::move_cards(world, from, hint.card_count, to)
+}
+
+/// 確認結果を確認状況に記録し、初めて勝てないと分かった場合は通知する
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - ゲーム状態エンティティ
+/// * `winnability` - 確認した局面の勝ち筋の有無
+/// * `move_count` - 確認した局面の手数
+/// * `settings` - 勝ち筋の確認の設定
+fn record_check(
+    world: &mut World,
+    entity: Entity,
+    winnability: Winnability,
+    move_count: u32,
+    settings: UnwinnableCheckSettings,
+) -> WinnabilityReport {
+    let mut watch = world
+        .get_component::<WinnabilityWatch>(entity)
+        .copied()
+        .unwrap_or_default();
+    let newly_lost = watch.record(winnability, move_count);
+    let report = watch.report(winnability, move_count);
+    world.add_component(entity, watch);
+
+    if newly_lost {
+        info!("🪦 勝ち筋がなくなりました: {:?}", report);
+        if settings.notify {
+            notify_lost(world, &report);
+        }
+    }
+    report
+}
+
+/// 勝ち筋がなくなったことを通知
+fn notify_lost(world: &mut World, report: &WinnabilityReport) {
+    let message = match report.lost_at_move {
+        Some(0) | None => "🪦 この配り札には勝ち筋がありません。やり直しますか？".to_string(),
+        Some(lost) => format!(
+            "🪦 {}手目から勝ち筋がなくなっています（{}手前）。やり直しますか？",
+            lost,
+            report.moves_since_lost.unwrap_or(0)
+        ),
+    };
+    if let Some(events) = world.get_resource_mut::<EventQueue>() {
+        events.push(GameEvent::Notification {
+            severity: NotificationSeverity::Warning,
+            message,
+            auto_dismiss_ms: None,
+        });
+    }
+}
+
+// =============================================================================
+// 勝ち筋の確認システム
+// =============================================================================
+
+/// 進行中の勝ち筋の確認コンポーネント
+///
+/// 時間予算の中で終わらなかった探索を、次のフレームまでゲーム状態エンティティに保持します。
+struct PendingCheck {
+    /// 確認を始めた時点の手数
+    move_count: u32,
+
+    /// 結果をキャッシュに覚える配り札のシード（配られた直後の局面でない場合はNone）
+    seed: Option<u64>,
+
+    /// 途中まで進めた探索
+    search: SolverSearch,
+
+    /// 探索を進めたフレーム数
+    frames: u32,
+}
+
+impl Component for PendingCheck {}
+
+/// 勝ち筋の確認システム
+///
+/// 設定した手数ごとに現在の局面を確認し、勝てなくなったら通知します。
+/// 一度勝てないと分かったゲームはそれ以上確認しません。
+/// 探索はフレームごとの時間予算（FrameBudget）の中で進め、終わらなければ次のフレームで続けます。
+/// 複数のフレームにまたがった探索は、進み具合をSearchProgressイベントで知らせます。
+pub struct WinnabilitySystem;
+
+impl System for WinnabilitySystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let settings = world
+            .get_resource::<GameSettings>()
+            .map(|settings| settings.unwinnable_check)
+            .unwrap_or_default();
+        if settings.check_every_moves == 0 {
+            return;
+        }
+        let slice = FrameBudget::of(world).start();
+
+        let due: Vec<(Entity, u32)> = world
+            .query::<SolitaireGameState>()
+            .filter(|(_, state)| !state.is_completed)
+            .filter(|(entity, state)| {
+                let watch = world
+                    .get_component::<WinnabilityWatch>(*entity)
+                    .copied()
+                    .unwrap_or_default();
+                !world.has_component::<PendingCheck>(*entity)
+                    && watch.lost_at_move.is_none()
+                    && watch.checked_moves.is_none_or(|checked| {
+                        state.move_count >= checked + settings.check_every_moves
+                    })
+            })
+            .map(|(entity, state)| (entity, state.move_count))
+            .collect();
+        for (entity, move_count) in due {
+            let seed = solve_cache::deal_seed(world, entity);
+            let cached = seed.and_then(|seed| {
+                world
+                    .get_resource_mut::<SolveCache>()?
+                    .get(seed, settings.search_limit)
+            });
+            if let Some(solved) = cached {
+                debug!(
+                    "📚 解いた配り札のキャッシュを使いました: シード{}",
+                    solved.seed
+                );
+                record_check(world, entity, solved.winnability, move_count, settings);
+                continue;
+            }
+
+            let search = SolverSearch::new(world, settings.search_limit);
+            world.add_component(
+                entity,
+                PendingCheck {
+                    move_count,
+                    seed,
+                    search,
+                    frames: 0,
+                },
+            );
+        }
+
+        let pending: Vec<Entity> = world
+            .query::<PendingCheck>()
+            .map(|(entity, _)| entity)
+            .collect();
+        for entity in pending {
+            let Some(mut check) = world.remove_component::<PendingCheck>(entity) else {
+                continue;
+            };
+            check.frames += 1;
+            match timeslice::run_until(&mut check.search, &slice) {
+                Progress::Done(solution) => {
+                    if check.frames > 1 {
+                        debug!("🧮 勝ち筋の確認に{}フレームかかりました", check.frames);
+                        push_progress(world, 1.0);
+                    }
+                    if let (Some(seed), Some(cache)) =
+                        (check.seed, world.get_resource_mut::<SolveCache>())
+                    {
+                        cache.insert(SolvedDeal::new(seed, &solution, settings.search_limit));
+                    }
+                    record_check(
+                        world,
+                        entity,
+                        solution.winnability,
+                        check.move_count,
+                        settings,
+                    );
+                }
+                Progress::Pending(progress) => {
+                    push_progress(world, progress);
+                    world.add_component(entity, check);
+                }
+            }
+        }
+    }
+}
+
+/// 勝ち筋の確認の進み具合を知らせる
+fn push_progress(world: &mut World, progress: f32) {
+    if let Some(events) = world.get_resource_mut::<EventQueue>() {
+        events.push(GameEvent::SearchProgress {
+            search: "winnability".to_string(),
+            progress,
+        });
+    }
+}
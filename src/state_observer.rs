@@ -0,0 +1,131 @@
+// =============================================================================
+// ゲーム状態の変化の監視
+// =============================================================================
+// このファイルでは、SolitaireGameStateのスコア・手数・経過時間・デッキを戻した回数を
+// 毎フレーム前回の値と比べ、変わったものをゲームイベントとして送るシステムを実装します。
+//
+// 送るイベント：
+// - ScoreChanged：スコアが変わった（移動・デッキを戻した減点・最終スコアの計算）
+// - MovesChanged：手数が変わった
+// - TimerTick：ゲーム中に経過時間が1秒進んだ
+// - DeckRecycled：ウェイストをデッキに戻した
+//
+// 仕組み：
+// - 前回見た値はゲーム状態エンティティのGameStateWatchに保持する
+// - イベントはEventQueue（JavaScriptのコールバック）とStateChanges（ネットワークへの反映）の
+//   両方に追加する。GameRuntimeはStateChangesを見て、スコアが変わったときだけ他のプレイヤーに送る
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Component, Entity, MessageQueue, System, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::solitaire::SolitaireGameState;
+use log::debug;
+
+/// ネットワークへ反映するためのゲーム状態の変化（リソース）
+///
+/// GameStateObserverSystemがEventQueueと同じイベントを追加し、GameRuntimeがフレームの最後に取り出します。
+pub type StateChanges = MessageQueue<GameEvent>;
+
+/// 前回見たゲーム状態コンポーネント
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GameStateWatch {
+    /// 前回見たスコア
+    pub score: u32,
+
+    /// 前回見た手数
+    pub move_count: u32,
+
+    /// 前回見た経過時間（秒）
+    pub elapsed_seconds: u64,
+
+    /// 前回見たデッキを戻した回数
+    pub deck_turns: u32,
+}
+
+impl Component for GameStateWatch {}
+
+impl GameStateWatch {
+    /// 前回見た値と比べ、変わったもののイベントを作る
+    ///
+    /// # 引数
+    /// * `state` - 現在のゲーム状態
+    /// * `elapsed_seconds` - 現在の経過時間（秒）
+    ///
+    /// # 戻り値
+    /// 発生順のイベント（変わったものがなければ空）
+    pub fn observe(&mut self, state: &SolitaireGameState, elapsed_seconds: u64) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        if state.deck_turns > self.deck_turns {
+            events.push(GameEvent::DeckRecycled {
+                deck_turns: state.deck_turns,
+            });
+        }
+        if state.move_count != self.move_count {
+            events.push(GameEvent::MovesChanged {
+                move_count: state.move_count,
+            });
+        }
+        if state.score != self.score {
+            events.push(GameEvent::ScoreChanged {
+                score: state.score,
+                previous: self.score,
+            });
+        }
+        if elapsed_seconds != self.elapsed_seconds && !state.is_completed {
+            events.push(GameEvent::TimerTick { elapsed_seconds });
+        }
+
+        *self = Self {
+            score: state.score,
+            move_count: state.move_count,
+            elapsed_seconds,
+            deck_turns: state.deck_turns,
+        };
+        events
+    }
+}
+
+/// ゲーム状態監視システム
+///
+/// ゲーム状態の変化をイベントとしてJavaScriptとネットワークへ送ります。
+/// ゲーム状態を変えるシステム（移動・進行チェック・結果作成）より後に登録してください。
+pub struct GameStateObserverSystem;
+
+impl System for GameStateObserverSystem {
+    fn update(&mut self, world: &mut World, _delta_time: f64) {
+        let clock = GameClock::from_world(world);
+        let games: Vec<(Entity, SolitaireGameState)> = world
+            .query::<SolitaireGameState>()
+            .map(|(entity, state)| (entity, state.clone()))
+            .collect();
+
+        let mut events = Vec::new();
+        for (entity, state) in games {
+            let mut watch = world
+                .get_component::<GameStateWatch>(entity)
+                .copied()
+                .unwrap_or_default();
+            events.extend(watch.observe(&state, state.elapsed_seconds(&clock)));
+            world.add_component(entity, watch);
+        }
+        if events.is_empty() {
+            return;
+        }
+
+        if let Some(changes) = world.get_resource_mut::<StateChanges>() {
+            for event in &events {
+                if changes.push(event.clone()).is_err() {
+                    debug!("🗑️ ゲーム状態の変化のキューが上限に達しました");
+                    break;
+                }
+            }
+        }
+        if let Some(queue) = world.get_resource_mut::<EventQueue>() {
+            for event in events {
+                queue.push(event);
+            }
+        }
+    }
+}
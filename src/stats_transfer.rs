@@ -0,0 +1,165 @@
+// =============================================================================
+// 成績の書き出しと読み込み（端末間の引き継ぎ）
+// =============================================================================
+// このファイルでは、同期アカウント（セッショントークンを持つプレイヤー）の実績と通算成績を
+// サーバーが署名付きのJSONに書き出し、別の端末で読み込んでまとめるためのStatsExportを実装します。
+//
+// まとめ方（AchievementStore::merge）：
+// - ゲーム数・勝利数は足し合わせる（同じ出どころから前にまとめた分は数え直さない）
+// - 連勝数・最高スコアは大きい方、最短クリア時間は短い方を残す
+// - 実績はどちらかで解除していれば解除済み（解除した時刻は早い方）
+// - 同じ書き出し（export_id）を2回読み込んでも数え直さない
+//
+// 改ざんの検出：
+// - 書き出すのはサーバーが記録している同期アカウントの成績だけで、クライアントの送った成績には署名しない
+//   （SignStats → StatsSigned）
+// - 署名はサーバーだけが持つ鍵によるHMAC-SHA256のため、クライアントでは作れない
+// - 署名のない書き出しは読み込まず、署名付きの書き出しもサーバーで確かめてから読み込む
+//   （VerifyStats → StatsVerified）
+// =============================================================================
+
+use crate::achievements::AchievementStore;
+use crate::rng::Rng;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+/// 現在の書き出しの形式のバージョン
+pub const STATS_EXPORT_VERSION: u32 = 2;
+
+/// 受け付ける書き出しの最大サイズ（バイト、通信メッセージに収まる大きさ）
+pub const MAX_STATS_EXPORT_BYTES: usize = 16 * 1024;
+
+/// 署名に使うHMAC-SHA256
+type HmacSha256 = Hmac<Sha256>;
+
+/// 実績・通算成績の書き出し
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatsExport {
+    /// 書き出しの形式のバージョン
+    pub format_version: u32,
+
+    /// 書き出しのID（同じ書き出しを2回まとめないために使う）
+    pub export_id: String,
+
+    /// 成績の出どころ（同期アカウントのID、同じ出どころの成績を数え直さないために使う）
+    pub source_id: String,
+
+    /// 書き出した時刻（UNIX時刻のミリ秒）
+    pub exported_at: u64,
+
+    /// 実績と通算成績
+    pub achievements: AchievementStore,
+
+    /// サーバーの署名（16進数、署名していない場合はNone）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl StatsExport {
+    /// 実績・通算成績から署名なしの書き出しを作成
+    ///
+    /// # 引数
+    /// * `achievements` - 実績と通算成績
+    /// * `source_id` - 成績の出どころ（同期アカウントのID）
+    /// * `exported_at` - 書き出した時刻（UNIX時刻のミリ秒）
+    pub fn new(achievements: &AchievementStore, source_id: &str, exported_at: u64) -> Self {
+        Self {
+            format_version: STATS_EXPORT_VERSION,
+            export_id: format!("{:016x}", Rng::from_entropy().next_u64()),
+            source_id: source_id.to_string(),
+            exported_at,
+            achievements: achievements.clone(),
+            signature: None,
+        }
+    }
+
+    /// JSON文字列から読み込む
+    ///
+    /// # 引数
+    /// * `json` - 書き出しのJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はStatsExport、大きすぎる・形式不正・未対応のバージョンの場合はエラーメッセージ
+    pub fn parse(json: &str) -> Result<Self, String> {
+        if json.len() > MAX_STATS_EXPORT_BYTES {
+            return Err(format!(
+                "成績の書き出しが大きすぎます（{}バイト、上限{}バイト）",
+                json.len(),
+                MAX_STATS_EXPORT_BYTES
+            ));
+        }
+        let export: Self = serde_json::from_str(json)
+            .map_err(|e| format!("成績の書き出しの形式が不正です: {}", e))?;
+        if export.format_version != STATS_EXPORT_VERSION {
+            return Err(format!(
+                "成績の書き出しのバージョン{}には対応していません",
+                export.format_version
+            ));
+        }
+        Ok(export)
+    }
+
+    /// JSON文字列に変換
+    ///
+    /// # 戻り値
+    /// 成功時はJSON文字列、失敗時はエラーメッセージ
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string(self)
+            .map_err(|e| format!("成績の書き出しのシリアライゼーション失敗: {}", e))
+    }
+
+    /// 鍵で署名する（既にある署名は置き換える）
+    ///
+    /// # 引数
+    /// * `key` - サーバーの署名鍵
+    ///
+    /// # 戻り値
+    /// 成功時Ok(())、失敗時はエラーメッセージ
+    pub fn sign(&mut self, key: &[u8]) -> Result<(), String> {
+        let mac = self.mac(key)?.finalize().into_bytes();
+        self.signature = Some(mac.iter().map(|byte| format!("{:02x}", byte)).collect());
+        Ok(())
+    }
+
+    /// 署名が鍵と内容に合っているか確かめる
+    ///
+    /// # 引数
+    /// * `key` - サーバーの署名鍵
+    ///
+    /// # 戻り値
+    /// 正しい署名の場合Ok(())、署名がない・内容が書き換えられている場合はエラーメッセージ
+    pub fn verify(&self, key: &[u8]) -> Result<(), String> {
+        let signature = self
+            .signature
+            .as_deref()
+            .ok_or_else(|| "署名がありません".to_string())?;
+        let bytes = decode_hex(signature).ok_or_else(|| "署名の形式が不正です".to_string())?;
+        self.mac(key)?
+            .verify_slice(&bytes)
+            .map_err(|_| "署名が一致しません（内容が書き換えられています）".to_string())
+    }
+
+    /// 署名を除いた内容のHMACを計算する
+    fn mac(&self, key: &[u8]) -> Result<HmacSha256, String> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        let mut mac =
+            HmacSha256::new_from_slice(key).map_err(|e| format!("署名鍵が不正です: {}", e))?;
+        mac.update(unsigned.to_json()?.as_bytes());
+        Ok(mac)
+    }
+}
+
+/// 16進数の文字列をバイト列に変換
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
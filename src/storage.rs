@@ -0,0 +1,153 @@
+// =============================================================================
+// ローカル保存領域
+// =============================================================================
+// このファイルでは、実績や統計情報などを端末内に保存するための
+// シンプルなキー・バリュー型の保存領域を実装します。
+//
+// 保存先：
+// - WebAssembly環境：ブラウザのlocalStorage
+// - ネイティブ環境：カレントディレクトリのsave_data/<キー>.json
+//   （テストではuse_temp_dir()でプロセスごとの一時ディレクトリに変える）
+// =============================================================================
+
+/// 保存データのキーに付ける接頭辞（他のアプリのデータと衝突しないように）
+const KEY_PREFIX: &str = "ecs_wasm_solitaire";
+
+/// ネイティブ環境での保存先ディレクトリ
+#[cfg(not(feature = "wasm"))]
+const DATA_DIR: &str = "save_data";
+
+/// use_temp_dir()で変えた保存先ディレクトリ（変えていない場合はDATA_DIRを使う）
+#[cfg(not(feature = "wasm"))]
+static TEMP_DATA_DIR: std::sync::OnceLock<std::path::PathBuf> = std::sync::OnceLock::new();
+
+/// 保存先をプロセスごとの一時ディレクトリに変える（テスト用）
+///
+/// 保存データを読み書きするテストの最初に呼び、実際のsave_data/に書き込まないようにします。
+/// 最初に呼んだときに、同じプロセスIDで残っていた古いディレクトリを消します。
+#[cfg(not(feature = "wasm"))]
+pub fn use_temp_dir() {
+    TEMP_DATA_DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("{}-test-{}", KEY_PREFIX, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    });
+}
+
+/// 保存先をプロセスごとの一時ディレクトリに変える（テスト用）
+///
+/// WebAssembly環境ではlocalStorageに保存するため何もしません。
+#[cfg(feature = "wasm")]
+pub fn use_temp_dir() {}
+
+/// 保存されている文字列を読み込む
+///
+/// # 引数
+/// * `key` - 保存データのキー
+///
+/// # 戻り値
+/// データが存在する場合はSome(文字列)、存在しない・読み込めない場合はNone
+#[cfg(feature = "wasm")]
+pub fn load(key: &str) -> Option<String> {
+    let storage = web_sys::window()?.local_storage().ok()??;
+    storage.get_item(&format!("{}.{}", KEY_PREFIX, key)).ok()?
+}
+
+/// 文字列を保存する
+///
+/// # 引数
+/// * `key` - 保存データのキー
+/// * `value` - 保存する文字列
+///
+/// # 戻り値
+/// 保存成功時Ok(())、失敗時Err
+#[cfg(feature = "wasm")]
+pub fn save(key: &str, value: &str) -> Result<(), String> {
+    let storage = web_sys::window()
+        .ok_or("windowが取得できません")?
+        .local_storage()
+        .map_err(|e| format!("localStorageにアクセスできません: {:?}", e))?
+        .ok_or("localStorageが利用できません")?;
+
+    storage
+        .set_item(&format!("{}.{}", KEY_PREFIX, key), value)
+        .map_err(|e| format!("localStorageへの保存失敗: {:?}", e))
+}
+
+/// 保存されている文字列を消す
+///
+/// # 引数
+/// * `key` - 保存データのキー
+///
+/// # 戻り値
+/// 消去成功時（データが存在しなかった場合も含む）Ok(())、失敗時Err
+#[cfg(feature = "wasm")]
+pub fn remove(key: &str) -> Result<(), String> {
+    let storage = web_sys::window()
+        .ok_or("windowが取得できません")?
+        .local_storage()
+        .map_err(|e| format!("localStorageにアクセスできません: {:?}", e))?
+        .ok_or("localStorageが利用できません")?;
+
+    storage
+        .remove_item(&format!("{}.{}", KEY_PREFIX, key))
+        .map_err(|e| format!("localStorageからの削除失敗: {:?}", e))
+}
+
+/// 保存されている文字列を読み込む
+///
+/// # 引数
+/// * `key` - 保存データのキー
+///
+/// # 戻り値
+/// データが存在する場合はSome(文字列)、存在しない・読み込めない場合はNone
+#[cfg(not(feature = "wasm"))]
+pub fn load(key: &str) -> Option<String> {
+    std::fs::read_to_string(file_path(key)).ok()
+}
+
+/// 文字列を保存する
+///
+/// # 引数
+/// * `key` - 保存データのキー
+/// * `value` - 保存する文字列
+///
+/// # 戻り値
+/// 保存成功時Ok(())、失敗時Err
+#[cfg(not(feature = "wasm"))]
+pub fn save(key: &str, value: &str) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir())
+        .map_err(|e| format!("保存ディレクトリの作成失敗: {}", e))?;
+    std::fs::write(file_path(key), value).map_err(|e| format!("ファイルへの保存失敗: {}", e))
+}
+
+/// 保存されている文字列を消す
+///
+/// # 引数
+/// * `key` - 保存データのキー
+///
+/// # 戻り値
+/// 消去成功時（データが存在しなかった場合も含む）Ok(())、失敗時Err
+#[cfg(not(feature = "wasm"))]
+pub fn remove(key: &str) -> Result<(), String> {
+    match std::fs::remove_file(file_path(key)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(format!("保存ファイルの削除失敗: {}", e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 保存先ディレクトリを取得（ネイティブ環境用）
+#[cfg(not(feature = "wasm"))]
+fn data_dir() -> &'static std::path::Path {
+    TEMP_DATA_DIR
+        .get()
+        .map_or(std::path::Path::new(DATA_DIR), |dir| dir.as_path())
+}
+
+/// キーに対応する保存ファイルのパスを取得（ネイティブ環境用）
+#[cfg(not(feature = "wasm"))]
+fn file_path(key: &str) -> std::path::PathBuf {
+    data_dir().join(format!("{}.{}.json", KEY_PREFIX, key))
+}
@@ -0,0 +1,180 @@
+// =============================================================================
+// カードの裏面とテーブルのテーマ
+// =============================================================================
+// このファイルでは、プレイヤーが選んだカードの裏面のデザインとテーブルの配色を
+// Themeリソースとして保持します。テーマは設定（settings.rsのPreferences）の一部として
+// 端末内に保存され、次回の起動に引き継がれます。
+//
+// 仕組み：
+// - JavaScript側はset_theme()でテーマを変え、get_solitaire_state()のthemeを見て描画する
+// - フロントエンドはthemeのcard_back・tableの値からCSSクラス名（card-back-ocean、table-midnightなど）を作り、
+//   bodyに付けて描画を切り替える
+// - 見えにくいプレイヤー向けに、4色のスート・大きなランク表示・スートごとの模様・高コントラストを選べる
+//   （カードごとの色と模様はget_solitaire_state()のカードのcolor・patternに反映される）
+// - share_with_roomを有効にすると、マルチプレイで同じルームの他のプレイヤーにも
+//   カードの裏面が伝わる（CardBackChanged、スコアボードのcard_back）
+// =============================================================================
+
+use crate::ecs::Resource;
+use crate::solitaire::CardSuit;
+use crate::storage;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// 以前のバージョンのテーマの保存キー
+const STORAGE_KEY: &str = "theme";
+
+/// 大きなランク表示の場合に、ランクとスートの記号を拡大する倍率
+pub const LARGE_PRINT_SCALE: f32 = 1.6;
+
+/// カードの裏面のデザイン
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum CardBack {
+    /// 青の格子模様（標準）
+    #[default]
+    Classic,
+
+    /// 赤の格子模様
+    Crimson,
+
+    /// 市松模様
+    Checkered,
+
+    /// 波模様
+    Ocean,
+}
+
+/// テーブルの配色
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum TableTheme {
+    /// 緑のフェルト（標準）
+    #[default]
+    GreenFelt,
+
+    /// 青のフェルト
+    BlueFelt,
+
+    /// 木目
+    Wood,
+
+    /// 暗い配色
+    Midnight,
+}
+
+/// スートの記号・ランクを描く色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SuitColor {
+    Red,
+    Black,
+    Blue,
+    Green,
+}
+
+/// 色の違いが分かりにくいプレイヤー向けに、スートごとにカードの表面を塗る模様
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SuitPattern {
+    /// 水玉
+    Dots,
+
+    /// 斜めの縞
+    Stripes,
+
+    /// 波線
+    Waves,
+
+    /// 格子
+    Crosshatch,
+}
+
+/// 選んでいるテーマ（リソース）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+#[serde(default)]
+pub struct Theme {
+    /// カードの裏面のデザイン
+    pub card_back: CardBack,
+
+    /// テーブルの配色
+    pub table: TableTheme,
+
+    /// マルチプレイで同じルームの他のプレイヤーにカードの裏面を見せるか
+    pub share_with_room: bool,
+
+    /// 4色のスート（ダイヤを青、クラブを緑にして赤・黒の2色と見分けやすくする）
+    pub four_color_suits: bool,
+
+    /// ランクとスートの記号を大きく表示する（LARGE_PRINT_SCALE倍）
+    pub large_print: bool,
+
+    /// スートごとの模様でカードの表面を塗る
+    pub pattern_fills: bool,
+
+    /// 高コントラストの配色（白地に濃い色の文字・太い枠線）
+    pub high_contrast: bool,
+}
+
+impl Resource for Theme {}
+
+impl Theme {
+    /// 以前のバージョンで保存したテーマを読み込む
+    ///
+    /// テーマは現在は設定（Preferences）の一部として保存します。
+    /// 設定がまだ保存されていない場合に、以前のテーマを引き継ぐために使います。
+    ///
+    /// # 戻り値
+    /// 保存データがあればその内容、なければ標準のテーマ
+    pub fn load() -> Self {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// スートの記号・ランクを描く色
+    ///
+    /// # 引数
+    /// * `suit` - カードのスート
+    pub fn suit_color(&self, suit: CardSuit) -> SuitColor {
+        match (suit, self.four_color_suits) {
+            (CardSuit::Hearts, _) | (CardSuit::Diamonds, false) => SuitColor::Red,
+            (CardSuit::Spades, _) | (CardSuit::Clubs, false) => SuitColor::Black,
+            (CardSuit::Diamonds, true) => SuitColor::Blue,
+            (CardSuit::Clubs, true) => SuitColor::Green,
+        }
+    }
+
+    /// カードの表面を塗る模様（模様を使わない設定の場合はNone）
+    ///
+    /// # 引数
+    /// * `suit` - カードのスート
+    pub fn suit_pattern(&self, suit: CardSuit) -> Option<SuitPattern> {
+        self.pattern_fills.then_some(match suit {
+            CardSuit::Hearts => SuitPattern::Dots,
+            CardSuit::Diamonds => SuitPattern::Stripes,
+            CardSuit::Clubs => SuitPattern::Waves,
+            CardSuit::Spades => SuitPattern::Crosshatch,
+        })
+    }
+
+    /// ランクとスートの記号を描く大きさの倍率
+    pub fn rank_scale(&self) -> f32 {
+        if self.large_print {
+            LARGE_PRINT_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    /// 他のプレイヤーに見せるカードの裏面（見せない設定の場合はNone）
+    pub fn shared_card_back(&self) -> Option<CardBack> {
+        self.share_with_room.then_some(self.card_back)
+    }
+}
@@ -0,0 +1,123 @@
+// =============================================================================
+// クライアントとサーバーの時刻合わせ
+// =============================================================================
+// このファイルでは、Ping/Pongのやり取りからサーバーの時計とのずれ（オフセット）を
+// NTPと同じ方法で推定するClockSyncと、クライアントの時刻で送られてきた
+// タイムスタンプをサーバーの時刻に直す関数を実装します。
+//
+// 仕組み：
+// - クライアントはPingに送信時刻（自分の時計）を入れ、サーバーはPongに受信時刻を入れて返す
+// - Pongを受け取った時刻との中間がサーバーの時刻に対応するとみなし、
+//   オフセット = サーバーの時刻 - (送信時刻 + 受信時刻) / 2 とする
+// - 往復時間が短いほど誤差が小さいため、直近SAMPLE_WINDOW回のうち
+//   往復時間が最も短かった測定値を使う
+// - クライアントは推定したオフセットを次のPingでサーバーに伝え、サーバーは
+//   MousePosition / GameActionのタイムスタンプをサーバーの時刻に直してから配信する
+//
+// 時刻はすべてUNIX時刻（ミリ秒）です。
+// =============================================================================
+
+use std::collections::VecDeque;
+
+/// オフセットの推定に使う直近の測定の数
+const SAMPLE_WINDOW: usize = 8;
+
+/// サーバーの時刻に直したタイムスタンプとして受け付ける古さの上限（ミリ秒）
+pub const MAX_TIMESTAMP_AGE_MS: u64 = 10_000;
+
+/// 1回分の測定結果
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OffsetSample {
+    /// サーバーの時計とのずれ（ミリ秒）
+    offset_ms: f64,
+
+    /// 往復時間（ミリ秒）
+    rtt_ms: f64,
+}
+
+/// サーバーの時計とのずれの推定器
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    /// 直近の測定結果
+    samples: VecDeque<OffsetSample>,
+}
+
+impl ClockSync {
+    /// 測定前の推定器を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ping/Pongの1往復を記録する
+    ///
+    /// # 引数
+    /// * `client_sent_ms` - Pingを送った時刻（クライアントの時計）
+    /// * `server_time_ms` - サーバーがPingを受け取った時刻（サーバーの時計）
+    /// * `client_received_ms` - Pongを受け取った時刻（クライアントの時計）
+    ///
+    /// # 戻り値
+    /// この往復から求めたオフセット（ミリ秒）、受信時刻が送信時刻より前の場合はNone
+    pub fn record(
+        &mut self,
+        client_sent_ms: f64,
+        server_time_ms: f64,
+        client_received_ms: f64,
+    ) -> Option<f64> {
+        let rtt_ms = client_received_ms - client_sent_ms;
+        if !rtt_ms.is_finite() || rtt_ms < 0.0 {
+            return None;
+        }
+
+        let offset_ms = server_time_ms - (client_sent_ms + client_received_ms) / 2.0;
+        if self.samples.len() == SAMPLE_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(OffsetSample { offset_ms, rtt_ms });
+        Some(offset_ms)
+    }
+
+    /// サーバーの時計とのずれ（サーバーの時刻 - クライアントの時刻、ミリ秒）
+    ///
+    /// # 戻り値
+    /// 直近で往復時間が最も短かった測定のオフセット、測定前はNone
+    pub fn offset_ms(&self) -> Option<f64> {
+        self.samples
+            .iter()
+            .min_by(|a, b| a.rtt_ms.total_cmp(&b.rtt_ms))
+            .map(|sample| sample.offset_ms)
+    }
+
+    /// クライアントの時刻をサーバーの時刻に直す（測定前はそのまま）
+    pub fn to_server_time(&self, local_ms: f64) -> f64 {
+        local_ms + self.offset_ms().unwrap_or(0.0)
+    }
+
+    /// サーバーの時刻をクライアントの時刻に直す（測定前はそのまま）
+    pub fn to_local_time(&self, server_ms: f64) -> f64 {
+        server_ms - self.offset_ms().unwrap_or(0.0)
+    }
+}
+
+/// クライアントの時刻で送られてきたタイムスタンプをサーバーの時刻に直す
+///
+/// 時計が大きくずれたクライアントや不正な値で他のプレイヤーの補間が乱れないよう、
+/// 結果はサーバーの現在時刻からMAX_TIMESTAMP_AGE_MSまでの範囲に収めます。
+///
+/// # 引数
+/// * `client_timestamp_ms` - クライアントの時計でのタイムスタンプ
+/// * `clock_offset_ms` - そのクライアントが推定したオフセット（未報告の場合は0）
+/// * `server_now_ms` - サーバーの現在時刻
+///
+/// # 戻り値
+/// サーバーの時刻でのタイムスタンプ
+pub fn client_to_server_time(
+    client_timestamp_ms: u64,
+    clock_offset_ms: i64,
+    server_now_ms: u64,
+) -> u64 {
+    let converted = (client_timestamp_ms as i128 + clock_offset_ms as i128).max(0) as u64;
+    converted.clamp(
+        server_now_ms.saturating_sub(MAX_TIMESTAMP_AGE_MS),
+        server_now_ms,
+    )
+}
@@ -0,0 +1,218 @@
+// =============================================================================
+// イベント・操作のタイムライン
+// =============================================================================
+// このファイルでは、ゲーム中に起きたイベント・移動・プレイヤーの操作を、
+// 起きたフレーム（ティック）の番号付きで記録するTimelineRecorderを実装します。
+//
+// 仕組み：
+// - GameRuntime::update()の1回を1ティックとして数える
+// - 記録は上限（TIMELINE_CAPACITY件）を超えると古いものから捨てる（リングバッファ）
+// - 記録する内容：
+//   - ゲームイベント（EventQueueに追加されたもの、配信前に取り込む）
+//   - カードの移動（MoveLogに記録した手）
+//   - プレイヤーのポインター操作（押した・離した・中断のみ、動かした操作は多すぎるため記録しない）
+// - get_timeline(since_tick)で指定したティック以降の記録を取り出す。
+//   デバッグ用オーバーレイの直近のイベント・クラッシュレポート・
+//   サーバーとの食い違いの調査は、すべてこの記録を読む
+// - リプレイは古い記録を捨てない移動履歴（MoveLog）から再現する（タイムラインは上限を超えると
+//   古い手が抜けるため使わない）
+// - TimelineRecorderが登録されていないワールド（サーバー・ソルバー・ボットなど）では何も記録しない
+// =============================================================================
+
+use crate::ecs::{Resource, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::input::PointerEvent;
+use crate::solitaire::MoveRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// タイムラインに保持する記録の上限
+pub const TIMELINE_CAPACITY: usize = 1000;
+
+/// デバッグ表示・クラッシュレポートに含める直近のイベント数
+pub const RECENT_EVENT_COUNT: usize = 20;
+
+/// タイムラインの記録の内容
+///
+/// JSONでは`{"kind": "move", "record": {...}}`の形式になります。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimelineRecord {
+    /// ゲームイベント
+    Event {
+        /// 発生したイベント
+        event: GameEvent,
+    },
+
+    /// カードの移動
+    Move {
+        /// 移動の記録
+        record: MoveRecord,
+    },
+
+    /// プレイヤーのポインター操作
+    Pointer {
+        /// 受け付けたポインターイベント
+        pointer: PointerEvent,
+    },
+}
+
+/// タイムラインの記録1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// 記録したティック（GameRuntime::update()の回数）
+    pub tick: u64,
+
+    /// 記録の内容
+    #[serde(flatten)]
+    pub record: TimelineRecord,
+}
+
+/// タイムラインの記録のリソース
+#[derive(Debug, Clone)]
+pub struct TimelineRecorder {
+    /// 現在のティック
+    tick: u64,
+
+    /// 記録（古い順）
+    entries: VecDeque<TimelineEntry>,
+
+    /// 保持する記録の上限
+    capacity: usize,
+
+    /// 上限を超えて捨てた記録の数
+    dropped: u64,
+}
+
+impl Resource for TimelineRecorder {}
+
+impl TimelineRecorder {
+    /// 保持する記録の上限を指定してタイムラインを作成
+    ///
+    /// # 引数
+    /// * `capacity` - 保持する記録の上限（0の場合は1）
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            tick: 0,
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// 現在のティックを取得
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// ティックを1つ進める
+    pub fn advance_tick(&mut self) {
+        self.tick += 1;
+    }
+
+    /// 現在のティックで記録を追加（上限を超えた場合は一番古い記録を捨てる）
+    ///
+    /// # 引数
+    /// * `record` - 追加する記録
+    pub fn record(&mut self, record: TimelineRecord) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries.push_back(TimelineEntry {
+            tick: self.tick,
+            record,
+        });
+    }
+
+    /// 指定したティック以降の記録を取得
+    ///
+    /// # 引数
+    /// * `since_tick` - このティック以降（このティックを含む）の記録を返す
+    ///
+    /// # 戻り値
+    /// 古い順の記録のイテレータ
+    pub fn since(&self, since_tick: u64) -> impl Iterator<Item = &TimelineEntry> {
+        // 記録はティック順に並んでいるため、最初に該当する位置から後ろをすべて返す
+        let start = self
+            .entries
+            .partition_point(|entry| entry.tick < since_tick);
+        self.entries.range(start..)
+    }
+
+    /// 直近のゲームイベントを取得（デバッグ表示・クラッシュレポート用）
+    ///
+    /// # 引数
+    /// * `count` - 取得する最大件数
+    ///
+    /// # 戻り値
+    /// 古い順のイベント（最大count件）
+    pub fn recent_events(&self, count: usize) -> Vec<GameEvent> {
+        let mut events: Vec<GameEvent> = self
+            .entries
+            .iter()
+            .rev()
+            .filter_map(|entry| match &entry.record {
+                TimelineRecord::Event { event } => Some(event.clone()),
+                _ => None,
+            })
+            .take(count)
+            .collect();
+        events.reverse();
+        events
+    }
+
+    /// 保持している記録の数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 記録がないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 上限を超えて捨てた記録の数
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+impl Default for TimelineRecorder {
+    fn default() -> Self {
+        Self::with_capacity(TIMELINE_CAPACITY)
+    }
+}
+
+/// ワールドのタイムラインに記録を追加する（タイムラインがない場合は何もしない）
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `record` - 追加する記録
+pub fn record(world: &mut World, record: TimelineRecord) {
+    if let Some(timeline) = world.get_resource_mut::<TimelineRecorder>() {
+        timeline.record(record);
+    }
+}
+
+/// EventQueueにまだ記録していないイベントをタイムラインに取り込む
+///
+/// イベントは配信で取り出される前に、発生した順に記録されます。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+pub fn record_events(world: &mut World) {
+    if world.get_resource::<TimelineRecorder>().is_none() {
+        return;
+    }
+    let events = world
+        .get_resource_mut::<EventQueue>()
+        .map(EventQueue::take_unrecorded)
+        .unwrap_or_default();
+    if let Some(timeline) = world.get_resource_mut::<TimelineRecorder>() {
+        for event in events {
+            timeline.record(TimelineRecord::Event { event });
+        }
+    }
+}
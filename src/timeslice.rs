@@ -0,0 +1,146 @@
+// =============================================================================
+// フレームごとの時間予算による分割実行
+// =============================================================================
+// このファイルでは、勝ち筋の探索のように数十ミリ秒かかる処理を、1フレームあたりの
+// 時間予算（FrameBudget）の中で少しずつ進める仕組みを実装します。
+// WebAssembly版ではワーカーを使わずにメインスレッドで探索するため、
+// 1回で最後まで進めると16ms（60FPS）のフレームに収まらず、描画が止まってしまいます。
+//
+// 仕組み：
+// - 分割できる処理はIncrementalを実装し、advance()で指定した単位数だけ進めて途中の状態を保持する
+// - システムはフレームの開始時にTimeSliceを作り、予算を使い切るまでrun_until()で処理を進める
+// - 終わらなかった処理は次のフレームで続きから進め、進み具合（0.0〜1.0）を返す
+// =============================================================================
+
+use crate::clock::monotonic_ms;
+use crate::ecs::{Resource, World};
+
+/// 1フレームあたりに重い処理へ使う時間の既定値（ミリ秒）
+///
+/// 描画やほかのシステムの時間を残すため、16msのフレームの4分の1にしています。
+pub const DEFAULT_FRAME_BUDGET_MS: f64 = 4.0;
+
+/// 時間を確認するまでに進める単位数
+///
+/// 時計の読み取りも無料ではないため、ある程度まとめて進めてから確認します。
+pub const STEPS_PER_CHECK: u32 = 64;
+
+/// 1フレームあたりの時間予算リソース
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameBudget {
+    /// 1フレームあたりに重い処理へ使う時間（ミリ秒）
+    pub ms_per_frame: f64,
+}
+
+impl Resource for FrameBudget {}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self {
+            ms_per_frame: DEFAULT_FRAME_BUDGET_MS,
+        }
+    }
+}
+
+impl FrameBudget {
+    /// ワールドに登録されている時間予算を取得（登録されていない場合は既定値）
+    pub fn of(world: &World) -> Self {
+        world.get_resource::<Self>().copied().unwrap_or_default()
+    }
+
+    /// このフレームの時間枠を開始する
+    pub fn start(&self) -> TimeSlice {
+        TimeSlice::new(self.ms_per_frame)
+    }
+}
+
+/// 1フレーム分の時間枠
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeSlice {
+    /// 時間枠が終わる時刻（monotonic_ms()の値）
+    deadline_ms: f64,
+}
+
+impl TimeSlice {
+    /// 今から指定した時間の時間枠を作成
+    ///
+    /// # 引数
+    /// * `budget_ms` - 使える時間（ミリ秒、0以下の場合は最低限の1回分だけ進める）
+    pub fn new(budget_ms: f64) -> Self {
+        Self {
+            deadline_ms: monotonic_ms() + budget_ms.max(0.0),
+        }
+    }
+
+    /// 残り時間を取得（ミリ秒、使い切った場合は0）
+    pub fn remaining_ms(&self) -> f64 {
+        (self.deadline_ms - monotonic_ms()).max(0.0)
+    }
+
+    /// 時間を使い切ったかどうか
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_ms() <= 0.0
+    }
+}
+
+/// 分割して進める処理の状態
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress<T> {
+    /// まだ終わっていない（進み具合、0.0〜1.0）
+    Pending(f32),
+
+    /// 終わった（処理の結果）
+    Done(T),
+}
+
+/// 少しずつ進められる処理
+pub trait Incremental {
+    /// 処理の結果の型
+    type Output;
+
+    /// 処理を最大`steps`単位だけ進める
+    ///
+    /// 1単位は時間を確認せずに続けて進めてもよい小さな処理（探索なら局面1つ分）です。
+    ///
+    /// # 引数
+    /// * `steps` - 進める単位数の上限
+    ///
+    /// # 戻り値
+    /// 終わった場合は結果、終わっていない場合は進み具合
+    fn advance(&mut self, steps: u32) -> Progress<Self::Output>;
+}
+
+/// 時間枠を使い切るか処理が終わるまで進める
+///
+/// 時間枠を使い切っていても必ず1回（STEPS_PER_CHECK単位）は進めるため、
+/// 予算が小さくても処理はいずれ終わります。
+///
+/// # 引数
+/// * `task` - 進める処理
+/// * `slice` - このフレームの時間枠
+///
+/// # 戻り値
+/// 終わった場合は結果、終わっていない場合は進み具合
+pub fn run_until<T: Incremental>(task: &mut T, slice: &TimeSlice) -> Progress<T::Output> {
+    loop {
+        match task.advance(STEPS_PER_CHECK) {
+            Progress::Pending(_) if !slice.is_exhausted() => continue,
+            progress => return progress,
+        }
+    }
+}
+
+/// 時間を気にせず最後まで進める
+///
+/// # 引数
+/// * `task` - 進める処理
+///
+/// # 戻り値
+/// 処理の結果
+pub fn run_to_completion<T: Incremental>(task: &mut T) -> T::Output {
+    loop {
+        if let Progress::Done(output) = task.advance(u32::MAX) {
+            return output;
+        }
+    }
+}
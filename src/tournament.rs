@@ -0,0 +1,298 @@
+// =============================================================================
+// トーナメント（サーバー用）
+// =============================================================================
+// このファイルでは、ルーム内で行う連戦形式のトーナメントを実装します。
+// ホストがN回分のシード付き配り札を用意し、参加者は同じ配り札を順番にプレイします。
+// サーバーは各ラウンドの結果を集計し、ラウンド間に順位を配信し、最後に優勝者を決定します。
+//
+// 進行の流れ：
+// 1. ホストがトーナメントを作成（受付中）
+// 2. ホストが開始 → その時点のルームメンバーが参加者になる
+// 3. 全参加者が現在ラウンドの結果を送信 → 順位を配信して次ラウンドへ
+// 4. 最終ラウンドの集計が終わったら優勝者を決定
+// =============================================================================
+
+use crate::leaderboard::SubmittedResult;
+use crate::protocol::TournamentStanding;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// トーナメントの進行状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TournamentPhase {
+    Registration, // 参加受付中
+    InProgress,   // ラウンド進行中
+    Finished,     // 終了
+}
+
+/// 結果送信後のトーナメント進行
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoundProgress {
+    /// まだ結果を送信していない参加者がいる
+    Waiting { remaining: usize },
+
+    /// ラウンドが終了し、次のラウンドが始まった
+    NextRound { round: u8, seed: u64 },
+
+    /// 最終ラウンドが終了し、トーナメントが終了した
+    Finished,
+}
+
+/// トーナメント
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    /// トーナメントID
+    pub id: String,
+
+    /// ホスト（作成者）のプレイヤーID
+    pub host_id: String,
+
+    /// 各ラウンドの配り札のシード値
+    pub seeds: Vec<u64>,
+
+    /// 現在のラウンド（0始まりのインデックス）
+    pub current_round: usize,
+
+    /// 進行状況
+    pub phase: TournamentPhase,
+
+    /// 結果待ちの対象となる参加者（プレイヤーID, プレイヤー名）
+    participants: Vec<(String, String)>,
+
+    /// 開始時の全参加者の名前（プレイヤーID → プレイヤー名）
+    player_names: HashMap<String, String>,
+
+    /// ラウンドごとの結果（プレイヤーID → 結果）
+    round_results: Vec<HashMap<String, SubmittedResult>>,
+}
+
+impl Tournament {
+    /// 新しいトーナメントを作成
+    ///
+    /// 各ラウンドのシードは基準シードから決定的に導出されるため、
+    /// 同じ基準シードなら同じ配り札の組み合わせになります。
+    ///
+    /// # 引数
+    /// * `host_id` - ホストのプレイヤーID
+    /// * `rounds` - ラウンド数（1以上）
+    /// * `base_seed` - 基準シード値
+    ///
+    /// # 戻り値
+    /// 受付中のTournamentインスタンス
+    pub fn new(host_id: String, rounds: u8, base_seed: u64) -> Self {
+        let rounds = rounds.max(1) as usize;
+        let seeds = (0..rounds as u64)
+            .map(|i| derive_seed(base_seed.wrapping_add(i)))
+            .collect();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            host_id,
+            seeds,
+            current_round: 0,
+            phase: TournamentPhase::Registration,
+            participants: Vec::new(),
+            player_names: HashMap::new(),
+            round_results: vec![HashMap::new(); rounds],
+        }
+    }
+
+    /// ラウンド数を取得
+    pub fn total_rounds(&self) -> u8 {
+        self.seeds.len() as u8
+    }
+
+    /// 現在のラウンド番号を取得（1始まり、クライアント表示用）
+    pub fn round_number(&self) -> u8 {
+        self.current_round as u8 + 1
+    }
+
+    /// 現在のラウンドのシード値を取得
+    ///
+    /// # 戻り値
+    /// 進行中の場合はSome(シード値)、それ以外はNone
+    pub fn current_seed(&self) -> Option<u64> {
+        if self.phase != TournamentPhase::InProgress {
+            return None;
+        }
+        self.seeds.get(self.current_round).copied()
+    }
+
+    /// トーナメントを開始
+    ///
+    /// # 引数
+    /// * `participants` - 参加者（プレイヤーID, プレイヤー名）のリスト
+    ///
+    /// # 戻り値
+    /// 成功時は第1ラウンドのシード値、失敗時はエラーメッセージ
+    pub fn start(&mut self, participants: Vec<(String, String)>) -> Result<u64, String> {
+        if self.phase != TournamentPhase::Registration {
+            return Err("トーナメントは既に開始されています".to_string());
+        }
+        if participants.is_empty() {
+            return Err("参加者がいません".to_string());
+        }
+
+        self.player_names = participants.iter().cloned().collect();
+        self.participants = participants;
+        self.phase = TournamentPhase::InProgress;
+        self.current_round = 0;
+        Ok(self.seeds[0])
+    }
+
+    /// 参加者かどうかチェック
+    pub fn is_participant(&self, player_id: &str) -> bool {
+        self.participants.iter().any(|(id, _)| id == player_id)
+    }
+
+    /// 参加者を外す（切断・退室時）
+    ///
+    /// 外れた参加者のこれまでの結果は順位に残りますが、
+    /// 以降のラウンドで結果を待たなくなります。
+    ///
+    /// # 引数
+    /// * `player_id` - 外すプレイヤーID
+    ///
+    /// # 戻り値
+    /// 外したことで現在ラウンドが終了した場合はその進行、それ以外はNone
+    pub fn remove_participant(&mut self, player_id: &str) -> Option<RoundProgress> {
+        let before = self.participants.len();
+        self.participants.retain(|(id, _)| id != player_id);
+
+        if self.participants.len() == before || self.phase != TournamentPhase::InProgress {
+            return None;
+        }
+
+        if self.participants.is_empty() {
+            self.phase = TournamentPhase::Finished;
+            return Some(RoundProgress::Finished);
+        }
+
+        match self.advance_if_complete() {
+            RoundProgress::Waiting { .. } => None,
+            progress => Some(progress),
+        }
+    }
+
+    /// 現在ラウンドの結果を送信
+    ///
+    /// # 引数
+    /// * `player_id` - 送信したプレイヤーID
+    /// * `result` - ゲーム結果の要約
+    ///
+    /// # 戻り値
+    /// 成功時はトーナメントの進行、失敗時はエラーメッセージ
+    pub fn submit_result(
+        &mut self,
+        player_id: &str,
+        result: SubmittedResult,
+    ) -> Result<RoundProgress, String> {
+        let Some(seed) = self.current_seed() else {
+            return Err("トーナメントは進行中ではありません".to_string());
+        };
+        if !self.is_participant(player_id) {
+            return Err("トーナメントの参加者ではありません".to_string());
+        }
+        if result.seed != seed {
+            return Err(format!(
+                "ラウンド{}のシード({})と異なる結果です",
+                self.round_number(),
+                seed
+            ));
+        }
+
+        let results = &mut self.round_results[self.current_round];
+        if results.contains_key(player_id) {
+            return Err("このラウンドの結果は送信済みです".to_string());
+        }
+        results.insert(player_id.to_string(), result);
+
+        Ok(self.advance_if_complete())
+    }
+
+    /// 全参加者の結果が揃っていれば次のラウンドへ進める
+    fn advance_if_complete(&mut self) -> RoundProgress {
+        let results = &self.round_results[self.current_round];
+        let remaining = self
+            .participants
+            .iter()
+            .filter(|(id, _)| !results.contains_key(id))
+            .count();
+
+        if remaining > 0 {
+            return RoundProgress::Waiting { remaining };
+        }
+
+        if self.current_round + 1 >= self.seeds.len() {
+            self.phase = TournamentPhase::Finished;
+            return RoundProgress::Finished;
+        }
+
+        self.current_round += 1;
+        RoundProgress::NextRound {
+            round: self.round_number(),
+            seed: self.seeds[self.current_round],
+        }
+    }
+
+    /// 累計成績による順位を取得
+    ///
+    /// 累計スコアが高い → 累計プレイ時間が短い の順に並べます。
+    /// 途中で外れた参加者も順位に含まれます。
+    ///
+    /// # 戻り値
+    /// 順位順のTournamentStandingのベクター
+    pub fn standings(&self) -> Vec<TournamentStanding> {
+        let mut totals: HashMap<&str, TournamentStanding> = self
+            .player_names
+            .iter()
+            .map(|(id, name)| (id.as_str(), new_standing(id, name)))
+            .collect();
+
+        for (id, result) in self.round_results.iter().flatten() {
+            let Some(standing) = totals.get_mut(id.as_str()) else {
+                continue;
+            };
+            standing.total_score += result.score;
+            standing.total_time_seconds += result.duration_seconds;
+            standing.rounds_played += 1;
+            if result.won {
+                standing.rounds_won += 1;
+            }
+        }
+
+        let mut standings: Vec<TournamentStanding> = totals.into_values().collect();
+        standings.sort_by(|a, b| {
+            b.total_score
+                .cmp(&a.total_score)
+                .then(a.total_time_seconds.cmp(&b.total_time_seconds))
+                .then(a.player_id.cmp(&b.player_id))
+        });
+        for (index, standing) in standings.iter_mut().enumerate() {
+            standing.rank = index as u32 + 1;
+        }
+        standings
+    }
+}
+
+/// 空の順位情報を作成
+fn new_standing(player_id: &str, player_name: &str) -> TournamentStanding {
+    TournamentStanding {
+        rank: 0,
+        player_id: player_id.to_string(),
+        player_name: player_name.to_string(),
+        total_score: 0,
+        total_time_seconds: 0,
+        rounds_played: 0,
+        rounds_won: 0,
+    }
+}
+
+/// 基準値からラウンド用のシード値を導出（SplitMix64）
+fn derive_seed(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
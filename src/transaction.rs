@@ -0,0 +1,182 @@
+// =============================================================================
+// 手の適用のトランザクション
+// =============================================================================
+// このファイルでは、複数のカード・スタック・ゲーム状態・移動履歴を書き換える1手
+// （タブローの列ごとの移動など）を、まとめて反映するか何も反映しないかのどちらかにする
+// MoveTransactionを実装します。
+//
+// 仕組み：
+// - 書き換えるコンポーネントは、最初に触れたときにワールドから写して手元（スクラッチ）で書き換える
+//   （同じトランザクションの中で読み直すと、書き換えた後の値が返る）
+// - 移動履歴に追加する記録も手元に溜めておく
+// - 移動元のタブローで露出した裏向きのカードはreveal()でめくり、得点とCardRevealedイベントを溜める
+// - commit()で手元の値をまとめてワールドに書き戻す。書き戻しは失敗しない操作だけで行う
+// - 途中で検証に失敗した・パニックした場合は、commit()せずに捨てればワールドは元のまま
+// =============================================================================
+
+use crate::ecs::{Component, Entity, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::solitaire::{
+    CardStack, MoveRecord, SolitaireCard, SolitaireGameState, SolitaireManager, REVEAL_POINTS,
+};
+use log::debug;
+
+/// 1手分の書き換えをまとめて反映するトランザクション
+///
+/// commit()しないまま捨てると、ワールドには何も反映されません。
+#[derive(Debug, Clone, Default)]
+pub struct MoveTransaction {
+    /// 書き換えたカード（触れた順）
+    cards: Vec<(Entity, SolitaireCard)>,
+
+    /// 書き換えたスタック（触れた順）
+    stacks: Vec<(Entity, CardStack)>,
+
+    /// 書き換えたゲーム状態
+    game_state: Option<(Entity, SolitaireGameState)>,
+
+    /// 移動履歴に追加する記録（古い順）
+    records: Vec<MoveRecord>,
+
+    /// 反映後に送るイベント（古い順）
+    events: Vec<GameEvent>,
+}
+
+impl MoveTransaction {
+    /// 空のトランザクションを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// カードを書き換える（初めて触れる場合はワールドから写す）
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `entity` - カードのエンティティ
+    ///
+    /// # 戻り値
+    /// 成功時は書き換える値への可変参照、カードがない場合はエラーメッセージ
+    pub fn card(&mut self, world: &World, entity: Entity) -> Result<&mut SolitaireCard, String> {
+        stage(&mut self.cards, world, entity)
+            .ok_or_else(|| format!("カード{}がありません", entity.0))
+    }
+
+    /// スタックを書き換える（初めて触れる場合はワールドから写す）
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `entity` - スタックのエンティティ
+    ///
+    /// # 戻り値
+    /// 成功時は書き換える値への可変参照、スタックがない場合はエラーメッセージ
+    pub fn stack(&mut self, world: &World, entity: Entity) -> Result<&mut CardStack, String> {
+        stage(&mut self.stacks, world, entity)
+            .ok_or_else(|| format!("スタック{}がありません", entity.0))
+    }
+
+    /// ゲーム状態を書き換える（初めて触れる場合はワールドから写す）
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    ///
+    /// # 戻り値
+    /// 書き換える値への可変参照（ゲーム状態がない場合はNone）
+    pub fn game_state(&mut self, world: &World) -> Option<&mut SolitaireGameState> {
+        if self.game_state.is_none() {
+            self.game_state = world
+                .query::<SolitaireGameState>()
+                .next()
+                .map(|(entity, state)| (entity, state.clone()));
+        }
+        self.game_state.as_mut().map(|(_, state)| state)
+    }
+
+    /// 移動履歴に追加する記録を溜める
+    ///
+    /// # 引数
+    /// * `record` - 追加する移動記録
+    pub fn record(&mut self, record: MoveRecord) {
+        self.records.push(record);
+    }
+
+    /// 移動元のタブローで露出した裏向きのカードをめくる
+    ///
+    /// 得点（REVEAL_POINTS）を加え、反映時にこの手の移動記録をめくった手として記録し、
+    /// CardRevealedイベントを送ります。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの参照
+    /// * `entity` - めくるカードのエンティティ
+    /// * `column` - カードのあるタブローの列番号
+    ///
+    /// # 戻り値
+    /// 成功時はOk、カードがない場合はエラーメッセージ
+    pub fn reveal(&mut self, world: &World, entity: Entity, column: u32) -> Result<(), String> {
+        self.card(world, entity)?.flip_up();
+        if let Some(state) = self.game_state(world) {
+            state.record_reveal();
+        }
+        self.events.push(GameEvent::CardRevealed {
+            card_id: entity.id(),
+            column,
+            points: REVEAL_POINTS,
+        });
+        Ok(())
+    }
+
+    /// 書き換えをまとめてワールドに反映する
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    pub fn commit(mut self, world: &mut World) {
+        debug!(
+            "🧾 手を反映: カード{}枚, スタック{}個, 移動記録{}件",
+            self.cards.len(),
+            self.stacks.len(),
+            self.records.len()
+        );
+        if !self.events.is_empty() {
+            if let Some(last) = self.records.last_mut() {
+                last.revealed = true;
+            }
+        }
+        write_back(world, self.cards);
+        write_back(world, self.stacks);
+        if let Some((entity, state)) = self.game_state {
+            write_back(world, vec![(entity, state)]);
+        }
+        for record in self.records {
+            SolitaireManager::record_to_move_log(world, record);
+        }
+        if let Some(queue) = world.get_resource_mut::<EventQueue>() {
+            for event in self.events {
+                queue.push(event);
+            }
+        }
+    }
+}
+
+/// コンポーネントを手元に写して書き換える値を返す
+fn stage<'a, T: Component + Clone>(
+    staged: &'a mut Vec<(Entity, T)>,
+    world: &World,
+    entity: Entity,
+) -> Option<&'a mut T> {
+    let index = match staged.iter().position(|(staged, _)| *staged == entity) {
+        Some(index) => index,
+        None => {
+            staged.push((entity, world.get_component::<T>(entity)?.clone()));
+            staged.len() - 1
+        }
+    };
+    staged.get_mut(index).map(|(_, value)| value)
+}
+
+/// 手元で書き換えた値をワールドに書き戻す
+fn write_back<T: Component>(world: &mut World, staged: Vec<(Entity, T)>) {
+    for (entity, value) in staged {
+        if let Some(component) = world.get_component_mut::<T>(entity) {
+            *component = value;
+        }
+    }
+}
@@ -0,0 +1,125 @@
+// =============================================================================
+// 通信経路の選択
+// =============================================================================
+// このファイルでは、メッセージをWebSocketとWebRTCのデータチャネルの
+// どちらで送るかを決めるTransportSelectorを実装します。
+//
+// 仕組み：
+// - 同じルームのプレイヤー同士は、WebSocketでの接続交渉（RtcSignal）を通して
+//   データチャネルを開く（IDが小さい方が申し込み、同時に申し込むのを防ぐ）
+// - データチャネルは順序も再送もない設定で開くため、遅延は小さいが欠落し得る
+// - 優先度がLowのメッセージ（カーソル位置など、次の値で上書きされるもの）だけを、
+//   ルームの全員とデータチャネルが開いている場合に限ってデータチャネルで送る
+// - それ以外のメッセージと、1人でもデータチャネルが開いていない場合はWebSocketで送る
+//   （サーバーが検証・記録するメッセージは必ずサーバーを通す）
+// =============================================================================
+
+use crate::network::MessagePriority;
+use crate::protocol::WebSocketMessage;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// メッセージの通信経路
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    /// サーバー経由のWebSocket（順序通り・欠落なし）
+    WebSocket,
+
+    /// ルームのプレイヤーと直接つながるデータチャネル（低遅延・欠落あり）
+    DataChannel,
+}
+
+/// メッセージの優先度
+///
+/// # 引数
+/// * `message` - 送信するメッセージ
+///
+/// # 戻り値
+/// 次の値で上書きされるメッセージはLow、接続確認はHigh、それ以外はNormal
+pub fn message_priority(message: &WebSocketMessage) -> MessagePriority {
+    match message {
+        WebSocketMessage::MousePosition { .. } => MessagePriority::Low,
+        WebSocketMessage::Ping { .. } | WebSocketMessage::Pong { .. } => MessagePriority::High,
+        _ => MessagePriority::Normal,
+    }
+}
+
+/// データチャネルを申し込む側かどうか
+///
+/// 両者が同時に申し込むと交渉が衝突するため、プレイヤーIDが小さい方だけが申し込みます。
+///
+/// # 引数
+/// * `own_id` - 自分のプレイヤーID
+/// * `peer_id` - 相手のプレイヤーID
+pub fn should_initiate(own_id: &str, peer_id: &str) -> bool {
+    own_id < peer_id
+}
+
+/// データチャネルの状態と、通信経路の選択
+#[derive(Debug, Clone, Default)]
+pub struct TransportSelector {
+    /// 同じルームの他のプレイヤー
+    room_peers: Vec<String>,
+
+    /// データチャネルが開いている相手
+    open_channels: HashSet<String>,
+}
+
+impl TransportSelector {
+    /// ルームに参加していない状態のセレクターを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 同じルームの他のプレイヤーを設定（ルームにいなくなった相手のチャネルは閉じたものとする）
+    ///
+    /// # 引数
+    /// * `peers` - 同じルームの他のプレイヤーのID
+    pub fn set_room_peers(&mut self, peers: Vec<String>) {
+        self.open_channels.retain(|peer| peers.contains(peer));
+        self.room_peers = peers;
+    }
+
+    /// 同じルームの他のプレイヤー
+    pub fn room_peers(&self) -> &[String] {
+        &self.room_peers
+    }
+
+    /// データチャネルが開いた
+    pub fn channel_opened(&mut self, peer_id: &str) {
+        if self.room_peers.iter().any(|peer| peer == peer_id) {
+            self.open_channels.insert(peer_id.to_string());
+        }
+    }
+
+    /// データチャネルが閉じた
+    pub fn channel_closed(&mut self, peer_id: &str) {
+        self.open_channels.remove(peer_id);
+    }
+
+    /// 相手とのデータチャネルが開いているか
+    pub fn is_open(&self, peer_id: &str) -> bool {
+        self.open_channels.contains(peer_id)
+    }
+
+    /// ルームの全員とデータチャネルが開いているか（ルームに他のプレイヤーがいない場合はfalse）
+    pub fn all_channels_open(&self) -> bool {
+        !self.room_peers.is_empty() && self.room_peers.iter().all(|peer| self.is_open(peer))
+    }
+
+    /// メッセージの優先度から通信経路を選ぶ
+    ///
+    /// # 引数
+    /// * `priority` - メッセージの優先度
+    ///
+    /// # 戻り値
+    /// 優先度がLowで全員とデータチャネルが開いていればDataChannel、それ以外はWebSocket
+    pub fn select(&self, priority: MessagePriority) -> Transport {
+        if priority == MessagePriority::Low && self.all_channels_open() {
+            Transport::DataChannel
+        } else {
+            Transport::WebSocket
+        }
+    }
+}
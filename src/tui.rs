@@ -0,0 +1,393 @@
+// =============================================================================
+// ターミナル版（TUI）
+// =============================================================================
+// このファイルでは、ターミナル上でクロンダイクを遊べる画面を実装します。
+// 盤面の操作はブラウザ版と同じSolitaireManager・HintEngineをそのまま呼び出すため、
+// ルールの動作をブラウザなしで手早く確かめるのに使います。
+//
+// 操作方法：
+// - d / スペース：デッキからカードを引く
+// - w：ウェイストを移動元に選ぶ、1〜7：タブローの列を移動元に選ぶ
+// - 移動元を選んだ後に 1〜7：その列へ移動、f：組札へ移動（Escで選択を取り消す）
+// - h：ヒントを表示、a：ヒントの手を打つ、n：新しいゲーム、q：終了
+//
+// タブローの列から移動する場合は、移動先に置ける一番多い枚数をまとめて移動します。
+// =============================================================================
+
+use crate::clock::GameClock;
+use crate::ecs::{Entity, World};
+use crate::hint::{HintEngine, HintLocation};
+use crate::rng::Rng;
+use crate::scenario::{parse_card, BoardBuilder, Scenario};
+use crate::solitaire::{
+    CardLocation, CardSuit, SolitaireGameState, SolitaireManager, SolitaireType,
+};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// タブローの列数
+const TABLEAU_COLUMNS: u32 = 7;
+
+/// 組札の数
+const FOUNDATION_COUNT: u32 = 4;
+
+/// 1枚のカードの表示幅（文字数）
+const CELL_WIDTH: usize = 5;
+
+/// 経過時間の表示を更新する間隔（キー入力がなくても再描画する）
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 移動先の指定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// タブローの列（0始まり）
+    Tableau(u32),
+
+    /// 組札（空いている・置ける組を自動で選ぶ）
+    Foundation,
+}
+
+/// ターミナル版のゲームの状態
+pub struct Tui {
+    /// ECSワールド
+    world: World,
+
+    /// ゲーム状態エンティティ
+    game: Entity,
+
+    /// 選択中の移動元
+    selected: Option<HintLocation>,
+
+    /// 画面下部に表示するメッセージ
+    message: String,
+}
+
+impl Tui {
+    /// 指定したシードで新しいゲームを開始
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード
+    pub fn new(seed: u64) -> Self {
+        let mut world = World::new();
+        let game =
+            SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, seed);
+        Self {
+            world,
+            game,
+            selected: None,
+            message: format!("シード{}のゲームを開始しました", seed),
+        }
+    }
+
+    /// シナリオで組み立てた盤面から開始（テストや不具合の再現用）
+    ///
+    /// # 引数
+    /// * `board` - 盤面を組み立てるビルダー
+    ///
+    /// # 戻り値
+    /// 盤面を組み立てられた場合はTui、シナリオが不正な場合はエラーメッセージ
+    pub fn from_board(board: &BoardBuilder) -> Result<Self, String> {
+        let mut world = World::new();
+        let game = board.build(&mut world)?;
+        Ok(Self {
+            world,
+            game,
+            selected: None,
+            message: String::new(),
+        })
+    }
+
+    /// キー入力を処理
+    ///
+    /// # 引数
+    /// * `key` - 押されたキー
+    ///
+    /// # 戻り値
+    /// ゲームを続ける場合true、終了する場合false
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            KeyCode::Char('q') => return false,
+            KeyCode::Esc => {
+                self.selected = None;
+                self.message.clear();
+            }
+            KeyCode::Char('n') => *self = Self::new(Rng::from_entropy().next_u64()),
+            KeyCode::Char('d') | KeyCode::Char(' ') => {
+                self.selected = None;
+                self.message = match SolitaireManager::draw_card(&mut self.world) {
+                    Ok(()) => String::new(),
+                    Err(e) => e,
+                };
+            }
+            KeyCode::Char('h') => {
+                self.message = HintEngine::find_hint(&self.world)
+                    .map_or_else(|| "打てる手がありません".to_string(), |hint| hint.message);
+            }
+            KeyCode::Char('a') => {
+                self.selected = None;
+                self.message = match HintEngine::find_hint(&self.world) {
+                    Some(hint) if HintEngine::apply(&mut self.world, &hint) => hint.message,
+                    _ => "打てる手がありません".to_string(),
+                };
+            }
+            KeyCode::Char('w') => self.select(HintLocation {
+                location: CardLocation::Waste,
+                index: 0,
+            }),
+            KeyCode::Char('f') => self.move_selected(Target::Foundation),
+            KeyCode::Char(c @ '1'..='7') => {
+                let column = c as u32 - '1' as u32;
+                if self.selected.is_some() {
+                    self.move_selected(Target::Tableau(column));
+                } else {
+                    self.select(HintLocation {
+                        location: CardLocation::Tableau,
+                        index: column,
+                    });
+                }
+            }
+            _ => {}
+        }
+        if SolitaireManager::check_windows_solitaire_win(&self.world) {
+            self.message = "🎉 クリアしました！ n：新しいゲーム / q：終了".to_string();
+        }
+        true
+    }
+
+    /// 選択中の移動元
+    pub fn selected(&self) -> Option<HintLocation> {
+        self.selected
+    }
+
+    /// 画面下部に表示しているメッセージ
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// 盤面のECSワールド
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// 移動元を選択
+    fn select(&mut self, from: HintLocation) {
+        self.selected = Some(from);
+        self.message = "移動先を選んでください（1〜7：列 / f：組札 / Esc：取り消し）".to_string();
+    }
+
+    /// 選択中の移動元から移動先へカードを移動
+    fn move_selected(&mut self, to: Target) {
+        let Some(from) = self.selected.take() else {
+            self.message = "先に移動元（w / 1〜7）を選んでください".to_string();
+            return;
+        };
+        self.message = match self.try_move(from, to) {
+            Ok(()) => String::new(),
+            Err(e) => e,
+        };
+    }
+
+    /// 置ける組み合わせを探してカードを移動
+    ///
+    /// 組札へは1枚ずつ、タブローの列からタブローへは多い枚数から順に試します。
+    fn try_move(&mut self, from: HintLocation, to: Target) -> Result<(), String> {
+        let destinations: Vec<HintLocation> = match to {
+            Target::Tableau(index) => vec![HintLocation {
+                location: CardLocation::Tableau,
+                index,
+            }],
+            Target::Foundation => (0..FOUNDATION_COUNT)
+                .map(|index| HintLocation {
+                    location: CardLocation::Foundation,
+                    index,
+                })
+                .collect(),
+        };
+        let max_count = match (from.location, to) {
+            (CardLocation::Tableau, Target::Tableau(_)) => self.column_len(from.index),
+            _ => 1,
+        };
+
+        let mut last_error = "その移動はできません".to_string();
+        for count in (1..=max_count.max(1)).rev() {
+            for &destination in &destinations {
+                match HintEngine::move_cards(&mut self.world, from, count, destination) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => last_error = e,
+                }
+            }
+        }
+        Err(last_error)
+    }
+
+    /// タブローの列のカードの枚数
+    fn column_len(&self, column: u32) -> usize {
+        Scenario::from_world(&self.world)
+            .tableau
+            .get(column as usize)
+            .map_or(0, Vec::len)
+    }
+
+    /// 画面全体を描画
+    ///
+    /// # 引数
+    /// * `out` - 描画先（ターミナルの標準出力、テストではバッファ）
+    pub fn render(&self, out: &mut impl Write) -> io::Result<()> {
+        let board = Scenario::from_world(&self.world);
+        let state = self.world.get_component::<SolitaireGameState>(self.game);
+        queue!(out, Clear(ClearType::All))?;
+
+        // 1行目：スコアと経過時間
+        if let Some(state) = state {
+            let elapsed = state.elapsed_seconds(&GameClock::from_world(&self.world));
+            let status = format!(
+                "ECS ソリティア（クロンダイク）  シード {}  スコア {}  手数 {}  経過 {:02}:{:02}",
+                state.seed,
+                state.score,
+                state.move_count,
+                elapsed / 60,
+                elapsed % 60
+            );
+            queue!(out, cursor::MoveTo(0, 0), Print(status))?;
+        }
+
+        // 3行目：デッキ・ウェイスト・組札
+        queue!(out, cursor::MoveTo(0, 2))?;
+        let deck = if board.deck.is_empty() {
+            "--".to_string()
+        } else {
+            format!("##({})", board.deck.len())
+        };
+        queue!(out, Print(format!("山 {:<8}", deck)))?;
+        self.label(out, "捨 ", CardLocation::Waste, 0)?;
+        print_card(out, board.waste.last())?;
+        queue!(out, Print("    組 "))?;
+        for pile in &board.foundations {
+            print_card(out, pile.last())?;
+        }
+
+        // 5行目以降：タブロー
+        queue!(out, cursor::MoveTo(0, 4))?;
+        for column in 0..TABLEAU_COLUMNS {
+            self.label(
+                out,
+                &format!("{:<width$}", column + 1, width = CELL_WIDTH),
+                CardLocation::Tableau,
+                column,
+            )?;
+        }
+        let rows = board.tableau.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..rows {
+            queue!(out, cursor::MoveTo(0, 5 + row as u16))?;
+            for (column, cards) in board.tableau.iter().enumerate() {
+                let face_down = board.face_down.get(column).copied().unwrap_or(0);
+                match cards.get(row) {
+                    Some(_) if row < face_down => {
+                        queue!(out, Print(format!("{:<CELL_WIDTH$}", "##")))?
+                    }
+                    Some(code) => print_card(out, Some(code))?,
+                    None => queue!(out, Print(" ".repeat(CELL_WIDTH)))?,
+                }
+            }
+        }
+
+        // 下部：メッセージと操作方法
+        let bottom = 6 + rows.max(1) as u16;
+        queue!(
+            out,
+            cursor::MoveTo(0, bottom),
+            Print(&self.message),
+            cursor::MoveTo(0, bottom + 2),
+            Print("d：引く  w / 1〜7：移動元  1〜7 / f：移動先  h：ヒント  a：ヒントの手  n：新規  q：終了"),
+        )?;
+        out.flush()
+    }
+
+    /// 場所の見出しを描画（選択中の移動元は反転表示）
+    fn label(
+        &self,
+        out: &mut impl Write,
+        text: &str,
+        location: CardLocation,
+        index: u32,
+    ) -> io::Result<()> {
+        let selected = self.selected == Some(HintLocation { location, index });
+        if selected {
+            queue!(out, SetAttribute(Attribute::Reverse))?;
+        }
+        queue!(out, Print(text))?;
+        if selected {
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        Ok(())
+    }
+}
+
+/// 1枚のカードを記号付きで描画（ハートとダイヤは赤、カードがない場合は"--"）
+fn print_card(out: &mut impl Write, code: Option<&String>) -> io::Result<()> {
+    let Some((suit, rank)) = code.and_then(|code| parse_card(code).ok()) else {
+        return queue!(out, Print(format!("{:<CELL_WIDTH$}", "--")));
+    };
+    let color = match suit {
+        CardSuit::Hearts | CardSuit::Diamonds => Color::Red,
+        CardSuit::Clubs | CardSuit::Spades => Color::Reset,
+    };
+    let text = format!("{}{}", rank.display(), suit.symbol());
+    queue!(
+        out,
+        SetForegroundColor(color),
+        Print(format!("{:<CELL_WIDTH$}", text)),
+        SetForegroundColor(Color::Reset),
+    )
+}
+
+/// ターミナルのRawモードと代替画面を、抜けるときに必ず元に戻すガード
+///
+/// Dropで代替画面からの復帰を試み、それに失敗してもRawモードは必ず解除します。
+struct TerminalGuard;
+
+impl TerminalGuard {
+    /// Rawモードと代替画面に切り替える
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        // ここから先で失敗してもDropでRawモードを解除する
+        let guard = Self;
+        execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// ターミナル版を起動し、終了するまでキー入力を処理
+///
+/// 終了時（エラーやパニックで終わった場合も含む）はターミナルを元の状態に戻します。
+///
+/// # 引数
+/// * `seed` - 配り札のシード（省略時はランダム）
+pub fn run(seed: Option<u64>) -> io::Result<()> {
+    let mut tui = Tui::new(seed.unwrap_or_else(|| Rng::from_entropy().next_u64()));
+    let mut out = io::stdout();
+
+    let _guard = TerminalGuard::enter()?;
+    loop {
+        tui.render(&mut out)?;
+        if !event::poll(REFRESH_INTERVAL)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !tui.handle_key(key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
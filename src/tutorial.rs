@@ -0,0 +1,354 @@
+// =============================================================================
+// チュートリアル
+// =============================================================================
+// このファイルでは、決められた手順を1つずつ案内するチュートリアルを実装します。
+// 各手順では打つべき手が1つだけ決まっていて、それ以外の手は受け付けません。
+// 正しい手を打つと次の手順へ進み、最後の手順が終わると完了します。
+//
+// チュートリアルはデータファイル（data/tutorials.json）で定義するため、
+// 新しいチュートリアルを追加するのにコードの変更は必要ありません。
+//
+// データの形式：
+// {
+//   "id": "basics",
+//   "title": "はじめてのソリティア",
+//   "scenario": { ... },                  // 盤面（scenario.rsのシナリオ形式）
+//   "steps": [
+//     {
+//       "message": "♥Aを組札へ動かしましょう",
+//       "action": {
+//         "type": "move",                  // "move"（カードの移動）または"draw"（デッキから引く）
+//         "from": { "type": "tableau", "position": 0 },
+//         "to": { "type": "foundation", "position": 0 },
+//         "count": 1                       // 一緒に動かす枚数（省略時は1）
+//       }
+//     }
+//   ]
+// }
+// =============================================================================
+
+use crate::ecs::{Component, Entity, World};
+use crate::events::{EventQueue, GameEvent};
+use crate::hint::{HintEngine, HintLocation};
+use crate::protocol::MoveLocation;
+use crate::scenario::{BoardBuilder, Scenario};
+use crate::solitaire::{CardLocation, SolitaireCard, SolitaireManager};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// 組み込みのチュートリアル定義
+const BUILTIN_TUTORIALS: &str = include_str!("../data/tutorials.json");
+
+/// 受け付けるチュートリアルJSONの最大サイズ（バイト）
+const MAX_TUTORIAL_BYTES: usize = 64 * 1024;
+
+// =============================================================================
+// チュートリアルの定義
+// =============================================================================
+
+/// 手順で打つべき手
+///
+/// JSONでは`{"type": "move", "from": ..., "to": ..., "count": 1}`
+/// または`{"type": "draw"}`の形式になります。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TutorialAction {
+    /// カードを移動する
+    Move {
+        /// 移動元
+        from: MoveLocation,
+        /// 移動先
+        to: MoveLocation,
+        /// 一緒に移動する枚数
+        #[serde(default = "default_move_count")]
+        count: usize,
+    },
+
+    /// デッキからカードを引く
+    Draw,
+}
+
+/// 移動する枚数の既定値
+fn default_move_count() -> usize {
+    1
+}
+
+/// チュートリアルの手順
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TutorialStep {
+    /// プレイヤーへの案内文
+    pub message: String,
+
+    /// この手順で打つべき手
+    pub action: TutorialAction,
+}
+
+/// チュートリアル
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tutorial {
+    /// チュートリアルID
+    pub id: String,
+
+    /// チュートリアル名
+    pub title: String,
+
+    /// 開始時の盤面
+    pub scenario: Scenario,
+
+    /// 手順（順番に進める）
+    pub steps: Vec<TutorialStep>,
+}
+
+/// チュートリアル一覧に表示する情報
+#[derive(Debug, Clone, Serialize)]
+pub struct TutorialSummary {
+    /// チュートリアルID
+    pub id: String,
+
+    /// チュートリアル名
+    pub title: String,
+
+    /// 手順の数
+    pub step_count: usize,
+}
+
+impl Tutorial {
+    /// チュートリアル定義のJSON（配列）を解析
+    ///
+    /// # 引数
+    /// * `json` - チュートリアルの配列のJSON文字列
+    ///
+    /// # 戻り値
+    /// 成功時はチュートリアルのベクター、形式不正の場合はエラーメッセージ
+    pub fn parse_all(json: &str) -> Result<Vec<Self>, String> {
+        if json.len() > MAX_TUTORIAL_BYTES {
+            return Err(format!(
+                "チュートリアルのJSONが大きすぎます（{}バイト）",
+                json.len()
+            ));
+        }
+        serde_json::from_str(json).map_err(|e| format!("チュートリアルの形式が不正です: {}", e))
+    }
+
+    /// 一覧表示用の情報を取得
+    pub fn summary(&self) -> TutorialSummary {
+        TutorialSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            step_count: self.steps.len(),
+        }
+    }
+
+    /// チュートリアルの盤面をワールドに作成し、最初の手順から始める
+    ///
+    /// 手順がない・盤面が不正な場合、ワールドは変更されません。
+    ///
+    /// # 引数
+    /// * `world` - ECSワールドへの可変参照
+    ///
+    /// # 戻り値
+    /// 成功時はゲーム状態エンティティ（TutorialProgressを持つ）、不正な場合はエラーメッセージ
+    pub fn start(&self, world: &mut World) -> Result<Entity, String> {
+        if self.steps.is_empty() {
+            return Err(format!(
+                "チュートリアル「{}」に手順がありません",
+                self.title
+            ));
+        }
+        let game_entity = BoardBuilder::from_scenario(self.scenario.clone()).build(world)?;
+        world.add_component(game_entity, TutorialProgress::new(self.clone()));
+
+        info!(
+            "🎓 チュートリアル「{}」開始: 全{}手順",
+            self.title,
+            self.steps.len()
+        );
+        Ok(game_entity)
+    }
+}
+
+/// 組み込みのチュートリアルを取得
+///
+/// # 戻り値
+/// data/tutorials.jsonに定義されたチュートリアルのベクター
+pub fn builtin_tutorials() -> Vec<Tutorial> {
+    Tutorial::parse_all(BUILTIN_TUTORIALS).unwrap_or_else(|e| {
+        warn!("⚠️ 組み込みチュートリアルを読み込めません: {}", e);
+        Vec::new()
+    })
+}
+
+/// 組み込みのチュートリアルをIDで探す
+///
+/// # 引数
+/// * `id` - チュートリアルID
+///
+/// # 戻り値
+/// 見つかった場合はSome(Tutorial)
+pub fn find_tutorial(id: &str) -> Option<Tutorial> {
+    builtin_tutorials()
+        .into_iter()
+        .find(|tutorial| tutorial.id == id)
+}
+
+// =============================================================================
+// 進み具合の追跡
+// =============================================================================
+
+/// 強調表示する対象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TutorialHighlight {
+    /// 動かすカード（デッキから引く手順ではNone）
+    pub card: Option<Entity>,
+
+    /// 動かす元の山
+    pub from: HintLocation,
+
+    /// 動かす先の山
+    pub to: HintLocation,
+}
+
+/// チュートリアルの進み具合コンポーネント
+///
+/// チュートリアルのゲーム状態エンティティに添付されます。
+/// やり直しに使うため、チュートリアルの定義そのものを保持します。
+#[derive(Debug, Clone, PartialEq)]
+pub struct TutorialProgress {
+    /// 進めているチュートリアル
+    pub tutorial: Tutorial,
+
+    /// 現在の手順の番号（0から開始、手順数と同じなら完了）
+    pub current_step: usize,
+}
+
+impl Component for TutorialProgress {}
+
+impl TutorialProgress {
+    /// 最初の手順から始める進み具合を作成
+    ///
+    /// # 引数
+    /// * `tutorial` - 進めるチュートリアル
+    pub fn new(tutorial: Tutorial) -> Self {
+        Self {
+            tutorial,
+            current_step: 0,
+        }
+    }
+
+    /// 現在の手順を取得
+    ///
+    /// # 戻り値
+    /// 進行中の場合はSome(&TutorialStep)、完了している場合はNone
+    pub fn step(&self) -> Option<&TutorialStep> {
+        self.tutorial.steps.get(self.current_step)
+    }
+
+    /// すべての手順が終わったかチェック
+    pub fn is_completed(&self) -> bool {
+        self.current_step >= self.tutorial.steps.len()
+    }
+
+    /// 現在の手順で強調表示する対象を求める
+    ///
+    /// # 引数
+    /// * `world` - ECSワールド
+    ///
+    /// # 戻り値
+    /// 進行中の場合はSome(TutorialHighlight)、完了している場合はNone
+    pub fn highlight(&self, world: &World) -> Option<TutorialHighlight> {
+        match self.step()?.action {
+            TutorialAction::Move { from, to, count } => Some(TutorialHighlight {
+                card: moving_card(world, from, count),
+                from: pile(from),
+                to: pile(to),
+            }),
+            TutorialAction::Draw => Some(TutorialHighlight {
+                card: None,
+                from: HintLocation {
+                    location: CardLocation::Deck,
+                    index: 0,
+                },
+                to: HintLocation {
+                    location: CardLocation::Waste,
+                    index: 0,
+                },
+            }),
+        }
+    }
+}
+
+/// チュートリアルの手を打つ
+///
+/// 現在の手順で決められた手と同じ場合だけ盤面に適用し、次の手順へ進めます。
+/// 最後の手順が終わるとチュートリアル完了のイベントを通知します。
+///
+/// # 引数
+/// * `world` - ECSワールドへの可変参照
+/// * `entity` - チュートリアルのゲーム状態エンティティ
+/// * `action` - プレイヤーが打った手
+///
+/// # 戻り値
+/// 成功時はOk、決められた手と違う・打てない場合は理由を表すエラーメッセージ
+pub fn perform(world: &mut World, entity: Entity, action: &TutorialAction) -> Result<(), String> {
+    let progress = world
+        .get_component::<TutorialProgress>(entity)
+        .ok_or_else(|| "チュートリアル中ではありません".to_string())?;
+    let step = progress
+        .step()
+        .ok_or_else(|| "チュートリアルは完了しています".to_string())?;
+    if step.action != *action {
+        return Err(format!("今の手順はこちらです: {}", step.message));
+    }
+
+    match *action {
+        TutorialAction::Move { from, to, count } => {
+            HintEngine::move_cards(world, pile(from), count, pile(to))?
+        }
+        TutorialAction::Draw => SolitaireManager::draw_card(world)?,
+    }
+
+    let Some(progress) = world.get_component_mut::<TutorialProgress>(entity) else {
+        return Ok(());
+    };
+    progress.current_step += 1;
+    if !progress.is_completed() {
+        return Ok(());
+    }
+
+    let id = progress.tutorial.id.clone();
+    info!("🎓 チュートリアル「{}」完了", progress.tutorial.title);
+    if let Some(events) = world.get_resource_mut::<EventQueue>() {
+        events.push(GameEvent::TutorialCompleted { id });
+    }
+    Ok(())
+}
+
+/// 場所指定をヒントエンジンの場所に変換
+fn pile(location: MoveLocation) -> HintLocation {
+    HintLocation {
+        location: location.location,
+        index: location.index,
+    }
+}
+
+/// 移動元の山から`count`枚を動かす場合に、先頭になるカードを探す
+fn moving_card(world: &World, from: MoveLocation, count: usize) -> Option<Entity> {
+    // ウェイストは1つしかなく、位置は積んだ順番を表す
+    let mut cards: Vec<(Entity, &SolitaireCard)> = world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| {
+            card.location_type == from.location
+                && (from.location == CardLocation::Waste || card.position_in_location == from.index)
+        })
+        .collect();
+    match from.location {
+        CardLocation::Tableau => {
+            cards.sort_by(|(_, a), (_, b)| a.display_y.total_cmp(&b.display_y))
+        }
+        CardLocation::Waste => cards.sort_by_key(|(_, card)| card.position_in_location),
+        _ => cards.sort_by_key(|(_, card)| card.rank),
+    }
+
+    let start = cards.len().checked_sub(count)?;
+    cards.get(start).map(|(entity, _)| *entity)
+}
@@ -0,0 +1,123 @@
+// =============================================================================
+// 表示領域（ズーム・パン）
+// =============================================================================
+// このファイルでは、小さな画面でも遊べるように、盤面の拡大率と表示位置を
+// Viewportリソースとして保持し、画面の座標と盤面の座標を変換する仕組みを実装します。
+//
+// 座標の関係：
+// - 画面の座標 = 盤面の座標 × scale + offset
+// - ポインターイベントは画面の座標で届き、InputSystemが盤面の座標に直してから当たり判定する
+// - get_solitaire_state()のviewportで同じ変換を返すため、JavaScript側はそのまま描画に使える
+//
+// 2本指のピンチ操作（InputSystemが検出）では、指の中点を動かさないように拡大・縮小し、
+// 中点の移動に合わせて表示位置をずらします。
+// =============================================================================
+
+use crate::ecs::Resource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// 拡大率の下限
+pub const MIN_SCALE: f32 = 0.5;
+
+/// 拡大率の上限
+pub const MAX_SCALE: f32 = 3.0;
+
+/// 表示領域リソース
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema, TS)]
+#[ts(export)]
+pub struct Viewport {
+    /// 拡大率（1.0で等倍、MIN_SCALE〜MAX_SCALE）
+    pub scale: f32,
+
+    /// 盤面の原点を表示する画面のX座標
+    pub offset_x: f32,
+
+    /// 盤面の原点を表示する画面のY座標
+    pub offset_y: f32,
+}
+
+impl Resource for Viewport {}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+        }
+    }
+}
+
+impl Viewport {
+    /// 拡大率と表示位置を指定して作成
+    ///
+    /// # 引数
+    /// * `scale` - 拡大率
+    /// * `offset_x` - 盤面の原点を表示する画面のX座標
+    /// * `offset_y` - 盤面の原点を表示する画面のY座標
+    ///
+    /// # 戻り値
+    /// 成功時はViewport、拡大率が範囲外・値が有限でない場合はエラーメッセージ
+    pub fn new(scale: f32, offset_x: f32, offset_y: f32) -> Result<Self, String> {
+        if !offset_x.is_finite() || !offset_y.is_finite() {
+            return Err("表示位置は有限の値で指定してください".to_string());
+        }
+        if !(MIN_SCALE..=MAX_SCALE).contains(&scale) {
+            return Err(format!(
+                "拡大率は{}〜{}で指定してください: {}",
+                MIN_SCALE, MAX_SCALE, scale
+            ));
+        }
+        Ok(Self {
+            scale,
+            offset_x,
+            offset_y,
+        })
+    }
+
+    /// 画面の座標を盤面の座標に変換
+    pub fn to_board(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.offset_x) / self.scale,
+            (y - self.offset_y) / self.scale,
+        )
+    }
+
+    /// 盤面の座標を画面の座標に変換
+    pub fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.scale + self.offset_x,
+            y * self.scale + self.offset_y,
+        )
+    }
+
+    /// 画面上の1点を動かさないように拡大・縮小する
+    ///
+    /// 拡大率はMIN_SCALE〜MAX_SCALEに収めます。
+    ///
+    /// # 引数
+    /// * `factor` - 現在の拡大率に掛ける倍率
+    /// * `focus_x` - 動かさない点の画面のX座標
+    /// * `focus_y` - 動かさない点の画面のY座標
+    pub fn zoom_at(&mut self, factor: f32, focus_x: f32, focus_y: f32) {
+        if !factor.is_finite() || factor <= 0.0 {
+            return;
+        }
+        let (board_x, board_y) = self.to_board(focus_x, focus_y);
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.offset_x = focus_x - board_x * self.scale;
+        self.offset_y = focus_y - board_y * self.scale;
+    }
+
+    /// 表示位置をずらす
+    ///
+    /// # 引数
+    /// * `dx` - 画面上でずらすX方向の距離
+    /// * `dy` - 画面上でずらすY方向の距離
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.offset_x += dx;
+        self.offset_y += dy;
+    }
+}
@@ -9,17 +9,115 @@
 // - プレイヤーの接続・切断管理
 // - マウスカーソル位置のリアルタイム同期
 // - ゲームアクションのブロードキャスト
-// - 部屋（Room）システムによるマルチプレイ管理
+// - 部屋（Room）システムによるマルチプレイ管理（クライアントからのルームの作成を含む）
+// - ゲーム結果のリーダーボード記録とルーム内トーナメント
+// - 対戦結果によるEloレーティングとレーティング帯でのマッチング
+// - 空席を埋めるボット対戦相手（同じ配り札をヒントエンジンでプレイ）
+// - ルームのホスト管理（切断時の引き継ぎ、設定変更・キック・ゲーム開始はホストのみ）
+// - ルームのパスワード、キック後の一時的な再参加禁止、ルームごとの参加禁止リスト
+// - 準備完了の確認と、カウントダウン付きのゲーム同時開始
+// - 観戦者も送れるクイックリアクション（連打の制限付き）の中継
+// - カーソルの色（ルーム内で重複しない）と表示名の変更、セッショントークンごとの設定の保存
+// - 接続品質の測定と時刻合わせに使うPingへの応答
+// - クライアントの時計でのタイムスタンプをサーバーの時刻に直してから配信
+// - 協力プレイでのカードの取り合いの判定（遅延を補正した時刻で先に掴んだ方が取る）
+// - 共有盤面のルームの盤面の写しで、実際の操作をカードの持ち主と参加者の立場に照らして確かめる
+// - ルームごとのティックタスクでECSシステムを実行（ターン制のゲームの制限時間など）
+// - ルームの定期的な保存と、再起動後のルームの復元（同じセッショントークンの参加者を元のルームに戻す）
+// - Redisのバックプレーンによる複数インスタンス間のルーム一覧の共有と配信の中継（任意）
+// - ルーム一覧・リーダーボードなどを返す読み取り専用のHTTP APIと死活監視
+// - 同じルームのプレイヤー同士がWebRTCのデータチャネルを開くための接続交渉の中継
+// - Reliableで届いたメッセージへの受け取りの確認（Ack）と、再送で重複したメッセージの除外
+// - 状態を変える送信メッセージのReliableへの包み込みと、Ackが届くまでの再送
+// - チャネルごとの連番による抜けの検出と再送の要求、古いカーソル位置の破棄
+// - フレンドの登録と在席状況の通知、フレンドのルームへの招待と返事
+// - 0時（UTC）の日替わりの配り札の公開と、過去の配り札のリーダーボードと一緒の保存
+// - 日替わり・レース・届いたゲーム結果の配り札をソルバーで解いた結果（勝ち筋の有無・難しさ・手数）の保存と、日替わりの配り札の結果の配信
+// - 届いたゲーム結果から記録した同期アカウントの実績・通算成績の署名付きの書き出しと、別の端末で読み込む前の署名の確認
+// - 自分のターン・ホストをしているルームが満員になったときのプッシュ通知（中継サーバー経由、任意）
 // =============================================================================
 
-use std::collections::HashMap;
+// サーバーはtokio・tungsteniteなどネイティブ環境の非同期I/Oを使うため、WebAssembly向けにはビルドできない
+#[cfg(target_arch = "wasm32")]
+compile_error!("websocket_server はネイティブ環境でのみビルドできます（--features serverはWebAssembly向けには使えません）");
+
+// サーバーだけで使うモジュール
+mod account_stats;
+mod backplane;
+mod bot;
+mod card_claims;
+mod daily_deal;
+mod friends;
+mod http_api;
+mod leaderboard;
+mod outbound;
+mod preferences;
+mod push_gateway;
+mod rating;
+mod room_simulation;
+mod room_store;
+mod shared_board;
+mod tournament;
+
+// クライアントと共有するモジュール（ライブラリクレートから使い、サーバー側で別にコンパイルしない）
+// - ボットが盤面を再現するためのゲームロジック（clock・ecs・hint・rng・solitaire）
+// - スコアボードに載せるカードの裏面の種類（theme、サーバーは中継のみ）
+// - ボットのプレイ中の盤面をセッションIDで管理するレジストリ（session）
+// - 通信メッセージの定義と検証（protocol）
+// - 時刻合わせ（time_sync、サーバーはタイムスタンプの変換のみ使う）
+// - 確実な配送とチャネルごとの連番（reliable・sequence、サーバーは受け取りの確認・重複と抜けの判定のみ使う）
+// - ルームのシミュレーションで実行するゲーム状態・ターン管理のシステムとイベント（events・game）
+// - ログの出力先と保存データの読み書き（logging・storage）
+// - 実績・通算成績の書き出しの署名と確認（achievements・result・stats_transfer、署名鍵はサーバーだけが持つ）
+// - レースのコンボの数え方とパワーアップの種類・クールダウン（combo・power_up）
+// - 操作の止まったプレイヤーを離席中にする判定（afk）
+// - 共有盤面のルームの観戦者・カードの持ち主・山札の戻しの権限の規則（permissions）
+use ecs_wasm_solitaire::{
+    achievements, afk, clock, combo, ecs, events, game, hint, logging, permissions, power_up, protocol, reliable,
+    result, rng, scenario, sequence, session, solitaire, solve_cache, stats_transfer, storage, theme, time_sync,
+};
+
+use log::{debug, error, info, warn};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use serde::{Deserialize, Serialize};
 use tokio::net::{TcpListener, TcpStream};
-use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
 use futures_util::{SinkExt, StreamExt};
 use uuid::Uuid;
+use account_stats::AccountStatsStore;
+use backplane::{Backplane, BackplaneEvent, RemoteRooms, ANNOUNCE_INTERVAL_MS};
+use bot::{BotConfig, BotPlayer, BotStep};
+use clock::GameClock;
+use combo::ComboTracker;
+use afk::AfkTracker;
+use permissions::SeatRole;
+use power_up::{PowerUp, PowerUpTracker};
+use daily_deal::{DailyArchive, DailyDeal};
+use friends::{FriendEntry, FriendStore, InviteBook};
+use leaderboard::{Leaderboard, SubmittedResult};
+use outbound::{OutboundCounters, OutboundSender, ReliableOutbox};
+use preferences::PreferenceStore;
+use push_gateway::{NotificationStore, PushGateway, PushNotification};
+use rating::{RatingChange, RatingStore};
+use shared_board::SharedBoards;
+use protocol::{
+    Channel, FriendStatus, GameState, LoggedAction, PlayerProfile, PresenceStatus, RoomInfo, ScoreboardEntry,
+    WebSocketMessage, CURSOR_COLOR_COUNT,
+};
+use reliable::{DuplicateFilter, RECENT_ID_WINDOW};
+use stats_transfer::StatsExport;
+use rng::{Rng, DAY_MS};
+use scenario::Scenario;
+use sequence::{Arrival, SequenceTracker};
+use session::SessionRegistry;
+use solve_cache::{SolveCache, SolvedDeal, SERVER_SEARCH_LIMIT};
+use tournament::{RoundProgress, Tournament, TournamentPhase};
+use card_claims::{CardClaims, ClaimOutcome};
+use room_simulation::{RoomSimulation, TurnSnapshot, TICK_INTERVAL_MS};
+use room_store::{BanSnapshot, PasswordHash, PendingRestore, RoomSnapshot, RoomStore, SeatSnapshot, SEAT_RESERVATION_SECONDS, SNAPSHOT_INTERVAL_MS};
 
 // =============================================================================
 // データ構造定義
@@ -35,10 +133,33 @@ pub struct Player {
     pub cursor_y: f64,
     pub is_connected: bool,
     pub color_index: u8, // カーソル色用のインデックス
+    pub rating: u32,     // Eloレーティング
+    pub games_rated: u32, // レーティング対象の対戦数
+    pub bot: Option<BotConfig>, // ボットの場合は設定（人間の場合はNone）
+    pub connected_at: std::time::SystemTime, // 接続した時刻（ホストの引き継ぎ先の判定に使用）
+    #[serde(skip)]
+    pub recent_reactions: Vec<std::time::SystemTime>, // 直近のリアクションの送信時刻（連打の制限に使用）
+    #[serde(skip)]
+    pub session_token: String, // 設定の保存先を表すトークン（ボットは空）
+    #[serde(skip)]
+    pub clock_offset_ms: i64, // クライアントが推定したサーバーの時計とのずれ（未報告の場合は0）
+    #[serde(skip)]
+    pub score: u32, // 参加中のルームで最後に報告されたスコア
+    #[serde(skip)]
+    pub foundation_cards: u16, // 参加中のルームで最後に報告されたファウンデーションのカードの枚数
+    #[serde(skip)]
+    pub card_back: Option<theme::CardBack>, // 参加中のルームで見せているカードの裏面（見せていない場合はNone）
+    #[serde(skip)]
+    pub casual_seed: Option<u64>, // パワーアップを使えるルームで参加したレースの配り札のシード（その結果はランク外）
 }
 
 impl Player {
-    pub fn new(name: String) -> Self {
+    /// 新しいプレイヤーを作成
+    ///
+    /// # 引数
+    /// * `name` - 表示名
+    /// * `connected_at` - 接続した時刻（サーバーの時計で求める）
+    pub fn new(name: String, connected_at: std::time::SystemTime) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             name,
@@ -47,6 +168,84 @@ impl Player {
             cursor_y: 0.0,
             is_connected: true,
             color_index: 1,
+            rating: rating::INITIAL_RATING,
+            games_rated: 0,
+            bot: None,
+            connected_at,
+            recent_reactions: Vec::new(),
+            session_token: String::new(),
+            clock_offset_ms: 0,
+            score: 0,
+            foundation_cards: 0,
+            card_back: None,
+            casual_seed: None,
+        }
+    }
+
+    /// 参加禁止・キックの判定に使う本人の識別子
+    ///
+    /// 表示名は変えられるため、セッショントークンで見分けます（トークンのないボットは名前）。
+    pub fn identity(&self) -> String {
+        if self.session_token.is_empty() {
+            format!("bot:{}", self.name)
+        } else {
+            self.session_token.clone()
+        }
+    }
+
+    /// ボットプレイヤーを作成
+    ///
+    /// # 引数
+    /// * `config` - ボットの設定（打つ速さ・間違える確率）
+    /// * `connected_at` - 追加した時刻（サーバーの時計で求める）
+    pub fn new_bot(config: BotConfig, connected_at: std::time::SystemTime) -> Self {
+        let id = Uuid::new_v4().to_string();
+        let name = format!("Bot-{}", &id[..4]);
+        Self {
+            id,
+            bot: Some(config),
+            ..Self::new(name, connected_at)
+        }
+    }
+
+    /// リアクションを送れるか判定し、送れる場合は送信時刻を記録する
+    ///
+    /// リアクションは短いので、REACTION_WINDOW_SECONDS秒にREACTION_BURST回まで続けて送れます。
+    ///
+    /// # 戻り値
+    /// 送れる場合はtrue、連打の制限を超えた場合はfalse
+    pub fn record_reaction(&mut self, now: std::time::SystemTime) -> bool {
+        let window = std::time::Duration::from_secs(REACTION_WINDOW_SECONDS);
+        self.recent_reactions
+            .retain(|sent_at| now.duration_since(*sent_at).is_ok_and(|elapsed| elapsed < window));
+        if self.recent_reactions.len() >= REACTION_BURST {
+            return false;
+        }
+        self.recent_reactions.push(now);
+        true
+    }
+
+    /// プロフィール情報を取得（クライアント送信用）
+    pub fn profile(&self) -> PlayerProfile {
+        PlayerProfile {
+            player_id: self.id.clone(),
+            player_name: self.name.clone(),
+            color_index: self.color_index,
+            rating: self.rating,
+            games_rated: self.games_rated,
+            is_bot: self.bot.is_some(),
+        }
+    }
+
+    /// スコアボードに表示する最新のスコア
+    pub fn scoreboard_entry(&self) -> ScoreboardEntry {
+        ScoreboardEntry {
+            player_id: self.id.clone(),
+            player_name: self.name.clone(),
+            color_index: self.color_index,
+            score: self.score,
+            foundation_cards: self.foundation_cards,
+            card_back: self.card_back,
         }
     }
 }
@@ -60,17 +259,189 @@ pub struct GameRoom {
     pub max_players: u8,
     pub game_state: GameState,
     pub created_at: std::time::SystemTime,
+    pub tournament: Option<Tournament>, // 開催中・開催済みのトーナメント
+    pub host_id: Option<String>, // ホストのプレイヤーID（人間の参加者がいない場合はNone）
+    pub ready: HashSet<String>, // 準備完了したプレイヤーID（ゲーム開始時に空になる）
+    pub password: Option<PasswordHash>, // 参加に必要なパスワードのハッシュ（Noneの場合は誰でも参加できる）
+    pub banned: HashMap<String, String>, // 参加禁止のプレイヤーの識別子（Player::identity）→ 禁止したときの表示名
+    pub kick_blocks: HashMap<String, std::time::SystemTime>, // キックされたプレイヤーの識別子と再参加できる時刻
+    pub card_claims: CardClaims, // 協力プレイで掴まれているカード
+    pub turn_time_limit: u32, // ターンの制限時間（秒、0の場合はターン制にしない）
+    pub combo_window_seconds: u32, // レースのコンボの受付時間（秒、0の場合はコンボを数えない）
+    pub combos: ComboTracker, // レース中のプレイヤーごとのコンボ
+    pub power_ups: bool, // パワーアップを使えるカジュアルなルームかどうか（結果はランク外）
+    pub power_up_usage: PowerUpTracker, // レース中のパワーアップのクールダウンと相手から足されたタイム
+    pub shared_board: bool, // 参加者全員で1つの盤面を動かすルームかどうか（権限を確かめる）
+    pub spectators: HashSet<String>, // 共有盤面のルームで観戦に切り替えた参加者のID
+    pub card_owners: BTreeMap<String, String>, // 共有盤面のカードのID → 持ち主のプレイヤーID
+    pub afk_seconds: u32, // 操作がないまま離席中とみなすまでの時間（秒、0の場合は判定しない）
+    pub pause_on_afk: bool, // 共有盤面のルームで誰かが離席している間ゲームを一時停止するか
+    pub afk_forfeit_turns: u32, // ターン制で離席中に続けて飛ばしたら席を没収するターン数（0の場合は没収しない）
+    pub afk: AfkTracker, // プレイヤーごとの最後の操作と離席の状態
+    pub seed: Option<u64>, // 最後に始まった配り札のシード
+    pub finished: HashSet<String>, // 最後に始まった配り札の結果を送った参加者のID（全員揃うとレースが終わる）
+    pub action_log: Vec<LoggedAction>, // 配り札の開始からのアクション（再起動後の盤面の再現用）
+    pub turn_snapshot: Option<TurnSnapshot>, // ティックタスクが最後に記録したターンの状態
+    pub restore: Option<PendingRestore>, // 再起動後、参加していたプレイヤーが戻るのを待っている場合の状態
+    pub permanent: bool, // 空になっても削除しないルームかどうか（デフォルトルーム）
+    pub creator: Option<String>, // 作成したプレイヤーのセッショントークン（ない場合はプレイヤーID、サーバーが作ったルームはNone）
+    pub empty_since_ms: Option<u64>, // 参加者がいなくなった時刻（UNIX時刻、ミリ秒、参加者がいる場合はNone）
+    pub mirrored_from: Option<String>, // 他のインスタンスのルームを中継している場合、そのインスタンスのID
 }
 
 impl GameRoom {
-    pub fn new(name: String, max_players: u8) -> Self {
+    /// 新しいルームを作成
+    ///
+    /// # 引数
+    /// * `name` - ルーム名
+    /// * `max_players` - 最大人数
+    /// * `created_at` - 作成した時刻（サーバーの時計で求める）
+    pub fn new(name: String, max_players: u8, created_at: std::time::SystemTime) -> Self {
         Self {
             id: Uuid::new_v4().to_string(),
             name,
             players: Vec::new(),
             max_players,
             game_state: GameState::Waiting,
-            created_at: std::time::SystemTime::now(),
+            created_at,
+            tournament: None,
+            host_id: None,
+            ready: HashSet::new(),
+            password: None,
+            banned: HashMap::new(),
+            kick_blocks: HashMap::new(),
+            card_claims: CardClaims::default(),
+            turn_time_limit: 0,
+            combo_window_seconds: 0,
+            combos: ComboTracker::new(),
+            power_ups: false,
+            power_up_usage: PowerUpTracker::new(),
+            shared_board: false,
+            spectators: HashSet::new(),
+            card_owners: BTreeMap::new(),
+            afk_seconds: 0,
+            pause_on_afk: false,
+            afk_forfeit_turns: 0,
+            afk: AfkTracker::new(),
+            seed: None,
+            finished: HashSet::new(),
+            action_log: Vec::new(),
+            turn_snapshot: None,
+            restore: None,
+            permanent: false,
+            creator: None,
+            empty_since_ms: None,
+            mirrored_from: None,
+        }
+    }
+
+    /// 保存されていたスナップショットからルームを復元
+    ///
+    /// 参加者は戻ってくるまで席を空けておき、カウントダウン中だったルームは待機中に戻します。
+    ///
+    /// # 引数
+    /// * `snapshot` - 保存されていたルーム
+    /// * `now_ms` - 現在時刻（UNIX時刻、ミリ秒）
+    pub fn from_snapshot(snapshot: RoomSnapshot, now_ms: u64) -> Self {
+        let game_state = match snapshot.game_state {
+            GameState::Starting => GameState::Waiting,
+            game_state => game_state,
+        };
+        Self {
+            id: snapshot.id,
+            game_state,
+            password: snapshot
+                .password_hash
+                .or_else(|| snapshot.password.as_deref().map(PasswordHash::new)),
+            banned: snapshot
+                .banned_players
+                .into_iter()
+                .map(|ban| (ban.identity, ban.player_name))
+                .collect(),
+            turn_time_limit: snapshot.turn_time_limit,
+            combo_window_seconds: snapshot.combo_window_seconds,
+            power_ups: snapshot.power_ups,
+            shared_board: snapshot.shared_board,
+            card_owners: snapshot.card_owners,
+            afk_seconds: snapshot.afk_seconds,
+            pause_on_afk: snapshot.pause_on_afk,
+            afk_forfeit_turns: snapshot.afk_forfeit_turns,
+            seed: snapshot.seed,
+            action_log: snapshot.action_log,
+            permanent: snapshot.permanent,
+            creator: snapshot.creator,
+            mirrored_from: snapshot.mirrored_from,
+            restore: Some(PendingRestore {
+                seats: snapshot.seats,
+                turns: snapshot.turns,
+                expires_at_ms: now_ms + SEAT_RESERVATION_SECONDS * 1000,
+            }),
+            ..Self::new(
+                snapshot.name,
+                snapshot.max_players,
+                std::time::UNIX_EPOCH + std::time::Duration::from_millis(now_ms),
+            )
+        }
+    }
+
+    /// ルームのスナップショットを作成（保存用）
+    ///
+    /// まだ戻っていない参加者の席と再開前のターンの状態も、次の再起動に備えて含めます。
+    ///
+    /// # 引数
+    /// * `players` - 全プレイヤー
+    /// * `board` - 共有盤面のルームのワールドの盤面（SharedBoards::snapshot）
+    pub fn snapshot(&self, players: &HashMap<String, Player>, board: Option<Scenario>) -> RoomSnapshot {
+        let mut seats: Vec<SeatSnapshot> = self
+            .players
+            .iter()
+            .filter_map(|id| players.get(id))
+            .filter(|player| !player.session_token.is_empty())
+            .map(|player| SeatSnapshot {
+                player_id: player.id.clone(),
+                session_token: player.session_token.clone(),
+                player_name: player.name.clone(),
+            })
+            .collect();
+        let mut turns = self.turn_snapshot.clone();
+        if let Some(restore) = &self.restore {
+            seats.extend(restore.seats.iter().cloned());
+            turns = restore.turns.clone().or(turns);
+        }
+        let mut banned_players: Vec<BanSnapshot> = self
+            .banned
+            .iter()
+            .map(|(identity, player_name)| BanSnapshot {
+                identity: identity.clone(),
+                player_name: player_name.clone(),
+            })
+            .collect();
+        banned_players.sort_by(|a, b| a.identity.cmp(&b.identity));
+
+        RoomSnapshot {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            max_players: self.max_players,
+            game_state: self.game_state.clone(),
+            password_hash: self.password.clone(),
+            password: None,
+            banned_players,
+            turn_time_limit: self.turn_time_limit,
+            combo_window_seconds: self.combo_window_seconds,
+            power_ups: self.power_ups,
+            shared_board: self.shared_board,
+            card_owners: self.card_owners.clone(),
+            afk_seconds: self.afk_seconds,
+            pause_on_afk: self.pause_on_afk,
+            afk_forfeit_turns: self.afk_forfeit_turns,
+            seed: self.seed,
+            seats,
+            turns,
+            action_log: self.action_log.clone(),
+            permanent: self.permanent,
+            creator: self.creator.clone(),
+            mirrored_from: self.mirrored_from.clone(),
+            board,
         }
     }
 
@@ -86,6 +457,12 @@ impl GameRoom {
     pub fn remove_player(&mut self, player_id: &str) -> bool {
         if let Some(pos) = self.players.iter().position(|x| x == player_id) {
             self.players.remove(pos);
+            self.ready.remove(player_id);
+            self.finished.remove(player_id);
+            self.combos.reset(Some(player_id));
+            self.spectators.remove(player_id);
+            self.card_owners.retain(|_, owner_id| owner_id != player_id);
+            self.afk.remove_player(player_id);
             true
         } else {
             false
@@ -95,122 +472,550 @@ impl GameRoom {
     pub fn is_full(&self) -> bool {
         self.players.len() >= self.max_players as usize
     }
-}
 
-/// ゲーム状態
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GameState {
-    Waiting,    // プレイヤー待機中
-    Playing,    // ゲーム進行中
-    Finished,   // ゲーム終了
-}
+    /// プレイヤーがこのルームに入れるかチェック
+    ///
+    /// # 引数
+    /// * `identity` - 参加するプレイヤーの識別子（Player::identity）
+    /// * `password` - 入力されたパスワード
+    /// * `now` - 現在時刻（サーバーの時計で求める）
+    ///
+    /// # 戻り値
+    /// 入れる場合はOk(())、参加禁止・再参加の待ち時間中・パスワード違いの場合はエラーメッセージ
+    pub fn check_entry(&self, identity: &str, password: Option<&str>, now: std::time::SystemTime) -> Result<(), String> {
+        self.check_access(identity, now)?;
+        match &self.password {
+            Some(expected) if !password.is_some_and(|password| expected.matches(password)) => {
+                Err("パスワードが違います".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
 
-/// WebSocketメッセージタイプ
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum WebSocketMessage {
-    // 接続関連
-    PlayerJoin {
-        player_id: String,
-        player_name: String,
-        player_index: u8,
-    },
-    PlayerLeft {
-        player_id: String,
-        player_name: String,
-    },
-    
-    // マウスカーソル関連
-    MousePosition {
-        player_id: String,
-        x: f64,
-        y: f64,
-        timestamp: u64,
-    },
-    
-    // ゲームアクション関連
-    GameAction {
-        player_id: String,
-        player_name: String,
-        action: String,
-        x: Option<f64>,
-        y: Option<f64>,
-        timestamp: u64,
-    },
-    
-    // ルーム関連
-    JoinRoom {
-        room_id: String,
-        player_id: String,
-    },
-    LeaveRoom {
-        room_id: String,
-        player_id: String,
-    },
-    RoomList {
-        rooms: Vec<RoomInfo>,
-    },
-    
-    // エラー
-    Error {
-        message: String,
-    },
-}
+    /// パスワードを除いて、プレイヤーがこのルームに入れるかチェック（招待された場合）
+    ///
+    /// # 引数
+    /// * `identity` - 参加するプレイヤーの識別子（Player::identity）
+    /// * `now` - 現在時刻（サーバーの時計で求める）
+    ///
+    /// # 戻り値
+    /// 入れる場合はOk(())、参加禁止・再参加の待ち時間中の場合はエラーメッセージ
+    pub fn check_access(&self, identity: &str, now: std::time::SystemTime) -> Result<(), String> {
+        if self.banned.contains_key(identity) {
+            return Err("このルームへの参加は禁止されています".to_string());
+        }
+        let blocked_for = self
+            .kick_blocks
+            .get(identity)
+            .and_then(|until| until.duration_since(now).ok());
+        if let Some(remaining) = blocked_for {
+            return Err(format!(
+                "キックされたため、あと{}秒はこのルームに参加できません",
+                remaining.as_secs() + 1
+            ));
+        }
+        Ok(())
+    }
 
-/// ルーム情報（クライアント送信用）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RoomInfo {
-    pub id: String,
-    pub name: String,
-    pub player_count: u8,
-    pub max_players: u8,
-    pub game_state: GameState,
+    /// ルームのホストかチェック
+    pub fn is_host(&self, player_id: &str) -> bool {
+        self.host_id.as_deref() == Some(player_id)
+    }
+
+    /// 共有盤面のルームでの参加者の立場
+    pub fn seat_role(&self, player_id: &str) -> SeatRole {
+        SeatRole::of(player_id, self.host_id.as_deref(), self.spectators.iter())
+    }
+
+    /// 共有盤面のルームの権限を知らせるメッセージ（共有盤面でない場合はNone）
+    pub fn permissions_message(&self) -> Option<WebSocketMessage> {
+        let mut spectators: Vec<String> = self.spectators.iter().cloned().collect();
+        spectators.sort();
+        self.shared_board.then(|| WebSocketMessage::PermissionsChanged {
+            room_id: self.id.clone(),
+            host_id: self.host_id.clone(),
+            spectators,
+            card_owners: self.card_owners.clone(),
+        })
+    }
+
+    /// 誰かが離席しているため、共有盤面のゲームを一時停止しているかどうか
+    pub fn is_paused(&self) -> bool {
+        self.shared_board
+            && self.pause_on_afk
+            && matches!(self.game_state, GameState::Playing)
+            && self.afk.any_afk(&self.players)
+    }
+
+    /// 離席中になった・戻ったことを知らせるメッセージ
+    ///
+    /// # 引数
+    /// * `player_id` - プレイヤーID
+    /// * `afk` - 離席中になった場合true、戻った場合false
+    /// * `idle_seconds` - 操作していなかった時間（秒）
+    pub fn afk_message(&self, player_id: &str, afk: bool, idle_seconds: u32) -> WebSocketMessage {
+        WebSocketMessage::PlayerAfk {
+            room_id: self.id.clone(),
+            player_id: player_id.to_string(),
+            afk,
+            idle_seconds,
+            paused: self.is_paused(),
+        }
+    }
+
+    /// 準備完了したプレイヤーIDの一覧（参加順、ボットは常に準備完了）
+    pub fn ready_player_ids(&self, players: &HashMap<String, Player>) -> Vec<String> {
+        self.players
+            .iter()
+            .filter(|id| {
+                self.ready.contains(*id) || players.get(*id).is_some_and(|player| player.bot.is_some())
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 着席している全員が準備完了かチェック
+    ///
+    /// 対戦にならないため、参加者が2人未満の場合はfalseを返します。
+    pub fn all_ready(&self, players: &HashMap<String, Player>) -> bool {
+        self.players.len() >= 2 && self.ready_player_ids(players).len() == self.players.len()
+    }
+
+    /// 開始のカウントダウンを始められるかチェック
+    ///
+    /// 待機中か、前のレースが終わったルームだけがカウントダウンを始められます。
+    ///
+    /// # 戻り値
+    /// 始められる場合はOk(())、カウントダウン中・レース中・トーナメント開催中の場合はエラーメッセージ
+    pub fn can_start_countdown(&self) -> Result<(), String> {
+        match self.game_state {
+            GameState::Starting => Err("既に開始のカウントダウン中です".to_string()),
+            GameState::Playing => Err("レース中は新しいレースを開始できません".to_string()),
+            _ if self.has_active_tournament() => {
+                Err("トーナメント開催中はレースを開始できません".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 最後に始まった配り札の結果を送った参加者を記録
+    ///
+    /// 観戦者を除く参加者全員の結果が揃ったら、レースを終えて次のレースを始められるようにします
+    /// （トーナメントのラウンドはトーナメントの進行で終えるため、ここでは数えません）。
+    ///
+    /// # 引数
+    /// * `player_id` - 結果を送ったプレイヤーID
+    /// * `seed` - 結果の配り札のシード
+    ///
+    /// # 戻り値
+    /// レースを終えた場合true
+    pub fn record_finish(&mut self, player_id: &str, seed: u64) -> bool {
+        if !matches!(self.game_state, GameState::Playing)
+            || self.seed != Some(seed)
+            || self.has_active_tournament()
+            || !self.players.iter().any(|id| id == player_id)
+        {
+            return false;
+        }
+        self.finished.insert(player_id.to_string());
+        let all_finished = self
+            .players
+            .iter()
+            .filter(|id| !self.spectators.contains(*id))
+            .all(|id| self.finished.contains(id));
+        if all_finished {
+            self.game_state = GameState::Finished;
+        }
+        all_finished
+    }
+
+    /// ホストの引き継ぎ先を選ぶ
+    ///
+    /// ボットを除いた参加者のうち、最も長く接続しているプレイヤーを選びます
+    /// （接続時刻が同じ場合は先に参加したプレイヤー）。
+    ///
+    /// # 戻り値
+    /// 引き継ぎ先のプレイヤーID（人間の参加者がいない場合はNone）
+    pub fn next_host(&self, players: &HashMap<String, Player>) -> Option<String> {
+        self.players
+            .iter()
+            .filter_map(|id| players.get(id))
+            .filter(|player| player.bot.is_none())
+            .min_by_key(|player| player.connected_at)
+            .map(|player| player.id.clone())
+    }
+
+    /// ルーム参加者の平均レーティングを取得
+    ///
+    /// # 戻り値
+    /// 参加者がいる場合はSome(平均レーティング)、空の場合はNone
+    pub fn average_rating(&self, players: &HashMap<String, Player>) -> Option<u32> {
+        let ratings: Vec<u32> = self
+            .players
+            .iter()
+            .filter_map(|id| players.get(id))
+            .map(|player| player.rating)
+            .collect();
+
+        if ratings.is_empty() {
+            None
+        } else {
+            Some(ratings.iter().sum::<u32>() / ratings.len() as u32)
+        }
+    }
+
+    /// 同じルームの他の参加者が使っているカーソルの色
+    pub fn taken_colors(&self, player_id: &str, players: &HashMap<String, Player>) -> HashSet<u8> {
+        self.players
+            .iter()
+            .filter(|id| id.as_str() != player_id)
+            .filter_map(|id| players.get(id))
+            .map(|player| player.color_index)
+            .collect()
+    }
+
+    /// ルーム情報を作成（クライアント送信用）
+    pub fn to_info(&self, players: &HashMap<String, Player>) -> RoomInfo {
+        RoomInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            player_count: self.players.len() as u8,
+            max_players: self.max_players,
+            game_state: self.game_state.clone(),
+            host_id: self.host_id.clone(),
+            has_password: self.password.is_some(),
+            ready_player_ids: self.ready_player_ids(players),
+            average_rating: self.average_rating(players),
+            power_ups: self.power_ups,
+            shared_board: self.shared_board,
+            players: self
+                .players
+                .iter()
+                .filter_map(|id| players.get(id))
+                .map(Player::profile)
+                .collect(),
+        }
+    }
+
+    /// 中継しているルームを、管理しているインスタンスのルームの情報に合わせる
+    ///
+    /// パスワードは中継されないため、管理しているルームにパスワードが付いた場合は
+    /// このインスタンスからは新しく参加できないようにします（招待は受けられる）。
+    ///
+    /// # 引数
+    /// * `info` - 管理しているインスタンスから届いたルームの情報
+    pub fn refresh_from(&mut self, info: &RoomInfo) {
+        self.name = info.name.clone();
+        self.max_players = info.max_players;
+        self.game_state = info.game_state.clone();
+        self.power_ups = info.power_ups;
+        self.shared_board = info.shared_board;
+        if !info.has_password {
+            self.password = None;
+        } else if self.password.is_none() {
+            self.password = Some(PasswordHash::new(&Uuid::new_v4().to_string()));
+        }
+    }
+
+    /// パワーアップを使えるレースの最中なら、その配り札のシード
+    pub fn casual_race_seed(&self) -> Option<u64> {
+        self.seed
+            .filter(|_| self.power_ups && matches!(self.game_state, GameState::Playing))
+    }
+
+    /// トーナメントが受付中または進行中かチェック
+    pub fn has_active_tournament(&self) -> bool {
+        self.tournament
+            .as_ref()
+            .is_some_and(|t| t.phase != TournamentPhase::Finished)
+    }
+
+    /// トーナメントの進行に応じて配信するメッセージを作成
+    ///
+    /// ラウンド終了時は順位と次ラウンド開始を、最終ラウンド終了時は順位と優勝者を配信します。
+    ///
+    /// # 引数
+    /// * `progress` - 結果送信・参加者離脱後のトーナメント進行
+    ///
+    /// # 戻り値
+    /// ルーム内に配信するメッセージのベクター
+    fn tournament_progress_messages(&mut self, progress: RoundProgress) -> Vec<WebSocketMessage> {
+        let Some(tournament) = self.tournament.as_ref() else {
+            return Vec::new();
+        };
+        let standings = tournament.standings();
+
+        match progress {
+            RoundProgress::Waiting { .. } => Vec::new(),
+            RoundProgress::NextRound { round, seed } => vec![
+                WebSocketMessage::TournamentStandings {
+                    tournament_id: tournament.id.clone(),
+                    round: round - 1,
+                    standings,
+                },
+                WebSocketMessage::TournamentRoundStart {
+                    tournament_id: tournament.id.clone(),
+                    round,
+                    total_rounds: tournament.total_rounds(),
+                    seed,
+                },
+            ],
+            RoundProgress::Finished => {
+                let mut messages = vec![WebSocketMessage::TournamentStandings {
+                    tournament_id: tournament.id.clone(),
+                    round: tournament.round_number(),
+                    standings: standings.clone(),
+                }];
+                if let Some(winner) = standings.first() {
+                    info!("🏆 トーナメント優勝: {} ({}点)", winner.player_name, winner.total_score);
+                    messages.push(WebSocketMessage::TournamentFinished {
+                        tournament_id: tournament.id.clone(),
+                        winner_id: winner.player_id.clone(),
+                        winner_name: winner.player_name.clone(),
+                        standings: standings.clone(),
+                    });
+                }
+                self.game_state = GameState::Finished;
+                messages
+            }
+        }
+    }
 }
 
 // =============================================================================
 // サーバーメイン構造体
 // =============================================================================
 
+/// ゲーム開始前のカウントダウンの秒数
+const START_COUNTDOWN_SECONDS: u8 = 3;
+
+/// キックされたプレイヤーが同じルームに再参加できるまでの秒数
+const KICK_REJOIN_BLOCK_SECONDS: u64 = 60;
+
+/// ルームごとに記録するアクションの上限（古いものから捨てる）
+const ACTION_LOG_CAPACITY: usize = 2000;
+
+/// 1人のプレイヤーが作成して残しておけるルームの数
+const MAX_ROOMS_PER_PLAYER: usize = 3;
+
+/// サーバー全体でプレイヤーが作成できるルームの数
+const MAX_ROOMS: usize = 500;
+
+/// 空になったルームを削除するまでの猶予時間の既定値（秒、環境変数EMPTY_ROOM_GRACE_SECONDSで変更できる）
+const DEFAULT_EMPTY_ROOM_GRACE_SECONDS: u64 = 60;
+
+/// リアクションを続けて送れる回数と、その回数を数える時間（秒）
+const REACTION_BURST: usize = 5;
+const REACTION_WINDOW_SECONDS: u64 = 3;
+
+/// 待ち受けアドレスの既定値
+const DEFAULT_ADDR: &str = "162.43.8.148:8101";
+
+/// HTTP APIの待ち受けアドレス（環境変数HTTP_ADDRで変更できる）
+const DEFAULT_HTTP_ADDR: &str = "162.43.8.148:8102";
+
+/// 送信タスクがReliableで送ったメッセージの再送を確認する間隔（ミリ秒）
+const RELIABLE_RETRY_CHECK_MS: u64 = 100;
+
+/// 成績の書き出しの署名鍵の保存キー（環境変数STATS_SIGNING_KEYが設定されていない場合に使う）
+const STATS_SIGNING_KEY_STORAGE_KEY: &str = "stats_signing_key";
+
 type Players = Arc<Mutex<HashMap<String, Player>>>;
 type Rooms = Arc<Mutex<HashMap<String, GameRoom>>>;
-type Connections = Arc<Mutex<HashMap<String, WebSocketStream<TcpStream>>>>;
+type Senders = Arc<Mutex<HashMap<String, OutboundSender>>>;
+type SharedLeaderboard = Arc<Mutex<Leaderboard>>;
+type Ratings = Arc<Mutex<RatingStore>>;
+type Preferences = Arc<Mutex<PreferenceStore>>;
+type BotRaces = tokio::sync::mpsc::UnboundedSender<BotRace>;
+type BotSessions = Arc<Mutex<SessionRegistry<BotPlayer>>>;
 
-pub struct SolitaireServer {
+/// ボットにプレイさせる配り札（ルームとシード）
+#[derive(Debug, Clone)]
+struct BotRace {
+    room_id: String,
+    seed: u64,
+}
+
+/// 各接続タスクで共有するサーバーの状態
+#[derive(Clone)]
+struct ServerState {
     players: Players,
     rooms: Rooms,
-    connections: Connections,
+    senders: Senders,
+    leaderboard: SharedLeaderboard,
+    ratings: Ratings,
+    preferences: Preferences, // セッショントークンごとのカーソルの色と表示名
     next_color_index: Arc<Mutex<u8>>,
+    bot_races: BotRaces, // ボットのレース開始要求の送信先
+    bot_sessions: BotSessions, // プレイ中のボットの盤面（セッションIDは「ボットID:シード」）
+    clock: GameClock, // サーバーの時計（配信するタイムスタンプの基準）
+    room_store: Arc<Mutex<RoomStore>>, // ルームのスナップショットの保存先
+    backplane: Option<Backplane>, // 他のインスタンスとの中継（設定されていない場合はNone）
+    remote_rooms: Arc<Mutex<RemoteRooms>>, // 他のインスタンスのルーム一覧
+    ready: Arc<AtomicBool>, // WebSocketの待ち受けを始めたかどうか（HTTP APIの/readyで返す）
+    stats_signing_key: Arc<Vec<u8>>, // 成績の書き出しの署名鍵（クライアントには送らない）
+    account_stats: Arc<Mutex<AccountStatsStore>>, // セッショントークンごとの、届いたゲーム結果から記録した実績・通算成績
+    shared_boards: Arc<Mutex<SharedBoards>>, // 共有盤面のルームの盤面の写し（操作の権限の確認用）
+    empty_room_grace_ms: u64, // 空になったルームを削除するまでの猶予時間（ミリ秒）
+    daily_archive: Arc<Mutex<DailyArchive>>, // 過去の日替わりの配り札とその日のリーダーボード
+    solved_deals: Arc<Mutex<SolveCache>>, // シードごとのソルバーの結果（日替わり・レース・届いたゲーム結果の配り札を解き直さないため）
+    friends: Arc<Mutex<FriendStore>>, // セッショントークンごとのフレンドの一覧
+    invites: Arc<Mutex<InviteBook>>, // 返事を待っているルームへの招待
+    notifications: Arc<Mutex<NotificationStore>>, // セッショントークンごとのプッシュ通知の設定
+    push_gateway: PushGateway, // プッシュ通知の中継サーバー（設定されていない場合は送らない）
+    outbound: Arc<OutboundCounters>, // 接続ごとの送信チャンネルを通ったメッセージの数（HTTP APIの/metricsで返す）
+}
+
+pub struct SolitaireServer {
+    state: ServerState,
+    bot_race_receiver: Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<BotRace>>>,
+    backplane_receiver: Mutex<Option<tokio::sync::mpsc::UnboundedReceiver<BackplaneEvent>>>,
 }
 
 impl SolitaireServer {
     pub fn new() -> Self {
+        let (bot_races, bot_race_receiver) = tokio::sync::mpsc::unbounded_channel();
         Self {
-            players: Arc::new(Mutex::new(HashMap::new())),
-            rooms: Arc::new(Mutex::new(HashMap::new())),
-            connections: Arc::new(Mutex::new(HashMap::new())),
-            next_color_index: Arc::new(Mutex::new(1)),
+            state: ServerState {
+                players: Arc::new(Mutex::new(HashMap::new())),
+                rooms: Arc::new(Mutex::new(HashMap::new())),
+                senders: Arc::new(Mutex::new(HashMap::new())),
+                leaderboard: Arc::new(Mutex::new(Leaderboard::new())),
+                ratings: Arc::new(Mutex::new(RatingStore::load())),
+                preferences: Arc::new(Mutex::new(PreferenceStore::load())),
+                next_color_index: Arc::new(Mutex::new(1)),
+                bot_races,
+                bot_sessions: Arc::new(Mutex::new(SessionRegistry::new())),
+                clock: Self::server_clock(),
+                room_store: Arc::new(Mutex::new(RoomStore::default())),
+                backplane: None,
+                remote_rooms: Arc::new(Mutex::new(RemoteRooms::default())),
+                ready: Arc::new(AtomicBool::new(false)),
+                stats_signing_key: Arc::new(Self::load_stats_signing_key()),
+                account_stats: Arc::new(Mutex::new(AccountStatsStore::load())),
+                shared_boards: Arc::new(Mutex::new(SharedBoards::new())),
+                empty_room_grace_ms: Self::empty_room_grace_seconds() * 1000,
+                daily_archive: Arc::new(Mutex::new(DailyArchive::load())),
+                solved_deals: Arc::new(Mutex::new(SolveCache::load())),
+                friends: Arc::new(Mutex::new(FriendStore::load())),
+                invites: Arc::new(Mutex::new(InviteBook::default())),
+                notifications: Arc::new(Mutex::new(NotificationStore::load())),
+                push_gateway: PushGateway::from_env(),
+                outbound: Arc::new(OutboundCounters::default()),
+            },
+            bot_race_receiver: Mutex::new(Some(bot_race_receiver)),
+            backplane_receiver: Mutex::new(None),
+        }
+    }
+
+    /// サーバーの時計を作成
+    ///
+    /// 環境変数CLOCK_ADVANCE_MSが設定されていれば、その分だけ時計を進めます（日付の切り替えの確認用）。
+    fn server_clock() -> GameClock {
+        let mut clock = GameClock::new();
+        if let Some(ms) = std::env::var("CLOCK_ADVANCE_MS").ok().and_then(|ms| ms.parse().ok()) {
+            clock.advance(ms);
+        }
+        clock
+    }
+
+    /// サーバーの時計の現在時刻をSystemTimeで取得
+    ///
+    /// 時刻をSystemTimeで持つ記録（接続・ルーム作成の時刻、リアクションの間隔、招待とキックの期限）も
+    /// CLOCK_ADVANCE_MSで進めた時計に揃えるため、SystemTime::now()の代わりに使います。
+    fn system_time(clock: &GameClock) -> std::time::SystemTime {
+        std::time::UNIX_EPOCH + std::time::Duration::from_millis(clock.now_ms())
+    }
+
+    /// 空になったルームを削除するまでの猶予時間（秒）
+    ///
+    /// 環境変数EMPTY_ROOM_GRACE_SECONDSが設定されていればその値を使います。
+    fn empty_room_grace_seconds() -> u64 {
+        std::env::var("EMPTY_ROOM_GRACE_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse().ok())
+            .unwrap_or(DEFAULT_EMPTY_ROOM_GRACE_SECONDS)
+    }
+
+    /// 成績の書き出しの署名鍵を読み込む
+    ///
+    /// 環境変数STATS_SIGNING_KEYが設定されていればその値を使います（複数のインスタンスで
+    /// 同じ鍵を使う場合）。設定されていない場合は保存されている鍵を使い、
+    /// なければ新しく作って保存します（再起動しても前に署名した書き出しを確認できるように）。
+    fn load_stats_signing_key() -> Vec<u8> {
+        if let Ok(key) = std::env::var("STATS_SIGNING_KEY") {
+            if !key.is_empty() {
+                return key.into_bytes();
+            }
+        }
+        if let Some(key) = storage::load(STATS_SIGNING_KEY_STORAGE_KEY).filter(|key| !key.is_empty()) {
+            return key.into_bytes();
+        }
+        
+        let key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        match storage::save(STATS_SIGNING_KEY_STORAGE_KEY, &key) {
+            Ok(()) => info!("🔑 成績の書き出しの署名鍵を作成しました"),
+            Err(e) => warn!("⚠️ 署名鍵の保存失敗（再起動すると前の署名を確認できません）: {}", e),
         }
+        key.into_bytes()
+    }
+
+    /// バックプレーンに接続し、他のインスタンスとルームを共有する
+    ///
+    /// # 引数
+    /// * `url` - 接続先（redis://ホスト:ポート）
+    pub async fn connect_backplane(&mut self, url: &str) -> Result<(), String> {
+        let (backplane, receiver) = Backplane::connect(url).await?;
+        self.state.backplane = Some(backplane);
+        *self.backplane_receiver.lock().unwrap() = Some(receiver);
+        Ok(())
     }
 
     /// サーバーを開始
     pub async fn start(&self, addr: &str) -> Result<(), Box<dyn std::error::Error>> {
         let listener = TcpListener::bind(addr).await?;
-        println!("🌐 WebSocketサーバーを{}で開始しました", addr);
+        info!("🌐 WebSocketサーバーを{}で開始しました", addr);
+
+        // 保存されていたルームを復元（なければデフォルトルームを作成）
+        if !self.restore_rooms() {
+            self.create_default_room().await;
+        }
+
+        // 止まっていた間に切り替えられなかった日の配り札を保存
+        Self::archive_missed_days(&self.state);
+        
+        // ルームのスナップショットを定期的に保存するタスクを起動
+        tokio::spawn(Self::run_room_snapshots(self.state.clone()));
+
+        // 0時（UTC）に日替わりの配り札を切り替えるタスクを起動
+        tokio::spawn(Self::run_daily_deals(self.state.clone()));
+
+        // 中継サーバーに届けられなかったプッシュ通知を送り直すタスクを起動
+        if self.state.push_gateway.is_enabled() {
+            tokio::spawn(Self::run_push_retries(self.state.clone()));
+        }
+
+        // ボットのレースを管理するタスクを起動
+        if let Some(receiver) = self.bot_race_receiver.lock().unwrap().take() {
+            tokio::spawn(Self::run_bot_races(receiver, self.state.clone()));
+        }
+
+        // 他のインスタンスとの中継とルーム一覧の発行を起動
+        if let Some(receiver) = self.backplane_receiver.lock().unwrap().take() {
+            tokio::spawn(Self::run_backplane(receiver, self.state.clone()));
+            tokio::spawn(Self::run_room_announcements(self.state.clone()));
+        }
 
-        // デフォルトルームを作成
-        self.create_default_room().await;
+        self.state.ready.store(true, Ordering::Release);
 
         while let Ok((stream, addr)) = listener.accept().await {
-            println!("🔗 新しい接続: {}", addr);
+            info!("🔗 新しい接続: {}", addr);
             
-            let players = Arc::clone(&self.players);
-            let rooms = Arc::clone(&self.rooms);
-            let connections = Arc::clone(&self.connections);
-            let next_color_index = Arc::clone(&self.next_color_index);
+            let state = self.state.clone();
 
             tokio::spawn(async move {
-                if let Err(e) = Self::handle_connection(stream, addr, players, rooms, connections, next_color_index).await {
-                    println!("❌ 接続処理エラー: {}", e);
+                if let Err(e) = Self::handle_connection(stream, addr, state).await {
+                    error!("❌ 接続処理エラー: {}", e);
                 }
             });
         }
@@ -220,46 +1025,459 @@ impl SolitaireServer {
 
     /// デフォルトルームを作成
     async fn create_default_room(&self) {
-        let mut rooms = self.rooms.lock().unwrap();
-        let default_room = GameRoom::new("メインルーム".to_string(), 4);
-        rooms.insert(default_room.id.clone(), default_room);
-        println!("🏠 デフォルトルームを作成しました");
+        let mut rooms = self.state.rooms.lock().unwrap();
+        let mut default_room = GameRoom::new("メインルーム".to_string(), 4, Self::system_time(&self.state.clock));
+        default_room.permanent = true;
+        Self::insert_room(default_room, &mut rooms, &self.state);
+        info!("🏠 デフォルトルームを作成しました");
+    }
+
+    /// 保存されていたルームを復元
+    ///
+    /// # 戻り値
+    /// 1つ以上のルームを復元した場合true
+    fn restore_rooms(&self) -> bool {
+        let snapshots = self.state.room_store.lock().unwrap().load();
+        let now_ms = self.state.clock.now_ms();
+        let mut rooms = self.state.rooms.lock().unwrap();
+        for mut snapshot in snapshots {
+            if let Some(board) = snapshot.board.take() {
+                if let Err(e) = self.state.shared_boards.lock().unwrap().restore(&snapshot.id, board) {
+                    warn!("⚠️ 保存していた盤面を復元できません（アクションの記録から作り直します）: {}", e);
+                }
+            }
+            let room = GameRoom::from_snapshot(snapshot, now_ms);
+            info!("♻️ ルームを復元しました: {} (参加者{}人)", room.name, room.restore.as_ref().map_or(0, |restore| restore.seats.len()));
+            Self::insert_room(room, &mut rooms, &self.state);
+        }
+        !rooms.is_empty()
+    }
+
+    /// 全ルームのスナップショットを保存（前回の保存から変わっていない場合は書き込まない）
+    fn save_rooms(state: &ServerState) {
+        let snapshots: Vec<RoomSnapshot> = {
+            let players_map = state.players.lock().unwrap();
+            let rooms_map = state.rooms.lock().unwrap();
+            let boards = state.shared_boards.lock().unwrap();
+            let mut snapshots: Vec<RoomSnapshot> = rooms_map
+                .values()
+                .map(|room| room.snapshot(&players_map, boards.snapshot(&room.id)))
+                .collect();
+            snapshots.sort_by(|a, b| a.id.cmp(&b.id));
+            snapshots
+        };
+        match state.room_store.lock().unwrap().save(&snapshots) {
+            Ok(true) => debug!("💾 ルームを保存しました: {}件", snapshots.len()),
+            Ok(false) => {}
+            Err(e) => error!("❌ ルームの保存失敗: {}", e),
+        }
+    }
+
+    /// ルームのスナップショットをSNAPSHOT_INTERVAL_MSごとに保存し続ける
+    async fn run_room_snapshots(state: ServerState) {
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(SNAPSHOT_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            Self::save_rooms(&state);
+        }
+    }
+
+    /// サーバーが止まっていて0時に保存できなかった日の配り札を、その日のリーダーボードと一緒に保存する
+    fn archive_missed_days(state: &ServerState) {
+        let today = DailyDeal::at(state.clock.now_ms()).day;
+        let leaderboard = state.leaderboard.lock().unwrap();
+        let mut archive = state.daily_archive.lock().unwrap();
+        let missed = archive.missed_days(today);
+        if missed.is_empty() {
+            return;
+        }
+        for day in &missed {
+            let deal = DailyDeal::at(day * DAY_MS);
+            archive.archive(&deal, leaderboard.entries_for(deal.seed));
+        }
+        if let Err(e) = archive.save() {
+            warn!("⚠️ 過去の配り札の保存失敗: {}", e);
+        }
+        info!("📅 止まっていた間の配り札を保存しました: {}日分", missed.len());
+    }
+
+    /// 0時（UTC）ごとに前日の配り札を保存し、新しい配り札を全員に送り続ける
+    /// 
+    /// 今日と明日の配り札は先に解いておき、切り替えたときに結果を付けて送れるようにします。
+    async fn run_daily_deals(state: ServerState) {
+        loop {
+            let deal = DailyDeal::at(state.clock.now_ms());
+            for seed in [deal.seed, DailyDeal::at(deal.next_change_ms).seed] {
+                Self::solve_deal(&state, seed).await;
+            }
+            let wait_ms = deal.next_change_ms.saturating_sub(state.clock.now_ms());
+            tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
+            
+            let entries = state.leaderboard.lock().unwrap().entries_for(deal.seed).to_vec();
+            {
+                let mut archive = state.daily_archive.lock().unwrap();
+                archive.archive(&deal, &entries);
+                if let Err(e) = archive.save() {
+                    warn!("⚠️ 過去の配り札の保存失敗: {}", e);
+                }
+            }
+            
+            let next = DailyDeal::at(deal.next_change_ms);
+            info!("📅 日替わりの配り札を切り替えました: {}日目 → {}日目（結果{}件を保存）", deal.day, next.day, entries.len());
+            let solved = Self::solved_deal(&state, next.seed);
+            Self::broadcast_to_all(&next.to_message(solved, None), &state.senders, None).await;
+        }
+    }
+    
+    /// 配り札をソルバーで解いて結果を保存する（保存済みの場合は解かない）
+    /// 
+    /// 探索には数秒かかることがあるため、非同期のタスクを止めないよう別のスレッドで解きます。
+    /// 
+    /// # 引数
+    /// * `state` - サーバーの状態
+    /// * `seed` - 配り札のシード
+    async fn solve_deal(state: &ServerState, seed: u64) -> Option<SolvedDeal> {
+        if let Some(solved) = Self::solved_deal(state, seed) {
+            return Some(solved);
+        }
+        let solved = match tokio::task::spawn_blocking(move || solve_cache::solve_seed(seed, SERVER_SEARCH_LIMIT)).await {
+            Ok(solved) => solved,
+            Err(e) => {
+                warn!("⚠️ 配り札を解けませんでした（シード{}）: {}", seed, e);
+                return None;
+            }
+        };
+        info!("🧮 配り札を解きました: シード{} {:?}（難しさ: {:?}）", seed, solved.winnability, solved.difficulty);
+        
+        let mut solved_deals = state.solved_deals.lock().unwrap();
+        solved_deals.insert(solved);
+        if let Err(e) = solved_deals.save() {
+            warn!("⚠️ 解いた配り札の保存失敗: {}", e);
+        }
+        Some(solved)
+    }
+    
+    /// 配り札を別のタスクで解いておく（保存済みの場合は解かない）
+    /// 
+    /// # 引数
+    /// * `state` - サーバーの状態
+    /// * `seed` - 配り札のシード
+    fn solve_deal_later(state: &ServerState, seed: u64) {
+        let state = state.clone();
+        tokio::spawn(async move {
+            Self::solve_deal(&state, seed).await;
+        });
+    }
+    
+    /// 保存済みの配り札を解いた結果を取得
+    /// 
+    /// # 引数
+    /// * `state` - サーバーの状態
+    /// * `seed` - 配り札のシード
+    /// 
+    /// # 戻り値
+    /// 解いた結果（まだ解いていない場合はNone）
+    fn solved_deal(state: &ServerState, seed: u64) -> Option<SolvedDeal> {
+        state.solved_deals.lock().unwrap().get(seed, SERVER_SEARCH_LIMIT)
+    }
+
+    /// 他のインスタンスから届いたイベントを処理し続ける
+    async fn run_backplane(mut receiver: tokio::sync::mpsc::UnboundedReceiver<BackplaneEvent>, state: ServerState) {
+        while let Some(event) = receiver.recv().await {
+            match event {
+                BackplaneEvent::RoomBroadcast { room_id, message, exclude_player, .. } => {
+                    Self::deliver_to_room(&message, &room_id, &state, exclude_player.as_deref());
+                }
+                BackplaneEvent::RoomRegistry { origin, rooms } => {
+                    Self::refresh_mirrored_rooms(&origin, &rooms, &state);
+                    state.remote_rooms.lock().unwrap().update(&origin, rooms, state.clock.now_ms());
+                }
+            }
+        }
+        warn!("⚠️ バックプレーンから切断されました（他のインスタンスとの中継を停止します）");
+    }
+
+    /// このインスタンスのルーム一覧をANNOUNCE_INTERVAL_MSごとに発行し続ける
+    async fn run_room_announcements(state: ServerState) {
+        let Some(backplane) = state.backplane.clone() else {
+            return;
+        };
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(ANNOUNCE_INTERVAL_MS));
+        loop {
+            interval.tick().await;
+            let rooms: Vec<RoomInfo> = {
+                let players_map = state.players.lock().unwrap();
+                let rooms_map = state.rooms.lock().unwrap();
+                rooms_map
+                    .values()
+                    .filter(|room| room.mirrored_from.is_none())
+                    .map(|room| room.to_info(&players_map))
+                    .collect()
+            };
+            backplane.publish(&BackplaneEvent::RoomRegistry {
+                origin: backplane.instance_id().to_string(),
+                rooms,
+            });
+        }
+    }
+
+    /// 他のインスタンスのルームに参加する場合、同じIDのルームをこのインスタンスに作る
+    ///
+    /// # 戻り値
+    /// 参加できる場合Ok、他のインスタンスのパスワード付きルームの場合Err
+    fn mirror_remote_room(room_id: &str, state: &ServerState) -> Result<(), String> {
+        if state.rooms.lock().unwrap().contains_key(room_id) {
+            return Ok(());
+        }
+        let Some((origin, info)) = state.remote_rooms.lock().unwrap().find(room_id, state.clock.now_ms()) else {
+            return Ok(());
+        };
+        if info.has_password {
+            return Err("他のサーバーのパスワード付きルームには参加できません".to_string());
+        }
+
+        let mut rooms_map = state.rooms.lock().unwrap();
+        if !rooms_map.contains_key(room_id) {
+            let mut room = GameRoom::new(info.name.clone(), info.max_players, Self::system_time(&state.clock));
+            room.id = info.id.clone();
+            room.refresh_from(&info);
+            room.mirrored_from = Some(origin);
+            Self::insert_room(room, &mut rooms_map, state);
+            info!("🛰️ 他のサーバーのルームを中継します: {}", room_id);
+        }
+        Ok(())
+    }
+
+    /// 他のインスタンスから届いたルーム一覧で、そのインスタンスのルームを中継しているルームを更新する
+    ///
+    /// # 引数
+    /// * `origin` - ルーム一覧を発行したインスタンスのID
+    /// * `rooms` - そのインスタンスのルーム一覧
+    fn refresh_mirrored_rooms(origin: &str, rooms: &[RoomInfo], state: &ServerState) {
+        let mut rooms_map = state.rooms.lock().unwrap();
+        for info in rooms {
+            if let Some(room) = rooms_map
+                .get_mut(&info.id)
+                .filter(|room| room.mirrored_from.as_deref() == Some(origin))
+            {
+                room.refresh_from(info);
+            }
+        }
+    }
+
+    /// ルームの作成者として数えるキー（セッショントークン、ない場合はプレイヤーID）
+    fn room_creator(player_id: &str, players: &Players) -> String {
+        players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .map(|player| player.session_token.clone())
+            .filter(|token| !token.is_empty())
+            .unwrap_or_else(|| player_id.to_string())
+    }
+
+    /// プレイヤーがルームを作成できるかチェック
+    ///
+    /// # 引数
+    /// * `creator` - 作成するプレイヤーのキー（room_creator()）
+    /// * `rooms_map` - 全ルーム
+    ///
+    /// # 戻り値
+    /// 作成できる場合はOk(())、1人あたり・サーバー全体の上限に達している場合はエラーメッセージ
+    fn check_room_limits(creator: &str, rooms_map: &HashMap<String, GameRoom>) -> Result<(), String> {
+        let created = rooms_map
+            .values()
+            .filter(|room| room.creator.as_deref() == Some(creator))
+            .count();
+        if created >= MAX_ROOMS_PER_PLAYER {
+            return Err(format!("作成できるルームは{}つまでです", MAX_ROOMS_PER_PLAYER));
+        }
+        if rooms_map.len() >= MAX_ROOMS {
+            return Err("ルームの数が上限に達しているため、作成できません".to_string());
+        }
+        Ok(())
+    }
+
+    /// ルームを登録し、そのルームのティックタスクを起動
+    ///
+    /// # 戻り値
+    /// 登録したルームのID
+    fn insert_room(room: GameRoom, rooms_map: &mut HashMap<String, GameRoom>, state: &ServerState) -> String {
+        let room_id = room.id.clone();
+        rooms_map.insert(room_id.clone(), room);
+        tokio::spawn(Self::run_room_ticks(room_id.clone(), state.clone()));
+        room_id
     }
 
     /// 個別の接続を処理
     async fn handle_connection(
         stream: TcpStream,
         addr: SocketAddr,
-        players: Players,
-        rooms: Rooms,
-        connections: Connections,
-        next_color_index: Arc<Mutex<u8>>,
+        state: ServerState,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let ServerState { players, rooms, senders, ratings, preferences, next_color_index, clock, .. } = &state;
         let ws_stream = accept_async(stream).await?;
-        let (ws_sender, mut ws_receiver) = ws_stream.split();
-        
-        // 送信用のsenderをArcで包む
-        let ws_sender = Arc::new(Mutex::new(ws_sender));
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
+        let (tx, mut rx) = outbound::channel(&state.outbound);
+        let (ack_tx, mut ack_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
         let mut player_id: Option<String> = None;
+        let mut recent_ids = DuplicateFilter::new(RECENT_ID_WINDOW);
+        let mut sequences = SequenceTracker::new();
+
+        // 送信タスクを別途起動
+        // 状態を変えるメッセージはReliableで包み、クライアントからAckが届くまで再送する
+        let send_clock = *clock;
+        let sender_task = tokio::spawn(async move {
+            let mut outbox = ReliableOutbox::new();
+            let mut retry_check = tokio::time::interval(std::time::Duration::from_millis(RELIABLE_RETRY_CHECK_MS));
+            loop {
+                tokio::select! {
+                    message = rx.recv() => {
+                        let Some(message) = message else {
+                            break;
+                        };
+                        let text = outbox.wrap(message, send_clock.now_ms() as f64);
+                        if ws_sender.send(Message::Text(text)).await.is_err() {
+                            rx.mark_discarded();
+                            break;
+                        }
+                        rx.mark_sent();
+                    }
+                    Some(message_id) = ack_rx.recv() => outbox.ack(&message_id),
+                    _ = retry_check.tick() => {
+                        for text in outbox.due(send_clock.now_ms() as f64) {
+                            if ws_sender.send(Message::Text(text)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
 
         while let Some(message) = ws_receiver.next().await {
             match message? {
                 Message::Text(text) => {
-                    println!("📥 受信メッセージ: {}", text);
+                    debug!("📥 受信メッセージ: {}バイト", text.len());
                     
-                    match serde_json::from_str::<WebSocketMessage>(&text) {
+                    match WebSocketMessage::parse(&text) {
                         Ok(msg) => {
+                            // Reliableで届いたメッセージには受け取りの確認を返し、再送で重複したものは処理しない
+                            let msg = match msg {
+                                WebSocketMessage::Reliable { message_id, message, sequence } => {
+                                    let is_new = recent_ids.is_new(&message_id);
+                                    let ack = WebSocketMessage::Ack { message_id };
+                                    if tx.send(serde_json::to_string(&ack)?).is_err() {
+                                        warn!("⚠️ Ackの送信失敗: {}", addr);
+                                    }
+                                    if !is_new {
+                                        debug!("🔁 重複したメッセージを無視: {}", addr);
+                                        continue;
+                                    }
+                                    
+                                    // 連番が飛んでいたら、抜けているメッセージの再送をすぐに要求する
+                                    let channel = message.channel();
+                                    match sequence.map(|sequence| sequences.record(channel, sequence)) {
+                                        Some(Arrival::Gap(missing)) => {
+                                            debug!("📭 連番の抜け: {} {} {:?}", addr, channel.as_str(), missing);
+                                            let request = WebSocketMessage::ResendRequest { channel, sequences: missing };
+                                            if tx.send(serde_json::to_string(&request)?).is_err() {
+                                                warn!("⚠️ 再送の要求の送信失敗: {}", addr);
+                                            }
+                                        }
+                                        Some(Arrival::Late) => {
+                                            debug!("🔀 順序が入れ替わって届きました: {} {}", addr, channel.as_str());
+                                        }
+                                        _ => {}
+                                    }
+                                    *message
+                                }
+                                msg => msg,
+                            };
+
+                            // プレイヤーIDは他のプレイヤーにも見えるので、この接続以外のプレイヤーを名乗るメッセージは処理しない
+                            // （確認を通ったメッセージは、すべてこの接続のプレイヤーとして処理する）
+                            if let Err(e) = msg.check_sender(player_id.as_deref()) {
+                                warn!("🚫 なりすましのメッセージを拒否: {} {:?}", addr, msg.sender_id());
+                                let reply = WebSocketMessage::Error {
+                                    message: e,
+                                    request_id: msg.request_id().cloned(),
+                                    field: None,
+                                };
+                                match &player_id {
+                                    Some(id) => Self::send_to_player(id, &reply, senders).await,
+                                    None => {
+                                        if tx.send(serde_json::to_string(&reply)?).is_err() {
+                                            warn!("⚠️ エラーの送信失敗: {}", addr);
+                                        }
+                                    }
+                                }
+                                continue;
+                            }
+                            let sender_id = player_id.clone().unwrap_or_default();
+
                             match msg {
-                                WebSocketMessage::PlayerJoin { player_name, .. } => {
-                                    // 新しいプレイヤーを作成
-                                    let mut player = Player::new(player_name.clone());
+                                WebSocketMessage::PlayerJoin { player_name, session_token, request_id, .. } => {
+                                    // 1つの接続で参加できるのは1人だけ（前のプレイヤーが切断時に片付けられずに残るため）
+                                    if let Some(id) = &player_id {
+                                        let reply = WebSocketMessage::Error {
+                                            message: "この接続ではすでに参加しています".to_string(),
+                                            request_id,
+                                            field: None,
+                                        };
+                                        Self::send_to_player(id, &reply, senders).await;
+                                        continue;
+                                    }
+                                    
+                                    // 保存された設定をセッショントークンから探す（なければ新しいトークンを発行する）
+                                    let saved = session_token
+                                        .as_deref()
+                                        .and_then(|token| preferences.lock().unwrap().get(token).cloned());
+                                    // 再起動前にルームにいたプレイヤーは、設定を保存していなくても同じトークンを使い続ける
+                                    let restored_room = session_token
+                                        .as_deref()
+                                        .and_then(|token| Self::restored_room_for(token, rooms));
+                                    let session_token = match (session_token, &saved, &restored_room) {
+                                        (Some(token), Some(_), _) | (Some(token), _, Some(_)) => token,
+                                        _ => Uuid::new_v4().to_string(),
+                                    };
+                                    
+                                    // 新しいプレイヤーを作成（表示名は保存された名前を優先する）
+                                    // 接続中か別のトークンで押さえられている名前は使えないので、仮の名前にする
+                                    let mut player = Player::new(String::new(), Self::system_time(clock));
+                                    let requested = match saved.as_ref().and_then(|saved| saved.player_name.clone()) {
+                                        Some(name) => Ok(name),
+                                        None => protocol::sanitize_display_name(&player_name),
+                                    };
+                                    let name_in_use = |name: &str| {
+                                        players
+                                            .lock()
+                                            .unwrap()
+                                            .values()
+                                            .any(|player| player.name.to_lowercase() == name.to_lowercase())
+                                    };
+                                    player.name = match requested {
+                                        Ok(name) if !name_in_use(&name) && Self::claim_display_name(&session_token, &name, &state) => name,
+                                        _ => format!("Player-{}", &player.id[..4]),
+                                    };
+                                    player.session_token = session_token.clone();
                                     
-                                    // カラーインデックスを割り当て
+                                    // カラーインデックスを割り当て（保存された色がなければ接続順に循環）
+                                    match saved.and_then(|saved| saved.color_index) {
+                                        Some(color) => player.color_index = color,
+                                        None => {
+                                            let mut color_index = next_color_index.lock().unwrap();
+                                            player.color_index = *color_index;
+                                            *color_index = (*color_index % CURSOR_COLOR_COUNT) + 1;
+                                        }
+                                    }
+                                    
+                                    // 保存されているレーティングを反映
                                     {
-                                        let mut color_index = next_color_index.lock().unwrap();
-                                        player.color_index = *color_index;
-                                        *color_index = (*color_index % 5) + 1; // 1-5の循環
+                                        let record = ratings.lock().unwrap().get(&player.name);
+                                        player.rating = record.rating;
+                                        player.games_rated = record.games_rated;
                                     }
                                     
                                     player_id = Some(player.id.clone());
@@ -270,7 +1488,25 @@ impl SolitaireServer {
                                         players_map.insert(player.id.clone(), player.clone());
                                     }
                                     
-                                    println!("👤 プレイヤー参加: {} ({})", player.name, player.id);
+                                    // 送信チャンネルに追加
+                                    {
+                                        let mut senders_map = senders.lock().unwrap();
+                                        senders_map.insert(player.id.clone(), tx.clone());
+                                    }
+                                    
+                                    info!("👤 プレイヤー参加: {} ({}) レート{}", player.name, player.id, player.rating);
+                                    
+                                    // 本人にプロフィールを通知
+                                    Self::send_to_player(
+                                        &player.id,
+                                        &WebSocketMessage::PlayerProfile { profile: player.profile(), request_id },
+                                        senders
+                                    ).await;
+                                    Self::send_to_player(
+                                        &player.id,
+                                        &WebSocketMessage::SessionToken { session_token },
+                                        senders
+                                    ).await;
                                     
                                     // 他のプレイヤーに通知
                                     Self::broadcast_to_all(
@@ -278,65 +1514,893 @@ impl SolitaireServer {
                                             player_id: player.id.clone(),
                                             player_name: player.name.clone(),
                                             player_index: player.color_index,
+                                            session_token: None,
+                                            request_id: None,
                                         },
-                                        &connections,
+                                        senders,
                                         Some(&player.id)
                                     ).await;
+                                    Self::notify_presence(&player.session_token, &player.name, &state).await;
+                                    
+                                    // 再起動前にいたルームに戻す
+                                    if let Some(room_id) = restored_room {
+                                        Self::rejoin_restored_room(&player.id, &player.session_token, &room_id, &state).await;
+                                    }
                                 }
                                 
-                                WebSocketMessage::MousePosition { player_id: msg_player_id, x, y, timestamp } => {
-                                    // プレイヤーのマウス位置を更新
+                                WebSocketMessage::Ack { message_id } => {
+                                    // サーバーがReliableで送ったメッセージの受け取りの確認は送信タスクへ渡す
+                                    let _ = ack_tx.send(message_id);
+                                }
+                                
+                                WebSocketMessage::Ping { ping_id, client_time_ms, clock_offset_ms } => {
+                                    // 往復時間の測定と時刻合わせに使うので、他の処理より先に受信時刻を付けて返す
+                                    let pong = WebSocketMessage::Pong {
+                                        ping_id,
+                                        client_time_ms,
+                                        server_time_ms: clock.now_ms(),
+                                    };
+                                    if tx.send(serde_json::to_string(&pong)?).is_err() {
+                                        warn!("⚠️ Pongの送信失敗: {}", addr);
+                                    }
+                                    
+                                    // クライアントが推定した時計のずれを、タイムスタンプの変換用に覚えておく
+                                    if let (Some(id), Some(offset)) = (&player_id, clock_offset_ms) {
+                                        if let Some(player) = players.lock().unwrap().get_mut(id) {
+                                            player.clock_offset_ms = offset;
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::MousePosition { x, y, timestamp, sequence, .. } => {
+                                    // 新しい位置より後に届いた古い位置は捨てる
+                                    let arrival = sequence.map(|sequence| sequences.record(Channel::Cursor, sequence));
+                                    if matches!(arrival, Some(Arrival::Late | Arrival::Stale)) {
+                                        debug!("🗑️ 古いカーソル位置を破棄: {}", addr);
+                                        continue;
+                                    }
+                                    
+                                    // プレイヤーのマウス位置を更新
                                     {
                                         let mut players_map = players.lock().unwrap();
-                                        if let Some(player) = players_map.get_mut(&msg_player_id) {
+                                        if let Some(player) = players_map.get_mut(&sender_id) {
                                             player.cursor_x = x;
                                             player.cursor_y = y;
                                         }
                                     }
                                     
-                                    // 他のプレイヤーに位置をブロードキャスト
+                                    // 他のプレイヤーに位置をブロードキャスト（タイムスタンプはサーバーの時刻に直す）
                                     Self::broadcast_to_all(
                                         &WebSocketMessage::MousePosition {
-                                            player_id: msg_player_id.clone(),
+                                            player_id: sender_id.clone(),
                                             x,
                                             y,
-                                            timestamp,
+                                            timestamp: Self::to_server_time(&sender_id, timestamp, &state),
+                                            sequence,
                                         },
-                                        &connections,
-                                        Some(&msg_player_id)
+                                        senders,
+                                        Some(&sender_id)
                                     ).await;
                                 }
                                 
-                                WebSocketMessage::GameAction { player_id: msg_player_id, player_name, action, x, y, timestamp } => {
-                                    println!("🎯 ゲームアクション: {} by {}", action, player_name);
+                                WebSocketMessage::UpdatePreferences { color_index, player_name, .. } => {
+                                    match Self::update_preferences(&sender_id, color_index, player_name, &state) {
+                                        Ok(player) => {
+                                            info!("🎨 設定変更: {} 色{}", player.player_name, player.color_index);
+                                            Self::send_to_player(
+                                                &sender_id,
+                                                &WebSocketMessage::PlayerProfile { profile: player.clone(), request_id: None },
+                                                senders
+                                            ).await;
+                                            // カーソルは全員に見えるので、変更も全員に送る
+                                            Self::broadcast_to_all(
+                                                &WebSocketMessage::PlayerUpdated {
+                                                    player_id: player.player_id,
+                                                    player_name: player.player_name,
+                                                    color_index: player.color_index,
+                                                },
+                                                senders,
+                                                None
+                                            ).await;
+                                            Self::notify_player_presence(&sender_id, &state).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::Reaction { emote, .. } => {
+                                    // リアクションはカーソルの横に表示するので、カーソルと同じく全員に送る
+                                    // （ルームに参加していない観戦者も送受信できる）
+                                    let allowed = players
+                                        .lock()
+                                        .unwrap()
+                                        .get_mut(&sender_id)
+                                        .map(|player| player.record_reaction(Self::system_time(clock)));
+                                    let rejected = match allowed {
+                                        None => Some("プレイヤーが見つかりません"),
+                                        Some(false) => Some("リアクションの送信が多すぎます。少し待ってください"),
+                                        Some(true) => None,
+                                    };
+                                    if let Some(e) = rejected {
+                                        Self::send_error(&sender_id, e, senders).await;
+                                        continue;
+                                    }
                                     
-                                    // 他のプレイヤーにアクションをブロードキャスト
+                                    debug!("{} リアクション: {}", emote.as_emoji(), sender_id);
                                     Self::broadcast_to_all(
-                                        &WebSocketMessage::GameAction {
-                                            player_id: msg_player_id.clone(),
-                                            player_name,
-                                            action,
+                                        &WebSocketMessage::Reaction {
+                                            player_id: sender_id.clone(),
+                                            emote,
+                                        },
+                                        senders,
+                                        Some(&sender_id)
+                                    ).await;
+                                }
+                                
+                                WebSocketMessage::GameAction { player_name, action, x, y, timestamp, .. } => {
+                                    debug!("🎯 ゲームアクション: {} by {}", action, player_name);
+                                    Self::record_activity(&sender_id, 0, &state).await;
+                                    
+                                    // 共有盤面のルームでは、盤面の写しで観戦者の操作・持ち主以外のカードの移動・
+                                    // ホスト以外の山札の戻しを断る
+                                    if let Err(e) = Self::check_board_action(&sender_id, &action, &state) {
+                                        Self::send_error(&sender_id, &e, senders).await;
+                                        continue;
+                                    }
+                                    
+                                    // パワーアップはサーバーで使えるかを確かめ、使ったプレイヤーを含むルームの全員に送る
+                                    let power_up_room = match PowerUp::from_action(&action) {
+                                        Some(power_up) => {
+                                            match power_up.and_then(|power_up| Self::use_power_up(&sender_id, power_up, &state)) {
+                                                Ok(room_id) => {
+                                                    info!("✨ パワーアップ: {} by {} (ルーム{})", action, player_name, room_id);
+                                                    Some(room_id)
+                                                }
+                                                Err(e) => {
+                                                    Self::send_error(&sender_id, &e, senders).await;
+                                                    continue;
+                                                }
+                                            }
+                                        }
+                                        None => None,
+                                    };
+                                    
+                                    // 再起動後に盤面を再現できるよう、ゲーム中のルームの記録に追加する
+                                    let timestamp = Self::to_server_time(&sender_id, timestamp, &state);
+                                    Self::log_action(
+                                        LoggedAction {
+                                            player_id: sender_id.clone(),
+                                            action: action.clone(),
                                             x,
                                             y,
                                             timestamp,
                                         },
-                                        &connections,
-                                        Some(&msg_player_id)
+                                        &state,
+                                    );
+                                    
+                                    // 他のプレイヤーにアクションをブロードキャスト（タイムスタンプはサーバーの時刻に直す）
+                                    let message = WebSocketMessage::GameAction {
+                                        player_id: sender_id.clone(),
+                                        player_name,
+                                        action,
+                                        x,
+                                        y,
+                                        timestamp,
+                                    };
+                                    match power_up_room {
+                                        Some(room_id) => Self::broadcast_to_room(&message, &room_id, &state, None).await,
+                                        None => Self::broadcast_to_all(&message, senders, Some(&sender_id)).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::ScoreUpdate { room_id, score, foundation_cards, .. } => {
+                                    let is_member = rooms
+                                        .lock()
+                                        .unwrap()
+                                        .get(&room_id)
+                                        .is_some_and(|room| room.players.contains(&sender_id));
+                                    if !is_member {
+                                        Self::send_error(&sender_id, "ルームに参加していません", senders).await;
+                                        continue;
+                                    }
+                                    
+                                    // 後から参加したプレイヤーに送れるよう、最新のスコアを覚えておく
+                                    if let Some(player) = players.lock().unwrap().get_mut(&sender_id) {
+                                        player.score = score;
+                                        player.foundation_cards = foundation_cards;
+                                    }
+                                    
+                                    debug!("📊 スコア更新: {} = {} ({}枚)", sender_id, score, foundation_cards);
+                                    Self::broadcast_to_room(
+                                        &WebSocketMessage::ScoreUpdate {
+                                            room_id: room_id.clone(),
+                                            player_id: sender_id.clone(),
+                                            score,
+                                            foundation_cards,
+                                        },
+                                        &room_id,
+                                        &state,
+                                        Some(&sender_id)
+                                    ).await;
+                                    
+                                    // コンボを数えるルームのレース中は、伸びたコンボを本人を含む全員に送る
+                                    let combo_update = rooms.lock().unwrap().get_mut(&room_id).and_then(|room| {
+                                        let racing = room.combo_window_seconds > 0 && matches!(room.game_state, GameState::Playing);
+                                        racing.then(|| {
+                                            room.combos.record(
+                                                &room_id,
+                                                &sender_id,
+                                                foundation_cards,
+                                                room.combo_window_seconds,
+                                                state.clock.now_ms(),
+                                            )
+                                        })?
+                                    });
+                                    if let Some(message) = combo_update {
+                                        Self::broadcast_to_room(&message, &room_id, &state, None).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::CardBackChanged { room_id, card_back, .. } => {
+                                    let is_member = rooms
+                                        .lock()
+                                        .unwrap()
+                                        .get(&room_id)
+                                        .is_some_and(|room| room.players.contains(&sender_id));
+                                    if !is_member {
+                                        Self::send_error(&sender_id, "ルームに参加していません", senders).await;
+                                        continue;
+                                    }
+                                    
+                                    // 後から参加したプレイヤーにもスコアボードで伝える
+                                    if let Some(player) = players.lock().unwrap().get_mut(&sender_id) {
+                                        player.card_back = card_back;
+                                    }
+                                    
+                                    debug!("🎴 カードの裏面の変更: {} = {:?}", sender_id, card_back);
+                                    Self::broadcast_to_room(
+                                        &WebSocketMessage::CardBackChanged {
+                                            room_id: room_id.clone(),
+                                            player_id: sender_id.clone(),
+                                            card_back,
+                                        },
+                                        &room_id,
+                                        &state,
+                                        Some(&sender_id)
+                                    ).await;
+                                }
+                                
+                                WebSocketMessage::IdleStatus { room_id, idle_seconds, .. } => {
+                                    let is_member = rooms
+                                        .lock()
+                                        .unwrap()
+                                        .get(&room_id)
+                                        .is_some_and(|room| room.players.contains(&sender_id));
+                                    if !is_member {
+                                        Self::send_error(&sender_id, "ルームに参加していません", senders).await;
+                                        continue;
+                                    }
+                                    
+                                    debug!("💤 操作していない時間: {} = {}秒", sender_id, idle_seconds);
+                                    Self::record_activity(&sender_id, idle_seconds, &state).await;
+                                }
+                                
+                                WebSocketMessage::GrabCard { room_id, card_id, timestamp, .. } => {
+                                    Self::record_activity(&sender_id, 0, &state).await;
+                                    Self::grab_card(&sender_id, &room_id, &card_id, timestamp, &state).await;
+                                }
+                                
+                                WebSocketMessage::SetSpectating { room_id, spectating, .. } => {
+                                    let changed = {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        match rooms_map.get_mut(&room_id) {
+                                            None => Err("ルームが存在しません".to_string()),
+                                            Some(room) if !room.players.contains(&sender_id) => {
+                                                Err("ルームに参加していません".to_string())
+                                            }
+                                            Some(room) if !room.shared_board => {
+                                                Err("共有盤面のルームではありません".to_string())
+                                            }
+                                            Some(room) if spectating && room.is_host(&sender_id) => {
+                                                Err("ホストは観戦に切り替えられません".to_string())
+                                            }
+                                            Some(room) => {
+                                                if spectating {
+                                                    room.spectators.insert(sender_id.clone());
+                                                } else {
+                                                    room.spectators.remove(&sender_id);
+                                                }
+                                                Ok(())
+                                            }
+                                        }
+                                    };
+                                    match changed {
+                                        Ok(()) => {
+                                            info!("👀 観戦の切り替え: {} = {} (ルーム{})", sender_id, spectating, room_id);
+                                            Self::broadcast_permissions(&room_id, &state).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::SetCardOwner { room_id, card_id, owner_id, .. } => {
+                                    let changed = Self::check_host(&sender_id, &room_id, rooms).and_then(|()| {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        let room = rooms_map
+                                            .get_mut(&room_id)
+                                            .ok_or_else(|| "ルームが存在しません".to_string())?;
+                                        if !room.shared_board {
+                                            return Err("共有盤面のルームではありません".to_string());
+                                        }
+                                        match owner_id {
+                                            Some(owner_id) if !room.players.contains(&owner_id) => {
+                                                Err("持ち主はルームの参加者にしてください".to_string())
+                                            }
+                                            Some(owner_id) => {
+                                                room.card_owners.insert(card_id.clone(), owner_id);
+                                                Ok(())
+                                            }
+                                            None => {
+                                                room.card_owners.remove(&card_id);
+                                                Ok(())
+                                            }
+                                        }
+                                    });
+                                    match changed {
+                                        Ok(()) => {
+                                            debug!("🔐 カードの持ち主の変更: {} (ルーム{})", card_id, room_id);
+                                            Self::broadcast_permissions(&room_id, &state).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::ReleaseCard { room_id, card_id, .. } => {
+                                    Self::record_activity(&sender_id, 0, &state).await;
+                                    
+                                    // 取り上げられた後に届いた離す操作は無視する
+                                    let released = rooms
+                                        .lock()
+                                        .unwrap()
+                                        .get_mut(&room_id)
+                                        .is_some_and(|room| room.card_claims.release(&card_id, &sender_id));
+                                    if released {
+                                        Self::broadcast_to_room(
+                                            &WebSocketMessage::CardReleased { room_id: room_id.clone(), card_id },
+                                            &room_id,
+                                            &state,
+                                            None
+                                        ).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::JoinRoom { room_id, password, request_id, .. } => {
+                                    let identity = players
+                                        .lock()
+                                        .unwrap()
+                                        .get(&sender_id)
+                                        .map(Player::identity)
+                                        .unwrap_or_default();
+                                    let entry = Self::mirror_remote_room(&room_id, &state).and_then(|_| {
+                                        rooms
+                                            .lock()
+                                            .unwrap()
+                                            .get(&room_id)
+                                            .map_or(Ok(()), |room| room.check_entry(&identity, password.as_deref(), Self::system_time(clock)))
+                                    });
+                                    
+                                    if let Err(e) = entry {
+                                        Self::send_error_reply(&sender_id, &e, request_id, senders).await;
+                                    } else if !Self::join_room(&sender_id, &room_id, request_id.as_deref(), &state).await {
+                                        Self::send_error_reply(&sender_id, "ルームに参加できません（存在しないか満員です）", request_id, senders).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::GetRoomList { player_id: msg_player_id, request_id } => {
+                                    let room_list = Self::room_list(&state, request_id);
+                                    Self::send_to_player(&msg_player_id, &room_list, senders).await;
+                                }
+                                
+                                WebSocketMessage::AddFriend { player_id: msg_player_id, friend_id, request_id } => {
+                                    let added = Self::add_friend(player_id.as_deref(), &msg_player_id, &friend_id, &state);
+                                    let now_friends = added.is_ok();
+                                    Self::reply_friend_list(added, player_id.as_deref(), request_id, &state).await;
+                                    // 相手もこちらをフレンドにしていれば、これで相手に在席状況が見えるようになる
+                                    if now_friends {
+                                        Self::notify_player_presence(&msg_player_id, &state).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::RemoveFriend { player_id: msg_player_id, friend_name, request_id } => {
+                                    let removed = Self::remove_friend(player_id.as_deref(), &msg_player_id, &friend_name, &state);
+                                    Self::reply_friend_list(removed, player_id.as_deref(), request_id, &state).await;
+                                }
+                                
+                                WebSocketMessage::GetFriends { player_id: msg_player_id, request_id } => {
+                                    let owner = Self::friend_owner_token(player_id.as_deref(), &msg_player_id, players);
+                                    Self::reply_friend_list(owner, player_id.as_deref(), request_id, &state).await;
+                                }
+                                
+                                WebSocketMessage::InviteToRoom { player_id: msg_player_id, target_id, room_id } => {
+                                    if let Err(e) = Self::invite_to_room(player_id.as_deref(), &msg_player_id, &target_id, &room_id, &state).await {
+                                        if let Some(id) = &player_id {
+                                            Self::send_error(id, &e, senders).await;
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::RespondToInvite { player_id: msg_player_id, invite_id, accept } => {
+                                    if let Err(e) = Self::respond_to_invite(player_id.as_deref(), &msg_player_id, &invite_id, accept, &state).await {
+                                        if let Some(id) = &player_id {
+                                            Self::send_error(id, &e, senders).await;
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::GetDailyDeal { player_id: msg_player_id, request_id } => {
+                                    let deal = DailyDeal::at(state.clock.now_ms());
+                                    let solved = Self::solved_deal(&state, deal.seed);
+                                    Self::send_to_player(&msg_player_id, &deal.to_message(solved, request_id), senders).await;
+                                }
+                                
+                                WebSocketMessage::GetDailyArchive { player_id: msg_player_id, request_id } => {
+                                    let deals = state.daily_archive.lock().unwrap().deals().to_vec();
+                                    Self::send_to_player(
+                                        &msg_player_id,
+                                        &WebSocketMessage::DailyArchive { deals, request_id },
+                                        senders
+                                    ).await;
+                                }
+                                
+                                WebSocketMessage::QuickMatch { .. } => {
+                                    let room_id = Self::find_match_room(&sender_id, &state);
+                                    if !Self::join_room(&sender_id, &room_id, None, &state).await {
+                                        Self::send_error(&sender_id, "マッチングに失敗しました", senders).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::CreateRoom { name, max_players, password, turn_time_limit, combo_window_seconds, power_ups, shared_board, request_id, .. } => {
+                                    let creator = Self::room_creator(&sender_id, players);
+                                    let created = {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        Self::check_room_limits(&creator, &rooms_map).map(|()| {
+                                            let mut room = GameRoom::new(name, max_players.unwrap_or(4), Self::system_time(clock));
+                                            room.password = password
+                                                .filter(|password| !password.is_empty())
+                                                .map(|password| PasswordHash::new(&password));
+                                            room.turn_time_limit = turn_time_limit.unwrap_or(0);
+                                            room.combo_window_seconds = combo_window_seconds.unwrap_or(0);
+                                            room.power_ups = power_ups.unwrap_or(false);
+                                            room.shared_board = shared_board.unwrap_or(false);
+                                            room.creator = Some(creator);
+                                            info!("🏠 ルームを作成しました: {} (作成者: {})", room.name, sender_id);
+                                            Self::insert_room(room, &mut rooms_map, &state)
+                                        })
+                                    };
+                                    let room_id = match created {
+                                        Ok(room_id) => room_id,
+                                        Err(e) => {
+                                            Self::send_error_reply(&sender_id, &e, request_id, senders).await;
+                                            continue;
+                                        }
+                                    };
+                                    
+                                    // 作成者が最初の参加者としてホストになり、参加の通知（JoinRoom）が作成の応答になる
+                                    if !Self::join_room(&sender_id, &room_id, request_id.as_deref(), &state).await {
+                                        Self::send_error_reply(&sender_id, "作成したルームに参加できません", request_id, senders).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::LeaveRoom { room_id, .. } => {
+                                    Self::leave_room(&sender_id, &room_id, &state).await;
+                                }
+                                
+                                WebSocketMessage::GameResult { result, .. } => {
+                                    info!("📋 ゲーム結果受信: {} -> {}", sender_id, result);
+                                    
+                                    let result = Self::mark_casual_result(&sender_id, result, &state);
+                                    Self::record_game_result(&sender_id, &result, &state).await;
+                                    
+                                    // 他のプレイヤーに結果をブロードキャスト
+                                    Self::broadcast_to_all(
+                                        &WebSocketMessage::GameResult {
+                                            player_id: sender_id.clone(),
+                                            result,
+                                        },
+                                        senders,
+                                        Some(&sender_id)
                                     ).await;
                                 }
                                 
+                                WebSocketMessage::SignStats { player_id: msg_player_id, request_id } => {
+                                    let signed = Self::check_synced_account(player_id.as_deref(), &msg_player_id, players)
+                                        .and_then(|session_token| Self::signed_account_stats(&session_token, &state));
+                                    match signed {
+                                        Ok(export) => {
+                                            info!("🔏 成績の書き出しに署名しました: {}", msg_player_id);
+                                            Self::send_to_player(
+                                                &msg_player_id,
+                                                &WebSocketMessage::StatsSigned { export, request_id },
+                                                senders
+                                            ).await;
+                                        }
+                                        Err(e) => {
+                                            if let Some(id) = &player_id {
+                                                Self::send_error_reply(id, &e, request_id, senders).await;
+                                            }
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::VerifyStats { player_id: msg_player_id, export, request_id } => {
+                                    match Self::check_synced_account(player_id.as_deref(), &msg_player_id, players) {
+                                        Ok(_) => {
+                                            // 署名が正しくない場合もエラーではなく、理由を付けて結果として返す
+                                            let verified = StatsExport::parse(&export).and_then(|export| {
+                                                export.verify(&state.stats_signing_key)?;
+                                                Ok(export.signature)
+                                            });
+                                            let (signature, reason) = match verified {
+                                                Ok(signature) => (signature, None),
+                                                Err(e) => (None, Some(e)),
+                                            };
+                                            Self::send_to_player(
+                                                &msg_player_id,
+                                                &WebSocketMessage::StatsVerified {
+                                                    valid: signature.is_some(),
+                                                    reason,
+                                                    signature,
+                                                    request_id,
+                                                },
+                                                senders
+                                            ).await;
+                                        }
+                                        Err(e) => {
+                                            if let Some(id) = &player_id {
+                                                Self::send_error_reply(id, &e, request_id, senders).await;
+                                            }
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::UpdateNotificationSettings { player_id: msg_player_id, endpoint, turn, room_full, request_id } => {
+                                    match Self::update_notification_settings(player_id.as_deref(), &msg_player_id, endpoint, turn, room_full, &state) {
+                                        Ok(settings) => {
+                                            Self::send_to_player(
+                                                &msg_player_id,
+                                                &WebSocketMessage::NotificationSettings {
+                                                    enabled: settings.is_some(),
+                                                    turn: settings.as_ref().is_some_and(|settings| settings.turn),
+                                                    room_full: settings.as_ref().is_some_and(|settings| settings.room_full),
+                                                    request_id,
+                                                },
+                                                senders
+                                            ).await;
+                                        }
+                                        Err(e) => {
+                                            if let Some(id) = &player_id {
+                                                Self::send_error_reply(id, &e, request_id, senders).await;
+                                            }
+                                        }
+                                    }
+                                }
+                                
+                                WebSocketMessage::CreateTournament { room_id, rounds, base_seed, .. } => {
+                                    let created = {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        match rooms_map.get_mut(&room_id) {
+                                            None => Err("ルームが存在しません".to_string()),
+                                            Some(room) if !room.players.contains(&sender_id) => {
+                                                Err("ルームに参加していません".to_string())
+                                            }
+                                            Some(room) if !room.is_host(&sender_id) => {
+                                                Err("トーナメントを作成できるのはホストのみです".to_string())
+                                            }
+                                            Some(room) if room.has_active_tournament() => {
+                                                Err("このルームでは既にトーナメントが開催中です".to_string())
+                                            }
+                                            Some(room) => {
+                                                let base_seed = base_seed.unwrap_or_else(|| Rng::from_entropy().next_u64());
+                                                let tournament = Tournament::new(sender_id.clone(), rounds, base_seed);
+                                                let created = WebSocketMessage::TournamentCreated {
+                                                    tournament_id: tournament.id.clone(),
+                                                    room_id: room_id.clone(),
+                                                    host_id: sender_id.clone(),
+                                                    rounds: tournament.total_rounds(),
+                                                };
+                                                room.tournament = Some(tournament);
+                                                Ok(created)
+                                            }
+                                        }
+                                    };
+                                    
+                                    match created {
+                                        Ok(message) => {
+                                            info!("🏁 トーナメント作成: ルーム{} ({}ラウンド)", room_id, rounds.max(1));
+                                            Self::broadcast_to_room(&message, &room_id, &state, None).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::StartTournament { room_id, .. } => {
+                                    // 参加者名を先に解決（ロック順序: players → rooms）
+                                    let participants: Vec<(String, String)> = {
+                                        let room_players = rooms
+                                            .lock()
+                                            .unwrap()
+                                            .get(&room_id)
+                                            .map(|room| room.players.clone())
+                                            .unwrap_or_default();
+                                        let players_map = players.lock().unwrap();
+                                        room_players
+                                            .into_iter()
+                                            .map(|id| {
+                                                let name = players_map
+                                                    .get(&id)
+                                                    .map(|player| player.name.clone())
+                                                    .unwrap_or_else(|| "Unknown".to_string());
+                                                (id, name)
+                                            })
+                                            .collect()
+                                    };
+                                    
+                                    let started = {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        match rooms_map.get_mut(&room_id) {
+                                            None => Err("ルームが存在しません".to_string()),
+                                            Some(room) => match room.tournament.as_mut() {
+                                                None => Err("トーナメントが作成されていません".to_string()),
+                                                Some(t) if t.host_id != sender_id => {
+                                                    Err("トーナメントを開始できるのはホストのみです".to_string())
+                                                }
+                                                Some(t) => t.start(participants).map(|seed| {
+                                                    let message = WebSocketMessage::TournamentRoundStart {
+                                                        tournament_id: t.id.clone(),
+                                                        round: t.round_number(),
+                                                        total_rounds: t.total_rounds(),
+                                                        seed,
+                                                    };
+                                                    room.game_state = GameState::Playing;
+                                                    message
+                                                }),
+                                            },
+                                        }
+                                    };
+                                    
+                                    match started {
+                                        Ok(message) => {
+                                            info!("🚦 トーナメント開始: ルーム{}", room_id);
+                                            Self::dispatch_room_messages(vec![message], &room_id, &state).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::AddBot { room_id, count, moves_per_second, mistake_probability, .. } => {
+                                    if let Err(e) = Self::check_host(&sender_id, &room_id, rooms) {
+                                        Self::send_error(&sender_id, &e, senders).await;
+                                        continue;
+                                    }
+                                    
+                                    let config = BotConfig::new(moves_per_second, mistake_probability);
+                                    let empty_seats = rooms
+                                        .lock()
+                                        .unwrap()
+                                        .get(&room_id)
+                                        .map_or(0, |room| (room.max_players as usize).saturating_sub(room.players.len()));
+                                    let count = count.map_or(empty_seats, |count| (count as usize).min(empty_seats));
+                                    
+                                    if count == 0 {
+                                        Self::send_error(&sender_id, "ルームに空席がありません", senders).await;
+                                        continue;
+                                    }
+                                    
+                                    for _ in 0..count {
+                                        let bot = Player::new_bot(config, Self::system_time(clock));
+                                        let bot_id = bot.id.clone();
+                                        info!("🤖 ボット追加: {} -> ルーム{}", bot.name, room_id);
+                                        players.lock().unwrap().insert(bot_id.clone(), bot);
+                                        Self::join_room(&bot_id, &room_id, None, &state).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::StartRace { room_id, seed, .. } => {
+                                    // ホストは全員の準備完了を待たずに開始できる
+                                    let started = Self::check_host(&sender_id, &room_id, rooms)
+                                        .and_then(|()| Self::start_countdown(&room_id, seed, &state));
+                                    if let Err(e) = started {
+                                        Self::send_error(&sender_id, &e, senders).await;
+                                    }
+                                }
+                                
+                                WebSocketMessage::SetReady { room_id, ready, .. } => {
+                                    let updated = {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        match rooms_map.get_mut(&room_id) {
+                                            None => Err("ルームが存在しません".to_string()),
+                                            Some(room) if !room.players.contains(&sender_id) => {
+                                                Err("ルームに参加していません".to_string())
+                                            }
+                                            Some(room) => {
+                                                if ready {
+                                                    room.ready.insert(sender_id.clone());
+                                                } else {
+                                                    room.ready.remove(&sender_id);
+                                                }
+                                                Ok(())
+                                            }
+                                        }
+                                    };
+                                    
+                                    match updated {
+                                        Ok(()) => {
+                                            debug!("✋ 準備完了: {} = {}", sender_id, ready);
+                                            Self::update_readiness(&room_id, &state).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::KickPlayer { room_id, target_id, .. } => {
+                                    match Self::check_kick(&sender_id, &room_id, &target_id, rooms) {
+                                        Ok(()) => Self::kick_player(&target_id, &room_id, false, &state).await,
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::BanPlayer { room_id, target_id, .. } => {
+                                    match Self::check_kick(&sender_id, &room_id, &target_id, rooms) {
+                                        Ok(()) => {
+                                            Self::kick_player(&target_id, &room_id, true, &state).await;
+                                            Self::send_ban_list(&sender_id, &room_id, rooms, senders).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::UnbanPlayer { room_id, target_name, .. } => {
+                                    let unbanned = Self::check_host(&sender_id, &room_id, rooms).and_then(|()| {
+                                        let removed = rooms
+                                            .lock()
+                                            .unwrap()
+                                            .get_mut(&room_id)
+                                            .is_some_and(|room| {
+                                                let before = room.banned.len();
+                                                room.banned.retain(|_, name| *name != target_name);
+                                                room.banned.len() < before
+                                            });
+                                        if removed {
+                                            Ok(())
+                                        } else {
+                                            Err(format!("{}は参加禁止になっていません", target_name))
+                                        }
+                                    });
+                                    
+                                    match unbanned {
+                                        Ok(()) => {
+                                            info!("🔓 参加禁止を解除: {} -> ルーム{}", target_name, room_id);
+                                            Self::send_ban_list(&sender_id, &room_id, rooms, senders).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::UpdateRoomSettings { room_id, name, max_players, password, turn_time_limit, combo_window_seconds, power_ups, afk_seconds, pause_on_afk, afk_forfeit_turns, .. } => {
+                                    let updated = Self::check_host(&sender_id, &room_id, rooms).and_then(|()| {
+                                        let mut rooms_map = rooms.lock().unwrap();
+                                        let room = rooms_map
+                                            .get_mut(&room_id)
+                                            .ok_or_else(|| "ルームが存在しません".to_string())?;
+                                        // レース中に変えると、パワーアップの有無などが参加者ごとに食い違ってしまう
+                                        if matches!(room.game_state, GameState::Playing) {
+                                            return Err("レース中はルームの設定を変更できません".to_string());
+                                        }
+                                        if let Some(max_players) = max_players.filter(|&max| (max as usize) < room.players.len()) {
+                                            return Err(format!("定員（{}人）を参加者数より少なくできません", max_players));
+                                        }
+                                        if let Some(name) = name {
+                                            room.name = name;
+                                        }
+                                        if let Some(max_players) = max_players {
+                                            room.max_players = max_players;
+                                        }
+                                        if let Some(password) = password {
+                                            room.password = Some(password)
+                                                .filter(|password| !password.is_empty())
+                                                .map(|password| PasswordHash::new(&password));
+                                        }
+                                        if let Some(turn_time_limit) = turn_time_limit {
+                                            room.turn_time_limit = turn_time_limit;
+                                        }
+                                        if let Some(combo_window_seconds) = combo_window_seconds {
+                                            room.combo_window_seconds = combo_window_seconds;
+                                        }
+                                        if let Some(power_ups) = power_ups {
+                                            room.power_ups = power_ups;
+                                        }
+                                        if let Some(afk_seconds) = afk_seconds {
+                                            room.afk_seconds = afk_seconds;
+                                        }
+                                        if let Some(pause_on_afk) = pause_on_afk {
+                                            room.pause_on_afk = pause_on_afk;
+                                        }
+                                        if let Some(afk_forfeit_turns) = afk_forfeit_turns {
+                                            room.afk_forfeit_turns = afk_forfeit_turns;
+                                        }
+                                        Ok(WebSocketMessage::RoomSettingsChanged {
+                                            room_id: room_id.clone(),
+                                            name: room.name.clone(),
+                                            max_players: room.max_players,
+                                            has_password: room.password.is_some(),
+                                            turn_time_limit: room.turn_time_limit,
+                                            combo_window_seconds: room.combo_window_seconds,
+                                            power_ups: room.power_ups,
+                                            afk_seconds: room.afk_seconds,
+                                            pause_on_afk: room.pause_on_afk,
+                                            afk_forfeit_turns: room.afk_forfeit_turns,
+                                        })
+                                    });
+                                    
+                                    match updated {
+                                        Ok(message) => {
+                                            info!("⚙️ ルーム設定変更: ルーム{}", room_id);
+                                            Self::broadcast_to_room(&message, &room_id, &state, None).await;
+                                        }
+                                        Err(e) => Self::send_error(&sender_id, &e, senders).await,
+                                    }
+                                }
+                                
+                                WebSocketMessage::RtcSignal { room_id, from_player_id, to_player_id, signal } => {
+                                    // 接続交渉は同じルームの参加者同士でだけ、相手1人にそのまま中継する
+                                    match Self::check_rtc_signal(player_id.as_deref(), &from_player_id, &to_player_id, &room_id, rooms) {
+                                        Ok(()) => {
+                                            Self::send_to_player(
+                                                &to_player_id,
+                                                &WebSocketMessage::RtcSignal {
+                                                    room_id,
+                                                    from_player_id,
+                                                    to_player_id: to_player_id.clone(),
+                                                    signal,
+                                                },
+                                                senders
+                                            ).await;
+                                        }
+                                        Err(e) => {
+                                            if let Some(id) = &player_id {
+                                                Self::send_error(id, &e, senders).await;
+                                            }
+                                        }
+                                    }
+                                }
+                                
                                 _ => {
-                                    println!("⚠️ 未対応メッセージタイプ: {:?}", msg);
+                                    warn!("⚠️ 未対応メッセージタイプ: {:?}", msg);
                                 }
                             }
                         }
                         Err(e) => {
-                            println!("❌ メッセージパースエラー: {}", e);
+                            error!("❌ メッセージパースエラー: {}", e);
+                            // 座標が不正な場合は、どのフィールドが不正だったかも返す
+                            let reply = WebSocketMessage::Error {
+                                message: e,
+                                request_id: WebSocketMessage::request_id_of(&text),
+                                field: WebSocketMessage::invalid_field_of(&text),
+                            };
+                            match &player_id {
+                                Some(id) => Self::send_to_player(id, &reply, senders).await,
+                                // 参加前（PlayerJoinが不正だった場合など）は、まだ登録されていないこの接続へ直接返す
+                                None => {
+                                    if tx.send(serde_json::to_string(&reply)?).is_err() {
+                                        warn!("⚠️ エラーの送信失敗: {}", addr);
+                                    }
+                                }
+                            }
                         }
                     }
                 }
                 Message::Close(_) => {
-                    println!("🔌 接続クローズ: {}", addr);
+                    info!("🔌 接続クローズ: {}", addr);
                     break;
                 }
                 _ => {}
@@ -345,78 +2409,2041 @@ impl SolitaireServer {
 
         // プレイヤーが切断した場合のクリーンアップ
         if let Some(pid) = player_id {
-            let player_name = {
+            {
+                let mut senders_map = senders.lock().unwrap();
+                senders_map.remove(&pid);
+            }
+            
+            // ルームに参加していた場合は退室させる
+            let room_id = players
+                .lock()
+                .unwrap()
+                .get(&pid)
+                .and_then(|player| player.room_id.clone());
+            if let Some(room_id) = room_id {
+                Self::leave_room(&pid, &room_id, &state).await;
+            }
+            
+            let (player_name, session_token) = {
                 let mut players_map = players.lock().unwrap();
                 if let Some(player) = players_map.remove(&pid) {
-                    player.name
+                    (player.name, player.session_token)
                 } else {
-                    "Unknown".to_string()
+                    ("Unknown".to_string(), String::new())
                 }
             };
             
-            {
-                let mut connections_map = connections.lock().unwrap();
-                connections_map.remove(&pid);
-            }
-            
-            println!("👋 プレイヤー退出: {} ({})", player_name, pid);
+            info!("👋 プレイヤー退出: {} ({})", player_name, pid);
+            state.invites.lock().unwrap().remove_player(&pid);
             
             // 他のプレイヤーに退出を通知
             Self::broadcast_to_all(
                 &WebSocketMessage::PlayerLeft {
                     player_id: pid,
-                    player_name,
+                    player_name: player_name.clone(),
                 },
-                &connections,
+                senders,
                 None
             ).await;
+            Self::notify_presence(&session_token, &player_name, &state).await;
         }
 
+        // 送信タスクを終了
+        sender_task.abort();
+
         Ok(())
     }
 
-    /// 全プレイヤーにメッセージをブロードキャスト
-    async fn broadcast_to_all(
-        message: &WebSocketMessage,
-        connections: &Connections,
-        exclude_player: Option<&str>,
-    ) {
-        let message_text = match serde_json::to_string(message) {
-            Ok(text) => text,
-            Err(e) => {
-                println!("❌ メッセージシリアライゼーションエラー: {}", e);
-                return;
-            }
+    /// プレイヤーをルームに参加させる
+    ///
+    /// 既に別のルームにいる場合は先に退室させ、
+    /// 参加後はルーム内に参加とプロフィールを通知します。
+    ///
+    /// # 引数
+    /// * `request_id` - 参加の通知に付けて返す要求のID（クライアントが応答を待つ場合のみ）
+    ///
+    /// # 戻り値
+    /// 参加できた場合true
+    async fn join_room(player_id: &str, room_id: &str, request_id: Option<&str>, state: &ServerState) -> bool {
+        let ServerState { players, rooms, senders, .. } = state;
+        let previous_room = players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .and_then(|player| player.room_id.clone());
+        if let Some(previous_room) = previous_room.filter(|id| id != room_id) {
+            Self::leave_room(player_id, &previous_room, state).await;
+        }
+
+        let joined = {
+            let mut rooms_map = rooms.lock().unwrap();
+            rooms_map
+                .get_mut(room_id)
+                .is_some_and(|room| room.add_player(player_id.to_string()))
         };
 
-        let connections_map = connections.lock().unwrap();
-        for (player_id, _connection) in connections_map.iter() {
-            if let Some(exclude) = exclude_player {
-                if player_id == exclude {
-                    continue;
+        if !joined {
+            return false;
+        }
+
+        let joined_player = {
+            let mut players_map = players.lock().unwrap();
+            let (taken, casual_seed) = rooms
+                .lock()
+                .unwrap()
+                .get(room_id)
+                .map(|room| (room.taken_colors(player_id, &players_map), room.casual_race_seed()))
+                .unwrap_or_default();
+            players_map.get_mut(player_id).map(|player| {
+                player.room_id = Some(room_id.to_string());
+                // パワーアップを使えるレースの途中から参加した場合も、そのレースの結果はランク外
+                if casual_seed.is_some() {
+                    player.casual_seed = casual_seed;
+                }
+                player.score = 0;
+                player.foundation_cards = 0;
+                player.card_back = None;
+                
+                // 同じルームで使われている色の場合は空いている色に変える（保存された設定は変えない）
+                let recolored = taken.contains(&player.color_index);
+                if recolored {
+                    if let Some(color) = (1..=CURSOR_COLOR_COUNT).find(|color| !taken.contains(color)) {
+                        player.color_index = color;
+                    }
                 }
+                (player.profile(), recolored)
+            })
+        };
+        info!("🏠 ルーム参加: {} -> {}", player_id, room_id);
+
+        Self::broadcast_to_room(
+            &WebSocketMessage::JoinRoom {
+                room_id: room_id.to_string(),
+                player_id: player_id.to_string(),
+                password: None,
+                request_id: request_id.map(str::to_string),
+            },
+            room_id,
+            state,
+            None,
+        ).await;
+
+        if let Some((profile, recolored)) = joined_player {
+            if recolored {
+                debug!("🎨 ルーム内で色が重なったため変更: {} -> 色{}", player_id, profile.color_index);
+                Self::broadcast_to_all(
+                    &WebSocketMessage::PlayerUpdated {
+                        player_id: profile.player_id.clone(),
+                        player_name: profile.player_name.clone(),
+                        color_index: profile.color_index,
+                    },
+                    senders,
+                    None,
+                ).await;
             }
-            
-            // 実際の送信は実装の都合上省略（tokio-tungsteniteの使用方法による）
-            println!("📤 ブロードキャスト -> {}: {}", player_id, message_text);
+            Self::broadcast_to_room(
+                &WebSocketMessage::PlayerProfile { profile, request_id: None },
+                room_id,
+                state,
+                Some(player_id),
+            ).await;
         }
+        Self::notify_player_presence(player_id, state).await;
+        
+        // ルームが満員になった場合は、ホストが受け取りを選んでいれば知らせる
+        let full_room = rooms
+            .lock()
+            .unwrap()
+            .get(room_id)
+            .filter(|room| room.is_full())
+            .and_then(|room| room.host_id.clone().map(|host_id| (host_id, room.name.clone())));
+        if let Some((host_id, room_name)) = full_room.filter(|(host_id, _)| host_id != player_id) {
+            Self::push_to_player(&host_id, state, |settings| {
+                settings
+                    .room_full
+                    .then(|| PushNotification::room_full(&settings.endpoint, room_id, &room_name))
+            });
+        }
+
+        // 参加したプレイヤーには、他の参加者の最新のスコアを送る
+        let scoreboard = {
+            let members = rooms
+                .lock()
+                .unwrap()
+                .get(room_id)
+                .map(|room| room.players.clone())
+                .unwrap_or_default();
+            let players_map = players.lock().unwrap();
+            members
+                .iter()
+                .filter(|member| member.as_str() != player_id)
+                .filter_map(|member| players_map.get(member).map(Player::scoreboard_entry))
+                .collect()
+        };
+        Self::send_to_player(
+            player_id,
+            &WebSocketMessage::Scoreboard { room_id: room_id.to_string(), players: scoreboard },
+            senders,
+        ).await;
+
+        Self::update_host(room_id, state).await;
+        Self::broadcast_permissions(room_id, state).await;
+        Self::update_readiness(room_id, state).await;
+        true
     }
-}
 
-// =============================================================================
-// サーバー起動用のメイン関数
-// =============================================================================
+    /// 再起動前にこのセッショントークンのプレイヤーがいたルームを探す
+    fn restored_room_for(session_token: &str, rooms: &Rooms) -> Option<String> {
+        rooms
+            .lock()
+            .unwrap()
+            .values()
+            .find(|room| {
+                room.restore
+                    .as_ref()
+                    .is_some_and(|restore| restore.seats.iter().any(|seat| seat.session_token == session_token))
+            })
+            .map(|room| room.id.clone())
+    }
 
-pub async fn run_websocket_server() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 マルチプレイソリティア WebSocketサーバー起動中...");
-    
-    let server = SolitaireServer::new();
-    server.start("162.43.8.148:8101").await?;
-    
-    Ok(())
-}
+    /// 再起動前にいたルームにプレイヤーを戻す
+    ///
+    /// ターンの状態とアクションの記録にある再起動前のプレイヤーIDを新しいIDに置き換えてから参加させ、
+    /// 本人に配り札のシードとアクションの記録を送ります。
+    async fn rejoin_restored_room(player_id: &str, session_token: &str, room_id: &str, state: &ServerState) {
+        let seat = state.rooms.lock().unwrap().get_mut(room_id).and_then(|room| {
+            let restore = room.restore.as_mut()?;
+            let seat = restore.take_seat(session_token)?;
+            if let Some(turns) = restore.turns.as_mut() {
+                turns.rename_player(&seat.player_id, player_id);
+            }
+            for action in room.action_log.iter_mut().filter(|action| action.player_id == seat.player_id) {
+                action.player_id = player_id.to_string();
+            }
+            Some(seat)
+        });
+        let Some(seat) = seat else {
+            return;
+        };
+        if !Self::join_room(player_id, room_id, None, state).await {
+            warn!("⚠️ 再起動前のルームに戻れません: {} -> {}", seat.player_name, room_id);
+            return;
+        }
+        info!("♻️ 再起動前のルームに復帰: {} -> {}", seat.player_name, room_id);
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    run_websocket_server().await
+        let restored = state.rooms.lock().unwrap().get(room_id).map(|room| WebSocketMessage::RoomRestored {
+            room_id: room_id.to_string(),
+            seed: room.seed.filter(|_| matches!(room.game_state, GameState::Playing)),
+            actions: room.action_log.clone(),
+        });
+        if let Some(message) = restored {
+            Self::send_to_player(player_id, &message, &state.senders).await;
+        }
+    }
+
+    /// ゲームアクションを、プレイヤーがいるゲーム中のルームの記録に追加
+    fn log_action(action: LoggedAction, state: &ServerState) {
+        let room_id = state
+            .players
+            .lock()
+            .unwrap()
+            .get(&action.player_id)
+            .and_then(|player| player.room_id.clone());
+        let Some(room_id) = room_id else {
+            return;
+        };
+        let mut rooms_map = state.rooms.lock().unwrap();
+        let Some(room) = rooms_map.get_mut(&room_id) else {
+            return;
+        };
+        if !matches!(room.game_state, GameState::Playing) {
+            return;
+        }
+        if room.action_log.len() >= ACTION_LOG_CAPACITY {
+            room.action_log.remove(0);
+        }
+        room.action_log.push(action);
+    }
+
+    /// ルームのホストを決め直し、変わった場合はルーム内に通知
+    ///
+    /// ホストがいない・ホストが退室した場合は、最も長く接続している参加者に引き継ぎます。
+    /// トーナメントの主催者（開始できるプレイヤー）も新しいホストに引き継がれます。
+    async fn update_host(room_id: &str, state: &ServerState) {
+        let ServerState { players, rooms, .. } = state;
+        let changed = {
+            let players_map = players.lock().unwrap();
+            let mut rooms_map = rooms.lock().unwrap();
+            rooms_map.get_mut(room_id).and_then(|room| {
+                if room.host_id.as_ref().is_some_and(|id| room.players.contains(id)) {
+                    return None;
+                }
+                let host_id = room.next_host(&players_map);
+                if host_id == room.host_id {
+                    return None;
+                }
+                room.host_id = host_id.clone();
+                if let (Some(host_id), Some(tournament)) = (&host_id, room.tournament.as_mut()) {
+                    tournament.host_id = host_id.clone();
+                }
+                let host_name = host_id
+                    .as_ref()
+                    .and_then(|id| players_map.get(id))
+                    .map(|player| player.name.clone());
+                Some(WebSocketMessage::HostChanged {
+                    room_id: room_id.to_string(),
+                    host_id,
+                    host_name,
+                })
+            })
+        };
+
+        if let Some(message) = changed {
+            info!("👑 ルーム{}のホストが変わりました", room_id);
+            Self::broadcast_to_room(&message, room_id, state, None).await;
+        }
+    }
+
+    /// 共有盤面のルームの権限をルーム内に配信
+    ///
+    /// 参加者・ホスト・観戦者・カードの持ち主が変わるたびに、権限全体を送り直します。
+    async fn broadcast_permissions(room_id: &str, state: &ServerState) {
+        let message = state
+            .rooms
+            .lock()
+            .unwrap()
+            .get(room_id)
+            .and_then(GameRoom::permissions_message);
+        if let Some(message) = message {
+            Self::broadcast_to_room(&message, room_id, state, None).await;
+        }
+    }
+
+    /// プレイヤーの操作を記録し、離席中だった場合は戻ったことをルームの全員に知らせる
+    ///
+    /// # 引数
+    /// * `player_id` - 操作したプレイヤーのID
+    /// * `idle_seconds` - クライアントから届いた操作していない時間（秒、操作が届いた場合は0）
+    async fn record_activity(player_id: &str, idle_seconds: u32, state: &ServerState) {
+        let Some(room_id) = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .and_then(|player| player.room_id.clone())
+        else {
+            return;
+        };
+        let message = state.rooms.lock().unwrap().get_mut(&room_id).and_then(|room| {
+            let returned = room.afk.record_idle(player_id, idle_seconds, state.clock.now_ms());
+            returned.then(|| room.afk_message(player_id, false, 0))
+        });
+        if let Some(message) = message {
+            info!("👋 離席から戻りました: {} (ルーム{})", player_id, room_id);
+            Self::broadcast_to_room(&message, &room_id, state, None).await;
+        }
+    }
+    
+    /// 共有盤面のルームで、プレイヤーがGameActionを送れるかチェック
+    ///
+    /// 誰かが離席して一時停止している間は、誰の操作も断ります。
+    /// 盤面の操作は、ルームの盤面の写しで実際の操作（引くのか山札に戻すのか、どのカードを動かすのか）を
+    /// 確かめてから写しに反映します。
+    ///
+    /// # 戻り値
+    /// 送れる場合（共有盤面のルームにいない場合を含む）はOk(())、そうでなければ送り返すエラーメッセージ
+    fn check_board_action(player_id: &str, action: &str, state: &ServerState) -> Result<(), String> {
+        let room_id = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .and_then(|player| player.room_id.clone());
+        let rooms_map = state.rooms.lock().unwrap();
+        let room = match room_id.and_then(|room_id| rooms_map.get(&room_id)) {
+            Some(room) if room.is_paused() => {
+                return Err("離席中のプレイヤーがいるため、ゲームを一時停止しています".to_string())
+            }
+            Some(room) if room.shared_board => room,
+            _ => return Ok(()),
+        };
+        let role = room.seat_role(player_id);
+        let Some(payload) = permissions::board_payload(action) else {
+            return permissions::check_action(role, action);
+        };
+        let Some(seed) = room.seed.filter(|_| matches!(room.game_state, GameState::Playing)) else {
+            return Err("ゲームが始まっていません".to_string());
+        };
+        let mut boards = state.shared_boards.lock().unwrap();
+        let board = boards.board(&room.id, seed, &room.action_log);
+        SharedBoards::apply(board, &room.card_owners, role, player_id, &payload)
+    }
+
+    /// ホストのみ実行できる操作かチェック
+    ///
+    /// # 引数
+    /// * `player_id` - この接続のプレイヤーID（メッセージに書かれたIDではなく）
+    ///
+    /// # 戻り値
+    /// ホストの場合はOk(())、そうでなければ送り返すエラーメッセージ
+    fn check_host(player_id: &str, room_id: &str, rooms: &Rooms) -> Result<(), String> {
+        match rooms.lock().unwrap().get(room_id) {
+            None => Err("ルームが存在しません".to_string()),
+            Some(room) if !room.players.iter().any(|id| id == player_id) => {
+                Err("ルームに参加していません".to_string())
+            }
+            Some(room) if !room.is_host(player_id) => Err("この操作はホストのみ実行できます".to_string()),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// キック・参加禁止にできる相手かチェック
+    ///
+    /// # 引数
+    /// * `player_id` - この接続のプレイヤーID（メッセージに書かれたIDではなく）
+    ///
+    /// # 戻り値
+    /// できる場合はOk(())、ホストでない・自分自身・ルームにいない相手の場合はエラーメッセージ
+    fn check_kick(player_id: &str, room_id: &str, target_id: &str, rooms: &Rooms) -> Result<(), String> {
+        Self::check_host(player_id, room_id, rooms)?;
+        let in_room = rooms
+            .lock()
+            .unwrap()
+            .get(room_id)
+            .is_some_and(|room| room.players.iter().any(|id| id == target_id));
+        if target_id == player_id {
+            Err("自分自身はキックできません".to_string())
+        } else if !in_room {
+            Err("対象のプレイヤーはルームにいません".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// フレンド機能を使えるプレイヤーのセッショントークンを取得
+    ///
+    /// # 引数
+    /// * `sender_id` - この接続のプレイヤーID（参加前はNone）
+    /// * `player_id` - メッセージに書かれたプレイヤーID
+    ///
+    /// # 戻り値
+    /// セッショントークン、なりすまし・セッショントークンを持たない場合はエラーメッセージ
+    fn friend_owner_token(sender_id: Option<&str>, player_id: &str, players: &Players) -> Result<String, String> {
+        if sender_id != Some(player_id) {
+            return Err("他のプレイヤーのフレンドは操作できません".to_string());
+        }
+        match players.lock().unwrap().get(player_id) {
+            None => Err("プレイヤーが見つかりません".to_string()),
+            Some(player) if player.session_token.is_empty() => {
+                Err("フレンドはセッショントークンを持つプレイヤーのみ利用できます".to_string())
+            }
+            Some(player) => Ok(player.session_token.clone()),
+        }
+    }
+
+    /// 接続中のプレイヤーをフレンドに追加する
+    ///
+    /// # 戻り値
+    /// 追加したプレイヤーのセッショントークン、追加できない場合はエラーメッセージ
+    fn add_friend(sender_id: Option<&str>, player_id: &str, friend_id: &str, state: &ServerState) -> Result<String, String> {
+        let session_token = Self::friend_owner_token(sender_id, player_id, &state.players)?;
+        if friend_id == player_id {
+            return Err("自分自身はフレンドにできません".to_string());
+        }
+        let (friend_token, friend_name) = match state.players.lock().unwrap().get(friend_id) {
+            None => return Err("フレンドにするプレイヤーが見つかりません".to_string()),
+            Some(friend) if friend.bot.is_some() => return Err("ボットはフレンドにできません".to_string()),
+            Some(friend) if friend.session_token.is_empty() => {
+                return Err("セッショントークンを持たないプレイヤーはフレンドにできません".to_string());
+            }
+            Some(friend) => (friend.session_token.clone(), friend.name.clone()),
+        };
+        
+        let mut store = state.friends.lock().unwrap();
+        if store.add(&session_token, &friend_token, &friend_name)? {
+            info!("🤝 フレンド追加: {} -> {}", player_id, friend_name);
+            if let Err(e) = store.save() {
+                warn!("⚠️ フレンドの一覧の保存失敗: {}", e);
+            }
+        }
+        Ok(session_token)
+    }
+
+    /// フレンドを名前で外す
+    ///
+    /// # 戻り値
+    /// 外したプレイヤーのセッショントークン、フレンドではない場合はエラーメッセージ
+    fn remove_friend(sender_id: Option<&str>, player_id: &str, friend_name: &str, state: &ServerState) -> Result<String, String> {
+        let session_token = Self::friend_owner_token(sender_id, player_id, &state.players)?;
+        let mut store = state.friends.lock().unwrap();
+        if !store.remove(&session_token, friend_name) {
+            return Err("フレンドではありません".to_string());
+        }
+        info!("👋 フレンド解除: {} -> {}", player_id, friend_name);
+        if let Err(e) = store.save() {
+            warn!("⚠️ フレンドの一覧の保存失敗: {}", e);
+        }
+        Ok(session_token)
+    }
+
+    /// フレンドの一覧を本人に返す（失敗した場合はエラーを返す）
+    ///
+    /// # 引数
+    /// * `owner` - フレンドの一覧の持ち主のセッショントークン、または失敗の理由
+    /// * `sender_id` - この接続のプレイヤーID（参加前はNone）
+    async fn reply_friend_list(owner: Result<String, String>, sender_id: Option<&str>, request_id: Option<String>, state: &ServerState) {
+        let Some(sender_id) = sender_id else {
+            return;
+        };
+        let message = match owner {
+            Ok(session_token) => {
+                let players_map = state.players.lock().unwrap();
+                let store = state.friends.lock().unwrap();
+                let rooms_map = state.rooms.lock().unwrap();
+                let friends = store
+                    .friends(&session_token)
+                    .iter()
+                    .map(|friend| {
+                        let mutual = store.is_friend(&friend.session_token, &session_token);
+                        Self::friend_status(friend, mutual, &players_map, &rooms_map)
+                    })
+                    .collect();
+                WebSocketMessage::FriendList { friends, request_id }
+            }
+            Err(message) => WebSocketMessage::Error { message, request_id, field: None },
+        };
+        Self::send_to_player(sender_id, &message, &state.senders).await;
+    }
+
+    /// フレンドの在席状況を求める（同じセッショントークンの人間のプレイヤーが接続していればオンライン）
+    ///
+    /// # 引数
+    /// * `friend` - フレンドの記録
+    /// * `mutual` - フレンドもこちらをフレンドにしているか（していない場合は常にオフラインとして返す）
+    /// * `players_map` - 接続中のプレイヤー
+    /// * `rooms_map` - 全ルーム（パスワード付きのルームのIDは返さない）
+    fn friend_status(
+        friend: &FriendEntry,
+        mutual: bool,
+        players_map: &HashMap<String, Player>,
+        rooms_map: &HashMap<String, GameRoom>,
+    ) -> FriendStatus {
+        let online = players_map
+            .values()
+            .find(|player| player.bot.is_none() && player.session_token == friend.session_token)
+            .filter(|_| mutual);
+        FriendStatus {
+            player_name: online.map_or_else(|| friend.player_name.clone(), |player| player.name.clone()),
+            player_id: online.map(|player| player.id.clone()),
+            status: match online {
+                None => PresenceStatus::Offline,
+                Some(player) if player.room_id.is_some() => PresenceStatus::InRoom,
+                Some(_) => PresenceStatus::Online,
+            },
+            room_id: online
+                .and_then(|player| player.room_id.clone())
+                .filter(|room_id| rooms_map.get(room_id).is_some_and(|room| room.password.is_none())),
+        }
+    }
+
+    /// 接続中のプレイヤーの在席状況を、お互いにフレンドにしている接続中のプレイヤーに通知する
+    async fn notify_player_presence(player_id: &str, state: &ServerState) {
+        let player = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .map(|player| (player.session_token.clone(), player.name.clone()));
+        if let Some((session_token, player_name)) = player {
+            Self::notify_presence(&session_token, &player_name, state).await;
+        }
+    }
+
+    /// プレイヤーの在席状況を、お互いにフレンドにしている接続中のプレイヤーに通知する
+    ///
+    /// フレンドの記録に残っている表示名もここで新しくします。
+    ///
+    /// # 引数
+    /// * `session_token` - 在席状況が変わったプレイヤーのセッショントークン
+    /// * `player_name` - そのプレイヤーの今の表示名
+    async fn notify_presence(session_token: &str, player_name: &str, state: &ServerState) {
+        if session_token.is_empty() {
+            return;
+        }
+        let (friend, followers) = {
+            let players_map = state.players.lock().unwrap();
+            let mut store = state.friends.lock().unwrap();
+            if store.rename(session_token, player_name) {
+                if let Err(e) = store.save() {
+                    warn!("⚠️ フレンドの一覧の保存失敗: {}", e);
+                }
+            }
+            let followers: Vec<String> = players_map
+                .values()
+                .filter(|player| !player.session_token.is_empty() && store.is_mutual(&player.session_token, session_token))
+                .map(|player| player.id.clone())
+                .collect();
+            let entry = FriendEntry {
+                session_token: session_token.to_string(),
+                player_name: player_name.to_string(),
+            };
+            let rooms_map = state.rooms.lock().unwrap();
+            (Self::friend_status(&entry, true, &players_map, &rooms_map), followers)
+        };
+        
+        let message = WebSocketMessage::FriendPresence { friend };
+        for follower in followers {
+            Self::send_to_player(&follower, &message, &state.senders).await;
+        }
+    }
+
+    /// フレンドを自分のいるルームに招待する
+    ///
+    /// # 戻り値
+    /// 招待を届けた場合Ok(())、なりすまし・ルームにいない・フレンドではない場合はエラーメッセージ
+    async fn invite_to_room(
+        sender_id: Option<&str>,
+        player_id: &str,
+        target_id: &str,
+        room_id: &str,
+        state: &ServerState,
+    ) -> Result<(), String> {
+        if sender_id != Some(player_id) {
+            return Err("他のプレイヤーとして招待はできません".to_string());
+        }
+        if target_id == player_id {
+            return Err("自分自身は招待できません".to_string());
+        }
+        let invite = {
+            let players_map = state.players.lock().unwrap();
+            let inviter = players_map.get(player_id).ok_or("プレイヤーが見つかりません")?;
+            if inviter.room_id.as_deref() != Some(room_id) {
+                return Err("参加しているルームにだけ招待できます".to_string());
+            }
+            let target = players_map.get(target_id).ok_or("招待するプレイヤーが見つかりません")?;
+            if target.room_id.as_deref() == Some(room_id) {
+                return Err("招待するプレイヤーは既に同じルームにいます".to_string());
+            }
+            if target.session_token.is_empty()
+                || !state.friends.lock().unwrap().is_friend(&inviter.session_token, &target.session_token)
+            {
+                return Err("フレンドにしているプレイヤーだけ招待できます".to_string());
+            }
+            let room_name = state
+                .rooms
+                .lock()
+                .unwrap()
+                .get(room_id)
+                .map(|room| room.name.clone())
+                .ok_or("ルームが存在しません")?;
+            let invite_id = state
+                .invites
+                .lock()
+                .unwrap()
+                .create(room_id, player_id, target_id, Self::system_time(&state.clock));
+            
+            WebSocketMessage::RoomInvite {
+                invite_id,
+                room_id: room_id.to_string(),
+                room_name,
+                from_player_id: player_id.to_string(),
+                from_player_name: inviter.name.clone(),
+                join_link: format!("?room={}", room_id),
+            }
+        };
+        
+        info!("✉️ ルームへの招待: {} -> {} (ルーム{})", player_id, target_id, room_id);
+        Self::send_to_player(target_id, &invite, &state.senders).await;
+        Ok(())
+    }
+
+    /// 招待への返事を受け、受けた場合はルームに参加させて招待した人に結果を知らせる
+    ///
+    /// 招待されたルームにはパスワードなしで参加できますが、参加禁止・再参加の待ち時間は守ります。
+    ///
+    /// # 戻り値
+    /// 返事を受け付けた場合Ok(())、招待が見つからない・参加できなかった場合はエラーメッセージ
+    async fn respond_to_invite(
+        sender_id: Option<&str>,
+        player_id: &str,
+        invite_id: &str,
+        accept: bool,
+        state: &ServerState,
+    ) -> Result<(), String> {
+        if sender_id != Some(player_id) {
+            return Err("他のプレイヤーとして返事はできません".to_string());
+        }
+        let invite = state
+            .invites
+            .lock()
+            .unwrap()
+            .take(invite_id, player_id, Self::system_time(&state.clock))?;
+        let (player_name, identity) = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .map(|player| (player.name.clone(), player.identity()))
+            .ok_or("プレイヤーが見つかりません")?;
+        
+        let joined = if accept {
+            let entry = state
+                .rooms
+                .lock()
+                .unwrap()
+                .get(&invite.room_id)
+                .map_or(Err("ルームが存在しません".to_string()), |room| {
+                    room.check_access(&identity, Self::system_time(&state.clock))
+                });
+            match entry {
+                Ok(()) if Self::join_room(player_id, &invite.room_id, None, state).await => Ok(()),
+                Ok(()) => Err("ルームに参加できません（存在しないか満員です）".to_string()),
+                Err(e) => Err(e),
+            }
+        } else {
+            Ok(())
+        };
+        
+        info!("✉️ 招待への返事: {} {} (ルーム{})", player_id, if accept && joined.is_ok() { "参加" } else { "辞退" }, invite.room_id);
+        Self::send_to_player(
+            &invite.from_player_id,
+            &WebSocketMessage::InviteAnswered {
+                invite_id: invite_id.to_string(),
+                player_id: player_id.to_string(),
+                player_name,
+                accepted: accept && joined.is_ok(),
+            },
+            &state.senders,
+        ).await;
+        joined
+    }
+
+    /// 成績の書き出しに署名・確認できる同期アカウントかチェック
+    ///
+    /// # 引数
+    /// * `sender_id` - この接続のプレイヤーID（参加前はNone）
+    /// * `player_id` - メッセージに書かれたプレイヤーID
+    ///
+    /// # 戻り値
+    /// 同期アカウントの場合はOk(())、なりすまし・セッショントークンを持たない場合はエラーメッセージ
+    fn check_synced_account(sender_id: Option<&str>, player_id: &str, players: &Players) -> Result<String, String> {
+        if sender_id != Some(player_id) {
+            return Err("他のプレイヤーの成績には署名できません".to_string());
+        }
+        match players.lock().unwrap().get(player_id) {
+            None => Err("プレイヤーが見つかりません".to_string()),
+            Some(player) if player.session_token.is_empty() => {
+                Err("成績の署名は同期アカウント（セッショントークンを持つプレイヤー）のみ利用できます".to_string())
+            }
+            Some(player) => Ok(player.session_token.clone()),
+        }
+    }
+
+    /// サーバーが記録している同期アカウントの成績を書き出して署名する
+    ///
+    /// # 引数
+    /// * `session_token` - 同期アカウントのセッショントークン
+    ///
+    /// # 戻り値
+    /// 署名付きの書き出しのJSON文字列、まだゲーム結果が届いていない場合はエラーメッセージ
+    fn signed_account_stats(session_token: &str, state: &ServerState) -> Result<String, String> {
+        let mut export = {
+            let store = state.account_stats.lock().unwrap();
+            let stats = store
+                .get(session_token)
+                .ok_or("まだこのアカウントのゲーム結果が届いていません")?;
+            StatsExport::new(&stats.achievements, &stats.account_id, state.clock.now_ms())
+        };
+        export.sign(&state.stats_signing_key)?;
+        export.to_json()
+    }
+
+    /// プッシュ通知の設定を変更する
+    ///
+    /// 送り先（endpoint）を指定しない場合は、送り先を消して通知をやめます。
+    ///
+    /// # 引数
+    /// * `sender_id` - この接続のプレイヤーID（参加前はNone）
+    /// * `player_id` - メッセージに書かれたプレイヤーID
+    ///
+    /// # 戻り値
+    /// 変更後の設定（通知をやめた場合はNone）、なりすまし・セッショントークンを持たない場合はエラーメッセージ
+    fn update_notification_settings(
+        sender_id: Option<&str>,
+        player_id: &str,
+        endpoint: Option<String>,
+        turn: bool,
+        room_full: bool,
+        state: &ServerState,
+    ) -> Result<Option<push_gateway::NotificationSettings>, String> {
+        if sender_id != Some(player_id) {
+            return Err("他のプレイヤーの通知の設定は変更できません".to_string());
+        }
+        let session_token = match state.players.lock().unwrap().get(player_id) {
+            None => return Err("プレイヤーが見つかりません".to_string()),
+            Some(player) if player.session_token.is_empty() => {
+                return Err("プッシュ通知はセッショントークンを持つプレイヤーのみ利用できます".to_string());
+            }
+            Some(player) => player.session_token.clone(),
+        };
+        
+        let settings = endpoint.map(|endpoint| push_gateway::NotificationSettings { endpoint, turn, room_full });
+        let mut store = state.notifications.lock().unwrap();
+        store.set(&session_token, settings.clone());
+        match &settings {
+            Some(settings) => info!("🔔 通知の設定を変更: {} (ターン: {}, 満員: {})", player_id, settings.turn, settings.room_full),
+            None => info!("🔕 通知を停止: {}", player_id),
+        }
+        if let Err(e) = store.save() {
+            warn!("⚠️ 通知の設定の保存失敗: {}", e);
+        }
+        Ok(settings)
+    }
+
+    /// プレイヤーが受け取りを選んでいる通知を中継サーバーに送る
+    ///
+    /// 送信は別のタスクで行い、届かなくてもゲームの進行は止めません。
+    /// 中継サーバーが受け取らなかった通知は溜めておき、run_push_retriesで送り直します。
+    ///
+    /// # 引数
+    /// * `player_id` - 通知を受け取るプレイヤーのID
+    /// * `notification` - 通知の設定から送る通知を作る関数（受け取らない設定の場合はNone）
+    fn push_to_player(
+        player_id: &str,
+        state: &ServerState,
+        notification: impl FnOnce(&push_gateway::NotificationSettings) -> Option<PushNotification>,
+    ) {
+        if !state.push_gateway.is_enabled() {
+            return;
+        }
+        let session_token = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .map(|player| player.session_token.clone())
+            .filter(|token| !token.is_empty());
+        let Some(session_token) = session_token else {
+            return;
+        };
+        let Some(notification) = state.notifications.lock().unwrap().get(&session_token).and_then(notification) else {
+            return;
+        };
+        
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = state.push_gateway.send(&notification).await {
+                warn!("⚠️ プッシュ通知の送信失敗（後で送り直します）: {}", e);
+                let mut store = state.notifications.lock().unwrap();
+                store.queue(&session_token, notification);
+                if let Err(e) = store.save() {
+                    warn!("⚠️ 通知の設定の保存失敗: {}", e);
+                }
+            }
+        });
+    }
+
+    /// 中継サーバーに届けられなかった通知を、PushGateway::retry_interval()ごとに送り直し続ける
+    async fn run_push_retries(state: ServerState) {
+        let mut interval = tokio::time::interval(state.push_gateway.retry_interval());
+        loop {
+            interval.tick().await;
+            let pending = state.notifications.lock().unwrap().take_pending();
+            if pending.is_empty() {
+                continue;
+            }
+            
+            let mut failed = Vec::new();
+            for (session_token, notification) in pending {
+                if let Err(e) = state.push_gateway.send(&notification).await {
+                    debug!("🔔 プッシュ通知の送り直し失敗: {}", e);
+                    failed.push((session_token, notification));
+                }
+            }
+            let mut store = state.notifications.lock().unwrap();
+            for (session_token, notification) in failed {
+                store.queue(&session_token, notification);
+            }
+            if store.pending_count() > 0 {
+                debug!("🔔 送り直しを待っているプッシュ通知: {}件", store.pending_count());
+            }
+            if let Err(e) = store.save() {
+                warn!("⚠️ 通知の設定の保存失敗: {}", e);
+            }
+        }
+    }
+
+    /// WebRTCの接続交渉を中継できるかチェック
+    ///
+    /// # 引数
+    /// * `sender_id` - この接続のプレイヤーID（参加前はNone）
+    /// * `from_player_id` - メッセージに書かれた送信者
+    /// * `to_player_id` - 交渉の相手
+    ///
+    /// # 戻り値
+    /// 中継できる場合はOk(())、なりすまし・自分宛て・同じルームにいない相手の場合はエラーメッセージ
+    fn check_rtc_signal(
+        sender_id: Option<&str>,
+        from_player_id: &str,
+        to_player_id: &str,
+        room_id: &str,
+        rooms: &Rooms,
+    ) -> Result<(), String> {
+        if sender_id != Some(from_player_id) {
+            return Err("他のプレイヤーとして接続交渉はできません".to_string());
+        }
+        if from_player_id == to_player_id {
+            return Err("自分自身とは接続交渉できません".to_string());
+        }
+        match rooms.lock().unwrap().get(room_id) {
+            None => Err("ルームが存在しません".to_string()),
+            Some(room) if !room.players.iter().any(|id| id == from_player_id) => {
+                Err("ルームに参加していません".to_string())
+            }
+            Some(room) if !room.players.iter().any(|id| id == to_player_id) => {
+                Err("相手のプレイヤーはルームにいません".to_string())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// プレイヤーをルームからキックする
+    ///
+    /// 本人にKickedを送ってから退室させます。参加禁止にしない場合も、
+    /// KICK_REJOIN_BLOCK_SECONDS秒の間は同じルームに再参加できません。
+    ///
+    /// # 引数
+    /// * `banned` - ルームの参加禁止リストにも追加する場合true
+    async fn kick_player(target_id: &str, room_id: &str, banned: bool, state: &ServerState) {
+        let ServerState { players, rooms, senders, .. } = state;
+        let Some((identity, target_name)) = players
+            .lock()
+            .unwrap()
+            .get(target_id)
+            .map(|player| (player.identity(), player.name.clone()))
+        else {
+            return;
+        };
+        let rejoin_block = std::time::Duration::from_secs(KICK_REJOIN_BLOCK_SECONDS);
+        if let Some(room) = rooms.lock().unwrap().get_mut(room_id) {
+            if banned {
+                room.banned.insert(identity, target_name.clone());
+            } else {
+                room.kick_blocks.insert(identity, Self::system_time(&state.clock) + rejoin_block);
+            }
+        }
+        info!("👢 {}: {} <- ルーム{}", if banned { "参加禁止" } else { "キック" }, target_name, room_id);
+
+        Self::send_to_player(
+            target_id,
+            &WebSocketMessage::Kicked {
+                room_id: room_id.to_string(),
+                player_id: target_id.to_string(),
+                banned,
+                rejoin_after_seconds: (!banned).then_some(KICK_REJOIN_BLOCK_SECONDS),
+            },
+            senders,
+        ).await;
+        Self::leave_room(target_id, room_id, state).await;
+    }
+
+    /// ルームの参加禁止リストをホストに送る
+    async fn send_ban_list(player_id: &str, room_id: &str, rooms: &Rooms, senders: &Senders) {
+        let banned_names = rooms
+            .lock()
+            .unwrap()
+            .get(room_id)
+            .map(|room| {
+                let mut names: Vec<String> = room.banned.values().cloned().collect();
+                names.sort();
+                names
+            })
+            .unwrap_or_default();
+        Self::send_to_player(
+            player_id,
+            &WebSocketMessage::BanList {
+                room_id: room_id.to_string(),
+                banned_names,
+            },
+            senders,
+        ).await;
+    }
+
+    /// ルーム一覧メッセージを作成
+    ///
+    /// # 引数
+    /// * `request_id` - 一覧に付けて返す要求のID
+    fn room_list(state: &ServerState, request_id: Option<String>) -> WebSocketMessage {
+        WebSocketMessage::RoomList {
+            rooms: Self::room_infos(state),
+            request_id,
+        }
+    }
+
+    /// ルーム一覧を作成（他のインスタンスのルームも含める）
+    fn room_infos(state: &ServerState) -> Vec<RoomInfo> {
+        let players_map = state.players.lock().unwrap();
+        let rooms_map = state.rooms.lock().unwrap();
+        let mut rooms: Vec<RoomInfo> = rooms_map.values().map(|room| room.to_info(&players_map)).collect();
+        let remote_rooms = state.remote_rooms.lock().unwrap().list(state.clock.now_ms());
+        rooms.extend(remote_rooms.into_iter().filter(|room| !rooms_map.contains_key(&room.id)));
+        rooms
+    }
+
+    /// レーティング帯が近いルームを探す（見つからなければ新しく作成）
+    ///
+    /// 満員・トーナメント開催中・パスワード付き・参加禁止のルームは除外し、
+    /// 同じレーティング帯の参加者がいるルームを優先します。
+    ///
+    /// # 戻り値
+    /// 参加先のルームID
+    fn find_match_room(player_id: &str, state: &ServerState) -> String {
+        let players_map = state.players.lock().unwrap();
+        let mut rooms_map = state.rooms.lock().unwrap();
+
+        let player = players_map.get(player_id);
+        let identity = player.map(Player::identity).unwrap_or_default();
+        let bucket = rating::rating_bucket(player.map_or(rating::INITIAL_RATING, |player| player.rating));
+
+        let matched = rooms_map
+            .values()
+            .filter(|room| !room.is_full() && !room.has_active_tournament())
+            .filter(|room| room.check_entry(&identity, None, Self::system_time(&state.clock)).is_ok())
+            .find(|room| {
+                room.average_rating(&players_map)
+                    .is_some_and(|average| rating::rating_bucket(average) == bucket)
+            })
+            .map(|room| room.id.clone());
+
+        if let Some(room_id) = matched {
+            return room_id;
+        }
+
+        let room = GameRoom::new(format!("レート帯{}ルーム", bucket), 4, Self::system_time(&state.clock));
+        info!("🏠 マッチング用ルームを作成しました: {}", room.name);
+        Self::insert_room(room, &mut rooms_map, state)
+    }
+
+    /// プレイヤーをルームから退室させる
+    ///
+    /// トーナメント進行中の場合は参加者から外し、
+    /// それによってラウンドが終了した場合は順位を配信します。
+    /// 退室したのがホストの場合は、残った参加者にホストを引き継ぎます。
+    async fn leave_room(player_id: &str, room_id: &str, state: &ServerState) {
+        let ServerState { players, rooms, .. } = state;
+        let (left, messages, released_cards) = {
+            let mut rooms_map = rooms.lock().unwrap();
+            match rooms_map.get_mut(room_id) {
+                Some(room) => {
+                    let left = room.remove_player(player_id);
+                    let progress = room
+                        .tournament
+                        .as_mut()
+                        .and_then(|t| t.remove_participant(player_id));
+                    let messages = progress
+                        .map(|progress| room.tournament_progress_messages(progress))
+                        .unwrap_or_default();
+                    (left, messages, room.card_claims.release_all(player_id))
+                }
+                None => (false, Vec::new(), Vec::new()),
+            }
+        };
+
+        if !left {
+            return;
+        }
+
+        if let Some(player) = players.lock().unwrap().get_mut(player_id) {
+            player.room_id = None;
+        }
+        info!("🚪 ルーム退室: {} <- {}", player_id, room_id);
+
+        Self::broadcast_to_room(
+            &WebSocketMessage::LeaveRoom {
+                room_id: room_id.to_string(),
+                player_id: player_id.to_string(),
+            },
+            room_id,
+            state,
+            None,
+        ).await;
+        
+        // 掴んだままだったカードは他のプレイヤーが掴めるようにする
+        for card_id in released_cards {
+            Self::broadcast_to_room(
+                &WebSocketMessage::CardReleased {
+                    room_id: room_id.to_string(),
+                    card_id,
+                },
+                room_id,
+                state,
+                None,
+            ).await;
+        }
+
+        Self::update_host(room_id, state).await;
+        Self::broadcast_permissions(room_id, state).await;
+        Self::update_readiness(room_id, state).await;
+        Self::dispatch_room_messages(messages, room_id, state).await;
+        Self::notify_player_presence(player_id, state).await;
+    }
+
+    /// 準備完了の状況をルーム内に配信し、全員が揃ったら開始のカウントダウンを始める
+    async fn update_readiness(room_id: &str, state: &ServerState) {
+        let ServerState { players, rooms, .. } = state;
+        let (status, all_ready) = {
+            let players_map = players.lock().unwrap();
+            let rooms_map = rooms.lock().unwrap();
+            let Some(room) = rooms_map.get(room_id) else {
+                return;
+            };
+            let all_ready = room.all_ready(&players_map) && room.can_start_countdown().is_ok();
+            let status = WebSocketMessage::ReadyStatus {
+                room_id: room_id.to_string(),
+                ready_player_ids: room.ready_player_ids(&players_map),
+                all_ready,
+            };
+            (status, all_ready)
+        };
+
+        Self::broadcast_to_room(&status, room_id, state, None).await;
+        if all_ready {
+            if let Err(e) = Self::start_countdown(room_id, None, state) {
+                warn!("⚠️ カウントダウンを開始できません: {}", e);
+            }
+        }
+    }
+
+    /// ゲーム開始のカウントダウンを始める
+    ///
+    /// ルームをカウントダウン中にして準備完了をリセットし、
+    /// カウントダウンの配信と終了後のレース開始は別タスクで行います。
+    ///
+    /// # 引数
+    /// * `seed` - 配り札のシード（Noneの場合はランダム）
+    ///
+    /// # 戻り値
+    /// 始めた場合はOk(())、ルームがない・始められない場合はエラーメッセージ
+    fn start_countdown(room_id: &str, seed: Option<u64>, state: &ServerState) -> Result<(), String> {
+        {
+            let mut rooms_map = state.rooms.lock().unwrap();
+            let room = rooms_map
+                .get_mut(room_id)
+                .ok_or_else(|| "ルームが存在しません".to_string())?;
+            room.can_start_countdown()?;
+            room.game_state = GameState::Starting;
+            room.ready.clear();
+        }
+
+        let seed = seed.unwrap_or_else(|| Rng::from_entropy().next_u64());
+        info!("⏱️ 開始カウントダウン: ルーム{}", room_id);
+        tokio::spawn(Self::run_countdown(room_id.to_string(), seed, state.clone()));
+        Ok(())
+    }
+
+    /// カウントダウンを1秒ごとに配信し、終わったらレースを開始する
+    async fn run_countdown(room_id: String, seed: u64, state: ServerState) {
+        for seconds_remaining in (1..=START_COUNTDOWN_SECONDS).rev() {
+            Self::broadcast_to_room(
+                &WebSocketMessage::StartCountdown {
+                    room_id: room_id.clone(),
+                    seconds_remaining,
+                },
+                &room_id,
+                &state,
+                None,
+            ).await;
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+
+        // カウントダウン中にルームが消えた場合は開始しない
+        let started = match state.rooms.lock().unwrap().get_mut(&room_id) {
+            Some(room) if matches!(room.game_state, GameState::Starting) => {
+                room.game_state = GameState::Playing;
+                room.combos.reset(None);
+                room.power_up_usage.reset();
+                true
+            }
+            _ => false,
+        };
+        if started {
+            info!("🏁 レース開始: ルーム{} (シード: {})", room_id, seed);
+            Self::solve_deal_later(&state, seed);
+            Self::dispatch_room_messages(
+                vec![WebSocketMessage::RaceStart { room_id: room_id.clone(), seed }],
+                &room_id,
+                &state,
+            ).await;
+        }
+    }
+
+    /// ルームのシミュレーションを一定間隔で進める
+    ///
+    /// メッセージが届かなくても進む処理（ターンの制限時間など）のため、TICK_INTERVAL_MSごとに
+    /// ルームの状態をシミュレーションに反映してからシステムを実行し、発生したメッセージを配信します。
+    /// ルームがなくなったら終了します。
+    async fn run_room_ticks(room_id: String, state: ServerState) {
+        let mut simulation = RoomSimulation::new(room_id.clone(), state.clock);
+        let mut interval = tokio::time::interval(std::time::Duration::from_millis(TICK_INTERVAL_MS));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut last_tick = tokio::time::Instant::now();
+        
+        loop {
+            let now = interval.tick().await;
+            let delta_seconds = now.duration_since(last_tick).as_secs_f64();
+            last_tick = now;
+            
+            let messages = {
+                let players_map = state.players.lock().unwrap();
+                let mut rooms_map = state.rooms.lock().unwrap();
+                let Some(room) = rooms_map.get_mut(&room_id) else {
+                    break;
+                };
+                
+                // 再起動前の参加者が戻るのを待っている間はゲームを進めない
+                let waiting_for_seats = room
+                    .restore
+                    .as_ref()
+                    .is_some_and(|restore| restore.is_waiting(state.clock.now_ms()));
+                
+                // 空になったルームは猶予時間が過ぎたら削除する（デフォルトルームと参加者の戻りを待つルームは残す）
+                if room.players.is_empty() && !room.permanent && !waiting_for_seats {
+                    let now_ms = state.clock.now_ms();
+                    let empty_since_ms = *room.empty_since_ms.get_or_insert(now_ms);
+                    if now_ms.saturating_sub(empty_since_ms) >= state.empty_room_grace_ms {
+                        rooms_map.remove(&room_id);
+                        state.shared_boards.lock().unwrap().remove(&room_id);
+                        info!("🧹 空のルームを削除しました: {}", room_id);
+                        break;
+                    }
+                } else {
+                    room.empty_since_ms = None;
+                }
+                if !waiting_for_seats {
+                    if let Some(turns) = room.restore.take().and_then(|restore| restore.turns) {
+                        simulation.restore_turns(turns);
+                    }
+                }
+                
+                let playing = !waiting_for_seats && matches!(room.game_state, GameState::Playing);
+                let mut messages = Vec::new();
+                if !playing {
+                    room.afk.end_game();
+                } else if room.afk_seconds > 0 {
+                    // ボットは操作を送らないため、人間の参加者だけを離席の判定に入れる
+                    let humans: Vec<String> = room
+                        .players
+                        .iter()
+                        .filter(|id| players_map.get(*id).is_some_and(|player| player.bot.is_none()))
+                        .cloned()
+                        .collect();
+                    for (player_id, idle_seconds) in room.afk.check(&humans, room.afk_seconds, state.clock.now_ms()) {
+                        info!("💤 離席: {} (ルーム{}、{}秒操作なし)", player_id, room_id, idle_seconds);
+                        messages.push(room.afk_message(&player_id, true, idle_seconds));
+                    }
+                }
+                
+                // 席を没収されたプレイヤーはターン順から外す
+                let seated: Vec<String> = room
+                    .players
+                    .iter()
+                    .filter(|id| !room.afk.is_forfeited(id))
+                    .cloned()
+                    .collect();
+                messages.extend(simulation.sync_room(playing, &seated, room.turn_time_limit));
+                if !room.is_paused() {
+                    messages.extend(simulation.tick(delta_seconds));
+                }
+                
+                // 離席中のプレイヤーのターンは飛ばし、続けて飛ばした回数が設定に達したら席を没収する
+                for _ in 0..seated.len() {
+                    let Some(current) = simulation.current_player().filter(|id| room.afk.is_afk(id)) else {
+                        break;
+                    };
+                    messages.extend(simulation.skip_turn(&current));
+                    let afk_turns = room.afk.record_afk_turn(&current);
+                    if room.afk_forfeit_turns > 0 && afk_turns >= room.afk_forfeit_turns {
+                        info!("🪑 席を没収: {} (ルーム{}、{}ターン離席)", current, room_id, afk_turns);
+                        room.afk.forfeit(&current);
+                        messages.push(WebSocketMessage::SeatForfeited {
+                            room_id: room_id.clone(),
+                            player_id: current.clone(),
+                            afk_turns,
+                        });
+                        let seated: Vec<String> = room
+                            .players
+                            .iter()
+                            .filter(|id| !room.afk.is_forfeited(id))
+                            .cloned()
+                            .collect();
+                        messages.extend(simulation.sync_room(playing, &seated, room.turn_time_limit));
+                    }
+                }
+                if room.combo_window_seconds > 0 {
+                    messages.extend(room.combos.expire(&room_id, room.combo_window_seconds, state.clock.now_ms()));
+                }
+                room.turn_snapshot = simulation.snapshot();
+                messages
+            };
+            for message in &messages {
+                Self::broadcast_to_room(message, &room_id, &state, None).await;
+                
+                // 手番になったプレイヤーには、受け取りを選んでいればプッシュ通知でも知らせる
+                if let WebSocketMessage::TurnStarted { player_id, turn_number, .. } = message {
+                    let room_name = state
+                        .rooms
+                        .lock()
+                        .unwrap()
+                        .get(&room_id)
+                        .map(|room| room.name.clone())
+                        .unwrap_or_default();
+                    Self::push_to_player(player_id, &state, |settings| {
+                        settings
+                            .turn
+                            .then(|| PushNotification::turn(&settings.endpoint, &room_id, &room_name, *turn_number))
+                    });
+                }
+            }
+        }
+        
+        debug!("🛑 ティックタスク終了: ルーム{}", room_id);
+    }
+
+    /// パワーアップを使えるルームで始まったレースのゲーム結果を、相手から足されたタイムを加えたランク外の結果にする
+    ///
+    /// ランク外かどうかはレースの開始時（途中から参加した場合は参加時）の設定で決め、
+    /// 結果を送るまでにルームの設定や参加者が変わっても変えません。
+    ///
+    /// # 戻り値
+    /// 印を付けたゲーム結果（パワーアップを使えるレースの結果でない場合はそのまま）
+    fn mark_casual_result(player_id: &str, mut result: serde_json::Value, state: &ServerState) -> serde_json::Value {
+        let (room_id, casual_seed) = match state.players.lock().unwrap().get(player_id) {
+            Some(player) => (player.room_id.clone(), player.casual_seed),
+            None => return result,
+        };
+        let seed = result.get("seed").and_then(serde_json::Value::as_u64);
+        if casual_seed.is_none() || casual_seed != seed {
+            return result;
+        }
+        let time_penalty = room_id
+            .and_then(|room_id| {
+                state
+                    .rooms
+                    .lock()
+                    .unwrap()
+                    .get(&room_id)
+                    .map(|room| room.power_up_usage.time_penalty(player_id))
+            })
+            .unwrap_or_default();
+        power_up::mark_unranked(&mut result, time_penalty);
+        result
+    }
+
+    /// パワーアップを使えるかチェックし、使ったことを記録
+    ///
+    /// # 戻り値
+    /// 使えた場合は使ったプレイヤーのルームID、使えない場合はエラーメッセージ
+    fn use_power_up(player_id: &str, power_up: PowerUp, state: &ServerState) -> Result<String, String> {
+        let room_id = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .and_then(|player| player.room_id.clone())
+            .ok_or_else(|| "ルームに参加していません".to_string())?;
+        let mut rooms_map = state.rooms.lock().unwrap();
+        let room = rooms_map
+            .get_mut(&room_id)
+            .ok_or_else(|| "ルームが存在しません".to_string())?;
+        if !room.power_ups {
+            return Err("このルームではパワーアップを使えません".to_string());
+        }
+        if !matches!(room.game_state, GameState::Playing) {
+            return Err("パワーアップはレース中のみ使えます".to_string());
+        }
+        let opponents = room.players.clone();
+        room.power_up_usage.use_power_up(player_id, power_up, &opponents, state.clock.now_ms())?;
+        Ok(room_id)
+    }
+
+    /// ゲーム結果をリーダーボードとトーナメントに記録
+    ///
+    /// プレイヤーがトーナメント進行中のルームにいる場合は現在ラウンドの結果として扱い、
+    /// ラウンドが終了したら順位・次ラウンド・優勝者をルームに配信します。
+    async fn record_game_result(player_id: &str, result: &serde_json::Value, state: &ServerState) {
+        let ServerState { players, rooms, senders, leaderboard, ratings, .. } = state;
+        let Some(mut submitted) = SubmittedResult::from_json(result) else {
+            warn!("⚠️ ゲーム結果の形式が不正です: {}", player_id);
+            return;
+        };
+
+        let (player_name, room_id, is_bot, session_token) = match players.lock().unwrap().get(player_id) {
+            Some(player) => (player.name.clone(), player.room_id.clone(), player.bot.is_some(), player.session_token.clone()),
+            None => ("Unknown".to_string(), None, false, String::new()),
+        };
+        
+        // レースで貯まったコンボのボーナスは、サーバーで数えたものを結果のスコアに足してから順位を付ける
+        let combo_bonus = room_id.as_ref().map_or(0, |room_id| {
+            rooms
+                .lock()
+                .unwrap()
+                .get(room_id)
+                .filter(|room| room.seed == Some(submitted.seed))
+                .map_or(0, |room| room.combos.bonus_score(player_id))
+        });
+        if combo_bonus > 0 {
+            debug!("🔥 コンボのボーナス: {} +{}", player_id, combo_bonus);
+            submitted.score = submitted.score.saturating_add(combo_bonus);
+        }
+        let ranked = !power_up::is_unranked(result);
+        
+        // 同期アカウントの成績として記録する（成績の書き出しにはここに記録した成績だけを載せる）
+        if !session_token.is_empty() {
+            match serde_json::from_value::<result::GameResult>(result.clone()) {
+                Ok(game_result) => {
+                    let mut store = state.account_stats.lock().unwrap();
+                    store.record(&session_token, &game_result, &state.clock);
+                    if let Err(e) = store.save() {
+                        warn!("⚠️ アカウントの成績の保存失敗: {}", e);
+                    }
+                }
+                Err(e) => debug!("ゲーム結果をアカウントの成績に記録できません: {}", e),
+            }
+        }
+
+        // 同じルームで同じ配り札を先にプレイしたプレイヤーとの対戦としてレーティングを更新
+        // （ボットとパワーアップを使えるルームの結果はレーティングの対象外）
+        let opponents: Vec<(String, SubmittedResult)> = match &room_id {
+            Some(room_id) if !is_bot && ranked => {
+                let room_players: Vec<String> = {
+                    let room_players = rooms
+                        .lock()
+                        .unwrap()
+                        .get(room_id)
+                        .map(|room| room.players.clone())
+                        .unwrap_or_default();
+                    let players_map = players.lock().unwrap();
+                    room_players
+                        .into_iter()
+                        .filter(|id| players_map.get(id).is_some_and(|player| player.bot.is_none()))
+                        .collect()
+                };
+                let leaderboard = leaderboard.lock().unwrap();
+                let mut opponents: HashMap<&str, SubmittedResult> = HashMap::new();
+                for entry in leaderboard.entries_for(submitted.seed) {
+                    if entry.player_id != player_id && room_players.contains(&entry.player_id) {
+                        // 同じプレイヤーの記録が複数ある場合は最新のものを使う
+                        opponents.insert(&entry.player_name, entry.to_result(submitted.seed));
+                    }
+                }
+                opponents
+                    .into_iter()
+                    .map(|(name, result)| (name.to_string(), result))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        if ranked {
+            leaderboard
+                .lock()
+                .unwrap()
+                .record(player_id, &player_name, &submitted);
+        }
+        
+        // シードから配ったクロンダイクの配り札は、次に同じシードで遊ばれたときのために解いておく
+        let is_klondike = result.get("game_type").and_then(serde_json::Value::as_str) == Some("Klondike");
+        if is_klondike && submitted.seed != 0 {
+            Self::solve_deal_later(state, submitted.seed);
+        }
+
+        let Some(room_id) = room_id else {
+            return;
+        };
+
+        if !opponents.is_empty() {
+            let changes = Self::update_ratings(&player_name, &submitted, &opponents, ratings);
+            for change in changes {
+                let record = ratings.lock().unwrap().get(&change.player_name);
+                let changed_id = {
+                    let mut players_map = players.lock().unwrap();
+                    players_map
+                        .values_mut()
+                        .find(|player| player.name == change.player_name)
+                        .map(|player| {
+                            player.rating = record.rating;
+                            player.games_rated = record.games_rated;
+                            player.id.clone()
+                        })
+                };
+                if let Some(changed_id) = changed_id {
+                    info!("📈 レーティング更新: {} {} -> {}", change.player_name, change.old_rating, change.new_rating);
+                    Self::broadcast_to_room(
+                        &WebSocketMessage::RatingChanged {
+                            player_id: changed_id,
+                            player_name: change.player_name,
+                            old_rating: change.old_rating,
+                            new_rating: change.new_rating,
+                        },
+                        &room_id,
+                        state,
+                        None,
+                    ).await;
+                }
+            }
+        }
+
+        let race_finished = rooms
+            .lock()
+            .unwrap()
+            .get_mut(&room_id)
+            .is_some_and(|room| room.record_finish(player_id, submitted.seed));
+        if race_finished {
+            info!("🏁 レース終了: ルーム{}（全員の結果が揃いました）", room_id);
+        }
+        
+        let outcome = {
+            let mut rooms_map = rooms.lock().unwrap();
+            rooms_map.get_mut(&room_id).and_then(|room| {
+                let tournament = room.tournament.as_mut()?;
+                if !tournament.is_participant(player_id) {
+                    return None;
+                }
+                let progress = tournament.submit_result(player_id, submitted);
+                Some(progress.map(|progress| room.tournament_progress_messages(progress)))
+            })
+        };
+
+        match outcome {
+            Some(Ok(messages)) => Self::dispatch_room_messages(messages, &room_id, state).await,
+            Some(Err(e)) => Self::send_error(player_id, &e, senders).await,
+            None => {}
+        }
+    }
+
+    /// ルーム内にメッセージを配信
+    ///
+    /// 配り札の開始（レース開始・トーナメントのラウンド開始）を含む場合は、
+    /// ルーム内のボットにも同じ配り札でプレイを始めさせます。
+    async fn dispatch_room_messages(messages: Vec<WebSocketMessage>, room_id: &str, state: &ServerState) {
+        for message in &messages {
+            Self::broadcast_to_room(message, room_id, state, None).await;
+            
+            let seed = match message {
+                WebSocketMessage::RaceStart { seed, .. } => Some(*seed),
+                WebSocketMessage::TournamentRoundStart { seed, .. } => Some(*seed),
+                _ => None,
+            };
+            if let Some(seed) = seed {
+                // 新しい配り札ではカードの持ち主を決め直す
+                let shared_board = {
+                    let mut players_map = state.players.lock().unwrap();
+                    match state.rooms.lock().unwrap().get_mut(room_id) {
+                        Some(room) => {
+                            room.seed = Some(seed);
+                            room.finished.clear();
+                            room.action_log.clear();
+                            room.card_owners.clear();
+                            // 開始時の設定でランク外かどうかを決める（結果を送るまでに設定や参加者が変わっても変えない）
+                            for member_id in &room.players {
+                                if let Some(member) = players_map.get_mut(member_id) {
+                                    member.casual_seed = room.power_ups.then_some(seed);
+                                }
+                            }
+                            room.shared_board
+                        }
+                        None => false,
+                    }
+                };
+                if shared_board {
+                    state.shared_boards.lock().unwrap().deal(room_id, seed);
+                    Self::broadcast_permissions(room_id, state).await;
+                }
+                let race = BotRace {
+                    room_id: room_id.to_string(),
+                    seed,
+                };
+                if state.bot_races.send(race).is_err() {
+                    warn!("⚠️ ボットのレース開始要求を送信できません");
+                }
+            }
+        }
+    }
+
+    /// ボットのレース開始要求を処理し続ける
+    ///
+    /// ボットのプレイ結果が次のラウンド開始につながることがあるため、
+    /// 開始要求はチャンネル経由でこのタスクがまとめて受け付けます。
+    async fn run_bot_races(mut receiver: tokio::sync::mpsc::UnboundedReceiver<BotRace>, state: ServerState) {
+        while let Some(race) = receiver.recv().await {
+            let room_players = state
+                .rooms
+                .lock()
+                .unwrap()
+                .get(&race.room_id)
+                .map(|room| room.players.clone())
+                .unwrap_or_default();
+            
+            let bots: Vec<(String, String, BotConfig)> = {
+                let players_map = state.players.lock().unwrap();
+                room_players
+                    .iter()
+                    .filter_map(|id| players_map.get(id))
+                    .filter_map(|player| player.bot.map(|config| (player.id.clone(), player.name.clone(), config)))
+                    .collect()
+            };
+            
+            for (bot_id, bot_name, config) in bots {
+                tokio::spawn(Self::run_bot(bot_id, bot_name, config, race.clone(), state.clone()));
+            }
+        }
+    }
+
+    /// ボット1人分のプレイを実行
+    ///
+    /// 設定された速度で1手ずつ進め、人間と同じGameActionを配信し、
+    /// 終了したらGameResultとして結果を記録・配信します。
+    async fn run_bot(bot_id: String, bot_name: String, config: BotConfig, race: BotRace, state: ServerState) {
+        info!("🤖 {}がプレイ開始 (シード: {})", bot_name, race.seed);
+        let session_id = format!("{}:{}", bot_id, race.seed);
+        state
+            .bot_sessions
+            .lock()
+            .unwrap()
+            .insert(&session_id, BotPlayer::new(config, race.seed));
+        let clock = state.clock;
+        
+        loop {
+            tokio::time::sleep(config.move_interval()).await;
+            
+            // ルームから外された場合は中断
+            let in_room = state
+                .players
+                .lock()
+                .unwrap()
+                .get(&bot_id)
+                .is_some_and(|player| player.room_id.as_deref() == Some(race.room_id.as_str()));
+            if !in_room {
+                info!("🤖 {}のプレイを中断しました", bot_name);
+                break;
+            }
+            
+            // 一時停止中のセッションは手を進めない（破棄された場合は中断）
+            let step = {
+                let mut sessions = state.bot_sessions.lock().unwrap();
+                if sessions.status(&session_id).is_none() {
+                    info!("🤖 {}のセッションが破棄されました", bot_name);
+                    break;
+                }
+                sessions.get_active_mut(&session_id).map(BotPlayer::step)
+            };
+            
+            match step {
+                None => {}
+                Some(BotStep::Moved { action }) => {
+                    let timestamp = clock.now_ms();
+                    Self::broadcast_to_room(
+                        &WebSocketMessage::GameAction {
+                            player_id: bot_id.clone(),
+                            player_name: bot_name.clone(),
+                            action,
+                            x: None,
+                            y: None,
+                            timestamp,
+                        },
+                        &race.room_id,
+                        &state,
+                        None,
+                    ).await;
+                }
+                Some(BotStep::Finished { won, result }) => {
+                    info!("🤖 {}のプレイ終了: {}", bot_name, if won { "勝利" } else { "手詰まり" });
+                    let result = Self::mark_casual_result(&bot_id, result, &state);
+                    Self::record_game_result(&bot_id, &result, &state).await;
+                    Self::broadcast_to_all(
+                        &WebSocketMessage::GameResult {
+                            player_id: bot_id.clone(),
+                            result,
+                        },
+                        &state.senders,
+                        None,
+                    ).await;
+                    break;
+                }
+            }
+        }
+        
+        state.bot_sessions.lock().unwrap().destroy(&session_id);
+    }
+
+    /// 対戦結果でレーティングを更新して保存
+    ///
+    /// 対戦相手ごとに1対1の対戦として計算し、最終的な変更を返します。
+    ///
+    /// # 戻り値
+    /// プレイヤーごとの最終的なレーティング変更
+    fn update_ratings(
+        player_name: &str,
+        result: &SubmittedResult,
+        opponents: &[(String, SubmittedResult)],
+        ratings: &Ratings,
+    ) -> Vec<RatingChange> {
+        let mut store = ratings.lock().unwrap();
+        let mut changes: Vec<RatingChange> = Vec::new();
+
+        for (opponent_name, opponent_result) in opponents {
+            let outcome = rating::compare_race_results(result, opponent_result);
+            let (change_a, change_b) = store.record_match(player_name, opponent_name, outcome);
+
+            for change in [change_a, change_b] {
+                match changes.iter_mut().find(|c| c.player_name == change.player_name) {
+                    Some(existing) => existing.new_rating = change.new_rating,
+                    None => changes.push(change),
+                }
+            }
+        }
+
+        if let Err(e) = store.save() {
+            warn!("⚠️ レーティングの保存失敗: {}", e);
+        }
+        changes
+    }
+
+    /// 表示名をセッショントークンのものとして押さえて保存する
+    ///
+    /// # 戻り値
+    /// 押さえられた場合true、他のトークンのプレイヤーが使っている場合false
+    fn claim_display_name(session_token: &str, player_name: &str, state: &ServerState) -> bool {
+        let mut store = state.preferences.lock().unwrap();
+        if store.claim_name(session_token, player_name).is_err() {
+            return false;
+        }
+        if let Err(e) = store.save() {
+            warn!("⚠️ 設定の保存失敗: {}", e);
+        }
+        true
+    }
+
+    /// プレイヤーのカーソルの色・表示名を変更し、セッショントークンに保存する
+    ///
+    /// 色は同じルームの他の参加者と重ならない場合だけ変更できます。
+    /// 表示名は整えてから使い、レーティングは新しい名前の記録に切り替わります。
+    /// 他のプレイヤーが使っている（接続中か、別のセッショントークンで押さえている）名前には変更できません。
+    ///
+    /// # 戻り値
+    /// 成功時は変更後のプロフィール、色・名前が使われている・名前が空などの場合はエラーメッセージ
+    fn update_preferences(
+        player_id: &str,
+        color_index: Option<u8>,
+        player_name: Option<String>,
+        state: &ServerState,
+    ) -> Result<PlayerProfile, String> {
+        let player_name = player_name
+            .map(|name| protocol::sanitize_display_name(&name))
+            .transpose()?;
+        let record = player_name
+            .as_ref()
+            .map(|name| state.ratings.lock().unwrap().get(name));
+
+        let (profile, session_token) = {
+            let mut players_map = state.players.lock().unwrap();
+            let room_id = players_map
+                .get(player_id)
+                .ok_or("プレイヤーが見つかりません")?
+                .room_id
+                .clone();
+            if let (Some(color), Some(room_id)) = (color_index, room_id) {
+                let color_taken = state
+                    .rooms
+                    .lock()
+                    .unwrap()
+                    .get(&room_id)
+                    .is_some_and(|room| room.taken_colors(player_id, &players_map).contains(&color));
+                if color_taken {
+                    return Err("その色は同じルームの他のプレイヤーが使っています".to_string());
+                }
+            }
+            
+            // 他のプレイヤーの名前を名乗ると、名前で記録しているレーティングや成績を引き継げてしまう
+            if let Some(name) = &player_name {
+                let name_in_use = players_map
+                    .values()
+                    .any(|player| player.id != player_id && player.name.to_lowercase() == name.to_lowercase());
+                if name_in_use {
+                    return Err(format!("「{}」は他のプレイヤーが使っている表示名です", name));
+                }
+                let session_token = &players_map[player_id].session_token;
+                state.preferences.lock().unwrap().claim_name(session_token, name)?;
+            }
+            
+            let player = players_map
+                .get_mut(player_id)
+                .ok_or("プレイヤーが見つかりません")?;
+            if let Some(color) = color_index {
+                player.color_index = color;
+            }
+            if let (Some(name), Some(record)) = (&player_name, record) {
+                player.name = name.clone();
+                player.rating = record.rating;
+                player.games_rated = record.games_rated;
+            }
+            (player.profile(), player.session_token.clone())
+        };
+        
+        let mut store = state.preferences.lock().unwrap();
+        store.update(&session_token, color_index, player_name);
+        if let Err(e) = store.save() {
+            warn!("⚠️ 設定の保存失敗: {}", e);
+        }
+        Ok(profile)
+    }
+
+    /// 特定のプレイヤーにメッセージを送信
+    async fn send_to_player(player_id: &str, message: &WebSocketMessage, senders: &Senders) {
+        let message_text = match serde_json::to_string(message) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("❌ メッセージシリアライゼーションエラー: {}", e);
+                return;
+            }
+        };
+
+        let senders_map = senders.lock().unwrap();
+        if let Some(sender) = senders_map.get(player_id) {
+            if sender.send(message_text).is_err() {
+                warn!("⚠️ プレイヤー{}への送信失敗", player_id);
+            }
+        }
+    }
+
+    /// 特定のプレイヤーにエラーメッセージを送信
+    async fn send_error(player_id: &str, message: &str, senders: &Senders) {
+        Self::send_error_reply(player_id, message, None, senders).await;
+    }
+
+    /// 要求への応答としてエラーを送信（要求のIDを付けて、クライアントが待っている応答を失敗にする）
+    async fn send_error_reply(player_id: &str, message: &str, request_id: Option<String>, senders: &Senders) {
+        Self::send_to_player(
+            player_id,
+            &WebSocketMessage::Error {
+                message: message.to_string(),
+                request_id,
+                field: None,
+            },
+            senders,
+        ).await;
+    }
+
+    /// クライアントの時計でのタイムスタンプをサーバーの時刻に直す
+    ///
+    /// プレイヤーが時計のずれを報告していない場合は、ずれがないものとして扱います。
+    fn to_server_time(player_id: &str, timestamp: u64, state: &ServerState) -> u64 {
+        let clock_offset_ms = state
+            .players
+            .lock()
+            .unwrap()
+            .get(player_id)
+            .map_or(0, |player| player.clock_offset_ms);
+        time_sync::client_to_server_time(timestamp, clock_offset_ms, state.clock.now_ms())
+    }
+
+    /// 協力プレイでカードを掴む
+    ///
+    /// 同じカードを複数のプレイヤーがほぼ同時に掴んだ場合は、届いた順ではなく
+    /// 時計のずれを補正した掴んだ時刻で判定します。掴めたことはルーム全員に配信し、
+    /// 掴めなかったプレイヤー（取り上げられたプレイヤーを含む）にはGrabRejectedを送ります。
+    async fn grab_card(player_id: &str, room_id: &str, card_id: &str, timestamp: u64, state: &ServerState) {
+        let ServerState { rooms, senders, clock, .. } = state;
+        let claimed_at = Self::to_server_time(player_id, timestamp, state);
+        let received_at = clock.now_ms();
+        let outcome = {
+            let mut rooms_map = rooms.lock().unwrap();
+            match rooms_map.get_mut(room_id) {
+                None => Err("ルームが存在しません".to_string()),
+                Some(room) if !room.players.iter().any(|id| id == player_id) => {
+                    Err("ルームに参加していません".to_string())
+                }
+                Some(room) => {
+                    // 共有盤面のルームでは、観戦者と持ち主以外のプレイヤーには掴ませない
+                    let allowed = if room.shared_board {
+                        let owner_id = room.card_owners.get(card_id).map(String::as_str);
+                        permissions::check_card(room.seat_role(player_id), player_id, owner_id)
+                    } else {
+                        Ok(())
+                    };
+                    allowed.map(|()| room.card_claims.claim(card_id, player_id, claimed_at, received_at))
+                }
+            }
+        };
+        
+        // 掴めなかったプレイヤー（取り上げられたプレイヤーを含む）にはカードを元の位置に戻してもらう
+        let (loser_id, owner_id) = match outcome {
+            Ok(ClaimOutcome::Granted) => (None, player_id.to_string()),
+            Ok(ClaimOutcome::Overtook { previous_owner }) => {
+                info!("✋ カードの取り合い: {} が {} より先に {} を掴んでいました", player_id, previous_owner, card_id);
+                (Some(previous_owner), player_id.to_string())
+            }
+            Ok(ClaimOutcome::Rejected { owner }) => (Some(player_id.to_string()), owner),
+            Err(e) => {
+                Self::send_error(player_id, &e, senders).await;
+                return;
+            }
+        };
+        if let Some(loser_id) = &loser_id {
+            Self::send_to_player(
+                loser_id,
+                &WebSocketMessage::GrabRejected {
+                    room_id: room_id.to_string(),
+                    card_id: card_id.to_string(),
+                    owner_id: owner_id.clone(),
+                },
+                senders,
+            ).await;
+        }
+        if owner_id != player_id {
+            return;
+        }
+        
+        Self::broadcast_to_room(
+            &WebSocketMessage::CardGrabbed {
+                room_id: room_id.to_string(),
+                player_id: player_id.to_string(),
+                card_id: card_id.to_string(),
+            },
+            room_id,
+            state,
+            None,
+        ).await;
+    }
+
+    /// ルーム内のプレイヤーにメッセージをブロードキャスト
+    ///
+    /// バックプレーンに接続している場合は、他のインスタンスにつながっている
+    /// ルームの参加者にも届くよう発行します。
+    async fn broadcast_to_room(
+        message: &WebSocketMessage,
+        room_id: &str,
+        state: &ServerState,
+        exclude_player: Option<&str>,
+    ) {
+        let message_text = match serde_json::to_string(message) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("❌ メッセージシリアライゼーションエラー: {}", e);
+                return;
+            }
+        };
+
+        if !Self::deliver_to_room(&message_text, room_id, state, exclude_player) {
+            return;
+        }
+        if let Some(backplane) = &state.backplane {
+            backplane.publish(&BackplaneEvent::RoomBroadcast {
+                origin: backplane.instance_id().to_string(),
+                room_id: room_id.to_string(),
+                message: message_text,
+                exclude_player: exclude_player.map(str::to_string),
+            });
+        }
+    }
+
+    /// このインスタンスにつながっているルームの参加者にメッセージを送る
+    ///
+    /// # 戻り値
+    /// ルームが存在した場合true
+    fn deliver_to_room(
+        message_text: &str,
+        room_id: &str,
+        state: &ServerState,
+        exclude_player: Option<&str>,
+    ) -> bool {
+        let room_players = match state.rooms.lock().unwrap().get(room_id) {
+            Some(room) => room.players.clone(),
+            None => return false,
+        };
+
+        let senders_map = state.senders.lock().unwrap();
+        for player_id in &room_players {
+            if exclude_player == Some(player_id.as_str()) {
+                continue;
+            }
+            if let Some(sender) = senders_map.get(player_id) {
+                if sender.send(message_text.to_string()).is_err() {
+                    warn!("⚠️ プレイヤー{}への送信失敗", player_id);
+                }
+            }
+        }
+        true
+    }
+
+    /// 全プレイヤーにメッセージをブロードキャスト
+    async fn broadcast_to_all(
+        message: &WebSocketMessage,
+        senders: &Senders,
+        exclude_player: Option<&str>,
+    ) {
+        let message_text = match serde_json::to_string(message) {
+            Ok(text) => text,
+            Err(e) => {
+                error!("❌ メッセージシリアライゼーションエラー: {}", e);
+                return;
+            }
+        };
+
+        let senders_map = senders.lock().unwrap();
+        for (player_id, sender) in senders_map.iter() {
+            if let Some(exclude) = exclude_player {
+                if player_id == exclude {
+                    continue;
+                }
+            }
+            
+            if sender.send(message_text.clone()).is_err() {
+                warn!("⚠️ プレイヤー{}への送信失敗", player_id);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// サーバー起動用のメイン関数
+// =============================================================================
+
+pub async fn run_websocket_server(addr: &str) -> Result<(), Box<dyn std::error::Error>> {
+    info!("🚀 マルチプレイソリティア WebSocketサーバー起動中...");
+    
+    let mut server = SolitaireServer::new();
+    
+    // 環境変数BACKPLANE_URLが設定されていれば、他のインスタンスとルームを共有する
+    match std::env::var("BACKPLANE_URL") {
+        Ok(url) => {
+            if let Err(e) = server.connect_backplane(&url).await {
+                error!("❌ {}（このインスタンスのみでルームを管理します）", e);
+            }
+        }
+        Err(_) => info!("ℹ️ バックプレーン未設定: このインスタンスのみでルームを管理します（スティッキーセッションが必要です）"),
+    }
+    
+    // HTTP APIはWebSocketの待ち受け前から起動し、準備ができるまで/readyで503を返す
+    let http_addr = std::env::var("HTTP_ADDR").unwrap_or_else(|_| DEFAULT_HTTP_ADDR.to_string());
+    tokio::spawn(http_api::serve(http_addr, server.state.clone()));
+    
+    // Ctrl+Cで終了する場合は、進行中のルームを保存してから終了する
+    tokio::select! {
+        result = server.start(addr) => result?,
+        _ = tokio::signal::ctrl_c() => info!("🛑 終了要求を受信しました"),
+    }
+    SolitaireServer::save_rooms(&server.state);
+    
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // ログ出力を初期化（環境変数RUST_LOGでレベルを変更できる）
+    logging::init();
+
+    // 第1引数で待ち受けアドレスを変更できる（テストではローカルの空きポートを指定する）
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    run_websocket_server(&addr).await
 }
\ No newline at end of file
@@ -0,0 +1,81 @@
+// =============================================================================
+// 離席（AFK）の判定のテスト
+// =============================================================================
+// 操作のないまま設定の時間を超えたプレイヤーが離席中になり、操作が届くと戻ること、
+// クライアントから届いた操作していない時間を数えること、離席中に飛ばしたターンを数えて
+// 席を没収できること、操作していない時間を段階が変わったときだけ送ること、
+// 相手の離席がスコアボードに反映されることを確認します。
+//
+// 実行方法：cargo test --test afk
+// =============================================================================
+
+use ecs_wasm_solitaire::afk::{idle_report_step, AfkTracker, IDLE_REPORT_STEP_SECONDS};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use ecs_wasm_solitaire::scoreboard;
+
+#[test]
+fn idle_players_become_afk_and_forfeit_after_skipped_turns() {
+    let players = ["alice".to_string(), "bob".to_string()];
+    let mut tracker = AfkTracker::new();
+    let start = 1_000_000;
+
+    // 最初の判定から数え始め、操作した人は数え直す
+    assert!(tracker.check(&players, 30, start).is_empty());
+    assert!(!tracker.record_activity("bob", start + 20_000));
+    assert_eq!(
+        tracker.check(&players, 30, start + 30_000),
+        vec![("alice".to_string(), 30)]
+    );
+    assert!(tracker.is_afk("alice"));
+    assert!(tracker.any_afk(&players));
+
+    // クライアントが知らせた操作していない時間でも離席中になる
+    tracker.record_idle("bob", 45, start + 70_000);
+    assert_eq!(
+        tracker.check(&players, 30, start + 70_000),
+        vec![("bob".to_string(), 45)]
+    );
+
+    // 操作が届くと戻り、飛ばしたターンの数も数え直す
+    assert_eq!(tracker.record_afk_turn("bob"), 1);
+    assert!(tracker.record_idle("bob", 0, start + 71_000));
+    assert!(!tracker.is_afk("bob"));
+    assert_eq!(tracker.record_afk_turn("bob"), 1);
+
+    // 没収した席はゲームが終わるまで離席の判定に入れない
+    assert_eq!(tracker.record_afk_turn("alice"), 1);
+    assert_eq!(tracker.record_afk_turn("alice"), 2);
+    tracker.forfeit("alice");
+    assert!(tracker.is_forfeited("alice"));
+    assert!(!tracker.any_afk(&players));
+    tracker.end_game();
+    assert!(!tracker.is_forfeited("alice"));
+    assert!(tracker.is_afk("alice"));
+
+    tracker.remove_player("alice");
+    assert!(!tracker.is_afk("alice"));
+}
+
+#[test]
+fn idle_time_is_reported_in_steps_and_shown_on_the_scoreboard() {
+    let step = f64::from(IDLE_REPORT_STEP_SECONDS);
+    assert_eq!(idle_report_step(0.0), 0);
+    assert_eq!(idle_report_step(step - 0.1), 0);
+    assert_eq!(idle_report_step(step), 1);
+    assert_eq!(idle_report_step(step * 3.5), 3);
+    assert_eq!(idle_report_step(f64::NAN), 0);
+
+    let mut world = World::new();
+    let afk = |afk| WebSocketMessage::PlayerAfk {
+        room_id: "room".to_string(),
+        player_id: "bob".to_string(),
+        afk,
+        idle_seconds: 30,
+        paused: false,
+    };
+    assert!(scoreboard::apply(&mut world, Some("alice"), &afk(true)));
+    assert!(scoreboard::players(&world)[0].afk);
+    assert!(scoreboard::apply(&mut world, Some("alice"), &afk(false)));
+    assert!(!scoreboard::players(&world)[0].afk);
+}
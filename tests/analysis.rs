@@ -0,0 +1,60 @@
+// =============================================================================
+// 対局後の振り返りのテスト
+// =============================================================================
+// シードから配ったゲームをヒントの手で進め、移動履歴の全ての手が評価されること、
+// シードから再現できないシナリオの盤面は振り返りできないことを確認します。
+//
+// 実行方法：cargo test --test analysis
+// =============================================================================
+
+use ecs_wasm_solitaire::analysis::{GameAnalysis, MoveQuality};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::hint::HintEngine;
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::{CardLocation, MoveLog, SolitaireManager, SolitaireType};
+
+/// 振り返りでソルバーが1局面あたりに調べる局面数の上限（テストを速くするため小さめ）
+const SEARCH_LIMIT: u32 = 500;
+
+#[test]
+fn every_logged_move_is_reviewed() {
+    let mut world = World::new();
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 7);
+    for _ in 0..12 {
+        let hint = HintEngine::find_hint(&world).expect("打てる手がある");
+        assert!(HintEngine::apply(&mut world, &hint));
+    }
+    let logged = world.get_component::<MoveLog>(game).unwrap().moves.len();
+
+    let analysis = GameAnalysis::analyze(&world, game, SEARCH_LIMIT).expect("シードから再現できる");
+    assert_eq!(analysis.moves.len(), logged);
+    assert_eq!(
+        analysis.best + analysis.good + analysis.inaccuracies + analysis.blunders,
+        logged as u32
+    );
+    assert!(analysis
+        .moves
+        .iter()
+        .zip(1..)
+        .all(|(reviewed, number)| reviewed.move_number == number));
+
+    // デッキから引く手は局面を変えないので、最善にも悪手にもならない
+    assert!(analysis
+        .moves
+        .iter()
+        .filter(|reviewed| reviewed.from == CardLocation::Deck)
+        .all(|reviewed| reviewed.quality == MoveQuality::Good));
+}
+
+#[test]
+fn boards_not_dealt_from_the_seed_cannot_be_reviewed() {
+    let mut world = World::new();
+    let game = BoardBuilder::new()
+        .tableau(0, 0, &["9D"])
+        .tableau(1, 0, &["10S"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+
+    let error = GameAnalysis::analyze(&world, game, SEARCH_LIMIT).unwrap_err();
+    assert!(error.contains("再現できません"), "{}", error);
+}
@@ -0,0 +1,171 @@
+// =============================================================================
+// アニメーションの順番待ちのテスト
+// =============================================================================
+// 続けて登録した移動が登録した順に1手ずつ再生されること（同じカードの移動が
+// 追い越さないこと）、自動プレイの手が1手ずつ順番待ちに入ること、
+// 飛ばす操作ですべてのカードが最後の移動先へすぐに置かれること、視差効果を減らす設定では
+// 同じ移動が短い時間で終わることを確認します。
+//
+// 実行方法：cargo test --test animation_queue
+// =============================================================================
+
+use ecs_wasm_solitaire::animation_queue::{self, AnimationQueue, AnimationQueueSystem, MoveQueue};
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::game::{AnimationSettings, GameSettings, REDUCED_MOTION_SPEEDUP};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::{CardAnimationSystem, SolitaireCard, SolitaireType};
+
+/// 1フレームの時間（秒）
+const FRAME: f64 = 0.016;
+
+/// 3列にカードを1枚ずつ置いた盤面と、そのカード
+fn three_cards() -> (World, [Entity; 3]) {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 0, &["KS"])
+        .tableau(1, 0, &["KH"])
+        .tableau(2, 0, &["KD"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    let find = |column: u32| {
+        world
+            .query::<SolitaireCard>()
+            .find(|(_, card)| card.position_in_location == column)
+            .map(|(entity, _)| entity)
+            .expect("列にカードがある")
+    };
+    let cards = [find(0), find(1), find(2)];
+    (world, cards)
+}
+
+/// カードの表示座標
+fn position(world: &World, entity: Entity) -> (f32, f32) {
+    let card = world
+        .get_component::<SolitaireCard>(entity)
+        .expect("カードがある");
+    (card.display_x, card.display_y)
+}
+
+/// アニメーションのシステムを1フレーム分実行
+fn tick(world: &mut World) {
+    AnimationQueueSystem.update(world, FRAME);
+    CardAnimationSystem.update(world, FRAME);
+}
+
+#[test]
+fn queued_moves_play_one_step_at_a_time() {
+    let (mut world, [first, second, third]) = three_cards();
+    let second_start = position(&world, second);
+    let third_start = position(&world, third);
+
+    // 1手目：1枚目と2枚目が一緒に動く / 2手目：3枚目が動く / 3手目：1枚目がさらに動く
+    animation_queue::enqueue(&mut world, &[(first, 20.0, 400.0), (second, 120.0, 400.0)]);
+    animation_queue::enqueue(&mut world, &[(third, 220.0, 400.0)]);
+    animation_queue::enqueue(&mut world, &[(first, 320.0, 400.0)]);
+    assert_eq!(
+        world
+            .get_resource::<AnimationQueue>()
+            .expect("順番待ちがある")
+            .pending_steps(),
+        3
+    );
+
+    // 待っている間も、移動先は最後の移動先になっている
+    let card = world.get_component::<SolitaireCard>(first).unwrap();
+    assert!(card.is_animating);
+    assert_eq!((card.target_x, card.target_y), (320.0, 400.0));
+
+    // 1手目の間は、一緒に動くカードだけが動き、3枚目は元の位置で待つ
+    tick(&mut world);
+    assert_ne!(position(&world, second), second_start);
+    assert_eq!(position(&world, third), third_start);
+
+    let mut frames = 0;
+    while position(&world, first) != (20.0, 400.0) {
+        assert_eq!(position(&world, third), third_start);
+        tick(&mut world);
+        frames += 1;
+        assert!(frames < 200, "1手目が終わらない");
+    }
+    assert_eq!(position(&world, second), (120.0, 400.0));
+
+    // 1手目が終わってから2手目が始まり、1枚目は3手目まで止まっている
+    tick(&mut world);
+    assert_ne!(position(&world, third), third_start);
+    assert_eq!(position(&world, first), (20.0, 400.0));
+
+    for _ in 0..400 {
+        tick(&mut world);
+    }
+    assert_eq!(position(&world, first), (320.0, 400.0));
+    assert_eq!(position(&world, third), (220.0, 400.0));
+    assert_eq!(world.query::<MoveQueue>().count(), 0);
+    assert!(world
+        .query::<SolitaireCard>()
+        .all(|(_, card)| !card.is_animating));
+}
+
+#[test]
+fn auto_play_queues_each_move_and_can_be_skipped() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+
+    let moves = rt.auto_play_until_stuck();
+    assert!(moves > 0, "デッキを引く手は必ず打てる");
+    let pending = rt
+        .world
+        .get_resource::<AnimationQueue>()
+        .expect("順番待ちがある")
+        .pending_steps();
+    assert_eq!(pending, u64::from(moves), "1手ずつ順番待ちに入る");
+
+    // 飛ばすと、すべてのカードが最後の移動先に置かれる
+    assert_eq!(rt.skip_animations(), pending);
+    assert_eq!(rt.world.query::<MoveQueue>().count(), 0);
+    assert!(rt
+        .world
+        .query::<SolitaireCard>()
+        .all(|(_, card)| !card.is_animating
+            && (card.display_x, card.display_y) == (card.target_x, card.target_y)));
+    assert_eq!(
+        rt.world
+            .get_resource::<AnimationQueue>()
+            .expect("順番待ちがある")
+            .pending_steps(),
+        0
+    );
+}
+
+#[test]
+fn reduced_motion_shortens_card_moves() {
+    // 1枚を同じ距離だけ動かし、止まるまでのフレーム数を数える
+    let frames_to_finish = |animation: AnimationSettings| {
+        let (mut world, [card, _, _]) = three_cards();
+        world.insert_resource(GameSettings {
+            animation,
+            ..GameSettings::default()
+        });
+        animation_queue::enqueue(&mut world, &[(card, 20.0, 600.0)]);
+        let mut frames = 0;
+        while world.query::<MoveQueue>().count() > 0 {
+            tick(&mut world);
+            frames += 1;
+            assert!(frames < 1000, "移動が終わらない");
+        }
+        frames
+    };
+
+    let normal = frames_to_finish(AnimationSettings::default());
+    let reduced = frames_to_finish(AnimationSettings {
+        reduced_motion: true,
+        ..AnimationSettings::default()
+    });
+    assert!(reduced > 0);
+    assert!(
+        (reduced as f32) <= (normal as f32 / REDUCED_MOTION_SPEEDUP).ceil() + 1.0,
+        "通常{}フレーム、視差効果を減らす設定で{}フレーム",
+        normal,
+        reduced
+    );
+}
@@ -0,0 +1,130 @@
+// =============================================================================
+// 止まったアニメーションの見張りのテスト
+// =============================================================================
+// 動かすシステムのなくなったカードが決まったフレーム数の後に移動先へ置かれて
+// AnimationStalledイベントで知らされること、経過時間0のフレームと
+// 順番待ちで前のステップを待っているカードは止まったとみなさないことを確認します。
+//
+// 実行方法：cargo test --test animation_watchdog
+// =============================================================================
+
+use ecs_wasm_solitaire::animation_queue::{self, AnimationQueueSystem, MoveQueue};
+use ecs_wasm_solitaire::animation_watchdog::{AnimationWatchdogSystem, STALL_FRAMES};
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::{CardAnimationSystem, SolitaireCard};
+
+/// 1フレームの時間（秒）
+const FRAME: f64 = 0.016;
+
+/// 2列にカードを1枚ずつ置いた盤面と、そのカード
+fn two_cards() -> (World, [Entity; 2]) {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    BoardBuilder::new()
+        .tableau(0, 0, &["KS"])
+        .tableau(1, 0, &["KH"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    let find = |column: u32| {
+        world
+            .query::<SolitaireCard>()
+            .find(|(_, card)| card.position_in_location == column)
+            .map(|(entity, _)| entity)
+            .expect("列にカードがある")
+    };
+    let cards = [find(0), find(1)];
+    (world, cards)
+}
+
+/// アニメーションのシステムと見張りを1フレーム分実行
+fn tick(world: &mut World, delta_time: f64) {
+    AnimationQueueSystem.update(world, delta_time);
+    CardAnimationSystem.update(world, delta_time);
+    AnimationWatchdogSystem.update(world, delta_time);
+}
+
+/// 溜まっているAnimationStalledイベントの(カードID, フレーム数)
+fn stalled_events(world: &mut World) -> Vec<(u32, u32)> {
+    world
+        .get_resource_mut::<EventQueue>()
+        .expect("イベントキューがある")
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::AnimationStalled {
+                card_id,
+                stalled_frames,
+            } => Some((card_id, stalled_frames)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn orphaned_animations_are_completed_after_the_stall_limit() {
+    let (mut world, [card, _]) = two_cards();
+
+    // 順番待ちが空のまま残ったカードは、どのシステムも動かさない
+    let target = world
+        .get_component_mut::<SolitaireCard>(card)
+        .map(|card| {
+            card.start_animation(card.display_x + 300.0, card.display_y);
+            (card.target_x, card.target_y)
+        })
+        .expect("カードがある");
+    world.add_component(card, MoveQueue::default());
+
+    // 経過時間0のフレームは数えない
+    for _ in 0..STALL_FRAMES * 2 {
+        tick(&mut world, 0.0);
+    }
+    for _ in 0..STALL_FRAMES {
+        tick(&mut world, FRAME);
+    }
+    assert!(
+        world
+            .get_component::<SolitaireCard>(card)
+            .unwrap()
+            .is_animating
+    );
+    assert!(stalled_events(&mut world).is_empty());
+
+    tick(&mut world, FRAME);
+    let finished = world.get_component::<SolitaireCard>(card).unwrap();
+    assert!(!finished.is_animating);
+    assert_eq!((finished.display_x, finished.display_y), target);
+    assert!(!world.has_component::<MoveQueue>(card));
+    assert_eq!(stalled_events(&mut world), vec![(card.0, STALL_FRAMES)]);
+}
+
+#[test]
+fn cards_waiting_for_an_earlier_step_are_not_stalled() {
+    let (mut world, [first, second]) = two_cards();
+    let (x, y) = {
+        let card = world.get_component::<SolitaireCard>(first).unwrap();
+        (card.display_x, card.display_y)
+    };
+
+    // 1手目は遠くまで動くため、2手目のカードは止まったとみなすフレーム数より長く待つ
+    animation_queue::enqueue(&mut world, &[(first, x + 2000.0, y)]);
+    animation_queue::enqueue(&mut world, &[(second, x, y + 100.0)]);
+    let mut frames = 0;
+    while world
+        .query::<SolitaireCard>()
+        .any(|(_, card)| card.is_animating)
+    {
+        tick(&mut world, FRAME);
+        frames += 1;
+        assert!(frames < 1000, "アニメーションが終わらない");
+    }
+    assert!(frames > STALL_FRAMES as usize * 2);
+    assert!(stalled_events(&mut world).is_empty());
+    assert_eq!(
+        world
+            .get_component::<SolitaireCard>(second)
+            .map(|card| (card.display_x, card.display_y)),
+        Some((x, y + 100.0))
+    );
+}
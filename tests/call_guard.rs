@@ -0,0 +1,138 @@
+// =============================================================================
+// 呼び出し順序のチェックのテスト
+// =============================================================================
+// セッションの作成前・ゲームの開始前の操作、他のプレイヤーのターン中の操作、
+// カードのアニメーション中の盤面の置き換えが理由付きのエラーになり、
+// 正しい順序の呼び出しは通ること、ゲームが終わると手番の制限が解けることを確認します。
+//
+// 実行方法：cargo test --test call_guard
+// =============================================================================
+
+use ecs_wasm_solitaire::call_guard::{check, CallKind};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+use ecs_wasm_solitaire::storage;
+use serde_json::json;
+
+/// サーバーに参加してルームに入ったランタイム
+fn runtime_in_room(player_id: &str) -> GameRuntime {
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    rt.network
+        .connect(&mut rt.world, "ws://localhost:8101")
+        .expect("接続を開始できる");
+    rt.network.set_connected(&mut rt.world, true);
+    rt.network.join(&rt.world, "Alice");
+    let messages = [
+        json!({
+            "type": "PlayerProfile",
+            "profile": {
+                "player_id": player_id,
+                "player_name": "Alice",
+                "color_index": 0,
+                "rating": 1500,
+                "games_rated": 0,
+                "is_bot": false,
+            },
+        }),
+        json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": player_id }),
+    ];
+    for message in messages {
+        rt.network
+            .receive(&mut rt.world, &message.to_string())
+            .expect("メッセージを受け取れる");
+    }
+    rt
+}
+
+/// ターンの開始を受け取る
+fn start_turn(rt: &mut GameRuntime, player_id: &str) {
+    let turn = json!({
+        "type": "TurnStarted",
+        "room_id": "room-1",
+        "player_id": player_id,
+        "turn_number": 1,
+        "time_limit_seconds": 30,
+    });
+    rt.network
+        .receive(&mut rt.world, &turn.to_string())
+        .expect("ターンの開始を受け取れる");
+}
+
+#[test]
+fn calls_before_the_session_or_game_exist_are_rejected() {
+    let error = check(None, "move_card", CallKind::Move).unwrap_err();
+    assert!(error.contains("move_card()"));
+    assert!(error.contains("initialize_game()"));
+
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    assert!(check(Some(&rt), "save_game", CallKind::Session).is_ok());
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move)
+        .unwrap_err()
+        .contains("ゲームの開始前"));
+
+    // 配るアニメーションの間は盤面を置き換えられない
+    rt.start_game(SolitaireType::Klondike);
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move).is_ok());
+    let error = check(Some(&rt), "load_game", CallKind::ReplaceBoard).unwrap_err();
+    assert!(error.contains("load_game()"));
+    assert!(error.contains("skip_animations()"));
+    rt.skip_animations();
+    assert!(check(Some(&rt), "load_game", CallKind::ReplaceBoard).is_ok());
+}
+
+#[test]
+fn moves_are_rejected_during_another_players_turn() {
+    let mut rt = runtime_in_room("player-1");
+    rt.start_game(SolitaireType::Klondike);
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move).is_ok());
+
+    start_turn(&mut rt, "player-2");
+    assert_eq!(rt.network.turn_player_id(), Some("player-2"));
+    let error = check(Some(&rt), "draw_card_from_deck", CallKind::Move).unwrap_err();
+    assert!(error.contains("player-2"));
+    assert!(check(Some(&rt), "save_game", CallKind::Session).is_ok());
+
+    // 自分のターンになれば打てる
+    start_turn(&mut rt, "player-1");
+    assert!(!rt.network.is_opponents_turn());
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move).is_ok());
+
+    // 別のルームのターンは数えない
+    let other_room = json!({
+        "type": "TurnStarted",
+        "room_id": "room-2",
+        "player_id": "player-3",
+        "turn_number": 1,
+        "time_limit_seconds": 30,
+    });
+    rt.network
+        .receive(&mut rt.world, &other_room.to_string())
+        .expect("ターンの開始を受け取れる");
+    assert_eq!(rt.network.turn_player_id(), Some("player-1"));
+}
+
+#[test]
+fn the_turn_is_forgotten_when_the_game_ends() {
+    let mut rt = runtime_in_room("player-1");
+    rt.start_game(SolitaireType::Klondike);
+    start_turn(&mut rt, "player-2");
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move).is_err());
+
+    // ゲームが終わって結果を送ると、他のプレイヤーのターンとして扱わない
+    let state = rt.game_state_mut().expect("ゲーム中");
+    state.is_completed = true;
+    state.end_time = Some(state.start_time);
+    rt.update(0.016);
+    assert!(rt.game_result().is_some());
+    // 結果は次のフレームの通信で送る
+    rt.update(0.016);
+    assert_eq!(rt.network.turn_player_id(), None);
+    assert!(!rt.network.is_opponents_turn());
+
+    // 次のゲームのターンが始まれば、また制限する
+    rt.start_game(SolitaireType::Klondike);
+    start_turn(&mut rt, "player-2");
+    assert!(check(Some(&rt), "draw_card_from_deck", CallKind::Move).is_err());
+}
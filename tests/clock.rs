@@ -0,0 +1,56 @@
+// =============================================================================
+// フレームの経過時間の切り詰めと分割のテスト
+// =============================================================================
+// 1フレームの経過時間が上限で切り詰められ、システムの実行回数が上限を超えないこと、
+// スリープ復帰のような大きな飛びが検出されることを確認します。
+//
+// 実行方法：cargo test --test clock
+// =============================================================================
+
+use ecs_wasm_solitaire::clock::{
+    FrameSteps, GameClock, MAX_FRAME_SECONDS, MAX_STEPS_PER_FRAME, MAX_STEP_SECONDS,
+};
+
+/// 浮動小数点の比較に使う許容誤差
+const EPSILON: f64 = 1e-9;
+
+#[test]
+fn frames_are_split_into_bounded_steps() {
+    // 通常のフレームは1回で実行する
+    let frame = FrameSteps::split(0.016);
+    assert_eq!(frame.steps, 1);
+    assert!((frame.step_seconds - 0.016).abs() < EPSILON);
+    assert!(!frame.is_clock_jump());
+
+    // 遅いフレームは同じ長さに分ける
+    let frame = FrameSteps::split(0.12);
+    assert_eq!(frame.steps, 3);
+    assert!(frame.step_seconds <= MAX_STEP_SECONDS);
+    assert!((frame.simulated_seconds() - 0.12).abs() < EPSILON);
+    assert!(frame.dropped_seconds() < EPSILON);
+
+    // 30秒の飛びは上限までしか進めず、残りは捨てる
+    let frame = FrameSteps::split(30.0);
+    assert_eq!(frame.steps, MAX_STEPS_PER_FRAME);
+    assert!((frame.simulated_seconds() - MAX_FRAME_SECONDS).abs() < EPSILON);
+    assert!((frame.dropped_seconds() - (30.0 - MAX_FRAME_SECONDS)).abs() < EPSILON);
+    assert!(frame.is_clock_jump());
+
+    // 負の値・非数は経過時間0の1回として扱う
+    for delta in [-1.0, f64::NAN, f64::INFINITY] {
+        let frame = FrameSteps::split(delta);
+        assert_eq!((frame.steps, frame.step_seconds), (1, 0.0));
+        assert!(!frame.is_clock_jump());
+    }
+}
+
+#[test]
+fn the_clock_records_only_the_simulated_time() {
+    let mut clock = GameClock::new();
+    let frame = clock.tick_steps(30.0);
+    assert!(frame.is_clock_jump());
+    assert!((clock.frame_seconds() - MAX_FRAME_SECONDS).abs() < EPSILON);
+
+    clock.tick_steps(0.016);
+    assert!((clock.game_seconds() - (MAX_FRAME_SECONDS + 0.016)).abs() < EPSILON);
+}
@@ -0,0 +1,118 @@
+// =============================================================================
+// レースのコンボのテスト
+// =============================================================================
+// 受付時間内に続けてファウンデーションに置くとコンボと倍率が上がってボーナスが貯まること、
+// 受付時間が切れると途切れて1から数え直すこと、元に戻してもコンボが伸びないこと、
+// 1手で何枚置いてもコンボは1つだけ伸びること、
+// 相手のコンボがスコアボードに反映されることを確認します。
+//
+// 実行方法：cargo test --test combo
+// =============================================================================
+
+use ecs_wasm_solitaire::combo::{multiplier, ComboTracker, MAX_MULTIPLIER};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use ecs_wasm_solitaire::scoreboard;
+
+/// ComboUpdateのコンボ・倍率・ボーナス・期限
+fn combo_of(message: Option<WebSocketMessage>) -> (u32, u32, u32, Option<u64>) {
+    match message {
+        Some(WebSocketMessage::ComboUpdate {
+            combo,
+            multiplier,
+            bonus_score,
+            expires_at_ms,
+            ..
+        }) => (combo, multiplier, bonus_score, expires_at_ms),
+        other => panic!("ComboUpdateではありません: {:?}", other),
+    }
+}
+
+#[test]
+fn plays_within_the_window_build_the_multiplier_until_it_expires() {
+    let mut tracker = ComboTracker::new();
+    let mut now = 1_000_000;
+
+    // 1枚目は倍率1、3枚目で倍率2になりボーナスが付く
+    assert_eq!(
+        combo_of(tracker.record("room", "alice", 1, 5, now)),
+        (1, 1, 0, Some(now + 5_000))
+    );
+    now += 2_000;
+    assert_eq!(combo_of(tracker.record("room", "alice", 2, 5, now)).0, 2);
+    now += 2_000;
+    assert_eq!(
+        combo_of(tracker.record("room", "alice", 3, 5, now)),
+        (3, 2, 10, Some(now + 5_000))
+    );
+
+    // 元に戻して置き直しても、元の枚数を超えるまでコンボは伸びない
+    assert!(tracker.record("room", "alice", 2, 5, now).is_none());
+    assert!(tracker.record("room", "alice", 3, 5, now).is_none());
+    assert_eq!(
+        combo_of(tracker.record("room", "alice", 4, 5, now + 1_000)).0,
+        4
+    );
+    now += 1_000;
+
+    // 受付時間内は途切れず、過ぎると途切れたことを伝える
+    assert!(tracker.expire("room", 5, now + 5_000).is_empty());
+    let expired = tracker.expire("room", 5, now + 5_001);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(combo_of(expired.into_iter().next()), (0, 1, 20, None));
+    assert!(tracker.expire("room", 5, now + 10_000).is_empty());
+
+    // 途切れた後は1から数え直し、ボーナスは残る
+    assert_eq!(
+        combo_of(tracker.record("room", "alice", 5, 5, now + 20_000)),
+        (1, 1, 20, Some(now + 25_000))
+    );
+    assert_eq!(tracker.bonus_score("alice"), 20);
+
+    tracker.reset(None);
+    assert!(tracker.streak("alice").is_none());
+    assert_eq!(tracker.bonus_score("alice"), 0);
+
+    // 1手で何枚置いても（まとめて片付けたなど）コンボは1つだけ伸びる
+    assert_eq!(combo_of(tracker.record("room", "alice", 1, 5, now)).0, 1);
+    assert_eq!(
+        combo_of(tracker.record("room", "alice", 10, 5, now + 1_000)),
+        (2, 1, 0, Some(now + 6_000))
+    );
+
+    // 倍率には上限がある
+    assert_eq!(multiplier(0), 1);
+    assert_eq!(multiplier(100), MAX_MULTIPLIER);
+}
+
+#[test]
+fn opponent_combos_reach_the_scoreboard() {
+    let mut world = World::new();
+    let combo_update = |player_id: &str, combo: u32| WebSocketMessage::ComboUpdate {
+        room_id: "room".to_string(),
+        player_id: player_id.to_string(),
+        combo,
+        multiplier: multiplier(combo),
+        bonus_score: 30,
+        expires_at_ms: None,
+    };
+
+    assert!(scoreboard::apply(
+        &mut world,
+        Some("me"),
+        &combo_update("bob", 6)
+    ));
+    let players = scoreboard::players(&world);
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].combo, 6);
+    assert_eq!(players[0].multiplier, 3);
+    assert_eq!(players[0].bonus_score, 30);
+
+    // 自分のコンボは一覧に載せない
+    assert!(!scoreboard::apply(
+        &mut world,
+        Some("me"),
+        &combo_update("me", 3)
+    ));
+    assert_eq!(scoreboard::players(&world).len(), 1);
+}
@@ -0,0 +1,470 @@
+// =============================================================================
+// 結合テスト用の共通処理
+// =============================================================================
+// サーバーのバイナリをローカルの空きポートで起動し、tokio-tungsteniteの
+// クライアントから接続して、メッセージの送受信を確認するための補助関数です。
+//
+// - TestServer：サーバープロセス（テスト終了時に自動で終了する。クラッシュを再現する再起動も可能）
+// - FakeBroker：バックプレーンの代わりになる、SUBSCRIBEとPUBLISHだけを扱うRedis
+// - FakePushGateway：プッシュ通知の中継サーバーの代わりに、POSTされた通知を受け取る
+// - http_get：HTTP APIへのGETリクエスト（ステータスコードとJSONの本文を返す）
+// - TestClient：WebSocketクライアント（JSONの送受信とタイムアウト付きの待機、Reliableで届いたメッセージへのAck）
+// =============================================================================
+
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream as TokioTcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// メッセージを待つ時間の上限
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// サーバーの起動を待つ時間の上限
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 起動したサーバープロセス
+pub struct TestServer {
+    /// サーバーのプロセス
+    process: Child,
+
+    /// サーバーのバイナリのパス（再起動用）
+    binary: String,
+
+    /// 待ち受けアドレス
+    addr: SocketAddr,
+
+    /// サーバーの作業ディレクトリ（保存データの書き込み先）
+    work_dir: PathBuf,
+
+    /// サーバーに渡す環境変数（再起動用）
+    envs: Vec<(String, String)>,
+}
+
+impl TestServer {
+    /// サーバーのバイナリを空きポートで起動し、接続できるようになるまで待つ
+    ///
+    /// # 引数
+    /// * `binary` - サーバーのバイナリのパス（env!("CARGO_BIN_EXE_...")）
+    ///
+    /// # 戻り値
+    /// 起動済みのTestServer
+    pub fn start(binary: &str) -> Self {
+        Self::start_with_env(binary, &[])
+    }
+
+    /// 環境変数を指定してサーバーを起動する
+    ///
+    /// # 引数
+    /// * `binary` - サーバーのバイナリのパス
+    /// * `envs` - サーバーに渡す環境変数（名前と値）
+    pub fn start_with_env(binary: &str, envs: &[(&str, &str)]) -> Self {
+        let addr = free_local_addr();
+        let envs: Vec<(String, String)> = envs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        // 保存データがリポジトリ内に書き込まれないよう、テストごとの作業ディレクトリで起動する
+        let work_dir = std::env::temp_dir().join(format!("solitaire-test-{}", addr.port()));
+        std::fs::create_dir_all(&work_dir).expect("作業ディレクトリを作成できる");
+
+        let server = Self {
+            process: spawn_server(binary, addr, &work_dir, &envs),
+            binary: binary.to_string(),
+            addr,
+            work_dir,
+            envs,
+        };
+        server.wait_until_listening();
+        server
+    }
+
+    /// サーバーを強制終了し、同じアドレスと作業ディレクトリで起動し直す（クラッシュの再現）
+    pub fn restart(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        self.process = spawn_server(&self.binary, self.addr, &self.work_dir, &self.envs);
+        self.wait_until_listening();
+    }
+
+    /// WebSocketのURL
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.addr)
+    }
+
+    /// サーバーの作業ディレクトリ（保存データはこの下のsave_dataに書き込まれる）
+    pub fn work_dir(&self) -> &Path {
+        &self.work_dir
+    }
+
+    /// サーバーがポートを開くまで待つ
+    fn wait_until_listening(&self) {
+        let deadline = Instant::now() + STARTUP_TIMEOUT;
+        while TcpStream::connect(self.addr).is_err() {
+            assert!(Instant::now() < deadline, "サーバーが起動しませんでした: {}", self.addr);
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = std::fs::remove_dir_all(&self.work_dir);
+    }
+}
+
+/// サーバーのプロセスを起動する
+fn spawn_server(binary: &str, addr: SocketAddr, work_dir: &Path, envs: &[(String, String)]) -> Child {
+    Command::new(binary)
+        .arg(addr.to_string())
+        .current_dir(work_dir)
+        .env("RUST_LOG", "warn")
+        .envs(envs.iter().map(|(name, value)| (name, value)))
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("サーバーを起動できる")
+}
+
+/// OSに空いているポートを割り当ててもらう
+pub fn free_local_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("空きポートを取得できる")
+}
+
+/// HTTP APIにGETリクエストを送る（待ち受けが始まるまで接続を繰り返す）
+///
+/// # 引数
+/// * `addr` - HTTP APIの待ち受けアドレス
+/// * `path` - リクエストするパス
+///
+/// # 戻り値
+/// ステータスコードと、本文をJSONとして解析した値（JSONでない場合はNull）
+pub fn http_get(addr: SocketAddr, path: &str) -> (u16, Value) {
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    let mut stream = loop {
+        match TcpStream::connect(addr) {
+            Ok(stream) => break stream,
+            Err(_) => {
+                assert!(Instant::now() < deadline, "HTTP APIが起動しませんでした: {}", addr);
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    };
+    stream
+        .set_read_timeout(Some(RECEIVE_TIMEOUT))
+        .expect("タイムアウトを設定できる");
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, addr
+    );
+    stream
+        .write_all(request.as_bytes())
+        .expect("リクエストを送信できる");
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .expect("レスポンスを受信できる");
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .expect("レスポンスにヘッダーがある");
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .expect("ステータスコードがある");
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+/// テスト用のRedis（SUBSCRIBEとPUBLISHだけを扱い、購読中の全接続に配る）
+pub struct FakeBroker {
+    /// 待ち受けアドレス
+    addr: SocketAddr,
+}
+
+impl FakeBroker {
+    /// 空きポートで待ち受けを始める（接続ごとにスレッドで処理する）
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("ブローカーのポートを開ける");
+        let addr = listener.local_addr().expect("ブローカーのアドレスを取得できる");
+        let subscribers: Arc<Mutex<Vec<TcpStream>>> = Arc::new(Mutex::new(Vec::new()));
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let subscribers = Arc::clone(&subscribers);
+                std::thread::spawn(move || serve_broker_connection(stream, subscribers));
+            }
+        });
+        Self { addr }
+    }
+
+    /// サーバーに渡すバックプレーンのURL
+    pub fn url(&self) -> String {
+        format!("redis://{}", self.addr)
+    }
+}
+
+/// ブローカーへの1接続分のコマンドを処理する
+fn serve_broker_connection(stream: TcpStream, subscribers: Arc<Mutex<Vec<TcpStream>>>) {
+    let mut writer = stream.try_clone().expect("接続を複製できる");
+    let mut reader = BufReader::new(stream);
+    while let Some(command) = read_command(&mut reader) {
+        match command.first().map(String::as_str) {
+            Some("SUBSCRIBE") => {
+                let channel = command.get(1).cloned().unwrap_or_default();
+                let reply = format!("*3\r\n$9\r\nsubscribe\r\n${}\r\n{}\r\n:1\r\n", channel.len(), channel);
+                let _ = writer.write_all(reply.as_bytes());
+                subscribers
+                    .lock()
+                    .unwrap()
+                    .push(writer.try_clone().expect("接続を複製できる"));
+            }
+            Some("PUBLISH") if command.len() == 3 => {
+                let (channel, payload) = (&command[1], &command[2]);
+                let message = format!(
+                    "*3\r\n$7\r\nmessage\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
+                    channel.len(),
+                    channel,
+                    payload.len(),
+                    payload
+                );
+                let mut subscribers = subscribers.lock().unwrap();
+                subscribers.retain_mut(|subscriber| subscriber.write_all(message.as_bytes()).is_ok());
+                let _ = writer.write_all(format!(":{}\r\n", subscribers.len()).as_bytes());
+            }
+            _ => {
+                let _ = writer.write_all(b"-ERR unknown command\r\n");
+            }
+        }
+    }
+}
+
+/// RESPの配列で送られてきたコマンドを読み込む（接続が閉じられた場合はNone）
+fn read_command(reader: &mut BufReader<TcpStream>) -> Option<Vec<String>> {
+    let count: usize = read_resp_line(reader)?.strip_prefix('*')?.parse().ok()?;
+    let mut args = Vec::with_capacity(count);
+    for _ in 0..count {
+        let length: usize = read_resp_line(reader)?.strip_prefix('$')?.parse().ok()?;
+        let mut data = vec![0; length + 2];
+        reader.read_exact(&mut data).ok()?;
+        data.truncate(length);
+        args.push(String::from_utf8(data).ok()?);
+    }
+    Some(args)
+}
+
+/// 1行読み込む（末尾の改行は除く）
+fn read_resp_line(reader: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end().to_string()),
+    }
+}
+
+/// テスト用のプッシュ通知の中継サーバー（POSTされた本文をJSONとして受け取り、204を返す）
+pub struct FakePushGateway {
+    /// 待ち受けアドレス
+    addr: SocketAddr,
+
+    /// 受け取った通知
+    received: Receiver<Value>,
+}
+
+impl FakePushGateway {
+    /// 空きポートで待ち受けを始める（接続ごとにスレッドで処理する）
+    pub fn start() -> Self {
+        Self::start_at("127.0.0.1:0".parse().expect("アドレスの形式が正しい"))
+    }
+
+    /// 指定したアドレスで待ち受けを始める（後から中継サーバーが動き出す場合の確認用）
+    ///
+    /// # 引数
+    /// * `addr` - 待ち受けるアドレス
+    pub fn start_at(addr: SocketAddr) -> Self {
+        let listener = TcpListener::bind(addr).expect("中継サーバーのポートを開ける");
+        let addr = listener.local_addr().expect("中継サーバーのアドレスを取得できる");
+        let (sender, received) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                std::thread::spawn(move || serve_push_request(stream, sender));
+            }
+        });
+        Self { addr, received }
+    }
+
+    /// サーバーに渡す中継サーバーのURL
+    pub fn url(&self) -> String {
+        format!("http://{}/push", self.addr)
+    }
+
+    /// 次の通知を待つ（タイムアウトした場合はパニック）
+    pub fn recv(&self) -> Value {
+        self.received
+            .recv_timeout(RECEIVE_TIMEOUT)
+            .expect("プッシュ通知が届く")
+    }
+
+    /// 指定した時間、通知が届かないことを確認する
+    pub fn expect_silence(&self, duration: Duration) {
+        if let Ok(notification) = self.received.recv_timeout(duration) {
+            panic!("予期しないプッシュ通知: {}", notification);
+        }
+    }
+}
+
+/// 中継サーバーへの1件のPOSTを処理する
+fn serve_push_request(stream: TcpStream, sender: Sender<Value>) {
+    let mut writer = stream.try_clone().expect("接続を複製できる");
+    let mut reader = BufReader::new(stream);
+    let mut content_length = 0;
+    while let Some(line) = read_resp_line(&mut reader) {
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    let _ = writer.write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n");
+    if let Ok(notification) = serde_json::from_slice(&body) {
+        let _ = sender.send(notification);
+    }
+}
+
+/// テスト用のWebSocketクライアント
+///
+/// サーバーがReliableで包んで送ったメッセージには、実際のクライアントと同じくAckを返し、
+/// 中のメッセージを取り出して返します（再送で重複したものは読み飛ばす）。
+pub struct TestClient {
+    stream: WebSocketStream<MaybeTlsStream<TokioTcpStream>>,
+
+    /// Reliableを包んだまま返し、Ackも返さない（サーバーの再送の確認用）
+    raw: bool,
+
+    /// 受け取ったReliableのメッセージID
+    seen_ids: HashSet<String>,
+}
+
+impl TestClient {
+    /// サーバーに接続する
+    ///
+    /// # 引数
+    /// * `server` - 接続先のサーバー
+    ///
+    /// # 戻り値
+    /// 接続済みのTestClient
+    pub async fn connect(server: &TestServer) -> Self {
+        let (stream, _) = connect_async(server.url())
+            .await
+            .expect("サーバーに接続できる");
+        Self {
+            stream,
+            raw: false,
+            seen_ids: HashSet::new(),
+        }
+    }
+
+    /// Reliableを包んだまま受け取り、Ackを返さないクライアントとして接続する
+    ///
+    /// # 引数
+    /// * `server` - 接続先のサーバー
+    pub async fn connect_raw(server: &TestServer) -> Self {
+        let mut client = Self::connect(server).await;
+        client.raw = true;
+        client
+    }
+
+    /// JSONメッセージを送信する
+    pub async fn send(&mut self, message: Value) {
+        self.send_text(message.to_string()).await;
+    }
+
+    /// テキストをそのまま送信する（不正なメッセージの送信に使う）
+    pub async fn send_text(&mut self, text: String) {
+        self.stream
+            .send(Message::Text(text))
+            .await
+            .expect("メッセージを送信できる");
+    }
+
+    /// 次のJSONメッセージを受信する（タイムアウトした場合はテスト失敗）
+    pub async fn recv(&mut self) -> Value {
+        self.try_recv(RECEIVE_TIMEOUT)
+            .await
+            .expect("時間内にメッセージを受信できる")
+    }
+
+    /// 指定した種類のメッセージが届くまで、他のメッセージを読み飛ばして待つ
+    ///
+    /// # 引数
+    /// * `message_type` - 待つメッセージの"type"の値
+    pub async fn recv_type(&mut self, message_type: &str) -> Value {
+        let deadline = Instant::now() + RECEIVE_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.try_recv(remaining).await {
+                Some(message) if message["type"] == message_type => return message,
+                Some(_) => continue,
+                None => panic!("{}を受信できませんでした", message_type),
+            }
+        }
+    }
+
+    /// 指定した時間内にメッセージが届かないことを確認する
+    pub async fn expect_silence(&mut self, duration: Duration) {
+        if let Some(message) = self.try_recv(duration).await {
+            panic!("メッセージを受信しないはずでした: {}", message);
+        }
+    }
+
+    /// 指定した時間だけメッセージを待つ（テキスト以外のフレームは読み飛ばす）
+    async fn try_recv(&mut self, timeout: Duration) -> Option<Value> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let frame = tokio::time::timeout(remaining, self.stream.next()).await.ok()??;
+            let Ok(Message::Text(text)) = frame else {
+                continue;
+            };
+            let message: Value = serde_json::from_str(&text).expect("サーバーはJSONを送信する");
+            if self.raw || message["type"] != "Reliable" {
+                return Some(message);
+            }
+            let message_id = message["message_id"].as_str().unwrap_or_default().to_string();
+            self.send(serde_json::json!({ "type": "Ack", "message_id": message_id }))
+                .await;
+            if self.seen_ids.insert(message_id) {
+                return Some(message["message"].clone());
+            }
+        }
+    }
+
+    /// 接続を閉じる
+    pub async fn close(mut self) {
+        let _ = self.stream.close(None).await;
+    }
+}
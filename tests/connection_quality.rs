@@ -0,0 +1,114 @@
+// =============================================================================
+// 接続品質の測定のテスト
+// =============================================================================
+// Pingの往復時間と応答のなかったPingの割合から接続品質が判定され、
+// 品質が下がるとカーソル位置の送信間隔が広がることを確認します。
+//
+// 実行方法：cargo test --test connection_quality
+// =============================================================================
+
+use ecs_wasm_solitaire::connection_quality::{
+    ConnectionMonitor, ConnectionQuality, PING_INTERVAL_MS, PONG_TIMEOUT_MS,
+};
+
+/// 接続済みの測定器で、指定した往復時間のPingをcount回やり取りする
+///
+/// # 戻り値
+/// 最後のやり取りが終わった時刻（ミリ秒）
+fn exchange(monitor: &mut ConnectionMonitor, start_ms: f64, rtt_ms: f64, count: usize) -> f64 {
+    let mut now = start_ms;
+    for _ in 0..count {
+        let ping_id = monitor.tick(now).expect("Pingを送る時刻");
+        assert_eq!(monitor.record_pong(ping_id, now + rtt_ms), Some(rtt_ms));
+        now += PING_INTERVAL_MS;
+    }
+    now
+}
+
+#[test]
+fn fast_replies_are_rated_good() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.set_connected(true);
+    assert_eq!(monitor.quality(), ConnectionQuality::Unknown, "測定前");
+
+    exchange(&mut monitor, 0.0, 40.0, 5);
+    let report = monitor.report();
+    assert_eq!(report.quality, ConnectionQuality::Good);
+    assert_eq!(report.rtt_ms, Some(40));
+    assert_eq!(report.packet_loss, 0.0);
+    assert_eq!(report.rates.cursor_interval_ms, 50);
+}
+
+#[test]
+fn pings_are_sent_once_per_interval() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.set_connected(true);
+
+    let first = monitor.tick(0.0).expect("最初は必ず送る");
+    assert_eq!(
+        monitor.tick(PING_INTERVAL_MS / 2.0),
+        None,
+        "間隔が空くまで送らない"
+    );
+    let second = monitor.tick(PING_INTERVAL_MS).expect("間隔が空いたら送る");
+    assert_ne!(first, second);
+}
+
+#[test]
+fn slow_replies_lower_the_quality_and_the_update_rate() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.set_connected(true);
+    let now = exchange(&mut monitor, 0.0, 40.0, 3);
+    let good_rates = monitor.report().rates;
+
+    // 往復時間が長くなると平滑化した値が徐々に上がる
+    exchange(&mut monitor, now, 900.0, 15);
+    let report = monitor.report();
+    assert_eq!(report.quality, ConnectionQuality::Poor);
+    assert!(report.rtt_ms.unwrap() > 400);
+    assert!(report.rates.cursor_interval_ms > good_rates.cursor_interval_ms);
+    assert!(report.rates.keyframe_interval_ms > good_rates.keyframe_interval_ms);
+}
+
+#[test]
+fn missed_pongs_count_as_packet_loss() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.set_connected(true);
+    let mut now = exchange(&mut monitor, 0.0, 40.0, 6);
+
+    // 応答のないPingを4回送り、時間切れまで待つ
+    for _ in 0..4 {
+        monitor.tick(now).expect("Pingを送る時刻");
+        now += PING_INTERVAL_MS;
+    }
+    let late_ping = monitor.tick(now).expect("Pingを送る時刻");
+    monitor.tick(now + PONG_TIMEOUT_MS);
+
+    // 時間切れ後に届いたPongは数えない
+    assert_eq!(
+        monitor.record_pong(late_ping, now + PONG_TIMEOUT_MS + 1.0),
+        None
+    );
+    assert_eq!(monitor.packet_loss(), 5.0 / 11.0);
+    assert_eq!(monitor.quality(), ConnectionQuality::Poor);
+}
+
+#[test]
+fn reconnecting_starts_a_fresh_measurement() {
+    let mut monitor = ConnectionMonitor::new();
+    assert_eq!(monitor.quality(), ConnectionQuality::Offline);
+    assert_eq!(monitor.tick(0.0), None, "未接続ではPingを送らない");
+
+    monitor.set_connected(true);
+    exchange(&mut monitor, 0.0, 900.0, 3);
+    assert_eq!(monitor.quality(), ConnectionQuality::Poor);
+
+    monitor.set_connected(false);
+    let report = monitor.report();
+    assert!(!report.connected);
+    assert_eq!(report.quality, ConnectionQuality::Offline);
+    assert_eq!(report.rtt_ms, None);
+
+    monitor.set_connected(true);
+    assert_eq!(monitor.quality(), ConnectionQuality::Unknown);
+}
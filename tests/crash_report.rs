@@ -0,0 +1,105 @@
+// =============================================================================
+// クラッシュレポートのテスト
+// =============================================================================
+// パニックが起きると直前に控えておいたゲームの状態（直近のイベント・移動の記録・
+// 盤面）がパニックの内容と一緒に保存され、保存された盤面から同じ局面を
+// 作り直せることを確認します。毎フレーム控える移動の記録は、増えた手だけを書き足しても
+// 毎回すべて集め直した場合と同じになることも確認します。
+//
+// 実行方法：cargo test --test crash_report
+// =============================================================================
+
+use ecs_wasm_solitaire::crash_report::{self, CrashContext};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::events::EventQueue;
+use ecs_wasm_solitaire::hint::HintEngine;
+use ecs_wasm_solitaire::scenario::Scenario;
+use ecs_wasm_solitaire::solitaire::{MoveLog, SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::storage;
+
+/// ヒントの手をcount手打つ
+fn play_hints(world: &mut World, count: usize) {
+    for _ in 0..count {
+        let hint = HintEngine::find_hint(world).expect("打てる手がある");
+        assert!(HintEngine::apply(world, &hint));
+    }
+}
+
+#[test]
+fn a_panic_saves_the_last_remembered_game_state() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 7);
+    play_hints(&mut world, 3);
+    let context = CrashContext::capture(&world, Some(game));
+    assert_eq!(context.game_type, Some(SolitaireType::Klondike));
+    assert_eq!(context.seed, 7);
+    assert_eq!(context.moves.len(), 3);
+
+    storage::use_temp_dir();
+    crash_report::install_panic_hook();
+    crash_report::remember("crash-test", &world, Some(game));
+    let result = std::panic::catch_unwind(|| panic!("テスト用のパニック"));
+    assert!(result.is_err());
+
+    let report = crash_report::load().expect("クラッシュレポートが保存される");
+    assert_eq!(report.message, "テスト用のパニック");
+    assert!(report
+        .location
+        .is_some_and(|location| location.contains("crash_report.rs")));
+    assert_eq!(report.sessions.get("crash-test"), Some(&context));
+
+    // 保存された盤面から同じ局面を作り直せる
+    let board = &report.sessions["crash-test"].board;
+    assert_eq!(
+        Scenario::from_compact(board),
+        Ok(Scenario::from_world(&world))
+    );
+
+    crash_report::clear().expect("クラッシュレポートを消せる");
+    assert_eq!(crash_report::load(), None);
+}
+
+#[test]
+fn a_world_without_a_game_still_has_a_context() {
+    let context = CrashContext::capture(&World::new(), None);
+    assert_eq!(context.game_type, None);
+    assert!(context.moves.is_empty() && context.recent_events.is_empty());
+    assert_eq!(
+        Scenario::from_compact(&context.board).map(|scenario| scenario.deck.len()),
+        Ok(0)
+    );
+}
+
+#[test]
+fn remembered_moves_follow_the_game_across_frames_and_new_games() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 7);
+    crash_report::remember("frames", &world, Some(game));
+    assert_eq!(crash_report::remembered("frames").map(|c| c.moves.len()), Some(0));
+
+    // フレームごとに増えた手が書き足され、毎回すべてを集め直した場合と同じになる
+    for _ in 0..3 {
+        play_hints(&mut world, 2);
+        crash_report::remember("frames", &world, Some(game));
+        assert_eq!(
+            crash_report::remembered("frames"),
+            Some(CrashContext::capture(&world, Some(game)))
+        );
+    }
+    assert_eq!(crash_report::remembered("frames").map(|c| c.moves.len()), Some(6));
+
+    // 同じシードで始め直すと、前のゲームの手は残らない
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 7);
+    play_hints(&mut world, 1);
+    crash_report::remember("frames", &world, Some(game));
+    let log = world.get_component::<MoveLog>(game).expect("移動履歴がある");
+    assert_eq!(
+        crash_report::remembered("frames").map(|c| c.moves),
+        Some(log.moves.clone())
+    );
+
+    crash_report::forget("frames");
+    assert_eq!(crash_report::remembered("frames"), None);
+}
@@ -0,0 +1,97 @@
+// =============================================================================
+// デッキの構成のテスト
+// =============================================================================
+// DeckSpecで指定した構成どおりにカードが作られること、構成の検証で
+// 不正なデッキ数・スート・ジョーカーが拒否されること、不変条件の確認で
+// カードの増減が検出されることを確認します。
+//
+// 実行方法：cargo test --test deck
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::solitaire::{
+    CardRank, CardSuit, DeckSpec, SolitaireCard, SolitaireGameState, SolitaireManager,
+    SolitaireType,
+};
+
+#[test]
+fn one_suit_spider_deals_eight_decks_of_spades() {
+    let mut world = World::new();
+    let deck = DeckSpec::spider(1).expect("1スートのスパイダーを作れる");
+    let game =
+        SolitaireManager::start_new_game_with_deck(&mut world, SolitaireType::Spider, deck, 42)
+            .expect("構成が正しい");
+
+    let cards: Vec<_> = world
+        .query::<SolitaireCard>()
+        .map(|(_, card)| card)
+        .collect();
+    assert_eq!(cards.len(), 104);
+    assert!(cards.iter().all(|card| card.suit == CardSuit::Spades));
+    assert_eq!(
+        cards
+            .iter()
+            .filter(|card| card.rank == CardRank::Queen)
+            .count(),
+        8
+    );
+    assert_eq!(
+        world
+            .get_component::<SolitaireGameState>(game)
+            .map(|state| state.deck.total_cards()),
+        Some(104)
+    );
+    assert_eq!(SolitaireManager::check_invariants(&world), Ok(()));
+}
+
+#[test]
+fn invalid_compositions_are_rejected() {
+    assert!(DeckSpec::spider(3).is_err());
+    assert_eq!(DeckSpec::spider(2).map(|deck| deck.total_cards()), Ok(104));
+
+    let mut deck = DeckSpec::standard();
+    deck.jokers = 2;
+    assert!(deck.validate().is_err(), "ジョーカーを使うゲームはまだない");
+
+    let deck = DeckSpec {
+        decks: 1,
+        suits: vec![CardSuit::Hearts, CardSuit::Hearts],
+        jokers: 0,
+    };
+    assert!(deck.validate().is_err(), "スートの重複");
+
+    let deck = DeckSpec {
+        decks: 0,
+        ..DeckSpec::standard()
+    };
+    let mut world = World::new();
+    assert!(SolitaireManager::start_new_game_with_deck(
+        &mut world,
+        SolitaireType::Klondike,
+        deck,
+        1
+    )
+    .is_err());
+    assert_eq!(world.query::<SolitaireCard>().count(), 0, "配らない");
+}
+
+#[test]
+fn invariant_checker_detects_extra_and_missing_cards() {
+    let mut world = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 7);
+    assert_eq!(SolitaireManager::check_invariants(&world), Ok(()));
+
+    let extra = world.create_entity();
+    world.add_component(extra, SolitaireCard::new(CardSuit::Hearts, CardRank::Ace));
+    let error = SolitaireManager::check_invariants(&world).unwrap_err();
+    assert!(error.contains("2枚"), "{}", error);
+
+    world.remove_entity(extra);
+    let (ace, _) = world
+        .query::<SolitaireCard>()
+        .find(|(_, card)| card.suit == CardSuit::Spades && card.rank == CardRank::Ace)
+        .expect("♠Aがある");
+    world.remove_entity(ace);
+    let error = SolitaireManager::check_invariants(&world).unwrap_err();
+    assert!(error.contains("0枚"), "{}", error);
+}
@@ -0,0 +1,70 @@
+// =============================================================================
+// 大量のエンティティを扱うECSワールドのテスト
+// =============================================================================
+// 長時間動くサーバーで多数のゲームを作っては消すことを想定し、100万個の
+// エンティティを生成・削除・再生成しても、IDが再利用されて増え続けないこと、
+// 生存確認とコンポーネントの対応が崩れないことを確認します。
+//
+// 実行方法：cargo test --test ecs_stress
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{Component, Entity, World};
+
+/// 一度に生成するエンティティ数
+const ENTITY_COUNT: u32 = 1_000_000;
+
+/// テスト用の値を持つコンポーネント
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Tag(u32);
+
+impl Component for Tag {}
+
+#[test]
+fn a_million_entities_keep_their_components() {
+    let mut world = World::new();
+    let entities = world.spawn_batch((0..ENTITY_COUNT).map(Tag));
+    assert_eq!(world.entity_count(), ENTITY_COUNT as usize);
+
+    // 奇数番目を削除すると、残りのエンティティとコンポーネントの対応は変わらない
+    for &entity in entities.iter().skip(1).step_by(2) {
+        assert!(world.remove_entity(entity));
+    }
+    assert_eq!(world.entity_count(), ENTITY_COUNT as usize / 2);
+    assert_eq!(world.query::<Tag>().count(), ENTITY_COUNT as usize / 2);
+    for (index, &entity) in entities.iter().enumerate() {
+        let alive = index % 2 == 0;
+        assert_eq!(world.contains_entity(entity), alive);
+        assert_eq!(
+            world.get_component::<Tag>(entity).copied(),
+            alive.then_some(Tag(index as u32))
+        );
+    }
+    assert!(world
+        .entities()
+        .iter()
+        .all(|&entity| world.contains_entity(entity)));
+
+    // 削除済みのエンティティは2回目の削除で失敗する
+    assert!(!world.remove_entity(entities[1]));
+}
+
+#[test]
+fn entity_ids_are_recycled_across_many_generations() {
+    let mut world = World::new();
+    for generation in 0..10 {
+        let entities = world.spawn_batch((0..ENTITY_COUNT / 10).map(|i| Tag(i + generation)));
+        assert!(entities
+            .iter()
+            .all(|entity| entity.id() >= 1 && entity.id() <= ENTITY_COUNT / 10));
+        for entity in entities {
+            assert!(world.remove_entity(entity));
+        }
+        assert_eq!(world.entity_count(), 0);
+        assert_eq!(world.query::<Tag>().count(), 0);
+    }
+    assert_eq!(world.free_entity_id_count(), ENTITY_COUNT as usize / 10);
+
+    // 存在したことのないIDは存在しない扱い
+    assert!(!world.contains_entity(Entity::new(u32::MAX)));
+    assert!(!world.remove_entity(Entity::new(ENTITY_COUNT + 1)));
+}
@@ -0,0 +1,74 @@
+// =============================================================================
+// フレームごとの処理時間の記録のテスト
+// =============================================================================
+// システム名から処理時間の種類が決まり、フレームごとの値が決まった並びの配列になること、
+// 古いフレームが捨てられ時間予算を超えた割合が分かること、GameRuntimeのupdate()が
+// 1フレームごとに記録することを確認します。
+//
+// 実行方法：cargo test --test frame_timings
+// =============================================================================
+
+use ecs_wasm_solitaire::frame_timings::{
+    FrameTimingHistory, TimingCategory, FIELDS_PER_FRAME, FRAME_BUDGET_MS, FRAME_TIMING_HISTORY,
+};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+
+#[test]
+fn timings_are_grouped_by_category_and_keep_recent_frames() {
+    assert_eq!(
+        TimingCategory::of_system("CardAnimationSystem"),
+        TimingCategory::Animation
+    );
+    assert_eq!(
+        TimingCategory::of_system("MessageProcessingSystem"),
+        TimingCategory::Network
+    );
+    assert_eq!(
+        TimingCategory::of_system("CardMovementSystem"),
+        TimingCategory::Logic
+    );
+
+    // フレームを始める前の記録は新しいフレームに入る
+    let mut history = FrameTimingHistory::new();
+    history.add(TimingCategory::Logic, 2.0);
+    history.add(TimingCategory::Logic, 1.0);
+    history.add(TimingCategory::Animation, 4.0);
+    history.add(TimingCategory::Serialization, 0.5);
+    history.add(TimingCategory::Network, 0.25);
+    assert_eq!(history.to_array(), [3.0, 4.0, 0.5, 0.25]);
+
+    history.begin_frame();
+    history.add(TimingCategory::Animation, 20.0);
+    assert_eq!(history.to_array().len(), 2 * FIELDS_PER_FRAME);
+    assert_eq!(history.over_budget_ratio(FRAME_BUDGET_MS), 0.5);
+
+    // 残す数を超えると古いフレームから捨てる
+    for _ in 0..FRAME_TIMING_HISTORY {
+        history.begin_frame();
+    }
+    assert_eq!(history.frames().count(), FRAME_TIMING_HISTORY);
+    assert!(history.to_array().iter().all(|ms| *ms == 0.0));
+    assert_eq!(history.over_budget_ratio(FRAME_BUDGET_MS), 0.0);
+}
+
+#[test]
+fn runtime_records_one_entry_per_frame() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+    for _ in 0..3 {
+        rt.update(1.0 / 60.0);
+    }
+    assert_eq!(rt.frame_timings.frames().count(), 3);
+    let total: f64 = rt
+        .frame_timings
+        .frames()
+        .map(|frame| frame.total_ms())
+        .sum();
+    assert!(total > 0.0);
+
+    // タブが非表示の間はフレームを数えない
+    rt.set_hidden(true);
+    rt.update(1.0 / 60.0);
+    assert_eq!(rt.frame_timings.frames().count(), 3);
+}
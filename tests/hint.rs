@@ -0,0 +1,192 @@
+// =============================================================================
+// ヒントの説明とチュートリアル用の手の評価のテスト
+// =============================================================================
+// BoardBuilderで用意した局面で、ヒントに手を勧める理由が付くこと、
+// 全合法手の一覧に効果の薄い手も含めて評価順に並ぶこと、しばらく操作がないと
+// 次の一手を1回だけ知らせ、移動するとまた知らせるようになること、
+// 自動プレイがクリアか手詰まりで止まることを確認します。
+//
+// 実行方法：cargo test --test hint
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::hint::{Hint, HintEngine, HintKind};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::selection::Highlighted;
+use ecs_wasm_solitaire::solitaire::{
+    CardLocation, CardRank, CardSuit, SolitaireGameState, SolitaireProgressSystem,
+};
+
+/// 盤面を組み立てたワールドを作成
+fn world_with(board: BoardBuilder) -> World {
+    let mut world = World::new();
+    board.build(&mut world).expect("シナリオから盤面を作れる");
+    world
+}
+
+/// 指定したカードを動かす手を探す
+fn move_of(hints: &[Hint], suit: CardSuit, rank: CardRank) -> Vec<&Hint> {
+    hints
+        .iter()
+        .filter(|hint| {
+            hint.card
+                .is_some_and(|card| card.suit == suit && card.rank == rank)
+        })
+        .collect()
+}
+
+#[test]
+fn revealing_a_face_down_card_is_explained() {
+    let world = world_with(
+        BoardBuilder::new()
+            .tableau(0, 2, &["3C", "4H", "9D"])
+            .tableau(1, 0, &["10S"]),
+    );
+
+    let hint = HintEngine::find_hint(&world).expect("9♦を10♠へ動かせる");
+    assert_eq!(hint.kind, HintKind::Move);
+    assert!(hint.reason.contains("裏向き"), "{}", hint.reason);
+    assert!(hint.reason.contains("2枚"), "{}", hint.reason);
+}
+
+#[test]
+fn emptying_a_column_names_the_waiting_king() {
+    let world = world_with(
+        BoardBuilder::new()
+            .tableau(0, 0, &["AS"])
+            .tableau(1, 0, &["5D"])
+            .waste(&["KC"]),
+    );
+
+    let hint = HintEngine::find_hint(&world).expect("♠Aを組札へ置ける");
+    assert_eq!(
+        hint.to.map(|to| to.location),
+        Some(CardLocation::Foundation)
+    );
+    assert!(hint.reason.contains("♠A"), "{}", hint.reason);
+    assert!(hint.reason.contains("♣K"), "{}", hint.reason);
+    assert!(hint.reason.contains("タブロー1"), "{}", hint.reason);
+}
+
+#[test]
+fn ranked_moves_include_weak_moves_in_order() {
+    let world = world_with(
+        BoardBuilder::new()
+            .tableau(0, 0, &["KH"])
+            .tableau(1, 1, &["2C", "8S", "7H"])
+            .tableau(2, 0, &["9D"])
+            .tableau(3, 0, &["8C"])
+            .foundation(0, &["AC"])
+            .deck(&["4D"]),
+    );
+
+    let ranked = HintEngine::ranked_moves(&world);
+    assert!(ranked
+        .windows(2)
+        .all(|pair| pair[0].priority >= pair[1].priority));
+
+    // 最善手はヒントと同じ評価
+    let best = HintEngine::find_hint(&world).expect("打てる手がある");
+    assert_eq!(ranked[0].priority, best.priority);
+    assert!(ranked.iter().all(|hint| !hint.reason.is_empty()));
+
+    // 空き列から空き列へのKは意味のない手として最低評価
+    let king = move_of(&ranked, CardSuit::Hearts, CardRank::King);
+    assert!(!king.is_empty());
+    assert!(king.iter().all(|hint| hint.priority == 0));
+
+    // 7♥だけを8♣へ動かしても裏向きのカードは出てこない
+    let seven = move_of(&ranked, CardSuit::Hearts, CardRank::Seven);
+    assert_eq!(seven.len(), 1);
+    assert_eq!(seven[0].card_count, 1);
+    assert!(seven[0].priority < best.priority);
+    assert!(
+        seven[0].reason.contains("組み替える"),
+        "{}",
+        seven[0].reason
+    );
+
+    // 有望な手だけを返すall_movesには含まれない
+    assert!(move_of(
+        &HintEngine::all_moves(&world),
+        CardSuit::Hearts,
+        CardRank::Seven
+    )
+    .is_empty());
+
+    assert_eq!(ranked.last().map(|hint| hint.kind), Some(HintKind::Draw));
+}
+
+#[test]
+fn idle_players_are_nudged_once_per_pause() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    let game = BoardBuilder::new()
+        .tableau(0, 2, &["3C", "4H", "9D"])
+        .tableau(1, 0, &["10S"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    let idle_for = |world: &mut World, seconds: f64| -> Vec<GameEvent> {
+        SolitaireProgressSystem.update(world, seconds);
+        world.get_resource_mut::<EventQueue>().unwrap().drain()
+    };
+
+    assert!(idle_for(&mut world, 29.0).is_empty());
+    let nudges = idle_for(&mut world, 1.0);
+    let [GameEvent::HintNudge {
+        card_id: Some(card_id),
+        idle_seconds: 30,
+        ..
+    }] = nudges.as_slice()
+    else {
+        panic!("30秒操作がないと次の一手を知らせる: {:?}", nudges);
+    };
+    let (card, highlighted) = world
+        .query::<Highlighted>()
+        .next()
+        .expect("知らせたカードが点滅する");
+    assert_eq!(card.id(), *card_id);
+    assert!(highlighted.pulse);
+    assert!(
+        idle_for(&mut world, 60.0).is_empty(),
+        "同じ休止中には1回だけ"
+    );
+
+    // 移動するとまた知らせるようになる
+    world
+        .get_component_mut::<SolitaireGameState>(game)
+        .unwrap()
+        .record_move(0);
+    assert!(idle_for(&mut world, 10.0).is_empty());
+    assert_eq!(idle_for(&mut world, 20.0).len(), 1);
+}
+
+#[test]
+fn auto_play_runs_until_cleared_or_stuck() {
+    // 各スートのQまで組札に載っていれば、残りのKを4手で片付けてクリアする
+    let ranks = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q"];
+    let mut board = BoardBuilder::new();
+    for (index, suit) in ["S", "H", "D", "C"].into_iter().enumerate() {
+        let pile: Vec<String> = ranks
+            .iter()
+            .map(|rank| format!("{}{}", rank, suit))
+            .collect();
+        let pile: Vec<&str> = pile.iter().map(String::as_str).collect();
+        board = board
+            .foundation(index, &pile)
+            .tableau(index, 0, &[&format!("K{}", suit)]);
+    }
+    let mut world = world_with(board);
+    assert_eq!(HintEngine::play_until_stuck(&mut world, 100), 4);
+    assert!(HintEngine::all_moves(&world).is_empty());
+
+    // 引くしか打てないデッキは一巡したところで止める
+    let mut world = world_with(
+        BoardBuilder::new()
+            .tableau(0, 0, &["2S"])
+            .deck(&["9H", "5D"]),
+    );
+    let played = HintEngine::play_until_stuck(&mut world, 100);
+    assert!(played > 0 && played < 100, "{}", played);
+}
@@ -0,0 +1,79 @@
+// =============================================================================
+// 言語に合わせた数値・時間の整形のテスト
+// =============================================================================
+// 経過時間が"mm:ss"（1時間以上は"h:mm:ss"）になること、スコアが言語ごとの区切り文字で
+// 3桁ごとに区切られること、設定のformat_numbersが有効な場合だけ状態の出力に
+// 整形済みの値が加わることを確認します。
+//
+// 実行方法：cargo test --test i18n
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::ClientState;
+use ecs_wasm_solitaire::i18n::{self, format_elapsed, format_number, FormattedStats};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::settings::PreferenceOverrides;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+
+#[test]
+fn elapsed_time_and_scores_are_formatted_for_the_locale() {
+    assert_eq!(format_elapsed(0), "00:00");
+    assert_eq!(format_elapsed(95), "01:35");
+    assert_eq!(format_elapsed(3599), "59:59");
+    assert_eq!(format_elapsed(3725), "1:02:05");
+
+    assert_eq!(format_number(0, "ja"), "0");
+    assert_eq!(format_number(999, "en"), "999");
+    assert_eq!(format_number(1_234_567, "en-US"), "1,234,567");
+    assert_eq!(format_number(1_234_567, "de"), "1.234.567");
+    assert_eq!(format_number(1_234_567, "fr-FR"), "1\u{a0}234\u{a0}567");
+    assert_eq!(format_number(1_234_567, "de-CH"), "1\u{2019}234\u{2019}567");
+    assert_eq!(
+        format_number(12_345, "xx"),
+        "12,345",
+        "知らない言語は,で区切る"
+    );
+
+    assert_eq!(
+        FormattedStats::new(4_250, 754, "es"),
+        FormattedStats {
+            elapsed: "12:34".to_string(),
+            score: "4.250".to_string(),
+            locale: "es".to_string(),
+        }
+    );
+}
+
+#[test]
+fn formatted_fields_are_added_only_when_enabled() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+
+    // 標準では整数だけを返し、JSONにもformattedを含めない
+    let state = ClientState::from_world(&rt.world, rt.game_entity);
+    assert_eq!(state.score.formatted, None);
+    let json = serde_json::to_value(&state).expect("状態をJSONにできる");
+    assert!(json["score"].get("formatted").is_none());
+
+    // 有効にすると、このセッションの言語で整形した値が加わる
+    rt.set_preference_overrides(PreferenceOverrides {
+        format_numbers: Some(true),
+        locale: Some("de".to_string()),
+        ..PreferenceOverrides::default()
+    })
+    .expect("整形を有効にできる");
+    let state = ClientState::from_world(&rt.world, rt.game_entity);
+    let formatted = state.score.formatted.expect("整形した値がある");
+    assert_eq!(formatted.locale, "de");
+    assert_eq!(
+        formatted.elapsed,
+        format_elapsed(state.score.elapsed_seconds)
+    );
+    assert_eq!(
+        formatted.score,
+        format_number(u64::from(state.score.score), "de")
+    );
+    assert_eq!(
+        i18n::formatted_stats(&rt.world, 12_000, 61).map(|stats| stats.score),
+        Some("12.000".to_string())
+    );
+}
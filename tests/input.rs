@@ -0,0 +1,334 @@
+// =============================================================================
+// 入力イベントキューのテスト
+// =============================================================================
+// ポインターイベントがInputSystemの実行までワールドを変更しないこと、
+// 受け付けた順に処理されてドラッグ＆ドロップがカードの移動になること、
+// 同じイベント列を流せば同じ盤面になること、拡大・移動した画面の座標が盤面の座標に直されること、
+// 2本指のピンチ操作がカードではなく表示領域を動かすこと、カードのない山を押す・
+// その上で離す操作がその山へのドロップになること、有限でない座標を受け付けず、
+// 大きすぎる座標を範囲に収めること、盤面を置き換えると古い盤面の入力とドラッグが
+// 捨てられることを確認します。
+//
+// 実行方法：cargo test --test input
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::input::{
+    card_at, pile_at, push_pointer, InputEvent, InputState, InputSystem, PointerEvent,
+    PointerKind, MAX_POINTER_COORDINATE,
+};
+use ecs_wasm_solitaire::puzzle::builtin_puzzles;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::scenario::{BoardBuilder, Scenario};
+use ecs_wasm_solitaire::selection::{self, Dropped, SelectionSystem};
+use ecs_wasm_solitaire::solitaire::{
+    CardLocation, CardMovementSystem, CardRank, CardSuit, SolitaireCard,
+};
+use ecs_wasm_solitaire::viewport::Viewport;
+
+/// 9♦を10♠へ動かせる盤面を作成
+fn board() -> World {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 0, &["9D"])
+        .tableau(1, 0, &["10S"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    world
+}
+
+/// ポインターイベントを作成
+fn pointer(kind: PointerKind, x: f32, y: f32) -> PointerEvent {
+    PointerEvent {
+        kind,
+        x,
+        y,
+        pointer_id: 0,
+    }
+}
+
+/// 9♦のエンティティを探す
+fn nine(world: &World) -> Entity {
+    world
+        .query::<SolitaireCard>()
+        .find(|(_, card)| card.suit == CardSuit::Diamonds && card.rank == CardRank::Nine)
+        .map(|(entity, _)| entity)
+        .expect("盤面に9♦がある")
+}
+
+/// 9♦をタブロー2列目（10♠）の山の位置までドラッグして離すイベント列
+fn drag_nine_onto_ten() -> Vec<PointerEvent> {
+    vec![
+        pointer(PointerKind::Down, 30.0, 160.0),
+        pointer(PointerKind::Move, 80.0, 170.0),
+        pointer(PointerKind::Move, 130.0, 165.0),
+        pointer(PointerKind::Up, 130.0, 165.0),
+    ]
+}
+
+/// 入力から移動までのシステムを1フレーム分実行
+fn tick(world: &mut World) {
+    InputSystem.update(world, 0.016);
+    SelectionSystem.update(world, 0.016);
+    CardMovementSystem.update(world, 0.016);
+}
+
+#[test]
+fn events_wait_in_the_queue_until_the_input_system_runs() {
+    let mut world = board();
+    let card = nine(&world);
+    for event in drag_nine_onto_ten() {
+        push_pointer(&mut world, event);
+    }
+
+    assert_eq!(world.query::<InputEvent>().count(), 4);
+    assert_eq!(selection::selected_card(&world), None);
+
+    InputSystem.update(&mut world, 0.016);
+    assert_eq!(
+        world.query::<InputEvent>().count(),
+        0,
+        "処理済みのイベントは消える"
+    );
+    assert!(world.has_component::<Dropped>(card));
+    let card_ref = world
+        .get_component::<SolitaireCard>(card)
+        .expect("カードがある");
+    assert_eq!((card_ref.display_x, card_ref.display_y), (120.0, 155.0));
+}
+
+#[test]
+fn drag_and_drop_moves_the_card_and_replays_identically() {
+    let mut first = board();
+    let mut second = board();
+    for world in [&mut first, &mut second] {
+        for event in drag_nine_onto_ten() {
+            push_pointer(world, event);
+        }
+        tick(world);
+    }
+
+    let card = first
+        .get_component::<SolitaireCard>(nine(&first))
+        .expect("カードがある");
+    assert_eq!(card.location_type, CardLocation::Tableau);
+    assert_eq!(card.position_in_location, 1);
+    assert_eq!(selection::selected_card(&first), None);
+    assert_eq!(Scenario::from_world(&first), Scenario::from_world(&second));
+}
+
+#[test]
+fn taps_select_and_cancel_restores_the_card() {
+    let mut world = board();
+    let card = nine(&world);
+    assert_eq!(card_at(&world, 30.0, 160.0), Some(card));
+
+    // 動かさずに離すとタップとして選択だけが残る
+    push_pointer(&mut world, pointer(PointerKind::Down, 30.0, 160.0));
+    push_pointer(&mut world, pointer(PointerKind::Up, 31.0, 161.0));
+    tick(&mut world);
+    assert_eq!(selection::selected_card(&world), Some(card));
+    assert!(!world.has_component::<Dropped>(card));
+
+    // 途中で中断されたドラッグは元の位置に戻る
+    push_pointer(&mut world, pointer(PointerKind::Down, 30.0, 160.0));
+    push_pointer(&mut world, pointer(PointerKind::Move, 300.0, 400.0));
+    push_pointer(&mut world, pointer(PointerKind::Cancel, 300.0, 400.0));
+    tick(&mut world);
+    let card_ref = world
+        .get_component::<SolitaireCard>(card)
+        .expect("カードがある");
+    assert_eq!((card_ref.display_x, card_ref.display_y), (20.0, 150.0));
+    assert_eq!(selection::selected_card(&world), None);
+
+    // 何もない場所を押すと選択が外れる
+    selection::select(&mut world, card);
+    push_pointer(&mut world, pointer(PointerKind::Down, 700.0, 500.0));
+    tick(&mut world);
+    assert_eq!(selection::selected_card(&world), None);
+}
+
+#[test]
+fn screen_coordinates_are_converted_through_the_viewport() {
+    let mut world = board();
+    let viewport = Viewport::new(2.0, -20.0, -100.0).expect("範囲内の拡大率");
+    world.insert_resource(viewport);
+
+    // 盤面の座標で流すのと同じ操作を、拡大した画面の座標で流す
+    for event in drag_nine_onto_ten() {
+        let (x, y) = viewport.to_screen(event.x, event.y);
+        push_pointer(&mut world, PointerEvent { x, y, ..event });
+    }
+    tick(&mut world);
+
+    let card = world
+        .get_component::<SolitaireCard>(nine(&world))
+        .expect("カードがある");
+    assert_eq!(card.location_type, CardLocation::Tableau);
+    assert_eq!(card.position_in_location, 1);
+}
+
+#[test]
+fn two_finger_pinch_zooms_instead_of_dragging() {
+    let mut world = board();
+    let card = nine(&world);
+    let finger = |kind, x, y, pointer_id| PointerEvent {
+        kind,
+        x,
+        y,
+        pointer_id,
+    };
+
+    // 1本目の指で9♦を掴んで動かした後、2本目の指が触れる
+    push_pointer(&mut world, finger(PointerKind::Down, 30.0, 160.0, 1));
+    push_pointer(&mut world, finger(PointerKind::Move, 60.0, 160.0, 1));
+    push_pointer(&mut world, finger(PointerKind::Down, 130.0, 160.0, 2));
+    // 2本目の指を離していき、間隔を70から170に広げる
+    push_pointer(&mut world, finger(PointerKind::Move, 230.0, 160.0, 2));
+    push_pointer(&mut world, finger(PointerKind::Up, 230.0, 160.0, 2));
+    push_pointer(&mut world, finger(PointerKind::Move, 300.0, 400.0, 1));
+    push_pointer(&mut world, finger(PointerKind::Up, 300.0, 400.0, 1));
+    tick(&mut world);
+
+    let card_ref = world
+        .get_component::<SolitaireCard>(card)
+        .expect("カードがある");
+    assert_eq!(
+        (card_ref.display_x, card_ref.display_y),
+        (20.0, 150.0),
+        "ピンチ操作を始めるとドラッグは元に戻る"
+    );
+    assert!(!world.has_component::<Dropped>(card));
+
+    // 指の中点（95→145）の下にあった盤面の点が、拡大後も中点の下にある
+    let viewport = *world.get_resource::<Viewport>().expect("表示領域がある");
+    assert!((viewport.scale - 170.0 / 70.0).abs() < 1e-4);
+    let (x, y) = viewport.to_board(145.0, 160.0);
+    assert!((x - 95.0).abs() < 1e-3 && (y - 160.0).abs() < 1e-3);
+}
+
+#[test]
+fn pressing_or_releasing_over_an_empty_pile_drops_onto_it() {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 0, &["AH"])
+        .tableau(1, 1, &["2C", "KS"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    let find = |world: &World, suit, rank| {
+        world
+            .query::<SolitaireCard>()
+            .find(|(_, card)| card.suit == suit && card.rank == rank)
+            .map(|(entity, _)| entity)
+            .expect("盤面にカードがある")
+    };
+    let ace = find(&world, CardSuit::Hearts, CardRank::Ace);
+    let king = find(&world, CardSuit::Spades, CardRank::King);
+
+    // 空の組札（1つ目は400, 20）と空いた列（3列目は220, 150）は置き場所で押せる
+    assert!(pile_at(&world, 410.0, 30.0).is_some());
+    assert!(pile_at(&world, 230.0, 160.0).is_some());
+    assert_eq!(pile_at(&world, 30.0, 160.0), None, "カードのある山は対象外");
+
+    // ♥Aを押して選び、空の組札を押すとそこへ移動する
+    push_pointer(&mut world, pointer(PointerKind::Down, 30.0, 160.0));
+    push_pointer(&mut world, pointer(PointerKind::Up, 30.0, 160.0));
+    push_pointer(&mut world, pointer(PointerKind::Down, 410.0, 30.0));
+    tick(&mut world);
+    let card = world
+        .get_component::<SolitaireCard>(ace)
+        .expect("カードがある");
+    assert_eq!(card.location_type, CardLocation::Foundation);
+
+    // ♠Kの下端を掴み、カードの左上から離れていても空いた列の上で離せばその列へ置かれる
+    push_pointer(&mut world, pointer(PointerKind::Down, 190.0, 280.0));
+    push_pointer(&mut world, pointer(PointerKind::Move, 210.0, 270.0));
+    push_pointer(&mut world, pointer(PointerKind::Up, 225.0, 265.0));
+    tick(&mut world);
+    let card = world
+        .get_component::<SolitaireCard>(king)
+        .expect("カードがある");
+    assert_eq!(
+        (card.location_type, card.position_in_location),
+        (CardLocation::Tableau, 2)
+    );
+}
+
+#[test]
+fn non_finite_coordinates_are_rejected_and_huge_ones_clamped() {
+    assert!(pointer(PointerKind::Move, f32::NAN, 0.0)
+        .sanitized()
+        .is_err());
+    assert!(pointer(PointerKind::Move, 0.0, f32::INFINITY)
+        .sanitized()
+        .is_err());
+    let clamped = pointer(PointerKind::Move, 1e9, -1e9)
+        .sanitized()
+        .expect("有限の座標は受け付ける");
+    assert_eq!(
+        (clamped.x, clamped.y),
+        (MAX_POINTER_COORDINATE, -MAX_POINTER_COORDINATE)
+    );
+
+    // 範囲に収めた座標でドラッグしても、カードの表示位置は有限のまま
+    let mut world = board();
+    let card = nine(&world);
+    push_pointer(&mut world, pointer(PointerKind::Down, 30.0, 160.0));
+    push_pointer(&mut world, clamped);
+    InputSystem.update(&mut world, 0.016);
+    let card_ref = world
+        .get_component::<SolitaireCard>(card)
+        .expect("カードがある");
+    assert!(card_ref.display_x.is_finite() && card_ref.display_y.is_finite());
+    assert!(card_ref.display_x > MAX_POINTER_COORDINATE / 2.0);
+}
+
+#[test]
+fn a_new_board_drops_queued_events_and_the_drag_of_the_old_board() {
+    let puzzles = builtin_puzzles();
+    let mut rt = GameRuntime::new();
+    rt.start_puzzle(&puzzles[0]).expect("組み込みのパズルを開始できる");
+    rt.skip_animations();
+
+    // 古い盤面の動かせるカードを押してドラッグを始める
+    let (x, y) = rt
+        .world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| card.is_movable)
+        .map(|(entity, card)| (entity, card.display_x + 5.0, card.display_y + 5.0))
+        .find(|&(entity, x, y)| card_at(&rt.world, x, y) == Some(entity))
+        .map(|(_, x, y)| (x, y))
+        .expect("押せるカードがある");
+    rt.push_pointer(pointer(PointerKind::Down, x, y))
+        .expect("座標は有限");
+    rt.update(0.016);
+    let dragging = |rt: &GameRuntime| {
+        rt.world
+            .get_resource::<InputState>()
+            .and_then(|state| state.drag)
+    };
+    assert!(dragging(&rt).is_some());
+    rt.push_pointer(pointer(PointerKind::Move, x + 200.0, y + 200.0))
+        .expect("座標は有限");
+
+    // 盤面を置き換えると、古いカードのIDが使い回されても新しいカードは動かない
+    rt.start_puzzle(&puzzles[0]).expect("組み込みのパズルを開始できる");
+    rt.skip_animations();
+    assert_eq!(rt.world.query::<InputEvent>().count(), 0);
+    assert_eq!(dragging(&rt), None);
+
+    let positions = |rt: &GameRuntime| {
+        let mut positions: Vec<(Entity, f32, f32)> = rt
+            .world
+            .query::<SolitaireCard>()
+            .map(|(entity, card)| (entity, card.display_x, card.display_y))
+            .collect();
+        positions.sort_by_key(|(entity, _, _)| entity.0);
+        positions
+    };
+    let before = positions(&rt);
+    rt.push_pointer(pointer(PointerKind::Move, x + 300.0, y + 300.0))
+        .expect("座標は有限");
+    rt.update(0.016);
+    assert_eq!(positions(&rt), before);
+}
@@ -0,0 +1,107 @@
+// =============================================================================
+// 盤面のレイアウトのテスト
+// =============================================================================
+// get_layout()で返すレイアウトのカードの座標が、実際にカードを置いた座標と一致すること、
+// カードのない山も基準座標と0枚として返り、ドロップ先の山（CardStack）と同じ位置にあることを
+// 確認します。
+//
+// 実行方法：cargo test --test layout
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::Entity;
+use ecs_wasm_solitaire::layout::{BoardLayout, StackLayout, CARD_HEIGHT, CARD_WIDTH};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::solitaire::{
+    CardLocation, CardStack, SolitaireCard, SolitaireManager, SolitaireType,
+};
+
+/// IDで山のレイアウトを探す
+fn stack<'a>(layout: &'a BoardLayout, id: &str) -> &'a StackLayout {
+    layout
+        .stacks
+        .iter()
+        .find(|stack| stack.id == id)
+        .unwrap_or_else(|| panic!("{}のレイアウトがある", id))
+}
+
+#[test]
+fn card_positions_match_where_the_cards_were_dealt() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+    let layout = rt.layout().expect("クロンダイクのレイアウトを取得できる");
+
+    assert_eq!(
+        (layout.card_width, layout.card_height),
+        (CARD_WIDTH, CARD_HEIGHT)
+    );
+    assert_eq!(layout.stacks.len(), 2 + 4 + 7);
+    assert_eq!(stack(&layout, "deck").card_count, 24);
+    for column in 0..7 {
+        let tableau = stack(&layout, &format!("tableau-{}", column));
+        assert_eq!(tableau.card_count, column + 1);
+        assert_eq!(tableau.cards.len(), tableau.card_count as usize);
+    }
+
+    // 各カードの座標は配り終えたときの位置と同じ（配るアニメーション中でも移動先を返す）
+    for stack in &layout.stacks {
+        for (row, position) in stack.cards.iter().enumerate() {
+            let card = rt
+                .world
+                .get_component::<SolitaireCard>(Entity(position.card_id))
+                .expect("カードがある");
+            assert_eq!(card.location_type, stack.stack_type);
+            let resting = if card.is_animating {
+                (card.target_x, card.target_y)
+            } else {
+                (card.display_x, card.display_y)
+            };
+            assert_eq!(resting, (position.x, position.y));
+            assert_eq!(position.y, stack.anchor_y + row as f32 * stack.spacing);
+        }
+    }
+}
+
+#[test]
+fn empty_piles_keep_their_anchor_and_match_the_drop_targets() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+    let layout = rt.layout().expect("クロンダイクのレイアウトを取得できる");
+
+    // 配った直後のウェイストと組札は空でも、枠を描く位置を返す
+    let waste = stack(&layout, "waste");
+    assert_eq!(waste.card_count, 0);
+    assert_eq!((waste.anchor_x, waste.anchor_y), (140.0, 20.0));
+    for index in 0..4 {
+        let foundation = stack(&layout, &format!("foundation-{}", index));
+        assert_eq!(foundation.card_count, 0);
+        assert!(foundation.cards.is_empty());
+    }
+
+    // ドロップ先の山は、レイアウトと同じ位置・間隔にある
+    for (_, card_stack) in rt.world.query::<CardStack>() {
+        let stack = layout
+            .stacks
+            .iter()
+            .find(|stack| {
+                stack.stack_type == card_stack.stack_type && stack.index == card_stack.stack_index
+            })
+            .expect("ドロップ先の山のレイアウトがある");
+        assert_eq!(
+            (stack.anchor_x, stack.anchor_y, stack.spacing),
+            (
+                card_stack.base_x,
+                card_stack.base_y,
+                card_stack.card_spacing
+            )
+        );
+    }
+
+    // カードを引くと、ウェイストの枠の位置に置かれる
+    SolitaireManager::draw_card(&mut rt.world).expect("デッキから引ける");
+    let layout = rt.layout().expect("クロンダイクのレイアウトを取得できる");
+    let waste = stack(&layout, "waste");
+    assert_eq!(waste.card_count, 1);
+    assert_eq!((waste.cards[0].x, waste.cards[0].y), (140.0, 20.0));
+    assert_eq!(stack(&layout, "deck").card_count, 23);
+    assert_eq!(waste.stack_type, CardLocation::Waste);
+}
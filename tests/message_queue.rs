@@ -0,0 +1,109 @@
+// =============================================================================
+// メッセージキューのテスト
+// =============================================================================
+// 上限付きのメッセージキューが上限を超えたメッセージを拒否して使用状況に記録すること、
+// 受信したネットワークメッセージがエンティティを作らずにキューに溜まり、
+// MessageProcessingSystemが取り出して空にすることを確認します。
+//
+// 実行方法：cargo test --test message_queue
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{MessageQueue, QueueStats, System, World};
+use ecs_wasm_solitaire::events::EventQueue;
+use ecs_wasm_solitaire::network::{
+    MessagePriority, MessageProcessingSystem, MessageType, NetworkManager, NetworkQueues,
+};
+
+#[test]
+fn full_queues_reject_new_items_and_record_back_pressure() {
+    let mut queue = MessageQueue::with_capacity(2);
+    assert_eq!(queue.push(1), Ok(()));
+    assert_eq!(queue.push(2), Ok(()));
+    assert!(queue.is_full());
+    assert_eq!(queue.push(3), Err(3), "上限を超えた分は返される");
+
+    assert_eq!(queue.drain(), [1, 2]);
+    assert!(queue.is_empty());
+    assert_eq!(queue.push(4), Ok(()));
+    assert_eq!(
+        queue.stats(),
+        QueueStats {
+            pending: 1,
+            capacity: 2,
+            high_water_mark: 2,
+            enqueued: 3,
+            dropped: 1,
+        }
+    );
+}
+
+#[test]
+fn network_messages_are_queued_without_entities_and_drained_when_processed() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+
+    let chat = r#"{"type": "Chat", "text": "hi"}"#;
+    assert!(NetworkManager::send_message(
+        &mut world,
+        MessageType::Chat,
+        chat.to_string(),
+        None,
+        None
+    ));
+    assert!(NetworkManager::send_priority_message(
+        &mut world,
+        MessageType::Chat,
+        chat.to_string(),
+        None,
+        None
+    ));
+    assert_eq!(
+        world.entity_count(),
+        0,
+        "メッセージごとにエンティティを作らない"
+    );
+
+    let queues = world.get_resource::<NetworkQueues>().unwrap();
+    let priorities: Vec<MessagePriority> = queues
+        .inbound
+        .iter()
+        .map(|message| message.priority)
+        .collect();
+    assert_eq!(priorities, [MessagePriority::Normal, MessagePriority::High]);
+
+    MessageProcessingSystem.update(&mut world, 0.016);
+    let queues = world.get_resource::<NetworkQueues>().unwrap();
+    assert!(queues.inbound.is_empty());
+    assert_eq!(queues.inbound.stats().enqueued, 2);
+}
+
+#[test]
+fn messages_beyond_the_queue_capacity_are_dropped() {
+    let mut world = World::new();
+    world.insert_resource(NetworkQueues {
+        inbound: MessageQueue::with_capacity(1),
+        outbound: MessageQueue::with_capacity(1),
+    });
+
+    let ping = r#"{"type": "Ping"}"#;
+    for (expected, message_type) in [(true, MessageType::Ping), (false, MessageType::Ping)] {
+        let queued =
+            NetworkManager::send_message(&mut world, message_type, ping.to_string(), None, None);
+        assert_eq!(queued, expected);
+    }
+    assert!(NetworkManager::queue_outbound(
+        &mut world,
+        MessageType::Ping,
+        ping.to_string()
+    ));
+    assert!(!NetworkManager::queue_outbound(
+        &mut world,
+        MessageType::Ping,
+        ping.to_string()
+    ));
+
+    let queues = world.get_resource::<NetworkQueues>().unwrap();
+    assert_eq!(queues.inbound.stats().dropped, 1);
+    assert_eq!(queues.outbound.stats().dropped, 1);
+    assert_eq!(NetworkManager::take_outbound(&mut world).len(), 1);
+}
@@ -0,0 +1,821 @@
+// =============================================================================
+// ネットワーククライアントのテスト
+// =============================================================================
+// JavaScript側がWebSocketを持つ場合と同じ手順（set_connected / receive / take_outgoing）で
+// サーバーとのやり取りを再現し、参加・ルームへの参加・アクションの送信が順序通りに
+// 送られること、届いたメッセージが購読者とECSワールドに渡ること、
+// 接続し直したときに同じ表示名・トークンで参加し直してルームに戻ること、
+// 接続・ルームへの参加・作成・一覧の要求が、同じ要求のIDが付いたサーバーの応答
+// （またはエラー・切断・タイムアウト）で1回だけ完了すること、
+// 受け取りの確認（Ack）が届かないメッセージが再送され、Reliableで届いたメッセージには
+// 確認を返して重複を処理しないこと、チャネルごとの連番が付き、再送を要求された連番の
+// メッセージがすぐに送り直され、古いカーソル位置が捨てられること、
+// 接続中は一定間隔でPingを送り、届いたPongから接続品質とサーバーの時計とのずれを求めて
+// 次のPingでずれを伝えること、接続品質が下がるとカーソル位置がまとめられ、
+// スコアがキーフレームの間隔で送り直されることを確認します。
+//
+// 実行方法：cargo test --test network_client
+// =============================================================================
+
+use ecs_wasm_solitaire::clock::GameClock;
+use ecs_wasm_solitaire::connection_quality::{ConnectionQuality, PING_INTERVAL_MS, PONG_TIMEOUT_MS};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::network::{
+    ConnectionStatus, MessageType, NetworkConnection, NetworkManager, NetworkMessage, NetworkQueues,
+};
+use ecs_wasm_solitaire::network_client::{
+    NetworkClient, Reply, RoomOptions, ALL_MESSAGES, REQUEST_TIMEOUT_MS,
+};
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use ecs_wasm_solitaire::reliable::{retry_delay_ms, MAX_RESENDS};
+use ecs_wasm_solitaire::scoreboard;
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// 要求ごとに記録した結果（応答の種類またはエラーメッセージ）
+type Results = Rc<RefCell<Vec<Result<String, String>>>>;
+
+fn world() -> World {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    world.insert_resource(GameClock::new());
+    world
+}
+
+/// 送信待ちのメッセージをすべてJSONとして取り出す
+fn all_outgoing(client: &mut NetworkClient, world: &mut World) -> Vec<Value> {
+    client
+        .take_outgoing(world)
+        .iter()
+        .map(|text| serde_json::from_str(text).expect("送信するメッセージはJSON"))
+        .collect()
+}
+
+/// 送信待ちのメッセージをJSONとして取り出す（接続品質を測るPingは除く）
+fn raw_outgoing(client: &mut NetworkClient, world: &mut World) -> Vec<Value> {
+    all_outgoing(client, world)
+        .into_iter()
+        .filter(|message| message["type"] != "Ping")
+        .collect()
+}
+
+/// 送信待ちのメッセージをJSONとして取り出す（Reliableで包まれたメッセージは中身を取り出す）
+fn outgoing(client: &mut NetworkClient, world: &mut World) -> Vec<Value> {
+    raw_outgoing(client, world)
+        .into_iter()
+        .map(|message| match message["type"].as_str() {
+            Some("Reliable") => message["message"].clone(),
+            _ => message,
+        })
+        .collect()
+}
+
+/// 送信待ちのメッセージを取り出し、Reliableで包まれたものに受け取りの確認を返す（再送させないため）
+fn acknowledge(client: &mut NetworkClient, world: &mut World) {
+    for message in raw_outgoing(client, world) {
+        if message["type"] == "Reliable" {
+            let ack = json!({ "type": "Ack", "message_id": message["message_id"] });
+            client.receive(world, &ack.to_string()).unwrap();
+        }
+    }
+}
+
+/// ゲーム時計を進める
+fn advance(world: &mut World, ms: f64) {
+    world.get_resource_mut::<GameClock>().unwrap().advance(ms);
+}
+
+fn profile(player_id: &str) -> String {
+    profile_reply(player_id, Value::Null)
+}
+
+/// 参加の要求に答えるプロフィール
+fn profile_reply(player_id: &str, request_id: Value) -> String {
+    json!({
+        "type": "PlayerProfile",
+        "profile": {
+            "player_id": player_id,
+            "player_name": "Alice",
+            "color_index": 0,
+            "rating": 1500,
+            "games_rated": 0,
+            "is_bot": false,
+        },
+        "request_id": request_id,
+    })
+    .to_string()
+}
+
+/// 要求の結果を記録する関数を作る（JSONの"type"またはエラーメッセージを記録）
+fn recorder() -> (Reply, Results) {
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&results);
+    let reply: Reply = Box::new(move |result: Result<WebSocketMessage, String>| {
+        let result =
+            result.map(|message| serde_json::to_value(&message).unwrap()["type"].to_string());
+        log.borrow_mut().push(result);
+    });
+    (reply, results)
+}
+
+/// 接続してプレイヤーIDを受け取るまで進める
+fn connect_as(client: &mut NetworkClient, world: &mut World, player_id: &str) {
+    client.connect(world, "ws://localhost:8101").unwrap();
+    client.join(world, "Alice");
+    client.set_connected(world, true);
+    client.receive(world, &profile(player_id)).unwrap();
+}
+
+#[test]
+fn join_and_room_requests_wait_for_the_connection_and_player_id() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    assert_eq!(client.status(&world), ConnectionStatus::Disconnected);
+    assert!(client.join_room("room-1", None).is_err(), "参加前");
+
+    client.connect(&mut world, "ws://localhost:8101").unwrap();
+    client.join(&world, "Alice");
+    client.join_room("room-1", None).unwrap();
+    assert_eq!(client.status(&world), ConnectionStatus::Connecting);
+    assert!(
+        outgoing(&mut client, &mut world).is_empty(),
+        "接続前は送らない"
+    );
+
+    client.set_connected(&mut world, true);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "PlayerJoin");
+    assert_eq!(sent[0]["player_name"], "Alice");
+
+    // プレイヤーIDを受け取ったら、申し込んでいたルームに参加する
+    client.receive(&mut world, &profile("player-1")).unwrap();
+    assert_eq!(client.player_id(), Some("player-1"));
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "JoinRoom");
+    assert_eq!(sent[0]["room_id"], "room-1");
+    assert_eq!(sent[0]["player_id"], "player-1");
+    assert_eq!(client.room_id(), None, "サーバーが受け付けるまでは未参加");
+
+    let joined = json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": "player-1" });
+    client.receive(&mut world, &joined.to_string()).unwrap();
+    assert_eq!(client.room_id(), Some("room-1"));
+
+    let connection = client.connection().expect("接続の記録がある");
+    let record = world
+        .get_component::<NetworkConnection>(connection)
+        .unwrap();
+    assert_eq!(record.sent_messages, 2);
+    assert_eq!(record.received_messages, 2);
+
+    let events = world.get_resource_mut::<EventQueue>().unwrap().drain();
+    let statuses: Vec<&GameEvent> = events
+        .iter()
+        .filter(|event| matches!(event, GameEvent::ConnectionChanged { .. }))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            &GameEvent::ConnectionChanged {
+                status: "connecting".to_string()
+            },
+            &GameEvent::ConnectionChanged {
+                status: "connected".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn actions_are_sent_only_after_joining() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    assert!(client.send_action("draw", None, None).is_err());
+
+    connect_as(&mut client, &mut world, "player-1");
+    outgoing(&mut client, &mut world);
+    client.send_action("draw", Some(12.0), None).unwrap();
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "GameAction");
+    assert_eq!(sent[0]["player_id"], "player-1");
+    assert_eq!(sent[0]["action"], "draw");
+    assert_eq!(sent[0]["x"], 12.0);
+    assert!(sent[0]["timestamp"]
+        .as_u64()
+        .is_some_and(|timestamp| timestamp > 0));
+}
+
+#[test]
+fn queued_game_results_are_sent_with_the_player_id() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    let result = json!({ "outcome": "won", "move_count": 87 });
+
+    // 参加する前の結果は送らずに捨てる
+    assert!(NetworkManager::queue_outbound(
+        &mut world,
+        MessageType::GameResult,
+        result.to_string()
+    ));
+    client.poll(&mut world);
+    assert!(outgoing(&mut client, &mut world).is_empty());
+
+    connect_as(&mut client, &mut world, "player-1");
+    outgoing(&mut client, &mut world);
+    NetworkManager::queue_outbound(&mut world, MessageType::GameResult, result.to_string());
+    client.poll(&mut world);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "GameResult");
+    assert_eq!(sent[0]["player_id"], "player-1");
+    assert_eq!(sent[0]["result"], result);
+    assert!(world
+        .get_resource::<NetworkQueues>()
+        .unwrap()
+        .outbound
+        .is_empty());
+}
+
+#[test]
+fn scores_are_shared_with_the_room_and_room_members_are_tracked() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    assert!(
+        client.send_score(10, 0).is_err(),
+        "ルームに参加する前は送れない"
+    );
+
+    let joined = json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": "player-1" });
+    client.receive(&mut world, &joined.to_string()).unwrap();
+    let scoreboard = json!({
+        "type": "Scoreboard",
+        "room_id": "room-1",
+        "players": [{
+            "player_id": "player-2",
+            "player_name": "Bob",
+            "color_index": 2,
+            "score": 45,
+            "foundation_cards": 3,
+        }],
+    });
+    client.receive(&mut world, &scoreboard.to_string()).unwrap();
+    let players = scoreboard::players(&world);
+    assert_eq!(players.len(), 1);
+    assert_eq!(players[0].player_name, "Bob");
+    assert_eq!(players[0].score, 45);
+
+    // 変わった場合だけ送る
+    outgoing(&mut client, &mut world);
+    assert_eq!(client.send_score(10, 1), Ok(true));
+    assert_eq!(client.send_score(10, 1), Ok(false));
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "ScoreUpdate");
+    assert_eq!(sent[0]["room_id"], "room-1");
+    assert_eq!(sent[0]["score"], 10);
+    assert_eq!(sent[0]["foundation_cards"], 1);
+
+    // 接続が切れたらスコアボードを空にする
+    client.set_connected(&mut world, false);
+    assert!(scoreboard::players(&world).is_empty());
+}
+
+#[test]
+fn received_messages_reach_subscribers_and_the_world() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+
+    let room_lists = Rc::new(RefCell::new(0));
+    let everything = Rc::new(RefCell::new(Vec::new()));
+    let counter = Rc::clone(&room_lists);
+    let room_list_id = client.subscribe("RoomList", Box::new(move |_| *counter.borrow_mut() += 1));
+    let log = Rc::clone(&everything);
+    client.subscribe(
+        ALL_MESSAGES,
+        Box::new(move |message| log.borrow_mut().push(format!("{:?}", message))),
+    );
+
+    let room_list = json!({ "type": "RoomList", "rooms": [] }).to_string();
+    client.receive(&mut world, &room_list).unwrap();
+    let left = json!({ "type": "PlayerLeft", "player_id": "player-2", "player_name": "Bob" });
+    client.receive(&mut world, &left.to_string()).unwrap();
+    assert_eq!(*room_lists.borrow(), 1);
+    assert_eq!(everything.borrow().len(), 2);
+
+    // 退出の通知はMessageProcessingSystemが扱うため、ワールドの受信キューにも追加される
+    let messages: Vec<&NetworkMessage> = world
+        .get_resource::<NetworkQueues>()
+        .unwrap()
+        .inbound
+        .iter()
+        .collect();
+    assert_eq!(messages.len(), 1);
+    assert!(messages[0].payload.contains("player-2"));
+
+    assert!(client.unsubscribe(room_list_id));
+    assert!(!client.unsubscribe(room_list_id));
+    client.receive(&mut world, &room_list).unwrap();
+    assert_eq!(*room_lists.borrow(), 1);
+
+    assert!(client.receive(&mut world, "not json").is_err());
+    assert_eq!(everything.borrow().len(), 3, "不正なメッセージは渡さない");
+}
+
+#[test]
+fn reconnecting_rejoins_with_the_session_token_and_returns_to_the_room() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    let token = json!({ "type": "SessionToken", "session_token": "token-1" });
+    client.receive(&mut world, &token.to_string()).unwrap();
+    client.join_room("room-1", None).unwrap();
+    let joined = json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": "player-1" });
+    client.receive(&mut world, &joined.to_string()).unwrap();
+    outgoing(&mut client, &mut world);
+
+    client.set_connected(&mut world, false);
+    assert_eq!(client.player_id(), None);
+    assert_eq!(client.room_id(), None);
+
+    client.set_connected(&mut world, true);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "PlayerJoin");
+    assert_eq!(sent[0]["session_token"], "token-1");
+
+    client.receive(&mut world, &profile("player-9")).unwrap();
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "JoinRoom");
+    assert_eq!(sent[0]["room_id"], "room-1");
+    assert_eq!(sent[0]["player_id"], "player-9");
+
+    // 退出した後は戻らない
+    client.leave_room().unwrap();
+    assert_eq!(outgoing(&mut client, &mut world)[0]["type"], "LeaveRoom");
+    assert!(client.leave_room().is_err());
+
+    client.disconnect(&mut world);
+    assert_eq!(client.status(&world), ConnectionStatus::Disconnected);
+    assert_eq!(client.connection(), None);
+}
+
+#[test]
+fn requests_resolve_on_replies_with_the_same_request_id() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    client.connect(&mut world, "ws://localhost:8101").unwrap();
+    let join_id = client.join(&world, "Alice");
+    let (reply, connected) = recorder();
+    client.await_reply(&world, &join_id, reply);
+    client.set_connected(&mut world, true);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "PlayerJoin");
+    assert_eq!(sent[0]["request_id"], join_id.as_str());
+
+    // 要求のIDが付いていないプロフィール（ルームの参加者への通知など）では完了しない
+    client.receive(&mut world, &profile("player-1")).unwrap();
+    assert!(connected.borrow().is_empty());
+    client
+        .receive(&mut world, &profile_reply("player-1", json!(join_id)))
+        .unwrap();
+    client
+        .receive(&mut world, &profile_reply("player-1", json!(join_id)))
+        .unwrap();
+    assert_eq!(
+        *connected.borrow(),
+        [Ok("\"PlayerProfile\"".to_string())],
+        "応答は1回だけ渡す"
+    );
+
+    let create_id = client
+        .create_room(RoomOptions {
+            name: "Alice's room".to_string(),
+            max_players: Some(2),
+            password: Some("secret".to_string()),
+            turn_time_limit: None,
+            combo_window_seconds: None,
+            power_ups: None,
+            shared_board: None,
+        })
+        .unwrap();
+    assert_ne!(create_id, join_id);
+    let (reply, created) = recorder();
+    client.await_reply(&world, &create_id, reply);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "CreateRoom");
+    assert_eq!(sent[0]["max_players"], 2);
+    assert_eq!(sent[0]["request_id"], create_id.as_str());
+
+    // 他のクライアントの要求への応答では完了しない
+    let others = json!({
+        "type": "JoinRoom",
+        "room_id": "room-7",
+        "player_id": "player-2",
+        "request_id": "req_other_1",
+    });
+    client.receive(&mut world, &others.to_string()).unwrap();
+    assert!(created.borrow().is_empty());
+    let joined = json!({
+        "type": "JoinRoom",
+        "room_id": "room-7",
+        "player_id": "player-1",
+        "request_id": create_id,
+    });
+    client.receive(&mut world, &joined.to_string()).unwrap();
+    assert_eq!(*created.borrow(), [Ok("\"JoinRoom\"".to_string())]);
+    assert_eq!(client.room_id(), Some("room-7"));
+
+    // 作成したルームにも、接続し直したときにパスワード付きで戻る
+    client.set_connected(&mut world, false);
+    client.set_connected(&mut world, true);
+    client.receive(&mut world, &profile("player-3")).unwrap();
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent[1]["type"], "JoinRoom");
+    assert_eq!(sent[1]["room_id"], "room-7");
+    assert_eq!(sent[1]["password"], "secret");
+
+    let list_id = client.request_room_list().unwrap();
+    let (reply, listed) = recorder();
+    client.await_reply(&world, &list_id, reply);
+    assert_eq!(outgoing(&mut client, &mut world)[0]["type"], "GetRoomList");
+    let room_list = json!({ "type": "RoomList", "rooms": [], "request_id": list_id });
+    client.receive(&mut world, &room_list.to_string()).unwrap();
+    assert_eq!(*listed.borrow(), [Ok("\"RoomList\"".to_string())]);
+}
+
+#[test]
+fn requests_are_rejected_on_errors_disconnects_and_timeouts() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    assert!(
+        client.create_room(RoomOptions::default()).is_err(),
+        "参加前"
+    );
+    assert!(client.request_room_list().is_err(), "参加前");
+    connect_as(&mut client, &mut world, "player-1");
+    assert!(
+        client.create_room(RoomOptions::default()).is_err(),
+        "ルーム名が空"
+    );
+
+    // エラーは同じ要求のIDが付いている要求だけを失敗にする
+    let list_id = client.request_room_list().unwrap();
+    let (reply, listed) = recorder();
+    client.await_reply(&world, &list_id, reply);
+    let join_id = client
+        .join_room("room-1", Some("wrong".to_string()))
+        .unwrap();
+    let (reply, joined) = recorder();
+    client.await_reply(&world, &join_id, reply);
+    let unrelated = json!({ "type": "Error", "message": "ホストのみ実行できます" });
+    client.receive(&mut world, &unrelated.to_string()).unwrap();
+    assert!(joined.borrow().is_empty());
+    let error = json!({
+        "type": "Error",
+        "message": "パスワードが違います",
+        "request_id": join_id,
+    });
+    client.receive(&mut world, &error.to_string()).unwrap();
+    assert_eq!(*joined.borrow(), [Err("パスワードが違います".to_string())]);
+    assert!(listed.borrow().is_empty());
+
+    // 期限を過ぎた要求はpoll()でエラーになる
+    advance(&mut world, REQUEST_TIMEOUT_MS + 1.0);
+    client.poll(&mut world);
+    assert_eq!(
+        *listed.borrow(),
+        [Err("サーバーからの応答がありません".to_string())]
+    );
+
+    let list_id = client.request_room_list().unwrap();
+    let (reply, relisted) = recorder();
+    client.await_reply(&world, &list_id, reply);
+    client.poll(&mut world);
+    assert!(relisted.borrow().is_empty(), "期限内");
+    client.set_connected(&mut world, false);
+    assert_eq!(
+        *relisted.borrow(),
+        [Err("サーバーとの接続が切れました".to_string())]
+    );
+}
+
+#[test]
+fn messages_are_resent_until_acknowledged() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    let sent = raw_outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["type"], "Reliable");
+    assert_eq!(sent[0]["message"]["type"], "PlayerJoin");
+    let join_id = sent[0]["message_id"].clone();
+
+    client.send_action("draw", None, None).unwrap();
+    let sent = raw_outgoing(&mut client, &mut world);
+    assert_eq!(sent[0]["message"]["type"], "GameAction");
+    let action_id = sent[0]["message_id"].clone();
+    assert_ne!(action_id, join_id);
+    assert_eq!(client.unacked_count(), 2);
+
+    // 確認が届いたメッセージは再送しない
+    let ack = json!({ "type": "Ack", "message_id": join_id });
+    client.receive(&mut world, &ack.to_string()).unwrap();
+    assert_eq!(client.unacked_count(), 1);
+    client.poll(&mut world);
+    assert!(raw_outgoing(&mut client, &mut world).is_empty(), "再送の前");
+
+    advance(&mut world, retry_delay_ms(0));
+    client.poll(&mut world);
+    let resent = raw_outgoing(&mut client, &mut world);
+    assert_eq!(resent, sent, "同じメッセージIDで再送する");
+
+    // 再送するたびに間隔が延びる
+    advance(&mut world, retry_delay_ms(0));
+    client.poll(&mut world);
+    assert!(raw_outgoing(&mut client, &mut world).is_empty());
+    advance(&mut world, retry_delay_ms(1) - retry_delay_ms(0));
+    client.poll(&mut world);
+    assert_eq!(raw_outgoing(&mut client, &mut world).len(), 1);
+
+    let ack = json!({ "type": "Ack", "message_id": action_id });
+    client.receive(&mut world, &ack.to_string()).unwrap();
+    assert_eq!(client.unacked_count(), 0);
+    advance(&mut world, REQUEST_TIMEOUT_MS);
+    client.poll(&mut world);
+    assert!(raw_outgoing(&mut client, &mut world).is_empty());
+}
+
+#[test]
+fn unacknowledged_messages_are_given_up_after_the_resend_limit() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    raw_outgoing(&mut client, &mut world);
+
+    let mut resends = 0;
+    for _ in 0..MAX_RESENDS * 2 {
+        advance(&mut world, REQUEST_TIMEOUT_MS);
+        client.poll(&mut world);
+        resends += raw_outgoing(&mut client, &mut world).len();
+    }
+    assert_eq!(resends, MAX_RESENDS as usize);
+    assert_eq!(client.unacked_count(), 0);
+}
+
+#[test]
+fn reliable_messages_are_acknowledged_and_processed_once() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    raw_outgoing(&mut client, &mut world);
+    let received = Rc::new(RefCell::new(0));
+    let counter = Rc::clone(&received);
+    client.subscribe("PlayerLeft", Box::new(move |_| *counter.borrow_mut() += 1));
+
+    let reliable = json!({
+        "type": "Reliable",
+        "message_id": "srv_1",
+        "message": { "type": "PlayerLeft", "player_id": "player-2", "player_name": "Bob" },
+    })
+    .to_string();
+    client.receive(&mut world, &reliable).unwrap();
+    client.receive(&mut world, &reliable).unwrap();
+    assert_eq!(
+        *received.borrow(),
+        1,
+        "再送で重複したメッセージは処理しない"
+    );
+
+    // 重複したメッセージにも確認を返す（最初の確認が失われた場合のため）
+    let sent = raw_outgoing(&mut client, &mut world);
+    let ack = json!({ "type": "Ack", "message_id": "srv_1" });
+    assert_eq!(sent, [ack.clone(), ack]);
+
+    // ワールドの受信キューには包まれていた中身を追加する
+    let messages: Vec<&NetworkMessage> = world
+        .get_resource::<NetworkQueues>()
+        .unwrap()
+        .inbound
+        .iter()
+        .collect();
+    assert_eq!(messages.len(), 1);
+    assert!(!messages[0].payload.contains("Reliable"));
+    assert!(messages[0].payload.contains("player-2"));
+}
+
+#[test]
+fn sent_messages_are_numbered_per_channel_and_resent_on_request() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    assert!(client.send_cursor(&world, 1.0, 2.0).is_err(), "参加前");
+    connect_as(&mut client, &mut world, "player-1");
+    client.send_action("draw", None, None).unwrap();
+    client.send_action("flip", None, None).unwrap();
+    client.request_room_list().unwrap();
+    assert_eq!(client.send_cursor(&world, 10.0, 20.0), Ok(true));
+    advance(&mut world, 100.0);
+    assert_eq!(client.send_cursor(&world, 11.0, 21.0), Ok(true));
+
+    let sent = raw_outgoing(&mut client, &mut world);
+    let sequences: Vec<(Value, Value)> = sent
+        .iter()
+        .map(|message| match message["type"].as_str() {
+            Some("Reliable") => (
+                message["message"]["type"].clone(),
+                message["sequence"].clone(),
+            ),
+            _ => (message["type"].clone(), message["sequence"].clone()),
+        })
+        .collect();
+    assert_eq!(
+        sequences,
+        [
+            (json!("PlayerJoin"), json!(1)),
+            (json!("GameAction"), json!(1)),
+            (json!("GameAction"), json!(2)),
+            (json!("GetRoomList"), json!(2)),
+            (json!("MousePosition"), json!(1)),
+            (json!("MousePosition"), json!(2)),
+        ]
+    );
+    assert_eq!(client.unacked_count(), 4, "カーソル位置は確認を待たない");
+
+    // 再送を要求された連番のメッセージだけを、再送の時刻を待たずに送り直す
+    let request = json!({ "type": "ResendRequest", "channel": "actions", "sequences": [2, 9] });
+    client.receive(&mut world, &request.to_string()).unwrap();
+    assert_eq!(raw_outgoing(&mut client, &mut world), [sent[2].clone()]);
+}
+
+#[test]
+fn cursor_positions_are_coalesced_and_scores_resent_as_keyframes_on_a_poor_connection() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    let joined = json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": "player-1" });
+    client.receive(&mut world, &joined.to_string()).unwrap();
+    acknowledge(&mut client, &mut world);
+
+    let poor = ConnectionQuality::Poor.update_rates();
+    let good = ConnectionQuality::Good.update_rates();
+    assert!(poor.cursor_interval_ms > good.cursor_interval_ms);
+
+    // Pingに応答がないまま時間切れが続くと、不安定な接続とみなす
+    for _ in 0..3 {
+        client.poll(&mut world);
+        advance(&mut world, PONG_TIMEOUT_MS);
+    }
+    client.poll(&mut world);
+    assert_eq!(client.connection_report().quality, ConnectionQuality::Poor);
+    assert_eq!(client.update_rates(), poor);
+    acknowledge(&mut client, &mut world);
+
+    // 間隔より早いカーソル位置は送らず、最新の位置だけを残す
+    assert_eq!(client.send_cursor(&world, 1.0, 1.0), Ok(true));
+    for step in 2..=5 {
+        advance(&mut world, f64::from(good.cursor_interval_ms));
+        assert_eq!(client.send_cursor(&world, step as f64, 1.0), Ok(false));
+        client.poll(&mut world);
+    }
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["x"], 1.0);
+
+    // 間隔が空いたら、残していた最新の位置を1つだけ送る
+    advance(&mut world, f64::from(poor.cursor_interval_ms));
+    client.poll(&mut world);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "MousePosition");
+    assert_eq!(sent[0]["x"], 5.0);
+    client.poll(&mut world);
+    assert!(outgoing(&mut client, &mut world).is_empty());
+
+    // 変わっていないスコアも、品質に合わせたキーフレームの間隔ごとに送り直す
+    assert_eq!(client.send_score(30, 2), Ok(true));
+    client.poll(&mut world);
+    acknowledge(&mut client, &mut world);
+    advance(&mut world, f64::from(good.keyframe_interval_ms));
+    client.poll(&mut world);
+    assert!(outgoing(&mut client, &mut world).is_empty(), "不安定な接続では間隔が広い");
+    advance(&mut world, f64::from(poor.keyframe_interval_ms - good.keyframe_interval_ms));
+    client.poll(&mut world);
+    let sent = outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "ScoreUpdate");
+    assert_eq!(sent[0]["score"], 30);
+    assert_eq!(sent[0]["foundation_cards"], 2);
+}
+
+#[test]
+fn pings_are_sent_while_connected_and_pongs_update_the_quality_and_clock_offset() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    client.poll(&mut world);
+    assert!(all_outgoing(&mut client, &mut world).is_empty(), "未接続ではPingを送らない");
+    assert_eq!(client.connection_report().quality, ConnectionQuality::Offline);
+
+    connect_as(&mut client, &mut world, "player-1");
+    acknowledge(&mut client, &mut world);
+    assert_eq!(client.connection_report().quality, ConnectionQuality::Unknown);
+    client.poll(&mut world);
+    let ping = all_outgoing(&mut client, &mut world)
+        .into_iter()
+        .find(|message| message["type"] == "Ping")
+        .expect("接続するとPingを送る");
+    assert_eq!(ping["clock_offset_ms"], Value::Null, "Pongを受け取るまでは時計のずれは不明");
+    assert_eq!(client.clock_offset_ms(), None);
+
+    // サーバーの時計が1分進んでいて、Pongが40ms後に届いた場合
+    let sent_at = ping["client_time_ms"].as_u64().expect("Pingに送信時刻が入る");
+    advance(&mut world, 40.0);
+    let pong = json!({
+        "type": "Pong",
+        "ping_id": ping["ping_id"],
+        "client_time_ms": sent_at,
+        "server_time_ms": sent_at + 60_020,
+    })
+    .to_string();
+    client.receive(&mut world, &pong).unwrap();
+    client.receive(&mut world, &pong).unwrap();
+    let report = client.connection_report();
+    assert_eq!(report.quality, ConnectionQuality::Good);
+    assert_eq!(report.rtt_ms, Some(40), "同じPongは2回数えない");
+    assert_eq!(client.update_rates(), ConnectionQuality::Good.update_rates());
+    let offset = client.clock_offset_ms().expect("Pongから時計のずれを推定する");
+    assert!((offset - 60_000.0).abs() < 1.0);
+    assert_eq!(client.server_to_local_time(sent_at as f64 + offset), sent_at as f64);
+
+    // 次のPingは間隔が空いてから、推定したずれを付けて送る
+    client.poll(&mut world);
+    assert!(all_outgoing(&mut client, &mut world).is_empty());
+    advance(&mut world, PING_INTERVAL_MS);
+    client.poll(&mut world);
+    let sent = all_outgoing(&mut client, &mut world);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0]["type"], "Ping");
+    assert_eq!(sent[0]["clock_offset_ms"], 60_000);
+
+    // 応答が遅いと品質が下がる
+    let late = json!({
+        "type": "Pong",
+        "ping_id": sent[0]["ping_id"],
+        "client_time_ms": sent[0]["client_time_ms"],
+        "server_time_ms": sent[0]["client_time_ms"].as_u64().unwrap() + 60_000,
+    })
+    .to_string();
+    advance(&mut world, 3_000.0);
+    client.receive(&mut world, &late).unwrap();
+    assert_eq!(client.connection_report().quality, ConnectionQuality::Poor);
+}
+
+#[test]
+fn stale_cursor_positions_are_dropped() {
+    let mut world = world();
+    let mut client = NetworkClient::new();
+    connect_as(&mut client, &mut world, "player-1");
+    let cursors = Rc::new(RefCell::new(Vec::new()));
+    let log = Rc::clone(&cursors);
+    client.subscribe(
+        "MousePosition",
+        Box::new(move |message| {
+            if let WebSocketMessage::MousePosition { player_id, x, .. } = message {
+                log.borrow_mut().push((player_id.clone(), *x));
+            }
+        }),
+    );
+
+    let cursor = |player_id: &str, x: f64, sequence: u32| {
+        json!({
+            "type": "MousePosition",
+            "player_id": player_id,
+            "x": x,
+            "y": 0.0,
+            "timestamp": 0,
+            "sequence": sequence,
+        })
+        .to_string()
+    };
+    client
+        .receive(&mut world, &cursor("player-2", 1.0, 1))
+        .unwrap();
+    client
+        .receive(&mut world, &cursor("player-2", 3.0, 3))
+        .unwrap();
+    client
+        .receive(&mut world, &cursor("player-2", 2.0, 2))
+        .unwrap();
+    client
+        .receive(&mut world, &cursor("player-3", 5.0, 1))
+        .unwrap();
+    assert_eq!(
+        *cursors.borrow(),
+        [
+            ("player-2".to_string(), 1.0),
+            ("player-2".to_string(), 3.0),
+            ("player-3".to_string(), 5.0),
+        ],
+        "新しい位置より後に届いた古い位置は捨てる"
+    );
+}
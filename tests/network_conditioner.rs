@@ -0,0 +1,215 @@
+// =============================================================================
+// 通信状態の再現のテスト
+// =============================================================================
+// NetworkConditionerが遅延・揺らぎ・順序の入れ替わり・欠落をシードどおりに再現し、
+// 受信キューに挟んだ場合はメッセージの処理が遅れることを確認します。
+// 接続品質の判定が、再現した悪い回線で下がることもここで確認します。
+//
+// 実行方法：cargo test --test network_conditioner
+// =============================================================================
+
+use ecs_wasm_solitaire::connection_quality::{
+    ConnectionMonitor, ConnectionQuality, PING_INTERVAL_MS,
+};
+use ecs_wasm_solitaire::ecs::{System, World};
+use ecs_wasm_solitaire::network::{
+    MessageProcessingSystem, MessageType, NetworkManager, NetworkMessage, NetworkQueues,
+};
+use ecs_wasm_solitaire::network_conditioner::{
+    ConditionerSettings, NetworkConditioner, NetworkConditionerSystem, MAX_REORDER_HOLD_MS,
+};
+use ecs_wasm_solitaire::reaction;
+
+/// 指定した通信状態の中継器を作成
+fn conditioner(settings: ConditionerSettings) -> NetworkConditioner<u32> {
+    NetworkConditioner::new(settings).expect("有効な設定")
+}
+
+/// 0からcount-1までを順に送り、十分に時間を進めて届いた順に取り出す
+fn send_all(conditioner: &mut NetworkConditioner<u32>, count: u32) -> Vec<u32> {
+    for item in 0..count {
+        conditioner.send(item);
+    }
+    conditioner.advance(10_000.0)
+}
+
+#[test]
+fn messages_arrive_after_the_latency() {
+    let mut conditioner = conditioner(ConditionerSettings {
+        latency_ms: 100.0,
+        ..Default::default()
+    });
+    assert!(conditioner.send(1));
+
+    assert!(
+        conditioner.advance(99.0).is_empty(),
+        "遅延が過ぎるまで届かない"
+    );
+    assert_eq!(conditioner.pending_count(), 1);
+    assert_eq!(conditioner.advance(1.0), vec![1]);
+    assert_eq!(conditioner.pending_count(), 0);
+
+    // 設定なし（すべて0）の場合はすぐに届く
+    let mut direct = self::conditioner(ConditionerSettings::default());
+    assert_eq!(send_all(&mut direct, 3), vec![0, 1, 2]);
+}
+
+#[test]
+fn jitter_stays_within_the_configured_range() {
+    let mut conditioner = conditioner(ConditionerSettings {
+        latency_ms: 100.0,
+        jitter_ms: 30.0,
+        seed: 7,
+        ..Default::default()
+    });
+    for item in 0..50 {
+        conditioner.send(item);
+    }
+
+    assert!(
+        conditioner.advance(69.0).is_empty(),
+        "遅延-揺らぎより前には届かない"
+    );
+    let delivered = conditioner.advance(61.0);
+    assert_eq!(delivered.len(), 50, "遅延+揺らぎまでにすべて届く");
+    assert_ne!(
+        delivered,
+        (0..50).collect::<Vec<_>>(),
+        "揺らぎで順序が入れ替わる"
+    );
+}
+
+#[test]
+fn reordered_messages_are_overtaken_by_the_next_one() {
+    let mut conditioner = conditioner(ConditionerSettings {
+        reorder_rate: 1.0,
+        ..Default::default()
+    });
+    assert_eq!(send_all(&mut conditioner, 4), vec![1, 0, 3, 2]);
+
+    // 後続のメッセージが来なくても、待ちすぎる前に届く
+    conditioner.send(4);
+    assert!(conditioner.advance(0.0).is_empty());
+    assert_eq!(conditioner.advance(MAX_REORDER_HOLD_MS), vec![4]);
+}
+
+#[test]
+fn drops_are_deterministic_for_a_seed() {
+    let settings = ConditionerSettings {
+        drop_rate: 0.5,
+        seed: 42,
+        ..Default::default()
+    };
+    let mut first = conditioner(settings.clone());
+    let mut second = conditioner(settings);
+
+    let delivered = send_all(&mut first, 100);
+    assert_eq!(
+        delivered,
+        send_all(&mut second, 100),
+        "同じシードなら同じ結果"
+    );
+    assert_eq!(first.dropped_count() as usize, 100 - delivered.len());
+    assert!((30..70).contains(&delivered.len()), "およそ半分が欠落する");
+
+    let mut lossy = conditioner(ConditionerSettings {
+        drop_rate: 1.0,
+        ..Default::default()
+    });
+    assert!(!lossy.send(0));
+    assert!(lossy.advance(10_000.0).is_empty());
+}
+
+#[test]
+fn invalid_settings_are_rejected() {
+    for settings in [
+        ConditionerSettings {
+            latency_ms: -1.0,
+            ..Default::default()
+        },
+        ConditionerSettings {
+            jitter_ms: f64::NAN,
+            ..Default::default()
+        },
+        ConditionerSettings {
+            drop_rate: 1.5,
+            ..Default::default()
+        },
+    ] {
+        assert!(NetworkConditioner::<u32>::new(settings).is_err());
+    }
+
+    // 省略した項目は0になる
+    let settings: ConditionerSettings = serde_json::from_str(r#"{"latency_ms": 80}"#).unwrap();
+    assert_eq!(settings.latency_ms, 80.0);
+    assert_eq!(settings.drop_rate, 0.0);
+}
+
+#[test]
+fn conditioner_system_delays_received_messages() {
+    let mut world = World::new();
+    world.insert_resource(
+        NetworkConditioner::<NetworkMessage>::new(ConditionerSettings {
+            latency_ms: 100.0,
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let frame = |world: &mut World, delta_time: f64| {
+        NetworkConditionerSystem.update(world, delta_time);
+        MessageProcessingSystem.update(world, delta_time);
+    };
+
+    NetworkManager::send_message(
+        &mut world,
+        MessageType::Reaction,
+        r#"{"type": "Reaction", "player_id": "p2", "emote": "snail"}"#.to_string(),
+        None,
+        None,
+    );
+    frame(&mut world, 0.05);
+    assert!(
+        reaction::active(&world).is_empty(),
+        "遅延中はまだ処理されない"
+    );
+
+    frame(&mut world, 0.05);
+    assert_eq!(reaction::active(&world).len(), 1);
+    assert!(
+        world
+            .get_resource::<NetworkQueues>()
+            .unwrap()
+            .inbound
+            .is_empty(),
+        "処理済み"
+    );
+}
+
+#[test]
+fn lossy_link_lowers_the_connection_quality() {
+    let mut monitor = ConnectionMonitor::new();
+    monitor.set_connected(true);
+    let mut link = conditioner(ConditionerSettings {
+        latency_ms: 60.0,
+        jitter_ms: 20.0,
+        drop_rate: 0.4,
+        seed: 3,
+        ..Default::default()
+    });
+
+    // 100ミリ秒ごとに時間を進め、届いたPongを記録する
+    let mut now = 0.0;
+    while now < 40.0 * PING_INTERVAL_MS {
+        if let Some(ping_id) = monitor.tick(now) {
+            link.send(ping_id);
+        }
+        now += 100.0;
+        for ping_id in link.advance(100.0) {
+            monitor.record_pong(ping_id, now);
+        }
+    }
+
+    assert!(link.dropped_count() > 0);
+    assert!(monitor.packet_loss() > 0.2);
+    assert_eq!(monitor.quality(), ConnectionQuality::Poor);
+}
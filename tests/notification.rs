@@ -0,0 +1,122 @@
+// =============================================================================
+// 通知イベントのテスト
+// =============================================================================
+// デッキの戻しによる減点、手詰まり、対戦相手の切断が、重要度と自動で閉じる時間の
+// 付いた通知イベントとしてイベントキューに入ることを確認します。
+//
+// 実行方法：cargo test --test notification
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent, NotificationSeverity};
+use ecs_wasm_solitaire::network::{MessageProcessingSystem, MessageType, NetworkManager};
+use ecs_wasm_solitaire::notification::NotificationSystem;
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::SolitaireManager;
+
+/// イベントキュー付きで盤面を組み立てたワールドを作成
+fn world_with(board: BoardBuilder) -> World {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    board.build(&mut world).expect("シナリオから盤面を作れる");
+    world
+}
+
+/// デッキから引いて通知システムを1フレーム分実行することを繰り返す
+fn draw_times(world: &mut World, times: usize) {
+    for _ in 0..times {
+        SolitaireManager::draw_card(world).expect("デッキかウェイストにカードがある");
+        NotificationSystem.update(world, 0.016);
+    }
+}
+
+/// 溜まっている通知を(重要度, 文章, 自動で閉じる時間)として取り出す
+fn notifications(world: &mut World) -> Vec<(NotificationSeverity, String, Option<u32>)> {
+    world
+        .get_resource_mut::<EventQueue>()
+        .expect("イベントキューがある")
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::Notification {
+                severity,
+                message,
+                auto_dismiss_ms,
+            } => Some((severity, message, auto_dismiss_ms)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn deck_recycle_penalty_is_notified() {
+    // 5♦を6♣へ動かせるので手詰まりにはならない
+    let mut world = world_with(
+        BoardBuilder::new()
+            .tableau(0, 0, &["5D"])
+            .tableau(1, 0, &["6C"])
+            .deck(&["KH"]),
+    );
+
+    // 引く・戻すを2回ずつ繰り返しても減点はない
+    draw_times(&mut world, 4);
+    assert!(notifications(&mut world).is_empty());
+
+    // 3回目の戻しで減点される
+    draw_times(&mut world, 2);
+    let notified = notifications(&mut world);
+    assert_eq!(notified.len(), 1);
+    let (severity, message, auto_dismiss_ms) = &notified[0];
+    assert_eq!(*severity, NotificationSeverity::Warning);
+    assert!(message.contains("減点"), "{}", message);
+    assert!(auto_dismiss_ms.is_some());
+}
+
+#[test]
+fn running_out_of_moves_asks_for_confirmation_once() {
+    let mut world = world_with(BoardBuilder::new().tableau(0, 0, &["5D"]).deck(&["2C"]));
+
+    draw_times(&mut world, 10);
+    let stuck: Vec<_> = notifications(&mut world)
+        .into_iter()
+        .filter(|(_, message, _)| message.contains("打てる手がなくなりました"))
+        .collect();
+    assert_eq!(stuck.len(), 1, "手詰まりは1回だけ通知する");
+    assert_eq!(
+        stuck[0].2, None,
+        "手詰まりは確認が必要なので自動では閉じない"
+    );
+}
+
+#[test]
+fn opponent_leaving_is_notified() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    NetworkManager::send_message(
+        &mut world,
+        MessageType::PlayerJoinLeave,
+        r#"{"type": "PlayerLeft", "player_id": "p2", "player_name": "Bob"}"#.to_string(),
+        None,
+        None,
+    );
+
+    MessageProcessingSystem.update(&mut world, 0.016);
+    let notified = notifications(&mut world);
+    assert_eq!(notified.len(), 1);
+    assert_eq!(notified[0].0, NotificationSeverity::Warning);
+    assert!(notified[0].1.contains("Bob"));
+}
+
+#[test]
+fn severities_have_dismiss_defaults() {
+    let json = serde_json::to_value(GameEvent::notification(
+        NotificationSeverity::Achievement,
+        "🏅 実績解除",
+    ))
+    .expect("通知はJSONにできる");
+    assert_eq!(json["type"], "notification");
+    assert_eq!(json["severity"], "achievement");
+    assert!(json["auto_dismiss_ms"].is_u64());
+
+    assert_eq!(NotificationSeverity::Error.default_auto_dismiss_ms(), None);
+}
@@ -0,0 +1,176 @@
+// =============================================================================
+// 共有盤面の権限のテスト
+// =============================================================================
+// 観戦者・他のプレイヤーのカード・ホスト以外の山札の戻しが断られる規則と、
+// サーバーから届いたPermissionsChangedがカードの選択・アクションの検証・
+// クライアント向け状態のlockedに反映され、ルームを抜けると外れること、
+// 他のプレイヤーのIDを書いて持ち主やホストになりすましたメッセージが断られることを確認します。
+//
+// 実行方法：cargo test --test permissions
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::ClientState;
+use ecs_wasm_solitaire::clock::GameClock;
+use ecs_wasm_solitaire::ecs::{Entity, World};
+use ecs_wasm_solitaire::game::{ActionPayload, ActionProcessingSystem, GameAction};
+use ecs_wasm_solitaire::permissions::{self, check_action, check_card, SeatRole, SharedBoard};
+use ecs_wasm_solitaire::protocol::{MoveLocation, WebSocketMessage};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::selection;
+use ecs_wasm_solitaire::solitaire::{CardLocation, SolitaireCard};
+use std::collections::BTreeMap;
+
+/// タブローの1列目のカード
+fn first_column(world: &World) -> Entity {
+    world
+        .query::<SolitaireCard>()
+        .find(|(_, card)| {
+            card.location_type == CardLocation::Tableau && card.position_in_location == 0
+        })
+        .map(|(entity, _)| entity)
+        .expect("1列目にカードがある")
+}
+
+/// 自分（Alice）が受け取るメッセージを反映する
+fn receive(world: &mut World, message: WebSocketMessage) -> bool {
+    permissions::apply(world, Some("alice"), &message)
+}
+
+/// アクションを検証する
+fn validate(world: &World, payload: ActionPayload) -> Result<(), String> {
+    let action = GameAction::new(Entity(0), payload, &GameClock::from_world(world));
+    ActionProcessingSystem::validate(world, &action)
+}
+
+#[test]
+fn spectators_other_players_cards_and_recycling_are_refused() {
+    let spectators = ["carol".to_string()];
+    let role = |id: &str| SeatRole::of(id, Some("alice"), spectators.iter());
+    assert_eq!(role("alice"), SeatRole::Host);
+    assert_eq!(role("bob"), SeatRole::Player);
+    assert_eq!(role("carol"), SeatRole::Spectator);
+
+    // 持ち主のいないカードと自分のカードは動かせる
+    assert!(check_card(SeatRole::Player, "bob", None).is_ok());
+    assert!(check_card(SeatRole::Player, "bob", Some("bob")).is_ok());
+    assert!(check_card(SeatRole::Player, "bob", Some("alice"))
+        .unwrap_err()
+        .contains("alice"));
+    assert!(check_card(SeatRole::Spectator, "carol", None).is_err());
+
+    // 山札に戻せるのはホストだけで、観戦者は何も送れない
+    assert!(check_action(SeatRole::Host, permissions::RECYCLE_ACTION).is_ok());
+    assert!(check_action(SeatRole::Player, permissions::RECYCLE_ACTION).is_err());
+    assert!(check_action(SeatRole::Player, "draw").is_ok());
+    assert!(check_action(SeatRole::Spectator, "draw").is_err());
+}
+
+#[test]
+fn messages_naming_another_player_are_refused_before_the_seat_is_checked() {
+    let game_action = |player_id: &str, action: &str| WebSocketMessage::GameAction {
+        player_id: player_id.to_string(),
+        player_name: "Carol".to_string(),
+        action: action.to_string(),
+        x: None,
+        y: None,
+        timestamp: 0,
+    };
+    let grab = |player_id: &str| WebSocketMessage::GrabCard {
+        room_id: "room-1".to_string(),
+        player_id: player_id.to_string(),
+        card_id: "spades-13".to_string(),
+        timestamp: 0,
+    };
+    let stop_spectating = |player_id: &str| WebSocketMessage::SetSpectating {
+        room_id: "room-1".to_string(),
+        player_id: player_id.to_string(),
+        spectating: false,
+    };
+
+    // 観戦者のCarolの接続から、ホストのAliceや持ち主のBobを名乗ったメッセージは断られる
+    let carol = Some("carol");
+    assert!(game_action("alice", permissions::RECYCLE_ACTION).check_sender(carol).is_err());
+    assert!(grab("bob").check_sender(carol).is_err());
+    assert!(stop_spectating("bob").check_sender(carol).is_err());
+    let wrapped = WebSocketMessage::Reliable {
+        message_id: "m-1".to_string(),
+        message: Box::new(grab("bob")),
+        sequence: Some(1),
+    };
+    assert_eq!(wrapped.sender_id(), Some("bob"));
+    assert!(wrapped.check_sender(carol).is_err());
+
+    // 参加前の接続は誰も名乗れない
+    assert!(grab("bob").check_sender(None).is_err());
+
+    // 自分のIDで送ったものは通り、権限は接続のプレイヤーの立場で判断される
+    assert!(game_action("carol", permissions::RECYCLE_ACTION).check_sender(carol).is_ok());
+    let spectators = ["carol".to_string()];
+    let role = SeatRole::of("carol", Some("alice"), spectators.iter());
+    assert!(check_action(role, permissions::RECYCLE_ACTION).is_err());
+    assert!(check_card(role, "carol", Some("bob")).is_err());
+}
+
+#[test]
+fn permissions_from_the_server_lock_cards_until_leaving_the_room() {
+    let mut world = World::new();
+    let game = BoardBuilder::new()
+        .tableau(0, 0, &["KS"])
+        .tableau(1, 0, &["QH"])
+        .waste(&["2C"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    let bobs_card = first_column(&world);
+
+    let bobs_card_id =
+        permissions::card_id(world.get_component::<SolitaireCard>(bobs_card).unwrap());
+    assert_eq!(bobs_card_id, "spades-13");
+    let card_owners = BTreeMap::from([(bobs_card_id, "bob".to_string())]);
+    assert!(receive(
+        &mut world,
+        WebSocketMessage::PermissionsChanged {
+            room_id: "room-1".to_string(),
+            host_id: Some("bob".to_string()),
+            spectators: Vec::new(),
+            card_owners,
+        }
+    ));
+    assert_eq!(
+        world.get_resource::<SharedBoard>().map(|seat| seat.role),
+        Some(SeatRole::Player)
+    );
+
+    // Bobのカードは選べず、動かす手も断られ、状態ではlockedになる
+    assert!(!selection::select(&mut world, bobs_card));
+    let move_bobs_card = ActionPayload::MoveCard {
+        from: MoveLocation {
+            location: CardLocation::Tableau,
+            index: 0,
+        },
+        to: MoveLocation {
+            location: CardLocation::Foundation,
+            index: 0,
+        },
+        count: 1,
+    };
+    assert!(validate(&world, move_bobs_card.clone())
+        .unwrap_err()
+        .contains("bob"));
+    let state = ClientState::from_world(&world, Some(game));
+    assert!(state.piles.tableau[0][0].locked);
+    assert!(!state.piles.tableau[1][0].locked);
+
+    // デッキが空のときに引く操作は山札の戻しなので、ホストでないと断られる
+    assert!(validate(&world, ActionPayload::DrawCard).is_err());
+
+    // 他のプレイヤーが抜けても権限は残り、自分が抜けると外れる
+    let leave = |player_id: &str| WebSocketMessage::LeaveRoom {
+        room_id: "room-1".to_string(),
+        player_id: player_id.to_string(),
+    };
+    assert!(!receive(&mut world, leave("bob")));
+    assert!(receive(&mut world, leave("alice")));
+    assert!(world.get_resource::<SharedBoard>().is_none());
+    assert!(validate(&world, ActionPayload::DrawCard).is_ok());
+    assert!(selection::select(&mut world, bobs_card));
+}
@@ -0,0 +1,165 @@
+// =============================================================================
+// パワーアップのテスト
+// =============================================================================
+// クールダウン中・使用済みのパワーアップが断られること、相手のタイムの加算が
+// ランク外の結果に足されること、不正なパワーアップのGameActionが検証で弾かれること、
+// 送り返されたパワーアップで裏向きのカードを覗き・山札をシャッフルできることを確認します。
+//
+// 実行方法：cargo test --test power_up
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::power_up::{self, PowerUp, PowerUpTracker};
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use ecs_wasm_solitaire::solitaire::{CardLocation, SolitaireCard, SolitaireManager, SolitaireType};
+use serde_json::json;
+
+/// パワーアップのGameAction
+fn power_up_action(player_id: &str, power_up: PowerUp, x: Option<f64>) -> WebSocketMessage {
+    WebSocketMessage::GameAction {
+        player_id: player_id.to_string(),
+        player_name: player_id.to_string(),
+        action: power_up.action(),
+        x,
+        y: None,
+        timestamp: 0,
+    }
+}
+
+/// 届いた通知の文章
+fn notifications(world: &mut World) -> Vec<String> {
+    world
+        .get_resource_mut::<EventQueue>()
+        .expect("イベントキューがある")
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::Notification { message, .. } => Some(message),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn cooldowns_and_penalties_are_tracked_per_race() {
+    let mut tracker = PowerUpTracker::new();
+    let players = vec!["alice".to_string(), "bob".to_string(), "carol".to_string()];
+
+    // 相手のタイムの加算は自分以外の全員に足され、クールダウンが明けるまで使えない
+    tracker
+        .use_power_up("alice", PowerUp::TimePenalty, &players, 0)
+        .unwrap();
+    let cooling = tracker.use_power_up("alice", PowerUp::TimePenalty, &players, 59_000);
+    assert!(cooling.unwrap_err().contains("あと1秒"));
+    tracker
+        .use_power_up("alice", PowerUp::TimePenalty, &players, 60_000)
+        .unwrap();
+    assert_eq!(tracker.time_penalty("alice"), 0);
+    assert_eq!(tracker.time_penalty("bob"), 60);
+
+    // 無料のシャッフルは1レースに1回だけ
+    tracker
+        .use_power_up("bob", PowerUp::FreeShuffle, &players, 0)
+        .unwrap();
+    assert!(tracker
+        .use_power_up("bob", PowerUp::FreeShuffle, &players, 600_000)
+        .is_err());
+    tracker.reset();
+    assert!(tracker
+        .use_power_up("bob", PowerUp::FreeShuffle, &players, 0)
+        .is_ok());
+    assert_eq!(tracker.time_penalty("bob"), 0);
+
+    // ランク外の結果にはタイムが足される
+    let mut result = json!({ "seed": 1, "duration_seconds": 100, "outcome": "Won" });
+    assert!(!power_up::is_unranked(&result));
+    power_up::mark_unranked(&mut result, 60);
+    assert_eq!(result["duration_seconds"], 160);
+    assert_eq!(result["time_penalty_seconds"], 60);
+    assert!(power_up::is_unranked(&result));
+
+    // 名前の不明なパワーアップと、列を指定しない覗き見は検証で弾く
+    let mut unknown = power_up_action("alice", PowerUp::Peek, Some(0.0));
+    if let WebSocketMessage::GameAction { action, .. } = &mut unknown {
+        *action = "power_up:teleport".to_string();
+    }
+    assert!(unknown.validate().is_err());
+    assert!(power_up_action("alice", PowerUp::Peek, None)
+        .validate()
+        .is_err());
+    assert!(power_up_action("alice", PowerUp::Peek, Some(7.0))
+        .validate()
+        .is_err());
+    assert!(power_up_action("alice", PowerUp::Peek, Some(6.0))
+        .validate()
+        .is_ok());
+}
+
+#[test]
+fn confirmed_power_ups_peek_and_shuffle_the_own_board() {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 3);
+
+    // 覗いてもカードはめくられない
+    let hidden = power_up::peek(&world, 6).expect("7列目には裏向きのカードがある");
+    assert!(!hidden.is_face_up);
+    assert!(power_up::peek(&world, 0).is_err());
+    assert!(power_up::apply(
+        &mut world,
+        Some("me"),
+        &power_up_action("me", PowerUp::Peek, Some(6.0))
+    ));
+    let peeked = notifications(&mut world);
+    assert_eq!(peeked.len(), 1);
+    assert!(peeked[0].contains(hidden.suit.symbol()));
+    assert_eq!(
+        world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.location_type == CardLocation::Tableau && card.is_face_up)
+            .count(),
+        7
+    );
+
+    // シャッフルはデッキとウェイストをまとめて裏向きのデッキに戻す
+    SolitaireManager::draw_card(&mut world).unwrap();
+    let stock = |world: &World| {
+        let mut cards: Vec<(u32, String)> = world
+            .query::<SolitaireCard>()
+            .filter(|(_, card)| card.location_type == CardLocation::Deck)
+            .map(|(_, card)| {
+                (
+                    card.position_in_location,
+                    format!("{}{}", card.suit.symbol(), card.rank.display()),
+                )
+            })
+            .collect();
+        cards.sort();
+        cards
+    };
+    let before = stock(&world);
+    assert!(power_up::apply(
+        &mut world,
+        Some("me"),
+        &power_up_action("me", PowerUp::FreeShuffle, None)
+    ));
+    let after = stock(&world);
+    assert_eq!(after.len(), before.len() + 1);
+    assert_ne!(after[..before.len()], before[..]);
+    assert_eq!(notifications(&mut world).len(), 1);
+
+    // 相手のシャッフルは自分の盤面を変えず、相手のタイムの加算だけを知らせる
+    assert!(!power_up::apply(
+        &mut world,
+        Some("me"),
+        &power_up_action("rival", PowerUp::FreeShuffle, None)
+    ));
+    assert_eq!(stock(&world), after);
+    assert!(power_up::apply(
+        &mut world,
+        Some("me"),
+        &power_up_action("rival", PowerUp::TimePenalty, None)
+    ));
+    assert!(notifications(&mut world)[0].contains("+30秒"));
+}
@@ -0,0 +1,101 @@
+// =============================================================================
+// 練習モードのテスト
+// =============================================================================
+// 進行中のゲームの好きな手数の局面から練習を分岐でき、元のゲームは変わらないこと、
+// 練習では何回でも元に戻せて、保存・実績の対象にならないことを確認します。
+//
+// 実行方法：cargo test --test practice
+// =============================================================================
+
+use ecs_wasm_solitaire::practice::PracticeGame;
+use ecs_wasm_solitaire::rng::Rng;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::scenario::Scenario;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+
+/// シードを固定したゲームを始め、指定した手数だけ自動で打つ
+///
+/// # 戻り値
+/// ランタイムと、配られた直後から各手を打った後までの局面
+fn played_game(moves: usize) -> (GameRuntime, Vec<Scenario>) {
+    let mut rt = GameRuntime::new();
+    rt.world.insert_resource(Rng::new(11));
+    rt.start_game(SolitaireType::Klondike);
+    rt.skip_animations();
+    let mut positions = vec![Scenario::from_world(&rt.world)];
+    for _ in 0..moves {
+        rt.auto_play_one_move().expect("打てる手がある");
+        rt.skip_animations();
+        positions.push(Scenario::from_world(&rt.world));
+    }
+    (rt, positions)
+}
+
+#[test]
+fn practice_forks_from_any_move_without_touching_the_game() {
+    let (mut rt, positions) = played_game(4);
+    let original = positions[4].clone();
+    let move_count = rt.game_state().expect("ゲームがある").move_count;
+
+    for (move_index, position) in positions.iter().enumerate() {
+        let mut sandbox = rt.fork_practice(move_index).expect("分岐できる");
+        sandbox.skip_animations();
+        assert_eq!(&Scenario::from_world(&sandbox.world), position);
+        assert!(sandbox.is_practice());
+        assert_eq!(sandbox.game_state().expect("ゲームがある").move_count, 0);
+        let game = sandbox.game_entity.expect("ゲームがある");
+        assert_eq!(
+            sandbox
+                .world
+                .get_component::<PracticeGame>(game)
+                .map(|practice| practice.forked_at_move),
+            Some(move_index)
+        );
+    }
+
+    // 元のゲームはそのまま進められ、練習ではない
+    let beyond = rt.fork_practice(5).err().expect("5手目はない");
+    assert!(beyond.contains("5手目はありません"));
+    assert!(!rt.is_practice());
+    assert_eq!(Scenario::from_world(&rt.world), original);
+    assert_eq!(
+        rt.game_state().expect("ゲームがある").move_count,
+        move_count
+    );
+    assert!(rt.undo_practice_move().is_err());
+}
+
+#[test]
+fn practice_undo_is_unlimited_and_the_game_is_not_saved() {
+    let (mut rt, positions) = played_game(2);
+    let mut sandbox = rt.fork_practice(1).expect("分岐できる");
+
+    let mut practice_positions = vec![Scenario::from_world(&sandbox.world)];
+    for _ in 0..3 {
+        sandbox.auto_play_one_move().expect("打てる手がある");
+        sandbox.skip_animations();
+        practice_positions.push(Scenario::from_world(&sandbox.world));
+    }
+
+    // 1手ずつ戻すと、打つ前の局面に順に戻る
+    for (undos, position) in (1..).zip(practice_positions.iter().rev().skip(1)) {
+        sandbox.undo_practice_move().expect("元に戻せる");
+        sandbox.skip_animations();
+        assert_eq!(&Scenario::from_world(&sandbox.world), position);
+        assert_eq!(
+            sandbox.game_state().expect("ゲームがある").undos_used,
+            undos
+        );
+    }
+    assert_eq!(practice_positions[0], positions[1]);
+    assert!(sandbox
+        .undo_practice_move()
+        .unwrap_err()
+        .contains("元に戻す手がありません"));
+
+    // 練習の局面からさらに分岐でき、練習のゲームは保存できない
+    sandbox.auto_play_one_move().expect("打てる手がある");
+    let nested = sandbox.fork_practice(0).expect("分岐できる");
+    assert_eq!(Scenario::from_world(&nested.world), positions[1]);
+    assert!(sandbox.save_game().unwrap_err().contains("練習"));
+}
@@ -0,0 +1,132 @@
+// =============================================================================
+// パズルモードのテスト
+// =============================================================================
+// 組み込みパズルがすべて正しい局面として作れること、目標を達成すると
+// 達成になり、手数を使い切ると失敗になることを確認します。
+//
+// 実行方法：cargo test --test puzzle
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::hint::{HintEngine, HintLocation};
+use ecs_wasm_solitaire::puzzle::{
+    builtin_puzzles, find_puzzle, PuzzleObjective, PuzzleProgress, PuzzleStatus, PuzzleSystem,
+};
+use ecs_wasm_solitaire::solitaire::{CardLocation, SolitaireManager};
+use std::collections::HashSet;
+
+/// パズルを開始してゲーム状態エンティティを返す
+fn start(world: &mut World, id: &str) -> Entity {
+    find_puzzle(id)
+        .expect("組み込みパズルがある")
+        .start(world)
+        .expect("組み込みパズルは開始できる")
+}
+
+/// パズル判定システムを1フレーム分実行して進み具合を返す
+fn progress(world: &mut World, entity: Entity) -> PuzzleProgress {
+    PuzzleSystem.update(world, 0.016);
+    world
+        .get_component::<PuzzleProgress>(entity)
+        .expect("パズルのゲームには進み具合がある")
+        .clone()
+}
+
+#[test]
+fn builtin_puzzles_start_and_are_unsolved() {
+    let puzzles = builtin_puzzles();
+    assert!(!puzzles.is_empty());
+
+    let ids: HashSet<&str> = puzzles.iter().map(|puzzle| puzzle.id.as_str()).collect();
+    assert_eq!(ids.len(), puzzles.len(), "パズルIDは重複しない");
+
+    for puzzle in &puzzles {
+        let mut world = World::new();
+        let entity = puzzle
+            .start(&mut world)
+            .expect("組み込みパズルは開始できる");
+        let progress = progress(&mut world, entity);
+        assert_eq!(progress.status, PuzzleStatus::InProgress, "{}", puzzle.id);
+        assert_eq!(progress.moves_used, 0);
+        assert_eq!(progress.moves_remaining(), puzzle.objective.move_limit());
+    }
+}
+
+#[test]
+fn winning_within_the_limit_solves_the_puzzle() {
+    let mut world = World::new();
+    let entity = start(&mut world, "finishing_touch");
+
+    while progress(&mut world, entity).status == PuzzleStatus::InProgress {
+        let hint = HintEngine::find_hint(&world).expect("組札へ置く手がある");
+        assert!(HintEngine::apply(&mut world, &hint));
+    }
+
+    let progress = progress(&mut world, entity);
+    assert_eq!(progress.status, PuzzleStatus::Solved);
+    assert_eq!(progress.moves_used, 3);
+}
+
+#[test]
+fn freeing_the_card_solves_the_puzzle() {
+    let mut world = World::new();
+    let entity = start(&mut world, "free_the_spade_ace");
+    let tableau = |index| HintLocation {
+        location: CardLocation::Tableau,
+        index,
+    };
+    let foundation = HintLocation {
+        location: CardLocation::Foundation,
+        index: 0,
+    };
+
+    HintEngine::move_cards(&mut world, tableau(0), 2, tableau(1)).expect("9♦8♣を10♠へ");
+    HintEngine::move_cards(&mut world, tableau(0), 1, tableau(2)).expect("6♥を7♣へ");
+    assert_eq!(
+        progress(&mut world, entity).status,
+        PuzzleStatus::InProgress
+    );
+
+    HintEngine::move_cards(&mut world, tableau(0), 1, foundation).expect("♠Aを組札へ");
+    let progress = progress(&mut world, entity);
+    assert_eq!(progress.status, PuzzleStatus::Solved);
+    assert_eq!(progress.moves_used, 3);
+}
+
+#[test]
+fn running_out_of_moves_fails_the_puzzle() {
+    let mut world = World::new();
+    let entity = start(&mut world, "from_the_deck");
+
+    // 引くだけで手数を使い切る（ウェイストの戻しは手数に数えない）
+    for _ in 0..10 {
+        if progress(&mut world, entity).status != PuzzleStatus::InProgress {
+            break;
+        }
+        SolitaireManager::draw_card(&mut world).expect("デッキかウェイストにカードがある");
+    }
+
+    let progress = progress(&mut world, entity);
+    assert_eq!(progress.status, PuzzleStatus::Failed);
+    assert_eq!(progress.moves_used, 5);
+    assert_eq!(progress.moves_remaining(), 0);
+}
+
+#[test]
+fn invalid_objectives_are_rejected() {
+    let mut puzzle = find_puzzle("free_the_spade_ace").expect("組み込みパズルがある");
+    puzzle.objective = PuzzleObjective::FreeCard {
+        card: "ZZ".to_string(),
+        moves: 5,
+    };
+    let mut world = World::new();
+    assert!(puzzle.start(&mut world).is_err());
+    assert_eq!(
+        world.entity_count(),
+        0,
+        "拒否された場合はワールドを変更しない"
+    );
+
+    puzzle.objective = PuzzleObjective::WinWithin { moves: 0 };
+    assert!(puzzle.start(&mut world).is_err());
+}
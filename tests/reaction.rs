@@ -0,0 +1,101 @@
+// =============================================================================
+// クイックリアクションのテスト
+// =============================================================================
+// サーバーから届いたリアクションが送信者ごとに1つだけ表示され、
+// 数秒経つと消えること、決められた絵文字以外は受け付けないことを確認します。
+//
+// 実行方法：cargo test --test reaction
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{System, World};
+use ecs_wasm_solitaire::network::{MessageProcessingSystem, MessageType, NetworkManager};
+use ecs_wasm_solitaire::protocol::{Emote, WebSocketMessage};
+use ecs_wasm_solitaire::reaction::{self, ReactionSystem, REACTION_DISPLAY_SECONDS};
+
+/// サーバーから届いたリアクションを受信キューに入れる
+fn receive(world: &mut World, payload: &str) {
+    NetworkManager::send_message(
+        world,
+        MessageType::Reaction,
+        payload.to_string(),
+        None,
+        None,
+    );
+}
+
+/// 表示中のリアクションを(送信者ID, 絵文字)として取り出す
+fn shown(world: &World) -> Vec<(String, Emote)> {
+    reaction::active(world)
+        .into_iter()
+        .map(|reaction| (reaction.player_id, reaction.emote))
+        .collect()
+}
+
+#[test]
+fn received_reactions_show_one_per_player() {
+    let mut world = World::new();
+    receive(
+        &mut world,
+        r#"{"type": "Reaction", "player_id": "p2", "emote": "thumbs_up"}"#,
+    );
+    receive(
+        &mut world,
+        r#"{"type": "Reaction", "player_id": "watcher", "emote": "snail"}"#,
+    );
+    MessageProcessingSystem.update(&mut world, 0.016);
+    assert_eq!(
+        shown(&world),
+        vec![
+            ("p2".to_string(), Emote::ThumbsUp),
+            ("watcher".to_string(), Emote::Snail),
+        ]
+    );
+
+    // 同じプレイヤーの新しいリアクションは前のものを置き換える
+    receive(
+        &mut world,
+        r#"{"type": "Reaction", "player_id": "p2", "emote": "party"}"#,
+    );
+    MessageProcessingSystem.update(&mut world, 0.016);
+    assert_eq!(
+        shown(&world),
+        vec![
+            ("p2".to_string(), Emote::Party),
+            ("watcher".to_string(), Emote::Snail),
+        ]
+    );
+}
+
+#[test]
+fn reactions_disappear_after_a_few_seconds() {
+    let mut world = World::new();
+    reaction::show(&mut world, "p2", Emote::Surprised);
+
+    ReactionSystem.update(&mut world, REACTION_DISPLAY_SECONDS - 0.5);
+    assert_eq!(shown(&world).len(), 1, "まだ表示中");
+    assert!(reaction::active(&world)[0].remaining_seconds <= 0.5);
+
+    ReactionSystem.update(&mut world, 0.5);
+    assert!(shown(&world).is_empty(), "時間切れで消える");
+    assert_eq!(world.entities().len(), 0, "エンティティごと消える");
+}
+
+#[test]
+fn only_predefined_emotes_are_accepted() {
+    assert!(WebSocketMessage::parse(
+        r#"{"type": "Reaction", "player_id": "p2", "emote": "free text"}"#
+    )
+    .is_err());
+
+    // 不正なリアクションは表示しない
+    let mut world = World::new();
+    receive(
+        &mut world,
+        r#"{"type": "Reaction", "player_id": "p2", "emote": "skull"}"#,
+    );
+    MessageProcessingSystem.update(&mut world, 0.016);
+    assert!(shown(&world).is_empty());
+
+    let emojis: Vec<&str> = Emote::ALL.iter().map(Emote::as_emoji).collect();
+    assert_eq!(emojis, vec!["👍", "😮", "🐌", "🎉"]);
+}
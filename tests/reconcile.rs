@@ -0,0 +1,136 @@
+// =============================================================================
+// サーバーの盤面に合わせ直すときのアニメーションのテスト
+// =============================================================================
+// 手元で進めた盤面をサーバーの正しい盤面に合わせ直すと、位置の違うカードが
+// 手元の位置から約0.2秒で正しい位置へ動くこと、スコアの修正が移動の得点として
+// 送られないこと、手元で記録済みのゲーム結果で通算成績を二重に更新しないことを確認します。
+// 視差効果を減らす設定では、動かさずに正しい位置へそのまま置くことも確認します。
+//
+// 実行方法：cargo test --test reconcile
+// =============================================================================
+
+use ecs_wasm_solitaire::events::GameEvent;
+use ecs_wasm_solitaire::game::AnimationSettings;
+use ecs_wasm_solitaire::reconcile::{ReconcileTween, RECONCILE_SECONDS};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::save_game::SavedGame;
+use ecs_wasm_solitaire::solitaire::{SolitaireCard, SolitaireGameState, SolitaireType};
+use ecs_wasm_solitaire::storage;
+
+/// ゲームを始めたランタイムと、その時点のサーバーの盤面
+fn started_game() -> (GameRuntime, SavedGame) {
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    let game = rt.start_game(SolitaireType::Klondike);
+    let snapshot = SavedGame::capture(&rt.world, game).expect("盤面を記録できる");
+    (rt, snapshot)
+}
+
+#[test]
+fn mismatched_cards_slide_back_to_the_authoritative_positions() {
+    let (mut rt, snapshot) = started_game();
+
+    // 手元では1手進めたが、サーバーでは受け付けられなかった
+    rt.auto_play_one_move()
+        .expect("最初の局面には指せる手がある");
+    for _ in 0..30 {
+        rt.update(0.05);
+    }
+    rt.drain_events();
+    let predicted_score = rt.game_state().expect("ゲーム中").score;
+
+    let cards_moved = rt.reconcile(&snapshot).expect("正しい盤面に合わせ直せる");
+    assert!(cards_moved > 0);
+    let (entity, tween) = rt
+        .world
+        .query::<ReconcileTween>()
+        .map(|(entity, tween)| (entity, *tween))
+        .next()
+        .expect("位置の違うカードは手元の位置から動く");
+    let card = rt.world.get_component::<SolitaireCard>(entity).unwrap();
+    assert_eq!(
+        (card.display_x, card.display_y),
+        (tween.from_x, tween.from_y)
+    );
+    let target = (card.target_x, card.target_y);
+
+    // 途中では手元の位置と正しい位置の間にいる
+    rt.update(RECONCILE_SECONDS / 2.0);
+    let card = rt.world.get_component::<SolitaireCard>(entity).unwrap();
+    assert_ne!(
+        (card.display_x, card.display_y),
+        (tween.from_x, tween.from_y)
+    );
+    assert_ne!((card.display_x, card.display_y), target);
+
+    rt.update(RECONCILE_SECONDS);
+    let card = rt.world.get_component::<SolitaireCard>(entity).unwrap();
+    assert_eq!((card.display_x, card.display_y), target);
+    assert!(!card.is_animating);
+    assert_eq!(rt.world.query::<ReconcileTween>().count(), 0);
+
+    // 修正は1回だけ知らせ、スコア・手数の変化としては送らない
+    let events = rt.drain_events();
+    assert!(events.contains(&GameEvent::StateReconciled {
+        score: 0,
+        previous_score: predicted_score,
+        cards_moved,
+    }));
+    assert!(!events.iter().any(|event| matches!(
+        event,
+        GameEvent::ScoreChanged { .. } | GameEvent::MovesChanged { .. }
+    )));
+}
+
+#[test]
+fn results_already_counted_locally_are_not_counted_again() {
+    let (mut rt, _) = started_game();
+
+    // 手元でゲームを終え、通算成績を更新する
+    let state = rt.game_state_mut().expect("ゲーム中");
+    state.is_completed = true;
+    state.end_time = Some(state.start_time);
+    rt.update(0.016);
+    assert!(rt.game_result().is_some());
+    let games_played = rt.achievements().expect("成績がある").stats.games_played;
+
+    // サーバーの盤面も終わっている
+    let game = rt
+        .world
+        .query::<SolitaireGameState>()
+        .map(|(entity, _)| entity)
+        .next()
+        .expect("ゲーム状態がある");
+    let snapshot = SavedGame::capture(&rt.world, game).expect("盤面を記録できる");
+    rt.reconcile(&snapshot).expect("正しい盤面に合わせ直せる");
+    rt.update(0.016);
+
+    assert!(rt.game_result().is_some());
+    assert_eq!(
+        rt.achievements().expect("成績がある").stats.games_played,
+        games_played
+    );
+}
+
+#[test]
+fn reduced_motion_puts_mismatched_cards_in_place_without_sliding() {
+    let (mut rt, snapshot) = started_game();
+    rt.set_animation_settings(AnimationSettings {
+        reduced_motion: true,
+        ..AnimationSettings::default()
+    })
+    .expect("設定を変えられる");
+
+    rt.auto_play_one_move()
+        .expect("最初の局面には指せる手がある");
+    for _ in 0..30 {
+        rt.update(0.05);
+    }
+    let cards_moved = rt.reconcile(&snapshot).expect("正しい盤面に合わせ直せる");
+    assert!(cards_moved > 0);
+    assert_eq!(rt.world.query::<ReconcileTween>().count(), 0);
+    assert!(rt
+        .world
+        .query::<SolitaireCard>()
+        .all(|(_, card)| !card.is_animating));
+}
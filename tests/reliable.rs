@@ -0,0 +1,78 @@
+// =============================================================================
+// 確実な配送のテスト
+// =============================================================================
+// 受け取りの確認（Ack）が届かないメッセージが間隔を倍にしながら再送され、
+// 上限の回数で諦めること、確認が届いたメッセージは再送しないこと、
+// 最近届いたメッセージIDの重複が除外され、古いIDは忘れることを確認します。
+//
+// 実行方法：cargo test --test reliable
+// =============================================================================
+
+use ecs_wasm_solitaire::reliable::{
+    retry_delay_ms, DuplicateFilter, ReliableSender, MAX_RESENDS, MAX_RETRY_DELAY_MS, RETRY_BASE_MS,
+};
+
+#[test]
+fn retry_delay_doubles_up_to_the_limit() {
+    assert_eq!(retry_delay_ms(0), RETRY_BASE_MS);
+    assert_eq!(retry_delay_ms(1), RETRY_BASE_MS * 2.0);
+    assert_eq!(retry_delay_ms(2), RETRY_BASE_MS * 4.0);
+    assert_eq!(retry_delay_ms(100), MAX_RETRY_DELAY_MS);
+}
+
+#[test]
+fn unacked_messages_are_resent_with_backoff_until_given_up() {
+    let mut sender = ReliableSender::new();
+    sender.track("m1".to_string(), "text-1".to_string());
+    assert!(
+        sender.due(1_000_000.0).resend.is_empty(),
+        "送る前は再送しない"
+    );
+
+    sender.mark_sent(0.0);
+    let mut now = 0.0;
+    for resends in 0..MAX_RESENDS {
+        now += retry_delay_ms(resends);
+        assert!(
+            sender.due(now - 1.0).resend.is_empty(),
+            "{}回目の再送の前",
+            resends + 1
+        );
+        assert_eq!(sender.due(now).resend, ["text-1"]);
+    }
+
+    now += retry_delay_ms(MAX_RESENDS);
+    let retries = sender.due(now);
+    assert!(retries.resend.is_empty());
+    assert_eq!(retries.given_up, ["m1"]);
+    assert_eq!(sender.pending_count(), 0);
+}
+
+#[test]
+fn acked_messages_are_not_resent() {
+    let mut sender = ReliableSender::new();
+    sender.track("m1".to_string(), "text-1".to_string());
+    sender.track("m2".to_string(), "text-2".to_string());
+    sender.mark_sent(0.0);
+
+    assert!(sender.ack("m1"));
+    assert!(!sender.ack("m1"), "重複したAck");
+    assert!(!sender.ack("unknown"));
+    assert_eq!(sender.due(RETRY_BASE_MS).resend, ["text-2"]);
+
+    sender.clear();
+    assert_eq!(sender.pending_count(), 0);
+}
+
+#[test]
+fn duplicate_filter_remembers_only_recent_ids() {
+    let mut filter = DuplicateFilter::new(2);
+    assert!(filter.is_new("a"));
+    assert!(!filter.is_new("a"));
+    assert!(filter.is_new("b"));
+    assert!(filter.is_new("c"));
+
+    // 古いIDは忘れる
+    assert!(filter.is_new("a"));
+    assert!(!filter.is_new("c"));
+}
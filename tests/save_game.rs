@@ -0,0 +1,154 @@
+// =============================================================================
+// ゲームの保存データと形式の変換のテスト
+// =============================================================================
+// tests/fixtures/save_v<バージョン>.jsonに残した各バージョンの保存データが
+// 現在のアプリで読み込めて盤面を復元できること、変換が古いバージョンから
+// 1つずつ順番に行われること、新しすぎる・バージョンのない保存データを拒否すること、
+// 途中のゲームが中断したゲームの一覧に加わり、続きから始めたり消したりできることを確認します。
+//
+// フィクスチャは各バージョンのアプリが実際に保存した形のまま残し、書き換えません。
+// 形式を変えた場合は新しいフィクスチャとテストを追加してください。
+//
+// 実行方法：cargo test --test save_game
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::save_game::{self, MigrationRegistry, SavedGame, SAVE_SCHEMA_VERSION};
+use ecs_wasm_solitaire::scenario::Scenario;
+use ecs_wasm_solitaire::solitaire::{MoveLog, SolitaireGameState, SolitaireType};
+use ecs_wasm_solitaire::storage;
+use serde_json::{json, Value};
+
+/// バージョン1の保存データ（シード7のゲームを5手進めたところ）
+const SAVE_V1: &str = include_str!("fixtures/save_v1.json");
+
+/// バージョン2の保存データ（バージョン1の続きを、裏向きのカードが表向きになるまで進めたところ）
+const SAVE_V2: &str = include_str!("fixtures/save_v2.json");
+
+#[test]
+fn version_1_saves_restore_the_board() {
+    let saved = SavedGame::from_json(SAVE_V1).expect("バージョン1の保存データを読み込める");
+    assert_eq!(saved.schema_version, SAVE_SCHEMA_VERSION);
+    assert_eq!((saved.state.seed, saved.state.move_count), (7, 5));
+
+    let mut world = World::new();
+    let game = saved.restore(&mut world).expect("盤面を復元できる");
+    assert_eq!(Scenario::from_world(&world), saved.board);
+
+    let state = world
+        .get_component::<SolitaireGameState>(game)
+        .expect("ゲーム状態を復元できる");
+    assert_eq!((state.score, state.move_count), (10, 5));
+    let log = world
+        .get_component::<MoveLog>(game)
+        .expect("移動の記録を復元できる");
+    assert_eq!(log.moves, saved.moves);
+
+    // 保存し直しても同じ内容になる
+    let again = SavedGame::capture(&world, game).expect("復元したゲームを保存できる");
+    assert_eq!(again.board, saved.board);
+    assert_eq!(again.moves, saved.moves);
+
+    // バージョン1の移動の記録は、どれも表向きにしていない手として読み込む
+    assert!(saved.moves.iter().all(|record| !record.revealed));
+}
+
+#[test]
+fn version_2_saves_keep_revealed_moves() {
+    let saved = SavedGame::from_json(SAVE_V2).expect("バージョン2の保存データを読み込める");
+    assert_eq!(saved.schema_version, SAVE_SCHEMA_VERSION);
+    assert_eq!((saved.state.score, saved.state.move_count), (35, 14));
+    assert!(saved.moves.last().expect("移動の記録がある").revealed);
+
+    let mut world = World::new();
+    let game = saved.restore(&mut world).expect("盤面を復元できる");
+    assert_eq!(Scenario::from_world(&world), saved.board);
+    let log = world
+        .get_component::<MoveLog>(game)
+        .expect("移動の記録を復元できる");
+    assert_eq!(log.moves, saved.moves);
+}
+
+#[test]
+fn migrations_run_one_version_at_a_time() {
+    fn to_v2(data: &mut Value) -> Result<(), String> {
+        data["steps"] = json!(["2"]);
+        Ok(())
+    }
+    fn to_v3(data: &mut Value) -> Result<(), String> {
+        let steps = data["steps"]
+            .as_array_mut()
+            .ok_or("バージョン2の形ではありません")?;
+        steps.push(json!("3"));
+        Ok(())
+    }
+    let registry = MigrationRegistry::new(3)
+        .register(2, "手順3", to_v3)
+        .register(1, "手順2", to_v2);
+
+    let data: Value = serde_json::from_str(SAVE_V1).expect("フィクスチャはJSON");
+    let upgraded = registry.upgrade(data).expect("バージョン3まで変換できる");
+    assert_eq!(upgraded["schema_version"], json!(3));
+    assert_eq!(upgraded["steps"], json!(["2", "3"]));
+
+    // 途中のバージョンからも残りの変換だけを行う
+    let upgraded = registry
+        .upgrade(json!({ "schema_version": 2, "steps": [] }))
+        .expect("バージョン2から変換できる");
+    assert_eq!(upgraded["steps"], json!(["3"]));
+
+    // 変換が抜けている場合は読み込まない
+    let error = MigrationRegistry::new(3)
+        .register(1, "手順2", to_v2)
+        .upgrade(json!({ "schema_version": 1 }))
+        .unwrap_err();
+    assert!(error.contains("バージョン2"), "{}", error);
+}
+
+#[test]
+fn newer_or_unversioned_saves_are_rejected() {
+    let mut newer: Value = serde_json::from_str(SAVE_V1).expect("フィクスチャはJSON");
+    newer["schema_version"] = json!(SAVE_SCHEMA_VERSION + 1);
+    let error = SavedGame::from_json(&newer.to_string()).unwrap_err();
+    assert!(error.contains("新しい"), "{}", error);
+
+    let mut unversioned = newer;
+    unversioned
+        .as_object_mut()
+        .expect("フィクスチャはオブジェクト")
+        .remove("schema_version");
+    let error = SavedGame::from_json(&unversioned.to_string()).unwrap_err();
+    assert!(error.contains("schema_version"), "{}", error);
+}
+
+#[test]
+fn unfinished_games_are_listed_and_can_be_resumed_or_deleted() {
+    let saved = SavedGame::from_json(SAVE_V1).expect("バージョン1の保存データを読み込める");
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    rt.reconcile(&saved).expect("途中の盤面を作成できる");
+
+    // 途中のゲームを残したまま新しいゲームを始めると一覧に加わる
+    rt.start_game(SolitaireType::Klondike);
+    let info = save_game::list()
+        .into_iter()
+        .find(|info| info.saved_at >= saved.saved_at && info.move_count == 5 && info.score == 10)
+        .expect("中断したゲームが一覧にある");
+    assert_eq!(info.variant, SolitaireType::Klondike);
+    assert_eq!(info.thumbnail.tableau.len(), saved.board.tableau.len());
+    assert!(info.progress_percent <= 100);
+
+    // 続きから始めると一覧から外れ、1手も動かしていないゲームは一覧に加わらない
+    rt.resume_saved_game(&info.id).expect("中断したゲームを再開できる");
+    assert_eq!(Scenario::from_world(&rt.world), saved.board);
+    assert_eq!(rt.game_state().map(|state| state.move_count), Some(5));
+    assert!(save_game::list().iter().all(|entry| entry.id != info.id));
+    assert!(rt.resume_saved_game(&info.id).is_err());
+
+    // 消したゲームは一覧から外れる
+    let added = save_game::add_to_list(&saved).expect("一覧に加えられる");
+    assert_eq!(save_game::remove_from_list(&added.id), Ok(true));
+    assert_eq!(save_game::remove_from_list(&added.id), Ok(false));
+    assert!(save_game::load_from_list(&added.id).is_err());
+}
@@ -0,0 +1,163 @@
+// =============================================================================
+// 盤面シナリオのテスト
+// =============================================================================
+// BoardBuilderで組み立てた局面が正しくワールドに作られること、
+// 不正なシナリオが拒否されること、配り札の盤面をシナリオ（JSON・短い表記）として
+// 書き出して同じ局面を作り直せることを確認します。
+//
+// 実行方法：cargo test --test scenario
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::hint::HintEngine;
+use ecs_wasm_solitaire::scenario::{parse_card, BoardBuilder, Scenario};
+use ecs_wasm_solitaire::solitaire::{
+    CardLocation, CardRank, CardSuit, SolitaireCard, SolitaireManager, SolitaireType,
+};
+
+/// 指定したスートのAから`top`までの表記
+fn run(suit: char, top: usize) -> Vec<String> {
+    [
+        "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+    ][..top]
+        .iter()
+        .map(|rank| format!("{}{}", rank, suit))
+        .collect()
+}
+
+#[test]
+fn card_codes_accept_letters_and_symbols() {
+    assert_eq!(parse_card("AS"), Ok((CardSuit::Spades, CardRank::Ace)));
+    assert_eq!(parse_card("10h"), Ok((CardSuit::Hearts, CardRank::Ten)));
+    assert_eq!(parse_card("TD"), Ok((CardSuit::Diamonds, CardRank::Ten)));
+    assert_eq!(parse_card("Q♣"), Ok((CardSuit::Clubs, CardRank::Queen)));
+    assert!(parse_card("1S").is_err());
+    assert!(parse_card("KX").is_err());
+    assert!(parse_card("").is_err());
+}
+
+#[test]
+fn builder_places_cards_on_the_requested_piles() {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 1, &["7S", "KH", "QC"])
+        .foundation(2, &["AD", "2D"])
+        .waste(&["5C", "9H"])
+        .deck(&["3H"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+
+    let cards: Vec<SolitaireCard> = world
+        .query::<SolitaireCard>()
+        .map(|(_, card)| card.clone())
+        .collect();
+    assert_eq!(cards.len(), 8);
+
+    let find = |suit, rank| {
+        cards
+            .iter()
+            .find(|card| card.suit == suit && card.rank == rank)
+            .expect("置いたカードが存在する")
+    };
+    let bottom = find(CardSuit::Spades, CardRank::Seven);
+    assert_eq!(bottom.location_type, CardLocation::Tableau);
+    assert!(!bottom.is_face_up);
+    assert!(find(CardSuit::Clubs, CardRank::Queen).is_face_up);
+
+    let foundation = find(CardSuit::Diamonds, CardRank::Two);
+    assert_eq!(foundation.location_type, CardLocation::Foundation);
+    assert_eq!(foundation.position_in_location, 2);
+
+    assert_eq!(
+        find(CardSuit::Hearts, CardRank::Nine).position_in_location,
+        1
+    );
+    assert_eq!(
+        find(CardSuit::Hearts, CardRank::Three).location_type,
+        CardLocation::Deck
+    );
+}
+
+#[test]
+fn last_king_to_foundation_wins_the_game() {
+    let scenario = Scenario {
+        tableau: vec![vec!["KS".to_string()]],
+        foundations: vec![run('H', 13), run('D', 13), run('C', 13), run('S', 12)],
+        ..Scenario::default()
+    };
+    let mut world = World::new();
+    BoardBuilder::from_scenario(scenario)
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    assert!(!SolitaireManager::check_windows_solitaire_win(&world));
+
+    let hint = HintEngine::find_hint(&world).expect("キングを組札に置く手がある");
+    assert!(HintEngine::apply(&mut world, &hint));
+    assert!(SolitaireManager::check_windows_solitaire_win(&world));
+}
+
+#[test]
+fn invalid_scenarios_are_rejected() {
+    let rejects = |builder: BoardBuilder| {
+        let mut world = World::new();
+        assert!(builder.build(&mut world).is_err());
+        assert_eq!(
+            world.entity_count(),
+            0,
+            "拒否された場合はワールドを変更しない"
+        );
+    };
+
+    // 同じカードが2枚
+    rejects(BoardBuilder::new().tableau(0, 0, &["AS"]).deck(&["AS"]));
+    // 組札の順番が違う
+    rejects(BoardBuilder::new().foundation(0, &["AS", "3S"]));
+    // 組札のスートが混ざっている
+    rejects(BoardBuilder::new().foundation(0, &["AS", "2H"]));
+    // 裏向きの枚数が多すぎる
+    rejects(BoardBuilder::new().tableau(0, 2, &["AS"]));
+    // 列が多すぎる
+    rejects(BoardBuilder::new().tableau(7, 0, &["AS"]));
+    // 不明なカード
+    rejects(BoardBuilder::new().waste(&["ZZ"]));
+
+    assert!(BoardBuilder::from_json("{ not json").is_err());
+}
+
+#[test]
+fn dealt_board_round_trips_through_json() {
+    let mut dealt = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut dealt, SolitaireType::Klondike, 20250727);
+    let scenario = Scenario::from_world(&dealt);
+    assert_eq!(scenario.face_down, vec![0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(scenario.deck.len(), 24);
+
+    let json = serde_json::to_string(&scenario).expect("シナリオはJSONにできる");
+    let mut rebuilt = World::new();
+    BoardBuilder::from_json(&json)
+        .and_then(|builder| builder.build(&mut rebuilt))
+        .expect("書き出したシナリオを読み込める");
+
+    assert_eq!(Scenario::from_world(&rebuilt), scenario);
+}
+
+#[test]
+fn dealt_board_round_trips_through_the_compact_form() {
+    let mut dealt = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut dealt, SolitaireType::Klondike, 20250727);
+    let scenario = Scenario::from_world(&dealt);
+
+    let compact = scenario.to_compact().expect("配り札は短い表記にできる");
+    assert!(compact.len() < 200, "52枚でも短い: {}", compact);
+    assert_eq!(Scenario::from_compact(&compact), Ok(scenario));
+
+    let example = Scenario::from_compact("1:KSQH,0:TD/AH2H,,,/3C/4D9S").expect("例を読み込める");
+    assert_eq!(example.tableau, vec![vec!["KS", "QH"], vec!["10D"]]);
+    assert_eq!(example.face_down, vec![1, 0]);
+    assert_eq!(example.foundations[0], vec!["AH", "2H"]);
+    assert_eq!((example.waste.len(), example.deck.len()), (1, 2));
+
+    for invalid in ["", "0:KS/", "KS///", "0:KZ///", "0:K///"] {
+        assert!(Scenario::from_compact(invalid).is_err(), "{}", invalid);
+    }
+}
@@ -0,0 +1,163 @@
+// =============================================================================
+// システムの有効/無効の切り替えとスケジュールのテスト
+// =============================================================================
+// 無効にしたシステムが有効に戻すまで実行されないこと、登録されていない名前は
+// 切り替えられないこと、ランタイムでアニメーションを止めて再開できること、
+// スケジュールに含まれないシステムが実行されず、観戦中は入力を処理しないことを確認します。
+//
+// 実行方法：cargo test --test scheduler
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{System, SystemScheduler, World};
+use ecs_wasm_solitaire::input::{InputEvent, PointerEvent, PointerKind};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::schedule::{Schedule, ViewMode};
+use ecs_wasm_solitaire::solitaire::{SolitaireCard, SolitaireType};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// 実行された回数を数えるシステム
+struct CountingSystem(Arc<AtomicU32>);
+
+impl System for CountingSystem {
+    fn update(&mut self, _world: &mut World, _delta_time: f64) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// アニメーション中のカードの表示座標
+fn animating_positions(rt: &GameRuntime) -> Vec<(f32, f32)> {
+    rt.world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| card.is_animating)
+        .map(|(_, card)| (card.display_x, card.display_y))
+        .collect()
+}
+
+#[test]
+fn disabled_systems_are_skipped_until_enabled_again() {
+    let runs = Arc::new(AtomicU32::new(0));
+    let mut scheduler = SystemScheduler::new();
+    scheduler.add_system(CountingSystem(runs.clone()));
+    let mut world = World::new();
+
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(scheduler.is_enabled("CountingSystem"), Some(true));
+
+    assert!(scheduler.set_enabled("CountingSystem", false));
+    scheduler.update(&mut world, 0.016);
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(scheduler.disabled_systems(), vec!["CountingSystem"]);
+
+    // 計測中も無効のシステムは実行しない
+    scheduler.set_profiling(true);
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+    assert!(scheduler.set_enabled("CountingSystem", true));
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+    assert!(scheduler.disabled_systems().is_empty());
+
+    // 登録されていない名前は切り替えられない
+    assert!(!scheduler.set_enabled("MissingSystem", false));
+    assert_eq!(scheduler.is_enabled("MissingSystem"), None);
+}
+
+#[test]
+fn animations_can_be_frozen_and_resumed_at_runtime() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+    assert!(rt.set_system_enabled("UnknownSystem", false).is_err());
+
+    // 配るアニメーションは順番待ちのシステムが動かす
+    for name in ["AnimationQueueSystem", "CardAnimationSystem"] {
+        rt.set_system_enabled(name, false)
+            .expect("アニメーションのシステムを止められる");
+    }
+    assert_eq!(
+        rt.debug_info().disabled_systems,
+        vec!["AnimationQueueSystem", "CardAnimationSystem"]
+    );
+
+    // 止めている間は、配っている途中のカードがその場で止まる
+    let frozen = animating_positions(&rt);
+    assert!(!frozen.is_empty());
+    for _ in 0..60 {
+        rt.update(0.05);
+    }
+    assert_eq!(animating_positions(&rt), frozen);
+
+    for name in ["AnimationQueueSystem", "CardAnimationSystem"] {
+        rt.set_system_enabled(name, true)
+            .expect("アニメーションのシステムを再開できる");
+    }
+    for _ in 0..60 {
+        rt.update(0.05);
+    }
+    assert!(animating_positions(&rt).is_empty());
+}
+
+#[test]
+fn systems_only_run_in_their_schedules() {
+    let everywhere = Arc::new(AtomicU32::new(0));
+    let playing = Arc::new(AtomicU32::new(0));
+    let mut scheduler = SystemScheduler::new();
+    scheduler.add_system(CountingSystem(everywhere.clone()));
+    scheduler.add_system_to(CountingSystem(playing.clone()), &["playing"]);
+    let mut world = World::new();
+
+    // 切り替える前はすべて実行する
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(scheduler.schedule(), None);
+
+    scheduler.set_schedule("replay");
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(scheduler.scheduled_systems(), vec!["CountingSystem"]);
+
+    scheduler.set_schedule("playing");
+    scheduler.update(&mut world, 0.016);
+    assert_eq!(everywhere.load(Ordering::SeqCst), 3);
+    assert_eq!(playing.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn spectating_does_not_process_player_input() {
+    let mut rt = GameRuntime::new();
+    assert_eq!(rt.schedule(), Schedule::MainMenu);
+    rt.update(0.016);
+    assert_eq!(rt.debug_info().schedule, Some("main_menu"));
+
+    rt.start_game(SolitaireType::Klondike);
+    rt.set_view_mode(ViewMode::Spectate);
+    rt.update(0.016);
+    assert_eq!(rt.debug_info().schedule, Some("spectating"));
+
+    // 観戦中のポインター操作は受け付けない
+    rt.push_pointer(PointerEvent {
+        kind: PointerKind::Down,
+        x: 30.0,
+        y: 160.0,
+        pointer_id: 0,
+    })
+    .expect("観戦中の操作は捨てるだけ");
+    assert_eq!(rt.world.query::<InputEvent>().count(), 0);
+    assert!(!rt.scheduler.scheduled_systems().contains(&"InputSystem"));
+
+    // 自分のゲームに戻ると受け付ける
+    rt.set_view_mode(ViewMode::Play);
+    rt.update(0.016);
+    assert_eq!(rt.schedule(), Schedule::Playing);
+    rt.push_pointer(PointerEvent {
+        kind: PointerKind::Down,
+        x: 30.0,
+        y: 160.0,
+        pointer_id: 0,
+    })
+    .expect("座標は有限");
+    assert_eq!(rt.world.query::<InputEvent>().count(), 1);
+    rt.update(0.016);
+    assert_eq!(rt.world.query::<InputEvent>().count(), 0);
+}
@@ -0,0 +1,157 @@
+// =============================================================================
+// スコアボードのテスト
+// =============================================================================
+// サーバーから届いたスコアの一覧・プロフィール・スコアの更新・表示名の変更・カードの裏面・
+// 切断・退室が他のプレイヤーのRemotePlayerに反映され、自分は一覧に含まれないこと、
+// 一覧がスコアの高い順に並ぶことを確認します。
+//
+// 実行方法：cargo test --test scoreboard
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::protocol::{PlayerProfile, ScoreboardEntry, WebSocketMessage};
+use ecs_wasm_solitaire::scoreboard::{self, RemoteConnection};
+use ecs_wasm_solitaire::theme::CardBack;
+
+const OWN_ID: Option<&str> = Some("me");
+
+fn entry(player_id: &str, score: u32) -> ScoreboardEntry {
+    ScoreboardEntry {
+        player_id: player_id.to_string(),
+        player_name: player_id.to_uppercase(),
+        color_index: 1,
+        score,
+        foundation_cards: 0,
+        card_back: None,
+    }
+}
+
+fn score_update(player_id: &str, score: u32, foundation_cards: u16) -> WebSocketMessage {
+    WebSocketMessage::ScoreUpdate {
+        room_id: "room".to_string(),
+        player_id: player_id.to_string(),
+        score,
+        foundation_cards,
+    }
+}
+
+/// スコアボードの(プレイヤーID, スコア)の一覧
+fn scores(world: &World) -> Vec<(String, u32)> {
+    scoreboard::players(world)
+        .into_iter()
+        .map(|player| (player.player_id, player.score))
+        .collect()
+}
+
+#[test]
+fn scoreboard_follows_room_members_scores_sorted_by_score() {
+    let mut world = World::new();
+    let joined = WebSocketMessage::Scoreboard {
+        room_id: "room".to_string(),
+        players: vec![entry("alice", 120), entry("me", 999)],
+    };
+    assert!(scoreboard::apply(&mut world, OWN_ID, &joined));
+    assert_eq!(
+        scores(&world),
+        [("alice".to_string(), 120)],
+        "自分は含めない"
+    );
+
+    // 後から参加したプレイヤーはプロフィールで届き、スコアは更新で届く
+    let profile = WebSocketMessage::PlayerProfile {
+        profile: PlayerProfile {
+            player_id: "bob".to_string(),
+            player_name: "Bob".to_string(),
+            color_index: 3,
+            rating: 1500,
+            games_rated: 0,
+            is_bot: false,
+        },
+        request_id: None,
+    };
+    scoreboard::apply(&mut world, OWN_ID, &profile);
+    scoreboard::apply(&mut world, OWN_ID, &score_update("bob", 200, 12));
+    assert!(!scoreboard::apply(
+        &mut world,
+        OWN_ID,
+        &score_update("me", 5, 0)
+    ));
+
+    let players = scoreboard::players(&world);
+    assert_eq!(players[0].player_id, "bob");
+    assert_eq!(players[0].player_name, "Bob");
+    assert_eq!(players[0].color_index, 3);
+    assert_eq!(players[0].foundation_cards, 12);
+    assert_eq!(players[1].player_id, "alice");
+
+    let renamed = WebSocketMessage::PlayerUpdated {
+        player_id: "alice".to_string(),
+        player_name: "Alicia".to_string(),
+        color_index: 5,
+    };
+    scoreboard::apply(&mut world, OWN_ID, &renamed);
+    assert_eq!(scoreboard::players(&world)[1].player_name, "Alicia");
+}
+
+#[test]
+fn disconnected_players_stay_until_they_leave_the_room() {
+    let mut world = World::new();
+    scoreboard::apply(&mut world, OWN_ID, &score_update("alice", 10, 1));
+    scoreboard::apply(&mut world, OWN_ID, &score_update("bob", 20, 2));
+
+    let left = WebSocketMessage::PlayerLeft {
+        player_id: "alice".to_string(),
+        player_name: "Alice".to_string(),
+    };
+    assert!(scoreboard::apply(&mut world, OWN_ID, &left));
+    let players = scoreboard::players(&world);
+    assert_eq!(players[1].connection, RemoteConnection::Disconnected);
+    assert_eq!(players[0].connection, RemoteConnection::Connected);
+
+    let leave = |player_id: &str| WebSocketMessage::LeaveRoom {
+        room_id: "room".to_string(),
+        player_id: player_id.to_string(),
+    };
+    scoreboard::apply(&mut world, OWN_ID, &leave("alice"));
+    assert_eq!(scores(&world), [("bob".to_string(), 20)]);
+
+    // 自分が退室したら一覧を空にする
+    scoreboard::apply(&mut world, OWN_ID, &leave("me"));
+    assert!(scoreboard::players(&world).is_empty());
+    assert_eq!(world.entity_count(), 0);
+}
+
+#[test]
+fn shared_card_backs_are_shown_on_the_scoreboard() {
+    let mut world = World::new();
+    let joined = WebSocketMessage::Scoreboard {
+        room_id: "room".to_string(),
+        players: vec![ScoreboardEntry {
+            card_back: Some(CardBack::Ocean),
+            ..entry("alice", 0)
+        }],
+    };
+    scoreboard::apply(&mut world, OWN_ID, &joined);
+    assert_eq!(
+        scoreboard::players(&world)[0].card_back,
+        Some(CardBack::Ocean)
+    );
+
+    let changed = |player_id: &str, card_back| WebSocketMessage::CardBackChanged {
+        room_id: "room".to_string(),
+        player_id: player_id.to_string(),
+        card_back,
+    };
+    assert!(scoreboard::apply(
+        &mut world,
+        OWN_ID,
+        &changed("alice", None)
+    ));
+    assert_eq!(scoreboard::players(&world)[0].card_back, None);
+    assert!(!scoreboard::apply(
+        &mut world,
+        OWN_ID,
+        &changed("me", Some(CardBack::Crimson))
+    ));
+    assert_eq!(scoreboard::players(&world).len(), 1);
+}
@@ -0,0 +1,190 @@
+// =============================================================================
+// 選択・強調表示のテスト
+// =============================================================================
+// 選択できるカードが同時に1枚だけであること、選択中のカードを置ける山に
+// ドロップ先のマーカーが付くこと（カードのない山の置き場所を含む）、
+// 強調表示が時間切れで外れることを確認します。
+//
+// 実行方法：cargo test --test selection
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::ClientState;
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::selection::{
+    self, DropTarget, HighlightSystem, Highlighted, PilePlaceholder, Selected, SelectionSystem,
+};
+use ecs_wasm_solitaire::solitaire::{CardLocation, CardRank, CardStack, CardSuit, SolitaireCard};
+
+/// 盤面を組み立てたワールドを作成
+fn world_with(board: BoardBuilder) -> (World, Entity) {
+    let mut world = World::new();
+    let game_entity = board.build(&mut world).expect("シナリオから盤面を作れる");
+    (world, game_entity)
+}
+
+/// 指定したカードのエンティティを探す
+fn card(world: &World, suit: CardSuit, rank: CardRank) -> Entity {
+    world
+        .query::<SolitaireCard>()
+        .find(|(_, card)| card.suit == suit && card.rank == rank)
+        .map(|(entity, _)| entity)
+        .expect("盤面にカードがある")
+}
+
+/// ドロップ先になっている山を(場所, 番号)で取得
+fn drop_targets(world: &World) -> Vec<(CardLocation, u32)> {
+    let mut targets: Vec<(CardLocation, u32)> = world
+        .query::<DropTarget>()
+        .filter_map(|(entity, _)| world.get_component::<CardStack>(entity))
+        .map(|stack| (stack.stack_type, stack.stack_index))
+        .collect();
+    targets.sort_by_key(|&(location, index)| (location as u32, index));
+    targets
+}
+
+#[test]
+fn only_one_card_is_selected_at_a_time() {
+    let board = BoardBuilder::new()
+        .tableau(0, 1, &["2C", "9D"])
+        .tableau(1, 0, &["10S"]);
+    let (mut world, _) = world_with(board);
+    let nine = card(&world, CardSuit::Diamonds, CardRank::Nine);
+    let ten = card(&world, CardSuit::Spades, CardRank::Ten);
+    let hidden = card(&world, CardSuit::Clubs, CardRank::Two);
+
+    assert!(selection::select(&mut world, nine));
+    assert!(selection::select(&mut world, ten));
+    assert_eq!(selection::selected_card(&world), Some(ten));
+    assert_eq!(world.query::<Selected>().count(), 1);
+
+    // 裏向きのカードは選択できず、今の選択も変わらない
+    assert!(!selection::select(&mut world, hidden));
+    assert_eq!(selection::selected_card(&world), Some(ten));
+
+    // 直接追加された選択は、システムが一番新しいものだけを残す
+    world.add_component(nine, Selected { order: 100 });
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(selection::selected_card(&world), Some(nine));
+    assert!(!world.has_component::<Selected>(ten));
+
+    // 裏返されたカードの選択は外れる
+    if let Some(card) = world.get_component_mut::<SolitaireCard>(nine) {
+        card.flip_down();
+    }
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(selection::selected_card(&world), None);
+}
+
+#[test]
+fn drop_targets_follow_the_selected_card() {
+    let (mut world, game_entity) = world_with(
+        BoardBuilder::new()
+            .tableau(0, 0, &["9D"])
+            .tableau(1, 0, &["10S"])
+            .tableau(2, 0, &["10H"])
+            .tableau(3, 0, &["KC"])
+            .foundation(0, &["AS"])
+            .waste(&["2S"]),
+    );
+
+    let nine = card(&world, CardSuit::Diamonds, CardRank::Nine);
+    selection::select(&mut world, nine);
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(drop_targets(&world), vec![(CardLocation::Tableau, 1)]);
+
+    // Kは空いている列すべてに置ける
+    let king = card(&world, CardSuit::Clubs, CardRank::King);
+    selection::select(&mut world, king);
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(
+        drop_targets(&world),
+        (4..7)
+            .map(|index| (CardLocation::Tableau, index))
+            .collect::<Vec<_>>()
+    );
+
+    // ウェイストの2♠は組札の♠Aの上に置ける
+    let two = card(&world, CardSuit::Spades, CardRank::Two);
+    selection::select(&mut world, two);
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(drop_targets(&world), vec![(CardLocation::Foundation, 0)]);
+
+    let state = ClientState::from_world(&world, Some(game_entity));
+    assert_eq!(state.piles.drop_targets.len(), 1);
+    assert!(state.piles.waste.iter().all(|view| view.selected));
+
+    selection::clear_selection(&mut world);
+    SelectionSystem.update(&mut world, 0.016);
+    assert!(drop_targets(&world).is_empty());
+}
+
+#[test]
+fn empty_piles_have_placeholders_marked_as_drop_targets() {
+    let (mut world, _) = world_with(BoardBuilder::new().tableau(0, 0, &["AH"]).tableau(
+        1,
+        0,
+        &["KC"],
+    ));
+
+    // 山ごとに1つ、山の一番下のカードの位置に置き場所がある
+    assert_eq!(
+        world.query::<PilePlaceholder>().count(),
+        world.query::<CardStack>().count()
+    );
+    for (_, placeholder) in world.query::<PilePlaceholder>() {
+        let stack = world
+            .get_component::<CardStack>(placeholder.stack)
+            .expect("置き場所は山に対応する");
+        assert_eq!(
+            (placeholder.location, placeholder.index),
+            (stack.stack_type, stack.stack_index)
+        );
+        assert_eq!((placeholder.x, placeholder.y), (stack.base_x, stack.base_y));
+    }
+
+    // ♥Aを選ぶと、空の組札4つの置き場所がドロップ先になる
+    let ace = card(&world, CardSuit::Hearts, CardRank::Ace);
+    selection::select(&mut world, ace);
+    SelectionSystem.update(&mut world, 0.016);
+    let mut marked: Vec<(CardLocation, u32)> = world
+        .query::<PilePlaceholder>()
+        .filter(|(entity, _)| world.has_component::<DropTarget>(*entity))
+        .map(|(_, placeholder)| (placeholder.location, placeholder.index))
+        .collect();
+    marked.sort_by_key(|&(location, index)| (location as u32, index));
+    assert_eq!(marked, drop_targets(&world));
+    assert_eq!(marked.len(), 4);
+
+    selection::clear_selection(&mut world);
+    SelectionSystem.update(&mut world, 0.016);
+    assert_eq!(world.query::<DropTarget>().count(), 0);
+}
+
+#[test]
+fn highlights_expire_after_their_duration() {
+    let board = BoardBuilder::new()
+        .tableau(0, 0, &["9D"])
+        .tableau(1, 0, &["10S"]);
+    let (mut world, game_entity) = world_with(board);
+    let nine = card(&world, CardSuit::Diamonds, CardRank::Nine);
+    let ten = card(&world, CardSuit::Spades, CardRank::Ten);
+
+    selection::highlight(&mut world, nine, Some(1.0));
+    selection::highlight(&mut world, ten, None);
+
+    HighlightSystem.update(&mut world, 0.6);
+    assert!(world.has_component::<Highlighted>(nine));
+    let state = ClientState::from_world(&world, Some(game_entity));
+    assert!(state.piles.tableau[0][0].highlighted);
+
+    HighlightSystem.update(&mut world, 0.6);
+    assert!(!world.has_component::<Highlighted>(nine));
+    assert!(
+        world.has_component::<Highlighted>(ten),
+        "時間を指定しない強調表示は外すまで続く"
+    );
+
+    selection::clear_highlights(&mut world);
+    assert_eq!(world.query::<Highlighted>().count(), 0);
+}
@@ -0,0 +1,83 @@
+// =============================================================================
+// 送信待ちのキューのテスト
+// =============================================================================
+// いっぱいのキューでは優先度の低いメッセージから捨て、同じ優先度同士は
+// 優先度ごとの捨て方に従うこと、送り直した数・捨てた数が数えられ、
+// 送り直せなかったメッセージが元の順番で戻ることを確認します。
+//
+// 実行方法：cargo test --test send_queue
+// =============================================================================
+
+use ecs_wasm_solitaire::network::MessagePriority;
+use ecs_wasm_solitaire::send_queue::{QueueMetrics, SendQueue};
+
+/// 溜まっているメッセージ（古い順）
+fn texts(queue: &mut SendQueue) -> Vec<String> {
+    let messages = queue.take_for_retry();
+    let texts = messages.iter().map(|(_, text)| text.clone()).collect();
+    queue.restore(messages);
+    texts
+}
+
+#[test]
+fn full_queue_drops_by_priority_and_policy() {
+    let mut queue = SendQueue::new(3);
+    assert!(queue.push(MessagePriority::Low, "cursor-1".to_string()));
+    assert!(queue.push(MessagePriority::Normal, "draw".to_string()));
+    assert!(queue.push(MessagePriority::Low, "cursor-2".to_string()));
+
+    // 優先度の低い一番古いメッセージが押し出される
+    assert!(queue.push(MessagePriority::Normal, "move".to_string()));
+    assert_eq!(texts(&mut queue), ["draw", "cursor-2", "move"]);
+
+    // 新しいカーソル位置は古いカーソル位置を押し出す
+    assert!(queue.push(MessagePriority::Low, "cursor-3".to_string()));
+    assert_eq!(texts(&mut queue), ["draw", "move", "cursor-3"]);
+
+    // 同じ優先度のゲームアクションは順番を崩さないよう後から来た方を捨てる
+    assert!(queue.push(MessagePriority::Normal, "flip".to_string()));
+    assert!(!queue.push(MessagePriority::Normal, "undo".to_string()));
+    assert_eq!(texts(&mut queue), ["draw", "move", "flip"]);
+
+    // 溜まっているメッセージより優先度の低いメッセージは溜めない
+    assert!(!queue.push(MessagePriority::Low, "cursor-4".to_string()));
+
+    // 接続確認は溜まっている中で最も優先度の低いメッセージを押し出す
+    assert!(queue.push(MessagePriority::High, "ping".to_string()));
+    assert_eq!(texts(&mut queue), ["move", "flip", "ping"]);
+
+    let metrics = queue.metrics();
+    assert_eq!(metrics.capacity, 3);
+    assert_eq!(metrics.backlog, 3);
+    assert_eq!(metrics.queued, 7);
+    assert_eq!(metrics.dropped, 6);
+    assert_eq!(metrics.retried, 0);
+}
+
+#[test]
+fn retried_messages_are_counted_and_restored_in_order() {
+    let mut queue = SendQueue::default();
+    for text in ["a", "b", "c"] {
+        queue.push(MessagePriority::Normal, text.to_string());
+    }
+
+    // 1件目だけ送れて、2件目で送れなくなった
+    let mut messages = queue.take_for_retry().into_iter();
+    assert!(queue.is_empty());
+    messages.next();
+    queue.restore(messages.collect());
+    assert_eq!(queue.len(), 2);
+
+    assert_eq!(
+        queue.metrics(),
+        QueueMetrics {
+            capacity: 256,
+            backlog: 2,
+            queued: 3,
+            dropped: 0,
+            retried: 1,
+        }
+    );
+    assert_eq!(queue.take_for_retry()[0].1, "b");
+    assert_eq!(queue.metrics().retried, 3);
+}
@@ -0,0 +1,70 @@
+// =============================================================================
+// チャネルごとの連番のテスト
+// =============================================================================
+// 連番がチャネルごとに1から発行されること、届いた連番から抜け・順序の入れ替わり・
+// 古いメッセージが判定されること、極端に先の連番が届いても抜けとして覚える数が
+// 上限を超えないことを確認します。
+//
+// 実行方法：cargo test --test sequence
+// =============================================================================
+
+use ecs_wasm_solitaire::protocol::Channel;
+use ecs_wasm_solitaire::sequence::{Arrival, SequenceCounter, SequenceTracker, MAX_TRACKED_GAP};
+
+#[test]
+fn counter_numbers_each_channel_from_one() {
+    let mut counter = SequenceCounter::new();
+    assert_eq!(counter.next(Channel::Actions), 1);
+    assert_eq!(counter.next(Channel::Actions), 2);
+    assert_eq!(counter.next(Channel::Cursor), 1);
+    assert_eq!(counter.next(Channel::Actions), 3);
+
+    counter.reset();
+    assert_eq!(counter.next(Channel::Actions), 1);
+}
+
+#[test]
+fn tracker_detects_gaps_reordering_and_stale_messages() {
+    let mut tracker = SequenceTracker::new();
+    assert_eq!(tracker.record(Channel::Actions, 1), Arrival::InOrder);
+    assert_eq!(
+        tracker.record(Channel::Actions, 4),
+        Arrival::Gap(vec![2, 3])
+    );
+    assert_eq!(tracker.missing(Channel::Actions), [2, 3]);
+    assert_eq!(
+        tracker.record(Channel::Chat, 1),
+        Arrival::InOrder,
+        "チャネルごとに数える"
+    );
+
+    assert_eq!(tracker.record(Channel::Actions, 3), Arrival::Late);
+    assert_eq!(tracker.record(Channel::Actions, 3), Arrival::Stale, "重複");
+    assert_eq!(tracker.record(Channel::Actions, 1), Arrival::Stale);
+    assert_eq!(tracker.missing(Channel::Actions), [2]);
+    assert_eq!(tracker.record(Channel::Actions, 5), Arrival::InOrder);
+}
+
+#[test]
+fn far_ahead_sequences_track_a_bounded_gap() {
+    let mut tracker = SequenceTracker::new();
+    tracker.record(Channel::Sync, 1);
+    let Arrival::Gap(missing) = tracker.record(Channel::Sync, 1_000_000) else {
+        panic!("抜けとして判定される");
+    };
+    assert_eq!(missing.len(), MAX_TRACKED_GAP as usize);
+    assert_eq!(missing.last(), Some(&999_999));
+
+    let Arrival::Gap(_) = tracker.record(Channel::Sync, 2_000_000) else {
+        panic!("抜けとして判定される");
+    };
+    assert_eq!(
+        tracker.missing(Channel::Sync).len(),
+        MAX_TRACKED_GAP as usize
+    );
+    assert_eq!(
+        tracker.record(Channel::Sync, 2),
+        Arrival::Stale,
+        "覚えていない古い連番"
+    );
+}
@@ -0,0 +1,124 @@
+// =============================================================================
+// セッションレジストリのテスト
+// =============================================================================
+// 複数のセッションがIDごとに別々のワールドを持つこと、一時停止中のセッションは
+// 更新対象から外れること、破棄したセッションは一覧から消えることを確認します。
+//
+// 実行方法：cargo test --test session
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::session::{
+    SessionInfo, SessionRegistry, SessionStatus, DEFAULT_SESSION_ID,
+};
+use ecs_wasm_solitaire::solitaire::SolitaireCard;
+
+/// 指定したカードだけを置いたワールドを作成
+fn world_with(cards: &[&str]) -> World {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 0, cards)
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    world
+}
+
+/// ワールドにあるカードの枚数
+fn card_count(world: &World) -> usize {
+    world.query::<SolitaireCard>().count()
+}
+
+#[test]
+fn sessions_keep_separate_worlds() {
+    let mut sessions = SessionRegistry::new();
+    assert!(sessions.insert("table_a", world_with(&["KS"])).is_none());
+    assert!(sessions
+        .insert("table_b", world_with(&["KH", "QS"]))
+        .is_none());
+
+    assert_eq!(sessions.get("table_a").map(card_count), Some(1));
+    assert_eq!(sessions.get("table_b").map(card_count), Some(2));
+    assert!(sessions.get("table_c").is_none());
+
+    // 同じIDで登録し直すと古いワールドが返る
+    let old = sessions.insert("table_a", World::new());
+    assert_eq!(old.as_ref().map(card_count), Some(1));
+    assert_eq!(sessions.len(), 2);
+
+    assert_eq!(
+        SessionRegistry::<World>::resolve_id(None),
+        DEFAULT_SESSION_ID
+    );
+    assert_eq!(
+        SessionRegistry::<World>::resolve_id(Some("table_b")),
+        "table_b"
+    );
+}
+
+#[test]
+fn suspended_sessions_are_not_active() {
+    let mut sessions = SessionRegistry::new();
+    sessions.insert("table_a", 0u32);
+    sessions.insert("table_b", 0u32);
+
+    sessions
+        .suspend("table_a")
+        .expect("実行中のセッションは一時停止できる");
+    assert!(
+        sessions.suspend("table_a").is_err(),
+        "二重の一時停止はエラー"
+    );
+    assert!(sessions.suspend("missing").is_err());
+    assert_eq!(sessions.status("table_a"), Some(SessionStatus::Suspended));
+
+    // 一時停止中のセッションは更新されないが、中身は参照できる
+    for (_, frames) in sessions.active_sessions_mut() {
+        *frames += 1;
+    }
+    assert!(sessions.get_active_mut("table_a").is_none());
+    assert_eq!(sessions.get("table_a"), Some(&0));
+    assert_eq!(sessions.get("table_b"), Some(&1));
+
+    sessions
+        .resume("table_a")
+        .expect("一時停止中のセッションは再開できる");
+    assert!(
+        sessions.resume("table_a").is_err(),
+        "実行中のセッションは再開できない"
+    );
+    assert_eq!(sessions.active_sessions_mut().count(), 2);
+}
+
+#[test]
+fn destroyed_sessions_leave_the_list() {
+    let mut sessions = SessionRegistry::new();
+    sessions.insert("table_b", "b");
+    sessions.insert("table_a", "a");
+    sessions.suspend("table_b").expect("一時停止できる");
+
+    assert_eq!(
+        sessions.list(),
+        vec![
+            SessionInfo {
+                id: "table_a".to_string(),
+                status: SessionStatus::Active,
+            },
+            SessionInfo {
+                id: "table_b".to_string(),
+                status: SessionStatus::Suspended,
+            },
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(sessions.list()).expect("一覧はJSONにできる")[1]["status"],
+        "suspended"
+    );
+
+    assert_eq!(sessions.destroy("table_b"), Some("b"));
+    assert_eq!(sessions.destroy("table_b"), None);
+    assert_eq!(sessions.list().len(), 1);
+
+    sessions.destroy("table_a");
+    assert!(sessions.is_empty());
+}
@@ -0,0 +1,168 @@
+// =============================================================================
+// ゲームをまたいで引き継ぐ設定のテスト
+// =============================================================================
+// セッションごとの上書きが指定した項目だけを置き換えること、範囲外の値を
+// 受け付けないこと、ランタイムに適用した設定がテーマとアニメーションの速さに
+// 反映されること（速さは設定が変わったときだけ）、引く枚数が次のゲームから
+// 適用されることを確認します。
+//
+// 実行方法：cargo test --test settings
+// =============================================================================
+
+use ecs_wasm_solitaire::game::AnimationSettings;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::settings::{DrawMode, PreferenceOverrides, Preferences};
+use ecs_wasm_solitaire::solitaire::{CardLocation, SolitaireCard, SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::theme::{CardBack, Theme};
+
+/// 保存されている設定の例（裏面は波模様、アニメーションは2倍速）
+fn saved_preferences() -> Preferences {
+    Preferences {
+        theme: Theme {
+            card_back: CardBack::Ocean,
+            ..Theme::default()
+        },
+        animation_speed: 2.0,
+        player_name: Some("あかり".to_string()),
+        ..Preferences::default()
+    }
+}
+
+#[test]
+fn overrides_replace_only_the_given_fields() {
+    let overrides: PreferenceOverrides =
+        serde_json::from_str(r#"{"draw_mode": "three", "locale": "en-US"}"#)
+            .expect("一部の項目だけを指定できる");
+    let merged = saved_preferences().merged(&overrides);
+
+    assert_eq!(merged.draw_mode, DrawMode::Three);
+    assert_eq!(merged.draw_mode.count(), 3);
+    assert_eq!(merged.locale, "en-US");
+    assert_eq!(merged.theme.card_back, CardBack::Ocean);
+    assert_eq!(merged.animation_speed, 2.0);
+    assert_eq!(merged.player_name.as_deref(), Some("あかり"));
+
+    // 何も上書きしなければ保存されている設定のまま
+    assert_eq!(
+        saved_preferences().merged(&PreferenceOverrides::default()),
+        saved_preferences()
+    );
+}
+
+#[test]
+fn out_of_range_values_are_rejected() {
+    assert!(Preferences::default().validate().is_ok());
+
+    let invalid = [
+        Preferences {
+            animation_speed: 10.0,
+            ..Preferences::default()
+        },
+        Preferences {
+            sound_volume: 1.5,
+            ..Preferences::default()
+        },
+        Preferences {
+            locale: "ja<script>".to_string(),
+            ..Preferences::default()
+        },
+        Preferences {
+            player_name: Some("   ".to_string()),
+            ..Preferences::default()
+        },
+    ];
+    for preferences in invalid {
+        assert!(preferences.validate().is_err(), "{:?}", preferences);
+    }
+}
+
+#[test]
+fn the_runtime_applies_preferences_and_session_overrides() {
+    let mut rt = GameRuntime::new();
+    rt.apply_preferences(saved_preferences());
+    assert_eq!(
+        rt.world
+            .get_resource::<Theme>()
+            .map(|theme| theme.card_back),
+        Some(CardBack::Ocean)
+    );
+    assert_eq!(rt.animation_settings().speed_multiplier, 2.0);
+
+    // このセッションだけ半分の速さにする
+    let overrides = PreferenceOverrides {
+        animation_speed: Some(0.5),
+        ..PreferenceOverrides::default()
+    };
+    rt.set_preference_overrides(overrides)
+        .expect("範囲内の上書きは受け付ける");
+    assert_eq!(rt.animation_settings().speed_multiplier, 0.5);
+    assert_eq!(rt.preferences().theme.card_back, CardBack::Ocean);
+
+    // 範囲外の上書きは受け付けず、前の上書きのまま
+    let overrides = PreferenceOverrides {
+        sound_volume: Some(-1.0),
+        ..PreferenceOverrides::default()
+    };
+    assert!(rt.set_preference_overrides(overrides).is_err());
+    assert_eq!(rt.preferences().animation_speed, 0.5);
+
+    // 保存されている設定が変わっても、上書きした項目は上書きの値のまま
+    rt.apply_preferences(Preferences {
+        animation_speed: 1.5,
+        locale: "en".to_string(),
+        ..saved_preferences()
+    });
+    assert_eq!(rt.animation_settings().speed_multiplier, 0.5);
+    assert_eq!(rt.preferences().locale, "en");
+}
+
+#[test]
+fn draw_mode_applies_to_the_next_game() {
+    let mut rt = GameRuntime::new();
+    rt.apply_preferences(Preferences {
+        draw_mode: DrawMode::Three,
+        ..Preferences::default()
+    });
+    rt.start_game(SolitaireType::Klondike);
+    assert_eq!(rt.game_state().map(|state| state.draw_count), Some(3));
+
+    // 3枚引きでは1回の操作で3枚がウェイストに移る
+    SolitaireManager::draw_card(&mut rt.world).expect("デッキから引ける");
+    let waste = rt
+        .world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| card.location_type == CardLocation::Waste)
+        .count();
+    assert_eq!(waste, 3);
+
+    // 進行中のゲームの枚数は変えず、次のゲームから1枚引きに戻る
+    rt.apply_preferences(Preferences::default());
+    assert_eq!(rt.game_state().map(|state| state.draw_count), Some(3));
+    rt.start_game(SolitaireType::Klondike);
+    assert_eq!(rt.game_state().map(|state| state.draw_count), Some(1));
+}
+
+#[test]
+fn unrelated_preference_changes_keep_the_animation_speed() {
+    let mut rt = GameRuntime::new();
+    rt.apply_preferences(saved_preferences());
+    rt.set_animation_settings(AnimationSettings {
+        speed_multiplier: 3.0,
+        ..rt.animation_settings()
+    })
+    .expect("範囲内の速さは受け付ける");
+
+    // 速さ以外の設定を変えても、別の方法で変えた速さはそのまま
+    rt.apply_preferences(Preferences {
+        locale: "en".to_string(),
+        ..saved_preferences()
+    });
+    assert_eq!(rt.animation_settings().speed_multiplier, 3.0);
+
+    // 設定の速さを変えた場合は設定の値になる
+    rt.apply_preferences(Preferences {
+        animation_speed: 1.5,
+        ..saved_preferences()
+    });
+    assert_eq!(rt.animation_settings().speed_multiplier, 1.5);
+}
@@ -0,0 +1,119 @@
+// =============================================================================
+// 複数スレッドから共有するECSワールドのテスト
+// =============================================================================
+// SharedWorldを複数のスレッドから同時に読み書きしても更新が失われないこと、
+// 読み取り中に書き込みの途中の状態が見えないこと、par_queryの結果が
+// 順番に処理した場合と同じになり、組の数が同時に動かせるスレッドの数までに抑えられることを確認します。
+//
+// 実行方法：cargo test --test shared_world
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{Component, SharedWorld, World};
+
+/// テスト用の値を持つコンポーネント
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Counter(u64);
+
+impl Component for Counter {}
+
+/// スレッドの数
+const THREADS: u64 = 8;
+
+#[test]
+fn concurrent_writers_do_not_lose_updates() {
+    let mut world = World::new();
+    let entities = world.spawn_batch((0..100).map(|_| Counter(0)));
+    let shared = SharedWorld::new(world);
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    shared.write(|world| {
+                        for (_, counter) in world.query_mut::<Counter>() {
+                            counter.0 += 1;
+                        }
+                    });
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("書き込みスレッドが終わる");
+    }
+
+    let expected = Counter(THREADS * 100);
+    shared.read(|world| {
+        for &entity in &entities {
+            assert_eq!(world.get_component::<Counter>(entity), Some(&expected));
+        }
+    });
+}
+
+#[test]
+fn readers_never_observe_a_half_applied_write() {
+    let mut world = World::new();
+    world.spawn_batch((0..1_000).map(|_| Counter(0)));
+    let shared = SharedWorld::new(world);
+
+    let writer = {
+        let shared = shared.clone();
+        std::thread::spawn(move || {
+            for _ in 0..200 {
+                shared.write(|world| {
+                    for (_, counter) in world.query_mut::<Counter>() {
+                        counter.0 += 1;
+                    }
+                });
+            }
+        })
+    };
+    let readers: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for _ in 0..200 {
+                    // 1回の書き込みで全てのカウンターが揃って増えるため、読み取り時は全て同じ値
+                    let values = shared.read(|world| {
+                        world.par_query::<Counter, _>(4, |shard| {
+                            (
+                                shard.iter().map(|(_, c)| c.0).min(),
+                                shard.iter().map(|(_, c)| c.0).max(),
+                            )
+                        })
+                    });
+                    let low = values.iter().filter_map(|(min, _)| *min).min();
+                    let high = values.iter().filter_map(|(_, max)| *max).max();
+                    assert_eq!(low, high);
+                }
+            })
+        })
+        .collect();
+
+    writer.join().expect("書き込みスレッドが終わる");
+    for reader in readers {
+        reader.join().expect("読み取りスレッドが終わる");
+    }
+}
+
+#[test]
+fn par_query_matches_a_sequential_query() {
+    let mut world = World::new();
+    world.spawn_batch((1..=10_001).map(Counter));
+
+    let sequential: u64 = world.query::<Counter>().map(|(_, c)| c.0).sum();
+    for shards in [0, 1, 3, 16, 20_000] {
+        let results = world.par_query::<Counter, _>(shards, |shard| {
+            (shard.len(), shard.iter().map(|(_, c)| c.0).sum::<u64>())
+        });
+        // 組の数（スレッドの数）は同時に動かせるスレッドの数までに抑えられる
+        assert!(results.len() <= shards.clamp(1, World::max_shards()));
+        assert_eq!(results.iter().map(|(len, _)| len).sum::<usize>(), 10_001);
+        assert_eq!(results.iter().map(|(_, sum)| sum).sum::<u64>(), sequential);
+    }
+
+    assert!(World::new()
+        .par_query::<Counter, _>(4, |shard| shard.len())
+        .is_empty());
+}
@@ -0,0 +1,160 @@
+// =============================================================================
+// シンプルWebSocketサーバーの結合テスト
+// =============================================================================
+// simple_websocket_serverを空きポートで起動し、複数の疑似クライアントから接続して
+// 参加・退出の通知、マウスカーソルとゲームアクションの中継、
+// 不正な座標を中継せずに送信者へエラーを返すことを確認します。
+//
+// 実行方法：cargo test --features server --test simple_websocket_server
+// =============================================================================
+
+mod common;
+
+use common::{TestClient, TestServer};
+use serde_json::json;
+use std::time::Duration;
+
+/// 他のクライアントに届かないことを確認する待ち時間
+const SILENCE: Duration = Duration::from_millis(300);
+
+/// サーバーが参加メッセージを処理するまでの待ち時間
+/// （シンプルサーバーは参加者本人に応答を返さないため、少し待ってから次へ進む）
+const JOIN_SETTLE: Duration = Duration::from_millis(200);
+
+fn start_server() -> TestServer {
+    TestServer::start(env!("CARGO_BIN_EXE_simple_websocket_server"))
+}
+
+/// 接続してプレイヤーとして参加する
+async fn join(server: &TestServer, name: &str) -> TestClient {
+    let mut client = TestClient::connect(server).await;
+    client
+        .send(json!({
+            "type": "PlayerJoin",
+            "player_id": "",
+            "player_name": name,
+            "player_index": 0,
+        }))
+        .await;
+    tokio::time::sleep(JOIN_SETTLE).await;
+    client
+}
+
+#[tokio::test]
+async fn join_is_broadcast_to_other_players_only() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let mut bob = join(&server, "Bob").await;
+
+    let joined = alice.recv_type("PlayerJoin").await;
+    assert_eq!(joined["player_name"], "Bob");
+    assert!(joined["player_id"].as_str().is_some_and(|id| !id.is_empty()));
+
+    bob.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn leave_is_broadcast_when_connection_closes() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let bob = join(&server, "Bob").await;
+    let bob_id = alice.recv_type("PlayerJoin").await["player_id"].clone();
+
+    bob.close().await;
+
+    let left = alice.recv_type("PlayerLeft").await;
+    assert_eq!(left["player_id"], bob_id);
+    assert_eq!(left["player_name"], "Bob");
+}
+
+#[tokio::test]
+async fn cursor_position_is_relayed_to_others() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let mut bob = join(&server, "Bob").await;
+    let bob_id = alice.recv_type("PlayerJoin").await["player_id"].clone();
+
+    bob.send(json!({
+        "type": "MousePosition",
+        "player_id": bob_id,
+        "x": 120.5,
+        "y": 340.0,
+        "timestamp": 1,
+    }))
+    .await;
+
+    let cursor = alice.recv_type("MousePosition").await;
+    assert_eq!(cursor["player_id"], bob_id);
+    assert_eq!(cursor["x"], 120.5);
+    assert_eq!(cursor["y"], 340.0);
+
+    // 送信者本人には中継されない
+    bob.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn game_action_is_relayed_to_others() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let mut bob = join(&server, "Bob").await;
+    let bob_id = alice.recv_type("PlayerJoin").await["player_id"].clone();
+
+    bob.send(json!({
+        "type": "GameAction",
+        "player_id": bob_id,
+        "player_name": "Bob",
+        "action": "draw",
+        "x": null,
+        "y": null,
+        "timestamp": 2,
+    }))
+    .await;
+
+    let action = alice.recv_type("GameAction").await;
+    assert_eq!(action["action"], "draw");
+    assert_eq!(action["player_name"], "Bob");
+    bob.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn invalid_coordinates_are_rejected_instead_of_relayed() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let mut bob = join(&server, "Bob").await;
+    let bob_id = alice.recv_type("PlayerJoin").await["player_id"].clone();
+
+    bob.send(json!({
+        "type": "MousePosition",
+        "player_id": bob_id,
+        "x": 10.0,
+        "y": 5_000_000.0,
+        "timestamp": 1,
+    }))
+    .await;
+
+    let error = bob.recv_type("Error").await;
+    assert_eq!(error["field"], "y");
+    alice.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn malformed_messages_do_not_break_the_connection() {
+    let server = start_server();
+    let mut alice = join(&server, "Alice").await;
+    let mut bob = join(&server, "Bob").await;
+    let bob_id = alice.recv_type("PlayerJoin").await["player_id"].clone();
+
+    bob.send_text("{ not json".to_string()).await;
+    bob.send(json!({ "type": "NoSuchMessage" })).await;
+
+    // 不正なメッセージの後も、同じ接続で中継が続く
+    bob.send(json!({
+        "type": "MousePosition",
+        "player_id": bob_id,
+        "x": 1.0,
+        "y": 2.0,
+        "timestamp": 3,
+    }))
+    .await;
+    assert_eq!(alice.recv_type("MousePosition").await["x"], 1.0);
+}
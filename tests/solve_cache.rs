@@ -0,0 +1,160 @@
+// =============================================================================
+// 解いた配り札のキャッシュのテスト
+// =============================================================================
+// 上限を超えると一番長く使っていない結果から捨てること、短い形式で書き出して読み戻せること、分からなかった結果は
+// 同じか小さい上限でしか使わないこと、クライアントの勝ち筋の確認が配られた直後の局面で
+// キャッシュを引き、調べた結果を覚えること、ゲーム結果レポートが覚えている勝ち筋の手数から
+// 効率を求めることを確認します。
+//
+// 実行方法：cargo test --test solve_cache
+// =============================================================================
+
+use ecs_wasm_solitaire::events::GameEvent;
+use ecs_wasm_solitaire::rng::Rng;
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+use ecs_wasm_solitaire::solve_cache::{Difficulty, SolveCache, SolvedDeal};
+use ecs_wasm_solitaire::solver::{Winnability, WinnabilityWatch};
+use ecs_wasm_solitaire::storage;
+
+/// 勝ち筋の有無だけを指定した結果
+fn solved(seed: u64, winnability: Winnability, search_limit: u32) -> SolvedDeal {
+    SolvedDeal {
+        seed,
+        winnability,
+        difficulty: None,
+        line_length: None,
+        search_limit,
+    }
+}
+
+#[test]
+fn the_least_recently_used_results_are_dropped_first() {
+    let mut cache = SolveCache::with_capacity(2);
+    cache.insert(solved(1, Winnability::Winnable, 100));
+    cache.insert(solved(2, Winnability::Unwinnable, 100));
+
+    // 1を使ってから3を覚えると、使っていない2が捨てられる
+    assert!(
+        cache.get(1, 100_000).is_some(),
+        "勝ち筋の有無はどの上限でも使える"
+    );
+    cache.insert(solved(3, Winnability::Unknown, 5_000));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(2, 100), None);
+    assert!(cache.get(1, 100).is_some());
+
+    // 分からなかった結果は、同じか小さい上限で調べる場合だけ使う
+    assert!(cache.get(3, 5_000).is_some());
+    assert_eq!(cache.get(3, 10_000), None);
+
+    // 解いた結果は難しさと手数を持ち、2回目は覚えている結果を返す
+    let mut cache = SolveCache::default();
+    let first = cache.solve(2, 100_000);
+    assert_eq!(first.winnability, Winnability::Winnable);
+    assert!(first.difficulty.is_some());
+    assert!(first.line_length.is_some_and(|length| length > 0));
+    assert_eq!(cache.solve(2, 100_000), first);
+    assert_eq!(cache.len(), 1);
+    assert_eq!(Difficulty::from_nodes(10), Difficulty::Easy);
+}
+
+#[test]
+fn solved_deals_round_trip_through_the_compact_form() {
+    let winnable = SolvedDeal {
+        difficulty: Some(Difficulty::Medium),
+        line_length: Some(87),
+        ..solved(12_345, Winnability::Winnable, 100_000)
+    };
+    assert_eq!(winnable.to_compact(), "12345:W:M:87:100000");
+    let unknown = solved(u64::MAX, Winnability::Unknown, 5_000);
+    assert_eq!(unknown.to_compact(), format!("{}:?:::5000", u64::MAX));
+
+    for deal in [winnable, unknown, solved(3, Winnability::Unwinnable, 1)] {
+        assert_eq!(SolvedDeal::from_compact(&deal.to_compact()), Ok(deal));
+    }
+    assert!(SolvedDeal::from_compact("12345:W:M:87").is_err());
+    assert!(SolvedDeal::from_compact("12345:X::87:100000").is_err());
+    assert!(SolvedDeal::from_compact("seed:W:M:87:100000").is_err());
+}
+
+#[test]
+fn the_opening_check_uses_and_fills_the_client_cache() {
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    rt.world.insert_resource(Rng::new(7));
+    rt.start_game(SolitaireType::Klondike);
+    let seed = rt.game_state().expect("ゲームがある").seed;
+    for _ in 0..200 {
+        rt.update(0.016);
+    }
+    assert!(
+        rt.world
+            .get_resource_mut::<SolveCache>()
+            .and_then(|cache| cache.get(seed, 5_000))
+            .is_some(),
+        "配られた直後の局面を調べた結果を覚えている"
+    );
+
+    // 同じ配り札では探索せずに覚えている結果を使う（勝てないと覚えさせて確かめる）
+    rt.world.insert_resource(Rng::new(7));
+    let game = rt.start_game(SolitaireType::Klondike);
+    rt.world
+        .get_resource_mut::<SolveCache>()
+        .expect("キャッシュがある")
+        .insert(solved(seed, Winnability::Unwinnable, 5_000));
+    rt.drain_events();
+    rt.update(0.016);
+
+    let watch = rt
+        .world
+        .get_component::<WinnabilityWatch>(game)
+        .copied()
+        .expect("最初のフレームで確認が終わる");
+    assert_eq!(watch.checked_moves, Some(0));
+    assert_eq!(watch.lost_at_move, Some(0));
+    assert!(rt.drain_events().iter().any(|event| matches!(
+        event,
+        GameEvent::Notification { message, .. } if message.contains("配り札")
+    )));
+}
+
+#[test]
+fn the_game_result_compares_the_moves_with_the_remembered_line() {
+    storage::use_temp_dir();
+    let mut rt = GameRuntime::new();
+    rt.world.insert_resource(Rng::new(7));
+    rt.start_game(SolitaireType::Klondike);
+    let seed = rt.game_state().expect("ゲームがある").seed;
+    rt.world
+        .get_resource_mut::<SolveCache>()
+        .expect("キャッシュがある")
+        .insert(SolvedDeal {
+            line_length: Some(60),
+            ..solved(seed, Winnability::Winnable, 5_000)
+        });
+
+    // 120手で勝ったことにする
+    let state = rt.game_state_mut().expect("ゲーム中");
+    state.move_count = 120;
+    state.is_completed = true;
+    state.is_won = true;
+    state.end_time = Some(state.start_time);
+    rt.update(0.016);
+
+    let result = rt.game_result().expect("結果レポートがある");
+    assert_eq!(result.solver_optimal_moves, Some(60));
+    assert_eq!(result.efficiency, Some(0.5));
+
+    // 勝ち筋を覚えていない配り札では空のまま
+    rt.world.insert_resource(Rng::new(8));
+    rt.start_game(SolitaireType::Klondike);
+    let state = rt.game_state_mut().expect("ゲーム中");
+    state.is_completed = true;
+    state.end_time = Some(state.start_time);
+    rt.world.insert_resource(SolveCache::default());
+    rt.update(0.016);
+    let result = rt.game_result().expect("結果レポートがある");
+    assert_eq!(result.solver_optimal_moves, None);
+    assert_eq!(result.efficiency, None);
+}
@@ -0,0 +1,154 @@
+// =============================================================================
+// 勝ち筋の確認のテスト
+// =============================================================================
+// BoardBuilderで用意した局面で、ソルバーが勝ち筋の有無を判定できること、
+// 勝てなくなる手を打つと「やり直しますか？」と1回だけ通知し、
+// 何手目で勝てなくなったかを記録すること、見つけた勝ち筋を実際の手として最後まで打てて、
+// その移動記録を配り札から再現できることを確認します。
+//
+// 実行方法：cargo test --test solver
+// =============================================================================
+
+use ecs_wasm_solitaire::analysis::replay_move;
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::hint::{HintEngine, HintLocation};
+use ecs_wasm_solitaire::scenario::{self, BoardBuilder, Scenario};
+use ecs_wasm_solitaire::solitaire::{CardLocation, MoveLog, SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::solver::{Solver, Winnability, WinnabilitySystem, WinnabilityWatch};
+
+/// イベントキュー付きで盤面を組み立てたワールドを作成
+fn world_with(board: BoardBuilder) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    let game = board.build(&mut world).expect("シナリオから盤面を作れる");
+    (world, game)
+}
+
+/// 勝ち筋の確認システムを1フレーム分実行し、届いた通知の文章を取り出す
+fn check(world: &mut World) -> Vec<String> {
+    WinnabilitySystem.update(world, 0.016);
+    world
+        .get_resource_mut::<EventQueue>()
+        .expect("イベントキューがある")
+        .drain()
+        .into_iter()
+        .filter_map(|event| match event {
+            GameEvent::Notification { message, .. } => Some(message),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn solver_tells_whether_a_winning_line_remains() {
+    // ♥Aを出してから♥2を置ける
+    let (world, _) = world_with(
+        BoardBuilder::new()
+            .tableau(0, 1, &["2H", "3S"])
+            .tableau(1, 0, &["AH"])
+            .foundation(0, &["AS", "2S"]),
+    );
+    assert_eq!(Solver::check(&world, 1000), Winnability::Winnable);
+
+    // ♥Aが♥2の下に埋まっていて、♥2の置き場所がない
+    let (mut world, game) = world_with(
+        BoardBuilder::new()
+            .tableau(0, 1, &["AH", "2H"])
+            .deck(&["KS"]),
+    );
+    assert_eq!(Solver::check(&world, 0), Winnability::Unknown);
+    assert_eq!(Solver::check(&world, 1000), Winnability::Unwinnable);
+
+    let notified = check(&mut world);
+    assert_eq!(notified.len(), 1);
+    assert!(notified[0].contains("配り札"), "{}", notified[0]);
+    assert_eq!(
+        world
+            .get_component::<WinnabilityWatch>(game)
+            .and_then(|watch| watch.lost_at_move),
+        Some(0)
+    );
+}
+
+#[test]
+fn the_move_that_lost_the_game_is_reported_once() {
+    // ♥Kを空き列へ動かせば♥Aが出て勝てるが、先に♦Kで空き列を埋めると勝てなくなる
+    let (mut world, game) = world_with(
+        BoardBuilder::new()
+            .tableau(1, 1, &["AH", "KH"])
+            .tableau(2, 1, &["QD", "2H"])
+            .tableau(3, 0, &["3H"])
+            .tableau(4, 0, &["4H"])
+            .tableau(5, 0, &["5H"])
+            .tableau(6, 0, &["6H"])
+            .foundation(
+                0,
+                &[
+                    "AD", "2D", "3D", "4D", "5D", "6D", "7D", "8D", "9D", "10D", "JD",
+                ],
+            )
+            .waste(&["KD"])
+            .deck(&["7H", "8H", "9H", "10H", "JH", "QH"]),
+    );
+    assert!(check(&mut world).is_empty(), "配られた時点では勝てる");
+
+    HintEngine::move_cards(
+        &mut world,
+        HintLocation {
+            location: CardLocation::Waste,
+            index: 0,
+        },
+        1,
+        HintLocation {
+            location: CardLocation::Tableau,
+            index: 0,
+        },
+    )
+    .expect("♦Kを空き列へ動かせる");
+
+    let notified = check(&mut world);
+    assert_eq!(notified.len(), 1);
+    assert!(notified[0].contains("1手目"), "{}", notified[0]);
+    let watch = world.get_component::<WinnabilityWatch>(game).copied();
+    assert_eq!(watch.and_then(|watch| watch.lost_at_move), Some(1));
+    assert_eq!(
+        watch.map(|watch| watch.report(Winnability::Unwinnable, 4).moves_since_lost),
+        Some(Some(3))
+    );
+
+    assert!(check(&mut world).is_empty(), "同じゲームでは1回だけ");
+}
+
+#[test]
+fn the_winning_line_is_played_out_and_replays_from_the_seed() {
+    let mut world = World::new();
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 2);
+
+    let solution = Solver::play_solution(&mut world, 100_000).expect("勝ち筋の手を打てる");
+    assert_eq!(solution.winnability, Winnability::Winnable);
+    assert!(solution.nodes > 0);
+    assert!(SolitaireManager::check_windows_solitaire_win(&world));
+
+    // 打った手は移動記録に残り、配り札から同じ盤面を再現できる
+    let moves = world
+        .get_component::<MoveLog>(game)
+        .expect("移動記録がある")
+        .moves
+        .clone();
+    assert!(moves.len() as u32 >= solution.line_length.unwrap_or(0));
+    let mut replay = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut replay, SolitaireType::Klondike, 2);
+    for record in &moves {
+        replay_move(&mut replay, record).expect("記録した手を再現できる");
+    }
+    assert_eq!(Scenario::from_world(&replay), Scenario::from_world(&world));
+
+    // 手順は短い表記で書き出せる
+    let notation: Vec<String> = moves.iter().map(scenario::move_notation).collect();
+    assert!(notation
+        .iter()
+        .all(|step| step.len() >= 6 && step.contains(' ') && step.contains('-')));
+    assert!(notation.iter().any(|step| step.ends_with("d-w")));
+    assert!(notation.iter().any(|step| step.contains("-f")));
+}
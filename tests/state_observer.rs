@@ -0,0 +1,108 @@
+// =============================================================================
+// ゲーム状態の変化の監視のテスト
+// =============================================================================
+// スコア・手数・デッキを戻した回数・経過時間が変わったフレームだけ、対応するイベントが
+// EventQueueとStateChangesの両方に入り、変わらないフレームでは何も送られないこと、
+// 終了したゲームでは経過時間のイベントを送らないことを確認します。
+//
+// 実行方法：cargo test --test state_observer
+// =============================================================================
+
+use ecs_wasm_solitaire::clock::GameClock;
+use ecs_wasm_solitaire::ecs::{Entity, System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::{SolitaireGameState, SolitaireManager};
+use ecs_wasm_solitaire::state_observer::{GameStateObserverSystem, StateChanges};
+
+/// 監視に必要なリソース付きで盤面を組み立てたワールドを作成
+fn world_with(board: BoardBuilder) -> (World, Entity) {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    world.insert_resource(StateChanges::new());
+    world.insert_resource(GameClock::new());
+    let game = board.build(&mut world).expect("シナリオから盤面を作れる");
+    (world, game)
+}
+
+/// 監視システムを1フレーム分実行し、送られたイベントを取り出す（経過時間のイベントは除く）
+fn observe(world: &mut World) -> Vec<GameEvent> {
+    GameStateObserverSystem.update(world, 0.016);
+    let changes = world.get_resource_mut::<StateChanges>().unwrap().drain();
+    let events = world.get_resource_mut::<EventQueue>().unwrap().drain();
+    assert_eq!(
+        changes, events,
+        "JavaScriptとネットワークに同じイベントを送る"
+    );
+    events
+        .into_iter()
+        .filter(|event| !matches!(event, GameEvent::TimerTick { .. }))
+        .collect()
+}
+
+#[test]
+fn changed_fields_are_reported_once() {
+    let (mut world, game) = world_with(BoardBuilder::new().tableau(0, 0, &["5D"]).deck(&["KH"]));
+    assert!(observe(&mut world).is_empty(), "始めた時点では変化なし");
+
+    world
+        .get_component_mut::<SolitaireGameState>(game)
+        .unwrap()
+        .record_move(10);
+    assert_eq!(
+        observe(&mut world),
+        [
+            GameEvent::MovesChanged { move_count: 1 },
+            GameEvent::ScoreChanged {
+                score: 10,
+                previous: 0,
+            },
+        ]
+    );
+    assert!(
+        observe(&mut world).is_empty(),
+        "変わらないフレームでは送らない"
+    );
+
+    // 引いてから戻すとデッキを戻したイベントが届く
+    SolitaireManager::draw_card(&mut world).unwrap();
+    SolitaireManager::draw_card(&mut world).unwrap();
+    let events = observe(&mut world);
+    assert!(events.contains(&GameEvent::DeckRecycled { deck_turns: 1 }));
+}
+
+#[test]
+fn timer_ticks_every_second_until_the_game_ends() {
+    let (mut world, game) = world_with(BoardBuilder::new().tableau(0, 0, &["5D"]));
+    let ticks = |world: &mut World| -> Vec<u64> {
+        GameStateObserverSystem.update(world, 0.016);
+        world
+            .get_resource_mut::<EventQueue>()
+            .unwrap()
+            .drain()
+            .into_iter()
+            .filter_map(|event| match event {
+                GameEvent::TimerTick { elapsed_seconds } => Some(elapsed_seconds),
+                _ => None,
+            })
+            .collect()
+    };
+    ticks(&mut world);
+
+    world
+        .get_resource_mut::<GameClock>()
+        .unwrap()
+        .advance(1000.0);
+    assert_eq!(ticks(&mut world).len(), 1);
+
+    let clock = GameClock::from_world(&world);
+    world
+        .get_component_mut::<SolitaireGameState>(game)
+        .unwrap()
+        .finish_game(false, &clock);
+    world
+        .get_resource_mut::<GameClock>()
+        .unwrap()
+        .advance(5000.0);
+    assert!(ticks(&mut world).is_empty());
+}
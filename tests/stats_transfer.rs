@@ -0,0 +1,161 @@
+// =============================================================================
+// 実績・通算成績の書き出しと読み込みのテスト
+// =============================================================================
+// 別の端末の成績をまとめるとき、ゲーム数は足し合わせ、最短クリア時間・最高スコアは
+// 良い方を残すこと、同じ書き出しを2回まとめないこと、同じ出どころの新しい書き出しは
+// 増えた分だけを足すこと、署名した後に内容を書き換えると署名の確認で検出できること、
+// 署名のない書き出しやサーバーで確かめていない書き出しは読み込まないことを確認します。
+//
+// 実行方法：cargo test --test stats_transfer
+// =============================================================================
+
+use ecs_wasm_solitaire::achievements::{
+    AchievementId, AchievementStore, PlayerStats, UnlockedAchievement,
+};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::stats_transfer::StatsExport;
+use ecs_wasm_solitaire::storage;
+use serde_json::json;
+
+/// 署名鍵の例（実際にはサーバーだけが持つ）
+const SIGNING_KEY: &[u8] = b"test-signing-key";
+
+/// 成績の例
+fn store(
+    games_played: u32,
+    best_time_seconds: Option<u64>,
+    unlocked: &[(AchievementId, u64)],
+) -> AchievementStore {
+    AchievementStore {
+        unlocked: unlocked
+            .iter()
+            .map(|&(id, unlocked_at)| UnlockedAchievement { id, unlocked_at })
+            .collect(),
+        stats: PlayerStats {
+            games_played,
+            games_won: games_played / 2,
+            best_time_seconds,
+            best_score: games_played * 100,
+            ..PlayerStats::default()
+        },
+        ..AchievementStore::default()
+    }
+}
+
+#[test]
+fn merging_sums_counts_and_keeps_the_best_records() {
+    let mut phone = store(10, Some(300), &[(AchievementId::FirstWin, 2_000)]);
+    let laptop = store(
+        4,
+        Some(150),
+        &[
+            (AchievementId::FirstWin, 1_000),
+            (AchievementId::WinWithoutUndo, 1_500),
+        ],
+    );
+
+    let newly_unlocked = phone.merge("account", "laptop", &laptop).expect("まとめられる");
+    assert_eq!(newly_unlocked, vec![AchievementId::WinWithoutUndo]);
+    assert_eq!((phone.stats.games_played, phone.stats.games_won), (14, 7));
+    assert_eq!(phone.stats.best_time_seconds, Some(150));
+    assert_eq!(phone.stats.best_score, 1_000);
+
+    // 両方で解除していた実績は早い方の時刻になり、解除順に並ぶ
+    let unlocked: Vec<_> = phone
+        .unlocked
+        .iter()
+        .map(|achievement| (achievement.id, achievement.unlocked_at))
+        .collect();
+    assert_eq!(
+        unlocked,
+        vec![
+            (AchievementId::FirstWin, 1_000),
+            (AchievementId::WinWithoutUndo, 1_500),
+        ]
+    );
+
+    // 同じ書き出しを2回まとめても数え直さない
+    assert!(phone.merge("account", "laptop", &laptop).is_err());
+    assert_eq!(phone.stats.games_played, 14);
+}
+
+#[test]
+fn tampered_exports_fail_verification() {
+    let mut export = StatsExport::new(&store(3, Some(600), &[]), "account", 1_700_000_000_000);
+    assert!(
+        export.verify(SIGNING_KEY).is_err(),
+        "署名していない書き出し"
+    );
+
+    export.sign(SIGNING_KEY).expect("署名できる");
+    let json = export.to_json().expect("JSONに変換できる");
+    let received = StatsExport::parse(&json).expect("読み込める");
+    assert!(received.verify(SIGNING_KEY).is_ok());
+    assert!(
+        received.verify(b"another-key").is_err(),
+        "別の鍵では確認できない"
+    );
+
+    // 署名した後に勝利数を書き換える
+    let mut tampered = received.clone();
+    tampered.achievements.stats.games_won = 3_000;
+    let error = tampered.verify(SIGNING_KEY).unwrap_err();
+    assert!(error.contains("書き換え"), "{}", error);
+
+    // 形式のバージョンが違う書き出しは読み込まない
+    let future = json.replacen("\"format_version\":2", "\"format_version\":3", 1);
+    assert!(StatsExport::parse(&future).is_err());
+}
+
+#[test]
+fn newer_exports_from_the_same_source_add_only_the_new_games() {
+    let mut phone = store(10, None, &[]);
+
+    phone.merge("account", "first", &store(4, None, &[])).expect("まとめられる");
+    assert_eq!(phone.stats.games_played, 14);
+
+    // 同じアカウントの新しい書き出しは、前の書き出しから増えた2ゲームだけを足す
+    phone.merge("account", "second", &store(6, None, &[])).expect("まとめられる");
+    assert_eq!(phone.stats.games_played, 16);
+
+    // 古い書き出しを後から読み込んでも減らさず、次の書き出しで数え直さない
+    phone.merge("account", "older", &store(4, None, &[])).expect("まとめられる");
+    phone.merge("account", "third", &store(7, None, &[])).expect("まとめられる");
+    assert_eq!(phone.stats.games_played, 17);
+
+    // 別のアカウントの成績は足し合わせる
+    phone.merge("another", "fourth", &store(3, None, &[])).expect("まとめられる");
+    assert_eq!(phone.stats.games_played, 20);
+}
+
+#[test]
+fn runtime_imports_only_exports_verified_by_the_server() {
+    storage::use_temp_dir();
+    let mut runtime = GameRuntime::new();
+    runtime.world.insert_resource(AchievementStore::default());
+    let mut export = StatsExport::new(&store(5, Some(200), &[]), "account", 1_700_000_000_000);
+    let unsigned = export.to_json().expect("JSONに変換できる");
+    let error = runtime.import_statistics(&unsigned).unwrap_err();
+    assert!(error.contains("署名"), "{}", error);
+
+    // 署名があってもサーバーで確かめるまでは読み込まない
+    export.sign(SIGNING_KEY).expect("署名できる");
+    let signed = export.to_json().expect("JSONに変換できる");
+    assert!(runtime.import_statistics(&signed).is_err());
+
+    let verified = json!({
+        "type": "StatsVerified",
+        "valid": true,
+        "signature": export.signature,
+    });
+    runtime
+        .network
+        .receive(&mut runtime.world, &verified.to_string())
+        .expect("受け取れる");
+    runtime.import_statistics(&signed).expect("確かめた書き出しは読み込める");
+    let imported = runtime.achievements().expect("実績データがある");
+    assert_eq!(imported.stats.games_played, 5);
+
+    // 確かめた結果は1回の読み込みにだけ使う
+    assert!(runtime.import_statistics(&signed).is_err());
+}
@@ -0,0 +1,84 @@
+// =============================================================================
+// テーマのテスト
+// =============================================================================
+// 見えにくいプレイヤー向けの設定（4色のスート・スートごとの模様・大きなランク表示）が
+// get_solitaire_state()で返すカードの色・模様・大きさに反映されることを確認します。
+//
+// 実行方法：cargo test --test theme
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::{CardView, ClientState};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::CardSuit;
+use ecs_wasm_solitaire::theme::{SuitColor, SuitPattern, Theme, LARGE_PRINT_SCALE};
+
+/// 各スートのカードを1枚ずつ表向きに置いた盤面で、テーマを反映したカードの表示情報を取得
+fn tableau_views(theme: Theme) -> Vec<CardView> {
+    let mut world = World::new();
+    world.insert_resource(theme);
+    let game = BoardBuilder::new()
+        .tableau(0, 0, &["5H"])
+        .tableau(1, 0, &["5D"])
+        .tableau(2, 0, &["5C"])
+        .tableau(3, 0, &["5S"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    ClientState::from_world(&world, Some(game))
+        .piles
+        .tableau
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+#[test]
+fn standard_theme_uses_two_colors_without_patterns() {
+    let views = tableau_views(Theme::default());
+    let colors: Vec<SuitColor> = views.iter().map(|card| card.color).collect();
+    assert_eq!(
+        colors,
+        [
+            SuitColor::Red,
+            SuitColor::Red,
+            SuitColor::Black,
+            SuitColor::Black
+        ]
+    );
+    assert!(views.iter().all(|card| card.pattern.is_none()));
+    assert!(views.iter().all(|card| card.rank_scale == 1.0));
+}
+
+#[test]
+fn accessibility_options_flow_into_every_card() {
+    let views = tableau_views(Theme {
+        four_color_suits: true,
+        pattern_fills: true,
+        large_print: true,
+        ..Theme::default()
+    });
+    let styles: Vec<(CardSuit, SuitColor, Option<SuitPattern>)> = views
+        .iter()
+        .map(|card| (card.suit, card.color, card.pattern))
+        .collect();
+    assert_eq!(
+        styles,
+        [
+            (CardSuit::Hearts, SuitColor::Red, Some(SuitPattern::Dots)),
+            (
+                CardSuit::Diamonds,
+                SuitColor::Blue,
+                Some(SuitPattern::Stripes)
+            ),
+            (CardSuit::Clubs, SuitColor::Green, Some(SuitPattern::Waves)),
+            (
+                CardSuit::Spades,
+                SuitColor::Black,
+                Some(SuitPattern::Crosshatch)
+            ),
+        ]
+    );
+    assert!(views
+        .iter()
+        .all(|card| card.rank_scale == LARGE_PRINT_SCALE));
+}
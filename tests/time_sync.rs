@@ -0,0 +1,81 @@
+// =============================================================================
+// 時刻合わせのテスト
+// =============================================================================
+// Ping/Pongの往復からサーバーの時計とのずれが推定できること、
+// 往復時間が短い測定ほど優先されること、クライアントのタイムスタンプが
+// サーバーの時刻の妥当な範囲に直されることを確認します。
+//
+// 実行方法：cargo test --test time_sync
+// =============================================================================
+
+use ecs_wasm_solitaire::time_sync::{client_to_server_time, ClockSync, MAX_TIMESTAMP_AGE_MS};
+
+/// サーバーの時計がクライアントより進んでいる時間（ミリ秒）
+const SERVER_AHEAD_MS: f64 = 2_500.0;
+
+/// 行きと帰りにかかる時間を指定して1往復を記録する
+fn exchange(sync: &mut ClockSync, sent_at: f64, uplink_ms: f64, downlink_ms: f64) -> Option<f64> {
+    let server_time = sent_at + uplink_ms + SERVER_AHEAD_MS;
+    sync.record(sent_at, server_time, sent_at + uplink_ms + downlink_ms)
+}
+
+#[test]
+fn symmetric_round_trip_gives_the_exact_offset() {
+    let mut sync = ClockSync::new();
+    assert_eq!(sync.offset_ms(), None, "測定前");
+    assert_eq!(sync.to_server_time(1_000.0), 1_000.0, "測定前はそのまま");
+
+    assert_eq!(
+        exchange(&mut sync, 10_000.0, 40.0, 40.0),
+        Some(SERVER_AHEAD_MS)
+    );
+    assert_eq!(sync.offset_ms(), Some(SERVER_AHEAD_MS));
+    assert_eq!(sync.to_server_time(1_000.0), 1_000.0 + SERVER_AHEAD_MS);
+    assert_eq!(sync.to_local_time(sync.to_server_time(1_000.0)), 1_000.0);
+}
+
+#[test]
+fn shortest_round_trip_wins() {
+    let mut sync = ClockSync::new();
+
+    // 行きだけ遅い往復は、ずれの推定も大きく外れる
+    exchange(&mut sync, 0.0, 400.0, 20.0);
+    exchange(&mut sync, 2_000.0, 30.0, 30.0);
+    exchange(&mut sync, 4_000.0, 20.0, 300.0);
+    assert_eq!(sync.offset_ms(), Some(SERVER_AHEAD_MS));
+
+    // 受信時刻が送信時刻より前の測定は使わない
+    assert_eq!(sync.record(5_000.0, 9_999.0, 4_999.0), None);
+    assert_eq!(sync.offset_ms(), Some(SERVER_AHEAD_MS));
+}
+
+#[test]
+fn old_samples_are_forgotten() {
+    let mut sync = ClockSync::new();
+    exchange(&mut sync, 0.0, 1.0, 1.0);
+
+    // サーバーの時計が変わった後の測定だけが残る
+    for i in 1..=8 {
+        let sent_at = i as f64 * 2_000.0;
+        sync.record(sent_at, sent_at + 50.0 + 100.0, sent_at + 100.0);
+    }
+    assert_eq!(sync.offset_ms(), Some(100.0));
+}
+
+#[test]
+fn client_timestamps_are_converted_and_clamped() {
+    let server_now = 1_000_000;
+    assert_eq!(client_to_server_time(997_000, 2_500, server_now), 999_500);
+
+    // 未来の時刻はサーバーの現在時刻に、古すぎる時刻は受け付ける上限に収める
+    assert_eq!(client_to_server_time(2_000_000, 0, server_now), server_now);
+    assert_eq!(
+        client_to_server_time(0, 0, server_now),
+        server_now - MAX_TIMESTAMP_AGE_MS
+    );
+    assert_eq!(
+        client_to_server_time(u64::MAX, i64::MAX, server_now),
+        server_now
+    );
+    assert_eq!(client_to_server_time(10, -1_000, 5_000), 0);
+}
@@ -0,0 +1,139 @@
+// =============================================================================
+// タイムラインのテスト
+// =============================================================================
+// イベント・移動・ポインター操作が起きたティック付きで記録されること、
+// 指定したティック以降の記録だけを取り出せること、上限を超えると古い記録から
+// 捨てること、デバッグ用オーバーレイの直近のイベントがタイムラインと一致することを確認します。
+//
+// 実行方法：cargo test --test timeline
+// =============================================================================
+
+use ecs_wasm_solitaire::events::GameEvent;
+use ecs_wasm_solitaire::input::{PointerEvent, PointerKind};
+use ecs_wasm_solitaire::runtime::GameRuntime;
+use ecs_wasm_solitaire::solitaire::SolitaireType;
+use ecs_wasm_solitaire::timeline::{TimelineRecord, TimelineRecorder};
+
+/// ポインターイベントを作る
+fn pointer(kind: PointerKind) -> PointerEvent {
+    PointerEvent {
+        kind,
+        x: 500.0,
+        y: 500.0,
+        pointer_id: 0,
+    }
+}
+
+#[test]
+fn events_moves_and_pointer_actions_are_recorded_with_their_tick() {
+    let mut rt = GameRuntime::new();
+    rt.start_game(SolitaireType::Klondike);
+    rt.update(0.016);
+    rt.update(0.016);
+    rt.update(0.016);
+
+    // 何もない場所を押して動かして離す（動かした操作は記録しない）
+    // フレームの間の操作は、直前のフレームのティックに記録される
+    let start_tick = 3;
+    rt.push_pointer(pointer(PointerKind::Down)).expect("座標は有限");
+    rt.push_pointer(pointer(PointerKind::Move)).expect("座標は有限");
+    rt.push_pointer(pointer(PointerKind::Up)).expect("座標は有限");
+    rt.update(0.016);
+    rt.auto_play_one_move()
+        .expect("最初の局面には指せる手がある");
+    rt.update(0.016);
+
+    let timeline = rt.timeline(start_tick);
+    assert!(timeline.iter().all(|entry| entry.tick >= start_tick));
+    assert!(timeline.windows(2).all(|pair| pair[0].tick <= pair[1].tick));
+
+    let pointers: Vec<PointerKind> = timeline
+        .iter()
+        .filter_map(|entry| match &entry.record {
+            TimelineRecord::Pointer { pointer } => Some(pointer.kind),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(pointers, vec![PointerKind::Down, PointerKind::Up]);
+
+    assert!(timeline
+        .iter()
+        .any(|entry| entry.tick == start_tick
+            && matches!(entry.record, TimelineRecord::Pointer { .. })));
+    let draw = timeline
+        .iter()
+        .find_map(|entry| match &entry.record {
+            TimelineRecord::Move { record } => Some((entry.tick, *record)),
+            _ => None,
+        })
+        .expect("打った手が記録されている");
+    assert!(draw.0 > start_tick);
+    assert_ne!(
+        (draw.1.from, draw.1.from_index),
+        (draw.1.to, draw.1.to_index)
+    );
+
+    // 配信で取り出したイベントは、すべて発生した順にタイムラインに残っている
+    let events = rt.drain_events();
+    let recorded: Vec<GameEvent> = rt
+        .timeline(0)
+        .into_iter()
+        .filter_map(|entry| match entry.record {
+            TimelineRecord::Event { event } => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(recorded, events);
+}
+
+#[test]
+fn the_recorder_keeps_only_the_newest_entries() {
+    let mut timeline = TimelineRecorder::with_capacity(3);
+    for tick in 0..5 {
+        timeline.record(TimelineRecord::Pointer {
+            pointer: pointer(PointerKind::Down),
+        });
+        timeline.record(TimelineRecord::Event {
+            event: GameEvent::TimerTick {
+                elapsed_seconds: tick,
+            },
+        });
+        timeline.advance_tick();
+    }
+
+    assert_eq!(timeline.len(), 3);
+    assert_eq!(timeline.dropped(), 7);
+    let ticks: Vec<u64> = timeline.since(0).map(|entry| entry.tick).collect();
+    assert_eq!(ticks, vec![3, 4, 4]);
+    assert_eq!(timeline.since(4).count(), 2);
+    assert_eq!(timeline.since(5).count(), 0);
+    assert_eq!(
+        timeline.recent_events(1),
+        vec![GameEvent::TimerTick { elapsed_seconds: 4 }]
+    );
+}
+
+#[test]
+fn the_debug_overlay_reads_recent_events_from_the_timeline() {
+    let mut rt = GameRuntime::new();
+    rt.set_debug_mode(true);
+    rt.start_game(SolitaireType::Klondike);
+    for _ in 0..30 {
+        rt.auto_play_one_move();
+        rt.update(0.016);
+    }
+    rt.drain_events();
+
+    let recorded: Vec<GameEvent> = rt
+        .timeline(0)
+        .into_iter()
+        .filter_map(|entry| match entry.record {
+            TimelineRecord::Event { event } => Some(event),
+            _ => None,
+        })
+        .collect();
+    assert!(!recorded.is_empty());
+    let recent = rt.debug_info().recent_events;
+    assert!(recorded.ends_with(&recent));
+    assert_eq!(recent.len(), recorded.len().min(20));
+}
@@ -0,0 +1,107 @@
+// =============================================================================
+// フレームごとの時間予算による分割実行のテスト
+// =============================================================================
+// 時間枠を使い切ると処理を途中で止めて続きから進められること、
+// 勝ち筋の確認を複数のフレームに分けても一度に調べた場合と同じ結果になり、
+// その間の進み具合がSearchProgressイベントで届くことを確認します。
+//
+// 実行方法：cargo test --test timeslice
+// =============================================================================
+
+use ecs_wasm_solitaire::ecs::{System, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::solitaire::{SolitaireManager, SolitaireType};
+use ecs_wasm_solitaire::solver::{WinnabilitySystem, WinnabilityWatch};
+use ecs_wasm_solitaire::timeslice::{
+    self, FrameBudget, Incremental, Progress, TimeSlice, STEPS_PER_CHECK,
+};
+
+/// 指定した回数だけ数える処理
+struct CountTo {
+    current: u32,
+    target: u32,
+}
+
+impl Incremental for CountTo {
+    type Output = u32;
+
+    fn advance(&mut self, steps: u32) -> Progress<u32> {
+        self.current = self.target.min(self.current.saturating_add(steps));
+        if self.current == self.target {
+            Progress::Done(self.current)
+        } else {
+            Progress::Pending(self.current as f32 / self.target as f32)
+        }
+    }
+}
+
+#[test]
+fn an_exhausted_slice_still_makes_one_chunk_of_progress() {
+    let mut task = CountTo {
+        current: 0,
+        target: STEPS_PER_CHECK * 3,
+    };
+    let empty = TimeSlice::new(0.0);
+    assert!(empty.is_exhausted());
+
+    assert_eq!(
+        timeslice::run_until(&mut task, &empty),
+        Progress::Pending(1.0 / 3.0)
+    );
+    assert_eq!(task.current, STEPS_PER_CHECK);
+    assert_eq!(
+        timeslice::run_until(&mut task, &TimeSlice::new(1_000.0)),
+        Progress::Done(STEPS_PER_CHECK * 3)
+    );
+    assert_eq!(
+        timeslice::run_to_completion(&mut CountTo {
+            current: 0,
+            target: 10
+        }),
+        10
+    );
+}
+
+/// 勝ち筋のない配り札（シード22）を配ったワールドで勝ち筋の確認が終わるまでシステムを動かし、
+/// (かかったフレーム数, 確認状況, 届いた進み具合)を返す
+fn check_with_budget(ms_per_frame: f64) -> (u32, WinnabilityWatch, Vec<f32>) {
+    let mut world = World::new();
+    world.insert_resource(EventQueue::new());
+    world.insert_resource(FrameBudget { ms_per_frame });
+    let game = SolitaireManager::start_new_game_with_seed(&mut world, SolitaireType::Klondike, 22);
+
+    let mut progress = Vec::new();
+    for frame in 1..=1_000 {
+        WinnabilitySystem.update(&mut world, 0.016);
+        progress.extend(
+            world
+                .get_resource_mut::<EventQueue>()
+                .expect("イベントキューがある")
+                .drain()
+                .into_iter()
+                .filter_map(|event| match event {
+                    GameEvent::SearchProgress { progress, .. } => Some(progress),
+                    _ => None,
+                }),
+        );
+        if let Some(watch) = world.get_component::<WinnabilityWatch>(game) {
+            return (frame, *watch, progress);
+        }
+    }
+    panic!("勝ち筋の確認が終わらない");
+}
+
+#[test]
+fn a_check_spread_over_frames_matches_a_one_shot_check() {
+    let (frames, one_shot, progress) = check_with_budget(1_000.0);
+    assert_eq!(frames, 1);
+    assert!(progress.is_empty(), "1フレームで終われば進み具合は届かない");
+
+    let (frames, sliced, progress) = check_with_budget(0.0);
+    assert!(frames > 1, "予算がなければ複数のフレームに分かれる");
+    assert_eq!(sliced, one_shot);
+    assert_eq!(sliced.lost_at_move, Some(0));
+    assert_eq!(progress.len(), frames as usize);
+    assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(progress.last(), Some(&1.0));
+}
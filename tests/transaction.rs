@@ -0,0 +1,163 @@
+// =============================================================================
+// 手の適用のトランザクションのテスト
+// =============================================================================
+// commit()するまでワールドが書き換わらず、途中で失敗したトランザクションを捨てると
+// 盤面が元のままになること、タブローの列ごとの移動でカード・スコア・移動履歴が
+// まとめて反映され、露出したカードをめくると手数に数えずに得点とCardRevealedイベントが
+// 加わることを確認します。
+//
+// 実行方法：cargo test --test transaction
+// =============================================================================
+
+use ecs_wasm_solitaire::clock::GameClock;
+use ecs_wasm_solitaire::ecs::{Entity, World};
+use ecs_wasm_solitaire::events::{EventQueue, GameEvent};
+use ecs_wasm_solitaire::hint::{HintEngine, HintLocation};
+use ecs_wasm_solitaire::scenario::BoardBuilder;
+use ecs_wasm_solitaire::solitaire::{
+    CardLocation, CardStack, MoveLog, MoveRecord, SolitaireCard, SolitaireGameState,
+    REVEAL_POINTS,
+};
+use ecs_wasm_solitaire::transaction::MoveTransaction;
+
+/// タブローの場所
+fn tableau(index: u32) -> HintLocation {
+    HintLocation {
+        location: CardLocation::Tableau,
+        index,
+    }
+}
+
+/// 1列目に列ごと動かせるカード、2列目に受け取るカードを置いた盤面
+fn board() -> World {
+    let mut world = World::new();
+    BoardBuilder::new()
+        .tableau(0, 1, &["2S", "9D", "8C", "7H"])
+        .tableau(1, 0, &["10S"])
+        .build(&mut world)
+        .expect("シナリオから盤面を作れる");
+    world.insert_resource(EventQueue::new());
+    world
+}
+
+/// 列のカード（下から上の順）
+fn column(world: &World, index: u32) -> Vec<(Entity, SolitaireCard)> {
+    let mut cards: Vec<(Entity, SolitaireCard)> = world
+        .query::<SolitaireCard>()
+        .filter(|(_, card)| {
+            card.location_type == CardLocation::Tableau && card.position_in_location == index
+        })
+        .map(|(entity, card)| (entity, card.clone()))
+        .collect();
+    cards.sort_by(|a, b| a.1.display_y.total_cmp(&b.1.display_y));
+    cards
+}
+
+/// (スコア, 手数, 移動記録の件数)
+fn progress(world: &World) -> (u32, u32, usize) {
+    let state = world.query::<SolitaireGameState>().next().unwrap().1;
+    let log = world.query::<MoveLog>().next().unwrap().1;
+    (state.score, state.move_count, log.moves.len())
+}
+
+#[test]
+fn nothing_is_applied_until_commit() {
+    let mut world = board();
+    let (card, before) = column(&world, 0).pop().expect("1列目にカードがある");
+    let stack = world
+        .query::<CardStack>()
+        .next()
+        .map(|(entity, _)| entity)
+        .expect("スタックがある");
+    let stack_len = world.get_component::<CardStack>(stack).unwrap().len();
+
+    // 途中で失敗したトランザクションを捨てると、先に書き換えた分も反映されない
+    let mut transaction = MoveTransaction::new();
+    transaction
+        .card(&world, card)
+        .unwrap()
+        .set_location(CardLocation::Tableau, 1);
+    transaction.stack(&world, stack).unwrap().cards.clear();
+    transaction.game_state(&world).unwrap().record_move(10);
+    assert!(transaction.card(&world, Entity(9999)).is_err());
+    drop(transaction);
+    assert_eq!(world.get_component::<SolitaireCard>(card), Some(&before));
+    assert_eq!(
+        world.get_component::<CardStack>(stack).unwrap().len(),
+        stack_len
+    );
+    assert_eq!(progress(&world), (0, 0, 0));
+
+    // 同じトランザクションの中では書き換えた後の値が見え、commit()でまとめて反映される
+    let mut transaction = MoveTransaction::new();
+    transaction
+        .card(&world, card)
+        .unwrap()
+        .set_location(CardLocation::Tableau, 1);
+    assert_eq!(
+        transaction.card(&world, card).unwrap().position_in_location,
+        1
+    );
+    transaction.stack(&world, stack).unwrap().cards.clear();
+    transaction.game_state(&world).unwrap().record_move(10);
+    transaction.record(MoveRecord::new(
+        &before,
+        CardLocation::Tableau,
+        1,
+        10,
+        &GameClock::from_world(&world),
+    ));
+    transaction.commit(&mut world);
+    assert_eq!(
+        world
+            .get_component::<SolitaireCard>(card)
+            .unwrap()
+            .position_in_location,
+        1
+    );
+    assert_eq!(world.get_component::<CardStack>(stack).unwrap().len(), 0);
+    assert_eq!(progress(&world), (10, 1, 1));
+}
+
+#[test]
+fn tableau_runs_move_with_score_and_history_together() {
+    let mut world = board();
+    let run: Vec<Entity> = column(&world, 0)[1..]
+        .iter()
+        .map(|(entity, _)| *entity)
+        .collect();
+
+    HintEngine::move_cards(&mut world, tableau(0), 3, tableau(1)).expect("9♦8♣7♥を10♠へ");
+
+    // 動かした3枚は順番どおり2列目に並び、露出したカードは表向きになる
+    let target: Vec<Entity> = column(&world, 1)
+        .iter()
+        .map(|(entity, _)| *entity)
+        .collect();
+    assert_eq!(target[1..], run[..]);
+    let source = column(&world, 0);
+    assert_eq!(source.len(), 1);
+    assert!(source[0].1.is_face_up);
+
+    // めくった得点は加わるが手数には数えず、移動記録は1手分だけ増えてめくった手として残る
+    let (score, move_count, moves) = progress(&world);
+    assert_eq!(score, REVEAL_POINTS);
+    assert_eq!(move_count, 1);
+    assert_eq!(moves, 1);
+    let log = world.query::<MoveLog>().next().unwrap().1;
+    assert!(log.moves[0].revealed);
+    let events = world.get_resource_mut::<EventQueue>().unwrap().drain();
+    assert!(events.contains(&GameEvent::CardRevealed {
+        card_id: source[0].0.id(),
+        column: 0,
+        points: REVEAL_POINTS,
+    }));
+
+    // 移動できない手は何も変えない
+    let before = (column(&world, 0), column(&world, 1), progress(&world));
+    assert!(HintEngine::move_cards(&mut world, tableau(1), 5, tableau(0)).is_err());
+    assert_eq!(
+        (column(&world, 0), column(&world, 1), progress(&world)),
+        before
+    );
+}
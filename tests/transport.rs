@@ -0,0 +1,111 @@
+// =============================================================================
+// 通信経路の選択のテスト
+// =============================================================================
+// カーソル位置などの優先度がLowのメッセージだけが、ルームの全員と
+// データチャネルが開いている場合に限ってデータチャネルで送られること、
+// データチャネルを申し込む側が片方だけに決まることを確認します。
+//
+// 実行方法：cargo test --test transport
+// =============================================================================
+
+use ecs_wasm_solitaire::network::{MessagePriority, NetworkManager};
+use ecs_wasm_solitaire::protocol::WebSocketMessage;
+use ecs_wasm_solitaire::transport::{
+    message_priority, should_initiate, Transport, TransportSelector,
+};
+
+fn cursor() -> WebSocketMessage {
+    WebSocketMessage::MousePosition {
+        player_id: "alice".to_string(),
+        x: 10.0,
+        y: 20.0,
+        timestamp: 1,
+        sequence: None,
+    }
+}
+
+fn ping() -> WebSocketMessage {
+    WebSocketMessage::Ping {
+        ping_id: 1,
+        client_time_ms: 1,
+        clock_offset_ms: None,
+    }
+}
+
+fn selector_with_peers(peers: &[&str]) -> TransportSelector {
+    let mut selector = TransportSelector::new();
+    selector.set_room_peers(peers.iter().map(|peer| peer.to_string()).collect());
+    selector
+}
+
+#[test]
+fn only_cursor_updates_have_low_priority() {
+    assert_eq!(message_priority(&cursor()), MessagePriority::Low);
+    assert_eq!(message_priority(&ping()), MessagePriority::High);
+    let leave = WebSocketMessage::PlayerLeft {
+        player_id: "alice".to_string(),
+        player_name: "Alice".to_string(),
+    };
+    assert_eq!(message_priority(&leave), MessagePriority::Normal);
+}
+
+#[test]
+fn data_channel_is_used_only_when_every_peer_is_connected() {
+    let mut selector = selector_with_peers(&["bob", "carol"]);
+    assert_eq!(
+        NetworkManager::select_transport(&selector, &cursor()),
+        Transport::WebSocket
+    );
+
+    selector.channel_opened("bob");
+    assert!(selector.is_open("bob"));
+    assert!(!selector.all_channels_open());
+    assert_eq!(
+        NetworkManager::select_transport(&selector, &cursor()),
+        Transport::WebSocket
+    );
+
+    selector.channel_opened("carol");
+    assert_eq!(
+        NetworkManager::select_transport(&selector, &cursor()),
+        Transport::DataChannel
+    );
+    assert_eq!(
+        NetworkManager::select_transport(&selector, &ping()),
+        Transport::WebSocket,
+        "優先度がLow以外"
+    );
+
+    selector.channel_closed("carol");
+    assert_eq!(
+        NetworkManager::select_transport(&selector, &cursor()),
+        Transport::WebSocket
+    );
+}
+
+#[test]
+fn room_changes_drop_channels_of_departed_peers() {
+    let mut selector = selector_with_peers(&["bob"]);
+    selector.channel_opened("mallory");
+    assert!(!selector.is_open("mallory"), "ルームにいない相手");
+
+    selector.channel_opened("bob");
+    assert_eq!(
+        selector.select(MessagePriority::Low),
+        Transport::DataChannel
+    );
+
+    selector.set_room_peers(vec!["carol".to_string()]);
+    assert!(!selector.is_open("bob"));
+    assert_eq!(selector.select(MessagePriority::Low), Transport::WebSocket);
+
+    selector.set_room_peers(Vec::new());
+    assert!(!selector.all_channels_open(), "他のプレイヤーがいない");
+}
+
+#[test]
+fn exactly_one_side_initiates() {
+    assert!(should_initiate("alice", "bob"));
+    assert!(!should_initiate("bob", "alice"));
+    assert!(!should_initiate("alice", "alice"));
+}
@@ -0,0 +1,144 @@
+// =============================================================================
+// ターミナル版（TUI）のテスト
+// =============================================================================
+// キー入力から移動元の選択・カードの移動・ヒント・終了への対応と、
+// 画面の描画（スコア・山・カードの記号・選択中の移動元の反転表示）を確認します。
+// 描画はターミナルの代わりにバッファへ書き出して中身を調べます。
+//
+// 実行方法：cargo test --test tui
+// =============================================================================
+
+// ターミナル版はネイティブ環境のみ
+#![cfg(not(target_arch = "wasm32"))]
+
+use crossterm::event::KeyCode;
+use ecs_wasm_solitaire::hint::HintLocation;
+use ecs_wasm_solitaire::scenario::{BoardBuilder, Scenario};
+use ecs_wasm_solitaire::solitaire::CardLocation;
+use ecs_wasm_solitaire::tui::Tui;
+
+/// 盤面を描画した文字列（エスケープシーケンスを含む）
+fn rendered(tui: &Tui) -> String {
+    let mut out = Vec::new();
+    tui.render(&mut out).expect("バッファへは描画できる");
+    String::from_utf8(out).expect("描画結果はUTF-8")
+}
+
+/// 列0の赤の6を列1の黒の7へ、ウェイストのエースを組札へ動かせる盤面
+fn small_board() -> Tui {
+    Tui::from_board(
+        &BoardBuilder::new()
+            .tableau(0, 1, &["KC", "6H"])
+            .tableau(1, 0, &["7S"])
+            .waste(&["AD"])
+            .deck(&["2C"]),
+    )
+    .expect("シナリオから盤面を作れる")
+}
+
+#[test]
+fn q_quits_and_other_keys_keep_playing() {
+    let mut tui = small_board();
+    assert!(tui.handle_key(KeyCode::Char('x')));
+    assert!(tui.handle_key(KeyCode::Esc));
+    assert!(!tui.handle_key(KeyCode::Char('q')));
+}
+
+#[test]
+fn number_keys_select_a_column_and_then_move_to_another() {
+    let mut tui = small_board();
+    tui.handle_key(KeyCode::Char('1'));
+    assert_eq!(
+        tui.selected(),
+        Some(HintLocation {
+            location: CardLocation::Tableau,
+            index: 0,
+        })
+    );
+
+    tui.handle_key(KeyCode::Char('2'));
+    assert_eq!(tui.selected(), None);
+    assert_eq!(tui.message(), "");
+    let board = Scenario::from_world(tui.world());
+    assert_eq!(board.tableau[0], vec!["KC".to_string()]);
+    assert_eq!(board.tableau[1], vec!["7S".to_string(), "6H".to_string()]);
+}
+
+#[test]
+fn w_and_f_move_the_waste_card_to_a_foundation() {
+    let mut tui = small_board();
+    tui.handle_key(KeyCode::Char('w'));
+    tui.handle_key(KeyCode::Char('f'));
+    let board = Scenario::from_world(tui.world());
+    assert!(board.waste.is_empty());
+    assert!(board
+        .foundations
+        .iter()
+        .any(|pile| pile == &vec!["AD".to_string()]));
+}
+
+#[test]
+fn escape_cancels_the_selection_and_illegal_moves_report_an_error() {
+    let mut tui = small_board();
+    tui.handle_key(KeyCode::Char('w'));
+    tui.handle_key(KeyCode::Esc);
+    assert_eq!(tui.selected(), None);
+
+    // 移動元を選ばずに組札を指定
+    tui.handle_key(KeyCode::Char('f'));
+    assert!(tui.message().contains("移動元"));
+
+    // 赤の6を組札へは置けない
+    tui.handle_key(KeyCode::Char('1'));
+    tui.handle_key(KeyCode::Char('f'));
+    assert_eq!(tui.selected(), None);
+    assert!(!tui.message().is_empty());
+    assert_eq!(Scenario::from_world(tui.world()).tableau[0].len(), 2);
+}
+
+#[test]
+fn d_draws_from_the_deck() {
+    let mut tui = small_board();
+    tui.handle_key(KeyCode::Char('d'));
+    let board = Scenario::from_world(tui.world());
+    assert!(board.deck.is_empty());
+    assert_eq!(board.waste.last().map(String::as_str), Some("2C"));
+}
+
+#[test]
+fn h_shows_a_hint_without_moving_and_a_plays_it() {
+    let mut tui = small_board();
+    let before = Scenario::from_world(tui.world());
+    tui.handle_key(KeyCode::Char('h'));
+    assert!(!tui.message().is_empty());
+    assert_eq!(Scenario::from_world(tui.world()), before);
+
+    tui.handle_key(KeyCode::Char('a'));
+    assert_ne!(Scenario::from_world(tui.world()), before);
+}
+
+#[test]
+fn render_shows_the_status_piles_and_cards() {
+    let tui = Tui::new(42);
+    let screen = rendered(&tui);
+    assert!(screen.contains("シード 42"));
+    assert!(screen.contains("##(24)"));
+
+    let board = small_board();
+    let screen = rendered(&board);
+    // 裏向きのカードは##、表向きのカードはランクとスートの記号
+    assert!(screen.contains("6♥"));
+    assert!(screen.contains("7♠"));
+    assert!(screen.contains("A♦"));
+    assert!(!screen.contains("K♣"));
+}
+
+#[test]
+fn render_highlights_the_selected_source() {
+    let mut tui = small_board();
+    assert!(!rendered(&tui).contains("\x1b[7m"));
+    tui.handle_key(KeyCode::Char('w'));
+    let screen = rendered(&tui);
+    assert!(screen.contains("\x1b[7m捨 "));
+    assert!(screen.contains("移動先を選んでください"));
+}
@@ -0,0 +1,135 @@
+// =============================================================================
+// チュートリアルのテスト
+// =============================================================================
+// データファイルの組み込みチュートリアルが最後まで進められること、
+// 決められた手以外が拒否されること、強調表示とクライアント向け状態に
+// 現在の手順が反映されることを確認します。
+//
+// 実行方法：cargo test --test tutorial
+// =============================================================================
+
+use ecs_wasm_solitaire::client_state::ClientState;
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::protocol::MoveLocation;
+use ecs_wasm_solitaire::scenario::Scenario;
+use ecs_wasm_solitaire::solitaire::{CardLocation, CardRank, CardSuit, SolitaireCard};
+use ecs_wasm_solitaire::tutorial::{
+    builtin_tutorials, find_tutorial, perform, Tutorial, TutorialAction, TutorialProgress,
+};
+use std::collections::HashSet;
+
+/// 場所指定を作成
+fn pile(location: CardLocation, index: u32) -> MoveLocation {
+    MoveLocation { location, index }
+}
+
+#[test]
+fn builtin_tutorials_can_be_completed() {
+    let tutorials = builtin_tutorials();
+    assert!(!tutorials.is_empty(), "data/tutorials.jsonを読み込める");
+
+    let ids: HashSet<&str> = tutorials
+        .iter()
+        .map(|tutorial| tutorial.id.as_str())
+        .collect();
+    assert_eq!(ids.len(), tutorials.len(), "チュートリアルIDは重複しない");
+
+    for tutorial in &tutorials {
+        let mut world = World::new();
+        let entity = tutorial
+            .start(&mut world)
+            .expect("組み込みチュートリアルは開始できる");
+        for (index, step) in tutorial.steps.iter().enumerate() {
+            perform(&mut world, entity, &step.action)
+                .unwrap_or_else(|e| panic!("{}の手順{}: {}", tutorial.id, index + 1, e));
+        }
+
+        let progress = world
+            .get_component::<TutorialProgress>(entity)
+            .expect("チュートリアルの進み具合がある");
+        assert!(progress.is_completed(), "{}", tutorial.id);
+        assert!(progress.highlight(&world).is_none());
+    }
+}
+
+#[test]
+fn only_the_scripted_move_is_accepted() {
+    let tutorial = find_tutorial("basics").expect("basicsチュートリアルがある");
+    let mut world = World::new();
+    let entity = tutorial.start(&mut world).expect("開始できる");
+    let before = Scenario::from_world(&world);
+
+    // 手順1は♥Aを組札へ動かす手なので、デッキから引く手は受け付けない
+    assert!(perform(&mut world, entity, &TutorialAction::Draw).is_err());
+    // 同じカードでも移動先が違えば受け付けない
+    let wrong_target = TutorialAction::Move {
+        from: pile(CardLocation::Tableau, 0),
+        to: pile(CardLocation::Foundation, 1),
+        count: 1,
+    };
+    assert!(perform(&mut world, entity, &wrong_target).is_err());
+
+    assert_eq!(
+        Scenario::from_world(&world),
+        before,
+        "拒否した手は盤面を変えない"
+    );
+    let progress = world
+        .get_component::<TutorialProgress>(entity)
+        .expect("チュートリアルの進み具合がある");
+    assert_eq!(progress.current_step, 0);
+
+    perform(&mut world, entity, &tutorial.steps[0].action).expect("決められた手は受け付ける");
+    let progress = world
+        .get_component::<TutorialProgress>(entity)
+        .expect("チュートリアルの進み具合がある");
+    assert_eq!(progress.current_step, 1);
+}
+
+#[test]
+fn client_state_exposes_the_current_step_and_highlight() {
+    let tutorial = find_tutorial("basics").expect("basicsチュートリアルがある");
+    let mut world = World::new();
+    let entity = tutorial.start(&mut world).expect("開始できる");
+
+    let view = ClientState::from_world(&world, Some(entity))
+        .tutorial
+        .expect("チュートリアル中は進み具合が入る");
+    assert_eq!(view.step, 0);
+    assert_eq!(view.step_count as usize, tutorial.steps.len());
+    assert_eq!(view.message.as_ref(), Some(&tutorial.steps[0].message));
+
+    // 強調表示するのは動かす♥Aと、移動元・移動先の山
+    let highlight = view.highlight.expect("強調表示がある");
+    let ace = world
+        .query::<SolitaireCard>()
+        .find(|(_, card)| card.suit == CardSuit::Hearts && card.rank == CardRank::Ace)
+        .map(|(entity, _)| entity.id());
+    assert_eq!(highlight.card_id, ace);
+    assert_eq!(highlight.from.location, CardLocation::Tableau);
+    assert_eq!(highlight.to.location, CardLocation::Foundation);
+
+    assert!(ClientState::from_world(&World::new(), None)
+        .tutorial
+        .is_none());
+}
+
+#[test]
+fn tutorials_load_from_json() {
+    let json = r#"[{
+        "id": "draw_once",
+        "title": "引いてみよう",
+        "scenario": { "deck": ["AS"] },
+        "steps": [{ "message": "デッキから引きましょう", "action": { "type": "draw" } }]
+    }]"#;
+    let tutorials = Tutorial::parse_all(json).expect("チュートリアルのJSONを読み込める");
+    assert_eq!(tutorials.len(), 1);
+    assert_eq!(tutorials[0].steps[0].action, TutorialAction::Draw);
+
+    assert!(Tutorial::parse_all("{ not json").is_err());
+    let no_steps = Tutorial {
+        steps: Vec::new(),
+        ..tutorials[0].clone()
+    };
+    assert!(no_steps.start(&mut World::new()).is_err());
+}
@@ -0,0 +1,696 @@
+// =============================================================================
+// WebAssembly版APIのブラウザテスト
+// =============================================================================
+// JavaScriptから呼び出す公開API（#[wasm_bindgen]の関数）をヘッドレスブラウザ上で
+// 実行し、js_sys / web_sysを使う処理がWebAssembly環境で壊れていないかを確認します。
+//
+// 実行方法：
+// - wasm-pack test --headless --firefox -- --features wasm
+// - cargo test --target wasm32-unknown-unknown --features wasm --test web
+//   （wasm-bindgen-test-runnerとブラウザのドライバーが必要）
+// =============================================================================
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use ecs_wasm_solitaire::{
+    auto_play_until_stuck, clear_selection, connection_tick, destroy_session, dump_world,
+    get_clock_offset, get_connection_status, get_hint, get_players, get_puzzle_progress, get_reactions,
+    get_solitaire_state, initialize_game, join_room, list_puzzles, list_rooms, list_sessions,
+    list_tutorials, move_card, get_network_status, get_transport_status, network_join, network_join_room, network_receive,
+    network_send_action, network_send_cursor, network_set_connected, network_subscribe, network_take_outgoing,
+    network_unsubscribe, notify_visibility, push_pointer_event, push_reaction, record_pong, restart_tutorial,
+    resume_session, route_message, rtc_handle_signal, rtc_leave_room, rtc_set_room,
+    rtc_take_messages, rtc_take_signals, select_card, server_to_local_time, set_animation_settings, set_event_callback, set_theme, set_viewport,
+    start_new_game, start_puzzle, start_tutorial, storage, suspend_session, tutorial_action,
+    update_game,
+};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// 勝てる配り札が出るまでに試すゲーム数の上限
+/// （ヒントエンジンによる自動プレイの勝率は1割弱なので、十分に大きな値にしておく）
+const MAX_GAMES_UNTIL_WIN: usize = 200;
+
+/// 1フレーム分の経過時間（ミリ秒）
+const FRAME_MS: f64 = 16.0;
+
+/// 現在のゲーム状態をJSONとして取得
+fn state() -> Value {
+    serde_json::from_str(&get_solitaire_state(None)).expect("ゲーム状態はJSONとして読める")
+}
+
+/// 場札・組札・山札のカード総数
+fn total_cards(state: &Value) -> u64 {
+    let piles = &state["piles"];
+    let count = |pile: &Value| pile.as_array().map_or(0, |cards| cards.len() as u64);
+    let nested = |piles: &Value| {
+        piles
+            .as_array()
+            .map_or(0, |piles| piles.iter().map(count).sum::<u64>())
+    };
+    piles["deck_count"].as_u64().unwrap_or(0)
+        + count(&piles["waste"])
+        + nested(&piles["foundations"])
+        + nested(&piles["tableau"])
+}
+
+/// 送信待ちのメッセージをJSONとして取り出す（Reliableで包まれたメッセージは中身を取り出す）
+fn take_outgoing(session_id: &str) -> Vec<Value> {
+    let messages: Vec<Value> = serde_json::from_str(&network_take_outgoing(Some(session_id.to_string())))
+        .expect("送信するメッセージはJSON");
+    messages
+        .into_iter()
+        .map(|message| if message["type"] == "Reliable" { message["message"].clone() } else { message })
+        .collect()
+}
+
+/// 保存データ（localStorage）をすべて消す
+fn clear_local_storage() {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .expect("localStorageが利用できる")
+        .clear()
+        .expect("localStorageを消去できる");
+}
+
+#[wasm_bindgen_test]
+fn initialize_game_starts_without_a_game() {
+    assert!(initialize_game(None));
+
+    let state = state();
+    assert_eq!(state["phase"], "not_started");
+    assert_eq!(total_cards(&state), 0);
+}
+
+#[wasm_bindgen_test]
+fn start_new_game_deals_klondike() {
+    assert!(initialize_game(None));
+    assert_eq!(start_new_game("テスト", None), "default");
+
+    let state = state();
+    assert_eq!(state["phase"], "playing");
+    assert_eq!(state["piles"]["deck_count"], 24);
+    assert_eq!(total_cards(&state), 52);
+
+    // タブローは左から1〜7枚で、各列の一番上だけが表向き
+    let tableau = state["piles"]["tableau"].as_array().expect("タブローは配列");
+    for (column, cards) in tableau.iter().enumerate() {
+        let cards = cards.as_array().expect("列は配列");
+        assert_eq!(cards.len(), column + 1);
+        assert_eq!(cards.last().map(|card| &card["face_up"]), Some(&Value::Bool(true)));
+    }
+}
+
+#[wasm_bindgen_test]
+fn animation_settings_control_the_deal_animation() {
+    let animating = || {
+        state()["piles"]["tableau"]
+            .as_array()
+            .expect("タブローは配列")
+            .iter()
+            .flat_map(|column| column.as_array().cloned().unwrap_or_default())
+            .filter(|card| card["animating"] == true)
+            .count()
+    };
+
+    assert!(initialize_game(None));
+    assert!(!set_animation_settings(r#"{"speed_multiplier": 10.0}"#, None));
+    assert!(!set_animation_settings("速く", None));
+
+    // 標準では配ったカードが落ちてくる
+    start_new_game("テスト", None);
+    assert_eq!(animating(), 28);
+    for _ in 0..60 {
+        update_game(FRAME_MS, None);
+    }
+    assert_eq!(animating(), 0, "1秒以内に配り終わる");
+
+    // 演出を省略する設定では配るアニメーションがない
+    assert!(initialize_game(None));
+    assert!(set_animation_settings(r#"{"reduced_motion": true}"#, None));
+    start_new_game("テスト", None);
+    assert_eq!(animating(), 0);
+
+    // すべて省略する設定にすると進行中のアニメーションもすぐに終わる
+    assert!(initialize_game(None));
+    assert!(set_animation_settings(r#"{"speed_multiplier": 0.25}"#, None));
+    start_new_game("テスト", None);
+    assert_eq!(animating(), 28);
+    assert!(set_animation_settings(r#"{"instant": true}"#, None));
+    assert_eq!(animating(), 0);
+}
+
+#[wasm_bindgen_test]
+fn theme_is_included_in_the_state() {
+    assert!(initialize_game(None));
+    assert_eq!(state()["theme"]["card_back"], "classic");
+    assert_eq!(state()["theme"]["table"], "green_felt");
+
+    assert!(!set_theme(r#"{"card_back": "plaid"}"#, None));
+    assert!(set_theme(r#"{"card_back": "ocean", "table": "midnight"}"#, None));
+    assert_eq!(state()["theme"]["card_back"], "ocean");
+    assert_eq!(state()["theme"]["table"], "midnight");
+    assert_eq!(state()["theme"]["share_with_room"], false);
+}
+
+#[wasm_bindgen_test]
+fn dump_world_lists_entities_and_components() {
+    assert!(initialize_game(None));
+    start_new_game("テスト", None);
+
+    let dump: Value = serde_json::from_str(&dump_world(None)).expect("ダンプはJSONとして読める");
+    let entities = dump["entities"].as_array().expect("エンティティは配列");
+    assert_eq!(dump["entity_count"].as_u64(), Some(entities.len() as u64));
+
+    let cards = entities
+        .iter()
+        .filter(|entity| {
+            entity["components"]
+                .as_array()
+                .is_some_and(|components| components.iter().any(|name| name == "SolitaireCard"))
+        })
+        .count();
+    assert_eq!(cards, 52);
+}
+
+#[wasm_bindgen_test]
+fn start_puzzle_replaces_the_board() {
+    let puzzles: Value = serde_json::from_str(&list_puzzles()).expect("一覧はJSONとして読める");
+    assert!(puzzles
+        .as_array()
+        .expect("一覧は配列")
+        .iter()
+        .any(|puzzle| puzzle["id"] == "free_the_spade_ace"));
+
+    assert!(initialize_game(None));
+    start_new_game("テスト", None);
+    assert!(!start_puzzle("no_such_puzzle", None));
+    assert!(start_puzzle("free_the_spade_ace", None));
+    update_game(FRAME_MS, None);
+
+    let dump: Value = serde_json::from_str(&dump_world(None)).expect("ダンプはJSONとして読める");
+    let cards = dump["entities"]
+        .as_array()
+        .expect("エンティティは配列")
+        .iter()
+        .filter(|entity| {
+            entity["components"]
+                .as_array()
+                .is_some_and(|components| components.iter().any(|name| name == "SolitaireCard"))
+        })
+        .count();
+    assert_eq!(cards, 10, "配り札のカードは片付けられている");
+
+    let progress: Value =
+        serde_json::from_str(&get_puzzle_progress(None)).expect("進み具合はJSONとして読める");
+    assert_eq!(progress["status"], "in_progress");
+    assert_eq!(progress["moves_used"], 0);
+}
+
+#[wasm_bindgen_test]
+fn tutorial_advances_only_on_the_scripted_move() {
+    let tutorials: Value = serde_json::from_str(&list_tutorials()).expect("一覧はJSONとして読める");
+    assert!(tutorials
+        .as_array()
+        .expect("一覧は配列")
+        .iter()
+        .any(|tutorial| tutorial["id"] == "basics"));
+
+    assert!(initialize_game(None));
+    assert!(
+        !restart_tutorial(None),
+        "チュートリアル中でなければやり直せない"
+    );
+    assert!(start_tutorial("basics", None));
+    assert_eq!(state()["tutorial"]["step"], 0);
+    assert!(state()["tutorial"]["highlight"]["card_id"].is_u64());
+
+    assert!(!tutorial_action(r#"{"type": "draw"}"#, None));
+    assert!(tutorial_action(
+        r#"{"type": "move", "from": {"type": "tableau", "position": 0},
+            "to": {"type": "foundation", "position": 0}}"#,
+        None
+    ));
+    assert_eq!(state()["tutorial"]["step"], 1);
+
+    assert!(restart_tutorial(None));
+    assert_eq!(state()["tutorial"]["step"], 0);
+    assert_eq!(
+        state()["piles"]["foundations"][0].as_array().map(Vec::len),
+        Some(0)
+    );
+}
+
+#[wasm_bindgen_test]
+fn selected_card_shows_drop_targets_and_hint_highlights() {
+    assert!(initialize_game(None));
+    assert!(start_tutorial("basics", None));
+    let ace = state()["tutorial"]["highlight"]["card_id"]
+        .as_u64()
+        .expect("動かすカードがある") as u32;
+
+    assert!(select_card(ace, None));
+    update_game(FRAME_MS, None);
+    let state_json = state();
+    let targets = state_json["piles"]["drop_targets"]
+        .as_array()
+        .expect("ドロップ先は配列");
+    assert!(targets.iter().any(|target| target["type"] == "Foundation"));
+    assert_eq!(state_json["piles"]["tableau"][0][0]["selected"], true);
+
+    clear_selection(None);
+    update_game(FRAME_MS, None);
+    assert_eq!(state()["piles"]["drop_targets"], serde_json::json!([]));
+
+    let hint: Value = serde_json::from_str(&get_hint(None)).expect("ヒントはJSONとして読める");
+    assert_ne!(hint["type"], "none");
+    let highlighted = |state: &Value| {
+        state["piles"]["tableau"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|column| column.as_array().into_iter().flatten())
+            .any(|card| card["highlighted"] == true)
+    };
+    assert!(highlighted(&state()));
+    for _ in 0..300 {
+        update_game(FRAME_MS, None);
+    }
+    assert!(!highlighted(&state()), "ヒントの強調表示は時間切れで消える");
+}
+
+#[wasm_bindgen_test]
+fn pointer_events_are_processed_on_the_next_update() {
+    assert!(initialize_game(None));
+    assert!(start_tutorial("basics", None));
+    assert!(!push_pointer_event(r#"{"kind": "hover", "x": 0, "y": 0}"#, None));
+
+    // タブロー1列目の♥Aを押す
+    assert!(push_pointer_event(r#"{"kind": "down", "x": 30, "y": 160}"#, None));
+    assert_eq!(state()["piles"]["tableau"][0][0]["selected"], false);
+    update_game(FRAME_MS, None);
+    assert_eq!(state()["piles"]["tableau"][0][0]["selected"], true);
+}
+
+#[wasm_bindgen_test]
+fn pointer_events_use_the_viewport_transform() {
+    assert!(initialize_game(None));
+    assert!(start_tutorial("basics", None));
+    assert!(!set_viewport(10.0, 0.0, 0.0, None));
+    assert!(set_viewport(2.0, -20.0, -100.0, None));
+    assert_eq!(state()["viewport"]["scale"], 2.0);
+
+    // 盤面の(30, 160)にある♥Aは、2倍に拡大した画面では(40, 220)に表示される
+    assert!(push_pointer_event(r#"{"kind": "down", "x": 40, "y": 220}"#, None));
+    update_game(FRAME_MS, None);
+    assert_eq!(state()["piles"]["tableau"][0][0]["selected"], true);
+}
+
+#[wasm_bindgen_test]
+fn reactions_show_for_a_few_seconds() {
+    let reactions = || -> Value {
+        serde_json::from_str(&get_reactions(None)).expect("リアクションはJSONとして読める")
+    };
+
+    assert!(initialize_game(None));
+    assert!(!push_reaction(r#"{"type": "Reaction", "player_id": "p2", "emote": "skull"}"#, None));
+    assert!(!push_reaction(r#"{"type": "LeaveRoom", "room_id": "r", "player_id": "p2"}"#, None));
+    assert!(push_reaction(r#"{"type": "Reaction", "player_id": "p2", "emote": "party"}"#, None));
+
+    update_game(FRAME_MS, None);
+    assert_eq!(reactions()[0]["player_id"], "p2");
+    assert_eq!(reactions()[0]["emote"], "party");
+
+    for _ in 0..300 {
+        update_game(FRAME_MS, None);
+    }
+    assert_eq!(reactions(), serde_json::json!([]), "リアクションは時間切れで消える");
+}
+
+#[wasm_bindgen_test]
+fn hidden_tab_pauses_the_game_loop() {
+    let reactions = || -> Value {
+        serde_json::from_str(&get_reactions(None)).expect("リアクションはJSONとして読める")
+    };
+
+    assert!(initialize_game(None));
+    assert!(push_reaction(r#"{"type": "Reaction", "player_id": "p2", "emote": "party"}"#, None));
+    update_game(FRAME_MS, None);
+
+    // 非表示の間は何フレーム呼ばれても進まない
+    notify_visibility(true);
+    for _ in 0..300 {
+        update_game(FRAME_MS, None);
+    }
+    assert_eq!(reactions()[0]["emote"], "party");
+    assert!(!network_send_cursor(1.0, 2.0, None));
+
+    // 表示に戻った直後の大きな経過時間は捨てる（3秒の表示時間のうち0.1秒ほど残る）
+    notify_visibility(false);
+    update_game(600_000.0, None);
+    for _ in 0..180 {
+        update_game(FRAME_MS, None);
+    }
+    assert_eq!(reactions()[0]["emote"], "party");
+
+    for _ in 0..30 {
+        update_game(FRAME_MS, None);
+    }
+    assert_eq!(reactions(), serde_json::json!([]), "再開後は時間切れで消える");
+}
+
+#[wasm_bindgen_test]
+fn connection_status_reports_quality_and_update_rates() {
+    let status = || -> Value {
+        serde_json::from_str(&get_connection_status(None)).expect("接続状態はJSONとして読める")
+    };
+
+    assert_eq!(connection_tick(false), None, "未接続ではPingを送らない");
+    assert_eq!(status()["connected"], false);
+    assert_eq!(status()["queue"]["backlog"], 0, "送信待ちのキューの溜まり具合も返す");
+    assert_eq!(status()["quality"], "offline");
+
+    let ping: Value = serde_json::from_str(&connection_tick(true).expect("接続直後はPingを送る")).unwrap();
+    assert_eq!(ping["type"], "Ping");
+    assert_eq!(status()["quality"], "unknown");
+
+    assert_eq!(get_clock_offset(), None, "Pongを受け取るまでは時計のずれは不明");
+
+    // サーバーの時計が1分進んでいる場合
+    let sent_at = ping["client_time_ms"].as_f64().expect("Pingに送信時刻が入る");
+    let pong = serde_json::json!({
+        "type": "Pong",
+        "ping_id": ping["ping_id"],
+        "client_time_ms": ping["client_time_ms"],
+        "server_time_ms": sent_at as u64 + 60_000,
+    })
+    .to_string();
+    assert!(record_pong(&pong));
+    assert!(!record_pong(&pong), "同じPongは2回数えない");
+    assert_eq!(status()["quality"], "good");
+    assert_eq!(status()["cursor_interval_ms"], 50);
+    assert!(status()["keyframe_interval_ms"].is_u64());
+
+    let offset = get_clock_offset().expect("Pongから時計のずれを推定する");
+    assert!((offset - 60_000.0).abs() < 1_000.0);
+    assert_eq!(server_to_local_time(sent_at + offset), sent_at);
+}
+
+#[wasm_bindgen_test]
+fn messages_use_the_websocket_until_data_channels_open() {
+    let status = || -> Value {
+        serde_json::from_str(&get_transport_status()).expect("通信経路の状態はJSONとして読める")
+    };
+    let cursor = serde_json::json!({
+        "type": "MousePosition",
+        "player_id": "player-b",
+        "x": 10.0,
+        "y": 20.0,
+        "timestamp": 1,
+    })
+    .to_string();
+
+    // 相手のIDの方が小さいので、こちらからは申し込まない
+    assert!(rtc_set_room("player-b", "room-1", r#"["player-a", "player-b"]"#));
+    assert!(!rtc_set_room("player-b", "room-1", "not json"));
+    assert_eq!(status()["peers"], 1);
+    assert_eq!(status()["open_channels"], 0);
+    assert_eq!(status()["all_open"], false);
+    assert_eq!(rtc_take_signals(), "[]");
+
+    // データチャネルが開くまではWebSocketで送る
+    assert_eq!(route_message(&cursor), Some(cursor.clone()));
+    assert_eq!(route_message("not json"), Some("not json".to_string()));
+    assert_eq!(rtc_take_messages(), "[]");
+
+    // 申し込んでいない相手からの応答と、ルーム外からの交渉は受け付けない
+    let signal = |from: &str, to: &str| {
+        serde_json::json!({
+            "type": "RtcSignal",
+            "room_id": "room-1",
+            "from_player_id": from,
+            "to_player_id": to,
+            "signal": { "kind": "answer", "sdp": "v=0" },
+        })
+        .to_string()
+    };
+    assert!(!rtc_handle_signal(&signal("player-a", "player-b")));
+    assert!(!rtc_handle_signal(&signal("player-z", "player-b")));
+    assert!(!rtc_handle_signal(&signal("player-a", "player-c")));
+
+    rtc_leave_room();
+    assert_eq!(status()["peers"], 0);
+}
+
+#[wasm_bindgen_test]
+fn network_client_relays_through_a_javascript_websocket() {
+    let session = || Some("network".to_string());
+    let status = || -> Value {
+        serde_json::from_str(&get_network_status(session())).expect("通信の状態はJSONとして読める")
+    };
+    let take = || take_outgoing("network");
+    assert!(initialize_game(session()));
+    assert!(!network_join_room("room-1", None, session()), "参加前");
+
+    network_set_connected(true, session());
+    assert!(network_join("Alice", session()));
+    assert!(network_join_room("room-1", None, session()));
+    assert_eq!(take()[0]["type"], "PlayerJoin");
+
+    let received = Rc::new(RefCell::new(Vec::<String>::new()));
+    let sink = Rc::clone(&received);
+    let callback = Closure::wrap(Box::new(move |json: String| {
+        // コールバックの中からもセッションを使うAPIを呼べる
+        let status: Value = serde_json::from_str(&get_network_status(Some("network".to_string())))
+            .expect("通信の状態はJSONとして読める");
+        assert_eq!(status["room_id"], "room-1");
+        sink.borrow_mut().push(json);
+    }) as Box<dyn FnMut(String)>);
+    let function = callback.as_ref().unchecked_ref::<js_sys::Function>().clone();
+    let subscription = network_subscribe("JoinRoom", function, session()).expect("セッションがある");
+    callback.forget();
+
+    let profile = serde_json::json!({
+        "type": "PlayerProfile",
+        "profile": {
+            "player_id": "player-1",
+            "player_name": "Alice",
+            "color_index": 0,
+            "rating": 1500,
+            "games_rated": 0,
+            "is_bot": false,
+        },
+    });
+    assert!(network_receive(&profile.to_string(), session()));
+    assert_eq!(take()[0]["type"], "JoinRoom");
+    let joined = serde_json::json!({ "type": "JoinRoom", "room_id": "room-1", "player_id": "player-1" });
+    assert!(network_receive(&joined.to_string(), session()));
+    assert!(!network_receive("not json", session()));
+    assert_eq!(received.borrow().len(), 1);
+    assert_eq!(status()["status"], "connected");
+    assert_eq!(status()["player_id"], "player-1");
+    assert_eq!(status()["room_id"], "room-1");
+
+    let score = serde_json::json!({
+        "type": "ScoreUpdate",
+        "room_id": "room-1",
+        "player_id": "player-2",
+        "score": 80,
+        "foundation_cards": 6,
+    });
+    assert!(network_receive(&score.to_string(), session()));
+    let players: Value = serde_json::from_str(&get_players(session())).expect("一覧はJSONとして読める");
+    assert_eq!(players[0]["player_id"], "player-2");
+    assert_eq!(players[0]["score"], 80);
+    assert_eq!(players[0]["connection"], "connected");
+
+    assert!(network_send_action("draw", None, None, session()));
+    assert_eq!(take()[0]["action"], "draw");
+    assert!(network_send_cursor(5.0, 6.0, session()));
+    let cursor = take().remove(0);
+    assert_eq!(cursor["type"], "MousePosition");
+    assert_eq!(cursor["sequence"], 1);
+    assert!(network_unsubscribe(subscription, session()));
+    assert!(destroy_session("network"));
+}
+
+#[wasm_bindgen_test]
+async fn room_promises_resolve_on_server_replies() {
+    let session = || Some("rooms".to_string());
+    let take = || take_outgoing("rooms");
+    // Promiseの中の処理を進める（要求の送信・応答の受け取りはマイクロタスクで行われる）
+    let settle = || wasm_bindgen_futures::JsFuture::from(js_sys::Promise::resolve(&JsValue::NULL));
+    assert!(initialize_game(session()));
+    assert!(list_rooms(session()).await.is_err(), "参加前");
+
+    network_set_connected(true, session());
+    assert!(network_join("Alice", session()));
+    take();
+    let profile = serde_json::json!({
+        "type": "PlayerProfile",
+        "profile": {
+            "player_id": "player-1",
+            "player_name": "Alice",
+            "color_index": 0,
+            "rating": 1500,
+            "games_rated": 0,
+            "is_bot": false,
+        },
+    });
+    assert!(network_receive(&profile.to_string(), session()));
+
+    let rooms = wasm_bindgen_futures::future_to_promise(async move {
+        list_rooms(session()).await.map(JsValue::from)
+    });
+    settle().await.unwrap();
+    let request = take().remove(0);
+    assert_eq!(request["type"], "GetRoomList");
+    let room_list = serde_json::json!({ "type": "RoomList", "rooms": [], "request_id": request["request_id"] });
+    assert!(network_receive(&room_list.to_string(), session()));
+    let rooms = wasm_bindgen_futures::JsFuture::from(rooms).await.unwrap();
+    assert_eq!(rooms.as_string().as_deref(), Some("[]"));
+
+    // 同じ要求のIDが付いたエラーでPromiseが拒否される
+    let joined = wasm_bindgen_futures::future_to_promise(async move {
+        join_room("room-1".to_string(), None, session()).await.map(JsValue::from)
+    });
+    settle().await.unwrap();
+    let request = take().remove(0);
+    assert_eq!(request["type"], "JoinRoom");
+    let error = serde_json::json!({
+        "type": "Error",
+        "message": "ルームに参加できません",
+        "request_id": request["request_id"],
+    });
+    assert!(network_receive(&error.to_string(), session()));
+    let error = wasm_bindgen_futures::JsFuture::from(joined).await.unwrap_err();
+    assert_eq!(error.as_string().as_deref(), Some("ルームに参加できません"));
+    assert!(destroy_session("rooms"));
+}
+
+#[wasm_bindgen_test]
+fn sessions_run_independently_and_pause_while_suspended() {
+    let table = |id: &str| Some(id.to_string());
+    let state_of = |id: &str| -> Value {
+        serde_json::from_str(&get_solitaire_state(table(id))).expect("ゲーム状態はJSONとして読める")
+    };
+
+    assert!(initialize_game(table("table_a")));
+    assert!(initialize_game(table("table_b")));
+    assert_eq!(start_new_game("テスト", table("table_a")), "table_a");
+    assert!(start_tutorial("basics", table("table_b")));
+    assert_eq!(state_of("table_a")["phase"], "playing");
+    assert!(state_of("table_b")["tutorial"].is_object());
+    assert!(state_of("table_a")["tutorial"].is_null());
+
+    // 一時停止中のセッションは更新されない
+    let ace = state_of("table_b")["tutorial"]["highlight"]["card_id"]
+        .as_u64()
+        .expect("動かすカードがある") as u32;
+    assert!(select_card(ace, table("table_b")));
+    assert!(suspend_session("table_b"));
+    assert!(!suspend_session("table_b"));
+    update_game(FRAME_MS, None);
+    assert_eq!(state_of("table_b")["piles"]["drop_targets"], serde_json::json!([]));
+
+    assert!(resume_session("table_b"));
+    update_game(FRAME_MS, None);
+    assert_ne!(state_of("table_b")["piles"]["drop_targets"], serde_json::json!([]));
+
+    let sessions: Value = serde_json::from_str(&list_sessions()).expect("一覧はJSONとして読める");
+    assert!(sessions
+        .as_array()
+        .expect("一覧は配列")
+        .iter()
+        .any(|session| session["id"] == "table_a" && session["status"] == "active"));
+    assert!(destroy_session("table_a"));
+    assert!(destroy_session("table_b"));
+    assert!(!destroy_session("table_b"));
+}
+
+#[wasm_bindgen_test]
+fn move_card_accepts_valid_locations() {
+    assert!(move_card(
+        r#"{"type": "tableau", "position": 6}"#,
+        r#"{"type": "foundation", "position": 3}"#,
+        None
+    ));
+    assert!(move_card(
+        r#"{"type": "waste", "position": 0}"#,
+        r#"{"type": "tableau", "position": 0}"#,
+        None
+    ));
+}
+
+#[wasm_bindgen_test]
+fn move_card_rejects_illegal_locations() {
+    let tableau = r#"{"type": "tableau", "position": 0}"#;
+
+    // JSONでない
+    assert!(!move_card("{ not json", tableau, None));
+    // 負のインデックス
+    assert!(!move_card(r#"{"type": "tableau", "position": -1}"#, tableau, None));
+    // 範囲外のインデックス
+    assert!(!move_card(tableau, r#"{"type": "foundation", "position": 99}"#, None));
+    // 不明な場所
+    assert!(!move_card(r#"{"type": "unknown", "position": 0}"#, tableau, None));
+    // 巨大な入力
+    let huge = format!(r#"{{"type": "{}", "position": 0}}"#, "x".repeat(100_000));
+    assert!(!move_card(&huge, tableau, None));
+}
+
+#[wasm_bindgen_test]
+fn save_and_load_round_trip_through_local_storage() {
+    clear_local_storage();
+    assert_eq!(storage::load("wasm_test"), None);
+
+    let value = r#"{"score": 1234, "name": "テスト"}"#;
+    storage::save("wasm_test", value).expect("localStorageに保存できる");
+    assert_eq!(storage::load("wasm_test").as_deref(), Some(value));
+
+    // 上書きした値が読める
+    storage::save("wasm_test", "2").expect("上書き保存できる");
+    assert_eq!(storage::load("wasm_test").as_deref(), Some("2"));
+}
+
+#[wasm_bindgen_test]
+fn event_callback_receives_achievement_on_first_win() {
+    // 初勝利の実績が必ず新規解除されるよう、保存済みの実績を消してから初期化する
+    clear_local_storage();
+    assert!(initialize_game(None));
+
+    let received = Rc::new(RefCell::new(Vec::<String>::new()));
+    let sink = Rc::clone(&received);
+    let callback = Closure::wrap(Box::new(move |json: String| {
+        sink.borrow_mut().push(json);
+    }) as Box<dyn FnMut(String)>);
+    set_event_callback(callback.as_ref().unchecked_ref::<js_sys::Function>().clone());
+    callback.forget();
+
+    let won = (0..MAX_GAMES_UNTIL_WIN).any(|_| {
+        start_new_game("テスト", None);
+        auto_play_until_stuck(None);
+        update_game(FRAME_MS, None);
+        state()["phase"] == "won"
+    });
+    assert!(won, "{}ゲーム以内に自動プレイで勝利できませんでした", MAX_GAMES_UNTIL_WIN);
+
+    let events: Vec<Value> = received
+        .borrow()
+        .iter()
+        .map(|json| serde_json::from_str(json).expect("イベントはJSONとして読める"))
+        .collect();
+    assert!(
+        events
+            .iter()
+            .any(|event| event["type"] == "achievement_unlocked" && event["id"] == "first_win"),
+        "初勝利の実績イベントが届いていません: {:?}",
+        events
+    );
+}
@@ -0,0 +1,2446 @@
+// =============================================================================
+// WebSocketサーバーの結合テスト
+// =============================================================================
+// websocket_serverを空きポートで起動し、複数の疑似クライアントから接続して
+// 参加・退出の通知（1つの接続での2回目の参加は拒否）、ルーム単位の配信、カーソルとリアクションの中継、
+// カーソルの色と表示名の設定（他のプレイヤーの名前は名乗れない）、Pingへの応答とタイムスタンプの変換、
+// Reliableで届いたメッセージへの確認（Ack）と重複の除外、状態を変えるメッセージのReliableでの送信とAckまでの再送、
+// チャネルごとの連番の抜けの検出と古いカーソル位置の破棄、
+// WebRTCの接続交渉の中継、ルーム内のスコアの共有、
+// カードの取り合いの判定、コンボのボーナスの結果への加算、ルームの作成数の上限と空になったルームの削除、
+// 共有盤面のサーバーの盤面の写しでの権限の確認、サーバーのティックで進むターンの制限時間、
+// 再起動後のルームの復元、バックプレーンによるインスタンス間の中継と中継したルームの更新、
+// HTTP APIでの参照と死活監視、日替わりの配り札と過去の配り札の取得、
+// お互いにフレンドにしたプレイヤーへの在席状況の通知とルームへの招待、
+// 手番・満員になったときのプッシュ通知の中継サーバーへの送信、
+// 操作の止まったプレイヤーの離席の通知とターンの飛ばし・席の没収、
+// サーバーが記録した成績の書き出しへの署名と改ざんの検出、
+// 他のプレイヤーのIDを名乗るメッセージの拒否、
+// 不正なメッセージの拒否（不正な座標はフィールド名付きのエラー）を確認します。
+//
+// 実行方法：cargo test --features server --test websocket_server
+// =============================================================================
+
+mod common;
+
+use common::{free_local_addr, http_get, FakeBroker, FakePushGateway, TestClient, TestServer};
+use ecs_wasm_solitaire::ecs::World;
+use ecs_wasm_solitaire::permissions;
+use ecs_wasm_solitaire::rng::{daily_seed, DAY_MS};
+use ecs_wasm_solitaire::solitaire::{CardLocation, SolitaireCard, SolitaireManager, SolitaireType};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 他のクライアントに届かないことを確認する待ち時間
+const SILENCE: Duration = Duration::from_millis(300);
+
+/// 現在のUNIX時刻（ミリ秒）
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("システム時計はUNIXエポックより後")
+        .as_millis() as u64
+}
+
+fn start_server() -> TestServer {
+    TestServer::start(env!("CARGO_BIN_EXE_websocket_server"))
+}
+
+/// 接続してプレイヤーとして参加し、割り当てられたプレイヤーIDを受け取る
+async fn join(server: &TestServer, name: &str) -> (TestClient, String) {
+    let (client, profile, _) = join_with_token(server, name, None).await;
+    let player_id = profile["player_id"]
+        .as_str()
+        .expect("プロフィールにプレイヤーIDが含まれる")
+        .to_string();
+    (client, player_id)
+}
+
+/// セッショントークンを付けて参加し、本人のプロフィールと発行されたトークンを受け取る
+async fn join_with_token(
+    server: &TestServer,
+    name: &str,
+    session_token: Option<&str>,
+) -> (TestClient, Value, String) {
+    let mut client = TestClient::connect(server).await;
+    client
+        .send(json!({
+            "type": "PlayerJoin",
+            "player_id": "",
+            "player_name": name,
+            "player_index": 0,
+            "session_token": session_token,
+        }))
+        .await;
+
+    let profile = client.recv_type("PlayerProfile").await;
+    let token = client.recv_type("SessionToken").await["session_token"]
+        .as_str()
+        .expect("トークンが発行される")
+        .to_string();
+    (client, profile["profile"].clone(), token)
+}
+
+/// サーバー起動時に作られるメインルームのIDを取得
+async fn main_room_id(client: &mut TestClient, player_id: &str) -> String {
+    client
+        .send(json!({ "type": "GetRoomList", "player_id": player_id }))
+        .await;
+    let list = client.recv_type("RoomList").await;
+    list["rooms"][0]["id"]
+        .as_str()
+        .expect("メインルームが存在する")
+        .to_string()
+}
+
+/// ルームに参加し、参加通知を受け取るまで待つ
+async fn join_room(client: &mut TestClient, player_id: &str, room_id: &str) -> Value {
+    client
+        .send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": player_id }))
+        .await;
+    client.recv_type("JoinRoom").await
+}
+
+/// 準備完了を伝えるメッセージ
+fn set_ready(room_id: &str, player_id: &str) -> Value {
+    json!({ "type": "SetReady", "room_id": room_id, "player_id": player_id, "ready": true })
+}
+
+/// 準備完了したプレイヤーが指定の一覧になったReadyStatusが届くまで待つ
+async fn recv_ready_status(client: &mut TestClient, ready_player_ids: Value) -> Value {
+    loop {
+        let status = client.recv_type("ReadyStatus").await;
+        if status["ready_player_ids"] == ready_player_ids {
+            return status;
+        }
+    }
+}
+
+#[tokio::test]
+async fn join_sends_profile_and_notifies_others() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (_bob, bob_id) = join(&server, "Bob").await;
+
+    let joined = alice.recv_type("PlayerJoin").await;
+    assert_eq!(joined["player_id"], bob_id.as_str());
+    assert_eq!(joined["player_name"], "Bob");
+}
+
+#[tokio::test]
+async fn invalid_join_gets_an_error_before_the_player_is_registered() {
+    let server = start_server();
+    let mut client = TestClient::connect(&server).await;
+
+    client
+        .send(json!({
+            "type": "PlayerJoin",
+            "player_id": "",
+            "player_name": "A".repeat(200),
+            "player_index": 0,
+            "request_id": "join-1",
+        }))
+        .await;
+    let error = client.recv_type("Error").await;
+    assert_eq!(error["request_id"], "join-1");
+    assert!(error["message"].as_str().unwrap().contains("長すぎます"));
+
+    // 登録はされていないので、正しい名前でやり直せる
+    client
+        .send(json!({ "type": "PlayerJoin", "player_id": "", "player_name": "Alice", "player_index": 0 }))
+        .await;
+    assert_eq!(client.recv_type("PlayerProfile").await["profile"]["player_name"], "Alice");
+}
+
+#[tokio::test]
+async fn a_second_join_on_the_same_connection_is_refused() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    bob.send(json!({
+        "type": "PlayerJoin",
+        "player_id": "",
+        "player_name": "Ghost",
+        "player_index": 0,
+        "request_id": "join-2",
+    }))
+    .await;
+    let error = bob.recv_type("Error").await;
+    assert_eq!(error["request_id"], "join-2");
+    assert!(error["message"].as_str().unwrap().contains("すでに参加"));
+    alice.expect_silence(SILENCE).await;
+
+    // 切断すると最初のプレイヤーだけが退出し、2回目の名前は押さえられたまま残らない
+    bob.close().await;
+    assert_eq!(alice.recv_type("PlayerLeft").await["player_id"], bob_id.as_str());
+    alice.expect_silence(SILENCE).await;
+    let (_ghost, _) = join(&server, "Ghost").await;
+    assert_eq!(alice.recv_type("PlayerJoin").await["player_name"], "Ghost");
+}
+
+#[tokio::test]
+async fn leave_is_broadcast_when_connection_closes() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    bob.close().await;
+
+    let left = alice.recv_type("PlayerLeft").await;
+    assert_eq!(left["player_id"], bob_id.as_str());
+    assert_eq!(left["player_name"], "Bob");
+}
+
+#[tokio::test]
+async fn cursor_position_is_relayed_to_others() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    bob.send(json!({
+        "type": "MousePosition",
+        "player_id": bob_id,
+        "x": 64.0,
+        "y": 128.0,
+        "timestamp": 1,
+    }))
+    .await;
+
+    let cursor = alice.recv_type("MousePosition").await;
+    assert_eq!(cursor["player_id"], bob_id.as_str());
+    assert_eq!(cursor["x"], 64.0);
+    assert_eq!(cursor["y"], 128.0);
+    bob.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn rtc_signals_are_relayed_only_between_room_members() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let (mut carol, carol_id) = join(&server, "Carol").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let signal = |from: &str, to: &str| {
+        json!({
+            "type": "RtcSignal",
+            "room_id": room_id,
+            "from_player_id": from,
+            "to_player_id": to,
+            "signal": { "kind": "offer", "sdp": "v=0" },
+        })
+    };
+
+    alice.send(signal(&alice_id, &bob_id)).await;
+    let relayed = bob.recv_type("RtcSignal").await;
+    assert_eq!(relayed["from_player_id"], alice_id.as_str());
+    assert_eq!(relayed["signal"]["kind"], "offer");
+    assert_eq!(relayed["signal"]["sdp"], "v=0");
+
+    // ルームにいないプレイヤーとは交渉できず、他人を名乗ることもできない
+    carol.send(signal(&carol_id, &bob_id)).await;
+    carol.recv_type("Error").await;
+    carol.send(signal(&alice_id, &bob_id)).await;
+    carol.recv_type("Error").await;
+    alice.send(signal(&alice_id, &carol_id)).await;
+    alice.recv_type("Error").await;
+    while let Ok(message) = tokio::time::timeout(SILENCE, bob.recv()).await {
+        assert_ne!(message["type"], "RtcSignal");
+    }
+    while let Ok(message) = tokio::time::timeout(SILENCE, carol.recv()).await {
+        assert_ne!(message["type"], "RtcSignal");
+    }
+}
+
+#[tokio::test]
+async fn ping_is_answered_with_a_matching_pong_only_to_the_sender() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (mut bob, _) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    let sent_at = unix_time_ms();
+    bob.send(json!({ "type": "Ping", "ping_id": 42, "client_time_ms": sent_at }))
+        .await;
+    let pong = bob.recv_type("Pong").await;
+    assert_eq!(pong["ping_id"], 42);
+    assert_eq!(pong["client_time_ms"], sent_at, "送信時刻はそのまま返る");
+    let server_time = pong["server_time_ms"].as_u64().expect("サーバーの受信時刻");
+    assert!(server_time.abs_diff(unix_time_ms()) < 1_000);
+    alice.expect_silence(SILENCE).await;
+
+    // 参加前の接続でも応答する
+    let mut guest = TestClient::connect(&server).await;
+    guest.send(json!({ "type": "Ping", "ping_id": 7 })).await;
+    assert_eq!(guest.recv_type("Pong").await["ping_id"], 7);
+}
+
+#[tokio::test]
+async fn reliable_messages_are_acked_and_processed_once() {
+    let server = start_server();
+    let (mut alice, player_id) = join(&server, "Alice").await;
+
+    let reliable = json!({
+        "type": "Reliable",
+        "message_id": "msg_1",
+        "message": { "type": "GetRoomList", "player_id": player_id, "request_id": "req_1" },
+    });
+    alice.send(reliable.clone()).await;
+    assert_eq!(alice.recv_type("Ack").await["message_id"], "msg_1");
+    assert_eq!(alice.recv_type("RoomList").await["request_id"], "req_1");
+
+    // 再送で重複したメッセージにも確認は返すが、処理はしない
+    alice.send(reliable).await;
+    assert_eq!(alice.recv_type("Ack").await["message_id"], "msg_1");
+    alice.expect_silence(SILENCE).await;
+
+    // 入れ子にしたReliableは拒否する
+    alice
+        .send(json!({
+            "type": "Reliable",
+            "message_id": "msg_2",
+            "message": { "type": "Ack", "message_id": "msg_1" },
+        }))
+        .await;
+    assert!(alice.recv_type("Error").await["message"]
+        .as_str()
+        .is_some_and(|message| message.contains("入れ子")));
+}
+
+#[tokio::test]
+async fn server_messages_are_resent_until_acked() {
+    let server = start_server();
+    let mut client = TestClient::connect_raw(&server).await;
+
+    // Pongは包まずに返す
+    client.send(json!({ "type": "Ping", "ping_id": 1 })).await;
+    assert_eq!(client.recv_type("Pong").await["ping_id"], 1);
+
+    // 参加の応答はReliableで包まれ、Ackを返すまで同じIDで再送される
+    client
+        .send(json!({ "type": "PlayerJoin", "player_id": "", "player_name": "Alice", "player_index": 0 }))
+        .await;
+    let mut received: Vec<(String, Value)> = Vec::new();
+    let deadline = std::time::Instant::now() + Duration::from_millis(1500);
+    while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Ok(message) = tokio::time::timeout(remaining, client.recv_type("Reliable")).await else {
+            break;
+        };
+        received.push((message["message_id"].as_str().unwrap().to_string(), message["message"].clone()));
+    }
+    assert!(received.iter().any(|(_, message)| message["type"] == "PlayerProfile"));
+    let first_id = &received[0].0;
+    assert!(
+        received.iter().filter(|(id, _)| id == first_id).count() >= 2,
+        "Ackを返さないメッセージは再送される"
+    );
+
+    // Ackを返したメッセージは再送しない
+    let acked: HashSet<String> = received.iter().map(|(id, _)| id.clone()).collect();
+    for message_id in &acked {
+        client.send(json!({ "type": "Ack", "message_id": message_id })).await;
+    }
+    // Ackが届く前に再送されたものは読み捨てる
+    while tokio::time::timeout(Duration::from_millis(300), client.recv()).await.is_ok() {}
+    let deadline = std::time::Instant::now() + Duration::from_millis(1500);
+    while let Some(remaining) = deadline.checked_duration_since(std::time::Instant::now()) {
+        let Ok(message) = tokio::time::timeout(remaining, client.recv_type("Reliable")).await else {
+            break;
+        };
+        assert!(
+            !acked.contains(message["message_id"].as_str().unwrap()),
+            "Ackを返したメッセージが再送された: {}",
+            message
+        );
+    }
+}
+
+#[tokio::test]
+async fn sequence_gaps_are_reported_and_stale_cursors_dropped() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // 同期のチャネルで連番が飛んだら、抜けている連番の再送を要求する
+    let reliable = |message_id: &str, sequence: u32| {
+        json!({
+            "type": "Reliable",
+            "message_id": message_id,
+            "message": { "type": "GetRoomList", "player_id": bob_id },
+            "sequence": sequence,
+        })
+    };
+    bob.send(reliable("msg_1", 1)).await;
+    bob.recv_type("RoomList").await;
+    bob.send(reliable("msg_4", 4)).await;
+    let request = bob.recv_type("ResendRequest").await;
+    assert_eq!(request["channel"], "sync");
+    assert_eq!(request["sequences"], json!([2, 3]));
+    bob.recv_type("RoomList").await;
+
+    // 新しい位置より後に届いた古いカーソル位置は中継しない
+    let cursor = |x: f64, sequence: u32| {
+        json!({
+            "type": "MousePosition",
+            "player_id": bob_id,
+            "x": x,
+            "y": 0.0,
+            "timestamp": 1,
+            "sequence": sequence,
+        })
+    };
+    bob.send(cursor(2.0, 2)).await;
+    bob.send(cursor(1.0, 1)).await;
+    let relayed = alice.recv_type("MousePosition").await;
+    assert_eq!(relayed["x"], 2.0);
+    assert_eq!(relayed["sequence"], 2, "連番はそのまま中継する");
+    alice.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn relayed_timestamps_are_converted_to_server_time() {
+    let server = start_server();
+    let (mut alice, _) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // Bobの時計はサーバーより1分遅れている
+    const BOB_CLOCK_BEHIND_MS: u64 = 60_000;
+    bob.send(json!({
+        "type": "Ping",
+        "ping_id": 1,
+        "client_time_ms": unix_time_ms() - BOB_CLOCK_BEHIND_MS,
+        "clock_offset_ms": BOB_CLOCK_BEHIND_MS,
+    }))
+    .await;
+    bob.recv_type("Pong").await;
+
+    bob.send(json!({
+        "type": "MousePosition",
+        "player_id": bob_id,
+        "x": 1.0,
+        "y": 2.0,
+        "timestamp": unix_time_ms() - BOB_CLOCK_BEHIND_MS,
+    }))
+    .await;
+    let cursor = alice.recv_type("MousePosition").await;
+    let timestamp = cursor["timestamp"].as_u64().unwrap();
+    assert!(
+        timestamp.abs_diff(unix_time_ms()) < 1_000,
+        "サーバーの時刻に直る"
+    );
+
+    // ありえない時刻は、サーバーの現在時刻に近い範囲に収める
+    bob.send(json!({
+        "type": "GameAction",
+        "player_id": bob_id,
+        "player_name": "Bob",
+        "action": "draw",
+        "x": null,
+        "y": null,
+        "timestamp": u64::MAX / 2,
+    }))
+    .await;
+    let action = alice.recv_type("GameAction").await;
+    assert!(action["timestamp"].as_u64().unwrap() <= unix_time_ms());
+}
+
+#[tokio::test]
+async fn spectator_reactions_are_relayed_until_the_burst_limit() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // ルームに参加していない観戦者（Bob）もリアクションを送れる
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+
+    for _ in 0..5 {
+        bob.send(json!({ "type": "Reaction", "player_id": bob_id, "emote": "snail" }))
+            .await;
+        let reaction = alice.recv_type("Reaction").await;
+        assert_eq!(reaction["player_id"], bob_id.as_str());
+        assert_eq!(reaction["emote"], "snail");
+    }
+
+    // 短時間に送りすぎると本人にエラーが返り、中継されない
+    bob.send(json!({ "type": "Reaction", "player_id": bob_id, "emote": "party" }))
+        .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("リアクション")));
+    while let Ok(message) = tokio::time::timeout(SILENCE, alice.recv()).await {
+        assert_ne!(message["type"], "Reaction");
+    }
+}
+
+#[tokio::test]
+async fn cursor_colors_are_unique_per_room_and_preferences_follow_the_token() {
+    let server = start_server();
+    let (mut alice, alice_profile, token) = join_with_token(&server, "Alice", None).await;
+    let alice_id = alice_profile["player_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    // 表示名は見えない文字や余分な空白を取り除いてから使われる
+    alice
+        .send(json!({
+            "type": "UpdatePreferences",
+            "player_id": alice_id,
+            "color_index": 3,
+            "player_name": "  Ali\u{200B}ce \n Smith ",
+        }))
+        .await;
+    let updated = bob.recv_type("PlayerUpdated").await;
+    assert_eq!(updated["player_id"], alice_id.as_str());
+    assert_eq!(updated["player_name"], "Alice Smith");
+    assert_eq!(updated["color_index"], 3);
+
+    // ルームの外では同じ色を選べるが、同じルームに入ると空いている色に変わる
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "color_index": 3 }))
+        .await;
+    assert_eq!(bob.recv_type("PlayerUpdated").await["color_index"], 3);
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let recolored = alice.recv_type("PlayerUpdated").await;
+    assert_eq!(recolored["player_id"], bob_id.as_str());
+    assert_ne!(recolored["color_index"], 3);
+
+    // ルーム内で使われている色は選べない
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "color_index": 3 }))
+        .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"].as_str().is_some_and(|m| m.contains("色")));
+    alice
+        .send(json!({ "type": "UpdatePreferences", "player_id": alice_id, "player_name": " \t " }))
+        .await;
+    alice.recv_type("Error").await;
+
+    // 同じトークンで接続し直すと、保存した色と表示名が引き継がれる
+    alice.close().await;
+    let (_alice, profile, reissued) = join_with_token(&server, "Someone", Some(&token)).await;
+    assert_eq!(profile["player_name"], "Alice Smith");
+    assert_eq!(profile["color_index"], 3);
+    assert_eq!(reissued, token);
+}
+
+#[tokio::test]
+async fn display_names_held_by_other_players_cannot_be_taken() {
+    let server = start_server();
+    let (mut alice, _, alice_token) = join_with_token(&server, "Alice", None).await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // 接続中のプレイヤーの名前には（大文字・小文字を変えても）変更できない
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "player_name": "alice" }))
+        .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"].as_str().is_some_and(|m| m.contains("他のプレイヤー")));
+
+    // 切断した後も名前は元のトークンのもので、別の接続は仮の名前で参加する
+    alice.close().await;
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "player_name": "Alice" }))
+        .await;
+    bob.recv_type("Error").await;
+    let (_impostor, profile, _) = join_with_token(&server, "Alice", None).await;
+    assert_ne!(profile["player_name"], "Alice");
+
+    // 同じトークンで接続し直せば、その名前を使い続けられる
+    let (_alice, profile, _) = join_with_token(&server, "Alice", Some(&alice_token)).await;
+    assert_eq!(profile["player_name"], "Alice");
+}
+
+#[tokio::test]
+async fn another_players_name_cursor_and_presence_cannot_be_changed() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    // BobがAliceのIDを書いても、名前・色・カードの裏面・離席・リアクション・カーソルは変えられない
+    for message in [
+        json!({ "type": "UpdatePreferences", "player_id": alice_id, "player_name": "Mallory", "color_index": 3 }),
+        json!({ "type": "CardBackChanged", "room_id": room_id, "player_id": alice_id, "card_back": null }),
+        json!({ "type": "IdleStatus", "room_id": room_id, "player_id": alice_id, "idle_seconds": 600 }),
+        json!({ "type": "Reaction", "player_id": alice_id, "emote": "snail" }),
+        json!({ "type": "MousePosition", "player_id": alice_id, "x": 10.0, "y": 20.0, "timestamp": 0 }),
+    ] {
+        bob.send(message).await;
+        assert!(bob.recv_type("Error").await["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("他のプレイヤー")));
+    }
+
+    // Aliceが自分で名前を変えたときの通知が最初に届く
+    alice
+        .send(json!({ "type": "UpdatePreferences", "player_id": alice_id, "player_name": "Alicia" }))
+        .await;
+    let updated = bob.recv_type("PlayerUpdated").await;
+    assert_eq!(updated["player_id"], alice_id.as_str());
+    assert_eq!(updated["player_name"], "Alicia");
+}
+
+#[tokio::test]
+async fn room_messages_reach_only_room_members() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, _) = join(&server, "Bob").await;
+    let (mut carol, carol_id) = join(&server, "Carol").await;
+
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut carol, &carol_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 42,
+        }))
+        .await;
+
+    for member in [&mut alice, &mut carol] {
+        let race = member.recv_type("RaceStart").await;
+        assert_eq!(race["room_id"], room_id.as_str());
+        assert_eq!(race["seed"], 42);
+    }
+
+    // ルーム外のプレイヤーにはレース開始が届かない
+    while let Ok(message) = tokio::time::timeout(SILENCE, bob.recv()).await {
+        assert_ne!(message["type"], "RaceStart");
+    }
+}
+
+#[tokio::test]
+async fn scores_are_shared_with_room_members_and_late_joiners() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let (mut carol, carol_id) = join(&server, "Carol").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+
+    let score_update = |player_id: &str, score: u32| {
+        json!({
+            "type": "ScoreUpdate",
+            "room_id": room_id,
+            "player_id": player_id,
+            "score": score,
+            "foundation_cards": 4,
+        })
+    };
+    let card_back = |player_id: &str, card_back: Value| {
+        json!({
+            "type": "CardBackChanged",
+            "room_id": room_id,
+            "player_id": player_id,
+            "card_back": card_back,
+        })
+    };
+    alice.send(score_update(&alice_id, 120)).await;
+    alice.send(card_back(&alice_id, json!("ocean"))).await;
+    // 同じ接続のメッセージは順に処理されるため、Pongが届けば変更は反映済み
+    alice.send(json!({ "type": "Ping", "ping_id": 1 })).await;
+    alice.recv_type("Pong").await;
+
+    // 後から参加したプレイヤーには、それまでのスコアとカードの裏面がまとめて届く
+    bob.send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": bob_id }))
+        .await;
+    let scoreboard = bob.recv_type("Scoreboard").await;
+    assert_eq!(scoreboard["players"][0]["player_id"], alice_id.as_str());
+    assert_eq!(scoreboard["players"][0]["player_name"], "Alice");
+    assert_eq!(scoreboard["players"][0]["score"], 120);
+    assert_eq!(scoreboard["players"][0]["foundation_cards"], 4);
+    assert_eq!(scoreboard["players"][0]["card_back"], "ocean");
+
+    alice.send(card_back(&alice_id, Value::Null)).await;
+    let changed = bob.recv_type("CardBackChanged").await;
+    assert_eq!(changed["player_id"], alice_id.as_str());
+    assert_eq!(changed["card_back"], Value::Null);
+
+    bob.send(score_update(&bob_id, 30)).await;
+    let update = alice.recv_type("ScoreUpdate").await;
+    assert_eq!(update["player_id"], bob_id.as_str());
+    assert_eq!(update["score"], 30);
+
+    // ルームにいないプレイヤーのスコアは中継しない
+    carol.send(score_update(&carol_id, 999)).await;
+    carol.recv_type("Error").await;
+    while let Ok(message) = tokio::time::timeout(SILENCE, alice.recv()).await {
+        assert_ne!(message["type"], "ScoreUpdate");
+    }
+}
+
+#[tokio::test]
+async fn contested_card_goes_to_the_earliest_lag_compensated_grab() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let grab = |player_id: &str, timestamp: u64| {
+        json!({
+            "type": "GrabCard",
+            "room_id": room_id,
+            "player_id": player_id,
+            "card_id": "hearts-7",
+            "timestamp": timestamp,
+        })
+    };
+
+    // Bobの掴みが先に届く
+    bob.send(grab(&bob_id, unix_time_ms())).await;
+    let grabbed = alice.recv_type("CardGrabbed").await;
+    assert_eq!(grabbed["player_id"], bob_id.as_str());
+
+    // 後から届いたAliceの方が早く掴んでいた（遅延が大きかった）ので、Aliceが取る
+    alice.send(grab(&alice_id, unix_time_ms() - 150)).await;
+    let rejected = bob.recv_type("GrabRejected").await;
+    assert_eq!(rejected["card_id"], "hearts-7");
+    assert_eq!(rejected["owner_id"], alice_id.as_str());
+    loop {
+        let grabbed = bob.recv_type("CardGrabbed").await;
+        if grabbed["player_id"] == alice_id.as_str() {
+            break;
+        }
+    }
+
+    // 補正できる時間を過ぎてからは、どれだけ前の時刻を名乗っても取れない
+    tokio::time::sleep(SILENCE).await;
+    bob.send(grab(&bob_id, unix_time_ms() - 5_000)).await;
+    let rejected = bob.recv_type("GrabRejected").await;
+    assert_eq!(rejected["owner_id"], alice_id.as_str());
+
+    // 取り上げられた後のBobの離す操作は無視され、Aliceが離すと全員に知らされる
+    let release = |player_id: &str| {
+        json!({
+            "type": "ReleaseCard",
+            "room_id": room_id,
+            "player_id": player_id,
+            "card_id": "hearts-7",
+        })
+    };
+    bob.send(release(&bob_id)).await;
+    alice.send(release(&alice_id)).await;
+    let released = bob.recv_type("CardReleased").await;
+    assert_eq!(released["card_id"], "hearts-7");
+    while let Ok(message) = tokio::time::timeout(SILENCE, bob.recv()).await {
+        assert_ne!(message["type"], "CardReleased", "離す通知は1回だけ");
+    }
+}
+
+#[tokio::test]
+async fn room_actions_from_non_members_are_rejected() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 7,
+        }))
+        .await;
+
+    let error = alice.recv_type("Error").await;
+    assert!(error["message"].as_str().is_some_and(|m| !m.is_empty()));
+}
+
+#[tokio::test]
+async fn invalid_messages_are_rejected_with_an_error() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+
+    // 長すぎるフィールド
+    alice
+        .send(json!({
+            "type": "JoinRoom",
+            "room_id": "x".repeat(10_000),
+            "player_id": alice_id,
+        }))
+        .await;
+    alice.recv_type("Error").await;
+
+    // 範囲外の座標
+    alice
+        .send(json!({
+            "type": "MousePosition",
+            "player_id": alice_id,
+            "x": 1e300,
+            "y": 0.0,
+            "timestamp": 1,
+        }))
+        .await;
+    assert_eq!(alice.recv_type("Error").await["field"], "x");
+
+    // ゲームアクションの範囲外の座標も、どのフィールドが不正かを返す
+    alice
+        .send(json!({
+            "type": "GameAction",
+            "player_id": alice_id,
+            "player_name": "Alice",
+            "action": "draw",
+            "x": 10.0,
+            "y": -1e9,
+            "timestamp": 1,
+        }))
+        .await;
+    assert_eq!(alice.recv_type("Error").await["field"], "y");
+
+    // サイズ上限を超えるメッセージ
+    alice
+        .send(json!({
+            "type": "GameResult",
+            "player_id": alice_id,
+            "result": { "padding": "x".repeat(100_000) },
+        }))
+        .await;
+    alice.recv_type("Error").await;
+
+    // 拒否された後も接続は使える
+    alice
+        .send(json!({ "type": "GetRoomList", "player_id": alice_id }))
+        .await;
+    alice.recv_type("RoomList").await;
+}
+
+#[tokio::test]
+async fn host_migrates_to_the_longest_connected_player() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let (mut carol, carol_id) = join(&server, "Carol").await;
+
+    // 最初に参加したBobがホストになる
+    let room_id = main_room_id(&mut bob, &bob_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let host = bob.recv_type("HostChanged").await;
+    assert_eq!(host["host_id"], bob_id.as_str());
+
+    // Carolの方が先に参加しても、引き継ぐのは先に接続したAlice
+    join_room(&mut carol, &carol_id, &room_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    bob.close().await;
+
+    for member in [&mut alice, &mut carol] {
+        let host = member.recv_type("HostChanged").await;
+        assert_eq!(host["room_id"], room_id.as_str());
+        assert_eq!(host["host_id"], alice_id.as_str());
+        assert_eq!(host["host_name"], "Alice");
+    }
+
+    // ホストの権限も引き継がれる
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 3,
+        }))
+        .await;
+    carol.recv_type("RaceStart").await;
+}
+
+#[tokio::test]
+async fn races_cannot_restart_until_everyone_has_sent_a_result() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice
+        .send(json!({ "type": "CreateRoom", "player_id": alice_id, "name": "レース" }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let start_race = json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 5 });
+    alice.send(start_race.clone()).await;
+    bob.recv_type("RaceStart").await;
+
+    // レース中はやり直せず、全員の結果が揃うと次のレースを始められる
+    alice.send(start_race.clone()).await;
+    let error = alice.recv_type("Error").await;
+    assert!(error["message"].as_str().unwrap().contains("レース中"), "{}", error);
+    let result = json!({ "type": "GameResult", "player_id": alice_id, "result": game_result(5, true, 900) });
+    alice.send(result).await;
+    bob.recv_type("GameResult").await;
+    alice.send(start_race.clone()).await;
+    alice.recv_type("Error").await;
+
+    let result = json!({ "type": "GameResult", "player_id": bob_id, "result": game_result(5, false, 300) });
+    bob.send(result).await;
+    alice.recv_type("GameResult").await;
+    alice.send(start_race).await;
+    bob.recv_type("RaceStart").await;
+}
+
+#[tokio::test]
+async fn only_the_host_can_change_settings_and_kick() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    // ホストでないBobの操作は拒否される
+    for message in [
+        json!({ "type": "StartRace", "room_id": room_id, "player_id": bob_id, "seed": 1 }),
+        json!({ "type": "KickPlayer", "room_id": room_id, "player_id": bob_id, "target_id": alice_id }),
+        json!({ "type": "UpdateRoomSettings", "room_id": room_id, "player_id": bob_id, "name": "乗っ取り" }),
+    ] {
+        bob.send(message).await;
+        let error = bob.recv_type("Error").await;
+        assert!(error["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("ホスト")));
+    }
+
+    // 参加者数より少ない定員にはできない
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "max_players": 1,
+        }))
+        .await;
+    alice.recv_type("Error").await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "name": "練習部屋",
+            "max_players": 2,
+        }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["name"], "練習部屋");
+    assert_eq!(settings["max_players"], 2);
+
+    alice
+        .send(json!({
+            "type": "KickPlayer",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "target_id": bob_id,
+        }))
+        .await;
+    let kicked = bob.recv_type("Kicked").await;
+    assert_eq!(kicked["player_id"], bob_id.as_str());
+    assert_eq!(kicked["banned"], false);
+    let left = alice.recv_type("LeaveRoom").await;
+    assert_eq!(left["player_id"], bob_id.as_str());
+
+    // キックされた直後は再参加できない
+    bob.send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": bob_id }))
+        .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("キック")));
+}
+
+#[tokio::test]
+async fn host_actions_sent_with_the_hosts_id_from_another_connection_are_refused() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    // プレイヤーIDは参加の通知で分かるので、BobがホストのAliceのIDを書いて送っても断られる
+    for message in [
+        json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 1 }),
+        json!({ "type": "UpdateRoomSettings", "room_id": room_id, "player_id": alice_id, "name": "乗っ取り" }),
+        json!({ "type": "AddBot", "room_id": room_id, "player_id": alice_id, "count": 1 }),
+        json!({ "type": "UnbanPlayer", "room_id": room_id, "player_id": alice_id, "target_name": "Carol" }),
+    ] {
+        bob.send(message).await;
+        let error = bob.recv_type("Error").await;
+        assert!(error["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("他のプレイヤー")));
+    }
+
+    // 参加前の接続も、他のプレイヤーのIDでは何もできない
+    let mut mallory = TestClient::connect(&server).await;
+    mallory
+        .send(json!({ "type": "UpdateRoomSettings", "room_id": room_id, "player_id": alice_id, "name": "乗っ取り" }))
+        .await;
+    mallory.recv_type("Error").await;
+
+    // 最初に届く設定の変更は本人が送ったもの
+    alice
+        .send(json!({ "type": "UpdateRoomSettings", "room_id": room_id, "player_id": alice_id, "name": "練習部屋" }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["name"], "練習部屋");
+}
+
+#[tokio::test]
+async fn other_players_cannot_be_kicked_removed_or_moved_by_naming_their_id() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let (mut carol, _) = join(&server, "Carol").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    // Carolがホストや参加者のIDを書いて、キック・参加禁止・退室・参加をさせようとしても断られる
+    for message in [
+        json!({ "type": "KickPlayer", "room_id": room_id, "player_id": alice_id, "target_id": bob_id }),
+        json!({ "type": "BanPlayer", "room_id": room_id, "player_id": alice_id, "target_id": bob_id }),
+        json!({ "type": "LeaveRoom", "room_id": room_id, "player_id": bob_id }),
+        json!({ "type": "JoinRoom", "room_id": "another-room", "player_id": bob_id }),
+        json!({ "type": "QuickMatch", "player_id": bob_id }),
+    ] {
+        carol.send(message).await;
+        let error = carol.recv_type("Error").await;
+        assert!(error["message"]
+            .as_str()
+            .is_some_and(|m| m.contains("他のプレイヤー")));
+    }
+
+    // Bobはルームに残っていて、ホストが本当にキックしたときに初めて外れる
+    alice
+        .send(json!({ "type": "KickPlayer", "room_id": room_id, "player_id": alice_id, "target_id": bob_id }))
+        .await;
+    let kicked = bob.recv_type("Kicked").await;
+    assert_eq!(kicked["banned"], false);
+    let left = alice.recv_type("LeaveRoom").await;
+    assert_eq!(left["player_id"], bob_id.as_str());
+}
+
+#[tokio::test]
+async fn password_and_ban_list_guard_the_room() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "password": "hunter2",
+        }))
+        .await;
+    let settings = alice.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["has_password"], true);
+
+    // パスワードが違うと参加できない
+    bob.send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": bob_id }))
+        .await;
+    bob.recv_type("Error").await;
+    bob.send(json!({
+        "type": "JoinRoom",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "password": "hunter2",
+    }))
+    .await;
+    bob.recv_type("JoinRoom").await;
+
+    // 参加禁止にすると、正しいパスワードでも参加できない
+    alice
+        .send(json!({
+            "type": "BanPlayer",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "target_id": bob_id,
+        }))
+        .await;
+    let kicked = bob.recv_type("Kicked").await;
+    assert_eq!(kicked["banned"], true);
+    assert_eq!(kicked["rejoin_after_seconds"], Value::Null);
+    let bans = alice.recv_type("BanList").await;
+    assert_eq!(bans["banned_names"], json!(["Bob"]));
+
+    bob.send(json!({
+        "type": "JoinRoom",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "password": "hunter2",
+    }))
+    .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("禁止")));
+
+    // 表示名を変えても参加禁止のまま（禁止は名前ではなく本人に付く）
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "player_name": "Robert" }))
+        .await;
+    bob.recv_type("PlayerUpdated").await;
+    bob.send(json!({
+        "type": "JoinRoom",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "password": "hunter2",
+    }))
+    .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("禁止")));
+
+    alice
+        .send(json!({
+            "type": "UnbanPlayer",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "target_name": "Bob",
+        }))
+        .await;
+    let bans = alice.recv_type("BanList").await;
+    assert_eq!(bans["banned_names"], json!([]));
+    bob.send(json!({
+        "type": "JoinRoom",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "password": "hunter2",
+    }))
+    .await;
+    bob.recv_type("JoinRoom").await;
+}
+
+#[tokio::test]
+async fn created_room_is_hosted_by_its_creator() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    // 検証で弾かれた要求へのエラーにも要求のIDが付く
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "",
+            "request_id": "req-1",
+        }))
+        .await;
+    let error = alice.recv_type("Error").await;
+    assert_eq!(error["request_id"], "req-1");
+
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "Aliceの部屋",
+            "max_players": 2,
+            "password": "hunter2",
+            "request_id": "req-2",
+        }))
+        .await;
+    let joined = alice.recv_type("JoinRoom").await;
+    assert_eq!(joined["player_id"], alice_id.as_str());
+    assert_eq!(joined["request_id"], "req-2");
+    let room_id = joined["room_id"].as_str().unwrap().to_string();
+
+    bob.send(json!({ "type": "GetRoomList", "player_id": bob_id, "request_id": "req-1" }))
+        .await;
+    let list = bob.recv_type("RoomList").await;
+    assert_eq!(list["request_id"], "req-1");
+    let room = list["rooms"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|room| room["id"] == room_id.as_str())
+        .expect("作成したルームが一覧にある")
+        .clone();
+    assert_eq!(room["name"], "Aliceの部屋");
+    assert_eq!(room["max_players"], 2);
+    assert_eq!(room["has_password"], true);
+    assert_eq!(room["host_id"], alice_id.as_str());
+
+    bob.send(json!({
+        "type": "JoinRoom",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "request_id": "req-2",
+    }))
+    .await;
+    let error = bob.recv_type("Error").await;
+    assert_eq!(error["request_id"], "req-2");
+}
+
+#[tokio::test]
+async fn rooms_per_player_are_limited() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+
+    for number in 1..=3 {
+        alice
+            .send(json!({ "type": "CreateRoom", "player_id": alice_id, "name": format!("部屋{}", number) }))
+            .await;
+        alice.recv_type("JoinRoom").await;
+    }
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "部屋4",
+            "request_id": "req-4",
+        }))
+        .await;
+    let error = alice.recv_type("Error").await;
+    assert_eq!(error["request_id"], "req-4");
+}
+
+#[tokio::test]
+async fn empty_rooms_are_removed_after_the_grace_period() {
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("EMPTY_ROOM_GRACE_SECONDS", "1")],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let main_room_id = main_room_id(&mut alice, &alice_id).await;
+
+    alice
+        .send(json!({ "type": "CreateRoom", "player_id": alice_id, "name": "すぐ空く部屋" }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"].as_str().unwrap().to_string();
+    // メインルームに移ると作成したルームは空になる（メインルームは空でも消えない）
+    join_room(&mut alice, &alice_id, &main_room_id).await;
+
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    alice
+        .send(json!({ "type": "GetRoomList", "player_id": alice_id }))
+        .await;
+    let list = alice.recv_type("RoomList").await;
+    let ids: Vec<&str> = list["rooms"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|room| room["id"].as_str())
+        .collect();
+    assert!(!ids.contains(&room_id.as_str()), "空のルームが残っている: {:?}", ids);
+    assert!(ids.contains(&main_room_id.as_str()));
+}
+
+#[tokio::test]
+async fn race_starts_after_everyone_is_ready_and_the_countdown_ends() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    // 1人だけではまだ始まらない
+    alice.send(set_ready(&room_id, &alice_id)).await;
+    let status = recv_ready_status(&mut bob, json!([alice_id])).await;
+    assert_eq!(status["all_ready"], false);
+
+    // AliceがBobのIDで準備完了にしようとしても断られ、カウントダウンは始まらない
+    alice.send(set_ready(&room_id, &bob_id)).await;
+    assert!(alice.recv_type("Error").await["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("他のプレイヤー")));
+    bob.expect_silence(SILENCE).await;
+
+    bob.send(set_ready(&room_id, &bob_id)).await;
+    let status = recv_ready_status(&mut alice, json!([alice_id, bob_id])).await;
+    assert_eq!(status["all_ready"], true);
+
+    for expected in [3, 2, 1] {
+        let countdown = alice.recv_type("StartCountdown").await;
+        assert_eq!(countdown["seconds_remaining"], expected);
+    }
+    let race = alice.recv_type("RaceStart").await;
+    assert_eq!(race["room_id"], room_id.as_str());
+    bob.recv_type("RaceStart").await;
+
+    // 開始すると準備完了はリセットされる
+    alice
+        .send(json!({ "type": "GetRoomList", "player_id": alice_id }))
+        .await;
+    let list = alice.recv_type("RoomList").await;
+    assert_eq!(list["rooms"][0]["game_state"], "Playing");
+    assert_eq!(list["rooms"][0]["ready_player_ids"], json!([]));
+}
+
+#[tokio::test]
+async fn turn_timer_advances_on_the_server_tick() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "turn_time_limit": 2,
+        }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["turn_time_limit"], 2);
+
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 7,
+        }))
+        .await;
+    let turn = bob.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], alice_id.as_str());
+    assert_eq!(turn["turn_number"], 1);
+    assert_eq!(turn["time_limit_seconds"], 2);
+
+    // 誰もメッセージを送らなくても、制限時間が切れるとターンが進む
+    let timed_out = bob.recv_type("TurnTimedOut").await;
+    assert_eq!(timed_out["player_id"], alice_id.as_str());
+    assert_eq!(timed_out["turn_number"], 1);
+    assert_eq!(timed_out["auto_action"], "skip_turn");
+    let turn = bob.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], bob_id.as_str());
+    assert_eq!(turn["turn_number"], 2);
+
+    // ターン中のプレイヤーが抜けると、時間切れを待たずに次のターンになる
+    for expected in [1, 2] {
+        let turn = alice.recv_type("TurnStarted").await;
+        assert_eq!(turn["turn_number"], expected);
+    }
+    bob.close().await;
+    let next = loop {
+        let message = alice.recv().await;
+        if message["type"] == "TurnStarted" || message["type"] == "TurnTimedOut" {
+            break message;
+        }
+    };
+    assert_eq!(next["type"], "TurnStarted");
+    assert_eq!(next["player_id"], alice_id.as_str());
+    assert_eq!(next["turn_number"], 3);
+}
+
+#[tokio::test]
+async fn race_combos_are_broadcast_until_the_window_runs_out() {
+    let http_addr = free_local_addr();
+    let http_addr_text = http_addr.to_string();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("HTTP_ADDR", http_addr_text.as_str())],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "combo_window_seconds": 1,
+        }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["combo_window_seconds"], 1);
+
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 7,
+        }))
+        .await;
+    bob.recv_type("RaceStart").await;
+
+    // BobがAliceのIDで送ったスコアは断られ、Aliceのスコアとコンボは変わらない
+    bob.send(json!({
+        "type": "ScoreUpdate",
+        "room_id": room_id,
+        "player_id": alice_id,
+        "score": 900,
+        "foundation_cards": 2,
+    }))
+    .await;
+    assert!(bob.recv_type("Error").await["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("他のプレイヤー")));
+
+    // 続けてファウンデーションに置くと、相手にもコンボが届く（1手で2枚置いても1つだけ伸びる）
+    for (foundation_cards, combo, multiplier) in [(1, 1, 1), (3, 2, 1), (4, 3, 2)] {
+        alice
+            .send(json!({
+                "type": "ScoreUpdate",
+                "room_id": room_id,
+                "player_id": alice_id,
+                "score": foundation_cards * 10,
+                "foundation_cards": foundation_cards,
+            }))
+            .await;
+        let update = bob.recv_type("ComboUpdate").await;
+        assert_eq!(update["player_id"], alice_id.as_str());
+        assert_eq!(update["combo"], combo);
+        assert_eq!(update["multiplier"], multiplier);
+        assert!(update["expires_at_ms"].is_u64());
+    }
+
+    // 受付時間が切れると、誰も送らなくてもコンボが途切れたことが届く
+    let expired = bob.recv_type("ComboUpdate").await;
+    assert_eq!(expired["combo"], 0);
+    assert_eq!(expired["multiplier"], 1);
+    assert_eq!(expired["bonus_score"], 10);
+    assert!(expired.get("expires_at_ms").is_none());
+
+    // 貯まったボーナスはサーバーが結果のスコアに足してから順位を付ける
+    alice
+        .send(json!({ "type": "GameResult", "player_id": alice_id, "result": game_result(7, true, 500) }))
+        .await;
+    let mut entries = Value::Null;
+    for _ in 0..20 {
+        entries = http_get(http_addr, "/api/leaderboard/7").1["entries"].clone();
+        if entries[0]["player_name"] == "Alice" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(entries[0]["score"], 510);
+}
+
+#[tokio::test]
+async fn power_ups_are_checked_by_the_server_and_make_results_unranked() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let power_up = |name: &str| {
+        json!({
+            "type": "GameAction",
+            "player_id": alice_id,
+            "player_name": "Alice",
+            "action": format!("power_up:{}", name),
+            "x": null,
+            "y": null,
+            "timestamp": unix_time_ms(),
+        })
+    };
+
+    // パワーアップを有効にしていないルームでは使えない
+    alice.send(power_up("time_penalty")).await;
+    alice.recv_type("Error").await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "power_ups": true,
+        }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["power_ups"], true);
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 7,
+        }))
+        .await;
+    bob.recv_type("RaceStart").await;
+    alice.recv_type("RaceStart").await;
+
+    // BobがAliceのIDで使おうとしても断られ、Aliceのクールダウンは始まらない
+    bob.send(power_up("time_penalty")).await;
+    assert!(bob.recv_type("Error").await["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("他のプレイヤー")));
+
+    // 受け付けたパワーアップは使ったプレイヤーにも送り返され、クールダウン中は断られる
+    alice.send(power_up("time_penalty")).await;
+    let used = bob.recv_type("GameAction").await;
+    assert_eq!(used["action"], "power_up:time_penalty");
+    assert_eq!(alice.recv_type("GameAction").await["player_id"], alice_id.as_str());
+    alice.send(power_up("time_penalty")).await;
+    alice.recv_type("Error").await;
+
+    // レース中はパワーアップを切ってランク内の結果にすることはできない
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "power_ups": false,
+        }))
+        .await;
+    alice.recv_type("Error").await;
+
+    // 相手の結果にはタイムが足され、ランク外の印が付く
+    bob.send(json!({
+        "type": "GameResult",
+        "player_id": bob_id,
+        "result": {
+            "seed": 7,
+            "score": { "final_score": 500 },
+            "duration_seconds": 100,
+            "outcome": "Won",
+        },
+    }))
+    .await;
+    let result = alice.recv_type("GameResult").await;
+    assert_eq!(result["result"]["ranked"], false);
+    assert_eq!(result["result"]["duration_seconds"], 130);
+    assert_eq!(result["result"]["time_penalty_seconds"], 30);
+}
+
+#[tokio::test]
+async fn shared_board_rooms_enforce_card_owners_spectators_and_host_recycling() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "みんなで1つの盤面",
+            "shared_board": true,
+        }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let permissions = bob.recv_type("PermissionsChanged").await;
+    assert_eq!(permissions["host_id"], alice_id.as_str());
+    let action = |player_id: &str, action: &str| {
+        json!({
+            "type": "GameAction",
+            "player_id": player_id,
+            "player_name": "Bob",
+            "action": action,
+            "x": null,
+            "y": null,
+            "timestamp": unix_time_ms(),
+        })
+    };
+
+    // クライアントと同じシードで配った盤面で、タブロー1列目の表向きのカードと山札の枚数を調べる
+    let mut board = World::new();
+    SolitaireManager::start_new_game_with_seed(&mut board, SolitaireType::Klondike, 7);
+    let alices_card = board
+        .query::<SolitaireCard>()
+        .find(|(_, card)| card.location_type == CardLocation::Tableau && card.position_in_location == 0)
+        .map(|(_, card)| permissions::card_id(card))
+        .expect("1列目にカードがある");
+
+    // ホストだけがカードの持ち主を決められ、持ち主以外は掴めない
+    let set_owner = |player_id: &str| {
+        json!({
+            "type": "SetCardOwner",
+            "room_id": room_id,
+            "player_id": player_id,
+            "card_id": alices_card,
+            "owner_id": alice_id,
+        })
+    };
+    bob.send(set_owner(&bob_id)).await;
+    bob.recv_type("Error").await;
+    alice.send(set_owner(&alice_id)).await;
+    let permissions = bob.recv_type("PermissionsChanged").await;
+    assert_eq!(permissions["card_owners"][&alices_card], alice_id.as_str());
+
+    // 新しい配り札では持ち主が決め直しになる
+    alice
+        .send(json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 7 }))
+        .await;
+    bob.recv_type("RaceStart").await;
+    let permissions = bob.recv_type("PermissionsChanged").await;
+    assert_eq!(permissions["card_owners"], json!({}));
+    alice.send(set_owner(&alice_id)).await;
+    bob.recv_type("PermissionsChanged").await;
+    bob.send(json!({
+        "type": "GrabCard",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "card_id": alices_card,
+        "timestamp": unix_time_ms(),
+    }))
+    .await;
+    bob.recv_type("Error").await;
+
+    // サーバーの盤面で動かすカードを調べるため、Aliceのカードを動かす手は断られる
+    let move_first_column = json!({
+        "type": "move_card",
+        "from": { "type": "tableau", "position": 0 },
+        "to": { "type": "foundation", "position": 0 },
+        "count": 1,
+    })
+    .to_string();
+    bob.send(action(&bob_id, &move_first_column)).await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"].as_str().unwrap().contains(&alice_id), "{}", error);
+
+    // 山札から引く操作は誰でもでき、山札が空のときの引く操作（ウェイストの戻し）はホストだけができる
+    bob.send(action(&bob_id, "draw")).await;
+    assert_eq!(alice.recv_type("GameAction").await["action"], "draw");
+    SolitaireManager::draw_card(&mut board).expect("山札から引ける");
+    while board
+        .query::<SolitaireCard>()
+        .any(|(_, card)| card.location_type == CardLocation::Deck)
+    {
+        alice.send(action(&alice_id, "draw")).await;
+        bob.recv_type("GameAction").await;
+        SolitaireManager::draw_card(&mut board).expect("山札から引ける");
+    }
+    bob.send(action(&bob_id, "draw")).await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"].as_str().unwrap().contains("ホスト"), "{}", error);
+    alice.send(action(&alice_id, "draw")).await;
+    assert_eq!(bob.recv_type("GameAction").await["action"], "draw");
+
+    // 観戦に切り替えると何も送れない
+    bob.send(json!({
+        "type": "SetSpectating",
+        "room_id": room_id,
+        "player_id": bob_id,
+        "spectating": true,
+    }))
+    .await;
+    let permissions = alice.recv_type("PermissionsChanged").await;
+    assert_eq!(permissions["spectators"], json!([bob_id]));
+    bob.send(action(&bob_id, "draw")).await;
+    bob.recv_type("Error").await;
+}
+
+#[tokio::test]
+async fn idle_players_are_marked_afk_and_forfeit_their_seat_after_skipped_turns() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "turn_time_limit": 60,
+            "afk_seconds": 2,
+            "afk_forfeit_turns": 1,
+        }))
+        .await;
+    let settings = bob.recv_type("RoomSettingsChanged").await;
+    assert_eq!(settings["afk_seconds"], 2);
+    assert_eq!(settings["afk_forfeit_turns"], 1);
+
+    alice
+        .send(json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 7 }))
+        .await;
+    let turn = bob.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], alice_id.as_str());
+
+    // Bobは操作を続け、Aliceは何もしない
+    for _ in 0..7 {
+        bob.send(json!({ "type": "IdleStatus", "room_id": room_id, "player_id": bob_id, "idle_seconds": 0 }))
+            .await;
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let afk = bob.recv_type("PlayerAfk").await;
+    assert_eq!(afk["player_id"], alice_id.as_str());
+    assert_eq!(afk["afk"], true);
+    assert!(afk["idle_seconds"].as_u64().unwrap() >= 2);
+    assert_eq!(afk["paused"], false);
+
+    // 離席中のプレイヤーのターンは制限時間を待たずに飛ばし、設定の回数で席を没収する
+    let skipped = bob.recv_type("TurnTimedOut").await;
+    assert_eq!(skipped["player_id"], alice_id.as_str());
+    assert_eq!(skipped["auto_action"], "afk_skip");
+    let turn = bob.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], bob_id.as_str());
+    assert_eq!(turn["turn_number"], 2);
+    let forfeited = bob.recv_type("SeatForfeited").await;
+    assert_eq!(forfeited["player_id"], alice_id.as_str());
+    assert_eq!(forfeited["afk_turns"], 1);
+
+    // 操作が届くと戻ったことが配信される
+    alice
+        .send(json!({
+            "type": "GameAction",
+            "player_id": alice_id,
+            "player_name": "Alice",
+            "action": "draw",
+            "x": null,
+            "y": null,
+            "timestamp": unix_time_ms(),
+        }))
+        .await;
+    let back = loop {
+        let message = bob.recv_type("PlayerAfk").await;
+        if message["player_id"] == alice_id.as_str() {
+            break message;
+        }
+    };
+    assert_eq!(back["afk"], false);
+}
+
+#[tokio::test]
+async fn rooms_survive_a_server_restart() {
+    let mut server = start_server();
+    let (mut alice, profile, token) = join_with_token(&server, "Alice", None).await;
+    let alice_id = profile["player_id"].as_str().unwrap().to_string();
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "name": "続きの部屋",
+            "turn_time_limit": 1,
+        }))
+        .await;
+    alice.recv_type("RoomSettingsChanged").await;
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 99,
+        }))
+        .await;
+    alice.recv_type("RaceStart").await;
+    alice
+        .send(json!({
+            "type": "GameAction",
+            "player_id": alice_id,
+            "player_name": "Alice",
+            "action": "draw",
+            "x": null,
+            "y": null,
+            "timestamp": unix_time_ms(),
+        }))
+        .await;
+
+    // ターンが進んで定期的な保存が行われてから、サーバーを強制終了して起動し直す
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    server.restart();
+
+    let (mut alice, profile, reissued) = join_with_token(&server, "Alice", Some(&token)).await;
+    assert_eq!(reissued, token);
+    let new_id = profile["player_id"].as_str().unwrap().to_string();
+    assert_ne!(new_id, alice_id);
+
+    // 同じトークンで接続すると元のルームに戻り、配り札とアクションの記録が届く
+    let restored = alice.recv_type("RoomRestored").await;
+    assert_eq!(restored["room_id"], room_id.as_str());
+    assert_eq!(restored["seed"], 99);
+    assert_eq!(restored["actions"][0]["action"], "draw");
+    assert_eq!(restored["actions"][0]["player_id"], new_id.as_str());
+
+    // ターンは保存時の番号から再開する
+    let turn = alice.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], new_id.as_str());
+    assert!(turn["turn_number"].as_u64().unwrap() >= 2);
+
+    alice
+        .send(json!({ "type": "GetRoomList", "player_id": new_id }))
+        .await;
+    let list = alice.recv_type("RoomList").await;
+    assert_eq!(list["rooms"][0]["id"], room_id.as_str());
+    assert_eq!(list["rooms"][0]["name"], "続きの部屋");
+    assert_eq!(list["rooms"][0]["game_state"], "Playing");
+}
+
+#[tokio::test]
+async fn room_snapshots_keep_the_shared_board_and_only_a_password_hash() {
+    let mut server = start_server();
+    let (mut alice, profile, token) = join_with_token(&server, "Alice", None).await;
+    let alice_id = profile["player_id"].as_str().unwrap().to_string();
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "保存される盤面",
+            "password": "hunter2",
+            "shared_board": true,
+        }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    alice
+        .send(json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 7 }))
+        .await;
+    alice.recv_type("RaceStart").await;
+    alice
+        .send(json!({
+            "type": "GameAction",
+            "player_id": alice_id,
+            "player_name": "Alice",
+            "action": "draw",
+            "x": null,
+            "y": null,
+            "timestamp": unix_time_ms(),
+        }))
+        .await;
+
+    // 定期的な保存のあと、保存データには盤面が入り、パスワードはハッシュだけが残る
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    let saved = std::fs::read_to_string(server.work_dir().join("save_data/ecs_wasm_solitaire.rooms.json"))
+        .expect("ルームが保存されている");
+    assert!(!saved.contains("hunter2"), "パスワードがそのまま保存されている: {}", saved);
+    let rooms: Value = serde_json::from_str(&saved).unwrap();
+    let room = rooms
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|room| room["id"] == room_id.as_str())
+        .expect("作成したルームが保存されている");
+    assert!(room["password_hash"].is_object());
+    assert_eq!(room["board"]["waste"].as_array().unwrap().len(), 1);
+    assert_eq!(room["board"]["deck"].as_array().unwrap().len(), 23);
+
+    // 起動し直してもパスワードのハッシュで参加を確かめられる
+    server.restart();
+    let (_alice, _, _) = join_with_token(&server, "Alice", Some(&token)).await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    bob.send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": bob_id, "password": "wrong" }))
+        .await;
+    let error = bob.recv_type("Error").await;
+    assert!(error["message"].as_str().unwrap().contains("パスワード"), "{}", error);
+    bob.send(json!({ "type": "JoinRoom", "room_id": room_id, "player_id": bob_id, "password": "hunter2" }))
+        .await;
+    bob.recv_type("JoinRoom").await;
+}
+
+#[tokio::test]
+async fn rooms_are_shared_between_instances_over_the_backplane() {
+    let broker = FakeBroker::start();
+    let url = broker.url();
+    let envs = [("BACKPLANE_URL", url.as_str())];
+    let server_a = TestServer::start_with_env(env!("CARGO_BIN_EXE_websocket_server"), &envs);
+    let server_b = TestServer::start_with_env(env!("CARGO_BIN_EXE_websocket_server"), &envs);
+
+    let (mut alice, alice_id) = join(&server_a, "Alice").await;
+    let (mut bob, bob_id) = join(&server_b, "Bob").await;
+    let room_id = main_room_id(&mut alice, &alice_id).await;
+    join_room(&mut alice, &alice_id, &room_id).await;
+
+    // インスタンスAのルームが、インスタンスBのルーム一覧に載るまで待つ
+    let mut listed = false;
+    for _ in 0..20 {
+        bob.send(json!({ "type": "GetRoomList", "player_id": bob_id }))
+            .await;
+        let list = bob.recv_type("RoomList").await;
+        let rooms = list["rooms"].as_array().unwrap();
+        if rooms.iter().any(|room| room["id"] == room_id.as_str()) {
+            listed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    assert!(listed, "他のインスタンスのルームが一覧に載りませんでした");
+
+    // 別のインスタンスから参加すると、参加通知がもう一方のインスタンスにも届く
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let joined = alice.recv_type("JoinRoom").await;
+    assert_eq!(joined["player_id"], bob_id.as_str());
+
+    // 管理しているインスタンスでの設定の変更は、中継しているインスタンスのルームにも反映される
+    alice
+        .send(json!({
+            "type": "UpdateRoomSettings",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "name": "改名した部屋",
+        }))
+        .await;
+    let mut renamed = false;
+    for _ in 0..20 {
+        bob.send(json!({ "type": "GetRoomList", "player_id": bob_id }))
+            .await;
+        let list = bob.recv_type("RoomList").await;
+        let rooms = list["rooms"].as_array().unwrap();
+        if rooms
+            .iter()
+            .any(|room| room["id"] == room_id.as_str() && room["name"] == "改名した部屋")
+        {
+            renamed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    assert!(renamed, "中継しているルームに名前の変更が反映されませんでした");
+
+    alice
+        .send(json!({
+            "type": "StartRace",
+            "room_id": room_id,
+            "player_id": alice_id,
+            "seed": 7,
+        }))
+        .await;
+    let race = bob.recv_type("RaceStart").await;
+    assert_eq!(race["room_id"], room_id.as_str());
+    assert_eq!(race["seed"], 7);
+}
+
+#[tokio::test]
+async fn http_api_serves_read_only_json() {
+    let http_addr = free_local_addr();
+    let http_addr_text = http_addr.to_string();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("HTTP_ADDR", http_addr_text.as_str())],
+    );
+
+    let (status, health) = http_get(http_addr, "/health");
+    assert_eq!(status, 200);
+    assert_eq!(health["status"], "ok");
+    let (status, ready) = http_get(http_addr, "/ready");
+    assert_eq!(status, 200);
+    assert_eq!(ready["status"], "ready");
+
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (status, rooms) = http_get(http_addr, "/api/rooms");
+    assert_eq!(status, 200);
+    assert_eq!(rooms["rooms"][0]["name"], "メインルーム");
+
+    let (status, profile) = http_get(http_addr, &format!("/api/players/{}", alice_id));
+    assert_eq!(status, 200);
+    assert_eq!(profile["player_name"], "Alice");
+    let (status, _) = http_get(http_addr, "/api/players/unknown");
+    assert_eq!(status, 404);
+
+    // 日替わりの配り札は同じ日なら何度取得しても同じ
+    let (status, daily) = http_get(http_addr, "/api/daily");
+    assert_eq!(status, 200);
+    assert!(daily["seed"].is_u64());
+    assert_eq!(http_get(http_addr, "/api/daily").1["seed"], daily["seed"]);
+
+    alice
+        .send(json!({
+            "type": "GameResult",
+            "player_id": alice_id,
+            "result": {
+                "seed": 5,
+                "score": { "final_score": 800 },
+                "duration_seconds": 120,
+                "outcome": "Won",
+            },
+        }))
+        .await;
+    let mut entries = Value::Null;
+    for _ in 0..20 {
+        entries = http_get(http_addr, "/api/leaderboard/5").1["entries"].clone();
+        if entries[0]["player_name"] == "Alice" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    assert_eq!(entries[0]["player_name"], "Alice");
+    assert_eq!(entries[0]["score"], 800);
+
+    let (status, _) = http_get(http_addr, "/api/leaderboard/not-a-seed");
+    assert_eq!(status, 400);
+
+    // 送信チャンネルを通ったメッセージの数と接続数
+    let (status, metrics) = http_get(http_addr, "/metrics");
+    assert_eq!(status, 200);
+    assert_eq!(metrics["connections"], 1);
+    assert!(metrics["outbound"]["queued"].as_u64().unwrap() > 0);
+    assert_eq!(metrics["outbound"]["dropped"], 0);
+}
+
+/// サーバーに送るゲーム結果（GameResultの形）
+fn game_result(seed: u64, won: bool, final_score: u32) -> Value {
+    json!({
+        "outcome": if won { "Won" } else { "Lost" },
+        "game_type": "Klondike",
+        "seed": seed,
+        "score": { "base_score": final_score, "time_bonus": 0, "move_penalty": 0, "final_score": final_score },
+        "move_count": 100,
+        "deck_turns": 3,
+        "duration_seconds": 240,
+        "hints_used": 0,
+        "undos_used": 1,
+        "solver_optimal_moves": null,
+        "efficiency": null,
+        "finished_at": unix_time_ms() / 1000,
+    })
+}
+
+#[tokio::test]
+async fn stats_exports_are_signed_and_verified_for_synced_accounts() {
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("STATS_SIGNING_KEY", "test-signing-key")],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, _) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // まだゲーム結果が届いていないアカウントの成績は書き出せない
+    alice
+        .send(json!({ "type": "SignStats", "player_id": alice_id, "request_id": "sign-0" }))
+        .await;
+    assert_eq!(alice.recv_type("Error").await["request_id"], "sign-0");
+
+    // サーバーに届いたゲーム結果から記録した成績だけが書き出される
+    for (seed, won) in [(1, true), (2, false), (3, true)] {
+        alice
+            .send(json!({ "type": "GameResult", "player_id": alice_id, "result": game_result(seed, won, 900) }))
+            .await;
+        bob.recv_type("GameResult").await;
+    }
+    alice
+        .send(json!({ "type": "SignStats", "player_id": alice_id, "request_id": "sign-1" }))
+        .await;
+    let signed = alice.recv_type("StatsSigned").await;
+    assert_eq!(signed["request_id"], "sign-1");
+    let signed_export = signed["export"].as_str().expect("署名付きの書き出し").to_string();
+    let export: Value = serde_json::from_str(&signed_export).unwrap();
+    assert!(export["signature"].is_string());
+    assert!(export["source_id"].is_string());
+    assert_eq!(export["achievements"]["stats"]["games_played"], 3);
+    assert_eq!(export["achievements"]["stats"]["games_won"], 2);
+
+    alice
+        .send(json!({ "type": "VerifyStats", "player_id": alice_id, "export": signed_export, "request_id": "verify-1" }))
+        .await;
+    let verified = alice.recv_type("StatsVerified").await;
+    assert_eq!(verified["valid"], true);
+    assert_eq!(verified["signature"], export["signature"]);
+    assert_eq!(verified["request_id"], "verify-1");
+
+    // 署名した後に勝利数を書き換えると検出される
+    let tampered = signed_export.replacen("\"games_won\":2", "\"games_won\":5", 1);
+    alice
+        .send(json!({ "type": "VerifyStats", "player_id": alice_id, "export": tampered }))
+        .await;
+    let verified = alice.recv_type("StatsVerified").await;
+    assert_eq!(verified["valid"], false);
+    assert!(verified["reason"].is_string());
+    assert!(verified["signature"].is_null());
+
+    // 他のプレイヤーになりすまして書き出してもらうことはできない
+    bob.send(json!({ "type": "SignStats", "player_id": alice_id, "request_id": "sign-2" }))
+        .await;
+    assert_eq!(bob.recv_type("Error").await["request_id"], "sign-2");
+    alice.expect_silence(SILENCE).await;
+}
+
+#[tokio::test]
+async fn game_results_sent_with_another_players_id_are_not_recorded() {
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("STATS_SIGNING_KEY", "test-signing-key")],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, _) = join(&server, "Bob").await;
+    alice.recv_type("PlayerJoin").await;
+
+    // BobがAliceのIDで送った結果は断られ、他のプレイヤーにも配信されない
+    bob.send(json!({ "type": "GameResult", "player_id": alice_id, "result": game_result(1, true, 5000) }))
+        .await;
+    assert!(bob.recv_type("Error").await["message"]
+        .as_str()
+        .is_some_and(|m| m.contains("他のプレイヤー")));
+    alice.expect_silence(SILENCE).await;
+
+    // Aliceの成績には何も記録されていないので、まだ書き出せない
+    alice
+        .send(json!({ "type": "SignStats", "player_id": alice_id, "request_id": "sign-1" }))
+        .await;
+    assert_eq!(alice.recv_type("Error").await["request_id"], "sign-1");
+}
+
+#[tokio::test]
+async fn clients_agree_on_the_daily_deal_and_can_browse_the_archive() {
+    let http_addr = free_local_addr();
+    let http_addr_text = http_addr.to_string();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("HTTP_ADDR", http_addr_text.as_str())],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    alice
+        .send(json!({ "type": "GetDailyDeal", "player_id": alice_id, "request_id": "daily-1" }))
+        .await;
+    let alice_deal = alice.recv_type("DailyDeal").await;
+    assert_eq!(alice_deal["request_id"], "daily-1");
+    bob.send(json!({ "type": "GetDailyDeal", "player_id": bob_id })).await;
+    let bob_deal = bob.recv_type("DailyDeal").await;
+    assert_eq!(bob_deal["seed"], alice_deal["seed"], "同じ日なら全員が同じ配り札");
+
+    // 配り札は日付（UTC）だけから決まり、HTTP APIと同じ
+    let day = alice_deal["day"].as_u64().expect("日付");
+    assert_eq!(alice_deal["seed"], daily_seed(day));
+    assert_eq!(alice_deal["next_change_ms"], (day + 1) * DAY_MS);
+    assert_eq!(http_get(http_addr, "/api/daily").1["seed"], alice_deal["seed"]);
+
+    // 起動したばかりのサーバーには過去の配り札がまだない
+    alice
+        .send(json!({ "type": "GetDailyArchive", "player_id": alice_id, "request_id": "archive-1" }))
+        .await;
+    let archive = alice.recv_type("DailyArchive").await;
+    assert_eq!(archive["request_id"], "archive-1");
+    assert_eq!(archive["deals"], json!([]));
+    assert_eq!(http_get(http_addr, "/api/daily/archive").1["deals"], json!([]));
+}
+
+#[tokio::test]
+async fn days_missed_while_the_server_was_down_are_archived_on_restart() {
+    let http_addr = free_local_addr();
+    let http_addr_text = http_addr.to_string();
+    let mut server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("HTTP_ADDR", http_addr_text.as_str())],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    alice.send(json!({ "type": "GetDailyDeal", "player_id": alice_id })).await;
+    let today = alice.recv_type("DailyDeal").await["day"].as_u64().expect("日付");
+    alice.close().await;
+
+    // 3日前までの配り札を保存したところでサーバーが止まっていた
+    let save_dir = server.work_dir().join("save_data");
+    std::fs::create_dir_all(&save_dir).expect("保存先を作成できる");
+    let archive = json!({ "deals": [{ "day": today - 3, "seed": daily_seed(today - 3), "results": [] }] });
+    std::fs::write(save_dir.join("ecs_wasm_solitaire.daily_archive.json"), archive.to_string())
+        .expect("過去の配り札を書き込める");
+    server.restart();
+
+    // 起動時に、止まっていた間の日（一昨日・昨日）の配り札も保存される
+    let deals = http_get(http_addr, "/api/daily/archive").1["deals"].clone();
+    let days: Vec<u64> = deals
+        .as_array()
+        .expect("過去の配り札の一覧")
+        .iter()
+        .map(|deal| deal["day"].as_u64().expect("日付"))
+        .collect();
+    assert_eq!(days, vec![today - 1, today - 2, today - 3]);
+    assert_eq!(deals[0]["seed"], daily_seed(today - 1));
+    assert_eq!(deals[1]["seed"], daily_seed(today - 2));
+}
+
+#[tokio::test]
+async fn the_daily_deal_rolls_over_at_midnight_and_archives_the_results() {
+    // サーバーの時計を0時（UTC）の3秒前まで進めて起動する
+    let until_midnight = DAY_MS - unix_time_ms() % DAY_MS;
+    let advance = until_midnight.saturating_sub(3000).to_string();
+    let http_addr = free_local_addr();
+    let http_addr_text = http_addr.to_string();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("HTTP_ADDR", http_addr_text.as_str()), ("CLOCK_ADVANCE_MS", advance.as_str())],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    alice.send(json!({ "type": "GetDailyDeal", "player_id": alice_id })).await;
+    let deal = alice.recv_type("DailyDeal").await;
+    let day = deal["day"].as_u64().expect("日付");
+    let seed = deal["seed"].as_u64().expect("シード");
+    alice
+        .send(json!({ "type": "GameResult", "player_id": alice_id, "result": game_result(seed, true, 700) }))
+        .await;
+
+    // 0時を過ぎると、その日の配り札が結果と一緒に保存される
+    // （切り替えの前に今日と明日の配り札を解くため、少し待つことがある）
+    let mut deals = Value::Null;
+    for _ in 0..600 {
+        deals = http_get(http_addr, "/api/daily/archive").1["deals"].clone();
+        if deals.as_array().is_some_and(|deals| !deals.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(deals[0]["day"], day);
+    assert_eq!(deals[0]["seed"], seed);
+    assert_eq!(deals[0]["results"][0]["player_name"], "Alice");
+    assert_eq!(deals[0]["results"][0]["score"], 700);
+
+    // 接続中のプレイヤーには新しい配り札が届く
+    let next = alice.recv_type("DailyDeal").await;
+    assert_eq!(next["day"], day + 1);
+    assert_eq!(next["seed"], daily_seed(day + 1));
+    assert!(next["request_id"].is_null());
+}
+
+#[tokio::test]
+async fn friends_see_each_others_presence() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    // 片方だけフレンドにしても、相手の在席状況は分からない
+    alice
+        .send(json!({ "type": "AddFriend", "player_id": alice_id, "friend_id": bob_id, "request_id": "friend-1" }))
+        .await;
+    let list = alice.recv_type("FriendList").await;
+    assert_eq!(list["request_id"], "friend-1");
+    assert_eq!(list["friends"][0]["player_name"], "Bob");
+    assert_eq!(list["friends"][0]["player_id"], Value::Null);
+    assert_eq!(list["friends"][0]["status"], "Offline");
+
+    // お互いにフレンドにすると、追加した側の在席状況も相手に届く
+    bob.send(json!({ "type": "AddFriend", "player_id": bob_id, "friend_id": alice_id }))
+        .await;
+    let list = bob.recv_type("FriendList").await;
+    assert_eq!(list["friends"][0]["player_id"], alice_id.as_str());
+    assert_eq!(list["friends"][0]["status"], "Online");
+    let presence = alice.recv_type("FriendPresence").await;
+    assert_eq!(presence["friend"]["player_id"], bob_id.as_str());
+    assert_eq!(presence["friend"]["status"], "Online");
+
+    // フレンドがルームに参加すると在席状況が届く（パスワード付きのルームはIDを伏せる）
+    let room_id = main_room_id(&mut bob, &bob_id).await;
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let presence = alice.recv_type("FriendPresence").await;
+    assert_eq!(presence["friend"]["status"], "InRoom");
+    assert_eq!(presence["friend"]["room_id"], room_id.as_str());
+    bob.send(json!({ "type": "CreateRoom", "player_id": bob_id, "name": "内緒の部屋", "password": "hunter2" }))
+        .await;
+    bob.recv_type("JoinRoom").await;
+    let presence = loop {
+        let presence = alice.recv_type("FriendPresence").await;
+        if presence["friend"]["status"] == "InRoom" {
+            break presence;
+        }
+    };
+    assert_eq!(presence["friend"]["room_id"], Value::Null);
+
+    // フレンドはセッショントークンで覚えているので、表示名を変えても同じフレンドのまま
+    bob.send(json!({ "type": "UpdatePreferences", "player_id": bob_id, "player_name": "Bobby" }))
+        .await;
+    let presence = alice.recv_type("FriendPresence").await;
+    assert_eq!(presence["friend"]["player_name"], "Bobby");
+    assert_eq!(presence["friend"]["player_id"], bob_id.as_str());
+
+    // 切断するとオフラインになり、オフラインのフレンドも最後の名前で外せる
+    bob.close().await;
+    let presence = loop {
+        let presence = alice.recv_type("FriendPresence").await;
+        if presence["friend"]["status"] == "Offline" {
+            break presence;
+        }
+    };
+    assert_eq!(presence["friend"]["player_id"], Value::Null);
+    alice
+        .send(json!({ "type": "RemoveFriend", "player_id": alice_id, "friend_name": "Bobby", "request_id": "friend-2" }))
+        .await;
+    let list = alice.recv_type("FriendList").await;
+    assert_eq!(list["friends"], json!([]));
+
+    // 他のプレイヤーのフレンドは操作できない
+    let (mut carol, _) = join(&server, "Carol").await;
+    carol
+        .send(json!({ "type": "GetFriends", "player_id": alice_id, "request_id": "friend-3" }))
+        .await;
+    assert_eq!(carol.recv_type("Error").await["request_id"], "friend-3");
+}
+
+#[tokio::test]
+async fn friends_can_be_invited_to_a_room_and_accept_or_decline() {
+    let server = start_server();
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+    let (mut carol, carol_id) = join(&server, "Carol").await;
+
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "Aliceの部屋",
+            "password": "hunter2",
+        }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let invite = |target_id: &str| {
+        json!({ "type": "InviteToRoom", "player_id": alice_id, "target_id": target_id, "room_id": room_id })
+    };
+
+    // フレンドにしていないプレイヤーは招待できない
+    alice.send(invite(&bob_id)).await;
+    alice.recv_type("Error").await;
+
+    for friend_id in [&bob_id, &carol_id] {
+        alice
+            .send(json!({ "type": "AddFriend", "player_id": alice_id, "friend_id": friend_id }))
+            .await;
+        alice.recv_type("FriendList").await;
+    }
+
+    // 受けるとパスワード付きのルームにもそのまま参加できる
+    alice.send(invite(&bob_id)).await;
+    let received = bob.recv_type("RoomInvite").await;
+    assert_eq!(received["room_name"], "Aliceの部屋");
+    assert_eq!(received["from_player_name"], "Alice");
+    assert_eq!(received["join_link"], format!("?room={}", room_id));
+    bob.send(json!({ "type": "RespondToInvite", "player_id": bob_id, "invite_id": received["invite_id"], "accept": true }))
+        .await;
+    let joined = bob.recv_type("JoinRoom").await;
+    assert_eq!(joined["room_id"], room_id.as_str());
+    let answered = alice.recv_type("InviteAnswered").await;
+    assert_eq!(answered["player_name"], "Bob");
+    assert_eq!(answered["accepted"], true);
+
+    // 断ると招待した人にだけ知らされ、同じ招待には2回返事できない
+    alice.send(invite(&carol_id)).await;
+    let received = carol.recv_type("RoomInvite").await;
+    let decline = json!({ "type": "RespondToInvite", "player_id": carol_id, "invite_id": received["invite_id"], "accept": false });
+    carol.send(decline.clone()).await;
+    let answered = alice.recv_type("InviteAnswered").await;
+    assert_eq!(answered["accepted"], false);
+    carol.send(decline).await;
+    carol.recv_type("Error").await;
+}
+
+#[tokio::test]
+async fn opted_in_players_are_pushed_when_their_room_fills_and_their_turn_starts() {
+    let gateway = FakePushGateway::start();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[("PUSH_GATEWAY_URL", &gateway.url())],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    alice
+        .send(json!({
+            "type": "CreateRoom",
+            "player_id": alice_id,
+            "name": "ゆっくり対戦",
+            "max_players": 2,
+            "turn_time_limit": 60,
+        }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // 他のプレイヤーの設定は変えられない
+    let opt_in = json!({
+        "type": "UpdateNotificationSettings",
+        "player_id": alice_id,
+        "endpoint": "https://push.example/alice",
+        "turn": true,
+        "room_full": true,
+        "request_id": "notify-1",
+    });
+    bob.send(opt_in.clone()).await;
+    bob.recv_type("Error").await;
+    alice.send(opt_in).await;
+    let settings = alice.recv_type("NotificationSettings").await;
+    assert_eq!(settings["enabled"], true);
+    assert_eq!(settings["request_id"], "notify-1");
+
+    // 満員になるとホストに届く
+    join_room(&mut bob, &bob_id, &room_id).await;
+    let notification = gateway.recv();
+    assert_eq!(notification["kind"], "room_full");
+    assert_eq!(notification["endpoint"], "https://push.example/alice");
+    assert_eq!(notification["room_name"], "ゆっくり対戦");
+
+    // 手番になると本人に届く
+    alice
+        .send(json!({ "type": "StartRace", "room_id": room_id, "player_id": alice_id, "seed": 7 }))
+        .await;
+    let turn = bob.recv_type("TurnStarted").await;
+    assert_eq!(turn["player_id"], alice_id.as_str());
+    let notification = gateway.recv();
+    assert_eq!(notification["kind"], "turn");
+    assert_eq!(notification["room_id"], room_id.as_str());
+
+    // 送り先を消すと通知をやめる
+    alice
+        .send(json!({ "type": "UpdateNotificationSettings", "player_id": alice_id }))
+        .await;
+    let settings = alice.recv_type("NotificationSettings").await;
+    assert_eq!(settings["enabled"], false);
+    gateway.expect_silence(SILENCE);
+}
+
+#[tokio::test]
+async fn notifications_the_gateway_could_not_take_are_queued_and_sent_later() {
+    // 中継サーバーはまだ動いていない
+    let gateway_addr = free_local_addr();
+    let server = TestServer::start_with_env(
+        env!("CARGO_BIN_EXE_websocket_server"),
+        &[
+            ("PUSH_GATEWAY_URL", &format!("http://{}/push", gateway_addr)),
+            ("PUSH_RETRY_INTERVAL_MS", "200"),
+        ],
+    );
+    let (mut alice, alice_id) = join(&server, "Alice").await;
+    let (mut bob, bob_id) = join(&server, "Bob").await;
+
+    alice
+        .send(json!({ "type": "CreateRoom", "player_id": alice_id, "name": "ゆっくり対戦", "max_players": 2 }))
+        .await;
+    let room_id = alice.recv_type("JoinRoom").await["room_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    alice
+        .send(json!({
+            "type": "UpdateNotificationSettings",
+            "player_id": alice_id,
+            "endpoint": "https://push.example/alice",
+            "room_full": true,
+        }))
+        .await;
+    alice.recv_type("NotificationSettings").await;
+
+    // 満員の通知は届けられないまま溜まり、ホストが接続を切っても失われない
+    join_room(&mut bob, &bob_id, &room_id).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    drop(alice);
+
+    // 中継サーバーが動き出すと送り直される
+    let gateway = FakePushGateway::start_at(gateway_addr);
+    let notification = gateway.recv();
+    assert_eq!(notification["kind"], "room_full");
+    assert_eq!(notification["endpoint"], "https://push.example/alice");
+    assert_eq!(notification["room_id"], room_id.as_str());
+    gateway.expect_silence(SILENCE);
+}